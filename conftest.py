@@ -49,6 +49,7 @@ except ImportError:
         "DbcrustCommandError",
         "DbcrustConfigError",
         "DbcrustArgumentError",
+        "DbcrustLimitError",
     ):
         setattr(_stub, _name, type(_name, (Exception,), {}))
 
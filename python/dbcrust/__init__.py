@@ -19,7 +19,8 @@ from dbcrust._internal import (  # ty: ignore[unresolved-import]
     DbcrustConnectionError,
     DbcrustCommandError,
     DbcrustConfigError,
-    DbcrustArgumentError
+    DbcrustArgumentError,
+    DbcrustLimitError
 )
 
 try:
@@ -90,5 +91,6 @@ __all__ = [
     "DbcrustConnectionError",
     "DbcrustCommandError",
     "DbcrustConfigError",
-    "DbcrustArgumentError"
+    "DbcrustArgumentError",
+    "DbcrustLimitError"
 ]
@@ -5,7 +5,7 @@ Generic database connection interface supporting PostgreSQL, MySQL, and SQLite
 with a mysql.connector-like API.
 """
 
-from typing import Optional, Dict, Any, Union
+from typing import Iterator, Optional, Dict, Any, Union
 from urllib.parse import urlparse, urlunparse
 from dbcrust._internal import (  # ty: ignore[unresolved-import]
     py_connect,
@@ -17,7 +17,8 @@ from dbcrust._internal import (  # ty: ignore[unresolved-import]
     DbcrustConnectionError,
     DbcrustCommandError,
     DbcrustConfigError,
-    DbcrustArgumentError
+    DbcrustArgumentError,
+    DbcrustLimitError
 )
 
 
@@ -37,6 +38,8 @@ def connect(
     # Connection options
     timeout: Optional[float] = None,
     auto_commit: Optional[bool] = None,
+    max_rows: Optional[int] = None,
+    max_bytes: Optional[int] = None,
 
     # Database-specific options
     **kwargs
@@ -55,6 +58,9 @@ def connect(
         database: Database name (or use 'db')
         timeout: Connection timeout in seconds
         auto_commit: Enable auto-commit mode (default: True)
+        max_rows: Raise DbcrustLimitError when a result has more rows
+        max_bytes: Raise DbcrustLimitError when a result holds more bytes of
+            cell text
         **kwargs: Additional database-specific options
 
     Returns:
@@ -117,7 +123,7 @@ def connect(
 
     # Create the Rust-side connection
     try:
-        rust_connection = py_connect(url, timeout, auto_commit)
+        rust_connection = py_connect(url, timeout, auto_commit, max_rows, max_bytes)
     except Exception as e:
         # Re-raise with appropriate exception type
         if "connection" in str(e).lower() or "connect" in str(e).lower():
@@ -189,6 +195,27 @@ class Connection:
 
         return self._connection.execute_immediate(query)
 
+    def stats(self) -> dict:
+        """
+        Get usage counters for this connection, the same as ``\\stats`` in the CLI.
+
+        Returns:
+            dict: queries, failed_queries, rows_fetched, bytes_sent,
+            bytes_received, query_time_ms, cache_hits, cache_misses and
+            tracked_seconds
+        """
+        if self._closed:
+            raise DbcrustConnectionError("Connection is closed")
+
+        return self._connection.stats()
+
+    def reset_stats(self):
+        """Reset the usage counters returned by stats()"""
+        if self._closed:
+            raise DbcrustConnectionError("Connection is closed")
+
+        self._connection.reset_stats()
+
     def commit(self):
         """
         Commit the current transaction.
@@ -240,6 +267,26 @@ class Connection:
         """Set auto-commit setting"""
         self._connection.set_auto_commit(value)
 
+    @property
+    def max_rows(self) -> Optional[int]:
+        """Get the row limit applied to results (None: unlimited)"""
+        return self._connection.max_rows
+
+    @max_rows.setter
+    def max_rows(self, value: Optional[int]):
+        """Set the row limit for new cursors and execute_immediate()"""
+        self._connection.max_rows = value
+
+    @property
+    def max_bytes(self) -> Optional[int]:
+        """Get the byte limit applied to results (None: unlimited)"""
+        return self._connection.max_bytes
+
+    @max_bytes.setter
+    def max_bytes(self, value: Optional[int]):
+        """Set the byte limit for new cursors and execute_immediate()"""
+        self._connection.max_bytes = value
+
     @property
     def is_closed(self) -> bool:
         """Check if connection is closed"""
@@ -266,20 +313,51 @@ class Cursor:
         """Context manager exit"""
         self.close()
 
-    def execute(self, query: str) -> int:
+    def execute(
+        self,
+        query: str,
+        max_rows: Optional[int] = None,
+        max_bytes: Optional[int] = None,
+    ) -> int:
         """
         Execute a single SQL statement.
 
         Args:
             query: SQL query to execute
+            max_rows: Row limit for this query, replacing the connection's
+            max_bytes: Byte limit for this query, replacing the connection's
 
         Returns:
             int: Number of affected/returned rows
+
+        Raises:
+            DbcrustLimitError: The result passed max_rows or max_bytes; the
+                fetch stops there and no rows are kept
         """
         if self._closed:
             raise DbcrustCommandError("Cursor is closed")
 
-        return self._cursor.execute(query)
+        return self._cursor.execute(query, max_rows, max_bytes)
+
+    def fetch_iter(self, query: str) -> Iterator[PyRow]:
+        """
+        Run a query and yield its rows as the server sends them.
+
+        Only a small buffer of rows is held at a time, so results of any size
+        can be processed. Row limits do not apply. The connection is busy
+        until the iterator is exhausted or closed.
+
+        Args:
+            query: SQL query to execute
+
+        Returns:
+            Iterator[PyRow]: Rows, also usable as a context manager that
+            stops the query on exit
+        """
+        if self._closed:
+            raise DbcrustCommandError("Cursor is closed")
+
+        return self._cursor.fetch_iter(query)
 
     def executescript(self, script: str) -> int:
         """
@@ -337,7 +415,8 @@ class Cursor:
 
     def nextset(self) -> bool:
         """
-        Move to the next result set (for multi-statement queries).
+        Move to the next result set (from executescript(), or a stored
+        procedure returning several). description follows the current set.
 
         Returns:
             bool: True if there's another result set, False otherwise
@@ -363,12 +442,20 @@ class Cursor:
 
     @property
     def rowcount(self) -> int:
-        """Get the number of rows in the current result set"""
+        """Get the number of rows in the current result set, or the rows affected by INSERT/UPDATE/DELETE"""
         if self._closed:
             return -1
 
         return self._cursor.rowcount
 
+    @property
+    def lastrowid(self) -> Optional[int]:
+        """Get the id of the row inserted by the last execute() (MySQL and SQLite)"""
+        if self._closed:
+            return None
+
+        return self._cursor.lastrowid
+
     @property
     def is_closed(self) -> bool:
         """Check if cursor is closed"""
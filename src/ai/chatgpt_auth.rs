@@ -278,7 +278,7 @@ fn open_browser(url: &str) {
 // --- Token endpoint ---
 
 fn http_client() -> Result<reqwest::Client, AiError> {
-    reqwest::Client::builder()
+    crate::network::client_builder()
         .timeout(Duration::from_secs(30))
         .build()
         .map_err(|e| AiError::OAuth(format!("HTTP client error: {e}")))
@@ -23,7 +23,7 @@ pub mod streaming;
 use genai::adapter::AdapterKind;
 use genai::chat::{ChatMessage, ChatOptions, ChatRequest, ChatStreamEvent};
 use genai::resolver::{AuthData, AuthResolver, Endpoint, ServiceTargetResolver};
-use genai::{Client, ModelIden, ServiceTarget};
+use genai::{Client, ClientBuilder, ModelIden, ServiceTarget, WebConfig};
 use thiserror::Error;
 use tokio::sync::mpsc;
 
@@ -221,9 +221,11 @@ pub fn build_client(ai_config: &AiConfig, mode: &ResolvedAuthMode) -> Result<Cli
                 })
             },
         );
-        return Ok(Client::builder()
-            .with_service_target_resolver(target_resolver)
-            .build());
+        return Ok(with_proxy(
+            Client::builder().with_service_target_resolver(target_resolver),
+            Some(chatgpt_auth::CHATGPT_CODEX_BASE),
+        )
+        .build());
     }
 
     // Pre-flight the API key: the auth resolver below maps failures to
@@ -258,8 +260,11 @@ pub fn build_client(ai_config: &AiConfig, mode: &ResolvedAuthMode) -> Result<Cli
 
     let mut builder = Client::builder().with_auth_resolver(auth_resolver);
 
+    let custom_endpoint = ai_config.endpoint.clone().filter(|s| !s.trim().is_empty());
+    builder = with_proxy(builder, custom_endpoint.as_deref());
+
     // Override the endpoint for self-hosted / OpenAI-compatible gateways.
-    if let Some(endpoint) = ai_config.endpoint.clone().filter(|s| !s.trim().is_empty()) {
+    if let Some(endpoint) = custom_endpoint {
         let target_resolver = ServiceTargetResolver::from_resolver_fn(
             move |mut target: ServiceTarget| -> Result<ServiceTarget, genai::resolver::Error> {
                 target.endpoint = Endpoint::from_owned(endpoint.clone());
@@ -272,6 +277,27 @@ pub fn build_client(ai_config: &AiConfig, mode: &ResolvedAuthMode) -> Result<Cli
     Ok(builder.build())
 }
 
+/// Send requests through the `[network]` proxy, unless `endpoint` (the
+/// provider's public API when `None`) is in `no_proxy`. Proxies from the
+/// environment are picked up by genai's HTTP client itself.
+fn with_proxy(builder: ClientBuilder, endpoint: Option<&str>) -> ClientBuilder {
+    let network = crate::config::Config::load().network;
+    let proxy = match endpoint {
+        Some(endpoint) => network.proxy_for(endpoint),
+        None => network.proxy_url(),
+    };
+    let Some(proxy) = proxy else {
+        return builder;
+    };
+    match WebConfig::default().with_all_proxy_url(proxy) {
+        Ok(web_config) => builder.with_web_config(web_config),
+        Err(e) => {
+            tracing::warn!("Ignoring [network] proxy '{}': {}", proxy, e);
+            builder
+        }
+    }
+}
+
 fn build_request(system_prompt: &str, messages: &[(MessageRole, String)]) -> ChatRequest {
     let mut req = ChatRequest::default().with_system(system_prompt);
     for (role, content) in messages {
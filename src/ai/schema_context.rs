@@ -143,7 +143,7 @@ pub async fn build_agent_seed_context(db: &mut Database) -> String {
 async fn collect_table_names(
     db: &mut Database,
     db_type: &DatabaseType,
-) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+) -> crate::error::Result<Vec<String>> {
     if !matches!(db_type, DatabaseType::PostgreSQL) {
         return db.get_tables_and_views(None).await;
     }
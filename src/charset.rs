@@ -0,0 +1,191 @@
+//! Text in legacy encodings (`?charset=`)
+//!
+//! PostgreSQL and MySQL convert text to UTF-8 on the way out, so a latin1
+//! MySQL table or a WIN1252 PostgreSQL database reads fine as long as the
+//! session asks for UTF-8, which dbcrust always does. What still arrives as
+//! raw bytes is text stored without a usable encoding: PostgreSQL
+//! `SQL_ASCII` databases and MySQL columns the server does not convert.
+//! `charset` (or `client_encoding`) names the encoding those bytes are
+//! decoded with; MySQL falls back to the database's default character set.
+//! Valid UTF-8 is kept as is, and bytes the charset cannot decode are shown
+//! as `\xNN` instead of failing the value.
+
+use encoding_rs::{DecoderResult, Encoding};
+use std::collections::HashMap;
+
+const CHARSET_KEYS: &[&str] = &["charset", "client_encoding"];
+
+/// Whether a URL parameter names the charset
+pub fn is_charset_option(key: &str) -> bool {
+    CHARSET_KEYS.iter().any(|k| k.eq_ignore_ascii_case(key))
+}
+
+/// Encoding of the text a connection returns that is not valid UTF-8
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Charset {
+    /// `None`: UTF-8
+    encoding: Option<&'static Encoding>,
+}
+
+/// PostgreSQL and MySQL names that are not WHATWG labels, compared without
+/// `-` and `_`
+fn database_label(compact: &str) -> Option<String> {
+    if let Some(number) = compact.strip_prefix("win") {
+        // WIN1252 is cp1252; WIN874 has no cp label
+        return match number {
+            "874" => Some("windows-874".to_string()),
+            _ if number.chars().all(|c| c.is_ascii_digit()) => Some(format!("cp{number}")),
+            _ => None,
+        };
+    }
+    let label = match compact {
+        "utf8mb4" | "utf8mb3" | "unicode" => "utf-8",
+        "latin7" => "iso-8859-13",
+        "latin8" => "iso-8859-14",
+        "latin9" => "iso-8859-15",
+        "latin10" => "iso-8859-16",
+        "greek" => "iso-8859-7",
+        "hebrew" => "iso-8859-8",
+        "koi8r" => "koi8-r",
+        "koi8u" => "koi8-u",
+        "sjis" | "cp932" => "shift_jis",
+        "ujis" | "eucjp" | "eucjpms" => "euc-jp",
+        "euckr" | "uhc" => "euc-kr",
+        "euccn" | "gb2312" => "gbk",
+        _ => return None,
+    };
+    Some(label.to_string())
+}
+
+impl Charset {
+    /// `latin1`, `WIN1252`, `windows-1252`, `cp1251`, `sjis`, `utf8mb4`...
+    pub fn parse(name: &str) -> Result<Self, String> {
+        let name = name.trim();
+        let compact: String = name
+            .chars()
+            .filter(|c| *c != '-' && *c != '_')
+            .flat_map(char::to_lowercase)
+            .collect();
+        let encoding = Encoding::for_label(name.as_bytes())
+            .or_else(|| {
+                database_label(&compact).and_then(|label| Encoding::for_label(label.as_bytes()))
+            })
+            // Labels that only exist to refuse decoding, and UTF-16, which
+            // no database sends
+            .filter(|encoding| {
+                *encoding != encoding_rs::REPLACEMENT
+                    && *encoding != encoding_rs::UTF_16LE
+                    && *encoding != encoding_rs::UTF_16BE
+            })
+            .ok_or_else(|| {
+                format!("Unknown charset '{name}' (e.g. utf8, latin1, win1252, cp1251, sjis)")
+            })?;
+        Ok(Self {
+            encoding: (encoding != encoding_rs::UTF_8).then_some(encoding),
+        })
+    }
+
+    /// The charset named by the connection options, UTF-8 when none is
+    pub fn from_options(options: &HashMap<String, String>) -> Result<Self, String> {
+        match options
+            .iter()
+            .find(|(key, value)| is_charset_option(key) && !value.trim().is_empty())
+        {
+            Some((_, name)) => Self::parse(name),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Whether a charset other than UTF-8 is in use
+    pub fn is_set(&self) -> bool {
+        self.encoding.is_some()
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.encoding.unwrap_or(encoding_rs::UTF_8).name()
+    }
+
+    /// `bytes` as text: valid UTF-8 as is, anything else decoded with this
+    /// charset, and bytes that do not decode as `\xNN`
+    pub fn decode(&self, bytes: &[u8]) -> String {
+        if let Ok(text) = std::str::from_utf8(bytes) {
+            return text.to_string();
+        }
+        let encoding = self.encoding.unwrap_or(encoding_rs::UTF_8);
+        let mut decoder = encoding.new_decoder_without_bom_handling();
+        let mut text = String::with_capacity(bytes.len() * 2);
+        let mut input = bytes;
+        loop {
+            let needed = decoder
+                .max_utf8_buffer_length_without_replacement(input.len())
+                .unwrap_or(input.len() * 4);
+            text.reserve(needed);
+            let (result, read) =
+                decoder.decode_to_string_without_replacement(input, &mut text, true);
+            match result {
+                DecoderResult::InputEmpty => break,
+                DecoderResult::OutputFull => {}
+                DecoderResult::Malformed(length, after) => {
+                    let end = read - after as usize;
+                    for byte in &input[end - length as usize..end] {
+                        text.push_str(&format!("\\x{byte:02x}"));
+                    }
+                }
+            }
+            input = &input[read..];
+        }
+        if encoding.is_single_byte() && encoding != encoding_rs::UTF_8 {
+            // Single-byte charsets map their unassigned bytes (0x81 in
+            // windows-1252) to C1 controls, which would print as nothing
+            return text
+                .chars()
+                .map(|c| match c as u32 {
+                    code @ 0x80..=0x9f => format!("\\x{code:02x}"),
+                    _ => c.to_string(),
+                })
+                .collect();
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_database_names() {
+        assert_eq!(Charset::parse("latin1").unwrap().name(), "windows-1252");
+        assert_eq!(Charset::parse("WIN1252").unwrap().name(), "windows-1252");
+        assert_eq!(Charset::parse("WIN1251").unwrap().name(), "windows-1251");
+        assert_eq!(Charset::parse("cp1250").unwrap().name(), "windows-1250");
+        assert_eq!(Charset::parse("LATIN9").unwrap().name(), "ISO-8859-15");
+        assert_eq!(Charset::parse("sjis").unwrap().name(), "Shift_JIS");
+        assert_eq!(Charset::parse("EUC_JP").unwrap().name(), "EUC-JP");
+        assert!(!Charset::parse("utf8mb4").unwrap().is_set());
+        assert!(!Charset::parse("UTF8").unwrap().is_set());
+        assert!(Charset::parse("WIN9999").is_err());
+        assert!(Charset::parse("ebcdic").is_err());
+        assert!(Charset::parse("utf-16le").is_err());
+
+        let options = HashMap::from([("client_encoding".to_string(), "WIN1252".to_string())]);
+        assert!(Charset::from_options(&options).unwrap().is_set());
+        assert!(!Charset::from_options(&HashMap::new()).unwrap().is_set());
+    }
+
+    #[test]
+    fn test_decode_marks_undecodable_bytes() {
+        let win1252 = Charset::parse("win1252").unwrap();
+        assert_eq!(win1252.decode(b"caf\xe9 \x80 10"), "café € 10");
+        assert_eq!(win1252.decode("déjà".as_bytes()), "déjà");
+        assert_eq!(win1252.decode(b"a\x81b\xe9"), "a\\x81bé");
+
+        let utf8 = Charset::default();
+        assert_eq!(utf8.decode(b"caf\xe9!"), "caf\\xe9!");
+        assert_eq!(utf8.decode(b"\xff\xfe"), "\\xff\\xfe");
+
+        let sjis = Charset::parse("sjis").unwrap();
+        assert_eq!(sjis.decode(b"\x93\xfa\x96\x7b"), "日本");
+        assert_eq!(sjis.decode(b"\x93\xfa\xa0\xff"), "日\\xa0\\xff");
+    }
+}
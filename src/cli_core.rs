@@ -5,7 +5,7 @@ use crate::config::Config as DbCrustConfig;
 use crate::database::{ConnectionInfo, DatabaseType, DatabaseTypeExt};
 use crate::db::Database;
 use crate::format::{format_query_results_expanded, format_query_results_psql_with_info};
-use crate::history_manager::{SessionHistoryManager, SessionId};
+use crate::history_manager::{FormattingHistory, SessionHistoryManager, SessionId};
 use crate::prompt::DbPrompt;
 use crate::{logging, pager};
 use clap::CommandFactory;
@@ -51,6 +51,8 @@ pub struct CliCore {
     /// calls within the same database. Only the query-independent build is cached
     /// (see `build_schema_context`'s `cacheable` flag). Invalidated on DB switch.
     pub ai_schema_cache: Option<(String, String)>,
+    /// Text to pre-fill the next prompt with (`\browse` Enter)
+    pub pending_input: Option<String>,
 }
 
 #[derive(Debug)]
@@ -68,7 +70,7 @@ impl std::fmt::Display for CliError {
         match self {
             CliError::ConnectionError(msg) => {
                 // Check for specific Vault errors and provide user-friendly messages
-                if msg.contains("Failed to list Vault databases") && msg.contains("403 Forbidden") {
+                if msg.contains("Vault API error") && msg.contains("403 Forbidden") {
                     if msg.contains("invalid token") {
                         write!(
                             f,
@@ -136,6 +138,18 @@ impl std::fmt::Display for CliError {
     }
 }
 
+impl From<crate::error::DbcrustError> for CliError {
+    fn from(err: crate::error::DbcrustError) -> Self {
+        use crate::error::DbcrustError;
+        match err {
+            DbcrustError::Config(msg) => CliError::ConfigError(msg),
+            DbcrustError::Parse(msg) => CliError::ArgumentError(msg),
+            err if err.is_connection() => CliError::ConnectionError(err.to_string()),
+            err => CliError::CommandError(err.to_string()),
+        }
+    }
+}
+
 impl From<Box<dyn StdError>> for CliError {
     fn from(err: Box<dyn StdError>) -> Self {
         CliError::CommandError(err.to_string())
@@ -148,6 +162,12 @@ impl Default for CliCore {
 
         // Initialize global vector config for formatters
         crate::vector_display::set_global_vector_config(config.vector_display.clone());
+        if let Err(e) = crate::format::apply_display_config(&config.display) {
+            eprintln!("Warning: [display] {e}");
+        }
+        if let Err(e) = crate::masking::apply_config(&config.masking) {
+            eprintln!("Warning: [masking] {e}");
+        }
 
         let ai_history_len = config.ai.history_length;
         Self {
@@ -157,6 +177,7 @@ impl Default for CliCore {
             ai_conversation: crate::ai::conversation::AiConversation::new(ai_history_len),
             agentic_conversation: crate::ai::conversation::AiConversation::new(ai_history_len),
             ai_schema_cache: None,
+            pending_input: None,
         }
     }
 }
@@ -254,6 +275,72 @@ fn expand_tilde(path: &str) -> String {
     path.to_string()
 }
 
+/// Render a statement's results for the terminal; `None` when there is
+/// nothing to show. Procedures returning several result sets get each one
+/// rendered in turn under a "Result set n of m" line, and results served by
+/// the result cache end with a "(cached Ns ago)" line.
+fn format_result_sets(
+    results: &crate::db::QueryResultsWithInfo,
+    expanded: bool,
+    shorten_binary: bool,
+) -> Option<String> {
+    if results.data.is_empty() && results.more_results.is_empty() {
+        return results
+            .affected_rows_message()
+            .map(|message| format!("{message}\n"));
+    }
+    let render = |data: &[Vec<String>], info: Option<&crate::db::ColumnFilteringInfo>| {
+        if data.is_empty() {
+            "(no rows)\n".to_string()
+        } else if expanded {
+            format_query_results_expanded(data)
+                .into_iter()
+                .map(|table| format!("{table}\n"))
+                .collect()
+        } else {
+            format_query_results_psql_with_info(data, info)
+        }
+    };
+
+    let converted = if shorten_binary {
+        display_rows(results)
+    } else {
+        crate::format::render_datetime_columns(&results.data, &results.temporal_columns)
+    };
+    let data = converted.as_deref().unwrap_or(&results.data);
+    let mut output = if results.more_results.is_empty() {
+        render(data, results.column_info.as_ref())
+    } else {
+        let total = results.more_results.len() + 1;
+        let mut output = format!("Result set 1 of {total}\n");
+        output.push_str(&render(data, results.column_info.as_ref()));
+        for (index, set) in results.more_results.iter().enumerate() {
+            output.push_str(&format!("\nResult set {} of {total}\n", index + 2));
+            output.push_str(&render(set, None));
+        }
+        output
+    };
+    if let Some(age) = results.cached_age {
+        output.push_str(&crate::result_cache::age_note(age));
+        output.push('\n');
+    }
+    Some(output)
+}
+
+/// The first result set as shown in the REPL: date/time columns converted
+/// and bytea/BLOB cells shortened; `None` when it is shown as returned
+fn display_rows(results: &crate::db::QueryResultsWithInfo) -> Option<Vec<Vec<String>>> {
+    let converted =
+        crate::format::render_datetime_columns(&results.data, &results.temporal_columns);
+    let data = converted.as_deref().unwrap_or(&results.data);
+    crate::complex_display::render_binary_columns(
+        data,
+        &results.binary_columns,
+        &crate::complex_display::get_global_complex_config(),
+    )
+    .or(converted)
+}
+
 /// Check if user input matches a named query invocation.
 /// Returns `Some((name, args))` if the first word matches a stored named query,
 /// or `None` if it looks like SQL or doesn't match any named query.
@@ -338,7 +425,7 @@ impl CliCore {
 
     /// Main entry point with original args for shell completion generation
     pub async fn run_with_args_and_original(
-        args: Args,
+        mut args: Args,
         original_args: Option<Vec<String>>,
     ) -> Result<i32, CliError> {
         // Initialize the logging system
@@ -350,8 +437,11 @@ impl CliCore {
         let mut cli_core = Self::new();
 
         // Database clients are constructed without Config access — publish the
-        // configured query timeout for them (0 disables it)
+        // configured query timeout (0 disables it) and progress line delay
         crate::database::set_query_timeout_seconds(cli_core.config.query_timeout_seconds);
+        crate::query_progress::set_progress_after_seconds(
+            cli_core.config.query_progress_after_seconds,
+        );
 
         // Handle shell completion generation if requested
         if let Some(shell) = args.completions {
@@ -382,13 +472,76 @@ impl CliCore {
             return Ok(0);
         }
 
+        // Handle `dbcrust daemon ...` — manages the warm-connection daemon
+        if let Some(crate::cli::CliCommand::Daemon { action }) = &args.subcommand {
+            cli_core.handle_daemon_subcommand(action).await?;
+            return Ok(0);
+        }
+
+        // Handle `dbcrust tunnel ...` — manages ssh_tunnel_patterns
+        if let Some(crate::cli::CliCommand::Tunnel { action }) = &args.subcommand {
+            crate::ssh_tunnel::set_explicit_tunnel(args.ssh_tunnel.clone());
+            cli_core.handle_tunnel_subcommand(action).await?;
+            return Ok(0);
+        }
+
+        // Handle `dbcrust check [--group G]` — connects to saved sessions
+        if let Some(crate::cli::CliCommand::Check { group, timeout }) = &args.subcommand {
+            return cli_core
+                .handle_check_subcommand(group.as_deref(), *timeout)
+                .await;
+        }
+
+        // Handle `dbcrust monitor` — dashboard of saved sessions and aliases
+        if let Some(crate::cli::CliCommand::Monitor {
+            group,
+            interval,
+            timeout,
+        }) = &args.subcommand
+        {
+            crate::monitor_tui::run_monitor(
+                &cli_core.config,
+                group.as_deref(),
+                std::time::Duration::from_secs(*interval),
+                std::time::Duration::from_secs(*timeout),
+            )
+            .await
+            .map_err(CliError::CommandError)?;
+            return Ok(0);
+        }
+
+        // Handle `dbcrust export-settings FILE` / `import-settings FILE`
+        if let Some(crate::cli::CliCommand::ExportSettings { file }) = &args.subcommand {
+            let message = crate::settings_bundle::export(std::path::Path::new(file))
+                .map_err(CliError::CommandError)?;
+            println!("{message}");
+            return Ok(0);
+        }
+        if let Some(crate::cli::CliCommand::ImportSettings { file, yes }) = &args.subcommand {
+            Self::handle_import_settings(file, *yes)?;
+            return Ok(0);
+        }
+
+        // --from-env / --from-django stand in for the URL argument
+        if args.from_env.is_some() || args.from_django.is_some() {
+            args.connection_url = Some(Self::discover_connection_url(&args)?);
+        }
+
+        // Handle `dbcrust [URL] notebook run FILE` — connects, runs, writes back
+        if let Some(crate::cli::CliCommand::Notebook { action }) = &args.subcommand {
+            return cli_core.handle_notebook_subcommand(&args, action).await;
+        }
+
         // Log system information
         cli_core.log_system_info(&args);
 
         // SSH tunnel debug output now handled by tracing system
 
+        let scripts = Self::script_sources(&args);
+
         // Check if commands can be handled without database connection first
         if !args.command.is_empty()
+            && scripts.is_empty()
             && cli_core.can_handle_commands_without_connection(&args.command)
         {
             cli_core
@@ -397,33 +550,59 @@ impl CliCore {
             return Ok(0);
         }
 
+        // Route -c commands through a running daemon, which keeps the connection warm
+        if !args.command.is_empty()
+            && scripts.is_empty()
+            && args.connection_url.is_some()
+            && !args.no_daemon
+            && let Some(exit_code) = crate::daemon::try_execute(&args, &cli_core.config).await
+        {
+            return Ok(exit_code);
+        }
+
+        crate::db::set_status_to_stderr(crate::export::exports_to_stdout(&args.command));
+
         // Handle connection and database setup if connection URL provided
         if args.connection_url.is_some() {
-            cli_core.handle_database_connection(&args).await?;
+            // Vault leases are let go on every way out, failed connects included
+            let result = cli_core.run_connected(&args, &scripts).await;
+            crate::vault_client::release_leases(&mut cli_core.config).await;
+            return result;
+        }
 
-            // Handle -c commands if provided (execute and exit)
-            if !args.command.is_empty() {
-                let exit_code = cli_core.handle_command_mode(&args).await?;
-                return Ok(exit_code);
-            }
+        // No connection URL provided
+        if !args.command.is_empty() || !args.file.is_empty() {
+            return Err(CliError::ArgumentError(
+                "Database connection required for SQL commands. Use backslash commands like \\h for help without connection.".to_string()
+            ));
+        }
 
-            // Start interactive mode with database connection
-            cli_core.run_interactive_mode().await?;
-        } else {
-            // No connection URL provided
-            if !args.command.is_empty() {
-                return Err(CliError::ArgumentError(
-                    "Database connection required for SQL commands. Use backslash commands like \\h for help without connection.".to_string()
-                ));
-            }
+        // No URL and nothing to execute: print help instead of opening an
+        // empty REPL — connection examples live in `after_help` (cli.rs).
+        Args::command()
+            .print_help()
+            .map_err(|e| CliError::CommandError(format!("Failed to print help: {e}")))?;
 
-            // No URL and nothing to execute: print help instead of opening an
-            // empty REPL — connection examples live in `after_help` (cli.rs).
-            Args::command()
-                .print_help()
-                .map_err(|e| CliError::CommandError(format!("Failed to print help: {e}")))?;
+        Ok(0)
+    }
+
+    /// Connect, then run the -c commands and -f scripts, or the REPL when
+    /// there are none
+    async fn run_connected(&mut self, args: &Args, scripts: &[String]) -> Result<i32, CliError> {
+        self.handle_database_connection(args).await?;
+
+        // Handle -c commands and -f scripts if provided (execute and exit)
+        if !args.command.is_empty() || !scripts.is_empty() {
+            let mut output = CommandModeOutput::Terminal;
+            let mut result = self.handle_command_mode(&args.command, &mut output).await;
+            if matches!(result, Ok(0)) && !scripts.is_empty() {
+                result = self.handle_script_mode(scripts, &mut output).await;
+            }
+            return result;
         }
 
+        // Start interactive mode with database connection
+        self.run_interactive_mode().await?;
         Ok(0)
     }
 
@@ -450,6 +629,26 @@ impl CliCore {
         Ok(())
     }
 
+    /// The URL `--from-env` or `--from-django` finds for the current directory
+    fn discover_connection_url(args: &Args) -> Result<String, CliError> {
+        let cwd = std::env::current_dir().map_err(|e| {
+            CliError::ArgumentError(format!("Cannot read the current directory: {e}"))
+        })?;
+        let named = |value: &Option<String>| value.clone().filter(|value| !value.is_empty());
+        let discovered = if args.from_django.is_some() {
+            crate::project_url::from_django(named(&args.from_django).as_deref(), &cwd)
+        } else {
+            crate::project_url::from_env(named(&args.from_env).as_deref(), &cwd)
+        }
+        .map_err(CliError::ConnectionError)?;
+        eprintln!(
+            "Using {} ({})",
+            crate::password_sanitizer::sanitize_connection_url(&discovered.url),
+            discovered.source
+        );
+        Ok(discovered.url)
+    }
+
     /// Log system information for debugging
     fn log_system_info(&self, args: &Args) {
         debug!("Operating System: {}", std::env::consts::OS);
@@ -469,6 +668,23 @@ impl CliCore {
         }
     }
 
+    /// Script sources to run: the `-f` arguments, or stdin when input is piped
+    /// into a connection with nothing else to execute
+    fn script_sources(args: &Args) -> Vec<String> {
+        use std::io::IsTerminal;
+        if !args.file.is_empty() {
+            return args.file.clone();
+        }
+        if args.connection_url.is_some()
+            && args.command.is_empty()
+            && args.subcommand.is_none()
+            && !io::stdin().is_terminal()
+        {
+            return vec!["-".to_string()];
+        }
+        Vec::new()
+    }
+
     /// Check if commands can be handled without database connection
     fn can_handle_commands_without_connection(&self, commands: &[String]) -> bool {
         commands.iter().all(|cmd| {
@@ -504,21 +720,27 @@ impl CliCore {
                         println!("Saved sessions:");
                         for (name, session) in sessions {
                             let db_type = session.database_type.display_name();
+                            let group = session
+                                .group
+                                .as_ref()
+                                .map(|group| format!(" [{group}]"))
+                                .unwrap_or_default();
                             if session.database_type.is_file_based() {
                                 if let Some(ref file_path) = session.file_path {
-                                    println!("  {name} - {file_path} ({db_type})");
+                                    println!("  {name} - {file_path} ({db_type}){group}");
                                 } else {
-                                    println!("  {name} - SQLite (no path)");
+                                    println!("  {name} - SQLite (no path){group}");
                                 }
                             } else {
                                 println!(
-                                    "  {} - {}@{}:{}/{} ({})",
+                                    "  {} - {}@{}:{}/{} ({}){}",
                                     name,
                                     session.user,
                                     session.host,
                                     session.port,
                                     session.dbname,
-                                    db_type
+                                    db_type,
+                                    group
                                 );
                             }
                         }
@@ -590,7 +812,238 @@ impl CliCore {
         }
     }
 
-    /// Handle the `dbcrust config [show|get|set|edit]` CLI subcommand —
+    async fn handle_daemon_subcommand(
+        &self,
+        action: &Option<crate::cli::DaemonAction>,
+    ) -> Result<(), CliError> {
+        use crate::cli::DaemonAction;
+        let result = match action {
+            None => crate::daemon::start(crate::daemon::DEFAULT_IDLE_TIMEOUT_SECS).await,
+            Some(DaemonAction::Start { idle_timeout }) => crate::daemon::start(*idle_timeout).await,
+            Some(DaemonAction::Run { idle_timeout }) => {
+                crate::daemon::run(*idle_timeout, &self.config.metrics)
+                    .await
+                    .map(|()| "dbcrust daemon exited.".to_string())
+            }
+            Some(DaemonAction::Status) => crate::daemon::status().await.map(|s| s.render()),
+            Some(DaemonAction::Stop) => crate::daemon::stop().await,
+        };
+        match result {
+            Ok(message) => {
+                println!("{message}");
+                Ok(())
+            }
+            Err(e) => Err(CliError::CommandError(e.to_string())),
+        }
+    }
+
+    /// Handle the `dbcrust tunnel [list|add|remove|test|suggest]` CLI subcommand
+    async fn handle_tunnel_subcommand(
+        &mut self,
+        action: &Option<crate::cli::TunnelAction>,
+    ) -> Result<(), CliError> {
+        use crate::cli::TunnelAction;
+        use crate::config_editor as editor;
+        let result = match action {
+            None | Some(TunnelAction::List) => Ok(editor::list_tunnel_patterns(&self.config)),
+            Some(TunnelAction::Add { pattern, target }) => {
+                editor::add_tunnel_pattern(&mut self.config, pattern, target)
+            }
+            Some(TunnelAction::Remove { pattern }) => {
+                editor::remove_tunnel_pattern(&mut self.config, pattern)
+            }
+            Some(TunnelAction::Test { host, port }) => self.test_tunnel(host, *port).await,
+            Some(TunnelAction::Suggest) => Ok(editor::render_tunnel_suggestions(&self.config)),
+        };
+        match result {
+            Ok(message) => {
+                println!("{message}");
+                Ok(())
+            }
+            Err(e) => Err(CliError::CommandError(e)),
+        }
+    }
+
+    /// Handle `dbcrust import-settings`: ask for each part of the bundle
+    /// unless `yes`, then merge the chosen ones
+    fn handle_import_settings(file: &str, yes: bool) -> Result<(), CliError> {
+        let bundle = crate::settings_bundle::read(std::path::Path::new(file))
+            .map_err(CliError::CommandError)?;
+        println!(
+            "Settings bundle from dbcrust {} ({file})",
+            bundle.dbcrust_version
+        );
+        let mut selected = Vec::new();
+        for part in bundle.parts() {
+            let description = bundle.describe(part);
+            let wanted = yes
+                || inquire::Confirm::new(&format!("Import {description}?"))
+                    .with_default(true)
+                    .prompt()
+                    .map_err(|e| CliError::CommandError(e.to_string()))?;
+            if wanted {
+                selected.push(part);
+            }
+        }
+        let message =
+            crate::settings_bundle::import(&bundle, &selected).map_err(CliError::CommandError)?;
+        println!("{message}");
+        Ok(())
+    }
+
+    /// Handle `dbcrust check`: one row per session, exit status 1 when any failed
+    async fn handle_check_subcommand(
+        &self,
+        group: Option<&str>,
+        timeout: u64,
+    ) -> Result<i32, CliError> {
+        let results = crate::session_check::check_sessions(
+            &self.config,
+            group,
+            std::time::Duration::from_secs(timeout),
+        )
+        .await
+        .map_err(CliError::ArgumentError)?;
+        print!(
+            "{}",
+            crate::format::format_query_results_psql(&crate::session_check::to_rows(&results))
+        );
+        let failed = results.iter().filter(|result| result.is_failure()).count();
+        println!(
+            "{} of {} session{} reachable",
+            results
+                .iter()
+                .filter(|result| matches!(
+                    result.status,
+                    crate::session_check::CheckStatus::Reachable { .. }
+                ))
+                .count(),
+            results.len(),
+            if results.len() == 1 { "" } else { "s" }
+        );
+        Ok(if failed > 0 { 1 } else { 0 })
+    }
+
+    /// Handle `dbcrust notebook run`: connect, run the cells and write the
+    /// results back. Exits with 1 when a cell failed.
+    async fn handle_notebook_subcommand(
+        &mut self,
+        args: &Args,
+        action: &crate::cli::NotebookAction,
+    ) -> Result<i32, CliError> {
+        let crate::cli::NotebookAction::Run {
+            file,
+            output,
+            max_rows,
+            keep_going,
+        } = action;
+        let text = std::fs::read_to_string(file).map_err(|e| {
+            CliError::ArgumentError(format!("Failed to read notebook '{file}': {e}"))
+        })?;
+        let mut notebook = crate::notebook::parse(&text);
+
+        let connection_url = args
+            .connection_url
+            .clone()
+            .or_else(|| notebook.connection.clone())
+            .ok_or_else(|| {
+                CliError::ArgumentError(format!(
+                    "No connection for '{file}': pass a URL (dbcrust <URL> notebook run {file}) or add `connection: <URL>` front matter"
+                ))
+            })?;
+        let to_stdout = output.as_deref() == Some("-");
+        crate::db::set_status_to_stderr(to_stdout);
+        let connect_args = Args {
+            connection_url: Some(connection_url),
+            ..args.clone()
+        };
+        self.handle_database_connection(&connect_args).await?;
+
+        let database = self
+            .database
+            .as_mut()
+            .ok_or_else(|| CliError::CommandError("No database connection".to_string()))?;
+        let summary = crate::notebook::run(&mut notebook, database, *max_rows, *keep_going).await;
+        let rendered = crate::notebook::render(&notebook);
+
+        let target = output.as_deref().unwrap_or(file);
+        if to_stdout {
+            print!("{rendered}");
+        } else {
+            std::fs::write(target, rendered).map_err(|e| {
+                CliError::CommandError(format!("Failed to write notebook '{target}': {e}"))
+            })?;
+        }
+
+        let mut message = format!(
+            "Ran {} cell{}",
+            summary.executed,
+            if summary.executed == 1 { "" } else { "s" }
+        );
+        if summary.failed > 0 {
+            message.push_str(&format!(", {} failed", summary.failed));
+        }
+        if summary.not_run > 0 {
+            message.push_str(&format!(", {} not run", summary.not_run));
+        }
+        if !to_stdout {
+            message.push_str(&format!(" → {target}"));
+        }
+        eprintln!("{message}");
+        Ok(if summary.failed > 0 { 1 } else { 0 })
+    }
+
+    /// `dbcrust tunnel test`: resolve the tunnel for a host (or URL) the way a
+    /// connection would, open it, and check the database port answers.
+    async fn test_tunnel(&self, host_or_url: &str, port: Option<u16>) -> Result<String, String> {
+        let (host, url_port) = if host_or_url.contains("://") {
+            let url = url::Url::parse(host_or_url).map_err(|e| format!("Invalid URL: {e}"))?;
+            let host = url
+                .host_str()
+                .ok_or_else(|| format!("No host in {host_or_url}"))?
+                .to_string();
+            let default_port = DatabaseType::from_scheme(url.scheme())
+                .ok()
+                .and_then(|db_type| db_type.default_port());
+            (host, url.port().or(default_port))
+        } else {
+            (host_or_url.to_string(), None)
+        };
+        let port = port.or(url_port).unwrap_or(5432);
+
+        let tunnel_config = if let Some(target) = crate::ssh_tunnel::explicit_tunnel() {
+            println!(
+                "Using --ssh-tunnel {}",
+                crate::password_sanitizer::sanitize_ssh_tunnel_string(&target)
+            );
+            self.config.parse_ssh_tunnel_string(&target)
+        } else {
+            let matches = crate::config_editor::matching_tunnel_patterns(&self.config, &host);
+            let Some((pattern, target)) = matches.first() else {
+                return Err(format!(
+                    "No SSH tunnel pattern matches \"{host}\" — connections to it are direct. \
+                     Add one with: dbcrust tunnel add <pattern> <target>"
+                ));
+            };
+            println!(
+                "Pattern {pattern}  ->  {}",
+                crate::password_sanitizer::sanitize_ssh_tunnel_string(target)
+            );
+            self.config.get_ssh_tunnel_for_host(&host)
+        };
+        let tunnel_config =
+            tunnel_config.ok_or_else(|| "Failed to resolve the tunnel target".to_string())?;
+
+        match crate::ssh_tunnel::check_connectivity(&tunnel_config, &host, port).await {
+            Ok((route, elapsed)) => Ok(format!(
+                "✓ {host}:{port} is reachable through {route} ({} ms)",
+                elapsed.as_millis()
+            )),
+            Err(e) => Err(format!("✗ {host}:{port}: {e}")),
+        }
+    }
+
+    /// Handle the `dbcrust config [show|get|list|set|unset|edit]` CLI subcommand —
     /// no database connection involved. Bare `dbcrust config` opens the
     /// interactive menu when stdin/stdout are TTYs.
     fn handle_config_subcommand(
@@ -610,9 +1063,15 @@ impl CliCore {
             Some(ConfigAction::Get { key }) => {
                 crate::config_editor::get_value(&self.config, key.as_deref())
             }
+            Some(ConfigAction::List { changed }) => {
+                Ok(crate::config_editor::list_values(&self.config, *changed))
+            }
             Some(ConfigAction::Set { key, value }) => {
                 crate::config_editor::set_value(&mut self.config, key, value)
             }
+            Some(ConfigAction::Unset { key }) => {
+                crate::config_editor::unset_value(&mut self.config, key)
+            }
             Some(ConfigAction::Edit) => crate::config_editor::edit_in_editor(&mut self.config),
         };
         match result {
@@ -642,6 +1101,9 @@ impl CliCore {
             "SELECT queries use a default limit of {} rows. Use \\config to change defaults.",
             config.default_limit
         );
+        if let Some(layer) = config.project_layer() {
+            println!("Using project config {}", layer.path.display());
+        }
     }
 
     /// Handle database connection setup - core connection logic
@@ -663,6 +1125,10 @@ impl CliCore {
             .as_ref()
             .and_then(|info| crate::config::password_command_from_options(&info.options))
             .map(str::to_string);
+        // IAM tokens replace the password: there is nothing to look up or ask
+        let iam_auth = parsed_original_connection
+            .as_ref()
+            .is_some_and(|info| matches!(crate::iam_auth::provider(&info.options), Ok(Some(_))));
         let password_command_connection_info = if password_command.is_some() {
             parsed_original_connection.clone()
         } else {
@@ -711,9 +1177,12 @@ impl CliCore {
                 let error_msg = e.to_string().to_lowercase();
 
                 // Check if this is an authentication error
-                if !Self::is_authentication_error(&error_msg) {
+                if iam_auth || !Self::is_authentication_error(&error_msg) {
                     // Not an auth error, return the original error
-                    eprintln!("Failed to connect to database: {e}");
+                    eprintln!(
+                        "Failed to connect to database: {}",
+                        crate::password_sanitizer::sanitize_text_for_logging(&e.to_string())
+                    );
                     eprintln!(
                         "Connection URL: {}",
                         crate::password_sanitizer::sanitize_connection_url(original_url)
@@ -737,6 +1206,7 @@ impl CliCore {
             crate::database::DatabaseType::MongoDB => DatabaseType::MongoDB,
             crate::database::DatabaseType::Elasticsearch => DatabaseType::Elasticsearch,
             crate::database::DatabaseType::ClickHouse => DatabaseType::ClickHouse,
+            crate::database::DatabaseType::Snowflake => DatabaseType::Snowflake,
             crate::database::DatabaseType::SQLite
             | crate::database::DatabaseType::Parquet
             | crate::database::DatabaseType::CSV
@@ -757,6 +1227,7 @@ impl CliCore {
             DatabaseType::MongoDB => 27017,
             DatabaseType::Elasticsearch => 9200,
             DatabaseType::ClickHouse => 8123,
+            DatabaseType::Snowflake => 443,
             DatabaseType::SQLite => 0, // Not used
         });
         let database_name = connection_info.database.as_deref().unwrap_or("");
@@ -1189,33 +1660,59 @@ impl CliCore {
     }
 
     pub async fn handle_database_connection(&mut self, args: &Args) -> Result<(), CliError> {
-        let connection_url = args.connection_url.clone().ok_or_else(|| {
+        let mut connection_url = args.connection_url.clone().ok_or_else(|| {
             CliError::ArgumentError("No database connection specified".to_string())
         })?;
 
+        // An [aliases] name stands for its URL; history remembers the alias
+        let alias = self
+            .config
+            .resolve_alias(&connection_url)
+            .map(|url| std::mem::replace(&mut connection_url, url.to_string()));
+
+        // --ssh-tunnel wins over ssh_tunnel_patterns (set per connection: the
+        // daemon opens connections with different tunnels in one process)
+        crate::ssh_tunnel::set_explicit_tunnel(args.ssh_tunnel.clone());
+
         // Normalize bare targets: known local file extensions open as file-backed
         // connections, everything else keeps the historical PostgreSQL default.
-        let mut full_url_str = Self::normalize_connection_target(&connection_url);
+        let full_url_str = Self::normalize_connection_target(&connection_url);
 
         // Handle different URL schemes
-        full_url_str = self.handle_special_url_schemes(full_url_str).await?;
+        let (mut full_url_str, mut history_url) =
+            self.handle_special_url_schemes(full_url_str).await?;
+
+        // Expand `${NAME}` placeholders last, so history keeps the template
+        // rather than the secrets it resolves to
+        if crate::config::has_env_placeholders(&full_url_str) {
+            history_url.get_or_insert_with(|| full_url_str.clone());
+            full_url_str = crate::config::expand_env_in_url(&full_url_str)
+                .map_err(CliError::ConnectionError)?;
+        }
 
         // Handle vault URLs
         if full_url_str.starts_with("vault://") {
             let (database, connection_info) = self.handle_vault_connection(&full_url_str).await?;
 
             // Track vault connection in history with vault metadata
-            // Reconstruct the complete vault URL from metadata (like saved sessions do)
+            // Reconstruct the complete vault URL from metadata (like saved sessions do),
+            // keeping the auth query parameters so reconnecting logs in the same way
             let complete_vault_url = if let Some(ref conn_info) = connection_info {
                 if let (Some(vault_mount), Some(vault_database), Some(vault_role)) = (
                     conn_info.options.get("vault_mount"),
                     conn_info.options.get("vault_database"),
                     conn_info.options.get("vault_role"),
                 ) {
+                    let auth_query = history_url
+                        .as_deref()
+                        .unwrap_or(&full_url_str)
+                        .split_once('?')
+                        .map(|(_, query)| format!("?{query}"))
+                        .unwrap_or_default();
                     if vault_role.is_empty() {
-                        format!("vault://{vault_mount}/{vault_database}")
+                        format!("vault://{vault_mount}/{vault_database}{auth_query}")
                     } else {
-                        format!("vault://{vault_role}@{vault_mount}/{vault_database}")
+                        format!("vault://{vault_role}@{vault_mount}/{vault_database}{auth_query}")
                     }
                 } else {
                     full_url_str.to_string()
@@ -1224,11 +1721,14 @@ impl CliCore {
                 full_url_str.to_string()
             };
 
-            let options = if let Some(ref conn_info) = connection_info {
+            let mut options = if let Some(ref conn_info) = connection_info {
                 conn_info.options.clone()
             } else {
                 std::collections::HashMap::new()
             };
+            if let Some(alias) = alias {
+                options.insert("alias".to_string(), alias);
+            }
 
             if let Err(e) = self.config.add_recent_connection_with_options(
                 complete_vault_url,
@@ -1241,6 +1741,8 @@ impl CliCore {
 
             self.database = Some(database);
             self.connection_info = connection_info;
+            self.report_latency().await;
+            self.report_client_cert();
             return Ok(());
         }
 
@@ -1253,7 +1755,10 @@ impl CliCore {
             )
             .await
             .map_err(|e| {
-                eprintln!("Failed to connect to database: {e}");
+                eprintln!(
+                    "Failed to connect to database: {}",
+                    crate::password_sanitizer::sanitize_text_for_logging(&e.to_string())
+                );
                 eprintln!(
                     "Connection URL: {}",
                     crate::password_sanitizer::sanitize_connection_url(&full_url_str)
@@ -1285,12 +1790,37 @@ impl CliCore {
             let sanitized_url = crate::password_sanitizer::sanitize_connection_url(&full_url_str);
             (database_type, sanitized_url)
         };
+        let connection_url_for_history = history_url
+            .map(|url| crate::password_sanitizer::sanitize_connection_url(&url))
+            .unwrap_or(connection_url_for_history);
 
-        if let Err(e) = self.config.add_recent_connection_auto_display(
-            connection_url_for_history,
-            database_type,
-            true,
-        ) {
+        // Remember --ssh-tunnel so `dbcrust tunnel suggest` and recent:// can reuse it,
+        // and the alias so connecting through it again replaces this entry
+        let mut options = std::collections::HashMap::new();
+        if let Some(tunnel) = crate::ssh_tunnel::explicit_tunnel() {
+            options.insert(
+                "ssh_tunnel".to_string(),
+                crate::password_sanitizer::strip_ssh_tunnel_password(&tunnel),
+            );
+        }
+        if let Some(alias) = alias {
+            options.insert("alias".to_string(), alias);
+        }
+        let recorded = if !options.is_empty() {
+            self.config.add_recent_connection_with_options(
+                connection_url_for_history,
+                database_type,
+                true,
+                options,
+            )
+        } else {
+            self.config.add_recent_connection_auto_display(
+                connection_url_for_history,
+                database_type,
+                true,
+            )
+        };
+        if let Err(e) = recorded {
             debug!("Failed to add connection to history: {}", e);
         }
 
@@ -1298,21 +1828,98 @@ impl CliCore {
         self.connection_info = connection_info;
 
         // Show success message
-        println!("✓ Successfully connected to database");
+        crate::db::print_status("✓ Successfully connected to database");
+        self.report_latency().await;
+        self.report_client_cert();
         Ok(())
     }
 
+    /// Watch the mTLS client certificate for rotation and warn when it
+    /// expires soon (`[tls] cert_expiry_warn_days`)
+    fn report_client_cert(&mut self) {
+        let warn_days = self.config.tls.cert_expiry_warn_days;
+        let Some(database) = self.database.as_mut() else {
+            return;
+        };
+        *database.client_cert_watch_mut() = crate::client_cert::CertWatch::for_connection(database);
+        if let Some(warning) = crate::client_cert::check_expiry(database, warn_days) {
+            eprintln!("{warning}");
+        }
+    }
+
+    /// Latency banner and slow-connection warning after connecting
+    /// (`[display] show_latency`, `latency_warn_ms`)
+    async fn report_latency(&mut self) {
+        let show = self.config.display.show_latency;
+        let warn_ms = self.config.display.latency_warn_ms;
+        if !show && warn_ms == 0 {
+            return;
+        }
+        let Some(database) = self.database.as_mut() else {
+            return;
+        };
+        let report = crate::latency::measure(database).await;
+        if show && let Some(banner) = report.banner() {
+            crate::db::print_status(&banner);
+        }
+        if let Some(warning) = report.warning(warn_ms) {
+            eprintln!("{warning}");
+        }
+    }
+
     /// Handle -c command mode (execute commands and exit).
     /// Returns the process exit code: non-zero when any command failed, so
     /// scripts chaining `dbcrust ... -c "..." && next-step` can rely on it.
-    async fn handle_command_mode(&mut self, args: &Args) -> Result<i32, CliError> {
+    pub(crate) async fn handle_command_mode(
+        &mut self,
+        commands: &[String],
+        output: &mut CommandModeOutput,
+    ) -> Result<i32, CliError> {
+        self.execute_commands(commands, output, false).await
+    }
+
+    /// Handle -f script mode: run each script (`-` is stdin) and stop at the
+    /// first failing statement or backslash command, like psql with
+    /// `ON_ERROR_STOP`. Returns the process exit code.
+    pub(crate) async fn handle_script_mode(
+        &mut self,
+        sources: &[String],
+        output: &mut CommandModeOutput,
+    ) -> Result<i32, CliError> {
+        let mut commands = Vec::new();
+        for source in sources {
+            let script = if source == "-" {
+                io::read_to_string(io::stdin())
+                    .map_err(|e| CliError::ArgumentError(format!("Failed to read stdin: {e}")))?
+            } else {
+                std::fs::read_to_string(source).map_err(|e| {
+                    CliError::ArgumentError(format!("Failed to read script '{source}': {e}"))
+                })?
+            };
+            commands.extend(crate::sql_buffer::split_script(&script));
+        }
+        self.execute_commands(&commands, output, true).await
+    }
+
+    async fn execute_commands(
+        &mut self,
+        commands: &[String],
+        output: &mut CommandModeOutput,
+        stop_on_error: bool,
+    ) -> Result<i32, CliError> {
         let mut failed = false;
-        for command in &args.command {
+        for command in commands {
+            if failed && stop_on_error {
+                break;
+            }
             let command_trimmed = command.trim();
 
             if command_trimmed.starts_with('\\') {
                 // Handle backslash commands
-                match self.execute_backslash_command(command_trimmed).await? {
+                match self
+                    .execute_backslash_command(command_trimmed, output)
+                    .await?
+                {
                     CommandModeOutcome::Success => {}
                     CommandModeOutcome::Failed => failed = true,
                     // \q is a clean stop, not an error
@@ -1322,7 +1929,9 @@ impl CliCore {
                 self.resolve_named_query_for_command_mode(command_trimmed)
             {
                 // Execute named query
-                if self.execute_named_query_command_mode(name, args).await?
+                if self
+                    .execute_named_query_command_mode(name, args, output)
+                    .await?
                     == CommandModeOutcome::Failed
                 {
                     failed = true;
@@ -1358,32 +1967,22 @@ impl CliCore {
                         .await
                     {
                         Ok(results_with_info) => {
-                            if !results_with_info.data.is_empty() {
-                                let is_expanded = database.is_expanded_display();
-                                if is_expanded {
-                                    let tables =
-                                        format_query_results_expanded(&results_with_info.data);
-                                    let mut combined_output = String::new();
-                                    for table in tables {
-                                        combined_output.push_str(&format!("{table}\n"));
-                                    }
-                                    Self::page_or_print(&combined_output, &self.config)?;
-                                } else {
-                                    let formatted_output = format_query_results_psql_with_info(
-                                        &results_with_info.data,
-                                        results_with_info.column_info.as_ref(),
-                                    );
-                                    Self::page_or_print(&formatted_output, &self.config)?;
-                                }
+                            // Complete bytea/BLOB values: -c output is read by scripts
+                            if let Some(formatted_output) = format_result_sets(
+                                &results_with_info,
+                                database.is_expanded_display(),
+                                false,
+                            ) {
+                                output.page(&formatted_output, &self.config)?;
                             }
                         }
                         Err(e) => {
                             // Check if this is a column selection abort
-                            if e.to_string().contains("Column selection aborted") {
+                            if matches!(e, crate::error::DbcrustError::Aborted(_)) {
                                 // User-initiated abort: stop without an error
                                 return Ok(if failed { 1 } else { 0 });
                             }
-                            eprintln!("Error executing query: {e}");
+                            output.eprintln(&format!("Error executing query: {e}"));
                             failed = true;
                             // Stop the batch at the first failing statement
                             break 'statements;
@@ -1411,6 +2010,7 @@ impl CliCore {
         &mut self,
         name: String,
         args: Vec<String>,
+        output: &mut CommandModeOutput,
     ) -> Result<CommandModeOutcome, CliError> {
         let command = Command::ExecuteNamedQuery { name, args };
 
@@ -1446,16 +2046,16 @@ impl CliCore {
             )
             .await
         {
-            Ok(CommandResult::Output(output)) => {
-                Self::page_or_print(&output, &self.config)?;
+            Ok(CommandResult::Output(result)) => {
+                output.page(&result, &self.config)?;
                 CommandModeOutcome::Success
             }
             Ok(CommandResult::Error(error)) => {
-                eprintln!("Named query error: {error}");
+                output.eprintln(&format!("Named query error: {error}"));
                 CommandModeOutcome::Failed
             }
             Err(e) => {
-                eprintln!("Error executing named query: {e}");
+                output.eprintln(&format!("Error executing named query: {e}"));
                 CommandModeOutcome::Failed
             }
             _ => CommandModeOutcome::Success,
@@ -1475,6 +2075,7 @@ impl CliCore {
     async fn execute_backslash_command(
         &mut self,
         command_str: &str,
+        output: &mut CommandModeOutput,
     ) -> Result<CommandModeOutcome, CliError> {
         // Parse string command into typed Command enum
         let command = CommandParser::parse(command_str)
@@ -1526,16 +2127,27 @@ impl CliCore {
             // database must still be restored below before returning
             Ok(CommandResult::Exit) => CommandModeOutcome::Exit,
             Ok(CommandResult::Continue) => CommandModeOutcome::Success,
-            Ok(CommandResult::Output(output)) => {
-                println!("{output}");
+            Ok(CommandResult::Output(result)) if result.starts_with("__RECONNECT__") => {
+                output.eprintln(
+                    "Command error: \\c to another server only works in the interactive REPL",
+                );
+                CommandModeOutcome::Failed
+            }
+            Ok(CommandResult::Output(result)) => {
+                // No prompt to pre-fill in command mode: print what \browse picked
+                let result = result
+                    .strip_prefix("__INSERT_INPUT__")
+                    .or_else(|| result.strip_prefix("__PAGE__"))
+                    .unwrap_or(&result);
+                output.println(result);
                 CommandModeOutcome::Success
             }
             Ok(CommandResult::Error(error)) => {
-                eprintln!("Command error: {error}");
+                output.eprintln(&format!("Command error: {error}"));
                 CommandModeOutcome::Failed
             }
             Err(e) => {
-                eprintln!("Error executing command: {e}");
+                output.eprintln(&format!("Error executing command: {e}"));
                 CommandModeOutcome::Failed
             }
         };
@@ -1594,6 +2206,8 @@ impl CliCore {
         let db_arc = Arc::new(Mutex::new(database));
         let config_arc = Arc::new(Mutex::new(self.config.clone()));
         let mut last_script = String::new();
+        // Last query or backslash command entered, for \watch
+        let mut last_watchable: Option<String> = None;
         // The process-wide flag: the Ctrl-C handler sets it, the database
         // clients poll it to cancel the running statement server-side
         let interrupt_flag = crate::database::interrupt_flag().clone();
@@ -1619,36 +2233,34 @@ impl CliCore {
         let full_line_buffer = Arc::new(Mutex::new(None::<String>));
 
         // Create highlighter for SQL syntax
-        let highlighter = SqlHighlighter::new(full_line_buffer.clone());
+        let cursor_position = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let highlighter = SqlHighlighter::new(full_line_buffer.clone())
+            .with_cursor_position(cursor_position.clone());
 
         // Set up reedline components exactly as in the working version
         use reedline::{
-            ColumnarMenu, DefaultHinter, Emacs, FileBackedHistory, KeyCode, KeyModifiers,
-            MenuBuilder, ReedlineEvent, ReedlineMenu, default_emacs_keybindings,
+            ColumnarMenu, DefaultHinter, Emacs, FileBackedHistory, MenuBuilder, ReedlineMenu,
         };
 
         // Set up completion menu
         let completion_menu = Box::new(ColumnarMenu::default().with_name("completion_menu"));
 
-        // Set up keybindings (this enables Tab completion!)
-        let mut keybindings = default_emacs_keybindings();
-        keybindings.add_binding(
-            KeyModifiers::NONE,
-            KeyCode::Tab,
-            ReedlineEvent::UntilFound(vec![
-                ReedlineEvent::Menu("completion_menu".to_string()),
-                ReedlineEvent::MenuNext,
-            ]),
-        );
-
-        // Add Shift+Tab for navigating up through suggestions
-        keybindings.add_binding(
-            KeyModifiers::SHIFT,
-            KeyCode::BackTab,
-            ReedlineEvent::MenuPrevious,
-        );
+        // Emacs bindings with Tab for completion, plus [keybindings]
+        let (keybindings, warnings, configured) =
+            crate::keybindings::build(&self.config.keybindings);
+        for warning in warnings {
+            eprintln!("Warning: {warning}");
+        }
+        crate::keybindings::set_active(&keybindings, &configured);
 
-        let edit_mode = Box::new(Emacs::new(keybindings));
+        // Tab expands [snippets] triggers and jumps between their placeholders
+        // before falling back to the completion menu
+        let edit_mode = Box::new(crate::snippets::SnippetEditMode::new(
+            Box::new(Emacs::new(keybindings)),
+            config_arc.clone(),
+            full_line_buffer.clone(),
+            cursor_position,
+        ));
 
         // Set up hinter
         let hinter = Box::new(
@@ -1696,12 +2308,17 @@ impl CliCore {
         };
 
         // Create completer and editor with full configuration
+        let completion_usage =
+            Arc::new(Mutex::new(crate::completion_usage::CompletionUsage::load()));
         let completer = if self.config.autocomplete_enabled {
-            Box::new(SqlCompleter::new_with_line_buffer(
-                db_arc.clone(),
-                config_arc.clone(),
-                full_line_buffer.clone(),
-            )) as Box<dyn reedline::Completer>
+            Box::new(
+                SqlCompleter::new_with_line_buffer(
+                    db_arc.clone(),
+                    config_arc.clone(),
+                    full_line_buffer.clone(),
+                )
+                .with_usage(completion_usage.clone()),
+            ) as Box<dyn reedline::Completer>
         } else {
             Box::new(NoopCompleter {}) as Box<dyn reedline::Completer>
         };
@@ -1714,21 +2331,59 @@ impl CliCore {
             .with_hinter(hinter)
             .with_highlighter(Box::new(highlighter))
             // Enter inserts a newline instead of submitting while a string,
-            // dollar-quote, or block comment is still open
-            .with_validator(Box::new(crate::sql_buffer::SqlValidator))
-            .with_history(history);
+            // dollar-quote, block comment or parenthesis is still open, by
+            // the connected database's quoting rules
+            .with_validator(Box::new(crate::sql_buffer::SqlValidator::new(
+                db_arc.clone(),
+            )))
+            .with_history(Box::new(FormattingHistory::new(
+                history,
+                config_arc.clone(),
+                db_arc.clone(),
+            )));
+
+        // Completion starts from the names saved by the last session; bring
+        // them up to date without holding up the prompt
+        crate::schema_cache::refresh_in_background(db_arc.clone());
+        // Pings the server while the prompt waits so idle connections stay open
+        let _keepalive = crate::keepalive::start(&db_arc, &self.config.connection);
 
         println!("Connected! Type \\h for help or \\q to quit.");
 
+        let mut config_watcher = crate::config_editor::ConfigWatcher::new();
+
         // Main interactive loop
         loop {
+            if let Some(text) = self.pending_input.take() {
+                line_editor.run_edit_commands(&[reedline::EditCommand::InsertString(text)]);
+            }
             let sig = line_editor
                 .read_line(&prompt)
                 .map_err(|e| CliError::CommandError(format!("Read line error: {e}")))?;
 
+            // Pick up external edits of config.toml before handling the input
+            if let Some(watcher) = config_watcher.as_mut()
+                && watcher.has_changed()
+            {
+                let mut config_guard = config_arc.lock().unwrap();
+                if config_guard.auto_reload_config {
+                    match crate::config_editor::reload_from_disk(&mut config_guard) {
+                        Ok(changes) if !changes.is_empty() => {
+                            db_arc.lock().unwrap().apply_config(&config_guard);
+                            println!("{}", crate::config_editor::reload_report(&changes));
+                        }
+                        Ok(_) => {}
+                        Err(e) => eprintln!("{e}"),
+                    }
+                }
+            }
+
             match sig {
                 Signal::Success(buffer) => {
-                    let line = buffer.trim();
+                    // Commands sent by [keybindings] do not become the
+                    // previous input that \g and \watch repeat
+                    let bound = crate::keybindings::bound_action(&buffer);
+                    let line = bound.unwrap_or(buffer.trim());
 
                     // If empty input but we have a pending script (from \ed or
                     // \i), execute it ONCE. The buffer is cleared afterwards:
@@ -1754,6 +2409,44 @@ impl CliCore {
                         continue;
                     }
 
+                    // A connection idle for [connection] keepalive_seconds is
+                    // pinged, and replaced when it died, before it is used;
+                    // so is one whose client certificate was rotated
+                    if line != "\\q" {
+                        let (connection, warn_days) = {
+                            let config = config_arc.lock().unwrap();
+                            (config.connection.clone(), config.tls.cert_expiry_warn_days)
+                        };
+                        let (notice, queued) = {
+                            let mut db = db_arc.lock().unwrap();
+                            let notice =
+                                crate::keepalive::before_statement(&mut db, &connection).await;
+                            let notice = match (
+                                notice,
+                                crate::client_cert::before_statement(&mut db, warn_days).await,
+                            ) {
+                                (Some(a), Some(b)) => Some(format!("{a}\n{b}")),
+                                (a, b) => a.or(b),
+                            };
+                            let queued = if db.idle_state_mut().is_lost() {
+                                0
+                            } else {
+                                db.offline_queue_mut().len()
+                            };
+                            (notice, queued)
+                        };
+                        if let Some(notice) = notice {
+                            println!("{notice}");
+                        }
+                        // Statements typed while the connection was down
+                        if queued > 0
+                            && !line.starts_with("\\queue")
+                            && crate::offline_queue::confirm_run(queued)
+                        {
+                            self.run_offline_queue(&db_arc, &interrupt_flag).await;
+                        }
+                    }
+
                     // Handle AI agentic investigation prefix (???). Must be checked
                     // BEFORE ?? — "???x".strip_prefix("??") would also match.
                     if let Some(nl) = line.strip_prefix("???") {
@@ -1793,6 +2486,56 @@ impl CliCore {
                         continue;
                     }
 
+                    // \g runs the previous query or command again
+                    let repeated;
+                    let line = if let Ok(Command::Rerun) = CommandParser::parse(line) {
+                        match last_watchable.clone() {
+                            Some(previous) => {
+                                println!("{previous}");
+                                repeated = previous;
+                                repeated.as_str()
+                            }
+                            None => {
+                                eprintln!("\\g: no previous query or command to run");
+                                continue;
+                            }
+                        }
+                    } else {
+                        line
+                    };
+
+                    // \watch re-runs the previous query or command until Ctrl-C
+                    if let Ok(Command::Watch { interval }) = CommandParser::parse(line) {
+                        match last_watchable.clone() {
+                            Some(target) => {
+                                self.watch_interactive(
+                                    &target,
+                                    interval,
+                                    &db_arc,
+                                    &config_arc,
+                                    &mut last_script,
+                                    &interrupt_flag,
+                                    &mut prompt,
+                                )
+                                .await
+                            }
+                            None => eprintln!("\\watch: no previous query or command to repeat"),
+                        }
+                        continue;
+                    }
+                    if bound.is_none() {
+                        last_watchable = Some(line.to_string());
+                    }
+
+                    if bound.is_none() && config_arc.lock().unwrap().completion_rank_by_usage {
+                        let session = SessionId::from_database(&db_arc.lock().unwrap())
+                            .map(|sid| sid.to_hash());
+                        completion_usage
+                            .lock()
+                            .unwrap()
+                            .record(line, session.as_deref());
+                    }
+
                     // Handle backslash commands
                     if line.starts_with('\\') {
                         match self
@@ -1866,6 +2609,22 @@ impl CliCore {
                         }
                     }
 
+                    // While the connection is down, SQL waits in the \queue
+                    let queued = {
+                        let mut db = db_arc.lock().unwrap();
+                        if db.idle_state_mut().is_lost() {
+                            Some(db.offline_queue_mut().push(line))
+                        } else {
+                            None
+                        }
+                    };
+                    if let Some(position) = queued {
+                        println!(
+                            "Connection is down: queued as #{position}. It runs once the connection is back (\\queue list)."
+                        );
+                        continue;
+                    }
+
                     // Handle SQL queries (reedline handles multiline with Alt+Enter automatically)
                     match self
                         .execute_sql_interactive(line, &db_arc, &interrupt_flag)
@@ -1983,6 +2742,16 @@ impl CliCore {
                     self.handle_ai_select_provider(arg, config_arc).await;
                 } else if let Some(arg) = output.strip_prefix("__AI_MODEL__") {
                     self.handle_ai_select_model(arg, config_arc, db_arc).await;
+                } else if let Some(text) = output.strip_prefix("__INSERT_INPUT__") {
+                    self.pending_input = Some(text.to_string());
+                } else if let Some(url) = output.strip_prefix("__RECONNECT__") {
+                    if let Err(e) = self.reconnect(url, db_arc, config_arc, prompt).await {
+                        eprintln!("Command error: {e}");
+                    }
+                } else if output == "__QUEUE_RUN__" {
+                    self.run_offline_queue(db_arc, interrupt_flag).await;
+                } else if let Some(text) = output.strip_prefix("__PAGE__") {
+                    Self::page_or_print(&format!("{text}\n"), &self.config)?;
                 } else {
                     println!("{output}");
                 }
@@ -1999,6 +2768,94 @@ impl CliCore {
         }
     }
 
+    /// Run the statements queued while the connection was down, in order. A
+    /// failing one stops the run and stays queued with those after it.
+    async fn run_offline_queue(
+        &mut self,
+        db_arc: &Arc<Mutex<Database>>,
+        interrupt_flag: &Arc<AtomicBool>,
+    ) {
+        let statements = db_arc.lock().unwrap().offline_queue_mut().take();
+        let total = statements.len();
+        for (index, sql) in statements.iter().enumerate() {
+            println!("Queued statement {}/{total}: {sql}", index + 1);
+            if let Err(e) = self
+                .execute_sql_interactive(sql, db_arc, interrupt_flag)
+                .await
+            {
+                eprintln!("SQL error: {e}");
+                let rest = statements[index..].to_vec();
+                eprintln!(
+                    "Stopped; {} statement(s) stay queued (\\queue list).",
+                    rest.len()
+                );
+                db_arc.lock().unwrap().offline_queue_mut().restore(rest);
+                return;
+            }
+        }
+    }
+
+    /// Re-run `target` (SQL or a backslash command) every `interval`, psql
+    /// `\watch` style, until Ctrl-C or a failing SQL statement
+    #[allow(clippy::too_many_arguments)]
+    async fn watch_interactive(
+        &mut self,
+        target: &str,
+        interval: std::time::Duration,
+        db_arc: &Arc<Mutex<Database>>,
+        config_arc: &Arc<Mutex<DbCrustConfig>>,
+        last_script: &mut String,
+        interrupt_flag: &Arc<AtomicBool>,
+        prompt: &mut DbPrompt,
+    ) {
+        use std::sync::atomic::Ordering;
+
+        // A pager would block every refresh; print straight to the terminal
+        let pager_enabled = std::mem::replace(&mut self.config.pager_enabled, false);
+        interrupt_flag.store(false, Ordering::SeqCst);
+        loop {
+            println!(
+                "{} (every {}s)\n",
+                chrono::Local::now().format("%a %d %b %Y %H:%M:%S"),
+                interval.as_secs_f64()
+            );
+            let result = if target.starts_with('\\') {
+                self.execute_backslash_command_interactive(
+                    target,
+                    db_arc,
+                    config_arc,
+                    last_script,
+                    interrupt_flag,
+                    prompt,
+                )
+                .await
+                .map(|_| ())
+            } else {
+                self.execute_sql_interactive(target, db_arc, interrupt_flag)
+                    .await
+            };
+            if let Err(e) = result {
+                eprintln!("\\watch stopped: {e}");
+                break;
+            }
+
+            // Sleep in short steps so Ctrl-C ends the loop promptly
+            let deadline = std::time::Instant::now() + interval;
+            while !interrupt_flag.load(Ordering::SeqCst) {
+                let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+                if remaining.is_zero() {
+                    break;
+                }
+                tokio::time::sleep(remaining.min(std::time::Duration::from_millis(50))).await;
+            }
+            if interrupt_flag.load(Ordering::SeqCst) {
+                break;
+            }
+        }
+        interrupt_flag.store(false, Ordering::SeqCst);
+        self.config.pager_enabled = pager_enabled;
+    }
+
     // ==================== AI Assistant Methods ====================
 
     /// Handle ?? text-to-SQL generation
@@ -2401,6 +3258,60 @@ impl CliCore {
         }
     }
 
+    /// `\c <session|URL>`: connect to another server the way startup does
+    /// (tunnels, Vault, password prompts), then swap the new connection in
+    /// with the display settings of the old one. The old connection is only
+    /// closed once the new one works.
+    async fn reconnect(
+        &mut self,
+        url: &str,
+        db_arc: &Arc<Mutex<Database>>,
+        config_arc: &Arc<Mutex<DbCrustConfig>>,
+        prompt: &mut DbPrompt,
+    ) -> Result<(), CliError> {
+        // Sessions saved since startup live in the REPL's copy of the config
+        self.config = config_arc.lock().unwrap().clone();
+        let args = Args {
+            connection_url: Some(url.to_string()),
+            from_env: None,
+            from_django: None,
+            ssh_tunnel: None,
+            completions: None,
+            command: Vec::new(),
+            file: Vec::new(),
+            update: false,
+            no_daemon: true,
+            subcommand: None,
+        };
+        self.handle_database_connection(&args).await?;
+        // The new recent connection entry
+        *config_arc.lock().unwrap() = self.config.clone();
+
+        let mut database = self
+            .database
+            .take()
+            .ok_or_else(|| CliError::ConnectionError("No database connection".to_string()))?;
+        let previous = {
+            let mut db_guard = db_arc.lock().unwrap();
+            let queued = db_guard.offline_queue_mut().len();
+            if queued > 0 {
+                println!("Dropped {queued} statement(s) queued for the previous connection.");
+            }
+            database.adopt_display_settings(&db_guard);
+            let previous = std::mem::replace(&mut *db_guard, database);
+            *prompt = DbPrompt::with_config(
+                db_guard.prompt_username(),
+                db_guard.get_current_db(),
+                self.config.multiline_prompt_indicator.clone(),
+            );
+            previous
+        };
+        // Closes the old pool and its SSH tunnel
+        drop(previous);
+        self.ai_schema_cache = None;
+        Ok(())
+    }
+
     /// Handle \ai setup - interactive setup wizard
     async fn handle_ai_setup(&mut self, config_arc: &Arc<Mutex<DbCrustConfig>>) {
         if let Err(WizardCancelled) = self.run_ai_setup_wizard(config_arc).await {
@@ -2793,6 +3704,52 @@ impl CliCore {
         Ok(())
     }
 
+    /// Checks a statement typed in the REPL goes through before it reaches
+    /// the database: the lint pass, then the guardrail confirmation. An error
+    /// keeps the statement from running.
+    async fn pre_execution_checks(
+        &self,
+        sql: &str,
+        db_arc: &Arc<Mutex<Database>>,
+    ) -> Result<(), CliError> {
+        self.lint_statement(sql, db_arc).await?;
+        self.confirm_guarded_statement(sql)
+    }
+
+    #[allow(clippy::await_holding_lock)]
+    async fn lint_statement(
+        &self,
+        sql: &str,
+        db_arc: &Arc<Mutex<Database>>,
+    ) -> Result<(), CliError> {
+        if !self.config.lint.enabled {
+            return Ok(());
+        }
+        let findings = {
+            let mut db_guard = db_arc.lock().unwrap();
+            crate::query_lint::lint_for_database(&mut db_guard, sql, &self.config.lint).await
+        };
+        if !findings.is_empty() {
+            eprintln!("{}", crate::query_lint::format_findings(&findings));
+        }
+        if let Some(blocking) = findings
+            .iter()
+            .find(|finding| finding.severity == crate::query_lint::LintSeverity::Error)
+        {
+            return Err(CliError::CommandError(format!(
+                "Statement not run: lint rule {} is set to error (see [lint] in config.toml)",
+                blocking.rule
+            )));
+        }
+        Ok(())
+    }
+
+    /// `[guardrails]`: make the user type the target of a statement matching
+    /// one of `confirm_patterns` back before it runs
+    fn confirm_guarded_statement(&self, sql: &str) -> Result<(), CliError> {
+        crate::guardrails::confirm(sql, &self.config.guardrails).map_err(CliError::CommandError)
+    }
+
     // Lock intentionally held across the await: the REPL is single-task and
     // query execution needs exclusive Database access for its duration
     #[allow(clippy::await_holding_lock)]
@@ -2802,6 +3759,12 @@ impl CliCore {
         db_arc: &Arc<Mutex<Database>>,
         interrupt_flag: &Arc<AtomicBool>,
     ) -> Result<(), CliError> {
+        if self.reads_metadata_tables(sql, db_arc).await {
+            return self.execute_metadata_query(sql, db_arc).await;
+        }
+
+        self.pre_execution_checks(sql, db_arc).await?;
+
         // Lock held across await for query execution with column selection
         let results_with_info = {
             let mut db_guard = db_arc.lock().unwrap();
@@ -2812,62 +3775,156 @@ impl CliCore {
                 Ok(results_with_info) => results_with_info,
                 Err(e) => {
                     // Check if this is a column selection abort
-                    if e.to_string().contains("Column selection aborted") {
+                    if matches!(e, crate::error::DbcrustError::Aborted(_)) {
                         // Return Ok to go back to REPL without error
                         return Ok(());
                     }
-                    return Err(CliError::CommandError(e.to_string()));
+                    // Show the error as a block: class and code, detail and
+                    // hint, the offending line with a caret under the
+                    // position, and the table probably meant
+                    let error_text = e.to_string();
+                    return Err(match CliError::from(e) {
+                        CliError::CommandError(_) => {
+                            let report = crate::error_display::parse(&error_text);
+                            let excerpt = crate::error_position::excerpt(&error_text, sql);
+                            let suggestion =
+                                crate::error_display::suggest_table(&mut db_guard, &error_text)
+                                    .await;
+                            CliError::CommandError(crate::error_display::render(
+                                &report,
+                                excerpt.as_deref(),
+                                suggestion.as_deref(),
+                            ))
+                        }
+                        error => error,
+                    });
                 }
             }
         };
 
-        if !results_with_info.data.is_empty() {
-            let is_expanded = {
-                let db_guard = db_arc.lock().unwrap();
-                db_guard.is_expanded_display()
-            };
-
-            if is_expanded {
-                let tables = format_query_results_expanded(&results_with_info.data);
-                let mut combined_output = String::new();
-                for table in tables {
-                    combined_output.push_str(&format!("{table}\n"));
+        let is_expanded = {
+            let db_guard = db_arc.lock().unwrap();
+            db_guard.expanded_for_results()
+        };
+        if let Some(formatted_output) = format_result_sets(&results_with_info, is_expanded, true) {
+            if self.should_use_result_viewer(&results_with_info, is_expanded, &formatted_output) {
+                let shown = display_rows(&results_with_info);
+                let shown = shown.as_deref().unwrap_or(&results_with_info.data);
+                let masked = crate::masking::masked(shown);
+                let rows = masked.as_deref().unwrap_or(shown);
+                match crate::result_tui::run_result_viewer(rows) {
+                    Ok(()) => return Ok(()),
+                    Err(e) => debug!("Result viewer failed, falling back to the pager: {}", e),
                 }
-                Self::page_or_print(&combined_output, &self.config)?;
-            } else {
-                let formatted_output = format_query_results_psql_with_info(
-                    &results_with_info.data,
-                    results_with_info.column_info.as_ref(),
-                );
-                Self::page_or_print(&formatted_output, &self.config)?;
             }
+            Self::page_or_print(&formatted_output, &self.config)?;
         }
 
         Ok(())
     }
 
+    /// Whether `sql` reads the `dbcrust.*` tables rather than tables of a
+    /// `dbcrust` schema the server has
+    #[allow(clippy::await_holding_lock)]
+    async fn reads_metadata_tables(&self, sql: &str, db_arc: &Arc<Mutex<Database>>) -> bool {
+        let mut db_guard = db_arc.lock().unwrap();
+        if !crate::metadata_tables::references(sql, &db_guard.get_database_type()) {
+            return false;
+        }
+        !db_guard
+            .get_schemas()
+            .await
+            .unwrap_or_default()
+            .iter()
+            .any(|schema| schema.eq_ignore_ascii_case("dbcrust"))
+    }
+
+    /// Run a statement on the `dbcrust.*` tables instead of the server
+    async fn execute_metadata_query(
+        &self,
+        sql: &str,
+        db_arc: &Arc<Mutex<Database>>,
+    ) -> Result<(), CliError> {
+        let (session_id, schema, expanded) = {
+            let mut db_guard = db_arc.lock().unwrap();
+            (
+                SessionId::from_database(&db_guard),
+                db_guard.schema_cache_mut().snapshot().clone(),
+                db_guard.is_expanded_display(),
+            )
+        };
+        let history = SessionHistoryManager::new(&self.config)
+            .map(|mut manager| manager.entries(session_id.as_ref()))
+            .unwrap_or_default();
+        let sources = crate::metadata_tables::Sources {
+            history,
+            sessions: self.config.list_sessions(),
+            recent_connections: self.config.get_recent_connections().clone(),
+            schema,
+        };
+
+        let results = crate::metadata_tables::run(sql, &sources)
+            .await
+            .map_err(|e| CliError::CommandError(e.to_string()))?;
+        let output = if results.is_empty() {
+            "(no rows)\n".to_string()
+        } else if expanded {
+            format_query_results_expanded(&results)
+                .into_iter()
+                .map(|table| format!("{table}\n"))
+                .collect()
+        } else {
+            crate::format::format_query_results_psql(&results)
+        };
+        Self::page_or_print(&output, &self.config)
+    }
+
+    /// Whether results that would be paged go to the interactive grid
+    /// instead: a single tabular result set on a terminal that fits the TUI
+    fn should_use_result_viewer(
+        &self,
+        results: &crate::db::QueryResultsWithInfo,
+        expanded: bool,
+        formatted_output: &str,
+    ) -> bool {
+        let config = &self.config;
+        config.pager_interactive
+            && !expanded
+            && results.more_results.is_empty()
+            && results.data.len() > 1
+            && Self::should_use_pager(formatted_output, config)
+            && crate::explain_tui::can_run_tui()
+    }
+
     /// Handle special URL schemes like file://, session:// and recent://
-    async fn handle_special_url_schemes(&mut self, mut url: String) -> Result<String, CliError> {
+    /// Also returns the URL to record in history instead of the resolved one, if any.
+    async fn handle_special_url_schemes(
+        &mut self,
+        mut url: String,
+    ) -> Result<(String, Option<String>), CliError> {
         // Handle file picker / generic file URLs before database URL parsing
         if url.starts_with("file://") {
             url = self.handle_file_url(&url).await?;
         }
 
-        // Handle session URLs
-        if url.starts_with("session://") {
-            url = self.handle_session_url(&url).await?;
-        }
-
-        // Handle recent URLs
+        // Handle recent URLs (a recent entry may be a session:// URL)
         if url.starts_with("recent://") {
             url = self.handle_recent_url().await?;
         }
 
-        Ok(url)
+        // Handle session URLs
+        if url.starts_with("session://") {
+            return self.handle_session_url(&url).await;
+        }
+
+        Ok((url, None))
     }
 
     /// Handle session:// URLs
-    async fn handle_session_url(&mut self, url: &str) -> Result<String, CliError> {
+    async fn handle_session_url(
+        &mut self,
+        url: &str,
+    ) -> Result<(String, Option<String>), CliError> {
         let session_name = url.strip_prefix("session://").unwrap_or("");
 
         let final_session_name = if session_name.is_empty() {
@@ -2940,6 +3997,12 @@ impl CliCore {
 
                 println!("✓ Successfully retrieved session '{final_session_name}'");
 
+                // Sessions with `${NAME}` placeholders go to history by name,
+                // so the expanded values are never stored
+                if session.uses_env_placeholders() {
+                    return Ok((session_url, Some(format!("session://{final_session_name}"))));
+                }
+
                 // Track this connection in history
                 let sanitized_url =
                     crate::password_sanitizer::sanitize_connection_url(&session_url);
@@ -2951,7 +4014,7 @@ impl CliCore {
                     debug!("Failed to add connection to history: {}", e);
                 }
 
-                Ok(session_url)
+                Ok((session_url, None))
             }
             None => Err(CliError::ConnectionError(format!(
                 "Session '{final_session_name}' not found. Use \\s to list available sessions."
@@ -3007,6 +4070,11 @@ impl CliCore {
             "🔗 Connecting to recent connection: {}",
             selected_connection.display_name
         );
+        if let Some(tunnel) = selected_connection.options.get("ssh_tunnel")
+            && crate::ssh_tunnel::explicit_tunnel().is_none()
+        {
+            crate::ssh_tunnel::set_explicit_tunnel(Some(tunnel.clone()));
+        }
 
         // Reconstruct the connection URL with credentials (similar to session handling)
         let reconstructed_url =
@@ -3024,46 +4092,79 @@ impl CliCore {
             .map_err(CliError::ConnectionError)
     }
 
+    /// Value of `key` (`vault_database`, `vault_role`) in the latest successful
+    /// recent connection on this Vault mount, and database when given
+    fn last_vault_choice(
+        &self,
+        mount_path: &str,
+        database: Option<&str>,
+        key: &str,
+    ) -> Option<String> {
+        self.config
+            .get_recent_connections()
+            .iter()
+            .filter(|conn| conn.success)
+            .filter(|conn| conn.options.get("vault_mount").map(String::as_str) == Some(mount_path))
+            .filter(|conn| {
+                database.is_none_or(|database| {
+                    conn.options.get("vault_database").map(String::as_str) == Some(database)
+                })
+            })
+            .find_map(|conn| conn.options.get(key).filter(|value| !value.is_empty()))
+            .cloned()
+    }
+
     /// Handle vault:// URLs
     async fn handle_vault_connection(
         &mut self,
         url: &str,
-    ) -> Result<(Database, Option<ConnectionInfo>), CliError> {
+    ) -> crate::error::Result<(Database, Option<ConnectionInfo>)> {
+        use crate::error::DbcrustError;
+        use crate::vault_client::VaultError;
+
         let (role, mount_path, database_name) = crate::vault_client::parse_vault_url(url)
-            .ok_or_else(|| CliError::ConnectionError(format!("Invalid vault URL format: {url}")))?;
+            .ok_or_else(|| DbcrustError::Parse(format!("Invalid vault URL format: {url}")))?;
 
-        println!("🔐 Connecting to Vault...");
+        let auth_options = crate::vault_client::VaultAuthOptions::resolve(url, &self.config.vault)?;
+        let auth_summary = auth_options.summary();
+        crate::vault_client::set_vault_auth_options(auth_options);
+
+        if auth_summary.is_empty() {
+            println!("🔐 Connecting to Vault...");
+        } else {
+            println!("🔐 Connecting to Vault ({auth_summary})...");
+        }
 
         // Handle optional parameters - if None, prompt user to select
         let db_name = match database_name {
             Some(name) => name.clone(),
             None => {
                 // List all available databases and filter to only show accessible ones
-                let all_databases = crate::vault_client::list_vault_databases(&mount_path)
-                    .await
-                    .map_err(|e| {
-                        CliError::ConnectionError(format!("Failed to list Vault databases: {e}"))
-                    })?;
+                let all_databases = crate::vault_client::list_vault_databases(&mount_path).await?;
 
                 let databases = crate::vault_client::filter_databases_with_available_roles(
                     &mount_path,
                     all_databases,
                 )
-                .await
-                .map_err(|e| {
-                    CliError::ConnectionError(format!("Failed to filter accessible databases: {e}"))
-                })?;
+                .await?;
 
                 if databases.is_empty() {
-                    return Err(CliError::ConnectionError(
+                    return Err(DbcrustError::Connection(
                         "No accessible databases found in Vault mount".to_string(),
                     ));
                 }
 
+                // Start on the database picked last time on this mount
+                let cursor = self
+                    .last_vault_choice(&mount_path, None, "vault_database")
+                    .and_then(|last| databases.iter().position(|db| *db == last))
+                    .unwrap_or(0);
+
                 inquire::Select::new("Select a database:", databases)
+                    .with_starting_cursor(cursor)
                     .prompt()
                     .map_err(|e| {
-                        CliError::ConnectionError(format!("Database selection cancelled: {e}"))
+                        DbcrustError::Connection(format!("Database selection cancelled: {e}"))
                     })?
             }
         };
@@ -3074,21 +4175,24 @@ impl CliCore {
                 // List available roles for the selected database and prompt user to select
                 let roles =
                     crate::vault_client::get_available_roles_for_user(&mount_path, &db_name)
-                        .await
-                        .map_err(|e| {
-                            CliError::ConnectionError(format!("Failed to list Vault roles: {e}"))
-                        })?;
+                        .await?;
 
                 if roles.is_empty() {
-                    return Err(CliError::ConnectionError(format!(
+                    return Err(DbcrustError::Connection(format!(
                         "No roles available for database '{db_name}'"
                     )));
                 }
 
+                let cursor = self
+                    .last_vault_choice(&mount_path, Some(&db_name), "vault_role")
+                    .and_then(|last| roles.iter().position(|role| *role == last))
+                    .unwrap_or(0);
+
                 inquire::Select::new(&format!("Select role for database '{db_name}':"), roles)
+                    .with_starting_cursor(cursor)
                     .prompt()
                     .map_err(|e| {
-                        CliError::ConnectionError(format!("Role selection cancelled: {e}"))
+                        DbcrustError::Connection(format!("Role selection cancelled: {e}"))
                     })?
             }
         };
@@ -3100,39 +4204,28 @@ impl CliCore {
             &role_name,
             &mut self.config,
         )
-        .await
-        .map_err(|e| CliError::ConnectionError(format!("Failed to get Vault credentials: {e}")))?;
+        .await?;
 
         println!("✅ Successfully obtained dynamic credentials from Vault");
         println!("🔗 Connecting to PostgreSQL with temporary credentials...");
 
         // Get the database configuration from Vault to build the connection URL
-        let db_config = crate::vault_client::get_vault_database_config(&mount_path, &db_name)
-            .await
-            .map_err(|e| {
-                CliError::ConnectionError(format!("Failed to get database config from Vault: {e}"))
-            })?;
+        let db_config =
+            crate::vault_client::get_vault_database_config(&mount_path, &db_name).await?;
 
         // Extract the connection URL template from the config
         let connection_url_template = db_config
             .connection_details
             .connection_url
             .as_ref()
-            .ok_or_else(|| {
-                CliError::ConnectionError(
-                    "No connection URL found in Vault database config".to_string(),
-                )
-            })?;
+            .ok_or_else(|| VaultError::MissingConnectionUrl(db_name.clone()))?;
 
         // Construct the PostgreSQL URL using the dynamic credentials
         let postgres_url = crate::vault_client::construct_postgres_url(
             connection_url_template,
             &credentials.username,
             &credentials.password,
-        )
-        .map_err(|e| {
-            CliError::ConnectionError(format!("Failed to construct connection URL: {e}"))
-        })?;
+        )?;
 
         // Create database connection using the dynamic credentials
         let mut database = Database::from_url(
@@ -3140,19 +4233,13 @@ impl CliCore {
             Some(self.config.default_limit),
             Some(self.config.expanded_display_default),
         )
-        .await
-        .map_err(|e| {
-            CliError::ConnectionError(format!("Failed to connect with Vault credentials: {e}"))
-        })?;
+        .await?;
 
         // Create connection info for the Vault connection
         // Parse the original connection URL template to get the real host/port (not tunneled)
         let original_connection_info =
-            crate::database::ConnectionInfo::parse_url(connection_url_template).map_err(|e| {
-                CliError::ConnectionError(format!(
-                    "Failed to parse Vault connection URL template: {e}"
-                ))
-            })?;
+            crate::database::ConnectionInfo::parse_url(connection_url_template)
+                .map_err(DbcrustError::from_url_error)?;
 
         // Create connection info with original host/port and Vault metadata
         let mut options = std::collections::HashMap::new();
@@ -3171,6 +4258,7 @@ impl CliCore {
             options,
             docker_container: None,
             use_tls: original_connection_info.use_tls,
+            srv: original_connection_info.srv,
         };
 
         // Set the connection info in the database so it's accessible via get_connection_info()
@@ -3221,33 +4309,33 @@ impl CliCore {
         help
     }
 
-    /// Determine if output should be paged based on line count and configuration
+    /// Determine if output should be paged based on its size and configuration
     fn should_use_pager(output: &str, config: &DbCrustConfig) -> bool {
         // If pager is disabled, never page
         if !config.pager_enabled {
             return false;
         }
 
-        // Count lines in output
-        let line_count = output.lines().count();
+        let terminal_size = crossterm::terminal::size().ok();
 
         // Get the threshold
         let threshold = if config.pager_threshold_lines == 0 {
             // Use terminal height if available, otherwise default to 25
-            if let Ok((_, height)) = crossterm::terminal::size() {
-                height as usize
-            } else {
-                25 // Fallback default
-            }
+            terminal_size.map_or(25, |(_, height)| height as usize)
         } else {
             config.pager_threshold_lines
         };
 
-        line_count > threshold
+        // Lines wider than the terminal wrap into an unreadable table
+        let max_width = terminal_size
+            .filter(|_| config.pager_trigger_on_width)
+            .map(|(width, _)| width as usize);
+
+        pager::exceeds_screen(output, threshold, max_width)
     }
 
     /// Route output to pager or direct print based on configuration and content size
-    fn page_or_print(output: &str, config: &DbCrustConfig) -> Result<(), CliError> {
+    pub(crate) fn page_or_print(output: &str, config: &DbCrustConfig) -> Result<(), CliError> {
         if Self::should_use_pager(output, config) {
             // Try to use pager
             match pager::page_output(output, &config.pager_command) {
@@ -3284,6 +4372,38 @@ mod tests {
         config
     }
 
+    #[test]
+    fn test_format_result_sets_labels_each_set() {
+        let set =
+            |header: &str, value: &str| vec![vec![header.to_string()], vec![value.to_string()]];
+        let mut results = crate::db::QueryResultsWithInfo {
+            data: set("id", "1"),
+            column_info: None,
+            more_results: Vec::new(),
+            outcome: None,
+            cached_age: None,
+            temporal_columns: std::collections::HashMap::new(),
+            binary_columns: std::collections::HashSet::new(),
+        };
+        let single = format_result_sets(&results, false, false).unwrap();
+        assert!(!single.contains("Result set"));
+
+        results.cached_age = Some(std::time::Duration::from_secs(12));
+        let cached = format_result_sets(&results, false, false).unwrap();
+        assert!(cached.ends_with("(1 row)\n(cached 12s ago)\n"));
+        results.cached_age = None;
+
+        results.more_results = vec![set("today", "2026-10-16"), Vec::new()];
+        let output = format_result_sets(&results, false, false).unwrap();
+        assert!(output.starts_with("Result set 1 of 3\n"));
+        assert!(output.contains("Result set 2 of 3\ntoday"));
+        assert!(output.ends_with("Result set 3 of 3\n(no rows)\n"));
+
+        results.data.clear();
+        results.more_results.clear();
+        assert_eq!(format_result_sets(&results, false, false), None);
+    }
+
     #[test]
     fn test_normalize_connection_target_infers_file_formats() {
         // Relative paths resolve to an absolute path joined against cwd, so the
@@ -3651,6 +4771,62 @@ mod tests {
     }
 }
 
+/// Where non-interactive (-c) output goes: the terminal, or buffers that a
+/// `dbcrust daemon` sends back to its client.
+#[derive(Debug)]
+pub(crate) enum CommandModeOutput {
+    Terminal,
+    Captured { stdout: String, stderr: String },
+}
+
+impl CommandModeOutput {
+    pub(crate) fn captured() -> Self {
+        CommandModeOutput::Captured {
+            stdout: String::new(),
+            stderr: String::new(),
+        }
+    }
+
+    /// The buffered (stdout, stderr) text; empty for `Terminal`.
+    pub(crate) fn into_captured(self) -> (String, String) {
+        match self {
+            CommandModeOutput::Terminal => (String::new(), String::new()),
+            CommandModeOutput::Captured { stdout, stderr } => (stdout, stderr),
+        }
+    }
+
+    /// Query results — paged when writing to the terminal.
+    fn page(&mut self, text: &str, config: &DbCrustConfig) -> Result<(), CliError> {
+        match self {
+            CommandModeOutput::Terminal => CliCore::page_or_print(text, config),
+            CommandModeOutput::Captured { stdout, .. } => {
+                stdout.push_str(text);
+                Ok(())
+            }
+        }
+    }
+
+    fn println(&mut self, line: &str) {
+        match self {
+            CommandModeOutput::Terminal => println!("{line}"),
+            CommandModeOutput::Captured { stdout, .. } => {
+                stdout.push_str(line);
+                stdout.push('\n');
+            }
+        }
+    }
+
+    fn eprintln(&mut self, line: &str) {
+        match self {
+            CommandModeOutput::Terminal => eprintln!("{line}"),
+            CommandModeOutput::Captured { stderr, .. } => {
+                stderr.push_str(line);
+                stderr.push('\n');
+            }
+        }
+    }
+}
+
 /// Outcome of a single command in non-interactive (-c) mode.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum CommandModeOutcome {
@@ -0,0 +1,224 @@
+//! Client certificates for mutual TLS: expiry warnings and rotation
+//!
+//! Connecting with a client certificate that expires within
+//! `[tls] cert_expiry_warn_days` prints a warning. Short-lived certificates
+//! (SPIFFE, Vault PKI, cert-manager) are rewritten in place before they
+//! expire; a connection keeps the certificate it was opened with, so when
+//! the certificate or key file changes on disk the connection is replaced
+//! before the next statement, and `\ssl reload` replaces it on demand.
+
+use crate::db::Database;
+use crate::ssl_report::CertificateSummary;
+use crate::tls::TlsSettings;
+use chrono::NaiveDateTime;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// The first certificate of the PEM file at `path`
+pub(crate) fn read(path: &PathBuf) -> Result<CertificateSummary, String> {
+    let certs = crate::tls::load_certs(path)?;
+    crate::ssl_report::summarize(&certs[0])
+        .ok_or_else(|| format!("Cannot parse the certificate in {}", path.display()))
+}
+
+/// Warning for a certificate that expired or expires within `warn_days`
+/// of `now`; `None` when it is valid for longer or `warn_days` is 0
+pub(crate) fn expiry_warning(
+    path: &Path,
+    summary: &CertificateSummary,
+    warn_days: u32,
+    now: NaiveDateTime,
+) -> Option<String> {
+    let not_after = summary.not_after?;
+    if warn_days == 0 {
+        return None;
+    }
+    let date = not_after.format("%Y-%m-%d %H:%M:%S UTC");
+    let path = path.display();
+    let remaining = not_after - now;
+    if remaining.num_seconds() < 0 {
+        return Some(format!(
+            "Warning: the client certificate {path} expired on {date}."
+        ));
+    }
+    match remaining.num_days() {
+        0 => Some(format!(
+            "Warning: the client certificate {path} expires today ({date})."
+        )),
+        days if days < warn_days as i64 => Some(format!(
+            "Warning: the client certificate {path} expires in {days} day{} ({date}).",
+            if days == 1 { "" } else { "s" }
+        )),
+        _ => None,
+    }
+}
+
+/// Expiry warning for the client certificate of `database`'s connection
+pub fn check_expiry(database: &Database, warn_days: u32) -> Option<String> {
+    let info = database.get_connection_info()?;
+    let path = TlsSettings::for_connection(info).ok()?.client_cert?;
+    let summary = read(&path).ok()?;
+    expiry_warning(&path, &summary, warn_days, chrono::Utc::now().naive_utc())
+}
+
+/// Size and modification time of each client certificate and key file,
+/// as they were when the connection was opened
+#[derive(Debug, Default)]
+pub struct CertWatch {
+    files: Vec<(PathBuf, Option<(SystemTime, u64)>)>,
+    /// A change seen while a transaction was open, not reloaded yet
+    deferred: bool,
+}
+
+fn stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    // Follows symlinks, so swapping the link Kubernetes mounts secrets
+    // through counts as a change
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+impl CertWatch {
+    /// Watch the client certificate and key of `database`'s connection;
+    /// nothing is watched without a client certificate
+    pub fn for_connection(database: &Database) -> Self {
+        let Some(tls) = database
+            .get_connection_info()
+            .and_then(|info| TlsSettings::for_connection(info).ok())
+        else {
+            return Self::default();
+        };
+        let files = tls
+            .client_cert
+            .iter()
+            .chain(
+                tls.client_key
+                    .iter()
+                    .filter(|key| Some(*key) != tls.client_cert.as_ref()),
+            )
+            .map(|path| (path.clone(), stamp(path)))
+            .collect();
+        Self {
+            files,
+            deferred: false,
+        }
+    }
+
+    /// Whether a watched file changed since the last call; a file that is
+    /// missing for a moment while it is rewritten does not count
+    fn changed(&mut self) -> bool {
+        let mut changed = false;
+        for (path, seen) in &mut self.files {
+            let now = stamp(path);
+            if now.is_some() && now != *seen {
+                *seen = now;
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+/// Open a new connection, which reads the certificate files again.
+/// Returns what to tell the user.
+pub async fn reload(database: &mut Database, warn_days: u32) -> Result<String, String> {
+    database
+        .reconnect()
+        .await
+        .map_err(|e| format!("Reconnecting with the client certificate failed: {e}"))?;
+    let mut notice = "Reconnected with the client certificate on disk.".to_string();
+    if let Some(warning) = check_expiry(database, warn_days) {
+        notice.push('\n');
+        notice.push_str(&warning);
+    }
+    Ok(notice)
+}
+
+/// Replace the connection when its client certificate or key was rotated
+/// on disk since it was opened. An open transaction would be lost, so the
+/// connection is only replaced once it ends. Returns what to tell the user.
+pub async fn before_statement(database: &mut Database, warn_days: u32) -> Option<String> {
+    let changed = database.client_cert_watch_mut().changed();
+    if database.in_transaction() {
+        if !changed {
+            return None;
+        }
+        database.client_cert_watch_mut().deferred = true;
+        return Some(
+            "Client certificate changed on disk. The connection is replaced once the open transaction ends."
+                .to_string(),
+        );
+    }
+    if !changed && !std::mem::take(&mut database.client_cert_watch_mut().deferred) {
+        return None;
+    }
+    Some(match reload(database, warn_days).await {
+        Ok(notice) => format!("Client certificate changed on disk. {notice}"),
+        Err(e) => format!("Client certificate changed on disk. {e}"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expiring(not_after: &str) -> CertificateSummary {
+        CertificateSummary {
+            not_after: Some(NaiveDateTime::parse_from_str(not_after, "%Y-%m-%d %H:%M:%S").unwrap()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_expiry_warning_thresholds() {
+        let now =
+            NaiveDateTime::parse_from_str("2026-03-01 12:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        let path = Path::new("/run/spiffe/svid.pem");
+        let warn =
+            |not_after: &str, days: u32| expiry_warning(path, &expiring(not_after), days, now);
+
+        assert_eq!(warn("2026-04-01 00:00:00", 14), None);
+        assert_eq!(
+            warn("2026-03-05 00:00:00", 14).unwrap(),
+            "Warning: the client certificate /run/spiffe/svid.pem expires in 3 days (2026-03-05 00:00:00 UTC)."
+        );
+        assert!(
+            warn("2026-03-02 13:00:00", 14)
+                .unwrap()
+                .contains("expires in 1 day (")
+        );
+        assert!(
+            warn("2026-03-01 18:00:00", 14)
+                .unwrap()
+                .contains("expires today")
+        );
+        assert!(
+            warn("2026-02-28 00:00:00", 14)
+                .unwrap()
+                .contains("expired on 2026-02-28")
+        );
+        assert_eq!(warn("2026-03-05 00:00:00", 0), None);
+        assert_eq!(
+            expiry_warning(path, &CertificateSummary::default(), 14, now),
+            None
+        );
+    }
+
+    #[test]
+    fn test_cert_watch_sees_rewrites() {
+        let dir = tempfile::tempdir().unwrap();
+        let cert = dir.path().join("client.pem");
+        std::fs::write(&cert, "first").unwrap();
+        let mut watch = CertWatch {
+            files: vec![(cert.clone(), stamp(&cert))],
+            deferred: false,
+        };
+        assert!(!watch.changed());
+
+        std::fs::write(&cert, "second, longer").unwrap();
+        assert!(watch.changed());
+        assert!(!watch.changed());
+
+        std::fs::remove_file(&cert).unwrap();
+        assert!(!watch.changed());
+    }
+}
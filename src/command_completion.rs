@@ -105,6 +105,8 @@ impl CommandCompleter for BasicCommandCompleter {
                 | "\\cs"
                 | "\\clrcs"
                 | "\\resetview"
+                | "\\reloadconfig"
+                | "\\whoami"
                 | "\\vdc"
                 | "\\vs"
                 | "\\du"
@@ -214,6 +216,25 @@ impl DatabaseAwareCompleter {
         let flattened_databases: Vec<String> = databases.into_iter().flatten().collect();
         Ok(flattened_databases)
     }
+
+    #[allow(clippy::await_holding_lock)]
+    async fn get_schema_names(&self) -> CompletionResult<Vec<String>> {
+        if !self.database.lock().unwrap().has_database_connection() {
+            return Ok(Vec::new());
+        }
+
+        let db_arc = Arc::clone(&self.database);
+        let schemas = tokio::task::block_in_place(|| {
+            let handle = tokio::runtime::Handle::current();
+            handle.block_on(async {
+                let mut db_guard = db_arc.lock().unwrap();
+                db_guard.get_schemas().await
+            })
+        })
+        .unwrap_or_else(|_| Vec::new());
+
+        Ok(schemas)
+    }
 }
 
 #[async_trait]
@@ -234,6 +255,15 @@ impl CommandCompleter for DatabaseAwareCompleter {
                     .collect();
                 self.build_suggestions_from_items(items, args, pos, false) // case insensitive
             }
+            "\\knn" | "\\erd" if !args[..pos.min(args.len())].contains(char::is_whitespace) => {
+                // Complete the table name (first argument only)
+                let tables = self.get_table_names().await?;
+                let items: Vec<(String, String)> = tables
+                    .into_iter()
+                    .map(|table| (table, "Table".to_string()))
+                    .collect();
+                self.build_suggestions_from_items(items, args, pos, false) // case insensitive
+            }
             "\\c" => {
                 // Complete database names
                 let databases = self.get_database_names().await?;
@@ -243,6 +273,14 @@ impl CommandCompleter for DatabaseAwareCompleter {
                     .collect();
                 self.build_suggestions_from_items(items, args, pos, false) // case insensitive
             }
+            "\\schema" => {
+                let schemas = self.get_schema_names().await?;
+                let items: Vec<(String, String)> = schemas
+                    .into_iter()
+                    .map(|schema| (schema, "Schema".to_string()))
+                    .collect();
+                self.build_suggestions_from_items(items, args, pos, false) // case insensitive
+            }
             _ => Vec::new(),
         };
 
@@ -250,7 +288,7 @@ impl CommandCompleter for DatabaseAwareCompleter {
     }
 
     fn handles_command(&self, command: &str) -> bool {
-        matches!(command, "\\d" | "\\c")
+        matches!(command, "\\d" | "\\c" | "\\schema" | "\\knn" | "\\erd")
     }
 
     fn name(&self) -> &'static str {
@@ -567,6 +605,10 @@ impl CommandCompleter for ConfigCompleter {
                                 "Print configuration value(s)".to_string(),
                             ),
                             ("set".to_string(), "Set a configuration value".to_string()),
+                            (
+                                "unset".to_string(),
+                                "Reset a configuration value to its default".to_string(),
+                            ),
                             (
                                 "edit".to_string(),
                                 "Open config.toml in $EDITOR".to_string(),
@@ -574,7 +616,7 @@ impl CommandCompleter for ConfigCompleter {
                         ];
                         self.build_suggestions_from_items(items, args, pos, true)
                     }
-                    1 if matches!(words.first(), Some(&"get") | Some(&"set")) => {
+                    1 if matches!(words.first(), Some(&"get") | Some(&"set") | Some(&"unset")) => {
                         // Key paths come from the config_editor schema — never hardcode.
                         let mut items: Vec<(String, String)> = crate::config_editor::schema()
                             .iter()
@@ -10,17 +10,30 @@ use crate::database::{DatabaseType, DatabaseTypeExt};
 use crate::db::Database;
 use crate::history_manager::SessionId;
 use crate::prompt::DbPrompt;
-use std::error::Error as StdError;
 use std::sync::atomic::AtomicBool;
 use std::sync::{Arc, Mutex};
 use strum::{Display, EnumIter, IntoEnumIterator};
 use thiserror::Error;
 
+/// Rows `\\transpose` turns into columns when no count is given
+const DEFAULT_TRANSPOSE_ROWS: usize = 10;
+
+/// Backends `\\dbsize` and `\\tablesize` report on
+const SIZE_REPORT_DATABASES: &[DatabaseType] = &[
+    DatabaseType::PostgreSQL,
+    DatabaseType::MySQL,
+    DatabaseType::SQLite,
+    DatabaseType::ClickHouse,
+];
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Command {
     // Core commands
     Quit,
-    Help,
+    Help {
+        /// Backslash command or SQL keyword; `None` lists every command
+        topic: Option<String>,
+    },
 
     // Database navigation
     ListDatabases,
@@ -31,11 +44,97 @@ pub enum Command {
     ConnectDatabase {
         database_name: String,
     },
+    /// `\\schema [name]`: show or switch the schema unqualified names resolve to
+    Schema {
+        name: Option<String>,
+    },
+    ListSchemas,
+    /// `\\warehouse [name]`: show or switch the warehouse statements run on
+    Warehouse {
+        name: Option<String>,
+    },
+    /// `\\dbsize`: size of each database
+    DbSize,
+    /// `\\tablesize [n] [--sort ...]`: largest tables with data and index sizes
+    TableSize {
+        limit: usize,
+        sort: crate::database::SizeSort,
+    },
+    Whoami,
+    SetRole {
+        role: String,
+    },
+    ResetRole,
+    ShowSsl,
+    ReloadSsl,
+    Stats {
+        reset: bool,
+    },
+    // Result cache (\cache)
+    ShowResultCache,
+    SetResultCache {
+        enabled: bool,
+    },
+    ClearResultCache,
+    // Statements queued while the connection is down (\\queue)
+    ShowQueue,
+    ClearQueue,
+    RunQueue,
+    /// `\\refreshschema`: re-read the completion metadata saved for this connection
+    RefreshSchema,
+    // Query tags (\\tag)
+    ShowQueryTags,
+    SetQueryTags {
+        /// (key, value) pairs; an empty value removes the tag
+        tags: Vec<(String, String)>,
+    },
+    ClearQueryTags,
 
     // Display options
     ToggleExpandedDisplay,
+    /// `\\transpose [rows]`: the last result with columns as rows
+    Transpose {
+        max_rows: usize,
+    },
+    /// `\\pivot [row_key column_key [value [aggregate]]]`: the last result as a cross-tab
+    Pivot {
+        spec: crate::pivot::PivotSpec,
+    },
+    /// `\\dedup [columns...]`: the last result without duplicate rows
+    Dedup {
+        columns: Vec<String>,
+    },
+    // Saved results (\\snapshot)
+    ListSnapshots,
+    SaveSnapshot {
+        name: String,
+    },
+    /// `\\snapshot diff <name> [key columns...]`: the saved query run again,
+    /// compared with the saved result
+    DiffSnapshot {
+        name: String,
+        key: Vec<String>,
+    },
+    DeleteSnapshot {
+        name: String,
+    },
+    // Result masking (\\mask)
+    ShowMasking,
+    SetMasking {
+        enabled: bool,
+    },
     ToggleExplainMode,
     ToggleExplainTuiMode,
+    /// `\ev export`: the captured plan as folded stacks, DOT or JSON; the
+    /// format follows the file extension unless given
+    ExportExplainPlan {
+        filename: String,
+        format: Option<crate::explain_tui::PlanExportFormat>,
+    },
+    /// `\ev upload`: share the captured plan, after confirmation
+    UploadExplainPlan {
+        service: crate::explain_tui::PlanService,
+    },
     ShowConfig,
 
     // Configuration management (\config)
@@ -47,7 +146,33 @@ pub enum Command {
         key: String,
         value: String,
     },
+    ConfigUnset {
+        key: String,
+    },
     ConfigEdit,
+    ReloadConfig,
+    // REPL snippets (\snippets)
+    ListSnippets,
+    AddSnippet {
+        trigger: String,
+        expansion: String,
+    },
+    RemoveSnippet {
+        trigger: String,
+    },
+    // REPL key bindings ([keybindings], \keys)
+    ShowKeys,
+    // Per-table display preferences (\prefs)
+    ShowTablePrefs,
+    ClearTablePrefs {
+        /// `None` for the last queried table, `Some("all")` for everything
+        table: Option<String>,
+    },
+    SetTablePrefsSort {
+        /// `None` removes the sort
+        column: Option<String>,
+        descending: bool,
+    },
 
     // Script handling
     WriteScript {
@@ -58,6 +183,12 @@ pub enum Command {
     },
     EditMultiline,
     CopyExplainPlan,
+    Paste,
+    Watch {
+        interval: std::time::Duration,
+    },
+    /// `\g`: run the previous query or command again (REPL only)
+    Rerun,
 
     // Named queries
     ListNamedQueries,
@@ -82,6 +213,7 @@ pub enum Command {
     SaveSession {
         name: String,
         password_command: Option<String>,
+        group: Option<String>,
     },
     DeleteSession {
         name: String,
@@ -106,12 +238,25 @@ pub enum Command {
     TogglePager,
     ToggleBanner,
     ToggleServerInfo,
+    SetDisplayTimezone {
+        zone: String,
+    },
+    SetTableStyle {
+        style: String,
+    },
     ToggleAutocomplete,
     ToggleColumnSelection,
     SetColumnSelectionThreshold {
         threshold: usize,
     },
     ClearColumnViews,
+    SaveColumnView {
+        name: String,
+    },
+    ListColumnViews,
+    DeleteColumnView {
+        name: String,
+    },
     ResetView,
 
     // Vector display configuration commands
@@ -120,6 +265,41 @@ pub enum Command {
     },
     ShowVectorDisplayConfig,
     ToggleVectorStatistics,
+    VectorKnn {
+        table: String,
+        column: String,
+        target: crate::vector_search::KnnTarget,
+        k: usize,
+        metric: crate::vector_search::DistanceMetric,
+    },
+    ClickHouseInsert {
+        table: String,
+        file: String,
+        format: Option<String>,
+    },
+    Replication,
+    /// `\\cluster [name]`: ClickHouse cluster topology from system.clusters
+    Cluster {
+        name: Option<String>,
+    },
+    MySqlProcessList,
+    MySqlLocks,
+    MySqlSlowQueries {
+        limit: usize,
+    },
+    PgActivity,
+    PgBlocking,
+    PgBloat {
+        limit: usize,
+    },
+    SqliteBackup {
+        file: String,
+        force: bool,
+    },
+    SqliteVacuum,
+    SqliteIntegrityCheck {
+        quick: bool,
+    },
 
     // Vault credential caching commands
     VaultCacheStatus,
@@ -152,6 +332,12 @@ pub enum Command {
     ListUsers,
     ListIndexes,
     ListPragmas,
+    /// `\sf[+] <function> [--edit]`: PostgreSQL function source
+    ShowFunction {
+        name: String,
+        numbered: bool,
+        edit: bool,
+    },
     ShowPgpass,
     ShowMyconf,
     ListDockerContainers,
@@ -198,6 +384,76 @@ pub enum Command {
         query: String,
         filename: String,
     },
+    Export {
+        format: crate::export::ExportFormat,
+        filename: String,
+        query: String,
+        /// Continue a chunked table export from its checkpoint
+        resume: bool,
+        /// Rows per chunk of a whole-table export
+        chunk_rows: usize,
+    },
+    SaveBlob {
+        row: usize,
+        column: String,
+        filename: String,
+    },
+    Gexec {
+        query: Option<String>,
+    },
+    Dump {
+        target: String,
+        filename: String,
+    },
+    Restore {
+        filename: String,
+    },
+    FedJoin {
+        name: String,
+        source: String,
+        query: String,
+        local: bool,
+    },
+    FedJoinRun {
+        query: String,
+    },
+    ListFedJoins,
+    ClearFedJoins,
+    ShowScratch,
+    ScratchImport {
+        name: String,
+    },
+    ScratchRun {
+        query: String,
+    },
+    ClearScratch,
+    Lint {
+        query: String,
+    },
+    Format {
+        query: Option<String>,
+    },
+    DryRun {
+        query: String,
+    },
+    /// Exact row count of a table, a query, or the last query when `None`
+    Count {
+        target: Option<String>,
+    },
+    InsertRow {
+        table: String,
+    },
+    /// Insert `count` generated rows; `yes` skips the confirmation
+    GenRows {
+        table: String,
+        count: usize,
+        yes: bool,
+    },
+    /// Insert rows copied from a spreadsheet; `yes` skips the confirmation
+    PasteData {
+        table: String,
+        yes: bool,
+    },
 
     // Connection pool monitoring
     ShowPoolStats,
@@ -207,9 +463,23 @@ pub enum Command {
         mode: Option<String>,
     },
     ComplexDisplayJsonToggle,
+    /// Re-run the last MongoDB/Elasticsearch query with nested fields as
+    /// columns; `None` falls back to the collection's saved spec
+    Flatten {
+        depth: Option<usize>,
+        arrays: Option<crate::flatten::ArrayMode>,
+        save: bool,
+        forget: bool,
+    },
 
     // Schema viewer
     SchemaViewer,
+    SchemaBrowser,
+    EntityRelationshipDiagram {
+        target: Option<String>,
+        format: crate::schema_graph::ErdFormat,
+        output: Option<String>,
+    },
 
     // AI assistant commands
     AiStatus,
@@ -238,7 +508,7 @@ pub enum CommandError {
     #[error("Missing required argument: {0}")]
     MissingArgument(String),
     #[error("Database error: {0}")]
-    DatabaseError(#[from] Box<dyn StdError>),
+    DatabaseError(#[from] crate::error::DbcrustError),
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
     #[error("Unknown command: {0}")]
@@ -298,16 +568,42 @@ pub enum CommandShortcut {
     Dt,
     D,
     C,
+    Schema,
+    Schemas,
+    Warehouse,
+    Dbsize,
+    Tablesize,
+    Whoami,
+    Setrole,
+    Resetrole,
+    Ssl,
+    Stats,
+    Cache,
+    Queue,
+    Refreshschema,
+    Tag,
     // Display options
     X,
+    Transpose,
+    Pivot,
+    Dedup,
+    Snapshot,
+    Mask,
     E,
     Ev,
     Config,
+    Reloadconfig,
+    Snippets,
+    Keys,
+    Prefs,
     // Script handling
     W,
     I,
     Ed,
     Ecopy,
+    Paste,
+    Watch,
+    G,
     // Named queries
     N,
     Ns,
@@ -325,6 +621,7 @@ pub enum CommandShortcut {
     Du,
     Di,
     Dp,
+    Sf,
     Pgpass,
     Myconf,
     Docker,
@@ -332,20 +629,51 @@ pub enum CommandShortcut {
     Er,
     Ef,
     Ex,
+    Export,
+    Saveblob,
+    Gexec,
+    Dump,
+    Restore,
+    Fedjoin,
+    Scratch,
+    Lint,
+    Fmt,
+    Dryrun,
+    Count,
+    Insertrow,
+    Genrows,
+    Pastedata,
     // Advanced commands
     Setmulti,
     Pager,
     Banner,
     ServerInfo,
+    Tz,
+    Pset,
     A,
     Cs,
     Csthreshold,
     Clrcs,
+    Csave,
+    Cviews,
     Resetview,
     // Vector display commands
     Vd,
     Vdc,
     Vs,
+    Knn,
+    Chinsert,
+    Cluster,
+    Replication,
+    Processlist,
+    Locks,
+    Slowqueries,
+    Activity,
+    Blocking,
+    Bloat,
+    Backup,
+    Vacuum,
+    Integritycheck,
     // Connection pool monitoring
     Ps,
     // Vault credential cache commands
@@ -361,8 +689,11 @@ pub enum CommandShortcut {
     // Complex display commands (minimal set)
     Cd,
     Cdj,
+    Flatten,
     // Schema viewer
     Sv,
+    Browse,
+    Erd,
     // AI assistant
     Ai,
 }
@@ -379,16 +710,42 @@ impl CommandShortcut {
             CommandShortcut::Dt => "\\dt",
             CommandShortcut::D => "\\d",
             CommandShortcut::C => "\\c",
+            CommandShortcut::Schema => "\\schema",
+            CommandShortcut::Schemas => "\\schemas",
+            CommandShortcut::Warehouse => "\\warehouse",
+            CommandShortcut::Dbsize => "\\dbsize",
+            CommandShortcut::Tablesize => "\\tablesize",
+            CommandShortcut::Whoami => "\\whoami",
+            CommandShortcut::Setrole => "\\setrole",
+            CommandShortcut::Resetrole => "\\resetrole",
+            CommandShortcut::Ssl => "\\ssl",
+            CommandShortcut::Stats => "\\stats",
+            CommandShortcut::Cache => "\\cache",
+            CommandShortcut::Queue => "\\queue",
+            CommandShortcut::Refreshschema => "\\refreshschema",
+            CommandShortcut::Tag => "\\tag",
             // Display options
             CommandShortcut::X => "\\x",
+            CommandShortcut::Transpose => "\\transpose",
+            CommandShortcut::Pivot => "\\pivot",
+            CommandShortcut::Dedup => "\\dedup",
+            CommandShortcut::Snapshot => "\\snapshot",
+            CommandShortcut::Mask => "\\mask",
             CommandShortcut::E => "\\e",
             CommandShortcut::Ev => "\\ev",
             CommandShortcut::Config => "\\config",
+            CommandShortcut::Reloadconfig => "\\reloadconfig",
+            CommandShortcut::Snippets => "\\snippets",
+            CommandShortcut::Keys => "\\keys",
+            CommandShortcut::Prefs => "\\prefs",
             // Script handling
             CommandShortcut::W => "\\w",
             CommandShortcut::I => "\\i",
             CommandShortcut::Ed => "\\ed",
             CommandShortcut::Ecopy => "\\ecopy",
+            CommandShortcut::Paste => "\\paste",
+            CommandShortcut::Watch => "\\watch",
+            CommandShortcut::G => "\\g",
             // Named queries
             CommandShortcut::N => "\\n",
             CommandShortcut::Ns => "\\ns",
@@ -406,6 +763,7 @@ impl CommandShortcut {
             CommandShortcut::Du => "\\du",
             CommandShortcut::Di => "\\di",
             CommandShortcut::Dp => "\\dp",
+            CommandShortcut::Sf => "\\sf",
             CommandShortcut::Pgpass => "\\pgpass",
             CommandShortcut::Myconf => "\\myconf",
             CommandShortcut::Docker => "\\docker",
@@ -413,20 +771,51 @@ impl CommandShortcut {
             CommandShortcut::Er => "\\er",
             CommandShortcut::Ef => "\\ef",
             CommandShortcut::Ex => "\\ex",
+            CommandShortcut::Export => "\\export",
+            CommandShortcut::Saveblob => "\\saveblob",
+            CommandShortcut::Gexec => "\\gexec",
+            CommandShortcut::Dump => "\\dump",
+            CommandShortcut::Restore => "\\restore",
+            CommandShortcut::Fedjoin => "\\fedjoin",
+            CommandShortcut::Scratch => "\\scratch",
+            CommandShortcut::Lint => "\\lint",
+            CommandShortcut::Fmt => "\\fmt",
+            CommandShortcut::Dryrun => "\\dryrun",
+            CommandShortcut::Count => "\\count",
+            CommandShortcut::Insertrow => "\\insertrow",
+            CommandShortcut::Genrows => "\\genrows",
+            CommandShortcut::Pastedata => "\\pastedata",
             // Advanced commands
             CommandShortcut::Setmulti => "\\setmulti",
             CommandShortcut::Pager => "\\pager",
             CommandShortcut::Banner => "\\banner",
             CommandShortcut::ServerInfo => "\\serverinfo",
+            CommandShortcut::Tz => "\\tz",
+            CommandShortcut::Pset => "\\pset",
             CommandShortcut::A => "\\a",
             CommandShortcut::Cs => "\\cs",
             CommandShortcut::Csthreshold => "\\csthreshold",
             CommandShortcut::Clrcs => "\\clrcs",
+            CommandShortcut::Csave => "\\csave",
+            CommandShortcut::Cviews => "\\cviews",
             CommandShortcut::Resetview => "\\resetview",
             // Vector display commands
             CommandShortcut::Vd => "\\vd",
             CommandShortcut::Vdc => "\\vdc",
             CommandShortcut::Vs => "\\vs",
+            CommandShortcut::Knn => "\\knn",
+            CommandShortcut::Chinsert => "\\chinsert",
+            CommandShortcut::Cluster => "\\cluster",
+            CommandShortcut::Replication => "\\replication",
+            CommandShortcut::Processlist => "\\processlist",
+            CommandShortcut::Locks => "\\locks",
+            CommandShortcut::Slowqueries => "\\slowqueries",
+            CommandShortcut::Activity => "\\activity",
+            CommandShortcut::Blocking => "\\blocking",
+            CommandShortcut::Bloat => "\\bloat",
+            CommandShortcut::Backup => "\\backup",
+            CommandShortcut::Vacuum => "\\vacuum",
+            CommandShortcut::Integritycheck => "\\integritycheck",
             // Connection pool monitoring
             CommandShortcut::Ps => "\\ps",
             // Vault credential cache commands
@@ -442,8 +831,11 @@ impl CommandShortcut {
             // Complex display commands (minimal set)
             CommandShortcut::Cd => "\\cd",
             CommandShortcut::Cdj => "\\cdj",
+            CommandShortcut::Flatten => "\\flatten",
             // Schema viewer
             CommandShortcut::Sv => "\\sv",
+            CommandShortcut::Browse => "\\browse",
+            CommandShortcut::Erd => "\\erd",
             // AI assistant
             CommandShortcut::Ai => "\\ai",
         }
@@ -459,17 +851,53 @@ impl CommandShortcut {
             CommandShortcut::L => "List databases",
             CommandShortcut::Dt => "List tables",
             CommandShortcut::D => "Describe table or list all tables",
-            CommandShortcut::C => "Connect to database",
+            CommandShortcut::C => "Connect to a database, saved session, alias or URL",
+            CommandShortcut::Schema => "Show or switch the active schema",
+            CommandShortcut::Schemas => "List schemas with their table counts",
+            CommandShortcut::Warehouse => "Show or switch the Snowflake warehouse",
+            CommandShortcut::Dbsize => "Show the size of each database",
+            CommandShortcut::Tablesize => "Show the largest tables with their data and index sizes",
+            CommandShortcut::Whoami => "Show connected user, roles and connection security",
+            CommandShortcut::Setrole => "Act as another role for this session (alias \\su)",
+            CommandShortcut::Resetrole => "Go back to the login role after \\setrole",
+            CommandShortcut::Ssl => {
+                "Show negotiated TLS version, cipher and certificates (reload the client certificate)"
+            }
+            CommandShortcut::Stats => "Show session query statistics (reset)",
+            CommandShortcut::Cache => "Serve repeated SELECTs from memory (on|off|clear)",
+            CommandShortcut::Queue => {
+                "Statements typed while the connection was down (list|clear|run)"
+            }
+            CommandShortcut::Refreshschema => {
+                "Re-read the table and column names saved for completion"
+            }
+            CommandShortcut::Tag => "Tag following statements with key=value in a SQL comment",
             // Display options
             CommandShortcut::X => "Toggle expanded display",
+            CommandShortcut::Transpose => "Show the last result with columns as rows",
+            CommandShortcut::Pivot => "Show the last result as a cross-tab of two columns",
+            CommandShortcut::Dedup => "Show the last result without duplicate rows",
+            CommandShortcut::Snapshot => {
+                "Save the last result and diff the same query against it later"
+            }
+            CommandShortcut::Mask => "Mask sensitive values in displayed results (on|off)",
             CommandShortcut::E => "Toggle EXPLAIN mode",
-            CommandShortcut::Ev => "Toggle EXPLAIN TUI visualizer mode",
-            CommandShortcut::Config => "Configuration menu (show|get|set|edit)",
+            CommandShortcut::Ev => {
+                "Toggle EXPLAIN TUI visualizer mode, or export/publish the last plan"
+            }
+            CommandShortcut::Config => "Configuration menu (show|get|set|unset|edit)",
+            CommandShortcut::Reloadconfig => "Reload config.toml and report changes",
+            CommandShortcut::Snippets => "List or add Tab-expanded snippets (add|remove)",
+            CommandShortcut::Keys => "List the REPL's key bindings ([keybindings])",
+            CommandShortcut::Prefs => "Remembered per-table display settings (show|clear|sort)",
             // Script handling
             CommandShortcut::W => "Write script to file",
             CommandShortcut::I => "Load script from file",
             CommandShortcut::Ed => "Edit multiline script",
             CommandShortcut::Ecopy => "Copy EXPLAIN plan to clipboard",
+            CommandShortcut::Paste => "Format SQL from the clipboard into the edit buffer",
+            CommandShortcut::Watch => "Re-run the previous query or command every n seconds",
+            CommandShortcut::G => "Run the previous query or command again",
             // Named queries
             CommandShortcut::N => "List or execute named queries",
             CommandShortcut::Ns => "Save named query",
@@ -487,6 +915,7 @@ impl CommandShortcut {
             CommandShortcut::Du => "List users",
             CommandShortcut::Di => "List indexes",
             CommandShortcut::Dp => "List pragmas",
+            CommandShortcut::Sf => "Show (or edit) a PostgreSQL function's source",
             CommandShortcut::Pgpass => "Show .pgpass info",
             CommandShortcut::Myconf => "Show .my.cnf info",
             CommandShortcut::Docker => "List Docker containers",
@@ -494,20 +923,57 @@ impl CommandShortcut {
             CommandShortcut::Er => "Run EXPLAIN query in raw format",
             CommandShortcut::Ef => "Run EXPLAIN query in formatted output",
             CommandShortcut::Ex => "Export EXPLAIN result to file",
+            CommandShortcut::Export => "Stream query rows to a file (NDJSON)",
+            CommandShortcut::Saveblob => "Write a binary value of the last result to a file",
+            CommandShortcut::Gexec => "Run each value of a query result as a statement",
+            CommandShortcut::Dump => "Dump a table or the database as portable SQL",
+            CommandShortcut::Restore => "Run a SQL dump in one transaction, with progress",
+            CommandShortcut::Fedjoin => {
+                "Load a query result from another connection to join against"
+            }
+            CommandShortcut::Scratch => "Slice results in a local in-memory SQLite database",
+            CommandShortcut::Lint => "Check a query for anti-patterns without running it",
+            CommandShortcut::Fmt => "Pretty-print the last or given SQL",
+            CommandShortcut::Dryrun => "Preview the rows an UPDATE/DELETE would change",
+            CommandShortcut::Count => "Count the rows of the last query or a table",
+            CommandShortcut::Insertrow => "Build and run an INSERT by prompting for each column",
+            CommandShortcut::Genrows => {
+                "Insert fake rows that fit the table's types and foreign keys"
+            }
+            CommandShortcut::Pastedata => "Insert rows copied from a spreadsheet (TSV/CSV)",
             // Advanced commands
             CommandShortcut::Setmulti => "Set multiline prompt indicator",
             CommandShortcut::Pager => "Toggle pager for long output",
             CommandShortcut::Banner => "Toggle banner display",
             CommandShortcut::ServerInfo => "Toggle server info display",
+            CommandShortcut::Tz => "Show or set the display time zone",
+            CommandShortcut::Pset => "Show or set the table border style",
             CommandShortcut::A => "Toggle autocomplete",
             CommandShortcut::Cs => "Toggle column selection",
             CommandShortcut::Csthreshold => "Set column selection threshold",
             CommandShortcut::Clrcs => "Clear column views",
+            CommandShortcut::Csave => "Save column selection as a named view",
+            CommandShortcut::Cviews => "List or delete saved column views",
             CommandShortcut::Resetview => "Reset view",
             // Vector display commands
             CommandShortcut::Vd => "Set vector display mode",
             CommandShortcut::Vdc => "Show vector display config",
             CommandShortcut::Vs => "Toggle vector statistics",
+            CommandShortcut::Knn => "Nearest-neighbour search on a pgvector column",
+            CommandShortcut::Chinsert => "Bulk insert a CSV/TSV/JSONEachRow file into ClickHouse",
+            CommandShortcut::Cluster => "Show ClickHouse clusters with their shards and replicas",
+            CommandShortcut::Replication => {
+                "Show PostgreSQL replication slots, lag and subscriptions"
+            }
+            CommandShortcut::Processlist => "Show current MySQL sessions",
+            CommandShortcut::Locks => "Show MySQL lock waits and blocking chains",
+            CommandShortcut::Slowqueries => "Show the top MySQL statements by total latency",
+            CommandShortcut::Activity => "Show current PostgreSQL sessions",
+            CommandShortcut::Blocking => "Show PostgreSQL lock waits and blocking chains",
+            CommandShortcut::Bloat => "Show PostgreSQL tables with the most estimated bloat",
+            CommandShortcut::Backup => "Copy the open SQLite database to a file (online backup)",
+            CommandShortcut::Vacuum => "Rebuild the SQLite database file and truncate its WAL",
+            CommandShortcut::Integritycheck => "Run PRAGMA integrity_check on SQLite",
             // Connection pool monitoring
             CommandShortcut::Ps => "Show connection pool statistics",
             // Vault credential cache commands
@@ -523,8 +989,11 @@ impl CommandShortcut {
             // Complex display commands (minimal set)
             CommandShortcut::Cd => "Set complex data display mode",
             CommandShortcut::Cdj => "Toggle JSON pretty printing",
+            CommandShortcut::Flatten => "Show nested document fields of the last query as columns",
             // Schema viewer
             CommandShortcut::Sv => "Interactive schema viewer (TUI)",
+            CommandShortcut::Browse => "Browse schemas with sample rows; Enter inserts a name",
+            CommandShortcut::Erd => "Foreign key diagram (or DOT/Mermaid export)",
             // AI assistant
             CommandShortcut::Ai => {
                 "AI assistant (setup|status|provider|model|login|logout|toggle|clear)"
@@ -532,38 +1001,287 @@ impl CommandShortcut {
         }
     }
 
+    /// Full usage line, for `\\h <command>`
+    pub fn usage(&self) -> &'static str {
+        match self {
+            CommandShortcut::H => "\\h [command|sql-keyword]",
+            CommandShortcut::D => "\\d [table_name]",
+            CommandShortcut::C => "\\c <database|session|alias|URL>",
+            CommandShortcut::Schema => "\\schema [name]",
+            CommandShortcut::Warehouse => "\\warehouse [name]",
+            CommandShortcut::Tablesize => "\\tablesize [n] [--sort total|table|index|rows]",
+            CommandShortcut::Transpose => "\\transpose [rows]",
+            CommandShortcut::Pivot => {
+                "\\pivot [<row_key> <column_key> [<value> [sum|count|min|max|avg]]]"
+            }
+            CommandShortcut::Dedup => "\\dedup [columns...]",
+            CommandShortcut::Snapshot => {
+                "\\snapshot [list|save <name>|diff <name> [key columns...]|delete <name>]"
+            }
+            CommandShortcut::Mask => "\\mask [on|off]",
+            CommandShortcut::Setrole => "\\setrole <role>",
+            CommandShortcut::Ssl => "\\ssl [reload]",
+            CommandShortcut::Stats => "\\stats [reset]",
+            CommandShortcut::Cache => "\\cache [on|off|clear]",
+            CommandShortcut::Queue => "\\queue [list|clear|run]",
+            CommandShortcut::Tag => "\\tag [key=value ...|clear]",
+            CommandShortcut::Config => "\\config [show|edit|get [key]|set <key> <value>]",
+            CommandShortcut::Snippets => "\\snippets [add <trigger> <expansion>|remove <trigger>]",
+            CommandShortcut::Prefs => "\\prefs [show|clear [<table>|all]|sort [<column> [desc]]]",
+            CommandShortcut::W => "\\w <filename>",
+            CommandShortcut::I => "\\i <filename>",
+            CommandShortcut::Watch => "\\watch [seconds]",
+            CommandShortcut::N => "\\n [<name> [args...]]",
+            CommandShortcut::Ns => {
+                "\\ns [-g|--global|--postgres|--mysql|--sqlite] <name> <query> [--scope]"
+            }
+            CommandShortcut::Nd => "\\nd <name>",
+            CommandShortcut::S => "\\s [name]",
+            CommandShortcut::Ss => "\\ss <name> [--group <group>] [--password-command <command>]",
+            CommandShortcut::Sd => "\\sd <name>",
+            CommandShortcut::Hc => "\\hc [session_hash]",
+            CommandShortcut::Er => "\\er <query>",
+            CommandShortcut::Ef => "\\ef <query>",
+            CommandShortcut::Ev => "\\ev [export <file> [folded|dot|json]|upload [dalibo|depesz]]",
+            CommandShortcut::Ex => "\\ex <query> <filename>",
+            CommandShortcut::Export => {
+                "\\export ndjson [--resume] [--chunk <rows>] <file|-> <query|table>"
+            }
+            CommandShortcut::Saveblob => "\\saveblob <row> <column> <file>",
+            CommandShortcut::Gexec => "\\gexec [query]",
+            CommandShortcut::Dump => "\\dump <table|database> <file>",
+            CommandShortcut::Restore => "\\restore <file>",
+            CommandShortcut::Fedjoin => "\\fedjoin [--local] <name> <url|session|.> <query>",
+            CommandShortcut::Scratch => "\\scratch [import <name> | clear | <query>]",
+            CommandShortcut::Lint => "\\lint <query>",
+            CommandShortcut::Fmt => "\\fmt [query]",
+            CommandShortcut::Dryrun => "\\dryrun <statement>",
+            CommandShortcut::Count => "\\count [table|query]",
+            CommandShortcut::Sf => "\\sf[+] <function>[(argument types)] [--edit]",
+            CommandShortcut::Insertrow => "\\insertrow <table>",
+            CommandShortcut::Genrows => "\\genrows <table> <count> [--yes]",
+            CommandShortcut::Pastedata => "\\pastedata <table> [--yes]",
+            CommandShortcut::Setmulti => "\\setmulti <indicator>",
+            CommandShortcut::Tz => "\\tz [server|local|utc|<zone>]",
+            CommandShortcut::Pset => "\\pset border [style]",
+            CommandShortcut::Csthreshold => "\\csthreshold <number>",
+            CommandShortcut::Csave => "\\csave <name>",
+            CommandShortcut::Cviews => "\\cviews [delete <name>]",
+            CommandShortcut::Vd => "\\vd <mode>",
+            CommandShortcut::Knn => {
+                "\\knn <table> <vector_column> <[vector]|key=value> [k] [--metric l2|cosine|ip|l1]"
+            }
+            CommandShortcut::Chinsert => "\\chinsert <table> <file> [format]",
+            CommandShortcut::Cluster => "\\cluster [name]",
+            CommandShortcut::Slowqueries => "\\slowqueries [n]",
+            CommandShortcut::Bloat => "\\bloat [n]",
+            CommandShortcut::Backup => "\\backup <file> [--force]",
+            CommandShortcut::Integritycheck => "\\integritycheck [quick]",
+            CommandShortcut::Vcr => "\\vcr [role]",
+            CommandShortcut::Cd => "\\cd [mode]",
+            CommandShortcut::Flatten => "\\flatten [depth] [first|join|explode] [save|forget]",
+            CommandShortcut::Erd => {
+                "\\erd [schema|table] [--ascii] [--dot <file>|--mermaid <file>]"
+            }
+            CommandShortcut::Ai => {
+                "\\ai [status|setup|provider [name]|model [name]|on|off|clear|login|logout]"
+            }
+            // Everything else takes no arguments
+            _ => self.command(),
+        }
+    }
+
+    /// Example invocations, for `\\h <command>`
+    pub fn examples(&self) -> &'static [&'static str] {
+        match self {
+            CommandShortcut::H => &["\\h \\d", "\\h insert", "\\h create table"],
+            CommandShortcut::D => &["\\d", "\\d users", "\\d public.orders"],
+            CommandShortcut::C => &[
+                "\\c analytics",
+                "\\c prod-replica",
+                "\\c postgres://app@replica.internal/app",
+            ],
+            CommandShortcut::Schema => &["\\schema billing"],
+            CommandShortcut::Warehouse => &["\\warehouse", "\\warehouse reporting_wh"],
+            CommandShortcut::Tablesize => &["\\tablesize", "\\tablesize 5 --sort index"],
+            CommandShortcut::Transpose => &["\\transpose", "\\transpose 3"],
+            CommandShortcut::Pivot => &["\\pivot", "\\pivot day status total sum"],
+            CommandShortcut::Dedup => &["\\dedup", "\\dedup email", "\\dedup customer_id, day"],
+            CommandShortcut::Snapshot => &[
+                "\\snapshot save orders-before",
+                "\\snapshot diff orders-before",
+                "\\snapshot diff orders-before order_id",
+            ],
+            CommandShortcut::Setrole => &["\\setrole tenant_reader", "\\su app_user"],
+            CommandShortcut::Ssl => &["\\ssl", "\\ssl reload"],
+            CommandShortcut::Config => &[
+                "\\config get pager_enabled",
+                "\\config set default_limit 500",
+                "\\config unset default_limit",
+            ],
+            CommandShortcut::Snippets => &["\\snippets add sel SELECT * FROM"],
+            CommandShortcut::Prefs => &["\\prefs sort created_at desc", "\\prefs clear users"],
+            CommandShortcut::W => &["\\w report.sql"],
+            CommandShortcut::I => &["\\i migrations/001.sql"],
+            CommandShortcut::Watch => &["\\watch 5"],
+            CommandShortcut::N => &["\\n", "\\n active_users 30"],
+            CommandShortcut::Ns => &[
+                "\\ns active_users SELECT * FROM users WHERE last_seen > now() - interval '$1 days'",
+                "\\ns --global tables SELECT count(*) FROM information_schema.tables",
+            ],
+            CommandShortcut::S => &["\\s", "\\s prod"],
+            CommandShortcut::Ss => &[
+                "\\ss prod",
+                "\\ss eu-replica --group staging",
+                "\\ss prod --password-command \"op read op://db/prod/password\"",
+            ],
+            CommandShortcut::Ex => &["\\ex SELECT * FROM orders WHERE total > 100 plan.json"],
+            CommandShortcut::Export => &[
+                "\\export ndjson orders.ndjson SELECT * FROM orders",
+                "\\export ndjson - SELECT * FROM events | jq .",
+                "\\export ndjson events.ndjson events",
+                "\\export ndjson --resume --chunk 10000 events.ndjson events",
+            ],
+            CommandShortcut::Saveblob => &[
+                "\\saveblob 1 avatar avatar.png",
+                "\\saveblob 3 2 attachment.pdf",
+            ],
+            CommandShortcut::Gexec => &[
+                "\\gexec",
+                "\\gexec SELECT format('GRANT SELECT ON %I TO reporting', tablename) FROM pg_tables WHERE schemaname = 'public'",
+            ],
+            CommandShortcut::Dump => &["\\dump users users.sql", "\\dump app app-backup.sql"],
+            CommandShortcut::Restore => &["\\restore app-backup.sql"],
+            CommandShortcut::Fedjoin => &[
+                "\\fedjoin crm_accounts session://crm SELECT id, plan FROM accounts",
+                "\\fedjoin --local billing mysql://ro@billing/db SELECT account_id, total FROM invoices",
+                "\\fedjoin run SELECT * FROM billing b LEFT JOIN app a USING (account_id)",
+            ],
+            CommandShortcut::Scratch => &[
+                "\\scratch import orders",
+                "\\scratch SELECT status, count(*) FROM orders GROUP BY status",
+            ],
+            CommandShortcut::Lint => &["\\lint DELETE FROM orders"],
+            CommandShortcut::Fmt => &[
+                "\\fmt",
+                "\\fmt select id, name from users where active order by name",
+            ],
+            CommandShortcut::Dryrun => {
+                &["\\dryrun UPDATE orders SET status = 'void' WHERE id < 100"]
+            }
+            CommandShortcut::Count => &["\\count", "\\count public.orders"],
+            CommandShortcut::Insertrow => &["\\insertrow customers"],
+            CommandShortcut::Genrows => &["\\genrows customers 1000", "\\genrows orders 50 --yes"],
+            CommandShortcut::Pastedata => &["\\pastedata public.products"],
+            CommandShortcut::Tz => &["\\tz", "\\tz Europe/Paris", "\\tz utc"],
+            CommandShortcut::Pset => &["\\pset border", "\\pset border rounded"],
+            CommandShortcut::Csave => &["\\csave billing"],
+            CommandShortcut::Knn => &[
+                "\\knn items embedding [0.1,0.2,0.3] 5",
+                "\\knn items embedding id=42 10 --metric cosine",
+            ],
+            CommandShortcut::Chinsert => &["\\chinsert events events.csv CSVWithNames"],
+            CommandShortcut::Cluster => &["\\cluster", "\\cluster analytics"],
+            CommandShortcut::Slowqueries => &["\\slowqueries 20"],
+            CommandShortcut::Bloat => &["\\bloat 10"],
+            CommandShortcut::Backup => &["\\backup /tmp/app-copy.db"],
+            CommandShortcut::Erd => &["\\erd", "\\erd orders", "\\erd public --mermaid schema.md"],
+            CommandShortcut::Ai => &["\\ai setup", "\\ai model"],
+            CommandShortcut::Flatten => {
+                &["\\flatten", "\\flatten 2 join", "\\flatten explode save"]
+            }
+            CommandShortcut::Sf => &[
+                "\\sf refresh_totals",
+                "\\sf+ billing.total_for(integer)",
+                "\\sf refresh_totals --edit",
+            ],
+            CommandShortcut::Ev => &[
+                "\\ev",
+                "\\ev export plan.folded",
+                "\\ev export plan.dot",
+                "\\ev upload dalibo",
+            ],
+            _ => &[],
+        }
+    }
+
     /// Get the command category
     pub fn category(&self) -> CommandCategory {
         match self {
             // Core commands
             CommandShortcut::Q | CommandShortcut::H => CommandCategory::Core,
             // Database navigation
-            CommandShortcut::L | CommandShortcut::Dt | CommandShortcut::D | CommandShortcut::C => {
-                CommandCategory::DatabaseNavigation
-            }
+            CommandShortcut::L
+            | CommandShortcut::Dt
+            | CommandShortcut::D
+            | CommandShortcut::C
+            | CommandShortcut::Schema
+            | CommandShortcut::Schemas
+            | CommandShortcut::Warehouse
+            | CommandShortcut::Dbsize
+            | CommandShortcut::Tablesize
+            | CommandShortcut::Whoami
+            | CommandShortcut::Setrole
+            | CommandShortcut::Resetrole
+            | CommandShortcut::Ssl
+            | CommandShortcut::Stats
+            | CommandShortcut::Cache
+            | CommandShortcut::Queue
+            | CommandShortcut::Refreshschema
+            | CommandShortcut::Tag => CommandCategory::DatabaseNavigation,
             // Display options (including some advanced display commands)
             CommandShortcut::X
+            | CommandShortcut::Transpose
+            | CommandShortcut::Pivot
+            | CommandShortcut::Dedup
+            | CommandShortcut::Snapshot
+            | CommandShortcut::Mask
             | CommandShortcut::E
             | CommandShortcut::Ev
             | CommandShortcut::Config
+            | CommandShortcut::Reloadconfig
+            | CommandShortcut::Snippets
+            | CommandShortcut::Keys
+            | CommandShortcut::Prefs
             | CommandShortcut::Setmulti
             | CommandShortcut::Pager
             | CommandShortcut::Banner
             | CommandShortcut::ServerInfo
+            | CommandShortcut::Tz
+            | CommandShortcut::Pset
             | CommandShortcut::A
             | CommandShortcut::Cs
             | CommandShortcut::Csthreshold
             | CommandShortcut::Clrcs
+            | CommandShortcut::Csave
+            | CommandShortcut::Cviews
             | CommandShortcut::Resetview => CommandCategory::DisplayOptions,
             // Vector display commands
             CommandShortcut::Vd | CommandShortcut::Vdc | CommandShortcut::Vs => {
                 CommandCategory::DisplayOptions
             }
+            // Vector search
+            CommandShortcut::Knn
+            | CommandShortcut::Chinsert
+            | CommandShortcut::Cluster
+            | CommandShortcut::Replication
+            | CommandShortcut::Processlist
+            | CommandShortcut::Locks
+            | CommandShortcut::Slowqueries
+            | CommandShortcut::Activity
+            | CommandShortcut::Blocking
+            | CommandShortcut::Bloat
+            | CommandShortcut::Backup
+            | CommandShortcut::Vacuum
+            | CommandShortcut::Integritycheck => CommandCategory::DatabaseSpecific,
             // Script handling
             CommandShortcut::W
             | CommandShortcut::I
             | CommandShortcut::Ed
-            | CommandShortcut::Ecopy => CommandCategory::ScriptHandling,
+            | CommandShortcut::Ecopy
+            | CommandShortcut::Paste
+            | CommandShortcut::Watch
+            | CommandShortcut::G => CommandCategory::ScriptHandling,
             // Named queries
             CommandShortcut::N | CommandShortcut::Ns | CommandShortcut::Nd => {
                 CommandCategory::NamedQueries
@@ -580,6 +1298,7 @@ impl CommandShortcut {
             CommandShortcut::Du
             | CommandShortcut::Di
             | CommandShortcut::Dp
+            | CommandShortcut::Sf
             | CommandShortcut::Pgpass
             | CommandShortcut::Myconf
             | CommandShortcut::Docker => CommandCategory::DatabaseSpecific,
@@ -597,11 +1316,29 @@ impl CommandShortcut {
             CommandShortcut::Er
             | CommandShortcut::Ef
             | CommandShortcut::Ex
+            | CommandShortcut::Export
+            | CommandShortcut::Saveblob
+            | CommandShortcut::Gexec
+            | CommandShortcut::Dump
+            | CommandShortcut::Restore
+            | CommandShortcut::Fedjoin
+            | CommandShortcut::Scratch
+            | CommandShortcut::Lint
+            | CommandShortcut::Fmt
+            | CommandShortcut::Dryrun
+            | CommandShortcut::Count
+            | CommandShortcut::Insertrow
+            | CommandShortcut::Genrows
+            | CommandShortcut::Pastedata
             | CommandShortcut::Ps => CommandCategory::Advanced,
             // Complex display commands
-            CommandShortcut::Cd | CommandShortcut::Cdj => CommandCategory::DisplayOptions,
+            CommandShortcut::Cd | CommandShortcut::Cdj | CommandShortcut::Flatten => {
+                CommandCategory::DisplayOptions
+            }
             // Schema viewer
             CommandShortcut::Sv => CommandCategory::DatabaseNavigation,
+            CommandShortcut::Browse => CommandCategory::DatabaseNavigation,
+            CommandShortcut::Erd => CommandCategory::DatabaseNavigation,
             // AI assistant
             CommandShortcut::Ai => CommandCategory::AiAssistant,
         }
@@ -629,7 +1366,22 @@ impl CommandParser {
 
         let mut parts = args.splitn(2, char::is_whitespace);
         let first_token = parts.next().unwrap_or("");
-        let rest = parts.next().unwrap_or("").trim();
+        let mut rest = parts.next().unwrap_or("").trim();
+
+        // --group comes first: a password command takes the rest of the line
+        let mut group = None;
+        if let Some(after) = rest
+            .strip_prefix("--group")
+            .filter(|after| after.is_empty() || after.starts_with([' ', '=']))
+        {
+            let after = after.trim_start_matches('=').trim_start();
+            let (value, remaining) = after.split_once(char::is_whitespace).unwrap_or((after, ""));
+            if value.is_empty() {
+                return Err(CommandError::MissingArgument("group name".to_string()));
+            }
+            group = Some(value.to_string());
+            rest = remaining.trim();
+        }
 
         if matches!(rest, "--password-command" | "--password-cmd") {
             return Err(CommandError::MissingArgument(
@@ -654,12 +1406,125 @@ impl CommandParser {
             return Ok(Command::SaveSession {
                 name: first_token.to_string(),
                 password_command: Some(command.to_string()),
+                group,
             });
         }
 
         Ok(Command::SaveSession {
-            name: args.to_string(),
+            name: if group.is_some() {
+                first_token.to_string()
+            } else {
+                args.to_string()
+            },
             password_command: None,
+            group,
+        })
+    }
+
+    fn parse_erd_args(args: &str) -> Result<Command, CommandError> {
+        use crate::schema_graph::ErdFormat;
+
+        let mut target = None;
+        let mut format = ErdFormat::default();
+        let mut output = None;
+        let mut tokens = args.split_whitespace();
+        while let Some(token) = tokens.next() {
+            match token {
+                "--ascii" => format = ErdFormat::Ascii,
+                "--dot" | "--mermaid" => {
+                    format = token
+                        .trim_start_matches('-')
+                        .parse()
+                        .map_err(CommandError::InvalidSyntax)?;
+                    let file = tokens
+                        .next()
+                        .ok_or_else(|| CommandError::MissingArgument("output file".to_string()))?;
+                    output = Some(file.to_string());
+                }
+                _ if token.starts_with("--") => {
+                    return Err(CommandError::InvalidSyntax(format!(
+                        "Unknown option: {token}. Use --ascii, --dot <file> or --mermaid <file>"
+                    )));
+                }
+                _ if target.is_none() => target = Some(token.to_string()),
+                _ => {
+                    return Err(CommandError::InvalidSyntax(format!(
+                        "Unexpected argument: {token}"
+                    )));
+                }
+            }
+        }
+
+        Ok(Command::EntityRelationshipDiagram {
+            target,
+            format,
+            output,
+        })
+    }
+
+    fn parse_knn_args(args: &str) -> Result<Command, CommandError> {
+        use crate::vector_search::{DEFAULT_KNN_LIMIT, DistanceMetric, KnnTarget};
+
+        // Table and column are whitespace-separated words; the rest stays
+        // whole because vector literals may contain spaces
+        let mut words = args.split_whitespace();
+        let table = words.next().unwrap_or("");
+        let column = words.next().unwrap_or("");
+        let rest = args
+            .trim_start()
+            .strip_prefix(table)
+            .map(str::trim_start)
+            .and_then(|rest| rest.strip_prefix(column))
+            .unwrap_or("")
+            .trim();
+        if table.is_empty() || column.is_empty() || rest.is_empty() {
+            return Err(CommandError::MissingArgument(
+                "table, vector column and query vector or row reference".to_string(),
+            ));
+        }
+
+        // Vector literals may contain spaces, so take everything up to the closing bracket
+        let (target, options) = if rest.starts_with('[') {
+            match rest.find(']') {
+                Some(end) => (&rest[..=end], &rest[end + 1..]),
+                None => (rest, ""),
+            }
+        } else {
+            rest.split_once(char::is_whitespace).unwrap_or((rest, ""))
+        };
+        let target = KnnTarget::parse(target).map_err(CommandError::InvalidSyntax)?;
+
+        let mut k = DEFAULT_KNN_LIMIT;
+        let mut metric = DistanceMetric::default();
+        let mut tokens = options.split_whitespace();
+        while let Some(token) = tokens.next() {
+            let metric_value =
+                match token {
+                    "--metric" | "-m" => Some(tokens.next().ok_or_else(|| {
+                        CommandError::MissingArgument("distance metric".to_string())
+                    })?),
+                    _ => token.strip_prefix("--metric="),
+                };
+
+            if let Some(value) = metric_value {
+                metric = value.parse().map_err(CommandError::InvalidSyntax)?;
+            } else {
+                k = token
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|k| *k > 0)
+                    .ok_or_else(|| {
+                        CommandError::InvalidSyntax(format!("Invalid neighbour count: {token}"))
+                    })?;
+            }
+        }
+
+        Ok(Command::VectorKnn {
+            table: table.to_string(),
+            column: column.to_string(),
+            target,
+            k,
+            metric,
         })
     }
 
@@ -679,11 +1544,77 @@ impl CommandParser {
         match cmd {
             // Core commands
             "q" => Ok(Command::Quit),
-            "h" => Ok(Command::Help),
+            "h" => Ok(Command::Help {
+                topic: (!args.is_empty()).then(|| args.to_string()),
+            }),
 
             // Database navigation
             "l" => Ok(Command::ListDatabases),
             "dt" => Ok(Command::ListTables),
+            "whoami" => Ok(Command::Whoami),
+            "setrole" | "su" => {
+                if args.is_empty() {
+                    Err(CommandError::MissingArgument("role name".to_string()))
+                } else {
+                    Ok(Command::SetRole {
+                        role: args.to_string(),
+                    })
+                }
+            }
+            "resetrole" => Ok(Command::ResetRole),
+            "ssl" => match args {
+                "" => Ok(Command::ShowSsl),
+                "reload" => Ok(Command::ReloadSsl),
+                _ => Err(CommandError::InvalidSyntax(
+                    "Usage: \\ssl [reload]".to_string(),
+                )),
+            },
+            "stats" => match args {
+                "" => Ok(Command::Stats { reset: false }),
+                "reset" => Ok(Command::Stats { reset: true }),
+                _ => Err(CommandError::InvalidSyntax(
+                    "Usage: \\stats [reset]".to_string(),
+                )),
+            },
+            "cache" => match args {
+                "" | "show" | "status" => Ok(Command::ShowResultCache),
+                "on" => Ok(Command::SetResultCache { enabled: true }),
+                "off" => Ok(Command::SetResultCache { enabled: false }),
+                "clear" => Ok(Command::ClearResultCache),
+                _ => Err(CommandError::InvalidSyntax(
+                    "Usage: \\cache [on|off|clear]".to_string(),
+                )),
+            },
+            "queue" => match args {
+                "" | "list" => Ok(Command::ShowQueue),
+                "clear" => Ok(Command::ClearQueue),
+                "run" => Ok(Command::RunQueue),
+                _ => Err(CommandError::InvalidSyntax(
+                    "Usage: \\queue [list|clear|run]".to_string(),
+                )),
+            },
+            "refreshschema" => Ok(Command::RefreshSchema),
+            "tag" => match args {
+                "" => Ok(Command::ShowQueryTags),
+                "clear" => Ok(Command::ClearQueryTags),
+                _ => {
+                    let mut tags = Vec::new();
+                    for pair in args.split_whitespace() {
+                        match pair.split_once('=') {
+                            Some((key, value)) if crate::query_tags::is_valid_key(key) => {
+                                tags.push((key.to_string(), value.to_string()))
+                            }
+                            _ => {
+                                return Err(CommandError::InvalidSyntax(
+                                    "Usage: \\tag [key=value ...|clear] (keys use letters, digits, _ - .)"
+                                        .to_string(),
+                                ));
+                            }
+                        }
+                    }
+                    Ok(Command::SetQueryTags { tags })
+                }
+            },
             "d" => {
                 if args.is_empty() {
                     Ok(Command::DescribeTable { table_name: None })
@@ -702,11 +1633,165 @@ impl CommandParser {
                     })
                 }
             }
+            "schema" => Ok(Command::Schema {
+                name: (!args.is_empty()).then(|| args.to_string()),
+            }),
+            "schemas" => Ok(Command::ListSchemas),
+            "warehouse" => Ok(Command::Warehouse {
+                name: (!args.is_empty()).then(|| args.to_string()),
+            }),
+            "dbsize" => Ok(Command::DbSize),
+            "tablesize" => {
+                let mut limit = 20;
+                let mut sort = crate::database::SizeSort::default();
+                let mut words = args.split_whitespace();
+                while let Some(word) = words.next() {
+                    if word == "--sort" {
+                        sort = words
+                            .next()
+                            .and_then(crate::database::SizeSort::parse)
+                            .ok_or_else(|| {
+                                CommandError::InvalidSyntax(
+                                    "Usage: \\tablesize [n] [--sort total|table|index|rows]"
+                                        .to_string(),
+                                )
+                            })?;
+                    } else {
+                        limit = match word.parse::<usize>() {
+                            Ok(n) if n > 0 => n,
+                            _ => {
+                                return Err(CommandError::InvalidSyntax(
+                                    "Usage: \\tablesize [n] [--sort total|table|index|rows] (n must be a positive number)"
+                                        .to_string(),
+                                ));
+                            }
+                        };
+                    }
+                }
+                Ok(Command::TableSize { limit, sort })
+            }
 
             // Display options
             "x" => Ok(Command::ToggleExpandedDisplay),
+            "transpose" => match args {
+                "" => Ok(Command::Transpose {
+                    max_rows: DEFAULT_TRANSPOSE_ROWS,
+                }),
+                _ => match args.parse::<usize>() {
+                    Ok(max_rows) if max_rows > 0 => Ok(Command::Transpose { max_rows }),
+                    _ => Err(CommandError::InvalidSyntax(
+                        "Usage: \\transpose [rows]".to_string(),
+                    )),
+                },
+            },
+            "pivot" => {
+                let words: Vec<&str> = args.split_whitespace().collect();
+                let aggregate = |word: &str| {
+                    word.parse::<crate::pivot::Aggregate>()
+                        .map_err(CommandError::InvalidSyntax)
+                };
+                let spec = match words.as_slice() {
+                    [] => crate::pivot::PivotSpec::default(),
+                    [row, column, rest @ ..] if rest.len() <= 2 => crate::pivot::PivotSpec {
+                        row_key: Some(row.to_string()),
+                        column_key: Some(column.to_string()),
+                        value: rest.first().map(|value| value.to_string()),
+                        aggregate: rest.get(1).map(|word| aggregate(word)).transpose()?,
+                    },
+                    _ => {
+                        return Err(CommandError::InvalidSyntax(
+                            "Usage: \\pivot [<row_key> <column_key> [<value> [sum|count|min|max|avg]]]"
+                                .to_string(),
+                        ));
+                    }
+                };
+                Ok(Command::Pivot { spec })
+            }
+            "dedup" => Ok(Command::Dedup {
+                columns: args
+                    .split([',', ' '])
+                    .filter(|column| !column.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+            }),
+            "snapshot" => {
+                let mut words = args.split_whitespace();
+                match (words.next(), words.next()) {
+                    (None | Some("list"), None) => Ok(Command::ListSnapshots),
+                    (Some("save"), Some(name)) if words.clone().next().is_none() => {
+                        Ok(Command::SaveSnapshot {
+                            name: name.to_string(),
+                        })
+                    }
+                    (Some("diff"), Some(name)) => Ok(Command::DiffSnapshot {
+                        name: name.to_string(),
+                        key: words
+                            .flat_map(|word| word.split(','))
+                            .filter(|column| !column.is_empty())
+                            .map(str::to_string)
+                            .collect(),
+                    }),
+                    (Some("delete"), Some(name)) if words.clone().next().is_none() => {
+                        Ok(Command::DeleteSnapshot {
+                            name: name.to_string(),
+                        })
+                    }
+                    _ => Err(CommandError::InvalidSyntax(
+                        "Usage: \\snapshot [list|save <name>|diff <name> [key columns...]|delete <name>]"
+                            .to_string(),
+                    )),
+                }
+            }
+            "mask" => match args {
+                "" | "show" | "status" => Ok(Command::ShowMasking),
+                "on" => Ok(Command::SetMasking { enabled: true }),
+                "off" => Ok(Command::SetMasking { enabled: false }),
+                _ => Err(CommandError::InvalidSyntax(
+                    "Usage: \\mask [on|off]".to_string(),
+                )),
+            },
             "e" => Ok(Command::ToggleExplainMode),
-            "ev" => Ok(Command::ToggleExplainTuiMode),
+            "ev" => {
+                let mut parts = args.split_whitespace();
+                match parts.next() {
+                    None => Ok(Command::ToggleExplainTuiMode),
+                    Some("export") => {
+                        let filename = parts
+                            .next()
+                            .ok_or_else(|| CommandError::MissingArgument("file".to_string()))?;
+                        let format = parts
+                            .next()
+                            .map(|name| {
+                                crate::explain_tui::PlanExportFormat::from_name(name).ok_or_else(
+                                    || {
+                                        CommandError::InvalidSyntax(format!(
+                                            "Unknown plan format '{name}' (folded, dot or json)"
+                                        ))
+                                    },
+                                )
+                            })
+                            .transpose()?;
+                        Ok(Command::ExportExplainPlan {
+                            filename: filename.to_string(),
+                            format,
+                        })
+                    }
+                    Some("upload") => {
+                        let name = parts.next().unwrap_or_default();
+                        let service =
+                            crate::explain_tui::PlanService::from_name(name).ok_or_else(|| {
+                                CommandError::InvalidSyntax(format!(
+                                    "Unknown plan site '{name}' (dalibo or depesz)"
+                                ))
+                            })?;
+                        Ok(Command::UploadExplainPlan { service })
+                    }
+                    Some(_) => Err(CommandError::InvalidSyntax(
+                        "Usage: \\ev [export <file> [folded|dot|json]|upload [dalibo|depesz]]"
+                            .to_string(),
+                    )),
+                }
+            }
             "config" => {
                 if args.is_empty() {
                     Ok(Command::ConfigMenu)
@@ -744,13 +1829,89 @@ impl CommandParser {
                                 })
                             }
                         }
+                        "unset" => {
+                            if sub_args.is_empty() {
+                                Err(CommandError::MissingArgument(
+                                    "key (usage: \\config unset <key>)".to_string(),
+                                ))
+                            } else {
+                                Ok(Command::ConfigUnset {
+                                    key: sub_args.to_string(),
+                                })
+                            }
+                        }
                         "edit" => Ok(Command::ConfigEdit),
                         _ => Err(CommandError::InvalidSyntax(format!(
-                            "Unknown \\config subcommand: {subcmd}. Use: show|get|set|edit"
+                            "Unknown \\config subcommand: {subcmd}. Use: show|get|set|unset|edit"
                         ))),
                     }
                 }
             }
+            "reloadconfig" => Ok(Command::ReloadConfig),
+            "snippets" => {
+                let (subcmd, rest) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+                let rest = rest.trim();
+                match subcmd {
+                    "" | "list" => Ok(Command::ListSnippets),
+                    "add" => {
+                        let (trigger, expansion) =
+                            rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+                        if trigger.is_empty() {
+                            Err(CommandError::MissingArgument(
+                                "trigger (usage: \\snippets add <trigger> <expansion>)".to_string(),
+                            ))
+                        } else if expansion.trim().is_empty() {
+                            Err(CommandError::MissingArgument(format!(
+                                "expansion (usage: \\snippets add {trigger} <expansion>)"
+                            )))
+                        } else {
+                            Ok(Command::AddSnippet {
+                                trigger: trigger.to_string(),
+                                expansion: expansion.trim_start().to_string(),
+                            })
+                        }
+                    }
+                    "remove" | "rm" if !rest.is_empty() => Ok(Command::RemoveSnippet {
+                        trigger: rest.to_string(),
+                    }),
+                    "remove" | "rm" => Err(CommandError::MissingArgument(
+                        "trigger (usage: \\snippets remove <trigger>)".to_string(),
+                    )),
+                    _ => Err(CommandError::InvalidSyntax(format!(
+                        "Unknown \\snippets subcommand: {subcmd}. Use: list|add|remove"
+                    ))),
+                }
+            }
+            "prefs" => {
+                let (subcmd, rest) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+                let rest = rest.trim();
+                match subcmd {
+                    "" | "show" | "list" => Ok(Command::ShowTablePrefs),
+                    "clear" => Ok(Command::ClearTablePrefs {
+                        table: (!rest.is_empty()).then(|| rest.to_string()),
+                    }),
+                    "sort" => {
+                        let mut words = rest.split_whitespace();
+                        let column = words.next().filter(|word| *word != "off");
+                        let descending = match words.next().map(str::to_lowercase).as_deref() {
+                            None | Some("asc") => false,
+                            Some("desc") => true,
+                            Some(other) => {
+                                return Err(CommandError::InvalidSyntax(format!(
+                                    "Unknown sort direction: {other}. Use: asc|desc"
+                                )));
+                            }
+                        };
+                        Ok(Command::SetTablePrefsSort {
+                            column: column.map(str::to_string),
+                            descending,
+                        })
+                    }
+                    _ => Err(CommandError::InvalidSyntax(format!(
+                        "Unknown \\prefs subcommand: {subcmd}. Use: show|clear|sort"
+                    ))),
+                }
+            }
 
             // Script handling
             "w" => {
@@ -773,6 +1934,26 @@ impl CommandParser {
             }
             "ed" => Ok(Command::EditMultiline),
             "ecopy" => Ok(Command::CopyExplainPlan),
+            "paste" => Ok(Command::Paste),
+            "g" => Ok(Command::Rerun),
+            "keys" => Ok(Command::ShowKeys),
+            "watch" => {
+                if args.is_empty() {
+                    Ok(Command::Watch {
+                        interval: std::time::Duration::from_secs(2),
+                    })
+                } else {
+                    match args.parse::<f64>() {
+                        Ok(secs) if secs.is_finite() && secs > 0.0 => Ok(Command::Watch {
+                            interval: std::time::Duration::from_secs_f64(secs),
+                        }),
+                        _ => Err(CommandError::InvalidSyntax(
+                            "Usage: \\watch [seconds] (seconds must be a positive number)"
+                                .to_string(),
+                        )),
+                    }
+                }
+            }
 
             // Named queries
             "n" => {
@@ -963,6 +2144,24 @@ impl CommandParser {
             "du" => Ok(Command::ListUsers),
             "di" => Ok(Command::ListIndexes),
             "dp" => Ok(Command::ListPragmas),
+            "sf" | "sf+" => {
+                let is_edit = |word: &&str| matches!(*word, "--edit" | "-e");
+                let edit = args.split_whitespace().any(|word| is_edit(&word));
+                let name = args
+                    .split_whitespace()
+                    .filter(|word| !is_edit(word))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                if name.is_empty() {
+                    Err(CommandError::MissingArgument("function name".to_string()))
+                } else {
+                    Ok(Command::ShowFunction {
+                        name,
+                        numbered: cmd == "sf+",
+                        edit,
+                    })
+                }
+            }
             "pgpass" => Ok(Command::ShowPgpass),
             "myconf" => Ok(Command::ShowMyconf),
             "docker" => Ok(Command::ListDockerContainers),
@@ -986,6 +2185,76 @@ impl CommandParser {
                     })
                 }
             }
+            "insertrow" => match args.split_whitespace().collect::<Vec<_>>().as_slice() {
+                [table] => Ok(Command::InsertRow {
+                    table: table.to_string(),
+                }),
+                [] => Err(CommandError::MissingArgument("table name".to_string())),
+                _ => Err(CommandError::InvalidSyntax(
+                    "Usage: \\insertrow <table>".to_string(),
+                )),
+            },
+            "genrows" => {
+                let is_yes = |word: &&str| matches!(*word, "--yes" | "-y");
+                let yes = args.split_whitespace().any(|word| is_yes(&word));
+                let words: Vec<&str> = args.split_whitespace().filter(|w| !is_yes(w)).collect();
+                match words.as_slice() {
+                    [table, count] => match count.replace('_', "").parse::<usize>() {
+                        Ok(count) if count > 0 => Ok(Command::GenRows {
+                            table: table.to_string(),
+                            count,
+                            yes,
+                        }),
+                        _ => Err(CommandError::InvalidSyntax(format!(
+                            "row count must be a positive number, got '{count}'"
+                        ))),
+                    },
+                    [] => Err(CommandError::MissingArgument("table name".to_string())),
+                    [_] => Err(CommandError::MissingArgument("row count".to_string())),
+                    _ => Err(CommandError::InvalidSyntax(
+                        "Usage: \\genrows <table> <count> [--yes]".to_string(),
+                    )),
+                }
+            }
+            "pastedata" => {
+                let is_yes = |word: &&str| matches!(*word, "--yes" | "-y");
+                let yes = args.split_whitespace().any(|word| is_yes(&word));
+                let words: Vec<&str> = args.split_whitespace().filter(|w| !is_yes(w)).collect();
+                match words.as_slice() {
+                    [table] => Ok(Command::PasteData {
+                        table: table.to_string(),
+                        yes,
+                    }),
+                    [] => Err(CommandError::MissingArgument("table name".to_string())),
+                    _ => Err(CommandError::InvalidSyntax(
+                        "Usage: \\pastedata <table> [--yes]".to_string(),
+                    )),
+                }
+            }
+            "lint" => {
+                if args.is_empty() {
+                    Err(CommandError::MissingArgument("query".to_string()))
+                } else {
+                    Ok(Command::Lint {
+                        query: args.to_string(),
+                    })
+                }
+            }
+            "fmt" => Ok(Command::Format {
+                query: (!args.is_empty()).then(|| args.to_string()),
+            }),
+            "count" => Ok(Command::Count {
+                target: (!args.is_empty()).then(|| args.to_string()),
+            }),
+            "dryrun" => {
+                if args.is_empty() {
+                    Err(CommandError::MissingArgument("statement".to_string()))
+                } else {
+                    Ok(Command::DryRun {
+                        query: args.to_string(),
+                    })
+                }
+            }
             "ex" => {
                 // Split on the last space to separate query from filename
                 if let Some(last_space_pos) = args.rfind(' ') {
@@ -1006,14 +2275,175 @@ impl CommandParser {
                 }
             }
 
-            // Advanced commands
-            "setmulti" => Ok(Command::SetMultilineIndicator {
-                indicator: args.to_string(),
+            "export" => {
+                let (format, mut rest) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+                let mut resume = false;
+                let mut chunk_rows = crate::export_chunks::DEFAULT_CHUNK_ROWS;
+                loop {
+                    rest = rest.trim_start();
+                    let (word, after) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+                    match word {
+                        "--resume" => resume = true,
+                        "--chunk" => {
+                            let (size, after) = after
+                                .trim_start()
+                                .split_once(char::is_whitespace)
+                                .unwrap_or((after.trim_start(), ""));
+                            chunk_rows = size.parse().ok().filter(|&n| n > 0).ok_or_else(|| {
+                                CommandError::InvalidSyntax(format!(
+                                    "--chunk takes a number of rows, got '{size}'"
+                                ))
+                            })?;
+                            rest = after;
+                            continue;
+                        }
+                        _ => break,
+                    }
+                    rest = after;
+                }
+                let (filename, query) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+                let query = query.trim().trim_end_matches(';').trim_end();
+                if format.is_empty() {
+                    Err(CommandError::MissingArgument("format".to_string()))
+                } else if filename.is_empty() {
+                    Err(CommandError::MissingArgument("filename".to_string()))
+                } else if query.is_empty() {
+                    Err(CommandError::MissingArgument("query".to_string()))
+                } else {
+                    Ok(Command::Export {
+                        format: format.parse().map_err(CommandError::InvalidSyntax)?,
+                        filename: filename.to_string(),
+                        query: query.to_string(),
+                        resume,
+                        chunk_rows,
+                    })
+                }
+            }
+            "gexec" => Ok(Command::Gexec {
+                query: (!args.is_empty()).then(|| args.to_string()),
             }),
-            "pager" => Ok(Command::TogglePager),
-            "banner" => Ok(Command::ToggleBanner),
-            "serverinfo" => Ok(Command::ToggleServerInfo),
-            "a" => Ok(Command::ToggleAutocomplete),
+            "saveblob" => match args.split_whitespace().collect::<Vec<_>>().as_slice() {
+                [row, column, filename] => Ok(Command::SaveBlob {
+                    row: row.parse().map_err(|_| {
+                        CommandError::InvalidSyntax(format!(
+                            "Row must be a number from 1, got '{row}'"
+                        ))
+                    })?,
+                    column: column.to_string(),
+                    filename: filename.to_string(),
+                }),
+                [] => Err(CommandError::MissingArgument("row".to_string())),
+                [_] => Err(CommandError::MissingArgument("column".to_string())),
+                [_, _] => Err(CommandError::MissingArgument("file".to_string())),
+                _ => Err(CommandError::InvalidSyntax(
+                    "Usage: \\saveblob <row> <column> <file>".to_string(),
+                )),
+            },
+            "dump" => match args.split_whitespace().collect::<Vec<_>>().as_slice() {
+                [target, filename] => Ok(Command::Dump {
+                    target: target.to_string(),
+                    filename: filename.to_string(),
+                }),
+                [] => Err(CommandError::MissingArgument(
+                    "table or database".to_string(),
+                )),
+                [_] => Err(CommandError::MissingArgument("file".to_string())),
+                _ => Err(CommandError::InvalidSyntax(
+                    "Usage: \\dump <table|database> <file>".to_string(),
+                )),
+            },
+            "restore" => {
+                if args.is_empty() {
+                    Err(CommandError::MissingArgument("file".to_string()))
+                } else {
+                    Ok(Command::Restore {
+                        filename: args.to_string(),
+                    })
+                }
+            }
+            "fedjoin" => {
+                let (first, rest) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+                let rest = rest.trim();
+                match first {
+                    "" | "list" => Ok(Command::ListFedJoins),
+                    "clear" => Ok(Command::ClearFedJoins),
+                    "run" if rest.is_empty() => {
+                        Err(CommandError::MissingArgument("query".to_string()))
+                    }
+                    "run" => Ok(Command::FedJoinRun {
+                        query: rest.trim_end_matches(';').trim_end().to_string(),
+                    }),
+                    _ => {
+                        let (local, args) = match args.strip_prefix("--local") {
+                            Some(args) => (true, args.trim_start()),
+                            None => (false, args),
+                        };
+                        let mut parts = args.splitn(3, char::is_whitespace);
+                        let name = parts.next().unwrap_or_default();
+                        let source = parts.next().unwrap_or_default().trim();
+                        let query = parts.next().unwrap_or_default().trim();
+                        let query = query.trim_end_matches(';').trim_end();
+                        if name.is_empty() {
+                            Err(CommandError::MissingArgument("table name".to_string()))
+                        } else if source.is_empty() {
+                            Err(CommandError::MissingArgument("source".to_string()))
+                        } else if query.is_empty() {
+                            Err(CommandError::MissingArgument("query".to_string()))
+                        } else {
+                            Ok(Command::FedJoin {
+                                name: name.to_string(),
+                                source: source.to_string(),
+                                query: query.to_string(),
+                                local,
+                            })
+                        }
+                    }
+                }
+            }
+            "scratch" => {
+                let (first, rest) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+                let rest = rest.trim();
+                match first {
+                    "" | "list" => Ok(Command::ShowScratch),
+                    "clear" => Ok(Command::ClearScratch),
+                    "import" if rest.is_empty() => {
+                        Err(CommandError::MissingArgument("table name".to_string()))
+                    }
+                    "import" => Ok(Command::ScratchImport {
+                        name: rest.to_string(),
+                    }),
+                    _ => Ok(Command::ScratchRun {
+                        query: args.trim_end_matches(';').trim_end().to_string(),
+                    }),
+                }
+            }
+
+            // Advanced commands
+            "setmulti" => Ok(Command::SetMultilineIndicator {
+                indicator: args.to_string(),
+            }),
+            "pager" => Ok(Command::TogglePager),
+            "banner" => Ok(Command::ToggleBanner),
+            "serverinfo" => Ok(Command::ToggleServerInfo),
+            "tz" => Ok(Command::SetDisplayTimezone {
+                zone: args.to_string(),
+            }),
+            "pset" => {
+                let mut parts = args.split_whitespace();
+                match (parts.next(), parts.next(), parts.next()) {
+                    (None, ..) | (Some("border"), None, _) => Ok(Command::SetTableStyle {
+                        style: String::new(),
+                    }),
+                    (Some("border"), Some(style), None) => Ok(Command::SetTableStyle {
+                        style: style.to_string(),
+                    }),
+                    _ => Err(CommandError::InvalidSyntax(
+                        "Usage: \\pset border [style] (only the border option is supported)"
+                            .to_string(),
+                    )),
+                }
+            }
+            "a" => Ok(Command::ToggleAutocomplete),
             "cs" => Ok(Command::ToggleColumnSelection),
             "csthreshold" => {
                 let threshold = args.parse::<usize>().map_err(|_| {
@@ -1022,6 +2452,27 @@ impl CommandParser {
                 Ok(Command::SetColumnSelectionThreshold { threshold })
             }
             "clrcs" => Ok(Command::ClearColumnViews),
+            "csave" => {
+                if args.is_empty() || args.contains(char::is_whitespace) {
+                    return Err(CommandError::InvalidSyntax(
+                        "Usage: \\csave <name>".to_string(),
+                    ));
+                }
+                Ok(Command::SaveColumnView {
+                    name: args.to_string(),
+                })
+            }
+            "cviews" => match args.split_once(char::is_whitespace) {
+                None if args.is_empty() => Ok(Command::ListColumnViews),
+                Some(("delete", name)) if !name.trim().is_empty() => {
+                    Ok(Command::DeleteColumnView {
+                        name: name.trim().to_string(),
+                    })
+                }
+                _ => Err(CommandError::InvalidSyntax(
+                    "Usage: \\cviews [delete <name>]".to_string(),
+                )),
+            },
             "resetview" => Ok(Command::ResetView),
 
             // Vector display commands
@@ -1030,6 +2481,80 @@ impl CommandParser {
             }),
             "vdc" => Ok(Command::ShowVectorDisplayConfig),
             "vs" => Ok(Command::ToggleVectorStatistics),
+            "knn" => Self::parse_knn_args(args),
+            "replication" => Ok(Command::Replication),
+            "cluster" => Ok(Command::Cluster {
+                name: (!args.is_empty()).then(|| args.to_string()),
+            }),
+            "processlist" => Ok(Command::MySqlProcessList),
+            "locks" => Ok(Command::MySqlLocks),
+            "slowqueries" => {
+                if args.is_empty() {
+                    Ok(Command::MySqlSlowQueries { limit: 10 })
+                } else {
+                    match args.parse::<usize>() {
+                        Ok(limit) if limit > 0 => Ok(Command::MySqlSlowQueries { limit }),
+                        _ => Err(CommandError::InvalidSyntax(
+                            "Usage: \\slowqueries [n] (n must be a positive number)".to_string(),
+                        )),
+                    }
+                }
+            }
+            "activity" => Ok(Command::PgActivity),
+            "blocking" => Ok(Command::PgBlocking),
+            "bloat" => {
+                if args.is_empty() {
+                    Ok(Command::PgBloat { limit: 20 })
+                } else {
+                    match args.parse::<usize>() {
+                        Ok(limit) if limit > 0 => Ok(Command::PgBloat { limit }),
+                        _ => Err(CommandError::InvalidSyntax(
+                            "Usage: \\bloat [n] (n must be a positive number)".to_string(),
+                        )),
+                    }
+                }
+            }
+            "backup" => {
+                let mut force = false;
+                let mut file = None;
+                for part in args.split_whitespace() {
+                    match part {
+                        "--force" | "-f" => force = true,
+                        _ if file.is_none() => file = Some(part.to_string()),
+                        _ => {
+                            return Err(CommandError::InvalidSyntax(
+                                "Usage: \\backup <file> [--force]".to_string(),
+                            ));
+                        }
+                    }
+                }
+                match file {
+                    Some(file) => Ok(Command::SqliteBackup { file, force }),
+                    None => Err(CommandError::MissingArgument("backup file".to_string())),
+                }
+            }
+            "vacuum" => Ok(Command::SqliteVacuum),
+            "integritycheck" => match args {
+                "" => Ok(Command::SqliteIntegrityCheck { quick: false }),
+                "quick" => Ok(Command::SqliteIntegrityCheck { quick: true }),
+                _ => Err(CommandError::InvalidSyntax(
+                    "Usage: \\integritycheck [quick]".to_string(),
+                )),
+            },
+            "chinsert" => {
+                let parts: Vec<&str> = args.split_whitespace().collect();
+                match parts.as_slice() {
+                    [table, file] | [table, file, _] => Ok(Command::ClickHouseInsert {
+                        table: table.to_string(),
+                        file: file.to_string(),
+                        format: parts.get(2).map(|f| f.to_string()),
+                    }),
+                    [] | [_] => Err(CommandError::MissingArgument("table and file".to_string())),
+                    _ => Err(CommandError::InvalidSyntax(
+                        "Usage: \\chinsert <table> <file> [format]".to_string(),
+                    )),
+                }
+            }
 
             // Connection pool monitoring
             "ps" => Ok(Command::ShowPoolStats),
@@ -1045,6 +2570,33 @@ impl CommandParser {
                 }
             }
             "cdj" => Ok(Command::ComplexDisplayJsonToggle),
+            "flatten" => {
+                let (mut depth, mut arrays, mut save, mut forget) = (None, None, false, false);
+                for word in args.split_whitespace() {
+                    match word.to_lowercase().as_str() {
+                        "save" => save = true,
+                        "forget" | "clear" => forget = true,
+                        other => {
+                            if let Ok(levels) = other.parse::<usize>() {
+                                depth = Some(levels);
+                            } else {
+                                arrays = Some(other.parse().map_err(CommandError::InvalidSyntax)?);
+                            }
+                        }
+                    }
+                }
+                if save && forget {
+                    return Err(CommandError::InvalidSyntax(
+                        "Use either save or forget, not both".to_string(),
+                    ));
+                }
+                Ok(Command::Flatten {
+                    depth,
+                    arrays,
+                    save,
+                    forget,
+                })
+            }
 
             // Vault credential cache commands
             "vc" => Ok(Command::VaultCacheStatus),
@@ -1086,6 +2638,8 @@ impl CommandParser {
 
             // Schema viewer
             "sv" => Ok(Command::SchemaViewer),
+            "browse" => Ok(Command::SchemaBrowser),
+            "erd" => Self::parse_erd_args(args),
 
             // AI assistant commands
             "ai" => {
@@ -1265,29 +2819,237 @@ impl CommandExecutor for Command {
         database: &Arc<Mutex<Database>>,
         config: &mut DbCrustConfig,
         last_script: &mut String,
-        _interrupt_flag: &Arc<AtomicBool>,
+        interrupt_flag: &Arc<AtomicBool>,
         prompt: &mut DbPrompt,
     ) -> Result<CommandResult, CommandError> {
         match self {
             Command::Quit => Ok(CommandResult::Exit),
-            Command::Help => {
+            Command::Help { topic: None } => {
                 let help_text = generate_help_text();
                 Ok(CommandResult::Output(help_text))
             }
+            Command::Help { topic: Some(topic) } => {
+                let database_type = database.lock().unwrap().get_database_type();
+                Ok(help_topic(topic, Some(&database_type)))
+            }
 
             Command::ToggleExpandedDisplay => {
                 let mut db = database.lock().unwrap();
-                db.toggle_expanded_display();
-                let status = if db.is_expanded_display() {
-                    "on"
-                } else {
-                    "off"
+                let (expanded, key) = db.toggle_expanded_for_last_result();
+                let status = if expanded { "on" } else { "off" };
+                Ok(CommandResult::Output(match key {
+                    Some(key) => format!(
+                        "Expanded display is {status} (remembered for {}).",
+                        table_prefs_label(key)
+                    ),
+                    None => format!("Expanded display is {status}."),
+                }))
+            }
+
+            Command::Transpose { max_rows } => {
+                let db = database.lock().unwrap();
+                let result = db.last_result();
+                if result.is_empty() {
+                    return Ok(CommandResult::Error(
+                        "No result to transpose yet. Run a query that returns rows first."
+                            .to_string(),
+                    ));
+                }
+                let transposed = crate::format::transpose_results(result, *max_rows);
+                let mut output = crate::format::format_query_results_psql(&transposed);
+                let rows = result.len() - 1;
+                if rows > *max_rows {
+                    output.push_str(&format!(
+                        "Showing the first {max_rows} of {rows} rows; \\transpose {rows} shows them all."
+                    ));
+                }
+                Ok(CommandResult::Output(output))
+            }
+
+            Command::Pivot { spec } => {
+                let db = database.lock().unwrap();
+                match crate::pivot::pivot(db.last_result(), spec) {
+                    Ok(pivoted) => Ok(CommandResult::Output(
+                        crate::format::format_query_results_psql(&pivoted),
+                    )),
+                    Err(e) => Ok(CommandResult::Error(e)),
+                }
+            }
+
+            Command::Dedup { columns } => {
+                let db = database.lock().unwrap();
+                let deduped = match crate::dedup::dedup(db.last_result(), columns) {
+                    Ok(deduped) => deduped,
+                    Err(e) => return Ok(CommandResult::Error(e)),
+                };
+                let shown = crate::masking::masked(&deduped.data);
+                let mut output = crate::format::format_query_results_psql(
+                    shown.as_deref().unwrap_or(&deduped.data),
+                );
+                let kept = deduped.data.len() - 1;
+                output.push_str(&match deduped.removed {
+                    0 => format!("No duplicate rows ({kept} rows)."),
+                    1 => format!("Removed 1 duplicate row; {kept} rows left."),
+                    n => format!("Removed {n} duplicate rows; {kept} rows left."),
+                });
+                Ok(CommandResult::Output(output))
+            }
+
+            Command::ListSnapshots => {
+                let snapshots = match crate::snapshot::list() {
+                    Ok(snapshots) => snapshots,
+                    Err(e) => return Ok(CommandResult::Error(e)),
+                };
+                if snapshots.is_empty() {
+                    return Ok(CommandResult::Output(
+                        "No snapshots saved. Save the last result with \\snapshot save <name>."
+                            .to_string(),
+                    ));
+                }
+                let mut rows = vec![vec![
+                    "Name".to_string(),
+                    "Taken".to_string(),
+                    "Rows".to_string(),
+                    "Query".to_string(),
+                ]];
+                for (name, snapshot) in snapshots {
+                    let query = snapshot
+                        .query
+                        .split_whitespace()
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    let query = if query.chars().count() > 60 {
+                        format!("{}…", query.chars().take(59).collect::<String>())
+                    } else {
+                        query
+                    };
+                    rows.push(vec![
+                        name,
+                        snapshot.age(),
+                        snapshot.row_count().to_string(),
+                        query,
+                    ]);
+                }
+                Ok(CommandResult::Output(
+                    crate::format::format_query_results_psql(&rows),
+                ))
+            }
+
+            Command::SaveSnapshot { name } => {
+                let db = database.lock().unwrap();
+                let (Some(query), true) = (db.last_row_query(), db.last_result().len() > 1) else {
+                    return Ok(CommandResult::Error(
+                        "No result to save yet. Run a query that returns rows first.".to_string(),
+                    ));
+                };
+                // \\snapshot diff runs the query again
+                if !crate::ai::streaming::is_select_query(query) {
+                    return Ok(CommandResult::Error(
+                        "Only the result of a query that reads data can be saved as a snapshot: \\snapshot diff runs it again".to_string(),
+                    ));
+                }
+                let connection = db
+                    .get_connection_info()
+                    .map(|info| crate::password_sanitizer::sanitize_connection_url(&info.to_url()))
+                    .unwrap_or_default();
+                let snapshot = crate::snapshot::Snapshot::new(
+                    &db.maybe_add_limit(query),
+                    &connection,
+                    db.last_result(),
+                );
+                match crate::snapshot::save(name, &snapshot) {
+                    Ok(()) => Ok(CommandResult::Output(format!(
+                        "Saved {} rows as snapshot '{name}'. Compare later with \\snapshot diff {name}.",
+                        snapshot.row_count()
+                    ))),
+                    Err(e) => Ok(CommandResult::Error(e)),
+                }
+            }
+
+            Command::DiffSnapshot { name, key } => {
+                let snapshot = match crate::snapshot::load(name) {
+                    Ok(snapshot) => snapshot,
+                    Err(e) => return Ok(CommandResult::Error(e)),
+                };
+                // Snapshots saved before save checked the query
+                if !crate::ai::streaming::is_select_query(&snapshot.query) {
+                    return Ok(CommandResult::Error(format!(
+                        "Snapshot '{name}' was taken from a statement that changes data, which is not run again"
+                    )));
+                }
+                let db = database.lock().unwrap();
+                let Some(client) = db.get_database_client() else {
+                    return Ok(CommandResult::Error(
+                        "Not connected to a database".to_string(),
+                    ));
+                };
+                let connection = db
+                    .get_connection_info()
+                    .map(|info| crate::password_sanitizer::sanitize_connection_url(&info.to_url()))
+                    .unwrap_or_default();
+                let mut output = String::new();
+                if !snapshot.connection.is_empty() && snapshot.connection != connection {
+                    output.push_str(&format!(
+                        "Warning: snapshot '{name}' was taken on {}, not on the current connection {connection}.\n",
+                        snapshot.connection
+                    ));
+                }
+                // Straight to the server: neither the result cache nor the
+                // column selector stands between the query and its rows
+                let current = match client.execute_query(&snapshot.query).await {
+                    Ok(rows) => rows,
+                    Err(e) => {
+                        return Ok(CommandResult::Error(format!("{output}Query failed: {e}")));
+                    }
                 };
+                let drift = match crate::snapshot::diff(&snapshot.data, &current, key) {
+                    Ok(drift) => drift,
+                    Err(e) => return Ok(CommandResult::Error(e)),
+                };
+                output.push_str(&format!(
+                    "Snapshot '{name}' taken {}: {} rows, now {}.\n",
+                    snapshot.age(),
+                    snapshot.row_count(),
+                    current.len().saturating_sub(1)
+                ));
+                if drift.table.len() > 1 {
+                    let shown = crate::masking::masked(&drift.table);
+                    output.push_str(&crate::format::format_query_results_psql(
+                        shown.as_deref().unwrap_or(&drift.table),
+                    ));
+                }
+                output.push_str(&drift.summary());
+                Ok(CommandResult::Output(output))
+            }
+
+            Command::DeleteSnapshot { name } => match crate::snapshot::delete(name) {
+                Ok(()) => Ok(CommandResult::Output(format!("Deleted snapshot '{name}'."))),
+                Err(e) => Ok(CommandResult::Error(e)),
+            },
+
+            Command::ShowMasking => {
+                let rules = crate::masking::rule_count();
                 Ok(CommandResult::Output(format!(
-                    "Expanded display is {status}."
+                    "Result masking is {} ({rules} rule{} from [masking]).",
+                    if crate::masking::is_enabled() {
+                        "on"
+                    } else {
+                        "off"
+                    },
+                    if rules == 1 { "" } else { "s" }
                 )))
             }
 
+            Command::SetMasking { enabled } => {
+                crate::masking::set_enabled(*enabled);
+                Ok(CommandResult::Output(if *enabled {
+                    "Result masking on: displayed values matching the [masking] rules are hidden."
+                        .to_string()
+                } else {
+                    "Result masking off.".to_string()
+                }))
+            }
+
             Command::ToggleExplainMode => {
                 let mut db = database.lock().unwrap();
                 db.toggle_explain_mode();
@@ -1317,10 +3079,14 @@ impl CommandExecutor for Command {
                     }
 
                     // Check database compatibility
-                    if !matches!(db_type, crate::database::DatabaseType::PostgreSQL) {
+                    if !matches!(
+                        db_type,
+                        crate::database::DatabaseType::PostgreSQL
+                            | crate::database::DatabaseType::MongoDB
+                    ) {
                         db.toggle_explain_tui_mode(); // Turn it back off
                         return Ok(CommandResult::Error(format!(
-                            "Explain TUI mode currently only supports PostgreSQL. Current database: {}",
+                            "Explain TUI mode currently only supports PostgreSQL and MongoDB. Current database: {}",
                             db_type.display_name()
                         )));
                     }
@@ -1337,6 +3103,84 @@ impl CommandExecutor for Command {
                 }
             }
 
+            Command::ExportExplainPlan { filename, format } => {
+                let db = database.lock().unwrap();
+                let Some(plan) = db.get_last_json_plan() else {
+                    return Ok(CommandResult::Error(
+                        "No EXPLAIN plan captured yet. Run a query with \\ev on, or \\ef, first."
+                            .to_string(),
+                    ));
+                };
+                let Some(format) = format.or_else(|| {
+                    crate::explain_tui::PlanExportFormat::from_path(std::path::Path::new(filename))
+                }) else {
+                    return Ok(CommandResult::Error(format!(
+                        "Cannot tell the format from '{filename}': use a .folded, .dot or .json file, or name the format (\\ev export {filename} dot)"
+                    )));
+                };
+                let contents = match crate::explain_tui::render_plan(format, &plan) {
+                    Ok(contents) => contents,
+                    Err(e) => return Ok(CommandResult::Error(e)),
+                };
+                match std::fs::write(filename, contents) {
+                    Ok(()) => Ok(CommandResult::Output(format!(
+                        "Wrote the plan as {} to {filename}",
+                        format.name()
+                    ))),
+                    Err(e) => Ok(CommandResult::Error(format!(
+                        "Failed to write {filename}: {e}"
+                    ))),
+                }
+            }
+
+            Command::UploadExplainPlan { service } => {
+                let (plan, query) = {
+                    let db = database.lock().unwrap();
+                    (
+                        db.get_last_json_plan(),
+                        db.last_plan_query().map(str::to_string),
+                    )
+                };
+                let Some(plan) = plan else {
+                    return Ok(CommandResult::Error(
+                        "No EXPLAIN plan captured yet. Run a query with \\ev on, or \\ef, first."
+                            .to_string(),
+                    ));
+                };
+                if crate::explain_tui::parse_postgresql_plan(
+                    &serde_json::from_str(&plan).unwrap_or_default(),
+                )
+                .is_none()
+                {
+                    return Ok(CommandResult::Error(format!(
+                        "{} only visualizes PostgreSQL plans",
+                        service.host()
+                    )));
+                }
+                let prompt = format!(
+                    "Publish the plan{} on {}? Anyone with the link can read it.",
+                    if query.is_some() {
+                        " and its query"
+                    } else {
+                        ""
+                    },
+                    service.host()
+                );
+                match inquire::Confirm::new(&prompt).with_default(false).prompt() {
+                    Ok(true) => {}
+                    Ok(false)
+                    | Err(
+                        inquire::InquireError::OperationCanceled
+                        | inquire::InquireError::OperationInterrupted,
+                    ) => return Ok(CommandResult::Output("Nothing uploaded.".to_string())),
+                    Err(e) => return Ok(CommandResult::Error(format!("Input error: {e}"))),
+                }
+                match crate::explain_tui::upload_plan(*service, &plan, query.as_deref()).await {
+                    Ok(url) => Ok(CommandResult::Output(format!("Plan published: {url}"))),
+                    Err(e) => Ok(CommandResult::Error(e)),
+                }
+            }
+
             Command::ShowConfig => {
                 // Never Debug-dump the whole Config: it embeds decrypted vault
                 // credentials and other secrets in memory. The schema-driven
@@ -1374,17 +3218,154 @@ impl CommandExecutor for Command {
                 }
             }
 
-            Command::ConfigEdit => match crate::config_editor::edit_in_editor(config) {
+            Command::ConfigUnset { key } => match crate::config_editor::unset_value(config, key) {
                 Ok(message) => Ok(CommandResult::Output(message)),
                 Err(e) => Ok(CommandResult::Error(e)),
             },
 
-            Command::SchemaViewer => {
+            Command::ConfigEdit => match crate::config_editor::edit_in_editor(config) {
+                Ok(message) => {
+                    database.lock().unwrap().apply_config(config);
+                    Ok(CommandResult::Output(message))
+                }
+                Err(e) => Ok(CommandResult::Error(e)),
+            },
+
+            Command::ReloadConfig => match crate::config_editor::reload_from_disk(config) {
+                Ok(changes) => {
+                    database.lock().unwrap().apply_config(config);
+                    Ok(CommandResult::Output(crate::config_editor::reload_report(
+                        &changes,
+                    )))
+                }
+                Err(e) => Ok(CommandResult::Error(e)),
+            },
+
+            Command::ListSnippets => Ok(CommandResult::Output(crate::snippets::list_snippets(
+                config,
+            ))),
+
+            Command::ShowKeys => {
+                let mut rows = vec![vec![
+                    "key".to_string(),
+                    "action".to_string(),
+                    "source".to_string(),
+                ]];
+                rows.extend(crate::keybindings::active(&config.keybindings));
+                Ok(CommandResult::Output(
+                    crate::format::format_query_results_psql(&rows),
+                ))
+            }
+
+            Command::AddSnippet { trigger, expansion } => {
+                match crate::snippets::add_snippet(config, trigger, expansion) {
+                    Ok(message) => Ok(CommandResult::Output(message)),
+                    Err(e) => Ok(CommandResult::Error(e)),
+                }
+            }
+
+            Command::RemoveSnippet { trigger } => {
+                match crate::snippets::remove_snippet(config, trigger) {
+                    Ok(message) => Ok(CommandResult::Output(message)),
+                    Err(e) => Ok(CommandResult::Error(e)),
+                }
+            }
+
+            Command::ShowTablePrefs => {
+                let db = database.lock().unwrap();
+                let mut results = vec![
+                    ["Table", "Expanded", "Columns", "Sort"]
+                        .map(String::from)
+                        .to_vec(),
+                ];
+                for (key, prefs) in db.table_prefs().entries() {
+                    results.push(vec![
+                        table_prefs_label(key),
+                        prefs.expanded.map_or(String::new(), |on| {
+                            if on { "on" } else { "off" }.to_string()
+                        }),
+                        prefs.columns.join(", "),
+                        prefs.sort.as_ref().map_or(String::new(), |sort| {
+                            let direction = if sort.descending { "desc" } else { "asc" };
+                            format!("{} {direction}", sort.column)
+                        }),
+                    ]);
+                }
+                if results.len() == 1 {
+                    return Ok(CommandResult::Output(
+                        "No remembered display settings. Toggle \\x or pick columns after a query, or use \\prefs sort <column>.".to_string(),
+                    ));
+                }
+                Ok(CommandResult::Output(
+                    crate::format::format_query_results_psql(&results),
+                ))
+            }
+
+            Command::ClearTablePrefs { table } => {
+                let mut db = database.lock().unwrap();
+                match table.as_deref() {
+                    Some("all") => {
+                        let count = db.table_prefs_mut().clear();
+                        Ok(CommandResult::Output(format!(
+                            "Forgot display settings for {count} table(s)."
+                        )))
+                    }
+                    table => {
+                        let Some(key) = table
+                            .map(crate::table_prefs::key_for_table)
+                            .or_else(|| db.last_prefs_key().map(str::to_string))
+                        else {
+                            return Ok(CommandResult::Error(
+                                "No table to clear. Run a query first, or name one: \\prefs clear <table>|all".to_string(),
+                            ));
+                        };
+                        let label = table_prefs_label(&key);
+                        if db.table_prefs_mut().remove(&key) {
+                            Ok(CommandResult::Output(format!(
+                                "Forgot display settings for {label}."
+                            )))
+                        } else {
+                            Ok(CommandResult::Error(format!(
+                                "No display settings remembered for {label}."
+                            )))
+                        }
+                    }
+                }
+            }
+
+            Command::SetTablePrefsSort { column, descending } => {
+                let mut db = database.lock().unwrap();
+                let Some(key) = db.last_prefs_key().map(str::to_string) else {
+                    return Ok(CommandResult::Error(
+                        "No table to sort. Run a query first.".to_string(),
+                    ));
+                };
+                let label = table_prefs_label(&key);
+                let sort = column.as_ref().map(|column| crate::table_prefs::SortPref {
+                    column: column.clone(),
+                    descending: *descending,
+                });
+                let message = match &sort {
+                    Some(sort) => format!(
+                        "Results from {label} will be sorted by {} {}.",
+                        sort.column,
+                        if sort.descending { "desc" } else { "asc" }
+                    ),
+                    None => format!("Sort removed for {label}."),
+                };
+                db.table_prefs_mut().update(&key, |prefs| prefs.sort = sort);
+                Ok(CommandResult::Output(message))
+            }
+
+            Command::SchemaViewer | Command::SchemaBrowser => {
+                let browser = matches!(self, Command::SchemaBrowser);
+                let label = if browser { "browser" } else { "viewer" };
+
                 // Check if TUI can run
                 if !crate::explain_tui::can_run_tui() {
                     if let Some(reason) = crate::explain_tui::tui_unavailable_reason() {
                         return Ok(CommandResult::Error(format!(
-                            "Cannot launch schema viewer: {reason}"
+                            "Cannot launch schema {label}: {reason}"
                         )));
                     }
                 }
@@ -1394,10 +3375,19 @@ impl CommandExecutor for Command {
                 let schema_data = crate::schema_tui::load_schema_data(&mut db).await;
                 drop(db);
 
+                let run = if browser {
+                    crate::schema_tui::run_schema_browser
+                } else {
+                    crate::schema_tui::run_schema_tui
+                };
                 match schema_data {
-                    Ok(data) => match crate::schema_tui::run_schema_tui(data, database.clone()) {
+                    Ok(data) => match run(data, database.clone()) {
+                        // Picked up by the REPL loop, which pre-fills the next prompt
+                        Ok(crate::schema_tui::TuiResult::Insert(text)) => {
+                            Ok(CommandResult::Output(format!("__INSERT_INPUT__{text}")))
+                        }
                         Ok(_) => Ok(CommandResult::Continue),
-                        Err(e) => Ok(CommandResult::Error(format!("Schema viewer error: {e}"))),
+                        Err(e) => Ok(CommandResult::Error(format!("Schema {label} error: {e}"))),
                     },
                     Err(e) => Ok(CommandResult::Error(format!(
                         "Failed to load schema data: {e}"
@@ -1405,18 +3395,214 @@ impl CommandExecutor for Command {
                 }
             }
 
-            Command::ListDatabases => {
+            Command::EntityRelationshipDiagram {
+                target,
+                format,
+                output,
+            } => {
                 let mut db = database.lock().unwrap();
-                match db.list_databases().await {
-                    Ok(results) => {
-                        if results.is_empty() {
-                            Ok(CommandResult::Output("No databases found.".to_string()))
-                        } else {
-                            let output = if db.is_expanded_display() {
-                                let tables = crate::format::format_query_results_expanded(&results);
-                                tables
-                                    .into_iter()
-                                    .map(|t| t.to_string())
+                let graph = match crate::schema_graph::load_schema_graph(&mut db, target.as_deref())
+                    .await
+                {
+                    Ok(graph) => graph,
+                    Err(e) => return Ok(CommandResult::Error(e)),
+                };
+                let rendered = graph.render(*format);
+
+                match output {
+                    Some(filename) => match std::fs::write(filename, &rendered) {
+                        Ok(_) => Ok(CommandResult::Output(format!(
+                            "{format} diagram exported to {filename} {}",
+                            graph.summary()
+                        ))),
+                        Err(e) => Ok(CommandResult::Error(format!(
+                            "Failed to write to {filename}: {e}"
+                        ))),
+                    },
+                    None => Ok(CommandResult::Output(rendered)),
+                }
+            }
+
+            Command::Whoami => {
+                let mut db = database.lock().unwrap();
+                let report = crate::whoami::collect_identity(&mut db, config).await;
+                Ok(CommandResult::Output(report.render()))
+            }
+
+            Command::SetRole { role } => {
+                let mut db = database.lock().unwrap();
+                match db.set_role(Some(role)).await {
+                    Ok(()) => {
+                        *prompt = DbPrompt::with_config(
+                            db.prompt_username(),
+                            db.get_current_db(),
+                            config.multiline_prompt_indicator.clone(),
+                        );
+                        Ok(CommandResult::Output(format!(
+                            "Now acting as role '{role}'. Use \\resetrole to go back."
+                        )))
+                    }
+                    Err(e) => Ok(CommandResult::Error(format!(
+                        "Failed to set role '{role}': {e}"
+                    ))),
+                }
+            }
+
+            Command::ResetRole => {
+                let mut db = database.lock().unwrap();
+                if db.active_role().is_none() {
+                    return Ok(CommandResult::Output(format!(
+                        "Already acting as login role '{}'.",
+                        db.get_username()
+                    )));
+                }
+                match db.set_role(None).await {
+                    Ok(()) => {
+                        *prompt = DbPrompt::with_config(
+                            db.prompt_username(),
+                            db.get_current_db(),
+                            config.multiline_prompt_indicator.clone(),
+                        );
+                        Ok(CommandResult::Output(format!(
+                            "Back to login role '{}'.",
+                            db.get_username()
+                        )))
+                    }
+                    Err(e) => Ok(CommandResult::Error(format!("Failed to reset role: {e}"))),
+                }
+            }
+
+            Command::ShowSsl => {
+                let mut db = database.lock().unwrap();
+                let report = crate::ssl_report::collect_ssl(&mut db).await;
+                Ok(CommandResult::Output(report.render()))
+            }
+
+            Command::ReloadSsl => {
+                let mut db = database.lock().unwrap();
+                match crate::client_cert::reload(&mut db, config.tls.cert_expiry_warn_days).await {
+                    Ok(notice) => Ok(CommandResult::Output(notice)),
+                    Err(e) => Ok(CommandResult::Error(e)),
+                }
+            }
+
+            Command::Stats { reset } => {
+                let mut db = database.lock().unwrap();
+                if *reset {
+                    db.reset_stats();
+                    return Ok(CommandResult::Output(
+                        "Session statistics reset.".to_string(),
+                    ));
+                }
+                let rows = db.stats().to_rows();
+                Ok(CommandResult::Output(
+                    crate::format::format_query_results_psql(&rows),
+                ))
+            }
+
+            Command::ShowResultCache => {
+                let mut db = database.lock().unwrap();
+                Ok(CommandResult::Output(db.result_cache_mut().status()))
+            }
+
+            Command::SetResultCache { enabled } => {
+                let mut db = database.lock().unwrap();
+                db.result_cache_mut().set_enabled(*enabled);
+                Ok(CommandResult::Output(if *enabled {
+                    "Result cache on: repeated SELECTs are served from memory until the TTL passes."
+                        .to_string()
+                } else {
+                    "Result cache off.".to_string()
+                }))
+            }
+
+            Command::ClearResultCache => {
+                let mut db = database.lock().unwrap();
+                let count = db.result_cache_mut().clear();
+                Ok(CommandResult::Output(format!(
+                    "Dropped {count} cached result(s)."
+                )))
+            }
+
+            Command::ShowQueue => {
+                let mut db = database.lock().unwrap();
+                Ok(CommandResult::Output(db.offline_queue_mut().list()))
+            }
+
+            Command::ClearQueue => {
+                let mut db = database.lock().unwrap();
+                let count = db.offline_queue_mut().clear();
+                Ok(CommandResult::Output(format!(
+                    "Dropped {count} queued statement(s)."
+                )))
+            }
+
+            Command::RunQueue => {
+                let mut db = database.lock().unwrap();
+                if db.offline_queue_mut().is_empty() {
+                    Ok(CommandResult::Output("The queue is empty.".to_string()))
+                } else if db.idle_state_mut().is_lost() {
+                    Ok(CommandResult::Error(
+                        "The connection is still down; the queue runs once it is back.".to_string(),
+                    ))
+                } else {
+                    // The REPL runs them with the usual result display
+                    Ok(CommandResult::Output("__QUEUE_RUN__".to_string()))
+                }
+            }
+
+            Command::RefreshSchema => {
+                let started = std::time::Instant::now();
+                match crate::schema_cache::refresh(database).await {
+                    Ok(summary) => Ok(CommandResult::Output(format!(
+                        "Schema cache refreshed: {} table(s), columns of {} table(s) ({:.1}s).",
+                        summary.tables,
+                        summary.column_lists,
+                        started.elapsed().as_secs_f64()
+                    ))),
+                    Err(e) => Ok(CommandResult::Error(format!(
+                        "Failed to refresh the schema cache: {e}"
+                    ))),
+                }
+            }
+
+            Command::ShowQueryTags => {
+                let db = database.lock().unwrap();
+                Ok(CommandResult::Output(match db.query_tag_comment() {
+                    Some(comment) => format!("Statements are tagged with: {comment}"),
+                    None => "Statements are not tagged. Set [observability] query_tags = true or add tags with \\tag key=value.".to_string(),
+                }))
+            }
+
+            Command::SetQueryTags { tags } => {
+                let mut db = database.lock().unwrap();
+                for (key, value) in tags {
+                    db.query_tags_mut().set(key, value);
+                }
+                Ok(CommandResult::Output(match db.query_tag_comment() {
+                    Some(comment) => format!("Statements are tagged with: {comment}"),
+                    None => "Statements are not tagged.".to_string(),
+                }))
+            }
+
+            Command::ClearQueryTags => {
+                let mut db = database.lock().unwrap();
+                let count = db.query_tags_mut().clear();
+                Ok(CommandResult::Output(format!("Removed {count} tag(s).")))
+            }
+
+            Command::ListDatabases => {
+                let mut db = database.lock().unwrap();
+                match db.list_databases().await {
+                    Ok(results) => {
+                        if results.is_empty() {
+                            Ok(CommandResult::Output("No databases found.".to_string()))
+                        } else {
+                            let output = if db.is_expanded_display() {
+                                let tables = crate::format::format_query_results_expanded(&results);
+                                tables
+                                    .into_iter()
+                                    .map(|t| t.to_string())
                                     .collect::<Vec<_>>()
                                     .join("\n")
                             } else {
@@ -1497,11 +3683,15 @@ impl CommandExecutor for Command {
             }
 
             Command::ConnectDatabase { database_name } => {
+                // Another server: the REPL rebuilds the whole connection
+                if let Some(url) = reconnect_url(database_name, config) {
+                    return Ok(CommandResult::Output(format!("__RECONNECT__{url}")));
+                }
                 let mut db = database.lock().unwrap();
                 match db.connect_to_db(database_name).await {
                     Ok(_) => {
                         // Update prompt with new database name
-                        let username = db.get_username().to_string();
+                        let username = db.prompt_username();
                         let new_db_name = db.get_current_db();
                         *prompt = DbPrompt::with_config(
                             username,
@@ -1518,6 +3708,97 @@ impl CommandExecutor for Command {
                 }
             }
 
+            Command::Schema { name: None } => {
+                let db = database.lock().unwrap();
+                match db.current_schema() {
+                    Some(schema) => Ok(CommandResult::Output(format!(
+                        "Current schema is '{schema}'."
+                    ))),
+                    None => Ok(CommandResult::Error(format!(
+                        "{} has no schemas to switch between.",
+                        db.get_database_type().display_name()
+                    ))),
+                }
+            }
+
+            Command::Schema { name: Some(name) } => {
+                let mut db = database.lock().unwrap();
+                match db.set_schema(name).await {
+                    Ok(()) => {
+                        // MySQL and ClickHouse switch database, which the prompt shows
+                        *prompt = DbPrompt::with_config(
+                            db.prompt_username(),
+                            db.get_current_db(),
+                            config.multiline_prompt_indicator.clone(),
+                        );
+                        Ok(CommandResult::Output(format!(
+                            "Switched to schema '{name}'."
+                        )))
+                    }
+                    Err(e) => Ok(CommandResult::Error(format!(
+                        "Failed to switch to schema '{name}': {e}"
+                    ))),
+                }
+            }
+
+            Command::Warehouse { name: None } => {
+                let db = database.lock().unwrap();
+                match db.current_warehouse() {
+                    Some(warehouse) => Ok(CommandResult::Output(format!(
+                        "Current warehouse is '{warehouse}'."
+                    ))),
+                    None if db.get_database_type() == DatabaseType::Snowflake => {
+                        Ok(CommandResult::Output("No warehouse selected.".to_string()))
+                    }
+                    None => Ok(CommandResult::Error(format!(
+                        "{} has no warehouses to switch between.",
+                        db.get_database_type().display_name()
+                    ))),
+                }
+            }
+
+            Command::Warehouse { name: Some(name) } => {
+                let db = database.lock().unwrap();
+                match db.set_warehouse(name).await {
+                    Ok(()) => Ok(CommandResult::Output(format!(
+                        "Switched to warehouse '{}'.",
+                        db.current_warehouse().unwrap_or_else(|| name.clone())
+                    ))),
+                    Err(e) => Ok(CommandResult::Error(format!(
+                        "Failed to switch to warehouse '{name}': {e}"
+                    ))),
+                }
+            }
+
+            Command::ListSchemas => {
+                let db = database.lock().unwrap();
+                match db.schema_table_counts().await {
+                    Ok(counts) if counts.is_empty() => {
+                        Ok(CommandResult::Output("No schemas found.".to_string()))
+                    }
+                    Ok(counts) => {
+                        let current = db.current_schema();
+                        let mut rows = vec![vec![
+                            "Schema".to_string(),
+                            "Tables".to_string(),
+                            "Current".to_string(),
+                        ]];
+                        for (schema, tables) in counts {
+                            let marker = if current.as_deref() == Some(schema.as_str()) {
+                                "*"
+                            } else {
+                                ""
+                            };
+                            rows.push(vec![schema, tables.to_string(), marker.to_string()]);
+                        }
+                        Ok(CommandResult::Output(
+                            crate::format::format_query_results_psql(&rows),
+                        ))
+                    }
+                    Err(e) => Ok(CommandResult::Error(format!("Failed to list schemas: {e}"))),
+                }
+            }
+
             Command::ListSessions => {
                 let sessions = config.list_sessions();
                 if sessions.is_empty() {
@@ -1529,21 +3810,29 @@ impl CommandExecutor for Command {
                     output.push_str("Saved sessions:\n");
                     for (name, session) in sessions.iter() {
                         let db_type = session.database_type.display_name();
+                        let group = session
+                            .group
+                            .as_ref()
+                            .map(|group| format!(" [{group}]"))
+                            .unwrap_or_default();
                         if session.database_type.is_file_based() {
                             if let Some(ref file_path) = session.file_path {
-                                output.push_str(&format!("  {name} - {file_path} ({db_type})\n"));
+                                output.push_str(&format!(
+                                    "  {name} - {file_path} ({db_type}){group}\n"
+                                ));
                             } else {
-                                output.push_str(&format!("  {name} - SQLite (no path)\n"));
+                                output.push_str(&format!("  {name} - SQLite (no path){group}\n"));
                             }
                         } else {
                             output.push_str(&format!(
-                                "  {} - {}@{}:{}/{} ({})\n",
+                                "  {} - {}@{}:{}/{} ({}){}\n",
                                 name,
                                 session.user,
                                 session.host,
                                 session.port,
                                 session.dbname,
-                                db_type
+                                db_type,
+                                group
                             ));
                         }
                     }
@@ -1592,6 +3881,7 @@ impl CommandExecutor for Command {
             Command::SaveSession {
                 name,
                 password_command,
+                group,
             } => {
                 // Extract connection info from database and use the proper save method
                 let db = database.lock().unwrap();
@@ -1608,13 +3898,16 @@ impl CommandExecutor for Command {
                     name,
                     connection_info,
                     password_command.as_deref(),
+                    group.as_deref(),
                 ) {
                     Ok(_) => {
-                        let suffix = if password_command.is_some() {
-                            " Password command configured."
-                        } else {
-                            ""
-                        };
+                        let mut suffix = String::new();
+                        if let Some(group) = group {
+                            suffix.push_str(&format!(" Added to group '{group}'."));
+                        }
+                        if password_command.is_some() {
+                            suffix.push_str(" Password command configured.");
+                        }
                         Ok(CommandResult::Output(format!(
                             "Session '{name}' saved successfully.{suffix}"
                         )))
@@ -1718,11 +4011,24 @@ impl CommandExecutor for Command {
                     NamedQueryScope::Session(session_id)
                 };
 
+                // Reject malformed templates before anything else
+                if let Err(e) = crate::named_queries::validate_template(query) {
+                    return Ok(CommandResult::Error(format!(
+                        "Invalid query template: {e}\nQuery not saved."
+                    )));
+                }
+
                 // Test query before saving if enabled
                 if config.test_named_query_before_saving {
                     let mut db = database.lock().unwrap();
+                    // Templates are tested with every optional block left out
+                    let test_query = crate::named_queries::render_template(
+                        query,
+                        &std::collections::HashMap::new(),
+                    )
+                    .unwrap_or_else(|_| query.clone());
                     // Try to execute the query in a transaction (rollback to avoid side effects)
-                    match db.test_query_execution(query).await {
+                    match db.test_query_execution(&test_query).await {
                         Ok(_) => {
                             // Query is valid, proceed with saving
                         }
@@ -1818,10 +4124,19 @@ impl CommandExecutor for Command {
                 ) {
                     Some(named_query) => {
                         let mut db = database.lock().unwrap();
-                        // Apply parameter substitution
+                        // Apply template blocks and parameter substitution
                         let args_refs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
-                        let final_query =
-                            crate::named_queries::process_query(&named_query.query, &args_refs);
+                        let final_query = match crate::named_queries::expand_named_query(
+                            &named_query.query,
+                            &args_refs,
+                        ) {
+                            Ok(query) => query,
+                            Err(e) => {
+                                return Ok(CommandResult::Error(format!(
+                                    "Invalid template in named query '{name}': {e}"
+                                )));
+                            }
+                        };
 
                         // Execute the query
                         match db.execute_query(&final_query).await {
@@ -1886,6 +4201,13 @@ impl CommandExecutor for Command {
                 ))),
             },
 
+            Command::Rerun => Ok(CommandResult::Error(
+                "\\g is only available in the interactive shell".to_string(),
+            )),
+            Command::Watch { .. } => Ok(CommandResult::Error(
+                "\\watch is only available in the interactive shell".to_string(),
+            )),
+
             Command::EditMultiline => {
                 // Launch external editor with current script content
                 match crate::script::edit_multiline_script(last_script) {
@@ -1982,6 +4304,45 @@ impl CommandExecutor for Command {
                 )),
             },
 
+            Command::Paste => {
+                let text = match arboard::Clipboard::new().and_then(|mut c| c.get_text()) {
+                    Ok(text) => text,
+                    Err(e) => {
+                        return Ok(CommandResult::Error(format!(
+                            "Error reading the clipboard: {e}"
+                        )));
+                    }
+                };
+                let text = text.trim();
+                if text.is_empty() {
+                    return Ok(CommandResult::Error(
+                        "The clipboard holds no text.".to_string(),
+                    ));
+                }
+                // Backslash commands are placed as copied
+                let formatted = if text.starts_with('\\') {
+                    text.to_string()
+                } else {
+                    let db_type = database.lock().unwrap().get_database_type();
+                    crate::sql_formatter::format_sql(text, &db_type)
+                };
+
+                if crate::config_editor::can_run_interactive() {
+                    eprintln!("{formatted}\n");
+                    let confirmed = inquire::Confirm::new("Place it in the edit buffer?")
+                        .with_default(true)
+                        .prompt()
+                        .unwrap_or(false);
+                    if !confirmed {
+                        return Ok(CommandResult::Output("\\paste cancelled.".to_string()));
+                    }
+                }
+                // Picked up by the REPL loop, which pre-fills the next prompt
+                Ok(CommandResult::Output(format!(
+                    "__INSERT_INPUT__{formatted}"
+                )))
+            }
+
             Command::CopyExplainPlan => {
                 use arboard::Clipboard;
                 let db = database.lock().unwrap();
@@ -2043,6 +4404,90 @@ impl CommandExecutor for Command {
                 }
             }
 
+            Command::ShowFunction {
+                name,
+                numbered,
+                edit,
+            } => {
+                let mut db = database.lock().unwrap();
+                let db_type = db.get_database_type();
+                if db_type != DatabaseType::PostgreSQL {
+                    return Ok(CommandResult::Error(format!(
+                        "\\sf is only available for PostgreSQL. Current database: {}",
+                        db_type.display_name()
+                    )));
+                }
+                let color = std::io::IsTerminal::is_terminal(&std::io::stdout());
+                if *edit && !std::io::IsTerminal::is_terminal(&std::io::stdin()) {
+                    return Ok(CommandResult::Error(
+                        "\\sf --edit needs a terminal to open the editor".to_string(),
+                    ));
+                }
+                let source = match crate::function_source::fetch(&mut db, name).await {
+                    Ok(source) => source,
+                    Err(e) => return Ok(CommandResult::Error(e)),
+                };
+                if !*edit {
+                    return Ok(CommandResult::Output(format!(
+                        "__PAGE__{}",
+                        source.render(*numbered, color)
+                    )));
+                }
+
+                let edited = match crate::function_source::edit(&source.definition) {
+                    Ok(edited) => edited,
+                    Err(e) => return Ok(CommandResult::Error(format!("Editor error: {e}"))),
+                };
+                if edited.trim() == source.definition.trim() {
+                    return Ok(CommandResult::Output(format!(
+                        "No changes; {} left as it was.",
+                        source.signature
+                    )));
+                }
+                if !crate::function_source::is_create_or_replace(&edited) {
+                    return Ok(CommandResult::Error(
+                        "The edited text no longer starts with CREATE OR REPLACE; nothing was run"
+                            .to_string(),
+                    ));
+                }
+                println!(
+                    "{}",
+                    crate::function_source::format_diff(
+                        &crate::function_source::line_diff(&source.definition, &edited),
+                        color
+                    )
+                );
+                let prompt = format!("Replace {}?", source.signature);
+                match inquire::Confirm::new(&prompt).with_default(false).prompt() {
+                    Ok(true) => {}
+                    Ok(false)
+                    | Err(
+                        inquire::InquireError::OperationCanceled
+                        | inquire::InquireError::OperationInterrupted,
+                    ) => {
+                        *last_script = edited;
+                        return Ok(CommandResult::Output(
+                            "Nothing replaced; the edited text is in the buffer (\\ed)."
+                                .to_string(),
+                        ));
+                    }
+                    Err(e) => return Ok(CommandResult::Error(format!("Input error: {e}"))),
+                }
+                match db.execute_query(&edited).await {
+                    Ok(_) => Ok(CommandResult::Output(format!(
+                        "Replaced {}.",
+                        source.signature
+                    ))),
+                    Err(e) => {
+                        // Keep the edit so a typo does not cost the whole change
+                        *last_script = edited;
+                        Ok(CommandResult::Error(format!(
+                            "CREATE OR REPLACE failed: {e}\nThe edited text is in the buffer (\\ed)."
+                        )))
+                    }
+                }
+            }
+
             Command::ListDockerContainers => match crate::docker::DockerClient::new() {
                 Ok(docker_client) => match docker_client.list_database_containers().await {
                     Ok(containers) => {
@@ -2114,635 +4559,1158 @@ impl CommandExecutor for Command {
                 }
             }
 
-            Command::ExplainExport { query, filename } => {
+            Command::Lint { query } => {
                 let mut db = database.lock().unwrap();
-                match db.execute_explain_query_formatted(query).await {
-                    Ok(results) => {
-                        let output = crate::format::format_query_results_psql(&results);
-                        match std::fs::write(filename, &output) {
-                            Ok(_) => Ok(CommandResult::Output(format!(
-                                "EXPLAIN results exported to {filename}"
-                            ))),
-                            Err(e) => Ok(CommandResult::Error(format!(
-                                "Failed to write to {filename}: {e}"
-                            ))),
-                        }
-                    }
-                    Err(e) => Ok(CommandResult::Error(format!(
-                        "Failed to explain query: {e}"
-                    ))),
+                let findings =
+                    crate::query_lint::lint_for_database(&mut db, query, &config.lint).await;
+                if findings.is_empty() {
+                    Ok(CommandResult::Output("No lint findings.".to_string()))
+                } else {
+                    Ok(CommandResult::Output(crate::query_lint::format_findings(
+                        &findings,
+                    )))
                 }
             }
 
-            Command::SetMultilineIndicator { indicator } => {
-                config.multiline_prompt_indicator = indicator.clone();
-                config
-                    .save_with_documentation()
-                    .map_err(|e| CommandError::DatabaseError(e.into()))?;
-                Ok(CommandResult::Output(format!(
-                    "Multiline indicator set to: {indicator} (will take effect on next restart)"
-                )))
+            Command::Format { query } => {
+                let db = database.lock().unwrap();
+                let Some(sql) = query.as_deref().or(db.last_query()) else {
+                    return Ok(CommandResult::Error(
+                        "No query has been run yet. Use \\fmt <query>".to_string(),
+                    ));
+                };
+                let formatted = crate::sql_formatter::format_sql(sql, &db.get_database_type());
+                if std::io::IsTerminal::is_terminal(&std::io::stdout()) {
+                    Ok(CommandResult::Output(crate::highlighter::highlight_sql(
+                        &formatted,
+                    )))
+                } else {
+                    Ok(CommandResult::Output(formatted))
+                }
             }
 
-            Command::TogglePager => {
-                config.pager_enabled = !config.pager_enabled;
-                config
-                    .save_with_documentation()
-                    .map_err(|e| CommandError::DatabaseError(e.into()))?;
-                let status = if config.pager_enabled {
-                    "enabled"
-                } else {
-                    "disabled"
-                };
-                Ok(CommandResult::Output(format!("Pager is now {status}.")))
+            Command::DryRun { query } => {
+                let mut db = database.lock().unwrap();
+                match crate::dry_run::run(&mut db, query).await {
+                    Ok(output) => Ok(CommandResult::Output(output)),
+                    Err(e) => Ok(CommandResult::Error(e)),
+                }
             }
 
-            Command::ToggleBanner => {
-                config.show_banner = !config.show_banner;
-                config
-                    .save_with_documentation()
-                    .map_err(|e| CommandError::DatabaseError(e.into()))?;
-                let status = if config.show_banner {
-                    "enabled"
-                } else {
-                    "disabled"
-                };
-                Ok(CommandResult::Output(format!("Banner is now {status}.")))
+            Command::Count { target } => {
+                let db = database.lock().unwrap();
+                match crate::count_query::run(&db, target.as_deref()).await {
+                    Ok(output) => Ok(CommandResult::Output(output)),
+                    Err(e) => Ok(CommandResult::Error(e)),
+                }
             }
 
-            Command::ToggleServerInfo => {
-                config.show_server_info = !config.show_server_info;
-                config
-                    .save_with_documentation()
-                    .map_err(|e| CommandError::DatabaseError(e.into()))?;
-                let status = if config.show_server_info {
-                    "enabled"
-                } else {
-                    "disabled"
-                };
-                Ok(CommandResult::Output(format!(
-                    "Server info display is now {status}."
-                )))
-            }
+            Command::InsertRow { table } => {
+                let mut db = database.lock().unwrap();
+                let db_type = db.get_database_type();
+                if !matches!(
+                    db_type,
+                    DatabaseType::PostgreSQL
+                        | DatabaseType::MySQL
+                        | DatabaseType::SQLite
+                        | DatabaseType::ClickHouse
+                ) {
+                    return Ok(CommandResult::Error(format!(
+                        "\\insertrow is only available for SQL databases. Current database: {}",
+                        db_type.display_name()
+                    )));
+                }
 
-            Command::ToggleAutocomplete => {
-                config.autocomplete_enabled = !config.autocomplete_enabled;
-                config
-                    .save_with_documentation()
-                    .map_err(|e| CommandError::DatabaseError(e.into()))?;
-                let status = if config.autocomplete_enabled {
-                    "enabled"
-                } else {
-                    "disabled"
+                let details = match table.split_once('.') {
+                    Some((schema, name)) if matches!(db_type, DatabaseType::PostgreSQL) => {
+                        db.get_table_details_in_schema(name, Some(schema)).await
+                    }
+                    _ => db.get_table_details(table).await,
+                };
+                let details = match details {
+                    Ok(details) => details,
+                    Err(e) => {
+                        return Ok(CommandResult::Error(format!(
+                            "Failed to read columns of {table}: {e}"
+                        )));
+                    }
                 };
+
+                let plan =
+                    match crate::insert_builder::build_interactively(table, &details, &db_type) {
+                        Ok(Some(plan)) => plan,
+                        Ok(None) => {
+                            return Ok(CommandResult::Output("Insert cancelled.".to_string()));
+                        }
+                        Err(e) => return Ok(CommandResult::Error(e)),
+                    };
+                for (done, statement) in plan.statements.iter().enumerate() {
+                    if let Err(e) = db.execute_query(statement).await {
+                        let note = if done > 0 {
+                            format!(" ({done} earlier statement(s) already ran)")
+                        } else {
+                            String::new()
+                        };
+                        return Ok(CommandResult::Error(format!("INSERT failed{note}: {e}")));
+                    }
+                }
                 Ok(CommandResult::Output(format!(
-                    "Autocomplete is now {status}."
+                    "Inserted {} row(s) into {table}.",
+                    plan.rows
                 )))
             }
 
-            Command::ToggleColumnSelection => {
+            Command::GenRows { table, count, yes } => {
                 let mut db = database.lock().unwrap();
-                let new_status = db.toggle_column_select_mode();
-                let status = if new_status { "enabled" } else { "disabled" };
-                Ok(CommandResult::Output(format!(
-                    "Column selection mode is now {status}."
-                )))
-            }
+                let db_type = db.get_database_type();
+                if !matches!(
+                    db_type,
+                    DatabaseType::PostgreSQL
+                        | DatabaseType::MySQL
+                        | DatabaseType::SQLite
+                        | DatabaseType::ClickHouse
+                ) {
+                    return Ok(CommandResult::Error(format!(
+                        "\\genrows is only available for SQL databases. Current database: {}",
+                        db_type.display_name()
+                    )));
+                }
+                let interactive = std::io::IsTerminal::is_terminal(&std::io::stdin());
+                if !yes && !interactive {
+                    return Ok(CommandResult::Error(
+                        "\\genrows asks before inserting; add --yes to run it without a terminal"
+                            .to_string(),
+                    ));
+                }
 
-            Command::SetColumnSelectionThreshold { threshold } => {
-                config.column_selection_threshold = *threshold;
-                config
-                    .save_with_documentation()
-                    .map_err(|e| CommandError::DatabaseError(e.into()))?;
-                Ok(CommandResult::Output(format!(
-                    "Column selection threshold set to: {threshold}"
-                )))
-            }
+                let mut generated =
+                    match crate::datagen::generate_rows(&mut db, table, *count).await {
+                        Ok(generated) => generated,
+                        Err(e) => return Ok(CommandResult::Error(e)),
+                    };
+                println!(
+                    "{}",
+                    crate::format::format_query_results_psql(
+                        &generated.preview(crate::datagen::PREVIEW_ROWS)
+                    )
+                );
+                if !yes {
+                    let prompt = format!("Insert {count} generated row(s) into {table}?");
+                    match inquire::Confirm::new(&prompt).with_default(true).prompt() {
+                        Ok(true) => {}
+                        Ok(false)
+                        | Err(
+                            inquire::InquireError::OperationCanceled
+                            | inquire::InquireError::OperationInterrupted,
+                        ) => return Ok(CommandResult::Output("Nothing inserted.".to_string())),
+                        Err(e) => return Ok(CommandResult::Error(format!("Input error: {e}"))),
+                    }
+                }
 
-            Command::ClearColumnViews => {
-                let mut db = database.lock().unwrap();
-                db.clear_column_views();
-                Ok(CommandResult::Output("Column views cleared.".to_string()))
+                let start = std::time::Instant::now();
+                match crate::datagen::insert(&mut db, table, &mut generated).await {
+                    Ok(inserted) => Ok(CommandResult::Output(format!(
+                        "Inserted {} row(s) into {table} in {:.1}s.",
+                        crate::query_progress::group_digits(inserted as i64),
+                        start.elapsed().as_secs_f64()
+                    ))),
+                    Err(e) => Ok(CommandResult::Error(e)),
+                }
             }
 
-            Command::ResetView => {
+            Command::PasteData { table, yes } => {
                 let mut db = database.lock().unwrap();
-                if db.is_explain_mode() {
-                    db.toggle_explain_mode();
+                let db_type = db.get_database_type();
+                if !matches!(
+                    db_type,
+                    DatabaseType::PostgreSQL
+                        | DatabaseType::MySQL
+                        | DatabaseType::SQLite
+                        | DatabaseType::ClickHouse
+                ) {
+                    return Ok(CommandResult::Error(format!(
+                        "\\pastedata is only available for SQL databases. Current database: {}",
+                        db_type.display_name()
+                    )));
                 }
-                if db.is_expanded_display() {
-                    db.toggle_expanded_display();
+                let interactive = std::io::IsTerminal::is_terminal(&std::io::stdin());
+                if !yes && !interactive {
+                    return Ok(CommandResult::Error(
+                        "\\pastedata asks before inserting; add --yes to run it without a terminal"
+                            .to_string(),
+                    ));
                 }
-                db.reset_column_view();
-                config.explain_mode_default = false;
-                config.expanded_display_default = false;
-                config
-                    .save_with_documentation()
-                    .map_err(|e| CommandError::DatabaseError(e.into()))?;
-                Ok(CommandResult::Output(
-                    "View settings reset to defaults.".to_string(),
-                ))
-            }
-
-            // Vector display commands
-            Command::SetVectorDisplayMode { mode } => {
-                use crate::vector_display::VectorDisplayMode;
+                let text = match arboard::Clipboard::new().and_then(|mut c| c.get_text()) {
+                    Ok(text) => text,
+                    Err(e) => {
+                        return Ok(CommandResult::Error(format!(
+                            "Error reading the clipboard: {e}"
+                        )));
+                    }
+                };
 
-                if mode.is_empty() {
-                    return Ok(CommandResult::Output(format!(
-                        "Current vector display mode: {}\nAvailable modes: {}",
-                        config.vector_display.display_mode,
-                        VectorDisplayMode::all_modes().join(", ")
+                let details = match crate::datagen::table_details(&mut db, table).await {
+                    Ok(details) => details,
+                    Err(e) => return Ok(CommandResult::Error(e)),
+                };
+                if details.columns.is_empty() {
+                    return Ok(CommandResult::Error(format!(
+                        "Table {table} has no columns"
                     )));
                 }
-
-                match mode.parse::<VectorDisplayMode>() {
-                    Ok(new_mode) => {
-                        config.vector_display.display_mode = new_mode;
-
-                        // Update global config so formatters can use it immediately
-                        crate::vector_display::set_global_vector_config(
-                            config.vector_display.clone(),
-                        );
-
-                        config
-                            .save_with_documentation()
-                            .map_err(|e| CommandError::DatabaseError(e.into()))?;
-                        Ok(CommandResult::Output(format!(
-                            "Vector display mode set to: {mode}"
-                        )))
+                let columns: Vec<crate::insert_builder::ColumnSpec> = details
+                    .columns
+                    .iter()
+                    .map(crate::insert_builder::ColumnSpec::from)
+                    .collect();
+                let pasted = match crate::paste_data::prepare(&text, &columns) {
+                    Ok(pasted) => pasted,
+                    Err(e) => return Ok(CommandResult::Error(e)),
+                };
+                println!(
+                    "{} row(s), {}-separated, columns mapped {}:\n{}",
+                    pasted.rows.len(),
+                    pasted.delimiter_name(),
+                    if pasted.header {
+                        "by header"
+                    } else {
+                        "by position"
+                    },
+                    pasted.mapping()
+                );
+                if !pasted.errors.is_empty() {
+                    return Ok(CommandResult::Error(pasted.error_report()));
+                }
+                println!(
+                    "{}",
+                    crate::format::format_query_results_psql(
+                        &pasted.preview(crate::datagen::PREVIEW_ROWS)
+                    )
+                );
+                if !yes {
+                    let prompt = format!("Insert {} row(s) into {table}?", pasted.rows.len());
+                    match inquire::Confirm::new(&prompt).with_default(true).prompt() {
+                        Ok(true) => {}
+                        Ok(false)
+                        | Err(
+                            inquire::InquireError::OperationCanceled
+                            | inquire::InquireError::OperationInterrupted,
+                        ) => return Ok(CommandResult::Output("Nothing inserted.".to_string())),
+                        Err(e) => return Ok(CommandResult::Error(format!("Input error: {e}"))),
                     }
-                    Err(e) => Ok(CommandResult::Error(e.to_string())),
+                }
+
+                let start = std::time::Instant::now();
+                match crate::paste_data::insert(&mut db, table, &pasted).await {
+                    Ok(inserted) => Ok(CommandResult::Output(format!(
+                        "Inserted {} row(s) into {table} in {:.1}s.",
+                        crate::query_progress::group_digits(inserted as i64),
+                        start.elapsed().as_secs_f64()
+                    ))),
+                    Err(e) => Ok(CommandResult::Error(e)),
                 }
             }
 
-            Command::ShowVectorDisplayConfig => {
-                let config_display = format!(
-                    "Vector Display Configuration:\n\
-                    • Display mode: {}\n\
-                    • Truncation length: {}\n\
-                    • Viz width: {}\n\
-                    • Show statistics: {}\n\
-                    • Dimension threshold: {}\n\
-                    • Show dimensions: {}",
-                    config.vector_display.display_mode,
-                    config.vector_display.truncation_length,
-                    config.vector_display.viz_width,
-                    config.vector_display.show_statistics,
-                    config.vector_display.dimension_threshold,
-                    config.vector_display.show_dimensions
-                );
-                Ok(CommandResult::Output(config_display))
+            Command::ExplainExport { query, filename } => {
+                let mut db = database.lock().unwrap();
+                match db.execute_explain_query_formatted(query).await {
+                    Ok(results) => {
+                        let output = crate::format::format_query_results_psql(&results);
+                        match std::fs::write(filename, &output) {
+                            Ok(_) => Ok(CommandResult::Output(format!(
+                                "EXPLAIN results exported to {filename}"
+                            ))),
+                            Err(e) => Ok(CommandResult::Error(format!(
+                                "Failed to write to {filename}: {e}"
+                            ))),
+                        }
+                    }
+                    Err(e) => Ok(CommandResult::Error(format!(
+                        "Failed to explain query: {e}"
+                    ))),
+                }
             }
 
-            Command::ToggleVectorStatistics => {
-                config.vector_display.show_statistics = !config.vector_display.show_statistics;
+            Command::Export {
+                format,
+                filename,
+                query,
+                resume,
+                chunk_rows,
+            } => {
+                let mut db = database.lock().unwrap();
+                let started = std::time::Instant::now();
+                let table = crate::export_chunks::whole_table(query);
+                if *resume && (filename == "-" || table.is_none()) {
+                    return Ok(CommandResult::Error(
+                        "--resume continues a whole-table export to a file: \\export <format> --resume <file> <table>"
+                            .to_string(),
+                    ));
+                }
+                // Whole tables with a primary key go in key-ordered chunks
+                if let Some(table) = table.filter(|_| filename != "-") {
+                    let export = crate::export_chunks::TableExport {
+                        format: *format,
+                        table,
+                        filename,
+                        chunk_rows: *chunk_rows,
+                        resume: *resume,
+                    };
+                    match crate::export_chunks::export_table(&mut db, &export).await {
+                        Ok(Some(rows)) => {
+                            return Ok(CommandResult::Output(format!(
+                                "{rows} row(s) exported to {filename} ({format}) in {:.2}s",
+                                started.elapsed().as_secs_f64()
+                            )));
+                        }
+                        Ok(None) => {}
+                        Err(e) => return Ok(CommandResult::Error(format!("Export failed: {e}"))),
+                    }
+                }
+                // `-` streams to stdout for piping (`-c '\export ndjson - ...' | jq`),
+                // so the summary goes to stderr
+                if filename == "-" {
+                    let out = std::io::BufWriter::new(std::io::stdout());
+                    return match crate::export::export_query(&mut db, *format, query, out).await {
+                        Ok(rows) => {
+                            eprintln!(
+                                "{rows} row(s) exported ({format}) in {:.2}s",
+                                started.elapsed().as_secs_f64()
+                            );
+                            Ok(CommandResult::Continue)
+                        }
+                        Err(e) => Ok(CommandResult::Error(format!("Export failed: {e}"))),
+                    };
+                }
 
-                // Update global config so formatters can use it immediately
-                crate::vector_display::set_global_vector_config(config.vector_display.clone());
+                let file = match std::fs::File::create(filename) {
+                    Ok(file) => file,
+                    Err(e) => {
+                        return Ok(CommandResult::Error(format!(
+                            "Failed to create {filename}: {e}"
+                        )));
+                    }
+                };
+                let out = std::io::BufWriter::new(file);
+                match crate::export::export_query(&mut db, *format, query, out).await {
+                    Ok(rows) => Ok(CommandResult::Output(format!(
+                        "{rows} row(s) exported to {filename} ({format}) in {:.2}s",
+                        started.elapsed().as_secs_f64()
+                    ))),
+                    Err(e) => {
+                        // A partial file would pass for a complete export
+                        let _ = std::fs::remove_file(filename);
+                        Ok(CommandResult::Error(format!("Export failed: {e}")))
+                    }
+                }
+            }
 
-                config
-                    .save_with_documentation()
-                    .map_err(|e| CommandError::DatabaseError(e.into()))?;
-                let status = if config.vector_display.show_statistics {
-                    "enabled"
-                } else {
-                    "disabled"
+            Command::SaveBlob {
+                row,
+                column,
+                filename,
+            } => {
+                let db = database.lock().unwrap();
+                let bytes = match db.last_binary_cells().get(*row, column) {
+                    Ok(bytes) => bytes,
+                    Err(e) => return Ok(CommandResult::Error(e)),
                 };
-                Ok(CommandResult::Output(format!(
-                    "Vector statistics display is now {status}."
-                )))
+                match std::fs::write(filename, bytes) {
+                    Ok(()) => Ok(CommandResult::Output(format!(
+                        "Wrote {} ({}) to {filename}",
+                        crate::format::format_bytes(bytes.len() as i64),
+                        crate::complex_display::sniff_binary_type(bytes).unwrap_or("binary data")
+                    ))),
+                    Err(e) => Ok(CommandResult::Error(format!(
+                        "Failed to write {filename}: {e}"
+                    ))),
+                }
             }
 
-            // Vault credential cache commands
-            Command::VaultCacheStatus => {
-                if !config.vault_credential_cache_enabled {
-                    return Ok(CommandResult::Output(
-                        "Vault credential caching is disabled.".to_string(),
+            Command::Gexec { query } => {
+                let mut db = database.lock().unwrap();
+                let Some(source) = query.as_deref().or(db.last_row_query()) else {
+                    return Ok(CommandResult::Error(
+                        "No query has returned rows yet. Run a SELECT that generates statements first, or use \\gexec <query>".to_string(),
+                    ));
+                };
+                let read_only = crate::ai::streaming::is_select_query(source);
+                if query.is_some() && !read_only {
+                    return Ok(CommandResult::Error(
+                        "\\gexec only runs queries that read data to generate its statements"
+                            .to_string(),
                     ));
                 }
-
-                let cached_creds = config.list_cached_vault_credentials();
-                if cached_creds.is_empty() {
-                    Ok(CommandResult::Output(
-                        "No vault credentials cached.".to_string(),
-                    ))
+                let Some(client) = db.get_database_client() else {
+                    return Ok(CommandResult::Error(
+                        "Not connected to a database".to_string(),
+                    ));
+                };
+                let statements = if read_only {
+                    // Again and without the default limit, so no statement is left out
+                    match client.execute_query(source).await {
+                        Ok(rows) => gexec_statements(&rows),
+                        Err(e) => return Ok(CommandResult::Error(format!("Query failed: {e}"))),
+                    }
                 } else {
-                    let mut output = format!(
-                        "Vault credential cache status (showing {} entries):\n",
-                        cached_creds.len()
-                    );
-                    let now = chrono::Utc::now();
+                    // The rows of an INSERT/UPDATE/DELETE ... RETURNING: running
+                    // it again would change the data a second time
+                    gexec_statements(db.last_result())
+                };
+                if statements.is_empty() {
+                    return Ok(CommandResult::Output(
+                        "The query returned no statements to run.".to_string(),
+                    ));
+                }
 
-                    for (key, creds) in cached_creds {
-                        let remaining_seconds = (creds.expire_time - now).num_seconds().max(0);
-                        let remaining_hours = remaining_seconds / 3600;
-                        let remaining_mins = (remaining_seconds % 3600) / 60;
+                if crate::config_editor::can_run_interactive() {
+                    const PREVIEW: usize = 20;
+                    eprintln!("\\gexec will run {} statement(s):", statements.len());
+                    for statement in statements.iter().take(PREVIEW) {
+                        eprintln!("  {statement}");
+                    }
+                    if statements.len() > PREVIEW {
+                        eprintln!("  ... and {} more", statements.len() - PREVIEW);
+                    }
+                    let confirmed = inquire::Confirm::new("Run them?")
+                        .with_default(false)
+                        .prompt()
+                        .unwrap_or(false);
+                    if !confirmed {
+                        return Ok(CommandResult::Output("\\gexec cancelled.".to_string()));
+                    }
+                }
 
-                        let status = if now >= creds.expire_time {
-                            "EXPIRED"
-                        } else if remaining_seconds < config.vault_cache_min_ttl_seconds as i64 {
-                            "EXPIRING SOON"
-                        } else {
-                            "VALID"
-                        };
+                // The same typed confirmation as statements run at the prompt
+                for statement in &statements {
+                    if let Err(e) = crate::guardrails::confirm(statement, &config.guardrails) {
+                        return Ok(CommandResult::Error(format!("{e}\n  {statement}")));
+                    }
+                }
 
+                let mut output = String::new();
+                for (done, statement) in statements.iter().enumerate() {
+                    if interrupt_flag.load(std::sync::atomic::Ordering::SeqCst) {
                         output.push_str(&format!(
-                            "  {} ({}) - {}h{}m remaining - {}\n",
-                            key, creds.username, remaining_hours, remaining_mins, status
+                            "Interrupted after {done} of {} statement(s).",
+                            statements.len()
                         ));
+                        db.result_cache_mut().clear();
+                        return Ok(CommandResult::Error(output));
+                    }
+                    // Generated statements may change any table
+                    db.result_cache_mut().clear();
+                    let Some(client) = db.get_database_client() else {
+                        return Ok(CommandResult::Error(
+                            "Not connected to a database".to_string(),
+                        ));
+                    };
+                    match client.execute_statement(statement).await {
+                        Ok((sets, _)) => {
+                            for set in sets.iter().filter(|set| !set.is_empty()) {
+                                output.push_str(&crate::format::format_query_results_psql(set));
+                                output.push('\n');
+                            }
+                        }
+                        Err(e) => {
+                            output.push_str(&format!(
+                                "Statement {} of {} failed: {e}\n  {statement}\n{done} statement(s) ran before it.",
+                                done + 1,
+                                statements.len()
+                            ));
+                            return Ok(CommandResult::Error(output));
+                        }
                     }
-                    Ok(CommandResult::Output(output))
                 }
+                output.push_str(&format!("Ran {} statement(s).", statements.len()));
+                Ok(CommandResult::Output(output))
             }
 
-            Command::VaultCacheClear => match config.clear_vault_credentials() {
-                Ok(()) => Ok(CommandResult::Output(
-                    "All vault credentials cleared from cache.".to_string(),
-                )),
-                Err(e) => Ok(CommandResult::Error(format!(
-                    "Failed to clear vault credentials: {e}"
-                ))),
-            },
-
-            Command::VaultCacheRefresh { role } => {
-                match role {
-                    Some(role_key) => {
-                        // Force refresh specific role - would need to implement role-specific refresh
-                        Ok(CommandResult::Output(format!(
-                            "Force refresh for role '{role_key}' not yet implemented."
-                        )))
+            Command::Dump { target, filename } => {
+                let db = database.lock().unwrap();
+                let Some(client) = db.get_database_client() else {
+                    return Ok(CommandResult::Error(
+                        "Not connected to a database".to_string(),
+                    ));
+                };
+                let db_type = db.get_database_type();
+                if !crate::dump::is_supported(&db_type) {
+                    return Ok(CommandResult::Error(format!(
+                        "\\dump is available for PostgreSQL, MySQL and SQLite. Current database: {}",
+                        db_type.display_name()
+                    )));
+                }
+                let file = match std::fs::File::create(filename) {
+                    Ok(file) => file,
+                    Err(e) => {
+                        return Ok(CommandResult::Error(format!(
+                            "Failed to create {filename}: {e}"
+                        )));
                     }
-                    None => {
-                        // Reload from file
-                        config.reload_vault_credentials();
-                        Ok(CommandResult::Output(
-                            "Vault credential cache reloaded from file.".to_string(),
-                        ))
+                };
+                let mut out = std::io::BufWriter::new(file);
+                let started = std::time::Instant::now();
+                match crate::dump::dump(client, target, &mut out).await {
+                    Ok(summary) => Ok(CommandResult::Output(format!(
+                        "Dumped {} table(s), {} row(s) to {filename} in {:.2}s",
+                        summary.tables,
+                        summary.rows,
+                        started.elapsed().as_secs_f64()
+                    ))),
+                    Err(e) => {
+                        // A partial file would pass for a complete dump
+                        let _ = std::fs::remove_file(filename);
+                        Ok(CommandResult::Error(format!("Dump failed: {e}")))
                     }
                 }
             }
 
-            Command::VaultCacheExpired => {
-                let cached_creds = config.list_cached_vault_credentials();
-                let now = chrono::Utc::now();
-                let expired_creds: Vec<_> = cached_creds
-                    .into_iter()
-                    .filter(|(_, creds)| now >= creds.expire_time)
-                    .collect();
-
-                if expired_creds.is_empty() {
-                    Ok(CommandResult::Output(
-                        "No expired vault credentials found.".to_string(),
-                    ))
-                } else {
-                    let mut output = format!(
-                        "Expired vault credentials ({} entries):\n",
-                        expired_creds.len()
+            Command::Restore { filename } => {
+                let mut db = database.lock().unwrap();
+                let Some(client) = db.get_database_client() else {
+                    return Ok(CommandResult::Error(
+                        "Not connected to a database".to_string(),
+                    ));
+                };
+                let db_type = db.get_database_type();
+                if !crate::dump::is_supported(&db_type) {
+                    return Ok(CommandResult::Error(format!(
+                        "\\restore is available for PostgreSQL, MySQL and SQLite. Current database: {}",
+                        db_type.display_name()
+                    )));
+                }
+                let script = match std::fs::read_to_string(filename) {
+                    Ok(script) => script,
+                    Err(e) => {
+                        return Ok(CommandResult::Error(format!(
+                            "Failed to read {filename}: {e}"
+                        )));
+                    }
+                };
+                let plan = match crate::dump::plan_restore(&script) {
+                    Ok(plan) => plan,
+                    Err(e) => return Ok(CommandResult::Error(e)),
+                };
+                let total = plan.statements.len();
+                if total == 0 {
+                    return Ok(CommandResult::Output(format!(
+                        "No statements to run in {filename}"
+                    )));
+                }
+                let report = |done: usize| {
+                    eprint!(
+                        "\r  {done}/{total} statements ({:.0}%)",
+                        done as f64 * 100.0 / total as f64
                     );
-                    for (key, creds) in expired_creds {
-                        let expired_since = (now - creds.expire_time).num_seconds();
-                        let expired_hours = expired_since / 3600;
-                        let expired_mins = (expired_since % 3600) / 60;
+                };
+                let started = std::time::Instant::now();
+                let result = client.run_in_transaction(&plan.statements, &report).await;
+                eprintln!();
+                // The transaction is rolled back on failure, so only a
+                // restore that went through can outdate cached results
+                if result.is_ok() {
+                    db.result_cache_mut().clear();
+                }
 
-                        output.push_str(&format!(
-                            "  {} ({}) - expired {}h{}m ago\n",
-                            key, creds.username, expired_hours, expired_mins
-                        ));
+                match result {
+                    Ok(()) => {
+                        let mut message = format!(
+                            "Restored {filename}: {total} statement(s), {} row(s) inserted in {:.2}s",
+                            plan.rows,
+                            started.elapsed().as_secs_f64()
+                        );
+                        if plan.skipped > 0 {
+                            message.push_str(&format!(
+                                " ({} psql meta-command(s) skipped)",
+                                plan.skipped
+                            ));
+                        }
+                        Ok(CommandResult::Output(message))
                     }
-                    Ok(CommandResult::Output(output))
+                    Err(e) => Ok(CommandResult::Error(format!("Restore failed: {e}"))),
                 }
             }
 
-            // Password management commands
-            Command::SavePassword { .. } => {
-                use crate::dbcrust_pass::{DatabaseType, save_password};
-                use inquire::{Password, Select, Text};
-
-                // Interactive prompts for all parameters
-                let database_types = vec![
-                    "postgresql",
-                    "mysql",
-                    "mongodb",
-                    "elasticsearch",
-                    "clickhouse",
-                    "sqlite",
-                ];
-                let db_type_str = Select::new("Database type:", database_types)
-                    .prompt()
-                    .map_err(|e| CommandError::InvalidSyntax(format!("Input error: {e}")))?;
-
-                let db_type = DatabaseType::from_str(db_type_str).unwrap();
-
-                let host = Text::new("Host:")
-                    .with_default("localhost")
-                    .prompt()
-                    .map_err(|e| CommandError::InvalidSyntax(format!("Input error: {e}")))?;
+            Command::FedJoin {
+                name,
+                source,
+                query,
+                local,
+            } => {
+                use crate::federated::{Collected, LoadedTable, Target};
 
-                let port_str = Text::new("Port:")
-                    .with_default(match db_type {
-                        DatabaseType::PostgreSQL => "5432",
-                        DatabaseType::MySQL => "3306",
-                        DatabaseType::MongoDB => "27017",
-                        DatabaseType::Elasticsearch => "9200",
-                        DatabaseType::ClickHouse => "8123",
-                        DatabaseType::SQLite => "0", // Not used for SQLite
-                    })
-                    .prompt()
-                    .map_err(|e| CommandError::InvalidSyntax(format!("Input error: {e}")))?;
+                let mut db = database.lock().unwrap();
+                let on_postgres = db.get_database_type() == DatabaseType::PostgreSQL;
+                let target = if *local || !on_postgres {
+                    Target::Local
+                } else {
+                    Target::Session
+                };
 
-                let port: u16 = port_str
-                    .parse()
-                    .map_err(|_| CommandError::InvalidSyntax("Invalid port number".to_string()))?;
+                let started = std::time::Instant::now();
+                let mut collected = Collected::default();
+                let (label, fetched) = if source == "." {
+                    let fetched = db.stream_query(query, &mut collected).await;
+                    ("current connection".to_string(), fetched)
+                } else {
+                    let (url, label) = match crate::federated::resolve_source(source, config) {
+                        Ok(resolved) => resolved,
+                        Err(e) => return Ok(CommandResult::Error(e)),
+                    };
+                    let connected = crate::db::Database::from_url_with_mode(
+                        &url,
+                        None,
+                        None,
+                        crate::db::FrontendMode::Batch,
+                    )
+                    .await;
+                    let mut source_db = match connected {
+                        Ok(source_db) => source_db,
+                        Err(e) => {
+                            return Ok(CommandResult::Error(format!(
+                                "Failed to connect to {label}: {e}"
+                            )));
+                        }
+                    };
+                    let fetched = source_db.stream_query(query, &mut collected).await;
+                    (label, fetched)
+                };
+                if let Err(e) = fetched {
+                    return Ok(CommandResult::Error(format!(
+                        "Query on {label} failed: {e}"
+                    )));
+                }
+                if collected.columns.is_empty() {
+                    return Ok(CommandResult::Error(format!(
+                        "The query on {label} returned no rows; nothing to load"
+                    )));
+                }
 
-                let database = Text::new("Database name:")
-                    .prompt()
-                    .map_err(|e| CommandError::InvalidSyntax(format!("Input error: {e}")))?;
+                let loaded = match target {
+                    Target::Session => match db.get_database_client() {
+                        Some(client) => {
+                            crate::federated::load_session(client, &collected, name).await
+                        }
+                        None => return Ok(CommandResult::Error("Not connected".to_string())),
+                    },
+                    Target::Local => crate::federated::load_local(&collected, name).await,
+                };
+                if let Err(e) = loaded {
+                    return Ok(CommandResult::Error(format!("Failed to load {name}: {e}")));
+                }
 
-                let username = Text::new("Username:")
-                    .prompt()
-                    .map_err(|e| CommandError::InvalidSyntax(format!("Input error: {e}")))?;
+                let rows = collected.rows.len() as u64;
+                crate::federated::record(LoadedTable {
+                    name: name.clone(),
+                    target,
+                    source: label.clone(),
+                    rows,
+                    columns: collected.columns.len(),
+                })
+                .await;
+                let elapsed = started.elapsed().as_secs_f64();
+                Ok(CommandResult::Output(match target {
+                    Target::Session => format!(
+                        "Loaded {rows} row(s) from {label} into temp table {name} in {elapsed:.2}s. Join against it in your queries."
+                    ),
+                    Target::Local => format!(
+                        "Loaded {rows} row(s) from {label} into {name} in the local scratch database in {elapsed:.2}s{}. Query it with \\fedjoin run <query>.",
+                        if on_postgres || *local {
+                            ""
+                        } else {
+                            " (temp tables are only kept on PostgreSQL connections)"
+                        }
+                    ),
+                }))
+            }
 
-                let password = Password::new("Password:")
-                    .without_confirmation()
-                    .prompt()
-                    .map_err(|e| {
-                        CommandError::InvalidSyntax(format!("Password input error: {e}"))
-                    })?;
+            Command::FedJoinRun { query } => {
+                let results = match crate::federated::run_local(query).await {
+                    Ok(results) => results,
+                    Err(e) => return Ok(CommandResult::Error(e.to_string())),
+                };
+                if results.is_empty() {
+                    return Ok(CommandResult::Output("(0 rows)".to_string()));
+                }
+                let expanded = database.lock().unwrap().is_expanded_display();
+                Ok(CommandResult::Output(if expanded {
+                    crate::format::format_query_results_expanded(&results)
+                        .into_iter()
+                        .map(|t| t.to_string())
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                } else {
+                    crate::format::format_query_results_psql(&results)
+                }))
+            }
 
-                // Always encrypt passwords by default (no confirmation prompt)
-                let encrypt = true;
+            Command::ListFedJoins => {
+                let tables = crate::federated::loaded().await;
+                if tables.is_empty() {
+                    return Ok(CommandResult::Output(
+                        "Nothing loaded. Use \\fedjoin <name> <source> <query> to load a query result from another connection."
+                            .to_string(),
+                    ));
+                }
+                let mut rows = vec![
+                    ["name", "loaded as", "rows", "columns", "source"]
+                        .map(str::to_string)
+                        .to_vec(),
+                ];
+                rows.extend(tables.iter().map(|table| {
+                    vec![
+                        table.name.clone(),
+                        table.target.to_string(),
+                        table.rows.to_string(),
+                        table.columns.to_string(),
+                        table.source.clone(),
+                    ]
+                }));
+                Ok(CommandResult::Output(
+                    crate::format::format_query_results_psql(&rows),
+                ))
+            }
 
-                match save_password(
-                    db_type.clone(),
-                    &host,
-                    port,
-                    &database,
-                    &username,
-                    &password,
-                    encrypt,
-                ) {
-                    Ok(()) => Ok(CommandResult::Output(format!(
-                        "Password saved for {}:{}@{}:{}/{} (encrypted)",
-                        db_type.as_str(),
-                        username,
-                        host,
-                        port,
-                        database
-                    ))),
-                    Err(e) => Ok(CommandResult::Error(format!(
-                        "Failed to save password: {e}"
-                    ))),
+            Command::ClearFedJoins => {
+                let tables = crate::federated::clear().await;
+                let db = database.lock().unwrap();
+                if let Some(client) = db.get_database_client() {
+                    if db.get_database_type() == DatabaseType::PostgreSQL {
+                        for table in tables
+                            .iter()
+                            .filter(|table| table.target == crate::federated::Target::Session)
+                        {
+                            let drop = crate::federated::drop_statement(&table.name, table.target);
+                            let _ = client.execute_query(&drop).await;
+                        }
+                    }
                 }
+                Ok(CommandResult::Output(format!(
+                    "Dropped {} \\fedjoin table(s) and the local scratch database.",
+                    tables.len()
+                )))
             }
 
-            Command::ListPasswords => {
-                use crate::dbcrust_pass::list_entries;
+            Command::ShowScratch => {
+                if let Err(e) = crate::federated::open_local().await {
+                    return Ok(CommandResult::Error(format!(
+                        "Failed to open the scratch database: {e}"
+                    )));
+                }
+                let tables = match crate::federated::local_tables().await {
+                    Ok(tables) => tables.unwrap_or_default(),
+                    Err(e) => return Ok(CommandResult::Error(e.to_string())),
+                };
+                if tables.is_empty() {
+                    return Ok(CommandResult::Output(
+                        "Scratch database open (in-memory SQLite, no tables yet). Use \\scratch import <name> to copy the last result into it."
+                            .to_string(),
+                    ));
+                }
+                let mut rows = vec![vec!["table".to_string(), "rows".to_string()]];
+                rows.extend(
+                    tables
+                        .into_iter()
+                        .map(|(name, count)| vec![name, count.to_string()]),
+                );
+                Ok(CommandResult::Output(
+                    crate::format::format_query_results_psql(&rows),
+                ))
+            }
 
-                match list_entries() {
-                    Ok(entries) => {
-                        if entries.is_empty() {
-                            Ok(CommandResult::Output(
-                                "No saved passwords found.".to_string(),
-                            ))
-                        } else {
-                            let mut output = "Saved passwords:\n".to_string();
-                            for (db_type, host, port, database, username) in entries {
-                                output.push_str(&format!(
-                                    "  {}:{}@{}:{}/{}\n",
-                                    db_type.as_str(),
-                                    username,
-                                    host,
-                                    port,
-                                    database
-                                ));
-                            }
-                            Ok(CommandResult::Output(output))
-                        }
-                    }
-                    Err(e) => Ok(CommandResult::Error(format!(
-                        "Failed to list passwords: {e}"
-                    ))),
+            Command::ScratchImport { name } => {
+                let collected = {
+                    let db = database.lock().unwrap();
+                    crate::federated::Collected::from_displayed(db.last_result())
+                };
+                let Some(collected) = collected else {
+                    return Ok(CommandResult::Error(
+                        "No result to import yet. Run a query that returns rows first.".to_string(),
+                    ));
+                };
+                if let Err(e) = crate::federated::load_local(&collected, name).await {
+                    return Ok(CommandResult::Error(format!(
+                        "Failed to import {name}: {e}"
+                    )));
                 }
+                let rows = collected.rows.len() as u64;
+                crate::federated::record(crate::federated::LoadedTable {
+                    name: name.clone(),
+                    target: crate::federated::Target::Local,
+                    source: "last result".to_string(),
+                    rows,
+                    columns: collected.columns.len(),
+                })
+                .await;
+                Ok(CommandResult::Output(format!(
+                    "Imported {rows} row(s) into {name} in the scratch database. Query it with \\scratch <query>."
+                )))
             }
 
-            Command::DeletePassword { .. } => {
-                use crate::dbcrust_pass::{delete_password, list_entries};
-                use inquire::Select;
-
-                // First list available passwords
-                let entries = match list_entries() {
-                    Ok(entries) => entries,
-                    Err(e) => {
-                        return Ok(CommandResult::Error(format!(
-                            "Failed to list passwords: {e}"
-                        )));
-                    }
+            Command::ScratchRun { query } => {
+                let results = match crate::federated::run_local(query).await {
+                    Ok(results) => results,
+                    Err(e) => return Ok(CommandResult::Error(e.to_string())),
                 };
-
-                if entries.is_empty() {
-                    return Ok(CommandResult::Output(
-                        "No saved passwords to delete.".to_string(),
-                    ));
+                if results.is_empty() {
+                    return Ok(CommandResult::Output("(0 rows)".to_string()));
                 }
-
-                // Create selection list
-                let options: Vec<String> = entries
-                    .iter()
-                    .map(|(db_type, host, port, database, username)| {
-                        format!(
-                            "{}:{}@{}:{}/{}",
-                            db_type.as_str(),
-                            username,
-                            host,
-                            port,
-                            database
-                        )
-                    })
-                    .collect();
-
-                let selection = Select::new("Select password to delete:", options)
-                    .prompt()
-                    .map_err(|e| CommandError::InvalidSyntax(format!("Input error: {e}")))?;
-
-                // Find the selected entry
-                let selected_entry =
-                    entries
-                        .iter()
-                        .find(|(db_type, host, port, database, username)| {
-                            format!(
-                                "{}:{}@{}:{}/{}",
-                                db_type.as_str(),
-                                username,
-                                host,
-                                port,
-                                database
-                            ) == selection
-                        });
-
-                if let Some((db_type, host, port_str, database, username)) = selected_entry {
-                    let port: u16 = port_str.parse().map_err(|_| {
-                        CommandError::InvalidSyntax("Invalid port number".to_string())
-                    })?;
-
-                    match delete_password(db_type.clone(), host, port, database, username) {
-                        Ok(true) => Ok(CommandResult::Output(format!(
-                            "Password deleted: {selection}"
-                        ))),
-                        Ok(false) => Ok(CommandResult::Output(
-                            "No matching password found to delete.".to_string(),
-                        )),
-                        Err(e) => Ok(CommandResult::Error(format!(
-                            "Failed to delete password: {e}"
-                        ))),
-                    }
+                let expanded = database.lock().unwrap().is_expanded_display();
+                Ok(CommandResult::Output(if expanded {
+                    crate::format::format_query_results_expanded(&results)
+                        .into_iter()
+                        .map(|t| t.to_string())
+                        .collect::<Vec<_>>()
+                        .join("\n")
                 } else {
-                    Ok(CommandResult::Error(
-                        "Selected entry not found.".to_string(),
-                    ))
-                }
+                    crate::format::format_query_results_psql(&results)
+                }))
             }
 
-            Command::EncryptPasswords => {
-                use crate::dbcrust_pass::encrypt_all_passwords;
-
-                match encrypt_all_passwords() {
-                    Ok(0) => Ok(CommandResult::Output(
-                        "No plaintext passwords found to encrypt.".to_string(),
-                    )),
-                    Ok(count) => Ok(CommandResult::Output(format!(
-                        "Encrypted {count} password(s) in .dbcrust file."
-                    ))),
-                    Err(e) => Ok(CommandResult::Error(format!(
-                        "Failed to encrypt passwords: {e}"
-                    ))),
+            Command::ClearScratch => Ok(CommandResult::Output(
+                if crate::federated::clear_local().await {
+                    "Scratch database dropped."
+                } else {
+                    "The scratch database is not open."
                 }
+                .to_string(),
+            )),
+
+            Command::SetMultilineIndicator { indicator } => {
+                config.multiline_prompt_indicator = indicator.clone();
+                config
+                    .save_with_documentation()
+                    .map_err(|e| CommandError::DatabaseError(e.into()))?;
+                Ok(CommandResult::Output(format!(
+                    "Multiline indicator set to: {indicator} (will take effect on next restart)"
+                )))
             }
 
-            Command::ShowPoolStats => {
-                let db = database.lock().unwrap();
-                let connection_status = if db.is_connected().await {
-                    "✅ Connected"
+            Command::TogglePager => {
+                config.pager_enabled = !config.pager_enabled;
+                config
+                    .save_with_documentation()
+                    .map_err(|e| CommandError::DatabaseError(e.into()))?;
+                let status = if config.pager_enabled {
+                    "enabled"
                 } else {
-                    "❌ Disconnected"
+                    "disabled"
                 };
+                Ok(CommandResult::Output(format!("Pager is now {status}.")))
+            }
 
-                let output = format!(
-                    "Connection Status: {connection_status}\n\nNote: Detailed pool statistics are no longer available.\nConnection pooling is now managed by database-specific clients."
-                );
-
-                Ok(CommandResult::Output(output))
+            Command::ToggleBanner => {
+                config.show_banner = !config.show_banner;
+                config
+                    .save_with_documentation()
+                    .map_err(|e| CommandError::DatabaseError(e.into()))?;
+                let status = if config.show_banner {
+                    "enabled"
+                } else {
+                    "disabled"
+                };
+                Ok(CommandResult::Output(format!("Banner is now {status}.")))
             }
 
-            Command::ComplexDisplayMode { mode } => {
-                match mode {
-                    None => {
-                        // Show current mode
-                        let current_mode = config.complex_display.display_mode.clone();
-                        Ok(CommandResult::Output(format!(
-                            "Current complex display mode: {current_mode}"
-                        )))
-                    }
-                    Some(mode_str) => {
-                        // Set new mode
-                        use crate::complex_display::ComplexDisplayMode;
-                        match ComplexDisplayMode::from_str(mode_str) {
-                            Some(new_mode) => {
-                                config.complex_display.display_mode = new_mode.clone();
-                                // Update global config
-                                crate::complex_display::set_global_complex_config(
-                                    config.complex_display.clone(),
-                                );
+            Command::ToggleServerInfo => {
+                config.show_server_info = !config.show_server_info;
+                config
+                    .save_with_documentation()
+                    .map_err(|e| CommandError::DatabaseError(e.into()))?;
+                let status = if config.show_server_info {
+                    "enabled"
+                } else {
+                    "disabled"
+                };
+                Ok(CommandResult::Output(format!(
+                    "Server info display is now {status}."
+                )))
+            }
 
-                                // Save config
-                                if let Err(e) = config.save_with_documentation() {
-                                    return Ok(CommandResult::Error(format!(
-                                        "Failed to save config: {e}"
-                                    )));
-                                }
+            Command::SetDisplayTimezone { zone } => {
+                use crate::format::DisplayTimezone;
 
-                                Ok(CommandResult::Output(format!(
-                                    "Complex display mode set to: {new_mode}"
-                                )))
-                            }
-                            None => Ok(CommandResult::Error(
-                                "Invalid mode. Available modes: full, truncated, summary, viz"
-                                    .to_string(),
-                            )),
-                        }
+                if zone.is_empty() {
+                    let mut output = format!("Display time zone: {}", config.display.timezone);
+                    if !config.display.datetime_format.is_empty() {
+                        output.push_str(&format!(
+                            "\nTimestamp format: {}",
+                            config.display.datetime_format
+                        ));
+                    }
+                    if !config.display.date_format.is_empty() {
+                        output.push_str(&format!("\nDate format: {}", config.display.date_format));
                     }
+                    output.push_str("\nUsage: \\tz server|local|utc|<IANA zone>");
+                    return Ok(CommandResult::Output(output));
+                }
+
+                let timezone = match zone.parse::<DisplayTimezone>() {
+                    Ok(timezone) => timezone,
+                    Err(e) => return Ok(CommandResult::Error(e)),
+                };
+                config.display.timezone = timezone.to_string();
+                if let Err(e) = crate::format::apply_display_config(&config.display) {
+                    return Ok(CommandResult::Error(format!(
+                        "Invalid [display] settings: {e}"
+                    )));
                 }
+                config
+                    .save_with_documentation()
+                    .map_err(|e| CommandError::DatabaseError(e.into()))?;
+                Ok(CommandResult::Output(match timezone {
+                    DisplayTimezone::Server => {
+                        "Timestamps are now displayed as returned by the server.".to_string()
+                    }
+                    _ => format!("Timestamps with a time zone are now displayed in {timezone}."),
+                }))
             }
 
-            Command::ComplexDisplayJsonToggle => {
-                // Toggle JSON pretty printing
-                config.complex_display.json_pretty_print =
-                    !config.complex_display.json_pretty_print;
+            Command::SetTableStyle { style } => {
+                use crate::format::TableStyle;
 
-                // Update global config
-                crate::complex_display::set_global_complex_config(config.complex_display.clone());
+                if style.is_empty() {
+                    return Ok(CommandResult::Output(format!(
+                        "Table style: {}\nUsage: \\pset border {}",
+                        crate::format::table_style(),
+                        TableStyle::NAMES.join("|")
+                    )));
+                }
 
-                // Save config
-                if let Err(e) = config.save_with_documentation() {
-                    return Ok(CommandResult::Error(format!("Failed to save config: {e}")));
+                let table_style = match style.parse::<TableStyle>() {
+                    Ok(table_style) => table_style,
+                    Err(e) => return Ok(CommandResult::Error(e)),
+                };
+                config.display.table_style = table_style.to_string();
+                if let Err(e) = crate::format::apply_display_config(&config.display) {
+                    return Ok(CommandResult::Error(format!(
+                        "Invalid [display] settings: {e}"
+                    )));
                 }
+                config
+                    .save_with_documentation()
+                    .map_err(|e| CommandError::DatabaseError(e.into()))?;
+                Ok(CommandResult::Output(format!(
+                    "Table style is now {table_style}."
+                )))
+            }
 
-                let status = if config.complex_display.json_pretty_print {
+            Command::ToggleAutocomplete => {
+                config.autocomplete_enabled = !config.autocomplete_enabled;
+                config
+                    .save_with_documentation()
+                    .map_err(|e| CommandError::DatabaseError(e.into()))?;
+                let status = if config.autocomplete_enabled {
                     "enabled"
                 } else {
                     "disabled"
                 };
                 Ok(CommandResult::Output(format!(
-                    "JSON pretty printing {status}"
+                    "Autocomplete is now {status}."
                 )))
             }
 
-            // MongoDB-specific commands
-            Command::ListCollections => {
+            Command::ToggleColumnSelection => {
                 let mut db = database.lock().unwrap();
-                match db.list_collections().await {
-                    Ok(results) => {
-                        if results.is_empty() {
-                            Ok(CommandResult::Output("No collections found.".to_string()))
-                        } else {
-                            let output = if db.is_expanded_display() {
-                                let tables = crate::format::format_query_results_expanded(&results);
-                                tables
-                                    .into_iter()
-                                    .map(|t| t.to_string())
-                                    .collect::<Vec<_>>()
-                                    .join("\n")
-                            } else {
-                                crate::format::format_query_results_psql(&results)
-                            };
-                            Ok(CommandResult::Output(output))
-                        }
-                    }
-                    Err(e) => Ok(CommandResult::Error(format!(
-                        "Failed to list collections: {e}"
-                    ))),
+                let new_status = db.toggle_column_select_mode();
+                let status = if new_status { "enabled" } else { "disabled" };
+                Ok(CommandResult::Output(format!(
+                    "Column selection mode is now {status}."
+                )))
+            }
+
+            Command::SetColumnSelectionThreshold { threshold } => {
+                config.column_selection_threshold = *threshold;
+                config
+                    .save_with_documentation()
+                    .map_err(|e| CommandError::DatabaseError(e.into()))?;
+                Ok(CommandResult::Output(format!(
+                    "Column selection threshold set to: {threshold}"
+                )))
+            }
+
+            Command::ClearColumnViews => {
+                let mut db = database.lock().unwrap();
+                db.clear_column_views();
+                Ok(CommandResult::Output("Column views cleared.".to_string()))
+            }
+
+            Command::SaveColumnView { name } => {
+                let mut db = database.lock().unwrap();
+                let Some((fingerprint, columns)) = db.last_column_selection().cloned() else {
+                    return Ok(CommandResult::Error(
+                        "No column selection to save. Run a query and pick its columns first (\\cs forces the selector).".to_string(),
+                    ));
+                };
+                if let Err(e) = config.save_column_view(name, fingerprint, columns.clone()) {
+                    return Ok(CommandResult::Error(format!(
+                        "Failed to save column view: {e}"
+                    )));
                 }
+                db.set_saved_column_views(config.column_views());
+                Ok(CommandResult::Output(format!(
+                    "Column view '{name}' saved ({} columns: {}). It is applied whenever this query runs again.",
+                    columns.len(),
+                    columns.join(", ")
+                )))
             }
 
-            Command::DescribeCollection { collection_name } => {
+            Command::ListColumnViews => {
+                let mut views: Vec<_> = config.column_views().iter().collect();
+                if views.is_empty() {
+                    return Ok(CommandResult::Output(
+                        "No saved column views. Pick columns for a query, then use \\csave <name>."
+                            .to_string(),
+                    ));
+                }
+                views.sort_by(|a, b| a.0.cmp(b.0));
+                let mut results = vec![vec![
+                    "Name".to_string(),
+                    "Columns".to_string(),
+                    "Query".to_string(),
+                ]];
+                for (name, view) in views {
+                    let query = if view.fingerprint.chars().count() > 60 {
+                        format!("{}…", view.fingerprint.chars().take(59).collect::<String>())
+                    } else {
+                        view.fingerprint.clone()
+                    };
+                    results.push(vec![name.clone(), view.columns.join(", "), query]);
+                }
+                Ok(CommandResult::Output(
+                    crate::format::format_query_results_psql(&results),
+                ))
+            }
+
+            Command::DeleteColumnView { name } => match config.delete_column_view(name) {
+                Ok(true) => {
+                    database
+                        .lock()
+                        .unwrap()
+                        .set_saved_column_views(config.column_views());
+                    Ok(CommandResult::Output(format!(
+                        "Column view '{name}' deleted."
+                    )))
+                }
+                Ok(false) => Ok(CommandResult::Error(format!(
+                    "No saved column view named '{name}'."
+                ))),
+                Err(e) => Ok(CommandResult::Error(format!(
+                    "Failed to delete column view: {e}"
+                ))),
+            },
+
+            Command::ResetView => {
                 let mut db = database.lock().unwrap();
-                match db.describe_collection(collection_name).await {
-                    Ok(details) => {
-                        let output = crate::format::format_table_details(&details);
-                        Ok(CommandResult::Output(output))
+                if db.is_explain_mode() {
+                    db.toggle_explain_mode();
+                }
+                if db.is_expanded_display() {
+                    db.toggle_expanded_display();
+                }
+                db.reset_column_view();
+                config.explain_mode_default = false;
+                config.expanded_display_default = false;
+                config
+                    .save_with_documentation()
+                    .map_err(|e| CommandError::DatabaseError(e.into()))?;
+                Ok(CommandResult::Output(
+                    "View settings reset to defaults.".to_string(),
+                ))
+            }
+
+            // Vector display commands
+            Command::SetVectorDisplayMode { mode } => {
+                use crate::vector_display::VectorDisplayMode;
+
+                if mode.is_empty() {
+                    return Ok(CommandResult::Output(format!(
+                        "Current vector display mode: {}\nAvailable modes: {}",
+                        config.vector_display.display_mode,
+                        VectorDisplayMode::all_modes().join(", ")
+                    )));
+                }
+
+                match mode.parse::<VectorDisplayMode>() {
+                    Ok(new_mode) => {
+                        config.vector_display.display_mode = new_mode;
+
+                        // Update global config so formatters can use it immediately
+                        crate::vector_display::set_global_vector_config(
+                            config.vector_display.clone(),
+                        );
+
+                        config
+                            .save_with_documentation()
+                            .map_err(|e| CommandError::DatabaseError(e.into()))?;
+                        Ok(CommandResult::Output(format!(
+                            "Vector display mode set to: {mode}"
+                        )))
                     }
-                    Err(e) => Ok(CommandResult::Error(format!(
-                        "Failed to describe collection '{collection_name}': {e}"
-                    ))),
+                    Err(e) => Ok(CommandResult::Error(e.to_string())),
                 }
             }
 
-            Command::ListMongoIndexes => {
+            Command::ShowVectorDisplayConfig => {
+                let config_display = format!(
+                    "Vector Display Configuration:\n\
+                    • Display mode: {}\n\
+                    • Truncation length: {}\n\
+                    • Viz width: {}\n\
+                    • Show statistics: {}\n\
+                    • Dimension threshold: {}\n\
+                    • Show dimensions: {}",
+                    config.vector_display.display_mode,
+                    config.vector_display.truncation_length,
+                    config.vector_display.viz_width,
+                    config.vector_display.show_statistics,
+                    config.vector_display.dimension_threshold,
+                    config.vector_display.show_dimensions
+                );
+                Ok(CommandResult::Output(config_display))
+            }
+
+            Command::ToggleVectorStatistics => {
+                config.vector_display.show_statistics = !config.vector_display.show_statistics;
+
+                // Update global config so formatters can use it immediately
+                crate::vector_display::set_global_vector_config(config.vector_display.clone());
+
+                config
+                    .save_with_documentation()
+                    .map_err(|e| CommandError::DatabaseError(e.into()))?;
+                let status = if config.vector_display.show_statistics {
+                    "enabled"
+                } else {
+                    "disabled"
+                };
+                Ok(CommandResult::Output(format!(
+                    "Vector statistics display is now {status}."
+                )))
+            }
+
+            Command::VectorKnn {
+                table,
+                column,
+                target,
+                k,
+                metric,
+            } => {
                 let mut db = database.lock().unwrap();
-                match db.list_mongo_indexes().await {
+                let db_type = db.get_database_type();
+                if !matches!(db_type, DatabaseType::PostgreSQL) {
+                    return Ok(CommandResult::Error(format!(
+                        "\\knn requires PostgreSQL with the pgvector extension. Current database: {}",
+                        db_type.display_name()
+                    )));
+                }
+
+                let query =
+                    crate::vector_search::build_knn_query(table, column, target, *k, *metric);
+                match db.execute_query(&query).await {
                     Ok(results) => {
-                        if results.is_empty() {
-                            Ok(CommandResult::Output("No indexes found.".to_string()))
+                        if results.len() <= 1 {
+                            Ok(CommandResult::Output("No neighbours found.".to_string()))
                         } else {
                             let output = if db.is_expanded_display() {
                                 let tables = crate::format::format_query_results_expanded(&results);
@@ -2754,426 +5722,1434 @@ impl CommandExecutor for Command {
                             } else {
                                 crate::format::format_query_results_psql(&results)
                             };
-                            Ok(CommandResult::Output(output))
+                            Ok(CommandResult::Output(format!(
+                                "{output}\nDistance metric: {metric} ({})",
+                                metric.operator()
+                            )))
                         }
                     }
-                    Err(e) => Ok(CommandResult::Error(format!("Failed to list indexes: {e}"))),
+                    Err(e) => Ok(CommandResult::Error(format!(
+                        "Failed to run nearest-neighbour search: {e}"
+                    ))),
                 }
             }
 
-            Command::CreateMongoIndex {
-                collection,
-                field,
-                index_type,
-            } => {
+            Command::Replication => {
                 let mut db = database.lock().unwrap();
-                match db
-                    .create_mongo_index(collection, field, index_type.as_deref())
-                    .await
-                {
-                    Ok(_) => Ok(CommandResult::Output(format!(
-                        "Index created successfully on collection '{collection}' field '{field}'"
-                    ))),
-                    Err(e) => Ok(CommandResult::Error(format!("Failed to create index: {e}"))),
+                let db_type = db.get_database_type();
+                if !matches!(db_type, DatabaseType::PostgreSQL) {
+                    return Ok(CommandResult::Error(format!(
+                        "\\replication is only available for PostgreSQL. Current database: {}",
+                        db_type.display_name()
+                    )));
+                }
+                match crate::replication::replication_report(&mut db).await {
+                    Ok(report) => Ok(CommandResult::Output(report)),
+                    Err(e) => Ok(CommandResult::Error(e)),
                 }
             }
 
-            Command::DropMongoIndex {
-                collection,
-                index_name,
-            } => {
-                let mut db = database.lock().unwrap();
-                match db.drop_mongo_index(collection, index_name).await {
-                    Ok(_) => Ok(CommandResult::Output(format!(
-                        "Index '{index_name}' dropped successfully from collection '{collection}'"
-                    ))),
-                    Err(e) => Ok(CommandResult::Error(format!("Failed to drop index: {e}"))),
+            Command::MySqlProcessList
+            | Command::MySqlLocks
+            | Command::MySqlSlowQueries { .. }
+            | Command::PgActivity
+            | Command::PgBlocking
+            | Command::PgBloat { .. }
+            | Command::DbSize
+            | Command::TableSize { .. }
+            | Command::Cluster { .. } => {
+                use crate::database::ServerDiagnostic;
+
+                let (supported, diagnostic, empty): (&[DatabaseType], _, _) = match self {
+                    Command::MySqlProcessList => (
+                        &[DatabaseType::MySQL],
+                        ServerDiagnostic::Sessions,
+                        "No sessions.",
+                    ),
+                    Command::MySqlLocks => (
+                        &[DatabaseType::MySQL],
+                        ServerDiagnostic::LockWaits,
+                        "No lock waits.",
+                    ),
+                    Command::MySqlSlowQueries { limit } => (
+                        &[DatabaseType::MySQL],
+                        ServerDiagnostic::TopStatements { limit: *limit },
+                        "No statement statistics collected yet.",
+                    ),
+                    Command::PgActivity => (
+                        &[DatabaseType::PostgreSQL],
+                        ServerDiagnostic::Sessions,
+                        "No other client sessions.",
+                    ),
+                    Command::PgBlocking => (
+                        &[DatabaseType::PostgreSQL],
+                        ServerDiagnostic::LockWaits,
+                        "No blocked sessions.",
+                    ),
+                    Command::PgBloat { limit } => (
+                        &[DatabaseType::PostgreSQL],
+                        ServerDiagnostic::Bloat { limit: *limit },
+                        "No bloat detected (tables need ANALYZE statistics to be estimated).",
+                    ),
+                    Command::DbSize => (
+                        SIZE_REPORT_DATABASES,
+                        ServerDiagnostic::DatabaseSizes,
+                        "No databases.",
+                    ),
+                    Command::TableSize { limit, sort } => (
+                        SIZE_REPORT_DATABASES,
+                        ServerDiagnostic::RelationSizes {
+                            limit: *limit,
+                            sort: *sort,
+                        },
+                        "No tables.",
+                    ),
+                    Command::Cluster { .. } => (
+                        &[DatabaseType::ClickHouse],
+                        ServerDiagnostic::Clusters,
+                        "No clusters configured (see remote_servers in the server config).",
+                    ),
+                    _ => unreachable!(),
+                };
+
+                let db = database.lock().unwrap();
+                let db_type = db.get_database_type();
+                if !supported.contains(&db_type) {
+                    return Ok(CommandResult::Error(format!(
+                        "{} is only available for {}. Current database: {}",
+                        self.usage().split_whitespace().next().unwrap_or_default(),
+                        supported
+                            .iter()
+                            .map(|t| t.display_name())
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                        db_type.display_name()
+                    )));
                 }
-            }
 
-            Command::MongoStats => {
-                let mut db = database.lock().unwrap();
-                match db.mongo_stats().await {
-                    Ok(results) => {
-                        let output = if db.is_expanded_display() {
-                            let tables = crate::format::format_query_results_expanded(&results);
-                            tables
-                                .into_iter()
-                                .map(|t| t.to_string())
-                                .collect::<Vec<_>>()
-                                .join("\n")
-                        } else {
-                            crate::format::format_query_results_psql(&results)
-                        };
-                        Ok(CommandResult::Output(output))
+                let mut results = match db.server_diagnostic(diagnostic).await {
+                    Ok(results) => results,
+                    Err(e) => {
+                        return Ok(CommandResult::Error(format!(
+                            "Failed to read the {diagnostic}: {e}"
+                        )));
                     }
-                    Err(e) => Ok(CommandResult::Error(format!(
-                        "Failed to get MongoDB stats: {e}"
-                    ))),
+                };
+                if let Command::Cluster { name: Some(name) } = self {
+                    let header = results.remove(0);
+                    results.retain(|row| row.first() == Some(name));
+                    if results.is_empty() {
+                        return Ok(CommandResult::Error(format!(
+                            "No cluster named {name}. \\cluster lists them."
+                        )));
+                    }
+                    results.insert(0, header);
+                }
+                if results.len() < 2 {
+                    return Ok(CommandResult::Output(empty.to_string()));
+                }
+                if matches!(
+                    diagnostic,
+                    ServerDiagnostic::DatabaseSizes | ServerDiagnostic::RelationSizes { .. }
+                ) {
+                    crate::database::humanize_size_columns(&mut results);
                 }
-            }
 
-            Command::MongoFind {
-                collection,
-                filter,
-                projection,
-                limit,
-            } => {
-                let mut db = database.lock().unwrap();
-                match db
-                    .mongo_find(collection, filter.as_deref(), projection.as_deref(), *limit)
-                    .await
-                {
-                    Ok(results) => {
-                        let output = if db.is_expanded_display() {
-                            let tables = crate::format::format_query_results_expanded(&results);
-                            tables
-                                .into_iter()
-                                .map(|t| t.to_string())
-                                .collect::<Vec<_>>()
-                                .join("\n")
-                        } else {
-                            crate::format::format_query_results_psql(&results)
-                        };
-                        Ok(CommandResult::Output(output))
+                let mut output = if db.is_expanded_display() {
+                    crate::format::format_query_results_expanded(&results)
+                        .into_iter()
+                        .map(|t| t.to_string())
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                } else {
+                    crate::format::format_query_results_psql(&results)
+                };
+                if matches!(diagnostic, ServerDiagnostic::LockWaits) {
+                    let column = |name: &str| results[0].iter().position(|c| c == name);
+                    if let (Some(waiting), Some(blocking)) =
+                        (column("waiting_id"), column("blocking_id"))
+                    {
+                        let waits: Vec<(String, String)> = results[1..]
+                            .iter()
+                            .map(|row| (row[waiting].clone(), row[blocking].clone()))
+                            .collect();
+                        output.push_str("\nBlocking chains (blocker → waiters):\n");
+                        for chain in crate::database::lock_chains(&waits) {
+                            output.push_str(&format!("  {chain}\n"));
+                        }
                     }
-                    Err(e) => Ok(CommandResult::Error(format!("Failed to execute find: {e}"))),
                 }
+                Ok(CommandResult::Output(output))
             }
 
-            Command::MongoAggregate {
-                collection,
-                pipeline,
-            } => {
-                let mut db = database.lock().unwrap();
-                match db.mongo_aggregate(collection, pipeline).await {
-                    Ok(results) => {
-                        let output = if db.is_expanded_display() {
-                            let tables = crate::format::format_query_results_expanded(&results);
-                            tables
-                                .into_iter()
-                                .map(|t| t.to_string())
-                                .collect::<Vec<_>>()
-                                .join("\n")
-                        } else {
-                            crate::format::format_query_results_psql(&results)
-                        };
-                        Ok(CommandResult::Output(output))
-                    }
-                    Err(e) => Ok(CommandResult::Error(format!(
-                        "Failed to execute aggregation: {e}"
-                    ))),
-                }
-            }
-            Command::MongoTextSearch {
-                collection,
-                search_term,
-            } => {
-                let mut db = database.lock().unwrap();
-                match db.mongo_text_search(collection, search_term).await {
-                    Ok(results) => {
-                        let output = if db.is_expanded_display() {
-                            let tables = crate::format::format_query_results_expanded(&results);
-                            tables
-                                .into_iter()
-                                .map(|t| t.to_string())
-                                .collect::<Vec<_>>()
-                                .join("\n")
-                        } else {
-                            crate::format::format_query_results_psql(&results)
-                        };
-                        Ok(CommandResult::Output(output))
-                    }
-                    Err(e) => Ok(CommandResult::Error(format!(
-                        "Failed to execute text search: {e}"
-                    ))),
+            Command::SqliteBackup { .. }
+            | Command::SqliteVacuum
+            | Command::SqliteIntegrityCheck { .. } => {
+                let db = database.lock().unwrap();
+                let db_type = db.get_database_type();
+                if !matches!(db_type, DatabaseType::SQLite) {
+                    return Ok(CommandResult::Error(format!(
+                        "{} is only available for SQLite. Current database: {}",
+                        self.usage().split_whitespace().next().unwrap_or_default(),
+                        db_type.display_name()
+                    )));
                 }
-            }
-
-            // History management commands
-            Command::ClearSessionHistory { session_hash } => {
-                let history_manager =
-                    match crate::history_manager::SessionHistoryManager::new(config) {
-                        Ok(manager) => manager,
-                        Err(e) => {
-                            return Ok(CommandResult::Error(format!(
-                                "Failed to create history manager: {e}"
-                            )));
-                        }
-                    };
+                let mib = |bytes: u64| bytes as f64 / (1024.0 * 1024.0);
+                let confirm = |prompt: &str| {
+                    inquire::Confirm::new(prompt)
+                        .with_default(false)
+                        .prompt()
+                        .unwrap_or(false)
+                };
 
-                match session_hash {
-                    Some(hash) => {
-                        // Clear specific session history
-                        let histories = match history_manager.list_session_histories() {
-                            Ok(h) => h,
-                            Err(e) => {
+                match self {
+                    Command::SqliteBackup { file, force } => {
+                        let path = std::path::Path::new(file);
+                        if path.exists() && !force {
+                            if !crate::config_editor::can_run_interactive() {
                                 return Ok(CommandResult::Error(format!(
-                                    "Failed to list histories: {e}"
+                                    "{file} already exists; use \\backup {file} --force to overwrite it"
                                 )));
                             }
-                        };
-
-                        if let Some(history) = histories.iter().find(|h| h.session_hash == *hash) {
-                            match std::fs::remove_file(&history.path) {
-                                Ok(_) => Ok(CommandResult::Output(format!(
-                                    "Cleared history for session hash: {hash}"
-                                ))),
-                                Err(e) => Ok(CommandResult::Error(format!(
-                                    "Failed to clear history: {e}"
-                                ))),
+                            if !confirm(&format!("{file} already exists. Overwrite it?")) {
+                                return Ok(CommandResult::Output("Backup cancelled.".to_string()));
                             }
-                        } else {
-                            Ok(CommandResult::Error(format!(
-                                "No history found for session hash: {hash}"
-                            )))
+                        }
+
+                        let report = |progress: &crate::database_sqlite::BackupProgress| {
+                            eprint!(
+                                "\r  {}/{} pages copied, {:.1} MiB ({:.0}%)",
+                                progress.pages_copied,
+                                progress.total_pages,
+                                mib(progress.bytes()),
+                                progress.percent()
+                            );
+                        };
+                        let started = std::time::Instant::now();
+                        let result = db.backup_to_file(path, &report).await;
+                        eprintln!();
+
+                        match result {
+                            Ok(totals) => Ok(CommandResult::Output(format!(
+                                "Backed up {} pages ({:.1} MiB) to {file} in {:.1}s",
+                                totals.pages_copied,
+                                mib(totals.bytes()),
+                                started.elapsed().as_secs_f64()
+                            ))),
+                            Err(e) => Ok(CommandResult::Error(format!("Backup failed: {e}"))),
                         }
                     }
-                    None => {
-                        // Clear current session history
-                        let db_guard = database.lock().unwrap();
-                        if let Some(session_id) =
-                            crate::history_manager::SessionId::from_database(&db_guard)
+                    Command::SqliteVacuum => {
+                        if crate::config_editor::can_run_interactive()
+                            && !confirm(
+                                "VACUUM rewrites the whole database file and blocks writers until it finishes. Continue?",
+                            )
                         {
-                            let history_filename = session_id.history_filename();
-                            let config_dir = match crate::config::Config::get_config_dir() {
-                                Ok(dir) => dir,
-                                Err(e) => {
-                                    return Ok(CommandResult::Error(format!(
-                                        "Failed to get config directory: {e}"
-                                    )));
-                                }
-                            };
-                            let history_path = config_dir.join(&history_filename);
-
-                            if history_path.exists() {
-                                match std::fs::remove_file(&history_path) {
-                                    Ok(_) => Ok(CommandResult::Output(format!(
-                                        "Cleared history for current session: {}",
-                                        session_id.display_name
-                                    ))),
-                                    Err(e) => Ok(CommandResult::Error(format!(
-                                        "Failed to clear current session history: {e}"
-                                    ))),
-                                }
-                            } else {
-                                Ok(CommandResult::Output(
-                                    "No history found for current session.".to_string(),
-                                ))
+                            return Ok(CommandResult::Output("Vacuum cancelled.".to_string()));
+                        }
+                        match db.sqlite_vacuum().await {
+                            Ok((before, after)) => Ok(CommandResult::Output(format!(
+                                "Vacuumed: {:.1} MiB → {:.1} MiB ({:.1} MiB reclaimed)",
+                                mib(before),
+                                mib(after),
+                                mib(before.saturating_sub(after))
+                            ))),
+                            Err(e) => Ok(CommandResult::Error(format!("VACUUM failed: {e}"))),
+                        }
+                    }
+                    Command::SqliteIntegrityCheck { quick } => {
+                        match db.sqlite_integrity_check(*quick).await {
+                            Ok(problems) if problems.is_empty() => Ok(CommandResult::Output(
+                                "Integrity check passed: ok".to_string(),
+                            )),
+                            Ok(problems) => Ok(CommandResult::Error(format!(
+                                "Integrity check found {} problem{}:\n  {}",
+                                problems.len(),
+                                if problems.len() == 1 { "" } else { "s" },
+                                problems.join("\n  ")
+                            ))),
+                            Err(e) => {
+                                Ok(CommandResult::Error(format!("Integrity check failed: {e}")))
                             }
-                        } else {
-                            Ok(CommandResult::Error(
-                                "No session information available for current connection."
-                                    .to_string(),
-                            ))
                         }
                     }
+                    _ => unreachable!(),
                 }
             }
 
-            // AI assistant commands
-            Command::AiStatus => {
-                let mut output = String::new();
-                let adapter = crate::ai::effective_provider(&config.ai);
-                output.push_str("AI Assistant Status:\n");
-                output.push_str(&format!("  Enabled: {}\n", config.ai.enabled));
-                output.push_str(&format!("  Model: {}\n", config.ai.model));
-                if crate::ai::explicit_provider(&config.ai).is_some() {
-                    output.push_str(&format!("  Provider: {} (from config)\n", adapter.as_str()));
-                } else {
-                    output.push_str(&format!(
-                        "  Provider: {} (inferred from model; set with \\ai provider)\n",
-                        adapter.as_str()
-                    ));
-                }
-                output.push_str(&format!("  Auth method: {}\n", config.ai.auth_method));
-                if let Some(ref endpoint) = config.ai.endpoint
-                    && !endpoint.is_empty()
-                {
-                    output.push_str(&format!("  Endpoint: {endpoint}\n"));
+            Command::ClickHouseInsert {
+                table,
+                file,
+                format,
+            } => {
+                let mut db = database.lock().unwrap();
+                let db_type = db.get_database_type();
+                if !matches!(db_type, DatabaseType::ClickHouse) {
+                    return Ok(CommandResult::Error(format!(
+                        "\\chinsert is only available for ClickHouse. Current database: {}",
+                        db_type.display_name()
+                    )));
                 }
-                output.push_str(&format!("  Execution mode: {}\n", config.ai.execution_mode));
-                output.push_str(&format!("  Streaming: {}\n", config.ai.streaming));
-                output.push_str(&format!("  History length: {}\n", config.ai.history_length));
 
-                // Check credential status for the active auth method
-                if config.ai.auth_method == crate::ai::config::AiAuthMethod::ChatgptSubscription {
-                    match crate::ai::chatgpt_auth::load_tokens() {
-                        Some(tokens) => {
-                            output.push_str(&format!(
-                                "  ChatGPT: signed in (account {})\n",
-                                tokens.account_id
-                            ));
-                        }
-                        None => match crate::ai::chatgpt_auth::load_codex_tokens() {
-                            Ok(tokens) => output.push_str(&format!(
-                                "  ChatGPT: signed in via Codex auth file (account {})\n",
-                                tokens.account_id
-                            )),
-                            Err(_) => output.push_str(
-                                "  ChatGPT: NOT signed in (run \\ai login or mount ~/.codex/auth.json)\n",
-                            ),
-                        },
-                    }
-                } else {
-                    match crate::ai::key_storage::detect_key_storage(adapter) {
-                        Some(method) => {
-                            output.push_str(&format!("  API key: stored via {method}\n"))
-                        }
-                        None => {
-                            if crate::ai::key_storage::requires_api_key(adapter) {
-                                output.push_str("  API key: NOT SET (run \\ai setup)\n");
-                            } else {
-                                output.push_str("  API key: not required (local provider)\n");
-                            }
-                        }
-                    }
+                let path = std::path::Path::new(file);
+                if !path.is_file() {
+                    return Ok(CommandResult::Error(format!("File not found: {file}")));
                 }
+                let format = match crate::database_clickhouse::resolve_bulk_insert_format(
+                    path,
+                    format.as_deref(),
+                ) {
+                    Ok(format) => format,
+                    Err(e) => return Ok(CommandResult::Error(e)),
+                };
 
-                output.push_str(
-                    "\nUsage: Type ?? followed by a natural language query to generate SQL",
-                );
-                Ok(CommandResult::Output(output))
+                let mib = |bytes: u64| bytes as f64 / (1024.0 * 1024.0);
+                let report = |progress: &crate::database_clickhouse::BulkInsertProgress| {
+                    eprint!(
+                        "\r  {} rows sent, {:.1}/{:.1} MiB ({:.0}%)",
+                        progress.rows,
+                        mib(progress.bytes),
+                        mib(progress.total_bytes),
+                        progress.percent()
+                    );
+                };
+                let started = std::time::Instant::now();
+                let result = db.bulk_insert_file(table, path, format, &report).await;
+                eprintln!();
+
+                match result {
+                    Ok(totals) => Ok(CommandResult::Output(format!(
+                        "Inserted {} rows into {table} from {file} as {format} ({} batch{}, {:.1} MiB, {:.1}s)",
+                        totals.rows,
+                        totals.batches,
+                        if totals.batches == 1 { "" } else { "es" },
+                        mib(totals.bytes),
+                        started.elapsed().as_secs_f64()
+                    ))),
+                    Err(e) => Ok(CommandResult::Error(format!("Bulk insert failed: {e}"))),
+                }
             }
 
-            Command::AiSetup => {
-                // This is handled interactively in cli_core.rs
-                // Return a marker that cli_core should handle
-                Ok(CommandResult::Output("__AI_SETUP__".to_string()))
-            }
+            // Vault credential cache commands
+            Command::VaultCacheStatus => {
+                if !config.vault_credential_cache_enabled {
+                    return Ok(CommandResult::Output(
+                        "Vault credential caching is disabled.".to_string(),
+                    ));
+                }
 
-            Command::AiSelectProvider { provider } => {
-                // This is handled interactively in cli_core.rs
-                match provider {
-                    Some(p) => Ok(CommandResult::Output(format!("__AI_PROVIDER__{p}"))),
-                    None => Ok(CommandResult::Output("__AI_PROVIDER__".to_string())),
+                let cached_creds = config.list_cached_vault_credentials();
+                if cached_creds.is_empty() {
+                    Ok(CommandResult::Output(
+                        "No vault credentials cached.".to_string(),
+                    ))
+                } else {
+                    let mut output = format!(
+                        "Vault credential cache status (showing {} entries):\n",
+                        cached_creds.len()
+                    );
+                    let now = chrono::Utc::now();
+
+                    for (key, creds) in cached_creds {
+                        let remaining_seconds = (creds.expire_time - now).num_seconds().max(0);
+                        let remaining_hours = remaining_seconds / 3600;
+                        let remaining_mins = (remaining_seconds % 3600) / 60;
+
+                        let status = if now >= creds.expire_time {
+                            "EXPIRED"
+                        } else if remaining_seconds < config.vault_cache_min_ttl_seconds as i64 {
+                            "EXPIRING SOON"
+                        } else {
+                            "VALID"
+                        };
+
+                        let holders = creds
+                            .holders
+                            .iter()
+                            .filter(|pid| crate::vault_lease::is_alive(**pid))
+                            .count();
+                        let in_use = match holders {
+                            0 => String::new(),
+                            1 => " - used by 1 instance".to_string(),
+                            n => format!(" - used by {n} instances"),
+                        };
+                        output.push_str(&format!(
+                            "  {} ({}) - {}h{}m remaining - {}{}\n",
+                            key, creds.username, remaining_hours, remaining_mins, status, in_use
+                        ));
+                    }
+                    Ok(CommandResult::Output(output))
                 }
             }
 
-            Command::AiSelectModel { model } => match model {
-                Some(m) => Ok(CommandResult::Output(format!("__AI_MODEL__{m}"))),
-                None => Ok(CommandResult::Output("__AI_MODEL__".to_string())),
+            Command::VaultCacheClear => match config.clear_vault_credentials() {
+                Ok(()) => Ok(CommandResult::Output(
+                    "All vault credentials cleared from cache.".to_string(),
+                )),
+                Err(e) => Ok(CommandResult::Error(format!(
+                    "Failed to clear vault credentials: {e}"
+                ))),
             },
 
-            Command::AiToggle { state } => match state {
-                Some(true) => {
-                    config.ai.enabled = true;
-                    config.save_with_documentation().ok();
-                    Ok(CommandResult::Output("AI assistant enabled.".to_string()))
-                }
-                Some(false) => {
-                    config.ai.enabled = false;
-                    config.save_with_documentation().ok();
-                    Ok(CommandResult::Output("AI assistant disabled.".to_string()))
-                }
-                None => {
-                    config.ai.enabled = !config.ai.enabled;
-                    config.save_with_documentation().ok();
-                    let status = if config.ai.enabled {
-                        "enabled"
-                    } else {
-                        "disabled"
-                    };
-                    Ok(CommandResult::Output(format!("AI assistant {status}.")))
+            Command::VaultCacheRefresh { role } => {
+                match role {
+                    Some(role_key) => {
+                        // Force refresh specific role - would need to implement role-specific refresh
+                        Ok(CommandResult::Output(format!(
+                            "Force refresh for role '{role_key}' not yet implemented."
+                        )))
+                    }
+                    None => {
+                        // Reload from file
+                        config.reload_vault_credentials();
+                        Ok(CommandResult::Output(
+                            "Vault credential cache reloaded from file.".to_string(),
+                        ))
+                    }
                 }
-            },
-
-            Command::AiClearHistory => {
-                // Handled in cli_core.rs where conversation state lives
-                Ok(CommandResult::Output("__AI_CLEAR_HISTORY__".to_string()))
             }
 
-            Command::AiLogin => {
-                // Browser + local callback server — handled in cli_core.rs
-                Ok(CommandResult::Output("__AI_LOGIN__".to_string()))
-            }
+            Command::VaultCacheExpired => {
+                let cached_creds = config.list_cached_vault_credentials();
+                let now = chrono::Utc::now();
+                let expired_creds: Vec<_> = cached_creds
+                    .into_iter()
+                    .filter(|(_, creds)| now >= creds.expire_time)
+                    .collect();
 
-            Command::AiLogout => {
-                crate::ai::chatgpt_auth::delete_tokens();
-                if config.ai.auth_method == crate::ai::config::AiAuthMethod::ChatgptSubscription {
-                    config.ai.auth_method = crate::ai::config::AiAuthMethod::ApiKey;
-                    config.save_with_documentation().ok();
+                if expired_creds.is_empty() {
+                    Ok(CommandResult::Output(
+                        "No expired vault credentials found.".to_string(),
+                    ))
+                } else {
+                    let mut output = format!(
+                        "Expired vault credentials ({} entries):\n",
+                        expired_creds.len()
+                    );
+                    for (key, creds) in expired_creds {
+                        let expired_since = (now - creds.expire_time).num_seconds();
+                        let expired_hours = expired_since / 3600;
+                        let expired_mins = (expired_since % 3600) / 60;
+
+                        output.push_str(&format!(
+                            "  {} ({}) - expired {}h{}m ago\n",
+                            key, creds.username, expired_hours, expired_mins
+                        ));
+                    }
+                    Ok(CommandResult::Output(output))
                 }
-                Ok(CommandResult::Output(
-                    "Signed out of ChatGPT; auth method is back to api_key.".to_string(),
-                ))
             }
 
-            Command::AiGenerateSql { .. } => {
-                // Handled via ?? prefix in cli_core.rs REPL loop
-                Ok(CommandResult::Continue)
-            }
-        }
-    }
+            // Password management commands
+            Command::SavePassword { .. } => {
+                use crate::dbcrust_pass::{DatabaseType, save_password};
+                use inquire::{Password, Select, Text};
 
-    fn description(&self) -> &'static str {
-        match self {
-            Command::Quit => "Quit the application",
-            Command::Help => "Show help information",
-            Command::ListDatabases => "List all databases",
-            Command::ListTables => "List tables in current database",
-            Command::DescribeTable { .. } => "Describe table structure",
-            Command::ConnectDatabase { .. } => "Connect to a different database",
-            Command::ToggleExpandedDisplay => "Toggle expanded/vertical display mode",
-            Command::ToggleExplainMode => "Toggle automatic EXPLAIN for queries",
-            Command::ToggleExplainTuiMode => "Toggle TUI explain visualizer mode",
-            Command::ShowConfig => "Show current configuration",
-            Command::ConfigMenu => "Interactive configuration menu",
-            Command::ConfigGet { .. } => "Print configuration value(s)",
-            Command::ConfigSet { .. } => "Set a configuration value",
-            Command::ConfigEdit => "Open config.toml in $EDITOR and reload",
-            Command::ListSessions => "List saved sessions",
-            Command::SaveSession { .. } => "Save current connection as a session",
-            Command::DeleteSession { .. } => "Delete a saved session",
-            Command::ConnectSession { .. } => "Connect to a saved session",
-            Command::ListRecentConnections => "List recent connections",
-            Command::ClearRecentConnections => "Clear recent connection history",
-            Command::ClearSessionHistory { .. } => "Clear session command history",
-            Command::ListNamedQueries => "List named queries",
-            Command::SaveNamedQuery { .. } => "Save a named query",
-            Command::DeleteNamedQuery { .. } => "Delete a named query",
-            Command::ExecuteNamedQuery { .. } => "Execute a named query",
-            Command::WriteScript { .. } => "Write script to file",
-            Command::LoadScript { .. } => "Load script from file",
-            Command::EditMultiline => "Enter multiline edit mode",
-            Command::ListUsers => "List database users",
-            Command::ListIndexes => "List database indexes",
-            Command::ListPragmas => "List database pragmas (SQLite)",
-            Command::ShowPgpass => "Show PostgreSQL .pgpass file info",
-            Command::ShowMyconf => "Show MySQL .my.cnf file info",
-            Command::ListDockerContainers => "List available database containers",
-            Command::CopyExplainPlan => "Copy EXPLAIN plan to clipboard",
-            Command::ExplainRaw { .. } => "Execute EXPLAIN query (raw output)",
-            Command::ExplainFormatted { .. } => {
-                "Execute EXPLAIN query (same as explain mode, supports \\ecopy)"
+                // Interactive prompts for all parameters
+                let database_types = vec![
+                    "postgresql",
+                    "mysql",
+                    "mongodb",
+                    "elasticsearch",
+                    "clickhouse",
+                    "snowflake",
+                    "sqlite",
+                ];
+                let db_type_str = Select::new("Database type:", database_types)
+                    .prompt()
+                    .map_err(|e| CommandError::InvalidSyntax(format!("Input error: {e}")))?;
+
+                let db_type = DatabaseType::from_str(db_type_str).unwrap();
+
+                let host = Text::new("Host:")
+                    .with_default("localhost")
+                    .prompt()
+                    .map_err(|e| CommandError::InvalidSyntax(format!("Input error: {e}")))?;
+
+                let port_str = Text::new("Port:")
+                    .with_default(match db_type {
+                        DatabaseType::PostgreSQL => "5432",
+                        DatabaseType::MySQL => "3306",
+                        DatabaseType::MongoDB => "27017",
+                        DatabaseType::Elasticsearch => "9200",
+                        DatabaseType::ClickHouse => "8123",
+                        DatabaseType::Snowflake => "443",
+                        DatabaseType::SQLite => "0", // Not used for SQLite
+                    })
+                    .prompt()
+                    .map_err(|e| CommandError::InvalidSyntax(format!("Input error: {e}")))?;
+
+                let port: u16 = port_str
+                    .parse()
+                    .map_err(|_| CommandError::InvalidSyntax("Invalid port number".to_string()))?;
+
+                let database = Text::new("Database name:")
+                    .prompt()
+                    .map_err(|e| CommandError::InvalidSyntax(format!("Input error: {e}")))?;
+
+                let username = Text::new("Username:")
+                    .prompt()
+                    .map_err(|e| CommandError::InvalidSyntax(format!("Input error: {e}")))?;
+
+                let password = Password::new("Password:")
+                    .without_confirmation()
+                    .prompt()
+                    .map_err(|e| {
+                        CommandError::InvalidSyntax(format!("Password input error: {e}"))
+                    })?;
+
+                // Always encrypt passwords by default (no confirmation prompt)
+                let encrypt = true;
+
+                match save_password(
+                    db_type.clone(),
+                    &host,
+                    port,
+                    &database,
+                    &username,
+                    &password,
+                    encrypt,
+                ) {
+                    Ok(()) => Ok(CommandResult::Output(format!(
+                        "Password saved for {}:{}@{}:{}/{} (encrypted)",
+                        db_type.as_str(),
+                        username,
+                        host,
+                        port,
+                        database
+                    ))),
+                    Err(e) => Ok(CommandResult::Error(format!(
+                        "Failed to save password: {e}"
+                    ))),
+                }
             }
-            Command::ExplainExport { .. } => "Execute EXPLAIN query and export to file",
-            Command::SetMultilineIndicator { .. } => "Set custom multiline prompt indicator",
-            Command::TogglePager => "Toggle pager for long output",
-            Command::ToggleBanner => "Toggle startup banner display",
-            Command::ToggleServerInfo => "Toggle server info display on connection",
-            Command::ToggleAutocomplete => "Toggle autocomplete functionality",
-            Command::ToggleColumnSelection => "Toggle forced column selection mode (on/off)",
-            Command::SetColumnSelectionThreshold { .. } => "Set column selection threshold",
-            Command::ClearColumnViews => "Clear saved column views",
-            Command::ResetView => "Reset all view settings to defaults",
-            // Vector display commands
-            Command::SetVectorDisplayMode { .. } => {
-                "Set vector display mode (full, truncated, summary, viz)"
+
+            Command::ListPasswords => {
+                use crate::dbcrust_pass::list_entries;
+
+                match list_entries() {
+                    Ok(entries) => {
+                        if entries.is_empty() {
+                            Ok(CommandResult::Output(
+                                "No saved passwords found.".to_string(),
+                            ))
+                        } else {
+                            let mut output = "Saved passwords:\n".to_string();
+                            for (db_type, host, port, database, username) in entries {
+                                output.push_str(&format!(
+                                    "  {}:{}@{}:{}/{}\n",
+                                    db_type.as_str(),
+                                    username,
+                                    host,
+                                    port,
+                                    database
+                                ));
+                            }
+                            Ok(CommandResult::Output(output))
+                        }
+                    }
+                    Err(e) => Ok(CommandResult::Error(format!(
+                        "Failed to list passwords: {e}"
+                    ))),
+                }
             }
-            Command::ShowVectorDisplayConfig => "Show current vector display configuration",
-            Command::ToggleVectorStatistics => "Toggle vector statistics display",
-            Command::ShowPoolStats => "Show connection pool statistics",
-            // Complex display commands
-            Command::ComplexDisplayMode { .. } => "Set complex data display mode",
-            Command::ComplexDisplayJsonToggle => "Toggle JSON pretty printing",
-            // Vault credential cache commands
-            Command::VaultCacheStatus => "Show vault credential cache status",
-            Command::VaultCacheClear => "Clear all cached vault credentials",
-            Command::VaultCacheRefresh { .. } => "Refresh vault credential cache",
-            Command::VaultCacheExpired => "Show expired vault credentials",
-            // Password management commands
-            Command::SavePassword { .. } => "Save password to .dbcrust file (interactive)",
+
+            Command::DeletePassword { .. } => {
+                use crate::dbcrust_pass::{delete_password, list_entries};
+                use inquire::Select;
+
+                // First list available passwords
+                let entries = match list_entries() {
+                    Ok(entries) => entries,
+                    Err(e) => {
+                        return Ok(CommandResult::Error(format!(
+                            "Failed to list passwords: {e}"
+                        )));
+                    }
+                };
+
+                if entries.is_empty() {
+                    return Ok(CommandResult::Output(
+                        "No saved passwords to delete.".to_string(),
+                    ));
+                }
+
+                // Create selection list
+                let options: Vec<String> = entries
+                    .iter()
+                    .map(|(db_type, host, port, database, username)| {
+                        format!(
+                            "{}:{}@{}:{}/{}",
+                            db_type.as_str(),
+                            username,
+                            host,
+                            port,
+                            database
+                        )
+                    })
+                    .collect();
+
+                let selection = Select::new("Select password to delete:", options)
+                    .prompt()
+                    .map_err(|e| CommandError::InvalidSyntax(format!("Input error: {e}")))?;
+
+                // Find the selected entry
+                let selected_entry =
+                    entries
+                        .iter()
+                        .find(|(db_type, host, port, database, username)| {
+                            format!(
+                                "{}:{}@{}:{}/{}",
+                                db_type.as_str(),
+                                username,
+                                host,
+                                port,
+                                database
+                            ) == selection
+                        });
+
+                if let Some((db_type, host, port_str, database, username)) = selected_entry {
+                    let port: u16 = port_str.parse().map_err(|_| {
+                        CommandError::InvalidSyntax("Invalid port number".to_string())
+                    })?;
+
+                    match delete_password(db_type.clone(), host, port, database, username) {
+                        Ok(true) => Ok(CommandResult::Output(format!(
+                            "Password deleted: {selection}"
+                        ))),
+                        Ok(false) => Ok(CommandResult::Output(
+                            "No matching password found to delete.".to_string(),
+                        )),
+                        Err(e) => Ok(CommandResult::Error(format!(
+                            "Failed to delete password: {e}"
+                        ))),
+                    }
+                } else {
+                    Ok(CommandResult::Error(
+                        "Selected entry not found.".to_string(),
+                    ))
+                }
+            }
+
+            Command::EncryptPasswords => {
+                use crate::dbcrust_pass::encrypt_all_passwords;
+
+                match encrypt_all_passwords() {
+                    Ok(0) => Ok(CommandResult::Output(
+                        "No plaintext passwords found to encrypt.".to_string(),
+                    )),
+                    Ok(count) => Ok(CommandResult::Output(format!(
+                        "Encrypted {count} password(s) in .dbcrust file."
+                    ))),
+                    Err(e) => Ok(CommandResult::Error(format!(
+                        "Failed to encrypt passwords: {e}"
+                    ))),
+                }
+            }
+
+            Command::ShowPoolStats => {
+                let db = database.lock().unwrap();
+                let connection_status = if db.is_connected().await {
+                    "✅ Connected"
+                } else {
+                    "❌ Disconnected"
+                };
+
+                let output = format!(
+                    "Connection Status: {connection_status}\n\nNote: Detailed pool statistics are no longer available.\nConnection pooling is now managed by database-specific clients."
+                );
+
+                Ok(CommandResult::Output(output))
+            }
+
+            Command::ComplexDisplayMode { mode } => {
+                match mode {
+                    None => {
+                        // Show current mode
+                        let current_mode = config.complex_display.display_mode.clone();
+                        Ok(CommandResult::Output(format!(
+                            "Current complex display mode: {current_mode}"
+                        )))
+                    }
+                    Some(mode_str) => {
+                        // Set new mode
+                        use crate::complex_display::ComplexDisplayMode;
+                        match ComplexDisplayMode::from_str(mode_str) {
+                            Some(new_mode) => {
+                                config.complex_display.display_mode = new_mode.clone();
+                                // Update global config
+                                crate::complex_display::set_global_complex_config(
+                                    config.complex_display.clone(),
+                                );
+
+                                // Save config
+                                if let Err(e) = config.save_with_documentation() {
+                                    return Ok(CommandResult::Error(format!(
+                                        "Failed to save config: {e}"
+                                    )));
+                                }
+
+                                Ok(CommandResult::Output(format!(
+                                    "Complex display mode set to: {new_mode}"
+                                )))
+                            }
+                            None => Ok(CommandResult::Error(
+                                "Invalid mode. Available modes: full, truncated, summary, viz"
+                                    .to_string(),
+                            )),
+                        }
+                    }
+                }
+            }
+
+            Command::ComplexDisplayJsonToggle => {
+                // Toggle JSON pretty printing
+                config.complex_display.json_pretty_print =
+                    !config.complex_display.json_pretty_print;
+
+                // Update global config
+                crate::complex_display::set_global_complex_config(config.complex_display.clone());
+
+                // Save config
+                if let Err(e) = config.save_with_documentation() {
+                    return Ok(CommandResult::Error(format!("Failed to save config: {e}")));
+                }
+
+                let status = if config.complex_display.json_pretty_print {
+                    "enabled"
+                } else {
+                    "disabled"
+                };
+                Ok(CommandResult::Output(format!(
+                    "JSON pretty printing {status}"
+                )))
+            }
+
+            Command::Flatten {
+                depth,
+                arrays,
+                save,
+                forget,
+            } => {
+                use crate::flatten::{FlattenSpec, SavedSpecs, TypedRows};
+
+                let db = database.lock().unwrap();
+                let db_type = db.get_database_type();
+                if !matches!(db_type, DatabaseType::MongoDB | DatabaseType::Elasticsearch) {
+                    return Ok(CommandResult::Error(format!(
+                        "\\flatten is only available for MongoDB and Elasticsearch. Current database: {}",
+                        db_type.display_name()
+                    )));
+                }
+                let key = db
+                    .last_row_query()
+                    .and_then(crate::flatten::collection_of)
+                    .map(|collection| format!("{}.{collection}", db.get_current_db()));
+                let mut saved = SavedSpecs::load();
+
+                if *forget {
+                    let Some(key) = key else {
+                        return Ok(CommandResult::Error(
+                            "Run a query on the collection whose saved spec to forget first"
+                                .to_string(),
+                        ));
+                    };
+                    return Ok(match saved.remove(&key) {
+                        Ok(true) => {
+                            CommandResult::Output(format!("Forgot the flatten spec of {key}"))
+                        }
+                        Ok(false) => {
+                            CommandResult::Output(format!("No flatten spec saved for {key}"))
+                        }
+                        Err(e) => CommandResult::Error(e),
+                    });
+                }
+
+                let Some(query) = db.last_row_query() else {
+                    return Ok(CommandResult::Error(
+                        "No query has returned rows yet. Run a find, aggregate or SELECT first"
+                            .to_string(),
+                    ));
+                };
+                let base = key
+                    .as_deref()
+                    .and_then(|key| saved.get(key))
+                    .unwrap_or_default();
+                let spec = FlattenSpec {
+                    depth: depth.unwrap_or(base.depth),
+                    arrays: arrays.unwrap_or(base.arrays),
+                };
+                let Some(client) = db.get_database_client() else {
+                    return Ok(CommandResult::Error(
+                        "Not connected to a database".to_string(),
+                    ));
+                };
+                let mut typed = TypedRows::default();
+                if let Err(e) = client.stream_query(query, &mut typed).await {
+                    return Ok(CommandResult::Error(format!("Query failed: {e}")));
+                }
+                if typed.rows.is_empty() {
+                    return Ok(CommandResult::Output(
+                        "The query returned no documents.".to_string(),
+                    ));
+                }
+                let table = crate::flatten::flatten(&typed.columns, &typed.rows, &spec);
+
+                let mut note = format!("Flattened: {spec}");
+                if *save {
+                    match &key {
+                        Some(key) => match saved.set(key, spec) {
+                            Ok(()) => note.push_str(&format!(" (saved for {key})")),
+                            Err(e) => return Ok(CommandResult::Error(e)),
+                        },
+                        None => {
+                            note.push_str(" (not saved: the query names no collection)");
+                        }
+                    }
+                }
+                Ok(CommandResult::Output(format!(
+                    "{}\n{note}",
+                    crate::format::format_query_results_psql(&table)
+                )))
+            }
+
+            // MongoDB-specific commands
+            Command::ListCollections => {
+                let mut db = database.lock().unwrap();
+                match db.list_collections().await {
+                    Ok(results) => {
+                        if results.is_empty() {
+                            Ok(CommandResult::Output("No collections found.".to_string()))
+                        } else {
+                            let output = if db.is_expanded_display() {
+                                let tables = crate::format::format_query_results_expanded(&results);
+                                tables
+                                    .into_iter()
+                                    .map(|t| t.to_string())
+                                    .collect::<Vec<_>>()
+                                    .join("\n")
+                            } else {
+                                crate::format::format_query_results_psql(&results)
+                            };
+                            Ok(CommandResult::Output(output))
+                        }
+                    }
+                    Err(e) => Ok(CommandResult::Error(format!(
+                        "Failed to list collections: {e}"
+                    ))),
+                }
+            }
+
+            Command::DescribeCollection { collection_name } => {
+                let mut db = database.lock().unwrap();
+                match db.describe_collection(collection_name).await {
+                    Ok(details) => {
+                        let output = crate::format::format_table_details(&details);
+                        Ok(CommandResult::Output(output))
+                    }
+                    Err(e) => Ok(CommandResult::Error(format!(
+                        "Failed to describe collection '{collection_name}': {e}"
+                    ))),
+                }
+            }
+
+            Command::ListMongoIndexes => {
+                let mut db = database.lock().unwrap();
+                match db.list_mongo_indexes().await {
+                    Ok(results) => {
+                        if results.is_empty() {
+                            Ok(CommandResult::Output("No indexes found.".to_string()))
+                        } else {
+                            let output = if db.is_expanded_display() {
+                                let tables = crate::format::format_query_results_expanded(&results);
+                                tables
+                                    .into_iter()
+                                    .map(|t| t.to_string())
+                                    .collect::<Vec<_>>()
+                                    .join("\n")
+                            } else {
+                                crate::format::format_query_results_psql(&results)
+                            };
+                            Ok(CommandResult::Output(output))
+                        }
+                    }
+                    Err(e) => Ok(CommandResult::Error(format!("Failed to list indexes: {e}"))),
+                }
+            }
+
+            Command::CreateMongoIndex {
+                collection,
+                field,
+                index_type,
+            } => {
+                let mut db = database.lock().unwrap();
+                match db
+                    .create_mongo_index(collection, field, index_type.as_deref())
+                    .await
+                {
+                    Ok(_) => Ok(CommandResult::Output(format!(
+                        "Index created successfully on collection '{collection}' field '{field}'"
+                    ))),
+                    Err(e) => Ok(CommandResult::Error(format!("Failed to create index: {e}"))),
+                }
+            }
+
+            Command::DropMongoIndex {
+                collection,
+                index_name,
+            } => {
+                let mut db = database.lock().unwrap();
+                match db.drop_mongo_index(collection, index_name).await {
+                    Ok(_) => Ok(CommandResult::Output(format!(
+                        "Index '{index_name}' dropped successfully from collection '{collection}'"
+                    ))),
+                    Err(e) => Ok(CommandResult::Error(format!("Failed to drop index: {e}"))),
+                }
+            }
+
+            Command::MongoStats => {
+                let mut db = database.lock().unwrap();
+                match db.mongo_stats().await {
+                    Ok(results) => {
+                        let output = if db.is_expanded_display() {
+                            let tables = crate::format::format_query_results_expanded(&results);
+                            tables
+                                .into_iter()
+                                .map(|t| t.to_string())
+                                .collect::<Vec<_>>()
+                                .join("\n")
+                        } else {
+                            crate::format::format_query_results_psql(&results)
+                        };
+                        Ok(CommandResult::Output(output))
+                    }
+                    Err(e) => Ok(CommandResult::Error(format!(
+                        "Failed to get MongoDB stats: {e}"
+                    ))),
+                }
+            }
+
+            Command::MongoFind {
+                collection,
+                filter,
+                projection,
+                limit,
+            } => {
+                let mut db = database.lock().unwrap();
+                match db
+                    .mongo_find(collection, filter.as_deref(), projection.as_deref(), *limit)
+                    .await
+                {
+                    Ok(results) => {
+                        let output = if db.is_expanded_display() {
+                            let tables = crate::format::format_query_results_expanded(&results);
+                            tables
+                                .into_iter()
+                                .map(|t| t.to_string())
+                                .collect::<Vec<_>>()
+                                .join("\n")
+                        } else {
+                            crate::format::format_query_results_psql(&results)
+                        };
+                        Ok(CommandResult::Output(output))
+                    }
+                    Err(e) => Ok(CommandResult::Error(format!("Failed to execute find: {e}"))),
+                }
+            }
+
+            Command::MongoAggregate {
+                collection,
+                pipeline,
+            } => {
+                let mut db = database.lock().unwrap();
+                match db.mongo_aggregate(collection, pipeline).await {
+                    Ok(results) => {
+                        let output = if db.is_expanded_display() {
+                            let tables = crate::format::format_query_results_expanded(&results);
+                            tables
+                                .into_iter()
+                                .map(|t| t.to_string())
+                                .collect::<Vec<_>>()
+                                .join("\n")
+                        } else {
+                            crate::format::format_query_results_psql(&results)
+                        };
+                        Ok(CommandResult::Output(output))
+                    }
+                    Err(e) => Ok(CommandResult::Error(format!(
+                        "Failed to execute aggregation: {e}"
+                    ))),
+                }
+            }
+            Command::MongoTextSearch {
+                collection,
+                search_term,
+            } => {
+                let mut db = database.lock().unwrap();
+                match db.mongo_text_search(collection, search_term).await {
+                    Ok(results) => {
+                        let output = if db.is_expanded_display() {
+                            let tables = crate::format::format_query_results_expanded(&results);
+                            tables
+                                .into_iter()
+                                .map(|t| t.to_string())
+                                .collect::<Vec<_>>()
+                                .join("\n")
+                        } else {
+                            crate::format::format_query_results_psql(&results)
+                        };
+                        Ok(CommandResult::Output(output))
+                    }
+                    Err(e) => Ok(CommandResult::Error(format!(
+                        "Failed to execute text search: {e}"
+                    ))),
+                }
+            }
+
+            // History management commands
+            Command::ClearSessionHistory { session_hash } => {
+                let history_manager =
+                    match crate::history_manager::SessionHistoryManager::new(config) {
+                        Ok(manager) => manager,
+                        Err(e) => {
+                            return Ok(CommandResult::Error(format!(
+                                "Failed to create history manager: {e}"
+                            )));
+                        }
+                    };
+
+                match session_hash {
+                    Some(hash) => {
+                        // Clear specific session history
+                        let histories = match history_manager.list_session_histories() {
+                            Ok(h) => h,
+                            Err(e) => {
+                                return Ok(CommandResult::Error(format!(
+                                    "Failed to list histories: {e}"
+                                )));
+                            }
+                        };
+
+                        if let Some(history) = histories.iter().find(|h| h.session_hash == *hash) {
+                            match std::fs::remove_file(&history.path) {
+                                Ok(_) => Ok(CommandResult::Output(format!(
+                                    "Cleared history for session hash: {hash}"
+                                ))),
+                                Err(e) => Ok(CommandResult::Error(format!(
+                                    "Failed to clear history: {e}"
+                                ))),
+                            }
+                        } else {
+                            Ok(CommandResult::Error(format!(
+                                "No history found for session hash: {hash}"
+                            )))
+                        }
+                    }
+                    None => {
+                        // Clear current session history
+                        let db_guard = database.lock().unwrap();
+                        if let Some(session_id) =
+                            crate::history_manager::SessionId::from_database(&db_guard)
+                        {
+                            let history_filename = session_id.history_filename();
+                            let config_dir = match crate::config::Config::get_config_dir() {
+                                Ok(dir) => dir,
+                                Err(e) => {
+                                    return Ok(CommandResult::Error(format!(
+                                        "Failed to get config directory: {e}"
+                                    )));
+                                }
+                            };
+                            let history_path = config_dir.join(&history_filename);
+
+                            if history_path.exists() {
+                                match std::fs::remove_file(&history_path) {
+                                    Ok(_) => Ok(CommandResult::Output(format!(
+                                        "Cleared history for current session: {}",
+                                        session_id.display_name
+                                    ))),
+                                    Err(e) => Ok(CommandResult::Error(format!(
+                                        "Failed to clear current session history: {e}"
+                                    ))),
+                                }
+                            } else {
+                                Ok(CommandResult::Output(
+                                    "No history found for current session.".to_string(),
+                                ))
+                            }
+                        } else {
+                            Ok(CommandResult::Error(
+                                "No session information available for current connection."
+                                    .to_string(),
+                            ))
+                        }
+                    }
+                }
+            }
+
+            // AI assistant commands
+            Command::AiStatus => {
+                let mut output = String::new();
+                let adapter = crate::ai::effective_provider(&config.ai);
+                output.push_str("AI Assistant Status:\n");
+                output.push_str(&format!("  Enabled: {}\n", config.ai.enabled));
+                output.push_str(&format!("  Model: {}\n", config.ai.model));
+                if crate::ai::explicit_provider(&config.ai).is_some() {
+                    output.push_str(&format!("  Provider: {} (from config)\n", adapter.as_str()));
+                } else {
+                    output.push_str(&format!(
+                        "  Provider: {} (inferred from model; set with \\ai provider)\n",
+                        adapter.as_str()
+                    ));
+                }
+                output.push_str(&format!("  Auth method: {}\n", config.ai.auth_method));
+                if let Some(ref endpoint) = config.ai.endpoint
+                    && !endpoint.is_empty()
+                {
+                    output.push_str(&format!("  Endpoint: {endpoint}\n"));
+                }
+                output.push_str(&format!("  Execution mode: {}\n", config.ai.execution_mode));
+                output.push_str(&format!("  Streaming: {}\n", config.ai.streaming));
+                output.push_str(&format!("  History length: {}\n", config.ai.history_length));
+
+                // Check credential status for the active auth method
+                if config.ai.auth_method == crate::ai::config::AiAuthMethod::ChatgptSubscription {
+                    match crate::ai::chatgpt_auth::load_tokens() {
+                        Some(tokens) => {
+                            output.push_str(&format!(
+                                "  ChatGPT: signed in (account {})\n",
+                                tokens.account_id
+                            ));
+                        }
+                        None => match crate::ai::chatgpt_auth::load_codex_tokens() {
+                            Ok(tokens) => output.push_str(&format!(
+                                "  ChatGPT: signed in via Codex auth file (account {})\n",
+                                tokens.account_id
+                            )),
+                            Err(_) => output.push_str(
+                                "  ChatGPT: NOT signed in (run \\ai login or mount ~/.codex/auth.json)\n",
+                            ),
+                        },
+                    }
+                } else {
+                    match crate::ai::key_storage::detect_key_storage(adapter) {
+                        Some(method) => {
+                            output.push_str(&format!("  API key: stored via {method}\n"))
+                        }
+                        None => {
+                            if crate::ai::key_storage::requires_api_key(adapter) {
+                                output.push_str("  API key: NOT SET (run \\ai setup)\n");
+                            } else {
+                                output.push_str("  API key: not required (local provider)\n");
+                            }
+                        }
+                    }
+                }
+
+                output.push_str(
+                    "\nUsage: Type ?? followed by a natural language query to generate SQL",
+                );
+                Ok(CommandResult::Output(output))
+            }
+
+            Command::AiSetup => {
+                // This is handled interactively in cli_core.rs
+                // Return a marker that cli_core should handle
+                Ok(CommandResult::Output("__AI_SETUP__".to_string()))
+            }
+
+            Command::AiSelectProvider { provider } => {
+                // This is handled interactively in cli_core.rs
+                match provider {
+                    Some(p) => Ok(CommandResult::Output(format!("__AI_PROVIDER__{p}"))),
+                    None => Ok(CommandResult::Output("__AI_PROVIDER__".to_string())),
+                }
+            }
+
+            Command::AiSelectModel { model } => match model {
+                Some(m) => Ok(CommandResult::Output(format!("__AI_MODEL__{m}"))),
+                None => Ok(CommandResult::Output("__AI_MODEL__".to_string())),
+            },
+
+            Command::AiToggle { state } => match state {
+                Some(true) => {
+                    config.ai.enabled = true;
+                    config.save_with_documentation().ok();
+                    Ok(CommandResult::Output("AI assistant enabled.".to_string()))
+                }
+                Some(false) => {
+                    config.ai.enabled = false;
+                    config.save_with_documentation().ok();
+                    Ok(CommandResult::Output("AI assistant disabled.".to_string()))
+                }
+                None => {
+                    config.ai.enabled = !config.ai.enabled;
+                    config.save_with_documentation().ok();
+                    let status = if config.ai.enabled {
+                        "enabled"
+                    } else {
+                        "disabled"
+                    };
+                    Ok(CommandResult::Output(format!("AI assistant {status}.")))
+                }
+            },
+
+            Command::AiClearHistory => {
+                // Handled in cli_core.rs where conversation state lives
+                Ok(CommandResult::Output("__AI_CLEAR_HISTORY__".to_string()))
+            }
+
+            Command::AiLogin => {
+                // Browser + local callback server — handled in cli_core.rs
+                Ok(CommandResult::Output("__AI_LOGIN__".to_string()))
+            }
+
+            Command::AiLogout => {
+                crate::ai::chatgpt_auth::delete_tokens();
+                if config.ai.auth_method == crate::ai::config::AiAuthMethod::ChatgptSubscription {
+                    config.ai.auth_method = crate::ai::config::AiAuthMethod::ApiKey;
+                    config.save_with_documentation().ok();
+                }
+                Ok(CommandResult::Output(
+                    "Signed out of ChatGPT; auth method is back to api_key.".to_string(),
+                ))
+            }
+
+            Command::AiGenerateSql { .. } => {
+                // Handled via ?? prefix in cli_core.rs REPL loop
+                Ok(CommandResult::Continue)
+            }
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            Command::Quit => "Quit the application",
+            Command::Help { .. } => "Show help information",
+            Command::ListDatabases => "List all databases",
+            Command::Whoami => {
+                "Show the connected user, roles, database, encryption, tunnel and Vault lease"
+            }
+            Command::SetRole { .. } => {
+                "Switch the session to another role after checking it exists (SET ROLE)"
+            }
+            Command::ResetRole => "Switch the session back to the login role",
+            Command::ShowSsl => {
+                "Show the negotiated TLS version and cipher, the server certificate and whether it is trusted"
+            }
+            Command::ReloadSsl => {
+                "Reconnect so a rotated client certificate and key are read again"
+            }
+            Command::Stats { .. } => {
+                "Show queries, rows, bytes, query time and cache hits for this session"
+            }
+            Command::ShowResultCache => "Show whether the result cache is on and what it holds",
+            Command::SetResultCache { .. } => "Turn the result cache on or off for this session",
+            Command::ClearResultCache => "Drop every cached result",
+            Command::ShowQueue => "List the statements queued while the connection was down",
+            Command::ClearQueue => "Drop the statements queued while the connection was down",
+            Command::RunQueue => "Run the statements queued while the connection was down",
+            Command::RefreshSchema => {
+                "Re-read the table and column names completion saved for this connection"
+            }
+            Command::ShowQueryTags => "Show the comment statements are tagged with",
+            Command::SetQueryTags { .. } => "Tag following statements with key=value pairs",
+            Command::ClearQueryTags => "Remove every tag set with \\tag",
+            Command::ListTables => "List tables in current database",
+            Command::DescribeTable { .. } => "Describe table structure",
+            Command::ConnectDatabase { .. } => "Connect to a database, saved session, alias or URL",
+            Command::Schema { .. } => {
+                "Show the active schema, or make another one the default for unqualified names"
+            }
+            Command::ListSchemas => "List schemas with their number of tables and views",
+            Command::Warehouse { .. } => {
+                "Show the warehouse statements run on, or switch to another one (Snowflake)"
+            }
+            Command::DbSize => "Show the size of each database",
+            Command::TableSize { .. } => "Show the largest tables with their data and index sizes",
+            Command::ToggleExpandedDisplay => "Toggle expanded/vertical display mode",
+            Command::Transpose { .. } => {
+                "Show the last result with columns as rows, one column per row of the result"
+            }
+            Command::Pivot { .. } => {
+                "Show the last result as a cross-tab: row key values down, column key values across"
+            }
+            Command::Dedup { .. } => {
+                "Show the last result without duplicate rows, or keeping one row per key columns"
+            }
+            Command::ListSnapshots => "List the saved result snapshots",
+            Command::SaveSnapshot { .. } => {
+                "Save the last result with its query and the time it ran, to diff against later"
+            }
+            Command::DiffSnapshot { .. } => {
+                "Run a snapshot's query again and show the rows added, removed and changed since"
+            }
+            Command::DeleteSnapshot { .. } => "Delete a saved result snapshot",
+            Command::ShowMasking => "Show whether result masking is on",
+            Command::SetMasking { .. } => "Turn masking of sensitive values in results on or off",
+            Command::ToggleExplainMode => "Toggle automatic EXPLAIN for queries",
+            Command::ToggleExplainTuiMode => "Toggle TUI explain visualizer mode",
+            Command::ExportExplainPlan { .. } => {
+                "Save the last EXPLAIN plan as folded stacks, Graphviz DOT or JSON"
+            }
+            Command::UploadExplainPlan { .. } => {
+                "Publish the last EXPLAIN plan on explain.dalibo.com or explain.depesz.com"
+            }
+            Command::ShowConfig => "Show current configuration",
+            Command::ConfigMenu => "Interactive configuration menu",
+            Command::ConfigGet { .. } => "Print configuration value(s)",
+            Command::ConfigSet { .. } => "Set a configuration value",
+            Command::ConfigUnset { .. } => "Reset a configuration value to its default",
+            Command::ConfigEdit => "Open config.toml in $EDITOR and reload",
+            Command::ReloadConfig => "Reload config.toml from disk and report changed settings",
+            Command::ListSnippets => "List the snippets expanded with Tab in the REPL",
+            Command::ShowKeys => "List the REPL's key bindings, [keybindings] first",
+            Command::ShowTablePrefs => "Show the display settings remembered per table",
+            Command::ClearTablePrefs { .. } => "Forget remembered display settings",
+            Command::SetTablePrefsSort { .. } => "Remember a sort order for the last queried table",
+            Command::AddSnippet { .. } => "Add or replace a snippet and save it to config.toml",
+            Command::RemoveSnippet { .. } => "Remove a snippet from config.toml",
+            Command::ListSessions => "List saved sessions",
+            Command::SaveSession { .. } => "Save current connection as a session",
+            Command::DeleteSession { .. } => "Delete a saved session",
+            Command::ConnectSession { .. } => "Connect to a saved session",
+            Command::ListRecentConnections => "List recent connections",
+            Command::ClearRecentConnections => "Clear recent connection history",
+            Command::ClearSessionHistory { .. } => "Clear session command history",
+            Command::ListNamedQueries => "List named queries",
+            Command::SaveNamedQuery { .. } => "Save a named query",
+            Command::DeleteNamedQuery { .. } => "Delete a named query",
+            Command::ExecuteNamedQuery { .. } => "Execute a named query",
+            Command::WriteScript { .. } => "Write script to file",
+            Command::LoadScript { .. } => "Load script from file",
+            Command::EditMultiline => "Enter multiline edit mode",
+            Command::Watch { .. } => {
+                "Re-run the previous query or command every n seconds until Ctrl-C"
+            }
+            Command::Rerun => "Run the previous query or command again",
+            Command::ListUsers => "List database users",
+            Command::ListIndexes => "List database indexes",
+            Command::ListPragmas => "List database pragmas (SQLite)",
+            Command::ShowFunction { .. } => {
+                "Show a PostgreSQL function's source, or edit and replace it"
+            }
+            Command::ShowPgpass => "Show PostgreSQL .pgpass file info",
+            Command::ShowMyconf => "Show MySQL .my.cnf file info",
+            Command::ListDockerContainers => "List available database containers",
+            Command::CopyExplainPlan => "Copy EXPLAIN plan to clipboard",
+            Command::Paste => {
+                "Format the SQL on the clipboard for the current database and place it in the edit buffer"
+            }
+            Command::ExplainRaw { .. } => "Execute EXPLAIN query (raw output)",
+            Command::ExplainFormatted { .. } => {
+                "Execute EXPLAIN query (same as explain mode, supports \\ecopy)"
+            }
+            Command::ExplainExport { .. } => "Execute EXPLAIN query and export to file",
+            Command::Export { .. } => {
+                "Stream a query's rows to a file (or - for stdout) without buffering the result"
+            }
+            Command::SaveBlob { .. } => {
+                "Write the raw bytes of a binary cell of the last result to a file"
+            }
+            Command::Gexec { .. } => {
+                "Run every value of the last (or given) query's result as a statement, after a preview"
+            }
+            Command::Dump { .. } => {
+                "Write a table, or every table of the database, to a file as portable SQL"
+            }
+            Command::Restore { .. } => {
+                "Run a SQL dump file in one transaction, batching its INSERTs"
+            }
+            Command::FedJoin { .. } => {
+                "Run a query on another connection and load its rows into a table to join against"
+            }
+            Command::FedJoinRun { .. } => "Run a query on the local \\fedjoin scratch database",
+            Command::ListFedJoins => "List the tables loaded with \\fedjoin",
+            Command::ClearFedJoins => "Drop every table loaded with \\fedjoin",
+            Command::ShowScratch => "Open the local scratch database and list its tables",
+            Command::ScratchImport { .. } => {
+                "Copy the last result into a table of the local scratch database"
+            }
+            Command::ScratchRun { .. } => "Run a query on the local scratch database",
+            Command::ClearScratch => "Drop the local scratch database",
+            Command::Lint { .. } => "Check a query for anti-patterns without running it",
+            Command::Format { .. } => {
+                "Pretty-print the last query run, or the given one, for the current database"
+            }
+            Command::DryRun { .. } => {
+                "Show the rows an UPDATE/DELETE would change and its plan, without running it"
+            }
+            Command::Count { .. } => {
+                "Count the rows of a table, a query, or the last query, ignoring ORDER BY and LIMIT"
+            }
+            Command::InsertRow { .. } => {
+                "Prompt for column values and insert one or more rows into a table"
+            }
+            Command::GenRows { .. } => {
+                "Generate plausible rows for a table and insert them in batches"
+            }
+            Command::PasteData { .. } => {
+                "Check spreadsheet rows from the clipboard against a table and insert them"
+            }
+            Command::SetMultilineIndicator { .. } => "Set custom multiline prompt indicator",
+            Command::TogglePager => "Toggle pager for long output",
+            Command::ToggleBanner => "Toggle startup banner display",
+            Command::ToggleServerInfo => "Toggle server info display on connection",
+            Command::SetDisplayTimezone { .. } => "Show or set the time zone for timestamps",
+            Command::SetTableStyle { .. } => "Show or set the border style of result tables",
+            Command::ToggleAutocomplete => "Toggle autocomplete functionality",
+            Command::ToggleColumnSelection => "Toggle forced column selection mode (on/off)",
+            Command::SetColumnSelectionThreshold { .. } => "Set column selection threshold",
+            Command::ClearColumnViews => "Forget column selections made this session",
+            Command::SaveColumnView { .. } => {
+                "Save the last column selection as a named view for its query"
+            }
+            Command::ListColumnViews => "List saved column views",
+            Command::DeleteColumnView { .. } => "Delete a saved column view",
+            Command::ResetView => "Reset all view settings to defaults",
+            // Vector display commands
+            Command::SetVectorDisplayMode { .. } => {
+                "Set vector display mode (full, truncated, summary, viz)"
+            }
+            Command::ShowVectorDisplayConfig => "Show current vector display configuration",
+            Command::ToggleVectorStatistics => "Toggle vector statistics display",
+            Command::VectorKnn { .. } => "Find nearest neighbours in a pgvector column",
+            Command::ClickHouseInsert { .. } => {
+                "Bulk insert a CSV/TSV/JSONEachRow file into a ClickHouse table"
+            }
+            Command::Replication => {
+                "Show replication slots, WAL senders and subscriptions with color-coded lag"
+            }
+            Command::Cluster { .. } => {
+                "Show the shards and replicas of each ClickHouse cluster, or of one cluster"
+            }
+            Command::MySqlProcessList => "Show current MySQL sessions and their running statements",
+            Command::MySqlLocks => "Show InnoDB lock waits and the sessions blocking them",
+            Command::MySqlSlowQueries { .. } => {
+                "Show the top statements by total latency from performance_schema"
+            }
+            Command::PgActivity => "Show client sessions from pg_stat_activity",
+            Command::PgBlocking => "Show blocked sessions, their blockers and blocking chains",
+            Command::PgBloat { .. } => "Show the tables with the most estimated bloat",
+            Command::SqliteBackup { .. } => {
+                "Copy the open SQLite database to a file with the online backup API"
+            }
+            Command::SqliteVacuum => "Rebuild the SQLite database file and truncate its WAL",
+            Command::SqliteIntegrityCheck { .. } => {
+                "Check the SQLite database for corruption (PRAGMA integrity_check)"
+            }
+            Command::ShowPoolStats => "Show connection pool statistics",
+            // Complex display commands
+            Command::ComplexDisplayMode { .. } => "Set complex data display mode",
+            Command::ComplexDisplayJsonToggle => "Toggle JSON pretty printing",
+            Command::Flatten { .. } => "Show nested document fields of the last query as columns",
+            // Vault credential cache commands
+            Command::VaultCacheStatus => "Show vault credential cache status",
+            Command::VaultCacheClear => "Clear all cached vault credentials",
+            Command::VaultCacheRefresh { .. } => "Refresh vault credential cache",
+            Command::VaultCacheExpired => "Show expired vault credentials",
+            // Password management commands
+            Command::SavePassword { .. } => "Save password to .dbcrust file (interactive)",
             Command::ListPasswords => "List stored passwords (without showing passwords)",
             Command::DeletePassword { .. } => "Delete stored password (interactive)",
             Command::EncryptPasswords => "Encrypt all plaintext passwords in .dbcrust",
@@ -3189,6 +7165,12 @@ impl CommandExecutor for Command {
             Command::MongoTextSearch { .. } => "Execute MongoDB text search",
             // Schema viewer
             Command::SchemaViewer => "Interactive schema viewer (TUI)",
+            Command::SchemaBrowser => {
+                "Browse schemas, tables and columns with sample rows and DDL (TUI)"
+            }
+            Command::EntityRelationshipDiagram { .. } => {
+                "Draw foreign key relationships or export them as DOT/Mermaid"
+            }
             // AI assistant commands
             Command::AiStatus => "Show AI assistant configuration and status",
             Command::AiSetup => "Interactive AI assistant setup wizard",
@@ -3199,975 +7181,2086 @@ impl CommandExecutor for Command {
             Command::AiLogin => {
                 "Sign in with ChatGPT (use your subscription instead of an API key)"
             }
-            Command::AiLogout => "Sign out of ChatGPT and return to API-key auth",
-            Command::AiGenerateSql { .. } => "Generate SQL from natural language",
+            Command::AiLogout => "Sign out of ChatGPT and return to API-key auth",
+            Command::AiGenerateSql { .. } => "Generate SQL from natural language",
+        }
+    }
+
+    fn usage(&self) -> &'static str {
+        match self {
+            Command::Quit => "\\q",
+            Command::Help { .. } => "\\h [command|sql-keyword]",
+            Command::ListDatabases => "\\l",
+            Command::Whoami => "\\whoami",
+            Command::SetRole { .. } => "\\setrole <role>",
+            Command::ResetRole => "\\resetrole",
+            Command::ShowSsl => "\\ssl",
+            Command::ReloadSsl => "\\ssl reload",
+            Command::Stats { .. } => "\\stats [reset]",
+            Command::ShowResultCache => "\\cache",
+            Command::SetResultCache { .. } => "\\cache on|off",
+            Command::ClearResultCache => "\\cache clear",
+            Command::ShowQueue => "\\queue [list]",
+            Command::ClearQueue => "\\queue clear",
+            Command::RunQueue => "\\queue run",
+            Command::RefreshSchema => "\\refreshschema",
+            Command::ShowQueryTags => "\\tag",
+            Command::SetQueryTags { .. } => "\\tag key=value [key=value ...]",
+            Command::ClearQueryTags => "\\tag clear",
+            Command::ListTables => "\\dt",
+            Command::DescribeTable { .. } => "\\d [table_name]",
+            Command::ConnectDatabase { .. } => "\\c <database|session|alias|URL>",
+            Command::Schema { .. } => "\\schema [name]",
+            Command::ListSchemas => "\\schemas",
+            Command::Warehouse { .. } => "\\warehouse [name]",
+            Command::DbSize => "\\dbsize",
+            Command::TableSize { .. } => "\\tablesize [n] [--sort total|table|index|rows]",
+            Command::ToggleExpandedDisplay => "\\x",
+            Command::Transpose { .. } => "\\transpose [rows]",
+            Command::Pivot { .. } => {
+                "\\pivot [<row_key> <column_key> [<value> [sum|count|min|max|avg]]]"
+            }
+            Command::Dedup { .. } => "\\dedup [columns...]",
+            Command::ListSnapshots => "\\snapshot [list]",
+            Command::SaveSnapshot { .. } => "\\snapshot save <name>",
+            Command::DiffSnapshot { .. } => "\\snapshot diff <name> [key columns...]",
+            Command::DeleteSnapshot { .. } => "\\snapshot delete <name>",
+            Command::ShowMasking => "\\mask",
+            Command::SetMasking { .. } => "\\mask on|off",
+            Command::ToggleExplainMode => "\\e",
+            Command::ToggleExplainTuiMode => "\\ev",
+            Command::ExportExplainPlan { .. } => "\\ev export <file> [folded|dot|json]",
+            Command::UploadExplainPlan { .. } => "\\ev upload [dalibo|depesz]",
+            Command::ShowConfig => "\\config show",
+            Command::ConfigMenu => "\\config",
+            Command::ConfigGet { .. } => "\\config get [key]",
+            Command::ConfigSet { .. } => "\\config set <key> <value>",
+            Command::ConfigUnset { .. } => "\\config unset <key>",
+            Command::ConfigEdit => "\\config edit",
+            Command::ReloadConfig => "\\reloadconfig",
+            Command::ListSnippets => "\\snippets",
+            Command::ShowKeys => "\\keys",
+            Command::AddSnippet { .. } => "\\snippets add <trigger> <expansion>",
+            Command::RemoveSnippet { .. } => "\\snippets remove <trigger>",
+            Command::ShowTablePrefs => "\\prefs [show]",
+            Command::ClearTablePrefs { .. } => "\\prefs clear [<table>|all]",
+            Command::SetTablePrefsSort { .. } => "\\prefs sort [<column> [asc|desc]]",
+            Command::WriteScript { .. } => "\\w <filename>",
+            Command::LoadScript { .. } => "\\i <filename>",
+            Command::EditMultiline => "\\ed",
+            Command::Watch { .. } => "\\watch [seconds]",
+            Command::Rerun => "\\g",
+            Command::SaveNamedQuery { .. } => {
+                "\\ns [-g|--global|--postgres|--mysql|--sqlite] <name> <query> [--scope]"
+            }
+            Command::DeleteNamedQuery { .. } => "\\nd <name>",
+            Command::ExecuteNamedQuery { .. } => "\\n <name> [args...]",
+            Command::ListNamedQueries => "\\n",
+            Command::ListSessions => "\\s",
+            Command::SaveSession { .. } => {
+                "\\ss <name> [--group <group>] [--password-command <command>]"
+            }
+            Command::DeleteSession { .. } => "\\sd <name>",
+            Command::ConnectSession { .. } => "\\s <name>",
+            Command::ListRecentConnections => "\\r",
+            Command::ClearRecentConnections => "\\rc",
+            Command::ClearSessionHistory { .. } => "\\hc [session_hash]",
+            Command::ListUsers => "\\du",
+            Command::ListIndexes => "\\di",
+            Command::ListPragmas => "\\dp",
+            Command::ShowFunction { .. } => "\\sf[+] <function>[(argument types)] [--edit]",
+            Command::ShowPgpass => "\\pgpass",
+            Command::ShowMyconf => "\\myconf",
+            Command::ListDockerContainers => "\\docker",
+            Command::CopyExplainPlan => "\\ecopy",
+            Command::Paste => "\\paste",
+            Command::ExplainRaw { .. } => "\\er <query>",
+            Command::ExplainFormatted { .. } => "\\ef <query>",
+            Command::Lint { .. } => "\\lint <query>",
+            Command::Format { .. } => "\\fmt [query]",
+            Command::DryRun { .. } => "\\dryrun <statement>",
+            Command::Count { .. } => "\\count [table|query]",
+            Command::InsertRow { .. } => "\\insertrow <table>",
+            Command::GenRows { .. } => "\\genrows <table> <count> [--yes]",
+            Command::PasteData { .. } => "\\pastedata <table> [--yes]",
+            Command::ExplainExport { .. } => "\\ex <query> <filename>",
+            Command::Export { .. } => {
+                "\\export ndjson [--resume] [--chunk <rows>] <file|-> <query|table>"
+            }
+            Command::SaveBlob { .. } => "\\saveblob <row> <column> <file>",
+            Command::Gexec { .. } => "\\gexec [query]",
+            Command::Dump { .. } => "\\dump <table|database> <file>",
+            Command::Restore { .. } => "\\restore <file>",
+            Command::FedJoin { .. } => "\\fedjoin [--local] <name> <url|session|.> <query>",
+            Command::FedJoinRun { .. } => "\\fedjoin run <query>",
+            Command::ListFedJoins => "\\fedjoin",
+            Command::ClearFedJoins => "\\fedjoin clear",
+            Command::ShowScratch => "\\scratch",
+            Command::ScratchImport { .. } => "\\scratch import <name>",
+            Command::ScratchRun { .. } => "\\scratch <query>",
+            Command::ClearScratch => "\\scratch clear",
+            Command::SetMultilineIndicator { .. } => "\\setmulti <indicator>",
+            Command::TogglePager => "\\pager",
+            Command::ToggleBanner => "\\banner",
+            Command::ToggleServerInfo => "\\serverinfo",
+            Command::SetDisplayTimezone { .. } => "\\tz [server|local|utc|<zone>]",
+            Command::SetTableStyle { .. } => "\\pset border [style]",
+            Command::ToggleAutocomplete => "\\a",
+            Command::ToggleColumnSelection => "\\cs",
+            Command::SetColumnSelectionThreshold { .. } => "\\csthreshold <number>",
+            Command::ClearColumnViews => "\\clrcs",
+            Command::SaveColumnView { .. } => "\\csave <name>",
+            Command::ListColumnViews => "\\cviews",
+            Command::DeleteColumnView { .. } => "\\cviews delete <name>",
+            Command::ResetView => "\\resetview",
+            // Vector display commands
+            Command::SetVectorDisplayMode { .. } => "\\vd <mode>",
+            Command::ShowVectorDisplayConfig => "\\vdc",
+            Command::ToggleVectorStatistics => "\\vs",
+            Command::VectorKnn { .. } => {
+                "\\knn <table> <vector_column> <[vector]|key=value> [k] [--metric l2|cosine|ip|l1]"
+            }
+            Command::ClickHouseInsert { .. } => "\\chinsert <table> <file> [format]",
+            Command::Replication => "\\replication",
+            Command::Cluster { .. } => "\\cluster [name]",
+            Command::MySqlProcessList => "\\processlist",
+            Command::MySqlLocks => "\\locks",
+            Command::MySqlSlowQueries { .. } => "\\slowqueries [n]",
+            Command::PgActivity => "\\activity",
+            Command::PgBlocking => "\\blocking",
+            Command::PgBloat { .. } => "\\bloat [n]",
+            Command::SqliteBackup { .. } => "\\backup <file> [--force]",
+            Command::SqliteVacuum => "\\vacuum",
+            Command::SqliteIntegrityCheck { .. } => "\\integritycheck [quick]",
+            Command::ShowPoolStats => "\\ps",
+            // Complex display commands
+            Command::ComplexDisplayMode { .. } => "\\cd [mode]",
+            Command::Flatten { .. } => "\\flatten [depth] [first|join|explode] [save|forget]",
+            Command::ComplexDisplayJsonToggle => "\\cdj",
+            // Vault credential cache commands
+            Command::VaultCacheStatus => "\\vc",
+            Command::VaultCacheClear => "\\vcc",
+            Command::VaultCacheRefresh { .. } => "\\vcr [role]",
+            Command::VaultCacheExpired => "\\vce",
+            // Password management commands
+            Command::SavePassword { .. } => "\\savepass",
+            Command::ListPasswords => "\\listpass",
+            Command::DeletePassword { .. } => "\\deletepass",
+            Command::EncryptPasswords => "\\encryptpass",
+            // MongoDB-specific commands
+            Command::ListCollections => "\\collections",
+            Command::DescribeCollection { .. } => "\\dc <collection_name>",
+            Command::ListMongoIndexes => "\\dmi",
+            Command::CreateMongoIndex { .. } => "\\cmi <collection> <field> [type]",
+            Command::DropMongoIndex { .. } => "\\ddmi <collection> <index_name>",
+            Command::MongoStats => "\\mstats",
+            Command::MongoFind { .. } => "\\find <collection> [filter] [projection] [limit]",
+            Command::MongoAggregate { .. } => "\\aggregate <collection> <pipeline>",
+            Command::MongoTextSearch { .. } => "\\search <collection> <search_term>",
+            // Schema viewer
+            Command::SchemaViewer => "\\sv",
+            Command::SchemaBrowser => "\\browse",
+            Command::EntityRelationshipDiagram { .. } => {
+                "\\erd [schema|table] [--ascii] [--dot <file>|--mermaid <file>]"
+            }
+            // AI assistant commands
+            Command::AiStatus => "\\ai [status]",
+            Command::AiSetup => "\\ai setup",
+            Command::AiSelectProvider { .. } => "\\ai provider [name]",
+            Command::AiSelectModel { .. } => "\\ai model [name]",
+            Command::AiToggle { .. } => "\\ai toggle|on|off",
+            Command::AiClearHistory => "\\ai clear",
+            Command::AiLogin => "\\ai login",
+            Command::AiLogout => "\\ai logout",
+            Command::AiGenerateSql { .. } => "?? <natural language query>",
+        }
+    }
+
+    fn category(&self) -> CommandCategory {
+        match self {
+            Command::Quit | Command::Help { .. } => CommandCategory::Core,
+            Command::ListDatabases
+            | Command::ListTables
+            | Command::DescribeTable { .. }
+            | Command::ConnectDatabase { .. }
+            | Command::Schema { .. }
+            | Command::ListSchemas
+            | Command::Warehouse { .. }
+            | Command::DbSize
+            | Command::TableSize { .. }
+            | Command::Whoami
+            | Command::SetRole { .. }
+            | Command::ResetRole
+            | Command::ShowSsl
+            | Command::ReloadSsl
+            | Command::Stats { .. }
+            | Command::ShowResultCache
+            | Command::SetResultCache { .. }
+            | Command::ClearResultCache
+            | Command::ShowQueue
+            | Command::ClearQueue
+            | Command::RunQueue
+            | Command::RefreshSchema
+            | Command::ShowQueryTags
+            | Command::SetQueryTags { .. }
+            | Command::ClearQueryTags => CommandCategory::DatabaseNavigation,
+            Command::ToggleExpandedDisplay
+            | Command::Transpose { .. }
+            | Command::Pivot { .. }
+            | Command::Dedup { .. }
+            | Command::ListSnapshots
+            | Command::SaveSnapshot { .. }
+            | Command::DiffSnapshot { .. }
+            | Command::DeleteSnapshot { .. }
+            | Command::ShowMasking
+            | Command::SetMasking { .. }
+            | Command::ToggleExplainMode
+            | Command::ToggleExplainTuiMode
+            | Command::ShowConfig
+            | Command::ConfigMenu
+            | Command::ConfigGet { .. }
+            | Command::ConfigSet { .. }
+            | Command::ConfigUnset { .. }
+            | Command::ConfigEdit
+            | Command::ReloadConfig
+            | Command::ListSnippets
+            | Command::ShowKeys
+            | Command::AddSnippet { .. }
+            | Command::RemoveSnippet { .. }
+            | Command::ShowTablePrefs
+            | Command::ClearTablePrefs { .. }
+            | Command::SetTablePrefsSort { .. } => CommandCategory::DisplayOptions,
+            Command::WriteScript { .. }
+            | Command::LoadScript { .. }
+            | Command::EditMultiline
+            | Command::CopyExplainPlan
+            | Command::ExportExplainPlan { .. }
+            | Command::UploadExplainPlan { .. }
+            | Command::Paste
+            | Command::Watch { .. }
+            | Command::Rerun => CommandCategory::ScriptHandling,
+            Command::ListNamedQueries
+            | Command::SaveNamedQuery { .. }
+            | Command::DeleteNamedQuery { .. }
+            | Command::ExecuteNamedQuery { .. } => CommandCategory::NamedQueries,
+            Command::ListSessions
+            | Command::SaveSession { .. }
+            | Command::DeleteSession { .. }
+            | Command::ConnectSession { .. } => CommandCategory::SessionManagement,
+            Command::ListRecentConnections | Command::ClearRecentConnections => {
+                CommandCategory::ConnectionHistory
+            }
+            Command::ClearSessionHistory { .. } => CommandCategory::HistoryManagement,
+            Command::ListUsers
+            | Command::ListIndexes
+            | Command::ListPragmas
+            | Command::ShowFunction { .. }
+            | Command::ShowPgpass
+            | Command::ShowMyconf
+            | Command::ListDockerContainers => CommandCategory::DatabaseSpecific,
+            Command::ExplainRaw { .. }
+            | Command::ExplainFormatted { .. }
+            | Command::ExplainExport { .. }
+            | Command::Export { .. }
+            | Command::SaveBlob { .. }
+            | Command::Gexec { .. }
+            | Command::Dump { .. }
+            | Command::Restore { .. }
+            | Command::FedJoin { .. }
+            | Command::FedJoinRun { .. }
+            | Command::ListFedJoins
+            | Command::ClearFedJoins
+            | Command::ShowScratch
+            | Command::ScratchImport { .. }
+            | Command::ScratchRun { .. }
+            | Command::ClearScratch
+            | Command::Lint { .. }
+            | Command::Format { .. }
+            | Command::DryRun { .. }
+            | Command::Count { .. }
+            | Command::InsertRow { .. }
+            | Command::GenRows { .. }
+            | Command::PasteData { .. }
+            | Command::ShowPoolStats => CommandCategory::Advanced,
+            // Complex display commands
+            Command::ComplexDisplayMode { .. }
+            | Command::ComplexDisplayJsonToggle
+            | Command::Flatten { .. } => CommandCategory::DisplayOptions,
+            Command::SetMultilineIndicator { .. }
+            | Command::TogglePager
+            | Command::ToggleBanner
+            | Command::ToggleServerInfo
+            | Command::SetDisplayTimezone { .. }
+            | Command::SetTableStyle { .. }
+            | Command::ToggleAutocomplete
+            | Command::ToggleColumnSelection
+            | Command::SetColumnSelectionThreshold { .. }
+            | Command::ClearColumnViews
+            | Command::SaveColumnView { .. }
+            | Command::ListColumnViews
+            | Command::DeleteColumnView { .. }
+            | Command::ResetView => CommandCategory::DisplayOptions,
+            // Vector display commands
+            Command::SetVectorDisplayMode { .. }
+            | Command::ShowVectorDisplayConfig
+            | Command::ToggleVectorStatistics => CommandCategory::DisplayOptions,
+            Command::VectorKnn { .. }
+            | Command::ClickHouseInsert { .. }
+            | Command::Replication
+            | Command::Cluster { .. }
+            | Command::MySqlProcessList
+            | Command::MySqlLocks
+            | Command::MySqlSlowQueries { .. }
+            | Command::PgActivity
+            | Command::PgBlocking
+            | Command::PgBloat { .. }
+            | Command::SqliteBackup { .. }
+            | Command::SqliteVacuum
+            | Command::SqliteIntegrityCheck { .. } => CommandCategory::DatabaseSpecific,
+            Command::VaultCacheStatus
+            | Command::VaultCacheClear
+            | Command::VaultCacheRefresh { .. }
+            | Command::VaultCacheExpired => CommandCategory::VaultManagement,
+            // Password management commands
+            Command::SavePassword { .. }
+            | Command::ListPasswords
+            | Command::DeletePassword { .. }
+            | Command::EncryptPasswords => CommandCategory::PasswordManagement,
+            // MongoDB-specific commands
+            Command::ListCollections => CommandCategory::DatabaseNavigation,
+            Command::DescribeCollection { .. } => CommandCategory::DatabaseNavigation,
+            Command::ListMongoIndexes => CommandCategory::DatabaseSpecific,
+            Command::CreateMongoIndex { .. } => CommandCategory::DatabaseSpecific,
+            Command::DropMongoIndex { .. } => CommandCategory::DatabaseSpecific,
+            Command::MongoStats => CommandCategory::DatabaseSpecific,
+            Command::MongoFind { .. } => CommandCategory::DatabaseSpecific,
+            Command::MongoAggregate { .. } => CommandCategory::DatabaseSpecific,
+            Command::MongoTextSearch { .. } => CommandCategory::DatabaseSpecific,
+            // Schema viewer
+            Command::SchemaViewer => CommandCategory::DatabaseNavigation,
+            Command::SchemaBrowser => CommandCategory::DatabaseNavigation,
+            Command::EntityRelationshipDiagram { .. } => CommandCategory::DatabaseNavigation,
+            // AI assistant commands
+            Command::AiStatus
+            | Command::AiSetup
+            | Command::AiSelectProvider { .. }
+            | Command::AiSelectModel { .. }
+            | Command::AiToggle { .. }
+            | Command::AiClearHistory
+            | Command::AiLogin
+            | Command::AiLogout
+            | Command::AiGenerateSql { .. } => CommandCategory::AiAssistant,
+        }
+    }
+}
+
+/// URL to reconnect with when `\\c` names another server (a URL, an alias or
+/// a saved session) rather than a database of the current one
+fn reconnect_url(target: &str, config: &DbCrustConfig) -> Option<String> {
+    if target.contains("://") || config.resolve_alias(target).is_some() {
+        // Aliases are resolved by the reconnect itself, so history names them
+        Some(target.to_string())
+    } else {
+        config
+            .get_session(target)
+            .map(|_| format!("session://{target}"))
+    }
+}
+
+/// Statements for `\\gexec`: the non-NULL, non-blank values of every row, left to right
+fn gexec_statements(rows: &[Vec<String>]) -> Vec<String> {
+    rows.iter()
+        .skip(1)
+        .flatten()
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty() && *value != "NULL")
+        .map(str::to_string)
+        .collect()
+}
+
+/// `users` for `table:users`; the (shortened) query for `query:` keys
+fn table_prefs_label(key: &str) -> String {
+    match key.strip_prefix("table:") {
+        Some(table) => table.to_string(),
+        None => {
+            let query = key.strip_prefix("query:").unwrap_or(key);
+            if query.chars().count() > 40 {
+                format!("'{}…'", query.chars().take(39).collect::<String>())
+            } else {
+                format!("'{query}'")
+            }
+        }
+    }
+}
+
+/// `\\h <topic>`: usage and examples of a backslash command, or syntax of a
+/// SQL statement in the connected dialect. Typos get the closest topics.
+fn help_topic(topic: &str, database_type: Option<&DatabaseType>) -> CommandResult {
+    let topic = topic.trim();
+    if !topic.starts_with('\\')
+        && let Some(help) = crate::sql_help::help_for(topic, database_type)
+    {
+        return CommandResult::Output(help);
+    }
+
+    let command = format!("\\{}", topic.trim_start_matches('\\'));
+    if let Some(shortcut) = CommandShortcut::iter().find(|s| s.command() == command) {
+        let mut help = format!("{}\n  {}\n", shortcut.usage(), shortcut.description());
+        if !shortcut.examples().is_empty() {
+            help.push_str("\nExamples:\n");
+            for example in shortcut.examples() {
+                help.push_str(&format!("  {example}\n"));
+            }
+        }
+        return CommandResult::Output(help);
+    }
+    // Commands outside the \\h listing (MongoDB, ...) still carry metadata
+    if let Ok(parsed) = CommandParser::parse(&command) {
+        return CommandResult::Output(format!("{}\n  {}\n", parsed.usage(), parsed.description()));
+    }
+
+    let wanted = topic.to_lowercase();
+    let mut scored: Vec<(usize, String)> = CommandShortcut::iter()
+        .map(|s| s.command().to_string())
+        .chain(crate::sql_help::keywords().map(str::to_string))
+        .map(|candidate| {
+            let key = candidate.trim_start_matches('\\').to_lowercase();
+            let distance = crate::config_editor::levenshtein(wanted.trim_start_matches('\\'), &key);
+            (distance, candidate)
+        })
+        .filter(|(distance, candidate)| *distance <= (candidate.len() / 3).max(1))
+        .collect();
+    scored.sort();
+    let suggestions: Vec<String> = scored.into_iter().take(3).map(|(_, c)| c).collect();
+    if suggestions.is_empty() {
+        CommandResult::Error(format!("No help for '{topic}'. \\h lists every command."))
+    } else {
+        CommandResult::Error(format!(
+            "No help for '{topic}'. Did you mean {}?",
+            suggestions.join(", ")
+        ))
+    }
+}
+
+fn generate_help_text() -> String {
+    let mut help = String::new();
+    help.push_str("Available Commands:\n\n");
+
+    for (category, commands) in CommandParser::get_commands_by_category() {
+        help.push_str(&format!("{category:?}:\n"));
+        for (cmd, desc) in commands {
+            help.push_str(&format!("  {cmd:<12} - {desc}\n"));
+        }
+        help.push('\n');
+    }
+
+    help
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_parsing() {
+        // Test core commands
+        assert_eq!(CommandParser::parse("\\q").unwrap(), Command::Quit);
+        assert_eq!(
+            CommandParser::parse("\\h").unwrap(),
+            Command::Help { topic: None }
+        );
+        assert_eq!(
+            CommandParser::parse("\\h create table").unwrap(),
+            Command::Help {
+                topic: Some("create table".to_string())
+            }
+        );
+
+        // Test database navigation
+        assert_eq!(CommandParser::parse("\\l").unwrap(), Command::ListDatabases);
+        assert_eq!(CommandParser::parse("\\whoami").unwrap(), Command::Whoami);
+        assert_eq!(
+            CommandParser::parse("\\su tenant_reader").unwrap(),
+            Command::SetRole {
+                role: "tenant_reader".to_string()
+            }
+        );
+        assert!(CommandParser::parse("\\setrole").is_err());
+        assert_eq!(
+            CommandParser::parse("\\schema").unwrap(),
+            Command::Schema { name: None }
+        );
+        assert_eq!(
+            CommandParser::parse("\\schema billing").unwrap(),
+            Command::Schema {
+                name: Some("billing".to_string())
+            }
+        );
+        assert_eq!(
+            CommandParser::parse("\\schemas").unwrap(),
+            Command::ListSchemas
+        );
+        assert_eq!(
+            CommandParser::parse("\\warehouse").unwrap(),
+            Command::Warehouse { name: None }
+        );
+        assert_eq!(
+            CommandParser::parse("\\warehouse reporting_wh").unwrap(),
+            Command::Warehouse {
+                name: Some("reporting_wh".to_string())
+            }
+        );
+        assert_eq!(CommandParser::parse("\\dbsize").unwrap(), Command::DbSize);
+        assert_eq!(
+            CommandParser::parse("\\tablesize").unwrap(),
+            Command::TableSize {
+                limit: 20,
+                sort: crate::database::SizeSort::Total
+            }
+        );
+        assert_eq!(
+            CommandParser::parse("\\tablesize --sort index 5").unwrap(),
+            Command::TableSize {
+                limit: 5,
+                sort: crate::database::SizeSort::Index
+            }
+        );
+        assert!(matches!(
+            CommandParser::parse("\\tablesize --sort bytes"),
+            Err(CommandError::InvalidSyntax(_))
+        ));
+        assert_eq!(
+            CommandParser::parse("\\resetrole").unwrap(),
+            Command::ResetRole
+        );
+        assert_eq!(CommandParser::parse("\\ssl").unwrap(), Command::ShowSsl);
+        assert_eq!(
+            CommandParser::parse("\\ssl reload").unwrap(),
+            Command::ReloadSsl
+        );
+        assert!(CommandParser::parse("\\ssl renew").is_err());
+        assert_eq!(
+            CommandParser::parse("\\stats").unwrap(),
+            Command::Stats { reset: false }
+        );
+        assert_eq!(
+            CommandParser::parse("\\stats reset").unwrap(),
+            Command::Stats { reset: true }
+        );
+        assert_eq!(
+            CommandParser::parse("\\cache").unwrap(),
+            Command::ShowResultCache
+        );
+        assert_eq!(
+            CommandParser::parse("\\cache off").unwrap(),
+            Command::SetResultCache { enabled: false }
+        );
+        assert_eq!(
+            CommandParser::parse("\\cache clear").unwrap(),
+            Command::ClearResultCache
+        );
+        assert_eq!(CommandParser::parse("\\queue").unwrap(), Command::ShowQueue);
+        assert_eq!(
+            CommandParser::parse("\\queue run").unwrap(),
+            Command::RunQueue
+        );
+        assert!(CommandParser::parse("\\queue flush").is_err());
+        assert_eq!(
+            CommandParser::parse("\\tag ticket=OPS-12 note=").unwrap(),
+            Command::SetQueryTags {
+                tags: vec![
+                    ("ticket".to_string(), "OPS-12".to_string()),
+                    ("note".to_string(), String::new())
+                ]
+            }
+        );
+        assert_eq!(
+            CommandParser::parse("\\refreshschema").unwrap(),
+            Command::RefreshSchema
+        );
+        assert_eq!(
+            CommandParser::parse("\\tag clear").unwrap(),
+            Command::ClearQueryTags
+        );
+        assert!(CommandParser::parse("\\tag ticket").is_err());
+        assert!(CommandParser::parse("\\stats clear").is_err());
+        assert_eq!(CommandParser::parse("\\dt").unwrap(), Command::ListTables);
+        assert_eq!(
+            CommandParser::parse("\\d").unwrap(),
+            Command::DescribeTable { table_name: None }
+        );
+        assert_eq!(
+            CommandParser::parse("\\d users").unwrap(),
+            Command::DescribeTable {
+                table_name: Some("users".to_string())
+            }
+        );
+
+        // Test commands with arguments
+        assert_eq!(
+            CommandParser::parse("\\c testdb").unwrap(),
+            Command::ConnectDatabase {
+                database_name: "testdb".to_string()
+            }
+        );
+        assert_eq!(
+            CommandParser::parse("\\w script.sql").unwrap(),
+            Command::WriteScript {
+                filename: "script.sql".to_string()
+            }
+        );
+
+        // Test named queries
+        assert_eq!(
+            CommandParser::parse("\\ns test SELECT 1").unwrap(),
+            Command::SaveNamedQuery {
+                name: "test".to_string(),
+                query: "SELECT 1".to_string(),
+                global: false,
+                postgres: false,
+                mysql: false,
+                sqlite: false,
+            }
+        );
+
+        // Test error cases
+        assert!(matches!(
+            CommandParser::parse("\\c"),
+            Err(CommandError::MissingArgument(_))
+        ));
+        assert!(matches!(
+            CommandParser::parse("\\unknown"),
+            Err(CommandError::UnknownCommand(_))
+        ));
+        assert!(matches!(
+            CommandParser::parse("not_a_command"),
+            Err(CommandError::InvalidSyntax(_))
+        ));
+    }
+
+    #[test]
+    fn test_individual_command_categories() {
+        assert_eq!(Command::Quit.category(), CommandCategory::Core);
+        assert_eq!(
+            Command::ListDatabases.category(),
+            CommandCategory::DatabaseNavigation
+        );
+        assert_eq!(
+            Command::ToggleExpandedDisplay.category(),
+            CommandCategory::DisplayOptions
+        );
+    }
+
+    #[test]
+    fn test_get_command_names() {
+        let names = CommandParser::get_command_names();
+        assert!(names.contains(&"\\q"));
+        assert!(names.contains(&"\\h"));
+        assert!(names.contains(&"\\l"));
+        assert!(names.contains(&"\\dt"));
+        // Test our new complex display commands
+        assert!(
+            names.contains(&"\\cd"),
+            "\\cd command should be in autocomplete"
+        );
+        assert!(
+            names.contains(&"\\cdj"),
+            "\\cdj command should be in autocomplete"
+        );
+        assert!(names.len() > 20); // Should have all our commands
+    }
+
+    #[test]
+    fn test_command_categories() {
+        let categories = CommandParser::get_commands_by_category();
+        assert!(categories.len() >= 6); // We have 6+ categories
+
+        // Verify each category has commands
+        for (category, commands) in categories {
+            assert!(
+                !commands.is_empty(),
+                "Category {category:?} should have commands"
+            );
         }
     }
 
-    fn usage(&self) -> &'static str {
-        match self {
-            Command::Quit => "\\q",
-            Command::Help => "\\h",
-            Command::ListDatabases => "\\l",
-            Command::ListTables => "\\dt",
-            Command::DescribeTable { .. } => "\\d [table_name]",
-            Command::ConnectDatabase { .. } => "\\c <database_name>",
-            Command::ToggleExpandedDisplay => "\\x",
-            Command::ToggleExplainMode => "\\e",
-            Command::ToggleExplainTuiMode => "\\ev",
-            Command::ShowConfig => "\\config show",
-            Command::ConfigMenu => "\\config",
-            Command::ConfigGet { .. } => "\\config get [key]",
-            Command::ConfigSet { .. } => "\\config set <key> <value>",
-            Command::ConfigEdit => "\\config edit",
-            Command::WriteScript { .. } => "\\w <filename>",
-            Command::LoadScript { .. } => "\\i <filename>",
-            Command::EditMultiline => "\\ed",
-            Command::SaveNamedQuery { .. } => {
-                "\\ns [-g|--global|--postgres|--mysql|--sqlite] <name> <query> [--scope]"
-            }
-            Command::DeleteNamedQuery { .. } => "\\nd <name>",
-            Command::ExecuteNamedQuery { .. } => "\\n <name> [args...]",
-            Command::ListNamedQueries => "\\n",
-            Command::ListSessions => "\\s",
-            Command::SaveSession { .. } => "\\ss <name> [--password-command <command>]",
-            Command::DeleteSession { .. } => "\\sd <name>",
-            Command::ConnectSession { .. } => "\\s <name>",
-            Command::ListRecentConnections => "\\r",
-            Command::ClearRecentConnections => "\\rc",
-            Command::ClearSessionHistory { .. } => "\\hc [session_hash]",
-            Command::ListUsers => "\\du",
-            Command::ListIndexes => "\\di",
-            Command::ListPragmas => "\\dp",
-            Command::ShowPgpass => "\\pgpass",
-            Command::ShowMyconf => "\\myconf",
-            Command::ListDockerContainers => "\\docker",
-            Command::CopyExplainPlan => "\\ecopy",
-            Command::ExplainRaw { .. } => "\\er <query>",
-            Command::ExplainFormatted { .. } => "\\ef <query>",
-            Command::ExplainExport { .. } => "\\ex <query> <filename>",
-            Command::SetMultilineIndicator { .. } => "\\setmulti <indicator>",
-            Command::TogglePager => "\\pager",
-            Command::ToggleBanner => "\\banner",
-            Command::ToggleServerInfo => "\\serverinfo",
-            Command::ToggleAutocomplete => "\\a",
-            Command::ToggleColumnSelection => "\\cs",
-            Command::SetColumnSelectionThreshold { .. } => "\\csthreshold <number>",
-            Command::ClearColumnViews => "\\clrcs",
-            Command::ResetView => "\\resetview",
-            // Vector display commands
-            Command::SetVectorDisplayMode { .. } => "\\vd <mode>",
-            Command::ShowVectorDisplayConfig => "\\vdc",
-            Command::ToggleVectorStatistics => "\\vs",
-            Command::ShowPoolStats => "\\ps",
-            // Complex display commands
-            Command::ComplexDisplayMode { .. } => "\\cd [mode]",
-            Command::ComplexDisplayJsonToggle => "\\cdj",
-            // Vault credential cache commands
-            Command::VaultCacheStatus => "\\vc",
-            Command::VaultCacheClear => "\\vcc",
-            Command::VaultCacheRefresh { .. } => "\\vcr [role]",
-            Command::VaultCacheExpired => "\\vce",
-            // Password management commands
-            Command::SavePassword { .. } => "\\savepass",
-            Command::ListPasswords => "\\listpass",
-            Command::DeletePassword { .. } => "\\deletepass",
-            Command::EncryptPasswords => "\\encryptpass",
-            // MongoDB-specific commands
-            Command::ListCollections => "\\collections",
-            Command::DescribeCollection { .. } => "\\dc <collection_name>",
-            Command::ListMongoIndexes => "\\dmi",
-            Command::CreateMongoIndex { .. } => "\\cmi <collection> <field> [type]",
-            Command::DropMongoIndex { .. } => "\\ddmi <collection> <index_name>",
-            Command::MongoStats => "\\mstats",
-            Command::MongoFind { .. } => "\\find <collection> [filter] [projection] [limit]",
-            Command::MongoAggregate { .. } => "\\aggregate <collection> <pipeline>",
-            Command::MongoTextSearch { .. } => "\\search <collection> <search_term>",
-            // Schema viewer
-            Command::SchemaViewer => "\\sv",
-            // AI assistant commands
-            Command::AiStatus => "\\ai [status]",
-            Command::AiSetup => "\\ai setup",
-            Command::AiSelectProvider { .. } => "\\ai provider [name]",
-            Command::AiSelectModel { .. } => "\\ai model [name]",
-            Command::AiToggle { .. } => "\\ai toggle|on|off",
-            Command::AiClearHistory => "\\ai clear",
-            Command::AiLogin => "\\ai login",
-            Command::AiLogout => "\\ai logout",
-            Command::AiGenerateSql { .. } => "?? <natural language query>",
+    #[test]
+    fn test_help_topics() {
+        let CommandResult::Output(help) = help_topic("\\ss", None) else {
+            panic!("expected command help");
+        };
+        assert!(help.starts_with("\\ss <name> [--group <group>]"));
+        assert!(help.contains("Examples:\n  \\ss prod\n"));
+
+        let CommandResult::Output(help) = help_topic("update", Some(&DatabaseType::MySQL)) else {
+            panic!("expected SQL help");
+        };
+        assert!(help.contains("Syntax (MySQL)") && help.contains("[LIMIT count]"));
+
+        let CommandResult::Error(error) = help_topic("\\dtt", None) else {
+            panic!("expected a suggestion");
+        };
+        assert!(error.ends_with("Did you mean \\dt?"), "{error}");
+    }
+
+    #[test]
+    fn test_command_descriptions_and_usage() {
+        let test_commands = vec![
+            Command::Quit,
+            Command::Help { topic: None },
+            Command::ListDatabases,
+            Command::ListTables,
+            Command::DescribeTable {
+                table_name: Some("test".to_string()),
+            },
+            Command::SaveSession {
+                name: "test".to_string(),
+                password_command: None,
+                group: None,
+            },
+            Command::ListNamedQueries,
+        ];
+
+        for command in test_commands {
+            // Verify descriptions and usage are not default
+            assert_ne!(command.description(), "Command description not available");
+            assert_ne!(command.usage(), "Usage not available");
+
+            // Verify they have content
+            assert!(!command.description().is_empty());
+            assert!(!command.usage().is_empty());
         }
     }
 
-    fn category(&self) -> CommandCategory {
-        match self {
-            Command::Quit | Command::Help => CommandCategory::Core,
-            Command::ListDatabases
-            | Command::ListTables
-            | Command::DescribeTable { .. }
-            | Command::ConnectDatabase { .. } => CommandCategory::DatabaseNavigation,
-            Command::ToggleExpandedDisplay
-            | Command::ToggleExplainMode
-            | Command::ToggleExplainTuiMode
-            | Command::ShowConfig
-            | Command::ConfigMenu
-            | Command::ConfigGet { .. }
-            | Command::ConfigSet { .. }
-            | Command::ConfigEdit => CommandCategory::DisplayOptions,
-            Command::WriteScript { .. }
-            | Command::LoadScript { .. }
-            | Command::EditMultiline
-            | Command::CopyExplainPlan => CommandCategory::ScriptHandling,
+    #[test]
+    fn test_session_commands_parsing() {
+        // Test session listing
+        assert_eq!(CommandParser::parse("\\s").unwrap(), Command::ListSessions);
+
+        // Test session connection
+        assert_eq!(
+            CommandParser::parse("\\s production").unwrap(),
+            Command::ConnectSession {
+                name: "production".to_string()
+            }
+        );
+
+        // Test session saving
+        assert_eq!(
+            CommandParser::parse("\\ss staging").unwrap(),
+            Command::SaveSession {
+                name: "staging".to_string(),
+                password_command: None,
+                group: None
+            }
+        );
+
+        assert_eq!(
+            CommandParser::parse("\\ss eu_replica --group staging").unwrap(),
+            Command::SaveSession {
+                name: "eu_replica".to_string(),
+                password_command: None,
+                group: Some("staging".to_string())
+            }
+        );
+        assert_eq!(
+            CommandParser::parse("\\ss eu --group=staging --password-command pass show db")
+                .unwrap(),
+            Command::SaveSession {
+                name: "eu".to_string(),
+                password_command: Some("pass show db".to_string()),
+                group: Some("staging".to_string())
+            }
+        );
+        assert!(CommandParser::parse("\\ss eu --group").is_err());
+
+        assert_eq!(
+            CommandParser::parse("\\ss staging --password-command vault kv get secret/db").unwrap(),
+            Command::SaveSession {
+                name: "staging".to_string(),
+                password_command: Some("vault kv get secret/db".to_string()),
+                group: None
+            }
+        );
+
+        // Test session deletion
+        assert_eq!(
+            CommandParser::parse("\\sd old_session").unwrap(),
+            Command::DeleteSession {
+                name: "old_session".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_named_query_commands() {
+        // Test named query listing
+        assert_eq!(
+            CommandParser::parse("\\n").unwrap(),
             Command::ListNamedQueries
-            | Command::SaveNamedQuery { .. }
-            | Command::DeleteNamedQuery { .. }
-            | Command::ExecuteNamedQuery { .. } => CommandCategory::NamedQueries,
-            Command::ListSessions
-            | Command::SaveSession { .. }
-            | Command::DeleteSession { .. }
-            | Command::ConnectSession { .. } => CommandCategory::SessionManagement,
-            Command::ListRecentConnections | Command::ClearRecentConnections => {
-                CommandCategory::ConnectionHistory
+        );
+
+        // Test named query execution
+        assert_eq!(
+            CommandParser::parse("\\n get_users").unwrap(),
+            Command::ExecuteNamedQuery {
+                name: "get_users".to_string(),
+                args: vec![]
             }
-            Command::ClearSessionHistory { .. } => CommandCategory::HistoryManagement,
-            Command::ListUsers
-            | Command::ListIndexes
-            | Command::ListPragmas
-            | Command::ShowPgpass
-            | Command::ShowMyconf
-            | Command::ListDockerContainers => CommandCategory::DatabaseSpecific,
-            Command::ExplainRaw { .. }
-            | Command::ExplainFormatted { .. }
-            | Command::ExplainExport { .. }
-            | Command::ShowPoolStats => CommandCategory::Advanced,
-            // Complex display commands
-            Command::ComplexDisplayMode { .. } | Command::ComplexDisplayJsonToggle => {
-                CommandCategory::DisplayOptions
+        );
+
+        // Test named query execution with args
+        assert_eq!(
+            CommandParser::parse("\\n get_user_by_id 123").unwrap(),
+            Command::ExecuteNamedQuery {
+                name: "get_user_by_id".to_string(),
+                args: vec!["123".to_string()]
             }
-            Command::SetMultilineIndicator { .. }
-            | Command::TogglePager
-            | Command::ToggleBanner
-            | Command::ToggleServerInfo
-            | Command::ToggleAutocomplete
-            | Command::ToggleColumnSelection
-            | Command::SetColumnSelectionThreshold { .. }
-            | Command::ClearColumnViews
-            | Command::ResetView => CommandCategory::DisplayOptions,
-            // Vector display commands
-            Command::SetVectorDisplayMode { .. }
-            | Command::ShowVectorDisplayConfig
-            | Command::ToggleVectorStatistics => CommandCategory::DisplayOptions,
-            Command::VaultCacheStatus
-            | Command::VaultCacheClear
-            | Command::VaultCacheRefresh { .. }
-            | Command::VaultCacheExpired => CommandCategory::VaultManagement,
-            // Password management commands
-            Command::SavePassword { .. }
-            | Command::ListPasswords
-            | Command::DeletePassword { .. }
-            | Command::EncryptPasswords => CommandCategory::PasswordManagement,
-            // MongoDB-specific commands
-            Command::ListCollections => CommandCategory::DatabaseNavigation,
-            Command::DescribeCollection { .. } => CommandCategory::DatabaseNavigation,
-            Command::ListMongoIndexes => CommandCategory::DatabaseSpecific,
-            Command::CreateMongoIndex { .. } => CommandCategory::DatabaseSpecific,
-            Command::DropMongoIndex { .. } => CommandCategory::DatabaseSpecific,
-            Command::MongoStats => CommandCategory::DatabaseSpecific,
-            Command::MongoFind { .. } => CommandCategory::DatabaseSpecific,
-            Command::MongoAggregate { .. } => CommandCategory::DatabaseSpecific,
-            Command::MongoTextSearch { .. } => CommandCategory::DatabaseSpecific,
-            // Schema viewer
-            Command::SchemaViewer => CommandCategory::DatabaseNavigation,
-            // AI assistant commands
-            Command::AiStatus
-            | Command::AiSetup
-            | Command::AiSelectProvider { .. }
-            | Command::AiSelectModel { .. }
-            | Command::AiToggle { .. }
-            | Command::AiClearHistory
-            | Command::AiLogin
-            | Command::AiLogout
-            | Command::AiGenerateSql { .. } => CommandCategory::AiAssistant,
-        }
+        );
+
+        // Test named query saving
+        assert_eq!(
+            CommandParser::parse("\\ns get_all SELECT * FROM users").unwrap(),
+            Command::SaveNamedQuery {
+                name: "get_all".to_string(),
+                query: "SELECT * FROM users".to_string(),
+                global: false,
+                postgres: false,
+                mysql: false,
+                sqlite: false,
+            }
+        );
+
+        // Test named query deletion
+        assert_eq!(
+            CommandParser::parse("\\nd old_query").unwrap(),
+            Command::DeleteNamedQuery {
+                name: "old_query".to_string()
+            }
+        );
+
+        // Test named query saving with global scope
+        assert_eq!(
+            CommandParser::parse("\\ns -g global_query SELECT 1").unwrap(),
+            Command::SaveNamedQuery {
+                name: "global_query".to_string(),
+                query: "SELECT 1".to_string(),
+                global: true,
+                postgres: false,
+                mysql: false,
+                sqlite: false,
+            }
+        );
+
+        // Test named query saving with database type scope
+        assert_eq!(
+            CommandParser::parse("\\ns --postgres pg_query SELECT version()").unwrap(),
+            Command::SaveNamedQuery {
+                name: "pg_query".to_string(),
+                query: "SELECT version()".to_string(),
+                global: false,
+                postgres: true,
+                mysql: false,
+                sqlite: false,
+            }
+        );
     }
-}
 
-fn generate_help_text() -> String {
-    let mut help = String::new();
-    help.push_str("Available Commands:\n\n");
+    #[test]
+    fn test_named_query_trailing_scope_flags() {
+        // Trailing --global after query
+        assert_eq!(
+            CommandParser::parse("\\ns myquery SELECT 1 --global").unwrap(),
+            Command::SaveNamedQuery {
+                name: "myquery".to_string(),
+                query: "SELECT 1".to_string(),
+                global: true,
+                postgres: false,
+                mysql: false,
+                sqlite: false,
+            }
+        );
+
+        // Trailing --postgres after query
+        assert_eq!(
+            CommandParser::parse("\\ns myquery SELECT * FROM users --postgres").unwrap(),
+            Command::SaveNamedQuery {
+                name: "myquery".to_string(),
+                query: "SELECT * FROM users".to_string(),
+                global: false,
+                postgres: true,
+                mysql: false,
+                sqlite: false,
+            }
+        );
+
+        // Trailing --mysql after query
+        assert_eq!(
+            CommandParser::parse("\\ns myquery SELECT 1 --mysql").unwrap(),
+            Command::SaveNamedQuery {
+                name: "myquery".to_string(),
+                query: "SELECT 1".to_string(),
+                global: false,
+                postgres: false,
+                mysql: true,
+                sqlite: false,
+            }
+        );
+
+        // Trailing --sqlite after query
+        assert_eq!(
+            CommandParser::parse("\\ns myquery SELECT name FROM sqlite_master --sqlite").unwrap(),
+            Command::SaveNamedQuery {
+                name: "myquery".to_string(),
+                query: "SELECT name FROM sqlite_master".to_string(),
+                global: false,
+                postgres: false,
+                mysql: false,
+                sqlite: true,
+            }
+        );
 
-    for (category, commands) in CommandParser::get_commands_by_category() {
-        help.push_str(&format!("{category:?}:\n"));
-        for (cmd, desc) in commands {
-            help.push_str(&format!("  {cmd:<12} - {desc}\n"));
-        }
-        help.push('\n');
-    }
+        // Trailing -g (short form) after query
+        assert_eq!(
+            CommandParser::parse("\\ns myquery SELECT 1 -g").unwrap(),
+            Command::SaveNamedQuery {
+                name: "myquery".to_string(),
+                query: "SELECT 1".to_string(),
+                global: true,
+                postgres: false,
+                mysql: false,
+                sqlite: false,
+            }
+        );
 
-    help
-}
+        // Long query with trailing flag (matches documented usage)
+        assert_eq!(
+            CommandParser::parse(
+                "\\ns today_records SELECT * FROM $1 WHERE DATE(created_at) = CURRENT_DATE --global"
+            )
+            .unwrap(),
+            Command::SaveNamedQuery {
+                name: "today_records".to_string(),
+                query: "SELECT * FROM $1 WHERE DATE(created_at) = CURRENT_DATE".to_string(),
+                global: true,
+                postgres: false,
+                mysql: false,
+                sqlite: false,
+            }
+        );
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        // Multiple trailing flags
+        assert_eq!(
+            CommandParser::parse("\\ns myquery SELECT 1 --global --postgres").unwrap(),
+            Command::SaveNamedQuery {
+                name: "myquery".to_string(),
+                query: "SELECT 1".to_string(),
+                global: true,
+                postgres: true,
+                mysql: false,
+                sqlite: false,
+            }
+        );
+    }
 
     #[test]
-    fn test_command_parsing() {
-        // Test core commands
-        assert_eq!(CommandParser::parse("\\q").unwrap(), Command::Quit);
-        assert_eq!(CommandParser::parse("\\h").unwrap(), Command::Help);
-
-        // Test database navigation
-        assert_eq!(CommandParser::parse("\\l").unwrap(), Command::ListDatabases);
-        assert_eq!(CommandParser::parse("\\dt").unwrap(), Command::ListTables);
+    fn test_named_query_middle_scope_flags() {
+        // Flag between name and query
         assert_eq!(
-            CommandParser::parse("\\d").unwrap(),
-            Command::DescribeTable { table_name: None }
+            CommandParser::parse("\\ns myquery --global SELECT 1").unwrap(),
+            Command::SaveNamedQuery {
+                name: "myquery".to_string(),
+                query: "SELECT 1".to_string(),
+                global: true,
+                postgres: false,
+                mysql: false,
+                sqlite: false,
+            }
         );
+
+        // --mysql between name and query
         assert_eq!(
-            CommandParser::parse("\\d users").unwrap(),
-            Command::DescribeTable {
-                table_name: Some("users".to_string())
+            CommandParser::parse("\\ns myquery --mysql SELECT 1").unwrap(),
+            Command::SaveNamedQuery {
+                name: "myquery".to_string(),
+                query: "SELECT 1".to_string(),
+                global: false,
+                postgres: false,
+                mysql: true,
+                sqlite: false,
             }
         );
 
-        // Test commands with arguments
+        // -g between name and query
         assert_eq!(
-            CommandParser::parse("\\c testdb").unwrap(),
-            Command::ConnectDatabase {
-                database_name: "testdb".to_string()
+            CommandParser::parse("\\ns myquery -g SELECT 1").unwrap(),
+            Command::SaveNamedQuery {
+                name: "myquery".to_string(),
+                query: "SELECT 1".to_string(),
+                global: true,
+                postgres: false,
+                mysql: false,
+                sqlite: false,
             }
         );
+    }
+
+    #[test]
+    fn test_named_query_flag_not_consumed_from_sql() {
+        // Quoted flag in SQL should NOT be consumed
         assert_eq!(
-            CommandParser::parse("\\w script.sql").unwrap(),
-            Command::WriteScript {
-                filename: "script.sql".to_string()
+            CommandParser::parse("\\ns myquery SELECT '--global' FROM t").unwrap(),
+            Command::SaveNamedQuery {
+                name: "myquery".to_string(),
+                query: "SELECT '--global' FROM t".to_string(),
+                global: false,
+                postgres: false,
+                mysql: false,
+                sqlite: false,
             }
         );
 
-        // Test named queries
+        // Flag-like text in middle of SQL is preserved (not trailing)
         assert_eq!(
-            CommandParser::parse("\\ns test SELECT 1").unwrap(),
+            CommandParser::parse("\\ns myquery SELECT --global FROM t").unwrap(),
             Command::SaveNamedQuery {
-                name: "test".to_string(),
-                query: "SELECT 1".to_string(),
+                name: "myquery".to_string(),
+                query: "SELECT --global FROM t".to_string(),
                 global: false,
                 postgres: false,
                 mysql: false,
                 sqlite: false,
             }
         );
+    }
 
-        // Test error cases
+    #[test]
+    fn test_named_query_error_cases() {
+        // Empty args
         assert!(matches!(
-            CommandParser::parse("\\c"),
+            CommandParser::parse("\\ns"),
             Err(CommandError::MissingArgument(_))
         ));
+
+        // Just name, no query
         assert!(matches!(
-            CommandParser::parse("\\unknown"),
-            Err(CommandError::UnknownCommand(_))
+            CommandParser::parse("\\ns myquery"),
+            Err(CommandError::MissingArgument(_))
         ));
+
+        // Just flag and name, no query
         assert!(matches!(
-            CommandParser::parse("not_a_command"),
-            Err(CommandError::InvalidSyntax(_))
+            CommandParser::parse("\\ns --global myquery"),
+            Err(CommandError::MissingArgument(_))
+        ));
+
+        // Just flag, no name or query
+        assert!(matches!(
+            CommandParser::parse("\\ns --global"),
+            Err(CommandError::MissingArgument(_))
+        ));
+
+        // Name with only trailing flag (no actual query)
+        assert!(matches!(
+            CommandParser::parse("\\ns myquery --global"),
+            Err(CommandError::MissingArgument(_))
         ));
     }
 
     #[test]
-    fn test_individual_command_categories() {
-        assert_eq!(Command::Quit.category(), CommandCategory::Core);
+    fn test_named_query_complex_queries() {
+        // Multi-word query preserved
         assert_eq!(
-            Command::ListDatabases.category(),
-            CommandCategory::DatabaseNavigation
+            CommandParser::parse(
+                "\\ns report SELECT u.*, COUNT(o.id) FROM users u LEFT JOIN orders o ON u.id = o.user_id GROUP BY u.id"
+            )
+            .unwrap(),
+            Command::SaveNamedQuery {
+                name: "report".to_string(),
+                query: "SELECT u.*, COUNT(o.id) FROM users u LEFT JOIN orders o ON u.id = o.user_id GROUP BY u.id".to_string(),
+                global: false,
+                postgres: false,
+                mysql: false,
+                sqlite: false,
+            }
         );
+
+        // Query with parameters and trailing flag
         assert_eq!(
-            Command::ToggleExpandedDisplay.category(),
-            CommandCategory::DisplayOptions
+            CommandParser::parse(
+                "\\ns user_search SELECT * FROM users WHERE name = '$1' AND age > $2 --global"
+            )
+            .unwrap(),
+            Command::SaveNamedQuery {
+                name: "user_search".to_string(),
+                query: "SELECT * FROM users WHERE name = '$1' AND age > $2".to_string(),
+                global: true,
+                postgres: false,
+                mysql: false,
+                sqlite: false,
+            }
+        );
+
+        // Query with SQL comment containing flag-like text (trailing)
+        assert_eq!(
+            CommandParser::parse("\\ns myquery SELECT 1 -- this is a comment --postgres").unwrap(),
+            Command::SaveNamedQuery {
+                name: "myquery".to_string(),
+                query: "SELECT 1 -- this is a comment".to_string(),
+                global: false,
+                postgres: true,
+                mysql: false,
+                sqlite: false,
+            }
         );
     }
 
     #[test]
-    fn test_get_command_names() {
-        let names = CommandParser::get_command_names();
-        assert!(names.contains(&"\\q"));
-        assert!(names.contains(&"\\h"));
-        assert!(names.contains(&"\\l"));
-        assert!(names.contains(&"\\dt"));
-        // Test our new complex display commands
-        assert!(
-            names.contains(&"\\cd"),
-            "\\cd command should be in autocomplete"
+    fn test_database_specific_commands() {
+        assert_eq!(CommandParser::parse("\\du").unwrap(), Command::ListUsers);
+        assert_eq!(CommandParser::parse("\\di").unwrap(), Command::ListIndexes);
+        assert_eq!(
+            CommandParser::parse("\\pgpass").unwrap(),
+            Command::ShowPgpass
         );
-        assert!(
-            names.contains(&"\\cdj"),
-            "\\cdj command should be in autocomplete"
+        assert_eq!(
+            CommandParser::parse("\\myconf").unwrap(),
+            Command::ShowMyconf
         );
-        assert!(names.len() > 20); // Should have all our commands
     }
 
     #[test]
-    fn test_command_categories() {
-        let categories = CommandParser::get_commands_by_category();
-        assert!(categories.len() >= 6); // We have 6+ categories
+    fn test_script_commands() {
+        assert_eq!(
+            CommandParser::parse("\\w script.sql").unwrap(),
+            Command::WriteScript {
+                filename: "script.sql".to_string()
+            }
+        );
 
-        // Verify each category has commands
-        for (category, commands) in categories {
-            assert!(
-                !commands.is_empty(),
-                "Category {category:?} should have commands"
-            );
-        }
+        assert_eq!(
+            CommandParser::parse("\\i load.sql").unwrap(),
+            Command::LoadScript {
+                filename: "load.sql".to_string()
+            }
+        );
+
+        assert_eq!(
+            CommandParser::parse("\\ed").unwrap(),
+            Command::EditMultiline
+        );
+        assert_eq!(
+            CommandParser::parse("\\ecopy").unwrap(),
+            Command::CopyExplainPlan
+        );
+        assert_eq!(CommandParser::parse("\\paste").unwrap(), Command::Paste);
     }
 
     #[test]
-    fn test_command_descriptions_and_usage() {
-        let test_commands = vec![
-            Command::Quit,
-            Command::Help,
-            Command::ListDatabases,
-            Command::ListTables,
-            Command::DescribeTable {
-                table_name: Some("test".to_string()),
-            },
-            Command::SaveSession {
-                name: "test".to_string(),
-                password_command: None,
-            },
-            Command::ListNamedQueries,
-        ];
+    fn test_error_cases() {
+        // Missing required arguments
+        assert!(matches!(
+            CommandParser::parse("\\c"),
+            Err(CommandError::MissingArgument(_))
+        ));
+        assert!(matches!(
+            CommandParser::parse("\\w"),
+            Err(CommandError::MissingArgument(_))
+        ));
+        assert!(matches!(
+            CommandParser::parse("\\ss"),
+            Err(CommandError::MissingArgument(_))
+        ));
+        assert!(matches!(
+            CommandParser::parse("\\ns test"),
+            Err(CommandError::MissingArgument(_))
+        ));
 
-        for command in test_commands {
-            // Verify descriptions and usage are not default
-            assert_ne!(command.description(), "Command description not available");
-            assert_ne!(command.usage(), "Usage not available");
+        // Invalid command syntax
+        assert!(matches!(
+            CommandParser::parse("not_a_command"),
+            Err(CommandError::InvalidSyntax(_))
+        ));
+        assert!(matches!(
+            CommandParser::parse("regular text"),
+            Err(CommandError::InvalidSyntax(_))
+        ));
 
-            // Verify they have content
-            assert!(!command.description().is_empty());
-            assert!(!command.usage().is_empty());
-        }
+        // Unknown commands
+        assert!(matches!(
+            CommandParser::parse("\\unknown"),
+            Err(CommandError::UnknownCommand(_))
+        ));
+        assert!(matches!(
+            CommandParser::parse("\\xyz"),
+            Err(CommandError::UnknownCommand(_))
+        ));
     }
 
     #[test]
-    fn test_session_commands_parsing() {
-        // Test session listing
-        assert_eq!(CommandParser::parse("\\s").unwrap(), Command::ListSessions);
-
-        // Test session connection
+    fn test_advanced_commands() {
+        // Test EXPLAIN variants
         assert_eq!(
-            CommandParser::parse("\\s production").unwrap(),
-            Command::ConnectSession {
-                name: "production".to_string()
+            CommandParser::parse("\\er SELECT 1").unwrap(),
+            Command::ExplainRaw {
+                query: "SELECT 1".to_string()
             }
         );
 
-        // Test session saving
         assert_eq!(
-            CommandParser::parse("\\ss staging").unwrap(),
-            Command::SaveSession {
-                name: "staging".to_string(),
-                password_command: None
+            CommandParser::parse("\\ef SELECT 1").unwrap(),
+            Command::ExplainFormatted {
+                query: "SELECT 1".to_string()
             }
         );
 
         assert_eq!(
-            CommandParser::parse("\\ss staging --password-command vault kv get secret/db").unwrap(),
-            Command::SaveSession {
-                name: "staging".to_string(),
-                password_command: Some("vault kv get secret/db".to_string())
+            CommandParser::parse("\\ex SELECT 1 output.txt").unwrap(),
+            Command::ExplainExport {
+                query: "SELECT 1".to_string(),
+                filename: "output.txt".to_string()
             }
         );
 
-        // Test session deletion
         assert_eq!(
-            CommandParser::parse("\\sd old_session").unwrap(),
-            Command::DeleteSession {
-                name: "old_session".to_string()
+            CommandParser::parse("\\export ndjson rows.ndjson SELECT * FROM t;").unwrap(),
+            Command::Export {
+                format: crate::export::ExportFormat::Ndjson,
+                filename: "rows.ndjson".to_string(),
+                query: "SELECT * FROM t".to_string(),
+                resume: false,
+                chunk_rows: crate::export_chunks::DEFAULT_CHUNK_ROWS,
             }
         );
-    }
-
-    #[test]
-    fn test_named_query_commands() {
-        // Test named query listing
         assert_eq!(
-            CommandParser::parse("\\n").unwrap(),
-            Command::ListNamedQueries
+            CommandParser::parse("\\export ndjson --chunk 500 --resume rows.ndjson events")
+                .unwrap(),
+            Command::Export {
+                format: crate::export::ExportFormat::Ndjson,
+                filename: "rows.ndjson".to_string(),
+                query: "events".to_string(),
+                resume: true,
+                chunk_rows: 500,
+            }
         );
+        assert!(matches!(
+            CommandParser::parse("\\export ndjson --chunk 0 rows.ndjson events"),
+            Err(CommandError::InvalidSyntax(_))
+        ));
+        assert!(matches!(
+            CommandParser::parse("\\export ndjson rows.ndjson"),
+            Err(CommandError::MissingArgument(_))
+        ));
 
-        // Test named query execution
         assert_eq!(
-            CommandParser::parse("\\n get_users").unwrap(),
-            Command::ExecuteNamedQuery {
-                name: "get_users".to_string(),
-                args: vec![]
+            CommandParser::parse("\\saveblob 2 avatar out.png").unwrap(),
+            Command::SaveBlob {
+                row: 2,
+                column: "avatar".to_string(),
+                filename: "out.png".to_string()
             }
         );
+        assert!(matches!(
+            CommandParser::parse("\\saveblob 2 avatar"),
+            Err(CommandError::MissingArgument(_))
+        ));
+        assert!(matches!(
+            CommandParser::parse("\\saveblob first avatar out.png"),
+            Err(CommandError::InvalidSyntax(_))
+        ));
 
-        // Test named query execution with args
         assert_eq!(
-            CommandParser::parse("\\n get_user_by_id 123").unwrap(),
-            Command::ExecuteNamedQuery {
-                name: "get_user_by_id".to_string(),
-                args: vec!["123".to_string()]
+            CommandParser::parse("\\gexec").unwrap(),
+            Command::Gexec { query: None }
+        );
+        assert_eq!(
+            CommandParser::parse("\\gexec SELECT 'VACUUM ' || relname FROM pg_class").unwrap(),
+            Command::Gexec {
+                query: Some("SELECT 'VACUUM ' || relname FROM pg_class".to_string())
             }
         );
+        let rows = vec![
+            vec!["grant".to_string(), "revoke".to_string()],
+            vec!["GRANT SELECT ON a TO r".to_string(), "NULL".to_string()],
+            vec![" ".to_string(), "REVOKE ALL ON b FROM r".to_string()],
+        ];
+        assert_eq!(
+            gexec_statements(&rows),
+            ["GRANT SELECT ON a TO r", "REVOKE ALL ON b FROM r"]
+        );
 
-        // Test named query saving
         assert_eq!(
-            CommandParser::parse("\\ns get_all SELECT * FROM users").unwrap(),
-            Command::SaveNamedQuery {
-                name: "get_all".to_string(),
-                query: "SELECT * FROM users".to_string(),
-                global: false,
-                postgres: false,
-                mysql: false,
-                sqlite: false,
+            CommandParser::parse("\\dump public.users users.sql").unwrap(),
+            Command::Dump {
+                target: "public.users".to_string(),
+                filename: "users.sql".to_string()
             }
         );
-
-        // Test named query deletion
+        assert!(matches!(
+            CommandParser::parse("\\dump users"),
+            Err(CommandError::MissingArgument(_))
+        ));
         assert_eq!(
-            CommandParser::parse("\\nd old_query").unwrap(),
-            Command::DeleteNamedQuery {
-                name: "old_query".to_string()
+            CommandParser::parse("\\restore backup.sql").unwrap(),
+            Command::Restore {
+                filename: "backup.sql".to_string()
             }
         );
+        assert!(matches!(
+            CommandParser::parse("\\export xlsx rows.xlsx SELECT 1"),
+            Err(CommandError::InvalidSyntax(_))
+        ));
 
-        // Test named query saving with global scope
+        // Test threshold setting
         assert_eq!(
-            CommandParser::parse("\\ns -g global_query SELECT 1").unwrap(),
-            Command::SaveNamedQuery {
-                name: "global_query".to_string(),
-                query: "SELECT 1".to_string(),
-                global: true,
-                postgres: false,
-                mysql: false,
-                sqlite: false,
-            }
+            CommandParser::parse("\\csthreshold 50").unwrap(),
+            Command::SetColumnSelectionThreshold { threshold: 50 }
         );
 
-        // Test named query saving with database type scope
+        // Test multiline indicator
         assert_eq!(
-            CommandParser::parse("\\ns --postgres pg_query SELECT version()").unwrap(),
-            Command::SaveNamedQuery {
-                name: "pg_query".to_string(),
-                query: "SELECT version()".to_string(),
-                global: false,
-                postgres: true,
-                mysql: false,
-                sqlite: false,
+            CommandParser::parse("\\setmulti >").unwrap(),
+            Command::SetMultilineIndicator {
+                indicator: ">".to_string()
             }
         );
     }
 
     #[test]
-    fn test_named_query_trailing_scope_flags() {
-        // Trailing --global after query
+    fn test_display_option_commands() {
         assert_eq!(
-            CommandParser::parse("\\ns myquery SELECT 1 --global").unwrap(),
-            Command::SaveNamedQuery {
-                name: "myquery".to_string(),
-                query: "SELECT 1".to_string(),
-                global: true,
-                postgres: false,
-                mysql: false,
-                sqlite: false,
-            }
+            CommandParser::parse("\\x").unwrap(),
+            Command::ToggleExpandedDisplay
         );
-
-        // Trailing --postgres after query
         assert_eq!(
-            CommandParser::parse("\\ns myquery SELECT * FROM users --postgres").unwrap(),
-            Command::SaveNamedQuery {
-                name: "myquery".to_string(),
-                query: "SELECT * FROM users".to_string(),
-                global: false,
-                postgres: true,
-                mysql: false,
-                sqlite: false,
+            CommandParser::parse("\\transpose").unwrap(),
+            Command::Transpose {
+                max_rows: DEFAULT_TRANSPOSE_ROWS
             }
         );
-
-        // Trailing --mysql after query
         assert_eq!(
-            CommandParser::parse("\\ns myquery SELECT 1 --mysql").unwrap(),
-            Command::SaveNamedQuery {
-                name: "myquery".to_string(),
-                query: "SELECT 1".to_string(),
-                global: false,
-                postgres: false,
-                mysql: true,
-                sqlite: false,
+            CommandParser::parse("\\transpose 3").unwrap(),
+            Command::Transpose { max_rows: 3 }
+        );
+        assert!(CommandParser::parse("\\transpose 0").is_err());
+        assert_eq!(
+            CommandParser::parse("\\pivot day status total SUM").unwrap(),
+            Command::Pivot {
+                spec: crate::pivot::PivotSpec {
+                    row_key: Some("day".to_string()),
+                    column_key: Some("status".to_string()),
+                    value: Some("total".to_string()),
+                    aggregate: Some(crate::pivot::Aggregate::Sum),
+                }
             }
         );
-
-        // Trailing --sqlite after query
+        assert!(CommandParser::parse("\\pivot day").is_err());
+        assert!(CommandParser::parse("\\pivot day status total median").is_err());
         assert_eq!(
-            CommandParser::parse("\\ns myquery SELECT name FROM sqlite_master --sqlite").unwrap(),
-            Command::SaveNamedQuery {
-                name: "myquery".to_string(),
-                query: "SELECT name FROM sqlite_master".to_string(),
-                global: false,
-                postgres: false,
-                mysql: false,
-                sqlite: true,
+            CommandParser::parse("\\dedup").unwrap(),
+            Command::Dedup {
+                columns: Vec::new()
             }
         );
-
-        // Trailing -g (short form) after query
         assert_eq!(
-            CommandParser::parse("\\ns myquery SELECT 1 -g").unwrap(),
-            Command::SaveNamedQuery {
-                name: "myquery".to_string(),
-                query: "SELECT 1".to_string(),
-                global: true,
-                postgres: false,
-                mysql: false,
-                sqlite: false,
+            CommandParser::parse("\\dedup customer_id, day 3").unwrap(),
+            Command::Dedup {
+                columns: vec![
+                    "customer_id".to_string(),
+                    "day".to_string(),
+                    "3".to_string()
+                ]
             }
         );
-
-        // Long query with trailing flag (matches documented usage)
         assert_eq!(
-            CommandParser::parse(
-                "\\ns today_records SELECT * FROM $1 WHERE DATE(created_at) = CURRENT_DATE --global"
-            )
-            .unwrap(),
-            Command::SaveNamedQuery {
-                name: "today_records".to_string(),
-                query: "SELECT * FROM $1 WHERE DATE(created_at) = CURRENT_DATE".to_string(),
-                global: true,
-                postgres: false,
-                mysql: false,
-                sqlite: false,
+            CommandParser::parse("\\snapshot").unwrap(),
+            Command::ListSnapshots
+        );
+        assert_eq!(
+            CommandParser::parse("\\snapshot save before-fix").unwrap(),
+            Command::SaveSnapshot {
+                name: "before-fix".to_string()
             }
         );
-
-        // Multiple trailing flags
         assert_eq!(
-            CommandParser::parse("\\ns myquery SELECT 1 --global --postgres").unwrap(),
-            Command::SaveNamedQuery {
-                name: "myquery".to_string(),
-                query: "SELECT 1".to_string(),
-                global: true,
-                postgres: true,
-                mysql: false,
-                sqlite: false,
+            CommandParser::parse("\\snapshot diff before-fix id, region").unwrap(),
+            Command::DiffSnapshot {
+                name: "before-fix".to_string(),
+                key: vec!["id".to_string(), "region".to_string()]
             }
         );
-    }
-
-    #[test]
-    fn test_named_query_middle_scope_flags() {
-        // Flag between name and query
         assert_eq!(
-            CommandParser::parse("\\ns myquery --global SELECT 1").unwrap(),
-            Command::SaveNamedQuery {
-                name: "myquery".to_string(),
-                query: "SELECT 1".to_string(),
-                global: true,
-                postgres: false,
-                mysql: false,
-                sqlite: false,
+            CommandParser::parse("\\snapshot delete before-fix").unwrap(),
+            Command::DeleteSnapshot {
+                name: "before-fix".to_string()
             }
         );
-
-        // --mysql between name and query
+        assert!(CommandParser::parse("\\snapshot save").is_err());
+        assert!(CommandParser::parse("\\snapshot save a b").is_err());
         assert_eq!(
-            CommandParser::parse("\\ns myquery --mysql SELECT 1").unwrap(),
-            Command::SaveNamedQuery {
-                name: "myquery".to_string(),
-                query: "SELECT 1".to_string(),
-                global: false,
-                postgres: false,
-                mysql: true,
-                sqlite: false,
-            }
+            CommandParser::parse("\\mask").unwrap(),
+            Command::ShowMasking
         );
-
-        // -g between name and query
         assert_eq!(
-            CommandParser::parse("\\ns myquery -g SELECT 1").unwrap(),
-            Command::SaveNamedQuery {
-                name: "myquery".to_string(),
-                query: "SELECT 1".to_string(),
-                global: true,
-                postgres: false,
-                mysql: false,
-                sqlite: false,
+            CommandParser::parse("\\mask on").unwrap(),
+            Command::SetMasking { enabled: true }
+        );
+        assert!(CommandParser::parse("\\mask all").is_err());
+        assert_eq!(
+            CommandParser::parse("\\e").unwrap(),
+            Command::ToggleExplainMode
+        );
+        assert_eq!(
+            CommandParser::parse("\\ev").unwrap(),
+            Command::ToggleExplainTuiMode
+        );
+        assert_eq!(
+            CommandParser::parse("\\ev export plan.svg.folded").unwrap(),
+            Command::ExportExplainPlan {
+                filename: "plan.svg.folded".to_string(),
+                format: None,
             }
         );
-    }
-
-    #[test]
-    fn test_named_query_flag_not_consumed_from_sql() {
-        // Quoted flag in SQL should NOT be consumed
         assert_eq!(
-            CommandParser::parse("\\ns myquery SELECT '--global' FROM t").unwrap(),
-            Command::SaveNamedQuery {
-                name: "myquery".to_string(),
-                query: "SELECT '--global' FROM t".to_string(),
-                global: false,
-                postgres: false,
-                mysql: false,
-                sqlite: false,
+            CommandParser::parse("\\ev export plan.gv DOT").unwrap(),
+            Command::ExportExplainPlan {
+                filename: "plan.gv".to_string(),
+                format: Some(crate::explain_tui::PlanExportFormat::Dot),
             }
         );
-
-        // Flag-like text in middle of SQL is preserved (not trailing)
         assert_eq!(
-            CommandParser::parse("\\ns myquery SELECT --global FROM t").unwrap(),
-            Command::SaveNamedQuery {
-                name: "myquery".to_string(),
-                query: "SELECT --global FROM t".to_string(),
-                global: false,
-                postgres: false,
-                mysql: false,
-                sqlite: false,
+            CommandParser::parse("\\ev upload").unwrap(),
+            Command::UploadExplainPlan {
+                service: crate::explain_tui::PlanService::Dalibo,
             }
         );
+        assert!(CommandParser::parse("\\ev export").is_err());
+        assert!(CommandParser::parse("\\ev export plan.txt svg").is_err());
+        assert!(CommandParser::parse("\\ev upload pastebin").is_err());
+        assert_eq!(
+            CommandParser::parse("\\config").unwrap(),
+            Command::ConfigMenu
+        );
     }
 
-    #[test]
-    fn test_named_query_error_cases() {
-        // Empty args
-        assert!(matches!(
-            CommandParser::parse("\\ns"),
-            Err(CommandError::MissingArgument(_))
-        ));
-
-        // Just name, no query
-        assert!(matches!(
-            CommandParser::parse("\\ns myquery"),
-            Err(CommandError::MissingArgument(_))
-        ));
+    #[rstest::rstest]
+    #[case("\\config show", Command::ShowConfig)]
+    #[case("\\config get", Command::ConfigGet { key: None })]
+    #[case(
+        "\\config get logging.level",
+        Command::ConfigGet { key: Some("logging.level".to_string()) }
+    )]
+    #[case(
+        "\\config set logging.level debug",
+        Command::ConfigSet { key: "logging.level".to_string(), value: "debug".to_string() }
+    )]
+    // The value is the remainder of the line — spaces are preserved.
+    #[case(
+        "\\config set pager_command less -RFX",
+        Command::ConfigSet { key: "pager_command".to_string(), value: "less -RFX".to_string() }
+    )]
+    #[case(
+        "\\config unset logging.level",
+        Command::ConfigUnset { key: "logging.level".to_string() }
+    )]
+    #[case("\\config edit", Command::ConfigEdit)]
+    #[case("\\reloadconfig", Command::ReloadConfig)]
+    #[case("\\snippets", Command::ListSnippets)]
+    #[case("\\keys", Command::ShowKeys)]
+    #[case("\\g", Command::Rerun)]
+    #[case(
+        "\\snippets add cnt SELECT count(*) FROM ${table}",
+        Command::AddSnippet { trigger: "cnt".to_string(), expansion: "SELECT count(*) FROM ${table}".to_string() }
+    )]
+    #[case("\\snippets remove cnt", Command::RemoveSnippet { trigger: "cnt".to_string() })]
+    #[case("\\prefs", Command::ShowTablePrefs)]
+    #[case("\\prefs clear", Command::ClearTablePrefs { table: None })]
+    #[case("\\prefs clear all", Command::ClearTablePrefs { table: Some("all".to_string()) })]
+    #[case(
+        "\\prefs sort created_at desc",
+        Command::SetTablePrefsSort { column: Some("created_at".to_string()), descending: true }
+    )]
+    #[case("\\prefs sort off", Command::SetTablePrefsSort { column: None, descending: false })]
+    #[case("\\fedjoin", Command::ListFedJoins)]
+    #[case("\\fedjoin clear", Command::ClearFedJoins)]
+    #[case("\\fedjoin run SELECT * FROM a JOIN b USING (id);", Command::FedJoinRun { query: "SELECT * FROM a JOIN b USING (id)".to_string() })]
+    #[case(
+        "\\fedjoin crm session://crm SELECT id, plan FROM accounts;",
+        Command::FedJoin {
+            name: "crm".to_string(),
+            source: "session://crm".to_string(),
+            query: "SELECT id, plan FROM accounts".to_string(),
+            local: false,
+        }
+    )]
+    #[case(
+        "\\fedjoin --local mine . SELECT 1",
+        Command::FedJoin {
+            name: "mine".to_string(),
+            source: ".".to_string(),
+            query: "SELECT 1".to_string(),
+            local: true,
+        }
+    )]
+    #[case("\\scratch", Command::ShowScratch)]
+    #[case("\\scratch clear", Command::ClearScratch)]
+    #[case("\\scratch import orders", Command::ScratchImport { name: "orders".to_string() })]
+    #[case("\\scratch SELECT * FROM orders;", Command::ScratchRun { query: "SELECT * FROM orders".to_string() })]
+    fn test_config_subcommand_parsing(#[case] input: &str, #[case] expected: Command) {
+        assert_eq!(CommandParser::parse(input).unwrap(), expected);
+    }
 
-        // Just flag and name, no query
+    #[test]
+    fn test_config_subcommand_errors() {
         assert!(matches!(
-            CommandParser::parse("\\ns --global myquery"),
+            CommandParser::parse("\\config set"),
             Err(CommandError::MissingArgument(_))
         ));
-
-        // Just flag, no name or query
         assert!(matches!(
-            CommandParser::parse("\\ns --global"),
+            CommandParser::parse("\\config set default_limit"),
             Err(CommandError::MissingArgument(_))
         ));
-
-        // Name with only trailing flag (no actual query)
         assert!(matches!(
-            CommandParser::parse("\\ns myquery --global"),
-            Err(CommandError::MissingArgument(_))
+            CommandParser::parse("\\config bogus"),
+            Err(CommandError::InvalidSyntax(_))
         ));
     }
 
     #[test]
-    fn test_named_query_complex_queries() {
-        // Multi-word query preserved
+    fn test_timezone_command_parsing() {
         assert_eq!(
-            CommandParser::parse(
-                "\\ns report SELECT u.*, COUNT(o.id) FROM users u LEFT JOIN orders o ON u.id = o.user_id GROUP BY u.id"
-            )
-            .unwrap(),
-            Command::SaveNamedQuery {
-                name: "report".to_string(),
-                query: "SELECT u.*, COUNT(o.id) FROM users u LEFT JOIN orders o ON u.id = o.user_id GROUP BY u.id".to_string(),
-                global: false,
-                postgres: false,
-                mysql: false,
-                sqlite: false,
+            CommandParser::parse("\\tz").unwrap(),
+            Command::SetDisplayTimezone {
+                zone: String::new()
             }
         );
-
-        // Query with parameters and trailing flag
         assert_eq!(
-            CommandParser::parse(
-                "\\ns user_search SELECT * FROM users WHERE name = '$1' AND age > $2 --global"
-            )
-            .unwrap(),
-            Command::SaveNamedQuery {
-                name: "user_search".to_string(),
-                query: "SELECT * FROM users WHERE name = '$1' AND age > $2".to_string(),
-                global: true,
-                postgres: false,
-                mysql: false,
-                sqlite: false,
+            CommandParser::parse("\\tz Europe/Paris").unwrap(),
+            Command::SetDisplayTimezone {
+                zone: "Europe/Paris".to_string()
             }
         );
+    }
 
-        // Query with SQL comment containing flag-like text (trailing)
+    #[test]
+    fn test_pset_border_command_parsing() {
         assert_eq!(
-            CommandParser::parse("\\ns myquery SELECT 1 -- this is a comment --postgres").unwrap(),
-            Command::SaveNamedQuery {
-                name: "myquery".to_string(),
-                query: "SELECT 1 -- this is a comment".to_string(),
-                global: false,
-                postgres: true,
-                mysql: false,
-                sqlite: false,
+            CommandParser::parse("\\pset").unwrap(),
+            Command::SetTableStyle {
+                style: String::new()
             }
         );
+        assert_eq!(
+            CommandParser::parse("\\pset border unicode-heavy").unwrap(),
+            Command::SetTableStyle {
+                style: "unicode-heavy".to_string()
+            }
+        );
+        assert!(matches!(
+            CommandParser::parse("\\pset format html"),
+            Err(CommandError::InvalidSyntax(_))
+        ));
     }
 
     #[test]
-    fn test_database_specific_commands() {
-        assert_eq!(CommandParser::parse("\\du").unwrap(), Command::ListUsers);
-        assert_eq!(CommandParser::parse("\\di").unwrap(), Command::ListIndexes);
+    fn test_connection_history_commands() {
         assert_eq!(
-            CommandParser::parse("\\pgpass").unwrap(),
-            Command::ShowPgpass
+            CommandParser::parse("\\r").unwrap(),
+            Command::ListRecentConnections
         );
         assert_eq!(
-            CommandParser::parse("\\myconf").unwrap(),
-            Command::ShowMyconf
+            CommandParser::parse("\\rc").unwrap(),
+            Command::ClearRecentConnections
         );
     }
 
     #[test]
-    fn test_script_commands() {
+    fn test_history_management_commands() {
         assert_eq!(
-            CommandParser::parse("\\w script.sql").unwrap(),
-            Command::WriteScript {
-                filename: "script.sql".to_string()
+            CommandParser::parse("\\hc").unwrap(),
+            Command::ClearSessionHistory { session_hash: None }
+        );
+        assert_eq!(
+            CommandParser::parse("\\hc abc123").unwrap(),
+            Command::ClearSessionHistory {
+                session_hash: Some("abc123".to_string())
             }
         );
+    }
 
+    #[test]
+    fn test_toggle_commands() {
         assert_eq!(
-            CommandParser::parse("\\i load.sql").unwrap(),
-            Command::LoadScript {
-                filename: "load.sql".to_string()
+            CommandParser::parse("\\pager").unwrap(),
+            Command::TogglePager
+        );
+        assert_eq!(
+            CommandParser::parse("\\banner").unwrap(),
+            Command::ToggleBanner
+        );
+        assert_eq!(
+            CommandParser::parse("\\serverinfo").unwrap(),
+            Command::ToggleServerInfo
+        );
+        assert_eq!(
+            CommandParser::parse("\\a").unwrap(),
+            Command::ToggleAutocomplete
+        );
+        assert_eq!(
+            CommandParser::parse("\\cs").unwrap(),
+            Command::ToggleColumnSelection
+        );
+        assert_eq!(
+            CommandParser::parse("\\clrcs").unwrap(),
+            Command::ClearColumnViews
+        );
+        assert_eq!(
+            CommandParser::parse("\\csave wide_orders").unwrap(),
+            Command::SaveColumnView {
+                name: "wide_orders".to_string()
+            }
+        );
+        assert!(CommandParser::parse("\\csave").is_err());
+        assert_eq!(
+            CommandParser::parse("\\cviews").unwrap(),
+            Command::ListColumnViews
+        );
+        assert_eq!(
+            CommandParser::parse("\\cviews delete wide_orders").unwrap(),
+            Command::DeleteColumnView {
+                name: "wide_orders".to_string()
             }
         );
+        assert!(CommandParser::parse("\\cviews rename x").is_err());
+        assert_eq!(
+            CommandParser::parse("\\resetview").unwrap(),
+            Command::ResetView
+        );
+    }
+
+    #[test]
+    fn test_column_threshold_error() {
+        assert!(matches!(
+            CommandParser::parse("\\csthreshold abc"),
+            Err(CommandError::InvalidSyntax(_))
+        ));
+    }
 
+    #[test]
+    fn test_vector_display_commands() {
         assert_eq!(
-            CommandParser::parse("\\ed").unwrap(),
-            Command::EditMultiline
+            CommandParser::parse("\\vd full").unwrap(),
+            Command::SetVectorDisplayMode {
+                mode: "full".to_string()
+            }
+        );
+        assert_eq!(
+            CommandParser::parse("\\vdc").unwrap(),
+            Command::ShowVectorDisplayConfig
         );
         assert_eq!(
-            CommandParser::parse("\\ecopy").unwrap(),
-            Command::CopyExplainPlan
+            CommandParser::parse("\\vs").unwrap(),
+            Command::ToggleVectorStatistics
         );
     }
 
     #[test]
-    fn test_error_cases() {
-        // Missing required arguments
-        assert!(matches!(
-            CommandParser::parse("\\c"),
-            Err(CommandError::MissingArgument(_))
-        ));
+    fn test_insertrow_command_parsing() {
+        assert_eq!(
+            CommandParser::parse("\\insertrow public.users").unwrap(),
+            Command::InsertRow {
+                table: "public.users".to_string(),
+            }
+        );
         assert!(matches!(
-            CommandParser::parse("\\w"),
+            CommandParser::parse("\\insertrow"),
             Err(CommandError::MissingArgument(_))
         ));
+    }
+
+    #[test]
+    fn test_pastedata_command_parsing() {
+        assert_eq!(
+            CommandParser::parse("\\pastedata public.products --yes").unwrap(),
+            Command::PasteData {
+                table: "public.products".to_string(),
+                yes: true,
+            }
+        );
         assert!(matches!(
-            CommandParser::parse("\\ss"),
+            CommandParser::parse("\\pastedata"),
             Err(CommandError::MissingArgument(_))
         ));
         assert!(matches!(
-            CommandParser::parse("\\ns test"),
-            Err(CommandError::MissingArgument(_))
+            CommandParser::parse("\\pastedata a b"),
+            Err(CommandError::InvalidSyntax(_))
         ));
+    }
 
-        // Invalid command syntax
+    #[test]
+    fn test_genrows_command_parsing() {
+        assert_eq!(
+            CommandParser::parse("\\genrows public.users 1_000").unwrap(),
+            Command::GenRows {
+                table: "public.users".to_string(),
+                count: 1000,
+                yes: false,
+            }
+        );
+        assert_eq!(
+            CommandParser::parse("\\genrows orders 5 --yes").unwrap(),
+            Command::GenRows {
+                table: "orders".to_string(),
+                count: 5,
+                yes: true,
+            }
+        );
         assert!(matches!(
-            CommandParser::parse("not_a_command"),
-            Err(CommandError::InvalidSyntax(_))
+            CommandParser::parse("\\genrows orders"),
+            Err(CommandError::MissingArgument(_))
         ));
         assert!(matches!(
-            CommandParser::parse("regular text"),
+            CommandParser::parse("\\genrows orders 0"),
             Err(CommandError::InvalidSyntax(_))
         ));
+    }
 
-        // Unknown commands
-        assert!(matches!(
-            CommandParser::parse("\\unknown"),
-            Err(CommandError::UnknownCommand(_))
-        ));
+    #[test]
+    fn test_lint_command_parsing() {
+        assert_eq!(
+            CommandParser::parse("\\lint DELETE FROM users").unwrap(),
+            Command::Lint {
+                query: "DELETE FROM users".to_string(),
+            }
+        );
         assert!(matches!(
-            CommandParser::parse("\\xyz"),
-            Err(CommandError::UnknownCommand(_))
+            CommandParser::parse("\\lint"),
+            Err(CommandError::MissingArgument(_))
         ));
     }
 
     #[test]
-    fn test_advanced_commands() {
-        // Test EXPLAIN variants
+    fn test_fmt_command_parsing() {
         assert_eq!(
-            CommandParser::parse("\\er SELECT 1").unwrap(),
-            Command::ExplainRaw {
-                query: "SELECT 1".to_string()
+            CommandParser::parse("\\fmt").unwrap(),
+            Command::Format { query: None }
+        );
+        assert_eq!(
+            CommandParser::parse("\\fmt select 1").unwrap(),
+            Command::Format {
+                query: Some("select 1".to_string())
             }
         );
+    }
 
+    #[test]
+    fn test_dryrun_command_parsing() {
         assert_eq!(
-            CommandParser::parse("\\ef SELECT 1").unwrap(),
-            Command::ExplainFormatted {
-                query: "SELECT 1".to_string()
+            CommandParser::parse("\\dryrun DELETE FROM users WHERE id = 1").unwrap(),
+            Command::DryRun {
+                query: "DELETE FROM users WHERE id = 1".to_string(),
             }
         );
+        assert!(matches!(
+            CommandParser::parse("\\dryrun"),
+            Err(CommandError::MissingArgument(_))
+        ));
+    }
 
+    #[test]
+    fn test_count_command_parsing() {
         assert_eq!(
-            CommandParser::parse("\\ex SELECT 1 output.txt").unwrap(),
-            Command::ExplainExport {
-                query: "SELECT 1".to_string(),
-                filename: "output.txt".to_string()
+            CommandParser::parse("\\count").unwrap(),
+            Command::Count { target: None }
+        );
+        assert_eq!(
+            CommandParser::parse("\\count public.orders").unwrap(),
+            Command::Count {
+                target: Some("public.orders".to_string())
             }
         );
+    }
 
-        // Test threshold setting
+    #[test]
+    fn test_replication_command_parsing() {
         assert_eq!(
-            CommandParser::parse("\\csthreshold 50").unwrap(),
-            Command::SetColumnSelectionThreshold { threshold: 50 }
+            CommandParser::parse("\\replication").unwrap(),
+            Command::Replication
         );
+    }
 
-        // Test multiline indicator
+    #[test]
+    fn test_cluster_command_parsing() {
         assert_eq!(
-            CommandParser::parse("\\setmulti >").unwrap(),
-            Command::SetMultilineIndicator {
-                indicator: ">".to_string()
+            CommandParser::parse("\\cluster").unwrap(),
+            Command::Cluster { name: None }
+        );
+        assert_eq!(
+            CommandParser::parse("\\cluster analytics").unwrap(),
+            Command::Cluster {
+                name: Some("analytics".to_string())
             }
         );
     }
 
     #[test]
-    fn test_display_option_commands() {
+    fn test_connect_reconnect_url() {
+        let config = DbCrustConfig::default();
         assert_eq!(
-            CommandParser::parse("\\x").unwrap(),
-            Command::ToggleExpandedDisplay
+            reconnect_url("postgres://app@replica/app", &config).as_deref(),
+            Some("postgres://app@replica/app")
         );
         assert_eq!(
-            CommandParser::parse("\\e").unwrap(),
-            Command::ToggleExplainMode
+            reconnect_url("recent://2", &config).as_deref(),
+            Some("recent://2")
         );
-        assert_eq!(
-            CommandParser::parse("\\ev").unwrap(),
-            Command::ToggleExplainTuiMode
+        // Not a saved session: a database on the current server
+        assert_eq!(reconnect_url("analytics", &config), None);
+
+        let mut config = config;
+        config.aliases.insert(
+            "analytics".to_string(),
+            "postgres://bi@warehouse/analytics".to_string(),
         );
         assert_eq!(
-            CommandParser::parse("\\config").unwrap(),
-            Command::ConfigMenu
+            reconnect_url("analytics", &config).as_deref(),
+            Some("analytics")
         );
     }
 
-    #[rstest::rstest]
-    #[case("\\config show", Command::ShowConfig)]
-    #[case("\\config get", Command::ConfigGet { key: None })]
-    #[case(
-        "\\config get logging.level",
-        Command::ConfigGet { key: Some("logging.level".to_string()) }
-    )]
-    #[case(
-        "\\config set logging.level debug",
-        Command::ConfigSet { key: "logging.level".to_string(), value: "debug".to_string() }
-    )]
-    // The value is the remainder of the line — spaces are preserved.
-    #[case(
-        "\\config set pager_command less -RFX",
-        Command::ConfigSet { key: "pager_command".to_string(), value: "less -RFX".to_string() }
-    )]
-    #[case("\\config edit", Command::ConfigEdit)]
-    fn test_config_subcommand_parsing(#[case] input: &str, #[case] expected: Command) {
-        assert_eq!(CommandParser::parse(input).unwrap(), expected);
-    }
-
     #[test]
-    fn test_config_subcommand_errors() {
-        assert!(matches!(
-            CommandParser::parse("\\config set"),
-            Err(CommandError::MissingArgument(_))
-        ));
-        assert!(matches!(
-            CommandParser::parse("\\config set default_limit"),
-            Err(CommandError::MissingArgument(_))
-        ));
+    fn test_mysql_diagnostic_command_parsing() {
+        assert_eq!(
+            CommandParser::parse("\\processlist").unwrap(),
+            Command::MySqlProcessList
+        );
+        assert_eq!(
+            CommandParser::parse("\\locks").unwrap(),
+            Command::MySqlLocks
+        );
+        assert_eq!(
+            CommandParser::parse("\\slowqueries").unwrap(),
+            Command::MySqlSlowQueries { limit: 10 }
+        );
+        assert_eq!(
+            CommandParser::parse("\\slowqueries 25").unwrap(),
+            Command::MySqlSlowQueries { limit: 25 }
+        );
         assert!(matches!(
-            CommandParser::parse("\\config bogus"),
+            CommandParser::parse("\\slowqueries 0"),
             Err(CommandError::InvalidSyntax(_))
         ));
     }
 
     #[test]
-    fn test_connection_history_commands() {
+    fn test_postgres_diagnostic_command_parsing() {
         assert_eq!(
-            CommandParser::parse("\\r").unwrap(),
-            Command::ListRecentConnections
+            CommandParser::parse("\\activity").unwrap(),
+            Command::PgActivity
         );
         assert_eq!(
-            CommandParser::parse("\\rc").unwrap(),
-            Command::ClearRecentConnections
+            CommandParser::parse("\\blocking").unwrap(),
+            Command::PgBlocking
+        );
+        assert_eq!(
+            CommandParser::parse("\\bloat").unwrap(),
+            Command::PgBloat { limit: 20 }
+        );
+        assert_eq!(
+            CommandParser::parse("\\bloat 5").unwrap(),
+            Command::PgBloat { limit: 5 }
         );
+        assert!(matches!(
+            CommandParser::parse("\\bloat many"),
+            Err(CommandError::InvalidSyntax(_))
+        ));
     }
 
     #[test]
-    fn test_history_management_commands() {
+    fn test_sqlite_maintenance_command_parsing() {
         assert_eq!(
-            CommandParser::parse("\\hc").unwrap(),
-            Command::ClearSessionHistory { session_hash: None }
+            CommandParser::parse("\\backup /tmp/app.db.bak").unwrap(),
+            Command::SqliteBackup {
+                file: "/tmp/app.db.bak".to_string(),
+                force: false,
+            }
         );
         assert_eq!(
-            CommandParser::parse("\\hc abc123").unwrap(),
-            Command::ClearSessionHistory {
-                session_hash: Some("abc123".to_string())
+            CommandParser::parse("\\backup --force app.bak").unwrap(),
+            Command::SqliteBackup {
+                file: "app.bak".to_string(),
+                force: true,
             }
         );
+        assert!(matches!(
+            CommandParser::parse("\\backup"),
+            Err(CommandError::MissingArgument(_))
+        ));
+        assert_eq!(
+            CommandParser::parse("\\vacuum").unwrap(),
+            Command::SqliteVacuum
+        );
+        assert_eq!(
+            CommandParser::parse("\\integritycheck quick").unwrap(),
+            Command::SqliteIntegrityCheck { quick: true }
+        );
+        assert!(matches!(
+            CommandParser::parse("\\integritycheck full"),
+            Err(CommandError::InvalidSyntax(_))
+        ));
     }
 
     #[test]
-    fn test_toggle_commands() {
+    fn test_watch_command_parsing() {
         assert_eq!(
-            CommandParser::parse("\\pager").unwrap(),
-            Command::TogglePager
+            CommandParser::parse("\\watch").unwrap(),
+            Command::Watch {
+                interval: std::time::Duration::from_secs(2)
+            }
         );
         assert_eq!(
-            CommandParser::parse("\\banner").unwrap(),
-            Command::ToggleBanner
+            CommandParser::parse("\\watch 0.5").unwrap(),
+            Command::Watch {
+                interval: std::time::Duration::from_millis(500)
+            }
         );
+        assert!(matches!(
+            CommandParser::parse("\\watch 0"),
+            Err(CommandError::InvalidSyntax(_))
+        ));
+    }
+
+    #[test]
+    fn test_chinsert_command_parsing() {
         assert_eq!(
-            CommandParser::parse("\\serverinfo").unwrap(),
-            Command::ToggleServerInfo
+            CommandParser::parse("\\chinsert analytics.events /tmp/events.csv").unwrap(),
+            Command::ClickHouseInsert {
+                table: "analytics.events".to_string(),
+                file: "/tmp/events.csv".to_string(),
+                format: None,
+            }
         );
         assert_eq!(
-            CommandParser::parse("\\a").unwrap(),
-            Command::ToggleAutocomplete
+            CommandParser::parse("\\chinsert events data.ndjson JSONEachRow").unwrap(),
+            Command::ClickHouseInsert {
+                table: "events".to_string(),
+                file: "data.ndjson".to_string(),
+                format: Some("JSONEachRow".to_string()),
+            }
         );
+        assert!(matches!(
+            CommandParser::parse("\\chinsert events"),
+            Err(CommandError::MissingArgument(_))
+        ));
+        assert!(matches!(
+            CommandParser::parse("\\chinsert events a.csv CSV extra"),
+            Err(CommandError::InvalidSyntax(_))
+        ));
+    }
+
+    #[test]
+    fn test_knn_command_parsing() {
+        use crate::vector_search::{DistanceMetric, KnnTarget};
+
         assert_eq!(
-            CommandParser::parse("\\cs").unwrap(),
-            Command::ToggleColumnSelection
+            CommandParser::parse("\\knn items embedding [0.1, 0.2, 0.3] 5 --metric cosine")
+                .unwrap(),
+            Command::VectorKnn {
+                table: "items".to_string(),
+                column: "embedding".to_string(),
+                target: KnnTarget::Vector("[0.1,0.2,0.3]".to_string()),
+                k: 5,
+                metric: DistanceMetric::Cosine,
+            }
         );
         assert_eq!(
-            CommandParser::parse("\\clrcs").unwrap(),
-            Command::ClearColumnViews
+            CommandParser::parse("\\knn items embedding id=42").unwrap(),
+            Command::VectorKnn {
+                table: "items".to_string(),
+                column: "embedding".to_string(),
+                target: KnnTarget::Row {
+                    key: "id".to_string(),
+                    value: "42".to_string(),
+                },
+                k: crate::vector_search::DEFAULT_KNN_LIMIT,
+                metric: DistanceMetric::L2,
+            }
         );
+        // Repeated spaces between the arguments
         assert_eq!(
-            CommandParser::parse("\\resetview").unwrap(),
-            Command::ResetView
+            CommandParser::parse("\\knn items   embedding  id=42   3").unwrap(),
+            Command::VectorKnn {
+                table: "items".to_string(),
+                column: "embedding".to_string(),
+                target: KnnTarget::Row {
+                    key: "id".to_string(),
+                    value: "42".to_string(),
+                },
+                k: 3,
+                metric: DistanceMetric::L2,
+            }
         );
-    }
 
-    #[test]
-    fn test_column_threshold_error() {
         assert!(matches!(
-            CommandParser::parse("\\csthreshold abc"),
+            CommandParser::parse("\\knn items embedding"),
+            Err(CommandError::MissingArgument(_))
+        ));
+        assert!(matches!(
+            CommandParser::parse("\\knn items embedding id=42 zero"),
+            Err(CommandError::InvalidSyntax(_))
+        ));
+        assert!(matches!(
+            CommandParser::parse("\\knn items embedding id=42 --metric=hamming"),
             Err(CommandError::InvalidSyntax(_))
         ));
     }
 
     #[test]
-    fn test_vector_display_commands() {
+    fn test_browse_command_parsing() {
         assert_eq!(
-            CommandParser::parse("\\vd full").unwrap(),
-            Command::SetVectorDisplayMode {
-                mode: "full".to_string()
+            CommandParser::parse("\\browse").unwrap(),
+            Command::SchemaBrowser
+        );
+        assert_eq!(Command::SchemaBrowser.usage(), "\\browse");
+    }
+
+    #[test]
+    fn test_erd_command_parsing() {
+        use crate::schema_graph::ErdFormat;
+
+        assert_eq!(
+            CommandParser::parse("\\erd").unwrap(),
+            Command::EntityRelationshipDiagram {
+                target: None,
+                format: ErdFormat::Unicode,
+                output: None,
             }
         );
         assert_eq!(
-            CommandParser::parse("\\vdc").unwrap(),
-            Command::ShowVectorDisplayConfig
+            CommandParser::parse("\\erd public --mermaid erd.mmd").unwrap(),
+            Command::EntityRelationshipDiagram {
+                target: Some("public".to_string()),
+                format: ErdFormat::Mermaid,
+                output: Some("erd.mmd".to_string()),
+            }
         );
         assert_eq!(
-            CommandParser::parse("\\vs").unwrap(),
-            Command::ToggleVectorStatistics
+            CommandParser::parse("\\erd orders --ascii").unwrap(),
+            Command::EntityRelationshipDiagram {
+                target: Some("orders".to_string()),
+                format: ErdFormat::Ascii,
+                output: None,
+            }
         );
+
+        assert!(matches!(
+            CommandParser::parse("\\erd --dot"),
+            Err(CommandError::MissingArgument(_))
+        ));
+        assert!(matches!(
+            CommandParser::parse("\\erd --svg out.svg"),
+            Err(CommandError::InvalidSyntax(_))
+        ));
     }
 
     #[test]
@@ -4196,6 +9289,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_flatten_command() {
+        assert_eq!(
+            CommandParser::parse("\\flatten").unwrap(),
+            Command::Flatten {
+                depth: None,
+                arrays: None,
+                save: false,
+                forget: false
+            }
+        );
+        assert_eq!(
+            CommandParser::parse("\\flatten explode 2 save").unwrap(),
+            Command::Flatten {
+                depth: Some(2),
+                arrays: Some(crate::flatten::ArrayMode::Explode),
+                save: true,
+                forget: false
+            }
+        );
+        assert!(CommandParser::parse("\\flatten sideways").is_err());
+        assert!(CommandParser::parse("\\flatten save forget").is_err());
+    }
+
     #[test]
     fn test_vault_cache_commands() {
         assert_eq!(
@@ -4258,6 +9375,18 @@ mod tests {
     #[test]
     fn test_additional_database_specific_commands() {
         assert_eq!(CommandParser::parse("\\dp").unwrap(), Command::ListPragmas);
+        assert_eq!(
+            CommandParser::parse("\\sf+ billing.total_for(integer, text) --edit").unwrap(),
+            Command::ShowFunction {
+                name: "billing.total_for(integer, text)".to_string(),
+                numbered: true,
+                edit: true,
+            }
+        );
+        assert!(matches!(
+            CommandParser::parse("\\sf"),
+            Err(CommandError::MissingArgument(_))
+        ));
         assert_eq!(
             CommandParser::parse("\\docker").unwrap(),
             Command::ListDockerContainers
@@ -7,17 +7,20 @@
 use crate::command_completion::CommandCompletionManager;
 use crate::commands::CommandShortcut;
 use crate::completion_provider::TableInfo;
+use crate::completion_usage::CompletionUsage;
 use crate::config::Config;
 use crate::database::DatabaseType;
 use crate::db::Database;
+use crate::schema_cache::ColumnMeta;
 use crate::sql_parser::{ExpectedElement, SqlClause, SqlContext};
 use crate::sql_parser_trait::{
-    CompletionHintCategory, EnhancedSqlContext, SqlParserEngine, SqlParserFactory,
+    CompletionHintCategory, DatabaseSpecificContext, EnhancedSqlContext, SqlParserEngine,
+    SqlParserFactory,
 };
 use nu_ansi_term::{Color, Style};
 use reedline::{Completer, Span, Suggestion};
 use regex::Regex;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::{Arc, Mutex};
 use strum::IntoEnumIterator;
 use tracing::{debug, error};
@@ -31,6 +34,14 @@ impl Completer for NoopCompleter {
     }
 }
 
+/// `desc` preceded by the column's type, keys and distinct estimate when known
+fn with_column_meta(meta: &BTreeMap<String, ColumnMeta>, column: &str, desc: String) -> String {
+    match meta.get(column) {
+        Some(column_meta) => format!("{} · {desc}", column_meta.summary()),
+        None => desc,
+    }
+}
+
 /// Enhanced SQL completer with proper parsing and context awareness
 pub struct SqlCompleter {
     database: Arc<Mutex<Database>>,
@@ -42,10 +53,14 @@ pub struct SqlCompleter {
     table_cache: HashMap<String, Vec<TableInfo>>,
     /// Cache for columns by table
     column_cache: HashMap<String, Vec<String>>,
+    /// Cache for column metadata (type, keys, distinct estimate) by table
+    column_meta_cache: HashMap<String, BTreeMap<String, ColumnMeta>>,
     /// Last database name for cache invalidation
     last_db_name: Option<String>,
     /// Shared state to access full line buffer content
     full_line_buffer: Arc<Mutex<Option<String>>>,
+    /// Usage counters for frecency ranking, shared with the REPL that records them
+    usage: Option<Arc<Mutex<CompletionUsage>>>,
 }
 
 /// Command completion types for smart classification
@@ -87,8 +102,10 @@ impl SqlCompleter {
             schema_cache: None,
             table_cache: HashMap::new(),
             column_cache: HashMap::new(),
+            column_meta_cache: HashMap::new(),
             last_db_name: None,
             full_line_buffer: Arc::new(Mutex::new(None)),
+            usage: None,
         }
     }
 
@@ -107,16 +124,38 @@ impl SqlCompleter {
             schema_cache: None,
             table_cache: HashMap::new(),
             column_cache: HashMap::new(),
+            column_meta_cache: HashMap::new(),
             last_db_name: None,
             full_line_buffer,
+            usage: None,
         }
     }
 
+    /// Rank candidates by `usage` when `completion_rank_by_usage` is on
+    pub fn with_usage(mut self, usage: Arc<Mutex<CompletionUsage>>) -> Self {
+        self.usage = Some(usage);
+        self
+    }
+
+    fn rank_by_usage(&self, suggestions: &mut Vec<Suggestion>) {
+        let Some(usage) = &self.usage else {
+            return;
+        };
+        if suggestions.len() < 2 || !self.config.lock().unwrap().completion_rank_by_usage {
+            return;
+        }
+        let session = self.database.try_lock().ok().and_then(|db| {
+            crate::history_manager::SessionId::from_database(&db).map(|id| id.to_hash())
+        });
+        usage.lock().unwrap().rank(suggestions, session.as_deref());
+    }
+
     /// Clear all caches
     pub fn clear_cache(&mut self) {
         self.schema_cache = None;
         self.table_cache.clear();
         self.column_cache.clear();
+        self.column_meta_cache.clear();
     }
 
     /// Classify command completion type for smart handling
@@ -134,6 +173,19 @@ impl SqlCompleter {
             ));
         }
 
+        // \gexec takes an optional query generating the statements
+        if line.starts_with("\\gexec ") {
+            tracing::debug!("classify_command: SQL completion for \\gexec");
+            return Some(CommandCompletionType::SqlCompletion(
+                "\\gexec".to_string(),
+                7,
+            ));
+        }
+        if line.starts_with("\\fmt ") {
+            tracing::debug!("classify_command: SQL completion for \\fmt");
+            return Some(CommandCompletionType::SqlCompletion("\\fmt".to_string(), 5));
+        }
+
         // Check for complex commands
         if line.starts_with("\\ns ") {
             tracing::debug!("classify_command: Complex completion for \\ns");
@@ -172,11 +224,16 @@ impl SqlCompleter {
     /// Check if cache needs invalidation
     fn check_cache_validity(&mut self) {
         let (current_db, _has_connection) = {
-            let db_guard = self.database.lock().unwrap();
-            (
-                db_guard.get_current_db(),
-                db_guard.has_database_connection(),
-            )
+            let mut db_guard = self.database.lock().unwrap();
+            // The schema picked with \\schema changes which tables are visible
+            let mut current_db = match db_guard.current_schema() {
+                Some(schema) => format!("{}.{schema}", db_guard.get_current_db()),
+                None => db_guard.get_current_db(),
+            };
+            // A refreshed schema cache replaces what was saved before
+            let generation = db_guard.schema_cache_mut().generation();
+            current_db.push_str(&format!("#{generation}"));
+            (current_db, db_guard.has_database_connection())
         };
 
         if self.last_db_name.as_ref() != Some(&current_db) {
@@ -185,12 +242,21 @@ impl SqlCompleter {
         }
     }
 
+    /// Count a metadata lookup for `\stats`; skipped while the database is busy
+    fn record_cache_lookup(&self, hit: bool) {
+        if let Ok(mut db) = self.database.try_lock() {
+            db.record_cache_lookup(hit);
+        }
+    }
+
     /// Get schemas (with caching)
     #[allow(dead_code)]
     fn get_schemas(&mut self) -> Vec<String> {
         if let Some(ref schemas) = self.schema_cache {
+            self.record_cache_lookup(true);
             return schemas.clone();
         }
+        self.record_cache_lookup(false);
 
         let db_clone = Arc::clone(&self.database);
         let schemas = match tokio::runtime::Handle::try_current() {
@@ -211,13 +277,67 @@ impl SqlCompleter {
         schemas
     }
 
+    /// ClickHouse cluster names from system.clusters, for `ON CLUSTER`
+    fn get_clusters(&self) -> Vec<String> {
+        let db_clone = Arc::clone(&self.database);
+        let rows = match tokio::runtime::Handle::try_current() {
+            Ok(_) => tokio::task::block_in_place(|| {
+                let handle = tokio::runtime::Handle::current();
+                handle.block_on(async {
+                    let db_guard = db_clone.lock().unwrap();
+                    db_guard
+                        .server_diagnostic(crate::database::ServerDiagnostic::Clusters)
+                        .await
+                        .unwrap_or_default()
+                })
+            }),
+            Err(_) => {
+                error!("No tokio runtime for cluster fetch");
+                vec![]
+            }
+        };
+        let mut clusters: Vec<String> = rows
+            .into_iter()
+            .skip(1)
+            .filter_map(|row| row.into_iter().next())
+            .collect();
+        clusters.dedup();
+        clusters
+    }
+
     /// Get tables for a schema (with caching)
     fn get_tables(&mut self, schema: Option<&str>) -> Vec<TableInfo> {
         let cache_key = schema.unwrap_or("").to_string();
 
         if let Some(tables) = self.table_cache.get(&cache_key) {
-            return tables.clone();
+            let tables = tables.clone();
+            self.record_cache_lookup(true);
+            return tables;
         }
+        let to_table_info = |names: Vec<String>| -> Vec<TableInfo> {
+            names
+                .into_iter()
+                .map(|name| TableInfo {
+                    schema: schema.map(str::to_string),
+                    name,
+                    table_type: crate::completion_provider::TableType::Table,
+                })
+                .collect()
+        };
+        let saved = self
+            .database
+            .lock()
+            .unwrap()
+            .schema_cache_mut()
+            .tables(&cache_key)
+            .cloned();
+        if let Some(names) = saved {
+            let tables = to_table_info(names);
+            self.table_cache.insert(cache_key, tables.clone());
+            self.record_cache_lookup(true);
+            return tables;
+        }
+        self.record_cache_lookup(false);
 
         let db_clone = Arc::clone(&self.database);
         let schema_owned = schema.map(|s| s.to_string());
@@ -242,16 +362,10 @@ impl SqlCompleter {
                                 .await
                                 .unwrap_or_default()
                         };
-
-                        // Convert to TableInfo
+                        db_guard
+                            .schema_cache_mut()
+                            .store_tables(&cache_key, &table_names);
                         table_names
-                            .into_iter()
-                            .map(|name| TableInfo {
-                                schema: schema_owned.clone(),
-                                name,
-                                table_type: crate::completion_provider::TableType::Table,
-                            })
-                            .collect()
                     })
                 })
             }
@@ -260,6 +374,7 @@ impl SqlCompleter {
                 vec![]
             }
         };
+        let tables = to_table_info(tables);
 
         self.table_cache.insert(cache_key, tables.clone());
         tables
@@ -420,8 +535,23 @@ impl SqlCompleter {
     /// Get columns for a table (with caching)
     fn get_columns(&mut self, table: &str) -> Vec<String> {
         if let Some(columns) = self.column_cache.get(table) {
-            return columns.clone();
+            let columns = columns.clone();
+            self.record_cache_lookup(true);
+            return columns;
+        }
+        let saved = self
+            .database
+            .lock()
+            .unwrap()
+            .schema_cache_mut()
+            .columns(table)
+            .cloned();
+        if let Some(columns) = saved {
+            self.column_cache.insert(table.to_string(), columns.clone());
+            self.record_cache_lookup(true);
+            return columns;
         }
+        self.record_cache_lookup(false);
 
         let db_clone = Arc::clone(&self.database);
         let table_owned = table.to_string();
@@ -459,7 +589,12 @@ impl SqlCompleter {
                                 table_owned
                             );
                             match db_guard.get_columns(&table_owned).await {
-                                Ok(cols) => cols,
+                                Ok(cols) => {
+                                    db_guard
+                                        .schema_cache_mut()
+                                        .store_columns(&table_owned, &cols);
+                                    cols
+                                }
                                 Err(e) => {
                                     error!(
                                         "[SqlCompleter] ❌ Failed to fetch columns for '{}': {}",
@@ -506,6 +641,37 @@ impl SqlCompleter {
         columns
     }
 
+    /// Get column metadata for a table: from the saved snapshot, else read
+    /// once per session from backends with a catalog (with caching)
+    fn get_column_meta(&mut self, table: &str) -> BTreeMap<String, ColumnMeta> {
+        if let Some(meta) = self.column_meta_cache.get(table) {
+            return meta.clone();
+        }
+        let mut db_guard = self.database.lock().unwrap();
+        let saved = db_guard.schema_cache_mut().column_meta(table).cloned();
+        let meta = match saved {
+            Some(meta) => meta,
+            None if db_guard.has_database_connection()
+                && crate::schema_cache::has_column_catalog(&db_guard.get_database_type())
+                && tokio::runtime::Handle::try_current().is_ok() =>
+            {
+                tokio::task::block_in_place(|| {
+                    tokio::runtime::Handle::current().block_on(async {
+                        let meta =
+                            crate::schema_cache::fetch_column_meta(&mut db_guard, table).await;
+                        db_guard.schema_cache_mut().store_column_meta(table, &meta);
+                        meta
+                    })
+                })
+            }
+            None => BTreeMap::new(),
+        };
+        drop(db_guard);
+        self.column_meta_cache
+            .insert(table.to_string(), meta.clone());
+        meta
+    }
+
     /// Delegate to the command manager's argument completion without
     /// re-entering complete_backslash_commands: classify_command would route
     /// a "\ns " line straight back into complete_ns_command — infinite mutual
@@ -791,6 +957,30 @@ impl SqlCompleter {
         let mut suggestions = Vec::new();
         let lower_word = current_word.to_lowercase();
 
+        // ClickHouse distributed DDL: only cluster names fit after ON CLUSTER
+        if let DatabaseSpecificContext::ClickHouse {
+            expecting_cluster: true,
+        } = context.database_context
+        {
+            for cluster in self.get_clusters() {
+                if cluster.to_lowercase().starts_with(&lower_word) {
+                    suggestions.push(Suggestion {
+                        value: cluster,
+                        description: Some("Cluster".to_string()),
+                        span: Span {
+                            start: word_start,
+                            end: pos,
+                        },
+                        append_whitespace: true,
+                        extra: None,
+                        style: Some(Style::new().fg(Color::Blue)),
+                        ..Default::default()
+                    });
+                }
+            }
+            return suggestions;
+        }
+
         // PRIORITY 1: Columns first in WHERE clause, then handle specific context logic
         let mut columns_added = false;
 
@@ -803,6 +993,7 @@ impl SqlCompleter {
                     table_ref.table
                 );
                 let columns = self.get_columns(&table_ref.table);
+                let meta = self.get_column_meta(&table_ref.table);
                 debug!(
                     "[SqlCompleter] Got {} columns from {}",
                     columns.len(),
@@ -907,9 +1098,10 @@ impl SqlCompleter {
                                     {
                                         suggestions.push(Suggestion {
                                             value: format!("{table_prefix}.{column}"),
-                                            description: Some(format!(
-                                                "Column from {}",
-                                                table_ref.table
+                                            description: Some(with_column_meta(
+                                                &meta,
+                                                &column,
+                                                format!("Column from {}", table_ref.table),
                                             )),
                                             span: Span {
                                                 start: word_start,
@@ -951,6 +1143,7 @@ impl SqlCompleter {
                                 "[SqlCompleter] Adding column suggestion: {} -> {}",
                                 column, desc
                             );
+                            let desc = with_column_meta(&meta, &column, desc);
                             suggestions.push(Suggestion {
                                 value: column,
                                 description: Some(desc),
@@ -1055,6 +1248,7 @@ impl SqlCompleter {
                         i, table_ref.table, table_ref.alias
                     );
                     let columns = self.get_columns(&table_ref.table);
+                    let meta = self.get_column_meta(&table_ref.table);
                     debug!(
                         "[SqlCompleter] Got {} columns from future table {}",
                         columns.len(),
@@ -1160,9 +1354,13 @@ impl SqlCompleter {
                                         {
                                             suggestions.push(Suggestion {
                                                 value: format!("{table_prefix}.{column}"),
-                                                description: Some(format!(
-                                                    "Column from {} (forward-looking)",
-                                                    table_ref.table
+                                                description: Some(with_column_meta(
+                                                    &meta,
+                                                    &column,
+                                                    format!(
+                                                        "Column from {} (forward-looking)",
+                                                        table_ref.table
+                                                    ),
                                                 )),
                                                 span: Span {
                                                     start: word_start,
@@ -1207,6 +1405,7 @@ impl SqlCompleter {
                                     "[SqlCompleter] ✅ Adding forward-looking column suggestion: {} -> {}",
                                     column, desc
                                 );
+                                let desc = with_column_meta(&meta, &column, desc);
                                 suggestions.push(Suggestion {
                                     value: column,
                                     description: Some(desc),
@@ -1391,6 +1590,7 @@ impl SqlCompleter {
                             table_ref.table
                         );
                         let columns = self.get_columns(&table_ref.table);
+                        let meta = self.get_column_meta(&table_ref.table);
                         debug!(
                             "[SqlCompleter] Got {} columns from {}",
                             columns.len(),
@@ -1496,9 +1696,10 @@ impl SqlCompleter {
                                             {
                                                 suggestions.push(Suggestion {
                                                     value: format!("{table_prefix}.{column}"),
-                                                    description: Some(format!(
-                                                        "Column from {}",
-                                                        table_ref.table
+                                                    description: Some(with_column_meta(
+                                                        &meta,
+                                                        &column,
+                                                        format!("Column from {}", table_ref.table),
                                                     )),
                                                     span: Span {
                                                         start: word_start,
@@ -1549,6 +1750,7 @@ impl SqlCompleter {
                                         "[SqlCompleter] Adding column suggestion: {} -> {}",
                                         column, desc
                                     );
+                                    let desc = with_column_meta(&meta, &column, desc);
                                     suggestions.push(Suggestion {
                                         value: column,
                                         description: Some(desc),
@@ -1711,7 +1913,9 @@ impl SqlCompleter {
 
         // Get database type and create appropriate parser
         let database_type = self.get_database_type();
-        let parser = SqlParserFactory::create_parser(database_type.clone());
+        let extensions = self.database.lock().unwrap().pg_extensions();
+        let parser =
+            SqlParserFactory::create_parser_with_extensions(database_type.clone(), &extensions);
 
         // Parse SQL context using database-specific parser
         let enhanced_context = parser.parse_at_cursor(&full_line, sql_pos);
@@ -1781,12 +1985,14 @@ impl Completer for SqlCompleter {
             }
         };
 
-        if let Some(full_line) = full_line_option {
+        let mut suggestions = if let Some(full_line) = full_line_option {
             // Use full_line instead of the truncated line for parsing!
-            return self.complete_with_full_line(&full_line, pos);
-        }
-
-        self.complete_internal(line, pos)
+            self.complete_with_full_line(&full_line, pos)
+        } else {
+            self.complete_internal(line, pos)
+        };
+        self.rank_by_usage(&mut suggestions);
+        suggestions
     }
 }
 
@@ -1910,6 +2116,25 @@ impl SqlCompleter {
                         }
                     }
                 }
+
+                // `\c` also takes [aliases] names
+                let aliases = self.config.lock().unwrap().alias_names();
+                for alias in aliases {
+                    if alias.to_lowercase().contains(&current_word.to_lowercase()) {
+                        suggestions.push(Suggestion {
+                            value: alias,
+                            description: Some("Alias".to_string()),
+                            span: Span {
+                                start: word_start,
+                                end: pos,
+                            },
+                            append_whitespace: true,
+                            extra: None,
+                            style: Some(Style::new().fg(Color::Magenta)),
+                            ..Default::default()
+                        });
+                    }
+                }
                 return suggestions;
             }
 
@@ -1960,6 +2185,7 @@ impl SqlCompleter {
                                     crate::database::DatabaseType::Elasticsearch => {
                                         "[elasticsearch]"
                                     }
+                                    crate::database::DatabaseType::Snowflake => "[snowflake]",
                                     crate::database::DatabaseType::Parquet => "[parquet]",
                                     crate::database::DatabaseType::CSV => "[csv]",
                                     crate::database::DatabaseType::JSON => "[json]",
@@ -2033,6 +2259,7 @@ impl SqlCompleter {
                                     crate::database::DatabaseType::Elasticsearch => {
                                         "[elasticsearch]"
                                     }
+                                    crate::database::DatabaseType::Snowflake => "[snowflake]",
                                     crate::database::DatabaseType::Parquet => "[parquet]",
                                     crate::database::DatabaseType::CSV => "[csv]",
                                     crate::database::DatabaseType::JSON => "[json]",
@@ -2241,7 +2468,9 @@ impl SqlCompleter {
 
         // Get database type and create appropriate parser
         let database_type = self.get_database_type();
-        let parser = SqlParserFactory::create_parser(database_type.clone());
+        let extensions = self.database.lock().unwrap().pg_extensions();
+        let parser =
+            SqlParserFactory::create_parser_with_extensions(database_type.clone(), &extensions);
 
         // Parse SQL context using database-specific parser
         let enhanced_context = parser.parse_at_cursor(&full_line, pos);
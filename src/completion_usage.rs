@@ -0,0 +1,309 @@
+//! Frecency ranking of completion candidates
+//!
+//! Every statement and backslash command run in the REPL bumps a score for the
+//! identifiers and command it mentions, both globally and for the connection it
+//! ran on. Scores halve every [`HALF_LIFE_DAYS`], so names used often and
+//! recently float to the top of the completion menu. Stored next to the history
+//! files in `completion_usage.toml`; `completion_rank_by_usage = false` keeps
+//! the alphabetical order.
+
+use reedline::Suggestion;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tracing::debug;
+
+const HALF_LIFE_DAYS: f64 = 14.0;
+/// Names kept per scope; the lowest scores are dropped beyond this
+const MAX_ENTRIES: usize = 1000;
+/// Weight of the current connection's usage relative to the global one
+const SESSION_WEIGHT: f64 = 2.0;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+struct UsageEntry {
+    score: f64,
+    /// Unix timestamp of the last use
+    last_used: i64,
+}
+
+impl UsageEntry {
+    fn score_at(&self, now: i64) -> f64 {
+        let age_days = (now - self.last_used).max(0) as f64 / 86_400.0;
+        self.score * 0.5f64.powf(age_days / HALF_LIFE_DAYS)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct UsageStore {
+    #[serde(default)]
+    global: HashMap<String, UsageEntry>,
+    /// Keyed by the session hash used for the history file names
+    #[serde(default)]
+    sessions: HashMap<String, HashMap<String, UsageEntry>>,
+}
+
+#[derive(Debug, Default)]
+pub struct CompletionUsage {
+    store: UsageStore,
+    /// `None` keeps the counters in memory only (tests, unreadable config dir)
+    path: Option<PathBuf>,
+}
+
+impl CompletionUsage {
+    /// Load `completion_usage.toml` from the config directory
+    pub fn load() -> Self {
+        let path = match crate::config::Config::get_config_dir() {
+            Ok(dir) => dir.join("completion_usage.toml"),
+            Err(e) => {
+                debug!("No config directory for completion usage: {}", e);
+                return Self::default();
+            }
+        };
+        let store = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| match toml::from_str(&content) {
+                Ok(store) => Some(store),
+                Err(e) => {
+                    debug!("Ignoring unreadable {}: {}", path.display(), e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+        Self {
+            store,
+            path: Some(path),
+        }
+    }
+
+    /// Count the names used by `line` (a statement or backslash command)
+    pub fn record(&mut self, line: &str, session: Option<&str>) {
+        let now = chrono::Utc::now().timestamp();
+        let names = usage_names(line);
+        if names.is_empty() {
+            return;
+        }
+        bump(&mut self.store.global, &names, now);
+        if let Some(session) = session {
+            bump(
+                self.store.sessions.entry(session.to_string()).or_default(),
+                &names,
+                now,
+            );
+        }
+        self.save();
+    }
+
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let result = toml::to_string(&self.store)
+            .map_err(|e| e.to_string())
+            .and_then(|content| std::fs::write(path, content).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            debug!("Failed to save completion usage: {}", e);
+        }
+    }
+
+    /// Combined frecency of a candidate; 0 when it was never used
+    pub fn score(&self, value: &str, session: Option<&str>) -> f64 {
+        let now = chrono::Utc::now().timestamp();
+        let key = usage_key(value);
+        let lookup = |entries: &HashMap<String, UsageEntry>| {
+            entries
+                .get(&key)
+                .or_else(|| key.rsplit_once('.').and_then(|(_, name)| entries.get(name)))
+                .map_or(0.0, |entry| entry.score_at(now))
+        };
+        let session_score = session
+            .and_then(|session| self.store.sessions.get(session))
+            .map_or(0.0, lookup);
+        lookup(&self.store.global) + SESSION_WEIGHT * session_score
+    }
+
+    /// Most used candidates first; unused ones keep their relative order
+    pub fn rank(&self, suggestions: &mut Vec<Suggestion>, session: Option<&str>) {
+        let mut scored: Vec<(f64, Suggestion)> = suggestions
+            .drain(..)
+            .map(|suggestion| (self.score(&suggestion.value, session), suggestion))
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+        suggestions.extend(scored.into_iter().map(|(_, suggestion)| suggestion));
+    }
+}
+
+fn bump(entries: &mut HashMap<String, UsageEntry>, names: &[String], now: i64) {
+    for name in names {
+        let entry = entries.entry(name.clone()).or_insert(UsageEntry {
+            score: 0.0,
+            last_used: now,
+        });
+        *entry = UsageEntry {
+            score: entry.score_at(now) + 1.0,
+            last_used: now,
+        };
+    }
+    if entries.len() > MAX_ENTRIES {
+        let mut scores: Vec<f64> = entries.values().map(|e| e.score_at(now)).collect();
+        scores.sort_by(|a, b| b.total_cmp(a));
+        let cutoff = scores[MAX_ENTRIES - 1];
+        entries.retain(|_, entry| entry.score_at(now) >= cutoff);
+    }
+}
+
+/// Lowercased name without quotes or a trailing call, as completion values are matched
+fn usage_key(value: &str) -> String {
+    value
+        .trim()
+        .trim_end_matches("()")
+        .trim_end_matches('(')
+        .chars()
+        .filter(|c| !matches!(c, '"' | '`' | '[' | ']'))
+        .collect::<String>()
+        .to_lowercase()
+}
+
+/// Distinct names in `line`: the backslash command, identifiers and their
+/// dotted parts. String literals, comments and numbers are skipped.
+fn usage_names(line: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    let mut push = |name: String| {
+        if !name.is_empty() && !names.contains(&name) {
+            names.push(name);
+        }
+    };
+
+    let mut rest = line.trim_start();
+    if rest.starts_with('\\') {
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        push(rest[..end].to_string());
+        rest = &rest[end..];
+    }
+
+    let mut chars = rest.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        match c {
+            '\'' => {
+                while let Some((_, c)) = chars.next() {
+                    if c == '\'' && chars.next_if(|&(_, c)| c == '\'').is_none() {
+                        break;
+                    }
+                }
+            }
+            '-' if chars.next_if(|&(_, c)| c == '-').is_some() => {
+                for (_, c) in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            c if c.is_alphabetic() || c == '_' || c == '"' || c == '`' => {
+                let mut end = start + c.len_utf8();
+                let mut quote = matches!(c, '"' | '`').then_some(c);
+                while let Some(&(i, c)) = chars.peek() {
+                    let inside = match quote {
+                        Some(q) => {
+                            if c == q {
+                                quote = None;
+                            }
+                            true
+                        }
+                        None if matches!(c, '"' | '`') => {
+                            quote = Some(c);
+                            true
+                        }
+                        None => c.is_alphanumeric() || matches!(c, '_' | '$' | '.'),
+                    };
+                    if !inside {
+                        break;
+                    }
+                    end = i + c.len_utf8();
+                    chars.next();
+                }
+                let token = usage_key(&rest[start..end]);
+                if token.contains('.') {
+                    for part in token.split('.') {
+                        push(part.to_string());
+                    }
+                }
+                push(token);
+            }
+            c if c.is_ascii_digit() => {
+                while chars.next_if(|&(_, c)| c.is_alphanumeric()).is_some() {}
+            }
+            _ => {}
+        }
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn suggestion(value: &str) -> Suggestion {
+        Suggestion {
+            value: value.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_usage_names_skip_literals_and_numbers() {
+        assert_eq!(
+            usage_names(
+                "SELECT u.email FROM public.\"Users\" u WHERE name = 'it''s' -- users\nLIMIT 10"
+            ),
+            vec![
+                "select",
+                "u",
+                "email",
+                "u.email",
+                "from",
+                "public",
+                "users",
+                "public.users",
+                "where",
+                "name",
+                "limit"
+            ]
+        );
+        assert_eq!(usage_names("\\d orders"), vec!["\\d", "orders"]);
+    }
+
+    #[test]
+    fn test_rank_prefers_frequent_then_keeps_order() {
+        let mut usage = CompletionUsage::default();
+        usage.record("SELECT * FROM orders", None);
+        usage.record("SELECT * FROM orders", None);
+        usage.record("SELECT * FROM invoices", Some("abc"));
+
+        let mut suggestions = vec![
+            suggestion("customers"),
+            suggestion("invoices"),
+            suggestion("public.orders"),
+            suggestion("products"),
+        ];
+        usage.rank(&mut suggestions, None);
+        let order: Vec<_> = suggestions.iter().map(|s| s.value.as_str()).collect();
+        assert_eq!(
+            order,
+            ["public.orders", "invoices", "customers", "products"]
+        );
+
+        // The current connection's usage weighs more than the global one
+        usage.rank(&mut suggestions, Some("abc"));
+        assert_eq!(suggestions[0].value, "invoices");
+    }
+
+    #[test]
+    fn test_scores_decay_with_age() {
+        let entry = UsageEntry {
+            score: 4.0,
+            last_used: 0,
+        };
+        let half_life = (HALF_LIFE_DAYS * 86_400.0) as i64;
+        assert_eq!(entry.score_at(0), 4.0);
+        assert!((entry.score_at(half_life) - 2.0).abs() < 1e-9);
+    }
+}
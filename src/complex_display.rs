@@ -5,6 +5,7 @@
 //! PostgreSQL extension types with multiple display modes.
 
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::sync::{Arc, RwLock};
 
@@ -23,6 +24,8 @@ pub enum ComplexDataType {
     Map,
     /// Tuple of heterogeneous values (from ClickHouse)
     Tuple,
+    /// Raw bytes (PostgreSQL bytea, MySQL/SQLite BLOB)
+    Binary,
 }
 
 /// Display modes available for complex data types
@@ -67,6 +70,37 @@ impl ComplexDisplayMode {
     }
 }
 
+/// Text form used for PostGIS geometry and geography values
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum GeometryFormat {
+    /// Well-Known Text, e.g. `SRID=4326;POINT(2.35 48.85)`
+    #[default]
+    Wkt,
+    /// GeoJSON geometry object
+    GeoJson,
+}
+
+impl fmt::Display for GeometryFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GeometryFormat::Wkt => write!(f, "wkt"),
+            GeometryFormat::GeoJson => write!(f, "geojson"),
+        }
+    }
+}
+
+impl GeometryFormat {
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<GeometryFormat> {
+        match s.to_lowercase().as_str() {
+            "wkt" | "ewkt" => Some(GeometryFormat::Wkt),
+            "geojson" | "json" => Some(GeometryFormat::GeoJson),
+            _ => None,
+        }
+    }
+}
+
 /// Configuration for complex data display
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
@@ -91,6 +125,8 @@ pub struct ComplexDisplayConfig {
     pub full_show_numbers: bool,
     /// Whether to pretty-print JSON (false = compact, true = formatted)
     pub json_pretty_print: bool,
+    /// How PostGIS geometry/geography values are written out
+    pub geometry_format: GeometryFormat,
 }
 
 impl Default for ComplexDisplayConfig {
@@ -106,6 +142,7 @@ impl Default for ComplexDisplayConfig {
             max_width: 80,
             full_show_numbers: true,
             json_pretty_print: false,
+            geometry_format: GeometryFormat::Wkt,
         }
     }
 }
@@ -691,6 +728,258 @@ impl ComplexDataDisplay for ArrayDisplayAdapter {
     }
 }
 
+/// Bytes shown as hex before a binary value is cut short
+const BINARY_PREVIEW_BYTES: usize = 16;
+
+/// File signatures recognized in binary values, checked in order
+const BINARY_SIGNATURES: &[(&[u8], &str)] = &[
+    (b"\x89PNG\r\n\x1a\n", "PNG image"),
+    (b"\xff\xd8\xff", "JPEG image"),
+    (b"GIF87a", "GIF image"),
+    (b"GIF89a", "GIF image"),
+    (b"%PDF-", "PDF document"),
+    (b"PK\x03\x04", "ZIP archive"),
+    (b"\x1f\x8b", "gzip data"),
+    (b"BZh", "bzip2 data"),
+    (b"\x28\xb5\x2f\xfd", "zstd data"),
+    (b"\xfd7zXZ\x00", "xz data"),
+    (b"7z\xbc\xaf\x27\x1c", "7-Zip archive"),
+    (b"BM", "BMP image"),
+    (b"II*\x00", "TIFF image"),
+    (b"MM\x00*", "TIFF image"),
+    (b"SQLite format 3\x00", "SQLite database"),
+    (b"\x7fELF", "ELF binary"),
+    (b"PAR1", "Parquet file"),
+    (b"OggS", "Ogg media"),
+    (b"ID3", "MP3 audio"),
+    (b"fLaC", "FLAC audio"),
+];
+
+/// What kind of file `bytes` holds, from its leading magic bytes
+pub fn sniff_binary_type(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.len() >= 12 && &bytes[..4] == b"RIFF" {
+        return match &bytes[8..12] {
+            b"WEBP" => Some("WebP image"),
+            b"WAVE" => Some("WAV audio"),
+            b"AVI " => Some("AVI video"),
+            _ => None,
+        };
+    }
+    if bytes.len() >= 12 && &bytes[4..8] == b"ftyp" {
+        return Some("MP4 media");
+    }
+    BINARY_SIGNATURES
+        .iter()
+        .find(|(magic, _)| bytes.starts_with(magic))
+        .map(|(_, kind)| *kind)
+}
+
+/// Bytes of a `\\x`-prefixed lowercase hex cell, as the backends render
+/// bytea/BLOB values
+pub fn parse_hex_cell(value: &str) -> Option<Vec<u8>> {
+    let hex_digits = value.strip_prefix("\\x")?;
+    if hex_digits.is_empty()
+        || !hex_digits
+            .bytes()
+            .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+    {
+        return None;
+    }
+    hex::decode(hex_digits).ok()
+}
+
+/// Display for bytea/BLOB values: size, detected file type and a hex preview
+pub struct BinaryDisplayAdapter<'a> {
+    pub bytes: &'a [u8],
+}
+
+impl BinaryDisplayAdapter<'_> {
+    /// `PNG image, 24.1 kB`, or just the size when the type is unknown
+    fn description(&self) -> String {
+        let size = crate::format::format_bytes(self.bytes.len() as i64);
+        match sniff_binary_type(self.bytes) {
+            Some(kind) => format!("{kind}, {size}"),
+            None => size,
+        }
+    }
+}
+
+impl ComplexDataDisplay for BinaryDisplayAdapter<'_> {
+    fn metadata(&self) -> ComplexDataMetadata {
+        ComplexDataMetadata {
+            data_type: "binary".to_string(),
+            size: self.bytes.len(),
+            depth: None,
+            has_nested: false,
+            schema_info: sniff_binary_type(self.bytes).map(str::to_string),
+        }
+    }
+
+    fn format_full(&self, _config: &ComplexDisplayConfig) -> String {
+        format!("\\x{}", hex::encode(self.bytes))
+    }
+
+    fn format_truncated(&self, config: &ComplexDisplayConfig) -> String {
+        if self.bytes.len() <= BINARY_PREVIEW_BYTES {
+            return self.format_full(config);
+        }
+        let preview = format!("\\x{}…", hex::encode(&self.bytes[..BINARY_PREVIEW_BYTES]));
+        if config.show_dimensions {
+            format!("{preview} ({})", self.description())
+        } else {
+            preview
+        }
+    }
+
+    fn format_summary(&self, _config: &ComplexDisplayConfig) -> String {
+        format!(
+            "Binary: {} | Size: {} bytes",
+            sniff_binary_type(self.bytes).unwrap_or("unknown type"),
+            self.bytes.len()
+        )
+    }
+
+    fn format_viz(&self, config: &ComplexDisplayConfig) -> String {
+        // xxd-style dump of the first lines, 16 bytes per line
+        let max_lines = (config.viz_width / 10).clamp(2, 8);
+        let mut lines = vec![format!("Binary ({}):", self.description())];
+        for (i, chunk) in self.bytes.chunks(16).take(max_lines).enumerate() {
+            let hex: Vec<String> = chunk.iter().map(|b| format!("{b:02x}")).collect();
+            let ascii: String = chunk
+                .iter()
+                .map(|&b| {
+                    if b.is_ascii_graphic() || b == b' ' {
+                        b as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            lines.push(format!("  {:08x}  {:<47}  {ascii}", i * 16, hex.join(" ")));
+        }
+        let shown = max_lines * 16;
+        if self.bytes.len() > shown {
+            lines.push(format!("  ... {} more bytes", self.bytes.len() - shown));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Whether `type_name`, as the driver reports it, holds raw bytes
+fn is_binary_type(type_name: &str) -> bool {
+    let lower = type_name.to_ascii_lowercase();
+    lower == "bytea" || lower.ends_with("blob") || lower.ends_with("binary")
+}
+
+/// Names of the bytea/BLOB columns of a result, from its column types
+pub fn binary_columns(header: Option<&Vec<String>>, column_types: &[String]) -> HashSet<String> {
+    header
+        .into_iter()
+        .flatten()
+        .zip(column_types)
+        .filter(|(_, type_name)| is_binary_type(type_name))
+        .map(|(name, _)| name.clone())
+        .collect()
+}
+
+/// `data` with the cells of `columns` shortened to their binary display
+/// form under `config`; `None` when nothing changes
+pub fn render_binary_columns(
+    data: &[Vec<String>],
+    columns: &HashSet<String>,
+    config: &ComplexDisplayConfig,
+) -> Option<Vec<Vec<String>>> {
+    let indexes = column_indexes(data.first()?, columns);
+    if indexes.is_empty() {
+        return None;
+    }
+    let mut rendered = data.to_vec();
+    for row in rendered.iter_mut().skip(1) {
+        for &index in &indexes {
+            if let Some(cell) = row.get_mut(index)
+                && let Some(bytes) = parse_hex_cell(cell)
+            {
+                *cell = BinaryDisplayAdapter { bytes: &bytes }.format(config);
+            }
+        }
+    }
+    Some(rendered)
+}
+
+fn column_indexes(header: &[String], columns: &HashSet<String>) -> Vec<usize> {
+    header
+        .iter()
+        .enumerate()
+        .filter(|(_, name)| columns.contains(*name))
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Raw bytes of the binary cells of the last result, for `\\saveblob`
+#[derive(Debug, Default)]
+pub struct BinaryCells {
+    header: Vec<String>,
+    /// Keyed by data row and column, both from 0
+    cells: HashMap<(usize, usize), Vec<u8>>,
+}
+
+impl BinaryCells {
+    /// The bytes of the hex cells of `columns` in `rows` (header row first)
+    pub fn extract(rows: &[Vec<String>], columns: &HashSet<String>) -> Self {
+        let Some((header, data)) = rows.split_first() else {
+            return Self::default();
+        };
+        let indexes = column_indexes(header, columns);
+        let mut cells = HashMap::new();
+        for (row_index, row) in data.iter().enumerate() {
+            for &column_index in &indexes {
+                if let Some(bytes) = row.get(column_index).and_then(|cell| parse_hex_cell(cell)) {
+                    cells.insert((row_index, column_index), bytes);
+                }
+            }
+        }
+        Self {
+            header: header.clone(),
+            cells,
+        }
+    }
+
+    /// Bytes at data row `row` (from 1) and `column`, a name or a position from 1
+    pub fn get(&self, row: usize, column: &str) -> Result<&[u8], String> {
+        let column_index = match column.parse::<usize>() {
+            Ok(position) if position >= 1 && position <= self.header.len() => position - 1,
+            Ok(position) => {
+                return Err(format!(
+                    "Column {position} is out of range (the last result has {} columns)",
+                    self.header.len()
+                ));
+            }
+            Err(_) => self
+                .header
+                .iter()
+                .position(|name| name == column)
+                .or_else(|| {
+                    self.header
+                        .iter()
+                        .position(|name| name.eq_ignore_ascii_case(column))
+                })
+                .ok_or_else(|| format!("The last result has no column named '{column}'"))?,
+        };
+        if row == 0 {
+            return Err("Rows are numbered from 1".to_string());
+        }
+        self.cells
+            .get(&(row - 1, column_index))
+            .map(Vec::as_slice)
+            .ok_or_else(|| {
+                format!(
+                    "Row {row}, column '{}' of the last result is not a binary value",
+                    self.header[column_index]
+                )
+            })
+    }
+}
+
 /// Largest prefix of `s` that fits in `max_bytes` and ends on a char
 /// boundary. Raw byte slicing (`&s[..n]`) panics when `n` lands inside a
 /// multibyte codepoint — accents/CJK/emoji in displayed values crashed the
@@ -866,6 +1155,7 @@ mod tests {
             max_width: 120,
             full_show_numbers: false,
             json_pretty_print: true,
+            geometry_format: GeometryFormat::GeoJson,
         };
 
         set_global_complex_config(config.clone());
@@ -900,6 +1190,81 @@ mod tests {
         let _viz = adapter.format_viz(&config);
     }
 
+    #[test]
+    fn test_binary_display_adapter() {
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        png.resize(2048, 0);
+        let adapter = BinaryDisplayAdapter { bytes: &png };
+        let config = ComplexDisplayConfig::default();
+
+        assert_eq!(adapter.metadata().schema_info.as_deref(), Some("PNG image"));
+        assert_eq!(
+            adapter.format_truncated(&config),
+            "\\x89504e470d0a1a0a0000000000000000… (PNG image, 2.0 kB)"
+        );
+        assert_eq!(
+            adapter.format_summary(&config),
+            "Binary: PNG image | Size: 2048 bytes"
+        );
+        assert!(adapter.format_viz(&config).contains(".PNG...."));
+        assert_eq!(adapter.format_full(&config).len(), 2 + 2 * 2048);
+
+        // Short values stay whole
+        let short = BinaryDisplayAdapter {
+            bytes: &[0xde, 0xad],
+        };
+        assert_eq!(short.format_truncated(&config), "\\xdead");
+
+        assert_eq!(sniff_binary_type(b"%PDF-1.7"), Some("PDF document"));
+        assert_eq!(
+            sniff_binary_type(b"RIFF\0\0\0\0WEBPVP8 "),
+            Some("WebP image")
+        );
+        assert_eq!(sniff_binary_type(b"plain"), None);
+        assert_eq!(
+            parse_hex_cell("\\xdeadbeef"),
+            Some(vec![0xde, 0xad, 0xbe, 0xef])
+        );
+        assert_eq!(parse_hex_cell("\\xDEADBEEF"), None);
+        assert_eq!(parse_hex_cell("\\xabc"), None);
+        assert_eq!(parse_hex_cell("deadbeef"), None);
+    }
+
+    #[test]
+    fn test_binary_cells_keep_raw_bytes() {
+        let long_hex = format!("\\x{}", "ab".repeat(40));
+        let rows = vec![
+            vec!["id".to_string(), "Payload".to_string(), "note".to_string()],
+            vec!["1".to_string(), long_hex.clone(), long_hex.clone()],
+            vec!["2".to_string(), "text".to_string(), "\\xab".to_string()],
+        ];
+        let columns = binary_columns(
+            rows.first(),
+            &["int4".to_string(), "BYTEA".to_string(), "text".to_string()],
+        );
+        assert_eq!(columns, HashSet::from(["Payload".to_string()]));
+        let cells = BinaryCells::extract(&rows, &columns);
+
+        // Only the display is shortened, and only in binary columns
+        let shown =
+            render_binary_columns(&rows, &columns, &ComplexDisplayConfig::default()).unwrap();
+        assert!(shown[1][1].ends_with("… (40 bytes)"));
+        assert_eq!(shown[1][2], long_hex);
+        assert_eq!(rows[1][1], long_hex);
+        assert!(cells.get(1, "note").unwrap_err().contains("not a binary"));
+        assert_eq!(cells.get(1, "payload").unwrap(), &[0xab; 40][..]);
+        assert_eq!(cells.get(1, "2").unwrap().len(), 40);
+        assert!(
+            cells
+                .get(2, "payload")
+                .unwrap_err()
+                .contains("not a binary")
+        );
+        assert!(cells.get(1, "4").unwrap_err().contains("out of range"));
+        assert!(cells.get(1, "missing").is_err());
+        assert!(cells.get(0, "payload").is_err());
+    }
+
     #[test]
     fn test_truncate_str_bytes_is_char_safe() {
         // ASCII: plain byte semantics
@@ -0,0 +1,198 @@
+//! Exact row counts without editing the query by hand (`\count`)
+//!
+//! A query is wrapped in `SELECT COUNT(*) FROM (...)` after dropping its
+//! top-level ORDER BY, LIMIT, OFFSET and FETCH clauses, which only slow the
+//! count down or cut it short. Clauses are found on the dialect parser's
+//! tokens, so ones inside subqueries, window definitions, strings and
+//! comments are kept.
+
+use crate::database::{DatabaseType, DatabaseTypeExt};
+use crate::db::Database;
+use crate::sql_parser::TokenType;
+use crate::sql_parser_trait::SqlParserFactory;
+
+/// Whether `target` (the argument of `\count`) is a query rather than a table
+pub fn is_query(target: &str) -> bool {
+    let first = target
+        .trim_start_matches(|c: char| c.is_whitespace() || c == '(')
+        .split(|c: char| !c.is_alphanumeric())
+        .next()
+        .unwrap_or_default();
+    first.eq_ignore_ascii_case("SELECT") || first.eq_ignore_ascii_case("WITH")
+}
+
+/// `sql` without its top-level ORDER BY, LIMIT, OFFSET and FETCH and its
+/// trailing semicolon; an error when it is not a SELECT
+pub fn strip_ordering_and_limit(sql: &str, database_type: DatabaseType) -> Result<String, String> {
+    let uncommented = crate::sql_buffer::strip_comments(sql);
+    let tokens: Vec<_> = SqlParserFactory::create_parser(database_type)
+        .parse_at_cursor(&uncommented, uncommented.len())
+        .base_context
+        .tokens
+        .into_iter()
+        .filter(|token| token.token_type != TokenType::Whitespace)
+        .collect();
+    if !is_query(&uncommented) {
+        return Err("\\count only counts SELECT queries".to_string());
+    }
+
+    let upper = |index: usize| {
+        tokens
+            .get(index)
+            .map(|token| token.value.to_uppercase())
+            .unwrap_or_default()
+    };
+    let mut depth = 0usize;
+    let mut cut = uncommented.len();
+    for (index, token) in tokens.iter().enumerate() {
+        match token.value.as_str() {
+            "(" => depth += 1,
+            ")" => depth = depth.saturating_sub(1),
+            _ if depth > 0 => {}
+            _ => {
+                let clause = match upper(index).as_str() {
+                    "ORDER" => upper(index + 1) == "BY",
+                    // ClickHouse's `LIMIT n BY column` limits rows per group
+                    "LIMIT" => {
+                        let mut next = index + 1;
+                        while matches!(
+                            tokens.get(next).map(|t| &t.token_type),
+                            Some(TokenType::Literal)
+                        ) || upper(next) == ","
+                        {
+                            next += 1;
+                        }
+                        upper(next) != "BY"
+                    }
+                    "OFFSET" | "FETCH" => true,
+                    _ => false,
+                };
+                if clause {
+                    cut = token.start;
+                    break;
+                }
+            }
+        }
+    }
+
+    Ok(sql[..cut]
+        .trim_end()
+        .trim_end_matches(';')
+        .trim_end()
+        .to_string())
+}
+
+/// Statement counting the rows `sql` returns
+pub fn count_rows_of_query(sql: &str, database_type: DatabaseType) -> Result<String, String> {
+    let query = strip_ordering_and_limit(sql, database_type)?;
+    Ok(format!("SELECT COUNT(*) FROM ({query}) AS dbcrust_count"))
+}
+
+/// Statement counting the rows of `table`, named as typed
+pub fn count_rows_of_table(table: &str) -> String {
+    format!(
+        "SELECT COUNT(*) FROM {}",
+        table.trim().trim_end_matches(';')
+    )
+}
+
+/// `\count`: the exact number of rows of `target`, a table or a query, or
+/// of the last query run when `target` is `None`
+pub async fn run(db: &Database, target: Option<&str>) -> Result<String, String> {
+    let database_type = db.get_database_type();
+    if matches!(
+        database_type,
+        DatabaseType::MongoDB | DatabaseType::Elasticsearch
+    ) {
+        return Err(format!(
+            "\\count is only available for SQL databases. Current database: {}",
+            database_type.display_name()
+        ));
+    }
+    let (sql, label) = match target {
+        Some(table) if !is_query(table) => (count_rows_of_table(table), table.trim().to_string()),
+        Some(query) => (
+            count_rows_of_query(query, database_type)?,
+            "the query".to_string(),
+        ),
+        None => {
+            let Some(last) = db.last_query() else {
+                return Err("No query has been run yet. Use \\count <table>".to_string());
+            };
+            (
+                count_rows_of_query(last, database_type)?,
+                "the last query".to_string(),
+            )
+        }
+    };
+    let client = db
+        .get_database_client()
+        .ok_or("Not connected to a database")?;
+
+    let count = client
+        .execute_query(&sql)
+        .await
+        .map_err(|e| format!("Failed to count the rows: {e}"))?
+        .get(1)
+        .and_then(|row| row.first())
+        .cloned()
+        .unwrap_or_else(|| "0".to_string());
+    let count = count
+        .trim()
+        .parse::<i64>()
+        .map_or(count.clone(), crate::query_progress::group_digits);
+    Ok(format!("{count} row(s) in {label}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_ordering_and_limit() {
+        let strip = |sql: &str| strip_ordering_and_limit(sql, DatabaseType::PostgreSQL).unwrap();
+        assert_eq!(
+            strip("SELECT * FROM users WHERE active ORDER BY created_at DESC LIMIT 50;"),
+            "SELECT * FROM users WHERE active"
+        );
+        assert_eq!(
+            strip("SELECT id FROM orders OFFSET 10 ROWS FETCH FIRST 5 ROWS ONLY"),
+            "SELECT id FROM orders"
+        );
+        // Nested clauses, strings and comments stay
+        assert_eq!(
+            strip(
+                "WITH recent AS (SELECT * FROM orders ORDER BY id DESC LIMIT 10)\n\
+                 SELECT rank() OVER (ORDER BY total), 'limit 3' -- order by x\n\
+                 FROM recent"
+            ),
+            "WITH recent AS (SELECT * FROM orders ORDER BY id DESC LIMIT 10)\n\
+             SELECT rank() OVER (ORDER BY total), 'limit 3' -- order by x\n\
+             FROM recent"
+        );
+        assert_eq!(
+            strip_ordering_and_limit(
+                "SELECT * FROM events LIMIT 1 BY user_id LIMIT 100",
+                DatabaseType::ClickHouse
+            )
+            .unwrap(),
+            "SELECT * FROM events LIMIT 1 BY user_id"
+        );
+        assert!(strip_ordering_and_limit("DELETE FROM users", DatabaseType::PostgreSQL).is_err());
+    }
+
+    #[test]
+    fn test_count_statements() {
+        assert_eq!(
+            count_rows_of_query("select * from users order by id", DatabaseType::MySQL).unwrap(),
+            "SELECT COUNT(*) FROM (select * from users) AS dbcrust_count"
+        );
+        assert_eq!(
+            count_rows_of_table("public.users"),
+            "SELECT COUNT(*) FROM public.users"
+        );
+        assert!(is_query("  (SELECT 1)"));
+        assert!(is_query("with t as (select 1) select * from t"));
+        assert!(!is_query("selection"));
+    }
+}
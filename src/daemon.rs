@@ -0,0 +1,762 @@
+//! Warm-connection daemon for one-shot `-c` invocations
+//!
+//! `dbcrust daemon` listens on a Unix socket in the config directory and keeps
+//! one connected [`CliCore`](crate::cli_core::CliCore) per target. A later
+//! `dbcrust <url> -c ...` finds the socket, hands its commands to the daemon and
+//! prints the captured output, so SSH tunnels, Vault leases and TLS handshakes are
+//! paid once instead of on every invocation. Connections idle for longer than the
+//! idle timeout are closed, and the daemon exits once it has received no request
+//! for that long.
+//!
+//! The wire protocol is one JSON message per line: the client writes a
+//! [`DaemonRequest`], the daemon answers with a [`DaemonResponse`] and closes the
+//! stream.
+
+use crate::cli::Args;
+use crate::config::Config;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use tracing::debug;
+
+pub use crate::cli::DEFAULT_DAEMON_IDLE_TIMEOUT_SECS as DEFAULT_IDLE_TIMEOUT_SECS;
+
+const SOCKET_FILE_NAME: &str = "daemon.sock";
+
+#[derive(Error, Debug)]
+pub enum DaemonError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Protocol error: {0}")]
+    Protocol(String),
+    #[error("Configuration error: {0}")]
+    Config(String),
+    #[error("No dbcrust daemon is running")]
+    NotRunning,
+    #[error("A dbcrust daemon is already running (pid {0})")]
+    AlreadyRunning(u32),
+    #[error("{0}")]
+    Unsupported(String),
+}
+
+/// Message sent by a client
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DaemonRequest {
+    Execute {
+        connection_url: String,
+        ssh_tunnel: Option<String>,
+        /// Client working directory, so relative file targets resolve as they would locally
+        working_dir: PathBuf,
+        commands: Vec<String>,
+    },
+    Status,
+    Stop,
+}
+
+/// Message sent back by the daemon
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DaemonResponse {
+    Executed {
+        stdout: String,
+        stderr: String,
+        exit_code: i32,
+    },
+    /// The daemon could not open the connection; the client runs the commands itself
+    ConnectFailed {
+        message: String,
+    },
+    Status(DaemonStatus),
+    Stopped,
+    Error {
+        message: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DaemonStatus {
+    pub pid: u32,
+    pub uptime_secs: u64,
+    pub idle_timeout_secs: u64,
+    pub connections: Vec<PooledConnectionStatus>,
+    /// `host:port` of the metrics endpoint, when `[metrics]` is enabled
+    #[serde(default)]
+    pub metrics_address: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PooledConnectionStatus {
+    /// Connection URL with the password removed
+    pub target: String,
+    pub uses: u64,
+    pub idle_secs: u64,
+}
+
+impl DaemonStatus {
+    pub fn render(&self) -> String {
+        let mut out = format!(
+            "dbcrust daemon running (pid {}, up {}s, idle timeout {}s)\n",
+            self.pid, self.uptime_secs, self.idle_timeout_secs
+        );
+        if let Some(address) = &self.metrics_address {
+            out.push_str(&format!("Metrics on http://{address}/metrics\n"));
+        }
+        if self.connections.is_empty() {
+            out.push_str("No open connections.");
+        } else {
+            out.push_str(&format!("{} open connection(s):", self.connections.len()));
+            for conn in &self.connections {
+                out.push_str(&format!(
+                    "\n  {}  ({} use{}, idle {}s)",
+                    conn.target,
+                    conn.uses,
+                    if conn.uses == 1 { "" } else { "s" },
+                    conn.idle_secs
+                ));
+            }
+        }
+        out
+    }
+}
+
+/// Location of the daemon socket (`~/.config/dbcrust/daemon.sock`)
+pub fn socket_path() -> Result<PathBuf, DaemonError> {
+    Config::get_config_dir()
+        .map(|dir| dir.join(SOCKET_FILE_NAME))
+        .map_err(|e| DaemonError::Config(e.to_string()))
+}
+
+/// Identify a pooled connection. Local file targets are qualified with the
+/// client's working directory, since `./data.csv` differs between directories.
+pub fn connection_key(
+    connection_url: &str,
+    ssh_tunnel: Option<&str>,
+    working_dir: &Path,
+) -> String {
+    let is_local_file = match connection_url.split_once("://") {
+        Some((scheme, _)) => {
+            use crate::database::{DatabaseType, DatabaseTypeExt};
+            scheme == "file"
+                || DatabaseType::from_scheme(scheme).is_ok_and(|db_type| db_type.is_file_based())
+        }
+        None => true,
+    };
+
+    let mut key = connection_url.to_string();
+    if let Some(tunnel) = ssh_tunnel {
+        key.push_str(&format!(" via {tunnel}"));
+    }
+    if is_local_file {
+        key.push_str(&format!(" in {}", working_dir.display()));
+    }
+    key
+}
+
+/// Run `-c` commands through a running daemon. Returns the exit code, or `None`
+/// when no daemon is reachable or it could not connect — the caller then runs
+/// the commands in-process.
+pub async fn try_execute(args: &Args, config: &Config) -> Option<i32> {
+    let connection_url = args.connection_url.clone()?;
+    // The daemon's config may predate an alias added since it started
+    let connection_url = config
+        .resolve_alias(&connection_url)
+        .map(str::to_string)
+        .unwrap_or(connection_url);
+    // `\export <format> - ...` streams rows to this process's stdout, not the daemon's
+    if crate::export::exports_to_stdout(&args.command) {
+        return None;
+    }
+    let path = socket_path().ok()?;
+    if !path.exists() {
+        return None;
+    }
+
+    let request = DaemonRequest::Execute {
+        connection_url,
+        ssh_tunnel: args.ssh_tunnel.clone(),
+        working_dir: std::env::current_dir().ok()?,
+        commands: args.command.clone(),
+    };
+    match imp::send(&path, &request).await {
+        Ok(DaemonResponse::Executed {
+            stdout,
+            stderr,
+            exit_code,
+        }) => {
+            if !stdout.is_empty() {
+                if let Err(e) = crate::cli_core::CliCore::page_or_print(&stdout, config) {
+                    debug!("Failed to print daemon output: {e}");
+                }
+            }
+            eprint!("{stderr}");
+            Some(exit_code)
+        }
+        Ok(DaemonResponse::ConnectFailed { message }) => {
+            debug!("Daemon could not connect, running locally: {message}");
+            None
+        }
+        Ok(other) => {
+            debug!("Unexpected daemon response, running locally: {other:?}");
+            None
+        }
+        Err(e) => {
+            debug!("Daemon unreachable, running locally: {e}");
+            None
+        }
+    }
+}
+
+/// Query the running daemon's status
+pub async fn status() -> Result<DaemonStatus, DaemonError> {
+    match imp::send(&socket_path()?, &DaemonRequest::Status).await? {
+        DaemonResponse::Status(status) => Ok(status),
+        other => Err(DaemonError::Protocol(format!(
+            "unexpected response: {other:?}"
+        ))),
+    }
+}
+
+/// Ask the running daemon to close its connections and exit
+pub async fn stop() -> Result<String, DaemonError> {
+    match imp::send(&socket_path()?, &DaemonRequest::Stop).await? {
+        DaemonResponse::Stopped => Ok("dbcrust daemon stopped.".to_string()),
+        other => Err(DaemonError::Protocol(format!(
+            "unexpected response: {other:?}"
+        ))),
+    }
+}
+
+/// Start the daemon as a detached background process
+pub async fn start(idle_timeout_secs: u64) -> Result<String, DaemonError> {
+    imp::start(idle_timeout_secs).await
+}
+
+/// Run the daemon in the current process until stopped or idle. Serves the
+/// metrics endpoint alongside when `metrics.enabled`.
+pub async fn run(
+    idle_timeout_secs: u64,
+    metrics: &crate::metrics::MetricsConfig,
+) -> Result<(), DaemonError> {
+    imp::run(idle_timeout_secs, metrics).await
+}
+
+#[cfg(unix)]
+mod imp {
+    use super::*;
+    use crate::cli_core::{CliCore, CommandModeOutput};
+    use std::cell::Cell;
+    use std::collections::HashMap;
+    use std::os::unix::fs::PermissionsExt;
+    use std::rc::Rc;
+    use std::time::{Duration, Instant};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{UnixListener, UnixStream};
+    use tokio::sync::Notify;
+    use tracing::{info, warn};
+
+    /// How often idle connections are swept
+    const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+    /// How long `start` waits for the spawned daemon to accept connections
+    const STARTUP_TIMEOUT: Duration = Duration::from_secs(5);
+
+    struct PooledConnection {
+        core: CliCore,
+        target: String,
+        last_used: Instant,
+        uses: u64,
+    }
+
+    async fn write_message<W, T>(writer: &mut W, message: &T) -> Result<(), DaemonError>
+    where
+        W: AsyncWriteExt + Unpin,
+        T: Serialize,
+    {
+        let mut line =
+            serde_json::to_string(message).map_err(|e| DaemonError::Protocol(e.to_string()))?;
+        line.push('\n');
+        writer.write_all(line.as_bytes()).await?;
+        writer.flush().await?;
+        Ok(())
+    }
+
+    async fn read_message<R, T>(reader: R) -> Result<Option<T>, DaemonError>
+    where
+        R: tokio::io::AsyncRead + Unpin,
+        T: for<'de> Deserialize<'de>,
+    {
+        match BufReader::new(reader).lines().next_line().await? {
+            Some(line) => serde_json::from_str(&line)
+                .map(Some)
+                .map_err(|e| DaemonError::Protocol(e.to_string())),
+            None => Ok(None),
+        }
+    }
+
+    pub(super) async fn send(
+        path: &Path,
+        request: &DaemonRequest,
+    ) -> Result<DaemonResponse, DaemonError> {
+        let stream = UnixStream::connect(path).await.map_err(|e| {
+            if matches!(
+                e.kind(),
+                std::io::ErrorKind::NotFound | std::io::ErrorKind::ConnectionRefused
+            ) {
+                DaemonError::NotRunning
+            } else {
+                DaemonError::Io(e)
+            }
+        })?;
+        let (reader, mut writer) = stream.into_split();
+        write_message(&mut writer, request).await?;
+        read_message(reader)
+            .await?
+            .ok_or_else(|| DaemonError::Protocol("daemon closed the connection".to_string()))
+    }
+
+    pub(super) async fn start(idle_timeout_secs: u64) -> Result<String, DaemonError> {
+        use std::os::unix::process::CommandExt;
+
+        let path = socket_path()?;
+        if let Ok(DaemonResponse::Status(status)) = send(&path, &DaemonRequest::Status).await {
+            return Err(DaemonError::AlreadyRunning(status.pid));
+        }
+
+        let exe = std::env::current_exe()?;
+        let is_dbcrust = exe
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .is_some_and(|stem| stem.starts_with("dbcrust"));
+        if !is_dbcrust {
+            return Err(DaemonError::Unsupported(format!(
+                "cannot spawn the daemon from {}; run `dbcrust daemon run` instead",
+                exe.display()
+            )));
+        }
+
+        let child = std::process::Command::new(&exe)
+            .args([
+                "daemon",
+                "run",
+                "--idle-timeout",
+                &idle_timeout_secs.to_string(),
+            ])
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            // New process group: Ctrl-C in the launching shell must not reach the daemon
+            .process_group(0)
+            .spawn()?;
+
+        let deadline = Instant::now() + STARTUP_TIMEOUT;
+        while Instant::now() < deadline {
+            if let Ok(DaemonResponse::Status(_)) = send(&path, &DaemonRequest::Status).await {
+                return Ok(format!(
+                    "dbcrust daemon started (pid {}, idle timeout {idle_timeout_secs}s). \
+                     `dbcrust <url> -c ...` now reuses its connections.",
+                    child.id()
+                ));
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        Err(DaemonError::Protocol(
+            "daemon did not start listening in time; try `dbcrust daemon run` to see errors"
+                .to_string(),
+        ))
+    }
+
+    /// State shared by the accept loop and the tasks serving clients
+    struct Shared {
+        /// Connections by [`connection_key`], each locked while it runs a request
+        pool: tokio::sync::Mutex<HashMap<String, Rc<tokio::sync::Mutex<PooledConnection>>>>,
+        /// Working directory of the process. Requests from the same directory
+        /// run side by side; one from another directory waits for them, since
+        /// the directory is process-wide.
+        working_dir: tokio::sync::RwLock<Option<PathBuf>>,
+        started: Instant,
+        idle_timeout_secs: u64,
+        metrics_address: Option<String>,
+        /// Requests being served; the daemon never idles out under them
+        in_flight: Cell<usize>,
+        last_activity: Cell<Instant>,
+        stop: Notify,
+    }
+
+    impl Shared {
+        fn touch(&self) {
+            self.last_activity.set(Instant::now());
+        }
+
+        fn idle_for(&self) -> Duration {
+            if self.in_flight.get() > 0 {
+                return Duration::ZERO;
+            }
+            self.last_activity.get().elapsed()
+        }
+    }
+
+    /// Bind the socket in a fresh 0700 directory and move it into place, so
+    /// it is never reachable by other users before its mode is set
+    fn bind_private(path: &Path) -> Result<UnixListener, DaemonError> {
+        let parent = path.parent().ok_or_else(|| {
+            DaemonError::Config(format!("invalid socket path {}", path.display()))
+        })?;
+        std::fs::create_dir_all(parent)?;
+        let staging = tempfile::Builder::new()
+            .prefix(".daemon-")
+            .permissions(std::fs::Permissions::from_mode(0o700))
+            .tempdir_in(parent)?;
+        let staged = staging.path().join(SOCKET_FILE_NAME);
+        let listener = UnixListener::bind(&staged)?;
+        // Requests carry connection URLs, possibly with passwords
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o600))?;
+        std::fs::rename(&staged, path)?;
+        Ok(listener)
+    }
+
+    pub(super) async fn run(
+        idle_timeout_secs: u64,
+        metrics: &crate::metrics::MetricsConfig,
+    ) -> Result<(), DaemonError> {
+        let path = socket_path()?;
+        if let Ok(DaemonResponse::Status(status)) = send(&path, &DaemonRequest::Status).await {
+            return Err(DaemonError::AlreadyRunning(status.pid));
+        }
+        // Nobody answered: whatever is at the path is a stale socket
+        let _ = std::fs::remove_file(&path);
+
+        let listener = bind_private(&path)?;
+        info!(
+            "dbcrust daemon listening on {} (idle timeout {idle_timeout_secs}s)",
+            path.display()
+        );
+
+        let metrics_server = if metrics.enabled {
+            Some(crate::metrics::spawn_server(metrics).await.map_err(|e| {
+                DaemonError::Config(format!(
+                    "cannot serve metrics on {}: {e}",
+                    metrics.address()
+                ))
+            })?)
+        } else {
+            None
+        };
+
+        let idle_timeout = Duration::from_secs(idle_timeout_secs);
+        let shared = Rc::new(Shared {
+            pool: tokio::sync::Mutex::new(HashMap::new()),
+            working_dir: tokio::sync::RwLock::new(None),
+            started: Instant::now(),
+            idle_timeout_secs,
+            metrics_address: metrics.enabled.then(|| metrics.address()),
+            in_flight: Cell::new(0),
+            last_activity: Cell::new(Instant::now()),
+            stop: Notify::new(),
+        });
+
+        // CliCore is not Send: clients are served on tasks of this thread,
+        // interleaving whenever one waits on its database or socket
+        let local = tokio::task::LocalSet::new();
+        local
+            .run_until(async {
+                loop {
+                    tokio::select! {
+                        accepted = listener.accept() => match accepted {
+                            Ok((stream, _)) => {
+                                shared.touch();
+                                shared.in_flight.set(shared.in_flight.get() + 1);
+                                let shared = Rc::clone(&shared);
+                                // Each client on its own task, so a slow query or a
+                                // slow client does not hold up the others
+                                tokio::task::spawn_local(async move {
+                                    if let Err(e) = serve_client(stream, &shared).await {
+                                        warn!("Daemon request failed: {e}");
+                                    }
+                                    shared.touch();
+                                    shared.in_flight.set(shared.in_flight.get() - 1);
+                                });
+                            }
+                            Err(e) => warn!("Daemon failed to accept a client: {e}"),
+                        },
+                        _ = shared.stop.notified() => break,
+                        _ = tokio::time::sleep(IDLE_CHECK_INTERVAL.min(idle_timeout)) => {}
+                    }
+
+                    {
+                        let mut pool = shared.pool.lock().await;
+                        pool.retain(|_, conn| {
+                            // A connection serving a request is in use
+                            let Ok(conn) = conn.try_lock() else {
+                                return true;
+                            };
+                            let keep = conn.last_used.elapsed() < idle_timeout;
+                            if !keep {
+                                info!("Closing idle daemon connection: {}", conn.target);
+                            }
+                            keep
+                        });
+                        if metrics_server.is_some() {
+                            publish_metrics(&pool);
+                        }
+                    }
+
+                    if shared.idle_for() >= idle_timeout {
+                        info!("dbcrust daemon idle for {idle_timeout_secs}s, exiting");
+                        break;
+                    }
+                }
+            })
+            .await;
+
+        drop(listener);
+        let _ = std::fs::remove_file(&path);
+        if let Some(server) = metrics_server {
+            server.abort();
+        }
+        Ok(())
+    }
+
+    /// Report the pool's connections and tunnels to the metrics endpoint;
+    /// connections busy with a request keep their last reading
+    fn publish_metrics(pool: &HashMap<String, Rc<tokio::sync::Mutex<PooledConnection>>>) {
+        let connections = pool
+            .values()
+            .filter_map(|conn| conn.try_lock().ok())
+            .map(|conn| crate::metrics::ConnectionGauge {
+                target: conn.target.clone(),
+                tunnel_up: conn
+                    .core
+                    .database
+                    .as_ref()
+                    .and_then(|database| database.get_ssh_tunnel())
+                    .map(|tunnel| tunnel.is_active()),
+            })
+            .collect();
+        crate::metrics::set_connections(connections);
+    }
+
+    /// Handle one client
+    async fn serve_client(stream: UnixStream, shared: &Shared) -> Result<(), DaemonError> {
+        let (reader, mut writer) = stream.into_split();
+        let Some(request) = read_message::<_, DaemonRequest>(reader).await? else {
+            return Ok(());
+        };
+
+        let response = match request {
+            DaemonRequest::Execute {
+                connection_url,
+                ssh_tunnel,
+                working_dir,
+                commands,
+            } => execute(shared, connection_url, ssh_tunnel, working_dir, commands).await,
+            DaemonRequest::Status => {
+                let pool = shared.pool.lock().await;
+                let connections = pool
+                    .values()
+                    .filter_map(|conn| match conn.try_lock() {
+                        Ok(conn) => Some(PooledConnectionStatus {
+                            target: conn.target.clone(),
+                            uses: conn.uses,
+                            idle_secs: conn.last_used.elapsed().as_secs(),
+                        }),
+                        // Running a request right now
+                        Err(_) => None,
+                    })
+                    .collect();
+                DaemonResponse::Status(DaemonStatus {
+                    pid: std::process::id(),
+                    uptime_secs: shared.started.elapsed().as_secs(),
+                    idle_timeout_secs: shared.idle_timeout_secs,
+                    connections,
+                    metrics_address: shared.metrics_address.clone(),
+                })
+            }
+            DaemonRequest::Stop => DaemonResponse::Stopped,
+        };
+
+        write_message(&mut writer, &response).await?;
+        if response == DaemonResponse::Stopped {
+            // Answered first: the accept loop drops unfinished clients
+            shared.stop.notify_one();
+        }
+        Ok(())
+    }
+
+    async fn execute(
+        shared: &Shared,
+        connection_url: String,
+        ssh_tunnel: Option<String>,
+        working_dir: PathBuf,
+        commands: Vec<String>,
+    ) -> DaemonResponse {
+        let _in_dir = {
+            let current = shared.working_dir.read().await;
+            if current.as_deref() == Some(working_dir.as_path()) {
+                current
+            } else {
+                drop(current);
+                let mut current = shared.working_dir.write().await;
+                if let Err(e) = std::env::set_current_dir(&working_dir) {
+                    return DaemonResponse::ConnectFailed {
+                        message: format!("cannot enter {}: {e}", working_dir.display()),
+                    };
+                }
+                *current = Some(working_dir.clone());
+                current.downgrade()
+            }
+        };
+
+        let key = connection_key(&connection_url, ssh_tunnel.as_deref(), &working_dir);
+        let pooled = shared.pool.lock().await.get(&key).cloned();
+        let conn = match pooled {
+            Some(conn) => conn,
+            None => {
+                // Connect without holding the pool, other targets stay usable
+                let target = crate::password_sanitizer::sanitize_connection_url(&connection_url);
+                let args = Args {
+                    connection_url: Some(connection_url),
+                    from_env: None,
+                    from_django: None,
+                    ssh_tunnel,
+                    completions: None,
+                    command: commands.clone(),
+                    file: Vec::new(),
+                    update: false,
+                    no_daemon: true,
+                    subcommand: None,
+                };
+                let mut core = CliCore::new();
+                if let Err(e) = core.handle_database_connection(&args).await {
+                    return DaemonResponse::ConnectFailed {
+                        message: e.to_string(),
+                    };
+                }
+                info!("Daemon opened connection: {target}");
+                let conn = Rc::new(tokio::sync::Mutex::new(PooledConnection {
+                    core,
+                    target,
+                    last_used: Instant::now(),
+                    uses: 0,
+                }));
+                // Another client may have connected the same target meanwhile
+                Rc::clone(shared.pool.lock().await.entry(key.clone()).or_insert(conn))
+            }
+        };
+
+        let mut conn = conn.lock().await;
+        conn.uses += 1;
+
+        let mut output = CommandModeOutput::captured();
+        let result = conn.core.handle_command_mode(&commands, &mut output).await;
+        conn.last_used = Instant::now();
+        let lost_connection = conn.core.database.is_none();
+        drop(conn);
+
+        let (stdout, mut stderr) = output.into_captured();
+        let exit_code = match result {
+            Ok(code) => code,
+            Err(e) => {
+                stderr.push_str(&format!("Error: {e}\n"));
+                1
+            }
+        };
+        if lost_connection {
+            shared.pool.lock().await.remove(&key);
+        }
+
+        DaemonResponse::Executed {
+            stdout,
+            stderr,
+            exit_code,
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod imp {
+    use super::*;
+
+    fn unsupported() -> DaemonError {
+        DaemonError::Unsupported("dbcrust daemon requires Unix domain sockets".to_string())
+    }
+
+    pub(super) async fn send(
+        _path: &Path,
+        _request: &DaemonRequest,
+    ) -> Result<DaemonResponse, DaemonError> {
+        Err(unsupported())
+    }
+
+    pub(super) async fn start(_idle_timeout_secs: u64) -> Result<String, DaemonError> {
+        Err(unsupported())
+    }
+
+    pub(super) async fn run(
+        _idle_timeout_secs: u64,
+        _metrics: &crate::metrics::MetricsConfig,
+    ) -> Result<(), DaemonError> {
+        Err(unsupported())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_round_trip() {
+        let request = DaemonRequest::Execute {
+            connection_url: "postgres://localhost/app".to_string(),
+            ssh_tunnel: None,
+            working_dir: PathBuf::from("/tmp"),
+            commands: vec!["SELECT 1".to_string()],
+        };
+        let json = serde_json::to_string(&request).unwrap();
+        assert!(json.contains("\"type\":\"execute\""));
+        assert_eq!(
+            serde_json::from_str::<DaemonRequest>(&json).unwrap(),
+            request
+        );
+    }
+
+    #[test]
+    fn test_connection_key_scopes_local_files_by_directory() {
+        let a = Path::new("/a");
+        let b = Path::new("/b");
+        assert_eq!(
+            connection_key("postgres://db/app", None, a),
+            connection_key("postgres://db/app", None, b)
+        );
+        assert_ne!(
+            connection_key("./data.csv", None, a),
+            connection_key("./data.csv", None, b)
+        );
+        assert_ne!(
+            connection_key("sqlite://app.db", None, a),
+            connection_key("sqlite://app.db", None, b)
+        );
+        assert_ne!(
+            connection_key("postgres://db/app", Some("bastion"), a),
+            connection_key("postgres://db/app", None, a)
+        );
+    }
+
+    #[test]
+    fn test_status_render() {
+        let status = DaemonStatus {
+            pid: 42,
+            uptime_secs: 10,
+            idle_timeout_secs: 600,
+            connections: vec![PooledConnectionStatus {
+                target: "postgres://app@db/app".to_string(),
+                uses: 1,
+                idle_secs: 3,
+            }],
+            metrics_address: Some("127.0.0.1:9187".to_string()),
+        };
+        let rendered = status.render();
+        assert!(rendered.contains("pid 42"));
+        assert!(rendered.contains("Metrics on http://127.0.0.1:9187/metrics"));
+        assert!(rendered.contains("postgres://app@db/app  (1 use, idle 3s)"));
+    }
+}
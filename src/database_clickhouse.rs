@@ -4,11 +4,13 @@ use crate::complex_display::{
     ComplexTypeDetector, GenericComplexTypeDetector,
 };
 use crate::database::{
-    ConnectionInfo, DatabaseClient, DatabaseError, MetadataProvider, ServerInfo,
+    ConnectionInfo, DatabaseClient, DatabaseError, MetadataProvider, ServerDiagnostic, ServerInfo,
+    SizeSort,
 };
 use crate::db::TableDetails;
 use crate::geojson_display::GeoJsonDisplayAdapter;
 use crate::json_display::JsonDisplayAdapter;
+use crate::tls::{TlsMode, TlsSettings};
 use async_trait::async_trait;
 use clickhouse::{Client, Row};
 use serde::Deserialize;
@@ -17,11 +19,62 @@ use tracing::debug;
 /// ClickHouse metadata provider implementation
 pub struct ClickHouseMetadataProvider {
     client: Client,
+    /// Database the client is bound to
+    database: String,
 }
 
 impl ClickHouseMetadataProvider {
-    pub fn new(client: Client) -> Self {
-        Self { client }
+    pub fn new(client: Client, database: &str) -> Self {
+        Self {
+            client,
+            database: database.to_string(),
+        }
+    }
+
+    /// Resolve a parsed `Distributed(...)` engine against system.clusters
+    async fn distribution_info(
+        &self,
+        engine: DistributedEngine,
+        schema_name: &str,
+    ) -> Result<crate::db::DistributionInfo, DatabaseError> {
+        #[derive(Deserialize, Row)]
+        struct Replica {
+            shard_num: u32,
+            host_name: String,
+            port: u16,
+        }
+
+        let replicas = self
+            .client
+            .query(&format!(
+                "SELECT shard_num, host_name, port FROM system.clusters WHERE cluster = '{}' ORDER BY shard_num, replica_num",
+                crate::database::escape_sql_string(&engine.cluster)
+            ))
+            .fetch_all::<Replica>()
+            .await
+            .map_err(|e| DatabaseError::QueryError(format!("Failed to get cluster shards: {e}")))?;
+
+        let mut shards: Vec<(u32, Vec<String>)> = Vec::new();
+        for replica in replicas {
+            let host = format!("{}:{}", replica.host_name, replica.port);
+            match shards.last_mut() {
+                Some((shard, hosts)) if *shard == replica.shard_num => hosts.push(host),
+                _ => shards.push((replica.shard_num, vec![host])),
+            }
+        }
+
+        // An empty database argument means the Distributed table's own
+        let database = if engine.database.is_empty() {
+            schema_name.to_string()
+        } else {
+            engine.database
+        };
+        Ok(crate::db::DistributionInfo {
+            cluster: engine.cluster,
+            local_table: format!("{database}.{}", engine.table),
+            sharding_key: engine.sharding_key,
+            shards,
+        })
     }
 }
 
@@ -177,17 +230,17 @@ impl MetadataProvider for ClickHouseMetadataProvider {
         let database_lit = crate::database::escape_sql_string(schema_name);
         let table_lit = crate::database::escape_sql_string(table);
 
-        // Get column information
-        let columns_query = if schema.is_some() {
-            format!(
-                "SELECT name, type, default_expression, is_in_primary_key FROM system.columns WHERE database = '{database_lit}' AND table = '{table_lit}' ORDER BY position"
-            )
+        let database_filter = if schema.is_some() {
+            format!("'{database_lit}'")
         } else {
-            format!(
-                "SELECT name, type, default_expression, is_in_primary_key FROM system.columns WHERE database = currentDatabase() AND table = '{table_lit}' ORDER BY position"
-            )
+            "currentDatabase()".to_string()
         };
 
+        // Get column information
+        let columns_query = format!(
+            "SELECT name, type, default_expression, is_in_primary_key, is_in_partition_key FROM system.columns WHERE database = {database_filter} AND table = '{table_lit}' ORDER BY position"
+        );
+
         #[derive(Deserialize, Row)]
         struct ColumnDetail {
             name: String,
@@ -196,6 +249,7 @@ impl MetadataProvider for ClickHouseMetadataProvider {
             default_expression: String,
             #[allow(dead_code)] // May be used in future for primary key detection
             is_in_primary_key: u8,
+            is_in_partition_key: u8,
         }
 
         let columns = self
@@ -205,6 +259,12 @@ impl MetadataProvider for ClickHouseMetadataProvider {
             .await
             .map_err(|e| DatabaseError::QueryError(format!("Failed to get column details: {e}")))?;
 
+        let partition_key_columns: Vec<String> = columns
+            .iter()
+            .filter(|col| col.is_in_partition_key != 0)
+            .map(|col| col.name.clone())
+            .collect();
+
         let column_infos: Vec<crate::db::ColumnInfo> = columns
             .into_iter()
             .map(|col| crate::db::ColumnInfo {
@@ -232,6 +292,73 @@ impl MetadataProvider for ClickHouseMetadataProvider {
         let foreign_keys: Vec<crate::db::ForeignKeyInfo> = Vec::new();
         let referenced_by: Vec<crate::db::ReferencedByInfo> = Vec::new();
 
+        // MergeTree partitions are groups of parts sharing a partition key value
+        #[derive(Deserialize, Row)]
+        struct TableEngine {
+            partition_key: String,
+            engine: String,
+            engine_full: String,
+        }
+        #[derive(Deserialize, Row)]
+        struct PartitionParts {
+            partition: String,
+            parts: u64,
+            rows: u64,
+            bytes: u64,
+        }
+
+        let table_engine = self
+            .client
+            .query(&format!(
+                "SELECT partition_key, engine, engine_full FROM system.tables WHERE database = {database_filter} AND name = '{table_lit}'"
+            ))
+            .fetch_optional::<TableEngine>()
+            .await
+            .map_err(|e| DatabaseError::QueryError(format!("Failed to get partition key: {e}")))?;
+        let partition_key = table_engine
+            .as_ref()
+            .map(|row| row.partition_key.clone())
+            .filter(|key| !key.is_empty());
+
+        let partitioning = match partition_key {
+            Some(scheme) => {
+                let parts = self
+                    .client
+                    .query(&format!(
+                        "SELECT partition, count() AS parts, sum(rows) AS rows, sum(bytes_on_disk) AS bytes FROM system.parts WHERE database = {database_filter} AND table = '{table_lit}' AND active GROUP BY partition ORDER BY partition"
+                    ))
+                    .fetch_all::<PartitionParts>()
+                    .await
+                    .map_err(|e| DatabaseError::QueryError(format!("Failed to get partitions: {e}")))?;
+                Some(crate::db::PartitionInfo {
+                    scheme,
+                    key_columns: partition_key_columns,
+                    partitions: parts
+                        .into_iter()
+                        .map(|part| crate::db::PartitionDetail {
+                            name: part.partition,
+                            bound: format!(
+                                "{} part{}",
+                                part.parts,
+                                if part.parts == 1 { "" } else { "s" }
+                            ),
+                            rows: Some(part.rows as i64),
+                            bytes: Some(part.bytes as i64),
+                        })
+                        .collect(),
+                })
+            }
+            None => None,
+        };
+
+        let distribution = match table_engine
+            .filter(|row| row.engine == "Distributed")
+            .and_then(|row| parse_distributed_engine(&row.engine_full))
+        {
+            Some(engine) => Some(self.distribution_info(engine, schema_name).await?),
+            None => None,
+        };
+
         Ok(TableDetails {
             name: table.to_string(),
             schema: schema_name.to_string(),
@@ -242,6 +369,8 @@ impl MetadataProvider for ClickHouseMetadataProvider {
             foreign_keys,
             referenced_by,
             nested_field_details: std::collections::HashMap::new(),
+            partitioning,
+            distribution,
         })
     }
 
@@ -250,13 +379,288 @@ impl MetadataProvider for ClickHouseMetadataProvider {
     }
 
     fn default_schema(&self) -> Option<String> {
-        Some("default".to_string()) // ClickHouse default database is "default"
+        Some(self.database.clone())
+    }
+
+    async fn get_schema_table_counts(&self) -> Result<Vec<(String, usize)>, DatabaseError> {
+        #[derive(Deserialize, Row)]
+        struct TableCount {
+            database: String,
+            tables: u64,
+        }
+
+        let counts = self
+            .client
+            .query("SELECT database, count() AS tables FROM system.tables GROUP BY database")
+            .fetch_all::<TableCount>()
+            .await
+            .map_err(|e| DatabaseError::QueryError(format!("Failed to count tables: {e}")))?;
+        Ok(self
+            .get_schemas()
+            .await?
+            .into_iter()
+            .map(|schema| {
+                let tables = counts
+                    .iter()
+                    .find(|count| count.database == schema)
+                    .map_or(0, |count| count.tables as usize);
+                (schema, tables)
+            })
+            .collect())
+    }
+}
+
+/// Rows sent per INSERT statement by `\chinsert`
+pub const BULK_INSERT_BATCH_ROWS: u64 = 100_000;
+/// Upper bound on the payload of a single INSERT statement
+const BULK_INSERT_BATCH_BYTES: usize = 16 * 1024 * 1024;
+
+/// Input formats accepted for bulk file inserts (aliases resolve to the canonical name)
+const BULK_INSERT_FORMATS: &[(&str, &str)] = &[
+    ("csv", "CSV"),
+    ("csvwithnames", "CSVWithNames"),
+    ("tsv", "TabSeparated"),
+    ("tabseparated", "TabSeparated"),
+    ("tsvwithnames", "TabSeparatedWithNames"),
+    ("tabseparatedwithnames", "TabSeparatedWithNames"),
+    ("jsoneachrow", "JSONEachRow"),
+    ("jsonlines", "JSONEachRow"),
+    ("ndjson", "JSONEachRow"),
+];
+
+/// Running totals reported while a bulk file insert is in flight
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BulkInsertProgress {
+    pub rows: u64,
+    pub bytes: u64,
+    pub total_bytes: u64,
+    pub batches: usize,
+}
+
+impl BulkInsertProgress {
+    /// Share of the file sent so far, 0-100
+    pub fn percent(&self) -> f64 {
+        if self.total_bytes == 0 {
+            100.0
+        } else {
+            (self.bytes as f64 / self.total_bytes as f64 * 100.0).min(100.0)
+        }
+    }
+}
+
+/// Resolve the ClickHouse input format for a file, either from an explicit
+/// format name or from the file extension. Files with a `.csv` or `.tsv`
+/// extension are assumed to start with a header row.
+pub fn resolve_bulk_insert_format(
+    path: &std::path::Path,
+    requested: Option<&str>,
+) -> Result<&'static str, String> {
+    let supported = || {
+        let mut names: Vec<&str> = BULK_INSERT_FORMATS.iter().map(|(_, name)| *name).collect();
+        names.dedup();
+        names.join(", ")
+    };
+
+    if let Some(requested) = requested {
+        let key = requested.to_ascii_lowercase();
+        return BULK_INSERT_FORMATS
+            .iter()
+            .find(|(alias, _)| *alias == key)
+            .map(|(_, name)| *name)
+            .ok_or_else(|| {
+                format!(
+                    "Unsupported insert format '{requested}'. Supported formats: {}",
+                    supported()
+                )
+            });
+    }
+
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("csv") => Ok("CSVWithNames"),
+        Some("tsv" | "tab") => Ok("TabSeparatedWithNames"),
+        Some("json" | "jsonl" | "ndjson") => Ok("JSONEachRow"),
+        _ => Err(format!(
+            "Cannot infer the insert format of {}; pass one of: {}",
+            path.display(),
+            supported()
+        )),
+    }
+}
+
+/// Shards and replicas of every cluster in the server's remote_servers
+const CLUSTERS_QUERY: &str = r#"
+    SELECT cluster,
+           shard_num AS shard,
+           shard_weight AS weight,
+           replica_num AS replica,
+           host_name AS host,
+           port,
+           if(is_local, '*', '') AS local,
+           errors_count AS errors
+    FROM system.clusters
+    ORDER BY cluster, shard_num, replica_num
+"#;
+
+/// Every database with the size of its active parts on disk
+const DATABASE_SIZES_QUERY: &str = r#"
+    SELECT d.name AS database,
+           coalesce(s.size, 0) AS size,
+           if(d.name = currentDatabase(), '*', '') AS current
+    FROM system.databases AS d
+    LEFT JOIN (
+        SELECT database, sum(bytes_on_disk) AS size
+        FROM system.parts
+        WHERE active
+        GROUP BY database
+    ) AS s ON s.database = d.name
+    WHERE d.name NOT IN ('system', 'INFORMATION_SCHEMA', 'information_schema')
+    ORDER BY size DESC, database
+"#;
+
+/// Largest tables by their active parts: compressed column data, marks
+/// (ClickHouse's sparse index) and everything on disk
+fn relation_sizes_query(limit: usize, sort: SizeSort) -> String {
+    format!(
+        r#"
+        SELECT database AS schema, table,
+               sum(rows) AS rows,
+               count() AS parts,
+               sum(data_compressed_bytes) AS table_size,
+               sum(marks_bytes) AS index_size,
+               sum(bytes_on_disk) AS total_size
+        FROM system.parts
+        WHERE active AND database NOT IN ('system', 'INFORMATION_SCHEMA', 'information_schema')
+        GROUP BY database, table
+        ORDER BY `{}` DESC, schema, table
+        LIMIT {limit}
+        "#,
+        sort.column()
+    )
+}
+
+/// Quote each part of a (possibly database-qualified) table name with backticks
+/// Arguments of a `Distributed(cluster, database, table[, sharding_key[, policy]])` engine
+#[derive(Debug, PartialEq)]
+struct DistributedEngine {
+    cluster: String,
+    database: String,
+    table: String,
+    sharding_key: Option<String>,
+}
+
+/// Parse the engine_full of a Distributed table from system.tables
+fn parse_distributed_engine(engine_full: &str) -> Option<DistributedEngine> {
+    let args = engine_full.trim().strip_prefix("Distributed(")?;
+    let mut parts = Vec::new();
+    let (mut depth, mut quote, mut start) = (0usize, None, 0usize);
+    let mut end = None;
+    for (i, c) in args.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"' | '`') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') if depth == 0 => {
+                end = Some(i);
+                break;
+            }
+            (None, ')') => depth -= 1,
+            (None, ',') if depth == 0 => {
+                parts.push(args[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(args[start..end?].trim());
+
+    let unquote = |arg: &str| {
+        arg.trim_matches(|c| matches!(c, '\'' | '"' | '`'))
+            .to_string()
+    };
+    Some(DistributedEngine {
+        cluster: unquote(parts.first()?),
+        database: unquote(parts.get(1)?),
+        table: unquote(parts.get(2)?),
+        sharding_key: parts.get(3).map(|key| key.to_string()),
+    })
+}
+
+fn quote_table_name(table: &str) -> String {
+    table
+        .split('.')
+        .map(|part| {
+            if part.starts_with('`') && part.ends_with('`') && part.len() > 1 {
+                part.to_string()
+            } else {
+                format!("`{}`", part.replace('`', "``"))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Splits an input file into records so batches never cut a row in half.
+/// CSV fields may contain quoted newlines; the other formats are one record per line.
+struct RecordReader<R> {
+    reader: R,
+    quoted_newlines: bool,
+}
+
+impl<R: std::io::BufRead> RecordReader<R> {
+    fn new(reader: R, format: &str) -> Self {
+        Self {
+            reader,
+            quoted_newlines: format.starts_with("CSV"),
+        }
+    }
+
+    /// Append the next non-blank record (newline-terminated) to `buf`.
+    /// Returns the number of bytes consumed from the input, or 0 at end of file.
+    fn read_record(&mut self, buf: &mut Vec<u8>) -> std::io::Result<usize> {
+        loop {
+            let start = buf.len();
+            let mut consumed = 0;
+            let mut quotes = 0usize;
+            loop {
+                let line_start = buf.len();
+                let read = self.reader.read_until(b'\n', buf)?;
+                consumed += read;
+                if read == 0 {
+                    break;
+                }
+                if self.quoted_newlines {
+                    quotes += buf[line_start..].iter().filter(|b| **b == b'"').count();
+                }
+                if quotes % 2 == 0 {
+                    break;
+                }
+            }
+
+            if consumed == 0 {
+                return Ok(0);
+            }
+            if buf[start..].iter().all(u8::is_ascii_whitespace) {
+                buf.truncate(start);
+                continue;
+            }
+            if buf.last() != Some(&b'\n') {
+                buf.push(b'\n');
+            }
+            return Ok(consumed);
+        }
     }
 }
 
 /// ClickHouse database client implementation
 pub struct ClickHouseClient {
     client: Client,
+    /// Plain HTTP client for user queries, with the same TLS settings
+    http: reqwest::Client,
     connection_info: ConnectionInfo,
     current_database: String,
     metadata_provider: ClickHouseMetadataProvider,
@@ -293,9 +697,48 @@ impl ClickHouseClient {
         DatabaseError::ConnectionError(format!("Failed to connect to ClickHouse: {source_error}"))
     }
 
-    pub async fn new(connection_info: ConnectionInfo) -> Result<Self, DatabaseError> {
+    /// Clients honouring sslrootcert/sslcert/verify-ca/...; the default
+    /// ones only know the bundled roots
+    fn tls_clients(tls: &TlsSettings) -> Result<(Client, reqwest::Client), String> {
+        use hyper_util::client::legacy::{Client as HyperClient, connect::HttpConnector};
+
+        let config = tls.client_config(tls.encrypted_mode())?;
+        let mut connector = HttpConnector::new();
+        connector.set_keepalive(Some(std::time::Duration::from_secs(60)));
+        connector.enforce_http(false);
+        let connector = hyper_rustls::HttpsConnectorBuilder::new()
+            .with_tls_config(config.clone())
+            .https_or_http()
+            .enable_http1()
+            .wrap_connector(connector);
+        let client = Client::with_http_client(
+            HyperClient::builder(hyper_util::rt::TokioExecutor::new())
+                .pool_idle_timeout(std::time::Duration::from_secs(2))
+                .build(connector),
+        );
+        let http = reqwest::Client::builder()
+            .use_preconfigured_tls(config)
+            .build()
+            .map_err(|e| format!("Cannot build the HTTPS client: {e}"))?;
+        Ok((client, http))
+    }
+
+    pub async fn new(mut connection_info: ConnectionInfo) -> Result<Self, DatabaseError> {
         debug!("[ClickHouseClient::new] Creating ClickHouse client");
 
+        // TLS from sslmode/sslrootcert/... and [tls]; sslmode overrides ssl=true/false
+        let tls = TlsSettings::for_connection(&connection_info)
+            .map_err(DatabaseError::ConnectionError)?;
+        match tls.mode {
+            Some(mode) if mode.requires_tls() => connection_info.use_tls = true,
+            Some(TlsMode::Disable) => connection_info.use_tls = false,
+            _ => {}
+        }
+        if !connection_info.use_tls {
+            tls.check_negotiated(None)
+                .map_err(DatabaseError::ConnectionError)?;
+        }
+
         // Build connection URL with *.localhost resolution
         let original_host = connection_info.host.as_deref().unwrap_or("localhost");
 
@@ -341,9 +784,13 @@ impl ClickHouseClient {
             connection_info.password.is_some()
         );
 
-        let mut client = Client::default()
-            .with_url(database_url)
-            .with_database(&database);
+        let (client, http) = if connection_info.use_tls && (tls.customized() || tls.mode.is_some())
+        {
+            Self::tls_clients(&tls).map_err(DatabaseError::ConnectionError)?
+        } else {
+            (Client::default(), reqwest::Client::new())
+        };
+        let mut client = client.with_url(database_url).with_database(&database);
 
         if let Some(username) = &connection_info.username {
             client = client.with_user(username);
@@ -368,10 +815,11 @@ impl ClickHouseClient {
             .await
             .map_err(|e| Self::connection_error_with_http_hint(port, connection_info.use_tls, e))?;
 
-        let metadata_provider = ClickHouseMetadataProvider::new(client.clone());
+        let metadata_provider = ClickHouseMetadataProvider::new(client.clone(), &database);
 
         Ok(Self {
             client,
+            http,
             connection_info,
             current_database: database,
             metadata_provider,
@@ -394,6 +842,19 @@ impl ClickHouseClient {
         request
     }
 
+    /// Send one batch as its own `INSERT ... FORMAT` statement
+    async fn send_insert_batch(&self, sql: &str, batch: Vec<u8>) -> Result<(), DatabaseError> {
+        let mut insert = self.client.insert_formatted_with(sql.to_string());
+        insert
+            .send(batch.into())
+            .await
+            .map_err(|e| DatabaseError::QueryError(format!("Bulk insert failed: {e}")))?;
+        insert
+            .end()
+            .await
+            .map_err(|e| DatabaseError::QueryError(format!("Bulk insert failed: {e}")))
+    }
+
     /// Execute HTTP query via ClickHouse HTTP interface
     async fn execute_http_user_query(&self, sql: &str) -> Result<Vec<Vec<String>>, DatabaseError> {
         // Build HTTP URL
@@ -420,8 +881,7 @@ impl ClickHouseClient {
             self.connection_info.password.is_some()
         );
 
-        // Create HTTP client
-        let client = reqwest::Client::new();
+        let client = &self.http;
 
         // Try with TabSeparatedWithNames first (for SELECT queries)
         let formatted_sql = format!("{} FORMAT TabSeparatedWithNames", sql.trim_end_matches(';'));
@@ -742,6 +1202,11 @@ impl DatabaseClient for ClickHouseClient {
         Ok(())
     }
 
+    async fn set_schema(&mut self, schema: &str) -> Result<(), DatabaseError> {
+        // ClickHouse schemas are databases
+        self.connect_to_database(schema).await
+    }
+
     fn get_current_database(&self) -> String {
         self.current_database.clone()
     }
@@ -765,6 +1230,24 @@ impl DatabaseClient for ClickHouseClient {
         Ok(())
     }
 
+    async fn server_diagnostic(
+        &self,
+        diagnostic: ServerDiagnostic,
+    ) -> Result<Vec<Vec<String>>, DatabaseError> {
+        debug!("[ClickHouseClient::server_diagnostic] Running {diagnostic}");
+        match diagnostic {
+            ServerDiagnostic::DatabaseSizes => self.execute_query(DATABASE_SIZES_QUERY).await,
+            ServerDiagnostic::RelationSizes { limit, sort } => {
+                self.execute_query(&relation_sizes_query(limit, sort)).await
+            }
+            ServerDiagnostic::Clusters => self.execute_query(CLUSTERS_QUERY).await,
+            _ => Err(DatabaseError::FeatureNotSupported {
+                database_type: self.connection_info.database_type.clone(),
+                feature: diagnostic.to_string(),
+            }),
+        }
+    }
+
     async fn get_server_info(&self) -> Result<ServerInfo, DatabaseError> {
         debug!("[ClickHouseClient::get_server_info] Getting server info");
 
@@ -788,11 +1271,70 @@ impl DatabaseClient for ClickHouseClient {
 
         Ok(server_info)
     }
+
+    async fn bulk_insert_file(
+        &self,
+        table: &str,
+        path: &std::path::Path,
+        format: &str,
+        progress: &(dyn for<'p> Fn(&'p BulkInsertProgress) + Send + Sync),
+    ) -> Result<BulkInsertProgress, DatabaseError> {
+        debug!(
+            "[ClickHouseClient::bulk_insert_file] Inserting {} into {} as {}",
+            path.display(),
+            table,
+            format
+        );
+
+        let read_error = |e: std::io::Error| {
+            DatabaseError::QueryError(format!("Failed to read {}: {e}", path.display()))
+        };
+        let file = std::fs::File::open(path).map_err(read_error)?;
+        let mut totals = BulkInsertProgress {
+            total_bytes: file.metadata().map(|m| m.len()).unwrap_or(0),
+            ..Default::default()
+        };
+        let mut records = RecordReader::new(std::io::BufReader::new(file), format);
+
+        // Formats with a header row need it repeated at the start of every batch
+        let mut header = Vec::new();
+        if format.ends_with("WithNames") {
+            totals.bytes += records.read_record(&mut header).map_err(read_error)? as u64;
+        }
+
+        let sql = format!("INSERT INTO {} FORMAT {format}", quote_table_name(table));
+        let mut batch = header.clone();
+        let mut batch_rows = 0u64;
+        loop {
+            let consumed = records.read_record(&mut batch).map_err(read_error)?;
+            if consumed > 0 {
+                totals.bytes += consumed as u64;
+                batch_rows += 1;
+            }
+
+            let full =
+                batch_rows >= BULK_INSERT_BATCH_ROWS || batch.len() >= BULK_INSERT_BATCH_BYTES;
+            if batch_rows > 0 && (full || consumed == 0) {
+                let payload = std::mem::replace(&mut batch, header.clone());
+                self.send_insert_batch(&sql, payload).await?;
+                totals.rows += batch_rows;
+                totals.batches += 1;
+                batch_rows = 0;
+                progress(&totals);
+            }
+            if consumed == 0 {
+                break;
+            }
+        }
+
+        Ok(totals)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rstest::rstest;
 
     fn test_client_with_auth(username: Option<&str>, password: Option<&str>) -> ClickHouseClient {
         let connection_info = ConnectionInfo {
@@ -806,14 +1348,16 @@ mod tests {
             options: std::collections::HashMap::new(),
             docker_container: None,
             use_tls: false,
+            srv: false,
         };
         let client = Client::default().with_url("http://localhost:8123");
 
         ClickHouseClient {
             client: client.clone(),
+            http: reqwest::Client::new(),
             connection_info,
             current_database: "default".to_string(),
-            metadata_provider: ClickHouseMetadataProvider::new(client),
+            metadata_provider: ClickHouseMetadataProvider::new(client, "default"),
         }
     }
 
@@ -866,4 +1410,75 @@ mod tests {
             other => panic!("expected connection error, got {other:?}"),
         }
     }
+
+    #[rstest]
+    #[case("events.csv", None, Ok("CSVWithNames"))]
+    #[case("events.TSV", None, Ok("TabSeparatedWithNames"))]
+    #[case("events.ndjson", None, Ok("JSONEachRow"))]
+    #[case("events.csv", Some("csv"), Ok("CSV"))]
+    #[case("events.data", Some("JSONLines"), Ok("JSONEachRow"))]
+    #[case("events.data", None, Err(()))]
+    #[case("events.csv", Some("Parquet"), Err(()))]
+    fn test_resolve_bulk_insert_format(
+        #[case] path: &str,
+        #[case] requested: Option<&str>,
+        #[case] expected: Result<&str, ()>,
+    ) {
+        let resolved = resolve_bulk_insert_format(std::path::Path::new(path), requested);
+        assert_eq!(resolved.map_err(|_| ()), expected);
+    }
+
+    #[test]
+    fn test_parse_distributed_engine() {
+        assert_eq!(
+            parse_distributed_engine(
+                "Distributed('analytics', 'default', 'events_local', cityHash64(user_id, 'x,y'))"
+            ),
+            Some(DistributedEngine {
+                cluster: "analytics".to_string(),
+                database: "default".to_string(),
+                table: "events_local".to_string(),
+                sharding_key: Some("cityHash64(user_id, 'x,y')".to_string()),
+            })
+        );
+        assert_eq!(
+            parse_distributed_engine("Distributed(logs, '', hits) SETTINGS fsync_after_insert = 0"),
+            Some(DistributedEngine {
+                cluster: "logs".to_string(),
+                database: String::new(),
+                table: "hits".to_string(),
+                sharding_key: None,
+            })
+        );
+        assert_eq!(parse_distributed_engine("MergeTree ORDER BY id"), None);
+    }
+
+    #[test]
+    fn test_quote_table_name() {
+        assert_eq!(quote_table_name("events"), "`events`");
+        assert_eq!(quote_table_name("analytics.events"), "`analytics`.`events`");
+        assert_eq!(quote_table_name("`my table`"), "`my table`");
+    }
+
+    #[test]
+    fn test_record_reader_keeps_quoted_newlines_and_skips_blank_lines() {
+        let input = "id,note\n1,\"multi\nline\"\n\n2,plain";
+        let mut reader = RecordReader::new(input.as_bytes(), "CSVWithNames");
+        let mut records = Vec::new();
+        loop {
+            let mut buf = Vec::new();
+            if reader.read_record(&mut buf).unwrap() == 0 {
+                break;
+            }
+            records.push(String::from_utf8(buf).unwrap());
+        }
+        assert_eq!(records, ["id,note\n", "1,\"multi\nline\"\n", "2,plain\n"]);
+
+        // Quotes only matter for CSV
+        let mut reader =
+            RecordReader::new("{\"a\":\"\\\"\"}\n{\"a\":2}\n".as_bytes(), "JSONEachRow");
+        let mut buf = Vec::new();
+        reader.read_record(&mut buf).unwrap();
+        assert_eq!(buf, b"{\"a\":\"\\\"\"}\n");
+    }
 }
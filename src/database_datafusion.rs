@@ -1063,6 +1063,8 @@ impl MetadataProvider for DataFusionMetadataProvider {
             check_constraints: vec![],
             foreign_keys: vec![],
             referenced_by: vec![],
+            partitioning: None,
+            distribution: None,
         })
     }
 
@@ -1266,6 +1268,7 @@ mod tests {
             options,
             docker_container: None,
             use_tls: false,
+            srv: false,
         };
 
         let client = DataFusionClient::new(connection_info).await.unwrap();
@@ -1314,6 +1317,7 @@ mod tests {
             options: std::collections::HashMap::new(),
             docker_container: None,
             use_tls: false,
+            srv: false,
         };
 
         let client = DataFusionClient::new(connection_info).await.unwrap();
@@ -1353,6 +1357,7 @@ mod tests {
             options: std::collections::HashMap::new(),
             docker_container: None,
             use_tls: false,
+            srv: false,
         };
 
         let client = DataFusionClient::new(connection_info).await.unwrap();
@@ -1423,6 +1428,7 @@ mod tests {
             options: std::collections::HashMap::new(),
             docker_container: None,
             use_tls: false,
+            srv: false,
         };
 
         let client = DataFusionClient::new(connection_info).await.unwrap();
@@ -1460,6 +1466,7 @@ mod tests {
             options: std::collections::HashMap::new(),
             docker_container: None,
             use_tls: false,
+            srv: false,
         };
 
         let client = DataFusionClient::new(connection_info).await.unwrap();
@@ -1,14 +1,16 @@
 //! Elasticsearch implementation of the database abstraction layer
 use crate::complex_display::ComplexDisplayConfig;
 use crate::database::{
-    ConnectionInfo, DatabaseClient, DatabaseError, DatabaseTypeExt, MetadataProvider, ServerInfo,
+    ConnectionInfo, DatabaseClient, DatabaseError, DatabaseTypeExt, MetadataProvider, RowSink,
+    ServerInfo,
 };
+use crate::tls::{TlsMode, TlsSettings, TlsVersion};
 use async_trait::async_trait;
 use elasticsearch::{
-    Elasticsearch, SearchParts,
-    auth::Credentials,
+    Elasticsearch, OpenPointInTimeParts, SearchParts,
+    auth::{ClientCertificate, Credentials},
     cat::CatIndicesParts,
-    cert::CertificateValidation,
+    cert::{Certificate, CertificateValidation},
     http::{
         Url,
         transport::{SingleNodeConnectionPool, TransportBuilder},
@@ -270,6 +272,8 @@ impl MetadataProvider for ElasticsearchMetadataProvider {
             foreign_keys: Vec::new(),
             referenced_by: Vec::new(),
             nested_field_details: std::collections::HashMap::new(),
+            partitioning: None,
+            distribution: None,
         })
     }
 
@@ -467,13 +471,32 @@ impl ElasticsearchClient {
         // Build Elasticsearch URL
         let mut url_string = String::new();
 
-        // Handle scheme
-        if connection_info.options.contains_key("ssl")
-            && connection_info
+        // Handle scheme: ssl=true keeps meaning HTTPS with full verification,
+        // verify_certs=false keeps meaning no verification
+        let tls = TlsSettings::for_connection(&connection_info)
+            .map_err(DatabaseError::ConnectionError)?;
+        let legacy_https = connection_info.use_tls
+            || connection_info
                 .options
                 .get("ssl")
-                .is_some_and(|v| v == "true")
+                .is_some_and(|v| v == "true");
+        let legacy_mode = if connection_info
+            .options
+            .get("verify_certs")
+            .is_some_and(|v| v == "false")
         {
+            TlsMode::Require
+        } else if !tls.verify_hostname {
+            TlsMode::VerifyCa
+        } else {
+            TlsMode::VerifyFull
+        };
+        let tls_mode = match tls.mode {
+            None | Some(TlsMode::Allow | TlsMode::Prefer) if !legacy_https => TlsMode::Disable,
+            None | Some(TlsMode::Allow | TlsMode::Prefer) => legacy_mode,
+            Some(_) => tls.mode_or(TlsMode::Disable),
+        };
+        if tls_mode.requires_tls() {
             url_string.push_str("https://");
         } else {
             url_string.push_str("http://");
@@ -512,36 +535,86 @@ impl ElasticsearchClient {
         let conn_pool = SingleNodeConnectionPool::new(url);
         let mut transport_builder = TransportBuilder::new(conn_pool);
 
-        // Handle authentication
-        if let (Some(username), Some(password)) =
-            (&connection_info.username, &connection_info.password)
-        {
-            transport_builder =
-                transport_builder.auth(Credentials::Basic(username.clone(), password.clone()));
+        use elasticsearch::http::headers::{AUTHORIZATION, HeaderMap, HeaderValue};
+        let mut default_headers = HeaderMap::new();
+
+        // Handle authentication. A client certificate takes the transport's
+        // credentials slot, so basic auth then travels as a plain header.
+        let identity = tls.pem_identity().map_err(DatabaseError::ConnectionError)?;
+        let basic = match (&connection_info.username, &connection_info.password) {
+            (Some(username), Some(password)) => Some((username.clone(), password.clone())),
+            _ => None,
+        };
+        match (identity, basic) {
+            (Some(pem), basic) => {
+                transport_builder =
+                    transport_builder.auth(Credentials::Certificate(ClientCertificate::Pem(pem)));
+                if let Some((username, password)) = basic {
+                    use base64::Engine;
+                    let encoded = base64::engine::general_purpose::STANDARD
+                        .encode(format!("{username}:{password}"));
+                    if let Ok(mut value) = HeaderValue::from_str(&format!("Basic {encoded}")) {
+                        value.set_sensitive(true);
+                        default_headers.insert(AUTHORIZATION, value);
+                    }
+                }
+            }
+            (None, Some((username, password))) => {
+                transport_builder = transport_builder.auth(Credentials::Basic(username, password));
+            }
+            (None, None) => {}
         }
 
-        // Handle SSL verification
-        if connection_info
-            .options
-            .get("verify_certs")
-            .is_some_and(|v| v == "false")
-        {
-            transport_builder = transport_builder.cert_validation(CertificateValidation::None);
+        // Handle certificate verification
+        if tls.min_version > Some(TlsVersion::V1_2) {
+            return Err(DatabaseError::ConnectionError(TlsSettings::unsupported(
+                "Elasticsearch",
+                "a minimum TLS version above TLSv1.2",
+            )));
+        }
+        match tls_mode {
+            TlsMode::Require => {
+                transport_builder = transport_builder.cert_validation(CertificateValidation::None);
+            }
+            TlsMode::VerifyCa => {
+                return Err(DatabaseError::ConnectionError(TlsSettings::unsupported(
+                    "Elasticsearch",
+                    "verify-ca or verify_hostname=false (use sslmode=require to skip verification)",
+                )));
+            }
+            TlsMode::VerifyFull => {
+                if let Some(root_cert) = &tls.root_cert {
+                    let pem = std::fs::read(root_cert).map_err(|e| {
+                        DatabaseError::ConnectionError(format!(
+                            "Cannot read {}: {e}",
+                            root_cert.display()
+                        ))
+                    })?;
+                    let chain = Certificate::from_pem(&pem).map_err(|e| {
+                        DatabaseError::ConnectionError(format!(
+                            "Invalid CA certificate in {}: {e}",
+                            root_cert.display()
+                        ))
+                    })?;
+                    transport_builder =
+                        transport_builder.cert_validation(CertificateValidation::Full(chain));
+                }
+            }
+            TlsMode::Disable | TlsMode::Allow | TlsMode::Prefer => {}
         }
 
         // If we resolved a *.localhost domain, add the original hostname as a header
         // for proxy routing (but exclude plain "localhost")
         if let Some(ref original) = original_host {
             if original != "localhost" {
-                use elasticsearch::http::headers::HeaderMap;
-                use elasticsearch::http::headers::HeaderValue;
-                let mut default_headers = HeaderMap::new();
                 if let Ok(host_value) = HeaderValue::from_str(original) {
                     default_headers.insert("X-Original-Host", host_value);
                 }
-                transport_builder = transport_builder.headers(default_headers);
             }
         }
+        if !default_headers.is_empty() {
+            transport_builder = transport_builder.headers(default_headers);
+        }
 
         let transport = transport_builder.build().map_err(|e| {
             DatabaseError::ConnectionError(format!("Failed to build Elasticsearch transport: {e}"))
@@ -839,30 +912,58 @@ impl ElasticsearchClient {
         sql.replace('`', "\"")
     }
 
-    /// Execute SQL query via Elasticsearch SQL API
-    async fn execute_sql_query(&self, sql: &str) -> Result<Vec<Vec<String>>, DatabaseError> {
-        debug!(
-            "[ElasticsearchClient::execute_sql_query] Executing SQL: {}",
-            sql
-        );
-
+    /// Quote index names and identifiers the way the SQL API expects
+    fn normalize_sql(sql: &str) -> Result<String, DatabaseError> {
         // First, automatically quote table names in SQL
         let auto_quoted_sql = Self::auto_quote_table_names_in_sql(sql)?;
         if auto_quoted_sql != sql {
             debug!(
-                "[ElasticsearchClient::execute_sql_query] Auto-quoted table names: {} -> {}",
+                "[ElasticsearchClient::normalize_sql] Auto-quoted table names: {} -> {}",
                 sql, auto_quoted_sql
             );
         }
 
         // Fix quoting for Elasticsearch SQL API (backticks to double quotes)
-        let mut final_sql = Self::fix_elasticsearch_sql_quoting(&auto_quoted_sql);
+        let final_sql = Self::fix_elasticsearch_sql_quoting(&auto_quoted_sql);
         if final_sql != auto_quoted_sql {
             debug!(
-                "[ElasticsearchClient::execute_sql_query] Fixed SQL quoting: {} -> {}",
+                "[ElasticsearchClient::normalize_sql] Fixed SQL quoting: {} -> {}",
                 auto_quoted_sql, final_sql
             );
         }
+        Ok(final_sql)
+    }
+
+    /// Message for the `error` object of an SQL API response, with a hint
+    /// for the quoting mistakes people usually make
+    fn sql_error_message(error: &Value) -> String {
+        if let Some(reason) = error.get("reason").and_then(|r| r.as_str()) {
+            if reason.contains("backquoted identifiers not supported") {
+                format!(
+                    "Elasticsearch SQL Error: {reason}. Hint: Use double quotes (\") instead of backticks (`) for identifiers with special characters."
+                )
+            } else if reason.contains("mismatched input")
+                && (reason.contains("-") || reason.contains("."))
+            {
+                format!(
+                    "Elasticsearch SQL Error: {reason}. Hint: Index names with hyphens, dots, or special characters must be quoted with double quotes (\")."
+                )
+            } else {
+                format!("Elasticsearch SQL Error: {reason}")
+            }
+        } else {
+            format!("Elasticsearch SQL Error: {error:?}")
+        }
+    }
+
+    /// Execute SQL query via Elasticsearch SQL API
+    async fn execute_sql_query(&self, sql: &str) -> Result<Vec<Vec<String>>, DatabaseError> {
+        debug!(
+            "[ElasticsearchClient::execute_sql_query] Executing SQL: {}",
+            sql
+        );
+
+        let mut final_sql = Self::normalize_sql(sql)?;
 
         // Handle SELECT * queries by rewriting them to exclude array fields
         let mut excluded_fields = Vec::new();
@@ -921,24 +1022,7 @@ impl ElasticsearchClient {
 
         // Check for errors in response
         if let Some(error) = body.get("error") {
-            let error_msg = if let Some(reason) = error.get("reason").and_then(|r| r.as_str()) {
-                if reason.contains("backquoted identifiers not supported") {
-                    format!(
-                        "Elasticsearch SQL Error: {reason}. Hint: Use double quotes (\") instead of backticks (`) for identifiers with special characters."
-                    )
-                } else if reason.contains("mismatched input")
-                    && (reason.contains("-") || reason.contains("."))
-                {
-                    format!(
-                        "Elasticsearch SQL Error: {reason}. Hint: Index names with hyphens, dots, or special characters must be quoted with double quotes (\")."
-                    )
-                } else {
-                    format!("Elasticsearch SQL Error: {reason}")
-                }
-            } else {
-                format!("Elasticsearch SQL Error: {error:?}")
-            };
-            return Err(DatabaseError::QueryError(error_msg));
+            return Err(DatabaseError::QueryError(Self::sql_error_message(error)));
         }
 
         // Parse SQL API response format
@@ -1029,6 +1113,280 @@ impl ElasticsearchClient {
         Ok(results)
     }
 
+    /// Stream a `SELECT` to `sink` without stopping at the search window
+    /// (`index.max_result_window`, 10,000 hits by default). Queries that only
+    /// read fields of one index page through a point in time with
+    /// `search_after`; aggregations, scripts and older servers follow the SQL
+    /// API cursor instead.
+    async fn stream_select(&self, sql: &str, sink: &mut dyn RowSink) -> Result<u64, DatabaseError> {
+        let mut final_sql = Self::normalize_sql(sql)?;
+        // Same array field exclusion as for displayed results
+        if Self::is_select_star_query(&final_sql) {
+            if let Ok((rewritten, excluded)) = self.rewrite_select_star_query(&final_sql).await {
+                if !excluded.is_empty() {
+                    final_sql = rewritten;
+                }
+            }
+        }
+
+        let index = Self::extract_index_name_from_query(&final_sql)
+            .map(|index| index.trim_end_matches(';').to_string());
+        let translated = self.translate_sql(&final_sql).await?;
+        if let (Some(index), Some(fields)) = (index, point_in_time_fields(&translated)) {
+            let columns = self.sql_columns(&final_sql).await?;
+            if columns.len() == fields.len() {
+                debug!(
+                    "[ElasticsearchClient::stream_select] Paging {} with a point in time",
+                    index
+                );
+                return self
+                    .stream_point_in_time(
+                        &index,
+                        &translated,
+                        &columns,
+                        &fields,
+                        sql_limit(&final_sql),
+                        sink,
+                    )
+                    .await;
+            }
+        }
+        debug!("[ElasticsearchClient::stream_select] Following the SQL cursor");
+        self.stream_sql_cursor(&final_sql, sink).await
+    }
+
+    /// The search request the SQL API runs for `sql`
+    async fn translate_sql(&self, sql: &str) -> Result<Value, DatabaseError> {
+        let body: Value = self
+            .client
+            .sql()
+            .translate()
+            .body(json!({ "query": sql }))
+            .send()
+            .await
+            .map_err(|e| DatabaseError::QueryError(format!("Query translation failed: {e}")))?
+            .json()
+            .await
+            .map_err(|e| {
+                DatabaseError::QueryError(format!("Failed to parse translation response: {e}"))
+            })?;
+        match body.get("error") {
+            Some(error) => Err(DatabaseError::QueryError(Self::sql_error_message(error))),
+            None => Ok(body),
+        }
+    }
+
+    /// One page of the SQL API: `sql` for the first one, `cursor` afterwards
+    async fn sql_page(&self, request: Value) -> Result<Value, DatabaseError> {
+        let body: Value = self
+            .client
+            .sql()
+            .query()
+            .format("json")
+            .body(request)
+            .send()
+            .await
+            .map_err(|e| DatabaseError::QueryError(format!("SQL query failed: {e}")))?
+            .json()
+            .await
+            .map_err(|e| DatabaseError::QueryError(format!("Failed to parse SQL response: {e}")))?;
+        match body.get("error") {
+            Some(error) => Err(DatabaseError::QueryError(Self::sql_error_message(error))),
+            None => Ok(body),
+        }
+    }
+
+    async fn clear_sql_cursor(&self, cursor: &str) {
+        if let Err(e) = self
+            .client
+            .sql()
+            .clear_cursor()
+            .body(json!({ "cursor": cursor }))
+            .send()
+            .await
+        {
+            debug!("Failed to clear the SQL cursor: {}", e);
+        }
+    }
+
+    /// Column names of `sql` as the SQL API reports them (multi-valued
+    /// fields are tolerated, the rows are read with the search API)
+    async fn sql_columns(&self, sql: &str) -> Result<Vec<String>, DatabaseError> {
+        let page = self
+            .sql_page(json!({
+                "query": sql,
+                "fetch_size": 1,
+                "field_multi_value_leniency": true
+            }))
+            .await?;
+        if let Some(cursor) = page.get("cursor").and_then(Value::as_str) {
+            self.clear_sql_cursor(cursor).await;
+        }
+        Ok(sql_column_names(&page))
+    }
+
+    /// Follow the SQL API cursor until the last page
+    async fn stream_sql_cursor(
+        &self,
+        sql: &str,
+        sink: &mut dyn RowSink,
+    ) -> Result<u64, DatabaseError> {
+        let interrupt = crate::database::interrupt_flag();
+        let mut request = json!({ "query": sql, "fetch_size": STREAM_PAGE_SIZE });
+        let mut cursor: Option<String> = None;
+        let mut count = 0u64;
+        let result = loop {
+            let page = match self.sql_page(request).await {
+                Ok(page) => page,
+                Err(e) => break Err(e),
+            };
+            if cursor.is_none() {
+                if let Err(e) = sink.columns(&sql_column_names(&page)) {
+                    break Err(e);
+                }
+            }
+            let rows = page.get("rows").and_then(Value::as_array);
+            if let Err(e) = rows
+                .into_iter()
+                .flatten()
+                .try_for_each(|row| sink.row(row.as_array().cloned().unwrap_or_default()))
+            {
+                break Err(e);
+            }
+            count += rows.map_or(0, |rows| rows.len() as u64);
+
+            cursor = page
+                .get("cursor")
+                .and_then(Value::as_str)
+                .filter(|cursor| !cursor.is_empty())
+                .map(str::to_string);
+            match &cursor {
+                Some(_) if interrupt.load(std::sync::atomic::Ordering::Relaxed) => {
+                    break Err(DatabaseError::QueryError("Query cancelled".to_string()));
+                }
+                Some(next) => request = json!({ "cursor": next }),
+                None => break Ok(count),
+            }
+        };
+        if let (Err(_), Some(cursor)) = (&result, &cursor) {
+            self.clear_sql_cursor(cursor).await;
+        }
+        result
+    }
+
+    /// Page through `index` with a point in time and `search_after`, using
+    /// the search the SQL API translated the statement to
+    async fn stream_point_in_time(
+        &self,
+        index: &str,
+        translated: &Value,
+        columns: &[String],
+        fields: &[String],
+        limit: Option<u64>,
+        sink: &mut dyn RowSink,
+    ) -> Result<u64, DatabaseError> {
+        let opened: Value = self
+            .client
+            .open_point_in_time(OpenPointInTimeParts::Index(&[index]))
+            .keep_alive(POINT_IN_TIME_KEEP_ALIVE)
+            .send()
+            .await
+            .map_err(|e| DatabaseError::QueryError(format!("Failed to open a point in time: {e}")))?
+            .json()
+            .await
+            .map_err(|e| {
+                DatabaseError::QueryError(format!("Failed to parse point in time response: {e}"))
+            })?;
+        let Some(mut pit_id) = opened.get("id").and_then(Value::as_str).map(str::to_string) else {
+            return Err(DatabaseError::QueryError(format!(
+                "Failed to open a point in time on {index}: {}",
+                opened.get("error").unwrap_or(&opened)
+            )));
+        };
+
+        sink.columns(columns)?;
+        let result = self
+            .page_point_in_time(&mut pit_id, translated, fields, limit, sink)
+            .await;
+        if let Err(e) = self
+            .client
+            .close_point_in_time()
+            .body(json!({ "id": pit_id }))
+            .send()
+            .await
+        {
+            debug!("Failed to close the point in time: {}", e);
+        }
+        result
+    }
+
+    async fn page_point_in_time(
+        &self,
+        pit_id: &mut String,
+        translated: &Value,
+        fields: &[String],
+        limit: Option<u64>,
+        sink: &mut dyn RowSink,
+    ) -> Result<u64, DatabaseError> {
+        let interrupt = crate::database::interrupt_flag();
+        let mut request = translated.clone();
+        if request.get("sort").is_none() {
+            // Cheapest order for paging; points in time break ties on it anyway
+            request["sort"] = json!(["_shard_doc"]);
+        }
+        request["track_total_hits"] = json!(false);
+        let mut count = 0u64;
+        loop {
+            let size = limit.map_or(STREAM_PAGE_SIZE, |limit| {
+                limit.saturating_sub(count).min(STREAM_PAGE_SIZE)
+            });
+            if size == 0 {
+                return Ok(count);
+            }
+            if interrupt.load(std::sync::atomic::Ordering::Relaxed) {
+                return Err(DatabaseError::QueryError("Query cancelled".to_string()));
+            }
+            request["size"] = json!(size);
+            request["pit"] = json!({ "id": pit_id, "keep_alive": POINT_IN_TIME_KEEP_ALIVE });
+
+            let page: Value = self
+                .client
+                .search(SearchParts::None)
+                .body(request.clone())
+                .send()
+                .await
+                .map_err(|e| DatabaseError::QueryError(format!("Search failed: {e}")))?
+                .json()
+                .await
+                .map_err(|e| {
+                    DatabaseError::QueryError(format!("Failed to parse search response: {e}"))
+                })?;
+            if let Some(error) = page.get("error") {
+                return Err(DatabaseError::QueryError(Self::sql_error_message(error)));
+            }
+            // The id can change between pages; the latest one is closed
+            if let Some(id) = page.get("pit_id").and_then(Value::as_str) {
+                *pit_id = id.to_string();
+            }
+
+            let hits = page
+                .pointer("/hits/hits")
+                .and_then(Value::as_array)
+                .cloned()
+                .unwrap_or_default();
+            for hit in &hits {
+                sink.row(fields.iter().map(|field| hit_value(hit, field)).collect())?;
+            }
+            count += hits.len() as u64;
+            match hits.last().and_then(|hit| hit.get("sort")) {
+                Some(sort) if hits.len() as u64 == size => {
+                    request["search_after"] = sort.clone();
+                }
+                _ => return Ok(count),
+            }
+        }
+    }
+
     /// Format Elasticsearch values for display
     fn format_elasticsearch_value(&self, value: &Value) -> String {
         match value {
@@ -1294,6 +1652,68 @@ impl ElasticsearchClient {
     }
 }
 
+/// Rows fetched per page when streaming a query
+const STREAM_PAGE_SIZE: u64 = 1000;
+/// How long a point in time stays open between two pages
+const POINT_IN_TIME_KEEP_ALIVE: &str = "2m";
+
+/// Fields read by a translated search, in `SELECT` order, when it can be
+/// paged with a point in time: plain field reads through the `fields` option
+/// (Elasticsearch 8+), with no aggregation or script
+fn point_in_time_fields(translated: &Value) -> Option<Vec<String>> {
+    if translated.get("aggregations").is_some()
+        || translated.get("aggs").is_some()
+        || translated.get("script_fields").is_some()
+    {
+        return None;
+    }
+    let fields = translated
+        .get("fields")?
+        .as_array()?
+        .iter()
+        .map(|field| match field {
+            Value::String(name) => Some(name.clone()),
+            field => field.get("field")?.as_str().map(str::to_string),
+        })
+        .collect::<Option<Vec<_>>>()?;
+    (!fields.is_empty()).then_some(fields)
+}
+
+/// Column names of an SQL API response
+fn sql_column_names(page: &Value) -> Vec<String> {
+    page.get("columns")
+        .and_then(Value::as_array)
+        .map(|columns| {
+            columns
+                .iter()
+                .filter_map(|column| column.get("name").and_then(Value::as_str))
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Row limit of a statement: `SELECT TOP n` or a trailing `LIMIT n`
+fn sql_limit(sql: &str) -> Option<u64> {
+    static LIMIT: std::sync::OnceLock<Regex> = std::sync::OnceLock::new();
+    let captures = LIMIT
+        .get_or_init(|| {
+            Regex::new(r"(?i)^\s*SELECT\s+TOP\s+(\d+)|\bLIMIT\s+(\d+)\s*;?\s*$").unwrap()
+        })
+        .captures(sql)?;
+    captures.get(1).or(captures.get(2))?.as_str().parse().ok()
+}
+
+/// Value of `field` in a search hit: single values unwrapped, missing ones null
+fn hit_value(hit: &Value, field: &str) -> Value {
+    match hit.get("fields").and_then(|fields| fields.get(field)) {
+        Some(Value::Array(values)) if values.len() == 1 => values[0].clone(),
+        Some(Value::Array(values)) if values.is_empty() => Value::Null,
+        Some(value) => value.clone(),
+        None => Value::Null,
+    }
+}
+
 #[async_trait]
 impl DatabaseClient for ElasticsearchClient {
     async fn execute_query(&self, query: &str) -> Result<Vec<Vec<String>>, DatabaseError> {
@@ -1316,6 +1736,22 @@ impl DatabaseClient for ElasticsearchClient {
         }
     }
 
+    async fn stream_query(&self, sql: &str, sink: &mut dyn RowSink) -> Result<u64, DatabaseError> {
+        let sql = sql.trim();
+        if sql.to_uppercase().starts_with("SELECT") {
+            return self.stream_select(sql, sink).await;
+        }
+        let results = self.execute_query(sql).await?;
+        let Some((header, rows)) = results.split_first() else {
+            return Ok(0);
+        };
+        sink.columns(header)?;
+        for row in rows {
+            sink.row(row.iter().cloned().map(Value::String).collect())?;
+        }
+        Ok(rows.len() as u64)
+    }
+
     async fn test_query(&self, sql: &str) -> Result<(), DatabaseError> {
         debug!("[ElasticsearchClient::test_query] Testing query: {}", sql);
 
@@ -1479,6 +1915,42 @@ impl ComplexDisplayConfig {
             max_width: 200,
             full_show_numbers: false,
             json_pretty_print: true,
+            geometry_format: crate::complex_display::GeometryFormat::Wkt,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_point_in_time_fields_and_limit() {
+        let translated = json!({
+            "size": 1000,
+            "_source": false,
+            "fields": [{ "field": "user.name" }, { "field": "@timestamp", "format": "strict_date_optional_time_nanos" }],
+            "sort": [{ "_doc": { "order": "asc" } }]
+        });
+        assert_eq!(
+            point_in_time_fields(&translated).unwrap(),
+            vec!["user.name", "@timestamp"]
+        );
+        let grouped = json!({ "size": 0, "aggregations": { "groupby": {} } });
+        assert_eq!(point_in_time_fields(&grouped), None);
+        assert_eq!(point_in_time_fields(&json!({ "size": 10 })), None);
+
+        assert_eq!(sql_limit("SELECT a FROM logs LIMIT 25000;"), Some(25000));
+        assert_eq!(sql_limit("select top 5 a from logs"), Some(5));
+        assert_eq!(sql_limit("SELECT \"limit\" FROM logs"), None);
+    }
+
+    #[test]
+    fn test_hit_value() {
+        let hit = json!({ "fields": { "tags": ["a", "b"], "status": [200], "empty": [] } });
+        assert_eq!(hit_value(&hit, "status"), json!(200));
+        assert_eq!(hit_value(&hit, "tags"), json!(["a", "b"]));
+        assert_eq!(hit_value(&hit, "empty"), Value::Null);
+        assert_eq!(hit_value(&hit, "missing"), Value::Null);
+    }
+}
@@ -1,17 +1,24 @@
 //! MySQL implementation of the database abstraction layer
+use crate::charset::Charset;
 use crate::complex_display::{
     ArrayDisplayAdapter, ComplexDataDisplay, ComplexDataType, ComplexDisplayConfig,
     ComplexTypeDetector, GenericComplexTypeDetector,
 };
-use crate::database::{ConnectionInfo, DatabaseClient, DatabaseError, MetadataProvider};
+use crate::database::{
+    ConnectionInfo, DatabaseClient, DatabaseError, MetadataProvider, RowSink, ServerDiagnostic,
+    SizeSort, StatementOutcome,
+};
 use crate::db::TableDetails;
 use crate::geojson_display::GeoJsonDisplayAdapter;
 use crate::json_display::JsonDisplayAdapter;
 use crate::performance_analyzer::PerformanceAnalyzer;
+use crate::tls::{TlsMode, TlsSettings};
 use async_trait::async_trait;
-use sqlx::mysql::{MySqlPool, MySqlPoolOptions, MySqlRow};
+use sqlx::mysql::{MySqlConnectOptions, MySqlPool, MySqlPoolOptions, MySqlRow};
 use sqlx::types::Decimal;
 use sqlx::{Column, Row};
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
 use tracing::debug;
 
 /// MySQL metadata provider implementation
@@ -522,6 +529,76 @@ impl MetadataProvider for MySqlMetadataProvider {
             foreign_keys.push(fk_info);
         }
 
+        // Partitions, with subpartitions folded into their partition
+        let schema_filter = if schema.is_some() {
+            format!("'{schema_name}'")
+        } else {
+            "DATABASE()".to_string()
+        };
+        let partitions_query = format!(
+            r#"
+            SELECT
+                PARTITION_NAME AS name,
+                MAX(PARTITION_METHOD) AS method,
+                MAX(PARTITION_EXPRESSION) AS expression,
+                MAX(SUBPARTITION_METHOD) AS sub_method,
+                MAX(SUBPARTITION_EXPRESSION) AS sub_expression,
+                MAX(PARTITION_DESCRIPTION) AS description,
+                CAST(SUM(TABLE_ROWS) AS SIGNED) AS est_rows,
+                CAST(SUM(DATA_LENGTH + INDEX_LENGTH) AS SIGNED) AS bytes
+            FROM INFORMATION_SCHEMA.PARTITIONS
+            WHERE TABLE_SCHEMA = {schema_filter}
+              AND TABLE_NAME = '{table}'
+              AND PARTITION_NAME IS NOT NULL
+            GROUP BY PARTITION_NAME
+            ORDER BY MIN(PARTITION_ORDINAL_POSITION)
+            "#
+        );
+        let partition_rows = sqlx::query(&partitions_query).fetch_all(&self.pool).await?;
+
+        let partitioning = if partition_rows.is_empty() {
+            None
+        } else {
+            let text = |row: &sqlx::mysql::MySqlRow, column: &str| -> String {
+                if let Ok(Some(val)) = row.try_get::<Option<String>, _>(column) {
+                    val
+                } else if let Ok(Some(bytes)) = row.try_get::<Option<Vec<u8>>, _>(column) {
+                    String::from_utf8_lossy(&bytes).to_string()
+                } else {
+                    String::new()
+                }
+            };
+            let first = &partition_rows[0];
+            let method = text(first, "method");
+            let expression = text(first, "expression");
+            let mut scheme = format!("{method} ({expression})");
+            let mut key_columns = partition_key_columns(&expression);
+            let sub_method = text(first, "sub_method");
+            if !sub_method.is_empty() {
+                let sub_expression = text(first, "sub_expression");
+                scheme.push_str(&format!(" SUBPARTITION BY {sub_method} ({sub_expression})"));
+                for column in partition_key_columns(&sub_expression) {
+                    if !key_columns.contains(&column) {
+                        key_columns.push(column);
+                    }
+                }
+            }
+            let partitions = partition_rows
+                .iter()
+                .map(|row| crate::db::PartitionDetail {
+                    name: text(row, "name"),
+                    bound: partition_bound(&method, &text(row, "description")),
+                    rows: row.try_get::<Option<i64>, _>("est_rows").ok().flatten(),
+                    bytes: row.try_get::<Option<i64>, _>("bytes").ok().flatten(),
+                })
+                .collect();
+            Some(crate::db::PartitionInfo {
+                scheme,
+                key_columns,
+                partitions,
+            })
+        };
+
         let table_details = TableDetails {
             schema: schema.unwrap_or("default").to_string(),
             name: original_table.clone(),
@@ -536,6 +613,8 @@ impl MetadataProvider for MySqlMetadataProvider {
             foreign_keys,
             referenced_by: Vec::new(), // Would need complex query to find referencing tables
             nested_field_details: std::collections::HashMap::new(),
+            partitioning,
+            distribution: None,
         };
 
         debug!("[MySqlMetadataProvider::get_table_details] Table details retrieved successfully");
@@ -549,6 +628,57 @@ impl MetadataProvider for MySqlMetadataProvider {
     fn default_schema(&self) -> Option<String> {
         None // MySQL doesn't have a fixed default schema like SQLite's "main"
     }
+
+    async fn get_schema_table_counts(&self) -> Result<Vec<(String, usize)>, DatabaseError> {
+        let rows: Vec<(String, i64)> = sqlx::query_as(
+            r#"
+            SELECT CAST(s.SCHEMA_NAME AS CHAR), COUNT(t.TABLE_NAME)
+            FROM INFORMATION_SCHEMA.SCHEMATA s
+            LEFT JOIN INFORMATION_SCHEMA.TABLES t
+              ON t.TABLE_SCHEMA = s.SCHEMA_NAME AND t.TABLE_TYPE IN ('BASE TABLE', 'VIEW')
+            WHERE s.SCHEMA_NAME NOT IN ('information_schema', 'performance_schema', 'mysql', 'sys')
+            GROUP BY s.SCHEMA_NAME
+            ORDER BY s.SCHEMA_NAME
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|(schema, tables)| (schema, tables as usize))
+            .collect())
+    }
+}
+
+/// TLS settings as sqlx MySQL URL parameters. sqlx only verifies the host
+/// name in VERIFY_IDENTITY, so verify-full maps there and verify-ca to VERIFY_CA.
+fn mysql_tls_params(tls: &TlsSettings) -> Vec<String> {
+    use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+    let path = |path: &std::path::Path| {
+        utf8_percent_encode(&path.to_string_lossy(), NON_ALPHANUMERIC).to_string()
+    };
+
+    let mut params = Vec::new();
+    if tls.mode.is_some() || !tls.verify_hostname {
+        let mode = match tls.mode_or(TlsMode::Prefer) {
+            TlsMode::Disable => "DISABLED",
+            TlsMode::Allow | TlsMode::Prefer => "PREFERRED",
+            TlsMode::Require => "REQUIRED",
+            TlsMode::VerifyCa => "VERIFY_CA",
+            TlsMode::VerifyFull => "VERIFY_IDENTITY",
+        };
+        params.push(format!("ssl-mode={mode}"));
+    }
+    if let Some(root_cert) = &tls.root_cert {
+        params.push(format!("ssl-ca={}", path(root_cert)));
+    }
+    if let Some(client_cert) = &tls.client_cert {
+        params.push(format!("ssl-cert={}", path(client_cert)));
+    }
+    if let Some(client_key) = &tls.client_key {
+        params.push(format!("ssl-key={}", path(client_key)));
+    }
+    params
 }
 
 /// MySQL database client implementation
@@ -557,6 +687,17 @@ pub struct MySqlClient {
     connection_info: ConnectionInfo,
     current_database: String,
     metadata_provider: MySqlMetadataProvider,
+    /// `SET ROLE` statement from `\\setrole`, run on every connection taken
+    /// from the pool so the role holds across pooled connections
+    role_statement: Arc<RwLock<Option<String>>>,
+    /// `USE` statement from `\\c`/`\\schema`, run the same way
+    use_statement: Arc<RwLock<Option<String>>>,
+    /// Text that is not valid UTF-8 is decoded with it
+    charset: Charset,
+    /// Type names of the columns `execute_statement` last returned
+    last_column_types: std::sync::Mutex<Vec<String>>,
+    /// Renews the pool's password when it is an IAM token
+    _token_refresh: Option<crate::iam_auth::TokenRefresh>,
 }
 
 impl MySqlClient {
@@ -581,7 +722,18 @@ impl MySqlClient {
             .clone()
             .unwrap_or_else(|| "mysql".to_string());
 
-        let mut database_url = if let Some(password) = &connection_info.password {
+        // IAM tokens are full of URL metacharacters: they go on the options
+        let iam_token = matches!(
+            crate::iam_auth::provider(&connection_info.options),
+            Ok(Some(_))
+        )
+        .then(|| connection_info.password.clone())
+        .flatten();
+        let mut database_url = if let Some(password) = connection_info
+            .password
+            .as_ref()
+            .filter(|_| iam_token.is_none())
+        {
             format!("mysql://{username}:{password}@{connection_host}:{port}/{database}")
         } else {
             format!("mysql://{username}@{connection_host}:{port}/{database}")
@@ -601,16 +753,33 @@ impl MySqlClient {
             additional_params.push(format!("original_host={encoded_host}"));
         }
 
+        let charset = Charset::from_options(&connection_info.options)
+            .map_err(DatabaseError::ConnectionError)?;
+
         if !connection_info.options.is_empty() {
             let params: Vec<String> = connection_info
                 .options
                 .iter()
                 .filter(|(k, _)| !crate::config::is_dbcrust_internal_connection_option(k))
+                .filter(|(k, _)| !crate::tls::is_tls_option(k))
+                .filter(|(k, _)| !crate::iam_auth::is_auth_option(k))
+                // The session stays utf8mb4 and the server converts text to
+                // it; only a UTF-8 charset goes to sqlx
+                .filter(|(k, v)| {
+                    !crate::charset::is_charset_option(k)
+                        || (k.as_str() == "charset"
+                            && Charset::parse(v).is_ok_and(|charset| !charset.is_set()))
+                })
                 .map(|(k, v)| format!("{k}={v}"))
                 .collect();
             additional_params.extend(params);
         }
 
+        // TLS from sslmode/sslrootcert/... and [tls], in sqlx's MySQL spelling
+        let tls = TlsSettings::for_connection(&connection_info)
+            .map_err(DatabaseError::ConnectionError)?;
+        additional_params.extend(mysql_tls_params(&tls));
+
         if !additional_params.is_empty() {
             database_url.push('?');
             database_url.push_str(&additional_params.join("&"));
@@ -620,30 +789,99 @@ impl MySqlClient {
             "[MySqlClient::new] Connecting to: {}",
             crate::password_sanitizer::sanitize_connection_url(&database_url)
         );
+        let mut connect_options = MySqlConnectOptions::from_str(&database_url)
+            .map_err(|e| DatabaseError::ConnectionError(e.to_string()))?;
+        if let Some(token) = &iam_token {
+            // RDS, Cloud SQL and Azure check tokens with mysql_clear_password
+            connect_options = connect_options
+                .password(token)
+                .enable_cleartext_plugin(true);
+        }
 
         // Configure connection pool with MySQL-specific optimizations
+        let role_statement: Arc<RwLock<Option<String>>> = Arc::default();
+        let use_statement: Arc<RwLock<Option<String>>> = Arc::default();
+        let acquire_role = role_statement.clone();
+        let acquire_use = use_statement.clone();
         let pool = MySqlPoolOptions::new()
             .max_connections(10) // Same as PostgreSQL for consistency
             .min_connections(1)
             .acquire_timeout(std::time::Duration::from_secs(10))
             .idle_timeout(std::time::Duration::from_secs(600)) // Keep connections alive longer
-            .connect(&database_url)
+            .before_acquire(move |conn, _| {
+                let statements = [
+                    acquire_use.read().unwrap().clone(),
+                    acquire_role.read().unwrap().clone(),
+                ];
+                Box::pin(async move {
+                    for statement in statements.into_iter().flatten() {
+                        sqlx::Executor::execute(&mut *conn, statement.as_str()).await?;
+                    }
+                    Ok(true)
+                })
+            })
+            .connect_with(connect_options)
             .await
             .map_err(|e| DatabaseError::ConnectionError(e.to_string()))?;
 
+        if tls.min_version.is_some() {
+            let negotiated: Option<(String, String)> =
+                sqlx::query_as("SHOW SESSION STATUS LIKE 'Ssl_version'")
+                    .fetch_optional(&pool)
+                    .await
+                    .map_err(|e| DatabaseError::ConnectionError(e.to_string()))?;
+            tls.check_negotiated(negotiated.as_ref().map(|(_, version)| version.as_str()))
+                .map_err(DatabaseError::ConnectionError)?;
+        }
+
         // Apply MySQL-specific optimizations
         Self::apply_mysql_optimizations(&pool).await?;
 
+        // IAM tokens expire long before idle connections are replaced
+        let token_refresh = crate::iam_auth::keep_fresh(&connection_info, {
+            let pool = pool.clone();
+            move |token| {
+                pool.set_connect_options((*pool.connect_options()).clone().password(&token))
+            }
+        });
+
         let metadata_provider = MySqlMetadataProvider::new(pool.clone());
 
+        let charset = if charset.is_set() {
+            charset
+        } else {
+            Self::detect_charset(&pool).await
+        };
+        debug!(
+            "[MySqlClient::new] Decoding non-UTF-8 text as {}",
+            charset.name()
+        );
+
         Ok(Self {
             pool,
             connection_info,
             current_database: database,
             metadata_provider,
+            role_statement,
+            use_statement,
+            charset,
+            last_column_types: Default::default(),
+            _token_refresh: token_refresh,
         })
     }
 
+    /// The database's default character set, which legacy clients that
+    /// skip `SET NAMES` usually wrote their text in
+    async fn detect_charset(pool: &MySqlPool) -> Charset {
+        let name: Option<String> = sqlx::query_scalar("SELECT @@character_set_database")
+            .fetch_optional(pool)
+            .await
+            .ok()
+            .flatten();
+        name.and_then(|name| Charset::parse(&name).ok())
+            .unwrap_or_default()
+    }
+
     /// Apply MySQL-specific performance optimizations
     async fn apply_mysql_optimizations(pool: &MySqlPool) -> Result<(), DatabaseError> {
         debug!("[MySqlClient] Applying MySQL optimizations");
@@ -899,6 +1137,131 @@ impl MySqlClient {
     }
 }
 
+/// Sessions for `\processlist`; active sessions first, longest running on top
+const PROCESSLIST_QUERY: &str = r#"
+    SELECT ID AS id, USER AS user, HOST AS host, IFNULL(DB, '') AS db,
+           COMMAND AS command, TIME AS seconds, IFNULL(STATE, '') AS state,
+           IFNULL(LEFT(REPLACE(INFO, '\n', ' '), 120), '') AS query
+    FROM information_schema.PROCESSLIST
+    ORDER BY COMMAND = 'Sleep', TIME DESC
+"#;
+
+/// InnoDB lock waits through the sys schema (MySQL 5.7+)
+const LOCK_WAITS_QUERY: &str = r#"
+    SELECT waiting_pid AS waiting_id, wait_age_secs AS waited_s,
+           IFNULL(LEFT(waiting_query, 80), '') AS waiting_query,
+           locked_table, IFNULL(locked_index, '') AS locked_index, locked_type AS lock_type,
+           blocking_pid AS blocking_id, IFNULL(LEFT(blocking_query, 80), '') AS blocking_query,
+           blocking_trx_age
+    FROM sys.innodb_lock_waits
+    ORDER BY wait_age_secs DESC
+"#;
+
+/// Same report from information_schema, for MariaDB and servers without sys
+const LOCK_WAITS_FALLBACK_QUERY: &str = r#"
+    SELECT r.trx_mysql_thread_id AS waiting_id,
+           TIMESTAMPDIFF(SECOND, r.trx_wait_started, NOW()) AS waited_s,
+           IFNULL(LEFT(r.trx_query, 80), '') AS waiting_query,
+           l.lock_table AS locked_table, IFNULL(l.lock_index, '') AS locked_index,
+           l.lock_type AS lock_type,
+           b.trx_mysql_thread_id AS blocking_id,
+           IFNULL(LEFT(b.trx_query, 80), '') AS blocking_query,
+           SEC_TO_TIME(TIMESTAMPDIFF(SECOND, b.trx_started, NOW())) AS blocking_trx_age
+    FROM information_schema.INNODB_LOCK_WAITS w
+    JOIN information_schema.INNODB_TRX r ON r.trx_id = w.requesting_trx_id
+    JOIN information_schema.INNODB_TRX b ON b.trx_id = w.blocking_trx_id
+    JOIN information_schema.INNODB_LOCKS l ON l.lock_id = w.requested_lock_id
+    ORDER BY waited_s DESC
+"#;
+
+/// Columns named in a `PARTITION_EXPRESSION`: the backtick-quoted names, or
+/// the expression itself when it is a bare column
+fn partition_key_columns(expression: &str) -> Vec<String> {
+    let mut columns: Vec<String> = Vec::new();
+    for (i, part) in expression.split('`').enumerate() {
+        if i % 2 == 1 && !part.is_empty() && !columns.iter().any(|c| c == part) {
+            columns.push(part.to_string());
+        }
+    }
+    if columns.is_empty() {
+        let bare = expression.trim();
+        if !bare.is_empty() && bare.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            columns.push(bare.to_string());
+        }
+    }
+    columns
+}
+
+/// `VALUES LESS THAN (…)` / `VALUES IN (…)` for RANGE and LIST partitions;
+/// HASH and KEY partitions have no bound
+fn partition_bound(method: &str, description: &str) -> String {
+    if description.is_empty() {
+        String::new()
+    } else if method.starts_with("RANGE") {
+        if description == "MAXVALUE" {
+            "VALUES LESS THAN MAXVALUE".to_string()
+        } else {
+            format!("VALUES LESS THAN ({description})")
+        }
+    } else if method.starts_with("LIST") {
+        format!("VALUES IN ({description})")
+    } else {
+        String::new()
+    }
+}
+
+/// Every schema with the data and index size of its tables
+const DATABASE_SIZES_QUERY: &str = r#"
+    SELECT s.SCHEMA_NAME AS `database`,
+           CAST(COALESCE(SUM(t.DATA_LENGTH + t.INDEX_LENGTH), 0) AS SIGNED) AS size,
+           CASE WHEN s.SCHEMA_NAME = DATABASE() THEN '*' ELSE '' END AS current
+    FROM information_schema.SCHEMATA s
+    LEFT JOIN information_schema.TABLES t ON t.TABLE_SCHEMA = s.SCHEMA_NAME
+    GROUP BY s.SCHEMA_NAME
+    ORDER BY size DESC, s.SCHEMA_NAME
+"#;
+
+/// Largest tables of the current database, or of every user database when
+/// none is selected. Sizes and row counts are InnoDB's estimates.
+fn relation_sizes_query(limit: usize, sort: SizeSort) -> String {
+    format!(
+        r#"
+        SELECT TABLE_SCHEMA AS `schema`, TABLE_NAME AS `table`,
+               TABLE_ROWS AS `rows`,
+               CAST(DATA_LENGTH AS SIGNED) AS table_size,
+               CAST(INDEX_LENGTH AS SIGNED) AS index_size,
+               CAST(DATA_LENGTH + INDEX_LENGTH AS SIGNED) AS total_size
+        FROM information_schema.TABLES
+        WHERE TABLE_TYPE = 'BASE TABLE'
+          AND TABLE_SCHEMA NOT IN ('mysql', 'information_schema', 'performance_schema', 'sys')
+          AND (DATABASE() IS NULL OR TABLE_SCHEMA = DATABASE())
+        ORDER BY `{}` DESC, 1, 2
+        LIMIT {limit}
+        "#,
+        sort.column()
+    )
+}
+
+/// Top statements by total latency from the digest summary (`\slowqueries`)
+fn top_statements_query(limit: usize) -> String {
+    format!(
+        r#"
+        SELECT IFNULL(SCHEMA_NAME, '') AS db,
+               LEFT(DIGEST_TEXT, 120) AS query,
+               COUNT_STAR AS calls,
+               ROUND(SUM_TIMER_WAIT / 1e12, 3) AS total_s,
+               ROUND(AVG_TIMER_WAIT / 1e9, 3) AS avg_ms,
+               ROUND(MAX_TIMER_WAIT / 1e9, 3) AS max_ms,
+               SUM_ROWS_EXAMINED AS rows_examined,
+               SUM_ROWS_SENT AS rows_sent
+        FROM performance_schema.events_statements_summary_by_digest
+        WHERE DIGEST_TEXT IS NOT NULL
+        ORDER BY SUM_TIMER_WAIT DESC
+        LIMIT {limit}
+        "#
+    )
+}
+
 #[async_trait]
 impl DatabaseClient for MySqlClient {
     async fn execute_query(&self, sql: &str) -> Result<Vec<Vec<String>>, DatabaseError> {
@@ -913,22 +1276,11 @@ impl DatabaseClient for MySqlClient {
         let mut results = Vec::new();
 
         // Get column names from the first row
-        let first_row = &rows[0];
-        let column_names: Vec<String> = (0..first_row.len())
-            .map(|i| first_row.column(i).name().to_string())
-            .collect();
-
-        results.push(column_names);
+        results.push(mysql_column_names(&rows[0]));
 
         // Convert rows to strings with complex display formatting
-        for row in rows {
-            let mut string_row = Vec::new();
-            for i in 0..row.len() {
-                let column_name = row.column(i).name();
-                let value = format_mysql_value_with_complex_display(&row, i, column_name)?;
-                string_row.push(value);
-            }
-            results.push(string_row);
+        for row in &rows {
+            results.push(mysql_row_strings(row, &self.charset)?);
         }
 
         debug!(
@@ -938,6 +1290,140 @@ impl DatabaseClient for MySqlClient {
         Ok(results)
     }
 
+    async fn execute_query_sets(&self, sql: &str) -> Result<Vec<Vec<Vec<String>>>, DatabaseError> {
+        if crate::db::leading_sql_keyword(sql).as_deref() != Some("call") {
+            return Ok(vec![self.execute_query(sql).await?]);
+        }
+        debug!("[MySqlClient::execute_query_sets] Collecting procedure result sets");
+
+        // The prepared-statement path flattens every result set into one row
+        // stream; the text protocol reports where each set ends
+        use futures_util::TryStreamExt;
+        let mut stream = sqlx::raw_sql(sql).fetch_many(&self.pool);
+        let mut sets = Vec::new();
+        let mut current: Vec<Vec<String>> = Vec::new();
+        while let Some(item) = stream.try_next().await? {
+            match item {
+                sqlx::Either::Left(_) => {
+                    if !current.is_empty() {
+                        sets.push(std::mem::take(&mut current));
+                    }
+                }
+                sqlx::Either::Right(row) => {
+                    if current.is_empty() {
+                        current.push(mysql_column_names(&row));
+                    }
+                    current.push(mysql_row_strings(&row, &self.charset)?);
+                }
+            }
+        }
+        if !current.is_empty() {
+            sets.push(current);
+        }
+        if sets.is_empty() {
+            sets.push(Vec::new());
+        }
+        Ok(sets)
+    }
+
+    fn last_column_types(&self) -> Vec<String> {
+        self.last_column_types
+            .lock()
+            .map(|types| types.clone())
+            .unwrap_or_default()
+    }
+
+    async fn execute_statement(
+        &self,
+        sql: &str,
+    ) -> Result<(Vec<Vec<Vec<String>>>, Option<StatementOutcome>), DatabaseError> {
+        if crate::db::leading_sql_keyword(sql).as_deref() == Some("call") {
+            return Ok((self.execute_query_sets(sql).await?, None));
+        }
+
+        use futures_util::TryStreamExt;
+        let mut stream = sqlx::Executor::fetch_many(&self.pool, sqlx::query(sql));
+        let mut results = Vec::new();
+        let mut outcome = StatementOutcome::default();
+        let mut types = Vec::new();
+        while let Some(step) = stream.try_next().await? {
+            match step {
+                sqlx::Either::Left(done) => {
+                    outcome.rows_affected += done.rows_affected();
+                    // 0 when the statement generated no AUTO_INCREMENT value
+                    if done.last_insert_id() > 0 {
+                        outcome.last_insert_id = i64::try_from(done.last_insert_id()).ok();
+                    }
+                }
+                sqlx::Either::Right(row) => {
+                    if results.is_empty() {
+                        results.push(mysql_column_names(&row));
+                        types = row
+                            .columns()
+                            .iter()
+                            .map(|c| sqlx::TypeInfo::name(c.type_info()).to_string())
+                            .collect();
+                    }
+                    results.push(mysql_row_strings(&row, &self.charset)?);
+                }
+            }
+        }
+        if let Ok(mut last) = self.last_column_types.lock() {
+            *last = types;
+        }
+        if results.is_empty() {
+            Ok((vec![results], Some(outcome)))
+        } else {
+            Ok((vec![results], None))
+        }
+    }
+
+    async fn stream_query(&self, sql: &str, sink: &mut dyn RowSink) -> Result<u64, DatabaseError> {
+        debug!("[MySqlClient::stream_query] Streaming query");
+        let rows = sqlx::query(sql).fetch(&self.pool);
+        crate::database::drain_row_stream(rows, sink, |row, i| {
+            mysql_json_value(row, i, &self.charset)
+        })
+        .await
+    }
+
+    async fn run_in_transaction(
+        &self,
+        statements: &[String],
+        progress: &(dyn Fn(usize) + Send + Sync),
+    ) -> Result<(), DatabaseError> {
+        // DDL commits implicitly in MySQL, so only the data is rolled back on failure
+        let mut tx = self.pool.begin().await?;
+        for (done, statement) in statements.iter().enumerate() {
+            if crate::database::interrupt_flag().load(std::sync::atomic::Ordering::Relaxed) {
+                return Err(DatabaseError::QueryError("Restore cancelled".to_string()));
+            }
+            // A bare string without arguments goes over the text protocol,
+            // where SET and DDL that cannot be prepared also run
+            sqlx::Executor::execute(&mut *tx, statement.as_str()).await?;
+            progress(done + 1);
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn set_role(&self, role: Option<&str>) -> Result<(), DatabaseError> {
+        // The server rejects roles that do not exist or are not granted
+        let statement = match role {
+            Some(role) => format!("SET ROLE `{}`", role.replace('`', "``")),
+            None => "SET ROLE DEFAULT".to_string(),
+        };
+        let mut conn = self.pool.acquire().await?;
+        sqlx::Executor::execute(&mut *conn, statement.as_str()).await?;
+        *self.role_statement.write().unwrap() = Some(statement);
+        Ok(())
+    }
+
+    async fn set_schema(&mut self, schema: &str) -> Result<(), DatabaseError> {
+        // MySQL schemas are databases
+        self.connect_to_database(schema).await
+    }
+
     async fn test_query(&self, sql: &str) -> Result<(), DatabaseError> {
         debug!("[MySqlClient::test_query] Testing query for validation");
         // For MySQL, we can use EXPLAIN to validate query syntax without executing it
@@ -1097,9 +1583,12 @@ impl DatabaseClient for MySqlClient {
             database
         );
 
-        // Execute USE statement to change database
-        let use_query = format!("USE `{database}`");
-        sqlx::query(&use_query).execute(&self.pool).await?;
+        // Pooled connections each keep their own default database, so the
+        // USE runs again on every connection taken from the pool
+        let use_query = format!("USE `{}`", database.replace('`', "``"));
+        let mut conn = self.pool.acquire().await?;
+        sqlx::Executor::execute(&mut *conn, use_query.as_str()).await?;
+        *self.use_statement.write().unwrap() = Some(use_query);
 
         self.current_database = database.to_string();
 
@@ -1123,6 +1612,47 @@ impl DatabaseClient for MySqlClient {
         (sqlx::query("SELECT 1").fetch_one(&self.pool).await).is_ok()
     }
 
+    async fn server_diagnostic(
+        &self,
+        diagnostic: ServerDiagnostic,
+    ) -> Result<Vec<Vec<String>>, DatabaseError> {
+        debug!("[MySqlClient::server_diagnostic] Running {diagnostic}");
+        match diagnostic {
+            ServerDiagnostic::Sessions => self.execute_query(PROCESSLIST_QUERY).await,
+            ServerDiagnostic::LockWaits => match self.execute_query(LOCK_WAITS_QUERY).await {
+                Ok(results) => Ok(results),
+                Err(e) => {
+                    debug!("[MySqlClient::server_diagnostic] sys schema unavailable: {e}");
+                    self.execute_query(LOCK_WAITS_FALLBACK_QUERY).await
+                }
+            },
+            ServerDiagnostic::TopStatements { limit } => {
+                let enabled = self
+                    .execute_query("SELECT @@performance_schema")
+                    .await?
+                    .get(1)
+                    .and_then(|row| row.first().cloned())
+                    .unwrap_or_default();
+                if !matches!(enabled.as_str(), "1" | "ON" | "true") {
+                    return Err(DatabaseError::QueryError(
+                        "performance_schema is disabled; set performance_schema=ON in the server configuration and restart".to_string(),
+                    ));
+                }
+                self.execute_query(&top_statements_query(limit)).await
+            }
+            ServerDiagnostic::DatabaseSizes => self.execute_query(DATABASE_SIZES_QUERY).await,
+            ServerDiagnostic::RelationSizes { limit, sort } => {
+                self.execute_query(&relation_sizes_query(limit, sort)).await
+            }
+            ServerDiagnostic::Bloat { .. } | ServerDiagnostic::Clusters => {
+                Err(DatabaseError::FeatureNotSupported {
+                    database_type: self.connection_info.database_type.clone(),
+                    feature: diagnostic.to_string(),
+                })
+            }
+        }
+    }
+
     async fn close(&mut self) -> Result<(), DatabaseError> {
         debug!("[MySqlClient::close] Closing MySQL connection");
         self.pool.close().await;
@@ -1195,13 +1725,67 @@ impl DatabaseClient for MySqlClient {
     }
 }
 
+/// Typed value for exports: numbers, booleans and JSON stay native, DECIMAL
+/// and everything else is the raw string (no complex display formatting).
+fn mysql_json_value(row: &MySqlRow, column_index: usize, charset: &Charset) -> serde_json::Value {
+    use serde_json::Value;
+    use sqlx::{TypeInfo, ValueRef};
+
+    if row
+        .try_get_raw(column_index)
+        .map(|raw| raw.is_null())
+        .unwrap_or(false)
+    {
+        return Value::Null;
+    }
+    let type_name = row.column(column_index).type_info().name().to_uppercase();
+    let typed = match type_name.as_str() {
+        "BOOLEAN" => row.try_get::<bool, _>(column_index).ok().map(Value::from),
+        "TINYINT" | "SMALLINT" | "MEDIUMINT" | "INT" | "BIGINT" => {
+            row.try_get::<i64, _>(column_index).ok().map(Value::from)
+        }
+        "TINYINT UNSIGNED" | "SMALLINT UNSIGNED" | "MEDIUMINT UNSIGNED" | "INT UNSIGNED"
+        | "BIGINT UNSIGNED" | "YEAR" => row.try_get::<u64, _>(column_index).ok().map(Value::from),
+        "FLOAT" => row
+            .try_get::<f32, _>(column_index)
+            .ok()
+            .and_then(|v| serde_json::Number::from_f64(v as f64).map(Value::Number)),
+        "DOUBLE" => row
+            .try_get::<f64, _>(column_index)
+            .ok()
+            .and_then(|v| serde_json::Number::from_f64(v).map(Value::Number)),
+        "JSON" => row.try_get::<Value, _>(column_index).ok(),
+        _ => None,
+    };
+    typed.unwrap_or_else(|| {
+        Value::String(
+            format_mysql_value(row, column_index, charset)
+                .unwrap_or_else(|_| "?error?".to_string()),
+        )
+    })
+}
+
 /// Format a MySQL value to string representation with complex display support
+fn mysql_column_names(row: &MySqlRow) -> Vec<String> {
+    row.columns()
+        .iter()
+        .map(|column| column.name().to_string())
+        .collect()
+}
+
+fn mysql_row_strings(row: &MySqlRow, charset: &Charset) -> Result<Vec<String>, DatabaseError> {
+    (0..row.len())
+        .map(|i| format_mysql_value_with_complex_display(row, i, row.column(i).name(), charset))
+        .collect()
+}
+
 fn format_mysql_value_with_complex_display(
     row: &MySqlRow,
     column_index: usize,
     column_name: &str,
+    charset: &Charset,
 ) -> Result<String, DatabaseError> {
-    let raw_value = format_mysql_value(row, column_index)?;
+    let raw_value = format_mysql_value(row, column_index, charset)?;
 
     // Check if this value should use complex display
     if GenericComplexTypeDetector::should_use_complex_display(column_name, &raw_value) {
@@ -1288,7 +1872,11 @@ fn format_complex_value(value: &str, data_type: ComplexDataType) -> String {
 }
 
 /// Format a MySQL value to string representation
-fn format_mysql_value(row: &MySqlRow, column_index: usize) -> Result<String, DatabaseError> {
+fn format_mysql_value(
+    row: &MySqlRow,
+    column_index: usize,
+    charset: &Charset,
+) -> Result<String, DatabaseError> {
     use sqlx::TypeInfo;
     use sqlx::ValueRef;
 
@@ -1389,6 +1977,14 @@ fn format_mysql_value(row: &MySqlRow, column_index: usize) -> Result<String, Dat
 
     // Try as bytes for timestamp/date fields (MySQL sometimes returns these as bytes)
     if let Ok(bytes) = row.try_get::<Vec<u8>, _>(column_index) {
+        // Text that is not valid UTF-8, stored by a client that sent another
+        // encoding than the column's
+        if matches!(
+            type_info.name(),
+            "CHAR" | "VARCHAR" | "TINYTEXT" | "TEXT" | "MEDIUMTEXT" | "LONGTEXT" | "ENUM" | "SET"
+        ) {
+            return Ok(charset.decode(&bytes));
+        }
         if let Ok(timestamp_str) = String::from_utf8(bytes.clone()) {
             // If it looks like a timestamp, return it as-is
             if timestamp_str.contains("-")
@@ -1456,6 +2052,7 @@ mod tests {
             options: HashMap::new(),
             docker_container: None,
             use_tls: false,
+            srv: false,
         };
 
         // This test validates the connection info parsing without actual connection
@@ -1487,4 +2084,31 @@ mod tests {
             assert!(func.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'));
         }
     }
+
+    #[test]
+    fn test_partition_key_and_bounds() {
+        assert_eq!(partition_key_columns("year(`created`)"), vec!["created"]);
+        assert_eq!(
+            partition_key_columns("`region`,`day`,`region`"),
+            vec!["region", "day"]
+        );
+        assert_eq!(partition_key_columns("id"), vec!["id"]);
+        assert!(partition_key_columns("to_days(created)").is_empty());
+
+        assert_eq!(
+            partition_bound("RANGE COLUMNS", "'2024-01-01'"),
+            "VALUES LESS THAN ('2024-01-01')"
+        );
+        assert_eq!(
+            partition_bound("RANGE", "MAXVALUE"),
+            "VALUES LESS THAN MAXVALUE"
+        );
+        assert_eq!(partition_bound("LIST", "1,2"), "VALUES IN (1,2)");
+        assert_eq!(partition_bound("HASH", ""), "");
+    }
+
+    #[test]
+    fn test_top_statements_query_applies_limit() {
+        assert!(top_statements_query(5).trim_end().ends_with("LIMIT 5"));
+    }
 }
@@ -1,19 +1,105 @@
 //! PostgreSQL implementation of the database abstraction layer
+use crate::charset::Charset;
 use crate::complex_display::{
     ArrayDisplayAdapter, ComplexDataDisplay, ComplexDataType, ComplexTypeDetector,
     GenericComplexTypeDetector,
 };
-use crate::database::{ConnectionInfo, DatabaseClient, DatabaseError, MetadataProvider};
+use crate::database::{
+    ConnectionInfo, DatabaseClient, DatabaseError, MetadataProvider, RowSink, ServerDiagnostic,
+    SizeSort, StatementOutcome, quote_sql_ident,
+};
 use crate::db::TableDetails;
-use crate::geojson_display::GeoJsonDisplayAdapter;
+use crate::geojson_display::{GeoJsonDisplayAdapter, Geometry, GeometryDisplayAdapter};
 use crate::json_display::JsonDisplayAdapter;
 use crate::performance_analyzer::PerformanceAnalyzer;
+use crate::tls::{TlsMode, TlsSettings};
 use async_trait::async_trait;
 use serde_json;
-use sqlx::postgres::{PgPool, PgPoolOptions, PgRow};
+use sqlx::postgres::{PgDatabaseError, PgErrorPosition, PgPool, PgPoolOptions, PgRow};
 use sqlx::{Column, Row, TypeInfo};
 use tracing::{debug, warn};
 
+/// Typed value for exports: numbers, booleans, JSON and common arrays stay
+/// native and vectors become number arrays; everything else is the display
+/// string.
+fn postgresql_json_value(row: &PgRow, column_index: usize, charset: &Charset) -> serde_json::Value {
+    use serde_json::Value;
+    use sqlx::ValueRef;
+
+    if row
+        .try_get_raw(column_index)
+        .map(|raw| raw.is_null())
+        .unwrap_or(false)
+    {
+        return Value::Null;
+    }
+    let float = |v: f64| serde_json::Number::from_f64(v).map(Value::Number);
+    let type_name = row.column(column_index).type_info().name().to_uppercase();
+    let typed = match type_name.as_str() {
+        "BOOL" => row.try_get::<bool, _>(column_index).ok().map(Value::from),
+        "INT2" => row.try_get::<i16, _>(column_index).ok().map(Value::from),
+        "INT4" => row.try_get::<i32, _>(column_index).ok().map(Value::from),
+        "INT8" => row.try_get::<i64, _>(column_index).ok().map(Value::from),
+        "FLOAT4" => row
+            .try_get::<f32, _>(column_index)
+            .ok()
+            .and_then(|v| float(v as f64)),
+        "FLOAT8" => row.try_get::<f64, _>(column_index).ok().and_then(float),
+        "JSON" | "JSONB" => row.try_get::<Value, _>(column_index).ok(),
+        "INT2[]" => row
+            .try_get::<Vec<Option<i16>>, _>(column_index)
+            .ok()
+            .map(Value::from),
+        "INT4[]" => row
+            .try_get::<Vec<Option<i32>>, _>(column_index)
+            .ok()
+            .map(Value::from),
+        "INT8[]" => row
+            .try_get::<Vec<Option<i64>>, _>(column_index)
+            .ok()
+            .map(Value::from),
+        "TEXT[]" | "VARCHAR[]" => row
+            .try_get::<Vec<Option<String>>, _>(column_index)
+            .ok()
+            .map(Value::from),
+        "BOOL[]" => row
+            .try_get::<Vec<Option<bool>>, _>(column_index)
+            .ok()
+            .map(Value::from),
+        "VECTOR" => row
+            .try_get::<pgvector::Vector, _>(column_index)
+            .ok()
+            .map(|v| Value::from(v.to_vec())),
+        "GEOMETRY" | "GEOGRAPHY" => postgis_geometry(row, column_index).map(|g| g.to_geojson()),
+        _ => None,
+    };
+    typed.unwrap_or_else(|| {
+        Value::String(
+            format_postgresql_value(row, column_index, charset)
+                .unwrap_or_else(|_| "?error?".to_string()),
+        )
+    })
+}
+
+/// Decode a PostGIS `geometry`/`geography` column: EWKB bytes in the binary
+/// protocol, hex EWKB in text results
+fn postgis_geometry(row: &PgRow, column_index: usize) -> Option<Geometry> {
+    use sqlx::ValueRef;
+    use sqlx::postgres::PgValueFormat;
+
+    let raw = row.try_get_raw(column_index).ok()?;
+    if raw.is_null() {
+        return None;
+    }
+    let decoded = match raw.format() {
+        PgValueFormat::Binary => crate::geojson_display::decode_wkb(raw.as_bytes().ok()?),
+        PgValueFormat::Text => crate::geojson_display::decode_hex_wkb(raw.as_str().ok()?),
+    };
+    decoded
+        .map_err(|e| debug!("[PostgreSQL] Could not decode PostGIS value: {}", e))
+        .ok()
+}
+
 /// Check if a type name is a built-in PostgreSQL type
 fn is_builtin_postgresql_type(type_name: &str) -> bool {
     // Common PostgreSQL built-in types
@@ -85,11 +171,25 @@ fn is_builtin_postgresql_type(type_name: &str) -> bool {
 /// PostgreSQL metadata provider implementation
 pub struct PostgreSQLMetadataProvider {
     pool: PgPool,
+    /// Schema chosen with `\\schema`; unqualified lookups use it instead of `public`
+    schema: std::sync::Arc<std::sync::RwLock<Option<String>>>,
 }
 
 impl PostgreSQLMetadataProvider {
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            pool,
+            schema: Default::default(),
+        }
+    }
+
+    /// Schema unqualified names resolve to
+    fn current_schema(&self) -> String {
+        self.schema
+            .read()
+            .unwrap()
+            .clone()
+            .unwrap_or_else(|| "public".to_string())
     }
 
     /// Get detailed column information including data types, nullability, and defaults
@@ -98,7 +198,8 @@ impl PostgreSQLMetadataProvider {
         table: &str,
         schema: Option<&str>,
     ) -> Result<Vec<crate::db::ColumnInfo>, DatabaseError> {
-        let schema_name = schema.unwrap_or("public");
+        let default_schema = self.current_schema();
+        let schema_name = schema.unwrap_or(&default_schema);
 
         let rows = sqlx::query(
             r#"
@@ -426,6 +527,83 @@ impl PostgreSQLMetadataProvider {
 
         Ok(referenced_by)
     }
+
+    /// Partition key and partitions of a declaratively partitioned table;
+    /// `None` for other tables. Sub-partitioned partitions report the rows
+    /// and size of their leaves.
+    async fn get_table_partitioning(
+        &self,
+        table: &str,
+        schema: &str,
+    ) -> Result<Option<crate::db::PartitionInfo>, DatabaseError> {
+        let key: Option<(i64, String, Vec<String>)> = sqlx::query_as(
+            r#"
+            SELECT c.oid::bigint,
+                   pg_get_partkeydef(c.oid),
+                   ARRAY(
+                       SELECT a.attname::text
+                       FROM unnest(pt.partattrs::int2[]) WITH ORDINALITY AS k(attnum, ord)
+                       JOIN pg_attribute a ON a.attrelid = c.oid AND a.attnum = k.attnum
+                       ORDER BY k.ord
+                   )
+            FROM pg_partitioned_table pt
+            JOIN pg_class c ON c.oid = pt.partrelid
+            JOIN pg_namespace n ON n.oid = c.relnamespace
+            WHERE n.nspname = $1 AND c.relname = $2
+            "#,
+        )
+        .bind(schema)
+        .bind(table)
+        .fetch_optional(&self.pool)
+        .await?;
+        let Some((oid, scheme, key_columns)) = key else {
+            return Ok(None);
+        };
+
+        let rows = sqlx::query(
+            r#"
+            SELECT CASE WHEN n.nspname = $2 THEN c.relname::text
+                        ELSE n.nspname || '.' || c.relname END AS name,
+                   pg_get_expr(c.relpartbound, c.oid)
+                       || CASE WHEN c.relkind = 'p' THEN ', PARTITIONED' ELSE '' END AS bound,
+                   leaves.est_rows,
+                   leaves.bytes
+            FROM pg_inherits i
+            JOIN pg_class c ON c.oid = i.inhrelid
+            JOIN pg_namespace n ON n.oid = c.relnamespace
+            CROSS JOIN LATERAL (
+                SELECT CASE WHEN bool_and(l.reltuples < 0) THEN NULL
+                            ELSE sum(greatest(l.reltuples, 0))::bigint END AS est_rows,
+                       sum(pg_total_relation_size(l.oid))::bigint AS bytes
+                FROM pg_partition_tree(c.oid) t
+                JOIN pg_class l ON l.oid = t.relid
+                WHERE t.isleaf
+            ) leaves
+            WHERE i.inhparent = $1::bigint::oid
+            ORDER BY pg_get_expr(c.relpartbound, c.oid) = 'DEFAULT', c.relname
+            "#,
+        )
+        .bind(oid)
+        .bind(schema)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let partitions = rows
+            .iter()
+            .map(|row| crate::db::PartitionDetail {
+                name: row.get("name"),
+                bound: row.get::<Option<String>, _>("bound").unwrap_or_default(),
+                rows: row.get("est_rows"),
+                bytes: row.get("bytes"),
+            })
+            .collect();
+
+        Ok(Some(crate::db::PartitionInfo {
+            scheme,
+            key_columns,
+            partitions,
+        }))
+    }
 }
 
 #[async_trait]
@@ -460,7 +638,8 @@ impl MetadataProvider for PostgreSQLMetadataProvider {
             schema
         );
 
-        let query = if let Some(schema_name) = schema {
+        let selected = self.schema.read().unwrap().clone();
+        let query = if let Some(schema_name) = schema.or(selected.as_deref()) {
             sqlx::query(
                 r#"
                 SELECT c.relname as table_name
@@ -506,7 +685,8 @@ impl MetadataProvider for PostgreSQLMetadataProvider {
             table, schema
         );
 
-        let schema_name = schema.unwrap_or("public");
+        let default_schema = self.current_schema();
+        let schema_name = schema.unwrap_or(&default_schema);
 
         let rows = sqlx::query(
             r#"
@@ -587,7 +767,8 @@ impl MetadataProvider for PostgreSQLMetadataProvider {
             table, schema
         );
 
-        let schema_name = schema.unwrap_or("public");
+        let default_schema = self.current_schema();
+        let schema_name = schema.unwrap_or(&default_schema);
 
         // Get basic table information and columns
         let columns = self.get_detailed_columns(table, Some(schema_name)).await?;
@@ -604,6 +785,8 @@ impl MetadataProvider for PostgreSQLMetadataProvider {
         // Get referenced by information
         let referenced_by = self.get_table_referenced_by(table, schema_name).await?;
 
+        let partitioning = self.get_table_partitioning(table, schema_name).await?;
+
         let table_details = TableDetails {
             name: table.to_string(),
             schema: schema_name.to_string(),
@@ -614,6 +797,8 @@ impl MetadataProvider for PostgreSQLMetadataProvider {
             foreign_keys,
             referenced_by,
             nested_field_details: std::collections::HashMap::new(),
+            partitioning,
+            distribution: None,
         };
 
         debug!(
@@ -628,7 +813,28 @@ impl MetadataProvider for PostgreSQLMetadataProvider {
     }
 
     fn default_schema(&self) -> Option<String> {
-        Some("public".to_string())
+        Some(self.current_schema())
+    }
+
+    async fn get_schema_table_counts(&self) -> Result<Vec<(String, usize)>, DatabaseError> {
+        let rows: Vec<(String, i64)> = sqlx::query_as(
+            r#"
+            SELECT n.nspname, count(c.oid)
+            FROM pg_namespace n
+            LEFT JOIN pg_class c
+              ON c.relnamespace = n.oid AND c.relkind IN ('r', 'v', 'm', 'f', 'p')
+            WHERE n.nspname NOT LIKE 'pg_%'
+              AND n.nspname NOT IN ('information_schema', 'pg_toast')
+            GROUP BY n.nspname
+            ORDER BY n.nspname
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|(schema, tables)| (schema, tables as usize))
+            .collect())
     }
 }
 
@@ -780,6 +986,10 @@ struct SessionState {
     /// this is still false, the previous future was dropped mid-protocol and
     /// the connection must be discarded.
     clean: bool,
+    /// Role set with `\\setrole`, applied again whenever a new connection is pinned
+    role: Option<String>,
+    /// `SET search_path` from `\\schema`, applied again the same way
+    search_path: Option<String>,
 }
 
 impl SessionState {
@@ -794,6 +1004,140 @@ enum CancelReason {
     TimedOut(std::time::Duration),
 }
 
+/// Extensions with dedicated completions and EXPLAIN notes, detected once
+/// per connection from `pg_extension`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PgExtension {
+    Vector,
+    PostGis,
+    TimescaleDb,
+    Hstore,
+}
+
+impl PgExtension {
+    pub const ALL: [Self; 4] = [Self::Vector, Self::PostGis, Self::TimescaleDb, Self::Hstore];
+
+    /// Name in `pg_extension.extname`
+    pub fn extname(self) -> &'static str {
+        match self {
+            Self::Vector => "vector",
+            Self::PostGis => "postgis",
+            Self::TimescaleDb => "timescaledb",
+            Self::Hstore => "hstore",
+        }
+    }
+
+    pub fn from_extname(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|ext| ext.extname() == name)
+    }
+
+    /// Functions offered by SQL completion while the extension is installed
+    pub fn functions(self) -> &'static [&'static str] {
+        match self {
+            Self::Vector => &[
+                "l2_distance",
+                "cosine_distance",
+                "inner_product",
+                "l1_distance",
+                "hamming_distance",
+                "jaccard_distance",
+                "vector_dims",
+                "vector_norm",
+                "l2_normalize",
+                "binary_quantize",
+                "subvector",
+            ],
+            Self::PostGis => &[
+                "ST_AsGeoJSON",
+                "ST_AsText",
+                "ST_Area",
+                "ST_Buffer",
+                "ST_Centroid",
+                "ST_Contains",
+                "ST_Distance",
+                "ST_DWithin",
+                "ST_Envelope",
+                "ST_GeomFromGeoJSON",
+                "ST_GeomFromText",
+                "ST_Intersects",
+                "ST_IsValid",
+                "ST_Length",
+                "ST_MakePoint",
+                "ST_SetSRID",
+                "ST_Simplify",
+                "ST_SRID",
+                "ST_Transform",
+                "ST_Union",
+                "ST_Within",
+                "ST_X",
+                "ST_Y",
+            ],
+            Self::TimescaleDb => &[
+                "time_bucket",
+                "time_bucket_gapfill",
+                "locf",
+                "interpolate",
+                "first",
+                "last",
+                "create_hypertable",
+                "add_compression_policy",
+                "add_retention_policy",
+                "add_continuous_aggregate_policy",
+                "show_chunks",
+                "drop_chunks",
+                "compress_chunk",
+                "decompress_chunk",
+                "hypertable_size",
+                "approximate_row_count",
+            ],
+            Self::Hstore => &[
+                "akeys",
+                "avals",
+                "skeys",
+                "svals",
+                "hstore_to_json",
+                "hstore_to_jsonb",
+                "exist",
+                "defined",
+                "slice",
+            ],
+        }
+    }
+
+    /// Operators the extension adds
+    pub fn operators(self) -> &'static [&'static str] {
+        match self {
+            // L2, inner product, cosine, L1, Hamming, Jaccard
+            Self::Vector => &["<->", "<#>", "<=>", "<+>", "<~>", "<%>"],
+            // Bounding box overlap, KNN distance, containment
+            Self::PostGis => &["&&", "<->", "~", "@", "<#>"],
+            Self::TimescaleDb => &[],
+            Self::Hstore => &["->", "?", "?&", "?|", "@>", "<@", "#=", "%%", "%#"],
+        }
+    }
+}
+
+/// Installed extensions dbcrust knows about. Best effort: a failed or slow
+/// lookup just means no extension-specific completions.
+async fn detect_extensions(pool: &PgPool) -> Vec<PgExtension> {
+    let lookup =
+        sqlx::query_scalar::<_, String>("SELECT extname::text FROM pg_extension").fetch_all(pool);
+    match tokio::time::timeout(std::time::Duration::from_secs(2), lookup).await {
+        Ok(Ok(names)) => names
+            .iter()
+            .filter_map(|name| PgExtension::from_extname(name))
+            .collect(),
+        Ok(Err(e)) => {
+            debug!("[PostgreSQLClient] Extension detection failed: {e}");
+            Vec::new()
+        }
+        Err(_) => {
+            debug!("[PostgreSQLClient] Extension detection timed out");
+            Vec::new()
+        }
+    }
+}
+
 /// PostgreSQL database client implementation
 pub struct PostgreSQLClient {
     pool: PgPool,
@@ -801,6 +1145,14 @@ pub struct PostgreSQLClient {
     current_database: String,
     metadata_provider: PostgreSQLMetadataProvider,
     session: tokio::sync::Mutex<SessionState>,
+    /// Detected at connect time (and again on `\\c`)
+    extensions: Vec<PgExtension>,
+    /// Text that is not valid UTF-8 (`SQL_ASCII` databases) is decoded with it
+    charset: Charset,
+    /// Type names of the columns `execute_statement` last returned
+    last_column_types: std::sync::Mutex<Vec<String>>,
+    /// Renews the pool's password when it is an IAM token
+    _token_refresh: Option<crate::iam_auth::TokenRefresh>,
 }
 
 impl PostgreSQLClient {
@@ -853,19 +1205,32 @@ impl PostgreSQLClient {
             .unwrap_or_else(|| "postgres".to_string());
         connect_options = connect_options.database(&database_name);
 
-        // Handle SSL mode from options
-        if let Some(sslmode) = connection_info.options.get("sslmode") {
-            let ssl_mode = match sslmode.as_str() {
-                "disable" => sqlx::postgres::PgSslMode::Disable,
-                "allow" => sqlx::postgres::PgSslMode::Allow,
-                "prefer" => sqlx::postgres::PgSslMode::Prefer,
-                "require" => sqlx::postgres::PgSslMode::Require,
-                "verify-ca" => sqlx::postgres::PgSslMode::VerifyCa,
-                "verify-full" => sqlx::postgres::PgSslMode::VerifyFull,
-                _ => sqlx::postgres::PgSslMode::Prefer, // Default
+        let charset = Charset::from_options(&connection_info.options)
+            .map_err(DatabaseError::ConnectionError)?;
+
+        // TLS from sslmode/sslrootcert/... and [tls]
+        let tls = TlsSettings::for_connection(&connection_info)
+            .map_err(DatabaseError::ConnectionError)?;
+        if tls.mode.is_some() || !tls.verify_hostname {
+            let ssl_mode = match tls.mode_or(TlsMode::Prefer) {
+                TlsMode::Disable => sqlx::postgres::PgSslMode::Disable,
+                TlsMode::Allow => sqlx::postgres::PgSslMode::Allow,
+                TlsMode::Prefer => sqlx::postgres::PgSslMode::Prefer,
+                TlsMode::Require => sqlx::postgres::PgSslMode::Require,
+                TlsMode::VerifyCa => sqlx::postgres::PgSslMode::VerifyCa,
+                TlsMode::VerifyFull => sqlx::postgres::PgSslMode::VerifyFull,
             };
             connect_options = connect_options.ssl_mode(ssl_mode);
         }
+        if let Some(root_cert) = &tls.root_cert {
+            connect_options = connect_options.ssl_root_cert(root_cert);
+        }
+        if let Some(client_cert) = &tls.client_cert {
+            connect_options = connect_options.ssl_client_cert(client_cert);
+        }
+        if let Some(client_key) = &tls.client_key {
+            connect_options = connect_options.ssl_client_key(client_key);
+        }
 
         // Configure connection pool - don't connect yet for SSH tunnel scenarios
         let pool = PgPoolOptions::new()
@@ -878,7 +1243,28 @@ impl PostgreSQLClient {
             .await
             .map_err(|e| DatabaseError::ConnectionError(e.to_string()))?;
 
+        if tls.min_version.is_some() {
+            let negotiated: Option<String> = sqlx::query_scalar(
+                "SELECT version FROM pg_stat_ssl WHERE pid = pg_backend_pid() AND ssl",
+            )
+            .fetch_optional(&pool)
+            .await
+            .map_err(|e| DatabaseError::ConnectionError(e.to_string()))?;
+            tls.check_negotiated(negotiated.as_deref())
+                .map_err(DatabaseError::ConnectionError)?;
+        }
+
+        // IAM tokens expire long before idle connections are replaced
+        let token_refresh = crate::iam_auth::keep_fresh(&connection_info, {
+            let pool = pool.clone();
+            move |token| {
+                pool.set_connect_options((*pool.connect_options()).clone().password(&token))
+            }
+        });
+
         let metadata_provider = PostgreSQLMetadataProvider::new(pool.clone());
+        let extensions = detect_extensions(&pool).await;
+        debug!("[PostgreSQLClient::new] Known extensions: {:?}", extensions);
 
         Ok(Self {
             pool,
@@ -889,21 +1275,18 @@ impl PostgreSQLClient {
                 conn: None,
                 backend_pid: None,
                 clean: true,
+                role: None,
+                search_path: None,
             }),
+            extensions,
+            charset,
+            last_column_types: Default::default(),
+            _token_refresh: token_refresh,
         })
     }
 
-    /// Run `sql` on the pinned session connection, cancelling it server-side
-    /// (`pg_cancel_backend`) when Ctrl-C is pressed or the configured query
-    /// timeout elapses. Server-side cancellation lets the in-flight future
-    /// complete normally (SQLSTATE 57014) instead of being dropped mid-
-    /// protocol, so the session connection stays usable.
-    async fn fetch_all_session(
-        &self,
-        sql: &str,
-    ) -> Result<Vec<sqlx::postgres::PgRow>, DatabaseError> {
-        let mut session = self.session.lock().await;
-
+    /// Make sure `session` holds a usable pinned connection
+    async fn pin_session(&self, session: &mut SessionState) -> Result<(), DatabaseError> {
         // Discard a connection whose previous statement never completed —
         // its protocol state is unknown
         if !session.clean {
@@ -921,10 +1304,126 @@ impl PostgreSQLClient {
                 .fetch_one(&mut *conn)
                 .await
                 .map_err(|e| DatabaseError::ConnectionError(e.to_string()))?;
+            if let Some(role) = &session.role {
+                sqlx::query(&format!("SET ROLE {}", quote_sql_ident(role)))
+                    .execute(&mut *conn)
+                    .await
+                    .map_err(|e| DatabaseError::ConnectionError(e.to_string()))?;
+            }
+            if let Some(search_path) = &session.search_path {
+                sqlx::query(search_path)
+                    .execute(&mut *conn)
+                    .await
+                    .map_err(|e| DatabaseError::ConnectionError(e.to_string()))?;
+            }
             debug!("[PostgreSQLClient] Pinned session connection (backend pid {pid})");
             session.conn = Some(conn);
             session.backend_pid = Some(pid);
         }
+        Ok(())
+    }
+
+    /// Stream `sql` on the pinned session connection, handing rows to `sink`
+    /// as the server sends them. Ctrl-C (or a failing sink) cancels the
+    /// statement server-side and discards the connection, whose row stream
+    /// was abandoned mid-protocol.
+    async fn stream_session(
+        &self,
+        sql: &str,
+        sink: &mut dyn RowSink,
+    ) -> Result<u64, DatabaseError> {
+        let mut session = self.session.lock().await;
+        self.pin_session(&mut session).await?;
+        let backend_pid = session.backend_pid.unwrap_or_default();
+        session.clean = false;
+        let mut conn = session.conn.take().ok_or_else(|| {
+            DatabaseError::ConnectionError("session connection unavailable".to_string())
+        })?;
+
+        let rows = sqlx::query(sql).fetch(&mut *conn);
+        let result = crate::database::drain_row_stream(rows, sink, |row, i| {
+            postgresql_json_value(row, i, &self.charset)
+        })
+        .await;
+        match &result {
+            // A server-reported error leaves the protocol synchronized
+            Ok(_) | Err(DatabaseError::SqlxError(sqlx::Error::Database(_))) => {
+                session.clean = true;
+                session.conn = Some(conn);
+            }
+            Err(DatabaseError::SqlxError(_)) => session.discard(),
+            Err(_) => {
+                let _ = sqlx::query("SELECT pg_cancel_backend($1)")
+                    .bind(backend_pid)
+                    .execute(&self.pool)
+                    .await;
+                drop(conn);
+                session.discard();
+            }
+        }
+        result
+    }
+
+    /// Statement rows as result sets; a row of refcursors (procedures,
+    /// functions) is replaced by the sets the cursors point to
+    async fn result_sets(&self, rows: Vec<PgRow>) -> Result<Vec<Vec<Vec<String>>>, DatabaseError> {
+        let all_cursors = rows.first().is_some_and(|row| {
+            row.len() > 0
+                && row
+                    .columns()
+                    .iter()
+                    .all(|column| column.type_info().name().eq_ignore_ascii_case("refcursor"))
+        });
+        if !all_cursors {
+            return Ok(vec![pg_rows_to_strings(&rows, &self.charset)]);
+        }
+
+        // Procedures and functions hand back result sets as refcursors; fetch
+        // each one on the same session while the transaction keeps it open
+        debug!("[PostgreSQLClient::result_sets] Fetching refcursor result sets");
+        let mut sets = Vec::new();
+        for row in &rows {
+            for i in 0..row.len() {
+                let Some(cursor) = row.try_get_unchecked::<Option<&str>, _>(i).ok().flatten()
+                else {
+                    continue;
+                };
+                let fetch = format!("FETCH ALL FROM \"{}\"", cursor.replace('"', "\"\""));
+                let fetched = self.fetch_all_session(&fetch).await.map_err(|e| {
+                    if e.to_string().contains("does not exist") {
+                        DatabaseError::QueryError(format!(
+                            "{e}. Refcursors close when the transaction ends: run the call inside BEGIN ... COMMIT to read its result sets"
+                        ))
+                    } else {
+                        e
+                    }
+                })?;
+                sets.push(pg_rows_to_strings(&fetched, &self.charset));
+            }
+        }
+        Ok(sets)
+    }
+
+    /// Run `sql` on the pinned session connection, cancelling it server-side
+    /// (`pg_cancel_backend`) when Ctrl-C is pressed or the configured query
+    /// timeout elapses. Server-side cancellation lets the in-flight future
+    /// complete normally (SQLSTATE 57014) instead of being dropped mid-
+    /// protocol, so the session connection stays usable.
+    async fn fetch_all_session(
+        &self,
+        sql: &str,
+    ) -> Result<Vec<sqlx::postgres::PgRow>, DatabaseError> {
+        Ok(self.fetch_session_counted(sql).await?.0)
+    }
+
+    /// [`Self::fetch_all_session`], also returning the rows the statement
+    /// affected as reported by the server's command tag
+    async fn fetch_session_counted(
+        &self,
+        sql: &str,
+    ) -> Result<(Vec<sqlx::postgres::PgRow>, u64), DatabaseError> {
+        let mut session = self.session.lock().await;
+        self.pin_session(&mut session).await?;
 
         let backend_pid = session.backend_pid.unwrap_or_default();
         // Take the connection out for the duration of the statement; it is
@@ -943,8 +1442,34 @@ impl PostgreSQLClient {
         let mut grace_deadline: Option<tokio::time::Instant> = None;
         let mut cancel_expired = false;
 
+        // Status line for statements that run long; erased when it drops
+        let rows_received = std::sync::Arc::new(std::sync::atomic::AtomicU64::new(0));
+        let _progress = {
+            let pool = self.pool.clone();
+            let view_query = progress_view_query(sql);
+            crate::query_progress::watch(rows_received.clone(), move || {
+                let pool = pool.clone();
+                async move { poll_query_progress(&pool, backend_pid, view_query).await }
+            })
+        };
+
         let result = {
-            let query_fut = sqlx::query(sql).fetch_all(&mut *conn);
+            let query_fut = async {
+                use futures_util::TryStreamExt;
+                let mut stream = sqlx::Executor::fetch_many(&mut *conn, sqlx::query(sql));
+                let mut rows = Vec::new();
+                let mut rows_affected = 0;
+                while let Some(step) = stream.try_next().await? {
+                    match step {
+                        sqlx::Either::Left(done) => rows_affected += done.rows_affected(),
+                        sqlx::Either::Right(row) => {
+                            rows.push(row);
+                            rows_received.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                        }
+                    }
+                }
+                Ok((rows, rows_affected))
+            };
             tokio::pin!(query_fut);
 
             loop {
@@ -1026,7 +1551,7 @@ impl PostgreSQLClient {
                             t.as_secs()
                         )))
                     }
-                    _ => Err(DatabaseError::QueryError(e.to_string())),
+                    _ => Err(DatabaseError::QueryError(message_with_position(&e))),
                 }
             }
         }
@@ -1074,6 +1599,15 @@ impl PostgreSQLClient {
                         formatted_results.push(vec![line]);
                     }
 
+                    // Concrete CREATE INDEX suggestions for the patterns found
+                    let suggestions = PerformanceAnalyzer::suggest_postgresql_indexes(&json);
+                    if !suggestions.is_empty() {
+                        formatted_results.push(vec!["".to_string()]);
+                        for line in PerformanceAnalyzer::format_index_suggestions(&suggestions) {
+                            formatted_results.push(vec![line]);
+                        }
+                    }
+
                     formatted_results.push(vec!["".to_string()]);
                     formatted_results.push(vec![
                         "💡 Use \\ecopy to copy the raw JSON plan to clipboard".to_string(),
@@ -1126,7 +1660,7 @@ impl PostgreSQLClient {
         }
 
         // For other types, use the regular formatting function
-        format_postgresql_value(row, column_index)
+        format_postgresql_value(row, column_index, &self.charset)
     }
 
     /// Execute a query and return raw values without display formatting (for EXPLAIN JSON)
@@ -1170,6 +1704,227 @@ impl PostgreSQLClient {
     }
 }
 
+/// Client sessions for `\activity`; active sessions first, oldest query on top
+const ACTIVITY_QUERY: &str = r#"
+    SELECT pid, usename AS user, datname AS database,
+           COALESCE(NULLIF(application_name, ''), '') AS application,
+           COALESCE(client_addr::text, 'local') AS client, COALESCE(state, '') AS state,
+           COALESCE(wait_event_type || ':' || wait_event, '') AS waiting_on,
+           COALESCE(date_trunc('second', now() - xact_start)::text, '') AS xact_age,
+           COALESCE(date_trunc('second', now() - query_start)::text, '') AS query_age,
+           LEFT(regexp_replace(query, '\s+', ' ', 'g'), 120) AS query
+    FROM pg_stat_activity
+    WHERE backend_type = 'client backend' AND pid <> pg_backend_pid()
+    ORDER BY state = 'active' DESC, query_start NULLS LAST
+"#;
+
+/// Waiting sessions paired with each session blocking them (`\blocking`)
+const BLOCKING_QUERY: &str = r#"
+    SELECT w.pid AS waiting_id, b.pid AS blocking_id,
+           w.usename AS waiting_user,
+           COALESCE(date_trunc('second', now() - w.query_start)::text, '') AS waited,
+           COALESCE(l.locktype || ' ' || l.mode, '') AS lock,
+           COALESCE(l.relation::regclass::text, '') AS relation,
+           LEFT(regexp_replace(w.query, '\s+', ' ', 'g'), 80) AS waiting_query,
+           b.usename AS blocking_user, COALESCE(b.state, '') AS blocking_state,
+           LEFT(regexp_replace(b.query, '\s+', ' ', 'g'), 80) AS blocking_query
+    FROM pg_stat_activity w
+    CROSS JOIN LATERAL unnest(pg_blocking_pids(w.pid)) AS blocker(pid)
+    JOIN pg_stat_activity b ON b.pid = blocker.pid
+    LEFT JOIN LATERAL (
+        SELECT locktype, mode, relation FROM pg_locks
+        WHERE pid = w.pid AND NOT granted
+        LIMIT 1
+    ) l ON true
+    ORDER BY w.query_start
+"#;
+
+/// State and wait event of a running statement, for its progress line
+const PROGRESS_ACTIVITY_QUERY: &str = r#"
+    SELECT state, wait_event_type || ':' || wait_event
+    FROM pg_stat_activity WHERE pid = $1
+"#;
+
+/// sqlx's message for a failed statement, psql style: the SQLSTATE and
+/// the 1-based character position the server reported appended as
+/// `(SQLSTATE 42P01) at character N`, then its `DETAIL:` and `HINT:` lines
+/// (see [`crate::error_position`] and [`crate::error_display`])
+fn message_with_position(e: &sqlx::Error) -> String {
+    let Some(pg) = (match e {
+        sqlx::Error::Database(db) => db.try_downcast_ref::<PgDatabaseError>(),
+        _ => None,
+    }) else {
+        return e.to_string();
+    };
+    let mut message = format!("{e} (SQLSTATE {})", pg.code());
+    if let Some(PgErrorPosition::Original(position)) = pg.position() {
+        message.push_str(&format!(" at character {position}"));
+    }
+    if let Some(detail) = pg.detail() {
+        message.push_str(&format!("\nDETAIL:  {detail}"));
+    }
+    if let Some(hint) = pg.hint() {
+        message.push_str(&format!("\nHINT:  {hint}"));
+    }
+    message
+}
+
+/// The `pg_stat_progress_*` view reporting on `sql`, as a query for its
+/// phase, counters and unit. Views missing on older servers simply fail.
+fn progress_view_query(sql: &str) -> Option<&'static str> {
+    let words: Vec<String> = sql
+        .split_whitespace()
+        .take(2)
+        .map(str::to_uppercase)
+        .collect();
+    let first = words.first().map(String::as_str)?;
+    let second = words.get(1).map(String::as_str);
+    Some(match (first, second) {
+        ("VACUUM", Some("FULL")) | ("CLUSTER", _) => {
+            r#"SELECT command || ': ' || phase, heap_blks_scanned, heap_blks_total, 'blocks'
+               FROM pg_stat_progress_cluster WHERE pid = $1"#
+        }
+        ("VACUUM", _) => {
+            r#"SELECT 'VACUUM: ' || phase, heap_blks_scanned, heap_blks_total, 'blocks'
+               FROM pg_stat_progress_vacuum WHERE pid = $1"#
+        }
+        ("ANALYZE", _) => {
+            r#"SELECT 'ANALYZE: ' || phase, sample_blks_scanned, sample_blks_total, 'blocks'
+               FROM pg_stat_progress_analyze WHERE pid = $1"#
+        }
+        ("CREATE", _) | ("REINDEX", _) => {
+            r#"SELECT command || ': ' || phase,
+                      CASE WHEN blocks_total > 0 THEN blocks_done ELSE tuples_done END,
+                      CASE WHEN blocks_total > 0 THEN blocks_total ELSE tuples_total END,
+                      CASE WHEN blocks_total > 0 THEN 'blocks' ELSE 'rows' END
+               FROM pg_stat_progress_create_index WHERE pid = $1"#
+        }
+        ("COPY", _) => {
+            r#"SELECT command || ' ' || type,
+                      CASE WHEN bytes_total > 0 THEN bytes_processed ELSE tuples_processed END,
+                      NULLIF(bytes_total, 0),
+                      CASE WHEN bytes_total > 0 THEN 'bytes' ELSE 'rows' END
+               FROM pg_stat_progress_copy WHERE pid = $1"#
+        }
+        _ => return None,
+    })
+}
+
+/// What the server reports about backend `pid`; polled from a pool
+/// connection while the statement runs on the session one
+async fn poll_query_progress(
+    pool: &PgPool,
+    pid: i32,
+    view_query: Option<&str>,
+) -> Option<crate::query_progress::QueryProgress> {
+    let (state, wait_event): (Option<String>, Option<String>) =
+        sqlx::query_as(PROGRESS_ACTIVITY_QUERY)
+            .bind(pid)
+            .fetch_optional(pool)
+            .await
+            .ok()??;
+    let mut progress = crate::query_progress::QueryProgress {
+        state,
+        wait_event,
+        ..Default::default()
+    };
+    if let Some(query) = view_query
+        && let Ok(Some((phase, done, total, unit))) =
+            sqlx::query_as::<_, (Option<String>, Option<i64>, Option<i64>, String)>(query)
+                .bind(pid)
+                .fetch_optional(pool)
+                .await
+    {
+        progress.phase = phase;
+        progress.done = done;
+        progress.total = total;
+        progress.unit = match unit.as_str() {
+            "bytes" => "bytes",
+            "rows" => "rows",
+            _ => "blocks",
+        };
+    }
+    Some(progress)
+}
+
+/// Table bloat estimate for `\bloat`: compares the pages a table occupies
+/// with the pages its live rows should need, from `pg_stats` average widths.
+/// Ignores fillfactor and alignment padding, so treat it as an indication;
+/// tables that were never analyzed are skipped.
+/// Every database with its size; databases the role cannot connect to have none
+const DATABASE_SIZES_QUERY: &str = r#"
+    SELECT d.datname AS database,
+           pg_get_userbyid(d.datdba) AS owner,
+           CASE WHEN has_database_privilege(d.oid, 'CONNECT')
+                THEN pg_database_size(d.oid) END AS size,
+           CASE WHEN d.datname = current_database() THEN '*' ELSE '' END AS current
+    FROM pg_database d
+    WHERE NOT d.datistemplate
+    ORDER BY 3 DESC NULLS LAST, d.datname
+"#;
+
+/// Largest tables and materialized views of the current database
+fn relation_sizes_query(limit: usize, sort: SizeSort) -> String {
+    format!(
+        r#"
+        SELECT n.nspname AS schema, c.relname AS "table",
+               CASE WHEN c.reltuples < 0 THEN NULL ELSE c.reltuples::bigint END AS "rows",
+               pg_table_size(c.oid) AS table_size,
+               pg_indexes_size(c.oid) AS index_size,
+               pg_total_relation_size(c.oid) AS total_size
+        FROM pg_class c
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE c.relkind IN ('r', 'm')
+          AND n.nspname NOT IN ('pg_catalog', 'information_schema')
+          AND n.nspname NOT LIKE 'pg_toast%'
+        ORDER BY "{}" DESC NULLS LAST, 1, 2
+        LIMIT {limit}
+        "#,
+        sort.column()
+    )
+}
+
+fn bloat_query(limit: usize) -> String {
+    format!(
+        r#"
+        WITH tbl AS (
+            SELECT c.oid, n.nspname AS schema_name, c.relname AS table_name,
+                   c.reltuples::numeric AS tuples, c.relpages::numeric AS pages,
+                   current_setting('block_size')::numeric AS block_size
+            FROM pg_class c
+            JOIN pg_namespace n ON n.oid = c.relnamespace
+            WHERE c.relkind IN ('r', 'm') AND c.relpages > 0 AND c.reltuples > 0
+              AND n.nspname NOT IN ('pg_catalog', 'information_schema')
+              AND n.nspname NOT LIKE 'pg_toast%'
+        ),
+        widths AS (
+            SELECT schemaname, tablename,
+                   SUM((1 - null_frac) * avg_width)::numeric AS row_width
+            FROM pg_stats
+            GROUP BY schemaname, tablename
+        ),
+        est AS (
+            SELECT t.*,
+                   -- 24-byte tuple header + 4-byte line pointer per row, 24-byte page header
+                   CEIL(t.tuples * (w.row_width + 28) / (t.block_size - 24)) AS expected_pages
+            FROM tbl t
+            JOIN widths w ON w.schemaname = t.schema_name AND w.tablename = t.table_name
+        )
+        SELECT est.schema_name AS schema, est.table_name AS "table",
+               pg_size_pretty((est.pages * est.block_size)::bigint) AS table_size,
+               pg_size_pretty(((est.pages - est.expected_pages) * est.block_size)::bigint) AS est_bloat,
+               ROUND(100 * (est.pages - est.expected_pages) / est.pages, 1)::text AS bloat_pct,
+               COALESCE(st.n_dead_tup, 0) AS dead_tuples,
+               COALESCE(date_trunc('second', GREATEST(st.last_vacuum, st.last_autovacuum))::text, '') AS last_vacuum
+        FROM est
+        LEFT JOIN pg_stat_user_tables st ON st.relid = est.oid
+        WHERE est.pages > est.expected_pages
+        ORDER BY est.pages - est.expected_pages DESC
+        LIMIT {limit}
+        "#
+    )
+}
+
 #[async_trait]
 impl DatabaseClient for PostgreSQLClient {
     async fn execute_query(&self, sql: &str) -> Result<Vec<Vec<String>>, DatabaseError> {
@@ -1183,42 +1938,155 @@ impl DatabaseClient for PostgreSQLClient {
             return Ok(vec![]);
         }
 
-        let mut results = Vec::new();
+        let results = pg_rows_to_strings(&rows, &self.charset);
+        debug!(
+            "[PostgreSQLClient::execute_query] Query completed with {} rows",
+            results.len() - 1
+        );
+        Ok(results)
+    }
 
-        // Get column names from the first row
-        let first_row = &rows[0];
-        let column_names: Vec<String> = (0..first_row.len())
-            .map(|i| first_row.column(i).name().to_string())
-            .collect();
+    async fn execute_query_sets(&self, sql: &str) -> Result<Vec<Vec<Vec<String>>>, DatabaseError> {
+        let rows = self.fetch_all_session(sql).await?;
+        self.result_sets(rows).await
+    }
 
-        results.push(column_names);
+    fn last_column_types(&self) -> Vec<String> {
+        self.last_column_types
+            .lock()
+            .map(|types| types.clone())
+            .unwrap_or_default()
+    }
 
-        // Convert rows to strings
-        for row in rows {
-            let mut string_row = Vec::new();
-            for i in 0..row.len() {
-                let value = match format_postgresql_value(&row, i) {
-                    Ok(v) => v,
-                    Err(e) => {
-                        warn!(
-                            "[PostgreSQL] Failed to decode column '{}' (type: {}): {}",
-                            row.column(i).name(),
-                            row.column(i).type_info().name(),
-                            e
-                        );
-                        "?error?".to_string()
-                    }
-                };
-                string_row.push(value);
-            }
-            results.push(string_row);
+    async fn execute_statement(
+        &self,
+        sql: &str,
+    ) -> Result<(Vec<Vec<Vec<String>>>, Option<StatementOutcome>), DatabaseError> {
+        let (rows, rows_affected) = self.fetch_session_counted(sql).await?;
+        if let Ok(mut types) = self.last_column_types.lock() {
+            *types = rows
+                .first()
+                .map(|row| {
+                    row.columns()
+                        .iter()
+                        .map(|c| c.type_info().name().to_string())
+                        .collect()
+                })
+                .unwrap_or_default();
+        }
+        if rows.is_empty() {
+            let outcome = StatementOutcome {
+                rows_affected,
+                last_insert_id: None,
+            };
+            return Ok((vec![Vec::new()], Some(outcome)));
         }
+        Ok((self.result_sets(rows).await?, None))
+    }
 
-        debug!(
-            "[PostgreSQLClient::execute_query] Query completed with {} rows",
-            results.len() - 1
+    async fn stream_query(&self, sql: &str, sink: &mut dyn RowSink) -> Result<u64, DatabaseError> {
+        debug!("[PostgreSQLClient::stream_query] Streaming query");
+        self.stream_session(sql, sink).await
+    }
+
+    async fn copy_in_csv(
+        &self,
+        table: &str,
+        columns: &[String],
+        data: Vec<u8>,
+    ) -> Result<u64, DatabaseError> {
+        // On the pinned session, so a SET ROLE or search_path applies
+        let mut session = self.session.lock().await;
+        self.pin_session(&mut session).await?;
+        let conn = session.conn.as_mut().ok_or_else(|| {
+            DatabaseError::ConnectionError("session connection unavailable".to_string())
+        })?;
+        let statement = format!(
+            "COPY {table} ({}) FROM STDIN (FORMAT csv)",
+            columns.join(", ")
         );
-        Ok(results)
+        let result = async {
+            let mut copy = conn.copy_in_raw(&statement).await?;
+            copy.send(data).await?;
+            copy.finish().await
+        }
+        .await;
+        match result {
+            Ok(rows) => Ok(rows),
+            // A server-reported error leaves the protocol synchronized
+            Err(e @ sqlx::Error::Database(_)) => Err(e.into()),
+            Err(e) => {
+                session.discard();
+                Err(e.into())
+            }
+        }
+    }
+
+    async fn run_in_transaction(
+        &self,
+        statements: &[String],
+        progress: &(dyn Fn(usize) + Send + Sync),
+    ) -> Result<(), DatabaseError> {
+        // On the pinned session, so every statement sees the same transaction
+        self.fetch_all_session("BEGIN").await?;
+        for (done, statement) in statements.iter().enumerate() {
+            if crate::database::interrupt_flag().load(std::sync::atomic::Ordering::Relaxed) {
+                let _ = self.fetch_all_session("ROLLBACK").await;
+                return Err(DatabaseError::QueryError("Restore cancelled".to_string()));
+            }
+            if let Err(e) = self.fetch_all_session(statement).await {
+                let _ = self.fetch_all_session("ROLLBACK").await;
+                return Err(e);
+            }
+            progress(done + 1);
+        }
+        self.fetch_all_session("COMMIT").await?;
+        Ok(())
+    }
+
+    async fn set_role(&self, role: Option<&str>) -> Result<(), DatabaseError> {
+        let sql = match role {
+            Some(role) => {
+                let exists: bool =
+                    sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM pg_roles WHERE rolname = $1)")
+                        .bind(role)
+                        .fetch_one(&self.pool)
+                        .await?;
+                if !exists {
+                    return Err(DatabaseError::QueryError(format!(
+                        "role \"{role}\" does not exist"
+                    )));
+                }
+                format!("SET ROLE {}", quote_sql_ident(role))
+            }
+            None => "RESET ROLE".to_string(),
+        };
+        self.fetch_all_session(&sql).await?;
+        self.session.lock().await.role = role.map(str::to_string);
+        Ok(())
+    }
+
+    async fn set_schema(&mut self, schema: &str) -> Result<(), DatabaseError> {
+        let exists: bool =
+            sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM pg_namespace WHERE nspname = $1)")
+                .bind(schema)
+                .fetch_one(&self.pool)
+                .await?;
+        if !exists {
+            return Err(DatabaseError::QueryError(format!(
+                "schema \"{schema}\" does not exist"
+            )));
+        }
+        // Keep public reachable for extension types and functions installed there
+        let search_path = if schema == "public" {
+            "SET search_path TO public".to_string()
+        } else {
+            format!("SET search_path TO {}, public", quote_sql_ident(schema))
+        };
+        self.fetch_all_session(&search_path).await?;
+        self.session.lock().await.search_path = Some(search_path);
+        *self.metadata_provider.schema.write().unwrap() = Some(schema.to_string());
+        Ok(())
     }
 
     async fn test_query(&self, sql: &str) -> Result<(), DatabaseError> {
@@ -1254,6 +2122,11 @@ impl DatabaseClient for PostgreSQLClient {
         self.execute_query_raw_json(&explain_sql).await
     }
 
+    async fn explain_analyze_raw(&self, sql: &str) -> Result<Vec<Vec<String>>, DatabaseError> {
+        let explain_sql = format!("EXPLAIN (ANALYZE, BUFFERS, FORMAT JSON) {sql}");
+        self.execute_query_raw_json(&explain_sql).await
+    }
+
     async fn list_databases(&self) -> Result<Vec<Vec<String>>, DatabaseError> {
         let query = r#"
             SELECT
@@ -1277,6 +2150,8 @@ impl DatabaseClient for PostgreSQLClient {
         // Create new connection info with updated database
         let mut new_connection_info = self.connection_info.clone();
         new_connection_info.database = Some(database.to_string());
+        // The token the current pool signed in with may have expired
+        crate::iam_auth::apply(&mut new_connection_info).await?;
 
         // Create new client with the updated connection
         let new_client = PostgreSQLClient::new(new_connection_info).await?;
@@ -1291,6 +2166,10 @@ impl DatabaseClient for PostgreSQLClient {
         self.current_database.clone()
     }
 
+    fn pg_extensions(&self) -> &[PgExtension] {
+        &self.extensions
+    }
+
     fn get_connection_info(&self) -> &ConnectionInfo {
         &self.connection_info
     }
@@ -1366,6 +2245,28 @@ impl DatabaseClient for PostgreSQLClient {
         debug!("[PostgreSQLClient::get_server_info] Server info retrieved successfully");
         Ok(server_info)
     }
+
+    async fn server_diagnostic(
+        &self,
+        diagnostic: ServerDiagnostic,
+    ) -> Result<Vec<Vec<String>>, DatabaseError> {
+        debug!("[PostgreSQLClient::server_diagnostic] Running {diagnostic}");
+        match diagnostic {
+            ServerDiagnostic::Sessions => self.execute_query(ACTIVITY_QUERY).await,
+            ServerDiagnostic::LockWaits => self.execute_query(BLOCKING_QUERY).await,
+            ServerDiagnostic::Bloat { limit } => self.execute_query(&bloat_query(limit)).await,
+            ServerDiagnostic::DatabaseSizes => self.execute_query(DATABASE_SIZES_QUERY).await,
+            ServerDiagnostic::RelationSizes { limit, sort } => {
+                self.execute_query(&relation_sizes_query(limit, sort)).await
+            }
+            ServerDiagnostic::TopStatements { .. } | ServerDiagnostic::Clusters => {
+                Err(DatabaseError::FeatureNotSupported {
+                    database_type: self.connection_info.database_type.clone(),
+                    feature: diagnostic.to_string(),
+                })
+            }
+        }
+    }
 }
 
 /// Format PostgreSQL INTERVAL from its components (microseconds, days, months)
@@ -1551,8 +2452,62 @@ fn handle_custom_postgresql_type(
     }
 }
 
+/// Text the server sent as it is stored, decoded with the connection's
+/// charset; only `SQL_ASCII` databases send text that is not UTF-8
+fn decode_text(
+    row: &PgRow,
+    column_index: usize,
+    charset: &Charset,
+) -> Result<String, DatabaseError> {
+    let value = row
+        .try_get_raw(column_index)
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+    let bytes = value
+        .as_bytes()
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+    Ok(charset.decode(bytes))
+}
+
 /// Format a PostgreSQL value to string representation
-fn format_postgresql_value(row: &PgRow, column_index: usize) -> Result<String, DatabaseError> {
+/// Header row from the first row's columns, then every row as display strings
+fn pg_rows_to_strings(rows: &[PgRow], charset: &Charset) -> Vec<Vec<String>> {
+    let Some(first_row) = rows.first() else {
+        return Vec::new();
+    };
+    let mut results = Vec::with_capacity(rows.len() + 1);
+    results.push(
+        first_row
+            .columns()
+            .iter()
+            .map(|column| column.name().to_string())
+            .collect(),
+    );
+
+    for row in rows {
+        let string_row = (0..row.len())
+            .map(|i| match format_postgresql_value(row, i, charset) {
+                Ok(v) => v,
+                Err(e) => {
+                    warn!(
+                        "[PostgreSQL] Failed to decode column '{}' (type: {}): {}",
+                        row.column(i).name(),
+                        row.column(i).type_info().name(),
+                        e
+                    );
+                    "?error?".to_string()
+                }
+            })
+            .collect();
+        results.push(string_row);
+    }
+    results
+}
+
+fn format_postgresql_value(
+    row: &PgRow,
+    column_index: usize,
+    charset: &Charset,
+) -> Result<String, DatabaseError> {
     use sqlx::TypeInfo;
 
     let column = row.column(column_index);
@@ -1583,6 +2538,7 @@ fn format_postgresql_value(row: &PgRow, column_index: usize) -> Result<String, D
         // String types
         "TEXT" | "VARCHAR" | "CHAR" | "BPCHAR" | "NAME" | "CITEXT" => row
             .try_get::<String, _>(column_index)
+            .or_else(|_| decode_text(row, column_index, charset))
             .or_else(|_| handle_custom_postgresql_type(row, column_index, type_name)),
 
         // Integer types
@@ -2008,6 +2964,11 @@ fn format_postgresql_value(row: &PgRow, column_index: usize) -> Result<String, D
                 type_name, type_name_upper
             );
 
+            if let Some(geometry) = postgis_geometry(row, column_index) {
+                let config = crate::complex_display::get_global_complex_config();
+                return Ok(GeometryDisplayAdapter::new(geometry).format(&config));
+            }
+
             // Try String first, fallback to raw value extraction
             let raw_value = row
                 .try_get::<String, _>(column_index)
@@ -2242,6 +3203,7 @@ mod tests {
             options: HashMap::new(),
             docker_container: None,
             use_tls: false,
+            srv: false,
         };
 
         // This test will fail if no PostgreSQL server is running, which is expected
@@ -2584,6 +3546,7 @@ mod tests {
             options: HashMap::new(),
             docker_container: None,
             use_tls: false,
+            srv: false,
         };
 
         // Parse the URL to create a proper client
@@ -2611,7 +3574,13 @@ mod tests {
                 conn: None,
                 backend_pid: None,
                 clean: true,
+                role: None,
+                search_path: None,
             }),
+            extensions: Vec::new(),
+            charset: Charset::default(),
+            last_column_types: Default::default(),
+            _token_refresh: None,
         };
 
         // Comprehensive type query - tests ALL scalar and array types using SELECT with casts
@@ -2776,6 +3745,7 @@ mod tests {
             options: HashMap::new(),
             docker_container: None,
             use_tls: false,
+            srv: false,
         };
 
         let pool = PgPoolOptions::new()
@@ -2802,7 +3772,13 @@ mod tests {
                 conn: None,
                 backend_pid: None,
                 clean: true,
+                role: None,
+                search_path: None,
             }),
+            extensions: Vec::new(),
+            charset: Charset::default(),
+            last_column_types: Default::default(),
+            _token_refresh: None,
         };
 
         // Test ARRAY_AGG over NULL values - this is the exact scenario from the user's bug
@@ -1,12 +1,177 @@
 //! SQLite implementation of the database abstraction layer
-use crate::database::{ConnectionInfo, DatabaseClient, DatabaseError, MetadataProvider};
+use crate::database::{
+    ConnectionInfo, DatabaseClient, DatabaseError, DatabaseType, MetadataProvider, RowSink,
+    ServerDiagnostic, SizeSort, StatementOutcome,
+};
 use crate::db::TableDetails;
 use crate::performance_analyzer::PerformanceAnalyzer;
 use async_trait::async_trait;
 use sqlx::sqlite::{SqlitePool, SqlitePoolOptions, SqliteRow};
 use sqlx::{Column, Row};
+use std::path::Path;
 use tracing::debug;
 
+/// Pages copied per backup step; small enough for smooth progress and to let
+/// writers on other connections in between steps
+const BACKUP_STEP_PAGES: i32 = 256;
+
+/// Progress of `\\backup`, in database pages
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BackupProgress {
+    pub pages_copied: u64,
+    pub total_pages: u64,
+    pub page_size: u64,
+}
+
+impl BackupProgress {
+    /// Share of the database copied so far, 0-100
+    pub fn percent(&self) -> f64 {
+        if self.total_pages == 0 {
+            100.0
+        } else {
+            (self.pages_copied as f64 / self.total_pages as f64 * 100.0).min(100.0)
+        }
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.pages_copied * self.page_size
+    }
+}
+
+/// `<file>.partial`: backups are written there and renamed on success, so a
+/// failed backup never leaves a truncated file under the requested name
+fn partial_backup_path(path: &Path) -> std::path::PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".partial");
+    name.into()
+}
+
+/// Copy `source` into a new database at `dest` with `sqlite3_backup_*`,
+/// step by step so other connections can keep writing in between
+fn run_online_backup(
+    source: *mut libsqlite3_sys::sqlite3,
+    dest: &Path,
+    page_size: u64,
+    progress: &(dyn for<'p> Fn(&'p BackupProgress) + Send + Sync),
+) -> Result<BackupProgress, DatabaseError> {
+    use libsqlite3_sys as ffi;
+    use std::ffi::{CStr, CString};
+
+    let dest_name = CString::new(dest.to_string_lossy().as_bytes())
+        .map_err(|_| DatabaseError::QueryError("Backup path contains a NUL byte".to_string()))?;
+    let error_message = |db: *mut ffi::sqlite3| -> String {
+        // SAFETY: `db` is a valid handle; sqlite3_errmsg returns a NUL-terminated string it owns
+        unsafe { CStr::from_ptr(ffi::sqlite3_errmsg(db)) }
+            .to_string_lossy()
+            .into_owned()
+    };
+
+    // SAFETY: `source` stays locked by the caller for the whole backup, `dest_db`
+    // is opened and closed here, and every backup handle is finished before return.
+    unsafe {
+        let mut dest_db: *mut ffi::sqlite3 = std::ptr::null_mut();
+        let rc = ffi::sqlite3_open_v2(
+            dest_name.as_ptr(),
+            &mut dest_db,
+            ffi::SQLITE_OPEN_READWRITE | ffi::SQLITE_OPEN_CREATE,
+            std::ptr::null(),
+        );
+        if rc != ffi::SQLITE_OK {
+            let message = error_message(dest_db);
+            ffi::sqlite3_close(dest_db);
+            return Err(DatabaseError::QueryError(format!(
+                "Cannot create {}: {message}",
+                dest.display()
+            )));
+        }
+
+        let main = c"main";
+        let backup = ffi::sqlite3_backup_init(dest_db, main.as_ptr(), source, main.as_ptr());
+        if backup.is_null() {
+            let message = error_message(dest_db);
+            ffi::sqlite3_close(dest_db);
+            return Err(DatabaseError::QueryError(format!(
+                "Backup failed: {message}"
+            )));
+        }
+
+        let mut status = BackupProgress {
+            page_size,
+            ..Default::default()
+        };
+        let step_result = loop {
+            let rc = ffi::sqlite3_backup_step(backup, BACKUP_STEP_PAGES);
+            let total = ffi::sqlite3_backup_pagecount(backup).max(0) as u64;
+            let remaining = ffi::sqlite3_backup_remaining(backup).max(0) as u64;
+            status.total_pages = total;
+            status.pages_copied = total.saturating_sub(remaining);
+            progress(&status);
+            match rc {
+                ffi::SQLITE_DONE => break Ok(()),
+                ffi::SQLITE_OK => {}
+                ffi::SQLITE_BUSY | ffi::SQLITE_LOCKED => {
+                    std::thread::sleep(std::time::Duration::from_millis(50))
+                }
+                _ => break Err(error_message(dest_db)),
+            }
+        };
+        let finish_rc = ffi::sqlite3_backup_finish(backup);
+        let finish_error = (finish_rc != ffi::SQLITE_OK).then(|| error_message(dest_db));
+        ffi::sqlite3_close(dest_db);
+
+        match (step_result, finish_error) {
+            (Err(message), _) | (Ok(()), Some(message)) => Err(DatabaseError::QueryError(format!(
+                "Backup failed: {message}"
+            ))),
+            (Ok(()), None) => Ok(status),
+        }
+    }
+}
+
+/// Database size in bytes from `page_count * page_size`
+async fn database_bytes(client: &dyn DatabaseClient) -> Result<u64, DatabaseError> {
+    let rows = client
+        .execute_query("SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()")
+        .await?;
+    Ok(rows
+        .get(1)
+        .and_then(|row| row.first())
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(0))
+}
+
+/// `\\vacuum`: rebuild the database file, then checkpoint and truncate the
+/// WAL so the space is actually returned. Returns the size before and after.
+pub async fn vacuum(client: &dyn DatabaseClient) -> Result<(u64, u64), DatabaseError> {
+    let before = database_bytes(client).await?;
+    client.execute_query("VACUUM").await?;
+    client
+        .execute_query("PRAGMA wal_checkpoint(TRUNCATE)")
+        .await?;
+    let after = database_bytes(client).await?;
+    Ok((before, after))
+}
+
+/// `\\integritycheck`: the problems reported by `PRAGMA integrity_check`
+/// (or the faster `quick_check`); empty when the database is sound
+pub async fn integrity_check(
+    client: &dyn DatabaseClient,
+    quick: bool,
+) -> Result<Vec<String>, DatabaseError> {
+    let pragma = if quick {
+        "PRAGMA quick_check"
+    } else {
+        "PRAGMA integrity_check"
+    };
+    let rows = client.execute_query(pragma).await?;
+    Ok(rows
+        .into_iter()
+        .skip(1)
+        .filter_map(|row| row.into_iter().next())
+        .filter(|line| line != "ok")
+        .collect())
+}
+
 /// SQLite metadata provider implementation
 pub struct SqliteMetadataProvider {
     pool: SqlitePool,
@@ -260,6 +425,8 @@ impl MetadataProvider for SqliteMetadataProvider {
             foreign_keys,
             referenced_by: Vec::new(), // Would need complex query to find referencing tables
             nested_field_details: std::collections::HashMap::new(),
+            partitioning: None,
+            distribution: None,
         };
 
         debug!("[SqliteMetadataProvider::get_table_details] Table details retrieved successfully");
@@ -281,6 +448,8 @@ pub struct SqliteClient {
     connection_info: ConnectionInfo,
     current_database: String,
     metadata_provider: SqliteMetadataProvider,
+    /// Type names of the columns `execute_statement` last returned
+    last_column_types: std::sync::Mutex<Vec<String>>,
 }
 
 impl SqliteClient {
@@ -350,6 +519,7 @@ impl SqliteClient {
             connection_info,
             current_database,
             metadata_provider,
+            last_column_types: Default::default(),
         })
     }
 
@@ -598,6 +768,135 @@ impl SqliteClient {
     }
 }
 
+/// Pages each table and index uses in one attached database, from the `dbstat`
+/// virtual table. Leaf cells of a table's b-tree are its rows.
+fn relation_sizes_query(schema: &str) -> String {
+    let quoted = crate::insert_builder::quote_identifier(schema, &DatabaseType::SQLite);
+    let literal = crate::database::escape_sql_string(schema);
+    format!(
+        r#"
+        WITH sizes AS (
+            SELECT name, SUM(pgsize) AS bytes,
+                   SUM(CASE WHEN pagetype = 'leaf' THEN ncell ELSE 0 END) AS cells
+            FROM dbstat('{literal}')
+            GROUP BY name
+        )
+        SELECT '{literal}' AS schema, t.name AS "table",
+               COALESCE(s.cells, 0) AS "rows",
+               COALESCE(s.bytes, 0) AS table_size,
+               COALESCE((SELECT SUM(i.bytes) FROM sizes i
+                         JOIN {quoted}.sqlite_master x ON x.name = i.name
+                         WHERE x.type = 'index' AND x.tbl_name = t.name), 0) AS index_size
+        FROM {quoted}.sqlite_master t
+        LEFT JOIN sizes s ON s.name = t.name
+        WHERE t.type = 'table' AND t.name NOT LIKE 'sqlite_%'
+        "#
+    )
+}
+
+impl SqliteClient {
+    /// Names and files of the main and attached databases
+    async fn attached_databases(&self) -> Result<Vec<(String, String)>, DatabaseError> {
+        let rows = sqlx::query("PRAGMA database_list")
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows
+            .iter()
+            .map(|row| (row.get::<String, _>("name"), row.get::<String, _>("file")))
+            .collect())
+    }
+
+    /// File size (page count × page size) and free pages of each attached database
+    async fn database_sizes(&self) -> Result<Vec<Vec<String>>, DatabaseError> {
+        let mut results = vec![
+            ["database", "file", "size", "free"]
+                .iter()
+                .map(|c| c.to_string())
+                .collect::<Vec<_>>(),
+        ];
+        for (name, file) in self.attached_databases().await? {
+            let quoted = crate::insert_builder::quote_identifier(&name, &DatabaseType::SQLite);
+            let mut values = Vec::with_capacity(3);
+            for pragma in ["page_size", "page_count", "freelist_count"] {
+                let row = sqlx::query(&format!("PRAGMA {quoted}.{pragma}"))
+                    .fetch_one(&self.pool)
+                    .await?;
+                values.push(row.try_get::<i64, _>(0).unwrap_or(0));
+            }
+            let (page_size, pages, free) = (values[0], values[1], values[2]);
+            results.push(vec![
+                name,
+                if file.is_empty() {
+                    "(memory)".to_string()
+                } else {
+                    file
+                },
+                (pages * page_size).to_string(),
+                (free * page_size).to_string(),
+            ]);
+        }
+        Ok(results)
+    }
+
+    /// Largest tables across the attached databases
+    async fn relation_sizes(
+        &self,
+        limit: usize,
+        sort: SizeSort,
+    ) -> Result<Vec<Vec<String>>, DatabaseError> {
+        let mut rows: Vec<(String, String, i64, i64, i64)> = Vec::new();
+        for (schema, _) in self.attached_databases().await? {
+            let found = sqlx::query(&relation_sizes_query(&schema))
+                .fetch_all(&self.pool)
+                .await?;
+            rows.extend(found.iter().map(|row| {
+                (
+                    row.get::<String, _>("schema"),
+                    row.get::<String, _>("table"),
+                    row.get::<i64, _>("rows"),
+                    row.get::<i64, _>("table_size"),
+                    row.get::<i64, _>("index_size"),
+                )
+            }));
+        }
+        rows.sort_by_key(|(_, _, count, table, index)| {
+            std::cmp::Reverse(match sort {
+                SizeSort::Total => table + index,
+                SizeSort::Table => *table,
+                SizeSort::Index => *index,
+                SizeSort::Rows => *count,
+            })
+        });
+
+        let mut results = vec![
+            [
+                "schema",
+                "table",
+                "rows",
+                "table_size",
+                "index_size",
+                "total_size",
+            ]
+            .iter()
+            .map(|c| c.to_string())
+            .collect::<Vec<_>>(),
+        ];
+        results.extend(rows.into_iter().take(limit).map(
+            |(schema, table, count, table_size, index_size)| {
+                vec![
+                    schema,
+                    table,
+                    count.to_string(),
+                    table_size.to_string(),
+                    index_size.to_string(),
+                    (table_size + index_size).to_string(),
+                ]
+            },
+        ));
+        Ok(results)
+    }
+}
+
 #[async_trait]
 impl DatabaseClient for SqliteClient {
     async fn execute_query(&self, sql: &str) -> Result<Vec<Vec<String>>, DatabaseError> {
@@ -636,6 +935,123 @@ impl DatabaseClient for SqliteClient {
         Ok(results)
     }
 
+    async fn execute_statement(
+        &self,
+        sql: &str,
+    ) -> Result<(Vec<Vec<Vec<String>>>, Option<StatementOutcome>), DatabaseError> {
+        use futures_util::TryStreamExt;
+        // One connection for the statement and `last_insert_rowid()`
+        let mut conn = self.pool.acquire().await?;
+        let mut stream = sqlx::Executor::fetch_many(&mut *conn, sqlx::query(sql));
+        let mut results = Vec::new();
+        let mut outcome = StatementOutcome::default();
+        let mut types = Vec::new();
+        while let Some(step) = stream.try_next().await? {
+            match step {
+                sqlx::Either::Left(done) => {
+                    outcome.rows_affected += done.rows_affected();
+                    outcome.last_insert_id = Some(done.last_insert_rowid());
+                }
+                sqlx::Either::Right(row) => {
+                    if results.is_empty() {
+                        results.push(
+                            (0..row.len())
+                                .map(|i| row.column(i).name().to_string())
+                                .collect(),
+                        );
+                        types = row
+                            .columns()
+                            .iter()
+                            .map(|c| sqlx::TypeInfo::name(c.type_info()).to_string())
+                            .collect();
+                    }
+                    let values: Result<Vec<String>, DatabaseError> = (0..row.len())
+                        .map(|i| format_sqlite_value(&row, i))
+                        .collect();
+                    results.push(values?);
+                }
+            }
+        }
+        if let Ok(mut last) = self.last_column_types.lock() {
+            *last = types;
+        }
+        if results.is_empty() {
+            Ok((vec![results], Some(outcome)))
+        } else {
+            Ok((vec![results], None))
+        }
+    }
+
+    fn last_column_types(&self) -> Vec<String> {
+        self.last_column_types
+            .lock()
+            .map(|types| types.clone())
+            .unwrap_or_default()
+    }
+
+    async fn stream_query(&self, sql: &str, sink: &mut dyn RowSink) -> Result<u64, DatabaseError> {
+        debug!("[SqliteClient::stream_query] Streaming query");
+        let rows = sqlx::query(sql).fetch(&self.pool);
+        crate::database::drain_row_stream(rows, sink, sqlite_json_value).await
+    }
+
+    async fn run_in_transaction(
+        &self,
+        statements: &[String],
+        progress: &(dyn Fn(usize) + Send + Sync),
+    ) -> Result<(), DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+        // Connections enforce foreign keys; checking them at COMMIT lets
+        // rows load in dump order
+        sqlx::Executor::execute(&mut *tx, "PRAGMA defer_foreign_keys = ON").await?;
+        for (done, statement) in statements.iter().enumerate() {
+            if crate::database::interrupt_flag().load(std::sync::atomic::Ordering::Relaxed) {
+                return Err(DatabaseError::QueryError("Restore cancelled".to_string()));
+            }
+            sqlx::Executor::execute(&mut *tx, statement.as_str()).await?;
+            progress(done + 1);
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn backup_to_file(
+        &self,
+        path: &Path,
+        progress: &(dyn for<'p> Fn(&'p BackupProgress) + Send + Sync),
+    ) -> Result<BackupProgress, DatabaseError> {
+        debug!(
+            "[SqliteClient::backup_to_file] Backing up to {}",
+            path.display()
+        );
+        let page_size: i64 = sqlx::query_scalar("PRAGMA page_size")
+            .fetch_one(&self.pool)
+            .await?;
+        let partial = partial_backup_path(path);
+        let _ = std::fs::remove_file(&partial);
+
+        let mut conn = self.pool.acquire().await?;
+        let mut handle = conn.lock_handle().await?;
+        let result = run_online_backup(
+            handle.as_raw_handle().as_ptr(),
+            &partial,
+            page_size.max(0) as u64,
+            progress,
+        );
+        drop(handle);
+
+        match result.and_then(|status| {
+            std::fs::rename(&partial, path)?;
+            Ok(status)
+        }) {
+            Ok(status) => Ok(status),
+            Err(e) => {
+                let _ = std::fs::remove_file(&partial);
+                Err(e)
+            }
+        }
+    }
+
     async fn test_query(&self, sql: &str) -> Result<(), DatabaseError> {
         debug!("[SqliteClient::test_query] Testing query for validation");
         // For SQLite, we can use EXPLAIN QUERY PLAN to validate query syntax without executing it
@@ -800,6 +1216,23 @@ impl DatabaseClient for SqliteClient {
         Ok(())
     }
 
+    async fn server_diagnostic(
+        &self,
+        diagnostic: ServerDiagnostic,
+    ) -> Result<Vec<Vec<String>>, DatabaseError> {
+        debug!("[SqliteClient::server_diagnostic] Running {diagnostic}");
+        match diagnostic {
+            ServerDiagnostic::DatabaseSizes => self.database_sizes().await,
+            ServerDiagnostic::RelationSizes { limit, sort } => {
+                self.relation_sizes(limit, sort).await
+            }
+            _ => Err(DatabaseError::FeatureNotSupported {
+                database_type: self.connection_info.database_type.clone(),
+                feature: diagnostic.to_string(),
+            }),
+        }
+    }
+
     async fn get_server_info(&self) -> Result<crate::database::ServerInfo, DatabaseError> {
         debug!("[SqliteClient::get_server_info] Fetching server version information");
 
@@ -872,8 +1305,37 @@ impl DatabaseClient for SqliteClient {
     }
 }
 
+/// Typed value for exports, following the storage class of each value
+/// (SQLite columns are dynamically typed)
+fn sqlite_json_value(row: &SqliteRow, column_index: usize) -> serde_json::Value {
+    use serde_json::Value;
+    use sqlx::{TypeInfo, ValueRef};
+
+    let storage_class = match row.try_get_raw(column_index) {
+        Ok(raw) if raw.is_null() => return Value::Null,
+        Ok(raw) => raw.type_info().name().to_string(),
+        Err(_) => String::new(),
+    };
+    let typed = match storage_class.as_str() {
+        "INTEGER" => row.try_get::<i64, _>(column_index).ok().map(Value::from),
+        "REAL" => row
+            .try_get::<f64, _>(column_index)
+            .ok()
+            .and_then(|v| serde_json::Number::from_f64(v).map(Value::Number)),
+        _ => None,
+    };
+    typed.unwrap_or_else(|| {
+        Value::String(
+            format_sqlite_value(row, column_index).unwrap_or_else(|_| "?error?".to_string()),
+        )
+    })
+}
+
 /// Format a SQLite value to string representation
-fn format_sqlite_value(row: &SqliteRow, column_index: usize) -> Result<String, DatabaseError> {
+pub(crate) fn format_sqlite_value(
+    row: &SqliteRow,
+    column_index: usize,
+) -> Result<String, DatabaseError> {
     use sqlx::TypeInfo;
     use sqlx::ValueRef;
 
@@ -984,6 +1446,7 @@ mod tests {
             options: HashMap::new(),
             docker_container: None,
             use_tls: false,
+            srv: false,
         };
 
         match SqliteClient::new(connection_info).await {
@@ -1075,6 +1538,7 @@ mod tests {
             options: HashMap::new(),
             docker_container: None,
             use_tls: false,
+            srv: false,
         };
 
         let client = SqliteClient::new(connection_info).await.unwrap();
@@ -1120,4 +1584,54 @@ mod tests {
                 .any(|col| col.contains("SQLite Query Plan"))
         );
     }
+
+    #[tokio::test]
+    async fn test_backup_vacuum_and_integrity_check() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("source.db");
+        std::fs::File::create(&source).unwrap();
+        let connection_info = |path: &Path| ConnectionInfo {
+            database_type: DatabaseType::SQLite,
+            host: None,
+            port: None,
+            username: None,
+            password: None,
+            database: None,
+            file_path: Some(path.to_string_lossy().to_string()),
+            options: HashMap::new(),
+            docker_container: None,
+            use_tls: false,
+            srv: false,
+        };
+        let client = SqliteClient::new(connection_info(&source)).await.unwrap();
+        client
+            .execute_query(
+                "CREATE TABLE t AS WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 20000) SELECT i, hex(randomblob(64)) AS v FROM n",
+            )
+            .await
+            .unwrap();
+
+        let target = dir.path().join("backup.db");
+        let steps = std::sync::atomic::AtomicUsize::new(0);
+        let totals = client
+            .backup_to_file(&target, &|_| {
+                steps.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            })
+            .await
+            .unwrap();
+        assert!(totals.total_pages > BACKUP_STEP_PAGES as u64);
+        assert_eq!(totals.pages_copied, totals.total_pages);
+        assert_eq!(totals.percent(), 100.0);
+        assert!(steps.into_inner() > 1);
+        assert!(!partial_backup_path(&target).exists());
+
+        let copy = SqliteClient::new(connection_info(&target)).await.unwrap();
+        let count = copy.execute_query("SELECT COUNT(*) FROM t").await.unwrap();
+        assert_eq!(count[1][0], "20000");
+        assert!(integrity_check(&copy, false).await.unwrap().is_empty());
+
+        client.execute_query("DELETE FROM t").await.unwrap();
+        let (before, after) = vacuum(&client).await.unwrap();
+        assert!(after < before);
+    }
 }
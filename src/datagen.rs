@@ -0,0 +1,986 @@
+//! Fake rows for `\genrows <table> <count>`
+//!
+//! Reads the table's columns and constraints and makes up plausible values:
+//! names, emails, cities and the like picked from the column name, dates,
+//! numbers within the column's precision, enum labels, text cut to the
+//! column's length. Columns the server fills in (identity, sequences,
+//! auto-increment) are left out; other integer primary keys count up from the
+//! current maximum. Foreign key columns take their values from rows that
+//! already exist in the referenced table, so the generated rows satisfy the
+//! constraint; a nullable one stays NULL when that table is empty.
+//!
+//! Rows are made and inserted in batches of [`BATCH_ROWS`], after a preview
+//! of the first few, so only one batch is held in memory at a time.
+
+use crate::database::{DatabaseType, escape_sql_string};
+use crate::db::{Database, TableDetails};
+use crate::insert_builder::{
+    ColumnSpec, ColumnValue, ValueKind, build_insert_statements, quote_identifier,
+};
+use crate::schema_cache::parse_foreign_key;
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use rand::{Rng, SeedableRng};
+use std::collections::HashSet;
+use std::io::IsTerminal;
+use std::sync::atomic::Ordering;
+
+/// Rows per INSERT statement
+pub const BATCH_ROWS: usize = 500;
+/// Rows shown before asking to insert
+pub const PREVIEW_ROWS: usize = 5;
+/// Existing rows sampled from a referenced table
+const REFERENCE_SAMPLE: usize = 1000;
+/// Share of NULLs in nullable columns that are not keys
+const NULL_RATE: f64 = 0.1;
+
+const FIRST_NAMES: &[&str] = &[
+    "Alice", "Bruno", "Chloé", "David", "Emma", "Farid", "Grace", "Hugo", "Inès", "Jonas", "Keiko",
+    "Liam", "Maya", "Noah", "Olga", "Pablo", "Quentin", "Rosa", "Samir", "Tara", "Ugo", "Vera",
+    "Wei", "Yara", "Zoé",
+];
+const LAST_NAMES: &[&str] = &[
+    "Martin", "Smith", "Garcia", "Müller", "Rossi", "Dubois", "Kowalski", "Nguyen", "Silva",
+    "Tanaka", "Johnson", "Lefebvre", "Novak", "Haddad", "Jensen", "Moreau", "Costa", "Schmidt",
+    "Brown", "Ivanova",
+];
+const CITIES: &[&str] = &[
+    "Paris",
+    "Lyon",
+    "Berlin",
+    "Madrid",
+    "Lisbon",
+    "Rome",
+    "Amsterdam",
+    "Oslo",
+    "Montreal",
+    "Austin",
+    "Osaka",
+    "Nairobi",
+    "Melbourne",
+    "Prague",
+    "Dublin",
+];
+const COUNTRIES: &[&str] = &[
+    "France",
+    "Germany",
+    "Spain",
+    "Portugal",
+    "Italy",
+    "Netherlands",
+    "Norway",
+    "Canada",
+    "United States",
+    "Japan",
+    "Kenya",
+    "Australia",
+    "Czechia",
+    "Ireland",
+];
+const STREETS: &[&str] = &[
+    "Main St",
+    "Oak Avenue",
+    "Rue de la Paix",
+    "Station Road",
+    "Elm Street",
+    "Harbour Way",
+    "Park Lane",
+    "Mill Road",
+];
+const WORDS: &[&str] = &[
+    "quick", "report", "blue", "order", "system", "garden", "signal", "market", "river", "update",
+    "silver", "project", "review", "north", "cloud", "simple", "delivery", "account", "energy",
+    "design",
+];
+const STATUSES: &[&str] = &["active", "inactive", "pending", "archived"];
+const COMPANIES: &[&str] = &[
+    "Acme Corp",
+    "Globex",
+    "Initech",
+    "Umbrella Ltd",
+    "Hooli",
+    "Stark Industries",
+    "Wayne Enterprises",
+    "Soylent",
+];
+const COLORS: &[&str] = &["red", "green", "blue", "yellow", "black", "white", "purple"];
+
+/// What a column's values look like
+#[derive(Debug, Clone, PartialEq)]
+enum Fake {
+    Email,
+    FirstName,
+    LastName,
+    FullName,
+    Username,
+    Phone,
+    City,
+    Country,
+    Street,
+    PostalCode,
+    Url,
+    Title,
+    Sentence,
+    Status,
+    Company,
+    Color,
+    Uuid,
+    Date,
+    Timestamp,
+    Time,
+    Json,
+    Boolean,
+    /// `tinyint(1)`, MySQL's boolean
+    Bit,
+    Integer {
+        min: i64,
+        max: i64,
+    },
+    Decimal {
+        max: f64,
+        scale: usize,
+    },
+    /// `<column>_<n>`
+    Text,
+}
+
+/// How the values of one column are made
+#[derive(Debug, Clone, PartialEq)]
+enum Generator {
+    /// Left to the server: identity, sequence, auto-increment, generated
+    Default,
+    /// Integers after the column's current maximum
+    Sequence(i64),
+    /// Column `position` of a row sampled for foreign key `group`
+    Reference {
+        group: usize,
+        position: usize,
+    },
+    Choice(Vec<String>),
+    Fake(Fake),
+}
+
+#[derive(Debug, Clone)]
+struct ColumnPlan {
+    spec: ColumnSpec,
+    generator: Generator,
+    /// Character limit of `varchar(n)` and friends
+    max_len: Option<usize>,
+    null_rate: f64,
+}
+
+/// Rows to generate, made batch by batch by [`insert`]
+#[derive(Debug)]
+pub struct GeneratedRows {
+    plans: Vec<ColumnPlan>,
+    references: Vec<Vec<Vec<String>>>,
+    rng: StdRng,
+    /// Added to the row number in values that must not repeat
+    base: u64,
+    count: usize,
+    /// Rows made so far
+    made: usize,
+    /// First batch, held for the preview until it is inserted
+    first: Option<Vec<Vec<ColumnValue>>>,
+}
+
+impl GeneratedRows {
+    fn new(
+        plans: Vec<ColumnPlan>,
+        references: Vec<Vec<Vec<String>>>,
+        count: usize,
+    ) -> Result<Self, String> {
+        let mut rng = StdRng::from_rng(&mut rand::rng());
+        let base = rng.random_range(1_000..1_000_000);
+        let mut generated = Self {
+            plans,
+            references,
+            rng,
+            base,
+            count,
+            made: 0,
+            first: None,
+        };
+        generated.first = generated.next_batch().transpose()?;
+        Ok(generated)
+    }
+
+    fn columns(&self) -> Vec<ColumnSpec> {
+        self.plans.iter().map(|plan| plan.spec.clone()).collect()
+    }
+
+    /// The next [`BATCH_ROWS`] rows, `None` once all are made
+    fn next_batch(&mut self) -> Option<Result<Vec<Vec<ColumnValue>>, String>> {
+        if let Some(first) = self.first.take() {
+            return Some(Ok(first));
+        }
+        if self.made >= self.count {
+            return None;
+        }
+        let end = (self.made + BATCH_ROWS).min(self.count);
+        let rows = generate(
+            &self.plans,
+            &self.references,
+            self.made..end,
+            self.base,
+            &mut self.rng,
+        );
+        self.made = end;
+        Some(rows)
+    }
+
+    /// The first rows as a table, without the columns left to the server
+    pub fn preview(&self, limit: usize) -> Vec<Vec<String>> {
+        let first = self.first.as_deref().unwrap_or_default();
+        let shown: Vec<usize> = match first.first() {
+            Some(row) => (0..self.plans.len())
+                .filter(|&i| row[i] != ColumnValue::Default)
+                .collect(),
+            None => Vec::new(),
+        };
+        let header = shown
+            .iter()
+            .map(|&i| self.plans[i].spec.name.clone())
+            .collect();
+        let rows = first.iter().take(limit).map(|row| {
+            shown
+                .iter()
+                .map(|&i| match &row[i] {
+                    ColumnValue::Value(value) | ColumnValue::Expression(value) => value.clone(),
+                    _ => "NULL".to_string(),
+                })
+                .collect()
+        });
+        std::iter::once(header).chain(rows).collect()
+    }
+}
+
+/// The value kind a type maps to, with the pieces [`ValueKind`] drops
+fn fake_for_type(data_type: &str) -> Option<Fake> {
+    let lower = data_type.to_lowercase();
+    let base = lower.split(['(', ' ']).next().unwrap_or_default();
+    let number = |index: usize| -> Option<usize> {
+        let inner = lower.split_once('(')?.1.split_once(')')?.0;
+        inner.split(',').nth(index)?.trim().parse().ok()
+    };
+    Some(match base {
+        "uuid" | "uniqueidentifier" => Fake::Uuid,
+        "date" => Fake::Date,
+        "time" => Fake::Time,
+        "timestamp" | "timestamptz" | "datetime" | "datetime64" | "smalldatetime" => {
+            Fake::Timestamp
+        }
+        "json" | "jsonb" => Fake::Json,
+        "tinyint" if number(0) == Some(1) => Fake::Bit,
+        "tinyint" | "int1" | "uint8" => Fake::Integer { min: 0, max: 100 },
+        "numeric" | "decimal" => {
+            let scale = number(1).unwrap_or(2).min(6);
+            let digits = number(0).map_or(6, |precision| precision.saturating_sub(scale).min(6));
+            Fake::Decimal {
+                max: (10f64.powi(digits as i32) - 1.0).max(0.0),
+                scale,
+            }
+        }
+        _ => match ValueKind::from_type(data_type) {
+            ValueKind::Boolean => Fake::Boolean,
+            ValueKind::Decimal => Fake::Decimal {
+                max: 1000.0,
+                scale: 2,
+            },
+            _ => return None,
+        },
+    })
+}
+
+/// Guess the kind of value from the column name, then the type
+fn fake_for(spec: &ColumnSpec) -> Fake {
+    let name = spec.name.to_lowercase();
+    let has = |words: &[&str]| words.iter().any(|word| name.contains(word));
+    let is = |words: &[&str]| {
+        words
+            .iter()
+            .any(|word| name == *word || name.ends_with(&format!("_{word}")))
+    };
+    let fake = fake_for_type(&spec.data_type);
+    match spec.kind {
+        ValueKind::Integer if fake.is_none() => {
+            let (min, max) = if is(&["age"]) {
+                (18, 90)
+            } else if is(&["year"]) {
+                (1990, 2025)
+            } else if has(&["quantity", "qty", "count"]) {
+                (1, 20)
+            } else if has(&["rating", "score", "stars"]) {
+                (1, 5)
+            } else {
+                (1, 1000)
+            };
+            return Fake::Integer { min, max };
+        }
+        ValueKind::Decimal if has(&["lat"]) => {
+            return Fake::Decimal {
+                max: 90.0,
+                scale: 6,
+            };
+        }
+        ValueKind::Decimal if has(&["lon", "lng"]) => {
+            return Fake::Decimal {
+                max: 180.0,
+                scale: 6,
+            };
+        }
+        _ => {}
+    }
+    if let Some(fake) = fake {
+        return fake;
+    }
+    if has(&["email", "mail"]) {
+        Fake::Email
+    } else if has(&["first_name", "firstname", "given_name"]) {
+        Fake::FirstName
+    } else if has(&["last_name", "lastname", "surname", "family_name"]) {
+        Fake::LastName
+    } else if has(&["username", "user_name", "login", "handle", "nickname"]) {
+        Fake::Username
+    } else if has(&["phone", "mobile", "fax"]) {
+        Fake::Phone
+    } else if has(&["city", "town"]) {
+        Fake::City
+    } else if has(&["country"]) {
+        Fake::Country
+    } else if has(&["address", "street"]) {
+        Fake::Street
+    } else if has(&["zip", "postal", "postcode"]) {
+        Fake::PostalCode
+    } else if has(&["url", "website", "link"]) {
+        Fake::Url
+    } else if has(&["company", "organization", "organisation", "employer"]) {
+        Fake::Company
+    } else if has(&["colour", "color"]) {
+        Fake::Color
+    } else if is(&["status", "state"]) {
+        Fake::Status
+    } else if has(&["title", "subject", "headline"]) {
+        Fake::Title
+    } else if has(&[
+        "description",
+        "notes",
+        "note",
+        "comment",
+        "body",
+        "bio",
+        "content",
+        "message",
+        "summary",
+    ]) {
+        Fake::Sentence
+    } else if has(&["name"]) {
+        Fake::FullName
+    } else {
+        Fake::Text
+    }
+}
+
+/// `varchar(40)` → 40
+fn max_length(data_type: &str) -> Option<usize> {
+    let lower = data_type.to_lowercase();
+    if !lower.contains("char") && !lower.starts_with("string") {
+        return None;
+    }
+    lower
+        .split_once('(')?
+        .1
+        .split(|c: char| !c.is_ascii_digit())
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// Whether the server fills the column in by itself
+pub(crate) fn is_auto_default(default: Option<&str>) -> bool {
+    default.is_some_and(|default| {
+        let lower = default.to_lowercase();
+        [
+            "nextval(",
+            "identity",
+            "auto_increment",
+            "autoincrement",
+            "generated",
+        ]
+        .iter()
+        .any(|marker| lower.contains(marker))
+    })
+}
+
+fn words(rng: &mut impl Rng, count: usize) -> Vec<&'static str> {
+    (0..count)
+        .map(|_| *WORDS.choose(rng).unwrap_or(&"data"))
+        .collect()
+}
+
+fn pick(rng: &mut impl Rng, values: &[&str]) -> String {
+    values.choose(rng).copied().unwrap_or_default().to_string()
+}
+
+/// Lowercase ASCII version of a name, for emails and user names
+fn slug(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            'é' | 'è' => 'e',
+            'ü' => 'u',
+            'ï' | 'î' => 'i',
+            c => c.to_ascii_lowercase(),
+        })
+        .filter(char::is_ascii_alphanumeric)
+        .collect()
+}
+
+impl Fake {
+    /// Whether values carry the row's unique number, for columns that may
+    /// be unique
+    fn is_numbered(&self) -> bool {
+        matches!(
+            self,
+            Fake::Email | Fake::Username | Fake::Url | Fake::Json | Fake::Text
+        )
+    }
+}
+
+/// `value` cut to `max_len` characters. Numbered values keep `n` at the end
+/// so they stay distinct; `None` when the column cannot even hold `n`.
+fn fit_length(fake: &Fake, value: String, n: u64, max_len: usize) -> Option<String> {
+    if value.chars().count() <= max_len {
+        return Some(value);
+    }
+    if !fake.is_numbered() {
+        return Some(value.chars().take(max_len).collect());
+    }
+    let suffix = n.to_string();
+    let room = max_len.checked_sub(suffix.len())?;
+    Some(value.chars().take(room).chain(suffix.chars()).collect())
+}
+
+/// One value; `n` is unique within the run, for columns that may be unique
+fn fake_value(fake: &Fake, column: &str, n: u64, rng: &mut impl Rng) -> String {
+    let first = pick(rng, FIRST_NAMES);
+    let last = pick(rng, LAST_NAMES);
+    match fake {
+        Fake::Email => format!("{}.{}{n}@example.com", slug(&first), slug(&last)),
+        Fake::FirstName => first,
+        Fake::LastName => last,
+        Fake::FullName => format!("{first} {last}"),
+        Fake::Username => format!("{}{n}", slug(&first)),
+        Fake::Phone => format!(
+            "+1-555-{:03}-{:04}",
+            rng.random_range(100..1000),
+            rng.random_range(0..10000)
+        ),
+        Fake::City => pick(rng, CITIES),
+        Fake::Country => pick(rng, COUNTRIES),
+        Fake::Street => format!("{} {}", rng.random_range(1..300), pick(rng, STREETS)),
+        Fake::PostalCode => format!("{:05}", rng.random_range(1000..99999)),
+        Fake::Url => format!("https://example.com/{}/{n}", words(rng, 1)[0]),
+        Fake::Title => {
+            let count = rng.random_range(2..5);
+            let mut title = words(rng, count).join(" ");
+            if let Some(first) = title.get_mut(0..1) {
+                first.make_ascii_uppercase();
+            }
+            title
+        }
+        Fake::Sentence => {
+            let count = rng.random_range(6..14);
+            let mut sentence = words(rng, count).join(" ");
+            if let Some(first) = sentence.get_mut(0..1) {
+                first.make_ascii_uppercase();
+            }
+            format!("{sentence}.")
+        }
+        Fake::Status => pick(rng, STATUSES),
+        Fake::Company => pick(rng, COMPANIES),
+        Fake::Color => pick(rng, COLORS),
+        Fake::Uuid => uuid::Uuid::new_v4().to_string(),
+        Fake::Date | Fake::Timestamp => {
+            let seconds = rng.random_range(0..3 * 365 * 24 * 3600);
+            let at = chrono::Utc::now() - chrono::Duration::seconds(seconds);
+            if *fake == Fake::Date {
+                at.format("%Y-%m-%d").to_string()
+            } else {
+                at.format("%Y-%m-%d %H:%M:%S").to_string()
+            }
+        }
+        Fake::Time => format!(
+            "{:02}:{:02}:{:02}",
+            rng.random_range(0..24),
+            rng.random_range(0..60),
+            rng.random_range(0..60)
+        ),
+        Fake::Json => format!("{{\"{}\": {n}}}", words(rng, 1)[0]),
+        Fake::Boolean => rng.random_bool(0.5).to_string(),
+        Fake::Bit => u8::from(rng.random_bool(0.5)).to_string(),
+        Fake::Integer { min, max } => rng.random_range(*min..=*max).to_string(),
+        Fake::Decimal { max, scale } => {
+            let value = rng.random_range(0.0..=*max);
+            format!("{value:.scale$}")
+        }
+        Fake::Text => format!("{column}_{n}"),
+    }
+}
+
+/// Make the rows numbered `rows`; `references` holds the rows sampled for
+/// each foreign key group and `base` is added to the row number in values
+/// that must not repeat
+fn generate(
+    plans: &[ColumnPlan],
+    references: &[Vec<Vec<String>>],
+    rows: std::ops::Range<usize>,
+    base: u64,
+    rng: &mut impl Rng,
+) -> Result<Vec<Vec<ColumnValue>>, String> {
+    rows.map(|i| {
+        let picked: Vec<Option<&Vec<String>>> =
+            references.iter().map(|rows| rows.choose(rng)).collect();
+        plans
+            .iter()
+            .map(|plan| {
+                let null = plan.spec.nullable && rng.random_bool(plan.null_rate);
+                match &plan.generator {
+                    Generator::Default => Ok(ColumnValue::Default),
+                    _ if null => Ok(ColumnValue::Null),
+                    Generator::Sequence(start) => {
+                        Ok(ColumnValue::Value((start + i as i64).to_string()))
+                    }
+                    Generator::Reference { group, position } => {
+                        Ok(match picked[*group].and_then(|row| row.get(*position)) {
+                            Some(value) => ColumnValue::Value(value.clone()),
+                            None => ColumnValue::Null,
+                        })
+                    }
+                    Generator::Choice(values) => match values.choose(rng) {
+                        Some(value) => Ok(ColumnValue::Value(value.clone())),
+                        None => Ok(ColumnValue::Null),
+                    },
+                    Generator::Fake(fake) => {
+                        let n = base + i as u64;
+                        let value = fake_value(fake, &plan.spec.name, n, rng);
+                        match plan.max_len {
+                            Some(max_len) => fit_length(fake, value, n, max_len)
+                                .map(ColumnValue::Value)
+                                .ok_or_else(|| {
+                                    format!(
+                                        "{} ({}) is too short for distinct generated values",
+                                        plan.spec.name, plan.spec.data_type
+                                    )
+                                }),
+                            None => Ok(ColumnValue::Value(value)),
+                        }
+                    }
+                }
+            })
+            .collect()
+    })
+    .collect()
+}
+
+/// First column of each row of a metadata query, header skipped
+async fn first_column(db: &Database, sql: &str) -> Vec<String> {
+    let Some(client) = db.get_database_client() else {
+        return Vec::new();
+    };
+    match client.execute_query(sql).await {
+        Ok(rows) => rows
+            .into_iter()
+            .skip(1)
+            .filter_map(|row| row.into_iter().next())
+            .collect(),
+        Err(e) => {
+            tracing::debug!("Metadata query failed: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Identity, auto-increment and generated columns, which take no value
+async fn server_generated_columns(db: &Database, table: &str) -> HashSet<String> {
+    let parts: Vec<&str> = table
+        .split('.')
+        .map(|part| part.trim_matches(|c| c == '"' || c == '`'))
+        .collect();
+    let name = escape_sql_string(parts.last().copied().unwrap_or_default());
+    let sql = match db.get_database_type() {
+        DatabaseType::PostgreSQL => format!(
+            "SELECT attname FROM pg_attribute WHERE attrelid = '{}'::regclass \
+             AND attnum > 0 AND (attidentity <> '' OR attgenerated <> '')",
+            escape_sql_string(table)
+        ),
+        DatabaseType::MySQL => {
+            let schema = match parts.as_slice() {
+                [schema, _] => format!("'{}'", escape_sql_string(schema)),
+                _ => "DATABASE()".to_string(),
+            };
+            format!(
+                "SELECT COLUMN_NAME FROM information_schema.COLUMNS \
+                 WHERE TABLE_SCHEMA = {schema} AND TABLE_NAME = '{name}' \
+                 AND (EXTRA LIKE '%auto_increment%' OR EXTRA LIKE '%GENERATED%')"
+            )
+        }
+        DatabaseType::SQLite => {
+            format!("SELECT name FROM pragma_table_xinfo('{name}') WHERE hidden IN (2, 3)")
+        }
+        _ => return HashSet::new(),
+    };
+    first_column(db, &sql).await.into_iter().collect()
+}
+
+/// Rows of the referenced columns already in `table`
+async fn sample_references(
+    db: &Database,
+    table: &str,
+    columns: &[String],
+    database_type: &DatabaseType,
+) -> Vec<Vec<String>> {
+    let quoted: Vec<String> = columns
+        .iter()
+        .map(|column| quote_identifier(column, database_type))
+        .collect();
+    let quoted_table = table
+        .split('.')
+        .map(|part| quote_identifier(part, database_type))
+        .collect::<Vec<_>>()
+        .join(".");
+    let not_null = quoted
+        .iter()
+        .map(|column| format!("{column} IS NOT NULL"))
+        .collect::<Vec<_>>()
+        .join(" AND ");
+    let sql = format!(
+        "SELECT DISTINCT {} FROM {quoted_table} WHERE {not_null} LIMIT {REFERENCE_SAMPLE}",
+        quoted.join(", ")
+    );
+    let Some(client) = db.get_database_client() else {
+        return Vec::new();
+    };
+    match client.execute_query(&sql).await {
+        Ok(rows) => rows.into_iter().skip(1).collect(),
+        Err(e) => {
+            tracing::debug!("Could not sample {}: {}", table, e);
+            Vec::new()
+        }
+    }
+}
+
+/// Columns and constraints of `table`, the schema split off on PostgreSQL
+pub async fn table_details(db: &mut Database, table: &str) -> Result<TableDetails, String> {
+    let details = match table.split_once('.') {
+        Some((schema, name)) if matches!(db.get_database_type(), DatabaseType::PostgreSQL) => {
+            db.get_table_details_in_schema(name, Some(schema)).await
+        }
+        _ => db.get_table_details(table).await,
+    };
+    details.map_err(|e| format!("Failed to read columns of {table}: {e}"))
+}
+
+/// Read `table` and make `count` rows for it
+pub async fn generate_rows(
+    db: &mut Database,
+    table: &str,
+    count: usize,
+) -> Result<GeneratedRows, String> {
+    let database_type = db.get_database_type();
+    let details = table_details(db, table).await?;
+    if details.columns.is_empty() {
+        return Err(format!("Table {table} has no columns"));
+    }
+    let generated = server_generated_columns(db, table).await;
+    let primary_key = crate::export_chunks::primary_key(db, table)
+        .await
+        .unwrap_or_default();
+
+    let mut plans: Vec<ColumnPlan> = details
+        .columns
+        .iter()
+        .map(|column| {
+            let spec = ColumnSpec::from(column);
+            let key = primary_key.contains(&spec.name);
+            let generator = if generated.contains(&spec.name)
+                || is_auto_default(spec.default_value.as_deref())
+            {
+                Generator::Default
+            } else if !spec.enum_values.is_empty() {
+                Generator::Choice(spec.enum_values.clone())
+            } else {
+                Generator::Fake(fake_for(&spec))
+            };
+            ColumnPlan {
+                max_len: max_length(&spec.data_type),
+                null_rate: if key { 0.0 } else { NULL_RATE },
+                generator,
+                spec,
+            }
+        })
+        .collect();
+
+    // A single-column integer key the server does not fill counts up; on
+    // SQLite an INTEGER PRIMARY KEY is the rowid and fills itself
+    if let [key] = primary_key.as_slice()
+        && let Some(plan) = plans.iter_mut().find(|plan| &plan.spec.name == key)
+        && plan.generator != Generator::Default
+        && plan.spec.kind == ValueKind::Integer
+    {
+        if database_type == DatabaseType::SQLite
+            && plan.spec.data_type.eq_ignore_ascii_case("integer")
+        {
+            plan.generator = Generator::Default;
+        } else {
+            let sql = format!(
+                "SELECT MAX({}) FROM {}",
+                quote_identifier(key, &database_type),
+                table
+                    .split('.')
+                    .map(|part| quote_identifier(part, &database_type))
+                    .collect::<Vec<_>>()
+                    .join(".")
+            );
+            let max = first_column(db, &sql)
+                .await
+                .first()
+                .and_then(|max| max.parse::<i64>().ok())
+                .unwrap_or(0);
+            plan.generator = Generator::Sequence(max + 1);
+        }
+    }
+
+    let mut references = Vec::new();
+    for fk in details
+        .foreign_keys
+        .iter()
+        .filter_map(|fk| parse_foreign_key(&fk.definition))
+        .filter(|fk| fk.referenced.len() == fk.columns.len())
+    {
+        let rows = sample_references(db, &fk.table, &fk.referenced, &database_type).await;
+        for (position, column) in fk.columns.iter().enumerate() {
+            let Some(plan) = plans.iter_mut().find(|plan| &plan.spec.name == column) else {
+                continue;
+            };
+            if rows.is_empty() && !plan.spec.nullable {
+                return Err(format!(
+                    "{column} references {}, which has no rows to point to; fill it first",
+                    fk.table
+                ));
+            }
+            plan.generator = Generator::Reference {
+                group: references.len(),
+                position,
+            };
+            plan.null_rate = if rows.is_empty() { 1.0 } else { 0.0 };
+        }
+        references.push(rows);
+    }
+
+    GeneratedRows::new(plans, references, count)
+}
+
+/// Make and insert the rows in batches of [`BATCH_ROWS`]; Ctrl-C stops
+/// between batches. Returns the number of rows inserted.
+pub async fn insert(
+    db: &mut Database,
+    table: &str,
+    generated: &mut GeneratedRows,
+) -> Result<usize, String> {
+    let columns = generated.columns();
+    let total = generated.count;
+    let batches = std::iter::from_fn(|| generated.next_batch());
+    insert_batches(db, table, &columns, total, batches).await
+}
+
+/// [`insert`] for any rows of `columns` (`\pastedata` outside PostgreSQL)
+pub async fn insert_rows(
+    db: &mut Database,
+    table: &str,
+    columns: &[ColumnSpec],
+    rows: &[Vec<ColumnValue>],
+) -> Result<usize, String> {
+    let batches = rows.chunks(BATCH_ROWS).map(Ok);
+    insert_batches(db, table, columns, rows.len(), batches).await
+}
+
+/// Insert `total` rows of `columns`, one INSERT batch at a time
+async fn insert_batches<B: AsRef<[Vec<ColumnValue>]>>(
+    db: &mut Database,
+    table: &str,
+    columns: &[ColumnSpec],
+    total: usize,
+    mut batches: impl Iterator<Item = Result<B, String>>,
+) -> Result<usize, String> {
+    let database_type = db.get_database_type();
+    let interrupt = crate::database::interrupt_flag();
+    interrupt.store(false, Ordering::SeqCst);
+    let show_progress = std::io::stderr().is_terminal() && total > BATCH_ROWS;
+    let total = crate::query_progress::group_digits(total as i64);
+    let mut inserted = 0;
+    loop {
+        if interrupt.load(Ordering::SeqCst) {
+            if show_progress {
+                eprint!("\r\x1b[K");
+            }
+            return Err(format!(
+                "Cancelled after {inserted} row(s) were inserted into {table}"
+            ));
+        }
+        let batch = match batches.next() {
+            Some(Ok(batch)) => batch,
+            Some(Err(e)) => {
+                if show_progress {
+                    eprint!("\r\x1b[K");
+                }
+                return Err(format!(
+                    "Stopped after {inserted} row(s) were inserted: {e}"
+                ));
+            }
+            None => break,
+        };
+        let batch = batch.as_ref();
+        let statements = build_insert_statements(table, columns, batch, &database_type);
+        for statement in statements {
+            if let Err(e) = db.execute_query(&statement).await {
+                if show_progress {
+                    eprint!("\r\x1b[K");
+                }
+                return Err(format!(
+                    "INSERT failed after {inserted} row(s) were inserted: {e}"
+                ));
+            }
+        }
+        inserted += batch.len();
+        if show_progress {
+            eprint!(
+                "\r{} / {total} rows inserted...",
+                crate::query_progress::group_digits(inserted as i64)
+            );
+        }
+    }
+    if show_progress {
+        eprint!("\r\x1b[K");
+    }
+    Ok(inserted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(name: &str, data_type: &str, nullable: bool) -> ColumnSpec {
+        ColumnSpec {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            kind: ValueKind::from_type(data_type),
+            nullable,
+            default_value: None,
+            enum_values: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_fake_for_columns() {
+        assert_eq!(fake_for(&spec("email", "varchar(255)", false)), Fake::Email);
+        assert_eq!(
+            fake_for(&spec("first_name", "text", false)),
+            Fake::FirstName
+        );
+        assert_eq!(fake_for(&spec("name", "text", false)), Fake::FullName);
+        assert_eq!(
+            fake_for(&spec("created_at", "timestamp with time zone", false)),
+            Fake::Timestamp
+        );
+        assert_eq!(fake_for(&spec("birthday", "date", true)), Fake::Date);
+        assert_eq!(fake_for(&spec("id", "uuid", false)), Fake::Uuid);
+        assert_eq!(
+            fake_for(&spec("age", "integer", true)),
+            Fake::Integer { min: 18, max: 90 }
+        );
+        assert_eq!(fake_for(&spec("active", "tinyint(1)", false)), Fake::Bit);
+        assert_eq!(
+            fake_for(&spec("price", "numeric(5,2)", false)),
+            Fake::Decimal {
+                max: 999.0,
+                scale: 2
+            }
+        );
+        assert_eq!(fake_for(&spec("sku", "text", false)), Fake::Text);
+        assert_eq!(max_length("character varying(12)"), Some(12));
+        assert_eq!(max_length("integer"), None);
+        assert!(is_auto_default(Some("nextval('users_id_seq'::regclass)")));
+        assert!(!is_auto_default(Some("now()")));
+    }
+
+    #[test]
+    fn test_generate_respects_plans() {
+        let plan = |spec: ColumnSpec, generator: Generator| ColumnPlan {
+            max_len: max_length(&spec.data_type),
+            null_rate: 0.0,
+            spec,
+            generator,
+        };
+        let plans = vec![
+            plan(spec("id", "integer", false), Generator::Sequence(41)),
+            plan(spec("serial", "integer", false), Generator::Default),
+            plan(
+                spec("user_id", "integer", false),
+                Generator::Reference {
+                    group: 0,
+                    position: 0,
+                },
+            ),
+            plan(
+                spec("code", "varchar(10)", false),
+                Generator::Fake(Fake::Text),
+            ),
+            plan(
+                spec("mood", "mood", false),
+                Generator::Choice(vec!["happy".to_string()]),
+            ),
+        ];
+        let references = vec![vec![vec!["7".to_string()], vec!["9".to_string()]]];
+        let mut rng = StdRng::seed_from_u64(1);
+        let rows = generate(&plans, &references, 0..3, 123_456, &mut rng).unwrap();
+        assert_eq!(rows.len(), 3);
+        for (i, row) in rows.iter().enumerate() {
+            assert_eq!(row[0], ColumnValue::Value((41 + i).to_string()));
+            assert_eq!(row[1], ColumnValue::Default);
+            assert!(matches!(&row[2], ColumnValue::Value(v) if v == "7" || v == "9"));
+            // The number that keeps the values distinct survives the cut
+            assert_eq!(row[3], ColumnValue::Value(format!("code{}", 123_456 + i)));
+            assert_eq!(row[4], ColumnValue::Value("happy".to_string()));
+        }
+
+        let generated = GeneratedRows::new(plans.clone(), references.clone(), 3).unwrap();
+        let preview = generated.preview(2);
+        assert_eq!(preview.len(), 3);
+        assert_eq!(preview[0], ["id", "user_id", "code", "mood"]);
+
+        let short = vec![plan(
+            spec("code", "varchar(4)", false),
+            Generator::Fake(Fake::Text),
+        )];
+        assert!(
+            generate(&short, &[], 0..1, 123_456, &mut rng)
+                .unwrap_err()
+                .contains("code (varchar(4)) is too short")
+        );
+    }
+
+    #[test]
+    fn test_generated_rows_come_in_batches() {
+        let plans = vec![ColumnPlan {
+            spec: spec("id", "integer", false),
+            generator: Generator::Sequence(1),
+            max_len: None,
+            null_rate: 0.0,
+        }];
+        let mut generated = GeneratedRows::new(plans, Vec::new(), BATCH_ROWS + 2).unwrap();
+        let sizes: Vec<usize> = std::iter::from_fn(|| generated.next_batch())
+            .map(|batch| batch.unwrap().len())
+            .collect();
+        assert_eq!(sizes, [BATCH_ROWS, 2]);
+    }
+}
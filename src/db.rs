@@ -2,10 +2,11 @@ use crate::config::SSHTunnelConfig;
 use crate::database::{
     ConnectionInfo, DatabaseClient, DatabaseType, DatabaseTypeExt, create_database_client,
 };
+use crate::error::{DbcrustError, Result};
 use crate::pgpass;
 
 use inquire::MultiSelect;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::error::Error as StdError;
 use tracing::{debug, info};
 
@@ -36,6 +37,8 @@ pub struct ColumnFilteringInfo {
     pub total_columns: usize,
     pub displayed_columns: usize,
     pub filtered_column_names: Vec<String>,
+    /// Saved column view (`\\csave`) that produced this selection
+    pub view_name: Option<String>,
 }
 
 impl ColumnFilteringInfo {
@@ -48,6 +51,7 @@ impl ColumnFilteringInfo {
             total_columns,
             displayed_columns,
             filtered_column_names,
+            view_name: None,
         }
     }
 
@@ -61,12 +65,47 @@ impl ColumnFilteringInfo {
 pub struct QueryResultsWithInfo {
     pub data: Vec<Vec<String>>,
     pub column_info: Option<ColumnFilteringInfo>,
+    /// Result sets after the first one (stored procedures), header row first
+    pub more_results: Vec<Vec<Vec<String>>>,
+    /// Rows changed by an INSERT/UPDATE/DELETE, when the driver reports it
+    pub outcome: Option<crate::database::StatementOutcome>,
+    /// How old the rows are when they came from the result cache
+    pub cached_age: Option<std::time::Duration>,
+    /// Date/time columns of `data` by name, rendered per `[display]`
+    pub temporal_columns: HashMap<String, crate::format::TemporalKind>,
+    /// bytea/BLOB columns of `data` by name, shortened when displayed
+    pub binary_columns: HashSet<String>,
+}
+
+impl QueryResultsWithInfo {
+    /// `3 rows affected`, plus the insert id when there is one; `None` for
+    /// statements that returned rows or whose outcome is unknown
+    pub fn affected_rows_message(&self) -> Option<String> {
+        if !self.data.is_empty() || !self.more_results.is_empty() {
+            return None;
+        }
+        let outcome = self.outcome?;
+        let noun = if outcome.rows_affected == 1 {
+            "row"
+        } else {
+            "rows"
+        };
+        Some(match outcome.last_insert_id {
+            Some(id) => format!(
+                "{} {noun} affected (last insert id: {id})",
+                outcome.rows_affected
+            ),
+            None => format!("{} {noun} affected", outcome.rows_affected),
+        })
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FrontendMode {
     Cli,
     Gui,
+    /// Unattended runs (`dbcrust check`): no status lines, no pickers
+    Batch,
 }
 
 impl FrontendMode {
@@ -79,6 +118,23 @@ impl FrontendMode {
     }
 }
 
+/// Set when stdout carries data (`\export <format> - ...`): connection status
+/// lines then go to stderr so the output stays pipeable
+static STATUS_TO_STDERR: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+pub fn set_status_to_stderr(enabled: bool) {
+    STATUS_TO_STDERR.store(enabled, std::sync::atomic::Ordering::Relaxed);
+}
+
+/// Print a connection status line to stdout, or stderr when stdout carries data
+pub fn print_status(line: &str) {
+    if STATUS_TO_STDERR.load(std::sync::atomic::Ordering::Relaxed) {
+        eprintln!("{line}");
+    } else {
+        println!("{line}");
+    }
+}
+
 pub struct Database {
     // Database abstraction layer client
     database_client: Option<Box<dyn DatabaseClient>>,
@@ -101,8 +157,28 @@ pub struct Database {
     column_selection_default_all: bool,
     column_views: HashMap<String, Vec<String>>, // Map of column view name -> selected columns
     last_view_key: Option<String>,
+    saved_column_views: HashMap<String, crate::config::ColumnView>, // \csave views by name
+    last_query_fingerprint: Option<String>,
+    last_column_selection: Option<(String, Vec<String>)>, // (fingerprint, columns) for \csave
+    table_prefs: crate::table_prefs::TablePrefsStore,     // \prefs, reapplied per table
+    last_prefs_key: Option<String>, // table_prefs key of the last query that returned rows
+    expanded_override: Option<bool>, // Remembered \x state for the last result
     last_json_plan: Option<String>, // Store the last EXPLAIN JSON plan for copying
+    last_plan_query: Option<String>, // Query last_json_plan explains, for \ev upload
     frontend_mode: FrontendMode,
+    stats: crate::session_stats::SessionStats, // \stats counters
+    result_cache: crate::result_cache::ResultCache, // \cache, repeated SELECTs
+    query_tags: crate::query_tags::QueryTags,  // [observability] comment, \tag
+    schema_cache: crate::schema_cache::SchemaCache, // Completion metadata saved on disk
+    idle_state: crate::keepalive::IdleState,   // [connection] keepalive pings
+    offline_queue: crate::offline_queue::OfflineQueue, // \queue, SQL typed while down
+    client_cert_watch: crate::client_cert::CertWatch, // [tls] client_cert, reloaded when rotated
+    last_binary_cells: crate::complex_display::BinaryCells, // Raw bytes for \saveblob
+    active_role: Option<String>,               // \setrole, shown in the prompt
+    last_row_query: Option<String>,            // Last statement that returned rows, for \gexec
+    transaction_open: bool,                    // BEGIN seen and not yet committed or rolled back
+    last_query: Option<String>,                // Last statement run from the prompt, for \fmt
+    last_result: Vec<Vec<String>>,             // Last result with rows as displayed, for \transpose
 }
 
 impl Database {
@@ -111,7 +187,7 @@ impl Database {
         url: &str,
         default_limit: Option<usize>,
         expanded_display_default: Option<bool>,
-    ) -> std::result::Result<Self, Box<dyn StdError>> {
+    ) -> Result<Self> {
         Self::from_url_with_mode(
             url,
             default_limit,
@@ -126,10 +202,18 @@ impl Database {
         default_limit: Option<usize>,
         expanded_display_default: Option<bool>,
         frontend_mode: FrontendMode,
-    ) -> std::result::Result<Self, Box<dyn StdError>> {
+    ) -> Result<Self> {
         debug!("[Database::from_url] Creating database from URL");
         let step_start = std::time::Instant::now();
 
+        let expanded;
+        let url = if crate::config::has_env_placeholders(url) {
+            expanded = crate::config::expand_env_in_url(url).map_err(DbcrustError::Config)?;
+            expanded.as_str()
+        } else {
+            url
+        };
+
         // Handle Docker URLs specially
         if url.starts_with("docker://") {
             let (database, _) = Self::from_docker_url_with_mode(
@@ -146,7 +230,8 @@ impl Database {
         let config_start = std::time::Instant::now();
         let config = crate::config::Config::load();
         debug!("📋 Parsing connection URL...");
-        let connection_info = ConnectionInfo::parse_url(url)?;
+        let connection_info =
+            ConnectionInfo::parse_url(url).map_err(DbcrustError::from_url_error)?;
         debug!(
             "[Database::from_url] Parsed URL in {:?}",
             step_start.elapsed()
@@ -164,9 +249,11 @@ impl Database {
             .await;
         }
 
-        // For PostgreSQL/MySQL, check for SSH tunnel patterns
+        // For PostgreSQL/MySQL, --ssh-tunnel wins over the configured patterns
         debug!("🔍 Checking for SSH tunnel patterns...");
-        let ssh_tunnel_config = if let Some(ref host) = connection_info.host {
+        let ssh_tunnel_config = if let Some(target) = crate::ssh_tunnel::explicit_tunnel() {
+            config.parse_ssh_tunnel_string(&target)
+        } else if let Some(ref host) = connection_info.host {
             config.get_ssh_tunnel_for_host(host)
         } else {
             None
@@ -214,7 +301,7 @@ impl Database {
         url: &str,
         default_limit: Option<usize>,
         expanded_display_default: Option<bool>,
-    ) -> std::result::Result<(Self, Option<ConnectionInfo>), Box<dyn StdError>> {
+    ) -> Result<(Self, Option<ConnectionInfo>)> {
         Self::from_docker_url_with_mode(
             url,
             default_limit,
@@ -229,42 +316,45 @@ impl Database {
         default_limit: Option<usize>,
         expanded_display_default: Option<bool>,
         frontend_mode: FrontendMode,
-    ) -> std::result::Result<(Self, Option<ConnectionInfo>), Box<dyn StdError>> {
+    ) -> Result<(Self, Option<ConnectionInfo>)> {
         debug!("[Database::from_docker_url] Creating database from Docker URL");
 
         // Parse Docker URL
-        let connection_info = ConnectionInfo::parse_url(url)?;
+        let connection_info =
+            ConnectionInfo::parse_url(url).map_err(DbcrustError::from_url_error)?;
 
         // Get the container name from the connection info
-        let container_name = connection_info
-            .docker_container
-            .as_ref()
-            .ok_or("Docker container name not found in URL")?;
+        let container_name = connection_info.docker_container.as_ref().ok_or_else(|| {
+            DbcrustError::Connection("Docker container name not found in URL".to_string())
+        })?;
 
         // If container name is empty, provide interactive selection
         if container_name.is_empty() {
             if !frontend_mode.allows_interactive_terminal_ui() {
-                return Err("Interactive Docker container selection is not available in GUI mode. Provide an explicit container name.".into());
+                return Err(DbcrustError::Connection("Interactive Docker container selection is not available in GUI mode. Provide an explicit container name.".to_string()));
             }
             let selected_container = Self::select_docker_container().await?;
 
             // Create Docker client
-            let docker_client = crate::docker::DockerClient::new()
-                .map_err(|e| format!("Failed to create Docker client: {e}"))?;
+            let docker_client = crate::docker::DockerClient::new().map_err(|e| {
+                DbcrustError::Connection(format!("Failed to create Docker client: {e}"))
+            })?;
 
             let container_info = docker_client
                 .inspect_container(&selected_container)
                 .await
                 .map_err(|e| {
-                    format!(
+                    DbcrustError::Connection(format!(
                         "Failed to inspect selected Docker container '{selected_container}': {e}"
-                    )
+                    ))
                 })?;
 
             // Build database connection info from container
             let docker_connection = docker_client
                 .build_connection_info(&container_info)
-                .map_err(|e| format!("Failed to build connection info: {e}"))?;
+                .map_err(|e| {
+                    DbcrustError::Connection(format!("Failed to build connection info: {e}"))
+                })?;
 
             // Create a new ConnectionInfo with the resolved Docker information
             let resolved_connection_info = ConnectionInfo {
@@ -284,6 +374,7 @@ impl Database {
                 options: connection_info.options,
                 docker_container: Some(selected_container.clone()),
                 use_tls: connection_info.use_tls,
+                srv: connection_info.srv,
             };
 
             let database = Self::from_connection_info_with_mode(
@@ -298,18 +389,25 @@ impl Database {
         }
 
         // Create Docker client and inspect the container
-        let docker_client = crate::docker::DockerClient::new()
-            .map_err(|e| format!("Failed to create Docker client: {e}"))?;
+        let docker_client = crate::docker::DockerClient::new().map_err(|e| {
+            DbcrustError::Connection(format!("Failed to create Docker client: {e}"))
+        })?;
 
         let container_info = docker_client
             .inspect_container(container_name)
             .await
-            .map_err(|e| format!("Failed to inspect Docker container '{container_name}': {e}"))?;
+            .map_err(|e| {
+                DbcrustError::Connection(format!(
+                    "Failed to inspect Docker container '{container_name}': {e}"
+                ))
+            })?;
 
         // Build database connection info from container
         let docker_connection = docker_client
             .build_connection_info(&container_info)
-            .map_err(|e| format!("Failed to build connection info: {e}"))?;
+            .map_err(|e| {
+                DbcrustError::Connection(format!("Failed to build connection info: {e}"))
+            })?;
 
         // Create a new ConnectionInfo with the resolved Docker information
         let resolved_connection_info = ConnectionInfo {
@@ -329,6 +427,7 @@ impl Database {
             options: connection_info.options,
             docker_container: Some(container_name.clone()),
             use_tls: connection_info.use_tls,
+            srv: connection_info.srv,
         };
 
         debug!(
@@ -352,24 +451,27 @@ impl Database {
     }
 
     /// Interactive Docker container selection
-    async fn select_docker_container() -> std::result::Result<String, Box<dyn StdError>> {
+    async fn select_docker_container() -> Result<String> {
         println!("🐳 Discovering Docker database containers...");
 
         // Create Docker client
-        let docker_client = crate::docker::DockerClient::new()
-            .map_err(|e| format!("Failed to create Docker client: {e}"))?;
+        let docker_client = crate::docker::DockerClient::new().map_err(|e| {
+            DbcrustError::Connection(format!("Failed to create Docker client: {e}"))
+        })?;
 
         // List all database containers
         let containers = docker_client
             .list_database_containers()
             .await
-            .map_err(|e| format!("Failed to list Docker containers: {e}"))?;
+            .map_err(|e| {
+                DbcrustError::Connection(format!("Failed to list Docker containers: {e}"))
+            })?;
 
         if containers.is_empty() {
-            return Err(
+            return Err(DbcrustError::Connection(
                 "No database containers found. Make sure you have database containers running."
-                    .into(),
-            );
+                    .to_string(),
+            ));
         }
 
         // Separate running and stopped containers
@@ -404,10 +506,10 @@ impl Database {
 
         // Check if we have any running containers
         if running_containers.is_empty() {
-            return Err(
+            return Err(DbcrustError::Connection(
                 "No running database containers found. Please start a database container first."
-                    .into(),
-            );
+                    .to_string(),
+            ));
         }
 
         // Create selection options only for running containers
@@ -435,13 +537,13 @@ impl Database {
         // Show interactive selection and get the index
         let selected_index = inquire::Select::new("Select a database container:", options.clone())
             .prompt()
-            .map_err(|e| format!("Selection cancelled: {e}"))?;
+            .map_err(|e| DbcrustError::Connection(format!("Selection cancelled: {e}")))?;
 
         // Find the index of the selected option
         let container_index = options
             .iter()
             .position(|option| option == &selected_index)
-            .ok_or("Invalid selection")?;
+            .ok_or_else(|| DbcrustError::Connection("Invalid selection".to_string()))?;
 
         // Get the selected container by index (from running containers only)
         let selected_container = running_containers[container_index];
@@ -464,7 +566,7 @@ impl Database {
         url: &str,
         default_limit: Option<usize>,
         expanded_display_default: Option<bool>,
-    ) -> std::result::Result<(Self, Option<ConnectionInfo>), Box<dyn StdError>> {
+    ) -> Result<(Self, Option<ConnectionInfo>)> {
         Self::from_docker_url_with_tracking_mode(
             url,
             default_limit,
@@ -479,7 +581,7 @@ impl Database {
         default_limit: Option<usize>,
         expanded_display_default: Option<bool>,
         frontend_mode: FrontendMode,
-    ) -> std::result::Result<(Self, Option<ConnectionInfo>), Box<dyn StdError>> {
+    ) -> Result<(Self, Option<ConnectionInfo>)> {
         Self::from_docker_url_with_mode(url, default_limit, expanded_display_default, frontend_mode)
             .await
     }
@@ -490,7 +592,7 @@ impl Database {
         default_limit: Option<usize>,
         expanded_display_default: Option<bool>,
         ssh_tunnel_config: Option<SSHTunnelConfig>,
-    ) -> std::result::Result<Self, Box<dyn StdError>> {
+    ) -> Result<Self> {
         Self::from_connection_info_with_mode(
             connection_info,
             default_limit,
@@ -507,7 +609,7 @@ impl Database {
         expanded_display_default: Option<bool>,
         ssh_tunnel_config: Option<SSHTunnelConfig>,
         frontend_mode: FrontendMode,
-    ) -> std::result::Result<Self, Box<dyn StdError>> {
+    ) -> Result<Self> {
         debug!("[Database::from_connection_info] Creating database from connection info");
 
         let config = crate::config::Config::load();
@@ -517,25 +619,39 @@ impl Database {
         {
             if tunnel_config.enabled {
                 // Establish SSH tunnel
-                let mut ssh_tunnel =
-                    crate::ssh_tunnel::SSHTunnel::new().ok_or("Failed to create SSH tunnel")?;
+                let mut ssh_tunnel = crate::ssh_tunnel::SSHTunnel::new().ok_or_else(|| {
+                    DbcrustError::Connection("Failed to create SSH tunnel".to_string())
+                })?;
 
-                let original_host = connection_info
-                    .host
-                    .as_ref()
-                    .ok_or("Host is required for SSH tunnel")?;
+                let original_host = connection_info.host.as_ref().ok_or_else(|| {
+                    DbcrustError::Connection("Host is required for SSH tunnel".to_string())
+                })?;
                 let original_port = connection_info
                     .port
                     .or_else(|| connection_info.default_port())
-                    .ok_or("Port is required for SSH tunnel")?;
+                    .ok_or_else(|| {
+                        DbcrustError::Connection("Port is required for SSH tunnel".to_string())
+                    })?;
 
                 let local_port = ssh_tunnel
                     .establish(tunnel_config, original_host, original_port)
-                    .await
-                    .map_err(|e| format!("Failed to establish SSH tunnel: {e}"))?;
+                    .await?;
 
                 // Create modified connection info to use the local tunnel port
                 let mut modified_connection_info = connection_info.clone();
+                // IAM tokens are signed for the server, not the tunnel
+                if matches!(
+                    crate::iam_auth::provider(&connection_info.options),
+                    Ok(Some(_))
+                ) {
+                    let options = &mut modified_connection_info.options;
+                    options
+                        .entry("auth_host".to_string())
+                        .or_insert_with(|| original_host.clone());
+                    options
+                        .entry("auth_port".to_string())
+                        .or_insert_with(|| original_port.to_string());
+                }
                 modified_connection_info.host = Some("127.0.0.1".to_string());
                 modified_connection_info.port = Some(local_port);
 
@@ -551,7 +667,9 @@ impl Database {
         debug!("[Database::from_connection_info] Creating database client");
         let database_client = create_database_client(final_connection_info)
             .await
-            .map_err(|e| format!("Failed to create database client: {e}"))?;
+            .map_err(|e| {
+                DbcrustError::Connection(format!("Failed to create database client: {e}"))
+            })?;
 
         let db = Self {
             database_client: Some(database_client),
@@ -568,7 +686,31 @@ impl Database {
             column_selection_default_all: config.column_selection_default_all,
             column_views: HashMap::new(),
             last_view_key: None,
+            saved_column_views: config.column_views().clone(),
+            last_query_fingerprint: None,
+            last_column_selection: None,
+            table_prefs: if frontend_mode.allows_interactive_terminal_ui() {
+                crate::table_prefs::TablePrefsStore::load()
+            } else {
+                Default::default()
+            },
+            last_prefs_key: None,
+            expanded_override: None,
             last_json_plan: None,
+            last_plan_query: None,
+            stats: Default::default(),
+            result_cache: crate::result_cache::ResultCache::new(&config.result_cache),
+            query_tags: crate::query_tags::QueryTags::new(&config.observability),
+            schema_cache: Default::default(),
+            idle_state: Default::default(),
+            offline_queue: Default::default(),
+            client_cert_watch: Default::default(),
+            last_binary_cells: Default::default(),
+            active_role: None,
+            last_row_query: None,
+            transaction_open: false,
+            last_query: None,
+            last_result: Vec::new(),
             frontend_mode,
         };
 
@@ -594,7 +736,7 @@ impl Database {
         expanded_display_default: Option<bool>,
         ssh_tunnel_config: Option<SSHTunnelConfig>,
         _ssl_mode: Option<sqlx::postgres::PgSslMode>,
-    ) -> std::result::Result<Self, Box<dyn StdError>> {
+    ) -> Result<Self> {
         // Legacy method - convert parameters to ConnectionInfo and use from_connection_info
         let password = if password_param.is_empty() {
             pgpass::lookup_password(host, port, dbname, user)
@@ -613,6 +755,7 @@ impl Database {
             options: HashMap::new(),
             docker_container: None,
             use_tls: false,
+            srv: false,
         };
 
         Self::from_connection_info(
@@ -625,36 +768,35 @@ impl Database {
     }
 
     /// Prefetch metadata asynchronously to warm up autocompletion cache
-    pub async fn connect_to_db(
-        &mut self,
-        dbname: &str,
-    ) -> std::result::Result<(), Box<dyn StdError>> {
+    pub async fn connect_to_db(&mut self, dbname: &str) -> Result<()> {
         // Use new database abstraction layer
         if let Some(ref mut database_client) = self.database_client {
             debug!("Using database abstraction layer for connect_to_db");
-            return database_client
-                .connect_to_database(dbname)
-                .await
-                .map_err(|e| e.into());
+            database_client.connect_to_database(dbname).await?;
+            // Keep the \setrole role on the new connection when it exists there
+            if let Some(role) = self.active_role.clone()
+                && database_client.set_role(Some(&role)).await.is_err()
+            {
+                self.active_role = None;
+            }
+            Ok(())
         } else {
-            Err("No database client available".into())
+            Err(DbcrustError::no_client())
         }
     }
 
-    pub async fn list_databases(
-        &mut self,
-    ) -> std::result::Result<Vec<Vec<String>>, Box<dyn StdError>> {
+    pub async fn list_databases(&mut self) -> Result<Vec<Vec<String>>> {
         // Use new database abstraction layer
         if let Some(ref database_client) = self.database_client {
             debug!("Using database abstraction layer for list_databases");
             return database_client.list_databases().await.map_err(|e| e.into());
         } else {
-            Err("No database client available".into())
+            Err(DbcrustError::no_client())
         }
     }
 
     /// List users (database-specific implementation)
-    pub async fn list_users(&mut self) -> std::result::Result<Vec<Vec<String>>, Box<dyn StdError>> {
+    pub async fn list_users(&mut self) -> Result<Vec<Vec<String>>> {
         debug!("[Database::list_users] Listing database users");
 
         if let Some(ref database_client) = self.database_client {
@@ -671,18 +813,21 @@ impl Database {
                 ])
             } else {
                 match connection_info.database_type {
-                    crate::database::DatabaseType::MySQL => self
-                        .execute_query(
+                    crate::database::DatabaseType::MySQL => {
+                        self.execute_query(
                             "SELECT User, Host, account_locked FROM mysql.user ORDER BY User",
                         )
                         .await
-                        .map_err(|e| format!("Error listing MySQL users: {e}").into()),
+                        .map_err(|e| DbcrustError::Query(format!("Error listing MySQL users: {e}")))
+                    }
                     crate::database::DatabaseType::PostgreSQL => self
                         .execute_query(
                             "SELECT usename, usesuper, usecreatedb FROM pg_user ORDER BY usename",
                         )
                         .await
-                        .map_err(|e| format!("Error listing PostgreSQL users: {e}").into()),
+                        .map_err(|e| {
+                            DbcrustError::Query(format!("Error listing PostgreSQL users: {e}"))
+                        }),
                     _ => Ok(vec![
                         vec!["Error".to_string()],
                         vec!["Unsupported database type".to_string()],
@@ -690,14 +835,12 @@ impl Database {
                 }
             }
         } else {
-            Err("No database client available".into())
+            Err(DbcrustError::no_client())
         }
     }
 
     /// List indexes (primarily for SQLite)
-    pub async fn list_indexes(
-        &mut self,
-    ) -> std::result::Result<Vec<Vec<String>>, Box<dyn StdError>> {
+    pub async fn list_indexes(&mut self) -> Result<Vec<Vec<String>>> {
         debug!("[Database::list_indexes] Listing database indexes");
 
         // Try using the new database abstraction layer first
@@ -722,7 +865,11 @@ impl Database {
                 "#;
                 match self.execute_query(query).await {
                     Ok(results) => return Ok(results),
-                    Err(e) => return Err(format!("Error listing SQLite indexes: {e}").into()),
+                    Err(e) => {
+                        return Err(DbcrustError::Query(format!(
+                            "Error listing SQLite indexes: {e}"
+                        )));
+                    }
                 }
             } else {
                 match connection_info.database_type {
@@ -758,9 +905,7 @@ impl Database {
     }
 
     /// List pragmas (SQLite-specific)
-    pub async fn list_pragmas(
-        &mut self,
-    ) -> std::result::Result<Vec<Vec<String>>, Box<dyn StdError>> {
+    pub async fn list_pragmas(&mut self) -> Result<Vec<Vec<String>>> {
         debug!("[Database::list_pragmas] Listing database pragmas");
 
         // Try using the new database abstraction layer first
@@ -816,13 +961,11 @@ impl Database {
         }
 
         // Default response for when database abstraction layer is not available
-        Err("No database client available".into())
+        Err(DbcrustError::no_client())
     }
 
     /// List MongoDB collections
-    pub async fn list_collections(
-        &mut self,
-    ) -> std::result::Result<Vec<Vec<String>>, Box<dyn StdError>> {
+    pub async fn list_collections(&mut self) -> Result<Vec<Vec<String>>> {
         debug!("[Database::list_collections] Listing MongoDB collections");
 
         if let Some(ref database_client) = self.database_client {
@@ -847,9 +990,9 @@ impl Database {
                         ORDER BY name
                     "#;
 
-                    self.execute_query(query)
-                        .await
-                        .map_err(|e| format!("Error listing MongoDB collections: {e}").into())
+                    self.execute_query(query).await.map_err(|e| {
+                        DbcrustError::Query(format!("Error listing MongoDB collections: {e}"))
+                    })
                 }
                 _ => Ok(vec![
                     vec!["Error".to_string()],
@@ -857,7 +1000,7 @@ impl Database {
                 ]),
             }
         } else {
-            Err("No database client available".into())
+            Err(DbcrustError::no_client())
         }
     }
 
@@ -865,7 +1008,7 @@ impl Database {
     pub async fn describe_collection(
         &mut self,
         collection_name: &str,
-    ) -> std::result::Result<crate::db::TableDetails, Box<dyn StdError>> {
+    ) -> Result<crate::db::TableDetails> {
         debug!(
             "[Database::describe_collection] Describing MongoDB collection: {}",
             collection_name
@@ -881,19 +1024,21 @@ impl Database {
                         .get_metadata_provider()
                         .get_table_details(collection_name, None)
                         .await
-                        .map_err(|e| format!("Error describing MongoDB collection: {e}").into())
+                        .map_err(|e| {
+                            DbcrustError::Query(format!("Error describing MongoDB collection: {e}"))
+                        })
                 }
-                _ => Err("This command is only available for MongoDB databases".into()),
+                _ => Err(DbcrustError::Query(
+                    "This command is only available for MongoDB databases".to_string(),
+                )),
             }
         } else {
-            Err("No database client available".into())
+            Err(DbcrustError::no_client())
         }
     }
 
     /// List MongoDB indexes
-    pub async fn list_mongo_indexes(
-        &mut self,
-    ) -> std::result::Result<Vec<Vec<String>>, Box<dyn StdError>> {
+    pub async fn list_mongo_indexes(&mut self) -> Result<Vec<Vec<String>>> {
         debug!("[Database::list_mongo_indexes] Listing MongoDB indexes");
 
         if let Some(ref database_client) = self.database_client {
@@ -912,9 +1057,9 @@ impl Database {
                         ORDER BY ns, name
                     "#;
 
-                    self.execute_query(query)
-                        .await
-                        .map_err(|e| format!("Error listing MongoDB indexes: {e}").into())
+                    self.execute_query(query).await.map_err(|e| {
+                        DbcrustError::Query(format!("Error listing MongoDB indexes: {e}"))
+                    })
                 }
                 _ => Ok(vec![
                     vec!["Error".to_string()],
@@ -922,7 +1067,7 @@ impl Database {
                 ]),
             }
         } else {
-            Err("No database client available".into())
+            Err(DbcrustError::no_client())
         }
     }
 
@@ -932,7 +1077,7 @@ impl Database {
         collection: &str,
         field: &str,
         index_type: Option<&str>,
-    ) -> std::result::Result<(), Box<dyn StdError>> {
+    ) -> Result<()> {
         debug!(
             "[Database::create_mongo_index] Creating MongoDB index on {}.{}",
             collection, field
@@ -955,25 +1100,23 @@ impl Database {
                         "db.runCommand({{ createIndexes: \"{collection}\", indexes: [{{ key: {index_spec}, name: \"{collection}_{field}_idx\" }}] }})"
                     );
 
-                    self.execute_query(&query)
-                        .await
-                        .map_err(|e| format!("Error creating MongoDB index: {e}"))?;
+                    self.execute_query(&query).await.map_err(|e| {
+                        DbcrustError::Query(format!("Error creating MongoDB index: {e}"))
+                    })?;
 
                     Ok(())
                 }
-                _ => Err("This command is only available for MongoDB databases".into()),
+                _ => Err(DbcrustError::Query(
+                    "This command is only available for MongoDB databases".to_string(),
+                )),
             }
         } else {
-            Err("No database client available".into())
+            Err(DbcrustError::no_client())
         }
     }
 
     /// Drop MongoDB index
-    pub async fn drop_mongo_index(
-        &mut self,
-        collection: &str,
-        index_name: &str,
-    ) -> std::result::Result<(), Box<dyn StdError>> {
+    pub async fn drop_mongo_index(&mut self, collection: &str, index_name: &str) -> Result<()> {
         debug!(
             "[Database::drop_mongo_index] Dropping MongoDB index {} from {}",
             index_name, collection
@@ -989,23 +1132,122 @@ impl Database {
                         "db.runCommand({{ dropIndexes: \"{collection}\", index: \"{index_name}\" }})"
                     );
 
-                    self.execute_query(&query)
-                        .await
-                        .map_err(|e| format!("Error dropping MongoDB index: {e}"))?;
+                    self.execute_query(&query).await.map_err(|e| {
+                        DbcrustError::Query(format!("Error dropping MongoDB index: {e}"))
+                    })?;
 
                     Ok(())
                 }
-                _ => Err("This command is only available for MongoDB databases".into()),
+                _ => Err(DbcrustError::Query(
+                    "This command is only available for MongoDB databases".to_string(),
+                )),
             }
         } else {
-            Err("No database client available".into())
+            Err(DbcrustError::no_client())
         }
     }
 
-    /// Get MongoDB database statistics
-    pub async fn mongo_stats(
+    /// Stream a local CSV/TSV/JSONEachRow file into a ClickHouse table
+    pub async fn bulk_insert_file(
+        &mut self,
+        table: &str,
+        path: &std::path::Path,
+        format: &str,
+        progress: &(
+             dyn for<'p> Fn(&'p crate::database_clickhouse::BulkInsertProgress) + Send + Sync
+         ),
+    ) -> Result<crate::database_clickhouse::BulkInsertProgress> {
+        let database_client = self
+            .database_client
+            .as_ref()
+            .ok_or_else(DbcrustError::no_client)?;
+        let inserted = database_client
+            .bulk_insert_file(table, path, format, progress)
+            .await;
+        // Rows may have been sent even when the insert failed part way
+        self.result_cache.clear();
+        Ok(inserted?)
+    }
+
+    /// Load CSV rows into a PostgreSQL table with COPY (see
+    /// [`crate::database::DatabaseClient::copy_in_csv`])
+    pub async fn copy_in_csv(
+        &mut self,
+        table: &str,
+        columns: &[String],
+        data: Vec<u8>,
+    ) -> Result<u64> {
+        let database_client = self
+            .database_client
+            .as_ref()
+            .ok_or_else(DbcrustError::no_client)?;
+        let copied = database_client.copy_in_csv(table, columns, data).await?;
+        self.result_cache.clear();
+        Ok(copied)
+    }
+
+    /// Copy the open SQLite database to `path` with the online backup API
+    pub async fn backup_to_file(
+        &self,
+        path: &std::path::Path,
+        progress: &(dyn for<'p> Fn(&'p crate::database_sqlite::BackupProgress) + Send + Sync),
+    ) -> Result<crate::database_sqlite::BackupProgress> {
+        let database_client = self
+            .database_client
+            .as_ref()
+            .ok_or_else(DbcrustError::no_client)?;
+        Ok(database_client.backup_to_file(path, progress).await?)
+    }
+
+    /// `VACUUM` plus a WAL checkpoint; returns the database size before and after
+    pub async fn sqlite_vacuum(&self) -> Result<(u64, u64)> {
+        let database_client = self
+            .database_client
+            .as_ref()
+            .ok_or_else(DbcrustError::no_client)?;
+        Ok(crate::database_sqlite::vacuum(database_client.as_ref()).await?)
+    }
+
+    /// Problems found by `PRAGMA integrity_check` (`quick_check` when `quick`)
+    pub async fn sqlite_integrity_check(&self, quick: bool) -> Result<Vec<String>> {
+        let database_client = self
+            .database_client
+            .as_ref()
+            .ok_or_else(DbcrustError::no_client)?;
+        Ok(crate::database_sqlite::integrity_check(database_client.as_ref(), quick).await?)
+    }
+
+    /// Run `sql` and hand its rows to `sink` as they arrive instead of collecting them
+    pub async fn stream_query(
         &mut self,
-    ) -> std::result::Result<Vec<Vec<String>>, Box<dyn StdError>> {
+        sql: &str,
+        sink: &mut dyn crate::database::RowSink,
+    ) -> Result<u64> {
+        let database_client = self
+            .database_client
+            .as_ref()
+            .ok_or_else(DbcrustError::no_client)?;
+        let started = std::time::Instant::now();
+        let rows = database_client.stream_query(sql, sink).await;
+        self.stats
+            .record_query(sql, started.elapsed(), rows.as_ref().ok().copied(), 0);
+        Ok(rows?)
+    }
+
+    /// Run a built-in server diagnostic (see [`crate::database::ServerDiagnostic`])
+    pub async fn server_diagnostic(
+        &self,
+        diagnostic: crate::database::ServerDiagnostic,
+    ) -> Result<Vec<Vec<String>>> {
+        let database_client = self
+            .database_client
+            .as_ref()
+            .ok_or_else(DbcrustError::no_client)?;
+        Ok(database_client.server_diagnostic(diagnostic).await?)
+    }
+
+    /// Get MongoDB database statistics
+    pub async fn mongo_stats(&mut self) -> Result<Vec<Vec<String>>> {
         debug!("[Database::mongo_stats] Getting MongoDB database statistics");
 
         if let Some(ref database_client) = self.database_client {
@@ -1016,9 +1258,9 @@ impl Database {
                     // Get database statistics using MongoDB command
                     let query = "db.runCommand({ dbStats: 1 })";
 
-                    self.execute_query(query)
-                        .await
-                        .map_err(|e| format!("Error getting MongoDB stats: {e}").into())
+                    self.execute_query(query).await.map_err(|e| {
+                        DbcrustError::Query(format!("Error getting MongoDB stats: {e}"))
+                    })
                 }
                 _ => Ok(vec![
                     vec!["Error".to_string()],
@@ -1026,7 +1268,7 @@ impl Database {
                 ]),
             }
         } else {
-            Err("No database client available".into())
+            Err(DbcrustError::no_client())
         }
     }
 
@@ -1037,7 +1279,7 @@ impl Database {
         filter: Option<&str>,
         projection: Option<&str>,
         limit: Option<i64>,
-    ) -> std::result::Result<Vec<Vec<String>>, Box<dyn StdError>> {
+    ) -> Result<Vec<Vec<String>>> {
         debug!(
             "[Database::mongo_find] Executing MongoDB find on collection: {}",
             collection
@@ -1056,9 +1298,9 @@ impl Database {
                     let query =
                         format!("db.{collection}.find({filter_str}{projection_str}){limit_str}");
 
-                    self.execute_query(&query)
-                        .await
-                        .map_err(|e| format!("Error executing MongoDB find: {e}").into())
+                    self.execute_query(&query).await.map_err(|e| {
+                        DbcrustError::Query(format!("Error executing MongoDB find: {e}"))
+                    })
                 }
                 _ => Ok(vec![
                     vec!["Error".to_string()],
@@ -1066,7 +1308,7 @@ impl Database {
                 ]),
             }
         } else {
-            Err("No database client available".into())
+            Err(DbcrustError::no_client())
         }
     }
 
@@ -1075,7 +1317,7 @@ impl Database {
         &mut self,
         collection: &str,
         pipeline: &str,
-    ) -> std::result::Result<Vec<Vec<String>>, Box<dyn StdError>> {
+    ) -> Result<Vec<Vec<String>>> {
         debug!(
             "[Database::mongo_aggregate] Executing MongoDB aggregation on collection: {}",
             collection
@@ -1089,9 +1331,9 @@ impl Database {
                     // Execute aggregation pipeline
                     let query = format!("db.{collection}.aggregate({pipeline})");
 
-                    self.execute_query(&query)
-                        .await
-                        .map_err(|e| format!("Error executing MongoDB aggregation: {e}").into())
+                    self.execute_query(&query).await.map_err(|e| {
+                        DbcrustError::Query(format!("Error executing MongoDB aggregation: {e}"))
+                    })
                 }
                 _ => Ok(vec![
                     vec!["Error".to_string()],
@@ -1099,7 +1341,7 @@ impl Database {
                 ]),
             }
         } else {
-            Err("No database client available".into())
+            Err(DbcrustError::no_client())
         }
     }
 
@@ -1108,7 +1350,7 @@ impl Database {
         &mut self,
         collection: &str,
         search_term: &str,
-    ) -> std::result::Result<Vec<Vec<String>>, Box<dyn StdError>> {
+    ) -> Result<Vec<Vec<String>>> {
         debug!(
             "[Database::mongo_text_search] Executing text search on collection: {}",
             collection
@@ -1121,7 +1363,9 @@ impl Database {
                     let filter = format!(r#"{{"$text": {{"$search": "{search_term}"}}}}"#);
                     self.mongo_find(collection, Some(&filter), None, Some(10))
                         .await
-                        .map_err(|e| format!("Error executing text search: {e}").into())
+                        .map_err(|e| {
+                            DbcrustError::Query(format!("Error executing text search: {e}"))
+                        })
                 }
                 _ => Ok(vec![
                     vec!["Error".to_string()],
@@ -1129,7 +1373,7 @@ impl Database {
                 ]),
             }
         } else {
-            Err("No database client available".into())
+            Err(DbcrustError::no_client())
         }
     }
 
@@ -1153,6 +1397,147 @@ impl Database {
         }
     }
 
+    /// Role switched to with `\\setrole`, if any
+    pub fn active_role(&self) -> Option<&str> {
+        self.active_role.as_deref()
+    }
+
+    /// Switch the session to `role`, or back to the login role with `None`
+    pub async fn set_role(&mut self, role: Option<&str>) -> Result<()> {
+        let client = self
+            .database_client
+            .as_ref()
+            .ok_or_else(DbcrustError::no_client)?;
+        client.set_role(role).await?;
+        self.active_role = role.map(str::to_string);
+        Ok(())
+    }
+
+    /// Idle tracking for the `[connection]` keepalive pings
+    pub fn idle_state_mut(&mut self) -> &mut crate::keepalive::IdleState {
+        &mut self.idle_state
+    }
+
+    /// Statements waiting for the connection to come back (`\\queue`)
+    pub fn offline_queue_mut(&mut self) -> &mut crate::offline_queue::OfflineQueue {
+        &mut self.offline_queue
+    }
+
+    /// Client certificate files of the connection, checked for rotation
+    pub fn client_cert_watch_mut(&mut self) -> &mut crate::client_cert::CertWatch {
+        &mut self.client_cert_watch
+    }
+
+    /// Open a new connection with the same settings after this one died:
+    /// the SSH tunnel is started again when its process exited, and the
+    /// schema, warehouse and role switched to are restored
+    pub async fn reconnect(&mut self) -> Result<()> {
+        let client = self
+            .database_client
+            .as_ref()
+            .ok_or_else(DbcrustError::no_client)?;
+        let mut connection_info = client.get_connection_info().clone();
+        let schema = self.current_schema();
+        let warehouse = self.current_warehouse();
+
+        if let Some(tunnel) = self.ssh_tunnel.as_mut()
+            && !tunnel.is_active()
+        {
+            let local_port = tunnel.reopen().await?;
+            connection_info.port = Some(local_port);
+        }
+        let client = create_database_client(connection_info)
+            .await
+            .map_err(|e| DbcrustError::Connection(format!("Failed to reconnect: {e}")))?;
+        // The old pool only holds dead connections
+        self.database_client = Some(client);
+        self.transaction_open = false;
+        // The new connection read the client certificate files again
+        self.client_cert_watch = crate::client_cert::CertWatch::for_connection(self);
+
+        if let Some(schema) = schema
+            && let Err(e) = self.set_schema(&schema).await
+        {
+            debug!(
+                "[Database::reconnect] Could not restore schema {}: {}",
+                schema, e
+            );
+        }
+        if let Some(warehouse) = warehouse
+            && let Err(e) = self.set_warehouse(&warehouse).await
+        {
+            debug!(
+                "[Database::reconnect] Could not restore warehouse {}: {}",
+                warehouse, e
+            );
+        }
+        if let Some(role) = self.active_role.clone()
+            && self.set_role(Some(&role)).await.is_err()
+        {
+            self.active_role = None;
+        }
+        Ok(())
+    }
+
+    /// Make `schema` the one unqualified names resolve to (`\\schema`)
+    pub async fn set_schema(&mut self, schema: &str) -> Result<()> {
+        let client = self
+            .database_client
+            .as_mut()
+            .ok_or_else(DbcrustError::no_client)?;
+        client.set_schema(schema).await?;
+        // Cached results of unqualified queries may name other tables now
+        self.result_cache.clear();
+        Ok(())
+    }
+
+    /// Warehouse statements run on (`\\warehouse`), for backends that have one
+    pub fn current_warehouse(&self) -> Option<String> {
+        self.database_client.as_ref()?.warehouse()
+    }
+
+    /// Run following statements on `warehouse` (`\\warehouse <name>`)
+    pub async fn set_warehouse(&self, warehouse: &str) -> Result<()> {
+        let client = self
+            .database_client
+            .as_ref()
+            .ok_or_else(DbcrustError::no_client)?;
+        client.set_warehouse(warehouse).await?;
+        Ok(())
+    }
+
+    /// Schema `\\dt` and completion currently look at, for backends that have one
+    pub fn current_schema(&self) -> Option<String> {
+        let client = self.database_client.as_ref()?;
+        match client.get_connection_info().database_type {
+            DatabaseType::PostgreSQL | DatabaseType::ClickHouse | DatabaseType::Snowflake => {
+                client.get_metadata_provider().default_schema()
+            }
+            DatabaseType::MySQL => Some(client.get_current_database()).filter(|db| !db.is_empty()),
+            _ => None,
+        }
+    }
+
+    /// Every schema with its number of tables and views (`\\schemas`)
+    pub async fn schema_table_counts(&self) -> Result<Vec<(String, usize)>> {
+        let client = self
+            .database_client
+            .as_ref()
+            .ok_or_else(DbcrustError::no_client)?;
+        Ok(client
+            .get_metadata_provider()
+            .get_schema_table_counts()
+            .await?)
+    }
+
+    /// User shown in the prompt: the login user, plus the role switched to
+    pub fn prompt_username(&self) -> String {
+        match &self.active_role {
+            Some(role) => format!("{}(as {role})", self.get_username()),
+            None => self.get_username(),
+        }
+    }
+
     pub fn get_host(&self) -> String {
         if let Some(ref client) = self.database_client {
             client
@@ -1205,6 +1590,11 @@ impl Database {
         self.database_client.is_none()
     }
 
+    /// The SSH tunnel this connection goes through, if any
+    pub fn get_ssh_tunnel(&self) -> Option<&crate::ssh_tunnel::SSHTunnel> {
+        self.ssh_tunnel.as_ref()
+    }
+
     /// Get the current connection information
     pub fn get_connection_info(&self) -> Option<&crate::database::ConnectionInfo> {
         // Check override first (for Vault connections), then fall back to database client
@@ -1224,6 +1614,14 @@ impl Database {
             .unwrap_or(DatabaseType::PostgreSQL)
     }
 
+    /// PostgreSQL extensions detected on this connection
+    pub fn pg_extensions(&self) -> Vec<crate::database_postgresql::PgExtension> {
+        self.database_client
+            .as_ref()
+            .map(|client| client.pg_extensions().to_vec())
+            .unwrap_or_default()
+    }
+
     /// Set or override the connection information for this database
     /// This is useful for cases like Vault connections where the connection info
     /// needs to be set after database creation
@@ -1234,9 +1632,7 @@ impl Database {
         self.connection_info_override = Some(connection_info);
     }
 
-    pub async fn list_tables(
-        &mut self,
-    ) -> std::result::Result<Vec<Vec<String>>, Box<dyn StdError>> {
+    pub async fn list_tables(&mut self) -> Result<Vec<Vec<String>>> {
         // Use the new database abstraction layer
         if let Some(ref database_client) = self.database_client {
             let tables = database_client
@@ -1260,9 +1656,8 @@ impl Database {
                     if conn_info.database_type == crate::database::DatabaseType::MongoDB {
                         "".to_string() // MongoDB doesn't have schemas
                     } else {
-                        database_client
-                            .get_metadata_provider()
-                            .default_schema()
+                        self.current_schema()
+                            .or_else(|| database_client.get_metadata_provider().default_schema())
                             .unwrap_or_else(|| "main".to_string())
                     };
 
@@ -1278,14 +1673,11 @@ impl Database {
 
             Ok(results)
         } else {
-            Err("No database client available".into())
+            Err(DbcrustError::no_client())
         }
     }
 
-    pub async fn execute_query(
-        &mut self,
-        query: &str,
-    ) -> std::result::Result<Vec<Vec<String>>, Box<dyn StdError>> {
+    pub async fn execute_query(&mut self, query: &str) -> Result<Vec<Vec<String>>> {
         self.execute_query_with_interrupt(
             query,
             &std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false)),
@@ -1294,10 +1686,7 @@ impl Database {
     }
 
     /// Test query execution without side effects (for validating named queries before saving)
-    pub async fn test_query_execution(
-        &mut self,
-        query: &str,
-    ) -> std::result::Result<(), Box<dyn StdError>> {
+    pub async fn test_query_execution(&mut self, query: &str) -> Result<()> {
         if let Some(ref database_client) = self.database_client {
             // For file-based databases (SQLite), we can't use transactions in the same way
             // so we'll just do a basic validation query execution
@@ -1306,14 +1695,20 @@ impl Database {
                 .await
                 .map_err(|e| e.into())
         } else {
-            Err("No database client available".into())
+            Err(DbcrustError::no_client())
         }
     }
 
-    pub async fn execute_query_with_info(
-        &mut self,
-        query: &str,
-    ) -> std::result::Result<QueryResultsWithInfo, Box<dyn StdError>> {
+    /// Every result set of `query`, each header row first; stored procedures
+    /// can return several
+    pub async fn execute_query_sets(&mut self, query: &str) -> Result<Vec<Vec<Vec<String>>>> {
+        let results = self.execute_query_with_info(query).await?;
+        Ok(std::iter::once(results.data)
+            .chain(results.more_results)
+            .collect())
+    }
+
+    pub async fn execute_query_with_info(&mut self, query: &str) -> Result<QueryResultsWithInfo> {
         self.execute_query_with_interrupt_and_info(
             query,
             &std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false)),
@@ -1324,11 +1719,13 @@ impl Database {
     pub async fn execute_query_with_info_no_column_selection(
         &mut self,
         query: &str,
-    ) -> std::result::Result<QueryResultsWithInfo, Box<dyn StdError>> {
+    ) -> Result<QueryResultsWithInfo> {
         // Temporarily disable column selection
         let original_cs_mode = self.column_select_mode;
         let original_threshold = self.column_selection_threshold;
 
+        let saved_column_views = std::mem::take(&mut self.saved_column_views);
+        let table_prefs = std::mem::take(&mut self.table_prefs);
         self.column_select_mode = false;
         self.column_selection_threshold = usize::MAX; // Effectively disable auto-triggering
 
@@ -1342,6 +1739,8 @@ impl Database {
         // Restore original settings
         self.column_select_mode = original_cs_mode;
         self.column_selection_threshold = original_threshold;
+        self.saved_column_views = saved_column_views;
+        self.table_prefs = table_prefs;
 
         result
     }
@@ -1350,7 +1749,7 @@ impl Database {
         &mut self,
         query: &str,
         interrupt_flag: &std::sync::Arc<std::sync::atomic::AtomicBool>,
-    ) -> std::result::Result<Vec<Vec<String>>, Box<dyn StdError>> {
+    ) -> Result<Vec<Vec<String>>> {
         // Use the new method that returns metadata and extract just the data
         match self
             .execute_query_with_interrupt_and_info(query, interrupt_flag)
@@ -1371,9 +1770,11 @@ impl Database {
         &mut self,
         query: &str,
         interrupt_flag: &std::sync::Arc<std::sync::atomic::AtomicBool>,
-    ) -> std::result::Result<Vec<Vec<String>>, Box<dyn StdError>> {
+    ) -> Result<Vec<Vec<String>>> {
         let original_cs_mode = self.column_select_mode;
         let original_threshold = self.column_selection_threshold;
+        let saved_column_views = std::mem::take(&mut self.saved_column_views);
+        let table_prefs = std::mem::take(&mut self.table_prefs);
         self.column_select_mode = false;
         self.column_selection_threshold = usize::MAX; // disable auto-triggering
 
@@ -1383,6 +1784,8 @@ impl Database {
 
         self.column_select_mode = original_cs_mode;
         self.column_selection_threshold = original_threshold;
+        self.saved_column_views = saved_column_views;
+        self.table_prefs = table_prefs;
         result
     }
 
@@ -1390,13 +1793,20 @@ impl Database {
         &mut self,
         query: &str,
         interrupt_flag: &std::sync::Arc<std::sync::atomic::AtomicBool>,
-    ) -> std::result::Result<QueryResultsWithInfo, Box<dyn StdError>> {
+    ) -> Result<QueryResultsWithInfo> {
+        self.last_query = Some(query.to_string());
+
         // Check if we should EXPLAIN this query (applies to all database types)
         if self.explain_mode && is_query_explainable(query) {
             debug!("EXPLAIN mode is enabled, executing EXPLAIN query");
 
-            // Check if TUI mode is enabled (PostgreSQL only for now)
-            if self.explain_tui_mode && self.get_database_type() == DatabaseType::PostgreSQL {
+            // Check if TUI mode is enabled (PostgreSQL and MongoDB)
+            if self.explain_tui_mode
+                && matches!(
+                    self.get_database_type(),
+                    DatabaseType::PostgreSQL | DatabaseType::MongoDB
+                )
+            {
                 debug!("TUI mode is enabled, launching visualizer");
                 return self.execute_explain_with_tui(query).await;
             }
@@ -1405,6 +1815,11 @@ impl Database {
             return Ok(QueryResultsWithInfo {
                 data: results,
                 column_info: None,
+                more_results: Vec::new(),
+                outcome: None,
+                cached_age: None,
+                temporal_columns: HashMap::new(),
+                binary_columns: HashSet::new(),
             });
         }
 
@@ -1414,10 +1829,115 @@ impl Database {
             let query_with_limit = self.maybe_add_limit(query);
             debug!("[database_client] Original query: {}", query);
             debug!("[database_client] Query with limit: {}", query_with_limit);
-            let results = database_client.execute_query(&query_with_limit).await?;
-            self.apply_column_selection_if_needed_with_info(results, interrupt_flag)
+            let cache_key = (self.result_cache.is_enabled()
+                && crate::result_cache::is_cacheable(&query_with_limit))
+            .then(|| {
+                let connection = self
+                    .get_connection_info()
+                    .map(|info| info.to_url())
+                    .unwrap_or_default();
+                crate::result_cache::cache_key(
+                    &connection,
+                    self.active_role.as_deref(),
+                    self.current_schema().as_deref(),
+                    &query_with_limit,
+                )
+            });
+            let cached = cache_key
+                .as_ref()
+                .and_then(|key| self.result_cache.get(key));
+            let (result_sets, column_types, outcome, cached_age) =
+                if let Some((sets, column_types, age)) = cached {
+                    debug!("[database_client] Serving cached result ({:?} old)", age);
+                    (sets, column_types, None, Some(age))
+                } else {
+                    if crate::result_cache::invalidates(&query_with_limit) {
+                        self.result_cache.clear();
+                    }
+                    let statement = if matches!(
+                        self.get_database_type(),
+                        DatabaseType::PostgreSQL | DatabaseType::MySQL | DatabaseType::ClickHouse
+                    ) {
+                        self.query_tags
+                            .apply(&query_with_limit, &self.get_username())
+                    } else {
+                        query_with_limit.clone()
+                    };
+                    let started = std::time::Instant::now();
+                    let result_sets = database_client.execute_statement(&statement).await;
+                    match &result_sets {
+                        Ok((sets, _)) => {
+                            let rows = sets.iter().map(|set| set.len().saturating_sub(1) as u64);
+                            let bytes = sets
+                                .iter()
+                                .flatten()
+                                .flatten()
+                                .map(|cell| cell.len() as u64);
+                            self.stats.record_query(
+                                &query_with_limit,
+                                started.elapsed(),
+                                Some(rows.sum()),
+                                bytes.sum(),
+                            )
+                        }
+                        Err(_) => {
+                            self.stats
+                                .record_query(&query_with_limit, started.elapsed(), None, 0)
+                        }
+                    }
+                    let (result_sets, outcome) = result_sets?;
+                    if let Some(open) =
+                        transaction_effect(&query_with_limit, &self.get_database_type())
+                    {
+                        self.transaction_open = open;
+                    }
+                    let column_types = database_client.last_column_types();
+                    if let Some(key) = cache_key {
+                        self.result_cache
+                            .insert(key, result_sets.clone(), column_types.clone());
+                    }
+                    (result_sets, column_types, outcome, None)
+                };
+            let mut result_sets = result_sets.into_iter();
+            let results = result_sets.next().unwrap_or_default();
+            let temporal_columns = crate::format::temporal_columns(results.first(), &column_types);
+            let binary_columns =
+                crate::complex_display::binary_columns(results.first(), &column_types);
+            self.last_query_fingerprint = Some(crate::sql_buffer::query_fingerprint(query));
+            self.last_column_selection = None;
+            self.last_prefs_key =
+                (!results.is_empty()).then(|| crate::table_prefs::prefs_key(query));
+            if !results.is_empty() {
+                self.last_row_query = Some(query.to_string());
+            }
+            let prefs = self
+                .last_prefs_key
+                .as_ref()
+                .and_then(|key| self.table_prefs.get(key))
+                .cloned()
+                .unwrap_or_default();
+            self.expanded_override = prefs.expanded;
+            let mut results_with_info =
+                self.apply_column_selection_if_needed_with_info(results, interrupt_flag)?;
+            if let Some(sort) = &prefs.sort {
+                crate::table_prefs::sort_rows(&mut results_with_info.data, sort);
+            }
+            // After column selection and sorting, so \saveblob positions match the display
+            self.last_binary_cells = crate::complex_display::BinaryCells::extract(
+                &results_with_info.data,
+                &binary_columns,
+            );
+            if results_with_info.data.len() > 1 {
+                self.last_result = results_with_info.data.clone();
+            }
+            results_with_info.more_results = result_sets.collect();
+            results_with_info.outcome = outcome.and_then(|outcome| dml_outcome(query, outcome));
+            results_with_info.cached_age = cached_age;
+            results_with_info.temporal_columns = temporal_columns;
+            results_with_info.binary_columns = binary_columns;
+            Ok(results_with_info)
         } else {
-            Err("No database client available".into())
+            Err(DbcrustError::no_client())
         }
     }
 
@@ -1425,14 +1945,28 @@ impl Database {
         &mut self,
         results: Vec<Vec<String>>,
         interrupt_flag: &std::sync::Arc<std::sync::atomic::AtomicBool>,
-    ) -> std::result::Result<QueryResultsWithInfo, Box<dyn StdError>> {
+    ) -> Result<QueryResultsWithInfo> {
         if results.is_empty() {
             return Ok(QueryResultsWithInfo {
                 data: results,
                 column_info: None,
+                more_results: Vec::new(),
+                outcome: None,
+                cached_age: None,
+                temporal_columns: HashMap::new(),
+                binary_columns: HashSet::new(),
             });
         }
 
+        // A saved view for this query wins over the interactive prompt, then
+        // the columns remembered for the table
+        if let Some(results_with_info) = self.apply_saved_column_view(&results) {
+            return Ok(results_with_info);
+        }
+        if let Some(results_with_info) = self.apply_table_prefs_columns(&results) {
+            return Ok(results_with_info);
+        }
+
         let column_count = results[0].len();
 
         // Check if we should apply column selection
@@ -1446,7 +1980,7 @@ impl Database {
             );
             match self.interactive_column_selection_with_info(&results, interrupt_flag) {
                 Ok(results_with_info) => Ok(results_with_info),
-                Err(e) if e.to_string().contains("Column selection aborted") => {
+                Err(e @ DbcrustError::Aborted(_)) => {
                     // Re-throw the abort error to propagate it up
                     Err(e)
                 }
@@ -1456,6 +1990,11 @@ impl Database {
                     Ok(QueryResultsWithInfo {
                         data: results,
                         column_info: None,
+                        more_results: Vec::new(),
+                        outcome: None,
+                        cached_age: None,
+                        temporal_columns: HashMap::new(),
+                        binary_columns: HashSet::new(),
                     })
                 }
             }
@@ -1463,15 +2002,56 @@ impl Database {
             Ok(QueryResultsWithInfo {
                 data: results,
                 column_info: None,
+                more_results: Vec::new(),
+                outcome: None,
+                cached_age: None,
+                temporal_columns: HashMap::new(),
+                binary_columns: HashSet::new(),
             })
         }
     }
 
-    /// Execute an EXPLAIN query and display in the TUI visualizer
-    async fn execute_explain_with_tui(
+    /// Apply the `\\csave` view saved for the last query, if any and if all
+    /// of its columns are still present in the results
+    fn apply_saved_column_view(&mut self, results: &[Vec<String>]) -> Option<QueryResultsWithInfo> {
+        let fingerprint = self.last_query_fingerprint.as_ref()?;
+        let headers = results.first()?;
+        let (name, view) = self
+            .saved_column_views
+            .iter()
+            .find(|(_, view)| &view.fingerprint == fingerprint)?;
+        if !view.columns.iter().all(|column| headers.contains(column)) {
+            debug!("Saved column view '{name}' no longer matches the result columns");
+            return None;
+        }
+        let mut results_with_info = filter_result_columns(results, &view.columns);
+        if let Some(info) = results_with_info.column_info.as_mut() {
+            info.view_name = Some(name.clone());
+        }
+        self.last_column_selection = Some((fingerprint.clone(), view.columns.clone()));
+        Some(results_with_info)
+    }
+
+    /// Apply the columns remembered in `\\prefs` for the last query's table,
+    /// if all of them are still present in the results
+    fn apply_table_prefs_columns(
         &mut self,
-        query: &str,
-    ) -> std::result::Result<QueryResultsWithInfo, Box<dyn StdError>> {
+        results: &[Vec<String>],
+    ) -> Option<QueryResultsWithInfo> {
+        let prefs = self.table_prefs.get(self.last_prefs_key.as_ref()?)?;
+        let headers = results.first()?;
+        if prefs.columns.is_empty() || !prefs.columns.iter().all(|c| headers.contains(c)) {
+            return None;
+        }
+        let columns = prefs.columns.clone();
+        if let Some(fingerprint) = &self.last_query_fingerprint {
+            self.last_column_selection = Some((fingerprint.clone(), columns.clone()));
+        }
+        Some(filter_result_columns(results, &columns))
+    }
+
+    /// Execute an EXPLAIN query and display in the TUI visualizer
+    async fn execute_explain_with_tui(&mut self, query: &str) -> Result<QueryResultsWithInfo> {
         if let Some(ref database_client) = self.database_client {
             debug!("Executing EXPLAIN with TUI visualizer");
 
@@ -1484,18 +2064,54 @@ impl Database {
 
                 // Store for \ecopy
                 self.last_json_plan = Some(json_str.clone());
+                self.last_plan_query = Some(query.to_string());
 
                 // Parse the JSON
-                let plan_json: serde_json::Value = serde_json::from_str(json_str)
-                    .map_err(|e| format!("Failed to parse EXPLAIN JSON: {e}"))?;
+                let plan_json: serde_json::Value = serde_json::from_str(json_str).map_err(|e| {
+                    DbcrustError::Query(format!("Failed to parse EXPLAIN JSON: {e}"))
+                })?;
+
+                // `r` in the visualizer re-runs the query with EXPLAIN ANALYZE;
+                // the latest plan is kept for \ecopy
+                let mut latest_plan = None;
+                let mut refresh = || {
+                    let raw = tokio::task::block_in_place(|| {
+                        tokio::runtime::Handle::current()
+                            .block_on(database_client.explain_analyze_raw(query))
+                    })
+                    .map_err(|e| e.to_string())?;
+                    let json_str = raw
+                        .get(1)
+                        .and_then(|row| row.first())
+                        .ok_or("No EXPLAIN output received")?;
+                    let plan = serde_json::from_str(json_str)
+                        .map_err(|e| format!("Failed to parse EXPLAIN JSON: {e}"))?;
+                    latest_plan = Some(json_str.clone());
+                    Ok(plan)
+                };
+                let refresh: Option<&mut dyn FnMut() -> std::result::Result<_, String>> =
+                    if crate::explain_tui::writes_data(query) {
+                        None
+                    } else {
+                        Some(&mut refresh)
+                    };
 
                 // Run the TUI visualizer
-                match crate::explain_tui::run_explain_tui(&plan_json) {
+                let result = crate::explain_tui::run_explain_tui(&plan_json, refresh);
+                if let Some(plan) = latest_plan {
+                    self.last_json_plan = Some(plan);
+                }
+                match result {
                     Ok(_) => {
                         // TUI exited normally, return empty result to indicate success
                         Ok(QueryResultsWithInfo {
                             data: vec![vec!["Plan visualizer closed.".to_string()]],
                             column_info: None,
+                            more_results: Vec::new(),
+                            outcome: None,
+                            cached_age: None,
+                            temporal_columns: HashMap::new(),
+                            binary_columns: HashSet::new(),
                         })
                     }
                     Err(e) => {
@@ -1505,21 +2121,25 @@ impl Database {
                         Ok(QueryResultsWithInfo {
                             data: results,
                             column_info: None,
+                            more_results: Vec::new(),
+                            outcome: None,
+                            cached_age: None,
+                            temporal_columns: HashMap::new(),
+                            binary_columns: HashSet::new(),
                         })
                     }
                 }
             } else {
-                Err("No EXPLAIN output received".into())
+                Err(DbcrustError::Query(
+                    "No EXPLAIN output received".to_string(),
+                ))
             }
         } else {
-            Err("No database client available".into())
+            Err(DbcrustError::no_client())
         }
     }
 
-    pub async fn execute_explain_query(
-        &mut self,
-        query: &str,
-    ) -> std::result::Result<Vec<Vec<String>>, Box<dyn StdError>> {
+    pub async fn execute_explain_query(&mut self, query: &str) -> Result<Vec<Vec<String>>> {
         // Use new database abstraction layer for EXPLAIN queries
         if let Some(ref database_client) = self.database_client {
             debug!("Using database abstraction layer for execute_explain_query");
@@ -1531,6 +2151,7 @@ impl Database {
                     if raw_results.len() > 1 && !raw_results[1].is_empty() {
                         let json_plan = &raw_results[1][0]; // First data row, first column contains JSON
                         self.last_json_plan = Some(json_plan.clone());
+                        self.last_plan_query = Some(query.to_string());
                         debug!(
                             "Stored JSON plan for \\ecopy ({} characters)",
                             json_plan.len()
@@ -1550,14 +2171,11 @@ impl Database {
                 .await
                 .map_err(|e| e.into());
         } else {
-            Err("No database client available".into())
+            Err(DbcrustError::no_client())
         }
     }
 
-    pub async fn execute_explain_query_raw(
-        &mut self,
-        query: &str,
-    ) -> std::result::Result<Vec<Vec<String>>, Box<dyn StdError>> {
+    pub async fn execute_explain_query_raw(&mut self, query: &str) -> Result<Vec<Vec<String>>> {
         // Use new database abstraction layer for raw EXPLAIN queries
         if let Some(ref database_client) = self.database_client {
             debug!("Using database abstraction layer for execute_explain_query_raw");
@@ -1566,14 +2184,14 @@ impl Database {
                 .await
                 .map_err(|e| e.into());
         } else {
-            Err("No database client available".into())
+            Err(DbcrustError::no_client())
         }
     }
 
     pub async fn execute_explain_query_formatted(
         &mut self,
         query: &str,
-    ) -> std::result::Result<Vec<Vec<String>>, Box<dyn StdError>> {
+    ) -> Result<Vec<Vec<String>>> {
         // Use new database abstraction layer for formatted EXPLAIN queries
         if let Some(ref database_client) = self.database_client {
             debug!("Using database abstraction layer for execute_explain_query_formatted");
@@ -1585,6 +2203,7 @@ impl Database {
                     if raw_results.len() > 1 && !raw_results[1].is_empty() {
                         let json_plan = &raw_results[1][0]; // First data row, first column contains JSON
                         self.last_json_plan = Some(json_plan.clone());
+                        self.last_plan_query = Some(query.to_string());
                         debug!(
                             "Stored JSON plan for \\ecopy ({} characters)",
                             json_plan.len()
@@ -1603,20 +2222,22 @@ impl Database {
             let results = database_client.explain_query(query).await?;
             Ok(results)
         } else {
-            Err("No database client available".into())
+            Err(DbcrustError::no_client())
         }
     }
 
-    pub async fn validate_connection(&self) -> std::result::Result<(), Box<dyn StdError>> {
+    pub async fn validate_connection(&self) -> Result<()> {
         if let Some(ref database_client) = self.database_client {
             // Use the database client's connection validation
             if database_client.is_connected().await {
                 Ok(())
             } else {
-                Err("Database connection is not active".into())
+                Err(DbcrustError::Connection(
+                    "Database connection is not active".to_string(),
+                ))
             }
         } else {
-            Err("No database client available".into())
+            Err(DbcrustError::no_client())
         }
     }
 
@@ -1630,11 +2251,11 @@ impl Database {
             match database_client.get_server_info().await {
                 Ok(server_info) => {
                     // Display server info in pgcli style
-                    println!(
+                    print_status(&format!(
                         "Server: {} {}",
                         server_info.server_type, server_info.server_version
-                    );
-                    println!("Version: {}", server_info.client_version);
+                    ));
+                    print_status(&format!("Version: {}", server_info.client_version));
 
                     // Optionally show additional database-specific info in debug mode
                     if !server_info.additional_info.is_empty() {
@@ -1655,6 +2276,20 @@ impl Database {
         }
     }
 
+    /// Take over the display settings of the connection this one replaces
+    /// (`\\c` to another server): `\\x`, `\\e`, the limit and column selection
+    pub fn adopt_display_settings(&mut self, previous: &Database) {
+        self.expanded_display = previous.expanded_display;
+        self.default_limit = previous.default_limit;
+        self.autocomplete_enabled = previous.autocomplete_enabled;
+        self.explain_mode = previous.explain_mode;
+        self.explain_tui_mode = previous.explain_tui_mode;
+        self.column_select_mode = previous.column_select_mode;
+        self.banner_enabled = previous.banner_enabled;
+        self.column_selection_threshold = previous.column_selection_threshold;
+        self.column_selection_default_all = previous.column_selection_default_all;
+    }
+
     pub fn maybe_add_limit(&self, query: &str) -> String {
         add_default_limit(query, self.default_limit)
     }
@@ -1676,6 +2311,39 @@ impl Database {
         self.expanded_display
     }
 
+    /// Expanded display for the last query's results: the state remembered
+    /// for its table, or the session setting
+    pub fn expanded_for_results(&self) -> bool {
+        self.expanded_override.unwrap_or(self.expanded_display)
+    }
+
+    /// `\\x`: flip what the last result was shown with, and remember the new
+    /// state for its table. Returns the new state and the table key, if any.
+    pub fn toggle_expanded_for_last_result(&mut self) -> (bool, Option<&str>) {
+        let expanded = !self.expanded_for_results();
+        self.expanded_display = expanded;
+        self.expanded_override = None;
+        if let Some(key) = &self.last_prefs_key {
+            self.table_prefs
+                .update(key, |prefs| prefs.expanded = Some(expanded));
+        }
+        (expanded, self.last_prefs_key.as_deref())
+    }
+
+    /// Saved per-table display preferences (`\\prefs`)
+    pub fn table_prefs(&self) -> &crate::table_prefs::TablePrefsStore {
+        &self.table_prefs
+    }
+
+    pub fn table_prefs_mut(&mut self) -> &mut crate::table_prefs::TablePrefsStore {
+        &mut self.table_prefs
+    }
+
+    /// `\\prefs` key of the last query that returned rows
+    pub fn last_prefs_key(&self) -> Option<&str> {
+        self.last_prefs_key.as_deref()
+    }
+
     pub fn is_explain_mode(&self) -> bool {
         self.explain_mode
     }
@@ -1701,11 +2369,7 @@ impl Database {
     }
 
     /// Test network connectivity to a host:port combination with timeout
-    pub async fn test_network_connectivity(
-        host: &str,
-        port: u16,
-        timeout_secs: u64,
-    ) -> std::result::Result<(), Box<dyn StdError>> {
+    pub async fn test_network_connectivity(host: &str, port: u16, timeout_secs: u64) -> Result<()> {
         debug!(
             "[Database::test_network_connectivity] Testing connection to {}:{}",
             host, port
@@ -1715,9 +2379,9 @@ impl Database {
         match tokio::net::lookup_host(format!("{host}:{port}")).await {
             Ok(mut addresses) => {
                 if addresses.next().is_none() {
-                    return Err(
-                        format!("DNS resolution failed: no addresses found for {host}").into(),
-                    );
+                    return Err(DbcrustError::Connection(format!(
+                        "DNS resolution failed: no addresses found for {host}"
+                    )));
                 }
                 debug!(
                     "[Database::test_network_connectivity] DNS resolution successful for {}",
@@ -1725,7 +2389,9 @@ impl Database {
                 );
             }
             Err(e) => {
-                return Err(format!("DNS resolution failed for {host}: {e}").into());
+                return Err(DbcrustError::Connection(format!(
+                    "DNS resolution failed for {host}: {e}"
+                )));
             }
         }
 
@@ -1744,18 +2410,16 @@ impl Database {
                 );
                 Ok(())
             }
-            Ok(Err(e)) => Err(format!("TCP connection failed to {host}:{port}: {e}").into()),
-            Err(_) => Err(format!(
+            Ok(Err(e)) => Err(DbcrustError::Connection(format!(
+                "TCP connection failed to {host}:{port}: {e}"
+            ))),
+            Err(_) => Err(DbcrustError::Connection(format!(
                 "Connection timeout to {host}:{port} after {timeout_secs} seconds"
-            )
-            .into()),
+            ))),
         }
     }
 
-    pub async fn get_table_details(
-        &mut self,
-        table_name: &str,
-    ) -> std::result::Result<TableDetails, Box<dyn StdError>> {
+    pub async fn get_table_details(&mut self, table_name: &str) -> Result<TableDetails> {
         // Use the new database abstraction layer if available
         if let Some(ref database_client) = self.database_client {
             match database_client
@@ -1766,11 +2430,11 @@ impl Database {
                 Ok(table_details) => Ok(table_details),
                 Err(e) => {
                     debug!("Error using database client for get_table_details: {e}");
-                    Err(Box::new(e))
+                    Err(e.into())
                 }
             }
         } else {
-            Err("No database client available".into())
+            Err(DbcrustError::no_client())
         }
     }
 
@@ -1781,15 +2445,15 @@ impl Database {
         &mut self,
         table_name: &str,
         schema: Option<&str>,
-    ) -> std::result::Result<TableDetails, Box<dyn StdError>> {
+    ) -> Result<TableDetails> {
         if let Some(ref database_client) = self.database_client {
             database_client
                 .get_metadata_provider()
                 .get_table_details(table_name, schema)
                 .await
-                .map_err(|e| Box::new(e) as Box<dyn StdError>)
+                .map_err(DbcrustError::from)
         } else {
-            Err("No database client available".into())
+            Err(DbcrustError::no_client())
         }
     }
 
@@ -1860,14 +2524,32 @@ impl Database {
             column_selection_default_all: config.column_selection_default_all,
             column_views: HashMap::new(),
             last_view_key: None,
+            saved_column_views: config.column_views().clone(),
+            last_query_fingerprint: None,
+            last_column_selection: None,
+            table_prefs: Default::default(),
+            last_prefs_key: None,
+            expanded_override: None,
             last_json_plan: None,
+            last_plan_query: None,
+            stats: Default::default(),
+            result_cache: Default::default(),
+            query_tags: Default::default(),
+            schema_cache: Default::default(),
+            idle_state: Default::default(),
+            offline_queue: Default::default(),
+            client_cert_watch: Default::default(),
+            last_binary_cells: Default::default(),
+            active_role: None,
+            last_row_query: None,
+            transaction_open: false,
+            last_query: None,
+            last_result: Vec::new(),
             frontend_mode: FrontendMode::Cli,
         }
     }
 
-    pub async fn list_database_names(
-        &mut self,
-    ) -> std::result::Result<Vec<String>, Box<dyn StdError>> {
+    pub async fn list_database_names(&mut self) -> Result<Vec<String>> {
         // Use new database abstraction layer
         if let Some(ref database_client) = self.database_client {
             let databases = database_client.list_databases().await?;
@@ -1879,14 +2561,14 @@ impl Database {
                 .collect();
             Ok(names)
         } else {
-            Err("No database client available".into())
+            Err(DbcrustError::no_client())
         }
     }
 
     pub async fn get_tables_and_views(
         &mut self,
         schema_filter: Option<&str>,
-    ) -> std::result::Result<Vec<String>, Box<dyn StdError>> {
+    ) -> Result<Vec<String>> {
         let start_time = std::time::Instant::now();
         debug!(
             "[get_tables_and_views] Starting query for schema_filter: {:?}",
@@ -1915,15 +2597,15 @@ impl Database {
                         "Error using database client for get_tables_and_views: {}",
                         e
                     );
-                    Err(Box::new(e))
+                    Err(e.into())
                 }
             }
         } else {
-            Err("No database client available".into())
+            Err(DbcrustError::no_client())
         }
     }
 
-    pub async fn get_schemas(&mut self) -> std::result::Result<Vec<String>, Box<dyn StdError>> {
+    pub async fn get_schemas(&mut self) -> Result<Vec<String>> {
         let start_time = std::time::Instant::now();
         debug!("[get_schemas] Starting query");
 
@@ -1942,18 +2624,15 @@ impl Database {
                 }
                 Err(e) => {
                     debug!("Error using database client for get_schemas: {e}");
-                    Err(Box::new(e))
+                    Err(e.into())
                 }
             }
         } else {
-            Err("No database client available".into())
+            Err(DbcrustError::no_client())
         }
     }
 
-    pub async fn get_functions(
-        &mut self,
-        schema_filter: Option<&str>,
-    ) -> std::result::Result<Vec<String>, Box<dyn StdError>> {
+    pub async fn get_functions(&mut self, schema_filter: Option<&str>) -> Result<Vec<String>> {
         // Use new database abstraction layer
         if let Some(ref database_client) = self.database_client {
             let functions = database_client
@@ -1962,7 +2641,7 @@ impl Database {
                 .await?;
             Ok(functions)
         } else {
-            Err("No database client available".into())
+            Err(DbcrustError::no_client())
         }
     }
 
@@ -1970,7 +2649,7 @@ impl Database {
         &mut self,
         table_name: &str,
         schema: Option<&str>,
-    ) -> std::result::Result<Vec<String>, Box<dyn StdError>> {
+    ) -> Result<Vec<String>> {
         // Use new database abstraction layer
         if let Some(ref database_client) = self.database_client {
             let columns = database_client
@@ -1979,15 +2658,12 @@ impl Database {
                 .await?;
             Ok(columns)
         } else {
-            Err("No database client available".into())
+            Err(DbcrustError::no_client())
         }
     }
 
     /// Simplified column getter for the new completion system
-    pub async fn get_columns(
-        &mut self,
-        table_name: &str,
-    ) -> std::result::Result<Vec<String>, Box<dyn StdError>> {
+    pub async fn get_columns(&mut self, table_name: &str) -> Result<Vec<String>> {
         // Try to parse schema from table name if it contains a dot
         let (schema, table) = if table_name.contains('.') {
             let parts: Vec<&str> = table_name.splitn(2, '.').collect();
@@ -2033,10 +2709,87 @@ impl Database {
 
     // Utility methods for column view management
 
+    /// Counters for this session (`\stats`)
+    pub fn stats(&self) -> &crate::session_stats::SessionStats {
+        &self.stats
+    }
+
+    pub fn result_cache_mut(&mut self) -> &mut crate::result_cache::ResultCache {
+        &mut self.result_cache
+    }
+
+    /// Completion metadata saved for this connection and schema, loaded from
+    /// disk on first use and again after switching either
+    pub fn schema_cache_mut(&mut self) -> &mut crate::schema_cache::SchemaCache {
+        let identifier = crate::history_manager::SessionId::from_database(self).map(|session| {
+            match self.current_schema() {
+                Some(schema) => format!("{}/{schema}", session.identifier),
+                None => session.identifier,
+            }
+        });
+        if let Some(fingerprint) = identifier.as_deref().map(crate::schema_cache::fingerprint)
+            && self.schema_cache.fingerprint() != Some(fingerprint.as_str())
+        {
+            self.schema_cache = crate::schema_cache::SchemaCache::load(
+                &fingerprint,
+                self.frontend_mode.allows_interactive_terminal_ui(),
+            );
+        }
+        &mut self.schema_cache
+    }
+
+    /// Tags appended to statements as a comment (`\\tag`)
+    pub fn query_tags_mut(&mut self) -> &mut crate::query_tags::QueryTags {
+        &mut self.query_tags
+    }
+
+    /// The comment the next statement is tagged with, if any
+    pub fn query_tag_comment(&self) -> Option<String> {
+        self.query_tags.comment(&self.get_username())
+    }
+
+    /// Last statement that returned rows, without the default limit (`\\gexec`)
+    pub fn last_row_query(&self) -> Option<&str> {
+        self.last_row_query.as_deref()
+    }
+
+    /// Whether a transaction opened at the prompt is still open
+    pub fn in_transaction(&self) -> bool {
+        self.transaction_open
+    }
+
+    pub fn last_query(&self) -> Option<&str> {
+        self.last_query.as_deref()
+    }
+
+    /// Last result that had rows, header first, as displayed (`\\transpose`)
+    pub fn last_result(&self) -> &[Vec<String>] {
+        &self.last_result
+    }
+
+    /// Raw bytes of the binary cells of the last result (`\\saveblob`)
+    pub fn last_binary_cells(&self) -> &crate::complex_display::BinaryCells {
+        &self.last_binary_cells
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats.reset();
+    }
+
+    /// Record an autocompletion metadata lookup served (or not) from cache
+    pub fn record_cache_lookup(&mut self, hit: bool) {
+        self.stats.record_cache_lookup(hit);
+    }
+
     pub fn get_last_json_plan(&self) -> Option<String> {
         self.last_json_plan.clone()
     }
 
+    /// The query the last EXPLAIN plan was captured for
+    pub fn last_plan_query(&self) -> Option<&str> {
+        self.last_plan_query.as_deref()
+    }
+
     pub fn clear_column_views(&mut self) {
         self.column_views.clear();
         self.last_view_key = None;
@@ -2053,6 +2806,26 @@ impl Database {
         self.column_selection_threshold = threshold;
     }
 
+    /// Re-apply config-derived defaults after a config reload. Session toggles
+    /// (expanded display, EXPLAIN mode) are left as the user set them.
+    pub fn apply_config(&mut self, config: &crate::config::Config) {
+        self.default_limit = config.default_limit;
+        self.autocomplete_enabled = config.autocomplete_enabled;
+        self.column_selection_threshold = config.column_selection_threshold;
+        self.column_selection_default_all = config.column_selection_default_all;
+        self.saved_column_views = config.column_views().clone();
+    }
+
+    /// Replace the saved column views after `\\csave` or a delete
+    pub fn set_saved_column_views(&mut self, views: &HashMap<String, crate::config::ColumnView>) {
+        self.saved_column_views = views.clone();
+    }
+
+    /// Fingerprint and columns of the most recent column selection, for `\\csave`
+    pub fn last_column_selection(&self) -> Option<&(String, Vec<String>)> {
+        self.last_column_selection.as_ref()
+    }
+
     pub fn should_auto_enable_column_selection(&self, column_count: usize) -> bool {
         // Auto-enable column selection mode if there are more columns than the threshold
         column_count > self.column_selection_threshold
@@ -2062,11 +2835,16 @@ impl Database {
         &mut self,
         data: &[Vec<String>],
         interrupt_flag: &std::sync::Arc<std::sync::atomic::AtomicBool>,
-    ) -> Result<QueryResultsWithInfo, Box<dyn StdError>> {
+    ) -> Result<QueryResultsWithInfo> {
         if data.is_empty() {
             return Ok(QueryResultsWithInfo {
                 data: data.to_vec(),
                 column_info: None,
+                more_results: Vec::new(),
+                outcome: None,
+                cached_age: None,
+                temporal_columns: HashMap::new(),
+                binary_columns: HashSet::new(),
             });
         }
 
@@ -2121,7 +2899,7 @@ impl Database {
                 Ok(Err(_)) | Err(_) => {
                     // User cancelled with Ctrl-C or error occurred
                     if interrupt_flag.load(std::sync::atomic::Ordering::Relaxed) {
-                        return Err(Box::new(ColumnSelectionAborted));
+                        return Err(ColumnSelectionAborted.into());
                     }
                     // For other errors, return all columns
                     headers.clone()
@@ -2132,43 +2910,75 @@ impl Database {
         // Save the selection for future use
         self.save_column_view(&view_key, selected_columns.clone());
         self.last_view_key = Some(view_key);
-
-        // Get column indices for selected columns
-        let column_indices: Vec<usize> = selected_columns
-            .iter()
-            .filter_map(|col| headers.iter().position(|h| h == col))
-            .collect();
-
-        if column_indices.is_empty() {
-            // If no valid columns found, return all data
-            return Ok(QueryResultsWithInfo {
-                data: data.to_vec(),
-                column_info: None,
-            });
+        if let Some(fingerprint) = &self.last_query_fingerprint {
+            self.last_column_selection = Some((fingerprint.clone(), selected_columns.clone()));
+        }
+        if let Some(key) = &self.last_prefs_key {
+            let columns = if &selected_columns == headers {
+                Vec::new()
+            } else {
+                selected_columns.clone()
+            };
+            self.table_prefs
+                .update(key, |prefs| prefs.columns = columns);
         }
 
-        // Filter the data to include only selected columns
-        let filtered_data: Vec<Vec<String>> = data
-            .iter()
-            .map(|row| {
-                column_indices
-                    .iter()
-                    .map(|&idx| row.get(idx).cloned().unwrap_or_default())
-                    .collect()
-            })
-            .collect();
+        Ok(filter_result_columns(data, &selected_columns))
+    }
+}
 
-        // Create column info for selected columns
-        let column_info = Some(ColumnFilteringInfo {
-            total_columns: headers.len(),
-            displayed_columns: selected_columns.len(),
-            filtered_column_names: selected_columns.clone(),
-        });
+/// Keep only `selected_columns` (in that order) from a result set
+fn filter_result_columns(
+    data: &[Vec<String>],
+    selected_columns: &[String],
+) -> QueryResultsWithInfo {
+    let headers = &data[0];
+
+    // Get column indices for selected columns
+    let column_indices: Vec<usize> = selected_columns
+        .iter()
+        .filter_map(|col| headers.iter().position(|h| h == col))
+        .collect();
+
+    if column_indices.is_empty() {
+        // If no valid columns found, return all data
+        return QueryResultsWithInfo {
+            data: data.to_vec(),
+            column_info: None,
+            more_results: Vec::new(),
+            outcome: None,
+            cached_age: None,
+            temporal_columns: HashMap::new(),
+            binary_columns: HashSet::new(),
+        };
+    }
 
-        Ok(QueryResultsWithInfo {
-            data: filtered_data,
-            column_info,
+    // Filter the data to include only selected columns
+    let filtered_data: Vec<Vec<String>> = data
+        .iter()
+        .map(|row| {
+            column_indices
+                .iter()
+                .map(|&idx| row.get(idx).cloned().unwrap_or_default())
+                .collect()
         })
+        .collect();
+
+    // Create column info for selected columns
+    let column_info = Some(ColumnFilteringInfo::new(
+        headers.len(),
+        selected_columns.len(),
+        selected_columns.to_vec(),
+    ));
+
+    QueryResultsWithInfo {
+        data: filtered_data,
+        column_info,
+        more_results: Vec::new(),
+        outcome: None,
+        cached_age: None,
+        temporal_columns: HashMap::new(),
+        binary_columns: HashSet::new(),
     }
 }
 
@@ -2185,6 +2995,10 @@ pub struct TableDetails {
     pub referenced_by: Vec<ReferencedByInfo>,
     /// Nested field details for struct/complex types (column_name -> field descriptions)
     pub nested_field_details: std::collections::HashMap<String, Vec<String>>,
+    /// Partition scheme and partitions, for partitioned tables
+    pub partitioning: Option<PartitionInfo>,
+    /// Cluster and local table behind a ClickHouse `Distributed` table
+    pub distribution: Option<DistributionInfo>,
 }
 
 #[derive(Debug)]
@@ -2229,18 +3043,53 @@ pub struct ReferencedByInfo {
     pub definition: String,
 }
 
+/// How a partitioned table is split, shown by `\d`
+#[derive(Debug, Clone, Default)]
+pub struct PartitionInfo {
+    /// Partition method and key, e.g. `RANGE (created_at)` or `toYYYYMM(event_date)`
+    pub scheme: String,
+    /// Columns in the partition key: filtering on them lets the planner skip partitions
+    pub key_columns: Vec<String>,
+    pub partitions: Vec<PartitionDetail>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PartitionDetail {
+    pub name: String,
+    /// Values the partition holds, e.g. `FOR VALUES FROM ('2024-01-01') TO ('2024-02-01')`
+    pub bound: String,
+    /// Estimated rows, when the server keeps statistics
+    pub rows: Option<i64>,
+    pub bytes: Option<i64>,
+}
+
+/// Where a ClickHouse `Distributed` table reads and writes its rows, shown by `\d`
+#[derive(Debug, Clone, Default)]
+pub struct DistributionInfo {
+    pub cluster: String,
+    /// Table holding the rows on every shard, `database.table`
+    pub local_table: String,
+    /// Expression inserted rows are spread on, if any
+    pub sharding_key: Option<String>,
+    /// Replica hosts (`host:port`) of each shard, by shard number
+    pub shards: Vec<(u32, Vec<String>)>,
+}
+
 // Helper function to determine if a query can be explained
 fn is_query_explainable(query: &str) -> bool {
     let query = query.trim().to_lowercase();
 
     // Only try to EXPLAIN statements that make sense
-    // Only SELECT and WITH queries should be explainable
-    query.starts_with("select") || query.starts_with("with")
+    // Only SELECT and WITH queries should be explainable, plus MongoDB
+    // shell-style find/aggregate calls
+    query.starts_with("select")
+        || query.starts_with("with")
+        || (query.starts_with("db.") && (query.contains(".find(") || query.contains(".aggregate(")))
 }
 
 /// First SQL keyword of a statement, lowercased, skipping leading whitespace
 /// and `--`/`/* */` comments.
-fn leading_sql_keyword(query: &str) -> Option<String> {
+pub(crate) fn leading_sql_keyword(query: &str) -> Option<String> {
     let mut rest = query;
     loop {
         rest = rest.trim_start();
@@ -2263,6 +3112,46 @@ fn leading_sql_keyword(query: &str) -> Option<String> {
     }
 }
 
+/// Whether `sql` leaves a transaction open (`BEGIN`, `START TRANSACTION`) or
+/// closed (`COMMIT`, `ROLLBACK`, `END`, `ABORT`); `None` when it does neither.
+/// The last such statement of a script decides.
+fn transaction_effect(sql: &str, database_type: &DatabaseType) -> Option<bool> {
+    let blanked = crate::sql_formatter::blank_literals(sql, database_type);
+    blanked.rsplit(';').find_map(|statement| {
+        let mut words = statement.split_whitespace().map(str::to_lowercase);
+        match (words.next()?.as_str(), words.next().as_deref()) {
+            ("begin", _) | ("start", Some("transaction")) => Some(true),
+            ("rollback", Some("to")) => None,
+            ("commit" | "rollback" | "end" | "abort", _) => Some(false),
+            _ => None,
+        }
+    })
+}
+
+/// Keep a driver's statement outcome only for INSERT/UPDATE/DELETE/MERGE
+/// (drivers also count SELECT and DDL), and the insert id only for inserts:
+/// SQLite's last rowid outlives the INSERT that set it
+fn dml_outcome(
+    query: &str,
+    outcome: crate::database::StatementOutcome,
+) -> Option<crate::database::StatementOutcome> {
+    const DML: [&str; 5] = ["insert", "replace", "update", "delete", "merge"];
+    let keyword = match leading_sql_keyword(query)?.as_str() {
+        // WITH ... DELETE: the first data-modifying keyword decides
+        "with" => query
+            .split(|c: char| !c.is_ascii_alphabetic())
+            .map(str::to_ascii_lowercase)
+            .find(|word| DML.contains(&word.as_str()))?,
+        keyword if DML.contains(&keyword) => keyword.to_string(),
+        _ => return None,
+    };
+    let inserts = matches!(keyword.as_str(), "insert" | "replace");
+    Some(crate::database::StatementOutcome {
+        rows_affected: outcome.rows_affected,
+        last_insert_id: outcome.last_insert_id.filter(|_| inserts),
+    })
+}
+
 /// Append `LIMIT n` only to statements whose leading keyword is SELECT.
 /// Anything that merely *contains* a SELECT (INSERT … SELECT, CREATE TABLE AS,
 /// DELETE/UPDATE with subqueries) must stay untouched: a trailing LIMIT there
@@ -2308,6 +3197,9 @@ mod tests {
         assert!(is_query_explainable("SELECT * FROM users"));
         assert!(is_query_explainable("select * from users"));
         assert!(is_query_explainable("WITH t AS (SELECT 1) SELECT * FROM t"));
+        assert!(is_query_explainable(r#"db.users.find({"age": 30})"#));
+        assert!(is_query_explainable("db.orders.aggregate([])"));
+        assert!(!is_query_explainable("db.runCommand({ dbStats: 1 })"));
 
         // DDL and other commands - should not be explainable
         assert!(!is_query_explainable("CREATE TABLE users (id INT)"));
@@ -2323,6 +3215,27 @@ mod tests {
     }
 
     #[rstest]
+    fn test_transaction_effect() {
+        let pg = DatabaseType::PostgreSQL;
+        assert_eq!(transaction_effect("BEGIN", &pg), Some(true));
+        assert_eq!(
+            transaction_effect("start transaction read only", &pg),
+            Some(true)
+        );
+        assert_eq!(transaction_effect("COMMIT;", &pg), Some(false));
+        assert_eq!(
+            transaction_effect("BEGIN; UPDATE t SET a = 1; ROLLBACK", &pg),
+            Some(false)
+        );
+        assert_eq!(transaction_effect("ROLLBACK TO SAVEPOINT s", &pg), None);
+        assert_eq!(
+            transaction_effect("DO $$ BEGIN PERFORM 1; END $$", &pg),
+            None
+        );
+        assert_eq!(transaction_effect("SELECT 'begin'", &pg), None);
+    }
+
+    #[test]
     fn test_leading_sql_keyword() {
         assert_eq!(leading_sql_keyword("SELECT 1"), Some("select".into()));
         assert_eq!(
@@ -2338,6 +3251,54 @@ mod tests {
         assert_eq!(leading_sql_keyword(""), None);
     }
 
+    #[rstest]
+    fn test_dml_outcome_and_message() {
+        use crate::database::StatementOutcome;
+        let outcome = StatementOutcome {
+            rows_affected: 3,
+            last_insert_id: Some(42),
+        };
+        assert_eq!(dml_outcome("SELECT * FROM t WHERE false", outcome), None);
+        assert_eq!(dml_outcome("CREATE TABLE t (id int)", outcome), None);
+        assert_eq!(
+            dml_outcome("UPDATE t SET a = 1", outcome)
+                .unwrap()
+                .last_insert_id,
+            None
+        );
+        assert_eq!(
+            dml_outcome("WITH gone AS (DELETE FROM t) SELECT 1", outcome),
+            Some(StatementOutcome {
+                rows_affected: 3,
+                last_insert_id: None
+            })
+        );
+
+        let mut results = QueryResultsWithInfo {
+            data: Vec::new(),
+            column_info: None,
+            more_results: Vec::new(),
+            outcome: dml_outcome("insert into t values (1)", outcome),
+            cached_age: None,
+            temporal_columns: HashMap::new(),
+            binary_columns: HashSet::new(),
+        };
+        assert_eq!(
+            results.affected_rows_message().as_deref(),
+            Some("3 rows affected (last insert id: 42)")
+        );
+        results.outcome = Some(StatementOutcome {
+            rows_affected: 1,
+            last_insert_id: None,
+        });
+        assert_eq!(
+            results.affected_rows_message().as_deref(),
+            Some("1 row affected")
+        );
+        results.data = vec![vec!["id".to_string()], vec!["1".to_string()]];
+        assert_eq!(results.affected_rows_message(), None);
+    }
+
     #[rstest]
     fn test_add_default_limit_only_touches_plain_selects() {
         // Plain SELECTs get the limit
@@ -2381,4 +3342,50 @@ mod tests {
             assert_eq!(add_default_limit(q, 100), q, "must not rewrite: {q}");
         }
     }
+
+    #[test]
+    fn test_saved_column_view_applies_to_matching_query() {
+        let mut db = Database::new_for_test();
+        let fingerprint = crate::sql_buffer::query_fingerprint("SELECT * FROM orders WHERE id = 1");
+        db.set_saved_column_views(&HashMap::from([(
+            "slim".to_string(),
+            crate::config::ColumnView {
+                fingerprint: fingerprint.clone(),
+                columns: vec!["total".to_string(), "id".to_string()],
+                created_at: chrono::Utc::now(),
+            },
+        )]));
+        let results = vec![
+            vec!["id".to_string(), "status".to_string(), "total".to_string()],
+            vec!["1".to_string(), "open".to_string(), "9.50".to_string()],
+        ];
+
+        db.last_query_fingerprint = Some(crate::sql_buffer::query_fingerprint(
+            "select * from orders where id = 2",
+        ));
+        let applied = db.apply_saved_column_view(&results).unwrap();
+        assert_eq!(applied.data[1], vec!["9.50", "1"]);
+        let info = applied.column_info.unwrap();
+        assert_eq!(info.view_name.as_deref(), Some("slim"));
+        assert_eq!(
+            db.last_column_selection(),
+            Some(&(fingerprint, vec!["total".to_string(), "id".to_string()]))
+        );
+
+        // Other queries, or results missing a view column, are left alone
+        db.last_query_fingerprint = Some("select * from customers".to_string());
+        assert!(db.apply_saved_column_view(&results).is_none());
+        db.last_query_fingerprint = Some(crate::sql_buffer::query_fingerprint(
+            "SELECT * FROM orders WHERE id = 3",
+        ));
+        assert!(
+            db.apply_saved_column_view(
+                &results[..1]
+                    .iter()
+                    .map(|row| row[..2].to_vec())
+                    .collect::<Vec<_>>()
+            )
+            .is_none()
+        );
+    }
 }
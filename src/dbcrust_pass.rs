@@ -34,6 +34,7 @@ pub enum DatabaseType {
     MongoDB,
     Elasticsearch,
     ClickHouse,
+    Snowflake,
     SQLite,
 }
 
@@ -45,6 +46,7 @@ impl DatabaseType {
             "mongodb" | "mongo" => Some(DatabaseType::MongoDB),
             "elasticsearch" | "elastic" => Some(DatabaseType::Elasticsearch),
             "clickhouse" | "ch" => Some(DatabaseType::ClickHouse),
+            "snowflake" => Some(DatabaseType::Snowflake),
             "sqlite" | "sqlite3" => Some(DatabaseType::SQLite),
             _ => None,
         }
@@ -57,6 +59,7 @@ impl DatabaseType {
             DatabaseType::MongoDB => "mongodb",
             DatabaseType::Elasticsearch => "elasticsearch",
             DatabaseType::ClickHouse => "clickhouse",
+            DatabaseType::Snowflake => "snowflake",
             DatabaseType::SQLite => "sqlite",
         }
     }
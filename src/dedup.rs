@@ -0,0 +1,98 @@
+//! Duplicate rows dropped from the last result (`\dedup`)
+//!
+//! Rows count as duplicates when all their values match, or only the values
+//! of the key columns given to `\dedup`, like `SELECT DISTINCT` and
+//! `DISTINCT ON`. The first row of each group stays, so the order of the
+//! result is kept; NULLs match each other, as they do for DISTINCT.
+
+use std::collections::HashSet;
+
+/// The rows of a result left after dropping duplicates
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deduped {
+    /// Header first, like the result it came from
+    pub data: Vec<Vec<String>>,
+    pub removed: usize,
+}
+
+/// `data` (header first) without duplicate rows, keyed by `columns` (names or
+/// 1-based positions; all columns when empty)
+pub fn dedup(data: &[Vec<String>], columns: &[String]) -> Result<Deduped, String> {
+    let Some((header, rows)) = data.split_first() else {
+        return Err(
+            "No result to deduplicate yet. Run a query that returns rows first.".to_string(),
+        );
+    };
+    let keys = columns
+        .iter()
+        .map(|column| crate::pivot::resolve(header, column))
+        .collect::<Result<Vec<usize>, String>>()?;
+
+    let mut seen = HashSet::new();
+    let mut kept = vec![header.clone()];
+    for row in rows {
+        let key: Vec<&str> = if keys.is_empty() {
+            row.iter().map(String::as_str).collect()
+        } else {
+            keys.iter()
+                .map(|index| row.get(*index).map(String::as_str).unwrap_or_default())
+                .collect()
+        };
+        if seen.insert(key) {
+            kept.push(row.clone());
+        }
+    }
+    Ok(Deduped {
+        removed: data.len() - kept.len(),
+        data: kept,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(rows: &[&[&str]]) -> Vec<Vec<String>> {
+        rows.iter()
+            .map(|row| row.iter().map(|v| v.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_dedup_whole_rows_and_keys() {
+        let data = table(&[
+            &["id", "email", "city"],
+            &["1", "a@x.io", "Paris"],
+            &["2", "b@x.io", "Lyon"],
+            &["1", "a@x.io", "Paris"],
+            &["3", "a@x.io", "NULL"],
+            &["4", "NULL", "NULL"],
+            &["5", "NULL", "Lyon"],
+        ]);
+        let all = dedup(&data, &[]).unwrap();
+        assert_eq!(all.removed, 1);
+        assert_eq!(all.data.len(), 6);
+
+        let by_email = dedup(&data, &["EMAIL".to_string()]).unwrap();
+        assert_eq!(by_email.removed, 3);
+        assert_eq!(
+            by_email.data,
+            table(&[
+                &["id", "email", "city"],
+                &["1", "a@x.io", "Paris"],
+                &["2", "b@x.io", "Lyon"],
+                &["4", "NULL", "NULL"],
+            ])
+        );
+
+        let by_position = dedup(&data, &["2".to_string(), "city".to_string()]).unwrap();
+        assert_eq!(by_position.removed, 1);
+
+        assert!(
+            dedup(&data, &["country".to_string()])
+                .unwrap_err()
+                .contains("No column 'country'")
+        );
+        assert!(dedup(&[], &[]).is_err());
+    }
+}
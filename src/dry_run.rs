@@ -0,0 +1,636 @@
+//! Dry runs of UPDATE/DELETE statements (`\dryrun`)
+//!
+//! The statement is rewritten into SELECTs over the same tables and WHERE
+//! clause: a count of the rows it would touch and a preview of the first of
+//! them, with the new value of every assigned column for an UPDATE (as text
+//! when it calls a function, which could have side effects). The
+//! statement itself is only EXPLAINed (never with ANALYZE), so nothing is
+//! modified. ClickHouse mutations (`ALTER TABLE ... UPDATE/DELETE`) cannot be
+//! EXPLAINed; the plan of the count query is shown instead.
+
+use crate::database::{DatabaseType, DatabaseTypeExt};
+use crate::db::Database;
+use crate::sql_parser::{Token, TokenType};
+use crate::sql_parser_trait::SqlParserFactory;
+
+/// Rows shown in the preview
+const PREVIEW_ROWS: usize = 10;
+
+/// Keywords that must not appear anywhere but as the statement's own verb:
+/// a data-modifying CTE or a second statement would run for real
+const MODIFYING_KEYWORDS: &[&str] = &[
+    "INSERT", "UPDATE", "DELETE", "MERGE", "UPSERT", "TRUNCATE", "DROP", "ALTER", "CREATE",
+    "GRANT", "REVOKE", "COPY", "CALL", "EXEC", "EXECUTE", "VACUUM",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DryRunKind {
+    Update,
+    Delete,
+}
+
+/// The read-only queries standing in for a statement
+#[derive(Debug, Clone, PartialEq)]
+pub struct DryRunPlan {
+    pub kind: DryRunKind,
+    /// Table(s) the statement changes, as written
+    pub target: String,
+    /// `SELECT COUNT(*)` of the rows the statement would touch
+    pub count_sql: String,
+    /// The first touched rows, followed by a `<column> (new)` column per
+    /// assignment for an UPDATE
+    pub preview_sql: String,
+    /// EXPLAIN (without ANALYZE) of the statement, or of the count query on
+    /// ClickHouse
+    pub explain_sql: String,
+}
+
+/// Significant tokens of the statement with their parenthesis depth
+struct Statement {
+    sql: String,
+    tokens: Vec<(Token, usize)>,
+}
+
+impl Statement {
+    fn new(sql: &str, database_type: DatabaseType) -> Self {
+        let sql = crate::sql_buffer::strip_comments(sql);
+        let context = SqlParserFactory::create_parser(database_type)
+            .parse_at_cursor(&sql, sql.len())
+            .base_context;
+
+        let mut depth = 0usize;
+        let mut tokens = Vec::new();
+        for token in context.tokens {
+            if token.token_type == TokenType::Whitespace {
+                continue;
+            }
+            if token.value == ")" {
+                depth = depth.saturating_sub(1);
+            }
+            let token_depth = depth;
+            if token.value == "(" {
+                depth += 1;
+            }
+            tokens.push((token, token_depth));
+        }
+        Self { sql, tokens }
+    }
+
+    fn upper(&self, index: usize) -> String {
+        self.tokens
+            .get(index)
+            .map(|(token, _)| token.value.to_uppercase())
+            .unwrap_or_default()
+    }
+
+    fn is(&self, index: usize, value: &str) -> bool {
+        self.upper(index) == value
+    }
+
+    /// First top-level token at or after `from` that is one of `keywords`
+    fn find(&self, from: usize, keywords: &[&str]) -> Option<usize> {
+        (from..self.tokens.len())
+            .find(|&i| self.tokens[i].1 == 0 && keywords.contains(&self.upper(i).as_str()))
+    }
+
+    /// Source text from token `from` up to (not including) token `to`
+    fn text(&self, from: usize, to: Option<usize>) -> String {
+        let start = self
+            .tokens
+            .get(from)
+            .map_or(self.sql.len(), |(token, _)| token.start);
+        let end = to
+            .and_then(|to| self.tokens.get(to))
+            .map_or(self.sql.len(), |(token, _)| token.start);
+        self.sql
+            .get(start..end.max(start))
+            .unwrap_or_default()
+            .trim()
+            .to_string()
+    }
+
+    /// Top-level pieces of `from..to` separated by commas
+    fn split_commas(&self, from: usize, to: Option<usize>) -> Vec<(usize, Option<usize>)> {
+        let to_index = to.unwrap_or(self.tokens.len());
+        let mut pieces = Vec::new();
+        let mut start = from;
+        for i in from..to_index {
+            if self.tokens[i].1 == 0 && self.tokens[i].0.value == "," {
+                pieces.push((start, Some(i)));
+                start = i + 1;
+            }
+        }
+        pieces.push((start, to));
+        pieces
+    }
+}
+
+/// Clauses of an UPDATE or DELETE, as source text
+#[derive(Default)]
+struct Clauses {
+    /// `WITH ...` in front of the statement
+    prefix: String,
+    target: String,
+    /// Qualifier for `<qualifier>.*` when other tables are joined in
+    qualifier: Option<String>,
+    /// Everything after `FROM` in the generated SELECTs
+    from: String,
+    assignments: Vec<(String, String)>,
+    where_clause: Option<String>,
+    /// `ORDER BY ... LIMIT ...` of MySQL/SQLite statements
+    tail: Option<String>,
+}
+
+/// Rewrite an UPDATE or DELETE into read-only queries
+pub fn plan(sql: &str, database_type: DatabaseType) -> Result<DryRunPlan, String> {
+    let statement = Statement::new(sql.trim().trim_end_matches(';'), database_type.clone());
+    let tokens = &statement.tokens;
+
+    if let Some(semicolon) = (0..tokens.len()).find(|&i| statement.is(i, ";")) {
+        if semicolon + 1 < tokens.len() {
+            return Err("\\dryrun takes a single statement".to_string());
+        }
+    }
+    let verb = statement
+        .find(0, &["UPDATE", "DELETE", "ALTER"])
+        .ok_or("Only UPDATE and DELETE statements can be dry-run")?;
+    if verb > 0 && !statement.is(0, "WITH") {
+        return Err("Only UPDATE and DELETE statements can be dry-run".to_string());
+    }
+
+    let (kind, clauses, verbs) = if statement.is(verb, "ALTER") {
+        if database_type != DatabaseType::ClickHouse {
+            return Err("Only UPDATE and DELETE statements can be dry-run".to_string());
+        }
+        clickhouse_mutation(&statement, verb)?
+    } else if statement.is(verb, "UPDATE") {
+        (DryRunKind::Update, update(&statement, verb)?, vec![verb])
+    } else {
+        (
+            DryRunKind::Delete,
+            delete(&statement, verb, &database_type)?,
+            vec![verb],
+        )
+    };
+
+    if let Some(i) = (0..tokens.len()).find(|&i| {
+        !verbs.contains(&i)
+            && matches!(
+                tokens[i].0.token_type,
+                TokenType::Keyword | TokenType::Identifier
+            )
+            && MODIFYING_KEYWORDS.contains(&statement.upper(i).as_str())
+    }) {
+        return Err(format!(
+            "Cannot dry-run a statement containing another {} (it would run for real)",
+            statement.upper(i)
+        ));
+    }
+
+    let quote = crate::sql_parser_trait::parsing_utils::get_quote_char(database_type.clone());
+    let Clauses {
+        prefix,
+        target,
+        qualifier,
+        from,
+        assignments,
+        where_clause,
+        tail,
+    } = clauses;
+    let filter = where_clause
+        .map(|condition| format!(" WHERE {condition}"))
+        .unwrap_or_default();
+    let body = format!("FROM {from}{filter}");
+
+    let mut columns = vec![qualifier.map_or("*".to_string(), |q| format!("{q}.*"))];
+    columns.extend(assignments.iter().map(|(column, value)| {
+        let name = column.replace(['"', '`', '[', ']'], "");
+        format!("{value} AS {quote}{name} (new){quote}")
+    }));
+    let select = format!("{prefix}SELECT {} {body}", columns.join(", "));
+
+    let has_limit = tail
+        .as_ref()
+        .is_some_and(|tail| tail.to_uppercase().contains("LIMIT"));
+    let (count_sql, preview_sql) = match tail {
+        Some(tail) if has_limit => (
+            format!("{prefix}SELECT COUNT(*) FROM (SELECT 1 {body} {tail}) AS dry_run"),
+            format!("SELECT * FROM ({select} {tail}) AS dry_run LIMIT {PREVIEW_ROWS}"),
+        ),
+        Some(tail) => (
+            format!("{prefix}SELECT COUNT(*) {body}"),
+            format!("{select} {tail} LIMIT {PREVIEW_ROWS}"),
+        ),
+        None => (
+            format!("{prefix}SELECT COUNT(*) {body}"),
+            format!("{select} LIMIT {PREVIEW_ROWS}"),
+        ),
+    };
+    let explain_sql = match database_type {
+        DatabaseType::ClickHouse => format!("EXPLAIN {count_sql}"),
+        DatabaseType::SQLite => format!("EXPLAIN QUERY PLAN {}", statement.text(0, None)),
+        _ => format!("EXPLAIN {}", statement.text(0, None)),
+    };
+
+    Ok(DryRunPlan {
+        kind,
+        target,
+        count_sql,
+        preview_sql,
+        explain_sql,
+    })
+}
+
+fn update(statement: &Statement, verb: usize) -> Result<Clauses, String> {
+    let mut i = verb + 1;
+    loop {
+        match statement.upper(i).as_str() {
+            "LOW_PRIORITY" | "IGNORE" => i += 1,
+            // SQLite: UPDATE OR REPLACE / OR IGNORE ...
+            "OR" => i += 2,
+            _ => break,
+        }
+    }
+    let set = statement
+        .find(i, &["SET"])
+        .ok_or("UPDATE statement has no SET clause")?;
+    let assignments_end =
+        statement.find(set + 1, &["FROM", "WHERE", "RETURNING", "ORDER", "LIMIT"]);
+    let assignments = assignments(statement, set + 1, assignments_end)?;
+
+    let target = statement.text(i, Some(set));
+    let mut from = target.clone();
+    let mut joined = is_join(&target);
+    let mut next = assignments_end;
+    if let Some(from_index) = next.filter(|&n| statement.is(n, "FROM")) {
+        next = statement.find(from_index + 1, &["WHERE", "RETURNING", "ORDER", "LIMIT"]);
+        from = format!("{from}, {}", statement.text(from_index + 1, next));
+        joined = true;
+    }
+    let (where_clause, tail) = where_and_tail(statement, next);
+    Ok(Clauses {
+        prefix: prefix(statement, verb),
+        qualifier: joined.then(|| qualifier(&target)).flatten(),
+        target,
+        from,
+        assignments,
+        where_clause,
+        tail,
+    })
+}
+
+fn delete(
+    statement: &Statement,
+    verb: usize,
+    database_type: &DatabaseType,
+) -> Result<Clauses, String> {
+    let mut i = verb + 1;
+    while matches!(
+        statement.upper(i).as_str(),
+        "LOW_PRIORITY" | "QUICK" | "IGNORE"
+    ) {
+        i += 1;
+    }
+    let clause_keywords = ["USING", "WHERE", "RETURNING", "ORDER", "LIMIT"];
+
+    let (target, mut from, mut joined, mut next) = if statement.is(i, "FROM") {
+        let end = statement.find(i + 1, &clause_keywords);
+        let target = statement.text(i + 1, end);
+        (target.clone(), target.clone(), is_join(&target), end)
+    } else {
+        // MySQL multi-table form: DELETE t1, t2 FROM t1 JOIN t2 ...
+        let from_index = statement
+            .find(i, &["FROM"])
+            .ok_or("DELETE statement has no FROM clause")?;
+        let end = statement.find(from_index + 1, &clause_keywords);
+        (
+            statement.text(i, Some(from_index)),
+            statement.text(from_index + 1, end),
+            true,
+            end,
+        )
+    };
+    if let Some(using) = next.filter(|&n| statement.is(n, "USING")) {
+        next = statement.find(using + 1, &["WHERE", "RETURNING", "ORDER", "LIMIT"]);
+        let using_text = statement.text(using + 1, next);
+        from = if *database_type == DatabaseType::MySQL {
+            // MySQL: DELETE FROM t1, t2 USING t1 JOIN t2 ... lists the tables
+            // to delete from before USING
+            using_text
+        } else {
+            format!("{from}, {using_text}")
+        };
+        joined = true;
+    }
+    let (where_clause, tail) = where_and_tail(statement, next);
+    Ok(Clauses {
+        prefix: prefix(statement, verb),
+        qualifier: joined.then(|| qualifier(&target)).flatten(),
+        target,
+        from,
+        assignments: Vec::new(),
+        where_clause,
+        tail,
+    })
+}
+
+/// `ALTER TABLE t [ON CLUSTER c] UPDATE a = x WHERE ...` / `... DELETE WHERE ...`
+fn clickhouse_mutation(
+    statement: &Statement,
+    verb: usize,
+) -> Result<(DryRunKind, Clauses, Vec<usize>), String> {
+    if !statement.is(verb + 1, "TABLE") {
+        return Err("Only ALTER TABLE ... UPDATE/DELETE mutations can be dry-run".to_string());
+    }
+    let action = statement
+        .find(verb + 2, &["UPDATE", "DELETE"])
+        .ok_or("Only ALTER TABLE ... UPDATE/DELETE mutations can be dry-run")?;
+    let target_end = statement
+        .find(verb + 2, &["ON"])
+        .unwrap_or(action)
+        .min(action);
+    let target = statement.text(verb + 2, Some(target_end));
+    let where_index = statement
+        .find(action + 1, &["WHERE"])
+        .ok_or("ClickHouse mutations need a WHERE clause")?;
+
+    let (kind, assignments) = if statement.is(action, "UPDATE") {
+        (
+            DryRunKind::Update,
+            assignments(statement, action + 1, Some(where_index))?,
+        )
+    } else {
+        (DryRunKind::Delete, Vec::new())
+    };
+    let clauses = Clauses {
+        from: target.clone(),
+        target,
+        assignments,
+        where_clause: Some(statement.text(where_index + 1, None)),
+        ..Default::default()
+    };
+    Ok((kind, clauses, vec![verb, action]))
+}
+
+fn assignments(
+    statement: &Statement,
+    from: usize,
+    to: Option<usize>,
+) -> Result<Vec<(String, String)>, String> {
+    statement
+        .split_commas(from, to)
+        .into_iter()
+        .map(|(start, end)| {
+            let end_index = end.unwrap_or(statement.tokens.len());
+            let equals = (start..end_index)
+                .find(|&i| statement.tokens[i].1 == 0 && statement.tokens[i].0.value == "=")
+                .ok_or_else(|| {
+                    format!(
+                        "Cannot read the assignment '{}'",
+                        statement.text(start, end)
+                    )
+                })?;
+            let value = statement.text(equals + 1, end);
+            // A function may have side effects (nextval, random, ...): show
+            // its assignment as text instead of evaluating it
+            let calls = (equals + 1..end_index).any(|i| {
+                matches!(
+                    statement.tokens[i].0.token_type,
+                    TokenType::Keyword | TokenType::Identifier
+                ) && statement.is(i + 1, "(")
+            });
+            let value = if calls {
+                format!("'{}'", value.replace('\'', "''"))
+            } else {
+                value
+            };
+            Ok((statement.text(start, Some(equals)), value))
+        })
+        .collect()
+}
+
+fn where_and_tail(statement: &Statement, next: Option<usize>) -> (Option<String>, Option<String>) {
+    let mut next = next;
+    let mut where_clause = None;
+    if let Some(where_index) = next.filter(|&n| statement.is(n, "WHERE")) {
+        next = statement.find(where_index + 1, &["RETURNING", "ORDER", "LIMIT"]);
+        where_clause = Some(statement.text(where_index + 1, next));
+    }
+    let tail = next
+        .filter(|&n| !statement.is(n, "RETURNING"))
+        .map(|n| statement.text(n, statement.find(n, &["RETURNING"])));
+    (where_clause, tail)
+}
+
+/// The `WITH ...` part in front of the verb, with a trailing space
+fn prefix(statement: &Statement, verb: usize) -> String {
+    if verb == 0 {
+        String::new()
+    } else {
+        format!("{} ", statement.text(0, Some(verb)))
+    }
+}
+
+fn is_join(target: &str) -> bool {
+    target.contains(',')
+        || target
+            .to_uppercase()
+            .split_whitespace()
+            .any(|w| w == "JOIN")
+}
+
+/// Alias (or name) of a single table reference, for `<qualifier>.*`
+fn qualifier(target: &str) -> Option<String> {
+    if is_join(target) {
+        return None;
+    }
+    let target = target.trim();
+    let target = target
+        .strip_prefix("ONLY ")
+        .or_else(|| target.strip_prefix("only "))
+        .unwrap_or(target)
+        .trim();
+    let name = target
+        .rsplit_once(char::is_whitespace)
+        .map_or(target, |(_, alias)| alias);
+    let quoted = |c: char| matches!(c, '"' | '`');
+    // A quoted name containing spaces cannot be told from an alias
+    if name.ends_with(quoted) != name.starts_with(quoted) {
+        return None;
+    }
+    Some(name.to_string())
+}
+
+/// `\dryrun`: the count, preview and plan of an UPDATE/DELETE, nothing run
+pub async fn run(db: &mut Database, sql: &str) -> Result<String, String> {
+    let database_type = db.get_database_type();
+    if !matches!(
+        database_type,
+        DatabaseType::PostgreSQL
+            | DatabaseType::MySQL
+            | DatabaseType::SQLite
+            | DatabaseType::ClickHouse
+    ) {
+        return Err(format!(
+            "\\dryrun is only available for SQL databases. Current database: {}",
+            database_type.display_name()
+        ));
+    }
+    let plan = plan(sql, database_type.clone())?;
+    let client = db
+        .get_database_client()
+        .ok_or("Not connected to a database")?;
+
+    let count = client
+        .execute_query(&plan.count_sql)
+        .await
+        .map_err(|e| format!("Failed to count the affected rows: {e}"))?
+        .get(1)
+        .and_then(|row| row.first())
+        .cloned()
+        .unwrap_or_else(|| "0".to_string());
+    let verb = match plan.kind {
+        DryRunKind::Update => "update",
+        DryRunKind::Delete => "delete",
+    };
+    let mut output = format!(
+        "Dry run: would {verb} {count} row(s) in {}. Nothing was executed.\n",
+        plan.target
+    );
+
+    if count.trim() != "0" {
+        match client.execute_query(&plan.preview_sql).await {
+            Ok(rows) => {
+                let heading = match plan.kind {
+                    DryRunKind::Update => "with their new values",
+                    DryRunKind::Delete => "to be deleted",
+                };
+                output.push_str(&format!(
+                    "\nFirst {} row(s), {heading}:\n{}",
+                    rows.len().saturating_sub(1),
+                    crate::format::format_query_results_psql(&rows)
+                ));
+            }
+            Err(e) => output.push_str(&format!("\nPreview unavailable: {e}\n")),
+        }
+    }
+
+    let plan_heading = if database_type == DatabaseType::ClickHouse {
+        "Plan of the equivalent SELECT"
+    } else {
+        "Query plan"
+    };
+    match client.execute_query(&plan.explain_sql).await {
+        Ok(rows) => output.push_str(&format!(
+            "\n{plan_heading}:\n{}",
+            crate::format::format_query_results_psql(&rows)
+        )),
+        Err(e) => output.push_str(&format!("\n{plan_heading} unavailable: {e}\n")),
+    }
+    Ok(output.trim_end().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_rewrites_to_count_and_preview() {
+        let plan = super::plan(
+            "UPDATE orders SET status = 'shipped', total = total * 1.1 WHERE id < 10 RETURNING id;",
+            DatabaseType::PostgreSQL,
+        )
+        .unwrap();
+        assert_eq!(plan.kind, DryRunKind::Update);
+        assert_eq!(plan.target, "orders");
+        assert_eq!(plan.count_sql, "SELECT COUNT(*) FROM orders WHERE id < 10");
+        assert_eq!(
+            plan.preview_sql,
+            "SELECT *, 'shipped' AS \"status (new)\", total * 1.1 AS \"total (new)\" \
+             FROM orders WHERE id < 10 LIMIT 10"
+        );
+        assert_eq!(
+            plan.explain_sql,
+            "EXPLAIN UPDATE orders SET status = 'shipped', total = total * 1.1 WHERE id < 10 RETURNING id"
+        );
+    }
+
+    #[test]
+    fn test_function_calls_are_shown_as_text() {
+        let plan = super::plan(
+            "UPDATE orders SET ref = nextval('order_ref'), note = upper(note), total = 0 WHERE id = 1",
+            DatabaseType::PostgreSQL,
+        )
+        .unwrap();
+        assert_eq!(
+            plan.preview_sql,
+            "SELECT *, 'nextval(''order_ref'')' AS \"ref (new)\", 'upper(note)' AS \"note (new)\", \
+             0 AS \"total (new)\" FROM orders WHERE id = 1 LIMIT 10"
+        );
+    }
+
+    #[test]
+    fn test_joined_forms_keep_the_target_qualifier() {
+        let plan = super::plan(
+            "UPDATE orders o SET total = p.price FROM prices p WHERE p.id = o.price_id",
+            DatabaseType::PostgreSQL,
+        )
+        .unwrap();
+        assert_eq!(
+            plan.preview_sql,
+            "SELECT o.*, p.price AS \"total (new)\" FROM orders o, prices p \
+             WHERE p.id = o.price_id LIMIT 10"
+        );
+
+        let plan = super::plan(
+            "DELETE FROM sessions s USING users u WHERE u.id = s.user_id AND u.banned",
+            DatabaseType::PostgreSQL,
+        )
+        .unwrap();
+        assert_eq!(
+            plan.count_sql,
+            "SELECT COUNT(*) FROM sessions s, users u WHERE u.id = s.user_id AND u.banned"
+        );
+        assert!(plan.preview_sql.starts_with("SELECT s.* FROM"));
+    }
+
+    #[test]
+    fn test_mysql_order_limit_and_clickhouse_mutation() {
+        let plan = super::plan(
+            "DELETE FROM logs WHERE level = 'debug' ORDER BY created_at LIMIT 500",
+            DatabaseType::MySQL,
+        )
+        .unwrap();
+        assert_eq!(
+            plan.count_sql,
+            "SELECT COUNT(*) FROM (SELECT 1 FROM logs WHERE level = 'debug' \
+             ORDER BY created_at LIMIT 500) AS dry_run"
+        );
+
+        let plan = super::plan(
+            "ALTER TABLE events ON CLUSTER main UPDATE kind = 'x' WHERE kind = ''",
+            DatabaseType::ClickHouse,
+        )
+        .unwrap();
+        assert_eq!(plan.target, "events");
+        assert_eq!(
+            plan.count_sql,
+            "SELECT COUNT(*) FROM events WHERE kind = ''"
+        );
+        assert_eq!(plan.explain_sql, format!("EXPLAIN {}", plan.count_sql));
+    }
+
+    #[test]
+    fn test_refuses_anything_that_would_write() {
+        for sql in [
+            "SELECT 1",
+            "INSERT INTO t VALUES (1)",
+            "WITH gone AS (DELETE FROM a RETURNING id) DELETE FROM b WHERE id IN (SELECT id FROM gone)",
+            "DELETE FROM a WHERE id = 1; DROP TABLE b",
+            "UPDATE t WHERE id = 1",
+        ] {
+            assert!(super::plan(sql, DatabaseType::PostgreSQL).is_err(), "{sql}");
+        }
+        assert!(super::plan("ALTER TABLE t DROP COLUMN c", DatabaseType::PostgreSQL).is_err());
+    }
+}
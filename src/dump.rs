@@ -0,0 +1,1124 @@
+//! Portable SQL dumps (`\dump`) and their restore (`\restore`)
+//!
+//! Dumps are plain SQL built from the catalog, with rows streamed from the
+//! server one at a time and quoted by the server itself, so no client tools
+//! are needed: pg_dump-style plain SQL with column inserts for PostgreSQL,
+//! `SHOW CREATE TABLE` definitions for MySQL and the layout of the sqlite3
+//! `.dump` command for SQLite. Constraints, indexes and foreign keys come
+//! after the data, so rows load in any order. Only tables (with their
+//! sequences, constraints, indexes and triggers) are written; types,
+//! functions and roles need the native dump tools.
+
+use crate::database::{
+    DatabaseClient, DatabaseError, DatabaseType, RowSink, escape_sql_string, quote_sql_ident,
+};
+use crate::insert_builder::{quote_identifier, quote_literal};
+use serde_json::Value;
+use std::io::Write;
+
+/// Rows merged into one INSERT by `\restore`
+const BATCH_ROWS: usize = 500;
+
+/// Statement length after which `\restore` starts a new INSERT
+const BATCH_BYTES: usize = 1024 * 1024;
+
+/// MySQL data types written as hex literals
+const MYSQL_BINARY_TYPES: &[&str] = &[
+    "binary",
+    "varbinary",
+    "tinyblob",
+    "blob",
+    "mediumblob",
+    "longblob",
+    "geometry",
+    "point",
+    "linestring",
+    "polygon",
+    "multipoint",
+    "multilinestring",
+    "multipolygon",
+    "geometrycollection",
+    "geomcollection",
+];
+
+/// Whether `\dump` and `\restore` work on `database_type`
+pub fn is_supported(database_type: &DatabaseType) -> bool {
+    matches!(
+        database_type,
+        DatabaseType::PostgreSQL | DatabaseType::MySQL | DatabaseType::SQLite
+    )
+}
+
+/// What `\dump` wrote
+#[derive(Debug, Default)]
+pub struct DumpSummary {
+    pub tables: usize,
+    pub rows: u64,
+}
+
+/// Writes one INSERT per row. Each row arrives as a single column holding
+/// its values, already quoted as SQL literals by the server.
+struct InsertWriter<'a, W: Write + Send> {
+    out: &'a mut W,
+    head: &'a str,
+}
+
+impl<W: Write + Send> RowSink for InsertWriter<'_, W> {
+    fn columns(&mut self, _names: &[String]) -> Result<(), DatabaseError> {
+        Ok(())
+    }
+
+    fn row(&mut self, values: Vec<Value>) -> Result<(), DatabaseError> {
+        let literals = match values.into_iter().next() {
+            Some(Value::String(literals)) => literals,
+            Some(other) => other.to_string(),
+            None => String::new(),
+        };
+        writeln!(self.out, "{}{literals});", self.head)?;
+        Ok(())
+    }
+}
+
+/// Data rows of `sql`, without the header row
+async fn query_rows(
+    client: &dyn DatabaseClient,
+    sql: &str,
+) -> Result<Vec<Vec<String>>, DatabaseError> {
+    Ok(client
+        .execute_query(sql)
+        .await?
+        .into_iter()
+        .skip(1)
+        .collect())
+}
+
+/// `name` without the quotes or backticks around it
+fn unquote(name: &str) -> &str {
+    for quote in ['"', '`'] {
+        if let Some(inner) = name
+            .strip_prefix(quote)
+            .and_then(|rest| rest.strip_suffix(quote))
+        {
+            return inner;
+        }
+    }
+    name
+}
+
+/// Whether `target` names the connected database rather than one table
+fn is_whole_database(client: &dyn DatabaseClient, target: &str) -> bool {
+    let database_type = &client.get_connection_info().database_type;
+    target == client.get_current_database()
+        || (*database_type == DatabaseType::SQLite && target == "main")
+}
+
+fn no_such_table(client: &dyn DatabaseClient, target: &str) -> DatabaseError {
+    DatabaseError::QueryError(format!(
+        "No table named '{target}'. Use the database name ({}) to dump every table.",
+        client.get_current_database()
+    ))
+}
+
+/// Write `target` (a table, or the connected database by name) to `out` as SQL
+pub async fn dump<W: Write + Send>(
+    client: &dyn DatabaseClient,
+    target: &str,
+    out: &mut W,
+) -> Result<DumpSummary, DatabaseError> {
+    let database_type = client.get_connection_info().database_type.clone();
+    let summary = match database_type {
+        DatabaseType::PostgreSQL => dump_postgresql(client, target, out).await?,
+        DatabaseType::MySQL => dump_mysql(client, target, out).await?,
+        DatabaseType::SQLite => dump_sqlite(client, target, out).await?,
+        _ => {
+            return Err(DatabaseError::FeatureNotSupported {
+                database_type,
+                feature: "\\dump".to_string(),
+            });
+        }
+    };
+    out.flush()?;
+    Ok(summary)
+}
+
+fn dump_header(client: &dyn DatabaseClient, kind: &str, tables: usize) -> String {
+    format!(
+        "--\n-- {kind} dump of {} ({tables} table{}), written by dbcrust {}\n--\n\n",
+        client.get_current_database(),
+        if tables == 1 { "" } else { "s" },
+        env!("CARGO_PKG_VERSION")
+    )
+}
+
+/// A PostgreSQL table and everything written for it around its rows
+struct PgTable {
+    qualified: String,
+    schema: String,
+    definition: String,
+    /// `INSERT INTO ... VALUES (`, empty for tables without insertable columns
+    insert_head: String,
+    select: String,
+    sequences: Vec<String>,
+    after_data: Vec<String>,
+    foreign_keys: Vec<String>,
+}
+
+/// Sequence named in a `nextval('...'::regclass)` column default
+fn nextval_sequence(default: &str) -> Option<String> {
+    let start = default.find("nextval('")? + "nextval('".len();
+    let end = start + default[start..].find("'::regclass)")?;
+    Some(default[start..end].replace("''", "'"))
+}
+
+/// Where a table sits in a partition tree
+#[derive(Debug, Default, PartialEq)]
+struct PgPartitioning {
+    /// Quoted parent and `FOR VALUES ...` bound, for a partition
+    parent: Option<(String, String)>,
+    /// `RANGE (created_at)`..., for a partitioned table
+    key: Option<String>,
+}
+
+/// `CREATE TABLE` for a table with column `lines`. A partition takes its
+/// columns from its parent.
+fn postgresql_create_table(
+    qualified: &str,
+    lines: &[String],
+    partitioning: &PgPartitioning,
+) -> String {
+    let body = match &partitioning.parent {
+        Some((parent, bound)) => format!("PARTITION OF {parent} {bound}"),
+        None => format!("(\n{}\n)", lines.join(",\n")),
+    };
+    match &partitioning.key {
+        Some(key) => format!("CREATE TABLE {qualified} {body} PARTITION BY {key};"),
+        None => format!("CREATE TABLE {qualified} {body};"),
+    }
+}
+
+async fn postgresql_table(
+    client: &dyn DatabaseClient,
+    schema: &str,
+    name: &str,
+    relkind: &str,
+) -> Result<PgTable, DatabaseError> {
+    let qualified = format!("{}.{}", quote_sql_ident(schema), quote_sql_ident(name));
+    let regclass = format!("'{}'::regclass", escape_sql_string(&qualified));
+    let partitioned = relkind == "p";
+    let partition_info = query_rows(
+        client,
+        &format!(
+            "SELECT COALESCE(pn.nspname, ''), COALESCE(p.relname, ''), \
+                    COALESCE(pg_catalog.pg_get_expr(c.relpartbound, c.oid), ''), \
+                    COALESCE(pg_catalog.pg_get_partkeydef(c.oid), '') \
+             FROM pg_catalog.pg_class c \
+             LEFT JOIN pg_catalog.pg_inherits i ON i.inhrelid = c.oid AND c.relispartition \
+             LEFT JOIN pg_catalog.pg_class p ON p.oid = i.inhparent \
+             LEFT JOIN pg_catalog.pg_namespace pn ON pn.oid = p.relnamespace \
+             WHERE c.oid = {regclass}"
+        ),
+    )
+    .await?;
+    let partitioning = match partition_info.first().map(Vec::as_slice) {
+        Some([parent_schema, parent, bound, key]) => PgPartitioning {
+            parent: (!parent.is_empty()).then(|| {
+                (
+                    format!(
+                        "{}.{}",
+                        quote_sql_ident(parent_schema),
+                        quote_sql_ident(parent)
+                    ),
+                    bound.clone(),
+                )
+            }),
+            key: (!key.is_empty()).then(|| key.clone()),
+        },
+        _ => PgPartitioning::default(),
+    };
+    // Constraints and indexes of a partitioned table are added to the whole
+    // tree at once (no ONLY), so partitions skip the ones they inherit
+    let alter = if partitioned {
+        "ALTER TABLE"
+    } else {
+        "ALTER TABLE ONLY"
+    };
+    let columns = query_rows(
+        client,
+        &format!(
+            "SELECT a.attname, pg_catalog.format_type(a.atttypid, a.atttypmod), \
+                    a.attnotnull::text, \
+                    COALESCE(pg_catalog.pg_get_expr(d.adbin, d.adrelid), ''), \
+                    a.attidentity::text, a.attgenerated::text \
+             FROM pg_catalog.pg_attribute a \
+             LEFT JOIN pg_catalog.pg_attrdef d ON d.adrelid = a.attrelid AND d.adnum = a.attnum \
+             WHERE a.attrelid = {regclass} AND a.attnum > 0 AND NOT a.attisdropped \
+             ORDER BY a.attnum"
+        ),
+    )
+    .await?;
+
+    let mut lines = Vec::new();
+    let mut insert_columns = Vec::new();
+    let mut literals = Vec::new();
+    let mut sequences = Vec::new();
+    let mut after_data = Vec::new();
+    let mut identity_always = false;
+    for column in &columns {
+        let [
+            column_name,
+            data_type,
+            not_null,
+            default,
+            identity,
+            generated,
+        ] = column.as_slice()
+        else {
+            continue;
+        };
+        let quoted = quote_sql_ident(column_name);
+        let mut line = format!("    {quoted} {data_type}");
+        let mut sequence = None;
+        if generated == "s" {
+            line.push_str(&format!(" GENERATED ALWAYS AS ({default}) STORED"));
+        } else if !identity.is_empty() {
+            identity_always |= identity == "a";
+            line.push_str(if identity == "a" {
+                " GENERATED ALWAYS AS IDENTITY"
+            } else {
+                " GENERATED BY DEFAULT AS IDENTITY"
+            });
+            let serial = query_rows(
+                client,
+                &format!(
+                    "SELECT pg_catalog.pg_get_serial_sequence('{}', '{}')",
+                    escape_sql_string(&qualified),
+                    escape_sql_string(column_name)
+                ),
+            )
+            .await?;
+            sequence = serial.into_iter().flatten().next().map(|sequence| {
+                let setval_target = format!(
+                    "pg_catalog.pg_get_serial_sequence('{}', '{}')",
+                    escape_sql_string(&qualified),
+                    escape_sql_string(column_name)
+                );
+                (sequence, setval_target)
+            });
+        } else if !default.is_empty() {
+            line.push_str(&format!(" DEFAULT {default}"));
+            if let Some(name) = nextval_sequence(default) {
+                sequences.push(format!("CREATE SEQUENCE IF NOT EXISTS {name};"));
+                let setval_target = format!("'{}'", escape_sql_string(&name));
+                sequence = Some((name, setval_target));
+            }
+        }
+        if not_null == "true" && identity.is_empty() {
+            line.push_str(" NOT NULL");
+        }
+        lines.push(line);
+        if let Some((sequence, setval_target)) = sequence {
+            let state = query_rows(
+                client,
+                &format!("SELECT last_value, is_called::text FROM {sequence}"),
+            )
+            .await?;
+            if let Some([last_value, is_called]) = state.first().map(Vec::as_slice) {
+                after_data.push(format!(
+                    "SELECT pg_catalog.setval({setval_target}, {last_value}, {is_called});"
+                ));
+            }
+        }
+        if generated != "s" {
+            insert_columns.push(quoted.clone());
+            literals.push(format!("pg_catalog.quote_nullable({quoted})"));
+        }
+    }
+
+    let mut foreign_keys = Vec::new();
+    let constraints = query_rows(
+        client,
+        &format!(
+            "SELECT conname, contype::text, pg_catalog.pg_get_constraintdef(oid) \
+             FROM pg_catalog.pg_constraint \
+             WHERE conrelid = {regclass} AND contype IN ('p', 'u', 'c', 'x', 'f'){} \
+             ORDER BY contype <> 'p', conname",
+            if partitioning.parent.is_some() {
+                " AND coninhcount = 0"
+            } else {
+                ""
+            }
+        ),
+    )
+    .await?;
+    for constraint in &constraints {
+        let [constraint_name, kind, definition] = constraint.as_slice() else {
+            continue;
+        };
+        let statement = format!(
+            "{alter} {qualified} ADD CONSTRAINT {} {definition};",
+            quote_sql_ident(constraint_name)
+        );
+        if kind == "f" {
+            foreign_keys.push(statement);
+        } else {
+            after_data.push(statement);
+        }
+    }
+    let indexes = query_rows(
+        client,
+        &format!(
+            "SELECT pg_catalog.pg_get_indexdef(i.indexrelid) FROM pg_catalog.pg_index i \
+             WHERE i.indrelid = {regclass} AND NOT EXISTS ( \
+                 SELECT 1 FROM pg_catalog.pg_constraint c \
+                 WHERE c.conindid = i.indexrelid AND c.conrelid = i.indrelid \
+                   AND c.contype IN ('p', 'u', 'x')) \
+               AND NOT EXISTS ( \
+                 SELECT 1 FROM pg_catalog.pg_inherits h WHERE h.inhrelid = i.indexrelid) \
+             ORDER BY 1"
+        ),
+    )
+    .await?;
+    after_data.extend(indexes.into_iter().flatten().map(|definition| {
+        if partitioned {
+            // `ON ONLY` would leave the index without its partitions
+            format!("{};", definition.replacen(" ON ONLY ", " ON ", 1))
+        } else {
+            format!("{definition};")
+        }
+    }));
+
+    // A partitioned table holds no rows: each partition writes its own
+    let insert_head = if insert_columns.is_empty() || partitioned {
+        String::new()
+    } else {
+        format!(
+            "INSERT INTO {qualified} ({}){} VALUES (",
+            insert_columns.join(", "),
+            if identity_always {
+                " OVERRIDING SYSTEM VALUE"
+            } else {
+                ""
+            }
+        )
+    };
+    // ONLY keeps rows of inheritance children out
+    let select = format!(
+        "SELECT pg_catalog.concat_ws(', ', {}) FROM ONLY {qualified}",
+        literals.join(", ")
+    );
+    Ok(PgTable {
+        definition: postgresql_create_table(&qualified, &lines, &partitioning),
+        qualified,
+        schema: schema.to_string(),
+        insert_head,
+        select,
+        sequences,
+        after_data,
+        foreign_keys,
+    })
+}
+
+async fn dump_postgresql<W: Write + Send>(
+    client: &dyn DatabaseClient,
+    target: &str,
+    out: &mut W,
+) -> Result<DumpSummary, DatabaseError> {
+    const TABLES: &str = "SELECT n.nspname, c.relname, c.relkind::text \
+        FROM pg_catalog.pg_class c \
+        JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace";
+    const PARENTS_FIRST: &str =
+        "(SELECT count(*) FROM pg_catalog.pg_partition_ancestors(c.oid)), 1, 2";
+    // A partitioned table comes with its partitions, parents first
+    let mut tables = query_rows(
+        client,
+        &format!(
+            "{TABLES} WHERE c.relkind IN ('r', 'p') AND c.oid IN ( \
+                 SELECT pg_catalog.to_regclass('{0}') \
+                 UNION SELECT relid \
+                 FROM pg_catalog.pg_partition_tree(pg_catalog.to_regclass('{0}'))) \
+             ORDER BY {PARENTS_FIRST}",
+            escape_sql_string(target)
+        ),
+    )
+    .await?;
+    if tables.is_empty() {
+        if !is_whole_database(client, target) {
+            return Err(no_such_table(client, target));
+        }
+        tables = query_rows(
+            client,
+            &format!(
+                "{TABLES} WHERE c.relkind IN ('r', 'p') \
+                   AND n.nspname <> 'information_schema' AND n.nspname NOT LIKE 'pg\\_%' \
+                 ORDER BY {PARENTS_FIRST}"
+            ),
+        )
+        .await?;
+    }
+
+    let mut definitions = Vec::new();
+    for table in &tables {
+        if let [schema, name, relkind] = table.as_slice() {
+            definitions.push(postgresql_table(client, schema, name, relkind).await?);
+        }
+    }
+
+    write!(
+        out,
+        "{}SET client_encoding = 'UTF8';\nSET standard_conforming_strings = on;\n",
+        dump_header(client, "PostgreSQL", definitions.len())
+    )?;
+    let mut schemas: Vec<&str> = definitions
+        .iter()
+        .map(|table| table.schema.as_str())
+        .filter(|schema| *schema != "public")
+        .collect();
+    schemas.dedup();
+    for schema in schemas {
+        writeln!(
+            out,
+            "CREATE SCHEMA IF NOT EXISTS {};",
+            quote_sql_ident(schema)
+        )?;
+    }
+    for table in &definitions {
+        writeln!(out, "\n-- Table {}\n", table.qualified)?;
+        for sequence in &table.sequences {
+            writeln!(out, "{sequence}")?;
+        }
+        writeln!(out, "{}", table.definition)?;
+    }
+
+    let mut rows = 0;
+    for table in &definitions {
+        if table.insert_head.is_empty() {
+            continue;
+        }
+        writeln!(out, "\n-- Data for {}\n", table.qualified)?;
+        let mut writer = InsertWriter {
+            out: &mut *out,
+            head: &table.insert_head,
+        };
+        rows += client.stream_query(&table.select, &mut writer).await?;
+    }
+
+    let after_data: Vec<&String> = definitions
+        .iter()
+        .flat_map(|table| &table.after_data)
+        .collect();
+    if !after_data.is_empty() {
+        writeln!(out, "\n-- Sequence values, constraints and indexes\n")?;
+        for statement in after_data {
+            writeln!(out, "{statement}")?;
+        }
+    }
+    let foreign_keys: Vec<&String> = definitions
+        .iter()
+        .flat_map(|table| &table.foreign_keys)
+        .collect();
+    if !foreign_keys.is_empty() {
+        writeln!(out, "\n-- Foreign keys\n")?;
+        for statement in foreign_keys {
+            writeln!(out, "{statement}")?;
+        }
+    }
+    Ok(DumpSummary {
+        tables: definitions.len(),
+        rows,
+    })
+}
+
+/// `SHOW CREATE TABLE` output without its foreign keys, and the foreign key
+/// clauses taken out of it
+fn split_mysql_foreign_keys(definition: &str) -> (String, Vec<String>) {
+    let lines: Vec<&str> = definition.lines().collect();
+    let (Some((first, rest)), true) = (lines.split_first(), lines.len() >= 3) else {
+        return (definition.to_string(), Vec::new());
+    };
+    let Some((last, body)) = rest.split_last() else {
+        return (definition.to_string(), Vec::new());
+    };
+    let mut kept = Vec::new();
+    let mut foreign_keys = Vec::new();
+    for line in body {
+        let item = line.trim_end().trim_end_matches(',');
+        if item.trim_start().starts_with("CONSTRAINT ") && item.contains(" FOREIGN KEY ") {
+            foreign_keys.push(item.trim().to_string());
+        } else {
+            kept.push(item);
+        }
+    }
+    (
+        format!("{first}\n{}\n{last}", kept.join(",\n")),
+        foreign_keys,
+    )
+}
+
+/// Expression quoting one MySQL column as a literal that reads back the same
+fn mysql_literal(column: &str, data_type: &str) -> String {
+    let quoted = quote_identifier(column, &DatabaseType::MySQL);
+    let literal = if MYSQL_BINARY_TYPES.contains(&data_type) {
+        format!("CONCAT('X''', HEX({quoted}), '''')")
+    } else if data_type == "bit" {
+        format!("CONCAT('b''', BIN({quoted}), '''')")
+    } else {
+        // QUOTE() writes \' for quotes; doubled quotes read back the same
+        // and keep the statements splittable on `;`
+        format!(
+            "CONCAT('''', REPLACE(REPLACE({quoted}, CHAR(92 USING utf8mb4), \
+             CONCAT(CHAR(92 USING utf8mb4), CHAR(92 USING utf8mb4))), '''', ''''''), '''')"
+        )
+    };
+    format!("IF({quoted} IS NULL, 'NULL', {literal})")
+}
+
+async fn dump_mysql<W: Write + Send>(
+    client: &dyn DatabaseClient,
+    target: &str,
+    out: &mut W,
+) -> Result<DumpSummary, DatabaseError> {
+    const TABLES: &str = "SELECT TABLE_NAME FROM information_schema.TABLES \
+        WHERE TABLE_SCHEMA = DATABASE() AND TABLE_TYPE = 'BASE TABLE'";
+    let mut tables: Vec<String> = query_rows(
+        client,
+        &format!(
+            "{TABLES} AND TABLE_NAME = {}",
+            quote_literal(unquote(target), &DatabaseType::MySQL)
+        ),
+    )
+    .await?
+    .into_iter()
+    .flatten()
+    .collect();
+    if tables.is_empty() {
+        if !is_whole_database(client, target) {
+            return Err(no_such_table(client, target));
+        }
+        tables = query_rows(client, &format!("{TABLES} ORDER BY TABLE_NAME"))
+            .await?
+            .into_iter()
+            .flatten()
+            .collect();
+    }
+
+    writeln!(
+        out,
+        "{}SET NAMES utf8mb4;",
+        dump_header(client, "MySQL", tables.len())
+    )?;
+    let mut rows = 0;
+    let mut foreign_keys = Vec::new();
+    for table in &tables {
+        let quoted = quote_identifier(table, &DatabaseType::MySQL);
+        let created = query_rows(client, &format!("SHOW CREATE TABLE {quoted}")).await?;
+        let Some(definition) = created.first().and_then(|row| row.get(1)) else {
+            continue;
+        };
+        let (definition, table_foreign_keys) = split_mysql_foreign_keys(definition);
+        writeln!(out, "\n-- Table {quoted}\n\n{definition};")?;
+        foreign_keys.extend(
+            table_foreign_keys
+                .into_iter()
+                .map(|foreign_key| format!("ALTER TABLE {quoted} ADD {foreign_key};")),
+        );
+
+        let columns = query_rows(
+            client,
+            &format!(
+                "SELECT COLUMN_NAME, LOWER(DATA_TYPE), EXTRA FROM information_schema.COLUMNS \
+                 WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = {} ORDER BY ORDINAL_POSITION",
+                quote_literal(table, &DatabaseType::MySQL)
+            ),
+        )
+        .await?;
+        let (names, literals): (Vec<String>, Vec<String>) = columns
+            .iter()
+            .filter_map(|column| match column.as_slice() {
+                [name, data_type, extra]
+                    if !extra.contains("VIRTUAL GENERATED")
+                        && !extra.contains("STORED GENERATED") =>
+                {
+                    Some((
+                        quote_identifier(name, &DatabaseType::MySQL),
+                        mysql_literal(name, data_type),
+                    ))
+                }
+                _ => None,
+            })
+            .unzip();
+        if names.is_empty() {
+            continue;
+        }
+        writeln!(out)?;
+        let head = format!("INSERT INTO {quoted} ({}) VALUES (", names.join(", "));
+        let select = format!(
+            "SELECT CONVERT(CONCAT_WS(', ', {}) USING utf8mb4) FROM {quoted}",
+            literals.join(", ")
+        );
+        rows += client
+            .stream_query(
+                &select,
+                &mut InsertWriter {
+                    out: &mut *out,
+                    head: &head,
+                },
+            )
+            .await?;
+    }
+    if !foreign_keys.is_empty() {
+        writeln!(out, "\n-- Foreign keys\n")?;
+        for statement in foreign_keys {
+            writeln!(out, "{statement}")?;
+        }
+    }
+    Ok(DumpSummary {
+        tables: tables.len(),
+        rows,
+    })
+}
+
+async fn dump_sqlite<W: Write + Send>(
+    client: &dyn DatabaseClient,
+    target: &str,
+    out: &mut W,
+) -> Result<DumpSummary, DatabaseError> {
+    const USER_OBJECTS: &str = "name NOT LIKE 'sqlite\\_%' ESCAPE '\\'";
+    let name = unquote(target);
+    let mut tables = query_rows(
+        client,
+        &format!(
+            "SELECT name, sql FROM sqlite_master WHERE type = 'table' AND name = {}",
+            quote_literal(name, &DatabaseType::SQLite)
+        ),
+    )
+    .await?;
+    let whole = tables.is_empty();
+    if whole {
+        if !is_whole_database(client, target) {
+            return Err(no_such_table(client, target));
+        }
+        tables = query_rows(
+            client,
+            &format!(
+                "SELECT name, sql FROM sqlite_master \
+                 WHERE type = 'table' AND {USER_OBJECTS} ORDER BY rowid"
+            ),
+        )
+        .await?;
+    }
+
+    write!(
+        out,
+        "{}PRAGMA foreign_keys=OFF;\nBEGIN TRANSACTION;\n",
+        dump_header(client, "SQLite", tables.len())
+    )?;
+    let mut rows = 0;
+    for table in &tables {
+        let [table_name, definition] = table.as_slice() else {
+            continue;
+        };
+        writeln!(out, "{definition};")?;
+        if definition
+            .to_ascii_uppercase()
+            .starts_with("CREATE VIRTUAL TABLE")
+        {
+            continue;
+        }
+        let columns = query_rows(
+            client,
+            &format!(
+                "SELECT name, hidden FROM pragma_table_xinfo({}) ORDER BY cid",
+                quote_literal(table_name, &DatabaseType::SQLite)
+            ),
+        )
+        .await?;
+        let names: Vec<String> = columns
+            .iter()
+            .filter(|column| column.get(1).is_some_and(|hidden| hidden == "0"))
+            .map(|column| quote_sql_ident(&column[0]))
+            .collect();
+        if names.is_empty() {
+            continue;
+        }
+        let quoted = quote_sql_ident(table_name);
+        // Generated columns are left out, which needs the column list
+        let head = if names.len() == columns.len() {
+            format!("INSERT INTO {quoted} VALUES(")
+        } else {
+            format!("INSERT INTO {quoted}({}) VALUES(", names.join(","))
+        };
+        let literals: Vec<String> = names.iter().map(|name| format!("quote({name})")).collect();
+        let select = format!("SELECT {} FROM {quoted}", literals.join(" || ',' || "));
+        rows += client
+            .stream_query(
+                &select,
+                &mut InsertWriter {
+                    out: &mut *out,
+                    head: &head,
+                },
+            )
+            .await?;
+    }
+
+    let names: Vec<String> = tables
+        .iter()
+        .map(|table| quote_literal(&table[0], &DatabaseType::SQLite))
+        .collect();
+    if whole && !names.is_empty() {
+        let sequences = query_rows(
+            client,
+            "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'sqlite_sequence'",
+        )
+        .await?;
+        if !sequences.is_empty() {
+            writeln!(out, "DELETE FROM sqlite_sequence;")?;
+            for sequence in query_rows(client, "SELECT name, seq FROM sqlite_sequence").await? {
+                if let [table_name, seq] = sequence.as_slice() {
+                    writeln!(
+                        out,
+                        "INSERT INTO sqlite_sequence VALUES({},{seq});",
+                        quote_literal(table_name, &DatabaseType::SQLite)
+                    )?;
+                }
+            }
+        }
+    }
+    let objects = if whole {
+        format!("type IN ('index', 'trigger', 'view') AND {USER_OBJECTS}")
+    } else {
+        format!(
+            "type IN ('index', 'trigger') AND tbl_name IN ({})",
+            names.join(", ")
+        )
+    };
+    if !names.is_empty() {
+        for definition in query_rows(
+            client,
+            &format!(
+                "SELECT sql FROM sqlite_master WHERE sql IS NOT NULL AND {objects} ORDER BY rowid"
+            ),
+        )
+        .await?
+        .into_iter()
+        .flatten()
+        {
+            writeln!(out, "{definition};")?;
+        }
+    }
+    writeln!(out, "COMMIT;")?;
+    Ok(DumpSummary {
+        tables: tables.len(),
+        rows,
+    })
+}
+
+/// Statements `\restore` runs, in order
+#[derive(Debug, Default)]
+pub struct RestorePlan {
+    pub statements: Vec<String>,
+    /// Rows inserted, counted before consecutive INSERTs were merged
+    pub rows: usize,
+    /// psql meta-commands (`\connect`, ...) left out
+    pub skipped: usize,
+}
+
+/// Lowercased leading words of `statement`, up to `count`
+fn leading_words(statement: &str, count: usize) -> String {
+    statement
+        .split_whitespace()
+        .take(count)
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// `statement` without the `--` and `/* */` comments before it
+fn without_leading_comments(statement: &str) -> &str {
+    let mut rest = statement;
+    loop {
+        rest = rest.trim_start();
+        if let Some(after) = rest.strip_prefix("--") {
+            rest = after.split_once('\n').map_or("", |(_, tail)| tail);
+        } else if let Some(tail) = rest
+            .strip_prefix("/*")
+            .and_then(|after| after.split_once("*/"))
+            .map(|(_, tail)| tail)
+        {
+            rest = tail;
+        } else {
+            return rest;
+        }
+    }
+}
+
+/// Statements controlling the transaction the whole restore already runs in
+fn is_transaction_control(statement: &str) -> bool {
+    matches!(
+        leading_words(statement, 2).as_str(),
+        "begin"
+            | "begin transaction"
+            | "begin work"
+            | "start transaction"
+            | "commit"
+            | "commit transaction"
+            | "commit work"
+            | "end"
+            | "end transaction"
+    )
+}
+
+/// Whether `statement` opens a trigger whose `BEGIN ... END` body was split
+/// on its inner semicolons
+fn opens_trigger_body(statement: &str) -> bool {
+    let words = leading_words(statement, 4);
+    (words.starts_with("create trigger")
+        || words.starts_with("create temp trigger")
+        || words.starts_with("create temporary trigger")
+        || words.contains(" trigger ") && words.starts_with("create definer"))
+        && statement
+            .split_whitespace()
+            .any(|word| word.eq_ignore_ascii_case("begin"))
+        && !statement
+            .split_whitespace()
+            .last()
+            .is_some_and(|word| word.eq_ignore_ascii_case("end"))
+}
+
+/// The `INSERT ... VALUES` head and the row of `statement`, when it inserts
+/// a single row and nothing follows it
+fn single_row_insert(statement: &str) -> Option<(&str, &str)> {
+    if !statement.get(..11)?.eq_ignore_ascii_case("insert into") {
+        return None;
+    }
+    let bytes = statement.as_bytes();
+    let is_word = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+    let mut quote = None;
+    let mut values_at = None;
+    for (i, &b) in bytes.iter().enumerate() {
+        match quote {
+            // A doubled quote closes and reopens, which comes out the same
+            Some(q) if b == q => quote = None,
+            Some(_) => {}
+            None if matches!(b, b'\'' | b'"' | b'`') => quote = Some(b),
+            None => {
+                if bytes[i..].len() >= 6
+                    && bytes[i..i + 6].eq_ignore_ascii_case(b"values")
+                    && !is_word(bytes[i - 1])
+                    && bytes.get(i + 6).is_none_or(|&next| !is_word(next))
+                {
+                    values_at = Some(i + 6);
+                    break;
+                }
+            }
+        }
+    }
+    let rest = &statement[values_at?..];
+    let open = values_at? + rest.len() - rest.trim_start().len();
+    if bytes.get(open) != Some(&b'(') {
+        return None;
+    }
+    let mut depth = 0;
+    let mut quote = None;
+    for (i, &b) in bytes.iter().enumerate().skip(open) {
+        match quote {
+            Some(q) if b == q => quote = None,
+            Some(_) => {}
+            None => match b {
+                b'\'' | b'"' | b'`' => quote = Some(b),
+                b'(' => depth += 1,
+                b')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return (i + 1 == bytes.len())
+                            .then(|| (&statement[..open], &statement[open..]));
+                    }
+                }
+                _ => {}
+            },
+        }
+    }
+    None
+}
+
+/// Merge runs of single-row INSERTs into the same table and columns into
+/// multi-row INSERTs of up to [`BATCH_ROWS`] rows
+fn batch_inserts(statements: Vec<String>) -> (Vec<String>, usize) {
+    let mut batched = Vec::new();
+    let mut rows = 0;
+    // Head of the INSERT being built, the statement so far and its rows
+    let mut pending: Option<(String, String, usize)> = None;
+    for statement in statements {
+        let Some((head, row)) = single_row_insert(&statement) else {
+            batched.extend(pending.take().map(|(_, insert, _)| insert));
+            batched.push(statement);
+            continue;
+        };
+        rows += 1;
+        if let Some((pending_head, insert, count)) = &mut pending
+            && pending_head == head
+            && *count < BATCH_ROWS
+            && insert.len() + row.len() < BATCH_BYTES
+        {
+            insert.push_str(", ");
+            insert.push_str(row);
+            *count += 1;
+            continue;
+        }
+        let head = head.to_string();
+        batched.extend(pending.take().map(|(_, insert, _)| insert));
+        pending = Some((head, statement, 1));
+    }
+    batched.extend(pending.map(|(_, insert, _)| insert));
+    (batched, rows)
+}
+
+/// Turn a SQL script into the statements `\restore` runs: transaction control
+/// dropped (the restore is one transaction), psql meta-commands skipped and
+/// single-row INSERTs batched
+pub fn plan_restore(script: &str) -> Result<RestorePlan, String> {
+    let mut statements = Vec::new();
+    let mut skipped = 0;
+    for chunk in crate::sql_buffer::split_script(script) {
+        if chunk.starts_with('\\') {
+            skipped += 1;
+            continue;
+        }
+        let mut trigger: Option<String> = None;
+        for statement in crate::sql_buffer::split_statements(&chunk) {
+            if let Some(body) = &mut trigger {
+                body.push_str(";\n");
+                body.push_str(&statement);
+                let word = leading_words(&statement, 1);
+                if word == "end"
+                    || statement
+                        .split_whitespace()
+                        .last()
+                        .is_some_and(|last| last.eq_ignore_ascii_case("end"))
+                {
+                    statements.extend(trigger.take());
+                }
+                continue;
+            }
+            // `-- Data for ...` lines stick to the statement after them
+            let statement = without_leading_comments(&statement).to_string();
+            let lower = statement.to_lowercase();
+            if lower.starts_with("copy ") && lower.contains("from stdin") {
+                return Err(
+                    "COPY ... FROM stdin data is not supported; dump with pg_dump --inserts or \\dump"
+                        .to_string(),
+                );
+            }
+            if is_transaction_control(&statement) {
+                continue;
+            }
+            if opens_trigger_body(&statement) {
+                trigger = Some(statement);
+            } else {
+                statements.push(statement);
+            }
+        }
+        statements.extend(trigger);
+    }
+    let (statements, rows) = batch_inserts(statements);
+    Ok(RestorePlan {
+        statements,
+        rows,
+        skipped,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_restore_batches_inserts() {
+        let script = "\
+PRAGMA foreign_keys=OFF;
+BEGIN TRANSACTION;
+CREATE TABLE t(id INTEGER, name TEXT);
+
+-- Data for t
+
+INSERT INTO t VALUES(1,'a;b');
+INSERT INTO t VALUES(2,'it''s');
+INSERT INTO \"u\" (id) VALUES (3);
+CREATE TRIGGER trg AFTER INSERT ON t BEGIN UPDATE t SET name = 'x'; DELETE FROM t WHERE 0; END;
+INSERT INTO t VALUES(4,NULL) ON CONFLICT DO NOTHING;
+COMMIT;
+\\connect app
+";
+        let plan = plan_restore(script).unwrap();
+        assert_eq!(
+            plan.statements,
+            vec![
+                "PRAGMA foreign_keys=OFF",
+                "CREATE TABLE t(id INTEGER, name TEXT)",
+                "INSERT INTO t VALUES(1,'a;b'), (2,'it''s')",
+                "INSERT INTO \"u\" (id) VALUES (3)",
+                "CREATE TRIGGER trg AFTER INSERT ON t BEGIN UPDATE t SET name = 'x';\n\
+                 DELETE FROM t WHERE 0;\nEND",
+                "INSERT INTO t VALUES(4,NULL) ON CONFLICT DO NOTHING",
+            ]
+        );
+        assert_eq!(plan.rows, 3);
+        assert_eq!(plan.skipped, 1);
+
+        assert!(plan_restore("COPY t (id) FROM stdin;\n1\n\\.\n").is_err());
+        assert_eq!(
+            single_row_insert("insert into \"values\" values ((1 + 2), 'x)')"),
+            Some(("insert into \"values\" values ", "((1 + 2), 'x)')"))
+        );
+        assert_eq!(single_row_insert("INSERT INTO t SELECT * FROM u"), None);
+    }
+
+    #[test]
+    fn test_mysql_foreign_keys_move_after_the_data() {
+        let definition = "CREATE TABLE `orders` (\n  `id` int NOT NULL,\n  `user_id` int DEFAULT NULL,\n  PRIMARY KEY (`id`),\n  CONSTRAINT `fk_user` FOREIGN KEY (`user_id`) REFERENCES `users` (`id`)\n) ENGINE=InnoDB";
+        let (table, foreign_keys) = split_mysql_foreign_keys(definition);
+        assert_eq!(
+            table,
+            "CREATE TABLE `orders` (\n  `id` int NOT NULL,\n  `user_id` int DEFAULT NULL,\n  PRIMARY KEY (`id`)\n) ENGINE=InnoDB"
+        );
+        assert_eq!(
+            foreign_keys,
+            vec!["CONSTRAINT `fk_user` FOREIGN KEY (`user_id`) REFERENCES `users` (`id`)"]
+        );
+        assert_eq!(
+            nextval_sequence("nextval('\"Order''s_id_seq\"'::regclass)").as_deref(),
+            Some("\"Order's_id_seq\"")
+        );
+    }
+
+    #[test]
+    fn test_postgresql_partitions_are_created_from_their_parent() {
+        let lines = vec![
+            "    \"id\" integer".to_string(),
+            "    \"day\" date".to_string(),
+        ];
+        let root = PgPartitioning {
+            parent: None,
+            key: Some("RANGE (day)".to_string()),
+        };
+        assert_eq!(
+            postgresql_create_table("\"public\".\"events\"", &lines, &root),
+            "CREATE TABLE \"public\".\"events\" (\n    \"id\" integer,\n    \"day\" date\n) PARTITION BY RANGE (day);"
+        );
+        let partition = PgPartitioning {
+            parent: Some((
+                "\"public\".\"events\"".to_string(),
+                "FOR VALUES FROM ('2024-01-01') TO ('2025-01-01')".to_string(),
+            )),
+            key: None,
+        };
+        assert_eq!(
+            postgresql_create_table("\"public\".\"events_2024\"", &lines, &partition),
+            "CREATE TABLE \"public\".\"events_2024\" PARTITION OF \"public\".\"events\" \
+             FOR VALUES FROM ('2024-01-01') TO ('2025-01-01');"
+        );
+        assert_eq!(
+            postgresql_create_table("t", &lines, &PgPartitioning::default()),
+            "CREATE TABLE t (\n    \"id\" integer,\n    \"day\" date\n);"
+        );
+    }
+}
@@ -0,0 +1,111 @@
+//! Crate-wide error type for the public Rust API
+//!
+//! `DbcrustError` is what `Database` and the other embeddable entry points
+//! return, so callers can match on the kind of failure instead of parsing
+//! strings. Variants wrapping another error are transparent: their messages
+//! are exactly what the CLI printed before this type existed.
+
+use crate::database::DatabaseError;
+use crate::ssh_tunnel::SSHTunnelError;
+use crate::vault_client::VaultError;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum DbcrustError {
+    /// Establishing or keeping a connection failed
+    #[error("{0}")]
+    Connection(String),
+
+    /// The server rejected a statement or returned something unusable
+    #[error("{0}")]
+    Query(String),
+
+    /// Invalid or unreadable configuration
+    #[error("{0}")]
+    Config(String),
+
+    /// Malformed user input: URLs, arguments, query text
+    #[error("{0}")]
+    Parse(String),
+
+    /// Opening or reopening the SSH tunnel failed
+    #[error(transparent)]
+    Tunnel(#[from] SSHTunnelError),
+
+    /// Vault login, lookup or credential request failed
+    #[error(transparent)]
+    Vault(#[from] VaultError),
+
+    /// Error reported by a database backend
+    #[error(transparent)]
+    Database(#[from] DatabaseError),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    /// The user cancelled an interactive prompt
+    #[error("{0}")]
+    Aborted(#[from] crate::db::ColumnSelectionAborted),
+}
+
+pub type Result<T, E = DbcrustError> = std::result::Result<T, E>;
+
+impl DbcrustError {
+    /// No backend client attached to the `Database` yet
+    pub(crate) fn no_client() -> Self {
+        DbcrustError::Connection("No database client available".to_string())
+    }
+
+    /// A connection URL that could not be parsed is the user's input, not
+    /// a failure to connect
+    pub(crate) fn from_url_error(err: DatabaseError) -> Self {
+        match err {
+            DatabaseError::InvalidUrl(_) => DbcrustError::Parse(err.to_string()),
+            err => DbcrustError::Database(err),
+        }
+    }
+
+    /// Whether the failure is about reaching the server rather than the statement
+    pub fn is_connection(&self) -> bool {
+        match self {
+            DbcrustError::Connection(_) | DbcrustError::Tunnel(_) | DbcrustError::Vault(_) => true,
+            DbcrustError::Database(e) => matches!(
+                e,
+                DatabaseError::ConnectionError(_)
+                    | DatabaseError::UnsupportedScheme(_)
+                    | DatabaseError::InvalidUrl(_)
+                    | DatabaseError::Docker(_)
+            ),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_messages_match_wrapped_errors() {
+        let err: DbcrustError = DatabaseError::QueryError("syntax error".to_string()).into();
+        assert_eq!(err.to_string(), "Query error: syntax error");
+        assert!(!err.is_connection());
+
+        let err: DbcrustError = SSHTunnelError::AuthError("bad key".to_string()).into();
+        assert_eq!(err.to_string(), "SSH authentication error: bad key");
+        assert!(err.is_connection());
+
+        let err: DbcrustError = VaultError::TokenError.into();
+        assert!(err.to_string().starts_with("Vault token not found"));
+        assert!(err.is_connection());
+
+        let err = DbcrustError::from_url_error(DatabaseError::InvalidUrl("no host".to_string()));
+        assert!(matches!(err, DbcrustError::Parse(_)));
+        assert!(!err.is_connection());
+
+        assert_eq!(
+            DbcrustError::no_client().to_string(),
+            "No database client available"
+        );
+    }
+}
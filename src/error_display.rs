@@ -0,0 +1,343 @@
+//! Showing a failed statement's error as a block instead of one line
+//!
+//! Server errors reach the REPL as text, each backend shaping it its own
+//! way. [`parse`] pulls the pieces back out:
+//! - PostgreSQL: `... (SQLSTATE 42P01) at character N`, then psql's
+//!   `DETAIL:` and `HINT:` lines (see `database_postgresql`)
+//! - MySQL and Snowflake: `1146 (42S02): Table 'shop.user' doesn't exist`
+//! - Snowflake's SQL API: `... (SQLSTATE 42S02)`
+//! - SQLite: `(code: 1) no such table: user`
+//! - ClickHouse: `Code: 60. DB::Exception: ... (UNKNOWN_TABLE)`
+//!
+//! [`render`] shows the class of the error (from the SQLSTATE class or the
+//! ClickHouse error name), the code, the message, the detail and hint, the
+//! line the error points at ([`crate::error_position`]) and, for a missing
+//! table, the closest existing table name.
+
+use nu_ansi_term::{Color, Style};
+use regex::Regex;
+use std::sync::LazyLock;
+
+static SQLSTATE_SUFFIX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\s*\(SQLSTATE (\w{5})\)").unwrap());
+static POSITION_SUFFIX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\s+at character \d+\s*$").unwrap());
+static NUMBERED_STATE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\d+) \((\w{5})\):\s*").unwrap());
+static SQLITE_CODE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\(code: (\d+)\)\s*").unwrap());
+static CLICKHOUSE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?s)Code: (\d+)\. DB::Exception: (.*?)(?:\s*\(([A-Z_]+)\))?(?:\s*\(version [^)]*\))?\.?\s*$")
+        .unwrap()
+});
+
+/// How backends word a table that is not there; the name is the first group
+static MISSING_TABLE: LazyLock<Vec<Regex>> = LazyLock::new(|| {
+    [
+        r#"relation "([^"]+)" does not exist"#,
+        r"Table '([^']+)' doesn't exist",
+        r"no such table: (\S+)",
+        r"Table with name (\S+) does not exist",
+        r"Table (\S+) does not exist",
+        r"Object '([^']+)' does not exist",
+        r"table '([^']+)' not found",
+    ]
+    .iter()
+    .map(|pattern| Regex::new(pattern).unwrap())
+    .collect()
+});
+
+/// Text in front of every server message that says nothing
+const NOISE_PREFIXES: &[&str] = &[
+    "Query error: ",
+    "SQLx error: ",
+    "error returned from database: ",
+    "ClickHouse HTTP error: ",
+];
+
+/// A failed statement's error, taken apart
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ErrorReport {
+    /// What kind of error it is, e.g. "undefined table" or "syntax error"
+    pub class: Option<String>,
+    /// Five-character SQLSTATE
+    pub sqlstate: Option<String>,
+    /// Backend error number (MySQL, Snowflake, SQLite, ClickHouse)
+    pub code: Option<String>,
+    pub message: String,
+    pub detail: Option<String>,
+    pub hint: Option<String>,
+}
+
+/// Take the error text of a failed statement apart
+pub fn parse(error: &str) -> ErrorReport {
+    let mut report = ErrorReport::default();
+    let mut message_lines = Vec::new();
+    for line in error.lines() {
+        if let Some(detail) = line.strip_prefix("DETAIL:") {
+            report.detail = Some(detail.trim().to_string());
+        } else if let Some(hint) = line.strip_prefix("HINT:") {
+            report.hint = Some(hint.trim().to_string());
+        } else {
+            message_lines.push(line);
+        }
+    }
+    let mut message = message_lines.join("\n");
+    while let Some(prefix) = NOISE_PREFIXES.iter().find(|p| message.starts_with(*p)) {
+        message.drain(..prefix.len());
+    }
+    message = POSITION_SUFFIX.replace(&message, "").into_owned();
+
+    if let Some(caps) = SQLSTATE_SUFFIX.captures(&message) {
+        report.sqlstate = Some(caps[1].to_string());
+        message = SQLSTATE_SUFFIX.replace(&message, "").into_owned();
+    }
+    if let Some(caps) = NUMBERED_STATE.captures(&message) {
+        report.code = Some(caps[1].to_string());
+        report.sqlstate = Some(caps[2].to_string());
+        message = message[caps[0].len()..].to_string();
+    } else if let Some(caps) = SQLITE_CODE.captures(&message) {
+        report.code = Some(caps[1].to_string());
+        message = message[caps[0].len()..].to_string();
+    } else if let Some(caps) = CLICKHOUSE.captures(&message) {
+        report.code = Some(caps[1].to_string());
+        report.class = caps
+            .get(3)
+            .map(|name| name.as_str().to_lowercase().replace('_', " "));
+        message = caps[2].trim().to_string();
+    }
+
+    if report.class.is_none() {
+        report.class = report.sqlstate.as_deref().and_then(sqlstate_class);
+    }
+    report.message = message.trim().to_string();
+    report
+}
+
+/// Name of a SQLSTATE: the exact condition for the common ones, its class
+/// otherwise
+fn sqlstate_class(state: &str) -> Option<String> {
+    let exact = match state {
+        "42P01" | "42S02" => Some("undefined table"),
+        "42703" | "42S22" => Some("undefined column"),
+        "42883" => Some("undefined function"),
+        "42601" | "42000" => Some("syntax error"),
+        "42501" => Some("insufficient privilege"),
+        "23505" => Some("unique violation"),
+        "23503" => Some("foreign key violation"),
+        "23502" => Some("not null violation"),
+        "23514" => Some("check violation"),
+        "40001" => Some("serialization failure"),
+        "40P01" => Some("deadlock detected"),
+        "57014" => Some("query canceled"),
+        _ => None,
+    };
+    if let Some(exact) = exact {
+        return Some(exact.to_string());
+    }
+    let class = match state.get(..2)? {
+        "0A" => "feature not supported",
+        "08" => "connection exception",
+        "21" => "cardinality violation",
+        "22" => "data exception",
+        "23" => "integrity constraint violation",
+        "25" => "invalid transaction state",
+        "28" => "invalid authorization",
+        "3D" => "invalid catalog name",
+        "3F" => "invalid schema name",
+        "40" => "transaction rollback",
+        "42" => "syntax error or access rule violation",
+        "53" => "insufficient resources",
+        "54" => "program limit exceeded",
+        "55" => "object not in prerequisite state",
+        "57" => "operator intervention",
+        "58" | "XX" => "internal error",
+        _ => return None,
+    };
+    Some(class.to_string())
+}
+
+/// Unqualified, unquoted name of the table `message` says does not exist
+pub fn missing_table(message: &str) -> Option<String> {
+    let name = MISSING_TABLE
+        .iter()
+        .find_map(|pattern| pattern.captures(message))?
+        .get(1)?
+        .as_str();
+    let name = name.rsplit('.').next().unwrap_or(name);
+    let name = name.trim_matches(|c| matches!(c, '"' | '`' | '\'' | '[' | ']'));
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// The table of `tables` closest to `name` within a few typos; tables are
+/// compared on their unqualified name, case-insensitively, and one matching
+/// but for case is the answer (an identifier folded to lower case)
+pub fn closest_table<'a>(name: &str, tables: &'a [String]) -> Option<&'a str> {
+    let wanted = name.to_lowercase();
+    let allowed = (wanted.chars().count() / 3).max(1);
+    tables
+        .iter()
+        .filter(|table| table.as_str() != name)
+        .map(|table| {
+            let short = table.rsplit('.').next().unwrap_or(table).to_lowercase();
+            (crate::config_editor::levenshtein(&wanted, &short), table)
+        })
+        .filter(|(distance, _)| *distance <= allowed)
+        .min_by_key(|(distance, table)| (*distance, table.len()))
+        .map(|(_, table)| table.as_str())
+}
+
+/// The table meant by a statement failing on a missing table: looked up
+/// in the completion cache, or listed from the server when that is empty
+pub async fn suggest_table(db: &mut crate::db::Database, message: &str) -> Option<String> {
+    let name = missing_table(message)?;
+    let mut tables: Vec<String> = db
+        .schema_cache_mut()
+        .snapshot()
+        .tables
+        .values()
+        .flatten()
+        .cloned()
+        .collect();
+    if tables.is_empty() {
+        tables = db.get_tables_and_views(None).await.ok()?;
+    }
+    closest_table(&name, &tables).map(str::to_string)
+}
+
+/// The error block the REPL prints: message, class and code, detail, hint,
+/// `excerpt` (the line the error points at) and `suggestion`
+pub fn render(report: &ErrorReport, excerpt: Option<&str>, suggestion: Option<&str>) -> String {
+    let bar = Color::Red.paint("│");
+    let label = |name: &str| Style::new().bold().paint(format!("{name:<7}"));
+    let mut message_lines = report.message.lines();
+    let mut out = Color::Red
+        .bold()
+        .paint(message_lines.next().unwrap_or("unknown error"))
+        .to_string();
+    for line in message_lines {
+        out.push_str(&format!("\n{bar} {line}"));
+    }
+
+    let code = match (&report.sqlstate, &report.code) {
+        (Some(state), Some(code)) => Some(format!("{code} / SQLSTATE {state}")),
+        (Some(state), None) => Some(format!("SQLSTATE {state}")),
+        (None, Some(code)) => Some(format!("code {code}")),
+        (None, None) => None,
+    };
+    match (&report.class, code) {
+        (Some(class), Some(code)) => out.push_str(&format!(
+            "\n{bar} {} {class} {}",
+            label("Class"),
+            Color::DarkGray.paint(format!("({code})"))
+        )),
+        (Some(class), None) => out.push_str(&format!("\n{bar} {} {class}", label("Class"))),
+        (None, Some(code)) => out.push_str(&format!("\n{bar} {} {code}", label("Code"))),
+        (None, None) => {}
+    }
+    if let Some(detail) = &report.detail {
+        out.push_str(&format!("\n{bar} {} {detail}", label("Detail")));
+    }
+    if let Some(hint) = &report.hint {
+        out.push_str(&format!(
+            "\n{bar} {} {}",
+            label("Hint"),
+            Color::Yellow.paint(hint)
+        ));
+    }
+    if let Some(excerpt) = excerpt {
+        for line in excerpt.lines() {
+            out.push_str(&format!("\n{bar} {line}"));
+        }
+    }
+    if let Some(suggestion) = suggestion {
+        out.push_str(&format!(
+            "\n{bar} {}",
+            Color::Cyan.paint(format!("Did you mean table `{suggestion}`?"))
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_backend_shapes() {
+        let pg = parse(
+            "Query error: error returned from database: relation \"user\" does not exist (SQLSTATE 42P01) at character 15\nHINT:  Perhaps you meant \"users\".",
+        );
+        assert_eq!(pg.message, "relation \"user\" does not exist");
+        assert_eq!(pg.sqlstate.as_deref(), Some("42P01"));
+        assert_eq!(pg.class.as_deref(), Some("undefined table"));
+        assert_eq!(pg.hint.as_deref(), Some("Perhaps you meant \"users\"."));
+
+        let mysql = parse(
+            "Query error: error returned from database: 1146 (42S02): Table 'shop.user' doesn't exist",
+        );
+        assert_eq!(mysql.code.as_deref(), Some("1146"));
+        assert_eq!(mysql.sqlstate.as_deref(), Some("42S02"));
+        assert_eq!(mysql.message, "Table 'shop.user' doesn't exist");
+
+        let sqlite = parse("error returned from database: (code: 1) no such table: user");
+        assert_eq!(sqlite.code.as_deref(), Some("1"));
+        assert_eq!(sqlite.class, None);
+        assert_eq!(sqlite.message, "no such table: user");
+
+        let clickhouse = parse(
+            "ClickHouse HTTP error: Code: 60. DB::Exception: Table default.user does not exist. (UNKNOWN_TABLE) (version 24.3.1.1)",
+        );
+        assert_eq!(clickhouse.code.as_deref(), Some("60"));
+        assert_eq!(clickhouse.class.as_deref(), Some("unknown table"));
+        assert_eq!(clickhouse.message, "Table default.user does not exist.");
+
+        let deadlock = parse("deadlock detected\nDETAIL:  Process 1 waits for ShareLock");
+        assert_eq!(
+            deadlock.detail.as_deref(),
+            Some("Process 1 waits for ShareLock")
+        );
+        assert_eq!(sqlstate_class("22012").as_deref(), Some("data exception"));
+    }
+
+    #[test]
+    fn test_missing_table_suggestion() {
+        assert_eq!(
+            missing_table("Table 'shop.user' doesn't exist").as_deref(),
+            Some("user")
+        );
+        assert_eq!(
+            missing_table("relation \"public.orderz\" does not exist").as_deref(),
+            Some("orderz")
+        );
+        assert_eq!(missing_table("column \"x\" does not exist"), None);
+
+        let tables = vec![
+            "public.users".to_string(),
+            "orders".to_string(),
+            "Invoices".to_string(),
+        ];
+        assert_eq!(closest_table("user", &tables), Some("public.users"));
+        assert_eq!(closest_table("ordrs", &tables), Some("orders"));
+        assert_eq!(closest_table("invoices", &tables), Some("Invoices"));
+        assert_eq!(closest_table("payments", &tables), None);
+    }
+
+    #[test]
+    fn test_render_block() {
+        let report = parse("1146 (42S02): Table 'shop.user' doesn't exist");
+        let block = render(&report, Some("LINE 1: SELECT * FROM user"), Some("users"));
+        let plain: Vec<String> = block
+            .lines()
+            .map(|line| crate::pager::strip_ansi(line).into_owned())
+            .collect();
+        assert_eq!(
+            plain,
+            [
+                "Table 'shop.user' doesn't exist",
+                "│ Class   undefined table (1146 / SQLSTATE 42S02)",
+                "│ LINE 1: SELECT * FROM user",
+                "│ Did you mean table `users`?",
+            ]
+        );
+    }
+}
@@ -0,0 +1,158 @@
+//! Pointing at where a failed statement went wrong
+//!
+//! Server errors carry the position in different shapes:
+//! - PostgreSQL: a 1-based character offset, which the client appends to
+//!   the first line of the message as `at character N`
+//! - MySQL: `... near 'FORM users' at line 2`
+//! - SQLite: `near "FORM": syntax error`, found as the first occurrence of
+//!   the token in the statement
+//! - Snowflake: `syntax error line 2 at position 0 unexpected 'FORM'`
+//!
+//! The REPL echoes the offending line psql style, with a red caret under
+//! the position.
+
+use nu_ansi_term::Color;
+use regex::Regex;
+use std::sync::LazyLock;
+
+static PG_CHARACTER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)at character (\d+)\s*$").unwrap());
+static MYSQL_NEAR: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)near '(.*)' at line (\d+)").unwrap());
+static SQLITE_NEAR: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"near "([^"]+)": syntax error"#).unwrap());
+static SNOWFLAKE_POSITION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"line (\d+) at position (\d+)").unwrap());
+
+/// Where an error points: 1-based line, 0-based character column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Find the position `message` reports in `sql`
+pub fn locate(message: &str, sql: &str) -> Option<Location> {
+    if let Some(caps) = PG_CHARACTER.captures(message) {
+        let offset: usize = caps[1].parse().ok()?;
+        return from_offset(sql, offset.checked_sub(1)?);
+    }
+    if let Some(caps) = MYSQL_NEAR.captures(message) {
+        let line: usize = caps[2].parse().ok()?;
+        let text = sql.lines().nth(line.checked_sub(1)?)?;
+        // MySQL quotes what follows the error, cut to 80 characters and to
+        // the end of the line here
+        let near = caps[1].lines().next().unwrap_or_default();
+        let column = if near.is_empty() {
+            text.chars().count()
+        } else {
+            text[..text.find(near)?].chars().count()
+        };
+        return Some(Location { line, column });
+    }
+    if let Some(caps) = SNOWFLAKE_POSITION.captures(message) {
+        let line: usize = caps[1].parse().ok()?;
+        let column: usize = caps[2].parse().ok()?;
+        let text = sql.lines().nth(line.checked_sub(1)?)?;
+        return (column <= text.chars().count()).then_some(Location { line, column });
+    }
+    if let Some(caps) = SQLITE_NEAR.captures(message) {
+        let token = &caps[1];
+        let offset = sql.find(token)?;
+        return from_offset(sql, sql[..offset].chars().count());
+    }
+    None
+}
+
+/// Location of the 0-based character `offset` of `sql`
+fn from_offset(sql: &str, offset: usize) -> Option<Location> {
+    let mut line = 1;
+    let mut column = 0;
+    for (index, ch) in sql.chars().enumerate() {
+        if index == offset {
+            return Some(Location { line, column });
+        }
+        if ch == '\n' {
+            line += 1;
+            column = 0;
+        } else {
+            column += 1;
+        }
+    }
+    None
+}
+
+/// The line of `sql` the error points at with a caret under the position,
+/// e.g. `LINE 2: FORM users` / `        ^`; `None` when the message has no
+/// position this module understands
+pub fn excerpt(message: &str, sql: &str) -> Option<String> {
+    let location = locate(message, sql)?;
+    let text = sql.lines().nth(location.line - 1)?.replace('\t', " ");
+    let prefix = format!("LINE {}: ", location.line);
+    let pad = " ".repeat(prefix.chars().count() + location.column);
+    Some(format!(
+        "{prefix}{text}\n{pad}{}",
+        Color::Red.bold().paint("^")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locate_formats() {
+        let sql = "SELECT id\nFORM users";
+        assert_eq!(
+            locate(
+                "error returned from database: syntax error at or near \"FORM\" at character 11",
+                sql
+            ),
+            Some(Location { line: 2, column: 0 })
+        );
+        assert_eq!(
+            locate(
+                "error returned from database: 1064 (42000): You have an error in your SQL syntax; check the manual that corresponds to your MySQL server version for the right syntax to use near 'FORM users' at line 2",
+                sql
+            ),
+            Some(Location { line: 2, column: 0 })
+        );
+        assert_eq!(
+            locate(
+                "error returned from database: (code: 1) near \"users\": syntax error",
+                sql
+            ),
+            Some(Location { line: 2, column: 5 })
+        );
+        assert_eq!(
+            locate(
+                "SQL compilation error:\nsyntax error line 2 at position 0 unexpected 'FORM'.",
+                sql
+            ),
+            Some(Location { line: 2, column: 0 })
+        );
+        assert_eq!(
+            locate(
+                "syntax error at or near \"FORM\" (SQLSTATE 42601) at character 11\nHINT:  check the FROM clause",
+                sql
+            ),
+            Some(Location { line: 2, column: 0 })
+        );
+        assert_eq!(locate("relation \"users\" does not exist", sql), None);
+        // A position past the statement (in an appended LIMIT) is ignored
+        assert_eq!(locate("syntax error at character 99", sql), None);
+    }
+
+    #[test]
+    fn test_excerpt_points_at_column() {
+        let excerpt = excerpt(
+            "syntax error at or near \"FORM\" at character 8",
+            "SELECT FORM users",
+        )
+        .unwrap();
+        let lines: Vec<&str> = excerpt.lines().collect();
+        assert_eq!(lines[0], "LINE 1: SELECT FORM users");
+        assert!(lines[1].starts_with(&" ".repeat(15)));
+        assert!(!lines[1].starts_with(&" ".repeat(16)));
+    }
+}
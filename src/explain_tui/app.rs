@@ -1,6 +1,7 @@
 //! Application state and event handling for the TUI explain visualizer
 
 use super::plan_tree::{PlanNode, PlanStatistics};
+use crate::performance_analyzer::IndexSuggestion;
 use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
 use std::time::Duration;
 
@@ -23,6 +24,18 @@ pub struct ExplainTuiApp {
     pub details_scroll: u16,
     /// Current focus: 0 = tree, 1 = details
     pub focus: u8,
+    /// Suggested indexes for the plan, shown in a bottom panel
+    pub index_suggestions: Vec<IndexSuggestion>,
+    /// Whether `r` can re-run the query with EXPLAIN ANALYZE
+    pub can_refresh: bool,
+    /// Set by `r`; the event loop re-runs the query and clears it
+    pub refresh_requested: bool,
+    /// Plans captured so far, 1 until the query is re-run
+    pub run_count: usize,
+    /// Plan of the previous run, which deltas are measured against
+    pub previous_root: Option<PlanNode>,
+    /// Outcome of the last re-run, shown in the footer
+    pub status: Option<String>,
 }
 
 impl ExplainTuiApp {
@@ -46,9 +59,74 @@ impl ExplainTuiApp {
             should_quit: false,
             details_scroll: 0,
             focus: 0,
+            index_suggestions: Vec::new(),
+            can_refresh: false,
+            refresh_requested: false,
+            run_count: 1,
+            previous_root: None,
+            status: None,
         }
     }
 
+    /// Show the plan of a re-run, keeping the previous one for deltas. Nodes
+    /// are matched by position in the tree, so expanded nodes and the
+    /// selection stay where they were as long as the plan keeps its shape.
+    pub fn replace_plan(&mut self, plan_root: PlanNode) {
+        let mut expanded_paths = Vec::new();
+        Self::collect_expanded(
+            &self.plan_root,
+            &self.expanded_nodes,
+            &mut vec![],
+            &mut expanded_paths,
+        );
+
+        self.expanded_nodes = expanded_paths
+            .iter()
+            .filter_map(|path| node_at(&plan_root, path))
+            .map(|node| node.id.clone())
+            .collect();
+        self.expanded_nodes.insert(plan_root.id.clone());
+        if node_at(&plan_root, &self.selected_path).is_none() {
+            self.selected_path.clear();
+        }
+
+        self.statistics = PlanStatistics::from_plan(&plan_root);
+        self.previous_root = Some(std::mem::replace(&mut self.plan_root, plan_root));
+        self.run_count += 1;
+        self.details_scroll = 0;
+    }
+
+    fn collect_expanded(
+        node: &PlanNode,
+        expanded: &std::collections::HashSet<String>,
+        path: &mut Vec<usize>,
+        paths: &mut Vec<Vec<usize>>,
+    ) {
+        if expanded.contains(&node.id) {
+            paths.push(path.clone());
+        }
+        for (i, child) in node.children.iter().enumerate() {
+            path.push(i);
+            Self::collect_expanded(child, expanded, path, paths);
+            path.pop();
+        }
+    }
+
+    /// The node at `path` in the previous run, when it is the same operation
+    pub fn previous_node(&self, path: &[usize]) -> Option<&PlanNode> {
+        let previous = node_at(self.previous_root.as_ref()?, path)?;
+        let current = node_at(&self.plan_root, path)?;
+        (previous.node_type == current.node_type && previous.relation_name == current.relation_name)
+            .then_some(previous)
+    }
+
+    /// Change of the actual time of the node at `path` since the previous
+    /// run, in milliseconds, when both runs measured it
+    pub fn time_delta(&self, path: &[usize]) -> Option<f64> {
+        let current = node_at(&self.plan_root, path)?.actual_time_ms?;
+        Some(current - self.previous_node(path)?.actual_time_ms?)
+    }
+
     /// Expand nodes up to a certain depth for initial view
     fn expand_initial_nodes(
         node: &PlanNode,
@@ -310,6 +388,15 @@ impl ExplainTuiApp {
                 self.toggle_help();
             }
 
+            // Re-run with EXPLAIN ANALYZE
+            KeyCode::Char('r') => {
+                if self.can_refresh {
+                    self.refresh_requested = true;
+                } else {
+                    self.status = Some("Re-running is not available for this plan".to_string());
+                }
+            }
+
             // Details scroll (with Ctrl modifier)
             KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 self.scroll_details(5);
@@ -348,6 +435,12 @@ impl ExplainTuiApp {
     }
 }
 
+/// Node reached by following `path` (child indices) from `root`
+fn node_at<'a>(root: &'a PlanNode, path: &[usize]) -> Option<&'a PlanNode> {
+    path.iter()
+        .try_fold(root, |node, &index| node.children.get(index))
+}
+
 /// Result of running the TUI
 #[derive(Debug)]
 pub enum TuiResult {
@@ -356,3 +449,40 @@ pub enum TuiResult {
     /// An error occurred
     Error(String),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(times: [f64; 2]) -> PlanNode {
+        let mut root = PlanNode::new(format!("root-{}", times[0]), "Hash Join".to_string());
+        root.actual_time_ms = Some(times[0]);
+        let mut scan = PlanNode::new(format!("scan-{}", times[1]), "Seq Scan".to_string());
+        scan.relation_name = Some("users".to_string());
+        scan.actual_time_ms = Some(times[1]);
+        root.children.push(scan);
+        root
+    }
+
+    #[test]
+    fn test_replace_plan_keeps_state_and_measures_deltas() {
+        let mut app = ExplainTuiApp::new(plan([10.0, 8.0]));
+        app.select_next();
+        assert_eq!(app.selected_path, vec![0]);
+        assert_eq!(app.time_delta(&[0]), None);
+
+        app.replace_plan(plan([4.0, 3.0]));
+        assert_eq!(app.run_count, 2);
+        assert_eq!(app.selected_path, vec![0]);
+        assert!(app.is_expanded("root-4"));
+        assert_eq!(app.time_delta(&[]), Some(-6.0));
+        assert_eq!(app.time_delta(&[0]), Some(-5.0));
+
+        // A different operation at the same place has no delta
+        let mut changed = plan([5.0, 1.0]);
+        changed.children[0].node_type = "Index Scan".to_string();
+        app.replace_plan(changed);
+        assert_eq!(app.time_delta(&[0]), None);
+        assert_eq!(app.time_delta(&[]), Some(1.0));
+    }
+}
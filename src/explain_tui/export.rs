@@ -0,0 +1,370 @@
+//! Exporting captured plans to other visualizers (`\ev export`, `\ev upload`)
+//!
+//! - Folded stacks (`.folded`): one line per plan node with its own time in
+//!   microseconds (its own cost without ANALYZE), for `flamegraph.pl`,
+//!   inferno or speedscope
+//! - Graphviz DOT (`.dot`): the plan tree, colored by performance level
+//! - JSON (`.json`): the EXPLAIN (FORMAT JSON) output, which
+//!   explain.dalibo.com and explain.depesz.com accept as is
+//!
+//! Uploading publishes the plan and its query on one of those sites, so
+//! the command asks first.
+
+use super::plan_tree::PlanNode;
+use crate::performance_analyzer::PerformanceLevel;
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// File formats `\ev export` writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlanExportFormat {
+    Folded,
+    Dot,
+    Json,
+}
+
+impl PlanExportFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "folded" | "flamegraph" | "stacks" => Some(Self::Folded),
+            "dot" | "gv" | "graphviz" => Some(Self::Dot),
+            "json" | "dalibo" | "depesz" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    /// Format implied by the file extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "folded" | "stacks" | "txt" => Some(Self::Folded),
+            "dot" | "gv" => Some(Self::Dot),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Folded => "folded stacks",
+            Self::Dot => "Graphviz DOT",
+            Self::Json => "EXPLAIN JSON",
+        }
+    }
+}
+
+/// The plan in `format`; `plan_json` is the captured EXPLAIN output
+pub fn render_plan(format: PlanExportFormat, plan_json: &str) -> Result<String, String> {
+    let json: serde_json::Value = serde_json::from_str(plan_json)
+        .map_err(|e| format!("The captured plan is not valid JSON: {e}"))?;
+    let root = || {
+        super::parse_postgresql_plan(&json)
+            .or_else(|| super::parse_mongodb_plan(&json))
+            .ok_or("The captured plan is not a PostgreSQL or MongoDB plan".to_string())
+    };
+    match format {
+        PlanExportFormat::Folded => Ok(folded_stacks(&root()?)),
+        PlanExportFormat::Dot => Ok(graphviz_dot(&root()?)),
+        PlanExportFormat::Json => serde_json::to_string_pretty(&json).map_err(|e| e.to_string()),
+    }
+}
+
+/// Time spent in `node` and its children across all loops, in milliseconds
+fn inclusive_time(node: &PlanNode) -> Option<f64> {
+    Some(node.actual_time_ms? * node.actual_loops.unwrap_or(1) as f64)
+}
+
+/// What `node` adds on its own: microseconds with ANALYZE, cost units
+/// without. Parallel workers can make children add up to more than their
+/// parent, which leaves the parent nothing.
+fn self_weight(node: &PlanNode, analyzed: bool) -> u64 {
+    let children = node.children.iter().filter(|c| !c.is_rejected_plan());
+    let own = if analyzed {
+        let total = inclusive_time(node).unwrap_or(0.0);
+        (total - children.filter_map(inclusive_time).sum::<f64>()) * 1000.0
+    } else {
+        node.total_cost - children.map(|c| c.total_cost).sum::<f64>()
+    };
+    own.max(0.0).round() as u64
+}
+
+/// Flamegraph frames separate with `;` and end at the last space
+fn frame(node: &PlanNode) -> String {
+    node.display_label()
+        .replace(';', ",")
+        .replace(' ', "\u{a0}")
+}
+
+/// One `frame;frame;frame weight` line per plan node that adds time (or
+/// cost) of its own; plans the optimizer rejected are left out
+pub fn folded_stacks(root: &PlanNode) -> String {
+    fn walk(node: &PlanNode, stack: &mut Vec<String>, analyzed: bool, out: &mut String) {
+        stack.push(frame(node));
+        let weight = self_weight(node, analyzed);
+        if weight > 0 {
+            let _ = writeln!(out, "{} {weight}", stack.join(";"));
+        }
+        for child in node.children.iter().filter(|c| !c.is_rejected_plan()) {
+            walk(child, stack, analyzed, out);
+        }
+        stack.pop();
+    }
+    let mut out = String::new();
+    walk(
+        root,
+        &mut Vec::new(),
+        root.actual_time_ms.is_some(),
+        &mut out,
+    );
+    out
+}
+
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// The plan tree as a Graphviz digraph, root at the top
+pub fn graphviz_dot(root: &PlanNode) -> String {
+    /// Writes `node` as `n<id>` and returns the next free id
+    fn walk(node: &PlanNode, id: usize, out: &mut String) -> usize {
+        let mut label = vec![
+            node.display_label(),
+            node.cost_summary(),
+            node.rows_summary(),
+        ];
+        if let Some(condition) = node
+            .index_cond
+            .as_ref()
+            .or(node.hash_cond.as_ref())
+            .or(node.merge_cond.as_ref())
+            .or(node.filter.as_ref())
+        {
+            label.push(condition.chars().take(60).collect());
+        }
+        let label = label
+            .iter()
+            .map(|line| dot_escape(line))
+            .collect::<Vec<_>>()
+            .join("\\n");
+        let level = if node.is_rejected_plan() {
+            PerformanceLevel::Good
+        } else {
+            node.performance_level
+        };
+        let _ = writeln!(
+            out,
+            "  n{id} [label=\"{label}\", fillcolor=\"{}\"{}];",
+            level.dot_color(),
+            if node.is_rejected_plan() {
+                ", style=\"rounded,filled,dashed\""
+            } else {
+                ""
+            }
+        );
+        let mut next = id + 1;
+        for child in &node.children {
+            let _ = write!(out, "  n{id} -> n{next}");
+            match &child.parent_relationship {
+                Some(relationship) => {
+                    let _ = writeln!(out, " [label=\"{}\"];", dot_escape(relationship));
+                }
+                None => out.push_str(";\n"),
+            }
+            next = walk(child, next, out);
+        }
+        next
+    }
+    let mut out = String::from(
+        "digraph plan {\n  node [shape=box, style=\"rounded,filled\", fontname=\"Helvetica\"];\n  edge [dir=back, fontsize=10];\n",
+    );
+    walk(root, 0, &mut out);
+    out.push_str("}\n");
+    out
+}
+
+/// Sites that host shareable plan visualizations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlanService {
+    Dalibo,
+    Depesz,
+}
+
+impl PlanService {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "" | "dalibo" | "pev2" | "explain.dalibo.com" => Some(Self::Dalibo),
+            "depesz" | "explain.depesz.com" => Some(Self::Depesz),
+            _ => None,
+        }
+    }
+
+    pub fn host(&self) -> &'static str {
+        match self {
+            Self::Dalibo => "explain.dalibo.com",
+            Self::Depesz => "explain.depesz.com",
+        }
+    }
+}
+
+/// Publish the plan (and its query, when known) and return the link to it
+pub async fn upload_plan(
+    service: PlanService,
+    plan_json: &str,
+    query: Option<&str>,
+) -> Result<String, String> {
+    let client = crate::network::client_builder()
+        .timeout(std::time::Duration::from_secs(30))
+        // depesz answers with a redirect to the new plan
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .map_err(|e| e.to_string())?;
+    let title = format!("dbcrust {}", chrono::Local::now().format("%Y-%m-%d %H:%M"));
+    let query = query.unwrap_or_default();
+    let request = match service {
+        PlanService::Dalibo => client.post("https://explain.dalibo.com/new.json").form(&[
+            ("title", title.as_str()),
+            ("plan", plan_json),
+            ("query", query),
+        ]),
+        PlanService::Depesz => client.post("https://explain.depesz.com/").form(&[
+            ("title", title.as_str()),
+            ("plan", plan_json),
+            ("query", query),
+            ("is_public", "0"),
+            ("is_anon", "0"),
+        ]),
+    };
+    let response = request
+        .header(
+            reqwest::header::USER_AGENT,
+            concat!("dbcrust/", env!("CARGO_PKG_VERSION")),
+        )
+        .send()
+        .await
+        .map_err(|e| format!("Uploading to {} failed: {e}", service.host()))?;
+    let status = response.status();
+    match service {
+        PlanService::Dalibo => {
+            if !status.is_success() {
+                return Err(format!("{} returned HTTP {status}", service.host()));
+            }
+            #[derive(serde::Deserialize)]
+            struct Created {
+                id: String,
+            }
+            let created: Created = response
+                .json()
+                .await
+                .map_err(|e| format!("Unexpected answer from {}: {e}", service.host()))?;
+            Ok(format!("https://explain.dalibo.com/plan/{}", created.id))
+        }
+        PlanService::Depesz => {
+            let location = response
+                .headers()
+                .get(reqwest::header::LOCATION)
+                .and_then(|value| value.to_str().ok())
+                .filter(|_| status.is_redirection())
+                .ok_or_else(|| format!("{} returned HTTP {status}", service.host()))?;
+            Ok(if location.starts_with('/') {
+                format!("https://explain.depesz.com{location}")
+            } else {
+                location.to_string()
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn analyzed_plan() -> String {
+        serde_json::json!([{
+            "Plan": {
+                "Node Type": "Hash Join",
+                "Total Cost": 500.0,
+                "Plan Rows": 100,
+                "Actual Rows": 90,
+                "Actual Total Time": 12.5,
+                "Actual Loops": 1,
+                "Hash Cond": "(o.user_id = u.id)",
+                "Plans": [
+                    {
+                        "Node Type": "Seq Scan",
+                        "Relation Name": "orders",
+                        "Parent Relationship": "Outer",
+                        "Total Cost": 300.0,
+                        "Plan Rows": 1000,
+                        "Actual Rows": 1000,
+                        "Actual Total Time": 4.0,
+                        "Actual Loops": 1
+                    },
+                    {
+                        "Node Type": "Index Scan",
+                        "Relation Name": "users",
+                        "Index Name": "users_pkey",
+                        "Parent Relationship": "Inner",
+                        "Total Cost": 0.5,
+                        "Plan Rows": 1,
+                        "Actual Rows": 1,
+                        "Actual Total Time": 0.002,
+                        "Actual Loops": 1000
+                    }
+                ]
+            }
+        }])
+        .to_string()
+    }
+
+    #[test]
+    fn test_folded_stacks_use_self_time() {
+        let folded = render_plan(PlanExportFormat::Folded, &analyzed_plan()).unwrap();
+        let lines: Vec<&str> = folded.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "Hash\u{a0}Join 6500",
+                "Hash\u{a0}Join;Seq\u{a0}Scan\u{a0}on\u{a0}orders 4000",
+                "Hash\u{a0}Join;Index\u{a0}Scan\u{a0}on\u{a0}users\u{a0}using\u{a0}users_pkey 2000",
+            ]
+        );
+
+        // Without ANALYZE the weights are costs
+        let estimated = serde_json::json!([{
+            "Plan": {
+                "Node Type": "Sort",
+                "Total Cost": 120.4,
+                "Plans": [{"Node Type": "Seq Scan", "Relation Name": "t", "Total Cost": 100.0}]
+            }
+        }]);
+        let folded = render_plan(PlanExportFormat::Folded, &estimated.to_string()).unwrap();
+        assert_eq!(folded, "Sort 20\nSort;Seq\u{a0}Scan\u{a0}on\u{a0}t 100\n");
+    }
+
+    #[test]
+    fn test_graphviz_dot_and_formats() {
+        let dot = render_plan(PlanExportFormat::Dot, &analyzed_plan()).unwrap();
+        assert!(dot.starts_with("digraph plan {"));
+        assert!(dot.contains("n0 -> n1 [label=\"Outer\"];"));
+        assert!(dot.contains("n0 -> n2 [label=\"Inner\"];"));
+        assert!(dot.contains("label=\"Hash Join\\n12.50ms\\n"));
+        assert!(dot.contains("(o.user_id = u.id)"));
+        assert!(dot.trim_end().ends_with('}'));
+
+        assert_eq!(
+            PlanExportFormat::from_path(Path::new("/tmp/plan.dot")),
+            Some(PlanExportFormat::Dot)
+        );
+        assert_eq!(
+            PlanExportFormat::from_path(Path::new("plan.folded")),
+            Some(PlanExportFormat::Folded)
+        );
+        assert_eq!(PlanExportFormat::from_path(Path::new("plan")), None);
+        assert_eq!(
+            PlanExportFormat::from_name("depesz"),
+            Some(PlanExportFormat::Json)
+        );
+        assert!(render_plan(PlanExportFormat::Json, "{not json").is_err());
+        assert_eq!(PlanService::from_name(""), Some(PlanService::Dalibo));
+        assert_eq!(PlanService::from_name("depesz"), Some(PlanService::Depesz));
+    }
+}
@@ -1,12 +1,17 @@
 //! TUI-based Query Plan Visualizer
 //!
 //! This module provides an interactive terminal user interface for visualizing
-//! PostgreSQL EXPLAIN plans. It uses ratatui for rendering and provides:
+//! PostgreSQL and MongoDB EXPLAIN plans. It uses ratatui for rendering and provides:
 //!
 //! - Hierarchical tree view of the query plan
 //! - Color-coded performance indicators
 //! - Detailed node information panel
 //! - Keyboard navigation and interactive exploration
+//! - Re-running the query with EXPLAIN ANALYZE (`r`), with time deltas
+//!   against the previous run
+//! - Exporting the captured plan as folded stacks, Graphviz DOT or JSON, and
+//!   sharing it on explain.dalibo.com or explain.depesz.com (`\ev export`,
+//!   `\ev upload`)
 //!
 //! # Usage
 //!
@@ -23,10 +28,14 @@
 //! ```
 
 mod app;
+mod export;
+mod mongodb_plan;
 mod plan_tree;
 mod ui;
 
 pub use app::{ExplainTuiApp, TuiResult};
+pub use export::{PlanExportFormat, PlanService, render_plan, upload_plan};
+pub use mongodb_plan::{format_mongodb_plan_text, parse_mongodb_plan};
 pub use plan_tree::{PlanNode, PlanStatistics, parse_postgresql_plan};
 
 use crossterm::{
@@ -40,14 +49,18 @@ use std::io::{self, stdout};
 use std::panic;
 use std::time::Duration;
 
-/// Run the TUI explain visualizer for a PostgreSQL plan
+/// Run the TUI explain visualizer for a PostgreSQL or MongoDB plan
 ///
-/// This function takes a PostgreSQL EXPLAIN (FORMAT JSON) output and displays
-/// it in an interactive TUI. The function blocks until the user exits the TUI.
+/// This function takes a PostgreSQL EXPLAIN (FORMAT JSON) output, or a MongoDB
+/// `explain` document, and displays it in an interactive TUI. The function
+/// blocks until the user exits the TUI.
 ///
 /// # Arguments
 ///
 /// * `plan_json` - The JSON output from PostgreSQL's EXPLAIN (FORMAT JSON) command
+///   or MongoDB's `explain` command
+/// * `refresh` - Re-runs the query with EXPLAIN ANALYZE and returns its plan
+///   when `r` is pressed; `None` disables the key
 ///
 /// # Returns
 ///
@@ -66,16 +79,25 @@ use std::time::Duration;
 ///     }
 /// }]);
 ///
-/// run_explain_tui(&plan_json)?;
+/// run_explain_tui(&plan_json, None)?;
 /// ```
-pub fn run_explain_tui(plan_json: &JsonValue) -> Result<TuiResult, String> {
+pub fn run_explain_tui(
+    plan_json: &JsonValue,
+    refresh: Option<&mut dyn FnMut() -> Result<JsonValue, String>>,
+) -> Result<TuiResult, String> {
     // Parse the plan JSON into our tree structure
-    let plan_root = parse_postgresql_plan(plan_json).ok_or_else(|| {
-        "Failed to parse plan JSON. Expected PostgreSQL EXPLAIN (FORMAT JSON) output.".to_string()
-    })?;
+    let plan_root = parse_postgresql_plan(plan_json)
+        .or_else(|| parse_mongodb_plan(plan_json))
+        .ok_or_else(|| {
+            "Failed to parse plan JSON. Expected PostgreSQL EXPLAIN (FORMAT JSON) or MongoDB explain output."
+                .to_string()
+        })?;
 
     // Create the application state
     let mut app = ExplainTuiApp::new(plan_root);
+    app.index_suggestions =
+        crate::performance_analyzer::PerformanceAnalyzer::suggest_postgresql_indexes(plan_json);
+    app.can_refresh = refresh.is_some();
 
     // Set up panic hook to restore terminal on panic
     let original_hook = panic::take_hook();
@@ -87,7 +109,7 @@ pub fn run_explain_tui(plan_json: &JsonValue) -> Result<TuiResult, String> {
     }));
 
     // Initialize terminal
-    let result = run_tui_loop(&mut app);
+    let result = run_tui_loop(&mut app, refresh);
 
     // Restore panic hook
     let _ = panic::take_hook();
@@ -96,7 +118,10 @@ pub fn run_explain_tui(plan_json: &JsonValue) -> Result<TuiResult, String> {
 }
 
 /// Internal function to run the TUI event loop
-fn run_tui_loop(app: &mut ExplainTuiApp) -> Result<TuiResult, String> {
+fn run_tui_loop(
+    app: &mut ExplainTuiApp,
+    mut refresh: Option<&mut dyn FnMut() -> Result<JsonValue, String>>,
+) -> Result<TuiResult, String> {
     // Setup terminal
     enable_raw_mode().map_err(|e| format!("Failed to enable raw mode: {}", e))?;
 
@@ -126,6 +151,14 @@ fn run_tui_loop(app: &mut ExplainTuiApp) -> Result<TuiResult, String> {
                 if app.should_quit {
                     break Ok(TuiResult::Quit);
                 }
+                if let Some(refresh) = refresh.as_mut().filter(|_| app.refresh_requested) {
+                    app.refresh_requested = false;
+                    app.status = Some("Running EXPLAIN ANALYZE...".to_string());
+                    terminal
+                        .draw(|frame| ui::render(frame, app))
+                        .map_err(|e| format!("Failed to draw: {}", e))?;
+                    app.status = Some(rerun(app, refresh));
+                }
             }
             Err(e) => {
                 break Err(format!("Event error: {}", e));
@@ -150,6 +183,44 @@ fn run_tui_loop(app: &mut ExplainTuiApp) -> Result<TuiResult, String> {
     result
 }
 
+/// Re-run the query and show its plan; returns the status line
+fn rerun(
+    app: &mut ExplainTuiApp,
+    refresh: &mut dyn FnMut() -> Result<JsonValue, String>,
+) -> String {
+    let plan_json = match refresh() {
+        Ok(plan_json) => plan_json,
+        Err(e) => return format!("Re-run failed: {e}"),
+    };
+    let Some(plan_root) =
+        parse_postgresql_plan(&plan_json).or_else(|| parse_mongodb_plan(&plan_json))
+    else {
+        return "Re-run failed: unrecognized plan".to_string();
+    };
+    app.index_suggestions =
+        crate::performance_analyzer::PerformanceAnalyzer::suggest_postgresql_indexes(&plan_json);
+    app.replace_plan(plan_root);
+
+    let mut status = format!("Run {}", app.run_count);
+    if let Some(time) = app.statistics.total_time {
+        status.push_str(&format!(": {time:.2}ms"));
+        if let Some(delta) = app.time_delta(&[]) {
+            status.push_str(&format!(" ({delta:+.2}ms vs previous run)"));
+        }
+    }
+    status
+}
+
+/// Whether `query` may change data, so running it with EXPLAIN ANALYZE
+/// (which executes it) is refused. PostgreSQL accepts INSERT, UPDATE,
+/// DELETE and MERGE in a WITH clause.
+pub fn writes_data(query: &str) -> bool {
+    static DML: std::sync::LazyLock<regex::Regex> = std::sync::LazyLock::new(|| {
+        regex::Regex::new(r"(?i)\b(insert|update|delete|merge)\b").unwrap()
+    });
+    DML.is_match(&crate::sql_buffer::strip_comments(query))
+}
+
 /// Check if the terminal supports the TUI
 ///
 /// Returns true if the terminal is capable of running the TUI visualizer.
@@ -216,6 +287,15 @@ mod tests {
         assert_eq!(plan.relation_name, Some("test_table".to_string()));
     }
 
+    #[test]
+    fn test_writes_data() {
+        assert!(!writes_data("SELECT * FROM users -- then delete them"));
+        assert!(!writes_data("SELECT updated_at FROM users"));
+        assert!(writes_data(
+            "WITH gone AS (DELETE FROM users RETURNING id) SELECT count(*) FROM gone"
+        ));
+    }
+
     #[test]
     fn test_invalid_plan_json() {
         let invalid_json = serde_json::json!({
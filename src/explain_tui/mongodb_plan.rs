@@ -0,0 +1,584 @@
+//! MongoDB explain output parsing
+//!
+//! Maps the output of `explain` with `executionStats` verbosity onto the same
+//! [`PlanNode`] tree the PostgreSQL visualizer uses. Find plans come from
+//! `queryPlanner.winningPlan` (with per-stage metrics taken from
+//! `executionStats.executionStages` when the classic engine ran the query),
+//! aggregation pipelines from the `stages` array, and rejected candidate plans
+//! are kept next to the winner so COLLSCAN vs IXSCAN choices are visible.
+
+use super::plan_tree::{PlanNode, REJECTED_PLAN, next_node_id, reset_node_id_counter};
+use crate::performance_analyzer::PerformanceLevel;
+use serde_json::Value as JsonValue;
+
+/// Stages that read a collection directly (through an index or not)
+const SCAN_STAGES: &[&str] = &[
+    "COLLSCAN",
+    "IXSCAN",
+    "IDHACK",
+    "COUNT_SCAN",
+    "DISTINCT_SCAN",
+    "EXPRESS_IXSCAN",
+    "EXPRESS_CLUSTERED_IXSCAN",
+];
+
+/// Parse a MongoDB `explain` document (find or aggregate) into a PlanNode tree
+pub fn parse_mongodb_plan(explain: &JsonValue) -> Option<PlanNode> {
+    reset_node_id_counter();
+
+    if let Some(JsonValue::Array(stages)) = explain.get("stages") {
+        return parse_pipeline(stages);
+    }
+    parse_cursor_explain(explain)
+}
+
+/// Parse the `queryPlanner`/`executionStats` pair of a find (or `$cursor` stage)
+fn parse_cursor_explain(explain: &JsonValue) -> Option<PlanNode> {
+    let planner = explain.get("queryPlanner")?;
+    let namespace = planner.get("namespace").and_then(|v| v.as_str());
+    let winning = unwrap_query_plan(planner.get("winningPlan")?);
+    let execution = explain.get("executionStats");
+
+    // Slot-based engine plans report execution stages with different stage
+    // names; only reuse them when they describe the same (classic) tree.
+    let execution_stages = execution
+        .and_then(|e| e.get("executionStages"))
+        .filter(|stages| stage_name(stages) == stage_name(winning));
+
+    let mut root = parse_stage(execution_stages.unwrap_or(winning), namespace)?;
+    if execution_stages.is_none()
+        && let Some(execution) = execution
+    {
+        root.actual_rows = execution.get("nReturned").and_then(|v| v.as_u64());
+        root.docs_examined = execution.get("totalDocsExamined").and_then(|v| v.as_u64());
+        root.keys_examined = execution.get("totalKeysExamined").and_then(|v| v.as_u64());
+        finish_node(&mut root);
+    }
+
+    let rejected: Vec<PlanNode> = planner
+        .get("rejectedPlans")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|plan| {
+            let mut node = parse_stage(unwrap_query_plan(plan), namespace)?;
+            node.parent_relationship = Some(REJECTED_PLAN.to_string());
+            Some(node)
+        })
+        .collect();
+
+    let total_time = execution
+        .and_then(|e| e.get("executionTimeMillis"))
+        .and_then(|v| v.as_f64());
+
+    if rejected.is_empty() {
+        if total_time.is_some() {
+            root.actual_time_ms = total_time;
+        }
+        return Some(root);
+    }
+
+    // Hang the winner and the rejected candidates under one selection node
+    let mut selection = PlanNode::new(next_node_id(), "PLAN SELECTION".to_string());
+    selection.has_estimates = false;
+    set_namespace(&mut selection, namespace);
+    selection.actual_rows = root.actual_rows;
+    selection.actual_time_ms = total_time.or(root.actual_time_ms);
+    root.parent_relationship = Some("Winning".to_string());
+    selection.children.push(root);
+    selection.children.extend(rejected);
+    Some(selection)
+}
+
+/// Build a chain from an aggregation `stages` array (last stage on top)
+fn parse_pipeline(stages: &[JsonValue]) -> Option<PlanNode> {
+    let mut current: Option<PlanNode> = None;
+
+    for stage in stages {
+        let obj = stage.as_object()?;
+        let (name, body) = obj.iter().find(|(key, _)| key.starts_with('$'))?;
+
+        let mut node = if name == "$cursor" {
+            parse_cursor_explain(body)?
+        } else {
+            let mut node = PlanNode::new(next_node_id(), name.clone());
+            node.has_estimates = false;
+            match name.as_str() {
+                "$match" => node.filter = Some(body.to_string()),
+                "$sort" => {
+                    if let Some(pattern) = body.get("sortKey") {
+                        node.sort_key = sort_pattern(pattern);
+                    }
+                }
+                "$group" => {
+                    if let Some(id) = body.get("_id") {
+                        node.group_key = vec![id.to_string()];
+                    }
+                }
+                "$lookup" => {
+                    node.relation_name =
+                        body.get("from").and_then(|v| v.as_str()).map(String::from);
+                }
+                _ => node.output = vec![body.to_string()],
+            }
+            node
+        };
+
+        // Stage-level metrics sit next to the stage key
+        if let Some(returned) = obj.get("nReturned").and_then(|v| v.as_u64()) {
+            node.actual_rows = Some(returned);
+        }
+        if let Some(time) = obj
+            .get("executionTimeMillisEstimate")
+            .and_then(|v| v.as_f64())
+        {
+            node.actual_time_ms = Some(time);
+        }
+        if let Some(docs) = obj.get("totalDocsExamined").and_then(|v| v.as_u64()) {
+            node.docs_examined = Some(docs);
+        }
+        if let Some(keys) = obj.get("totalKeysExamined").and_then(|v| v.as_u64()) {
+            node.keys_examined = Some(keys);
+        }
+        if obj.get("usedDisk").and_then(|v| v.as_bool()) == Some(true) {
+            node.sort_space_type = Some("Disk".to_string());
+        }
+        if name != "$cursor" {
+            finish_node(&mut node);
+        }
+        if let Some(scans) = obj.get("collectionScans").and_then(|v| v.as_u64())
+            && scans > 0
+        {
+            node.warnings.push(format!(
+                "Lookup ran {scans} collection scan(s) on the foreign collection"
+            ));
+            node.recommendations
+                .push("Index the foreignField of the lookup".to_string());
+        }
+
+        if let Some(previous) = current.take() {
+            node.children.push(previous);
+        }
+        current = Some(node);
+    }
+
+    current
+}
+
+/// Explain version 2 (slot-based engine) wraps the classic tree in `queryPlan`
+fn unwrap_query_plan(plan: &JsonValue) -> &JsonValue {
+    plan.get("queryPlan").unwrap_or(plan)
+}
+
+fn stage_name(stage: &JsonValue) -> Option<&str> {
+    stage.get("stage").and_then(|v| v.as_str())
+}
+
+fn set_namespace(node: &mut PlanNode, namespace: Option<&str>) {
+    if let Some(namespace) = namespace {
+        match namespace.split_once('.') {
+            Some((database, collection)) => {
+                node.schema = Some(database.to_string());
+                node.relation_name = Some(collection.to_string());
+            }
+            None => node.relation_name = Some(namespace.to_string()),
+        }
+    }
+}
+
+/// `{"age": -1, "name": 1}` → `["age: -1", "name: 1"]`
+fn sort_pattern(pattern: &JsonValue) -> Vec<String> {
+    pattern
+        .as_object()
+        .map(|fields| {
+            fields
+                .iter()
+                .map(|(field, direction)| format!("{field}: {direction}"))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Recursively parse a classic query-plan stage
+fn parse_stage(stage: &JsonValue, namespace: Option<&str>) -> Option<PlanNode> {
+    let obj = stage.as_object()?;
+    let node_type = stage_name(stage).unwrap_or("UNKNOWN").to_string();
+    let mut node = PlanNode::new(next_node_id(), node_type);
+    node.has_estimates = false;
+
+    if SCAN_STAGES.contains(&node.node_type.as_str()) {
+        set_namespace(&mut node, namespace);
+    }
+    node.index_name = obj
+        .get("indexName")
+        .and_then(|v| v.as_str())
+        .map(String::from);
+    if let Some(key_pattern) = obj.get("keyPattern") {
+        node.index_cond = Some(match obj.get("indexBounds") {
+            Some(bounds) => format!("{key_pattern} bounds {bounds}"),
+            None => key_pattern.to_string(),
+        });
+    }
+    node.filter = obj.get("filter").map(|f| f.to_string());
+
+    // executionStats metrics (absent for rejected plans and plain queryPlanner output)
+    node.actual_rows = obj.get("nReturned").and_then(|v| v.as_u64());
+    node.actual_time_ms = obj
+        .get("executionTimeMillisEstimate")
+        .and_then(|v| v.as_f64());
+    node.docs_examined = obj.get("docsExamined").and_then(|v| v.as_u64());
+    node.keys_examined = obj.get("keysExamined").and_then(|v| v.as_u64());
+
+    if let Some(pattern) = obj.get("sortPattern") {
+        node.sort_key = sort_pattern(pattern);
+        let spilled = obj.get("usedDisk").and_then(|v| v.as_bool()) == Some(true);
+        node.sort_method = Some(if spilled {
+            "blocking, spilled to disk".to_string()
+        } else {
+            "blocking, in memory".to_string()
+        });
+        node.sort_space_type = Some(if spilled { "Disk" } else { "Memory" }.to_string());
+        node.sort_space_used = obj
+            .get("totalDataSizeSorted")
+            .or_else(|| obj.get("memUsage"))
+            .and_then(|v| v.as_u64())
+            .map(|bytes| bytes / 1024);
+    }
+
+    finish_node(&mut node);
+
+    if let Some(input) = obj.get("inputStage") {
+        node.children.extend(parse_stage(input, namespace));
+    }
+    for input in obj
+        .get("inputStages")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+    {
+        node.children.extend(parse_stage(input, namespace));
+    }
+    // Sharded plans: one subtree per shard
+    for shard in obj
+        .get("shards")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+    {
+        let plan = shard
+            .get("executionStages")
+            .or_else(|| shard.get("winningPlan"))
+            .map(unwrap_query_plan);
+        if let Some(mut child) = plan.and_then(|plan| parse_stage(plan, namespace)) {
+            child.parent_relationship = shard
+                .get("shardName")
+                .and_then(|v| v.as_str())
+                .map(String::from);
+            node.children.push(child);
+        }
+    }
+
+    Some(node)
+}
+
+/// Recompute the performance level and warnings once metrics are in place
+fn finish_node(node: &mut PlanNode) {
+    node.performance_level = calculate_performance_level(node);
+    add_warnings_and_recommendations(node);
+}
+
+/// Fewer than one in ten examined entries made it out of the stage
+fn is_wasteful(examined: u64, returned: u64) -> bool {
+    examined >= 1000 && returned.saturating_mul(10) < examined
+}
+
+fn calculate_performance_level(node: &PlanNode) -> PerformanceLevel {
+    let returned = node.actual_rows.unwrap_or(0);
+    let docs = node.docs_examined.unwrap_or(0);
+    let keys = node.keys_examined.unwrap_or(0);
+
+    match node.node_type.as_str() {
+        "COLLSCAN" if docs >= 1000 => {
+            return if node.filter.is_some() && is_wasteful(docs, returned) {
+                PerformanceLevel::Poor
+            } else {
+                PerformanceLevel::Warning
+            };
+        }
+        "FETCH" if node.filter.is_some() && is_wasteful(docs, returned) => {
+            return PerformanceLevel::Warning;
+        }
+        "SORT" | "$sort" if node.sort_space_type.as_deref() == Some("Disk") => {
+            return PerformanceLevel::Warning;
+        }
+        _ => {}
+    }
+
+    if let Some(time) = node.actual_time_ms {
+        if time > 1000.0 {
+            return PerformanceLevel::Critical;
+        } else if time > 100.0 {
+            return PerformanceLevel::Poor;
+        } else if time > 10.0 {
+            return PerformanceLevel::Warning;
+        }
+    }
+
+    match node.node_type.as_str() {
+        "IXSCAN" | "EXPRESS_IXSCAN" if is_wasteful(keys, returned) => PerformanceLevel::Warning,
+        "IXSCAN"
+        | "IDHACK"
+        | "COUNT_SCAN"
+        | "DISTINCT_SCAN"
+        | "EXPRESS_IXSCAN"
+        | "EXPRESS_CLUSTERED_IXSCAN" => PerformanceLevel::Excellent,
+        _ => PerformanceLevel::Good,
+    }
+}
+
+fn add_warnings_and_recommendations(node: &mut PlanNode) {
+    node.warnings.clear();
+    node.recommendations.clear();
+    let returned = node.actual_rows.unwrap_or(0);
+    let docs = node.docs_examined.unwrap_or(0);
+    let keys = node.keys_examined.unwrap_or(0);
+    let collection = node.relation_name.clone().unwrap_or_default();
+
+    match node.node_type.as_str() {
+        "COLLSCAN" if docs >= 1000 => {
+            node.warnings
+                .push(format!("Collection scan examined {docs} documents"));
+            match &node.filter {
+                Some(filter) => {
+                    if is_wasteful(docs, returned) {
+                        node.warnings.push(format!(
+                            "Only {returned} of {docs} documents matched the filter"
+                        ));
+                    }
+                    node.recommendations.push(format!(
+                        "Consider an index on '{collection}' for filter: {filter}"
+                    ));
+                }
+                None => node.recommendations.push(format!(
+                    "Consider if all documents from '{collection}' are needed"
+                )),
+            }
+        }
+        "IXSCAN" | "EXPRESS_IXSCAN" if is_wasteful(keys, returned) => {
+            node.warnings.push(format!(
+                "Index scan examined {keys} keys to return {returned} documents"
+            ));
+            node.recommendations
+                .push("Consider a more selective or compound index".to_string());
+        }
+        "FETCH" if node.filter.is_some() && is_wasteful(docs, returned) => {
+            node.warnings.push(format!(
+                "Fetched {docs} documents but the residual filter kept {returned}"
+            ));
+            node.recommendations.push(
+                "Add the filtered fields to the index so they are matched on keys".to_string(),
+            );
+        }
+        "SORT" | "$sort" if node.sort_space_type.as_deref() == Some("Disk") => {
+            node.warnings
+                .push("Blocking sort spilled to disk".to_string());
+            if !node.sort_key.is_empty() {
+                node.recommendations.push(format!(
+                    "Add an index matching the sort pattern: {}",
+                    node.sort_key.join(", ")
+                ));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Render a plan tree as indented text lines for plain `EXPLAIN` output
+pub fn format_mongodb_plan_text(root: &PlanNode) -> Vec<String> {
+    let mut lines = Vec::new();
+    format_node_text(root, 0, &mut lines);
+    lines
+}
+
+fn format_node_text(node: &PlanNode, depth: usize, lines: &mut Vec<String>) {
+    let indent = "  ".repeat(depth);
+    let arrow = if depth == 0 { "" } else { "-> " };
+
+    let mut metrics = Vec::new();
+    if let Some(returned) = node.actual_rows {
+        metrics.push(format!("returned {returned}"));
+    }
+    if let Some(docs) = node.docs_examined {
+        metrics.push(format!("docs examined {docs}"));
+    }
+    if let Some(keys) = node.keys_examined {
+        metrics.push(format!("keys examined {keys}"));
+    }
+    if let Some(time) = node.actual_time_ms {
+        metrics.push(format!("{time:.0} ms"));
+    }
+    let metrics = if metrics.is_empty() {
+        String::new()
+    } else {
+        format!("  ({})", metrics.join(", "))
+    };
+    lines.push(format!("{indent}{arrow}{}{metrics}", node.display_label()));
+
+    let detail_indent = format!("{indent}{}", if depth == 0 { "  " } else { "     " });
+    if let Some(cond) = &node.index_cond {
+        lines.push(format!("{detail_indent}Index: {cond}"));
+    }
+    if let Some(filter) = &node.filter {
+        lines.push(format!("{detail_indent}Filter: {filter}"));
+    }
+    if !node.sort_key.is_empty() {
+        lines.push(format!("{detail_indent}Sort: {}", node.sort_key.join(", ")));
+    }
+    for warning in &node.warnings {
+        lines.push(format!("{detail_indent}! {warning}"));
+    }
+
+    for child in &node.children {
+        format_node_text(child, depth + 1, lines);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find_explain() -> JsonValue {
+        serde_json::json!({
+            "queryPlanner": {
+                "namespace": "shop.users",
+                "winningPlan": {
+                    "stage": "FETCH",
+                    "inputStage": {
+                        "stage": "IXSCAN",
+                        "keyPattern": {"age": 1},
+                        "indexName": "age_1",
+                        "indexBounds": {"age": ["[30, inf.0]"]}
+                    }
+                },
+                "rejectedPlans": [{"stage": "COLLSCAN", "filter": {"age": {"$gte": 30}}}]
+            },
+            "executionStats": {
+                "nReturned": 12,
+                "executionTimeMillis": 3,
+                "totalKeysExamined": 12,
+                "totalDocsExamined": 12,
+                "executionStages": {
+                    "stage": "FETCH",
+                    "nReturned": 12,
+                    "executionTimeMillisEstimate": 1,
+                    "docsExamined": 12,
+                    "inputStage": {
+                        "stage": "IXSCAN",
+                        "nReturned": 12,
+                        "executionTimeMillisEstimate": 0,
+                        "keysExamined": 12,
+                        "keyPattern": {"age": 1},
+                        "indexName": "age_1"
+                    }
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_parse_find_with_rejected_plan() {
+        let root = parse_mongodb_plan(&find_explain()).unwrap();
+        assert_eq!(root.node_type, "PLAN SELECTION");
+        assert_eq!(root.actual_time_ms, Some(3.0));
+        assert_eq!(root.children.len(), 2);
+
+        let winning = &root.children[0];
+        assert_eq!(winning.node_type, "FETCH");
+        assert_eq!(winning.docs_examined, Some(12));
+        let ixscan = &winning.children[0];
+        assert_eq!(ixscan.display_label(), "IXSCAN on shop.users using age_1");
+        assert_eq!(ixscan.keys_examined, Some(12));
+        assert_eq!(ixscan.performance_level, PerformanceLevel::Excellent);
+
+        let rejected = &root.children[1];
+        assert!(rejected.is_rejected_plan());
+        assert_eq!(
+            rejected.display_label(),
+            "COLLSCAN on shop.users [rejected]"
+        );
+
+        // Rejected candidates don't count as indexes in use
+        assert_eq!(root.get_indexes(), vec!["age_1".to_string()]);
+    }
+
+    #[test]
+    fn test_collscan_with_selective_filter_is_flagged() {
+        let explain = serde_json::json!({
+            "queryPlanner": {
+                "namespace": "shop.orders",
+                "winningPlan": {"stage": "COLLSCAN", "filter": {"status": {"$eq": "open"}}},
+                "rejectedPlans": []
+            },
+            "executionStats": {
+                "nReturned": 5,
+                "executionTimeMillis": 40,
+                "executionStages": {
+                    "stage": "COLLSCAN",
+                    "filter": {"status": {"$eq": "open"}},
+                    "nReturned": 5,
+                    "executionTimeMillisEstimate": 38,
+                    "docsExamined": 50000
+                }
+            }
+        });
+        let root = parse_mongodb_plan(&explain).unwrap();
+        assert_eq!(root.node_type, "COLLSCAN");
+        assert_eq!(root.performance_level, PerformanceLevel::Poor);
+        assert_eq!(root.actual_time_ms, Some(40.0));
+        assert!(root.warnings[0].contains("50000 documents"));
+        assert!(root.recommendations[0].contains("index on 'orders'"));
+
+        let text = format_mongodb_plan_text(&root);
+        assert_eq!(
+            text[0],
+            "COLLSCAN on shop.orders  (returned 5, docs examined 50000, 40 ms)"
+        );
+    }
+
+    #[test]
+    fn test_parse_aggregation_pipeline() {
+        let explain = serde_json::json!({
+            "stages": [
+                {
+                    "$cursor": {
+                        "queryPlanner": {
+                            "namespace": "shop.orders",
+                            "winningPlan": {"stage": "COLLSCAN"},
+                            "rejectedPlans": []
+                        },
+                        "executionStats": {
+                            "nReturned": 200,
+                            "executionTimeMillis": 2,
+                            "totalDocsExamined": 200,
+                            "executionStages": {"stage": "COLLSCAN", "nReturned": 200, "docsExamined": 200}
+                        }
+                    },
+                    "nReturned": 200,
+                    "executionTimeMillisEstimate": 1
+                },
+                {"$group": {"_id": "$status", "n": {"$sum": 1}}, "nReturned": 3, "executionTimeMillisEstimate": 2}
+            ]
+        });
+        let root = parse_mongodb_plan(&explain).unwrap();
+        assert_eq!(root.node_type, "$group");
+        assert_eq!(root.group_key, vec!["\"$status\"".to_string()]);
+        assert_eq!(root.actual_rows, Some(3));
+        assert_eq!(root.children[0].node_type, "COLLSCAN");
+        assert_eq!(root.children[0].docs_examined, Some(200));
+    }
+
+    #[test]
+    fn test_not_a_mongodb_plan() {
+        assert!(parse_mongodb_plan(&serde_json::json!([{"Plan": {}}])).is_none());
+    }
+}
@@ -81,6 +81,12 @@ pub struct PlanNode {
     pub cte_name: Option<String>,
     /// Subplan name
     pub subplan_name: Option<String>,
+    /// Documents examined (MongoDB)
+    pub docs_examined: Option<u64>,
+    /// Index keys examined (MongoDB)
+    pub keys_examined: Option<u64>,
+    /// Whether the planner reported cost and row estimates (MongoDB does not)
+    pub has_estimates: bool,
 }
 
 impl PlanNode {
@@ -123,6 +129,9 @@ impl PlanNode {
             workers_launched: None,
             cte_name: None,
             subplan_name: None,
+            docs_examined: None,
+            keys_examined: None,
+            has_estimates: true,
         }
     }
 
@@ -149,6 +158,10 @@ impl PlanNode {
             label = format!("{} ({})", label, cte);
         }
 
+        if self.is_rejected_plan() {
+            label.push_str(" [rejected]");
+        }
+
         label
     }
 
@@ -156,11 +169,18 @@ impl PlanNode {
     pub fn cost_summary(&self) -> String {
         if let Some(time) = self.actual_time_ms {
             format!("{:.2}ms", time)
-        } else {
+        } else if self.has_estimates {
             format!("cost: {:.0}", self.total_cost)
+        } else {
+            self.rows_summary()
         }
     }
 
+    /// Whether this node is the root of a plan the optimizer considered but rejected
+    pub fn is_rejected_plan(&self) -> bool {
+        self.parent_relationship.as_deref() == Some(REJECTED_PLAN)
+    }
+
     /// Get row count summary (estimated vs actual)
     pub fn rows_summary(&self) -> String {
         if let Some(actual) = self.actual_rows {
@@ -236,7 +256,7 @@ impl PlanNode {
         if let Some(ref index) = self.index_name {
             indexes.push(index.clone());
         }
-        for child in &self.children {
+        for child in self.children.iter().filter(|c| !c.is_rejected_plan()) {
             indexes.extend(child.get_indexes());
         }
         indexes
@@ -263,10 +283,13 @@ impl PlanNode {
     }
 }
 
+/// `parent_relationship` marker for plans the optimizer rejected
+pub const REJECTED_PLAN: &str = "Rejected";
+
 /// Counter for generating unique node IDs
 static NODE_ID_COUNTER: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
 
-fn next_node_id() -> String {
+pub(super) fn next_node_id() -> String {
     let id = NODE_ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
     format!("node_{}", id)
 }
@@ -639,6 +662,9 @@ impl PlanStatistics {
         if node.node_type == "Seq Scan" && node.plan_rows >= 1000 {
             return true;
         }
+        if node.node_type == "COLLSCAN" && node.docs_examined.unwrap_or(0) >= 1000 {
+            return true;
+        }
         node.children.iter().any(Self::check_seq_scans)
     }
 
@@ -665,7 +691,7 @@ impl PlanStatistics {
             PerformanceLevel::Poor => 40,
             PerformanceLevel::Critical => 20,
         }];
-        for child in &node.children {
+        for child in node.children.iter().filter(|c| !c.is_rejected_plan()) {
             scores.extend(Self::collect_scores(child));
         }
         scores
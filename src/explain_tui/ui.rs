@@ -2,7 +2,7 @@
 
 use super::app::ExplainTuiApp;
 use super::plan_tree::PlanNode;
-use crate::performance_analyzer::PerformanceLevel;
+use crate::performance_analyzer::{IndexBenefit, PerformanceLevel};
 use ratatui::{
     Frame,
     layout::{Constraint, Direction, Layout, Rect},
@@ -38,6 +38,17 @@ fn performance_indicator(level: PerformanceLevel) -> &'static str {
 }
 
 /// Grade color based on score
+/// Colour of a time change: green when faster, red when slower
+fn delta_color(delta_ms: f64) -> Color {
+    if delta_ms < 0.0 {
+        Color::Green
+    } else if delta_ms > 0.0 {
+        Color::Red
+    } else {
+        Color::DarkGray
+    }
+}
+
 fn grade_color(score: u8) -> Color {
     match score {
         90..=100 => Color::Green,
@@ -53,19 +64,28 @@ fn grade_color(score: u8) -> Color {
 pub fn render(frame: &mut Frame, app: &ExplainTuiApp) {
     let area = frame.area();
 
-    // Main layout: header, content, footer
+    // Main layout: header, content, index suggestions (when any), footer
+    let suggestions_height = if app.index_suggestions.is_empty() {
+        0
+    } else {
+        (app.index_suggestions.len() as u16 * 2 + 2).min(10)
+    };
     let main_chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(3), // Header
-            Constraint::Min(10),   // Content
-            Constraint::Length(2), // Footer
+            Constraint::Length(3),                  // Header
+            Constraint::Min(10),                    // Content
+            Constraint::Length(suggestions_height), // Index suggestions
+            Constraint::Length(2),                  // Footer
         ])
         .split(area);
 
     render_header(frame, app, main_chunks[0]);
     render_content(frame, app, main_chunks[1]);
-    render_footer(frame, main_chunks[2]);
+    if suggestions_height > 0 {
+        render_index_suggestions(frame, app, main_chunks[2]);
+    }
+    render_footer(frame, app, main_chunks[3]);
 
     // Render help overlay if shown
     if app.show_help {
@@ -131,6 +151,14 @@ fn render_header(frame: &mut Frame, app: &ExplainTuiApp, area: Rect) {
         } else {
             Span::raw("")
         },
+        if app.run_count > 1 {
+            Span::styled(
+                format!(" | run {}", app.run_count),
+                Style::default().fg(Color::Cyan),
+            )
+        } else {
+            Span::raw("")
+        },
     ]);
 
     let header = Paragraph::new(title)
@@ -281,6 +309,14 @@ fn build_tree_items<'a>(
         Style::default().fg(Color::DarkGray),
     ));
 
+    // Change since the previous run
+    if let Some(delta) = app.time_delta(current_path) {
+        spans.push(Span::styled(
+            format!(" {:+.2}ms", delta),
+            Style::default().fg(delta_color(delta)),
+        ));
+    }
+
     // Add warning indicator
     if !warn_indicator.is_empty() {
         spans.push(Span::styled(
@@ -360,13 +396,15 @@ fn render_details(frame: &mut Frame, app: &ExplainTuiApp, area: Rect) {
     lines.push(Line::from(""));
 
     // Cost information
-    lines.push(Line::from(vec![
-        Span::styled("Cost: ", Style::default().fg(Color::Yellow)),
-        Span::styled(
-            format!("{:.2}..{:.2}", node.startup_cost, node.total_cost),
-            Style::default().fg(Color::White),
-        ),
-    ]));
+    if node.has_estimates {
+        lines.push(Line::from(vec![
+            Span::styled("Cost: ", Style::default().fg(Color::Yellow)),
+            Span::styled(
+                format!("{:.2}..{:.2}", node.startup_cost, node.total_cost),
+                Style::default().fg(Color::White),
+            ),
+        ]));
+    }
 
     // Timing (if available)
     if let Some(time) = node.actual_time_ms {
@@ -393,17 +431,62 @@ fn render_details(frame: &mut Frame, app: &ExplainTuiApp, area: Rect) {
         }
     }
 
+    // Previous run
+    if let Some(previous) = app.previous_node(&app.selected_path) {
+        let mut spans = vec![Span::styled(
+            "Previous run: ",
+            Style::default().fg(Color::Yellow),
+        )];
+        match previous.actual_time_ms {
+            Some(time) => spans.push(Span::styled(
+                format!("{:.3} ms", time),
+                Style::default().fg(Color::White),
+            )),
+            None => spans.push(Span::styled(
+                "not timed",
+                Style::default().fg(Color::DarkGray),
+            )),
+        }
+        if let Some(delta) = app.time_delta(&app.selected_path) {
+            let percent = previous
+                .actual_time_ms
+                .filter(|time| *time > 0.0)
+                .map(|time| format!(", {:+.0}%", delta / time * 100.0))
+                .unwrap_or_default();
+            spans.push(Span::styled(
+                format!(" ({:+.3} ms{})", delta, percent),
+                Style::default().fg(delta_color(delta)),
+            ));
+        }
+        if let (Some(before), Some(now)) = (previous.actual_rows, node.actual_rows) {
+            if before != now {
+                spans.push(Span::styled(
+                    format!("  rows {} -> {}", before, now),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+        }
+        lines.push(Line::from(spans));
+    }
+
     // Row estimates vs actual
     lines.push(Line::from(""));
-    lines.push(Line::from(vec![
-        Span::styled("Rows (est): ", Style::default().fg(Color::Yellow)),
-        Span::styled(
-            format!("{}", node.plan_rows),
-            Style::default().fg(Color::White),
-        ),
-    ]));
+    if node.has_estimates {
+        lines.push(Line::from(vec![
+            Span::styled("Rows (est): ", Style::default().fg(Color::Yellow)),
+            Span::styled(
+                format!("{}", node.plan_rows),
+                Style::default().fg(Color::White),
+            ),
+        ]));
+    } else if let Some(actual) = node.actual_rows {
+        lines.push(Line::from(vec![
+            Span::styled("Returned: ", Style::default().fg(Color::Yellow)),
+            Span::styled(format!("{}", actual), Style::default().fg(Color::White)),
+        ]));
+    }
 
-    if let Some(actual) = node.actual_rows {
+    if let Some(actual) = node.actual_rows.filter(|_| node.has_estimates) {
         let ratio = if node.plan_rows > 0 {
             actual as f64 / node.plan_rows as f64
         } else {
@@ -436,6 +519,28 @@ fn render_details(frame: &mut Frame, app: &ExplainTuiApp, area: Rect) {
         }
     }
 
+    // Documents/keys examined (MongoDB)
+    if node.docs_examined.is_some() || node.keys_examined.is_some() {
+        let returned = node.actual_rows.unwrap_or(0);
+        for (label, examined) in [
+            ("Docs Examined: ", node.docs_examined),
+            ("Keys Examined: ", node.keys_examined),
+        ] {
+            let Some(examined) = examined else {
+                continue;
+            };
+            let color = if examined >= 1000 && returned.saturating_mul(10) < examined {
+                Color::Red
+            } else {
+                Color::White
+            };
+            lines.push(Line::from(vec![
+                Span::styled(label, Style::default().fg(Color::Yellow)),
+                Span::styled(format!("{}", examined), Style::default().fg(color)),
+            ]));
+        }
+    }
+
     // Filter conditions
     if node.filter.is_some()
         || node.index_cond.is_some()
@@ -850,8 +955,18 @@ fn render_details(frame: &mut Frame, app: &ExplainTuiApp, area: Rect) {
     frame.render_widget(details, area);
 }
 
-/// Render the footer with keybindings
-fn render_footer(frame: &mut Frame, area: Rect) {
+/// Render the footer with keybindings, or the outcome of the last re-run
+fn render_footer(frame: &mut Frame, app: &ExplainTuiApp, area: Rect) {
+    if let Some(status) = &app.status {
+        let footer = Paragraph::new(Line::from(Span::styled(
+            format!(" {status}  [r] Re-run  [q] Quit"),
+            Style::default().fg(Color::Cyan),
+        )))
+        .block(Block::default().borders(Borders::TOP));
+        frame.render_widget(footer, area);
+        return;
+    }
+
     let help_text = Line::from(vec![
         Span::styled(" [", Style::default().fg(Color::DarkGray)),
         Span::styled("j/k", Style::default().fg(Color::Cyan)),
@@ -864,6 +979,8 @@ fn render_footer(frame: &mut Frame, area: Rect) {
         Span::styled("] Collapse  [", Style::default().fg(Color::DarkGray)),
         Span::styled("Tab", Style::default().fg(Color::Cyan)),
         Span::styled("] Focus  [", Style::default().fg(Color::DarkGray)),
+        Span::styled("r", Style::default().fg(Color::Cyan)),
+        Span::styled("] Re-run  [", Style::default().fg(Color::DarkGray)),
         Span::styled("?", Style::default().fg(Color::Cyan)),
         Span::styled("] Help  [", Style::default().fg(Color::DarkGray)),
         Span::styled("q", Style::default().fg(Color::Cyan)),
@@ -877,6 +994,52 @@ fn render_footer(frame: &mut Frame, area: Rect) {
     frame.render_widget(footer, area);
 }
 
+fn benefit_color(benefit: IndexBenefit) -> Color {
+    match benefit {
+        IndexBenefit::High => Color::Green,
+        IndexBenefit::Medium => Color::Yellow,
+        IndexBenefit::Low => Color::DarkGray,
+    }
+}
+
+/// Render the index suggestions panel
+fn render_index_suggestions(frame: &mut Frame, app: &ExplainTuiApp, area: Rect) {
+    let mut lines = Vec::new();
+    for suggestion in &app.index_suggestions {
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("{:<7}", suggestion.benefit.label()),
+                Style::default()
+                    .fg(benefit_color(suggestion.benefit))
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                suggestion.create_statement(),
+                Style::default().fg(Color::Cyan),
+            ),
+        ]));
+        lines.push(Line::from(Span::styled(
+            format!(
+                "       ~{:.0}% of plan · {}",
+                suggestion.plan_share * 100.0,
+                suggestion.reason
+            ),
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    let panel = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Green))
+            .title(format!(
+                " Index Suggestions ({}) ",
+                app.index_suggestions.len()
+            )),
+    );
+    frame.render_widget(panel, area);
+}
+
 /// Render help overlay
 fn render_help_overlay(frame: &mut Frame, area: Rect) {
     // Calculate centered popup area
@@ -920,6 +1083,7 @@ fn render_help_overlay(frame: &mut Frame, area: Rect) {
         Line::from("    Enter       - Toggle expand/collapse"),
         Line::from("    e           - Expand all nodes"),
         Line::from("    c           - Collapse all nodes"),
+        Line::from("    r           - Re-run with EXPLAIN ANALYZE, show deltas"),
         Line::from(""),
         Line::from(vec![Span::styled(
             "  Details Panel:",
@@ -0,0 +1,177 @@
+//! Row exports for `\export`
+//!
+//! Writers implement [`RowSink`], so rows go from the driver to the output as
+//! they arrive and the full result set is never held in memory.
+
+use crate::database::{DatabaseError, RowSink};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::str::FromStr;
+
+/// Output formats accepted by `\export`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Newline-delimited JSON, one object per row
+    Ndjson,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ndjson" | "jsonl" => Ok(ExportFormat::Ndjson),
+            other => Err(format!(
+                "Unknown export format '{other}' (supported: ndjson)"
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportFormat::Ndjson => write!(f, "ndjson"),
+        }
+    }
+}
+
+/// Whether any `-c` command is an `\export` to stdout (`-`)
+pub fn exports_to_stdout(commands: &[String]) -> bool {
+    commands.iter().any(|command| {
+        matches!(
+            crate::commands::CommandParser::parse(command.trim()),
+            Ok(crate::commands::Command::Export { ref filename, .. }) if filename == "-"
+        )
+    })
+}
+
+/// Run `query` and stream its rows to `out` in `format`; returns the row count
+pub async fn export_query<W: Write + Send>(
+    db: &mut crate::db::Database,
+    format: ExportFormat,
+    query: &str,
+    out: W,
+) -> crate::error::Result<u64> {
+    match format {
+        ExportFormat::Ndjson => {
+            let mut writer = NdjsonWriter::new(out);
+            let rows = db.stream_query(query, &mut writer).await?;
+            writer.finish()?;
+            Ok(rows)
+        }
+    }
+}
+
+/// Writes each row as a JSON object keyed by column name, in column order.
+/// Repeated column names get the first free `_2`, `_3`... suffix so no value is lost.
+pub struct NdjsonWriter<W: Write + Send> {
+    out: W,
+    keys: Vec<String>,
+    line: String,
+}
+
+impl<W: Write + Send> NdjsonWriter<W> {
+    pub fn new(out: W) -> Self {
+        NdjsonWriter {
+            out,
+            keys: Vec::new(),
+            line: String::new(),
+        }
+    }
+
+    /// Flush and return the underlying writer
+    pub fn finish(mut self) -> Result<W, DatabaseError> {
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+impl<W: Write + Send> RowSink for NdjsonWriter<W> {
+    fn columns(&mut self, names: &[String]) -> Result<(), DatabaseError> {
+        // Every key in use, so a suffix never lands on a real column name
+        let mut taken: HashSet<String> = names.iter().cloned().collect();
+        let mut seen: HashMap<&str, usize> = HashMap::new();
+        self.keys = names
+            .iter()
+            .map(|name| {
+                let count = seen.entry(name.as_str()).or_default();
+                *count += 1;
+                let key = if *count == 1 {
+                    name.clone()
+                } else {
+                    loop {
+                        let candidate = format!("{name}_{count}");
+                        if taken.insert(candidate.clone()) {
+                            break candidate;
+                        }
+                        *count += 1;
+                    }
+                };
+                Value::String(key).to_string()
+            })
+            .collect();
+        Ok(())
+    }
+
+    fn row(&mut self, values: Vec<Value>) -> Result<(), DatabaseError> {
+        self.line.clear();
+        self.line.push('{');
+        for (i, (key, value)) in self.keys.iter().zip(&values).enumerate() {
+            if i > 0 {
+                self.line.push(',');
+            }
+            self.line.push_str(key);
+            self.line.push(':');
+            self.line.push_str(&value.to_string());
+        }
+        self.line.push_str("}\n");
+        self.out.write_all(self.line.as_bytes())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_ndjson_rows_keep_column_order_and_types() {
+        let mut writer = NdjsonWriter::new(Vec::new());
+        writer
+            .columns(&["id".to_string(), "name".to_string(), "id".to_string()])
+            .unwrap();
+        writer
+            .row(vec![json!(1), json!("a \"quoted\"\nline"), json!(null)])
+            .unwrap();
+        writer
+            .row(vec![json!(2), json!({"k": [1, 2]}), json!(true)])
+            .unwrap();
+        let out = String::from_utf8(writer.finish().unwrap()).unwrap();
+        assert_eq!(
+            out,
+            "{\"id\":1,\"name\":\"a \\\"quoted\\\"\\nline\",\"id_2\":null}\n\
+             {\"id\":2,\"name\":{\"k\":[1,2]},\"id_2\":true}\n"
+        );
+        for line in out.lines() {
+            assert!(serde_json::from_str::<Value>(line).unwrap().is_object());
+        }
+        // A suffix skips names a real column already has
+        let mut writer = NdjsonWriter::new(Vec::new());
+        writer
+            .columns(&["id".to_string(), "id_2".to_string(), "id".to_string()])
+            .unwrap();
+        writer.row(vec![json!(1), json!(2), json!(3)]).unwrap();
+        let out = String::from_utf8(writer.finish().unwrap()).unwrap();
+        assert_eq!(out, "{\"id\":1,\"id_2\":2,\"id_3\":3}\n");
+    }
+
+    #[test]
+    fn test_export_format_parsing() {
+        assert_eq!("NDJSON".parse::<ExportFormat>(), Ok(ExportFormat::Ndjson));
+        assert_eq!("jsonl".parse::<ExportFormat>(), Ok(ExportFormat::Ndjson));
+        assert!("xlsx".parse::<ExportFormat>().is_err());
+    }
+}
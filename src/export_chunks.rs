@@ -0,0 +1,450 @@
+//! Whole-table `\export` in primary key chunks
+//!
+//! A single `SELECT *` over a huge table keeps one cursor (and on PostgreSQL
+//! one snapshot) open for the whole export, and a connection dropped an hour
+//! in loses all of it. When `\export` is given a table, as a name or
+//! `SELECT * FROM table`, and the table has a primary key, rows are read in
+//! key order a chunk at a time (`WHERE key > last ORDER BY key LIMIT n`).
+//! After each chunk the file is flushed and `<file>.checkpoint` records the
+//! last key and the file length. A failing chunk is retried a few times,
+//! reconnecting first when the connection is gone; when it keeps failing the
+//! checkpoint stays, and the same `\export` with `--resume` carries on from
+//! it.
+//!
+//! Primary keys are looked up on PostgreSQL, MySQL and SQLite. Other tables,
+//! queries and exports to stdout are read with a single query.
+
+use crate::database::{DatabaseError, DatabaseType, DatabaseTypeExt, RowSink};
+use crate::db::Database;
+use crate::export::{ExportFormat, NdjsonWriter};
+use crate::insert_builder::{quote_identifier, quote_literal};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::{BufWriter, IsTerminal, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+/// Rows per chunk unless `--chunk` says otherwise
+pub const DEFAULT_CHUNK_ROWS: usize = 50_000;
+
+/// Tries per chunk before the export stops
+const MAX_ATTEMPTS: u32 = 3;
+
+/// A table name, possibly qualified and quoted, alone or as `SELECT * FROM`
+static WHOLE_TABLE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?is)^\s*(?:select\s+\*\s+from\s+)?((?:"[^"]+"|`[^`]+`|[\w$]+)(?:\.(?:"[^"]+"|`[^`]+`|[\w$]+)){0,2})\s*$"#,
+    )
+    .unwrap()
+});
+
+/// The table `query` reads in full, if that is all it does
+pub fn whole_table(query: &str) -> Option<&str> {
+    WHOLE_TABLE
+        .captures(query)
+        .and_then(|caps| caps.get(1))
+        .map(|table| table.as_str())
+        .filter(|table| !table.eq_ignore_ascii_case("select"))
+}
+
+/// Progress saved next to the output after every chunk
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Checkpoint {
+    table: String,
+    format: String,
+    key_columns: Vec<String>,
+    /// Key of the last row written
+    last_key: Vec<Value>,
+    rows: u64,
+    /// Length of the output up to that row
+    bytes: u64,
+}
+
+fn checkpoint_path(filename: &str) -> PathBuf {
+    PathBuf::from(format!("{filename}.checkpoint"))
+}
+
+impl Checkpoint {
+    fn load(filename: &str) -> Result<Self, String> {
+        let path = checkpoint_path(filename);
+        let text = std::fs::read_to_string(&path)
+            .map_err(|e| format!("No checkpoint to resume from ({}: {e})", path.display()))?;
+        serde_json::from_str(&text).map_err(|e| format!("Unreadable {}: {e}", path.display()))
+    }
+
+    /// Written to a temporary file first, so a crash leaves the old one
+    fn save(&self, filename: &str) -> std::io::Result<()> {
+        let path = checkpoint_path(filename);
+        let temporary = path.with_extension("checkpoint.tmp");
+        std::fs::write(&temporary, serde_json::to_vec_pretty(self)?)?;
+        std::fs::rename(temporary, path)
+    }
+}
+
+/// Primary key columns of `table` in key order; empty when it has none or
+/// the backend is not one this module looks keys up on
+pub(crate) async fn primary_key(db: &Database, table: &str) -> Result<Vec<String>, DatabaseError> {
+    let unquote = |part: &str| part.trim_matches(|c| c == '"' || c == '`').to_string();
+    let parts: Vec<String> = table.split('.').map(unquote).collect();
+    let name = crate::database::escape_sql_string(parts.last().map_or("", String::as_str));
+    let sql = match db.get_database_type() {
+        DatabaseType::PostgreSQL => format!(
+            "SELECT a.attname FROM pg_index i \
+             JOIN pg_attribute a ON a.attrelid = i.indrelid AND a.attnum = ANY(i.indkey) \
+             WHERE i.indrelid = '{}'::regclass AND i.indisprimary \
+             ORDER BY array_position(i.indkey::int2[], a.attnum)",
+            crate::database::escape_sql_string(table)
+        ),
+        DatabaseType::MySQL => {
+            let schema = match parts.as_slice() {
+                [schema, _] => format!("'{}'", crate::database::escape_sql_string(schema)),
+                _ => "DATABASE()".to_string(),
+            };
+            format!(
+                "SELECT COLUMN_NAME FROM information_schema.KEY_COLUMN_USAGE \
+                 WHERE CONSTRAINT_NAME = 'PRIMARY' AND TABLE_SCHEMA = {schema} \
+                 AND TABLE_NAME = '{name}' ORDER BY ORDINAL_POSITION"
+            )
+        }
+        DatabaseType::SQLite => {
+            format!("SELECT name FROM pragma_table_info('{name}') WHERE pk > 0 ORDER BY pk")
+        }
+        _ => return Ok(Vec::new()),
+    };
+    let client = db
+        .get_database_client()
+        .ok_or_else(|| DatabaseError::ConnectionError("Not connected".to_string()))?;
+    let rows = client.execute_query(&sql).await?;
+    Ok(rows
+        .into_iter()
+        .skip(1)
+        .filter_map(|row| row.into_iter().next())
+        .collect())
+}
+
+/// The statement reading the chunk after `last` (the first one without it)
+fn chunk_query(
+    table: &str,
+    key_columns: &[String],
+    last: Option<&[Value]>,
+    limit: usize,
+    database_type: &DatabaseType,
+) -> String {
+    let keys: Vec<String> = key_columns
+        .iter()
+        .map(|column| quote_identifier(column, database_type))
+        .collect();
+    let literal = |value: &Value| match value {
+        Value::Number(number) => number.to_string(),
+        Value::Bool(flag) => flag.to_string().to_uppercase(),
+        Value::String(text) => quote_literal(text, database_type),
+        other => quote_literal(&other.to_string(), database_type),
+    };
+    // (a > 1) OR (a = 1 AND b > 2): row comparisons are not everywhere
+    let filter = last.map(|values| {
+        (0..keys.len())
+            .map(|depth| {
+                let mut terms: Vec<String> = (0..depth)
+                    .map(|i| format!("{} = {}", keys[i], literal(&values[i])))
+                    .collect();
+                terms.push(format!("{} > {}", keys[depth], literal(&values[depth])));
+                format!("({})", terms.join(" AND "))
+            })
+            .collect::<Vec<_>>()
+            .join(" OR ")
+    });
+    format!(
+        "SELECT * FROM {table}{} ORDER BY {} LIMIT {limit}",
+        filter.map(|f| format!(" WHERE {f}")).unwrap_or_default(),
+        keys.join(", ")
+    )
+}
+
+/// Passes rows on to the writer and remembers the key of the last one
+struct KeyTracker<'a> {
+    inner: &'a mut dyn RowSink,
+    key_columns: &'a [String],
+    positions: Vec<usize>,
+    last_key: Option<Vec<Value>>,
+    rows: u64,
+}
+
+impl RowSink for KeyTracker<'_> {
+    fn columns(&mut self, names: &[String]) -> Result<(), DatabaseError> {
+        self.positions = self
+            .key_columns
+            .iter()
+            .map(|key| {
+                names
+                    .iter()
+                    .position(|name| name.eq_ignore_ascii_case(key))
+                    .ok_or_else(|| {
+                        DatabaseError::QueryError(format!("Key column {key} is not in the result"))
+                    })
+            })
+            .collect::<Result<_, _>>()?;
+        self.inner.columns(names)
+    }
+
+    fn row(&mut self, values: Vec<Value>) -> Result<(), DatabaseError> {
+        self.last_key = Some(self.positions.iter().map(|&i| values[i].clone()).collect());
+        self.rows += 1;
+        self.inner.row(values)
+    }
+}
+
+/// A chunked export of one table to a file
+pub struct TableExport<'a> {
+    pub format: ExportFormat,
+    pub table: &'a str,
+    pub filename: &'a str,
+    pub chunk_rows: usize,
+    pub resume: bool,
+}
+
+/// Export the table in key order, a chunk at a time; `Ok(None)` when it has
+/// no primary key, for the caller to export it with one query instead
+pub async fn export_table(
+    db: &mut Database,
+    export: &TableExport<'_>,
+) -> Result<Option<u64>, String> {
+    let database_type = db.get_database_type();
+    let key_columns = primary_key(db, export.table)
+        .await
+        .map_err(|e| format!("Failed to look up the primary key of {}: {e}", export.table))?;
+    if key_columns.is_empty() {
+        return if export.resume {
+            Err(format!(
+                "--resume needs a table with a primary key ({} has none on {})",
+                export.table,
+                database_type.display_name()
+            ))
+        } else {
+            Ok(None)
+        };
+    }
+
+    let mut checkpoint = if export.resume {
+        let checkpoint = Checkpoint::load(export.filename)?;
+        if checkpoint.table != export.table || checkpoint.format != export.format.to_string() {
+            return Err(format!(
+                "{} was written by an export of {} ({}), not {} ({})",
+                export.filename, checkpoint.table, checkpoint.format, export.table, export.format
+            ));
+        }
+        checkpoint
+    } else {
+        let _ = std::fs::remove_file(checkpoint_path(export.filename));
+        Checkpoint {
+            table: export.table.to_string(),
+            format: export.format.to_string(),
+            key_columns: key_columns.clone(),
+            last_key: Vec::new(),
+            rows: 0,
+            bytes: 0,
+        }
+    };
+
+    let file = std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(!export.resume)
+        .open(export.filename)
+        .map_err(|e| format!("Failed to open {}: {e}", export.filename))?;
+    let mut out = BufWriter::new(file);
+    // Rows after the checkpoint may be half written
+    rewind(&mut out, checkpoint.bytes).map_err(|e| format!("{}: {e}", export.filename))?;
+
+    let show_progress = std::io::stderr().is_terminal();
+    loop {
+        let last = (!checkpoint.last_key.is_empty()).then_some(checkpoint.last_key.as_slice());
+        let sql = chunk_query(
+            export.table,
+            &checkpoint.key_columns,
+            last,
+            export.chunk_rows,
+            &database_type,
+        );
+        let mut attempt = 1;
+        let (rows, last_key) = loop {
+            match read_chunk(db, export.format, &sql, &mut out, &checkpoint.key_columns).await {
+                Ok(chunk) => break chunk,
+                Err(e) => {
+                    let cancelled = crate::database::interrupt_flag()
+                        .load(std::sync::atomic::Ordering::Relaxed);
+                    if cancelled || attempt >= MAX_ATTEMPTS {
+                        if show_progress {
+                            eprint!("\r\x1b[K");
+                        }
+                        let _ = rewind(&mut out, checkpoint.bytes);
+                        return Err(format!(
+                            "{e}. {} row(s) were written; run the same \\export with --resume to continue",
+                            checkpoint.rows
+                        ));
+                    }
+                    tokio::time::sleep(Duration::from_secs(1 << attempt)).await;
+                    if !db.is_connected().await {
+                        let _ = db.reconnect().await;
+                    }
+                    rewind(&mut out, checkpoint.bytes)
+                        .map_err(|e| format!("{}: {e}", export.filename))?;
+                    attempt += 1;
+                }
+            }
+        };
+        let Some(last_key) = last_key else {
+            break;
+        };
+        out.flush()
+            .map_err(|e| format!("Failed to write {}: {e}", export.filename))?;
+        checkpoint.rows += rows;
+        checkpoint.last_key = last_key;
+        checkpoint.bytes = out
+            .stream_position()
+            .map_err(|e| format!("{}: {e}", export.filename))?;
+        checkpoint
+            .save(export.filename)
+            .map_err(|e| format!("Failed to save the checkpoint: {e}"))?;
+        if show_progress {
+            eprint!(
+                "\r{} rows exported...",
+                crate::query_progress::group_digits(checkpoint.rows as i64)
+            );
+        }
+        if rows < export.chunk_rows as u64 {
+            break;
+        }
+    }
+    if show_progress {
+        eprint!("\r\x1b[K");
+    }
+    out.flush()
+        .map_err(|e| format!("Failed to write {}: {e}", export.filename))?;
+    let _ = std::fs::remove_file(checkpoint_path(export.filename));
+    Ok(Some(checkpoint.rows))
+}
+
+/// Cut the output back to `bytes` and write from there
+fn rewind(out: &mut BufWriter<std::fs::File>, bytes: u64) -> std::io::Result<()> {
+    out.flush()?;
+    out.get_ref().set_len(bytes)?;
+    out.seek(SeekFrom::Start(bytes))?;
+    Ok(())
+}
+
+/// Run one chunk's statement into `out`; the row count and last key
+async fn read_chunk(
+    db: &mut Database,
+    format: ExportFormat,
+    sql: &str,
+    out: &mut BufWriter<std::fs::File>,
+    key_columns: &[String],
+) -> crate::error::Result<(u64, Option<Vec<Value>>)> {
+    match format {
+        ExportFormat::Ndjson => {
+            let mut writer = NdjsonWriter::new(out);
+            let mut tracker = KeyTracker {
+                inner: &mut writer,
+                key_columns,
+                positions: Vec::new(),
+                last_key: None,
+                rows: 0,
+            };
+            db.stream_query(sql, &mut tracker).await?;
+            let (rows, last_key) = (tracker.rows, tracker.last_key);
+            writer.finish()?;
+            Ok((rows, last_key))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_whole_table() {
+        assert_eq!(whole_table("events"), Some("events"));
+        assert_eq!(
+            whole_table("select * from public.\"Events\""),
+            Some("public.\"Events\"")
+        );
+        assert_eq!(
+            whole_table("SELECT *\nFROM `shop`.`orders`"),
+            Some("`shop`.`orders`")
+        );
+        assert_eq!(whole_table("SELECT * FROM events WHERE id > 3"), None);
+        assert_eq!(whole_table("SELECT id FROM events"), None);
+        assert_eq!(whole_table("SELECT"), None);
+    }
+
+    #[test]
+    fn test_chunk_query() {
+        let keys = vec!["id".to_string()];
+        assert_eq!(
+            chunk_query("events", &keys, None, 1000, &DatabaseType::PostgreSQL),
+            "SELECT * FROM events ORDER BY \"id\" LIMIT 1000"
+        );
+        assert_eq!(
+            chunk_query(
+                "events",
+                &keys,
+                Some(&[json!(42)]),
+                1000,
+                &DatabaseType::PostgreSQL
+            ),
+            "SELECT * FROM events WHERE (\"id\" > 42) ORDER BY \"id\" LIMIT 1000"
+        );
+        let keys = vec!["tenant".to_string(), "id".to_string()];
+        assert_eq!(
+            chunk_query(
+                "orders",
+                &keys,
+                Some(&[json!("o'neil"), json!(7)]),
+                10,
+                &DatabaseType::MySQL
+            ),
+            "SELECT * FROM orders WHERE (`tenant` > 'o''neil') OR (`tenant` = 'o''neil' AND `id` > 7) \
+             ORDER BY `tenant`, `id` LIMIT 10"
+        );
+    }
+
+    #[test]
+    fn test_key_tracker_and_checkpoint() {
+        let mut writer = NdjsonWriter::new(Vec::new());
+        let keys = vec!["ID".to_string()];
+        let mut tracker = KeyTracker {
+            inner: &mut writer,
+            key_columns: &keys,
+            positions: Vec::new(),
+            last_key: None,
+            rows: 0,
+        };
+        tracker
+            .columns(&["name".to_string(), "id".to_string()])
+            .unwrap();
+        tracker.row(vec![json!("a"), json!(1)]).unwrap();
+        tracker.row(vec![json!("b"), json!(2)]).unwrap();
+        assert_eq!(tracker.rows, 2);
+        assert_eq!(tracker.last_key, Some(vec![json!(2)]));
+        assert!(tracker.columns(&["name".to_string()]).is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let filename = dir.path().join("events.ndjson");
+        let filename = filename.to_str().unwrap();
+        let checkpoint = Checkpoint {
+            table: "events".to_string(),
+            format: "ndjson".to_string(),
+            key_columns: keys.clone(),
+            last_key: vec![json!(2)],
+            rows: 2,
+            bytes: 30,
+        };
+        checkpoint.save(filename).unwrap();
+        assert_eq!(Checkpoint::load(filename).unwrap(), checkpoint);
+        assert!(Checkpoint::load("missing.ndjson").is_err());
+    }
+}
@@ -0,0 +1,583 @@
+//! Cross-database joins without ETL tooling (`\fedjoin`)
+//!
+//! A query runs on another connection (a URL or a saved session) and its
+//! result is materialized where it can be joined:
+//! - on the current PostgreSQL connection, as a TEMP table; the pinned
+//!   session connection keeps it until the REPL exits or `\fedjoin clear`
+//! - with `--local` (and for every other backend, whose pools give no
+//!   guarantee a temp table is seen by the next statement), in an in-memory
+//!   SQLite scratch database shared by all local loads and queried with
+//!   `\fedjoin run`. `.` as the source loads from the current connection, so
+//!   both sides of a reconciliation can be brought together there.
+//!
+//! `\scratch` works on the same scratch database: it opens it without a
+//! load, imports the last displayed result into it and runs SQL there.
+//!
+//! Column types are inferred from the values: integers, floats and booleans
+//! keep their type, everything else (including JSON) becomes text.
+
+use crate::database::{DatabaseClient, DatabaseError, RowSink};
+use serde_json::Value;
+use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
+use sqlx::{Column, Row};
+use std::fmt;
+
+/// Rows per INSERT statement while loading
+const BATCH_ROWS: usize = 500;
+/// Rows a load may hold in memory; bigger sources must be filtered first
+pub const MAX_ROWS: usize = 1_000_000;
+
+/// Where a `\fedjoin` result was materialized
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    /// TEMP table on the current PostgreSQL connection
+    Session,
+    /// Table in the in-memory SQLite scratch database
+    Local,
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Target::Session => write!(f, "temp table"),
+            Target::Local => write!(f, "local scratch"),
+        }
+    }
+}
+
+/// A materialized result, as listed by `\fedjoin`
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadedTable {
+    pub name: String,
+    pub target: Target,
+    /// Source connection without its password
+    pub source: String,
+    pub rows: u64,
+    pub columns: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnKind {
+    Integer,
+    Real,
+    Boolean,
+    Text,
+}
+
+impl ColumnKind {
+    /// Kind of one value; `None` for NULL, which fits any column
+    fn of(value: &Value) -> Option<Self> {
+        match value {
+            Value::Null => None,
+            Value::Bool(_) => Some(ColumnKind::Boolean),
+            Value::Number(n) if n.is_i64() || n.is_u64() => Some(ColumnKind::Integer),
+            Value::Number(_) => Some(ColumnKind::Real),
+            // Backends without typed streaming send strings: keep canonical
+            // integers joinable, but not codes with leading zeros
+            Value::String(s) if s.parse::<i64>().is_ok_and(|n| n.to_string() == *s) => {
+                Some(ColumnKind::Integer)
+            }
+            _ => Some(ColumnKind::Text),
+        }
+    }
+
+    fn merge(self, other: Self) -> Self {
+        match (self, other) {
+            (a, b) if a == b => a,
+            (ColumnKind::Integer, ColumnKind::Real) | (ColumnKind::Real, ColumnKind::Integer) => {
+                ColumnKind::Real
+            }
+            _ => ColumnKind::Text,
+        }
+    }
+
+    fn sql_type(self, target: Target) -> &'static str {
+        match (self, target) {
+            (ColumnKind::Integer, Target::Session) => "BIGINT",
+            (ColumnKind::Real, Target::Session) => "DOUBLE PRECISION",
+            (ColumnKind::Boolean, Target::Session) => "BOOLEAN",
+            (ColumnKind::Integer | ColumnKind::Boolean, Target::Local) => "INTEGER",
+            (ColumnKind::Real, Target::Local) => "REAL",
+            (ColumnKind::Text, _) => "TEXT",
+        }
+    }
+}
+
+/// A result set held in memory until it is loaded
+#[derive(Debug)]
+pub struct Collected {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+    max_rows: usize,
+}
+
+impl Default for Collected {
+    fn default() -> Self {
+        Self {
+            columns: Vec::new(),
+            rows: Vec::new(),
+            max_rows: MAX_ROWS,
+        }
+    }
+}
+
+impl RowSink for Collected {
+    fn columns(&mut self, names: &[String]) -> Result<(), DatabaseError> {
+        self.columns = names.to_vec();
+        Ok(())
+    }
+
+    fn row(&mut self, values: Vec<Value>) -> Result<(), DatabaseError> {
+        if self.rows.len() >= self.max_rows {
+            return Err(DatabaseError::QueryError(format!(
+                "more than {} rows; the result is held in memory while it loads, so filter or aggregate it on the source side",
+                self.max_rows
+            )));
+        }
+        self.rows.push(values);
+        Ok(())
+    }
+}
+
+impl Collected {
+    /// A result as displayed (header first, `NULL` for nulls), for
+    /// `\scratch import`; `None` without a header
+    pub fn from_displayed(rows: &[Vec<String>]) -> Option<Self> {
+        let (header, rows) = rows.split_first()?;
+        Some(Self {
+            columns: header.clone(),
+            rows: rows
+                .iter()
+                .map(|row| row.iter().map(|text| displayed_value(text)).collect())
+                .collect(),
+            ..Default::default()
+        })
+    }
+
+    fn kinds(&self) -> Vec<ColumnKind> {
+        (0..self.columns.len())
+            .map(|i| {
+                self.rows
+                    .iter()
+                    .filter_map(|row| row.get(i).and_then(ColumnKind::of))
+                    .reduce(ColumnKind::merge)
+                    .unwrap_or(ColumnKind::Text)
+            })
+            .collect()
+    }
+
+    /// Column names made unique (`SELECT a.id, b.id` gives `id`, `id_2`)
+    fn unique_columns(&self) -> Vec<String> {
+        let mut seen: Vec<String> = Vec::with_capacity(self.columns.len());
+        for (i, column) in self.columns.iter().enumerate() {
+            let base = if column.is_empty() {
+                format!("column{}", i + 1)
+            } else {
+                column.clone()
+            };
+            let mut name = base.clone();
+            let mut suffix = 2;
+            while seen.iter().any(|s| s.eq_ignore_ascii_case(&name)) {
+                name = format!("{base}_{suffix}");
+                suffix += 1;
+            }
+            seen.push(name);
+        }
+        seen
+    }
+
+    /// `CREATE` and batched `INSERT` statements materializing the rows as `name`
+    pub fn load_statements(&self, name: &str, target: Target) -> Vec<String> {
+        let table = quote_ident(name);
+        let kinds = self.kinds();
+        let columns = self.unique_columns();
+        let definitions = columns
+            .iter()
+            .zip(&kinds)
+            .map(|(column, kind)| format!("{} {}", quote_ident(column), kind.sql_type(target)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let create = match target {
+            Target::Session => format!("CREATE TEMP TABLE {table} ({definitions})"),
+            Target::Local => format!("CREATE TABLE {table} ({definitions})"),
+        };
+
+        let column_list = columns
+            .iter()
+            .map(|column| quote_ident(column))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let inserts = self.rows.chunks(BATCH_ROWS).map(|batch| {
+            let values = batch
+                .iter()
+                .map(|row| {
+                    let literals = kinds
+                        .iter()
+                        .enumerate()
+                        .map(|(i, kind)| literal(row.get(i).unwrap_or(&Value::Null), *kind, target))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    format!("({literals})")
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("INSERT INTO {table} ({column_list}) VALUES {values}")
+        });
+        std::iter::once(create).chain(inserts).collect()
+    }
+}
+
+/// A displayed value with its number type back; values with leading zeros
+/// are codes (zip, phone) and stay text
+fn displayed_value(text: &str) -> Value {
+    if text == "NULL" {
+        return Value::Null;
+    }
+    let digits = text.strip_prefix('-').unwrap_or(text);
+    if !(digits.len() > 1 && digits.starts_with('0') && !digits.starts_with("0.")) {
+        if let Ok(n) = text.parse::<i64>() {
+            return Value::from(n);
+        }
+        let numeric = text.contains('.')
+            && text
+                .chars()
+                .all(|c| c.is_ascii_digit() || matches!(c, '-' | '.' | 'e' | 'E'));
+        if let Some(n) = numeric
+            .then(|| text.parse::<f64>().ok())
+            .flatten()
+            .and_then(serde_json::Number::from_f64)
+        {
+            return Value::Number(n);
+        }
+    }
+    Value::String(text.to_string())
+}
+
+/// Statement dropping a previous load of `name`
+pub fn drop_statement(name: &str, target: Target) -> String {
+    match target {
+        // pg_temp: never drop a permanent table of the same name
+        Target::Session => format!("DROP TABLE IF EXISTS pg_temp.{}", quote_ident(name)),
+        Target::Local => format!("DROP TABLE IF EXISTS {}", quote_ident(name)),
+    }
+}
+
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn quote_text(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+fn literal(value: &Value, kind: ColumnKind, target: Target) -> String {
+    match (value, kind) {
+        (Value::Null, _) => "NULL".to_string(),
+        (Value::Bool(b), ColumnKind::Boolean) => match target {
+            Target::Session => if *b { "TRUE" } else { "FALSE" }.to_string(),
+            Target::Local => u8::from(*b).to_string(),
+        },
+        (Value::Number(n), ColumnKind::Integer | ColumnKind::Real) => n.to_string(),
+        (Value::String(s), ColumnKind::Integer) => s.clone(),
+        (Value::String(s), _) => quote_text(s),
+        (other, _) => quote_text(&other.to_string()),
+    }
+}
+
+/// Connection URL and display label for a `\fedjoin` source: a URL, an
+/// `[aliases]` name, a `session://` URL or a bare saved session name
+pub fn resolve_source(
+    source: &str,
+    config: &crate::config::Config,
+) -> Result<(String, String), String> {
+    let name = source.strip_prefix("session://").unwrap_or(source);
+    if name.contains("://") {
+        return Ok((
+            source.to_string(),
+            crate::password_sanitizer::sanitize_connection_url(source),
+        ));
+    }
+    if name == source
+        && let Some(url) = config.resolve_alias(name)
+    {
+        let url = crate::config::expand_env_in_url(url)?;
+        return Ok((url, name.to_string()));
+    }
+    let session = config.get_session(name).ok_or_else(|| {
+        format!("'{name}' is neither a connection URL, an alias nor a saved session (see \\s)")
+    })?;
+    // Resolves the session's password command or `.dbcrust` entry, as `dbcrust check` does
+    Ok((
+        crate::session_check::connection_url(session)?,
+        format!("session://{name}"),
+    ))
+}
+
+/// Materialize `collected` as a TEMP table `name` on the current PostgreSQL
+/// connection; a failed load drops what was created
+pub async fn load_session(
+    client: &dyn DatabaseClient,
+    collected: &Collected,
+    name: &str,
+) -> Result<(), DatabaseError> {
+    // Checked first: dropping from a pg_temp that does not exist yet raises a notice
+    let existing = format!(
+        "SELECT 1 WHERE to_regclass({}) IS NOT NULL",
+        quote_text(&format!("pg_temp.{}", quote_ident(name)))
+    );
+    if !client.execute_query(&existing).await?.is_empty() {
+        client
+            .execute_query(&drop_statement(name, Target::Session))
+            .await?;
+    }
+    for statement in collected.load_statements(name, Target::Session) {
+        if let Err(e) = client.execute_query(&statement).await {
+            let _ = client
+                .execute_query(&drop_statement(name, Target::Session))
+                .await;
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+struct Scratch {
+    pool: Option<SqlitePool>,
+    tables: Vec<LoadedTable>,
+}
+
+static SCRATCH: tokio::sync::Mutex<Scratch> = tokio::sync::Mutex::const_new(Scratch {
+    pool: None,
+    tables: Vec::new(),
+});
+
+/// The in-memory scratch database, created on first use. A single
+/// connection that is never recycled, since the data lives in it.
+async fn scratch_pool(scratch: &mut Scratch) -> Result<SqlitePool, DatabaseError> {
+    if let Some(pool) = &scratch.pool {
+        return Ok(pool.clone());
+    }
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .min_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect("sqlite::memory:")
+        .await?;
+    scratch.pool = Some(pool.clone());
+    Ok(pool)
+}
+
+/// Materialize `collected` as `name` in the local scratch database
+pub async fn load_local(collected: &Collected, name: &str) -> Result<(), DatabaseError> {
+    let mut scratch = SCRATCH.lock().await;
+    let pool = scratch_pool(&mut scratch).await?;
+    let mut tx = pool.begin().await?;
+    sqlx::query(&drop_statement(name, Target::Local))
+        .execute(&mut *tx)
+        .await?;
+    for statement in collected.load_statements(name, Target::Local) {
+        sqlx::query(&statement).execute(&mut *tx).await?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Create the scratch database if it is not open yet (`\scratch`)
+pub async fn open_local() -> Result<(), DatabaseError> {
+    let mut scratch = SCRATCH.lock().await;
+    scratch_pool(&mut scratch).await.map(|_| ())
+}
+
+/// Tables of the scratch database with their row counts, `None` when it is
+/// not open
+pub async fn local_tables() -> Result<Option<Vec<(String, i64)>>, DatabaseError> {
+    let Some(pool) = SCRATCH.lock().await.pool.clone() else {
+        return Ok(None);
+    };
+    let names: Vec<String> = sqlx::query_scalar(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name",
+    )
+    .fetch_all(&pool)
+    .await?;
+    let mut tables = Vec::with_capacity(names.len());
+    for name in names {
+        let rows: i64 = sqlx::query_scalar(&format!("SELECT count(*) FROM {}", quote_ident(&name)))
+            .fetch_one(&pool)
+            .await?;
+        tables.push((name, rows));
+    }
+    Ok(Some(tables))
+}
+
+/// Run `sql` on the local scratch database; header row first
+pub async fn run_local(sql: &str) -> Result<Vec<Vec<String>>, DatabaseError> {
+    let mut scratch = SCRATCH.lock().await;
+    if scratch.pool.is_none() {
+        return Err(DatabaseError::QueryError(
+            "Nothing loaded locally yet. Use \\fedjoin --local <name> <source> <query> or \\scratch import <name> first."
+                .to_string(),
+        ));
+    }
+    let pool = scratch_pool(&mut scratch).await?;
+    let rows = sqlx::query(sql).fetch_all(&pool).await?;
+    with_header(&rows)
+}
+
+/// SQLite rows as display strings, header row first; empty without rows
+pub(crate) fn with_header(
+    rows: &[sqlx::sqlite::SqliteRow],
+) -> Result<Vec<Vec<String>>, DatabaseError> {
+    let Some(first) = rows.first() else {
+        return Ok(vec![]);
+    };
+    let mut results = vec![
+        first
+            .columns()
+            .iter()
+            .map(|column| column.name().to_string())
+            .collect::<Vec<_>>(),
+    ];
+    for row in rows {
+        results.push(
+            (0..row.len())
+                .map(|i| crate::database_sqlite::format_sqlite_value(row, i))
+                .collect::<Result<_, _>>()?,
+        );
+    }
+    Ok(results)
+}
+
+/// Remember a load, replacing an earlier one of the same name and target
+pub async fn record(table: LoadedTable) {
+    let mut scratch = SCRATCH.lock().await;
+    scratch
+        .tables
+        .retain(|t| !(t.name == table.name && t.target == table.target));
+    scratch.tables.push(table);
+}
+
+/// Every load of this REPL session, oldest first
+pub async fn loaded() -> Vec<LoadedTable> {
+    SCRATCH.lock().await.tables.clone()
+}
+
+/// Forget every load and drop the scratch database; returns the loads so the
+/// caller can drop the temp tables
+pub async fn clear() -> Vec<LoadedTable> {
+    let mut scratch = SCRATCH.lock().await;
+    if let Some(pool) = scratch.pool.take() {
+        pool.close().await;
+    }
+    std::mem::take(&mut scratch.tables)
+}
+
+/// Drop the scratch database and forget what was loaded into it, keeping
+/// the temp table loads; `false` when it was not open
+pub async fn clear_local() -> bool {
+    let mut scratch = SCRATCH.lock().await;
+    scratch.tables.retain(|table| table.target != Target::Local);
+    match scratch.pool.take() {
+        Some(pool) => {
+            pool.close().await;
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn collected(columns: &[&str], rows: Vec<Vec<Value>>) -> Collected {
+        Collected {
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+            rows,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_load_statements_infer_types_and_quote() {
+        let data = collected(
+            &["id", "id", "amount", "paid", "note"],
+            vec![
+                vec![json!(1), json!("7"), json!(9.5), json!(true), json!("it's")],
+                vec![
+                    json!(2),
+                    json!("007"),
+                    json!(3),
+                    Value::Null,
+                    json!({"a": 1}),
+                ],
+            ],
+        );
+        let statements = data.load_statements("orders", Target::Session);
+        assert_eq!(
+            statements[0],
+            "CREATE TEMP TABLE \"orders\" (\"id\" BIGINT, \"id_2\" TEXT, \"amount\" DOUBLE PRECISION, \"paid\" BOOLEAN, \"note\" TEXT)"
+        );
+        assert_eq!(
+            statements[1],
+            "INSERT INTO \"orders\" (\"id\", \"id_2\", \"amount\", \"paid\", \"note\") VALUES \
+             (1, '7', 9.5, TRUE, 'it''s'), (2, '007', 3, NULL, '{\"a\":1}')"
+        );
+
+        let local = data.load_statements("orders", Target::Local);
+        assert!(local[0].starts_with("CREATE TABLE \"orders\" (\"id\" INTEGER"));
+        assert!(local[1].contains("9.5, 1, 'it''s'"));
+    }
+
+    #[test]
+    fn test_from_displayed_restores_numbers() {
+        let displayed = [
+            vec!["id", "zip", "price", "note"],
+            vec!["1", "01234", "9.50", "NULL"],
+            vec!["-2", "75001", "1e3", "1.2.3"],
+        ]
+        .map(|row| row.iter().map(|v| v.to_string()).collect::<Vec<_>>());
+        let data = Collected::from_displayed(&displayed).unwrap();
+        assert_eq!(data.columns, ["id", "zip", "price", "note"]);
+        assert_eq!(
+            data.rows[0],
+            vec![json!(1), json!("01234"), json!(9.5), Value::Null]
+        );
+        assert_eq!(
+            data.rows[1],
+            vec![json!(-2), json!(75001), json!("1e3"), json!("1.2.3")]
+        );
+        assert!(Collected::from_displayed(&[]).is_none());
+    }
+
+    #[test]
+    fn test_collected_stops_at_row_cap() {
+        let mut data = Collected {
+            max_rows: 2,
+            ..Default::default()
+        };
+        data.columns(&["id".to_string()]).unwrap();
+        data.row(vec![json!(1)]).unwrap();
+        data.row(vec![json!(2)]).unwrap();
+        let err = data.row(vec![json!(3)]).unwrap_err().to_string();
+        assert!(err.contains("more than 2 rows"), "{err}");
+        assert_eq!(data.rows.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_local_scratch_round_trip() {
+        let data = collected(
+            &["id", "name"],
+            (1..=BATCH_ROWS as i64 + 1)
+                .map(|i| vec![json!(i), json!(format!("n{i}"))])
+                .collect(),
+        );
+        load_local(&data, "people").await.unwrap();
+        let results = run_local("SELECT count(*) AS n, max(id) FROM people")
+            .await
+            .unwrap();
+        assert_eq!(results[0][0], "n");
+        assert_eq!(results[1], vec!["501", "501"]);
+    }
+}
@@ -0,0 +1,273 @@
+//! Row and byte guards for fetches made by embedders (the Python bindings)
+//!
+//! [`LimitedRows`] collects a result while it streams in and stops reading
+//! the moment it passes `max_rows` rows or `max_bytes` bytes of cell text,
+//! so an unexpectedly large result fails fast instead of filling memory.
+//! [`RowChannel`] hands rows to another thread through a bounded channel for
+//! results too large to hold at all (`fetch_iter()`).
+
+use crate::database::{DatabaseError, RowSink};
+use serde_json::Value;
+use std::fmt;
+use std::sync::mpsc::SyncSender;
+
+/// Caps on one fetch; `None` is unlimited
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FetchLimits {
+    pub max_rows: Option<usize>,
+    pub max_bytes: Option<usize>,
+}
+
+impl FetchLimits {
+    pub fn is_unlimited(&self) -> bool {
+        self.max_rows.is_none() && self.max_bytes.is_none()
+    }
+
+    /// `self` with the limits set in `overrides` replacing its own
+    pub fn overridden_by(self, overrides: FetchLimits) -> FetchLimits {
+        FetchLimits {
+            max_rows: overrides.max_rows.or(self.max_rows),
+            max_bytes: overrides.max_bytes.or(self.max_bytes),
+        }
+    }
+}
+
+impl FetchLimits {
+    /// The limit a complete result (rows without the header) passes, for a
+    /// statement that was not streamed
+    pub fn exceeded_by(&self, rows: &[Vec<String>]) -> Option<LimitExceeded> {
+        if let Some(max) = self.max_rows
+            && rows.len() > max
+        {
+            return Some(LimitExceeded::Rows(max));
+        }
+        let bytes: usize = rows.iter().flatten().map(String::len).sum();
+        self.max_bytes
+            .filter(|&max| bytes > max)
+            .map(LimitExceeded::Bytes)
+    }
+}
+
+/// Whether a limited fetch of `sql` is streamed: only a single statement
+/// that just reads rows. Others (DML, procedures with several result sets)
+/// run normally, so their row count, insert id and extra result sets are
+/// kept, and are checked with [`FetchLimits::exceeded_by`] afterwards.
+pub fn streams(sql: &str) -> bool {
+    crate::ai::streaming::is_select_query(sql)
+}
+
+/// Which limit a fetch ran into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitExceeded {
+    Rows(usize),
+    Bytes(usize),
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitExceeded::Rows(max) => {
+                write!(f, "Result has more than max_rows={max} rows; fetch aborted")
+            }
+            LimitExceeded::Bytes(max) => {
+                write!(
+                    f,
+                    "Result is larger than max_bytes={max} bytes; fetch aborted"
+                )
+            }
+        }
+    }
+}
+
+/// A value as a cell shows it, `NULL` for SQL NULL
+pub fn cell_text(value: Value) -> String {
+    match value {
+        Value::Null => "NULL".to_string(),
+        Value::String(text) => text,
+        other => other.to_string(),
+    }
+}
+
+/// Collects rows as text until a limit is passed
+#[derive(Debug, Default)]
+pub struct LimitedRows {
+    limits: FetchLimits,
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    bytes: usize,
+    /// Set when the fetch stopped at a limit
+    pub exceeded: Option<LimitExceeded>,
+}
+
+impl LimitedRows {
+    pub fn new(limits: FetchLimits) -> Self {
+        Self {
+            limits,
+            ..Default::default()
+        }
+    }
+
+    /// Bytes of cell text collected so far
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    fn stop(&mut self, limit: LimitExceeded) -> Result<(), DatabaseError> {
+        self.exceeded = Some(limit);
+        // Dropping what was read keeps the memory bounded by the limit
+        self.rows = Vec::new();
+        Err(DatabaseError::QueryError(limit.to_string()))
+    }
+}
+
+impl RowSink for LimitedRows {
+    fn columns(&mut self, names: &[String]) -> Result<(), DatabaseError> {
+        self.columns = names.to_vec();
+        Ok(())
+    }
+
+    fn row(&mut self, values: Vec<Value>) -> Result<(), DatabaseError> {
+        if let Some(max) = self.limits.max_rows
+            && self.rows.len() >= max
+        {
+            return self.stop(LimitExceeded::Rows(max));
+        }
+        let row: Vec<String> = values.into_iter().map(cell_text).collect();
+        self.bytes += row.iter().map(String::len).sum::<usize>();
+        if let Some(max) = self.limits.max_bytes
+            && self.bytes > max
+        {
+            return self.stop(LimitExceeded::Bytes(max));
+        }
+        self.rows.push(row);
+        Ok(())
+    }
+}
+
+/// What a streamed fetch sends to its reader, in order: the columns, the
+/// rows, then how it ended
+#[derive(Debug)]
+pub enum Streamed {
+    Columns(Vec<String>),
+    Row(Vec<String>),
+    Done(Result<u64, String>),
+}
+
+/// Sends rows to a reader on another thread; the bounded channel makes the
+/// query wait while the reader is behind, and a reader that went away stops it
+pub struct RowChannel {
+    sender: SyncSender<Streamed>,
+}
+
+impl RowChannel {
+    pub fn new(sender: SyncSender<Streamed>) -> Self {
+        Self { sender }
+    }
+
+    fn send(&self, message: Streamed) -> Result<(), DatabaseError> {
+        // Waiting on a full channel must not stall the runtime's other tasks
+        tokio::task::block_in_place(|| self.sender.send(message))
+            .map_err(|_| DatabaseError::QueryError("Row reader went away".to_string()))
+    }
+}
+
+impl RowSink for RowChannel {
+    fn columns(&mut self, names: &[String]) -> Result<(), DatabaseError> {
+        self.send(Streamed::Columns(names.to_vec()))
+    }
+
+    fn row(&mut self, values: Vec<Value>) -> Result<(), DatabaseError> {
+        self.send(Streamed::Row(values.into_iter().map(cell_text).collect()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed(sink: &mut LimitedRows, rows: usize) -> Result<(), DatabaseError> {
+        sink.columns(&["id".to_string(), "name".to_string()])?;
+        for i in 0..rows {
+            sink.row(vec![Value::from(i), Value::from("abcd")])?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_limited_rows() {
+        let mut unlimited = LimitedRows::new(FetchLimits::default());
+        feed(&mut unlimited, 3).unwrap();
+        assert_eq!(unlimited.rows[2], ["2", "abcd"]);
+        assert_eq!(unlimited.bytes(), 15);
+
+        let mut rows = LimitedRows::new(FetchLimits {
+            max_rows: Some(3),
+            max_bytes: None,
+        });
+        feed(&mut rows, 3).unwrap();
+        assert!(feed(&mut rows, 1).is_err());
+        assert_eq!(rows.exceeded, Some(LimitExceeded::Rows(3)));
+        assert!(rows.rows.is_empty());
+
+        let mut bytes = LimitedRows::new(FetchLimits {
+            max_rows: None,
+            max_bytes: Some(10),
+        });
+        assert!(feed(&mut bytes, 3).is_err());
+        assert_eq!(bytes.exceeded, Some(LimitExceeded::Bytes(10)));
+        assert_eq!(
+            LimitExceeded::Bytes(10).to_string(),
+            "Result is larger than max_bytes=10 bytes; fetch aborted"
+        );
+    }
+
+    #[test]
+    fn test_statements_changing_data_are_not_streamed() {
+        assert!(streams("SELECT * FROM t"));
+        assert!(streams("WITH x AS (SELECT 1) SELECT * FROM x"));
+        // Their rowcount comes from the statement outcome, which only the
+        // non-streamed path has
+        assert!(!streams("UPDATE t SET a = 1"));
+        assert!(!streams("INSERT INTO t VALUES (1) RETURNING id"));
+        assert!(!streams("CALL report()"));
+
+        let limits = FetchLimits {
+            max_rows: Some(2),
+            max_bytes: Some(8),
+        };
+        let row = vec!["abc".to_string()];
+        assert_eq!(limits.exceeded_by(&[]), None);
+        assert_eq!(limits.exceeded_by(&[row.clone(), row.clone()]), None);
+        assert_eq!(
+            limits.exceeded_by(&[row.clone(), row.clone(), row.clone()]),
+            Some(LimitExceeded::Rows(2))
+        );
+        assert_eq!(
+            limits.exceeded_by(&[vec!["123456789".to_string()]]),
+            Some(LimitExceeded::Bytes(8))
+        );
+        assert_eq!(FetchLimits::default().exceeded_by(&vec![row; 5]), None);
+    }
+
+    #[test]
+    fn test_overridden_by() {
+        let connection = FetchLimits {
+            max_rows: Some(1000),
+            max_bytes: Some(1 << 20),
+        };
+        let call = FetchLimits {
+            max_rows: Some(10),
+            max_bytes: None,
+        };
+        assert_eq!(
+            connection.overridden_by(call),
+            FetchLimits {
+                max_rows: Some(10),
+                max_bytes: Some(1 << 20),
+            }
+        );
+        assert!(FetchLimits::default().is_unlimited());
+        assert_eq!(cell_text(Value::Null), "NULL");
+        assert_eq!(cell_text(Value::Bool(true)), "true");
+    }
+}
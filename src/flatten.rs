@@ -0,0 +1,350 @@
+//! Nested documents as flat tables (`\flatten`)
+//!
+//! MongoDB and Elasticsearch results keep nested objects and arrays in one
+//! cell. `\flatten` runs the last query again with its values typed and turns
+//! every nested field into a column named by its dotted path
+//! (`address.city`), down to a chosen depth; deeper values stay compact JSON.
+//! Arrays are handled one of three ways:
+//!
+//! - `first`: the first element only
+//! - `join`: the elements joined with `, `, per field for arrays of objects
+//! - `explode`: one row per element, repeating the other columns
+//!
+//! A depth and array mode can be saved per collection in `flatten.json` in
+//! the config directory; `\flatten` alone then uses them.
+
+use crate::database::{DatabaseError, RowSink};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Rows `explode` may produce from one document
+const MAX_EXPLODED_ROWS: usize = 1000;
+
+/// What becomes of array values
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArrayMode {
+    #[default]
+    First,
+    Join,
+    Explode,
+}
+
+impl FromStr for ArrayMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "first" => Ok(ArrayMode::First),
+            "join" => Ok(ArrayMode::Join),
+            "explode" => Ok(ArrayMode::Explode),
+            other => Err(format!(
+                "Unknown array mode '{other}': use first, join or explode"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ArrayMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ArrayMode::First => "first",
+            ArrayMode::Join => "join",
+            ArrayMode::Explode => "explode",
+        })
+    }
+}
+
+/// How deep to flatten and what to do with arrays
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlattenSpec {
+    /// Nesting levels turned into columns; 0 flattens everything
+    #[serde(default)]
+    pub depth: usize,
+    #[serde(default)]
+    pub arrays: ArrayMode,
+}
+
+impl fmt::Display for FlattenSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.depth {
+            0 => write!(f, "all levels, arrays: {}", self.arrays),
+            depth => write!(f, "depth {depth}, arrays: {}", self.arrays),
+        }
+    }
+}
+
+/// Collection a MongoDB or Elasticsearch query reads:
+/// `db.<collection>.find(..)` or `SELECT .. FROM <collection>`
+pub fn collection_of(query: &str) -> Option<String> {
+    let query = query.trim();
+    if let Some(rest) = query.strip_prefix("db.") {
+        let end = rest.find(['.', '(', ' ', ';']).unwrap_or(rest.len());
+        return Some(rest[..end].to_string()).filter(|name| !name.is_empty());
+    }
+    let re = regex::Regex::new(r#"(?is)\bFROM\s+["`]?([^\s"`;,()]+)"#).unwrap();
+    re.captures(query).map(|captures| captures[1].to_string())
+}
+
+/// Flattened row fragments: each alternative is one output row
+type Fragments = Vec<Vec<(String, String)>>;
+
+/// A leaf value as shown in a cell
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => "NULL".to_string(),
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Every pairing of the alternatives in `left` and `right`
+fn combine(left: Fragments, right: Fragments) -> Fragments {
+    let mut out = Vec::with_capacity(left.len() * right.len());
+    for l in &left {
+        for r in &right {
+            if out.len() >= MAX_EXPLODED_ROWS {
+                return out;
+            }
+            out.push(l.iter().chain(r).cloned().collect());
+        }
+    }
+    out
+}
+
+fn flatten_value(path: &str, value: &Value, level: usize, spec: &FlattenSpec) -> Fragments {
+    let expand = spec.depth == 0 || level <= spec.depth;
+    match value {
+        Value::Object(fields) if expand && !fields.is_empty() => {
+            fields.iter().fold(vec![Vec::new()], |rows, (key, value)| {
+                combine(
+                    rows,
+                    flatten_value(&format!("{path}.{key}"), value, level + 1, spec),
+                )
+            })
+        }
+        Value::Array(items) if expand && items.is_empty() => {
+            vec![vec![(path.to_string(), "NULL".to_string())]]
+        }
+        Value::Array(items) if expand => match spec.arrays {
+            ArrayMode::First => flatten_value(path, &items[0], level, spec),
+            ArrayMode::Explode => items
+                .iter()
+                .flat_map(|item| flatten_value(path, item, level, spec))
+                .take(MAX_EXPLODED_ROWS)
+                .collect(),
+            ArrayMode::Join => {
+                // Values of each sub-field across the elements
+                let mut joined: Vec<(String, Vec<String>)> = Vec::new();
+                for item in items {
+                    let first = flatten_value(
+                        path,
+                        item,
+                        level,
+                        &FlattenSpec {
+                            arrays: ArrayMode::Join,
+                            ..*spec
+                        },
+                    );
+                    for (column, text) in first.into_iter().next().unwrap_or_default() {
+                        match joined.iter_mut().find(|(name, _)| *name == column) {
+                            Some((_, values)) => values.push(text),
+                            None => joined.push((column, vec![text])),
+                        }
+                    }
+                }
+                vec![
+                    joined
+                        .into_iter()
+                        .map(|(column, values)| (column, values.join(", ")))
+                        .collect(),
+                ]
+            }
+        },
+        Value::Object(_) | Value::Array(_) => vec![vec![(path.to_string(), value.to_string())]],
+        leaf => vec![vec![(path.to_string(), cell(leaf))]],
+    }
+}
+
+/// Flatten rows of typed values under `columns` into a header and rows
+pub fn flatten(columns: &[String], rows: &[Vec<Value>], spec: &FlattenSpec) -> Vec<Vec<String>> {
+    let mut header: Vec<String> = Vec::new();
+    let mut flat_rows: Vec<Vec<(String, String)>> = Vec::new();
+    for row in rows {
+        let fragments = columns
+            .iter()
+            .zip(row)
+            .fold(vec![Vec::new()], |acc, (column, value)| {
+                combine(acc, flatten_value(column, value, 1, spec))
+            });
+        for fragment in fragments {
+            for (column, _) in &fragment {
+                if !header.contains(column) {
+                    header.push(column.clone());
+                }
+            }
+            flat_rows.push(fragment);
+        }
+    }
+    let mut table = vec![header.clone()];
+    table.extend(flat_rows.into_iter().map(|fragment| {
+        header
+            .iter()
+            .map(|column| {
+                fragment
+                    .iter()
+                    .find(|(name, _)| name == column)
+                    .map_or_else(|| "NULL".to_string(), |(_, text)| text.clone())
+            })
+            .collect()
+    }));
+    table
+}
+
+/// Collects a result with its values typed
+#[derive(Default)]
+pub struct TypedRows {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+}
+
+impl RowSink for TypedRows {
+    fn columns(&mut self, names: &[String]) -> Result<(), DatabaseError> {
+        self.columns = names.to_vec();
+        Ok(())
+    }
+
+    fn row(&mut self, values: Vec<Value>) -> Result<(), DatabaseError> {
+        self.rows.push(values);
+        Ok(())
+    }
+}
+
+/// Specs saved with `\flatten .. save`, keyed by `database.collection`
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SavedSpecs {
+    #[serde(flatten)]
+    specs: BTreeMap<String, FlattenSpec>,
+}
+
+impl SavedSpecs {
+    fn path() -> Option<PathBuf> {
+        crate::config::Config::get_config_dir()
+            .ok()
+            .map(|dir| dir.join("flatten.json"))
+    }
+
+    pub fn load() -> Self {
+        Self::path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let path = Self::path().ok_or("No config directory to save flatten specs in")?;
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(&path, content)
+            .map_err(|e| format!("Failed to write {}: {e}", path.display()))
+    }
+
+    pub fn get(&self, key: &str) -> Option<FlattenSpec> {
+        self.specs.get(key).copied()
+    }
+
+    pub fn set(&mut self, key: &str, spec: FlattenSpec) -> Result<(), String> {
+        self.specs.insert(key.to_string(), spec);
+        self.save()
+    }
+
+    /// Forget the spec of `key`; false when there was none
+    pub fn remove(&mut self, key: &str) -> Result<bool, String> {
+        let removed = self.specs.remove(key).is_some();
+        if removed {
+            self.save()?;
+        }
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn run(spec: FlattenSpec) -> Vec<Vec<String>> {
+        let columns = vec![
+            "_id".to_string(),
+            "customer".to_string(),
+            "items".to_string(),
+        ];
+        let rows = vec![vec![
+            json!("a1"),
+            json!({"address": {"city": "Paris", "zip": "75001"}, "name": "Ada"}),
+            json!([{"qty": 2, "sku": "X"}, {"qty": 1, "sku": "Y"}]),
+        ]];
+        flatten(&columns, &rows, &spec)
+    }
+
+    #[test]
+    fn test_flatten_depth_and_arrays() {
+        let table = run(FlattenSpec::default());
+        assert_eq!(
+            table[0],
+            [
+                "_id",
+                "customer.address.city",
+                "customer.address.zip",
+                "customer.name",
+                "items.qty",
+                "items.sku"
+            ]
+        );
+        assert_eq!(table[1], ["a1", "Paris", "75001", "Ada", "2", "X"]);
+
+        let table = run(FlattenSpec {
+            depth: 1,
+            arrays: ArrayMode::Join,
+        });
+        assert_eq!(
+            table[0],
+            [
+                "_id",
+                "customer.address",
+                "customer.name",
+                "items.qty",
+                "items.sku"
+            ]
+        );
+        assert_eq!(table[1][1], r#"{"city":"Paris","zip":"75001"}"#);
+        assert_eq!(table[1][3..], ["2, 1", "X, Y"]);
+
+        let table = run(FlattenSpec {
+            depth: 0,
+            arrays: ArrayMode::Explode,
+        });
+        assert_eq!(table.len(), 3);
+        assert_eq!(table[2][0], "a1");
+        assert_eq!(table[2][5], "Y");
+    }
+
+    #[test]
+    fn test_collection_of() {
+        assert_eq!(
+            collection_of("db.orders.find({})").as_deref(),
+            Some("orders")
+        );
+        assert_eq!(collection_of("db.users").as_deref(), Some("users"));
+        assert_eq!(
+            collection_of("SELECT * FROM \"logs-2024\" LIMIT 5").as_deref(),
+            Some("logs-2024")
+        );
+        assert_eq!(collection_of("SHOW TABLES"), None);
+        assert_eq!("explode".parse(), Ok(ArrayMode::Explode));
+        assert!("all".parse::<ArrayMode>().is_err());
+    }
+}
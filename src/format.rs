@@ -1,16 +1,25 @@
 use crate::db::{ColumnFilteringInfo, TableDetails};
 use chrono;
-use prettytable::{Cell, Row, Table};
+use std::fmt::{self, Write};
+use std::str::FromStr;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
 const MAX_FORMAT_CELL_CHARS: usize = 4_096;
 const MAX_FORMAT_DATA_ROWS: usize = 10_000;
 const MAX_FORMAT_OUTPUT_BYTES: usize = 32 * 1024 * 1024;
 
+/// At most `max_chars` characters of `value`, cut between grapheme clusters
+/// so an emoji sequence, flag or accented letter is never split
 fn bounded_cell_prefix(value: &str, max_chars: usize) -> (&str, bool) {
-    match value.char_indices().nth(max_chars) {
-        Some((split_at, _)) => (&value[..split_at], true),
-        None => (value, false),
+    let mut chars = 0;
+    for (start, grapheme) in value.grapheme_indices(true) {
+        chars += grapheme.chars().count();
+        if chars > max_chars {
+            return (&value[..start], true);
+        }
     }
+    (value, false)
 }
 
 /// Sanitize a cell value for table display by replacing newlines and control characters.
@@ -37,6 +46,458 @@ fn sanitize_cell_for_display(value: &str) -> String {
     sanitized
 }
 
+/// Time zone that timestamps with an offset are converted to (`[display] timezone`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DisplayTimezone {
+    /// Keep the value as returned by the server
+    #[default]
+    Server,
+    Local,
+    Utc,
+    Named(chrono_tz::Tz),
+}
+
+impl fmt::Display for DisplayTimezone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DisplayTimezone::Server => write!(f, "server"),
+            DisplayTimezone::Local => write!(f, "local"),
+            DisplayTimezone::Utc => write!(f, "utc"),
+            DisplayTimezone::Named(tz) => write!(f, "{}", tz.name()),
+        }
+    }
+}
+
+impl FromStr for DisplayTimezone {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "" | "server" => Ok(DisplayTimezone::Server),
+            "local" => Ok(DisplayTimezone::Local),
+            "utc" | "z" => Ok(DisplayTimezone::Utc),
+            _ => s
+                .trim()
+                .parse::<chrono_tz::Tz>()
+                .map(DisplayTimezone::Named)
+                .map_err(|_| {
+                    format!(
+                        "Unknown time zone '{}'. Use server, local, utc or an IANA name like Europe/Paris",
+                        s.trim()
+                    )
+                }),
+        }
+    }
+}
+
+/// Date/time type of a result column, from its database type name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemporalKind {
+    /// With an offset (PostgreSQL `timestamptz`): converted between zones
+    Timestamptz,
+    /// Without one (`timestamp`, MySQL `DATETIME`/`TIMESTAMP`): reformatted only
+    Timestamp,
+    Date,
+}
+
+impl TemporalKind {
+    pub fn from_type_name(type_name: &str) -> Option<Self> {
+        match type_name.to_ascii_uppercase().as_str() {
+            "TIMESTAMPTZ" | "TIMESTAMP WITH TIME ZONE" => Some(TemporalKind::Timestamptz),
+            "TIMESTAMP" | "TIMESTAMP WITHOUT TIME ZONE" | "DATETIME" => {
+                Some(TemporalKind::Timestamp)
+            }
+            "DATE" => Some(TemporalKind::Date),
+            _ => None,
+        }
+    }
+}
+
+/// Date/time columns of a result by name, from the header row and the
+/// database type of each column
+pub fn temporal_columns(
+    header: Option<&Vec<String>>,
+    column_types: &[String],
+) -> std::collections::HashMap<String, TemporalKind> {
+    header
+        .into_iter()
+        .flatten()
+        .zip(column_types)
+        .filter_map(|(name, type_name)| {
+            TemporalKind::from_type_name(type_name).map(|kind| (name.clone(), kind))
+        })
+        .collect()
+}
+
+/// Date/time rendering settings applied to result cells
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DateTimeDisplay {
+    pub timezone: DisplayTimezone,
+    pub datetime_format: Option<String>,
+    pub date_format: Option<String>,
+}
+
+/// Timestamp format used when converting without a configured `datetime_format`
+const DEFAULT_DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f%:z";
+
+impl DateTimeDisplay {
+    /// Validate `[display]` settings; invalid strftime formats are rejected here
+    /// rather than failing while rendering a result.
+    pub fn from_config(config: &crate::config::DisplayConfig) -> Result<Self, String> {
+        let validate = |format: &str, name: &str| -> Result<Option<String>, String> {
+            if format.is_empty() {
+                return Ok(None);
+            }
+            // Against the naive value: a format asking for a zone (`%z`) or,
+            // for dates, a time fails on the values it would be applied to
+            let sample = chrono::DateTime::UNIX_EPOCH.naive_utc();
+            let rendered = if name == "date_format" {
+                render_with(sample.date().format(format))
+            } else {
+                render_with(sample.format(format))
+            };
+            rendered.ok_or_else(|| format!("Invalid {name} '{format}'"))?;
+            Ok(Some(format.to_string()))
+        };
+
+        Ok(DateTimeDisplay {
+            timezone: config.timezone.parse()?,
+            datetime_format: validate(&config.datetime_format, "datetime_format")?,
+            date_format: validate(&config.date_format, "date_format")?,
+        })
+    }
+
+    fn is_passthrough(&self) -> bool {
+        self.timezone == DisplayTimezone::Server
+            && self.datetime_format.is_none()
+            && self.date_format.is_none()
+    }
+
+    /// Render a value of a `kind` column; `None` leaves it unchanged.
+    ///
+    /// Only values with an offset (`timestamptz`) are converted between zones.
+    /// Values without one (`timestamp`, MySQL `DATETIME`) carry no zone to convert
+    /// from, so they are only reformatted.
+    pub fn render(&self, kind: TemporalKind, value: &str) -> Option<String> {
+        match kind {
+            TemporalKind::Timestamptz => {
+                let datetime = parse_offset_datetime(value)?;
+                let format = self
+                    .datetime_format
+                    .as_deref()
+                    .unwrap_or(DEFAULT_DATETIME_FORMAT);
+                match self.timezone {
+                    DisplayTimezone::Server => {
+                        render_with(datetime.format(self.datetime_format.as_deref()?))
+                    }
+                    DisplayTimezone::Local => {
+                        render_with(datetime.with_timezone(&chrono::Local).format(format))
+                    }
+                    DisplayTimezone::Utc => {
+                        render_with(datetime.with_timezone(&chrono::Utc).format(format))
+                    }
+                    DisplayTimezone::Named(tz) => {
+                        render_with(datetime.with_timezone(&tz).format(format))
+                    }
+                }
+            }
+            TemporalKind::Timestamp => {
+                render_with(parse_naive_datetime(value)?.format(self.datetime_format.as_deref()?))
+            }
+            TemporalKind::Date => render_with(
+                chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                    .ok()?
+                    .format(self.date_format.as_deref()?),
+            ),
+        }
+    }
+}
+
+/// `item` written out; `None` when it fails, as chrono's formats do when the
+/// pattern asks for a field the value lacks (`to_string` would panic)
+fn render_with(item: impl fmt::Display) -> Option<String> {
+    let mut out = String::new();
+    write!(out, "{item}").ok()?;
+    Some(out)
+}
+
+fn parse_offset_datetime(value: &str) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+    if let Ok(datetime) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Some(datetime);
+    }
+    // PostgreSQL text output: `2024-01-01 12:00:00.5+01`
+    if let Ok(datetime) = chrono::DateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f%#z") {
+        return Some(datetime);
+    }
+    // chrono's `DateTime<Utc>` Display: `2024-01-01 12:00:00 UTC`
+    value
+        .strip_suffix(" UTC")
+        .and_then(parse_naive_datetime)
+        .map(|naive| naive.and_utc().fixed_offset())
+}
+
+fn parse_naive_datetime(value: &str) -> Option<chrono::NaiveDateTime> {
+    chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f")
+        .or_else(|_| chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f"))
+        .ok()
+}
+
+/// Global date/time display settings, set from `[display]` and `\tz` (same
+/// pattern as the global vector display config: formatters have no Config access)
+static DATETIME_DISPLAY: std::sync::OnceLock<std::sync::RwLock<DateTimeDisplay>> =
+    std::sync::OnceLock::new();
+
+/// Fast path so the default settings cost nothing per result
+static DATETIME_DISPLAY_ACTIVE: std::sync::atomic::AtomicBool =
+    std::sync::atomic::AtomicBool::new(false);
+
+pub fn set_datetime_display(display: DateTimeDisplay) {
+    DATETIME_DISPLAY_ACTIVE.store(
+        !display.is_passthrough(),
+        std::sync::atomic::Ordering::Relaxed,
+    );
+    let lock = DATETIME_DISPLAY.get_or_init(|| std::sync::RwLock::new(DateTimeDisplay::default()));
+    if let Ok(mut current) = lock.write() {
+        *current = display;
+    }
+}
+
+/// Apply `[display]` settings from the config. Invalid settings are reported
+/// and leave timestamps unconverted / tables in the default style.
+pub fn apply_display_config(config: &crate::config::DisplayConfig) -> Result<(), String> {
+    let style = config.table_style.parse::<TableStyle>();
+    set_table_style(style.clone().unwrap_or_default());
+    match DateTimeDisplay::from_config(config) {
+        Ok(display) => {
+            set_datetime_display(display);
+            style.map(|_| ())
+        }
+        Err(e) => {
+            set_datetime_display(DateTimeDisplay::default());
+            Err(e)
+        }
+    }
+}
+
+/// Border style of result tables (`[display] table_style`, `\pset border`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TableStyle {
+    /// psql's aligned format: `|` between columns, a dashed header rule, no frame
+    #[default]
+    Psql,
+    Ascii,
+    UnicodeLight,
+    UnicodeHeavy,
+    Markdown,
+    Borderless,
+}
+
+impl TableStyle {
+    pub const NAMES: &'static [&'static str] = &[
+        "psql",
+        "ascii",
+        "unicode-light",
+        "unicode-heavy",
+        "markdown",
+        "borderless",
+    ];
+
+    fn borders(self) -> Borders {
+        const fn rule(
+            left: &'static str,
+            fill: &'static str,
+            junction: &'static str,
+            right: &'static str,
+        ) -> Rule {
+            Rule {
+                left,
+                fill,
+                junction,
+                right,
+            }
+        }
+        match self {
+            TableStyle::Psql => Borders {
+                top: None,
+                header: Some(rule("", "-", "-+-", "")),
+                bottom: None,
+                cells: ("", " | ", ""),
+            },
+            TableStyle::Ascii => {
+                let line = rule("+-", "-", "-+-", "-+");
+                Borders {
+                    top: Some(line),
+                    header: Some(line),
+                    bottom: Some(line),
+                    cells: ("| ", " | ", " |"),
+                }
+            }
+            TableStyle::UnicodeLight => Borders {
+                top: Some(rule("┌─", "─", "─┬─", "─┐")),
+                header: Some(rule("├─", "─", "─┼─", "─┤")),
+                bottom: Some(rule("└─", "─", "─┴─", "─┘")),
+                cells: ("│ ", " │ ", " │"),
+            },
+            TableStyle::UnicodeHeavy => Borders {
+                top: Some(rule("┏━", "━", "━┳━", "━┓")),
+                header: Some(rule("┣━", "━", "━╋━", "━┫")),
+                bottom: Some(rule("┗━", "━", "━┻━", "━┛")),
+                cells: ("┃ ", " ┃ ", " ┃"),
+            },
+            TableStyle::Markdown => Borders {
+                top: None,
+                header: Some(rule("|-", "-", "-|-", "-|")),
+                bottom: None,
+                cells: ("| ", " | ", " |"),
+            },
+            TableStyle::Borderless => Borders {
+                top: None,
+                header: None,
+                bottom: None,
+                cells: ("", "  ", ""),
+            },
+        }
+    }
+
+    /// Frame of expanded (`\x`) records as `(vertical, [top, between rows,
+    /// bottom])`, each rule `[fill, junction, left, right]`; markdown has no
+    /// two-column equivalent and keeps the ASCII frame, borderless has none
+    fn expanded_frame(self) -> Option<ExpandedFrame> {
+        match self {
+            TableStyle::Psql | TableStyle::Ascii | TableStyle::Markdown => {
+                Some(('|', [['-', '+', '+', '+']; 3]))
+            }
+            TableStyle::UnicodeLight => Some((
+                '│',
+                [
+                    ['─', '┬', '┌', '┐'],
+                    ['─', '┼', '├', '┤'],
+                    ['─', '┴', '└', '┘'],
+                ],
+            )),
+            TableStyle::UnicodeHeavy => Some((
+                '┃',
+                [
+                    ['━', '┳', '┏', '┓'],
+                    ['━', '╋', '┣', '┫'],
+                    ['━', '┻', '┗', '┛'],
+                ],
+            )),
+            TableStyle::Borderless => None,
+        }
+    }
+}
+
+type ExpandedFrame = (char, [[char; 4]; 3]);
+
+impl fmt::Display for TableStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            TableStyle::Psql => "psql",
+            TableStyle::Ascii => "ascii",
+            TableStyle::UnicodeLight => "unicode-light",
+            TableStyle::UnicodeHeavy => "unicode-heavy",
+            TableStyle::Markdown => "markdown",
+            TableStyle::Borderless => "borderless",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for TableStyle {
+    type Err = String;
+
+    /// Style names, plus psql's `\pset border` levels 0-2
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "" | "psql" | "1" => Ok(TableStyle::Psql),
+            "ascii" | "2" => Ok(TableStyle::Ascii),
+            "unicode" | "unicode-light" => Ok(TableStyle::UnicodeLight),
+            "unicode-heavy" => Ok(TableStyle::UnicodeHeavy),
+            "markdown" | "md" => Ok(TableStyle::Markdown),
+            "borderless" | "none" | "0" => Ok(TableStyle::Borderless),
+            other => Err(format!(
+                "Unknown table style '{other}'. Use one of: {}",
+                TableStyle::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+/// Horizontal rule of a table: `left`, then `fill` repeated over each column
+/// joined by `junction`, then `right`
+#[derive(Clone, Copy)]
+struct Rule {
+    left: &'static str,
+    fill: &'static str,
+    junction: &'static str,
+    right: &'static str,
+}
+
+impl Rule {
+    fn render(&self, widths: &[usize]) -> String {
+        let columns: Vec<String> = widths.iter().map(|w| self.fill.repeat(*w)).collect();
+        format!(
+            "{}{}{}\n",
+            self.left,
+            columns.join(self.junction),
+            self.right
+        )
+    }
+}
+
+struct Borders {
+    top: Option<Rule>,
+    header: Option<Rule>,
+    bottom: Option<Rule>,
+    /// Left edge, column separator, right edge of each row
+    cells: (&'static str, &'static str, &'static str),
+}
+
+static TABLE_STYLE: std::sync::RwLock<TableStyle> = std::sync::RwLock::new(TableStyle::Psql);
+
+pub fn set_table_style(style: TableStyle) {
+    if let Ok(mut current) = TABLE_STYLE.write() {
+        *current = style;
+    }
+}
+
+pub fn table_style() -> TableStyle {
+    TABLE_STYLE.read().map(|style| *style).unwrap_or_default()
+}
+
+/// `data` (header row first) with the values of its date/time columns
+/// rendered per the `[display]` settings; `None` when nothing applies
+pub fn render_datetime_columns(
+    data: &[Vec<String>],
+    columns: &std::collections::HashMap<String, TemporalKind>,
+) -> Option<Vec<Vec<String>>> {
+    if columns.is_empty() || !DATETIME_DISPLAY_ACTIVE.load(std::sync::atomic::Ordering::Relaxed) {
+        return None;
+    }
+    let display = DATETIME_DISPLAY.get()?.read().ok()?;
+    let kinds: Vec<(usize, TemporalKind)> = data
+        .first()?
+        .iter()
+        .enumerate()
+        .filter_map(|(index, name)| columns.get(name).map(|kind| (index, *kind)))
+        .collect();
+    if kinds.is_empty() {
+        return None;
+    }
+    let mut rendered = data.to_vec();
+    for row in rendered.iter_mut().skip(1) {
+        for (index, kind) in &kinds {
+            if let Some(cell) = row.get_mut(*index)
+                && let Some(value) = display.render(*kind, cell)
+            {
+                *cell = value;
+            }
+        }
+    }
+    Some(rendered)
+}
+
 /// Format a data type with enum values if available
 fn format_type_with_enum_values(data_type: &str, enum_values: &Option<Vec<String>>) -> String {
     match enum_values {
@@ -49,16 +510,39 @@ fn format_type_with_enum_values(data_type: &str, enum_values: &Option<Vec<String
     }
 }
 
+/// Terminal columns taken by `text`: ANSI color sequences (cells colored by
+/// reports like `\replication`) take none, wide CJK characters and emoji two.
+/// Measured per string rather than per character, so emoji ZWJ sequences,
+/// flags and emoji variation selectors count as the single glyph they show as.
+fn visible_len(text: &str) -> usize {
+    let width = |s: &str| -> usize { s.width() };
+    let mut columns = 0;
+    let mut rest = text;
+    while let Some(start) = rest.find("\x1b[") {
+        columns += width(&rest[..start]);
+        let tail = &rest[start + 2..];
+        match tail.find(|c: char| !(c.is_ascii_digit() || c == ';')) {
+            Some(end) if tail[end..].starts_with('m') => rest = &tail[end + 1..],
+            _ => {
+                columns += width(&rest[start..start + 2]);
+                rest = tail;
+            }
+        }
+    }
+    columns + width(rest)
+}
+
 /// Safe formatting function to prevent "Formatting argument out of range" errors
 fn safe_format_with_width(text: &str, width: usize, left_align: bool) -> String {
     if width == 0 {
         return text.to_string();
     }
 
-    if text.len() >= width {
+    let len = visible_len(text);
+    if len >= width {
         text.to_string()
     } else {
-        let padding = width - text.len();
+        let padding = width - len;
         if left_align {
             format!("{}{}", text, " ".repeat(padding))
         } else {
@@ -67,53 +551,165 @@ fn safe_format_with_width(text: &str, width: usize, left_align: bool) -> String
     }
 }
 
+/// One record of the expanded (`\x`) display: column names next to their
+/// values, framed with the table style. Widths are terminal columns, so wide
+/// characters and emoji keep the frame aligned.
+pub struct ExpandedRecord {
+    rows: Vec<(String, String)>,
+    frame: Option<ExpandedFrame>,
+}
+
+impl fmt::Display for ExpandedRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = |cell: fn(&(String, String)) -> &str| {
+            self.rows
+                .iter()
+                .map(|row| visible_len(cell(row)))
+                .max()
+                .unwrap_or(0)
+        };
+        let name_width = width(|(name, _)| name);
+        let value_width = width(|(_, value)| value);
+        let pad = |text: &str, width: usize| safe_format_with_width(text, width, true);
+
+        let Some((vertical, rules)) = self.frame else {
+            for (name, value) in &self.rows {
+                writeln!(
+                    f,
+                    " {}  {} ",
+                    pad(name, name_width),
+                    pad(value, value_width)
+                )?;
+            }
+            return Ok(());
+        };
+        let rule = |[fill, junction, left, right]: [char; 4]| {
+            let line = |width: usize| fill.to_string().repeat(width + 2);
+            format!(
+                "{left}{}{junction}{}{right}",
+                line(name_width),
+                line(value_width)
+            )
+        };
+
+        writeln!(f, "{}", rule(rules[0]))?;
+        for (i, (name, value)) in self.rows.iter().enumerate() {
+            if i > 0 {
+                writeln!(f, "{}", rule(rules[1]))?;
+            }
+            writeln!(
+                f,
+                "{vertical} {} {vertical} {} {vertical}",
+                pad(name, name_width),
+                pad(value, value_width)
+            )?;
+        }
+        writeln!(f, "{}", rule(rules[2]))
+    }
+}
+
 #[allow(dead_code)]
-pub fn format_query_results_expanded(data: &[Vec<String>]) -> Vec<Table> {
-    let mut tables = Vec::new();
+pub fn format_query_results_expanded(data: &[Vec<String>]) -> Vec<ExpandedRecord> {
+    let mut records = Vec::new();
 
     if data.len() < 2 {
-        // If there's only a header row or empty data, return empty tables vector
-        return tables;
+        // If there's only a header row or empty data, return no records
+        return records;
     }
 
+    let masked = crate::masking::masked(data);
+    let data = masked.as_deref().unwrap_or(data);
     let header = &data[0]; // First row is header
+    let frame = table_style().expanded_frame();
 
-    // For each data row, create a separate vertical table, bounded so expanded
-    // mode cannot create an unbounded number of prettytable allocations.
+    // One record per data row, bounded so expanded mode cannot build an
+    // unbounded number of records
     for (i, row) in data.iter().skip(1).take(MAX_FORMAT_DATA_ROWS).enumerate() {
-        let mut table = Table::new();
-
-        // Add title row indicating record number
-        table.add_row(Row::new(vec![
-            Cell::new(&format!("Record {}", i + 1)),
-            Cell::new(""),
-        ]));
-
-        // Add each field with its column name and value
-        for (col_idx, col_name) in header.iter().enumerate() {
-            // Make sure we don't go out of bounds
-            if col_idx < row.len() {
-                let cell_value = sanitize_cell_for_display(&row[col_idx]);
-                table.add_row(Row::new(vec![Cell::new(col_name), Cell::new(&cell_value)]));
-            }
-        }
-
-        tables.push(table);
+        // Title row with the record number, then each column and its value
+        let mut rows = vec![(format!("Record {}", i + 1), String::new())];
+        rows.extend(
+            header
+                .iter()
+                .zip(row)
+                .map(|(col_name, value)| (col_name.clone(), sanitize_cell_for_display(value))),
+        );
+        records.push(ExpandedRecord { rows, frame });
     }
 
     if data.len().saturating_sub(1) > MAX_FORMAT_DATA_ROWS {
-        let mut table = Table::new();
-        table.add_row(Row::new(vec![
-            Cell::new("⚠ Output truncated"),
-            Cell::new(&format!(
-                "showing first {MAX_FORMAT_DATA_ROWS} rows out of {} returned rows",
-                data.len() - 1
-            )),
-        ]));
-        tables.push(table);
+        records.push(ExpandedRecord {
+            rows: vec![(
+                "⚠ Output truncated".to_string(),
+                format!(
+                    "showing first {MAX_FORMAT_DATA_ROWS} rows out of {} returned rows",
+                    data.len() - 1
+                ),
+            )],
+            frame: TableStyle::Ascii.expanded_frame(),
+        });
     }
 
-    tables
+    records
+}
+
+/// `512 bytes`, `3.2 MB`, `1.5 GB` (binary units, like `pg_size_pretty`)
+pub fn format_bytes(bytes: i64) -> String {
+    const UNITS: [&str; 4] = ["kB", "MB", "GB", "TB"];
+    if bytes.abs() < 1024 {
+        return format!("{bytes} bytes");
+    }
+    let mut value = bytes as f64;
+    let mut unit = "";
+    for candidate in UNITS {
+        value /= 1024.0;
+        unit = candidate;
+        if value.abs() < 1024.0 {
+            break;
+        }
+    }
+    format!("{value:.1} {unit}")
+}
+
+/// `0.25s`, `42s`, `5m12s`, `2h03m`
+pub fn format_lag(seconds: f64) -> String {
+    let seconds = seconds.max(0.0);
+    if seconds < 10.0 {
+        return format!("{seconds:.2}s");
+    }
+    let whole = seconds as i64;
+    match (whole / 3600, (whole % 3600) / 60, whole % 60) {
+        (0, 0, secs) => format!("{secs}s"),
+        (0, minutes, secs) => format!("{minutes}m{secs:02}s"),
+        (hours, minutes, _) => format!("{hours}h{minutes:02}m"),
+    }
+}
+
+/// Rows of `data` as columns (`\transpose`): one row per column, headed
+/// `Column` and `Value`, or `Row 1`, `Row 2`... when there are several rows.
+/// Only the first `max_rows` rows are kept.
+#[allow(dead_code)]
+pub fn transpose_results(data: &[Vec<String>], max_rows: usize) -> Vec<Vec<String>> {
+    // Masked while the column names are still in the header
+    let masked = crate::masking::masked(data);
+    let data = masked.as_deref().unwrap_or(data);
+    let Some((header, rows)) = data.split_first() else {
+        return Vec::new();
+    };
+    let rows = &rows[..rows.len().min(max_rows)];
+    let mut transposed = vec![
+        std::iter::once("Column".to_string())
+            .chain(match rows.len() {
+                1 => vec!["Value".to_string()],
+                n => (1..=n).map(|i| format!("Row {i}")).collect(),
+            })
+            .collect::<Vec<_>>(),
+    ];
+    for (i, name) in header.iter().enumerate() {
+        let mut row = vec![name.clone()];
+        row.extend(rows.iter().map(|r| r.get(i).cloned().unwrap_or_default()));
+        transposed.push(row);
+    }
+    transposed
 }
 
 #[allow(dead_code)]
@@ -127,7 +723,9 @@ pub fn format_query_results_psql_with_info(
     column_info: Option<&ColumnFilteringInfo>,
 ) -> String {
     // Use panic catching to handle any formatting errors gracefully
-    let result = std::panic::catch_unwind(|| format_query_results_psql_internal(data, column_info));
+    let style = table_style();
+    let result =
+        std::panic::catch_unwind(|| format_query_results_psql_internal(data, column_info, style));
 
     match result {
         Ok(formatted) => formatted,
@@ -173,11 +771,15 @@ pub fn format_query_results_psql_with_info(
 fn format_query_results_psql_internal(
     data: &[Vec<String>],
     column_info: Option<&ColumnFilteringInfo>,
+    style: TableStyle,
 ) -> String {
     if data.is_empty() {
         return String::new();
     }
 
+    // Masked before anything is measured, so widths follow the masked values
+    let masked = crate::masking::masked(data);
+    let data = masked.as_deref().unwrap_or(data);
     let header = &data[0];
 
     // Safety check: ensure header is not empty to prevent column width access errors
@@ -240,9 +842,19 @@ fn format_query_results_psql_internal(
     // Find the maximum width needed for each column (using extended header)
     let mut col_widths = vec![0; max_cols];
 
+    // Markdown cells can't contain a bare pipe
+    let cell_text = |value: &str| -> String {
+        let sanitized = sanitize_cell_for_display(value);
+        if style == TableStyle::Markdown {
+            sanitized.replace('|', "\\|")
+        } else {
+            sanitized
+        }
+    };
+
     // Calculate widths for header columns (including extended ones)
     for (i, col_name) in extended_header.iter().enumerate() {
-        col_widths[i] = col_name.len();
+        col_widths[i] = visible_len(&cell_text(col_name));
     }
 
     // Calculate widths for all data cells
@@ -250,32 +862,35 @@ fn format_query_results_psql_internal(
     for row in data.iter().take(display_row_limit) {
         for (i, cell) in row.iter().enumerate() {
             if i < col_widths.len() {
-                let sanitized = sanitize_cell_for_display(cell);
-                col_widths[i] = col_widths[i].max(sanitized.len());
+                col_widths[i] = col_widths[i].max(visible_len(&cell_text(cell)));
             }
             // Note: No warning here since we're handling dynamic column counts
         }
     }
 
+    let borders = style.borders();
+    let (left, separator, right) = borders.cells;
     let mut result = String::new();
 
+    if let Some(rule) = &borders.top {
+        result.push_str(&rule.render(&col_widths));
+    }
+
     // Add header row using extended header (left-aligned in psql)
+    result.push_str(left);
     for (i, h) in extended_header.iter().enumerate() {
         if i > 0 {
-            result.push_str(" | ");
+            result.push_str(separator);
         }
-        result.push_str(&safe_format_with_width(h, col_widths[i], true));
+        result.push_str(&safe_format_with_width(&cell_text(h), col_widths[i], true));
     }
+    result.push_str(right);
     result.push('\n');
 
     // Add separator line
-    for (i, width) in col_widths.iter().enumerate() {
-        if i > 0 {
-            result.push_str("-+-");
-        }
-        result.push_str(&"-".repeat(*width));
+    if let Some(rule) = &borders.header {
+        result.push_str(&rule.render(&col_widths));
     }
-    result.push('\n');
 
     // Add data rows (skip header which is data[0])
     let mut output_truncated = false;
@@ -285,15 +900,16 @@ fn format_query_results_psql_internal(
             break;
         }
 
+        result.push_str(left);
         for i in 0..max_cols {
             if i > 0 {
-                result.push_str(" | ");
+                result.push_str(separator);
             }
 
             let raw_cell_value = if i < row.len() { &row[i] } else { "" };
 
             // Sanitize cell value to prevent newlines from breaking table format
-            let cell_value = sanitize_cell_for_display(raw_cell_value);
+            let cell_value = cell_text(raw_cell_value);
 
             // Try to right-align numeric values, left-align text
             let is_numeric = !cell_value.is_empty()
@@ -307,6 +923,7 @@ fn format_query_results_psql_internal(
                 result.push_str(&safe_format_with_width(&cell_value, col_widths[i], true));
             }
         }
+        result.push_str(right);
         result.push('\n');
 
         if result.len() >= MAX_FORMAT_OUTPUT_BYTES {
@@ -315,6 +932,14 @@ fn format_query_results_psql_internal(
         }
     }
 
+    if let Some(rule) = &borders.bottom {
+        result.push_str(&rule.render(&col_widths));
+    }
+    if style == TableStyle::Markdown {
+        // Without a blank line the footer would be read as one more table row
+        result.push('\n');
+    }
+
     if total_data_rows > display_data_rows {
         result.push_str(&format!(
             "⚠ dbcrust formatted output truncated: showing first {display_data_rows} of {total_data_rows} returned rows. Add LIMIT or narrow the SELECT list.\n"
@@ -343,7 +968,13 @@ fn format_query_results_psql_internal(
                 info.total_columns,
                 info.filtered_column_names.join(", ")
             ));
-            result.push_str("💡 Use \\clrcs to clear column selections or \\resetview to reset all view settings\n");
+            if let Some(view_name) = &info.view_name {
+                result.push_str(&format!(
+                    "💡 Saved column view '{view_name}' — use \\cviews delete {view_name} to remove it\n"
+                ));
+            } else {
+                result.push_str("💡 Use \\clrcs to clear column selections or \\resetview to reset all view settings\n");
+            }
         }
     }
 
@@ -448,6 +1079,102 @@ pub fn analyze_format_crash(data: &[Vec<String>], query: &str) -> String {
     analysis
 }
 
+/// `~1,234 rows, 2.0 MB` for whichever of the two the server reported
+fn partition_stats(rows: Option<i64>, bytes: Option<i64>) -> String {
+    let mut stats = Vec::new();
+    if let Some(rows) = rows {
+        stats.push(format!(
+            "~{} rows",
+            crate::query_progress::group_digits(rows)
+        ));
+    }
+    if let Some(bytes) = bytes {
+        stats.push(format_bytes(bytes));
+    }
+    stats.join(", ")
+}
+
+/// Partition key, pruning key and one line per partition for `\d`
+fn format_partitioning(partitioning: &crate::db::PartitionInfo) -> String {
+    let mut result = format!("Partition key: {}\n", partitioning.scheme);
+    if !partitioning.key_columns.is_empty() {
+        result.push_str(&format!(
+            "Pruning key: {} (filter on {} to scan only the matching partitions)\n",
+            partitioning.key_columns.join(", "),
+            if partitioning.key_columns.len() == 1 {
+                "it"
+            } else {
+                "them"
+            }
+        ));
+    }
+
+    let parts = &partitioning.partitions;
+    let sum = |values: Vec<Option<i64>>| -> Option<i64> {
+        values
+            .iter()
+            .any(Option::is_some)
+            .then(|| values.iter().flatten().sum())
+    };
+    let total = partition_stats(
+        sum(parts.iter().map(|p| p.rows).collect()),
+        sum(parts.iter().map(|p| p.bytes).collect()),
+    );
+    result.push_str(&format!(
+        "Partitions: {}{}\n",
+        parts.len(),
+        if total.is_empty() {
+            String::new()
+        } else {
+            format!(" ({total})")
+        }
+    ));
+
+    let name_width = parts.iter().map(|p| p.name.len() + 2).max().unwrap_or(0);
+    let bound_width = parts.iter().map(|p| p.bound.len()).max().unwrap_or(0);
+    for part in parts {
+        let line = format!(
+            "    {:<name_width$}  {:<bound_width$}  {}",
+            format!("\"{}\"", part.name),
+            part.bound,
+            partition_stats(part.rows, part.bytes)
+        );
+        result.push_str(line.trim_end());
+        result.push('\n');
+    }
+    result.push('\n');
+    result
+}
+
+/// Cluster, local table and shard hosts of a ClickHouse Distributed table for `\d`
+fn format_distribution(distribution: &crate::db::DistributionInfo) -> String {
+    let mut result = format!(
+        "Distributed over cluster: {}{}\n",
+        distribution.cluster,
+        distribution
+            .sharding_key
+            .as_ref()
+            .map(|key| format!(" (sharding key: {key})"))
+            .unwrap_or_default()
+    );
+    result.push_str(&format!("Local table: {}\n", distribution.local_table));
+    let shards = &distribution.shards;
+    if shards.is_empty() {
+        result.push_str("Shards: cluster not found in system.clusters\n");
+    } else {
+        result.push_str(&format!(
+            "Shards: {}, replicas: {}\n",
+            shards.len(),
+            shards.iter().map(|(_, hosts)| hosts.len()).sum::<usize>()
+        ));
+        for (shard, hosts) in shards {
+            result.push_str(&format!("    shard {shard}  {}\n", hosts.join(", ")));
+        }
+    }
+    result.push('\n');
+    result
+}
+
 pub fn format_table_details(details: &TableDetails) -> String {
     let mut result = String::new();
 
@@ -801,6 +1528,14 @@ pub fn format_table_details(details: &TableDetails) -> String {
         result.push('\n'); // Add a blank line after the Referenced by section
     }
 
+    if let Some(partitioning) = &details.partitioning {
+        result.push_str(&format_partitioning(partitioning));
+    }
+
+    if let Some(distribution) = &details.distribution {
+        result.push_str(&format_distribution(distribution));
+    }
+
     // Nested field details (for struct/complex types like in DataFusion/Arrow)
     if !details.nested_field_details.is_empty() {
         result.push_str("Nested field details:\n");
@@ -951,6 +1686,17 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_colored_cells_pad_to_visible_width() {
+        assert_eq!(visible_len("\x1b[31mno\x1b[0m"), 2);
+        assert_eq!(visible_len("\x1b[1;32mOK\x1b[0m!"), 3);
+        assert_eq!(visible_len("a\x1b[b"), 4);
+        assert_eq!(
+            safe_format_with_width("\x1b[31mno\x1b[0m", 4, true),
+            "\x1b[31mno\x1b[0m  "
+        );
+    }
+
     #[test]
     fn test_empty_data_handling() {
         let empty_data: Vec<Vec<String>> = Vec::new();
@@ -1090,6 +1836,194 @@ mod tests {
         assert!(sanitized.len() < huge.len());
     }
 
+    #[test]
+    fn test_datetime_display_converts_offset_timestamps() {
+        let display = DateTimeDisplay {
+            timezone: "Europe/Paris".parse().unwrap(),
+            ..Default::default()
+        };
+        let tz = TemporalKind::Timestamptz;
+        assert_eq!(
+            display.render(tz, "2024-01-15T12:00:00+00:00").as_deref(),
+            Some("2024-01-15 13:00:00+01:00")
+        );
+        assert_eq!(
+            display.render(tz, "2024-07-15 12:00:00.5+00").as_deref(),
+            Some("2024-07-15 14:00:00.500+02:00")
+        );
+        // No offset: nothing to convert from
+        assert_eq!(
+            display.render(TemporalKind::Timestamp, "2024-01-15 12:00:00"),
+            None
+        );
+        assert_eq!(display.render(tz, "not a date"), None);
+    }
+
+    #[test]
+    fn test_datetime_display_formats() {
+        let config = crate::config::DisplayConfig {
+            timezone: "utc".to_string(),
+            datetime_format: "%d/%m/%Y %H:%M".to_string(),
+            date_format: "%d/%m/%Y".to_string(),
+            ..Default::default()
+        };
+        let display = DateTimeDisplay::from_config(&config).unwrap();
+        assert_eq!(
+            display
+                .render(TemporalKind::Timestamptz, "2024-01-15T12:00:00-05:00")
+                .as_deref(),
+            Some("15/01/2024 17:00")
+        );
+        assert_eq!(
+            display
+                .render(TemporalKind::Timestamp, "2024-01-15 12:00:00")
+                .as_deref(),
+            Some("15/01/2024 12:00")
+        );
+        assert_eq!(
+            display.render(TemporalKind::Date, "2024-01-15").as_deref(),
+            Some("15/01/2024")
+        );
+        // A text column holding a date-like string is left alone
+        let header = vec!["created".to_string(), "note".to_string()];
+        let columns = temporal_columns(Some(&header), &["DATE".to_string(), "TEXT".to_string()]);
+        assert_eq!(columns.len(), 1);
+        assert_eq!(columns.get("created"), Some(&TemporalKind::Date));
+
+        for (field, format) in [
+            ("datetime_format", "%Q"),
+            // Zones and times the values to format do not have
+            ("datetime_format", "%Y-%m-%d %z"),
+            ("date_format", "%d/%m/%Y %H:%M"),
+        ] {
+            let mut invalid = config.clone();
+            match field {
+                "datetime_format" => invalid.datetime_format = format.to_string(),
+                _ => invalid.date_format = format.to_string(),
+            }
+            assert!(DateTimeDisplay::from_config(&invalid).is_err(), "{format}");
+        }
+        // Rendering never panics on a format that slipped through
+        let zoned = DateTimeDisplay {
+            datetime_format: Some("%H:%M %Z".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            zoned.render(TemporalKind::Timestamp, "2024-01-15 12:00:00"),
+            None
+        );
+        assert!("Mars/Olympus".parse::<DisplayTimezone>().is_err());
+    }
+
+    #[test]
+    fn test_table_styles() {
+        let data = vec![
+            vec!["id".to_string(), "name".to_string()],
+            vec!["1".to_string(), "a|b".to_string()],
+        ];
+        let render = |style| format_query_results_psql_internal(&data, None, style);
+
+        assert_eq!(
+            render(TableStyle::Psql),
+            "id | name\n---+-----\n 1 | a|b \n(1 row)\n"
+        );
+        assert_eq!(
+            render(TableStyle::UnicodeLight),
+            "┌────┬──────┐\n│ id │ name │\n├────┼──────┤\n│  1 │ a|b  │\n└────┴──────┘\n(1 row)\n"
+        );
+        assert_eq!(
+            render(TableStyle::Markdown),
+            "| id | name |\n|----|------|\n|  1 | a\\|b |\n\n(1 row)\n"
+        );
+        assert_eq!(
+            render(TableStyle::Borderless),
+            "id  name\n 1  a|b \n(1 row)\n"
+        );
+
+        assert_eq!("2".parse::<TableStyle>(), Ok(TableStyle::Ascii));
+        assert_eq!(
+            "Unicode".parse::<TableStyle>(),
+            Ok(TableStyle::UnicodeLight)
+        );
+        assert!("fancy".parse::<TableStyle>().is_err());
+        for name in TableStyle::NAMES {
+            assert_eq!(name.parse::<TableStyle>().unwrap().to_string(), *name);
+        }
+    }
+
+    #[test]
+    fn test_wide_characters_align() {
+        assert_eq!(visible_len("東京"), 4);
+        assert_eq!(visible_len("café"), 4);
+
+        let data = vec![
+            vec!["city".to_string(), "n".to_string()],
+            vec!["東京".to_string(), "1".to_string()],
+            vec!["Paris".to_string(), "2".to_string()],
+        ];
+        let output = format_query_results_psql_internal(&data, None, TableStyle::Ascii);
+        let widths: Vec<usize> = output.lines().take(6).map(visible_len).collect();
+        assert!(widths.iter().all(|w| *w == widths[0]), "{output}");
+    }
+
+    /// CJK, emoji ZWJ sequences, emoji presentation selectors, flags and
+    /// combining accents, each shown as glyphs of one or two columns
+    fn mixed_width_rows() -> Vec<Vec<String>> {
+        [
+            ["name", "note"],
+            ["日本語テキスト", "a"],
+            [
+                "\u{2764}\u{fe0f} love",
+                "\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467} family",
+            ],
+            ["cafe\u{301}", "\u{1f1eb}\u{1f1f7} flag"],
+        ]
+        .iter()
+        .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+        .collect()
+    }
+
+    #[test]
+    fn test_mixed_width_cells_align() {
+        assert_eq!(visible_len("\u{2764}\u{fe0f}"), 2);
+        assert_eq!(
+            visible_len("\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467}"),
+            2
+        );
+        assert_eq!(visible_len("\u{1f1eb}\u{1f1f7}"), 2);
+        assert_eq!(visible_len("cafe\u{301}"), 4);
+
+        let data = mixed_width_rows();
+        let output = format_query_results_psql_internal(&data, None, TableStyle::Ascii);
+        let widths: Vec<usize> = output.lines().take(7).map(visible_len).collect();
+        assert!(widths.iter().all(|w| *w == widths[0]), "{output}");
+
+        for style in [
+            TableStyle::Ascii,
+            TableStyle::UnicodeHeavy,
+            TableStyle::Borderless,
+        ] {
+            for mut record in format_query_results_expanded(&data) {
+                record.frame = style.expanded_frame();
+                let output = record.to_string();
+                let widths: Vec<usize> = output.lines().map(visible_len).collect();
+                assert!(widths.iter().all(|w| *w == widths[0]), "{output}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_truncation_keeps_grapheme_clusters() {
+        let family = "\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467}";
+        assert_eq!(bounded_cell_prefix(&format!("ab{family}"), 4), ("ab", true));
+        assert_eq!(
+            bounded_cell_prefix(&format!("ab{family}"), 7),
+            (format!("ab{family}").as_str(), false)
+        );
+        assert_eq!(bounded_cell_prefix("cafe\u{301}s", 4), ("caf", true));
+        assert_eq!(bounded_cell_prefix("\u{1f1eb}\u{1f1f7}", 1), ("", true));
+    }
+
     #[test]
     fn test_psql_format_limits_displayed_rows() {
         let mut data = vec![vec!["id".to_string()]];
@@ -1253,6 +2187,87 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_transpose_results() {
+        let row = |cells: &[&str]| cells.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+        let data = vec![row(&["id", "name"]), row(&["1", "Ada"]), row(&["2", "Bob"])];
+
+        assert_eq!(
+            transpose_results(&data[..2], 10),
+            vec![
+                row(&["Column", "Value"]),
+                row(&["id", "1"]),
+                row(&["name", "Ada"])
+            ]
+        );
+        assert_eq!(
+            transpose_results(&data, 10),
+            vec![
+                row(&["Column", "Row 1", "Row 2"]),
+                row(&["id", "1", "2"]),
+                row(&["name", "Ada", "Bob"])
+            ]
+        );
+        assert_eq!(
+            transpose_results(&data, 1),
+            transpose_results(&data[..2], 10)
+        );
+        assert!(transpose_results(&[], 10).is_empty());
+    }
+
+    #[test]
+    fn test_format_partitioning() {
+        use crate::db::{PartitionDetail, PartitionInfo};
+
+        let partitioning = PartitionInfo {
+            scheme: "RANGE (created_at)".to_string(),
+            key_columns: vec!["created_at".to_string()],
+            partitions: vec![
+                PartitionDetail {
+                    name: "events_2024".to_string(),
+                    bound: "FOR VALUES FROM ('2024-01-01') TO ('2025-01-01')".to_string(),
+                    rows: Some(1_500),
+                    bytes: Some(2048),
+                },
+                PartitionDetail {
+                    name: "events_default".to_string(),
+                    bound: "DEFAULT".to_string(),
+                    rows: None,
+                    bytes: Some(8192),
+                },
+            ],
+        };
+        assert_eq!(
+            format_partitioning(&partitioning),
+            "Partition key: RANGE (created_at)\n\
+             Pruning key: created_at (filter on it to scan only the matching partitions)\n\
+             Partitions: 2 (~1,500 rows, 10.0 kB)\n    \
+             \"events_2024\"     FOR VALUES FROM ('2024-01-01') TO ('2025-01-01')  ~1,500 rows, 2.0 kB\n    \
+             \"events_default\"  DEFAULT                                           8.0 kB\n\n"
+        );
+    }
+
+    #[test]
+    fn test_format_distribution() {
+        let distribution = crate::db::DistributionInfo {
+            cluster: "analytics".to_string(),
+            local_table: "default.events_local".to_string(),
+            sharding_key: Some("rand()".to_string()),
+            shards: vec![
+                (1, vec!["ch-1:9000".to_string(), "ch-2:9000".to_string()]),
+                (2, vec!["ch-3:9000".to_string()]),
+            ],
+        };
+        assert_eq!(
+            format_distribution(&distribution),
+            "Distributed over cluster: analytics (sharding key: rand())\n\
+             Local table: default.events_local\n\
+             Shards: 2, replicas: 3\n    \
+             shard 1  ch-1:9000, ch-2:9000\n    \
+             shard 2  ch-3:9000\n\n"
+        );
+    }
+
     #[test]
     fn test_format_table_details_basic() {
         use crate::db::{
@@ -1311,6 +2326,8 @@ mod tests {
                 definition: "FOREIGN KEY (user_id) REFERENCES users(id)".to_string(),
             }],
             nested_field_details: std::collections::HashMap::new(),
+            partitioning: None,
+            distribution: None,
         };
 
         let output = format_table_details(&details);
@@ -1352,4 +2369,22 @@ mod tests {
             "Should contain referenced-by constraint name"
         );
     }
+
+    #[rstest::rstest]
+    #[case(512, "512 bytes")]
+    #[case(2048, "2.0 kB")]
+    #[case(16 * 1024 * 1024, "16.0 MB")]
+    #[case(3 * 1024 * 1024 * 1024 / 2, "1.5 GB")]
+    fn test_format_bytes(#[case] bytes: i64, #[case] expected: &str) {
+        assert_eq!(format_bytes(bytes), expected);
+    }
+
+    #[rstest::rstest]
+    #[case(0.25, "0.25s")]
+    #[case(42.0, "42s")]
+    #[case(312.0, "5m12s")]
+    #[case(7380.0, "2h03m")]
+    fn test_format_lag(#[case] seconds: f64, #[case] expected: &str) {
+        assert_eq!(format_lag(seconds), expected);
+    }
 }
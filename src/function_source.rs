@@ -0,0 +1,333 @@
+//! PostgreSQL function and procedure source (`\sf`)
+//!
+//! `\sf name` prints the `CREATE OR REPLACE` statement `pg_get_functiondef`
+//! rebuilds for a function or procedure; `\sf+` numbers the lines from the
+//! start of the body, the way PL/pgSQL counts them in error messages.
+//! `--edit` opens the statement in $EDITOR, shows what changed and runs the
+//! edited statement once confirmed.
+
+use crate::database::escape_sql_string;
+use crate::db::Database;
+use nu_ansi_term::Color;
+use std::io::Write;
+
+/// Unchanged lines kept around each change in the edit diff
+const DIFF_CONTEXT: usize = 3;
+
+/// A function or procedure and its full definition
+#[derive(Debug, Clone)]
+pub struct FunctionSource {
+    /// `schema.name(argument types)`, as `regprocedure` prints it
+    pub signature: String,
+    pub definition: String,
+}
+
+/// `"Name"` → `Name`, `Name` → `name`: what the catalog stores
+fn catalog_name(part: &str) -> String {
+    match part.strip_prefix('"').and_then(|p| p.strip_suffix('"')) {
+        Some(quoted) => quoted.replace("\"\"", "\""),
+        None => part.to_lowercase(),
+    }
+}
+
+/// `schema.name` split on the last dot outside double quotes
+fn split_qualified(name: &str) -> (Option<&str>, &str) {
+    let mut quoted = false;
+    let mut dot = None;
+    for (index, c) in name.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '.' if !quoted => dot = Some(index),
+            _ => {}
+        }
+    }
+    match dot {
+        Some(index) => (Some(&name[..index]), &name[index + 1..]),
+        None => (None, name),
+    }
+}
+
+/// Catalog query for the functions `name` can mean. With argument types the
+/// server resolves it like a call would; without, every overload of a visible
+/// (or schema-qualified) name matches. Aggregates have no definition to show.
+pub fn lookup_sql(name: &str) -> String {
+    let select = "SELECT p.oid::pg_catalog.regprocedure::text AS signature, \
+                  pg_catalog.pg_get_functiondef(p.oid) AS definition \
+                  FROM pg_catalog.pg_proc p \
+                  JOIN pg_catalog.pg_namespace n ON n.oid = p.pronamespace";
+    if name.contains('(') {
+        return format!(
+            "{select} WHERE p.oid = '{}'::pg_catalog.regprocedure",
+            escape_sql_string(name)
+        );
+    }
+    let (schema, function) = split_qualified(name);
+    let schema_filter = match schema {
+        Some(schema) => format!("n.nspname = '{}'", escape_sql_string(&catalog_name(schema))),
+        None => "pg_catalog.pg_function_is_visible(p.oid)".to_string(),
+    };
+    format!(
+        "{select} WHERE p.proname = '{}' AND {schema_filter} \
+         AND p.oid NOT IN (SELECT aggfnoid FROM pg_catalog.pg_aggregate) \
+         ORDER BY 1",
+        escape_sql_string(&catalog_name(function))
+    )
+}
+
+/// The one function `name` refers to; overloaded names have to be called
+/// with their argument types
+pub async fn fetch(database: &mut Database, name: &str) -> Result<FunctionSource, String> {
+    let rows = database
+        .execute_query(&lookup_sql(name))
+        .await
+        .map_err(|e| format!("Cannot read the source of {name}: {e}"))?;
+    let mut found: Vec<FunctionSource> = rows
+        .into_iter()
+        .skip(1)
+        .filter_map(|mut row| {
+            let definition = row.pop()?;
+            let signature = row.pop()?;
+            Some(FunctionSource {
+                signature,
+                definition,
+            })
+        })
+        .collect();
+    match found.len() {
+        0 => Err(format!("No function or procedure named {name}")),
+        1 => Ok(found.remove(0)),
+        _ => Err(format!(
+            "{name} is overloaded; add the argument types:\n{}",
+            found
+                .iter()
+                .map(|f| format!("  \\sf {}", f.signature))
+                .collect::<Vec<_>>()
+                .join("\n")
+        )),
+    }
+}
+
+/// Index of the line the body starts on, which PL/pgSQL numbers 1
+fn body_start(lines: &[&str]) -> usize {
+    lines
+        .iter()
+        .position(|line| {
+            line.starts_with("AS ") || line.starts_with("BEGIN ") || line.starts_with("RETURN ")
+        })
+        .unwrap_or(0)
+}
+
+impl FunctionSource {
+    /// The definition, highlighted when `color`, with body line numbers when
+    /// `numbered`
+    pub fn render(&self, numbered: bool, color: bool) -> String {
+        let text = self.definition.trim_end();
+        let text = if color {
+            crate::highlighter::highlight_sql(text)
+        } else {
+            text.to_string()
+        };
+        if !numbered {
+            return text;
+        }
+        let lines: Vec<&str> = text.lines().collect();
+        let plain: Vec<&str> = self.definition.trim_end().lines().collect();
+        let start = body_start(&plain);
+        let width = (lines.len() - start).to_string().len();
+        lines
+            .iter()
+            .enumerate()
+            .map(|(index, line)| match index.checked_sub(start) {
+                Some(offset) => format!("{:<width$}  {line}", offset + 1),
+                None => format!("{:width$}  {line}", ""),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Open `definition` in $EDITOR as a `.sql` file and return what was saved
+pub fn edit(definition: &str) -> Result<String, String> {
+    let mut file = tempfile::Builder::new()
+        .prefix("dbcrust-function-")
+        .suffix(".sql")
+        .tempfile()
+        .map_err(|e| format!("Cannot create a temporary file: {e}"))?;
+    file.write_all(definition.as_bytes())
+        .and_then(|_| file.flush())
+        .map_err(|e| format!("Cannot write the temporary file: {e}"))?;
+
+    let editor = crate::script::resolve_editor();
+    // $EDITOR may carry arguments ("code --wait"); split them off.
+    let mut parts = editor.split_whitespace();
+    let program = parts
+        .next()
+        .ok_or_else(|| "EDITOR is set but empty".to_string())?;
+    let status = std::process::Command::new(program)
+        .args(parts)
+        .arg(file.path())
+        .status()
+        .map_err(|e| format!("Failed to launch editor '{editor}': {e}"))?;
+    if !status.success() {
+        return Err(format!("Editor '{editor}' exited with non-zero status"));
+    }
+    std::fs::read_to_string(file.path()).map_err(|e| format!("Cannot read the edited file: {e}"))
+}
+
+/// Whether `sql` still starts with CREATE OR REPLACE, after comments
+pub fn is_create_or_replace(sql: &str) -> bool {
+    let words: Vec<String> = crate::sql_buffer::strip_comments(sql)
+        .split_whitespace()
+        .take(3)
+        .map(str::to_uppercase)
+        .collect();
+    words == ["CREATE", "OR", "REPLACE"]
+}
+
+/// One line of a line diff
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffLine<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Line diff of `old` and `new` along their longest common subsequence
+pub fn line_diff<'a>(old: &'a str, new: &'a str) -> Vec<DiffLine<'a>> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    // common[i][j]: length of the longest common subsequence of old[i..] and new[j..]
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut diff = Vec::with_capacity(old.len().max(new.len()));
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            diff.push(DiffLine::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if common[i + 1][j] >= common[i][j + 1] {
+            diff.push(DiffLine::Removed(old[i]));
+            i += 1;
+        } else {
+            diff.push(DiffLine::Added(new[j]));
+            j += 1;
+        }
+    }
+    diff.extend(old[i..].iter().map(|line| DiffLine::Removed(line)));
+    diff.extend(new[j..].iter().map(|line| DiffLine::Added(line)));
+    diff
+}
+
+/// Changed lines with a few unchanged ones around them, `-` removed and `+`
+/// added (red and green when `color`); `...` stands for skipped lines
+pub fn format_diff(diff: &[DiffLine], color: bool) -> String {
+    let changed: Vec<usize> = diff
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, DiffLine::Same(_)))
+        .map(|(index, _)| index)
+        .collect();
+    let shown = |index: usize| {
+        changed
+            .iter()
+            .any(|&c| index + DIFF_CONTEXT >= c && index <= c + DIFF_CONTEXT)
+    };
+    let paint = |color_of: Color, text: String| {
+        if color {
+            color_of.paint(text).to_string()
+        } else {
+            text
+        }
+    };
+
+    let mut out = Vec::new();
+    let mut skipped = false;
+    for (index, line) in diff.iter().enumerate() {
+        if !shown(index) {
+            skipped = true;
+            continue;
+        }
+        if std::mem::take(&mut skipped) {
+            out.push("  ...".to_string());
+        }
+        out.push(match line {
+            DiffLine::Same(text) => format!("  {text}"),
+            DiffLine::Removed(text) => paint(Color::Red, format!("- {text}")),
+            DiffLine::Added(text) => paint(Color::Green, format!("+ {text}")),
+        });
+    }
+    if skipped {
+        out.push("  ...".to_string());
+    }
+    out.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_sql() {
+        let sql = lookup_sql("Billing.\"TotalFor\"");
+        assert!(sql.contains("p.proname = 'TotalFor'"));
+        assert!(sql.contains("n.nspname = 'billing'"));
+        assert!(lookup_sql("total_for").contains("pg_function_is_visible"));
+        assert!(
+            lookup_sql("total_for(integer, text)")
+                .contains("p.oid = 'total_for(integer, text)'::pg_catalog.regprocedure")
+        );
+        assert!(lookup_sql("o'brien").contains("'o''brien'"));
+    }
+
+    #[test]
+    fn test_render_numbers_body_lines() {
+        let source = FunctionSource {
+            signature: "public.add_one(integer)".to_string(),
+            definition: "CREATE OR REPLACE FUNCTION public.add_one(x integer)\n RETURNS integer\n LANGUAGE plpgsql\nAS $function$\nBEGIN\n  RETURN x + 1;\nEND;\n$function$\n".to_string(),
+        };
+        let numbered = source.render(true, false);
+        let lines: Vec<&str> = numbered.lines().collect();
+        assert_eq!(
+            lines[0],
+            "   CREATE OR REPLACE FUNCTION public.add_one(x integer)"
+        );
+        assert_eq!(lines[3], "1  AS $function$");
+        assert_eq!(lines[5], "3    RETURN x + 1;");
+        assert!(source.render(false, false).starts_with("CREATE OR REPLACE"));
+    }
+
+    #[test]
+    fn test_line_diff_and_format() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj";
+        let new = "a\nb\nc\nd\ne\nF\ng\nh\ni\nj";
+        let diff = line_diff(old, new);
+        assert_eq!(diff[5], DiffLine::Removed("f"));
+        assert_eq!(diff[6], DiffLine::Added("F"));
+        assert_eq!(
+            format_diff(&diff, false),
+            "  ...\n  c\n  d\n  e\n- f\n+ F\n  g\n  h\n  i\n  ..."
+        );
+        assert_eq!(
+            line_diff("x", "x\ny"),
+            [DiffLine::Same("x"), DiffLine::Added("y")]
+        );
+    }
+
+    #[test]
+    fn test_is_create_or_replace() {
+        assert!(is_create_or_replace(
+            "-- tweaked\ncreate or replace function f() returns int language sql as 'select 1'"
+        ));
+        assert!(!is_create_or_replace("DROP FUNCTION f()"));
+    }
+}
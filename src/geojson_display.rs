@@ -1,7 +1,11 @@
 //! GeoJSON data display implementation using the unified complex display system
+//!
+//! Also decodes PostGIS `geometry`/`geography` values, which arrive as
+//! (E)WKB, into WKT or GeoJSON for [`GeometryDisplayAdapter`].
 
 use crate::complex_display::{
     ComplexDataDisplay, ComplexDataMetadata, ComplexDataParser, ComplexDisplayConfig,
+    GeometryFormat,
 };
 use serde_json::Value;
 use std::collections::HashSet;
@@ -531,6 +535,500 @@ impl ComplexDataParser<GeoJsonDisplayAdapter> for GeoJsonDisplayAdapter {
     }
 }
 
+/// A PostGIS geometry decoded from WKB or PostGIS' extended WKB
+#[derive(Debug, Clone, PartialEq)]
+pub struct Geometry {
+    pub srid: Option<i32>,
+    pub has_z: bool,
+    pub has_m: bool,
+    pub shape: Shape,
+}
+
+/// Geometry body. Positions hold x and y, then z and/or m when present.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    /// An empty position is `POINT EMPTY`
+    Point(Vec<f64>),
+    LineString(Vec<Vec<f64>>),
+    Polygon(Vec<Vec<Vec<f64>>>),
+    MultiPoint(Vec<Shape>),
+    MultiLineString(Vec<Shape>),
+    MultiPolygon(Vec<Shape>),
+    GeometryCollection(Vec<Shape>),
+}
+
+const EWKB_Z: u32 = 0x8000_0000;
+const EWKB_M: u32 = 0x4000_0000;
+const EWKB_SRID: u32 = 0x2000_0000;
+/// Collections nested deeper than this are rejected rather than recursed into
+const MAX_WKB_DEPTH: usize = 64;
+
+struct WkbReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    little_endian: bool,
+}
+
+impl WkbReader<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let chunk = self
+            .bytes
+            .get(self.pos..self.pos + N)
+            .ok_or("WKB value ends early")?;
+        self.pos += N;
+        let mut buf = [0u8; N];
+        buf.copy_from_slice(chunk);
+        Ok(buf)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let buf = self.take::<4>()?;
+        Ok(if self.little_endian {
+            u32::from_le_bytes(buf)
+        } else {
+            u32::from_be_bytes(buf)
+        })
+    }
+
+    fn f64(&mut self) -> Result<f64, String> {
+        let buf = self.take::<8>()?;
+        Ok(if self.little_endian {
+            f64::from_le_bytes(buf)
+        } else {
+            f64::from_be_bytes(buf)
+        })
+    }
+
+    /// Element count, checked against the bytes left so a corrupt value
+    /// cannot ask for a huge allocation
+    fn count(&mut self, min_element_size: usize) -> Result<usize, String> {
+        let count = self.u32()? as usize;
+        if count.saturating_mul(min_element_size) > self.bytes.len() - self.pos {
+            return Err(format!("WKB value too short for {count} elements"));
+        }
+        Ok(count)
+    }
+
+    fn position(&mut self, dims: usize) -> Result<Vec<f64>, String> {
+        (0..dims).map(|_| self.f64()).collect()
+    }
+
+    fn positions(&mut self, dims: usize) -> Result<Vec<Vec<f64>>, String> {
+        let count = self.count(dims * 8)?;
+        (0..count).map(|_| self.position(dims)).collect()
+    }
+
+    /// The geometry at the current position; `depth` counts the enclosing
+    /// collections
+    fn geometry(&mut self, depth: usize) -> Result<Geometry, String> {
+        if depth > MAX_WKB_DEPTH {
+            return Err(format!(
+                "WKB collections nested more than {MAX_WKB_DEPTH} levels deep"
+            ));
+        }
+        self.little_endian = match self.take::<1>()?[0] {
+            0 => false,
+            1 => true,
+            other => return Err(format!("invalid WKB byte order {other}")),
+        };
+        let code = self.u32()?;
+        let srid = if code & EWKB_SRID != 0 {
+            Some(self.u32()? as i32)
+        } else {
+            None
+        };
+        // PostGIS flags the dimensions with high bits, ISO WKB adds 1000/2000/3000
+        let base = code & 0x0FFF_FFFF;
+        let (iso_z, iso_m) = match base / 1000 {
+            0 => (false, false),
+            1 => (true, false),
+            2 => (false, true),
+            3 => (true, true),
+            _ => return Err(format!("unsupported WKB geometry type {base}")),
+        };
+        let has_z = code & EWKB_Z != 0 || iso_z;
+        let has_m = code & EWKB_M != 0 || iso_m;
+        let dims = 2 + has_z as usize + has_m as usize;
+
+        let parts = |reader: &mut Self| -> Result<Vec<Shape>, String> {
+            let count = reader.count(5)?;
+            (0..count)
+                .map(|_| reader.geometry(depth + 1).map(|part| part.shape))
+                .collect()
+        };
+        let shape = match base % 1000 {
+            1 => {
+                let position = self.position(dims)?;
+                if position.iter().all(|c| c.is_nan()) {
+                    Shape::Point(Vec::new())
+                } else {
+                    Shape::Point(position)
+                }
+            }
+            2 => Shape::LineString(self.positions(dims)?),
+            3 => {
+                let rings = self.count(4)?;
+                Shape::Polygon(
+                    (0..rings)
+                        .map(|_| self.positions(dims))
+                        .collect::<Result<_, _>>()?,
+                )
+            }
+            4 => Shape::MultiPoint(parts(self)?),
+            5 => Shape::MultiLineString(parts(self)?),
+            6 => Shape::MultiPolygon(parts(self)?),
+            7 => Shape::GeometryCollection(parts(self)?),
+            other => return Err(format!("unsupported WKB geometry type {other}")),
+        };
+        Ok(Geometry {
+            srid,
+            has_z,
+            has_m,
+            shape,
+        })
+    }
+}
+
+/// Decode a WKB or EWKB value, as PostgreSQL sends `geometry` and `geography`
+pub fn decode_wkb(bytes: &[u8]) -> Result<Geometry, String> {
+    let mut reader = WkbReader {
+        bytes,
+        pos: 0,
+        little_endian: true,
+    };
+    let geometry = reader.geometry(0)?;
+    if reader.pos != bytes.len() {
+        return Err(format!(
+            "{} unexpected bytes after the WKB geometry",
+            bytes.len() - reader.pos
+        ));
+    }
+    Ok(geometry)
+}
+
+/// Decode the hex text form (`0101000020E6100000...`, optionally `\x`-prefixed)
+pub fn decode_hex_wkb(text: &str) -> Result<Geometry, String> {
+    let text = text.trim();
+    let text = text.strip_prefix("\\x").unwrap_or(text);
+    let bytes = hex::decode(text).map_err(|e| format!("invalid WKB hex: {e}"))?;
+    decode_wkb(&bytes)
+}
+
+impl Shape {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Shape::Point(_) => "Point",
+            Shape::LineString(_) => "LineString",
+            Shape::Polygon(_) => "Polygon",
+            Shape::MultiPoint(_) => "MultiPoint",
+            Shape::MultiLineString(_) => "MultiLineString",
+            Shape::MultiPolygon(_) => "MultiPolygon",
+            Shape::GeometryCollection(_) => "GeometryCollection",
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        match self {
+            Shape::Point(p) => p.is_empty(),
+            Shape::LineString(ps) => ps.is_empty(),
+            Shape::Polygon(rings) => rings.is_empty(),
+            Shape::MultiPoint(parts)
+            | Shape::MultiLineString(parts)
+            | Shape::MultiPolygon(parts)
+            | Shape::GeometryCollection(parts) => parts.is_empty(),
+        }
+    }
+
+    /// Every position, in order
+    fn for_each_position(&self, f: &mut impl FnMut(&[f64])) {
+        match self {
+            Shape::Point(p) if !p.is_empty() => f(p),
+            Shape::Point(_) => {}
+            Shape::LineString(ps) => ps.iter().for_each(|p| f(p)),
+            Shape::Polygon(rings) => rings.iter().flatten().for_each(|p| f(p)),
+            Shape::MultiPoint(parts)
+            | Shape::MultiLineString(parts)
+            | Shape::MultiPolygon(parts)
+            | Shape::GeometryCollection(parts) => {
+                parts.iter().for_each(|part| part.for_each_position(f))
+            }
+        }
+    }
+
+    fn wkt(&self, dims: &str) -> String {
+        let keyword = self.type_name().to_uppercase();
+        if self.is_empty() {
+            return format!("{keyword} {}EMPTY", dims.trim_start());
+        }
+        let position = |p: &[f64]| {
+            p.iter()
+                .map(|c| c.to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        let list = |ps: &[Vec<f64>]| {
+            format!(
+                "({})",
+                ps.iter().map(|p| position(p)).collect::<Vec<_>>().join(",")
+            )
+        };
+        let body = match self {
+            Shape::Point(p) => format!("({})", position(p)),
+            Shape::LineString(ps) => list(ps),
+            Shape::Polygon(rings) => format!(
+                "({})",
+                rings.iter().map(|r| list(r)).collect::<Vec<_>>().join(",")
+            ),
+            Shape::MultiPoint(parts)
+            | Shape::MultiLineString(parts)
+            | Shape::MultiPolygon(parts) => {
+                format!(
+                    "({})",
+                    parts
+                        .iter()
+                        .map(|part| part
+                            .wkt(dims)
+                            .split_once('(')
+                            .map_or_else(|| "EMPTY".to_string(), |(_, rest)| format!("({rest}")))
+                        .collect::<Vec<_>>()
+                        .join(",")
+                )
+            }
+            Shape::GeometryCollection(parts) => format!(
+                "({})",
+                parts
+                    .iter()
+                    .map(|part| part.wkt(dims))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
+        };
+        format!("{keyword}{dims}{body}")
+    }
+
+    fn geojson_coordinates(&self, has_z: bool) -> Value {
+        // GeoJSON positions carry no measure. A part may have fewer
+        // dimensions than the collection holding it.
+        let position = |p: &[f64]| {
+            let dims = if has_z { 3 } else { 2 };
+            Value::from(p[..dims.min(p.len())].to_vec())
+        };
+        let list = |ps: &[Vec<f64>]| Value::Array(ps.iter().map(|p| position(p)).collect());
+        match self {
+            Shape::Point(p) if p.is_empty() => Value::Array(Vec::new()),
+            Shape::Point(p) => position(p),
+            Shape::LineString(ps) => list(ps),
+            Shape::Polygon(rings) => Value::Array(rings.iter().map(|r| list(r)).collect()),
+            Shape::MultiPoint(parts)
+            | Shape::MultiLineString(parts)
+            | Shape::MultiPolygon(parts)
+            | Shape::GeometryCollection(parts) => Value::Array(
+                parts
+                    .iter()
+                    .map(|part| part.geojson_coordinates(has_z))
+                    .collect(),
+            ),
+        }
+    }
+
+    fn geojson(&self, has_z: bool) -> Value {
+        let mut object = serde_json::Map::new();
+        object.insert("type".to_string(), Value::from(self.type_name()));
+        if let Shape::GeometryCollection(parts) = self {
+            object.insert(
+                "geometries".to_string(),
+                Value::Array(parts.iter().map(|part| part.geojson(has_z)).collect()),
+            );
+        } else {
+            object.insert("coordinates".to_string(), self.geojson_coordinates(has_z));
+        }
+        Value::Object(object)
+    }
+}
+
+impl Geometry {
+    /// `Point`, `Polygon Z`, `MultiLineString ZM`...
+    pub fn type_label(&self) -> String {
+        match self.dims_suffix() {
+            "" => self.shape.type_name().to_string(),
+            suffix => format!("{}{}", self.shape.type_name(), suffix.trim_end()),
+        }
+    }
+
+    fn dims_suffix(&self) -> &'static str {
+        match (self.has_z, self.has_m) {
+            (false, false) => "",
+            (true, false) => " Z ",
+            (false, true) => " M ",
+            (true, true) => " ZM ",
+        }
+    }
+
+    /// ISO WKT, as `ST_AsText` prints it
+    pub fn to_wkt(&self) -> String {
+        self.shape.wkt(self.dims_suffix())
+    }
+
+    /// WKT with the `SRID=n;` prefix when the value carries one
+    pub fn to_ewkt(&self) -> String {
+        match self.srid {
+            Some(srid) if srid != 0 => format!("SRID={srid};{}", self.to_wkt()),
+            _ => self.to_wkt(),
+        }
+    }
+
+    /// GeoJSON geometry object; measures are dropped
+    pub fn to_geojson(&self) -> Value {
+        self.shape.geojson(self.has_z)
+    }
+
+    /// x/y of every position
+    fn coordinates(&self) -> Vec<Coordinate> {
+        let mut coords = Vec::new();
+        self.shape.for_each_position(&mut |p| {
+            coords.push(Coordinate {
+                lng: p[0],
+                lat: p[1],
+            })
+        });
+        coords
+    }
+
+    fn part_count(&self) -> usize {
+        match &self.shape {
+            Shape::Polygon(rings) => rings.len(),
+            Shape::MultiPoint(parts)
+            | Shape::MultiLineString(parts)
+            | Shape::MultiPolygon(parts)
+            | Shape::GeometryCollection(parts) => parts.len(),
+            _ => 1,
+        }
+    }
+}
+
+/// Display adapter for PostGIS `geometry`/`geography` values
+pub struct GeometryDisplayAdapter {
+    pub geometry: Geometry,
+}
+
+impl GeometryDisplayAdapter {
+    pub fn new(geometry: Geometry) -> Self {
+        Self { geometry }
+    }
+
+    /// EWKT or GeoJSON depending on `geometry_format`
+    fn text(&self, config: &ComplexDisplayConfig) -> String {
+        match config.geometry_format {
+            GeometryFormat::Wkt => self.geometry.to_ewkt(),
+            GeometryFormat::GeoJson if config.json_pretty_print => {
+                serde_json::to_string_pretty(&self.geometry.to_geojson()).unwrap_or_default()
+            }
+            GeometryFormat::GeoJson => self.geometry.to_geojson().to_string(),
+        }
+    }
+
+    /// `Polygon, 5 points, SRID 4326`
+    fn description(&self, points: usize) -> String {
+        let noun = if points == 1 { "point" } else { "points" };
+        let mut description = format!("{}, {points} {noun}", self.geometry.type_label());
+        if let Some(srid) = self.geometry.srid.filter(|&srid| srid != 0) {
+            description.push_str(&format!(", SRID {srid}"));
+        }
+        description
+    }
+}
+
+impl ComplexDataDisplay for GeometryDisplayAdapter {
+    fn metadata(&self) -> ComplexDataMetadata {
+        let points = self.geometry.coordinates().len();
+        ComplexDataMetadata {
+            data_type: "geometry".to_string(),
+            size: points,
+            depth: None,
+            has_nested: matches!(
+                self.geometry.shape,
+                Shape::MultiPoint(_)
+                    | Shape::MultiLineString(_)
+                    | Shape::MultiPolygon(_)
+                    | Shape::GeometryCollection(_)
+            ),
+            schema_info: Some(format!("Geometry({})", self.description(points))),
+        }
+    }
+
+    fn format_full(&self, config: &ComplexDisplayConfig) -> String {
+        let text = self.text(config);
+        if config.show_metadata {
+            let points = self.geometry.coordinates().len();
+            format!("{text}\n[{}]", self.description(points))
+        } else {
+            text
+        }
+    }
+
+    fn format_truncated(&self, config: &ComplexDisplayConfig) -> String {
+        let text = self.text(config);
+        let compact = if config.geometry_format == GeometryFormat::GeoJson {
+            self.geometry.to_geojson().to_string()
+        } else {
+            text
+        };
+        if compact.chars().count() <= config.max_width {
+            return compact;
+        }
+        let shown: String = compact.chars().take(config.max_width).collect();
+        let points = self.geometry.coordinates().len();
+        format!("{shown}… [{}]", self.description(points))
+    }
+
+    fn format_summary(&self, _config: &ComplexDisplayConfig) -> String {
+        let coords = self.geometry.coordinates();
+        let mut parts = vec![format!("Geometry: {}", self.geometry.type_label())];
+        match self.geometry.srid.filter(|&srid| srid != 0) {
+            Some(srid) => parts.push(format!("SRID: {srid}")),
+            None => parts.push("SRID: none".to_string()),
+        }
+        let part_count = self.geometry.part_count();
+        if part_count > 1 {
+            let label = match self.geometry.shape {
+                Shape::Polygon(_) => "Rings",
+                _ => "Parts",
+            };
+            parts.push(format!("{label}: {part_count}"));
+        }
+        parts.push(format!("Points: {}", coords.len()));
+        if coords.len() > 1 {
+            let adapter = self.geojson_adapter();
+            if let Some(bounds) = adapter.calculate_bounds(&coords) {
+                parts.push(format!(
+                    "Bounds: [{:.3}, {:.3}] to [{:.3}, {:.3}]",
+                    bounds.min_lng, bounds.min_lat, bounds.max_lng, bounds.max_lat
+                ));
+            }
+        }
+        parts.join(" | ")
+    }
+
+    fn format_viz(&self, config: &ComplexDisplayConfig) -> String {
+        let coords = self.geometry.coordinates();
+        let map = self
+            .geojson_adapter()
+            .create_map_viz(&coords, config.viz_width);
+        format!("{}\n{map}", self.description(coords.len()))
+    }
+}
+
+impl GeometryDisplayAdapter {
+    /// GeoJSON adapter over the same geometry, for its bounds and map helpers
+    fn geojson_adapter(&self) -> GeoJsonDisplayAdapter {
+        let value = self.geometry.to_geojson();
+        GeoJsonDisplayAdapter {
+            raw_geojson: value.to_string(),
+            value,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -606,4 +1104,93 @@ mod tests {
         assert!(!GeoJsonDisplayAdapter::validate(r#"{"invalid": "json"}"#));
         assert!(!GeoJsonDisplayAdapter::validate("not json at all"));
     }
+
+    #[test]
+    fn test_decode_ewkb_point_with_srid() {
+        let geometry =
+            decode_hex_wkb("0101000020E6100000000000000000F03F0000000000000040").unwrap();
+        assert_eq!(geometry.srid, Some(4326));
+        assert_eq!(geometry.to_ewkt(), "SRID=4326;POINT(1 2)");
+        assert_eq!(
+            geometry.to_geojson().to_string(),
+            r#"{"type":"Point","coordinates":[1.0,2.0]}"#
+        );
+        // Same point, big endian without SRID
+        let geometry = decode_hex_wkb("\\x00000000013FF00000000000004000000000000000").unwrap();
+        assert_eq!(
+            (geometry.srid, geometry.to_wkt()),
+            (None, "POINT(1 2)".to_string())
+        );
+
+        assert!(decode_hex_wkb("0101000020E6100000000000000000F03F").is_err());
+        assert!(decode_hex_wkb("not hex").is_err());
+    }
+
+    #[test]
+    fn test_decode_polygon_and_multipoint_z() {
+        // POLYGON((0 0,1 0,1 1,0 0)) with SRID 3857
+        let polygon = decode_hex_wkb(concat!(
+            "0103000020110F00000100000004000000",
+            "00000000000000000000000000000000",
+            "000000000000F03F0000000000000000",
+            "000000000000F03F000000000000F03F",
+            "00000000000000000000000000000000"
+        ))
+        .unwrap();
+        assert_eq!(polygon.to_wkt(), "POLYGON((0 0,1 0,1 1,0 0))");
+        let adapter = GeometryDisplayAdapter::new(polygon);
+        let config = ComplexDisplayConfig::default();
+        assert_eq!(
+            adapter.format_summary(&config),
+            "Geometry: Polygon | SRID: 3857 | Points: 4 | Bounds: [0.000, 0.000] to [1.000, 1.000]"
+        );
+
+        // ISO MULTIPOINT Z((1 2 3)) (type 1004, part type 1001)
+        let multipoint = decode_hex_wkb(concat!(
+            "01EC03000001000000",
+            "01E9030000000000000000F03F00000000000000400000000000000840"
+        ))
+        .unwrap();
+        assert_eq!(multipoint.to_wkt(), "MULTIPOINT Z ((1 2 3))");
+        assert_eq!(multipoint.type_label(), "MultiPoint Z");
+        // GEOMETRYCOLLECTION Z holding a 2D point: each position keeps its own length
+        let mixed = decode_hex_wkb(concat!(
+            "01EF03000001000000",
+            "0101000000000000000000F03F0000000000000040"
+        ))
+        .unwrap();
+        assert_eq!(
+            mixed.to_geojson().to_string(),
+            r#"{"type":"GeometryCollection","geometries":[{"type":"Point","coordinates":[1.0,2.0]}]}"#
+        );
+
+        // Nesting is capped instead of recursing without bound
+        let nested = format!(
+            "{}010700000000000000",
+            "010700000001000000".repeat(MAX_WKB_DEPTH + 1)
+        );
+        assert!(decode_hex_wkb(&nested).unwrap_err().contains("nested"));
+    }
+
+    #[test]
+    fn test_geometry_truncated_and_geojson_format() {
+        let geometry =
+            decode_hex_wkb("0101000020E6100000000000000000F03F0000000000000040").unwrap();
+        let adapter = GeometryDisplayAdapter::new(geometry);
+        let mut config = ComplexDisplayConfig {
+            max_width: 12,
+            ..Default::default()
+        };
+        assert_eq!(
+            adapter.format_truncated(&config),
+            "SRID=4326;PO… [Point, 1 point, SRID 4326]"
+        );
+
+        config.geometry_format = GeometryFormat::GeoJson;
+        config.max_width = 80;
+        assert_eq!(
+            adapter.format(&config),
+            r#"{"type":"Point","coordinates":[1.0,2.0]}"#
+        );
+    }
 }
@@ -0,0 +1,174 @@
+//! Typed confirmation for dangerous statements (`[guardrails]`)
+//!
+//! Statements matching one of `confirm_patterns` (case-insensitive regexes,
+//! tried against the statement with whitespace collapsed and the trailing
+//! semicolon removed) are held back in the REPL until the name of the table
+//! they touch is typed back, the way hosting sites confirm a repository
+//! deletion. Statements without a recognizable target ask for the matched
+//! keyword instead.
+
+use nu_ansi_term::Color;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// `[guardrails]` configuration section
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct GuardrailsConfig {
+    /// Regexes; a matching statement needs its target typed back before it runs
+    pub confirm_patterns: Vec<String>,
+}
+
+impl GuardrailsConfig {
+    /// Compile the patterns, case-insensitive
+    pub fn compile(&self) -> Result<Vec<Regex>, String> {
+        self.confirm_patterns
+            .iter()
+            .map(|pattern| {
+                Regex::new(&format!("(?i){pattern}"))
+                    .map_err(|e| format!("Invalid guardrails pattern '{pattern}': {e}"))
+            })
+            .collect()
+    }
+}
+
+/// A statement held back by a guardrail
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuardedStatement {
+    /// The pattern that matched, as configured
+    pub pattern: String,
+    /// What must be typed back: the target table, or the matched text
+    pub confirmation: String,
+}
+
+/// Statement text the patterns are matched against
+fn normalize(sql: &str) -> String {
+    sql.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_end_matches(';')
+        .trim_end()
+        .to_string()
+}
+
+/// Object a destructive statement works on: `DROP TABLE [IF EXISTS] x`,
+/// `TRUNCATE [TABLE] x`, `DELETE FROM x`, `UPDATE x`, `ALTER TABLE x`, ...
+fn statement_target(sql: &str) -> Option<String> {
+    let words: Vec<&str> = sql.split_whitespace().collect();
+    let upper: Vec<String> = words.iter().map(|word| word.to_uppercase()).collect();
+    let mut i = match upper.first()?.as_str() {
+        "DROP" | "ALTER" => 2,
+        "TRUNCATE" if upper.get(1).is_some_and(|word| word == "TABLE") => 2,
+        "TRUNCATE" | "UPDATE" => 1,
+        "DELETE" if upper.get(1).is_some_and(|word| word == "FROM") => 2,
+        _ => return None,
+    };
+    if upper.get(i).is_some_and(|word| word == "IF") {
+        i += 2; // IF EXISTS
+    }
+    if upper.get(i).is_some_and(|word| word == "ONLY") {
+        i += 1;
+    }
+    let name = words
+        .get(i)?
+        .trim_end_matches([';', ','])
+        .trim_end_matches("()");
+    let name: String = name
+        .chars()
+        .filter(|c| !matches!(c, '"' | '`' | '[' | ']'))
+        .collect();
+    (!name.is_empty()).then_some(name)
+}
+
+/// The guardrail `sql` trips, if any
+pub fn check(sql: &str, patterns: &[Regex], config: &GuardrailsConfig) -> Option<GuardedStatement> {
+    let normalized = normalize(sql);
+    let (index, matched) = patterns
+        .iter()
+        .enumerate()
+        .find_map(|(index, regex)| regex.find(&normalized).map(|m| (index, m.as_str())))?;
+    let confirmation = statement_target(&normalized).unwrap_or_else(|| matched.trim().to_string());
+    Some(GuardedStatement {
+        pattern: config.confirm_patterns[index].clone(),
+        confirmation,
+    })
+}
+
+/// Warning shown before asking for the confirmation
+pub fn warning(guarded: &GuardedStatement) -> String {
+    format!(
+        "{} this statement matches the guardrail pattern '{}'.\nType {} to run it, anything else cancels.",
+        Color::Red.bold().paint("Careful:"),
+        guarded.pattern,
+        Color::Yellow.bold().paint(&guarded.confirmation)
+    )
+}
+
+/// Ask for the confirmation when `sql` trips a guardrail of `config`; `Err`
+/// tells why the statement must not run
+pub fn confirm(sql: &str, config: &GuardrailsConfig) -> Result<(), String> {
+    if config.confirm_patterns.is_empty() {
+        return Ok(());
+    }
+    // Fail closed: a broken pattern must not silently disable the guardrail
+    let patterns = config
+        .compile()
+        .map_err(|e| format!("Statement not run: {e} (fix [guardrails] in config.toml)"))?;
+    let Some(guarded) = check(sql, &patterns, config) else {
+        return Ok(());
+    };
+
+    eprintln!("{}", warning(&guarded));
+    let typed = inquire::Text::new("Confirm:")
+        .with_help_message("Esc or Ctrl-C cancels")
+        .prompt();
+    match typed {
+        Ok(typed) if typed.trim() == guarded.confirmation => Ok(()),
+        Ok(_) => Err("Statement not run: the confirmation did not match".to_string()),
+        Err(_) => Err("Statement not run: cancelled".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(patterns: &[&str]) -> GuardrailsConfig {
+        GuardrailsConfig {
+            confirm_patterns: patterns.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_check_asks_for_the_target() {
+        let config = config(&["DROP TABLE", "TRUNCATE", r"DELETE FROM \w+$"]);
+        let patterns = config.compile().unwrap();
+        let guard = |sql: &str| check(sql, &patterns, &config).map(|g| g.confirmation);
+
+        assert_eq!(
+            guard("drop table if exists public.\"Users\";"),
+            Some("public.Users".to_string())
+        );
+        assert_eq!(
+            guard("TRUNCATE TABLE audit_log"),
+            Some("audit_log".to_string())
+        );
+        assert_eq!(
+            guard("DELETE FROM sessions ;"),
+            Some("sessions".to_string())
+        );
+        // Only unbounded deletes match the anchored pattern
+        assert_eq!(guard("DELETE FROM sessions WHERE id = 1"), None);
+        assert_eq!(
+            guard("SELECT 'drop table x'"),
+            Some("drop table".to_string())
+        );
+        assert_eq!(guard("SELECT 1"), None);
+    }
+
+    #[test]
+    fn test_invalid_pattern_is_reported() {
+        let err = config(&["DROP (TABLE"]).compile().unwrap_err();
+        assert!(err.contains("DROP (TABLE"));
+    }
+}
@@ -1,6 +1,7 @@
 use nu_ansi_term::{Color, Style};
 use reedline::{Highlighter, StyledText};
 use regex::Regex;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 
 pub struct SqlHighlighter {
@@ -9,6 +10,8 @@ pub struct SqlHighlighter {
     sql_functions: Vec<String>,
     /// Shared state to pass full line content to the completer
     full_line_buffer: Arc<Mutex<Option<String>>>,
+    /// Cursor position in that line, for the snippet edit mode
+    cursor_position: Arc<AtomicUsize>,
 }
 
 impl Default for SqlHighlighter {
@@ -215,17 +218,26 @@ impl SqlHighlighter {
             sql_types: sql_types.into_iter().map(String::from).collect(),
             sql_functions: sql_functions.into_iter().map(String::from).collect(),
             full_line_buffer,
+            cursor_position: Arc::new(AtomicUsize::new(0)),
         }
     }
+
+    /// Share the cursor position seen on each repaint
+    #[allow(dead_code)]
+    pub fn with_cursor_position(mut self, cursor_position: Arc<AtomicUsize>) -> Self {
+        self.cursor_position = cursor_position;
+        self
+    }
 }
 
 impl Highlighter for SqlHighlighter {
-    fn highlight(&self, line: &str, _cursor: usize) -> StyledText {
+    fn highlight(&self, line: &str, cursor: usize) -> StyledText {
         // 🎯 CAPTURE FULL LINE FOR COMPLETER ACCESS!
         // This is the breakthrough - highlighter gets full line before completer runs
         if let Ok(mut buffer_guard) = self.full_line_buffer.lock() {
             *buffer_guard = Some(line.to_string());
         }
+        self.cursor_position.store(cursor, Ordering::Relaxed);
 
         // AI prompts (`??` text-to-SQL and `???` agentic) are natural language, not
         // SQL — applying SQL keyword/string/number coloring to them is noise. Render
@@ -403,10 +415,58 @@ impl Highlighter for SqlHighlighter {
             return styled_text;
         }
 
+        // The parenthesis next to the cursor and its match; red when it has none
+        if let Some((position, matching)) = crate::sql_buffer::matching_bracket(line, cursor) {
+            let bracket_style = match matching {
+                Some(_) => Style::new().fg(Color::Cyan).bold().underline(),
+                None => Style::new().fg(Color::Red).bold(),
+            };
+            for offset in std::iter::once(position).chain(matching) {
+                restyle_byte(&mut final_styled_text, offset, bracket_style);
+            }
+        }
+
         final_styled_text
     }
 }
 
+/// Give the one-byte character at `offset` of the styled line its own style
+fn restyle_byte(styled: &mut StyledText, offset: usize, style: Style) {
+    let mut start = 0;
+    for index in 0..styled.buffer.len() {
+        let end = start + styled.buffer[index].1.len();
+        if (start..end).contains(&offset) {
+            let (original, text) = styled.buffer.remove(index);
+            let at = offset - start;
+            let pieces = [
+                (original, text[..at].to_string()),
+                (style, text[at..at + 1].to_string()),
+                (original, text[at + 1..].to_string()),
+            ];
+            for (i, piece) in pieces
+                .into_iter()
+                .filter(|(_, text)| !text.is_empty())
+                .enumerate()
+            {
+                styled.buffer.insert(index + i, piece);
+            }
+            return;
+        }
+        start = end;
+    }
+}
+
+/// `sql` colored like the prompt, as ANSI escapes. Highlighted line by line
+/// since the comment pattern only reaches the end of a line.
+#[allow(dead_code)]
+pub fn highlight_sql(sql: &str) -> String {
+    let highlighter = SqlHighlighter::default();
+    sql.lines()
+        .map(|line| highlighter.highlight(line, 0).render_simple())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -443,6 +503,26 @@ mod tests {
         assert!(styled.buffer.len() > 1);
     }
 
+    #[rstest]
+    fn test_matching_parentheses_highlighted() {
+        let highlighter = SqlHighlighter::default();
+        let line = "SELECT count(id) FROM t";
+        let matched = Style::new().fg(Color::Cyan).bold().underline();
+        let styled = highlighter.highlight(line, 16);
+        let marked: Vec<&str> = styled
+            .buffer
+            .iter()
+            .filter(|(style, _)| *style == matched)
+            .map(|(_, text)| text.as_str())
+            .collect();
+        assert_eq!(marked, vec!["(", ")"]);
+        assert_eq!(styled.raw_string(), line);
+
+        // Nothing is marked away from a parenthesis
+        let styled = highlighter.highlight(line, 2);
+        assert!(!styled.buffer.iter().any(|(style, _)| *style == matched));
+    }
+
     #[rstest]
     fn test_complex_sql_query() {
         let highlighter = SqlHighlighter::new(Arc::new(Mutex::new(None)));
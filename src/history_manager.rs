@@ -1,8 +1,12 @@
-use reedline::{FileBackedHistory, History};
+use reedline::{
+    FileBackedHistory, History, HistoryItem, HistoryItemId, HistorySessionId, SearchDirection,
+    SearchQuery,
+};
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 use std::time::SystemTime;
 use tracing::{debug, warn};
 
@@ -186,6 +190,19 @@ impl SessionHistoryManager {
         )
     }
 
+    /// Entries of the history a session uses (the default one without a
+    /// session), oldest first
+    pub fn entries(&mut self, session_id: Option<&SessionId>) -> Vec<String> {
+        let history = match session_id {
+            Some(session_id) => self.get_session_history(session_id),
+            None => self.get_default_history(),
+        };
+        history
+            .search(SearchQuery::everything(SearchDirection::Forward, None))
+            .map(|items| items.into_iter().map(|item| item.command_line).collect())
+            .unwrap_or_default()
+    }
+
     /// List all session histories with metadata
     pub fn list_session_histories(
         &self,
@@ -315,6 +332,81 @@ pub struct SessionHistoryInfo {
     pub file_size: u64,
 }
 
+/// History that stores SQL statements formatted for the current database
+/// while `[history] store_formatted` is on. Backslash commands are stored
+/// with their credentials redacted (`\\c postgres://app:[REDACTED]@db/app`),
+/// `??` prompts as typed.
+pub struct FormattingHistory {
+    inner: Box<dyn History>,
+    config: Arc<Mutex<Config>>,
+    database: Arc<Mutex<Database>>,
+}
+
+impl FormattingHistory {
+    pub fn new(
+        inner: Box<dyn History>,
+        config: Arc<Mutex<Config>>,
+        database: Arc<Mutex<Database>>,
+    ) -> Self {
+        Self {
+            inner,
+            config,
+            database,
+        }
+    }
+}
+
+impl History for FormattingHistory {
+    fn save(&mut self, mut h: HistoryItem) -> reedline::Result<HistoryItem> {
+        let line = h.command_line.trim_start();
+        let is_sql = !line.is_empty() && !line.starts_with('\\') && !line.starts_with("??");
+        if is_sql && self.config.lock().unwrap().history.store_formatted {
+            let db_type = self.database.lock().unwrap().get_database_type();
+            h.command_line = crate::sql_formatter::format_sql(&h.command_line, &db_type);
+        } else if line.starts_with('\\') {
+            // The history file outlives the session: no passwords or tokens
+            h.command_line = crate::password_sanitizer::sanitize_text_for_logging(&h.command_line);
+        }
+        self.inner.save(h)
+    }
+
+    fn load(&self, id: HistoryItemId) -> reedline::Result<HistoryItem> {
+        self.inner.load(id)
+    }
+
+    fn count(&self, query: SearchQuery) -> reedline::Result<i64> {
+        self.inner.count(query)
+    }
+
+    fn search(&self, query: SearchQuery) -> reedline::Result<Vec<HistoryItem>> {
+        self.inner.search(query)
+    }
+
+    fn update(
+        &mut self,
+        id: HistoryItemId,
+        updater: &dyn Fn(HistoryItem) -> HistoryItem,
+    ) -> reedline::Result<()> {
+        self.inner.update(id, updater)
+    }
+
+    fn clear(&mut self) -> reedline::Result<()> {
+        self.inner.clear()
+    }
+
+    fn delete(&mut self, h: HistoryItemId) -> reedline::Result<()> {
+        self.inner.delete(h)
+    }
+
+    fn sync(&mut self) -> std::io::Result<()> {
+        self.inner.sync()
+    }
+
+    fn session(&self) -> Option<HistorySessionId> {
+        self.inner.session()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -334,6 +426,7 @@ mod tests {
             options: HashMap::new(),
             docker_container: None,
             use_tls: false,
+            srv: false,
         };
 
         let session_id = SessionId::from_connection_info(&connection_info);
@@ -359,6 +452,7 @@ mod tests {
             options: HashMap::new(),
             docker_container: None,
             use_tls: false,
+            srv: false,
         };
 
         let session_id = SessionId::from_connection_info(&connection_info);
@@ -379,6 +473,7 @@ mod tests {
             options: HashMap::new(),
             docker_container: Some("my-postgres-container".to_string()),
             use_tls: false,
+            srv: false,
         };
 
         let session_id = SessionId::from_connection_info(&connection_info);
@@ -402,6 +497,7 @@ mod tests {
             options: HashMap::new(),
             docker_container: None,
             use_tls: false,
+            srv: false,
         };
 
         connection_info
@@ -435,6 +531,7 @@ mod tests {
             options: HashMap::new(),
             docker_container: None,
             use_tls: false,
+            srv: false,
         };
 
         let session_id1 = SessionId::from_connection_info(&connection_info);
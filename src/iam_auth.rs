@@ -0,0 +1,333 @@
+//! Passwordless sign-in with cloud IAM tokens (`?auth=aws-iam`)
+//!
+//! Managed PostgreSQL and MySQL servers accept short-lived tokens issued by
+//! the cloud account in place of a password: RDS and Aurora IAM
+//! authentication, Cloud SQL IAM database authentication and Microsoft
+//! Entra ID (Azure AD) on Azure Database. With `auth=` in the URL no password
+//! is stored anywhere: every time a connection is opened, reconnects
+//! included, a fresh token is asked from the provider's CLI, signed in as
+//! whatever `aws`, `gcloud` or `az` is signed in as.
+//!
+//! - `aws-iam`: `aws rds generate-db-auth-token` for the host, port and
+//!   user; `aws_region` (default: taken from an RDS host name, else the CLI's
+//!   own configuration) and `aws_profile` pick the account
+//! - `gcp-iam`: `gcloud sql generate-login-token`
+//! - `azure-ad`: `az account get-access-token --resource-type oss-rdbms`
+//!
+//! Tokens expire (15 minutes for AWS and GCP, about an hour for Azure), so
+//! the pools that use one get a new token every [`REFRESH_INTERVAL`] for
+//! the connections they open later.
+//!
+//! AWS tokens are signed for the server's host and port. Behind an SSH
+//! tunnel those are set as `auth_host` and `auth_port` before the URL is
+//! pointed at the tunnel; set them by hand for other proxies.
+
+use crate::database::{ConnectionInfo, DatabaseError, DatabaseType};
+use std::collections::HashMap;
+use std::process::Command;
+use std::str::FromStr;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// How often a pool signing in with IAM tokens gets a new one, well within
+/// the shortest token lifetime (15 minutes)
+pub const REFRESH_INTERVAL: Duration = Duration::from_secs(10 * 60);
+/// Wait before asking again when the CLI could not issue a token
+const RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// URL options read here rather than by the database drivers
+const AUTH_OPTION_KEYS: &[&str] = &[
+    "auth",
+    "aws_region",
+    "aws_profile",
+    "auth_host",
+    "auth_port",
+];
+
+/// Whether `key` is one of the `auth=` options, never sent to the server
+pub fn is_auth_option(key: &str) -> bool {
+    AUTH_OPTION_KEYS
+        .iter()
+        .any(|option| key.eq_ignore_ascii_case(option))
+}
+
+/// Where the token comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IamProvider {
+    Aws,
+    Gcp,
+    Azure,
+}
+
+impl FromStr for IamProvider {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "aws-iam" | "aws" | "rds-iam" => Ok(IamProvider::Aws),
+            "gcp-iam" | "gcp" | "cloudsql-iam" => Ok(IamProvider::Gcp),
+            "azure-ad" | "azure" | "entra" => Ok(IamProvider::Azure),
+            other => Err(format!(
+                "Unknown auth '{other}': use aws-iam, gcp-iam or azure-ad"
+            )),
+        }
+    }
+}
+
+impl IamProvider {
+    /// The CLI asked for tokens
+    fn program(self) -> &'static str {
+        match self {
+            IamProvider::Aws => "aws",
+            IamProvider::Gcp => "gcloud",
+            IamProvider::Azure => "az",
+        }
+    }
+
+    /// Arguments of [`Self::program`] printing a token for `user` on
+    /// `host:port`
+    fn args(
+        self,
+        host: &str,
+        port: u16,
+        user: &str,
+        options: &HashMap<String, String>,
+    ) -> Vec<String> {
+        let mut args: Vec<String> = match self {
+            IamProvider::Aws => vec![
+                "rds".into(),
+                "generate-db-auth-token".into(),
+                "--hostname".into(),
+                host.into(),
+                "--port".into(),
+                port.to_string(),
+                "--username".into(),
+                user.into(),
+            ],
+            IamProvider::Gcp => vec!["sql".into(), "generate-login-token".into()],
+            IamProvider::Azure => [
+                "account",
+                "get-access-token",
+                "--resource-type",
+                "oss-rdbms",
+                "--query",
+                "accessToken",
+                "--output",
+                "tsv",
+            ]
+            .map(String::from)
+            .to_vec(),
+        };
+        if self == IamProvider::Aws {
+            let region = option(options, "aws_region").or_else(|| region_from_host(host));
+            if let Some(region) = region {
+                args.extend(["--region".to_string(), region.to_string()]);
+            }
+            if let Some(profile) = option(options, "aws_profile") {
+                args.extend(["--profile".to_string(), profile.to_string()]);
+            }
+        }
+        args
+    }
+}
+
+/// A non-empty option, keys compared case-insensitively
+fn option<'a>(options: &'a HashMap<String, String>, key: &str) -> Option<&'a str> {
+    options
+        .iter()
+        .find(|(name, value)| name.eq_ignore_ascii_case(key) && !value.trim().is_empty())
+        .map(|(_, value)| value.trim())
+}
+
+/// The provider `auth=` asks for; `None` without it or with `auth=password`
+pub fn provider(options: &HashMap<String, String>) -> Result<Option<IamProvider>, String> {
+    match option(options, "auth") {
+        None => Ok(None),
+        Some(value) if value.eq_ignore_ascii_case("password") => Ok(None),
+        Some(value) => value.parse().map(Some),
+    }
+}
+
+/// Region of an RDS endpoint, e.g. `eu-west-1` for
+/// `shop.abc123.eu-west-1.rds.amazonaws.com`
+fn region_from_host(host: &str) -> Option<&str> {
+    let labels: Vec<&str> = host.split('.').collect();
+    let rds = labels.iter().position(|label| *label == "rds")?;
+    let after = labels.get(rds + 1)?;
+    (rds > 0 && after.starts_with("amazonaws")).then(|| labels[rds - 1])
+}
+
+/// Run the CLI and return the token it printed
+fn run(program: &str, args: &[String]) -> Result<String, String> {
+    let output = Command::new(program).args(args).output().map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            format!("'{program}' was not found: install it and sign in to get IAM tokens")
+        } else {
+            format!("Failed to run '{program}': {e}")
+        }
+    })?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let detail = stderr
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .unwrap_or("no error output");
+        return Err(format!("'{program}' could not issue a token: {detail}"));
+    }
+    let token = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if token.is_empty() {
+        return Err(format!("'{program}' printed no token"));
+    }
+    Ok(token)
+}
+
+/// Put a fresh token in place of the password when the URL asks for IAM
+/// authentication; called each time a client connects
+pub async fn apply(connection_info: &mut ConnectionInfo) -> Result<(), DatabaseError> {
+    let Some(provider) = provider(&connection_info.options).map_err(DatabaseError::InvalidUrl)?
+    else {
+        return Ok(());
+    };
+    if !matches!(
+        connection_info.database_type,
+        DatabaseType::PostgreSQL | DatabaseType::MySQL
+    ) {
+        return Err(DatabaseError::FeatureNotSupported {
+            database_type: connection_info.database_type.clone(),
+            feature: "IAM authentication (auth=)".to_string(),
+        });
+    }
+    let user = connection_info.username.clone().ok_or_else(|| {
+        DatabaseError::InvalidUrl("IAM authentication needs a user name in the URL".to_string())
+    })?;
+    let host = option(&connection_info.options, "auth_host")
+        .map(str::to_string)
+        .or_else(|| connection_info.host.clone())
+        .unwrap_or_else(|| "localhost".to_string());
+    let port = match option(&connection_info.options, "auth_port") {
+        Some(port) => port
+            .parse()
+            .map_err(|_| DatabaseError::InvalidUrl(format!("Invalid auth_port '{port}'")))?,
+        None => connection_info
+            .port
+            .or_else(|| connection_info.default_port())
+            .unwrap_or_default(),
+    };
+
+    let program = provider.program();
+    let args = provider.args(&host, port, &user, &connection_info.options);
+    let token = tokio::task::spawn_blocking(move || run(program, &args))
+        .await
+        .map_err(|e| DatabaseError::ConnectionError(format!("IAM token task failed: {e}")))?
+        .map_err(DatabaseError::ConnectionError)?;
+    connection_info.password = Some(token);
+    Ok(())
+}
+
+/// Background refresh started by [`keep_fresh`]; stops when dropped
+#[derive(Debug)]
+pub struct TokenRefresh(tokio::task::AbortHandle);
+
+impl Drop for TokenRefresh {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// When `connection_info` signs in with IAM tokens, hand `set_password` a
+/// fresh token every [`REFRESH_INTERVAL`], so connections a pool opens
+/// after the first token expired still sign in; `None` otherwise
+pub fn keep_fresh(
+    connection_info: &ConnectionInfo,
+    set_password: impl Fn(String) + Send + 'static,
+) -> Option<TokenRefresh> {
+    if !matches!(provider(&connection_info.options), Ok(Some(_))) {
+        return None;
+    }
+    let connection_info = connection_info.clone();
+    let task = tokio::spawn(async move {
+        let mut wait = REFRESH_INTERVAL;
+        loop {
+            tokio::time::sleep(wait).await;
+            let mut refreshed = connection_info.clone();
+            wait = match apply(&mut refreshed).await {
+                Ok(()) => {
+                    debug!("[iam_auth] Refreshed the IAM token of the connection pool");
+                    if let Some(token) = refreshed.password {
+                        set_password(token);
+                    }
+                    REFRESH_INTERVAL
+                }
+                Err(e) => {
+                    warn!("[iam_auth] Could not refresh the IAM token: {e}");
+                    RETRY_INTERVAL
+                }
+            };
+        }
+    });
+    Some(TokenRefresh(task.abort_handle()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_provider_and_options() {
+        assert_eq!(provider(&options(&[])), Ok(None));
+        assert_eq!(provider(&options(&[("auth", "password")])), Ok(None));
+        assert_eq!(
+            provider(&options(&[("AUTH", "AWS-IAM")])),
+            Ok(Some(IamProvider::Aws))
+        );
+        assert_eq!(
+            provider(&options(&[("auth", "azure-ad")])),
+            Ok(Some(IamProvider::Azure))
+        );
+        assert!(provider(&options(&[("auth", "kerberos")])).is_err());
+        assert!(is_auth_option("aws_region"));
+        let info = ConnectionInfo::parse_url("postgres://app@db:5432/app").unwrap();
+        assert!(keep_fresh(&info, |_| {}).is_none());
+        assert!(!is_auth_option("sslmode"));
+    }
+
+    #[test]
+    fn test_aws_command() {
+        assert_eq!(
+            region_from_host("shop.abc123.eu-west-1.rds.amazonaws.com"),
+            Some("eu-west-1")
+        );
+        assert_eq!(region_from_host("db.example.com"), None);
+
+        let args = IamProvider::Aws.args(
+            "shop.abc123.eu-west-1.rds.amazonaws.com",
+            5432,
+            "app",
+            &options(&[("aws_profile", "prod")]),
+        );
+        assert_eq!(
+            args.join(" "),
+            "rds generate-db-auth-token --hostname shop.abc123.eu-west-1.rds.amazonaws.com \
+             --port 5432 --username app --region eu-west-1 --profile prod"
+        );
+        let args = IamProvider::Aws.args(
+            "10.0.0.5",
+            3306,
+            "app",
+            &options(&[("aws_region", "us-east-2")]),
+        );
+        assert!(args.join(" ").ends_with("--region us-east-2"));
+        assert_eq!(
+            IamProvider::Gcp.args("h", 5432, "u", &options(&[])),
+            ["sql", "generate-login-token"]
+        );
+    }
+}
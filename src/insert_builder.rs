@@ -0,0 +1,524 @@
+//! Interactive INSERT builder for the `\insertrow` command
+//!
+//! Walks the columns of a table and asks for one value per column, using the
+//! table metadata for hints: the column type, whether it is nullable, its
+//! default and enum labels. Values are validated against the column type while
+//! typing, several rows can be entered in one go, and the generated statement
+//! is shown for confirmation before anything runs.
+//!
+//! Input rules for a column prompt:
+//! - empty input uses the column default, or NULL for a nullable column
+//! - `DEFAULT` / `NULL` force either explicitly
+//! - `=expr` inserts a raw SQL expression, e.g. `=now()`
+//! - anything else is a value, quoted according to the column type
+
+use crate::database::DatabaseType;
+use crate::db::{ColumnInfo, TableDetails};
+use inquire::validator::Validation;
+use inquire::{Confirm, InquireError, Select, Text};
+
+/// How a column value is written into the statement
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    Integer,
+    Decimal,
+    Boolean,
+    Text,
+}
+
+impl ValueKind {
+    /// Classify a column type as reported by any of the SQL backends
+    pub fn from_type(data_type: &str) -> Self {
+        let lower = data_type.trim().to_lowercase();
+        // ClickHouse wraps types: Nullable(Int32), LowCardinality(String)
+        let lower = lower
+            .strip_prefix("nullable(")
+            .or_else(|| lower.strip_prefix("lowcardinality("))
+            .unwrap_or(&lower);
+        let base = lower
+            .split(['(', ' '])
+            .next()
+            .unwrap_or_default()
+            .trim_end_matches(')');
+
+        let sized_int = |prefix: &str| {
+            base.strip_prefix(prefix)
+                .is_some_and(|bits| !bits.is_empty() && bits.chars().all(|c| c.is_ascii_digit()))
+        };
+        match base {
+            "int" | "integer" | "bigint" | "smallint" | "tinyint" | "mediumint" | "serial"
+            | "bigserial" | "smallserial" => ValueKind::Integer,
+            _ if sized_int("int") || sized_int("uint") => ValueKind::Integer,
+            "numeric" | "decimal" | "real" | "double" | "float" | "float4" | "float8"
+            | "float32" | "float64" => ValueKind::Decimal,
+            _ if base.starts_with("decimal") => ValueKind::Decimal,
+            "boolean" | "bool" => ValueKind::Boolean,
+            _ => ValueKind::Text,
+        }
+    }
+
+    /// Whether `value` is acceptable for the column, or why not
+    fn check(self, value: &str) -> Result<(), String> {
+        let ok = match self {
+            ValueKind::Integer => value.parse::<i128>().is_ok(),
+            ValueKind::Decimal => value.parse::<f64>().is_ok(),
+            ValueKind::Boolean => parse_bool(value).is_some(),
+            ValueKind::Text => true,
+        };
+        if ok {
+            Ok(())
+        } else {
+            Err(match self {
+                ValueKind::Integer => "expected an integer".to_string(),
+                ValueKind::Decimal => "expected a number".to_string(),
+                _ => "expected true/false".to_string(),
+            })
+        }
+    }
+}
+
+pub(crate) fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "true" | "t" | "yes" | "y" | "1" | "on" => Some(true),
+        "false" | "f" | "no" | "n" | "0" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+/// One value entered for one column
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnValue {
+    /// Left out of the column list so the server fills it in
+    Default,
+    Null,
+    Value(String),
+    Expression(String),
+}
+
+/// Column metadata the builder needs
+#[derive(Debug, Clone)]
+pub struct ColumnSpec {
+    pub name: String,
+    pub data_type: String,
+    pub kind: ValueKind,
+    pub nullable: bool,
+    pub default_value: Option<String>,
+    pub enum_values: Vec<String>,
+}
+
+impl From<&ColumnInfo> for ColumnSpec {
+    fn from(column: &ColumnInfo) -> Self {
+        ColumnSpec {
+            name: column.name.clone(),
+            data_type: column.data_type.clone(),
+            kind: ValueKind::from_type(&column.data_type),
+            nullable: column.nullable,
+            default_value: column.default_value.clone().filter(|d| !d.is_empty()),
+            enum_values: column.enum_values.clone().unwrap_or_default(),
+        }
+    }
+}
+
+impl ColumnSpec {
+    /// `integer, not null, default nextval('users_id_seq')`
+    fn hint(&self) -> String {
+        let mut hint = self.data_type.clone();
+        if !self.nullable {
+            hint.push_str(", not null");
+        }
+        if let Some(default) = &self.default_value {
+            hint.push_str(&format!(", default {default}"));
+        }
+        hint
+    }
+
+    /// Interpret what the user typed for this column
+    pub fn parse_input(&self, input: &str) -> Result<ColumnValue, String> {
+        let trimmed = input.trim();
+        if trimmed.is_empty() {
+            return if self.default_value.is_some() {
+                Ok(ColumnValue::Default)
+            } else if self.nullable {
+                Ok(ColumnValue::Null)
+            } else {
+                Err("a value is required (not null, no default)".to_string())
+            };
+        }
+        if trimmed.eq_ignore_ascii_case("default") {
+            return Ok(ColumnValue::Default);
+        }
+        if trimmed.eq_ignore_ascii_case("null") {
+            return if self.nullable {
+                Ok(ColumnValue::Null)
+            } else {
+                Err("column is not nullable".to_string())
+            };
+        }
+        if let Some(expression) = trimmed.strip_prefix('=') {
+            return if expression.trim().is_empty() {
+                Err("empty expression".to_string())
+            } else {
+                Ok(ColumnValue::Expression(expression.trim().to_string()))
+            };
+        }
+        // Text keeps its surrounding whitespace; typed values do not
+        match self.kind {
+            ValueKind::Text => Ok(ColumnValue::Value(input.to_string())),
+            kind => kind
+                .check(trimmed)
+                .map(|_| ColumnValue::Value(trimmed.to_string())),
+        }
+    }
+
+    fn render(&self, value: &ColumnValue, database_type: &DatabaseType) -> String {
+        match value {
+            ColumnValue::Default => "DEFAULT".to_string(),
+            ColumnValue::Null => "NULL".to_string(),
+            ColumnValue::Expression(expression) => expression.clone(),
+            ColumnValue::Value(value) => match self.kind {
+                ValueKind::Integer | ValueKind::Decimal => value.clone(),
+                ValueKind::Boolean => {
+                    let value = parse_bool(value).unwrap_or_default();
+                    match database_type {
+                        DatabaseType::PostgreSQL => value.to_string().to_uppercase(),
+                        _ => u8::from(value).to_string(),
+                    }
+                }
+                ValueKind::Text => quote_literal(value, database_type),
+            },
+        }
+    }
+}
+
+pub(crate) fn quote_literal(value: &str, database_type: &DatabaseType) -> String {
+    let escaped = crate::database::escape_sql_string(value);
+    match database_type {
+        // Both treat backslash as an escape character inside string literals
+        DatabaseType::MySQL | DatabaseType::ClickHouse => {
+            format!("'{}'", escaped.replace('\\', "\\\\"))
+        }
+        _ => format!("'{escaped}'"),
+    }
+}
+
+pub(crate) fn quote_identifier(ident: &str, database_type: &DatabaseType) -> String {
+    match database_type {
+        DatabaseType::MySQL | DatabaseType::ClickHouse => {
+            format!("`{}`", ident.replace('`', "``"))
+        }
+        _ => crate::database::quote_sql_ident(ident),
+    }
+}
+
+/// `schema.table` → each part quoted for the backend
+pub(crate) fn quote_table(table: &str, database_type: &DatabaseType) -> String {
+    table
+        .split('.')
+        .map(|part| quote_identifier(part, database_type))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Build the INSERT statements for `rows`. Columns left at their default are
+/// omitted rather than written as `DEFAULT` (SQLite does not accept it in
+/// VALUES), so consecutive rows sharing the same column list become one
+/// multi-row INSERT. A row left entirely at defaults names every column with
+/// `DEFAULT` on ClickHouse, which has no `DEFAULT VALUES`.
+pub fn build_insert_statements(
+    table: &str,
+    columns: &[ColumnSpec],
+    rows: &[Vec<ColumnValue>],
+    database_type: &DatabaseType,
+) -> Vec<String> {
+    let table = quote_table(table, database_type);
+    let mut statements = Vec::new();
+    let mut group: Option<(Vec<usize>, Vec<String>)> = None;
+
+    let flush = |group: &mut Option<(Vec<usize>, Vec<String>)>, statements: &mut Vec<String>| {
+        let Some((indices, values)) = group.take() else {
+            return;
+        };
+        if indices.is_empty() {
+            statements.extend(values.iter().map(|_| match database_type {
+                DatabaseType::MySQL => format!("INSERT INTO {table} () VALUES ();"),
+                DatabaseType::ClickHouse => format!(
+                    "INSERT INTO {table} ({}) VALUES ({});",
+                    columns
+                        .iter()
+                        .map(|column| quote_identifier(&column.name, database_type))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    vec!["DEFAULT"; columns.len()].join(", ")
+                ),
+                _ => format!("INSERT INTO {table} DEFAULT VALUES;"),
+            }));
+            return;
+        }
+        let column_list = indices
+            .iter()
+            .map(|&i| quote_identifier(&columns[i].name, database_type))
+            .collect::<Vec<_>>()
+            .join(", ");
+        statements.push(format!(
+            "INSERT INTO {table} ({column_list})\nVALUES\n  {};",
+            values.join(",\n  ")
+        ));
+    };
+
+    for row in rows {
+        let indices: Vec<usize> = row
+            .iter()
+            .enumerate()
+            .filter(|(_, value)| **value != ColumnValue::Default)
+            .map(|(i, _)| i)
+            .collect();
+        let values = format!(
+            "({})",
+            indices
+                .iter()
+                .map(|&i| columns[i].render(&row[i], database_type))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        match &mut group {
+            Some((current, group_values)) if *current == indices => group_values.push(values),
+            _ => {
+                flush(&mut group, &mut statements);
+                group = Some((indices, vec![values]));
+            }
+        }
+    }
+    flush(&mut group, &mut statements);
+    statements
+}
+
+/// Ask for one value per column
+fn prompt_row(columns: &[ColumnSpec], row_number: usize) -> Result<Vec<ColumnValue>, InquireError> {
+    let mut row = Vec::with_capacity(columns.len());
+    for column in columns {
+        let label = format!("[row {row_number}] {}:", column.name);
+        let value = if column.enum_values.is_empty() {
+            let spec = column.clone();
+            let input = Text::new(&label)
+                .with_help_message(&column.hint())
+                .with_validator(move |input: &str| {
+                    Ok(match spec.parse_input(input) {
+                        Ok(_) => Validation::Valid,
+                        Err(message) => Validation::Invalid(message.into()),
+                    })
+                })
+                .prompt()?;
+            column
+                .parse_input(&input)
+                .map_err(|e| InquireError::Custom(e.into()))?
+        } else {
+            let mut options = column.enum_values.clone();
+            if column.default_value.is_some() {
+                options.insert(0, "(default)".to_string());
+            }
+            if column.nullable {
+                options.push("(null)".to_string());
+            }
+            match Select::new(&label, options)
+                .with_help_message(&column.hint())
+                .prompt()?
+                .as_str()
+            {
+                "(default)" => ColumnValue::Default,
+                "(null)" => ColumnValue::Null,
+                value => ColumnValue::Value(value.to_string()),
+            }
+        };
+        row.push(value);
+    }
+    Ok(row)
+}
+
+/// Confirmed statements and the number of rows they insert
+#[derive(Debug)]
+pub struct InsertPlan {
+    pub statements: Vec<String>,
+    pub rows: usize,
+}
+
+/// Prompt for rows until the user stops, then confirm the generated SQL.
+/// Returns `None` when the user declines or cancels.
+pub fn build_interactively(
+    table: &str,
+    details: &TableDetails,
+    database_type: &DatabaseType,
+) -> Result<Option<InsertPlan>, String> {
+    let columns: Vec<ColumnSpec> = details.columns.iter().map(ColumnSpec::from).collect();
+    if columns.is_empty() {
+        return Err(format!("Table {table} has no columns"));
+    }
+    println!(
+        "Inserting into {table}. Enter = default/NULL, NULL, DEFAULT, =expr for raw SQL, Esc to cancel."
+    );
+
+    let cancelled = |e: &InquireError| {
+        matches!(
+            e,
+            InquireError::OperationCanceled | InquireError::OperationInterrupted
+        )
+    };
+    let mut rows = Vec::new();
+    loop {
+        match prompt_row(&columns, rows.len() + 1) {
+            Ok(row) => rows.push(row),
+            Err(e) if cancelled(&e) => return Ok(None),
+            Err(e) => return Err(format!("Input error: {e}")),
+        }
+        match Confirm::new("Add another row?")
+            .with_default(false)
+            .prompt()
+        {
+            Ok(true) => continue,
+            Ok(false) => break,
+            Err(e) if cancelled(&e) => break,
+            Err(e) => return Err(format!("Input error: {e}")),
+        }
+    }
+
+    let statements = build_insert_statements(table, &columns, &rows, database_type);
+    println!("\n{}\n", statements.join("\n"));
+    match Confirm::new("Execute this INSERT?")
+        .with_default(true)
+        .prompt()
+    {
+        Ok(true) => Ok(Some(InsertPlan {
+            statements,
+            rows: rows.len(),
+        })),
+        Ok(false) => Ok(None),
+        Err(e) if cancelled(&e) => Ok(None),
+        Err(e) => Err(format!("Input error: {e}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn column(name: &str, data_type: &str, nullable: bool, default: Option<&str>) -> ColumnSpec {
+        ColumnSpec {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            kind: ValueKind::from_type(data_type),
+            nullable,
+            default_value: default.map(str::to_string),
+            enum_values: Vec::new(),
+        }
+    }
+
+    #[rstest]
+    #[case("integer", ValueKind::Integer)]
+    #[case("int(11) unsigned", ValueKind::Integer)]
+    #[case("Nullable(UInt64)", ValueKind::Integer)]
+    #[case("numeric(10,2)", ValueKind::Decimal)]
+    #[case("double precision", ValueKind::Decimal)]
+    #[case("Decimal64(4)", ValueKind::Decimal)]
+    #[case("boolean", ValueKind::Boolean)]
+    #[case("interval", ValueKind::Text)]
+    #[case("point", ValueKind::Text)]
+    #[case("character varying(255)", ValueKind::Text)]
+    fn test_value_kind_from_type(#[case] data_type: &str, #[case] expected: ValueKind) {
+        assert_eq!(ValueKind::from_type(data_type), expected);
+    }
+
+    #[rstest]
+    #[case("", Ok(ColumnValue::Default))]
+    #[case("null", Ok(ColumnValue::Null))]
+    #[case("=now()", Ok(ColumnValue::Expression("now()".to_string())))]
+    #[case(" 42 ", Ok(ColumnValue::Value("42".to_string())))]
+    #[case("forty-two", Err("expected an integer".to_string()))]
+    fn test_parse_input_nullable_with_default(
+        #[case] input: &str,
+        #[case] expected: Result<ColumnValue, String>,
+    ) {
+        let id = column("id", "bigint", true, Some("nextval('t_id_seq')"));
+        assert_eq!(id.parse_input(input), expected);
+    }
+
+    #[test]
+    fn test_parse_input_required_column() {
+        let name = column("name", "text", false, None);
+        assert!(name.parse_input("").is_err());
+        assert!(name.parse_input("NULL").is_err());
+        assert_eq!(
+            name.parse_input(" Ada "),
+            Ok(ColumnValue::Value(" Ada ".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_build_insert_groups_rows_with_the_same_columns() {
+        let columns = vec![
+            column("id", "serial", false, Some("nextval('users_id_seq')")),
+            column("name", "text", false, None),
+            column("active", "boolean", true, None),
+        ];
+        let rows = vec![
+            vec![
+                ColumnValue::Default,
+                ColumnValue::Value("O'Brien".to_string()),
+                ColumnValue::Value("yes".to_string()),
+            ],
+            vec![
+                ColumnValue::Default,
+                ColumnValue::Value("Ada".to_string()),
+                ColumnValue::Null,
+            ],
+            vec![
+                ColumnValue::Value("7".to_string()),
+                ColumnValue::Expression("upper('x')".to_string()),
+                ColumnValue::Default,
+            ],
+        ];
+        let statements =
+            build_insert_statements("app.users", &columns, &rows, &DatabaseType::PostgreSQL);
+        assert_eq!(
+            statements,
+            vec![
+                "INSERT INTO \"app\".\"users\" (\"name\", \"active\")\nVALUES\n  ('O''Brien', TRUE),\n  ('Ada', NULL);".to_string(),
+                "INSERT INTO \"app\".\"users\" (\"id\", \"name\")\nVALUES\n  (7, upper('x'));".to_string(),
+            ]
+        );
+
+        let statements = build_insert_statements(
+            "users",
+            &columns,
+            &[vec![
+                ColumnValue::Default,
+                ColumnValue::Value("a\\b".to_string()),
+                ColumnValue::Value("f".to_string()),
+            ]],
+            &DatabaseType::MySQL,
+        );
+        assert_eq!(
+            statements,
+            vec!["INSERT INTO `users` (`name`, `active`)\nVALUES\n  ('a\\\\b', 0);".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_build_insert_all_defaults() {
+        let columns = vec![column("id", "integer", false, Some("1"))];
+        let rows = vec![vec![ColumnValue::Default]];
+        assert_eq!(
+            build_insert_statements("t", &columns, &rows, &DatabaseType::SQLite),
+            vec!["INSERT INTO \"t\" DEFAULT VALUES;".to_string()]
+        );
+
+        let columns = vec![
+            column("id", "UInt64", false, Some("0")),
+            column("created", "DateTime", false, Some("now()")),
+        ];
+        let rows = vec![vec![ColumnValue::Default, ColumnValue::Default]];
+        assert_eq!(
+            build_insert_statements("t", &columns, &rows, &DatabaseType::ClickHouse),
+            vec!["INSERT INTO `t` (`id`, `created`) VALUES (DEFAULT, DEFAULT);".to_string()]
+        );
+    }
+}
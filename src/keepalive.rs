@@ -0,0 +1,296 @@
+//! Keeping idle connections open (`[connection]`)
+//!
+//! Firewalls and load balancers drop TCP connections that carry no traffic
+//! for a while, and the next statement then fails long after the connection
+//! died. While the REPL waits for input, a background thread pings the server
+//! once the connection has been idle for `keepalive_seconds`, with the
+//! backend's own check ([`crate::database::DatabaseClient::is_connected`]:
+//! `SELECT 1`, a MongoDB `ping`, an Elasticsearch info request). When the
+//! ping fails a new connection replaces the old one, restoring the database,
+//! schema, role and warehouse switched to. While no new connection can be
+//! opened, it is tried again every few seconds and SQL typed at the prompt
+//! waits in the [`crate::offline_queue`].
+//!
+//! Snowflake's HTTP client also sends TCP keepalives at that interval and
+//! ClickHouse's sends them every minute; the sqlx and MongoDB drivers do not
+//! expose them. File-based backends have nothing to keep open.
+
+use crate::database::DatabaseTypeExt;
+use crate::db::Database;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, info};
+
+/// How long a ping may take before the connection counts as lost
+const PING_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How often the background thread looks at the connection
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often a lost connection is tried again in the background
+const RETRY_INTERVAL: Duration = Duration::from_secs(15);
+
+/// `[connection]` configuration section
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ConnectionConfig {
+    /// Idle seconds before the connection is pinged, also the TCP keepalive
+    /// time of Snowflake connections; 0 turns both off
+    pub keepalive_seconds: u64,
+    /// Open a new connection when a ping fails
+    pub reconnect: bool,
+}
+
+impl Default for ConnectionConfig {
+    fn default() -> Self {
+        Self {
+            keepalive_seconds: 300,
+            reconnect: true,
+        }
+    }
+}
+
+impl ConnectionConfig {
+    /// Idle time after which the connection is pinged, `None` when off
+    pub fn interval(&self) -> Option<Duration> {
+        (self.keepalive_seconds > 0).then(|| Duration::from_secs(self.keepalive_seconds))
+    }
+}
+
+/// TCP keepalive time for HTTP clients, from `[connection]`
+pub fn tcp_keepalive() -> Option<Duration> {
+    crate::config::Config::load().connection.interval()
+}
+
+/// Per-connection idle tracking kept on [`Database`]
+#[derive(Debug)]
+pub struct IdleState {
+    last_used: Instant,
+    /// Last ping or reconnect attempt
+    last_check: Instant,
+    /// A ping failed and no new connection could be opened
+    lost: bool,
+    /// What happened in the background, shown before the next statement
+    notice: Option<String>,
+}
+
+impl Default for IdleState {
+    fn default() -> Self {
+        Self {
+            last_used: Instant::now(),
+            last_check: Instant::now(),
+            lost: false,
+            notice: None,
+        }
+    }
+}
+
+impl IdleState {
+    /// Record that the connection carried traffic just now
+    pub fn touch(&mut self) {
+        self.last_used = Instant::now();
+        self.lost = false;
+    }
+
+    /// Time since the connection last carried traffic
+    pub fn idle_for(&self) -> Duration {
+        self.last_used.elapsed()
+    }
+
+    /// Whether the connection died and could not be replaced yet; statements
+    /// then go to the [`crate::offline_queue::OfflineQueue`]
+    pub fn is_lost(&self) -> bool {
+        self.lost
+    }
+
+    /// Whether the connection is checked now: once it has been idle for
+    /// `interval`, or every [`RETRY_INTERVAL`] while it is lost
+    fn due(&self, interval: Duration) -> bool {
+        if self.lost {
+            self.last_check.elapsed() >= RETRY_INTERVAL
+        } else {
+            self.idle_for() >= interval
+        }
+    }
+}
+
+/// Whether the connection answers within [`PING_TIMEOUT`]
+async fn ping(db: &Database) -> bool {
+    tokio::time::timeout(PING_TIMEOUT, db.is_connected())
+        .await
+        .unwrap_or(false)
+}
+
+/// Background check of an idle connection. A dead SSH tunnel is left to
+/// [`before_statement`]: restarting it may ask for a password, which cannot
+/// be typed while the prompt owns the terminal.
+async fn check_idle(db: &mut Database, config: &ConnectionConfig) {
+    db.idle_state_mut().last_check = Instant::now();
+    if ping(db).await {
+        let state = db.idle_state_mut();
+        if state.lost {
+            state.notice = Some("The connection is back.".to_string());
+        }
+        state.touch();
+        return;
+    }
+    let tunnel_down = db
+        .get_ssh_tunnel()
+        .is_some_and(|tunnel| !tunnel.is_active());
+    if !config.reconnect || tunnel_down {
+        db.idle_state_mut().lost = true;
+        return;
+    }
+    match db.reconnect().await {
+        Ok(()) => {
+            info!("Connection lost while idle, reconnected");
+            let state = db.idle_state_mut();
+            state.notice = Some(if state.lost {
+                "The connection is back.".to_string()
+            } else {
+                "Connection lost while idle; reconnected.".to_string()
+            });
+            state.touch();
+        }
+        Err(e) => {
+            debug!("Reconnecting in the background failed: {}", e);
+            db.idle_state_mut().lost = true;
+        }
+    }
+}
+
+/// Make sure the connection still works before a statement runs: ping it
+/// when it has been idle for the keepalive interval (the background pings
+/// may have been missed, e.g. while the machine slept) or was lost, and
+/// reconnect when the ping fails. Returns what to tell the user.
+pub async fn before_statement(db: &mut Database, config: &ConnectionConfig) -> Option<String> {
+    let notice = db.idle_state_mut().notice.take();
+    let Some(interval) = config.interval() else {
+        return notice;
+    };
+    if db.get_database_type().is_file_based() {
+        db.idle_state_mut().touch();
+        return notice;
+    }
+    if !db.idle_state_mut().due(interval) {
+        // A lost connection is only tried every RETRY_INTERVAL, so typing
+        // into the queue does not wait for a timeout each time
+        if !db.idle_state_mut().lost {
+            db.idle_state_mut().touch();
+        }
+        return notice;
+    }
+    let was_lost = db.idle_state_mut().lost;
+    db.idle_state_mut().last_check = Instant::now();
+    if ping(db).await {
+        db.idle_state_mut().touch();
+        return if was_lost {
+            Some("The connection is back.".to_string())
+        } else {
+            notice
+        };
+    }
+    if config.reconnect {
+        match db.reconnect().await {
+            Ok(()) => {
+                db.idle_state_mut().touch();
+                return Some(if was_lost {
+                    "The connection is back.".to_string()
+                } else {
+                    "Connection lost; reconnected.".to_string()
+                });
+            }
+            Err(e) => debug!("Reconnecting failed: {}", e),
+        }
+    }
+    db.idle_state_mut().lost = true;
+    // Said once; statements typed meanwhile say they were queued
+    (!was_lost).then(|| {
+        if config.reconnect {
+            "Connection lost and reconnecting failed; SQL is queued until it is back (\\queue)."
+                .to_string()
+        } else {
+            "Connection lost; SQL is queued until it is back (\\queue). Reconnect with \\c."
+                .to_string()
+        }
+    })
+}
+
+/// Background pings of the REPL's connection; they stop when this is dropped
+pub struct Keepalive {
+    stop: Arc<AtomicBool>,
+}
+
+impl Drop for Keepalive {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Ping `database` whenever it has been idle for the keepalive interval,
+/// and try it again now and then while it is lost.
+/// The thread only holds the connection while it pings: a statement holding
+/// it means the connection is in use, so that round is skipped.
+pub fn start(database: &Arc<Mutex<Database>>, config: &ConnectionConfig) -> Option<Keepalive> {
+    let interval = config.interval()?;
+    let handle = tokio::runtime::Handle::try_current().ok()?;
+    let stop = Arc::new(AtomicBool::new(false));
+    let stopped = stop.clone();
+    let database = Arc::downgrade(database);
+    let config = config.clone();
+    std::thread::spawn(move || {
+        while !stopped.load(Ordering::Relaxed) {
+            std::thread::sleep(POLL_INTERVAL);
+            let Some(database) = database.upgrade() else {
+                break;
+            };
+            let Ok(mut db) = database.try_lock() else {
+                continue;
+            };
+            if db.get_database_type().is_file_based() || !db.idle_state_mut().due(interval) {
+                continue;
+            }
+            handle.block_on(check_idle(&mut db, &config));
+        }
+    });
+    Some(Keepalive { stop })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interval() {
+        assert_eq!(
+            ConnectionConfig::default().interval(),
+            Some(Duration::from_secs(300))
+        );
+        let off = ConnectionConfig {
+            keepalive_seconds: 0,
+            reconnect: true,
+        };
+        assert_eq!(off.interval(), None);
+        let parsed: ConnectionConfig = toml::from_str("keepalive_seconds = 60").unwrap();
+        assert_eq!(parsed.interval(), Some(Duration::from_secs(60)));
+        assert!(parsed.reconnect);
+    }
+
+    #[test]
+    fn test_idle_state_due() {
+        let mut state = IdleState::default();
+        assert!(!state.due(Duration::from_secs(60)));
+        assert!(state.due(Duration::ZERO));
+        // A lost connection is retried on its own schedule
+        state.lost = true;
+        assert!(state.is_lost());
+        assert!(!state.due(Duration::ZERO));
+        state.last_check -= RETRY_INTERVAL;
+        assert!(state.due(Duration::from_secs(60)));
+        state.touch();
+        assert!(!state.is_lost());
+        assert!(!state.due(Duration::from_secs(60)));
+    }
+}
@@ -0,0 +1,342 @@
+//! REPL key bindings (`[keybindings]`, `\keys`)
+//!
+//! The line editor starts from reedline's emacs bindings plus Tab and
+//! Shift+Tab for the completion menu. `[keybindings]` maps a key such as
+//! `ctrl+e`, `alt+x` or `f5` to an action on top of those:
+//!
+//! - a backslash command or SQL statement runs as if typed, leaving the
+//!   line being edited alone, e.g. `"alt+x" = "\\x"`
+//! - `insert:<text>` types `<text>` at the cursor
+//! - `complete`, `clear_screen` and `search_history` are editor actions
+//! - `none` removes a default binding
+//!
+//! Statements run from a key do not become the previous input, so a key
+//! bound to `\g` keeps re-running the last query typed.
+
+use reedline::{
+    EditCommand, KeyCode, KeyModifiers, Keybindings, ReedlineEvent, default_emacs_keybindings,
+};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Mutex;
+
+/// Prefix of the host commands bound keys send, so the REPL can tell them
+/// from typed input
+const BOUND_PREFIX: &str = "\u{0}key:";
+
+/// `[key, action, source]` rows of the bindings the REPL started with
+static ACTIVE: Mutex<Vec<Vec<String>>> = Mutex::new(Vec::new());
+
+/// The action a bound key sent, when `input` came from one
+pub fn bound_action(input: &str) -> Option<&str> {
+    input.strip_prefix(BOUND_PREFIX).map(str::trim)
+}
+
+/// `ctrl+e`, `Alt+Shift+Up`, `f5` → modifiers and key code
+pub fn parse_key(spec: &str) -> Result<(KeyModifiers, KeyCode), String> {
+    let mut modifiers = KeyModifiers::NONE;
+    let parts: Vec<&str> = spec.split('+').map(str::trim).collect();
+    // `ctrl++` binds the plus key itself
+    let (key, modifier_names) = match parts.as_slice() {
+        [rest @ .., "", ""] => ("+", rest),
+        [rest @ .., key] => (*key, rest),
+        [] => unreachable!("split always yields one part"),
+    };
+    for name in modifier_names {
+        modifiers |= match name.to_lowercase().as_str() {
+            "ctrl" | "control" => KeyModifiers::CONTROL,
+            "alt" | "meta" | "option" => KeyModifiers::ALT,
+            "shift" => KeyModifiers::SHIFT,
+            other => return Err(format!("unknown modifier '{other}' in '{spec}'")),
+        };
+    }
+
+    let lower = key.to_lowercase();
+    let code = match lower.as_str() {
+        "" => return Err(format!("no key in '{spec}'")),
+        "enter" | "return" => KeyCode::Enter,
+        "tab" if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+        "tab" => KeyCode::Tab,
+        "esc" | "escape" => KeyCode::Esc,
+        "space" => KeyCode::Char(' '),
+        "backspace" => KeyCode::Backspace,
+        "delete" | "del" => KeyCode::Delete,
+        "insert" | "ins" => KeyCode::Insert,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" | "pgup" => KeyCode::PageUp,
+        "pagedown" | "pgdn" => KeyCode::PageDown,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        _ => match (lower.strip_prefix('f'), lower.chars().count()) {
+            (Some(number), _)
+                if !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()) =>
+            {
+                match number.parse::<u8>() {
+                    Ok(n @ 1..=24) => KeyCode::F(n),
+                    _ => return Err(format!("no function key {key}")),
+                }
+            }
+            (_, 1) => KeyCode::Char(lower.chars().next().unwrap_or_default()),
+            _ => return Err(format!("unknown key '{key}' in '{spec}'")),
+        },
+    };
+    if let KeyCode::Char(c) = code
+        && (modifiers - KeyModifiers::SHIFT).is_empty()
+    {
+        return Err(format!(
+            "'{spec}' would stop '{c}' from being typed; add ctrl or alt"
+        ));
+    }
+    Ok((modifiers, code))
+}
+
+/// `Ctrl+Alt+E`, `F5`, `Shift+Tab`
+pub fn format_key(modifiers: KeyModifiers, code: KeyCode) -> String {
+    let mut parts = Vec::new();
+    for (modifier, name) in [
+        (KeyModifiers::CONTROL, "Ctrl"),
+        (KeyModifiers::ALT, "Alt"),
+        (KeyModifiers::SHIFT, "Shift"),
+    ] {
+        if modifiers.contains(modifier) {
+            parts.push(name.to_string());
+        }
+    }
+    parts.push(match code {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) => c.to_uppercase().to_string(),
+        KeyCode::F(n) => format!("F{n}"),
+        KeyCode::BackTab => "Tab".to_string(),
+        KeyCode::Esc => "Esc".to_string(),
+        KeyCode::PageUp => "PageUp".to_string(),
+        KeyCode::PageDown => "PageDown".to_string(),
+        other => format!("{other:?}"),
+    });
+    parts.join("+")
+}
+
+/// The event for an action of `[keybindings]`; `None` unbinds the key
+pub fn parse_action(action: &str) -> Result<Option<ReedlineEvent>, String> {
+    let action = action.trim();
+    if let Some(text) = action.strip_prefix("insert:") {
+        return Ok(Some(ReedlineEvent::Edit(vec![EditCommand::InsertString(
+            text.to_string(),
+        )])));
+    }
+    Ok(Some(match action {
+        "" => return Err("empty action".to_string()),
+        "none" => return Ok(None),
+        "complete" => completion_event(),
+        "clear_screen" => ReedlineEvent::ClearScreen,
+        "search_history" => ReedlineEvent::SearchHistory,
+        command => ReedlineEvent::ExecuteHostCommand(format!("{BOUND_PREFIX}{command}")),
+    }))
+}
+
+/// Open the completion menu, or move to its next entry
+fn completion_event() -> ReedlineEvent {
+    ReedlineEvent::UntilFound(vec![
+        ReedlineEvent::Menu("completion_menu".to_string()),
+        ReedlineEvent::MenuNext,
+    ])
+}
+
+/// The REPL's bindings with `[keybindings]` applied, and a warning for each
+/// entry that could not be. Keys of the applied entries come third.
+pub fn build(config: &HashMap<String, String>) -> (Keybindings, Vec<String>, BTreeSet<String>) {
+    let mut keybindings = default_emacs_keybindings();
+    keybindings.add_binding(KeyModifiers::NONE, KeyCode::Tab, completion_event());
+    keybindings.add_binding(
+        KeyModifiers::SHIFT,
+        KeyCode::BackTab,
+        ReedlineEvent::MenuPrevious,
+    );
+
+    let mut warnings = Vec::new();
+    let mut configured = BTreeSet::new();
+    let mut entries: Vec<_> = config.iter().collect();
+    entries.sort();
+    for (key, action) in entries {
+        let parsed = parse_key(key).and_then(|(modifiers, code)| {
+            parse_action(action).map(|event| (modifiers, code, event))
+        });
+        match parsed {
+            Ok((modifiers, code, Some(event))) => {
+                keybindings.add_binding(modifiers, code, event);
+                configured.insert(format_key(modifiers, code));
+            }
+            Ok((modifiers, code, None)) => {
+                keybindings.remove_binding(modifiers, code);
+            }
+            Err(e) => warnings.push(format!("[keybindings] {key}: {e}")),
+        }
+    }
+    (keybindings, warnings, configured)
+}
+
+/// `MoveToLineEnd { select: false }` → `move to line end`
+fn humanize(debug: &str) -> String {
+    let variant = debug
+        .split(|c: char| !c.is_alphanumeric())
+        .next()
+        .unwrap_or_default();
+    let mut words = String::new();
+    for c in variant.chars() {
+        if c.is_uppercase() && !words.is_empty() {
+            words.push(' ');
+        }
+        words.extend(c.to_lowercase());
+    }
+    words
+}
+
+/// What an event does, in words
+pub fn describe(event: &ReedlineEvent) -> String {
+    let join = |events: &[ReedlineEvent], separator: &str| {
+        events
+            .iter()
+            .map(describe)
+            .collect::<Vec<_>>()
+            .join(separator)
+    };
+    match event {
+        ReedlineEvent::ExecuteHostCommand(command) => {
+            format!("run {}", bound_action(command).unwrap_or(command))
+        }
+        ReedlineEvent::Edit(commands) => commands
+            .iter()
+            .map(|command| match command {
+                EditCommand::InsertString(text) => format!("insert {text:?}"),
+                EditCommand::InsertChar(c) => format!("insert {c:?}"),
+                other => humanize(&format!("{other:?}")),
+            })
+            .collect::<Vec<_>>()
+            .join(", "),
+        ReedlineEvent::UntilFound(events) => join(events, " or "),
+        ReedlineEvent::Multiple(events) => join(events, ", then "),
+        ReedlineEvent::Menu(name) => format!("open {}", name.replace('_', " ")),
+        other => humanize(&format!("{other:?}")),
+    }
+}
+
+/// `[key, action, source]` rows for `\keys`: `[keybindings]` entries first,
+/// then the defaults, each sorted by key
+pub fn rows(keybindings: &Keybindings, configured: &BTreeSet<String>) -> Vec<Vec<String>> {
+    let mut rows: Vec<Vec<String>> = keybindings
+        .get_keybindings()
+        .iter()
+        .map(|(combination, event)| {
+            let key = format_key(combination.modifier, combination.key_code);
+            let source = if configured.contains(&key) {
+                "config"
+            } else {
+                "default"
+            };
+            vec![key, describe(event), source.to_string()]
+        })
+        .collect();
+    rows.sort_by(|a, b| (a[2] != "config", &a[0]).cmp(&(b[2] != "config", &b[0])));
+    rows
+}
+
+/// Remember the bindings the REPL runs with for `\keys`
+pub fn set_active(keybindings: &Keybindings, configured: &BTreeSet<String>) {
+    *ACTIVE.lock().unwrap() = rows(keybindings, configured);
+}
+
+/// The bindings in effect: those the REPL started with, or what `config`
+/// gives outside of it
+pub fn active(config: &HashMap<String, String>) -> Vec<Vec<String>> {
+    let active = ACTIVE.lock().unwrap();
+    if active.is_empty() {
+        let (keybindings, _, configured) = build(config);
+        rows(&keybindings, &configured)
+    } else {
+        active.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_key() {
+        assert_eq!(
+            parse_key("Ctrl+E").unwrap(),
+            (KeyModifiers::CONTROL, KeyCode::Char('e'))
+        );
+        assert_eq!(
+            parse_key("f5").unwrap(),
+            (KeyModifiers::NONE, KeyCode::F(5))
+        );
+        assert_eq!(
+            parse_key("shift+tab").unwrap(),
+            (KeyModifiers::SHIFT, KeyCode::BackTab)
+        );
+        assert_eq!(
+            parse_key("ctrl+alt+pagedown").unwrap(),
+            (KeyModifiers::CONTROL | KeyModifiers::ALT, KeyCode::PageDown)
+        );
+        assert_eq!(
+            parse_key("alt++").unwrap(),
+            (KeyModifiers::ALT, KeyCode::Char('+'))
+        );
+        assert!(parse_key("x").is_err());
+        assert!(parse_key("shift+x").is_err());
+        assert!(parse_key("hyper+x").is_err());
+        assert!(parse_key("f99").is_err());
+        assert_eq!(
+            format_key(
+                KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+                KeyCode::Char('r')
+            ),
+            "Ctrl+Shift+R"
+        );
+    }
+
+    #[test]
+    fn test_build_applies_config() {
+        let config: HashMap<String, String> = [
+            ("ctrl+e", "\\ed"),
+            ("alt+x", "\\x"),
+            ("ctrl+t", "insert:SELECT now()"),
+            ("ctrl+k", "none"),
+            ("shift+q", "\\q"),
+        ]
+        .into_iter()
+        .map(|(key, action)| (key.to_string(), action.to_string()))
+        .collect();
+        let (keybindings, warnings, configured) = build(&config);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("[keybindings] shift+q:"));
+        assert_eq!(
+            configured.iter().collect::<Vec<_>>(),
+            ["Alt+X", "Ctrl+E", "Ctrl+T"]
+        );
+        assert!(
+            keybindings
+                .find_binding(KeyModifiers::CONTROL, KeyCode::Char('k'))
+                .is_none()
+        );
+        let event = keybindings
+            .find_binding(KeyModifiers::CONTROL, KeyCode::Char('e'))
+            .unwrap();
+        let ReedlineEvent::ExecuteHostCommand(sent) = &event else {
+            panic!("expected a host command, got {event:?}");
+        };
+        assert_eq!(bound_action(sent), Some("\\ed"));
+        assert_eq!(bound_action("\\ed"), None);
+
+        let rows = rows(&keybindings, &configured);
+        assert_eq!(rows[0], ["Alt+X", "run \\x", "config"]);
+        assert_eq!(rows[2], ["Ctrl+T", "insert \"SELECT now()\"", "config"]);
+        assert!(rows.contains(&vec![
+            "Tab".to_string(),
+            "open completion menu or menu next".to_string(),
+            "default".to_string()
+        ]));
+    }
+}
@@ -0,0 +1,155 @@
+//! Connection latency banner (`[display] show_latency`)
+//!
+//! Right after connecting, dbcrust can say how far away the server is, which
+//! is usually why every statement feels slow: the round trip of a trivial
+//! statement (the median of three), the TCP connect and TLS handshake of a
+//! fresh connection, and over an SSH tunnel the connect time to the SSH host,
+//! the extra hop every packet takes. With `latency_warn_ms` set, a round trip
+//! above it is called out even when the banner is off.
+
+use crate::database::DatabaseTypeExt;
+use crate::db::Database;
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+
+/// Statements timed for the round trip
+const PINGS: usize = 3;
+
+/// Give up on one measurement after this long
+const MEASURE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// What was measured; each part is `None` when it could not be
+#[derive(Debug, Default)]
+pub struct LatencyReport {
+    pub round_trip: Option<Duration>,
+    pub connect: Option<Duration>,
+    pub tls: Option<Duration>,
+    /// SSH host and the connect time to it
+    pub tunnel: Option<(String, Duration)>,
+}
+
+/// `0.4 ms`, `23 ms`, `1.25 s`
+pub fn format_duration(duration: Duration) -> String {
+    let ms = duration.as_secs_f64() * 1000.0;
+    if ms < 10.0 {
+        format!("{ms:.1} ms")
+    } else if ms < 1000.0 {
+        format!("{ms:.0} ms")
+    } else {
+        format!("{:.2} s", ms / 1000.0)
+    }
+}
+
+impl LatencyReport {
+    /// One line for the connect banner; `None` when nothing was measured
+    pub fn banner(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        if let Some(round_trip) = self.round_trip {
+            parts.push(format!("{} round trip", format_duration(round_trip)));
+        }
+        if let Some(connect) = self.connect {
+            parts.push(format!("TCP connect {}", format_duration(connect)));
+        }
+        if let Some(tls) = self.tls {
+            parts.push(format!("TLS handshake {}", format_duration(tls)));
+        }
+        if let Some((host, hop)) = &self.tunnel {
+            parts.push(format!("SSH hop {} ({host})", format_duration(*hop)));
+        }
+        (!parts.is_empty()).then(|| format!("Latency: {}", parts.join(" · ")))
+    }
+
+    /// Warning when the round trip is above `threshold_ms`; 0 never warns
+    pub fn warning(&self, threshold_ms: u64) -> Option<String> {
+        let round_trip = self.round_trip?;
+        let threshold = Duration::from_millis(threshold_ms);
+        if threshold_ms == 0 || round_trip <= threshold {
+            return None;
+        }
+        let mut warning = format!(
+            "Warning: round trip of {} is above latency_warn_ms ({} ms); every statement takes at least that long",
+            format_duration(round_trip),
+            threshold_ms
+        );
+        if let Some((host, hop)) = &self.tunnel {
+            warning.push_str(&format!(
+                ", {} of it to reach the SSH host {host}",
+                format_duration(*hop)
+            ));
+        }
+        Some(warning)
+    }
+}
+
+/// Median time of a trivial statement, `None` when it fails
+async fn round_trip(database: &Database) -> Option<Duration> {
+    let mut samples = Vec::with_capacity(PINGS);
+    for _ in 0..PINGS {
+        let started = Instant::now();
+        let answered = tokio::time::timeout(MEASURE_TIMEOUT, database.is_connected()).await;
+        if answered != Ok(true) {
+            return None;
+        }
+        samples.push(started.elapsed());
+    }
+    samples.sort();
+    samples.get(samples.len() / 2).copied()
+}
+
+/// Connect time to the SSH host of the tunnel, if there is one and it can be
+/// reached directly (not behind a `ProxyCommand`)
+async fn tunnel_hop(database: &Database) -> Option<(String, Duration)> {
+    let (host, port) = database.get_ssh_tunnel()?.ssh_address();
+    let started = Instant::now();
+    let connected = tokio::time::timeout(MEASURE_TIMEOUT, TcpStream::connect((host, port))).await;
+    matches!(connected, Ok(Ok(_))).then(|| (format!("{host}:{port}"), started.elapsed()))
+}
+
+/// Measure the current connection; local databases only get the round trip
+pub async fn measure(database: &mut Database) -> LatencyReport {
+    let mut report = LatencyReport {
+        round_trip: round_trip(database).await,
+        ..Default::default()
+    };
+    if database.get_database_type().is_file_based() {
+        return report;
+    }
+    if let Some(Ok(timings)) = crate::ssl_report::handshake_timings(database).await {
+        // Over a tunnel the TCP connect only reaches the local end
+        if database.get_ssh_tunnel().is_none() {
+            report.connect = Some(timings.connect);
+        }
+        report.tls = timings.tls;
+    }
+    report.tunnel = tunnel_hop(database).await;
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_banner_and_warning() {
+        let report = LatencyReport {
+            round_trip: Some(Duration::from_millis(230)),
+            connect: None,
+            tls: Some(Duration::from_micros(35_400)),
+            tunnel: Some(("bastion:22".to_string(), Duration::from_micros(8_300))),
+        };
+        assert_eq!(
+            report.banner().unwrap(),
+            "Latency: 230 ms round trip · TLS handshake 35 ms · SSH hop 8.3 ms (bastion:22)"
+        );
+        assert!(report.warning(0).is_none());
+        assert!(report.warning(250).is_none());
+        assert!(
+            report
+                .warning(200)
+                .unwrap()
+                .contains("230 ms is above latency_warn_ms (200 ms)")
+        );
+        assert!(LatencyReport::default().banner().is_none());
+        assert_eq!(format_duration(Duration::from_millis(1250)), "1.25 s");
+    }
+}
@@ -2,15 +2,20 @@
 #![allow(non_local_definitions)]
 
 pub mod ai; // AI assistant integration (text-to-SQL, multi-provider)
+pub mod charset; // Legacy text encodings for the charset URL parameter
 pub mod cli;
 pub mod cli_core; // New unified CLI core
+pub mod client_cert; // mTLS client certificate expiry warnings and rotation
 pub mod command_completion; // Trait-based command completion system
 pub mod commands; // New type-safe enum-based command system
 pub mod completion;
 pub mod completion_provider; // Database-agnostic completion trait
+pub mod completion_usage; // Frecency ranking of completion candidates
 pub mod complex_display; // Unified display system for complex data types
 pub mod config;
 pub mod config_editor; // Schema-driven \config menu, get/set, tunnel manager
+pub mod count_query; // \count: exact row counts of the last query or a table
+pub mod daemon; // Warm-connection daemon for one-shot -c invocations
 pub mod database; // New database abstraction layer
 pub mod database_clickhouse; // ClickHouse implementation
 pub mod database_datafusion; // DataFusion implementation for file formats (Parquet, CSV, JSON)
@@ -18,41 +23,93 @@ pub mod database_elasticsearch; // Elasticsearch implementation
 pub mod database_mongodb; // MongoDB implementation
 pub mod database_mysql; // MySQL implementation
 pub mod database_postgresql; // PostgreSQL implementation
+pub mod database_snowflake; // Snowflake implementation over the SQL API
 pub mod database_sqlite; // SQLite implementation
+pub mod datagen; // Fake rows for tables (\genrows)
 pub mod db;
 pub mod dbcrust_pass; // Universal password file (.dbcrust) support
+pub mod dedup; // Duplicate rows dropped from the last result (\dedup)
 pub mod docker; // Docker container integration
+pub mod dry_run; // Read-only previews of UPDATE/DELETE statements (\dryrun)
+pub mod dump; // Portable SQL dumps and their restore (\dump, \restore)
+pub mod error; // Crate-wide DbcrustError for the public API
+pub mod error_display; // Error block with class, code, detail, hint and suggestions
+pub mod error_position; // Caret under the position a server error points at
 pub mod explain_tui;
+pub mod export; // Streaming row exports (\export ndjson)
+pub mod export_chunks; // Whole-table exports in primary key chunks (\export --resume)
+pub mod federated; // Cross-database joins through temp tables (\fedjoin)
+pub mod fetch_limits; // Row/byte caps and streaming for Python fetches (max_rows, fetch_iter)
+pub mod flatten; // Nested MongoDB/Elasticsearch documents as dotted-path columns (\flatten)
 pub mod format; // Made format module public
+pub mod function_source; // PostgreSQL function source and edit round trip (\sf)
 pub mod geojson_display;
+pub mod guardrails; // Typed confirmation for statements matching [guardrails] patterns
 pub mod highlighter;
 pub mod history_manager; // Per-session command history management
+pub mod iam_auth; // Cloud IAM tokens in place of passwords (?auth=aws-iam)
+pub mod insert_builder; // Prompted INSERT builder (\insertrow)
 pub mod json_display; // JSON display implementation
+pub mod keepalive; // Idle pings and reconnects of the REPL connection ([connection])
+pub mod keybindings; // REPL key bindings ([keybindings], \keys)
+pub mod latency; // Round trip, handshake and tunnel timings after connecting (show_latency)
 pub mod logging;
+pub mod masking; // Result masking for screen sharing ([masking], \mask)
+pub mod metadata_tables; // dbcrust.history, dbcrust.sessions... as SQL tables
+pub mod metrics; // Prometheus /metrics and /health for the daemon ([metrics])
+pub mod monitor_tui; // `dbcrust monitor` dashboard of saved sessions and aliases
 pub mod myconf; // MySQL configuration file support
 pub mod named_queries;
+pub mod network; // Outbound HTTP(S) proxy ([network])
+pub mod notebook; // Executable markdown SQL notebooks (dbcrust notebook run)
+pub mod offline_queue; // Statements typed while the connection is down (\queue)
 pub mod pager;
 pub mod password_encryption; // Password encryption for .dbcrust file
 pub mod password_sanitizer;
+pub mod paste_data; // Spreadsheet rows from the clipboard into a table (\pastedata)
 pub mod performance_analyzer; // Performance analysis for EXPLAIN queries
 pub mod pgpass;
+pub mod pivot; // Client-side cross-tab of the last result (\pivot)
+pub mod project_url; // Connection URLs from DATABASE_URL/.env or Django settings (--from-env, --from-django)
 pub mod prompt;
+pub mod query_lint; // Pre-execution SQL lint pass ([lint], \lint)
+pub mod query_progress; // Status line for long-running queries (query_progress_after_seconds)
+pub mod query_tags; // SQL comment tags on outgoing statements ([observability], \tag)
+pub mod replication; // PostgreSQL replication slot/lag monitor (\replication)
+pub mod result_cache; // Client-side cache of repeated SELECT results ([result_cache], \cache)
+pub mod result_tui; // Interactive result grid used in place of the pager
+pub mod schema_cache; // Completion metadata saved per connection (\refreshschema)
+pub mod schema_graph; // Foreign key diagrams for \erd
 pub mod schema_tui;
 pub mod script;
+pub mod session_check; // Concurrent reachability check of saved sessions (dbcrust check)
+pub mod session_stats; // Per-session query counters (\stats)
+pub mod settings_bundle; // Config/snippets/named queries/sessions in one file (dbcrust export-settings)
 pub mod shell_completion; // Custom shell completion with URL schemes
+pub mod snapshot; // Saved results and their drift from the live data (\snapshot)
+pub mod snippets; // Tab-expanded REPL snippets ([snippets], \snippets)
 pub mod sql_buffer; // Multiline validation + statement splitting for the REPL
 pub mod sql_context; // SQL context analysis for better autocompletion
+pub mod sql_formatter; // Dialect-aware SQL pretty-printer (\paste, \fmt)
+pub mod sql_help; // Per-dialect SQL syntax help (\h <SQL keyword>)
 pub mod sql_parser; // Enhanced SQL parser for autocompletion
+pub mod sql_parser_clickhouse; // ClickHouse-specific SQL parser (ON CLUSTER)
 pub mod sql_parser_mysql; // MySQL-specific SQL parser
 pub mod sql_parser_postgresql; // PostgreSQL-specific SQL parser
 pub mod sql_parser_sqlite; // SQLite-specific SQL parser
 pub mod sql_parser_trait; // Database-specific SQL parser trait system
 pub mod ssh_tunnel; // Add the SSH tunnel module
+pub mod ssl_report; // Negotiated TLS and server certificate report (\ssl)
+pub mod table_prefs; // Saved per-table display preferences (\prefs)
+pub mod tls; // Per-connection TLS settings ([tls], sslmode/sslrootcert/... URL parameters)
 pub mod update; // Self-update (--update): release check + channel-aware upgrade
 pub mod url_scheme; // URL scheme autocompletion support
 pub mod vault_client; // Add backslash commands module
 pub mod vault_encryption; // Vault credential encryption utilities
+pub mod vault_lease; // Cross-instance Vault credential cache lock and lease holders
 pub mod vector_display; // Vector visualization for PostgreSQL extensions // GeoJSON display implementation // TUI-based query plan visualizer
+pub mod vector_search; // pgvector nearest-neighbour query helpers (\knn)
+pub mod whoami; // Connection identity report (\whoami)
 
 // Note: main.rs functions are not directly accessible as modules in lib.rs
 // We'll create PyO3 wrappers that call the main functionality directly
@@ -98,6 +155,8 @@ pub struct PyConnection {
     connection_url: String,
     database_type: Option<crate::database::DatabaseType>,
     auto_commit: bool,
+    /// Default `max_rows`/`max_bytes` for the cursors and queries of this connection
+    limits: crate::fetch_limits::FetchLimits,
     // Reserved for future use: connection timeout configuration
     #[allow(dead_code)]
     timeout: Option<f64>,
@@ -186,9 +245,24 @@ pub struct PyCursor {
     connection: Arc<TokioMutex<Database>>,
     rt: Arc<Runtime>,
     results: Vec<Vec<Vec<String>>>,
+    /// Column names of each result set in `results`
+    result_columns: Vec<Vec<String>>,
     current_result_index: usize,
     current_row_index: usize,
     column_names: Vec<String>,
+    /// Outcome of the last `execute()` when it returned no rows
+    outcome: Option<crate::database::StatementOutcome>,
+    /// Limits `execute()` applies unless given its own
+    limits: crate::fetch_limits::FetchLimits,
+}
+
+/// Rows of a `fetch_iter()` query, read from the server as they are consumed
+#[cfg(feature = "python")]
+#[pyclass]
+pub struct PyRowIterator {
+    /// `None` once the query ended or the iterator was closed
+    receiver: std::sync::Mutex<Option<std::sync::mpsc::Receiver<crate::fetch_limits::Streamed>>>,
+    column_names: Vec<String>,
 }
 
 // Define custom Python exceptions
@@ -231,6 +305,13 @@ create_exception!(
     DbcrustError,
     "Invalid argument error"
 );
+#[cfg(feature = "python")]
+create_exception!(
+    _internal,
+    DbcrustLimitError,
+    DbcrustError,
+    "Result exceeded max_rows or max_bytes"
+);
 
 /// Convert CliError to appropriate Python exception
 #[cfg(feature = "python")]
@@ -245,6 +326,30 @@ fn cli_error_to_pyerr(err: crate::cli_core::CliError) -> PyErr {
     }
 }
 
+/// Run `query` under `limits`, streaming its rows so the fetch stops as soon
+/// as a limit is passed; returns the rows with the header first
+#[cfg(feature = "python")]
+fn fetch_limited(
+    rt: &Runtime,
+    connection: &TokioMutex<Database>,
+    query: &str,
+    limits: crate::fetch_limits::FetchLimits,
+) -> PyResult<Vec<Vec<String>>> {
+    let mut sink = crate::fetch_limits::LimitedRows::new(limits);
+    let result = rt.block_on(async {
+        let mut db = connection.lock().await;
+        db.stream_query(query, &mut sink).await
+    });
+    if let Some(exceeded) = sink.exceeded {
+        return Err(DbcrustLimitError::new_err(exceeded.to_string()));
+    }
+    result.map_err(|e| DbcrustCommandError::new_err(format!("Query execution failed: {e}")))?;
+    if sink.columns.is_empty() {
+        return Ok(Vec::new());
+    }
+    Ok(std::iter::once(sink.columns).chain(sink.rows).collect())
+}
+
 /// A Python module implemented in Rust.
 #[cfg(feature = "python")]
 #[pymodule]
@@ -259,6 +364,7 @@ pub fn _internal(_py: Python<'_>, m: Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<PyRow>()?;
     m.add_class::<PyResultSet>()?;
     m.add_class::<PyCursor>()?;
+    m.add_class::<PyRowIterator>()?;
 
     // Functions
     m.add_function(wrap_pyfunction!(run_command, &m)?)?;
@@ -279,6 +385,7 @@ pub fn _internal(_py: Python<'_>, m: Bound<'_, PyModule>) -> PyResult<()> {
         "DbcrustArgumentError",
         _py.get_type::<DbcrustArgumentError>(),
     )?;
+    m.add("DbcrustLimitError", _py.get_type::<DbcrustLimitError>())?;
 
     Ok(())
 }
@@ -461,12 +568,16 @@ impl PyConfig {
 #[cfg(feature = "python")]
 #[pymethods]
 impl PyConnection {
-    /// Create a new database connection from URL
+    /// Create a new database connection from URL; `max_rows`/`max_bytes`
+    /// cap every result fetched through it (see `DbcrustLimitError`)
     #[new]
+    #[pyo3(signature = (connection_url, timeout=None, auto_commit=None, max_rows=None, max_bytes=None))]
     pub fn new(
         connection_url: &str,
         timeout: Option<f64>,
         auto_commit: Option<bool>,
+        max_rows: Option<usize>,
+        max_bytes: Option<usize>,
     ) -> PyResult<Self> {
         let rt = Runtime::new()
             .map_err(|e| DbcrustError::new_err(format!("Failed to create Tokio runtime: {e}")))?;
@@ -506,6 +617,10 @@ impl PyConnection {
             connection_url: connection_url.to_string(),
             database_type,
             auto_commit: auto_commit.unwrap_or(true),
+            limits: crate::fetch_limits::FetchLimits {
+                max_rows,
+                max_bytes,
+            },
             timeout,
         })
     }
@@ -533,9 +648,12 @@ impl PyConnection {
             connection: self.inner.clone(),
             rt: self.rt.clone(),
             results: Vec::new(),
+            result_columns: Vec::new(),
             current_result_index: 0,
             current_row_index: 0,
             column_names: Vec::new(),
+            outcome: None,
+            limits: self.limits,
         })
     }
 
@@ -568,13 +686,26 @@ impl PyConnection {
 
     /// Execute a single query immediately (convenience method)
     pub fn execute_immediate(&self, query: &str) -> PyResult<PyResultSet> {
-        let results = self
-            .rt
-            .block_on(async {
-                let mut db = self.inner.lock().await;
-                db.execute_query(query).await
-            })
-            .map_err(|e| DbcrustCommandError::new_err(format!("Query execution failed: {e}")))?;
+        let results = if self.limits.is_unlimited() || !crate::fetch_limits::streams(query) {
+            let results = self
+                .rt
+                .block_on(async {
+                    let mut db = self.inner.lock().await;
+                    db.execute_query(query).await
+                })
+                .map_err(|e| {
+                    DbcrustCommandError::new_err(format!("Query execution failed: {e}"))
+                })?;
+            if let Some(exceeded) = self
+                .limits
+                .exceeded_by(results.get(1..).unwrap_or_default())
+            {
+                return Err(DbcrustLimitError::new_err(exceeded.to_string()));
+            }
+            results
+        } else {
+            fetch_limited(&self.rt, &self.inner, query, self.limits)?
+        };
 
         // Extract column names and data rows
         if results.is_empty() {
@@ -634,42 +765,130 @@ impl PyConnection {
     pub fn set_auto_commit(&mut self, auto_commit: bool) {
         self.auto_commit = auto_commit;
     }
+
+    /// Most rows a result may have before fetching it raises `DbcrustLimitError`
+    #[getter]
+    pub fn max_rows(&self) -> Option<usize> {
+        self.limits.max_rows
+    }
+
+    /// Set the row limit for new cursors and `execute_immediate` (`None` lifts it)
+    #[setter]
+    pub fn set_max_rows(&mut self, max_rows: Option<usize>) {
+        self.limits.max_rows = max_rows;
+    }
+
+    /// Most bytes of cell text a result may hold before fetching it raises
+    /// `DbcrustLimitError`
+    #[getter]
+    pub fn max_bytes(&self) -> Option<usize> {
+        self.limits.max_bytes
+    }
+
+    /// Set the byte limit for new cursors and `execute_immediate` (`None` lifts it)
+    #[setter]
+    pub fn set_max_bytes(&mut self, max_bytes: Option<usize>) {
+        self.limits.max_bytes = max_bytes;
+    }
+
+    /// Session counters, the same as `\stats` in the CLI
+    pub fn stats(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        let stats = self
+            .rt
+            .block_on(async { self.inner.lock().await.stats().clone() });
+
+        let dict = PyDict::new(py);
+        dict.set_item("queries", stats.queries)?;
+        dict.set_item("failed_queries", stats.failed_queries)?;
+        dict.set_item("rows_fetched", stats.rows_fetched)?;
+        dict.set_item("bytes_sent", stats.bytes_sent)?;
+        dict.set_item("bytes_received", stats.bytes_received)?;
+        dict.set_item("query_time_ms", stats.query_time.as_secs_f64() * 1000.0)?;
+        dict.set_item("cache_hits", stats.cache_hits)?;
+        dict.set_item("cache_misses", stats.cache_misses)?;
+        dict.set_item("tracked_seconds", stats.since.elapsed().as_secs_f64())?;
+        Ok(dict.into_any().unbind())
+    }
+
+    /// Reset the session counters (`\stats reset`)
+    pub fn reset_stats(&self) {
+        self.rt
+            .block_on(async { self.inner.lock().await.reset_stats() });
+    }
 }
 
 /// Implementation for PyCursor - Multi-query execution and result navigation
 #[cfg(feature = "python")]
 #[pymethods]
 impl PyCursor {
-    /// Execute a single SQL statement
-    pub fn execute(&mut self, query: &str) -> PyResult<usize> {
+    /// Execute a single SQL statement; procedures may return several result
+    /// sets, reached with `nextset()`. Returns the row count, or the rows
+    /// affected for INSERT/UPDATE/DELETE.
+    ///
+    /// `max_rows`/`max_bytes` replace the connection's limits for this call.
+    /// A limited SELECT is streamed and raises `DbcrustLimitError` as soon as
+    /// it passes a limit; other statements keep their row count and result
+    /// sets, and raise it once they are complete.
+    #[pyo3(signature = (query, max_rows=None, max_bytes=None))]
+    pub fn execute(
+        &mut self,
+        query: &str,
+        max_rows: Option<usize>,
+        max_bytes: Option<usize>,
+    ) -> PyResult<usize> {
+        let limits = self.limits.overridden_by(crate::fetch_limits::FetchLimits {
+            max_rows,
+            max_bytes,
+        });
+        if !limits.is_unlimited() && crate::fetch_limits::streams(query) {
+            let mut rows = fetch_limited(&self.rt, &self.connection, query, limits)?;
+            let column_names = if rows.is_empty() {
+                Vec::new()
+            } else {
+                rows.remove(0)
+            };
+            self.outcome = None;
+            self.results = vec![rows];
+            self.result_columns = vec![column_names.clone()];
+            self.current_result_index = 0;
+            self.current_row_index = 0;
+            self.column_names = column_names;
+            return Ok(self.rowcount());
+        }
+
         let results = self
             .rt
             .block_on(async {
                 let mut db = self.connection.lock().await;
-                db.execute_query(query).await
+                db.execute_query_with_info_no_column_selection(query).await
             })
             .map_err(|e| DbcrustCommandError::new_err(format!("Query execution failed: {e}")))?;
+        if let Some(exceeded) = std::iter::once(&results.data)
+            .chain(&results.more_results)
+            .find_map(|set| limits.exceeded_by(set.get(1..).unwrap_or_default()))
+        {
+            return Err(DbcrustLimitError::new_err(exceeded.to_string()));
+        }
 
-        // Extract column names and data rows
-        if !results.is_empty() {
-            // First row contains column names
-            self.column_names = results[0].clone();
-            // Remaining rows contain actual data
-            let data_rows = if results.len() > 1 {
-                results[1..].to_vec()
-            } else {
+        self.results.clear();
+        self.result_columns.clear();
+        self.outcome = results.outcome;
+        let sets = std::iter::once(results.data).chain(results.more_results);
+        for mut set in sets {
+            // First row contains column names, the rest is data
+            let column_names = if set.is_empty() {
                 Vec::new()
+            } else {
+                set.remove(0)
             };
-            self.results = vec![data_rows];
-        } else {
-            self.column_names = vec![];
-            self.results = vec![Vec::new()];
+            self.result_columns.push(column_names);
+            self.results.push(set);
         }
-
         self.current_result_index = 0;
         self.current_row_index = 0;
+        self.column_names = self.result_columns[0].clone();
 
-        Ok(self.results[0].len())
+        Ok(self.rowcount())
     }
 
     /// Execute multiple SQL statements separated by semicolons
@@ -682,10 +901,10 @@ impl PyCursor {
             .collect();
 
         let mut all_results = Vec::new();
+        let mut all_columns = Vec::new();
         let mut total_rows = 0;
-        let mut first_column_names = Vec::new();
 
-        for (i, statement) in statements.iter().enumerate() {
+        for statement in &statements {
             let results = self
                 .rt
                 .block_on(async {
@@ -698,10 +917,7 @@ impl PyCursor {
 
             // Process results to separate headers from data
             if !results.is_empty() {
-                // For the first statement, save column names
-                if i == 0 {
-                    first_column_names = results[0].clone();
-                }
+                all_columns.push(results[0].clone());
                 // Extract data rows (skip header row)
                 let data_rows = if results.len() > 1 {
                     results[1..].to_vec()
@@ -711,15 +927,18 @@ impl PyCursor {
                 total_rows += data_rows.len();
                 all_results.push(data_rows);
             } else {
+                all_columns.push(Vec::new());
                 all_results.push(Vec::new());
             }
         }
 
         // Store all results for navigation
+        self.outcome = None;
         self.results = all_results;
+        self.result_columns = all_columns;
         self.current_result_index = 0;
         self.current_row_index = 0;
-        self.column_names = first_column_names;
+        self.column_names = self.result_columns.first().cloned().unwrap_or_default();
 
         Ok(total_rows)
     }
@@ -785,39 +1004,158 @@ impl PyCursor {
         if self.current_result_index + 1 < self.results.len() {
             self.current_result_index += 1;
             self.current_row_index = 0;
-            // TODO: Update column names for new result set
+            self.column_names = self.result_columns[self.current_result_index].clone();
             Ok(true)
         } else {
             Ok(false)
         }
     }
 
-    /// Get the number of rows in current result set
+    /// Number of rows in the current result set, or the rows affected by
+    /// an INSERT/UPDATE/DELETE
     #[getter]
     pub fn rowcount(&self) -> usize {
         if self.results.is_empty() || self.current_result_index >= self.results.len() {
             0
+        } else if let Some(outcome) = self.outcome.filter(|_| self.results.len() == 1) {
+            outcome.rows_affected as usize
         } else {
             self.results[self.current_result_index].len()
         }
     }
 
+    /// Id of the row inserted by the last `execute()` (MySQL AUTO_INCREMENT,
+    /// SQLite rowid), `None` otherwise
+    #[getter]
+    pub fn lastrowid(&self) -> Option<i64> {
+        self.outcome.and_then(|outcome| outcome.last_insert_id)
+    }
+
     /// Get column metadata for current result set
     #[getter]
     pub fn description(&self) -> Vec<String> {
         self.column_names.clone()
     }
 
+    /// Run `query` and return an iterator over its rows that reads them from
+    /// the server as it goes, for results too large to fetch at once. Limits
+    /// do not apply; only a small buffer of rows is held. The connection is
+    /// busy until the iterator is exhausted or closed.
+    pub fn fetch_iter(&self, query: &str) -> PyRowIterator {
+        use crate::fetch_limits::{RowChannel, Streamed};
+
+        let (sender, receiver) = std::sync::mpsc::sync_channel(FETCH_ITER_BUFFER_ROWS);
+        let connection = self.connection.clone();
+        let query = query.to_string();
+        self.rt.spawn(async move {
+            let mut channel = RowChannel::new(sender.clone());
+            let result = connection
+                .lock()
+                .await
+                .stream_query(&query, &mut channel)
+                .await;
+            let done = Streamed::Done(result.map_err(|e| e.to_string()));
+            let _ = tokio::task::block_in_place(|| sender.send(done));
+        });
+        PyRowIterator {
+            receiver: std::sync::Mutex::new(Some(receiver)),
+            column_names: Vec::new(),
+        }
+    }
+
     /// Close the cursor (cleanup)
     pub fn close(&mut self) -> PyResult<()> {
         self.results.clear();
+        self.result_columns.clear();
         self.current_result_index = 0;
         self.current_row_index = 0;
         self.column_names.clear();
+        self.outcome = None;
         Ok(())
     }
 }
 
+/// Rows `fetch_iter()` keeps buffered ahead of the reader
+#[cfg(feature = "python")]
+const FETCH_ITER_BUFFER_ROWS: usize = 1000;
+
+/// Implementation for PyRowIterator - Rows streamed by `fetch_iter()`
+#[cfg(feature = "python")]
+#[pymethods]
+impl PyRowIterator {
+    pub fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    /// Next row, waiting for the server without holding the GIL
+    pub fn __next__(&mut self, py: Python<'_>) -> PyResult<Option<PyRow>> {
+        use crate::fetch_limits::Streamed;
+
+        loop {
+            let Some(receiver) = self.receiver.lock().ok().and_then(|mut r| r.take()) else {
+                return Ok(None);
+            };
+            let (receiver, message) = py.detach(move || {
+                let message = receiver.recv();
+                (receiver, message)
+            });
+            match message {
+                Ok(Streamed::Columns(names)) => self.column_names = names,
+                Ok(Streamed::Row(data)) => {
+                    self.put_back(receiver);
+                    return Ok(Some(PyRow {
+                        data,
+                        column_names: self.column_names.clone(),
+                    }));
+                }
+                Ok(Streamed::Done(Ok(_))) | Err(_) => return Ok(None),
+                Ok(Streamed::Done(Err(e))) => {
+                    return Err(DbcrustCommandError::new_err(format!(
+                        "Query execution failed: {e}"
+                    )));
+                }
+            }
+            self.put_back(receiver);
+        }
+    }
+
+    /// Column names, known once the first row arrived
+    #[getter]
+    pub fn description(&self) -> Vec<String> {
+        self.column_names.clone()
+    }
+
+    /// Stop reading; the query is cancelled and the connection freed
+    pub fn close(&mut self) {
+        if let Ok(mut receiver) = self.receiver.lock() {
+            receiver.take();
+        }
+    }
+
+    pub fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    pub fn __exit__(
+        &mut self,
+        _exc_type: Option<Py<PyAny>>,
+        _exc_value: Option<Py<PyAny>>,
+        _traceback: Option<Py<PyAny>>,
+    ) -> bool {
+        self.close();
+        false
+    }
+}
+
+#[cfg(feature = "python")]
+impl PyRowIterator {
+    fn put_back(&self, receiver: std::sync::mpsc::Receiver<crate::fetch_limits::Streamed>) {
+        if let Ok(mut slot) = self.receiver.lock() {
+            *slot = Some(receiver);
+        }
+    }
+}
+
 /// Implementation for PyRow - Individual row data
 #[cfg(feature = "python")]
 #[pymethods]
@@ -895,13 +1233,15 @@ impl PyResultSet {
 /// Connect function - creates a PyConnection from URL with optional parameters
 #[cfg(feature = "python")]
 #[pyfunction]
-#[pyo3(signature = (connection_url, timeout=None, auto_commit=None))]
+#[pyo3(signature = (connection_url, timeout=None, auto_commit=None, max_rows=None, max_bytes=None))]
 pub fn py_connect(
     connection_url: &str,
     timeout: Option<f64>,
     auto_commit: Option<bool>,
+    max_rows: Option<usize>,
+    max_bytes: Option<usize>,
 ) -> PyResult<PyConnection> {
-    PyConnection::new(connection_url, timeout, auto_commit)
+    PyConnection::new(connection_url, timeout, auto_commit, max_rows, max_bytes)
 }
 
 /// Return non-secret AI configuration diagnostics for Python/Django callers.
@@ -1155,10 +1495,14 @@ pub async fn run_interactive_cli(url: &str) -> Result<(), Box<dyn std::error::Er
     // Create Args structure with the connection URL
     let args = Args {
         connection_url: Some(url.to_string()),
+        from_env: None,
+        from_django: None,
         command: Vec::new(),
+        file: Vec::new(),
         ssh_tunnel: None,
         completions: None,
         update: false,
+        no_daemon: false,
         subcommand: None,
     };
 
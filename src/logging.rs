@@ -2,7 +2,7 @@ use std::fs::{File, OpenOptions};
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 use std::ptr;
-use std::sync::{Mutex, Once};
+use std::sync::{Mutex, Once, OnceLock};
 use std::time::SystemTime;
 
 use crate::config;
@@ -113,7 +113,11 @@ fn log_message(message: &str) -> io::Result<()> {
         if let Some(log_file) = &*log_file_ptr {
             if let Ok(mut file_guard) = log_file.lock() {
                 if let Some((file, _)) = &mut *file_guard {
-                    let log_entry = format!("[{}] {}\n", timestamp(), message);
+                    let log_entry = format!(
+                        "[{}] {}\n",
+                        timestamp(),
+                        crate::password_sanitizer::sanitize_text_for_logging(message)
+                    );
                     file.write_all(log_entry.as_bytes())?;
                     file.flush()?;
                 }
@@ -138,3 +142,24 @@ pub fn debug(message: &str) -> io::Result<()> {
     init()?;
     log_message(&format!("DEBUG {message}"))
 }
+
+type LevelReloadHandle =
+    tracing_subscriber::reload::Handle<tracing_subscriber::EnvFilter, tracing_subscriber::Registry>;
+
+/// Handle to the tracing level filter installed by the binary, so the level can change at runtime
+static LEVEL_RELOAD_HANDLE: OnceLock<LevelReloadHandle> = OnceLock::new();
+
+/// Register the reload handle of the tracing subscriber's level filter
+pub fn set_level_reload_handle(handle: LevelReloadHandle) {
+    let _ = LEVEL_RELOAD_HANDLE.set(handle);
+}
+
+/// Change the tracing level at runtime. Returns false when no reloadable subscriber is installed.
+pub fn set_tracing_level(level: crate::config::LogLevel) -> bool {
+    match LEVEL_RELOAD_HANDLE.get() {
+        Some(handle) => handle
+            .reload(tracing_subscriber::EnvFilter::new(level.to_string()))
+            .is_ok(),
+        None => false,
+    }
+}
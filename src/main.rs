@@ -1,24 +1,24 @@
 extern crate dbcrust;
-mod cli;
-// completion is now in lib.rs
-mod highlighter;
-mod named_queries;
+// completion and highlighter are now in lib.rs
 mod pager;
-mod password_sanitizer;
 mod pgpass;
 mod script;
 
 use clap::Parser;
 use dbcrust::cli::Args;
 use dbcrust::config::{Config, LogLevel};
+use dbcrust::password_sanitizer::RedactingMakeWriter;
 use std::error::Error as StdError;
 use tracing_appender::rolling::{RollingFileAppender, Rotation};
-use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
+use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt, reload, util::SubscriberInitExt};
 // For `std::io::stdout().flush()`
 
 /// Initialize the tracing system based on configuration
 fn init_tracing() -> Result<(), Box<dyn StdError>> {
     let config = Config::load();
+    if let Err(e) = dbcrust::password_sanitizer::apply_config(&config.logging.redact) {
+        eprintln!("Warning: [logging] redact: {e}");
+    }
 
     // Convert our LogLevel to tracing filter
     let level_filter = match config.logging.level {
@@ -29,8 +29,10 @@ fn init_tracing() -> Result<(), Box<dyn StdError>> {
         LogLevel::Error => "error",
     };
 
-    // Create the base registry
-    let registry = tracing_subscriber::registry().with(EnvFilter::new(level_filter));
+    // Create the base registry; the level filter is reloadable so \reloadconfig can change it
+    let (filter, reload_handle) = reload::Layer::new(EnvFilter::new(level_filter));
+    dbcrust::logging::set_level_reload_handle(reload_handle);
+    let registry = tracing_subscriber::registry().with(filter);
 
     // Build and initialize subscriber based on output preferences
     match (config.logging.console_output, config.logging.file_output) {
@@ -47,10 +49,14 @@ fn init_tracing() -> Result<(), Box<dyn StdError>> {
                 .build(&config.logging.file_path)?;
 
             registry
-                .with(fmt::layer().compact()) // Console layer
+                .with(
+                    fmt::layer()
+                        .compact()
+                        .with_writer(RedactingMakeWriter(std::io::stdout)),
+                ) // Console layer
                 .with(
                     fmt::layer() // File layer
-                        .with_writer(file_appender)
+                        .with_writer(RedactingMakeWriter(file_appender))
                         .with_ansi(false)
                         .with_target(true)
                         .with_file(true)
@@ -60,7 +66,13 @@ fn init_tracing() -> Result<(), Box<dyn StdError>> {
         }
         (true, false) => {
             // Console output only
-            registry.with(fmt::layer().compact()).init();
+            registry
+                .with(
+                    fmt::layer()
+                        .compact()
+                        .with_writer(RedactingMakeWriter(std::io::stdout)),
+                )
+                .init();
         }
         (false, true) => {
             // File output only
@@ -77,7 +89,7 @@ fn init_tracing() -> Result<(), Box<dyn StdError>> {
             registry
                 .with(
                     fmt::layer()
-                        .with_writer(file_appender)
+                        .with_writer(RedactingMakeWriter(file_appender))
                         .with_ansi(false)
                         .with_target(true)
                         .with_file(true)
@@ -123,6 +135,12 @@ pub async fn async_main_with_args(args: Args) -> Result<(), Box<dyn StdError>> {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn StdError>> {
+    // ssh runs this binary again to ask for passwords, 2FA codes and host keys
+    if let Some(code) = dbcrust::ssh_tunnel::run_askpass() {
+        std::process::exit(code);
+    }
+    dbcrust::ssh_tunnel::enable_askpass();
+
     // Initialize tracing system before anything else
     if let Err(e) = init_tracing() {
         eprintln!("Failed to initialize logging: {e}");
@@ -134,7 +152,10 @@ async fn main() -> Result<(), Box<dyn StdError>> {
         Ok(exit_code) => std::process::exit(exit_code),
         Err(e) => {
             // Display user-friendly error message instead of Debug representation
-            eprintln!("Error: {e}");
+            eprintln!(
+                "Error: {}",
+                dbcrust::password_sanitizer::sanitize_text_for_logging(&e.to_string())
+            );
             std::process::exit(1);
         }
     }
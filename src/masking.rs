@@ -0,0 +1,300 @@
+//! Result masking for screen sharing (`[masking]`, `\mask`)
+//!
+//! While masking is on, displayed results go through the `[masking]` rules
+//! before they are formatted: a rule picks columns by a name pattern
+//! (`*email*`, case-insensitive), values by a regex, or both, and replaces
+//! what it matched. Only display is masked; `\export`, `\copy` and file
+//! output keep the real values.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// `[masking]` configuration section
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct MaskingConfig {
+    /// Mask from the start of every session instead of after `\mask on`
+    pub enabled: bool,
+    /// Applied in order, each to the output of the previous one
+    pub rules: Vec<MaskRule>,
+}
+
+/// One `[[masking.rules]]` entry
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(default)]
+pub struct MaskRule {
+    /// Column name pattern with `*` and `?` wildcards; every column when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<String>,
+    /// Regex replaced in the value (`$1` refers to groups); the whole value when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    pub replacement: String,
+}
+
+impl MaskRule {
+    fn new(column: Option<&str>, pattern: Option<&str>, replacement: &str) -> Self {
+        Self {
+            column: column.map(str::to_string),
+            pattern: pattern.map(str::to_string),
+            replacement: replacement.to_string(),
+        }
+    }
+}
+
+impl Default for MaskingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            rules: vec![
+                MaskRule::new(Some("*email*"), Some("^[^@]+"), "****"),
+                MaskRule::new(Some("*password*"), None, "****"),
+                MaskRule::new(Some("*secret*"), None, "****"),
+                MaskRule::new(Some("*token*"), None, "****"),
+                MaskRule::new(Some("*ssn*"), None, "***-**-****"),
+                MaskRule::new(Some("*phone*"), Some("[0-9]"), "#"),
+                MaskRule::new(
+                    None,
+                    Some("[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\\.[A-Za-z]{2,}"),
+                    "****@****",
+                ),
+            ],
+        }
+    }
+}
+
+#[derive(Debug)]
+struct CompiledRule {
+    column: Option<Regex>,
+    pattern: Option<Regex>,
+    replacement: String,
+}
+
+/// Compiled `[masking]` rules
+#[derive(Debug)]
+pub struct Masker {
+    rules: Vec<CompiledRule>,
+}
+
+impl MaskingConfig {
+    pub fn compile(&self) -> Result<Masker, String> {
+        let rules = self
+            .rules
+            .iter()
+            .map(|rule| {
+                let column = rule
+                    .column
+                    .as_deref()
+                    .map(|glob| {
+                        Regex::new(&column_pattern(glob))
+                            .map_err(|e| format!("Invalid masking column '{glob}': {e}"))
+                    })
+                    .transpose()?;
+                let pattern = rule
+                    .pattern
+                    .as_deref()
+                    .map(|pattern| {
+                        Regex::new(pattern)
+                            .map_err(|e| format!("Invalid masking pattern '{pattern}': {e}"))
+                    })
+                    .transpose()?;
+                Ok(CompiledRule {
+                    column,
+                    pattern,
+                    replacement: rule.replacement.clone(),
+                })
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Masker { rules })
+    }
+}
+
+/// Case-insensitive anchored regex for a column name pattern
+fn column_pattern(glob: &str) -> String {
+    let mut pattern = String::from("(?i)^");
+    for c in glob.chars() {
+        match c {
+            '*' => pattern.push_str(".*"),
+            '?' => pattern.push('.'),
+            c => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    pattern.push('$');
+    pattern
+}
+
+impl Masker {
+    /// `value` of `column` with every matching rule applied, or `None` when
+    /// no rule changed it. Empty values stay empty.
+    pub fn mask(&self, column: &str, value: &str) -> Option<String> {
+        if value.is_empty() {
+            return None;
+        }
+        let mut masked: Option<String> = None;
+        for rule in &self.rules {
+            if rule
+                .column
+                .as_ref()
+                .is_some_and(|column_regex| !column_regex.is_match(column))
+            {
+                continue;
+            }
+            let current = masked.as_deref().unwrap_or(value);
+            let next = match &rule.pattern {
+                Some(pattern) => match pattern.replace_all(current, rule.replacement.as_str()) {
+                    std::borrow::Cow::Borrowed(_) => continue,
+                    std::borrow::Cow::Owned(replaced) => replaced,
+                },
+                None => rule.replacement.clone(),
+            };
+            masked = Some(next);
+        }
+        masked
+    }
+
+    /// Rows with the header first, every data cell masked
+    pub fn mask_rows(&self, data: &[Vec<String>]) -> Vec<Vec<String>> {
+        let Some((header, rows)) = data.split_first() else {
+            return Vec::new();
+        };
+        std::iter::once(header.clone())
+            .chain(rows.iter().map(|row| {
+                row.iter()
+                    .enumerate()
+                    .map(|(i, value)| {
+                        let column = header.get(i).map(String::as_str).unwrap_or_default();
+                        self.mask(column, value).unwrap_or_else(|| value.clone())
+                    })
+                    .collect()
+            }))
+            .collect()
+    }
+}
+
+/// Rules in use, set from `[masking]` (formatters have no Config access)
+static MASKER: RwLock<Option<Masker>> = RwLock::new(None);
+static MASKING_ON: AtomicBool = AtomicBool::new(false);
+
+/// Compile and publish the `[masking]` rules and turn masking on or off as
+/// `enabled` says. Invalid rules are reported and leave masking as it was.
+pub fn apply_config(config: &MaskingConfig) -> Result<(), String> {
+    set_rules(config.compile()?);
+    set_enabled(config.enabled);
+    Ok(())
+}
+
+fn set_rules(masker: Masker) {
+    if let Ok(mut current) = MASKER.write() {
+        *current = Some(masker);
+    }
+}
+
+/// `\mask on|off`
+pub fn set_enabled(enabled: bool) {
+    MASKING_ON.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    MASKING_ON.load(Ordering::Relaxed)
+}
+
+/// Number of rules in use
+pub fn rule_count() -> usize {
+    MASKER
+        .read()
+        .ok()
+        .and_then(|masker| masker.as_ref().map(|masker| masker.rules.len()))
+        .unwrap_or(0)
+}
+
+/// `data` with the rules applied when masking is on, `None` otherwise
+pub fn masked(data: &[Vec<String>]) -> Option<Vec<Vec<String>>> {
+    if !is_enabled() {
+        return None;
+    }
+    // Default rules when no [masking] section was applied (e.g. from Python)
+    if MASKER.read().is_ok_and(|masker| masker.is_none()) {
+        set_rules(MaskingConfig::default().compile().ok()?);
+    }
+    let masker = MASKER.read().ok()?;
+    masker.as_ref().map(|masker| masker.mask_rows(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(header: &[&str], row: &[&str]) -> Vec<Vec<String>> {
+        vec![
+            header.iter().map(|s| s.to_string()).collect(),
+            row.iter().map(|s| s.to_string()).collect(),
+        ]
+    }
+
+    #[test]
+    fn test_default_rules() {
+        let masker = MaskingConfig::default().compile().unwrap();
+        let masked = masker.mask_rows(&rows(
+            &[
+                "id",
+                "User_Email",
+                "api_token",
+                "home_phone",
+                "notes",
+                "ssn",
+            ],
+            &[
+                "42",
+                "alice@example.com",
+                "abc123",
+                "+1 555-0100",
+                "ping bob@corp.io today",
+                "",
+            ],
+        ));
+        assert_eq!(
+            masked[1],
+            vec![
+                "42",
+                "****@example.com",
+                "****",
+                "+# ###-####",
+                "ping ****@**** today",
+                ""
+            ]
+        );
+        assert_eq!(masked[0][1], "User_Email");
+    }
+
+    #[test]
+    fn test_rule_errors_and_groups() {
+        let card = MaskingConfig {
+            enabled: true,
+            rules: vec![MaskRule::new(
+                Some("card_??"),
+                Some("^\\d{12}(\\d{4})$"),
+                "************$1",
+            )],
+        }
+        .compile()
+        .unwrap();
+        assert_eq!(
+            card.mask("CARD_NO", "4111111111111111").as_deref(),
+            Some("************1111")
+        );
+        assert_eq!(card.mask("card_number", "4111111111111111"), None);
+
+        let invalid = MaskingConfig {
+            enabled: true,
+            rules: vec![MaskRule::new(None, Some("("), "x")],
+        };
+        assert!(
+            invalid
+                .compile()
+                .unwrap_err()
+                .contains("Invalid masking pattern")
+        );
+    }
+}
@@ -0,0 +1,239 @@
+//! dbcrust's own metadata as SQL tables (`SELECT * FROM dbcrust.history`)
+//!
+//! A REPL statement naming one of the tables below is not sent to the server.
+//! It runs on a throwaway in-memory SQLite database where they are attached
+//! as the `dbcrust` schema, filled from the local files when it runs:
+//! - `dbcrust.history`: this connection's query history, oldest first
+//! - `dbcrust.sessions`: the saved sessions (`\s`)
+//! - `dbcrust.recent_connections`: the recent connections (`\r`)
+//! - `dbcrust.schema`: the tables and columns completion knows for the
+//!   current connection (see [`crate::schema_cache`])
+//!
+//! Only SELECT and WITH statements are intercepted, and a server that has a
+//! `dbcrust` schema of its own gets the statement as usual.
+//!
+//! The statement is SQLite SQL, so it can join these tables together but not
+//! with tables of the server.
+
+use crate::config::{RecentConnection, SavedSession};
+use crate::database::{DatabaseError, DatabaseType};
+use crate::schema_cache::SchemaSnapshot;
+use crate::sql_parser::TokenType;
+use crate::sql_parser_trait::SqlParserFactory;
+use sqlx::Connection;
+use sqlx::sqlite::SqliteConnection;
+
+const TABLES: [&str; 4] = ["history", "sessions", "recent_connections", "schema"];
+
+const CREATE_TABLES: [&str; 4] = [
+    "CREATE TABLE dbcrust.history (id INTEGER, query TEXT)",
+    "CREATE TABLE dbcrust.sessions (name TEXT, database_type TEXT, host TEXT, port INTEGER, \
+     username TEXT, dbname TEXT, file_path TEXT, ssh_host TEXT, group_name TEXT)",
+    "CREATE TABLE dbcrust.recent_connections (connected_at TEXT, display_name TEXT, url TEXT, \
+     database_type TEXT, success INTEGER)",
+    "CREATE TABLE dbcrust.schema (schema_name TEXT, table_name TEXT, column_name TEXT)",
+];
+
+/// Whether `sql` is a SELECT (or WITH) reading one of the `dbcrust.*`
+/// tables. Names inside string literals and comments don't count.
+pub fn references(sql: &str, database_type: &DatabaseType) -> bool {
+    let sql = crate::sql_buffer::strip_comments(sql);
+    let tokens: Vec<_> = SqlParserFactory::create_parser(database_type.clone())
+        .parse_at_cursor(&sql, sql.len())
+        .base_context
+        .tokens
+        .into_iter()
+        .filter(|token| token.token_type != TokenType::Whitespace)
+        .collect();
+
+    let reads = tokens.first().is_some_and(|token| {
+        token.value.eq_ignore_ascii_case("SELECT") || token.value.eq_ignore_ascii_case("WITH")
+    });
+    // Double-quoted identifiers come out of the tokenizer as literals
+    let name = |index: usize| {
+        tokens.get(index).and_then(|token| match token.token_type {
+            TokenType::Keyword | TokenType::Identifier => Some(token.value.clone()),
+            TokenType::Literal if token.value.starts_with('"') => {
+                Some(token.value.trim_matches('"').to_string())
+            }
+            _ => None,
+        })
+    };
+    reads
+        && (0..tokens.len()).any(|i| {
+            name(i).is_some_and(|schema| schema.eq_ignore_ascii_case("dbcrust"))
+                && tokens[i + 1..].first().is_some_and(|dot| dot.value == ".")
+                && name(i + 2).is_some_and(|table| {
+                    TABLES.iter().any(|known| table.eq_ignore_ascii_case(known))
+                })
+        })
+}
+
+/// What the tables are filled from
+#[derive(Debug, Default)]
+pub struct Sources {
+    pub history: Vec<String>,
+    pub sessions: Vec<(String, SavedSession)>,
+    pub recent_connections: Vec<RecentConnection>,
+    pub schema: SchemaSnapshot,
+}
+
+impl Sources {
+    /// Rows of `dbcrust.schema`: one per known column, or one with a NULL
+    /// column for tables whose columns were never fetched
+    fn schema_rows(&self) -> Vec<(String, String, Option<String>)> {
+        let mut rows = Vec::new();
+        for (schema, tables) in &self.schema.tables {
+            for table in tables {
+                let qualified = format!("{schema}.{table}");
+                let columns = self
+                    .schema
+                    .columns
+                    .get(table)
+                    .or_else(|| self.schema.columns.get(&qualified));
+                match columns {
+                    Some(columns) => rows.extend(
+                        columns
+                            .iter()
+                            .map(|column| (schema.clone(), table.clone(), Some(column.clone()))),
+                    ),
+                    None => rows.push((schema.clone(), table.clone(), None)),
+                }
+            }
+        }
+        rows
+    }
+}
+
+/// Run `sql` against the `dbcrust.*` tables; header row first
+pub async fn run(sql: &str, sources: &Sources) -> Result<Vec<Vec<String>>, DatabaseError> {
+    let mut conn = SqliteConnection::connect("sqlite::memory:").await?;
+    sqlx::query("ATTACH DATABASE ':memory:' AS dbcrust")
+        .execute(&mut conn)
+        .await?;
+    for statement in CREATE_TABLES {
+        sqlx::query(statement).execute(&mut conn).await?;
+    }
+
+    let mut tx = conn.begin().await?;
+    for (id, query) in sources.history.iter().enumerate() {
+        sqlx::query("INSERT INTO dbcrust.history VALUES (?, ?)")
+            .bind(id as i64 + 1)
+            .bind(query)
+            .execute(&mut *tx)
+            .await?;
+    }
+    for (name, session) in &sources.sessions {
+        sqlx::query("INSERT INTO dbcrust.sessions VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)")
+            .bind(name)
+            .bind(session.database_type.to_string())
+            .bind(&session.host)
+            .bind(session.port)
+            .bind(&session.user)
+            .bind(&session.dbname)
+            .bind(&session.file_path)
+            .bind(
+                session
+                    .ssh_tunnel
+                    .as_ref()
+                    .filter(|tunnel| tunnel.enabled)
+                    .map(|tunnel| tunnel.ssh_host.clone()),
+            )
+            .bind(&session.group)
+            .execute(&mut *tx)
+            .await?;
+    }
+    for recent in &sources.recent_connections {
+        sqlx::query("INSERT INTO dbcrust.recent_connections VALUES (?, ?, ?, ?, ?)")
+            .bind(recent.timestamp.to_rfc3339())
+            .bind(&recent.display_name)
+            .bind(&recent.connection_url)
+            .bind(recent.database_type.to_string())
+            .bind(recent.success)
+            .execute(&mut *tx)
+            .await?;
+    }
+    for (schema, table, column) in sources.schema_rows() {
+        sqlx::query("INSERT INTO dbcrust.schema VALUES (?, ?, ?)")
+            .bind(schema)
+            .bind(table)
+            .bind(column)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+
+    let rows = sqlx::query(sql).fetch_all(&mut conn).await?;
+    crate::federated::with_header(&rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_references() {
+        let pg = DatabaseType::PostgreSQL;
+        assert!(references("SELECT * FROM dbcrust.history", &pg));
+        assert!(references("select count(*) from DBCRUST.\"sessions\"", &pg));
+        assert!(references(
+            "SELECT * FROM dbcrust.recent_connections WHERE success = 0",
+            &pg
+        ));
+        assert!(references(
+            "-- recent\nWITH h AS (SELECT * FROM dbcrust . history) SELECT * FROM h",
+            &pg
+        ));
+        assert!(!references("SELECT * FROM dbcrust.users", &pg));
+        assert!(!references("SELECT * FROM history", &pg));
+        assert!(!references(
+            "INSERT INTO notes VALUES ('see dbcrust.history')",
+            &pg
+        ));
+        assert!(!references("SELECT 'dbcrust.history' FROM users", &pg));
+        assert!(!references(
+            "SELECT * FROM users /* not dbcrust.history */",
+            &pg
+        ));
+        assert!(!references("DELETE FROM dbcrust.sessions", &pg));
+    }
+
+    #[tokio::test]
+    async fn test_run_over_history_and_schema() {
+        let mut sources = Sources {
+            history: vec![
+                "SELECT * FROM users".to_string(),
+                "SELECT 1".to_string(),
+                "DELETE FROM users WHERE id = 3".to_string(),
+            ],
+            ..Default::default()
+        };
+        sources.schema.tables.insert(
+            "public".to_string(),
+            vec!["users".to_string(), "orders".to_string()],
+        );
+        sources.schema.columns.insert(
+            "users".to_string(),
+            vec!["id".to_string(), "email".to_string()],
+        );
+
+        let results = run(
+            "SELECT id, query FROM dbcrust.history WHERE query LIKE '%users%' ORDER BY id",
+            &sources,
+        )
+        .await
+        .unwrap();
+        assert_eq!(results[0], vec!["id", "query"]);
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[2][0], "3");
+
+        let results = run(
+            "SELECT table_name, count(column_name) FROM dbcrust.schema GROUP BY table_name ORDER BY table_name",
+            &sources,
+        )
+        .await
+        .unwrap();
+        assert_eq!(results[1], vec!["orders", "0"]);
+        assert_eq!(results[2], vec!["users", "2"]);
+    }
+}
@@ -0,0 +1,325 @@
+//! Prometheus metrics and health endpoint for the daemon (`[metrics]`)
+//!
+//! Query counters are process-wide and fed by
+//! [`SessionStats::record_query`](crate::session_stats::SessionStats::record_query),
+//! so everything the daemon runs is counted. The daemon publishes its pool
+//! (open connections and the state of their SSH tunnels) after every request.
+//! With `[metrics] enabled = true`, `dbcrust daemon` serves them over plain
+//! HTTP: `GET /metrics` in the Prometheus text format, `GET /health` as JSON.
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::sync::Mutex;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use tracing::{debug, info, warn};
+
+/// Upper bounds of the latency histogram buckets, in seconds
+const LATENCY_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// `[metrics]` configuration section
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct MetricsConfig {
+    /// Serve `/metrics` and `/health` while `dbcrust daemon` runs
+    pub enabled: bool,
+    /// Address to listen on; keep it on loopback unless the port is firewalled
+    pub bind: String,
+    pub port: u16,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind: "127.0.0.1".to_string(),
+            port: 9187,
+        }
+    }
+}
+
+impl MetricsConfig {
+    pub fn address(&self) -> String {
+        format!("{}:{}", self.bind, self.port)
+    }
+}
+
+/// A pooled connection as reported to the metrics
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConnectionGauge {
+    /// Connection URL without the password
+    pub target: String,
+    /// `Some(up)` when the connection goes through an SSH tunnel
+    pub tunnel_up: Option<bool>,
+}
+
+struct Registry {
+    queries: AtomicU64,
+    errors: AtomicU64,
+    /// Per-bucket (not cumulative) counts; the last slot is `+Inf`
+    latency_buckets: [AtomicU64; LATENCY_BUCKETS.len() + 1],
+    latency_sum_micros: AtomicU64,
+    connections: Mutex<Vec<ConnectionGauge>>,
+}
+
+static REGISTRY: Registry = Registry {
+    queries: AtomicU64::new(0),
+    errors: AtomicU64::new(0),
+    latency_buckets: [const { AtomicU64::new(0) }; LATENCY_BUCKETS.len() + 1],
+    latency_sum_micros: AtomicU64::new(0),
+    connections: Mutex::new(Vec::new()),
+};
+
+static STARTED: OnceLock<Instant> = OnceLock::new();
+
+/// Count one executed statement
+pub fn record_query(elapsed: Duration, ok: bool) {
+    REGISTRY.queries.fetch_add(1, Ordering::Relaxed);
+    if !ok {
+        REGISTRY.errors.fetch_add(1, Ordering::Relaxed);
+    }
+    let seconds = elapsed.as_secs_f64();
+    let bucket = LATENCY_BUCKETS
+        .iter()
+        .position(|bound| seconds <= *bound)
+        .unwrap_or(LATENCY_BUCKETS.len());
+    REGISTRY.latency_buckets[bucket].fetch_add(1, Ordering::Relaxed);
+    REGISTRY
+        .latency_sum_micros
+        .fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+}
+
+/// Replace the published connection pool
+pub fn set_connections(connections: Vec<ConnectionGauge>) {
+    if let Ok(mut guard) = REGISTRY.connections.lock() {
+        *guard = connections;
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// All metrics in the Prometheus text exposition format
+pub fn render() -> String {
+    let mut out = String::new();
+    let counter = |out: &mut String, name: &str, help: &str, value: u64| {
+        let _ = writeln!(
+            out,
+            "# HELP {name} {help}\n# TYPE {name} counter\n{name} {value}"
+        );
+    };
+    counter(
+        &mut out,
+        "dbcrust_queries_total",
+        "Statements executed.",
+        REGISTRY.queries.load(Ordering::Relaxed),
+    );
+    counter(
+        &mut out,
+        "dbcrust_query_errors_total",
+        "Statements that failed.",
+        REGISTRY.errors.load(Ordering::Relaxed),
+    );
+
+    let name = "dbcrust_query_duration_seconds";
+    let _ = writeln!(
+        out,
+        "# HELP {name} Statement execution time.\n# TYPE {name} histogram"
+    );
+    let mut cumulative = 0;
+    for (i, bucket) in REGISTRY.latency_buckets.iter().enumerate() {
+        cumulative += bucket.load(Ordering::Relaxed);
+        let bound = LATENCY_BUCKETS
+            .get(i)
+            .map_or("+Inf".to_string(), |bound| bound.to_string());
+        let _ = writeln!(out, "{name}_bucket{{le=\"{bound}\"}} {cumulative}");
+    }
+    let sum = REGISTRY.latency_sum_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0;
+    let _ = writeln!(out, "{name}_sum {sum}\n{name}_count {cumulative}");
+
+    let connections = REGISTRY
+        .connections
+        .lock()
+        .map(|guard| guard.clone())
+        .unwrap_or_default();
+    let name = "dbcrust_open_connections";
+    let _ = writeln!(
+        out,
+        "# HELP {name} Connections held open by the daemon.\n# TYPE {name} gauge\n{name} {}",
+        connections.len()
+    );
+    let name = "dbcrust_ssh_tunnel_up";
+    let _ = writeln!(
+        out,
+        "# HELP {name} Whether the SSH tunnel of a pooled connection is running.\n# TYPE {name} gauge"
+    );
+    for connection in &connections {
+        if let Some(up) = connection.tunnel_up {
+            let _ = writeln!(
+                out,
+                "{name}{{target=\"{}\"}} {}",
+                escape_label(&connection.target),
+                u8::from(up)
+            );
+        }
+    }
+
+    if let Some(started) = STARTED.get() {
+        let name = "dbcrust_uptime_seconds";
+        let _ = writeln!(
+            out,
+            "# HELP {name} Seconds since the metrics endpoint started.\n# TYPE {name} gauge\n{name} {}",
+            started.elapsed().as_secs()
+        );
+    }
+    out
+}
+
+/// `/health` body
+pub fn health() -> String {
+    let connections = REGISTRY
+        .connections
+        .lock()
+        .map(|guard| guard.clone())
+        .unwrap_or_default();
+    let tunnels_down = connections
+        .iter()
+        .filter(|connection| connection.tunnel_up == Some(false))
+        .count();
+    serde_json::json!({
+        "status": if tunnels_down == 0 { "ok" } else { "degraded" },
+        "uptime_secs": STARTED.get().map_or(0, |started| started.elapsed().as_secs()),
+        "open_connections": connections.len(),
+        "tunnels_down": tunnels_down,
+    })
+    .to_string()
+}
+
+/// Status line, content type and body for a request path
+fn respond(path: &str) -> (&'static str, &'static str, String) {
+    match path.split('?').next().unwrap_or(path) {
+        "/metrics" => (
+            "200 OK",
+            "text/plain; version=0.0.4; charset=utf-8",
+            render(),
+        ),
+        "/health" | "/healthz" => ("200 OK", "application/json", health()),
+        _ => (
+            "404 Not Found",
+            "text/plain; charset=utf-8",
+            "Not found. Try /metrics or /health.\n".to_string(),
+        ),
+    }
+}
+
+async fn serve_client(stream: tokio::net::TcpStream) -> std::io::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    let Some(request_line) = lines.next_line().await? else {
+        return Ok(());
+    };
+    // Skip the headers
+    while let Some(line) = lines.next_line().await? {
+        if line.is_empty() {
+            break;
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (status, content_type, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some(path)) => respond(path),
+        _ => (
+            "405 Method Not Allowed",
+            "text/plain; charset=utf-8",
+            "Only GET is supported.\n".to_string(),
+        ),
+    };
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    writer.write_all(response.as_bytes()).await?;
+    writer.shutdown().await
+}
+
+/// Bind the metrics endpoint and serve it in the background
+pub async fn spawn_server(config: &MetricsConfig) -> std::io::Result<tokio::task::JoinHandle<()>> {
+    let listener = TcpListener::bind(config.address()).await?;
+    STARTED.get_or_init(Instant::now);
+    info!(
+        "Metrics on http://{}/metrics (health on /health)",
+        listener.local_addr()?
+    );
+    Ok(tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, peer)) => {
+                    tokio::spawn(async move {
+                        if let Err(e) = serve_client(stream).await {
+                            debug!("Metrics request from {peer} failed: {e}");
+                        }
+                    });
+                }
+                Err(e) => warn!("Metrics endpoint failed to accept a client: {e}"),
+            }
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_exposes_counters_histogram_and_tunnels() {
+        record_query(Duration::from_millis(3), true);
+        record_query(Duration::from_secs(30), false);
+        set_connections(vec![
+            ConnectionGauge {
+                target: "postgres://app@db/app".to_string(),
+                tunnel_up: Some(true),
+            },
+            ConnectionGauge {
+                target: "sqlite://local.db".to_string(),
+                tunnel_up: None,
+            },
+        ]);
+
+        let text = render();
+        assert!(text.contains("# TYPE dbcrust_queries_total counter"));
+        assert!(text.contains("# TYPE dbcrust_query_duration_seconds histogram"));
+        assert!(text.contains("dbcrust_open_connections 2"));
+        assert!(text.contains("dbcrust_ssh_tunnel_up{target=\"postgres://app@db/app\"} 1"));
+        assert!(!text.contains("local.db"));
+
+        // Buckets are cumulative: +Inf counts every statement
+        let count = |prefix: &str| -> u64 {
+            text.lines()
+                .find_map(|line| line.strip_prefix(prefix))
+                .and_then(|value| value.trim().parse().ok())
+                .unwrap()
+        };
+        assert_eq!(
+            count("dbcrust_query_duration_seconds_bucket{le=\"+Inf\"}"),
+            count("dbcrust_query_duration_seconds_count")
+        );
+        assert!(count("dbcrust_query_errors_total") >= 1);
+    }
+
+    #[test]
+    fn test_respond_routes() {
+        assert_eq!(respond("/health").0, "200 OK");
+        assert!(respond("/health").2.contains("\"status\""));
+        assert_eq!(respond("/metrics?x=1").0, "200 OK");
+        assert_eq!(respond("/").0, "404 Not Found");
+    }
+}
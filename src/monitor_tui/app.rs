@@ -0,0 +1,280 @@
+//! Dashboard state and event handling for `dbcrust monitor`
+
+use super::probe::{Connections, Reading, Replication, Status, Target, TargetKind};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+
+/// Latency samples kept per target, about ten minutes at the default interval
+pub const HISTORY_LEN: usize = 120;
+
+const SPARK_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// One line of the dashboard
+#[derive(Debug, Clone)]
+pub struct TargetRow {
+    pub name: String,
+    pub kind: TargetKind,
+    pub location: String,
+    /// `None` until the first round finishes
+    pub status: Option<Status>,
+    /// Round trips in microseconds, oldest first; `None` for rounds the
+    /// target was down
+    pub latency: VecDeque<Option<u64>>,
+    pub version: Option<String>,
+    pub connections: Option<Connections>,
+    pub replication: Option<Replication>,
+    /// When the target last went up or down
+    pub since: Instant,
+}
+
+impl TargetRow {
+    fn new(target: &Target) -> Self {
+        Self {
+            name: target.name.clone(),
+            kind: target.kind,
+            location: target.location.clone(),
+            status: None,
+            latency: VecDeque::with_capacity(HISTORY_LEN),
+            version: None,
+            connections: None,
+            replication: None,
+            since: Instant::now(),
+        }
+    }
+
+    pub fn is_up(&self) -> bool {
+        self.status == Some(Status::Up)
+    }
+
+    /// Round trip of the last round
+    pub fn last_latency(&self) -> Option<Duration> {
+        self.latency
+            .back()
+            .copied()
+            .flatten()
+            .map(Duration::from_micros)
+    }
+
+    /// Min, average and max of the recorded round trips
+    pub fn latency_stats(&self) -> Option<(Duration, Duration, Duration)> {
+        let samples: Vec<u64> = self.latency.iter().flatten().copied().collect();
+        let min = *samples.iter().min()?;
+        let max = *samples.iter().max()?;
+        let avg = samples.iter().sum::<u64>() / samples.len() as u64;
+        Some((
+            Duration::from_micros(min),
+            Duration::from_micros(avg),
+            Duration::from_micros(max),
+        ))
+    }
+
+    fn record(&mut self, reading: Reading) {
+        let was_up = self.is_up();
+        if reading.status == Status::Up {
+            if !was_up {
+                self.since = Instant::now();
+            }
+            // Keep the last known version and counts while the target is
+            // down, they say what came back up
+            self.version = reading.version.or(self.version.take());
+            self.connections = reading.connections;
+            self.replication = reading.replication;
+        } else if was_up || self.status.is_none() {
+            self.since = Instant::now();
+        }
+        if !matches!(reading.status, Status::Skipped(_)) {
+            if self.latency.len() == HISTORY_LEN {
+                self.latency.pop_front();
+            }
+            self.latency
+                .push_back(reading.round_trip.map(|rtt| rtt.as_micros() as u64));
+        }
+        self.status = Some(reading.status);
+    }
+}
+
+/// What the probes have found so far, shared between the probe loop and the UI
+#[derive(Debug)]
+pub struct Board {
+    pub rows: Vec<TargetRow>,
+    /// Rounds started
+    pub rounds: u64,
+    /// Whether a round is running
+    pub probing: bool,
+    pub last_round: Option<Instant>,
+    pub interval: Duration,
+}
+
+impl Board {
+    pub fn new(targets: &[Target], interval: Duration) -> Self {
+        Self {
+            rows: targets.iter().map(TargetRow::new).collect(),
+            rounds: 0,
+            probing: false,
+            last_round: None,
+            interval,
+        }
+    }
+
+    pub fn record(&mut self, index: usize, reading: Reading) {
+        if let Some(row) = self.rows.get_mut(index) {
+            row.record(reading);
+        }
+    }
+
+    /// Targets up, and targets checked (skipped ones excluded)
+    pub fn up_count(&self) -> (usize, usize) {
+        let checked = self
+            .rows
+            .iter()
+            .filter(|row| !matches!(row.status, Some(Status::Skipped(_))))
+            .count();
+        (self.rows.iter().filter(|row| row.is_up()).count(), checked)
+    }
+}
+
+/// `samples` as a line of block characters, `width` wide, the newest at the
+/// right; rounds a target was down are blank
+pub fn sparkline(samples: &VecDeque<Option<u64>>, width: usize) -> String {
+    let recent: Vec<Option<u64>> = samples
+        .iter()
+        .skip(samples.len().saturating_sub(width))
+        .copied()
+        .collect();
+    let max = recent.iter().flatten().copied().max().unwrap_or(0);
+    let min = recent.iter().flatten().copied().min().unwrap_or(0);
+    let mut line = " ".repeat(width - recent.len());
+    for sample in recent {
+        line.push(match sample {
+            None => ' ',
+            Some(_) if max == min => SPARK_LEVELS[0],
+            Some(value) => {
+                let level = (value - min) * (SPARK_LEVELS.len() as u64 - 1) / (max - min);
+                SPARK_LEVELS[level as usize]
+            }
+        });
+    }
+    line
+}
+
+/// UI state of the dashboard
+pub struct MonitorApp {
+    pub board: Arc<Mutex<Board>>,
+    /// Index of the selected row
+    pub selected: usize,
+    pub should_quit: bool,
+    /// Wakes the probe loop for a round now
+    refresh: Arc<Notify>,
+}
+
+impl MonitorApp {
+    pub fn new(board: Arc<Mutex<Board>>, refresh: Arc<Notify>) -> Self {
+        Self {
+            board,
+            selected: 0,
+            should_quit: false,
+            refresh,
+        }
+    }
+
+    fn row_count(&self) -> usize {
+        self.board.lock().map(|board| board.rows.len()).unwrap_or(0)
+    }
+
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.row_count() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    fn handle_key_event(&mut self, key: event::KeyEvent) {
+        if key.kind != KeyEventKind::Press {
+            return;
+        }
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.should_quit = true,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.should_quit = true
+            }
+            KeyCode::Char('r') => self.refresh.notify_one(),
+            KeyCode::Down | KeyCode::Char('j') => self.select_next(),
+            KeyCode::Up | KeyCode::Char('k') => self.select_previous(),
+            KeyCode::Home | KeyCode::Char('g') => self.selected = 0,
+            KeyCode::End | KeyCode::Char('G') => self.selected = self.row_count().saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    /// Poll for and handle events
+    pub fn poll_events(&mut self, timeout: Duration) -> std::io::Result<bool> {
+        if event::poll(timeout)? {
+            if let Event::Key(key) = event::read()? {
+                self.handle_key_event(key);
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reading(status: Status, micros: Option<u64>) -> Reading {
+        Reading {
+            status,
+            round_trip: micros.map(Duration::from_micros),
+            version: Some("PostgreSQL 17.2".to_string()),
+            connections: None,
+            replication: None,
+        }
+    }
+
+    #[test]
+    fn test_sparkline_scales_to_window() {
+        let samples: VecDeque<Option<u64>> = [Some(100), Some(800), None, Some(450)]
+            .into_iter()
+            .collect();
+        assert_eq!(sparkline(&samples, 6), "  ▁█ ▄");
+        assert_eq!(sparkline(&samples, 2), " ▁");
+        assert_eq!(sparkline(&VecDeque::new(), 3), "   ");
+    }
+
+    #[test]
+    fn test_record_tracks_history_and_status() {
+        let mut config = crate::config::Config::default();
+        config.aliases.insert(
+            "prod".to_string(),
+            "postgres://app@db1:5432/app".to_string(),
+        );
+        let targets = super::super::probe::targets(&config, None).unwrap();
+        let index = targets.iter().position(|t| t.name == "prod").unwrap();
+        let mut board = Board::new(&targets, Duration::from_secs(5));
+
+        board.record(index, reading(Status::Up, Some(1500)));
+        board.record(index, reading(Status::Down("refused".to_string()), None));
+        let row = &board.rows[index];
+        assert!(!row.is_up());
+        assert_eq!(row.latency, [Some(1500), None]);
+        assert_eq!(row.version.as_deref(), Some("PostgreSQL 17.2"));
+
+        for _ in 0..HISTORY_LEN {
+            board.record(index, reading(Status::Up, Some(2000)));
+        }
+        let row = &board.rows[index];
+        assert_eq!(row.latency.len(), HISTORY_LEN);
+        assert_eq!(row.last_latency(), Some(Duration::from_millis(2)));
+        assert_eq!(board.up_count().0, 1);
+
+        board.record(index, reading(Status::Skipped("vault".to_string()), None));
+        assert_eq!(board.rows[index].latency.len(), HISTORY_LEN);
+    }
+}
@@ -0,0 +1,160 @@
+//! Live dashboard of saved sessions and aliases (`dbcrust monitor`)
+//!
+//! Every saved session and `[aliases]` entry (or the sessions of one group)
+//! is checked on an interval, each over its own connection kept open between
+//! rounds. The dashboard shows for each target:
+//!
+//! - Up/down status and how long it has held
+//! - Round-trip latency with a sparkline of recent rounds
+//! - Client connections and how many are active (PostgreSQL, MySQL)
+//! - Replication lag, as a standby or the slowest standby of a primary
+//!
+//! Probes run on the async runtime while the UI draws from a blocking thread;
+//! both share a [`app::Board`] the probes fill in as each answer arrives, so
+//! a slow target never holds up the others.
+//!
+//! # Example
+//!
+//! ```text
+//! $ dbcrust monitor --group prod --interval 10
+//! ```
+
+mod app;
+mod probe;
+mod ui;
+
+pub use probe::{Target, TargetKind, targets};
+
+use app::{Board, MonitorApp};
+use crossterm::{
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute,
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+};
+use probe::Probe;
+use ratatui::prelude::*;
+use std::io::stdout;
+use std::panic;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+
+/// Open the dashboard for the sessions of `group`, or every session and
+/// alias, until the user quits
+///
+/// # Arguments
+///
+/// * `interval` - Time between the end of a round and the start of the next
+/// * `timeout` - Limit on each connect and query, after which a target is down
+pub async fn run_monitor(
+    config: &crate::config::Config,
+    group: Option<&str>,
+    interval: Duration,
+    timeout: Duration,
+) -> Result<(), String> {
+    let targets = targets(config, group)?;
+    if let Some(reason) = crate::explain_tui::tui_unavailable_reason() {
+        return Err(format!(
+            "{reason}. Use `dbcrust check` for a one-off report."
+        ));
+    }
+
+    let board = Arc::new(Mutex::new(Board::new(&targets, interval)));
+    let refresh = Arc::new(Notify::new());
+    let mut probes: Vec<Probe> = targets.into_iter().map(Probe::new).collect();
+
+    let app = MonitorApp::new(Arc::clone(&board), Arc::clone(&refresh));
+    let mut ui = tokio::task::spawn_blocking(move || run_dashboard(app));
+    let ui_result = |joined: Result<Result<(), String>, tokio::task::JoinError>| {
+        joined.map_err(|e| format!("Dashboard failed: {e}"))?
+    };
+
+    loop {
+        if let Ok(mut board) = board.lock() {
+            board.probing = true;
+            board.rounds += 1;
+        }
+        let round =
+            futures_util::future::join_all(probes.iter_mut().enumerate().map(|(index, probe)| {
+                let board = Arc::clone(&board);
+                async move {
+                    let reading = probe.run(timeout).await;
+                    if let Ok(mut board) = board.lock() {
+                        board.record(index, reading);
+                    }
+                }
+            }));
+        tokio::select! {
+            joined = &mut ui => return ui_result(joined),
+            _ = round => {}
+        }
+        if let Ok(mut board) = board.lock() {
+            board.probing = false;
+            board.last_round = Some(Instant::now());
+        }
+
+        tokio::select! {
+            joined = &mut ui => return ui_result(joined),
+            _ = tokio::time::sleep(interval) => {}
+            _ = refresh.notified() => {}
+        }
+    }
+}
+
+/// Draw the dashboard until the user quits, restoring the terminal after
+fn run_dashboard(mut app: MonitorApp) -> Result<(), String> {
+    // Set up panic hook to restore terminal on panic
+    let original_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |panic_info| {
+        let _ = disable_raw_mode();
+        let _ = execute!(stdout(), LeaveAlternateScreen, DisableMouseCapture);
+        original_hook(panic_info);
+    }));
+
+    let result = run_tui_loop(&mut app);
+
+    // Restore panic hook
+    let _ = panic::take_hook();
+
+    result
+}
+
+fn run_tui_loop(app: &mut MonitorApp) -> Result<(), String> {
+    enable_raw_mode().map_err(|e| format!("Failed to enable raw mode: {e}"))?;
+
+    let mut stdout = stdout();
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)
+        .map_err(|e| format!("Failed to enter alternate screen: {e}"))?;
+
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal =
+        Terminal::new(backend).map_err(|e| format!("Failed to create terminal: {e}"))?;
+    terminal
+        .clear()
+        .map_err(|e| format!("Failed to clear terminal: {e}"))?;
+
+    // Redraw on every poll timeout so readings show up as they arrive
+    let result = loop {
+        if let Err(e) = terminal.draw(|frame| ui::render(frame, app)) {
+            break Err(format!("Failed to draw: {e}"));
+        }
+        match app.poll_events(Duration::from_millis(250)) {
+            Ok(_) if app.should_quit => break Ok(()),
+            Ok(_) => {}
+            Err(e) => break Err(format!("Event error: {e}")),
+        }
+    };
+
+    disable_raw_mode().map_err(|e| format!("Failed to disable raw mode: {e}"))?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    )
+    .map_err(|e| format!("Failed to leave alternate screen: {e}"))?;
+    terminal
+        .show_cursor()
+        .map_err(|e| format!("Failed to show cursor: {e}"))?;
+
+    result
+}
@@ -0,0 +1,294 @@
+//! UI rendering for `dbcrust monitor`
+
+use super::app::{Board, MonitorApp, TargetRow, sparkline};
+use super::probe::{Replication, Status};
+use crate::format::format_lag;
+use crate::latency::format_duration;
+use crate::replication::Health;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Sparkline, Table, TableState, Wrap},
+};
+use std::time::Duration;
+
+/// Width of the sparkline column, in samples
+const SPARK_WIDTH: usize = 20;
+
+fn health_color(health: Health) -> Color {
+    match health {
+        Health::Ok => Color::Green,
+        Health::Warning => Color::Yellow,
+        Health::Critical => Color::Red,
+    }
+}
+
+fn status_cell(status: Option<&Status>) -> Span<'static> {
+    match status {
+        None => Span::styled("…", Style::default().fg(Color::DarkGray)),
+        Some(Status::Up) => Span::styled("● up", Style::default().fg(Color::Green)),
+        Some(Status::Down(_)) => Span::styled(
+            "✕ down",
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ),
+        Some(Status::Skipped(_)) => Span::styled("– skip", Style::default().fg(Color::DarkGray)),
+    }
+}
+
+fn lag_cell(replication: Option<Replication>) -> Span<'static> {
+    let dim = Style::default().fg(Color::DarkGray);
+    match replication {
+        None => Span::styled("-", dim),
+        Some(Replication::Primary(None)) => Span::styled("primary", dim),
+        Some(Replication::Standby(None)) => Span::styled("standby", dim),
+        Some(replication) => {
+            let lag = replication.lag().unwrap_or_default();
+            let prefix = match replication {
+                Replication::Primary(_) => "max ",
+                Replication::Standby(_) => "",
+            };
+            Span::styled(
+                format!("{prefix}{}", format_lag(lag)),
+                Style::default().fg(health_color(Health::from_seconds(lag))),
+            )
+        }
+    }
+}
+
+/// `12s`, `4m`, `3h`: how long a target has been up or down
+fn format_since(elapsed: Duration) -> String {
+    let seconds = elapsed.as_secs();
+    match seconds {
+        0..60 => format!("{seconds}s"),
+        60..3600 => format!("{}m", seconds / 60),
+        3600..86400 => format!("{}h", seconds / 3600),
+        _ => format!("{}d", seconds / 86400),
+    }
+}
+
+/// Render the dashboard
+pub fn render(frame: &mut Frame, app: &MonitorApp) {
+    let Ok(board) = app.board.lock() else {
+        return;
+    };
+    let area = frame.area();
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3), // Header
+            Constraint::Min(5),    // Targets
+            Constraint::Length(8), // Selected target
+            Constraint::Length(2), // Footer
+        ])
+        .split(area);
+
+    render_header(frame, &board, chunks[0]);
+    render_table(frame, &board, app.selected, chunks[1]);
+    if let Some(row) = board.rows.get(app.selected) {
+        render_details(frame, row, chunks[2]);
+    }
+    render_footer(frame, chunks[3]);
+}
+
+fn render_header(frame: &mut Frame, board: &Board, area: Rect) {
+    let (up, checked) = board.up_count();
+    let up_color = if up == checked {
+        Color::Green
+    } else if up == 0 {
+        Color::Red
+    } else {
+        Color::Yellow
+    };
+    let round = if board.probing {
+        "checking...".to_string()
+    } else {
+        match board.last_round {
+            Some(at) => format!(
+                "checked {} ago",
+                format_since(at.elapsed().min(Duration::from_secs(86400 * 365)))
+            ),
+            None => "waiting".to_string(),
+        }
+    };
+    let header = Paragraph::new(Line::from(vec![
+        Span::styled(
+            " dbcrust monitor ",
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::raw("  "),
+        Span::styled(
+            format!("{up}/{checked} up"),
+            Style::default().fg(up_color).add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            format!(
+                "  every {}s  round {}  {round}",
+                board.interval.as_secs(),
+                board.rounds
+            ),
+            Style::default().fg(Color::DarkGray),
+        ),
+    ]))
+    .block(Block::default().borders(Borders::ALL));
+    frame.render_widget(header, area);
+}
+
+fn render_table(frame: &mut Frame, board: &Board, selected: usize, area: Rect) {
+    let dim = Style::default().fg(Color::DarkGray);
+    let header = Row::new(
+        [
+            "Status",
+            "Name",
+            "Kind",
+            "Target",
+            "Latency",
+            "History",
+            "Conns (active)",
+            "Lag",
+            "Version",
+        ]
+        .map(|title| Cell::from(title).style(Style::default().add_modifier(Modifier::BOLD))),
+    );
+
+    let rows = board.rows.iter().map(|row| {
+        let latency = match row.last_latency() {
+            Some(latency) => Span::raw(format_duration(latency)),
+            None => Span::styled("-", dim),
+        };
+        let connections = match row.connections {
+            Some(c) => Span::raw(format!("{} ({})", c.total, c.active)),
+            None => Span::styled("-", dim),
+        };
+        Row::new(vec![
+            Cell::from(status_cell(row.status.as_ref())),
+            Cell::from(row.name.clone()),
+            Cell::from(Span::styled(row.kind.label(), dim)),
+            Cell::from(row.location.clone()),
+            Cell::from(latency),
+            Cell::from(Span::styled(
+                sparkline(&row.latency, SPARK_WIDTH),
+                Style::default().fg(if row.is_up() {
+                    Color::Cyan
+                } else {
+                    Color::DarkGray
+                }),
+            )),
+            Cell::from(connections),
+            Cell::from(lag_cell(row.replication)),
+            Cell::from(Span::styled(row.version.clone().unwrap_or_default(), dim)),
+        ])
+    });
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(7),
+            Constraint::Min(10),
+            Constraint::Length(8),
+            Constraint::Min(16),
+            Constraint::Length(9),
+            Constraint::Length(SPARK_WIDTH as u16),
+            Constraint::Length(14),
+            Constraint::Length(12),
+            Constraint::Length(16),
+        ],
+    )
+    .header(header)
+    .row_highlight_style(Style::default().bg(Color::DarkGray))
+    .block(Block::default().borders(Borders::ALL).title(" Targets "));
+
+    let mut state = TableState::default().with_selected(Some(selected));
+    frame.render_stateful_widget(table, area, &mut state);
+}
+
+fn render_details(frame: &mut Frame, row: &TargetRow, area: Rect) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" {} ", row.name));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+        .split(inner);
+
+    let dim = Style::default().fg(Color::DarkGray);
+    let state = match &row.status {
+        Some(Status::Up) => "up",
+        Some(Status::Down(_)) => "down",
+        Some(Status::Skipped(_)) => "skipped",
+        None => "connecting",
+    };
+    let mut lines = vec![Line::from(vec![
+        status_cell(row.status.as_ref()),
+        Span::styled(
+            format!("  {state} for {}", format_since(row.since.elapsed())),
+            dim,
+        ),
+    ])];
+    match row.latency_stats() {
+        Some((min, avg, max)) => lines.push(Line::from(format!(
+            "min {}  avg {}  max {}",
+            format_duration(min),
+            format_duration(avg),
+            format_duration(max)
+        ))),
+        None => lines.push(Line::from(Span::styled("no round trips yet", dim))),
+    }
+    let down_rounds = row.latency.iter().filter(|sample| sample.is_none()).count();
+    if down_rounds > 0 {
+        lines.push(Line::from(Span::styled(
+            format!(
+                "down {down_rounds} of the last {} rounds",
+                row.latency.len()
+            ),
+            Style::default().fg(Color::Yellow),
+        )));
+    }
+    if let Some(Status::Down(reason) | Status::Skipped(reason)) = &row.status {
+        lines.push(Line::from(Span::styled(
+            reason.clone(),
+            Style::default().fg(Color::Red),
+        )));
+    }
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }), chunks[0]);
+
+    // Most recent samples that fit, newest at the right
+    let width = chunks[1].width as usize;
+    let samples: Vec<Option<u64>> = row
+        .latency
+        .iter()
+        .skip(row.latency.len().saturating_sub(width))
+        .copied()
+        .collect();
+    let sparkline = Sparkline::default()
+        .data(samples)
+        .absent_value_symbol("·")
+        .absent_value_style(Style::default().fg(Color::Red))
+        .style(Style::default().fg(Color::Cyan));
+    frame.render_widget(sparkline, chunks[1]);
+}
+
+fn render_footer(frame: &mut Frame, area: Rect) {
+    let help_text = Line::from(vec![
+        Span::styled(" [", Style::default().fg(Color::DarkGray)),
+        Span::styled("j/k", Style::default().fg(Color::Cyan)),
+        Span::styled("] Navigate  [", Style::default().fg(Color::DarkGray)),
+        Span::styled("r", Style::default().fg(Color::Cyan)),
+        Span::styled("] Check now  [", Style::default().fg(Color::DarkGray)),
+        Span::styled("q", Style::default().fg(Color::Cyan)),
+        Span::styled("] Quit ", Style::default().fg(Color::DarkGray)),
+    ]);
+
+    let footer = Paragraph::new(help_text)
+        .style(Style::default().fg(Color::DarkGray))
+        .block(Block::default().borders(Borders::TOP));
+
+    frame.render_widget(footer, area);
+}
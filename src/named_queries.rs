@@ -1,4 +1,7 @@
+use crate::database::DatabaseType;
 use regex::Regex;
+use std::collections::HashMap;
+use thiserror::Error;
 
 /// Process a named query with parameter substitution.
 /// Supports:
@@ -59,9 +62,311 @@ pub fn process_query(query: &str, args: &[&str]) -> String {
     result
 }
 
+/// Errors found while parsing a named query template
+#[derive(Debug, Error, PartialEq)]
+pub enum TemplateError {
+    #[error("Unclosed '{{{{#{0}}}}}' block")]
+    UnclosedBlock(String),
+    #[error("Unexpected '{{{{/{0}}}}}' without a matching '{{{{#{0}}}}}'")]
+    UnexpectedClose(String),
+    #[error("'{{{{else}}}}' is only allowed inside '{{{{#if}}}}'")]
+    MisplacedElse,
+    #[error("'{{{{#{0}}}}}' needs a parameter name")]
+    MissingParameter(String),
+    #[error("'{{{{{0}}}}}' is only allowed inside '{{{{#each}}}}'")]
+    OutsideEach(String),
+    #[error("Unknown template tag '{{{{{0}}}}}'")]
+    UnknownTag(String),
+    #[error("Unterminated '{{{{' tag")]
+    UnterminatedTag,
+}
+
+/// Parsed template node
+#[derive(Debug)]
+enum TemplateNode {
+    Text(String),
+    /// `{{this}}` or `{{@index}}` inside an `{{#each}}` block
+    LoopValue(String),
+    If {
+        name: String,
+        then: Vec<TemplateNode>,
+        otherwise: Vec<TemplateNode>,
+    },
+    Each {
+        name: String,
+        separator: String,
+        body: Vec<TemplateNode>,
+    },
+}
+
+/// Open block while parsing
+enum OpenBlock {
+    If {
+        name: String,
+        then: Option<Vec<TemplateNode>>,
+    },
+    Each {
+        name: String,
+        separator: String,
+    },
+}
+
+/// Whether a named query uses template blocks
+pub fn is_template(query: &str) -> bool {
+    query.contains("{{")
+}
+
+/// Parse `{{#each name}}` / `{{#each name separator=" OR "}}` arguments
+fn parse_each_args(args: &str) -> Result<(String, String), TemplateError> {
+    let (name, rest) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+    if name.is_empty() {
+        return Err(TemplateError::MissingParameter("each".to_string()));
+    }
+    let separator = match rest.trim().strip_prefix("separator=") {
+        Some(quoted) => quoted
+            .strip_prefix('"')
+            .and_then(|s| s.strip_suffix('"'))
+            .ok_or_else(|| TemplateError::UnknownTag(format!("#each {args}")))?
+            .to_string(),
+        None if rest.trim().is_empty() => ", ".to_string(),
+        None => return Err(TemplateError::UnknownTag(format!("#each {args}"))),
+    };
+    Ok((name.to_string(), separator))
+}
+
+fn parse_template(query: &str) -> Result<Vec<TemplateNode>, TemplateError> {
+    let mut stack: Vec<(OpenBlock, Vec<TemplateNode>)> = Vec::new();
+    let mut current: Vec<TemplateNode> = Vec::new();
+    let mut rest = query;
+
+    while let Some(start) = rest.find("{{") {
+        if start > 0 {
+            current.push(TemplateNode::Text(rest[..start].to_string()));
+        }
+        let after = &rest[start + 2..];
+        let end = after.find("}}").ok_or(TemplateError::UnterminatedTag)?;
+        let tag = after[..end].trim();
+        rest = &after[end + 2..];
+
+        let in_each = stack
+            .iter()
+            .any(|(block, _)| matches!(block, OpenBlock::Each { .. }));
+
+        if let Some(name) = tag.strip_prefix("#if") {
+            let name = name.trim();
+            if name.is_empty() || name.contains(char::is_whitespace) {
+                return Err(TemplateError::MissingParameter("if".to_string()));
+            }
+            let block = OpenBlock::If {
+                name: name.to_string(),
+                then: None,
+            };
+            stack.push((block, std::mem::take(&mut current)));
+        } else if let Some(args) = tag.strip_prefix("#each") {
+            let (name, separator) = parse_each_args(args.trim())?;
+            let block = OpenBlock::Each { name, separator };
+            stack.push((block, std::mem::take(&mut current)));
+        } else if tag == "else" {
+            match stack.last_mut() {
+                Some((OpenBlock::If { then, .. }, _)) if then.is_none() => {
+                    *then = Some(std::mem::take(&mut current));
+                }
+                _ => return Err(TemplateError::MisplacedElse),
+            }
+        } else if let Some(closing) = tag.strip_prefix('/') {
+            let closing = closing.trim();
+            let (block, parent) = match stack.pop() {
+                Some((block @ OpenBlock::If { .. }, parent)) if closing == "if" => (block, parent),
+                Some((block @ OpenBlock::Each { .. }, parent)) if closing == "each" => {
+                    (block, parent)
+                }
+                _ => return Err(TemplateError::UnexpectedClose(closing.to_string())),
+            };
+            let body = std::mem::replace(&mut current, parent);
+            current.push(match block {
+                OpenBlock::If { name, then } => match then {
+                    Some(then) => TemplateNode::If {
+                        name,
+                        then,
+                        otherwise: body,
+                    },
+                    None => TemplateNode::If {
+                        name,
+                        then: body,
+                        otherwise: Vec::new(),
+                    },
+                },
+                OpenBlock::Each { name, separator } => TemplateNode::Each {
+                    name,
+                    separator,
+                    body,
+                },
+            });
+        } else if tag == "this" || tag == "@index" {
+            if !in_each {
+                return Err(TemplateError::OutsideEach(tag.to_string()));
+            }
+            current.push(TemplateNode::LoopValue(tag.to_string()));
+        } else {
+            return Err(TemplateError::UnknownTag(tag.to_string()));
+        }
+    }
+
+    if let Some((block, _)) = stack.last() {
+        let helper = match block {
+            OpenBlock::If { .. } => "if",
+            OpenBlock::Each { .. } => "each",
+        };
+        return Err(TemplateError::UnclosedBlock(helper.to_string()));
+    }
+    if !rest.is_empty() {
+        current.push(TemplateNode::Text(rest.to_string()));
+    }
+    Ok(current)
+}
+
+/// Check a named query template for syntax errors (used before saving)
+#[allow(dead_code)]
+pub fn validate_template(query: &str) -> Result<(), TemplateError> {
+    parse_template(query).map(|_| ())
+}
+
+/// Items of a list parameter (`ids=1,2,3`)
+fn list_items(value: &str) -> Vec<&str> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+fn render_nodes(
+    nodes: &[TemplateNode],
+    params: &HashMap<String, String>,
+    loop_item: Option<(&str, usize)>,
+    out: &mut String,
+) {
+    for node in nodes {
+        match node {
+            TemplateNode::Text(text) => out.push_str(text),
+            TemplateNode::LoopValue(tag) => {
+                if let Some((item, index)) = loop_item {
+                    if tag == "this" {
+                        out.push_str(item);
+                    } else {
+                        out.push_str(&index.to_string());
+                    }
+                }
+            }
+            TemplateNode::If {
+                name,
+                then,
+                otherwise,
+            } => {
+                let truthy = params.get(name).is_some_and(|value| !value.is_empty());
+                let branch = if truthy { then } else { otherwise };
+                render_nodes(branch, params, loop_item, out);
+            }
+            TemplateNode::Each {
+                name,
+                separator,
+                body,
+            } => {
+                let items = params.get(name).map(|v| list_items(v)).unwrap_or_default();
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        out.push_str(separator);
+                    }
+                    render_nodes(body, params, Some((item, index)), out);
+                }
+            }
+        }
+    }
+}
+
+/// Render template blocks with the given named parameters
+#[allow(dead_code)]
+pub fn render_template(
+    query: &str,
+    params: &HashMap<String, String>,
+) -> Result<String, TemplateError> {
+    let nodes = parse_template(query)?;
+    let mut out = String::with_capacity(query.len());
+    render_nodes(&nodes, params, None, &mut out);
+    Ok(out)
+}
+
+/// Split `\n` arguments into positional arguments and `name=value` parameters
+pub fn split_named_args<'a>(args: &[&'a str]) -> (Vec<&'a str>, HashMap<String, String>) {
+    let named_pattern = Regex::new(r"^([A-Za-z_][A-Za-z0-9_]*)=(.*)$").unwrap();
+    let mut positional = Vec::new();
+    let mut named = HashMap::new();
+    for arg in args {
+        match named_pattern.captures(arg) {
+            Some(cap) => {
+                named.insert(cap[1].to_string(), cap[2].to_string());
+            }
+            None => positional.push(*arg),
+        }
+    }
+    (positional, named)
+}
+
+/// `:name` placeholders outside literals and comments, `::casts` left out
+fn named_placeholders(query: &str) -> Vec<(std::ops::Range<usize>, String)> {
+    let code = crate::sql_formatter::blank_literals(query, &DatabaseType::PostgreSQL);
+    let pattern = Regex::new(r":([A-Za-z_][A-Za-z0-9_]*)").unwrap();
+    pattern
+        .captures_iter(&code)
+        .filter_map(|cap| {
+            let whole = cap.get(0)?;
+            (!code[..whole.start()].ends_with(':')).then(|| (whole.range(), cap[1].to_string()))
+        })
+        .collect()
+}
+
+/// Replace `:name` placeholders for provided parameters, leaving `::casts`,
+/// quoted text and comments alone
+fn substitute_named_params(query: &str, params: &HashMap<String, String>) -> String {
+    if params.is_empty() {
+        return query.to_string();
+    }
+    let mut result = String::with_capacity(query.len());
+    let mut last = 0;
+    for (range, name) in named_placeholders(query) {
+        if let Some(value) = params.get(&name) {
+            result.push_str(&query[last..range.start]);
+            result.push_str(value);
+            last = range.end;
+        }
+    }
+    result.push_str(&query[last..]);
+    result
+}
+
+/// Expand a named query: template blocks and `:name` parameters from
+/// `name=value` arguments, then positional/aggregate parameters from the rest
+#[allow(dead_code)]
+pub fn expand_named_query(query: &str, args: &[&str]) -> Result<String, TemplateError> {
+    // `name=value` stays a positional argument of plain `$1`/`$*` queries
+    let (positional, named) = if is_template(query) || !named_placeholders(query).is_empty() {
+        split_named_args(args)
+    } else {
+        (args.to_vec(), HashMap::new())
+    };
+    let rendered = if is_template(query) {
+        render_template(query, &named)?
+    } else {
+        query.to_string()
+    };
+    let rendered = substitute_named_params(&rendered, &named);
+    Ok(process_query(&rendered, &positional))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rstest::rstest;
 
     #[test]
     fn test_positional_parameters() {
@@ -187,4 +492,77 @@ mod tests {
         let processed = process_query(query, &args);
         assert_eq!(processed, "SELECT * FROM users WHERE id IN ()");
     }
+
+    #[test]
+    fn test_template_optional_filter() {
+        let query = "SELECT * FROM orders WHERE 1=1{{#if user_id}} AND user_id = :user_id{{/if}}";
+        assert_eq!(
+            expand_named_query(query, &["user_id=42"]).unwrap(),
+            "SELECT * FROM orders WHERE 1=1 AND user_id = 42"
+        );
+        assert_eq!(
+            expand_named_query(query, &[]).unwrap(),
+            "SELECT * FROM orders WHERE 1=1"
+        );
+    }
+
+    #[test]
+    fn test_template_else_and_each() {
+        let query = "SELECT * FROM t WHERE {{#if tags}}({{#each tags separator=\" OR \"}}tag = '{{this}}'{{/each}}){{else}}true{{/if}} AND id IN ({{#each ids}}{{this}}{{/each}})";
+        assert_eq!(
+            expand_named_query(query, &["tags=a,b", "ids=1,2,3"]).unwrap(),
+            "SELECT * FROM t WHERE (tag = 'a' OR tag = 'b') AND id IN (1, 2, 3)"
+        );
+        assert_eq!(
+            expand_named_query(query, &["ids=7"]).unwrap(),
+            "SELECT * FROM t WHERE true AND id IN (7)"
+        );
+    }
+
+    #[test]
+    fn test_named_params_mix_with_positional_and_skip_casts() {
+        let query = "SELECT created::date FROM $1 WHERE status = :status AND region = :region";
+        assert_eq!(
+            expand_named_query(query, &["events", "status='open'"]).unwrap(),
+            "SELECT created::date FROM events WHERE status = 'open' AND region = :region"
+        );
+    }
+
+    #[test]
+    fn test_named_params_skip_quoted_text_and_comments() {
+        let query = "SELECT ':status', $$ :status $$ -- :status\nFROM t WHERE s = :status";
+        assert_eq!(
+            expand_named_query(query, &["status=1"]).unwrap(),
+            "SELECT ':status', $$ :status $$ -- :status\nFROM t WHERE s = 1"
+        );
+    }
+
+    #[rstest]
+    #[case(
+        "SELECT * FROM t WHERE filter = '$1'",
+        "SELECT * FROM t WHERE filter = 'status=active'"
+    )]
+    #[case(
+        "SELECT * FROM t WHERE f IN ($@)",
+        "SELECT * FROM t WHERE f IN ('status=active')"
+    )]
+    fn test_positional_key_value_args(#[case] query: &str, #[case] expected: &str) {
+        assert_eq!(
+            expand_named_query(query, &["status=active"]).unwrap(),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case("{{#if a}}x", TemplateError::UnclosedBlock("if".to_string()))]
+    #[case("x{{/each}}", TemplateError::UnexpectedClose("each".to_string()))]
+    #[case("{{#each a}}{{/if}}", TemplateError::UnexpectedClose("if".to_string()))]
+    #[case("{{else}}", TemplateError::MisplacedElse)]
+    #[case("{{#if}}x{{/if}}", TemplateError::MissingParameter("if".to_string()))]
+    #[case("{{this}}", TemplateError::OutsideEach("this".to_string()))]
+    #[case("{{user_id}}", TemplateError::UnknownTag("user_id".to_string()))]
+    #[case("{{#if a}", TemplateError::UnterminatedTag)]
+    fn test_validate_template_errors(#[case] query: &str, #[case] expected: TemplateError) {
+        assert_eq!(validate_template(query), Err(expected));
+    }
 }
@@ -0,0 +1,344 @@
+//! Executable SQL notebooks (`dbcrust notebook run`)
+//!
+//! A notebook is a markdown file (`.dbnb` by convention) whose ```` ```sql ````
+//! fences are run top to bottom against one connection. Each cell's results
+//! are written back right below it as markdown tables, between
+//! `<!-- dbcrust:output -->` markers, so running the notebook again replaces
+//! them instead of piling up. Everything else in the file is kept as written.
+//!
+//! The connection comes from the command line (`dbcrust <URL> notebook run
+//! file.dbnb`) or from a `connection:` line in YAML-style front matter.
+//! A ```` ```sql skip ```` fence is kept but never run.
+
+use crate::database::DatabaseType;
+use crate::db::Database;
+use std::fmt::Write as _;
+
+const OUTPUT_START: &str = "<!-- dbcrust:output -->";
+const OUTPUT_END: &str = "<!-- /dbcrust:output -->";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SqlCell {
+    /// The opening fence line, e.g. "```sql"
+    pub open: String,
+    pub code: String,
+    /// The closing fence line
+    pub close: String,
+    /// Results from the last run, without the markers
+    pub output: Option<String>,
+    /// ```` ```sql skip ````: kept, never executed
+    pub skip: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Block {
+    /// Markdown (and non-SQL fences), kept verbatim
+    Text(String),
+    Sql(SqlCell),
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Notebook {
+    /// `connection:` from the front matter
+    pub connection: Option<String>,
+    pub blocks: Vec<Block>,
+}
+
+/// Outcome of [`run`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RunSummary {
+    pub executed: usize,
+    pub failed: usize,
+    /// Cells left untouched after a failure (or marked `skip`)
+    pub not_run: usize,
+}
+
+/// Opening code fence: marker characters and the info string
+fn fence_open(line: &str) -> Option<(&str, &str)> {
+    let trimmed = line.trim_start();
+    let marker = trimmed.chars().next().filter(|c| matches!(c, '`' | '~'))?;
+    let len = trimmed.chars().take_while(|&c| c == marker).count();
+    (len >= 3).then(|| (&trimmed[..len], trimmed[len..].trim()))
+}
+
+fn closes_fence(line: &str, open_marker: &str) -> bool {
+    let trimmed = line.trim();
+    let marker = open_marker.chars().next().unwrap_or('`');
+    trimmed.len() >= open_marker.len() && trimmed.chars().all(|c| c == marker)
+}
+
+/// Parse a notebook. Output blocks found right after an SQL cell (blank lines
+/// in between are allowed) become that cell's previous output.
+pub fn parse(text: &str) -> Notebook {
+    let mut notebook = Notebook::default();
+    let lines: Vec<&str> = text.split_inclusive('\n').collect();
+    let mut i = 0;
+    let mut pending = String::new();
+
+    // Front matter: --- key: value ... ---
+    if lines.first().is_some_and(|line| line.trim_end() == "---")
+        && let Some(end) = lines[1..].iter().position(|line| line.trim_end() == "---")
+    {
+        for line in &lines[1..=end] {
+            if let Some((key, value)) = line.split_once(':')
+                && key.trim() == "connection"
+            {
+                let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+                if !value.is_empty() {
+                    notebook.connection = Some(value.to_string());
+                }
+            }
+        }
+        pending.extend(lines[..=end + 1].iter().copied());
+        i = end + 2;
+    }
+
+    while i < lines.len() {
+        let line = lines[i];
+        let Some((marker, info)) = fence_open(line) else {
+            pending.push_str(line);
+            i += 1;
+            continue;
+        };
+        let mut words = info.split_whitespace();
+        let is_sql = words
+            .next()
+            .is_some_and(|lang| lang.eq_ignore_ascii_case("sql"));
+        let skip = words.any(|word| word.eq_ignore_ascii_case("skip"));
+
+        let Some(close) = lines[i + 1..]
+            .iter()
+            .position(|line| closes_fence(line, marker))
+            .map(|offset| i + 1 + offset)
+        else {
+            // Unterminated fence: the rest of the file is plain text
+            pending.extend(lines[i..].iter().copied());
+            break;
+        };
+        if !is_sql {
+            pending.extend(lines[i..=close].iter().copied());
+            i = close + 1;
+            continue;
+        }
+
+        if !pending.is_empty() {
+            notebook
+                .blocks
+                .push(Block::Text(std::mem::take(&mut pending)));
+        }
+        let mut cell = SqlCell {
+            open: line.trim_end_matches(['\r', '\n']).to_string(),
+            code: lines[i + 1..close].concat(),
+            close: lines[close].trim_end_matches(['\r', '\n']).to_string(),
+            output: None,
+            skip,
+        };
+        i = close + 1;
+
+        // Previous output, if any
+        let mut next = i;
+        while next < lines.len() && lines[next].trim().is_empty() {
+            next += 1;
+        }
+        if next < lines.len()
+            && lines[next].trim() == OUTPUT_START
+            && let Some(end) = lines[next + 1..]
+                .iter()
+                .position(|line| line.trim() == OUTPUT_END)
+                .map(|offset| next + 1 + offset)
+        {
+            cell.output = Some(lines[next + 1..end].concat());
+            i = end + 1;
+        }
+        notebook.blocks.push(Block::Sql(cell));
+    }
+    if !pending.is_empty() {
+        notebook.blocks.push(Block::Text(pending));
+    }
+    notebook
+}
+
+/// Render the notebook back to markdown
+pub fn render(notebook: &Notebook) -> String {
+    let mut out = String::new();
+    for block in &notebook.blocks {
+        match block {
+            Block::Text(text) => out.push_str(text),
+            Block::Sql(cell) => {
+                out.push_str(&cell.open);
+                out.push('\n');
+                out.push_str(&cell.code);
+                out.push_str(&cell.close);
+                out.push('\n');
+                if let Some(output) = &cell.output {
+                    let _ = write!(out, "\n{OUTPUT_START}\n{output}{OUTPUT_END}\n");
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Cell text for a markdown table: pipes escaped, line breaks as `<br>`
+fn table_cell(value: &str) -> String {
+    let value = value
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace('\n', "<br>");
+    if value.is_empty() {
+        " ".to_string()
+    } else {
+        value
+    }
+}
+
+/// A result (header row first) as a markdown table followed by the row count
+pub fn markdown_table(results: &[Vec<String>], max_rows: usize) -> String {
+    let Some((header, rows)) = results.split_first() else {
+        return "_OK_\n".to_string();
+    };
+    if header.is_empty() {
+        return "_OK_\n".to_string();
+    }
+    let line = |row: &[String]| {
+        let cells: Vec<String> = (0..header.len())
+            .map(|i| table_cell(row.get(i).map_or("", String::as_str)))
+            .collect();
+        format!("| {} |\n", cells.join(" | "))
+    };
+
+    let mut out = line(header);
+    let _ = writeln!(out, "|{}", "---|".repeat(header.len()));
+    for row in rows.iter().take(max_rows) {
+        out.push_str(&line(row));
+    }
+    out.push('\n');
+    let count = rows.len();
+    let noun = if count == 1 { "row" } else { "rows" };
+    if count > max_rows {
+        let _ = writeln!(out, "_{count} {noun}, first {max_rows} shown_");
+    } else {
+        let _ = writeln!(out, "_{count} {noun}_");
+    }
+    out
+}
+
+/// Run every SQL cell in order, replacing its output. Stops at the first
+/// failing cell unless `keep_going`; cells after it keep their old output.
+pub async fn run(
+    notebook: &mut Notebook,
+    database: &mut Database,
+    max_rows: usize,
+    keep_going: bool,
+) -> RunSummary {
+    let splittable = !matches!(
+        database.get_database_type(),
+        DatabaseType::MongoDB | DatabaseType::Elasticsearch
+    );
+    let mut summary = RunSummary::default();
+
+    for block in &mut notebook.blocks {
+        let Block::Sql(cell) = block else {
+            continue;
+        };
+        if cell.skip || (summary.failed > 0 && !keep_going) {
+            summary.not_run += 1;
+            continue;
+        }
+
+        let statements = if splittable {
+            crate::sql_buffer::split_statements(&cell.code)
+        } else {
+            vec![cell.code.trim().to_string()]
+        };
+        let mut outputs = Vec::new();
+        let mut failed = false;
+        for statement in statements.iter().filter(|s| !s.is_empty()) {
+            match database
+                .execute_query_with_info_no_column_selection(statement)
+                .await
+            {
+                Ok(results) => {
+                    outputs.push(match results.affected_rows_message() {
+                        Some(message) => format!("_{message}_\n"),
+                        None => markdown_table(&results.data, max_rows),
+                    });
+                    for more in &results.more_results {
+                        outputs.push(markdown_table(more, max_rows));
+                    }
+                }
+                Err(e) => {
+                    outputs.push(format!("> **Error:** {}\n", table_cell(&e.to_string())));
+                    failed = true;
+                    break;
+                }
+            }
+        }
+
+        summary.executed += 1;
+        if failed {
+            summary.failed += 1;
+        }
+        cell.output = (!outputs.is_empty()).then(|| outputs.join("\n"));
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTEBOOK: &str = "---\ntitle: Nightly checks\nconnection: \"session://prod\"\n---\n# Checks\n\n```sql\nSELECT 1;\n```\n\n<!-- dbcrust:output -->\n| old |\n|---|\n<!-- /dbcrust:output -->\n\nSome text.\n\n```bash\necho not sql\n```\n\n```sql skip\nDELETE FROM t;\n```\n";
+
+    #[test]
+    fn test_parse_keeps_text_and_previous_output() {
+        let notebook = parse(NOTEBOOK);
+        assert_eq!(notebook.connection.as_deref(), Some("session://prod"));
+
+        let cells: Vec<&SqlCell> = notebook
+            .blocks
+            .iter()
+            .filter_map(|block| match block {
+                Block::Sql(cell) => Some(cell),
+                Block::Text(_) => None,
+            })
+            .collect();
+        assert_eq!(cells.len(), 2);
+        assert_eq!(cells[0].code, "SELECT 1;\n");
+        assert_eq!(cells[0].output.as_deref(), Some("| old |\n|---|\n"));
+        assert!(!cells[0].skip);
+        assert!(cells[1].skip);
+
+        // Rendering without changes gives the file back
+        assert_eq!(render(&notebook), NOTEBOOK);
+    }
+
+    #[test]
+    fn test_render_replaces_output() {
+        let mut notebook = parse("```sql\nSELECT 1\n```\n\nAfter\n");
+        if let Block::Sql(cell) = &mut notebook.blocks[0] {
+            cell.output = Some(markdown_table(
+                &[vec!["a|b".to_string()], vec!["x\ny".to_string()]],
+                10,
+            ));
+        }
+        let rendered = render(&notebook);
+        assert_eq!(
+            rendered,
+            "```sql\nSELECT 1\n```\n\n<!-- dbcrust:output -->\n| a\\|b |\n|---|\n| x<br>y |\n\n_1 row_\n<!-- /dbcrust:output -->\n\nAfter\n"
+        );
+        // A second run replaces the block rather than adding one
+        assert_eq!(render(&parse(&rendered)), rendered);
+    }
+
+    #[test]
+    fn test_markdown_table_truncates_rows() {
+        let results: Vec<Vec<String>> = std::iter::once(vec!["n".to_string()])
+            .chain((0..5).map(|i| vec![i.to_string()]))
+            .collect();
+        let table = markdown_table(&results, 2);
+        assert!(table.starts_with("| n |\n|---|\n| 0 |\n| 1 |\n\n"));
+        assert!(table.ends_with("_5 rows, first 2 shown_\n"));
+        assert_eq!(markdown_table(&[], 10), "_OK_\n");
+    }
+}
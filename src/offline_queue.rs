@@ -0,0 +1,103 @@
+//! Statements typed while the connection is down (`\queue`)
+//!
+//! Once the `[connection]` keepalive finds the connection dead and cannot
+//! open a new one, SQL typed at the prompt is queued instead of failing. When
+//! the connection is back, the REPL offers to run the queue in order before
+//! the next input; `\queue run` runs it on demand. A statement that fails
+//! stops the run and stays queued with the ones after it.
+
+/// Pending statements of one connection, oldest first
+#[derive(Debug, Default)]
+pub struct OfflineQueue {
+    statements: Vec<String>,
+}
+
+impl OfflineQueue {
+    /// Queue `sql`; returns its position
+    pub fn push(&mut self, sql: &str) -> usize {
+        self.statements.push(sql.trim().to_string());
+        self.statements.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.statements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.statements.is_empty()
+    }
+
+    /// Drop every queued statement; returns how many there were
+    pub fn clear(&mut self) -> usize {
+        std::mem::take(&mut self.statements).len()
+    }
+
+    /// Take the statements out to run them
+    pub fn take(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.statements)
+    }
+
+    /// Put back statements that did not run, ahead of any queued meanwhile
+    pub fn restore(&mut self, mut statements: Vec<String>) {
+        statements.append(&mut self.statements);
+        self.statements = statements;
+    }
+
+    /// `\queue list`: numbered statements, long ones on one line each
+    pub fn list(&self) -> String {
+        if self.statements.is_empty() {
+            return "The queue is empty.".to_string();
+        }
+        let mut out = format!("{} queued statement(s):", self.statements.len());
+        for (index, sql) in self.statements.iter().enumerate() {
+            let line = sql.split_whitespace().collect::<Vec<_>>().join(" ");
+            out.push_str(&format!("\n  #{} {}", index + 1, line));
+        }
+        out
+    }
+}
+
+/// Ask whether to run the `count` statements queued while the connection
+/// was down; no when there is no terminal to ask on
+pub fn confirm_run(count: usize) -> bool {
+    if !crate::config_editor::can_run_interactive() {
+        return false;
+    }
+    inquire::Confirm::new(&format!(
+        "The connection is back. Run the {count} queued statement(s) now?"
+    ))
+    .with_default(false)
+    .with_help_message("No keeps them queued: \\queue run or \\queue clear")
+    .prompt()
+    .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queue_order_and_restore() {
+        let mut queue = OfflineQueue::default();
+        assert_eq!(queue.list(), "The queue is empty.");
+        assert_eq!(queue.push("INSERT INTO t VALUES (1);\n"), 1);
+        assert_eq!(queue.push("UPDATE t\n  SET a = 2"), 2);
+        assert_eq!(
+            queue.list(),
+            "2 queued statement(s):\n  #1 INSERT INTO t VALUES (1);\n  #2 UPDATE t SET a = 2"
+        );
+
+        let mut taken = queue.take();
+        assert!(queue.is_empty());
+        queue.push("SELECT 3");
+        taken.remove(0);
+        queue.restore(taken);
+        assert_eq!(queue.len(), 2);
+        assert!(
+            queue
+                .list()
+                .contains("#1 UPDATE t SET a = 2\n  #2 SELECT 3")
+        );
+        assert_eq!(queue.clear(), 2);
+    }
+}
@@ -1,5 +1,45 @@
 use std::io::{ErrorKind, Write};
 use std::process::{Command, Stdio};
+use unicode_width::UnicodeWidthStr;
+
+/// Width of the widest line in terminal columns, ignoring ANSI escape codes
+#[allow(dead_code)]
+pub fn max_line_width(content: &str) -> usize {
+    content
+        .lines()
+        .map(|line| strip_ansi(line).width())
+        .max()
+        .unwrap_or(0)
+}
+
+pub(crate) fn strip_ansi(line: &str) -> std::borrow::Cow<'_, str> {
+    if !line.contains('\x1b') {
+        return line.into();
+    }
+    let mut plain = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // CSI sequences end with a letter: ESC [ 1 ; 31 m
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            plain.push(c);
+        }
+    }
+    plain.into()
+}
+
+/// Whether `content` does not fit the screen: more than `max_lines` lines, or
+/// (when `max_width` is given) a line wider than `max_width` columns
+#[allow(dead_code)]
+pub fn exceeds_screen(content: &str, max_lines: usize, max_width: Option<usize>) -> bool {
+    content.lines().count() > max_lines
+        || max_width.is_some_and(|width| max_line_width(content) > width)
+}
 
 #[allow(dead_code)]
 pub fn page_output(content: &str, pager_cmd_str: &str) -> std::io::Result<()> {
@@ -66,3 +106,20 @@ pub fn page_output(content: &str, pager_cmd_str: &str) -> std::io::Result<()> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exceeds_screen_by_lines_or_width() {
+        let wide = format!("| id | {} |\n", "x".repeat(200));
+        assert!(!exceeds_screen(&wide, 25, None));
+        assert!(exceeds_screen(&wide, 25, Some(120)));
+        assert!(exceeds_screen("a\nb\nc\n", 2, Some(120)));
+        assert!(!exceeds_screen("a\nb\n", 2, Some(120)));
+
+        // Colour codes and wide characters are measured as displayed
+        assert_eq!(max_line_width("\x1b[1;31mERROR\x1b[0m 表"), 8);
+    }
+}
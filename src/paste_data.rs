@@ -0,0 +1,541 @@
+//! Rows pasted from a spreadsheet into a table (`\pastedata <table>`)
+//!
+//! Reads the clipboard as delimited text: tabs (what spreadsheets copy),
+//! commas, semicolons or pipes, whichever splits every line into the same
+//! number of cells. Cells starting with a double quote are quoted CSV-style
+//! and may hold the delimiter, doubled quotes and line breaks.
+//!
+//! A first line naming table columns is a header and maps the cells by name;
+//! otherwise cells map by position onto all columns, or onto the columns the
+//! server does not fill in when that is how many there are. Each cell is
+//! checked against its column type before anything is inserted, and every
+//! cell that does not fit is reported with its line and column. PostgreSQL
+//! loads the rows with one `COPY`, other databases with batched INSERTs.
+
+use crate::db::Database;
+use crate::insert_builder::{ColumnSpec, ColumnValue, ValueKind, parse_bool, quote_identifier};
+use std::fmt;
+
+/// Delimiters tried, in order of preference
+const DELIMITERS: [char; 4] = ['\t', ',', ';', '|'];
+/// Cell errors listed before the rest are only counted
+pub const MAX_REPORTED_ERRORS: usize = 20;
+
+/// Split `text` into rows of cells on `delimiter`; blank lines are skipped
+pub fn parse_delimited(text: &str, delimiter: char) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    let end_row = |row: &mut Vec<String>, cell: &mut String, rows: &mut Vec<Vec<String>>| {
+        row.push(std::mem::take(cell));
+        let row = std::mem::take(row);
+        if !(row.len() == 1 && row[0].trim().is_empty()) {
+            rows.push(row);
+        }
+    };
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    cell.push('"');
+                    chars.next();
+                }
+                '"' => quoted = false,
+                _ => cell.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if cell.is_empty() => quoted = true,
+            c if c == delimiter => row.push(std::mem::take(&mut cell)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => end_row(&mut row, &mut cell, &mut rows),
+            _ => cell.push(c),
+        }
+    }
+    end_row(&mut row, &mut cell, &mut rows);
+    rows
+}
+
+/// The delimiter splitting every line of `text` into the same number (more
+/// than one) of cells; tab when none does, which reads a single column
+pub fn detect_delimiter(text: &str) -> char {
+    DELIMITERS
+        .into_iter()
+        .find(|&delimiter| {
+            let rows = parse_delimited(text, delimiter);
+            let width = rows.first().map_or(0, Vec::len);
+            width > 1 && rows.iter().all(|row| row.len() == width)
+        })
+        .unwrap_or('\t')
+}
+
+/// `Customer ID` and `customer_id` name the same column
+fn normalized(name: &str) -> String {
+    name.trim()
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '_' | '-'))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// The table column each pasted column goes to, and whether the first row is
+/// a header naming them
+pub fn map_columns(
+    first_row: &[String],
+    columns: &[ColumnSpec],
+) -> Result<(Vec<usize>, bool), String> {
+    let by_name: Option<Vec<usize>> = first_row
+        .iter()
+        .map(|cell| {
+            columns
+                .iter()
+                .position(|column| normalized(&column.name) == normalized(cell))
+        })
+        .collect();
+    if let Some(indices) = by_name {
+        if let Some(name) = indices
+            .iter()
+            .enumerate()
+            .find(|(i, index)| indices[..*i].contains(*index))
+            .map(|(_, &index)| &columns[index].name)
+        {
+            return Err(format!("The header names column {name} twice"));
+        }
+        return Ok((indices, true));
+    }
+
+    let width = first_row.len();
+    if width == columns.len() {
+        return Ok(((0..width).collect(), false));
+    }
+    let filled_in: Vec<usize> = (0..columns.len())
+        .filter(|&i| !crate::datagen::is_auto_default(columns[i].default_value.as_deref()))
+        .collect();
+    if width == filled_in.len() {
+        return Ok((filled_in, false));
+    }
+    Err(format!(
+        "{width} pasted column(s) for a table with {} column(s); \
+         copy a header row naming the columns along with the data",
+        columns.len()
+    ))
+}
+
+/// `1,234,567.5` → `1234567.5`: commas grouping thousands are dropped
+fn without_grouping(number: &str) -> Option<String> {
+    let (integer, fraction) = match number.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (number, None),
+    };
+    let digits = integer.strip_prefix('-').unwrap_or(integer);
+    let mut groups = digits.split(',');
+    let first = groups.next()?;
+    let grouped = (1..=3).contains(&first.len())
+        && groups.all(|group| group.len() == 3)
+        && digits.contains(',');
+    grouped.then(|| {
+        let mut plain = integer.replace(',', "");
+        if let Some(fraction) = fraction {
+            plain.push('.');
+            plain.push_str(fraction);
+        }
+        plain
+    })
+}
+
+/// What a spreadsheet shows as a number, as SQL takes it: grouping spaces and
+/// commas dropped, a lone decimal comma (`3,5`) turned into a point
+fn plain_number(cell: &str) -> String {
+    let compact: String = cell
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '_' | '\u{a0}' | '\u{202f}'))
+        .collect();
+    if let Some(plain) = without_grouping(&compact) {
+        return plain;
+    }
+    if compact.matches(',').count() == 1 && !compact.contains('.') {
+        return compact.replace(',', ".");
+    }
+    compact
+}
+
+/// The value of one pasted cell for `column`, or why it does not fit. Empty
+/// cells and `NULL` are NULL, or the column default when it is NOT NULL.
+pub fn coerce(column: &ColumnSpec, cell: &str) -> Result<ColumnValue, String> {
+    let trimmed = cell.trim();
+    if trimmed.is_empty() || trimmed == "NULL" {
+        return if column.nullable {
+            Ok(ColumnValue::Null)
+        } else if column.default_value.is_some() {
+            Ok(ColumnValue::Default)
+        } else {
+            Err("empty, but the column is NOT NULL without a default".to_string())
+        };
+    }
+    if !column.enum_values.is_empty() {
+        return if column.enum_values.iter().any(|label| label == trimmed) {
+            Ok(ColumnValue::Value(trimmed.to_string()))
+        } else {
+            Err(format!(
+                "'{trimmed}' is not one of {}",
+                column.enum_values.join(", ")
+            ))
+        };
+    }
+    match column.kind {
+        ValueKind::Text => Ok(ColumnValue::Value(cell.to_string())),
+        ValueKind::Integer => {
+            let number = plain_number(trimmed);
+            match number.parse::<i128>() {
+                Ok(_) => Ok(ColumnValue::Value(number)),
+                Err(_) => Err(format!("expected an integer, got '{trimmed}'")),
+            }
+        }
+        ValueKind::Decimal => {
+            let number = plain_number(trimmed);
+            match number.parse::<f64>() {
+                Ok(_) => Ok(ColumnValue::Value(number)),
+                Err(_) => Err(format!("expected a number, got '{trimmed}'")),
+            }
+        }
+        ValueKind::Boolean => match parse_bool(trimmed) {
+            Some(value) => Ok(ColumnValue::Value(value.to_string())),
+            None => Err(format!("expected true/false, got '{trimmed}'")),
+        },
+    }
+}
+
+/// A cell that does not fit its column
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CellError {
+    /// Line of the pasted text, counting the header
+    pub line: usize,
+    pub column: String,
+    pub message: String,
+}
+
+impl fmt::Display for CellError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, {}: {}", self.line, self.column, self.message)
+    }
+}
+
+/// Pasted rows mapped onto table columns
+#[derive(Debug)]
+pub struct PastedRows {
+    pub delimiter: char,
+    pub header: bool,
+    /// The table columns the pasted columns go to, in pasted order
+    pub columns: Vec<ColumnSpec>,
+    pub rows: Vec<Vec<ColumnValue>>,
+    pub errors: Vec<CellError>,
+}
+
+/// Parse `text` and check every cell against `columns`, the table's columns
+pub fn prepare(text: &str, columns: &[ColumnSpec]) -> Result<PastedRows, String> {
+    let delimiter = detect_delimiter(text);
+    let mut lines = parse_delimited(text, delimiter);
+    let Some(first) = lines.first() else {
+        return Err("The clipboard holds no rows.".to_string());
+    };
+    let (mapping, header) = map_columns(first, columns)?;
+    let first_line = if header {
+        lines.remove(0);
+        2
+    } else {
+        1
+    };
+    if lines.is_empty() {
+        return Err("The clipboard holds a header but no rows.".to_string());
+    }
+
+    let mapped: Vec<ColumnSpec> = mapping.iter().map(|&i| columns[i].clone()).collect();
+    let mut rows = Vec::with_capacity(lines.len());
+    let mut errors = Vec::new();
+    for (offset, cells) in lines.iter().enumerate() {
+        let line = first_line + offset;
+        if cells.len() > mapped.len() {
+            errors.push(CellError {
+                line,
+                column: format!("cell {}", mapped.len() + 1),
+                message: format!("{} cells for {} columns", cells.len(), mapped.len()),
+            });
+            continue;
+        }
+        // Spreadsheets leave out trailing empty cells
+        let mut row = Vec::with_capacity(mapped.len());
+        for (index, column) in mapped.iter().enumerate() {
+            let cell = cells.get(index).map(String::as_str).unwrap_or_default();
+            match coerce(column, cell) {
+                Ok(value) => row.push(value),
+                Err(message) => errors.push(CellError {
+                    line,
+                    column: column.name.clone(),
+                    message,
+                }),
+            }
+        }
+        rows.push(row);
+    }
+    Ok(PastedRows {
+        delimiter,
+        header,
+        columns: mapped,
+        rows,
+        errors,
+    })
+}
+
+impl PastedRows {
+    /// `tab`, `comma`, ... for the summary line
+    pub fn delimiter_name(&self) -> &'static str {
+        match self.delimiter {
+            '\t' => "tab",
+            ',' => "comma",
+            ';' => "semicolon",
+            _ => "pipe",
+        }
+    }
+
+    /// Pasted column → table column and type, one per line
+    pub fn mapping(&self) -> String {
+        self.columns
+            .iter()
+            .enumerate()
+            .map(|(index, column)| {
+                format!("  {} → {} ({})", index + 1, column.name, column.data_type)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// The first rows as a table under the table's column names
+    pub fn preview(&self, limit: usize) -> Vec<Vec<String>> {
+        let header = self.columns.iter().map(|c| c.name.clone()).collect();
+        std::iter::once(header)
+            .chain(self.rows.iter().take(limit).map(|row| {
+                row.iter()
+                    .map(|value| match value {
+                        ColumnValue::Value(value) | ColumnValue::Expression(value) => value.clone(),
+                        ColumnValue::Null => "NULL".to_string(),
+                        ColumnValue::Default => "DEFAULT".to_string(),
+                    })
+                    .collect()
+            }))
+            .collect()
+    }
+
+    /// The errors to show, with a count of the rest
+    pub fn error_report(&self) -> String {
+        let mut lines: Vec<String> = self
+            .errors
+            .iter()
+            .take(MAX_REPORTED_ERRORS)
+            .map(|error| format!("  {error}"))
+            .collect();
+        if self.errors.len() > MAX_REPORTED_ERRORS {
+            lines.push(format!(
+                "  ... and {} more",
+                self.errors.len() - MAX_REPORTED_ERRORS
+            ));
+        }
+        format!(
+            "{} cell(s) do not fit their column; nothing inserted:\n{}",
+            self.errors.len(),
+            lines.join("\n")
+        )
+    }
+
+    /// The columns and CSV data for `COPY ... (FORMAT csv)`: columns left at
+    /// their default in every row are left out; `None` when a column is at
+    /// its default in only some rows, which COPY cannot express
+    pub fn copy_data(&self) -> Option<(Vec<usize>, Vec<u8>)> {
+        let mut copied = Vec::new();
+        for index in 0..self.columns.len() {
+            let defaults = self
+                .rows
+                .iter()
+                .filter(|row| row[index] == ColumnValue::Default)
+                .count();
+            if defaults == 0 {
+                copied.push(index);
+            } else if defaults < self.rows.len() {
+                return None;
+            }
+        }
+        let mut data = String::new();
+        for row in &self.rows {
+            let cells: Vec<String> = copied
+                .iter()
+                .map(|&index| match &row[index] {
+                    // An unquoted empty field is NULL, a quoted one ''
+                    ColumnValue::Null => String::new(),
+                    ColumnValue::Value(value) | ColumnValue::Expression(value) => {
+                        format!("\"{}\"", value.replace('"', "\"\""))
+                    }
+                    ColumnValue::Default => unreachable!("default columns are left out"),
+                })
+                .collect();
+            data.push_str(&cells.join(","));
+            data.push('\n');
+        }
+        Some((copied, data.into_bytes()))
+    }
+}
+
+/// Insert the rows: one COPY on PostgreSQL, batched INSERTs elsewhere (or
+/// when COPY cannot take them). Returns the rows inserted.
+pub async fn insert(db: &mut Database, table: &str, pasted: &PastedRows) -> Result<usize, String> {
+    let database_type = db.get_database_type();
+    if database_type == crate::database::DatabaseType::PostgreSQL
+        && let Some((copied, data)) = pasted.copy_data()
+    {
+        let columns: Vec<String> = copied
+            .iter()
+            .map(|&index| quote_identifier(&pasted.columns[index].name, &database_type))
+            .collect();
+        let table = crate::insert_builder::quote_table(table, &database_type);
+        return db
+            .copy_in_csv(&table, &columns, data)
+            .await
+            .map(|rows| rows as usize)
+            .map_err(|e| format!("COPY failed, nothing inserted: {e}"));
+    }
+    crate::datagen::insert_rows(db, table, &pasted.columns, &pasted.rows).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(name: &str, data_type: &str, nullable: bool, default: Option<&str>) -> ColumnSpec {
+        ColumnSpec {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            kind: ValueKind::from_type(data_type),
+            nullable,
+            default_value: default.map(str::to_string),
+            enum_values: Vec::new(),
+        }
+    }
+
+    fn columns() -> Vec<ColumnSpec> {
+        vec![
+            spec("id", "integer", false, Some("nextval('items_id_seq')")),
+            spec("name", "text", false, None),
+            spec("price", "numeric(8,2)", true, None),
+            spec("in_stock", "boolean", false, Some("true")),
+        ]
+    }
+
+    #[test]
+    fn test_parse_and_detect() {
+        let tsv = "name\tnote\nwidget\t\"two\tcells, \"\"quoted\"\"\nline\"\n\ngadget\t\n";
+        assert_eq!(detect_delimiter(tsv), '\t');
+        assert_eq!(
+            parse_delimited(tsv, '\t'),
+            [
+                vec!["name", "note"],
+                vec!["widget", "two\tcells, \"quoted\"\nline"],
+                vec!["gadget", ""],
+            ]
+        );
+        assert_eq!(detect_delimiter("a;b\r\n1;2,5\r\n"), ';');
+        assert_eq!(detect_delimiter("a,b\n1,\"2,5\"\n"), ',');
+        assert_eq!(detect_delimiter("one\ntwo"), '\t');
+        assert_eq!(parse_delimited("5\" pipe\tx", '\t')[0][0], "5\" pipe");
+    }
+
+    #[test]
+    fn test_map_columns() {
+        let header = ["Name".to_string(), "In Stock".to_string()];
+        assert_eq!(
+            map_columns(&header, &columns()).unwrap(),
+            (vec![1, 3], true)
+        );
+        let data = ["widget".to_string(), "9.5".to_string(), "yes".to_string()];
+        assert_eq!(
+            map_columns(&data, &columns()).unwrap(),
+            (vec![1, 2, 3], false)
+        );
+        assert!(
+            map_columns(&data[..2], &columns())
+                .unwrap_err()
+                .contains("header row")
+        );
+        let twice = ["name".to_string(), "NAME".to_string()];
+        assert!(
+            map_columns(&twice, &columns())
+                .unwrap_err()
+                .contains("twice")
+        );
+    }
+
+    #[test]
+    fn test_coerce() {
+        let columns = columns();
+        let value = |s: &str| ColumnValue::Value(s.to_string());
+        assert_eq!(coerce(&columns[0], "1,234"), Ok(value("1234")));
+        assert_eq!(coerce(&columns[2], "1 234,50"), Ok(value("1234.50")));
+        assert_eq!(coerce(&columns[2], "3,5"), Ok(value("3.5")));
+        assert_eq!(coerce(&columns[2], ""), Ok(ColumnValue::Null));
+        assert_eq!(coerce(&columns[3], "NULL"), Ok(ColumnValue::Default));
+        assert_eq!(coerce(&columns[3], "Yes"), Ok(value("true")));
+        assert_eq!(coerce(&columns[1], " padded "), Ok(value(" padded ")));
+        assert!(coerce(&columns[1], "").unwrap_err().contains("NOT NULL"));
+        assert_eq!(
+            coerce(&columns[2], "n/a").unwrap_err(),
+            "expected a number, got 'n/a'"
+        );
+        let mut status = spec("status", "status_type", false, None);
+        status.enum_values = vec!["open".to_string(), "closed".to_string()];
+        assert!(
+            coerce(&status, "pending")
+                .unwrap_err()
+                .contains("open, closed")
+        );
+    }
+
+    #[test]
+    fn test_prepare_reports_cells_and_copies() {
+        let pasted = prepare(
+            "name\tprice\tin_stock\nwidget\t9.99\tyes\ngadget\tn/a\tmaybe\n\t1\n",
+            &columns(),
+        )
+        .unwrap();
+        assert!(pasted.header);
+        assert_eq!(pasted.delimiter_name(), "tab");
+        assert_eq!(
+            pasted
+                .errors
+                .iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>(),
+            [
+                "line 3, price: expected a number, got 'n/a'",
+                "line 3, in_stock: expected true/false, got 'maybe'",
+                "line 4, name: empty, but the column is NOT NULL without a default",
+            ]
+        );
+
+        let pasted = prepare(
+            "name,price\n\"Big, red\",\n\"Say \"\"hi\"\"\",2\n",
+            &columns(),
+        )
+        .unwrap();
+        assert!(pasted.errors.is_empty());
+        let (copied, data) = pasted.copy_data().unwrap();
+        assert_eq!(copied, [0, 1]);
+        assert_eq!(
+            String::from_utf8(data).unwrap(),
+            "\"Big, red\",\n\"Say \"\"hi\"\"\",\"2\"\n"
+        );
+
+        let mixed = prepare("name\tin_stock\na\t\nb\tno\n", &columns()).unwrap();
+        assert!(mixed.copy_data().is_none());
+        assert!(prepare("name\n", &columns()).is_err());
+    }
+}
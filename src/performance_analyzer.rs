@@ -36,6 +36,17 @@ impl PerformanceLevel {
         }
     }
 
+    /// Fill color for this performance level in exported Graphviz plans
+    pub fn dot_color(&self) -> &'static str {
+        match self {
+            PerformanceLevel::Excellent => "#b7e4c7",
+            PerformanceLevel::Good => "#d8f3dc",
+            PerformanceLevel::Warning => "#fff3bf",
+            PerformanceLevel::Poor => "#ffd8a8",
+            PerformanceLevel::Critical => "#ffc9c9",
+        }
+    }
+
     /// Get the text description for this performance level
     pub fn description(&self) -> &'static str {
         match self {
@@ -149,6 +160,84 @@ impl PerformanceMetrics {
     }
 }
 
+/// Expected payoff of an index suggestion
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IndexBenefit {
+    Low,
+    Medium,
+    High,
+}
+
+impl IndexBenefit {
+    fn from_share(share: f64) -> Self {
+        if share >= 0.5 {
+            IndexBenefit::High
+        } else if share >= 0.2 {
+            IndexBenefit::Medium
+        } else {
+            IndexBenefit::Low
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            IndexBenefit::High => "High",
+            IndexBenefit::Medium => "Medium",
+            IndexBenefit::Low => "Low",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            IndexBenefit::High => Color::Green,
+            IndexBenefit::Medium => Color::Yellow,
+            IndexBenefit::Low => Color::DarkGray,
+        }
+    }
+}
+
+/// A concrete `CREATE INDEX` suggestion derived from a query plan
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexSuggestion {
+    /// Table to index (schema-qualified when the plan says so)
+    pub table: String,
+    /// Index columns, equality columns first
+    pub columns: Vec<String>,
+    /// Plan pattern that triggered the suggestion
+    pub reason: String,
+    pub benefit: IndexBenefit,
+    /// Share of the plan's cost (or time, for ANALYZE plans) spent in the
+    /// operations the index would replace
+    pub plan_share: f64,
+}
+
+impl IndexSuggestion {
+    /// DDL for the suggested index
+    pub fn create_statement(&self) -> String {
+        let column_names: Vec<String> = self
+            .columns
+            .iter()
+            .map(|c| c.trim_end_matches(" DESC").to_string())
+            .collect();
+        format!(
+            "CREATE INDEX idx_{}_{} ON {} ({});",
+            self.table.replace('.', "_"),
+            column_names.join("_"),
+            self.table,
+            self.columns.join(", ")
+        )
+    }
+
+    /// `High (~85% of plan cost)`
+    pub fn benefit_summary(&self) -> String {
+        format!(
+            "{} (~{:.0}% of plan cost)",
+            self.benefit.label(),
+            self.plan_share * 100.0
+        )
+    }
+}
+
 /// Performance analyzer for different database types
 pub struct PerformanceAnalyzer;
 
@@ -213,6 +302,7 @@ impl PerformanceAnalyzer {
 
             // Add recommendations based on operation type
             Self::add_postgresql_recommendations(&mut metric, node_obj);
+            Self::add_postgresql_extension_notes(&mut metric, node_obj);
 
             metrics.push(metric);
 
@@ -646,6 +736,103 @@ impl PerformanceAnalyzer {
         }
     }
 
+    /// Notes for nodes and conditions that come from extensions: TimescaleDB
+    /// custom scans, pgvector distance ordering and PostGIS predicates
+    fn add_postgresql_extension_notes(
+        metric: &mut PerformanceMetrics,
+        node_obj: &serde_json::Map<String, JsonValue>,
+    ) {
+        const VECTOR_DISTANCE: [&str; 4] = ["<->", "<=>", "<#>", "<+>"];
+        const SPATIAL_PREDICATES: [&str; 5] = [
+            "st_dwithin",
+            "st_intersects",
+            "st_contains",
+            "st_within",
+            " && ",
+        ];
+
+        let text = |key: &str| node_obj.get(key).and_then(|v| v.as_str()).unwrap_or("");
+        let keys = |key: &str| -> Vec<String> {
+            node_obj
+                .get(key)
+                .and_then(|v| v.as_array())
+                .map(|keys| {
+                    keys.iter()
+                        .filter_map(|k| k.as_str().map(str::to_string))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        match metric.operation_type.as_str() {
+            "Custom Scan" => match text("Custom Plan Provider") {
+                "ChunkAppend" | "ConstraintAwareAppend" => {
+                    metric.sql_fragment = Some(
+                        "TimescaleDB chunk append: chunks outside the time filter are skipped"
+                            .to_string(),
+                    );
+                    if node_obj
+                        .get("Plans")
+                        .and_then(|v| v.as_array())
+                        .map(Vec::len)
+                        > Some(50)
+                    {
+                        metric.add_warning("Many chunks scanned".to_string());
+                        metric.add_recommendation(
+                            "Filter on the hypertable's time column with constants or now() so chunks are excluded"
+                                .to_string(),
+                        );
+                    }
+                }
+                "DecompressChunk" => {
+                    metric.sql_fragment =
+                        Some("TimescaleDB: reading a compressed chunk".to_string());
+                    metric.add_recommendation(
+                        "Filter on the compression segmentby columns to decompress fewer batches"
+                            .to_string(),
+                    );
+                }
+                _ => {}
+            },
+            "Index Scan" | "Index Only Scan" => {
+                let order_by = text("Order By");
+                if VECTOR_DISTANCE.iter().any(|op| order_by.contains(op)) {
+                    metric.sql_fragment =
+                        Some("pgvector approximate nearest-neighbour index scan".to_string());
+                    metric.add_recommendation(
+                        "Results are approximate: raise hnsw.ef_search (or ivfflat.probes) for better recall"
+                            .to_string(),
+                    );
+                }
+            }
+            "Sort" | "Incremental Sort"
+                if keys("Sort Key")
+                    .iter()
+                    .any(|key| VECTOR_DISTANCE.iter().any(|op| key.contains(op))) =>
+            {
+                metric.add_warning(
+                    "Exact vector search: every row's distance is computed".to_string(),
+                );
+                metric.add_recommendation(
+                    "Add an HNSW index on the vector column with the opclass of the operator: USING hnsw (column vector_l2_ops) for <->, vector_cosine_ops for <=>, vector_ip_ops for <#>"
+                        .to_string(),
+                );
+            }
+            "Seq Scan" => {
+                let filter = text("Filter").to_lowercase();
+                if SPATIAL_PREDICATES.iter().any(|p| filter.contains(p)) {
+                    metric.add_recommendation(match &metric.table_name {
+                        Some(table) => format!(
+                            "Spatial filter without an index: CREATE INDEX ON {table} USING gist (geom);"
+                        ),
+                        None => "Spatial filter without an index: add a GiST index on the geometry column".to_string(),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
     /// Analyze MySQL EXPLAIN JSON output
     pub fn analyze_mysql_plan(plan_json: &JsonValue) -> Vec<PerformanceMetrics> {
         let mut metrics = Vec::new();
@@ -1353,6 +1540,368 @@ impl PerformanceAnalyzer {
 
         recommendations
     }
+
+    /// Suggest indexes for a PostgreSQL EXPLAIN (FORMAT JSON) plan
+    ///
+    /// Looks for sequential scans with selective filters, nested loops that
+    /// rescan a large inner table once per outer row, and sorts that spill to
+    /// disk (or dominate the plan) on top of a single table scan.
+    pub fn suggest_postgresql_indexes(plan_json: &JsonValue) -> Vec<IndexSuggestion> {
+        let Some(root) = plan_json
+            .as_array()
+            .and_then(|plans| plans.first())
+            .and_then(|plan| plan.get("Plan"))
+        else {
+            return Vec::new();
+        };
+
+        let totals = PlanTotals {
+            cost: number(root, "Total Cost").unwrap_or(0.0),
+            time: node_time(root),
+        };
+        let mut suggestions: Vec<IndexSuggestion> = Vec::new();
+        Self::collect_postgresql_index_suggestions(root, &totals, &mut suggestions);
+
+        // Keep the strongest suggestion per (table, columns)
+        suggestions.sort_by(|a, b| b.plan_share.total_cmp(&a.plan_share));
+        let mut seen = std::collections::HashSet::new();
+        suggestions.retain(|s| seen.insert((s.table.clone(), s.columns.clone())));
+        suggestions
+    }
+
+    fn collect_postgresql_index_suggestions(
+        node: &JsonValue,
+        totals: &PlanTotals,
+        suggestions: &mut Vec<IndexSuggestion>,
+    ) {
+        let node_type = node.get("Node Type").and_then(|v| v.as_str()).unwrap_or("");
+        let children: Vec<&JsonValue> = node
+            .get("Plans")
+            .and_then(|v| v.as_array())
+            .map(|plans| plans.iter().collect())
+            .unwrap_or_default();
+
+        match node_type {
+            "Seq Scan" => {
+                if let Some(suggestion) = Self::suggest_for_selective_scan(node, totals) {
+                    suggestions.push(suggestion);
+                }
+            }
+            "Nested Loop" => {
+                if let Some(suggestion) = Self::suggest_for_nested_loop(node, &children, totals) {
+                    suggestions.push(suggestion);
+                }
+            }
+            "Sort" | "Incremental Sort" => {
+                if let Some(suggestion) = Self::suggest_for_sort(node, &children, totals) {
+                    suggestions.push(suggestion);
+                }
+            }
+            _ => {}
+        }
+
+        for child in children {
+            Self::collect_postgresql_index_suggestions(child, totals, suggestions);
+        }
+    }
+
+    /// Seq Scan whose filter keeps only a small fraction of the table
+    fn suggest_for_selective_scan(
+        node: &JsonValue,
+        totals: &PlanTotals,
+    ) -> Option<IndexSuggestion> {
+        let filter = node.get("Filter").and_then(|v| v.as_str())?;
+        if filter.contains(" OR ") {
+            return None; // a single b-tree index rarely helps OR filters
+        }
+        let table = relation_name(node)?;
+        let columns = filter_columns(filter);
+        if columns.is_empty() {
+            return None;
+        }
+
+        let cost = number(node, "Total Cost").unwrap_or(0.0);
+        let reason = match (
+            node.get("Actual Rows").and_then(|v| v.as_u64()),
+            node.get("Rows Removed by Filter").and_then(|v| v.as_u64()),
+        ) {
+            (Some(kept), Some(removed)) => {
+                let examined = kept + removed;
+                if examined < 1000 || kept * 10 > examined {
+                    return None;
+                }
+                format!(
+                    "Seq Scan keeps {kept} of {examined} rows ({:.1}%)",
+                    kept as f64 / examined as f64 * 100.0
+                )
+            }
+            _ => {
+                // Without ANALYZE, a full scan emits roughly 40 rows per cost
+                // unit; fewer than 10 rows per unit means a selective filter.
+                let estimated = number(node, "Plan Rows").unwrap_or(0.0);
+                if cost < 100.0 || estimated * 4.0 > cost {
+                    return None;
+                }
+                format!("Seq Scan with selective filter (~{estimated:.0} rows expected)")
+            }
+        };
+
+        let share = totals.share(node_time(node), cost);
+        Some(IndexSuggestion {
+            table,
+            columns: columns.into_iter().map(|(column, _)| column).collect(),
+            reason: format!("{reason}: {filter}"),
+            benefit: IndexBenefit::from_share(share),
+            plan_share: share,
+        })
+    }
+
+    /// Nested Loop over a large outer input that scans the inner table every loop
+    fn suggest_for_nested_loop(
+        node: &JsonValue,
+        children: &[&JsonValue],
+        totals: &PlanTotals,
+    ) -> Option<IndexSuggestion> {
+        let outer = children
+            .iter()
+            .find(|c| c.get("Parent Relationship").and_then(|v| v.as_str()) == Some("Outer"))?;
+        let outer_rows = outer
+            .get("Actual Rows")
+            .or_else(|| outer.get("Plan Rows"))
+            .and_then(|v| v.as_f64())
+            .unwrap_or(0.0);
+        if outer_rows < 1000.0 {
+            return None;
+        }
+
+        // Look through Materialize to the scan that runs per outer row
+        let mut inner = *children
+            .iter()
+            .find(|c| c.get("Parent Relationship").and_then(|v| v.as_str()) == Some("Inner"))?;
+        while inner.get("Node Type").and_then(|v| v.as_str()) == Some("Materialize") {
+            inner = inner.get("Plans")?.as_array()?.first()?;
+        }
+        if inner.get("Node Type").and_then(|v| v.as_str()) != Some("Seq Scan") {
+            return None;
+        }
+
+        let inner_alias = inner
+            .get("Alias")
+            .or_else(|| inner.get("Relation Name"))
+            .and_then(|v| v.as_str())?;
+        let condition = node
+            .get("Join Filter")
+            .or_else(|| inner.get("Filter"))
+            .and_then(|v| v.as_str())?;
+        let column = join_column_for_alias(condition, inner_alias)?;
+        let table = relation_name(inner)?;
+
+        let outer_cost = number(outer, "Total Cost").unwrap_or(0.0);
+        let loop_cost = (number(node, "Total Cost").unwrap_or(0.0) - outer_cost).max(0.0);
+        let share = totals.share(None, loop_cost);
+        Some(IndexSuggestion {
+            table,
+            columns: vec![column],
+            reason: format!(
+                "Nested Loop scans {inner_alias} once for each of ~{outer_rows:.0} outer rows: {condition}"
+            ),
+            benefit: IndexBenefit::from_share(share),
+            plan_share: share,
+        })
+    }
+
+    /// Sort that spills to disk (or dominates the plan) above a single table
+    fn suggest_for_sort(
+        node: &JsonValue,
+        children: &[&JsonValue],
+        totals: &PlanTotals,
+    ) -> Option<IndexSuggestion> {
+        let spilled = node
+            .get("Sort Method")
+            .and_then(|v| v.as_str())
+            .is_some_and(|m| m.contains("external"))
+            || node.get("Sort Space Type").and_then(|v| v.as_str()) == Some("Disk");
+
+        let input = children.first()?;
+        let input_cost = number(input, "Total Cost").unwrap_or(0.0);
+        let sort_cost = (number(node, "Total Cost").unwrap_or(0.0) - input_cost).max(0.0);
+        let share = totals.share(None, sort_cost);
+        let rows = number(node, "Plan Rows").unwrap_or(0.0);
+        if !spilled && (share < 0.3 || rows < 10000.0) {
+            return None;
+        }
+
+        // Only a plain single-table input can be replaced by an ordered index scan
+        if !matches!(
+            input.get("Node Type").and_then(|v| v.as_str()),
+            Some("Seq Scan" | "Index Scan" | "Bitmap Heap Scan")
+        ) {
+            return None;
+        }
+        let table = relation_name(input)?;
+
+        let mut columns: Vec<String> = input
+            .get("Filter")
+            .and_then(|v| v.as_str())
+            .map(|filter| {
+                filter_columns(filter)
+                    .into_iter()
+                    .filter(|(_, equality)| *equality)
+                    .map(|(column, _)| column)
+                    .collect()
+            })
+            .unwrap_or_default();
+        for key in node.get("Sort Key")?.as_array()? {
+            let key = key.as_str()?;
+            if key.contains('(') {
+                return None; // expression sort keys need an expression index
+            }
+            let (column, descending) = match key.strip_suffix(" DESC") {
+                Some(column) => (column, true),
+                None => (key, false),
+            };
+            let column = column.rsplit('.').next().unwrap_or(column).to_string();
+            let column = if descending {
+                format!("{column} DESC")
+            } else {
+                column
+            };
+            if !columns.contains(&column) {
+                columns.push(column);
+            }
+        }
+
+        Some(IndexSuggestion {
+            table,
+            columns,
+            reason: if spilled {
+                "Sort spilled to disk".to_string()
+            } else {
+                format!("Sort of ~{rows:.0} rows")
+            },
+            benefit: IndexBenefit::from_share(share.max(if spilled { 0.2 } else { 0.0 })),
+            plan_share: share,
+        })
+    }
+
+    /// Format index suggestions for text EXPLAIN output
+    pub fn format_index_suggestions(suggestions: &[IndexSuggestion]) -> Vec<String> {
+        if suggestions.is_empty() {
+            return Vec::new();
+        }
+        let mut lines = vec![
+            format!("🧭 {}", Color::White.bold().paint("Index Suggestions:")),
+            String::new(),
+        ];
+        for suggestion in suggestions {
+            lines.push(format!(
+                "  {}",
+                Color::Cyan.paint(suggestion.create_statement())
+            ));
+            lines.push(format!(
+                "    Benefit: {}",
+                suggestion
+                    .benefit
+                    .color()
+                    .paint(suggestion.benefit_summary())
+            ));
+            lines.push(format!(
+                "    Why: {}",
+                Color::DarkGray.paint(&suggestion.reason)
+            ));
+        }
+        lines
+    }
+}
+
+/// Root totals used to weigh each suggestion
+struct PlanTotals {
+    cost: f64,
+    time: Option<f64>,
+}
+
+impl PlanTotals {
+    /// Fraction of the plan spent in a node, by time when ANALYZE data exists
+    fn share(&self, node_time: Option<f64>, node_cost: f64) -> f64 {
+        let share = match (node_time, self.time) {
+            (Some(time), Some(total)) if total > 0.0 => time / total,
+            _ if self.cost > 0.0 => node_cost / self.cost,
+            _ => 0.0,
+        };
+        share.clamp(0.0, 1.0)
+    }
+}
+
+fn number(node: &JsonValue, key: &str) -> Option<f64> {
+    node.get(key).and_then(|v| v.as_f64())
+}
+
+/// Total time spent in a node across all loops (ANALYZE plans only)
+fn node_time(node: &JsonValue) -> Option<f64> {
+    let time = number(node, "Actual Total Time")?;
+    Some(time * number(node, "Actual Loops").unwrap_or(1.0))
+}
+
+fn relation_name(node: &JsonValue) -> Option<String> {
+    let relation = node.get("Relation Name").and_then(|v| v.as_str())?;
+    Some(match node.get("Schema").and_then(|v| v.as_str()) {
+        Some(schema) => format!("{schema}.{relation}"),
+        None => relation.to_string(),
+    })
+}
+
+/// Columns compared in a plan filter, equality comparisons first
+///
+/// `((status)::text = 'open'::text) AND (created_at > '2024-01-01'::date)`
+/// → `[("status", true), ("created_at", false)]`
+fn filter_columns(filter: &str) -> Vec<(String, bool)> {
+    let pattern = regex::Regex::new(
+        r"\(*(?:[A-Za-z_][A-Za-z0-9_]*\.)?([A-Za-z_][A-Za-z0-9_]*)\)*(?:::[A-Za-z_ ]+?)?\s+(=|<>|<=|>=|<|>|~~\*?|IS)\s",
+    )
+    .unwrap();
+    let mut equality = Vec::new();
+    let mut range = Vec::new();
+    for cap in pattern.captures_iter(filter) {
+        let column = cap[1].to_string();
+        // Skip the right-hand side of a comparison (`'x'::text`, `$1`)
+        let start = cap.get(0).unwrap().start();
+        if filter[..start].trim_end().ends_with(['\'', ':']) {
+            continue;
+        }
+        match &cap[2] {
+            "=" => equality.push(column),
+            "<>" => {} // inequality can't use an index effectively
+            _ => range.push(column),
+        }
+    }
+    let mut columns: Vec<(String, bool)> = Vec::new();
+    for (column, is_equality) in equality
+        .into_iter()
+        .map(|c| (c, true))
+        .chain(range.into_iter().map(|c| (c, false)))
+    {
+        if !columns.iter().any(|(c, _)| *c == column) {
+            columns.push((column, is_equality));
+        }
+    }
+    columns
+}
+
+/// The column of `alias` in an equi-join condition like `(o.customer_id = c.id)`
+fn join_column_for_alias(condition: &str, alias: &str) -> Option<String> {
+    let pattern = regex::Regex::new(
+        r"([A-Za-z_][A-Za-z0-9_]*)\.([A-Za-z_][A-Za-z0-9_]*)\s*=\s*([A-Za-z_][A-Za-z0-9_]*)\.([A-Za-z_][A-Za-z0-9_]*)",
+    )
+    .unwrap();
+    pattern.captures_iter(condition).find_map(|cap| {
+        if &cap[1] == alias {
+            Some(cap[2].to_string())
+        } else if &cap[3] == alias {
+            Some(cap[4].to_string())
+        } else {
+            None
+        }
+    })
 }
 
 #[cfg(test)]
@@ -1632,4 +2181,160 @@ mod tests {
         assert!(formatted.iter().any(|line| line.contains("Rows Returned:")));
         assert!(formatted.iter().any(|line| line.contains("Efficiency:")));
     }
+
+    #[test]
+    fn test_filter_columns_orders_equality_first() {
+        assert_eq!(
+            filter_columns(
+                "((created_at > '2024-01-01'::date) AND ((status)::text = 'open'::text))"
+            ),
+            vec![
+                ("status".to_string(), true),
+                ("created_at".to_string(), false)
+            ]
+        );
+        assert!(filter_columns("(status <> 'closed'::text)").is_empty());
+    }
+
+    #[test]
+    fn test_suggest_index_for_selective_seq_scan() {
+        let plan = serde_json::json!([{
+            "Plan": {
+                "Node Type": "Seq Scan",
+                "Relation Name": "orders",
+                "Schema": "public",
+                "Alias": "orders",
+                "Total Cost": 1943.0,
+                "Plan Rows": 12,
+                "Filter": "(customer_id = 42)"
+            }
+        }]);
+        let suggestions = PerformanceAnalyzer::suggest_postgresql_indexes(&plan);
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(
+            suggestions[0].create_statement(),
+            "CREATE INDEX idx_public_orders_customer_id ON public.orders (customer_id);"
+        );
+        assert_eq!(suggestions[0].benefit, IndexBenefit::High);
+
+        // Non-selective scans don't get a suggestion
+        let plan = serde_json::json!([{
+            "Plan": {
+                "Node Type": "Seq Scan",
+                "Relation Name": "orders",
+                "Total Cost": 1943.0,
+                "Plan Rows": 90000,
+                "Filter": "(customer_id > 42)"
+            }
+        }]);
+        assert!(PerformanceAnalyzer::suggest_postgresql_indexes(&plan).is_empty());
+    }
+
+    #[test]
+    fn test_extension_plan_notes() {
+        let plan = serde_json::json!([{
+            "Plan": {
+                "Node Type": "Limit",
+                "Plans": [{
+                    "Node Type": "Sort",
+                    "Sort Key": ["((embedding <=> '[1,2,3]'::vector))"],
+                    "Plans": [{
+                        "Node Type": "Custom Scan",
+                        "Custom Plan Provider": "ChunkAppend",
+                        "Relation Name": "readings"
+                    }]
+                }]
+            }
+        }]);
+        let metrics = PerformanceAnalyzer::analyze_postgresql_plan(&plan);
+        assert!(
+            metrics[1]
+                .warnings
+                .contains(&"Exact vector search: every row's distance is computed".to_string())
+        );
+        assert!(metrics[1].recommendations[0].contains("vector_cosine_ops"));
+        assert!(
+            metrics[2]
+                .sql_fragment
+                .as_deref()
+                .unwrap()
+                .starts_with("TimescaleDB chunk append")
+        );
+    }
+
+    #[test]
+    fn test_suggest_index_for_nested_loop_inner_scan() {
+        let plan = serde_json::json!([{
+            "Plan": {
+                "Node Type": "Nested Loop",
+                "Total Cost": 250000.0,
+                "Plan Rows": 5000,
+                "Join Filter": "(o.customer_id = c.id)",
+                "Plans": [
+                    {
+                        "Node Type": "Seq Scan",
+                        "Parent Relationship": "Outer",
+                        "Relation Name": "customers",
+                        "Alias": "c",
+                        "Total Cost": 100.0,
+                        "Plan Rows": 5000
+                    },
+                    {
+                        "Node Type": "Materialize",
+                        "Parent Relationship": "Inner",
+                        "Total Cost": 300.0,
+                        "Plan Rows": 10000,
+                        "Plans": [{
+                            "Node Type": "Seq Scan",
+                            "Parent Relationship": "Outer",
+                            "Relation Name": "orders",
+                            "Alias": "o",
+                            "Total Cost": 200.0,
+                            "Plan Rows": 10000
+                        }]
+                    }
+                ]
+            }
+        }]);
+        let suggestions = PerformanceAnalyzer::suggest_postgresql_indexes(&plan);
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].table, "orders");
+        assert_eq!(suggestions[0].columns, vec!["customer_id".to_string()]);
+        assert_eq!(suggestions[0].benefit, IndexBenefit::High);
+    }
+
+    #[test]
+    fn test_suggest_index_for_spilled_sort() {
+        let plan = serde_json::json!([{
+            "Plan": {
+                "Node Type": "Sort",
+                "Total Cost": 1200.0,
+                "Plan Rows": 5000,
+                "Actual Total Time": 80.0,
+                "Actual Loops": 1,
+                "Sort Key": ["e.created_at DESC"],
+                "Sort Method": "external merge",
+                "Sort Space Type": "Disk",
+                "Plans": [{
+                    "Node Type": "Seq Scan",
+                    "Relation Name": "events",
+                    "Alias": "e",
+                    "Total Cost": 1000.0,
+                    "Plan Rows": 5000,
+                    "Actual Total Time": 20.0,
+                    "Actual Loops": 1,
+                    "Actual Rows": 5000,
+                    "Rows Removed by Filter": 1000,
+                    "Filter": "(kind = 'click'::text)"
+                }]
+            }
+        }]);
+        let suggestions = PerformanceAnalyzer::suggest_postgresql_indexes(&plan);
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(
+            suggestions[0].create_statement(),
+            "CREATE INDEX idx_events_kind_created_at ON events (kind, created_at DESC);"
+        );
+        assert_eq!(suggestions[0].reason, "Sort spilled to disk");
+    }
 }
@@ -0,0 +1,299 @@
+//! Client-side cross-tab of the last result (`\pivot`)
+//!
+//! One column's values become the rows, another's the columns, and a third
+//! fills the cells, like psql's `\crosstabview`: a
+//! `SELECT day, status, count(*) ... GROUP BY 1, 2` turns into one row per
+//! day with a column per status. Rows and columns keep the order they first
+//! appear in, so the query's ORDER BY decides it. Two values for one cell are
+//! an error unless an aggregate (`sum`, `count`, `min`, `max`, `avg`)
+//! combines them; aggregates skip NULLs like SQL does.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// How the values landing in one cell are combined
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    Sum,
+    Count,
+    Min,
+    Max,
+    Avg,
+}
+
+impl FromStr for Aggregate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "sum" => Ok(Aggregate::Sum),
+            "count" => Ok(Aggregate::Count),
+            "min" => Ok(Aggregate::Min),
+            "max" => Ok(Aggregate::Max),
+            "avg" => Ok(Aggregate::Avg),
+            _ => Err(format!(
+                "unknown aggregate '{s}' (use sum, count, min, max or avg)"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Aggregate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Aggregate::Sum => "sum",
+            Aggregate::Count => "count",
+            Aggregate::Min => "min",
+            Aggregate::Max => "max",
+            Aggregate::Avg => "avg",
+        })
+    }
+}
+
+/// Arguments of `\pivot`; columns are names or 1-based positions
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PivotSpec {
+    /// `None` for all three: the columns of a three-column result, in order
+    pub row_key: Option<String>,
+    pub column_key: Option<String>,
+    /// `None`: the column left over in a three-column result
+    pub value: Option<String>,
+    pub aggregate: Option<Aggregate>,
+}
+
+/// Index of `column` (name, case-insensitive when not exact, or position)
+pub(crate) fn resolve(header: &[String], column: &str) -> Result<usize, String> {
+    if let Some(index) = header.iter().position(|name| name == column) {
+        return Ok(index);
+    }
+    if let Some(index) = header
+        .iter()
+        .position(|name| name.eq_ignore_ascii_case(column))
+    {
+        return Ok(index);
+    }
+    match column.parse::<usize>() {
+        Ok(position) if (1..=header.len()).contains(&position) => Ok(position - 1),
+        _ => Err(format!(
+            "No column '{column}' in the last result (columns: {})",
+            header.join(", ")
+        )),
+    }
+}
+
+/// Position of `key` in `keys`, added at the end when new
+fn slot(keys: &mut Vec<String>, index: &mut HashMap<String, usize>, key: &str) -> usize {
+    *index.entry(key.to_string()).or_insert_with(|| {
+        keys.push(key.to_string());
+        keys.len() - 1
+    })
+}
+
+/// `42` for whole numbers, otherwise up to six decimals without trailing zeros
+fn format_number(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{value:.0}")
+    } else {
+        let text = format!("{value:.6}");
+        text.trim_end_matches('0').trim_end_matches('.').to_string()
+    }
+}
+
+fn combine(aggregate: Aggregate, values: &[&str], value_name: &str) -> Result<String, String> {
+    let present: Vec<&str> = values.iter().copied().filter(|v| *v != "NULL").collect();
+    if aggregate == Aggregate::Count {
+        return Ok(present.len().to_string());
+    }
+    if present.is_empty() {
+        return Ok("NULL".to_string());
+    }
+    let numbers: Result<Vec<f64>, String> = present
+        .iter()
+        .map(|v| {
+            v.trim()
+                .parse::<f64>()
+                .map_err(|_| format!("{aggregate} needs numbers, but {value_name} has '{v}'"))
+        })
+        .collect();
+    let numbers = numbers?;
+    let result = match aggregate {
+        Aggregate::Sum => numbers.iter().sum(),
+        Aggregate::Avg => numbers.iter().sum::<f64>() / numbers.len() as f64,
+        Aggregate::Min => numbers.iter().copied().fold(f64::INFINITY, f64::min),
+        Aggregate::Max => numbers.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        Aggregate::Count => unreachable!("handled above"),
+    };
+    Ok(format_number(result))
+}
+
+/// The cross-tab of `data` (header first): the row key's values down the
+/// first column, a column per column key value
+pub fn pivot(data: &[Vec<String>], spec: &PivotSpec) -> Result<Vec<Vec<String>>, String> {
+    // Masked while the column names are still in the header
+    let masked = crate::masking::masked(data);
+    let data = masked.as_deref().unwrap_or(data);
+    let Some((header, rows)) = data.split_first() else {
+        return Err("No result to pivot yet. Run a query that returns rows first.".to_string());
+    };
+
+    let (row_index, column_index) = match (&spec.row_key, &spec.column_key) {
+        (Some(row), Some(column)) => (resolve(header, row)?, resolve(header, column)?),
+        _ if header.len() == 3 => (0, 1),
+        _ => {
+            return Err(format!(
+                "The last result has {} columns; name them: \\pivot <row_key> <column_key> <value>",
+                header.len()
+            ));
+        }
+    };
+    if row_index == column_index {
+        return Err("The row key and the column key must be different columns".to_string());
+    }
+    let value_index = match &spec.value {
+        Some(value) => resolve(header, value)?,
+        None => match (0..header.len())
+            .filter(|i| *i != row_index && *i != column_index)
+            .collect::<Vec<_>>()
+            .as_slice()
+        {
+            [only] => *only,
+            _ => {
+                return Err(
+                    "Name the value column: \\pivot <row_key> <column_key> <value>".to_string(),
+                );
+            }
+        },
+    };
+
+    let cell = |row: &Vec<String>, index: usize| row.get(index).cloned().unwrap_or_default();
+    let (mut row_keys, mut row_slots) = (Vec::new(), HashMap::new());
+    let (mut column_keys, mut column_slots) = (Vec::new(), HashMap::new());
+    let mut cells: HashMap<(usize, usize), Vec<&str>> = HashMap::new();
+    for row in rows {
+        let r = slot(&mut row_keys, &mut row_slots, &cell(row, row_index));
+        let c = slot(
+            &mut column_keys,
+            &mut column_slots,
+            &cell(row, column_index),
+        );
+        cells
+            .entry((r, c))
+            .or_default()
+            .push(row.get(value_index).map(String::as_str).unwrap_or_default());
+    }
+
+    let value_name = &header[value_index];
+    let mut pivoted = vec![
+        std::iter::once(header[row_index].clone())
+            .chain(column_keys.iter().cloned())
+            .collect::<Vec<_>>(),
+    ];
+    for (r, row_key) in row_keys.iter().enumerate() {
+        let mut line = vec![row_key.clone()];
+        for (c, column_key) in column_keys.iter().enumerate() {
+            line.push(match (cells.get(&(r, c)), spec.aggregate) {
+                (None, Some(Aggregate::Count)) => "0".to_string(),
+                (None, _) => String::new(),
+                (Some(values), Some(aggregate)) => combine(aggregate, values, value_name)?,
+                (Some(values), None) => match values.as_slice() {
+                    [value] => value.to_string(),
+                    _ => {
+                        return Err(format!(
+                            "{} values of {value_name} for {row_key} × {column_key}; \
+                             add sum, count, min, max or avg to combine them",
+                            values.len()
+                        ));
+                    }
+                },
+            });
+        }
+        pivoted.push(line);
+    }
+    Ok(pivoted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(rows: &[&[&str]]) -> Vec<Vec<String>> {
+        rows.iter()
+            .map(|row| row.iter().map(|v| v.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_pivot_three_columns() {
+        let data = table(&[
+            &["day", "status", "count"],
+            &["2024-05-01", "paid", "12"],
+            &["2024-05-01", "failed", "3"],
+            &["2024-05-02", "paid", "9"],
+            &["2024-05-02", "refunded", "1"],
+        ]);
+        assert_eq!(
+            pivot(&data, &PivotSpec::default()).unwrap(),
+            table(&[
+                &["day", "paid", "failed", "refunded"],
+                &["2024-05-01", "12", "3", ""],
+                &["2024-05-02", "9", "", "1"],
+            ])
+        );
+        let by_status = PivotSpec {
+            row_key: Some("Status".to_string()),
+            column_key: Some("1".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(pivot(&data, &by_status).unwrap()[1], ["paid", "12", "9"]);
+    }
+
+    #[test]
+    fn test_pivot_duplicates_and_aggregates() {
+        let data = table(&[
+            &["region", "product", "amount", "note"],
+            &["eu", "book", "10", "a"],
+            &["eu", "book", "5.5", "b"],
+            &["us", "pen", "NULL", "c"],
+        ]);
+        let mut spec = PivotSpec {
+            row_key: Some("region".to_string()),
+            column_key: Some("product".to_string()),
+            value: Some("amount".to_string()),
+            aggregate: None,
+        };
+        assert!(
+            pivot(&data, &spec)
+                .unwrap_err()
+                .contains("2 values of amount")
+        );
+
+        spec.aggregate = Some(Aggregate::Sum);
+        assert_eq!(
+            pivot(&data, &spec).unwrap(),
+            table(&[
+                &["region", "book", "pen"],
+                &["eu", "15.5", ""],
+                &["us", "", "NULL"],
+            ])
+        );
+        spec.aggregate = Some(Aggregate::Count);
+        assert_eq!(pivot(&data, &spec).unwrap()[2], ["us", "0", "0"]);
+
+        spec.value = Some("note".to_string());
+        spec.aggregate = Some(Aggregate::Avg);
+        assert!(
+            pivot(&data, &spec)
+                .unwrap_err()
+                .contains("avg needs numbers")
+        );
+
+        let unnamed = PivotSpec::default();
+        assert!(
+            pivot(&data, &unnamed)
+                .unwrap_err()
+                .contains("has 4 columns")
+        );
+        assert!("median".parse::<Aggregate>().is_err());
+    }
+}
@@ -0,0 +1,642 @@
+//! Pre-execution lint pass for SQL statements (`[lint]`, `\lint`)
+//!
+//! Flags common anti-patterns before a statement reaches the server:
+//! `SELECT *` on large tables, UPDATE/DELETE without WHERE, implicit cross
+//! joins, non-sargable predicates and LIKE patterns with a leading wildcard.
+//! Statements are tokenized with the dialect parser of the connected database.
+//! Each rule's severity comes from the `[lint]` config section; `error`
+//! findings stop the statement from running.
+
+use crate::database::DatabaseType;
+use crate::db::Database;
+use crate::sql_parser::{Token, TokenType};
+use crate::sql_parser_trait::SqlParserFactory;
+use nu_ansi_term::Color;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// How a lint rule is reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintSeverity {
+    Off,
+    Info,
+    Warning,
+    Error,
+}
+
+impl LintSeverity {
+    fn color(&self) -> Color {
+        match self {
+            LintSeverity::Off | LintSeverity::Info => Color::Cyan,
+            LintSeverity::Warning => Color::Yellow,
+            LintSeverity::Error => Color::Red,
+        }
+    }
+}
+
+impl fmt::Display for LintSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LintSeverity::Off => write!(f, "off"),
+            LintSeverity::Info => write!(f, "info"),
+            LintSeverity::Warning => write!(f, "warning"),
+            LintSeverity::Error => write!(f, "error"),
+        }
+    }
+}
+
+impl FromStr for LintSeverity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "off" | "none" => Ok(LintSeverity::Off),
+            "info" => Ok(LintSeverity::Info),
+            "warning" | "warn" => Ok(LintSeverity::Warning),
+            "error" => Ok(LintSeverity::Error),
+            _ => Err(format!(
+                "Invalid lint severity: '{s}'. Valid values: off, info, warning, error"
+            )),
+        }
+    }
+}
+
+/// Anti-patterns the lint pass knows about
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintRule {
+    SelectStar,
+    MissingWhere,
+    ImplicitCrossJoin,
+    NonSargable,
+    LeadingWildcard,
+}
+
+impl fmt::Display for LintRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            LintRule::SelectStar => "select_star",
+            LintRule::MissingWhere => "missing_where",
+            LintRule::ImplicitCrossJoin => "implicit_cross_join",
+            LintRule::NonSargable => "non_sargable",
+            LintRule::LeadingWildcard => "leading_wildcard",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// `[lint]` configuration section
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LintConfig {
+    /// Lint every statement before it runs (`\lint` works either way)
+    pub enabled: bool,
+    /// Estimated row count from which `SELECT *` is reported
+    pub large_table_rows: u64,
+    pub select_star: LintSeverity,
+    pub missing_where: LintSeverity,
+    pub implicit_cross_join: LintSeverity,
+    pub non_sargable: LintSeverity,
+    pub leading_wildcard: LintSeverity,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            large_table_rows: 100_000,
+            select_star: LintSeverity::Warning,
+            missing_where: LintSeverity::Error,
+            implicit_cross_join: LintSeverity::Warning,
+            non_sargable: LintSeverity::Info,
+            leading_wildcard: LintSeverity::Info,
+        }
+    }
+}
+
+impl LintConfig {
+    pub fn severity(&self, rule: LintRule) -> LintSeverity {
+        match rule {
+            LintRule::SelectStar => self.select_star,
+            LintRule::MissingWhere => self.missing_where,
+            LintRule::ImplicitCrossJoin => self.implicit_cross_join,
+            LintRule::NonSargable => self.non_sargable,
+            LintRule::LeadingWildcard => self.leading_wildcard,
+        }
+    }
+}
+
+/// One problem found in a statement
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintFinding {
+    pub rule: LintRule,
+    pub severity: LintSeverity,
+    pub message: String,
+}
+
+/// Significant tokens of a statement with their parenthesis depth
+struct Analysis {
+    tokens: Vec<(Token, usize)>,
+    tables: Vec<String>,
+}
+
+impl Analysis {
+    fn new(sql: &str, database_type: DatabaseType) -> Self {
+        let sql = crate::sql_buffer::strip_comments(sql);
+        let context = SqlParserFactory::create_parser(database_type)
+            .parse_at_cursor(&sql, sql.len())
+            .base_context;
+
+        let mut depth = 0usize;
+        let mut tokens = Vec::new();
+        for token in context.tokens {
+            if token.token_type == TokenType::Whitespace {
+                continue;
+            }
+            if token.value == ")" {
+                depth = depth.saturating_sub(1);
+            }
+            let token_depth = depth;
+            if token.value == "(" {
+                depth += 1;
+            }
+            tokens.push((token, token_depth));
+        }
+
+        let tables = context
+            .tables
+            .into_iter()
+            .map(|table| table.table.trim_matches(['"', '`']).to_string())
+            .collect();
+        Self { tokens, tables }
+    }
+
+    fn upper(&self, index: usize) -> String {
+        self.tokens
+            .get(index)
+            .map(|(token, _)| token.value.to_uppercase())
+            .unwrap_or_default()
+    }
+
+    fn is(&self, index: usize, value: &str) -> bool {
+        self.upper(index) == value
+    }
+
+    /// Index of the `)` closing the `(` at `open`
+    fn closing_paren(&self, open: usize) -> Option<usize> {
+        let depth = self.tokens.get(open)?.1;
+        (open + 1..self.tokens.len()).find(|&i| self.is(i, ")") && self.tokens[i].1 == depth)
+    }
+
+    fn top_level_keyword(&self, keyword: &str) -> bool {
+        (0..self.tokens.len()).any(|i| self.tokens[i].1 == 0 && self.is(i, keyword))
+    }
+
+    /// Whether the query has a top-level `SELECT *` (or `t.*`) and no LIMIT
+    fn unbounded_select_star(&self) -> bool {
+        if ["LIMIT", "FETCH", "TOP"]
+            .iter()
+            .any(|keyword| self.top_level_keyword(keyword))
+        {
+            return false;
+        }
+        let mut in_select_list = false;
+        for i in 0..self.tokens.len() {
+            if self.tokens[i].1 != 0 {
+                continue;
+            }
+            match self.upper(i).as_str() {
+                "SELECT" => in_select_list = true,
+                "FROM" | "INTO" | "WHERE" => in_select_list = false,
+                "*" if in_select_list && i > 0 => {
+                    if matches!(
+                        self.upper(i - 1).as_str(),
+                        "SELECT" | "DISTINCT" | "," | "."
+                    ) {
+                        return true;
+                    }
+                }
+                _ => {}
+            }
+        }
+        false
+    }
+}
+
+/// Tables worth a row estimate: only queried when the statement has an unbounded `SELECT *`
+pub fn tables_needing_estimates(sql: &str, database_type: DatabaseType) -> Vec<String> {
+    let analysis = Analysis::new(sql, database_type);
+    if analysis.unbounded_select_star() {
+        analysis.tables
+    } else {
+        Vec::new()
+    }
+}
+
+/// Run every enabled rule against one statement. `row_estimates` maps
+/// lowercase table names to estimated row counts; tables without an estimate
+/// are never reported as large.
+pub fn lint_statement(
+    sql: &str,
+    database_type: DatabaseType,
+    config: &LintConfig,
+    row_estimates: &HashMap<String, u64>,
+) -> Vec<LintFinding> {
+    let analysis = Analysis::new(sql, database_type);
+    let mut findings = Vec::new();
+    let mut report = |rule: LintRule, message: String| {
+        let severity = config.severity(rule);
+        if severity != LintSeverity::Off {
+            findings.push(LintFinding {
+                rule,
+                severity,
+                message,
+            });
+        }
+    };
+
+    if analysis.unbounded_select_star() {
+        let largest = analysis
+            .tables
+            .iter()
+            .filter_map(|table| Some((table, *row_estimates.get(&table.to_lowercase())?)))
+            .max_by_key(|(_, rows)| *rows);
+        if let Some((table, rows)) = largest
+            && rows >= config.large_table_rows
+        {
+            report(
+                LintRule::SelectStar,
+                format!(
+                    "SELECT * without LIMIT on {table} (~{rows} rows); list the columns you need or add a LIMIT"
+                ),
+            );
+        }
+    }
+
+    check_missing_where(&analysis, &mut report);
+    check_implicit_cross_join(&analysis, &mut report);
+    check_non_sargable(&analysis, &mut report);
+    check_leading_wildcard(&analysis, &mut report);
+
+    findings
+}
+
+fn check_missing_where(analysis: &Analysis, report: &mut impl FnMut(LintRule, String)) {
+    let statement = analysis.upper(0);
+    if !matches!(statement.as_str(), "UPDATE" | "DELETE") || analysis.top_level_keyword("WHERE") {
+        return;
+    }
+    let table_index = if statement == "DELETE" && analysis.is(1, "FROM") {
+        2
+    } else {
+        1
+    };
+    let table = analysis
+        .tokens
+        .get(table_index)
+        .map(|(token, _)| token.value.clone())
+        .unwrap_or_else(|| "the table".to_string());
+    report(
+        LintRule::MissingWhere,
+        format!("{statement} without WHERE changes every row of {table}"),
+    );
+}
+
+fn check_implicit_cross_join(analysis: &Analysis, report: &mut impl FnMut(LintRule, String)) {
+    // One scope per parenthesis level: (in FROM list, comma join seen, WHERE seen)
+    let mut scopes = vec![(false, false, false)];
+    let mut flagged = false;
+    let mut close_scope = |scope: (bool, bool, bool)| {
+        if scope.1 && !scope.2 && !flagged {
+            flagged = true;
+            report(
+                LintRule::ImplicitCrossJoin,
+                "Comma-separated FROM list without WHERE is a cross join; use an explicit JOIN ... ON"
+                    .to_string(),
+            );
+        }
+    };
+
+    for i in 0..analysis.tokens.len() {
+        match analysis.upper(i).as_str() {
+            "(" => scopes.push((false, false, false)),
+            ")" => {
+                if scopes.len() > 1 {
+                    let scope = scopes.pop().unwrap_or_default();
+                    close_scope(scope);
+                }
+            }
+            "UNION" | "INTERSECT" | "EXCEPT" => {
+                if let Some(scope) = scopes.last_mut() {
+                    let finished = std::mem::take(scope);
+                    close_scope(finished);
+                }
+            }
+            word => {
+                let Some(scope) = scopes.last_mut() else {
+                    continue;
+                };
+                match word {
+                    "FROM" => scope.0 = true,
+                    "," if scope.0 => scope.1 = true,
+                    "WHERE" => {
+                        scope.0 = false;
+                        scope.2 = true;
+                    }
+                    "JOIN" | "ON" | "GROUP" | "ORDER" | "HAVING" | "LIMIT" | "WINDOW" | ";" => {
+                        scope.0 = false
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    for scope in scopes {
+        close_scope(scope);
+    }
+}
+
+fn check_non_sargable(analysis: &Analysis, report: &mut impl FnMut(LintRule, String)) {
+    const PREDICATE_OPERATORS: &[&str] = &[
+        "=", "<", ">", "<=", ">=", "<>", "!=", "LIKE", "ILIKE", "IN", "BETWEEN",
+    ];
+    let is_comparison = |i: usize| PREDICATE_OPERATORS.contains(&analysis.upper(i).as_str());
+
+    // Whether each parenthesis level is inside a WHERE or ON condition
+    let mut in_predicate = vec![false];
+    for i in 0..analysis.tokens.len() {
+        let (token, _) = &analysis.tokens[i];
+        let word = analysis.upper(i);
+        match word.as_str() {
+            "(" => {
+                let inherited = *in_predicate.last().unwrap_or(&false);
+                in_predicate.push(inherited);
+                continue;
+            }
+            ")" => {
+                if in_predicate.len() > 1 {
+                    in_predicate.pop();
+                }
+                continue;
+            }
+            "WHERE" | "ON" => {
+                *in_predicate.last_mut().unwrap() = true;
+                continue;
+            }
+            "SELECT" | "FROM" | "JOIN" | "GROUP" | "ORDER" | "HAVING" | "LIMIT" | "UNION"
+            | "SET" | "RETURNING" => {
+                *in_predicate.last_mut().unwrap() = false;
+                continue;
+            }
+            _ => {}
+        }
+        if !*in_predicate.last().unwrap_or(&false) {
+            continue;
+        }
+
+        let is_function_name =
+            token.token_type == TokenType::Identifier || matches!(word.as_str(), "LEFT" | "RIGHT");
+        if is_function_name && analysis.is(i + 1, "(") {
+            let Some(close) = analysis.closing_paren(i + 1) else {
+                continue;
+            };
+            let wraps_column = analysis.tokens[i + 2..close]
+                .iter()
+                .any(|(arg, _)| arg.token_type == TokenType::Identifier);
+            if wraps_column && is_comparison(close + 1) {
+                report(
+                    LintRule::NonSargable,
+                    format!(
+                        "{}(...) around a column in a predicate prevents index use; compare the bare column or add an expression index",
+                        token.value
+                    ),
+                );
+            }
+        } else if token.token_type == TokenType::Identifier
+            && analysis.is(i + 1, ":")
+            && analysis.is(i + 2, ":")
+            && is_comparison(i + 4)
+        {
+            report(
+                LintRule::NonSargable,
+                format!(
+                    "Casting {}::{} in a predicate prevents index use; cast the other side instead",
+                    token.value,
+                    analysis.tokens[i + 3].0.value
+                ),
+            );
+        }
+    }
+}
+
+fn check_leading_wildcard(analysis: &Analysis, report: &mut impl FnMut(LintRule, String)) {
+    for i in 0..analysis.tokens.len() {
+        if !matches!(analysis.upper(i).as_str(), "LIKE" | "ILIKE") {
+            continue;
+        }
+        if let Some((pattern, _)) = analysis.tokens.get(i + 1)
+            && pattern.token_type == TokenType::Literal
+            && (pattern.value.starts_with("'%") || pattern.value.starts_with("'_"))
+        {
+            report(
+                LintRule::LeadingWildcard,
+                format!(
+                    "{} {} starts with a wildcard and cannot use a B-tree index; consider a trigram or full-text index",
+                    analysis.tokens[i].0.value, pattern.value
+                ),
+            );
+        }
+    }
+}
+
+/// Estimated row counts from the server's statistics, keyed by lowercase table name
+pub async fn table_row_estimates(
+    database: &mut Database,
+    tables: &[String],
+) -> HashMap<String, u64> {
+    if tables.is_empty() {
+        return HashMap::new();
+    }
+    let names = tables
+        .iter()
+        .map(|table| format!("'{}'", table.replace('\'', "''")))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let query = match database.get_database_type() {
+        DatabaseType::PostgreSQL => format!(
+            "SELECT relname, reltuples::bigint FROM pg_class \
+             WHERE relkind IN ('r', 'p', 'm') AND relname IN ({names})"
+        ),
+        DatabaseType::MySQL => format!(
+            "SELECT TABLE_NAME, TABLE_ROWS FROM information_schema.TABLES \
+             WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME IN ({names})"
+        ),
+        DatabaseType::ClickHouse => format!(
+            "SELECT name, total_rows FROM system.tables \
+             WHERE database = currentDatabase() AND name IN ({names})"
+        ),
+        // sqlite_stat1 only exists once ANALYZE has run; its first number is the row count
+        DatabaseType::SQLite => {
+            format!("SELECT tbl, stat FROM sqlite_stat1 WHERE idx IS NULL AND tbl IN ({names})")
+        }
+        _ => return HashMap::new(),
+    };
+
+    let Ok(results) = database.execute_query(&query).await else {
+        return HashMap::new();
+    };
+    let mut estimates = HashMap::new();
+    for row in results.iter().skip(1) {
+        let (Some(name), Some(rows)) = (row.first(), row.get(1)) else {
+            continue;
+        };
+        let Some(rows) = rows
+            .split_whitespace()
+            .next()
+            .and_then(|n| n.parse::<f64>().ok())
+            .filter(|n| *n >= 0.0)
+        else {
+            continue;
+        };
+        let entry = estimates.entry(name.to_lowercase()).or_insert(0);
+        *entry = (*entry).max(rows as u64);
+    }
+    estimates
+}
+
+/// Lint a statement against the connected database, fetching row estimates when needed
+pub async fn lint_for_database(
+    database: &mut Database,
+    sql: &str,
+    config: &LintConfig,
+) -> Vec<LintFinding> {
+    let database_type = database.get_database_type();
+    if matches!(
+        database_type,
+        DatabaseType::MongoDB | DatabaseType::Elasticsearch
+    ) {
+        return Vec::new();
+    }
+
+    let estimates = if config.select_star == LintSeverity::Off {
+        HashMap::new()
+    } else {
+        let tables = tables_needing_estimates(sql, database_type.clone());
+        table_row_estimates(database, &tables).await
+    };
+    lint_statement(sql, database_type, config, &estimates)
+}
+
+/// One colored line per finding
+pub fn format_findings(findings: &[LintFinding]) -> String {
+    findings
+        .iter()
+        .map(|finding| {
+            format!(
+                "{} {}: {}",
+                finding
+                    .severity
+                    .color()
+                    .bold()
+                    .paint(format!("lint {}", finding.severity)),
+                finding.rule,
+                finding.message
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn lint(sql: &str, estimates: &[(&str, u64)]) -> Vec<LintRule> {
+        let estimates = estimates
+            .iter()
+            .map(|(table, rows)| (table.to_string(), *rows))
+            .collect();
+        lint_statement(
+            sql,
+            DatabaseType::PostgreSQL,
+            &LintConfig::default(),
+            &estimates,
+        )
+        .into_iter()
+        .map(|finding| finding.rule)
+        .collect()
+    }
+
+    #[rstest]
+    #[case("SELECT * FROM events", &[("events", 5_000_000)], vec![LintRule::SelectStar])]
+    #[case("SELECT * FROM events LIMIT 10", &[("events", 5_000_000)], vec![])]
+    #[case("SELECT * FROM events", &[("events", 20)], vec![])]
+    #[case("SELECT * FROM events", &[], vec![])]
+    #[case("SELECT count(*) FROM events", &[("events", 5_000_000)], vec![])]
+    #[case("DELETE FROM users", &[], vec![LintRule::MissingWhere])]
+    #[case("UPDATE users SET active = false", &[], vec![LintRule::MissingWhere])]
+    #[case("UPDATE users SET active = false WHERE id = 1", &[], vec![])]
+    #[case("-- DELETE FROM users\nSELECT 1", &[], vec![])]
+    #[case("SELECT a.id FROM a, b", &[], vec![LintRule::ImplicitCrossJoin])]
+    #[case("SELECT a.id FROM a, b WHERE a.id = b.a_id", &[], vec![])]
+    #[case("SELECT id FROM a WHERE id IN (1, 2, 3)", &[], vec![])]
+    #[case("SELECT id FROM users WHERE lower(email) = 'x@y.z'", &[], vec![LintRule::NonSargable])]
+    #[case("SELECT id FROM users WHERE created_at > now()", &[], vec![])]
+    #[case("SELECT id FROM users WHERE created_at::date = '2024-01-01'", &[], vec![LintRule::NonSargable])]
+    #[case("SELECT lower(email) FROM users WHERE id = 1", &[], vec![])]
+    #[case("SELECT id FROM users WHERE email LIKE '%@example.com'", &[], vec![LintRule::LeadingWildcard])]
+    #[case("SELECT id FROM users WHERE email LIKE 'admin%'", &[], vec![])]
+    fn test_lint_rules(
+        #[case] sql: &str,
+        #[case] estimates: &[(&str, u64)],
+        #[case] expected: Vec<LintRule>,
+    ) {
+        assert_eq!(lint(sql, estimates), expected);
+    }
+
+    #[test]
+    fn test_severity_config_controls_findings() {
+        let config = LintConfig {
+            missing_where: LintSeverity::Off,
+            leading_wildcard: LintSeverity::Error,
+            ..LintConfig::default()
+        };
+        let findings = lint_statement(
+            "DELETE FROM logs WHERE message LIKE '%timeout%'",
+            DatabaseType::PostgreSQL,
+            &config,
+            &HashMap::new(),
+        );
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].rule, LintRule::LeadingWildcard);
+        assert_eq!(findings[0].severity, LintSeverity::Error);
+
+        let config = LintConfig {
+            missing_where: LintSeverity::Off,
+            ..LintConfig::default()
+        };
+        assert!(
+            lint_statement(
+                "DELETE FROM logs",
+                DatabaseType::PostgreSQL,
+                &config,
+                &HashMap::new()
+            )
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_tables_needing_estimates_only_for_select_star() {
+        assert_eq!(
+            tables_needing_estimates("SELECT * FROM events", DatabaseType::PostgreSQL),
+            vec!["events".to_string()]
+        );
+        assert!(
+            tables_needing_estimates("SELECT id FROM events", DatabaseType::PostgreSQL).is_empty()
+        );
+    }
+}
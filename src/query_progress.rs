@@ -0,0 +1,193 @@
+//! Status line for long-running queries (`query_progress_after_seconds`)
+//!
+//! Once a statement has run for the configured time, a side task draws a
+//! spinner with the elapsed time on stderr and refreshes it with whatever the
+//! backend reports about the statement: its state, what it waits on, the
+//! phase and counters of maintenance commands, and the rows received so far.
+//! The line is erased when the statement finishes.
+
+use std::future::Future;
+use std::io::{IsTerminal, Write};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Seconds a query runs before its status line shows up (0 = never). Set from
+/// `Config::query_progress_after_seconds` like the query timeout.
+static PROGRESS_AFTER_SECONDS: AtomicU64 = AtomicU64::new(3);
+
+const SPINNER: &[char] = &['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+const TICK: Duration = Duration::from_millis(100);
+const POLL_EVERY: Duration = Duration::from_secs(1);
+
+pub fn set_progress_after_seconds(seconds: u64) {
+    PROGRESS_AFTER_SECONDS.store(seconds, Ordering::Relaxed);
+}
+
+/// What the server reports about a running statement
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QueryProgress {
+    /// Backend state, e.g. `active`
+    pub state: Option<String>,
+    /// What the backend waits on, e.g. `Lock:transactionid`
+    pub wait_event: Option<String>,
+    /// Command and phase from a progress view, e.g. `CREATE INDEX: building index`
+    pub phase: Option<String>,
+    pub done: Option<i64>,
+    pub total: Option<i64>,
+    /// What `done` and `total` count (`blocks`, `rows`, `bytes`)
+    pub unit: &'static str,
+}
+
+/// `1234567` as `1,234,567`
+pub(crate) fn group_digits(n: i64) -> String {
+    let digits = n.unsigned_abs().to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    if n < 0 { format!("-{out}") } else { out }
+}
+
+/// The status line drawn after `elapsed`, with spinner frame `frame`
+pub fn status_line(
+    frame: usize,
+    elapsed: Duration,
+    progress: Option<&QueryProgress>,
+    rows: u64,
+) -> String {
+    let mut parts = vec![format!(
+        "{} {:.1}s",
+        SPINNER[frame % SPINNER.len()],
+        elapsed.as_secs_f64()
+    )];
+    if let Some(progress) = progress {
+        if let Some(state) = &progress.state {
+            parts.push(state.clone());
+        }
+        if let Some(wait) = &progress.wait_event {
+            parts.push(format!("waiting on {wait}"));
+        }
+        if let Some(phase) = &progress.phase {
+            let counters = match (progress.done, progress.total) {
+                (Some(done), Some(total)) if total > 0 => format!(
+                    " {}% ({}/{} {})",
+                    done * 100 / total,
+                    group_digits(done),
+                    group_digits(total),
+                    progress.unit
+                ),
+                (Some(done), _) => format!(" ({} {})", group_digits(done), progress.unit),
+                _ => String::new(),
+            };
+            parts.push(format!("{phase}{counters}"));
+        }
+    }
+    if rows > 0 {
+        parts.push(format!("{} rows received", group_digits(rows as i64)));
+    }
+    parts.join(" · ")
+}
+
+/// Keeps the status line task running; dropping it stops the task and erases
+/// the line
+pub struct ProgressGuard {
+    task: Option<tokio::task::JoinHandle<()>>,
+    drawn: Arc<AtomicBool>,
+}
+
+impl Drop for ProgressGuard {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+        if self.drawn.load(Ordering::Relaxed) {
+            eprint!("\r\x1b[2K");
+            let _ = std::io::stderr().flush();
+        }
+    }
+}
+
+/// Start the status line side task for a statement that started now.
+/// `poll` asks the server about the statement; `rows` counts rows received.
+/// Nothing is drawn when stderr is not a terminal or the feature is off.
+pub fn watch<F, Fut>(rows: Arc<AtomicU64>, mut poll: F) -> ProgressGuard
+where
+    F: FnMut() -> Fut + Send + 'static,
+    Fut: Future<Output = Option<QueryProgress>> + Send,
+{
+    let drawn = Arc::new(AtomicBool::new(false));
+    let after = PROGRESS_AFTER_SECONDS.load(Ordering::Relaxed);
+    if after == 0 || !std::io::stderr().is_terminal() {
+        return ProgressGuard { task: None, drawn };
+    }
+
+    let started = Instant::now();
+    let after = Duration::from_secs(after);
+    let task_drawn = drawn.clone();
+    let task = tokio::spawn(async move {
+        tokio::time::sleep(after).await;
+        let mut progress = None;
+        let mut last_poll: Option<Instant> = None;
+        let mut frame = 0;
+        loop {
+            if last_poll.is_none_or(|at| at.elapsed() >= POLL_EVERY) {
+                progress = poll().await;
+                last_poll = Some(Instant::now());
+            }
+            let line = status_line(
+                frame,
+                started.elapsed(),
+                progress.as_ref(),
+                rows.load(Ordering::Relaxed),
+            );
+            eprint!("\r\x1b[2K\x1b[2m{line}\x1b[0m");
+            let _ = std::io::stderr().flush();
+            task_drawn.store(true, Ordering::Relaxed);
+            frame += 1;
+            tokio::time::sleep(TICK).await;
+        }
+    });
+    ProgressGuard {
+        task: Some(task),
+        drawn,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_line() {
+        assert_eq!(
+            status_line(0, Duration::from_millis(4200), None, 0),
+            "⠋ 4.2s"
+        );
+
+        let progress = QueryProgress {
+            state: Some("active".to_string()),
+            wait_event: Some("IO:DataFileRead".to_string()),
+            phase: Some("CREATE INDEX: building index".to_string()),
+            done: Some(1_250),
+            total: Some(5_000),
+            unit: "blocks",
+        };
+        assert_eq!(
+            status_line(1, Duration::from_secs(12), Some(&progress), 0),
+            "⠙ 12.0s · active · waiting on IO:DataFileRead · CREATE INDEX: building index 25% (1,250/5,000 blocks)"
+        );
+
+        let state_only = QueryProgress {
+            state: Some("active".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            status_line(0, Duration::from_secs(5), Some(&state_only), 1_234_567),
+            "⠋ 5.0s · active · 1,234,567 rows received"
+        );
+    }
+}
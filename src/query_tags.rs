@@ -0,0 +1,181 @@
+//! SQL comment tags on outgoing statements (`[observability]`, `\tag`)
+//!
+//! With `query_tags` on, every statement sent to PostgreSQL, MySQL or
+//! ClickHouse ends with `/* app=dbcrust user=... session=... */`, so the
+//! traffic can be told apart in `pg_stat_statements`, slow query logs and
+//! `system.query_log`. Tags set with `\tag key=value` follow the built-in
+//! ones, and are sent even when `query_tags` is off. Values are
+//! percent-encoded so a tag can never close the comment.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+/// `[observability]` configuration section
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(default)]
+pub struct ObservabilityConfig {
+    /// Append `/* app=dbcrust user=... session=... */` to every statement
+    pub query_tags: bool,
+    /// Extra tags sent with every tagged statement, e.g. `{ team = "data" }`
+    pub tags: BTreeMap<String, String>,
+}
+
+/// Identifies this dbcrust process in the `session` tag
+pub fn session_id() -> &'static str {
+    static SESSION: OnceLock<String> = OnceLock::new();
+    SESSION.get_or_init(|| {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos() as u64)
+            .unwrap_or_default();
+        format!(
+            "{:08x}",
+            (nanos ^ (u64::from(std::process::id()) << 20)) as u32
+        )
+    })
+}
+
+/// Tags of one connection: the configured ones and those set with `\tag`
+#[derive(Debug, Clone, Default)]
+pub struct QueryTags {
+    enabled: bool,
+    configured: BTreeMap<String, String>,
+    custom: BTreeMap<String, String>,
+}
+
+impl QueryTags {
+    pub fn new(config: &ObservabilityConfig) -> Self {
+        Self {
+            enabled: config.query_tags,
+            configured: config.tags.clone(),
+            custom: BTreeMap::new(),
+        }
+    }
+
+    /// Set a `\tag`; an empty value removes it
+    pub fn set(&mut self, key: &str, value: &str) {
+        if value.is_empty() {
+            self.custom.remove(key);
+        } else {
+            self.custom.insert(key.to_string(), value.to_string());
+        }
+    }
+
+    /// Drop every `\tag`; returns how many there were
+    pub fn clear(&mut self) -> usize {
+        let count = self.custom.len();
+        self.custom.clear();
+        count
+    }
+
+    /// The comment appended for `user`, or `None` when nothing is tagged
+    pub fn comment(&self, user: &str) -> Option<String> {
+        if !self.enabled && self.custom.is_empty() {
+            return None;
+        }
+        let mut tags = vec![
+            ("app", "dbcrust"),
+            ("user", user),
+            ("session", session_id()),
+        ];
+        // Later tags override earlier ones with the same key
+        for (key, value) in self.configured.iter().chain(&self.custom) {
+            tags.retain(|(existing, _)| existing != key);
+            tags.push((key, value));
+        }
+        let tags: Vec<String> = tags
+            .iter()
+            .map(|(key, value)| format!("{}={}", encode(key), encode(value)))
+            .collect();
+        Some(format!("/* {} */", tags.join(" ")))
+    }
+
+    /// `sql` with the tag comment appended, or unchanged when nothing is tagged
+    pub fn apply(&self, sql: &str, user: &str) -> String {
+        match self.comment(user) {
+            Some(comment) => tag_statement(sql, &comment),
+            None => sql.to_string(),
+        }
+    }
+}
+
+/// Whether `key` can be used as a tag name
+pub fn is_valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+/// Percent-encode everything but letters, digits and `_-.:@/`
+fn encode(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || b"_-.:@/".contains(&byte) {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    out
+}
+
+/// Append `comment` after the statement's trailing semicolon is dropped. The
+/// comment goes on its own line when the last line ends in a `--` comment.
+fn tag_statement(sql: &str, comment: &str) -> String {
+    let statement = sql.trim_end().trim_end_matches(';').trim_end();
+    let last_line = statement.rsplit('\n').next().unwrap_or_default();
+    let separator = if last_line.contains("--") { "\n" } else { " " };
+    format!("{statement}{separator}{comment}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_comment_and_overrides() {
+        let mut tags = QueryTags::new(&ObservabilityConfig::default());
+        assert_eq!(tags.apply("SELECT 1;", "alice"), "SELECT 1;");
+
+        tags.set("ticket", "OPS-12");
+        tags.set("note", "a */ b");
+        assert_eq!(
+            tags.comment("alice").unwrap(),
+            format!(
+                "/* app=dbcrust user=alice session={} note=a%20%2A/%20b ticket=OPS-12 */",
+                session_id()
+            )
+        );
+
+        tags.set("note", "");
+        tags.set("app", "report");
+        let comment = tags.comment("alice").unwrap();
+        assert!(comment.starts_with("/* user=alice session="));
+        assert!(comment.ends_with(" app=report ticket=OPS-12 */"));
+        assert_eq!(tags.clear(), 2);
+
+        let configured = QueryTags::new(&ObservabilityConfig {
+            query_tags: true,
+            tags: BTreeMap::from([("team".to_string(), "data".to_string())]),
+        });
+        assert!(
+            configured
+                .comment("bob")
+                .unwrap()
+                .ends_with(" team=data */")
+        );
+    }
+
+    #[test]
+    fn test_tag_statement() {
+        assert_eq!(tag_statement("SELECT 1 ;\n", "/* t */"), "SELECT 1 /* t */");
+        assert_eq!(
+            tag_statement("SELECT 1\nFROM t -- all", "/* t */"),
+            "SELECT 1\nFROM t -- all\n/* t */"
+        );
+        assert!(is_valid_key("app.version"));
+        assert!(!is_valid_key("bad key"));
+    }
+}
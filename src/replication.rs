@@ -0,0 +1,468 @@
+//! Replication health report for the `\replication` command
+//!
+//! Puts everything an on-call engineer looks at during a lag incident on one
+//! screen: whether the server is a primary or a standby (and how far behind it
+//! replays), the replication slots with the WAL they pin, the WAL senders with
+//! their write/flush/replay lag, and the logical replication subscriptions.
+//! Lag values are colored green/yellow/red against fixed thresholds and the
+//! report ends with the worst status seen. Views the connected role cannot read
+//! are reported as unavailable instead of failing the whole command.
+
+use crate::db::Database;
+use crate::format::{format_bytes, format_lag};
+use nu_ansi_term::Color;
+use std::collections::HashMap;
+
+/// Byte lag below this is healthy (one WAL segment)
+pub const LAG_BYTES_WARNING: i64 = 16 * 1024 * 1024;
+/// Byte lag at or above this is critical
+pub const LAG_BYTES_CRITICAL: i64 = 1024 * 1024 * 1024;
+/// Time lag below this is healthy, in seconds
+pub const LAG_SECONDS_WARNING: f64 = 10.0;
+/// Time lag at or above this is critical, in seconds
+pub const LAG_SECONDS_CRITICAL: f64 = 300.0;
+/// A subscription that has not heard from its publisher for this long is stale
+pub const SUBSCRIPTION_STALE_SECONDS: f64 = 60.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Health {
+    Ok,
+    Warning,
+    Critical,
+}
+
+impl Health {
+    pub fn from_bytes(bytes: i64) -> Self {
+        if bytes >= LAG_BYTES_CRITICAL {
+            Health::Critical
+        } else if bytes >= LAG_BYTES_WARNING {
+            Health::Warning
+        } else {
+            Health::Ok
+        }
+    }
+
+    pub fn from_seconds(seconds: f64) -> Self {
+        if seconds >= LAG_SECONDS_CRITICAL {
+            Health::Critical
+        } else if seconds >= LAG_SECONDS_WARNING {
+            Health::Warning
+        } else {
+            Health::Ok
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Health::Ok => Color::Green,
+            Health::Warning => Color::Yellow,
+            Health::Critical => Color::Red,
+        }
+    }
+
+    fn paint(self, text: &str) -> String {
+        self.color().paint(text).to_string()
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Health::Ok => "OK",
+            Health::Warning => "WARNING",
+            Health::Critical => "CRITICAL",
+        }
+    }
+}
+
+/// Header → value maps for every row of a query result
+pub(crate) fn rows(results: &[Vec<String>]) -> Vec<HashMap<String, String>> {
+    let Some(header) = results.first() else {
+        return Vec::new();
+    };
+    results
+        .iter()
+        .skip(1)
+        .map(|row| {
+            header
+                .iter()
+                .cloned()
+                .zip(row.iter().cloned())
+                .filter(|(_, value)| !value.is_empty() && value != "NULL")
+                .collect()
+        })
+        .collect()
+}
+
+/// Colored byte lag cell; `-` when the server did not report one
+fn bytes_cell(value: Option<&String>, worst: &mut Health) -> String {
+    match value.and_then(|v| v.parse::<f64>().ok()) {
+        Some(bytes) => {
+            let bytes = bytes as i64;
+            let health = Health::from_bytes(bytes);
+            *worst = (*worst).max(health);
+            health.paint(&format_bytes(bytes))
+        }
+        None => "-".to_string(),
+    }
+}
+
+/// Colored time lag cell; `-` when the server did not report one
+fn seconds_cell(value: Option<&String>, worst: &mut Health) -> String {
+    match value.and_then(|v| v.parse::<f64>().ok()) {
+        Some(seconds) => {
+            let health = Health::from_seconds(seconds);
+            *worst = (*worst).max(health);
+            health.paint(&format_lag(seconds))
+        }
+        None => "-".to_string(),
+    }
+}
+
+fn text(row: &HashMap<String, String>, key: &str) -> String {
+    row.get(key).cloned().unwrap_or_default()
+}
+
+/// Current WAL position on either side of the replication link
+const CURRENT_LSN: &str = "CASE WHEN pg_is_in_recovery() THEN pg_last_wal_receive_lsn() \
+                           ELSE pg_current_wal_lsn() END";
+
+const ROLE_QUERY: &str = r#"
+    SELECT
+        pg_is_in_recovery() AS in_recovery,
+        extract(epoch FROM now() - pg_last_xact_replay_timestamp()) AS replay_delay,
+        pg_wal_lsn_diff(pg_last_wal_receive_lsn(), pg_last_wal_replay_lsn()) AS replay_backlog
+"#;
+
+fn slots_query() -> String {
+    // wal_status only exists from PostgreSQL 13, hence the jsonb lookup
+    format!(
+        r#"
+        SELECT
+            s.slot_name,
+            s.slot_type,
+            coalesce(s.plugin, '') AS plugin,
+            coalesce(s.database, '') AS database,
+            s.active,
+            to_jsonb(s) ->> 'wal_status' AS wal_status,
+            pg_wal_lsn_diff({CURRENT_LSN}, s.restart_lsn) AS retained_bytes,
+            pg_wal_lsn_diff({CURRENT_LSN}, s.confirmed_flush_lsn) AS flush_lag_bytes
+        FROM pg_replication_slots s
+        ORDER BY s.slot_name
+        "#
+    )
+}
+
+fn senders_query() -> String {
+    format!(
+        r#"
+        SELECT
+            coalesce(nullif(r.application_name, ''), r.pid::text) AS application_name,
+            coalesce(host(r.client_addr), 'local') AS client,
+            r.state,
+            r.sync_state,
+            pg_wal_lsn_diff({CURRENT_LSN}, r.replay_lsn) AS replay_lag_bytes,
+            extract(epoch FROM r.write_lag) AS write_lag,
+            extract(epoch FROM r.flush_lag) AS flush_lag,
+            extract(epoch FROM r.replay_lag) AS replay_lag
+        FROM pg_stat_replication r
+        ORDER BY application_name
+        "#
+    )
+}
+
+const SUBSCRIPTIONS_QUERY: &str = r#"
+    SELECT
+        sub.subname,
+        sub.subenabled AS enabled,
+        st.pid IS NOT NULL AS worker_running,
+        extract(epoch FROM now() - st.last_msg_receipt_time) AS last_message_age,
+        extract(epoch FROM st.last_msg_receipt_time - st.last_msg_send_time) AS transit_lag
+    FROM pg_subscription sub
+    LEFT JOIN pg_stat_subscription st
+           ON st.subid = sub.oid AND st.relid IS NULL
+    WHERE sub.subdbid = (SELECT oid FROM pg_database WHERE datname = current_database())
+    ORDER BY sub.subname
+"#;
+
+fn slot_table(rows: &[HashMap<String, String>], worst: &mut Health) -> Vec<Vec<String>> {
+    let mut table = vec![
+        [
+            "Slot",
+            "Type",
+            "Plugin",
+            "Database",
+            "Active",
+            "WAL status",
+            "Retained",
+            "Flush lag",
+        ]
+        .map(String::from)
+        .to_vec(),
+    ];
+    for row in rows {
+        let active = text(row, "active") == "true";
+        let wal_status = text(row, "wal_status");
+        // An inactive slot keeps pinning WAL until someone drops it
+        let active_cell = if active {
+            Health::Ok.paint("yes")
+        } else {
+            *worst = (*worst).max(Health::Critical);
+            Health::Critical.paint("no")
+        };
+        let wal_status_cell = match wal_status.as_str() {
+            "" => "-".to_string(),
+            "reserved" => Health::Ok.paint(&wal_status),
+            "extended" | "unreserved" => {
+                *worst = (*worst).max(Health::Warning);
+                Health::Warning.paint(&wal_status)
+            }
+            _ => {
+                *worst = (*worst).max(Health::Critical);
+                Health::Critical.paint(&wal_status)
+            }
+        };
+        table.push(vec![
+            text(row, "slot_name"),
+            text(row, "slot_type"),
+            text(row, "plugin"),
+            text(row, "database"),
+            active_cell,
+            wal_status_cell,
+            bytes_cell(row.get("retained_bytes"), worst),
+            bytes_cell(row.get("flush_lag_bytes"), worst),
+        ]);
+    }
+    table
+}
+
+fn sender_table(rows: &[HashMap<String, String>], worst: &mut Health) -> Vec<Vec<String>> {
+    let mut table = vec![
+        [
+            "Application",
+            "Client",
+            "State",
+            "Sync",
+            "Replay lag",
+            "Write lag",
+            "Flush lag",
+            "Replay lag (time)",
+        ]
+        .map(String::from)
+        .to_vec(),
+    ];
+    for row in rows {
+        let state = text(row, "state");
+        let state_cell = if state == "streaming" {
+            Health::Ok.paint(&state)
+        } else {
+            *worst = (*worst).max(Health::Warning);
+            Health::Warning.paint(&state)
+        };
+        table.push(vec![
+            text(row, "application_name"),
+            text(row, "client"),
+            state_cell,
+            text(row, "sync_state"),
+            bytes_cell(row.get("replay_lag_bytes"), worst),
+            seconds_cell(row.get("write_lag"), worst),
+            seconds_cell(row.get("flush_lag"), worst),
+            seconds_cell(row.get("replay_lag"), worst),
+        ]);
+    }
+    table
+}
+
+fn subscription_table(rows: &[HashMap<String, String>], worst: &mut Health) -> Vec<Vec<String>> {
+    let mut table = vec![
+        [
+            "Subscription",
+            "Enabled",
+            "Worker",
+            "Last message",
+            "Transit lag",
+        ]
+        .map(String::from)
+        .to_vec(),
+    ];
+    for row in rows {
+        let enabled = text(row, "enabled") == "true";
+        let running = text(row, "worker_running") == "true";
+        let enabled_cell = if enabled {
+            Health::Ok.paint("yes")
+        } else {
+            *worst = (*worst).max(Health::Warning);
+            Health::Warning.paint("no")
+        };
+        let worker_cell = match (enabled, running) {
+            (_, true) => Health::Ok.paint("running"),
+            (false, false) => "stopped".to_string(),
+            (true, false) => {
+                *worst = (*worst).max(Health::Critical);
+                Health::Critical.paint("not running")
+            }
+        };
+        let last_message = match row
+            .get("last_message_age")
+            .and_then(|v| v.parse::<f64>().ok())
+        {
+            Some(age) => {
+                let health = if age >= LAG_SECONDS_CRITICAL {
+                    Health::Critical
+                } else if age >= SUBSCRIPTION_STALE_SECONDS {
+                    Health::Warning
+                } else {
+                    Health::Ok
+                };
+                *worst = (*worst).max(health);
+                health.paint(&format!("{} ago", format_lag(age)))
+            }
+            None => "-".to_string(),
+        };
+        table.push(vec![
+            text(row, "subname"),
+            enabled_cell,
+            worker_cell,
+            last_message,
+            seconds_cell(row.get("transit_lag"), worst),
+        ]);
+    }
+    table
+}
+
+/// One titled section; an empty or failed query still gets a line
+fn section(title: &str, result: Result<Vec<Vec<String>>, String>, empty: &str) -> String {
+    let body = match result {
+        Ok(table) if table.len() > 1 => crate::format::format_query_results_psql(&table),
+        Ok(_) => format!("  {empty}"),
+        Err(e) => format!("  unavailable: {e}"),
+    };
+    format!("{}\n{body}", Color::Cyan.bold().paint(title))
+}
+
+/// Build the full replication report for a PostgreSQL connection
+pub async fn replication_report(database: &mut Database) -> Result<String, String> {
+    let role = database
+        .execute_query(ROLE_QUERY)
+        .await
+        .map_err(|e| format!("Failed to read replication state: {e}"))?;
+    let role = rows(&role).into_iter().next().unwrap_or_default();
+
+    let mut worst = Health::Ok;
+    let mut sections = Vec::new();
+
+    if text(&role, "in_recovery") == "true" {
+        let mut line = "Role: standby".to_string();
+        if let Some(delay) = role.get("replay_delay").and_then(|v| v.parse::<f64>().ok()) {
+            let health = Health::from_seconds(delay);
+            worst = worst.max(health);
+            line.push_str(&format!(
+                ", last replayed transaction {} ago",
+                health.paint(&format_lag(delay))
+            ));
+        }
+        if let Some(backlog) = role.get("replay_backlog") {
+            line.push_str(&format!(
+                ", {} received but not replayed",
+                bytes_cell(Some(backlog), &mut worst)
+            ));
+        }
+        sections.push(line);
+    } else {
+        sections.push("Role: primary".to_string());
+    }
+
+    let slots = database
+        .execute_query(&slots_query())
+        .await
+        .map(|results| slot_table(&rows(&results), &mut worst))
+        .map_err(|e| e.to_string());
+    sections.push(section("Replication slots", slots, "No replication slots."));
+
+    let senders = database
+        .execute_query(&senders_query())
+        .await
+        .map(|results| sender_table(&rows(&results), &mut worst))
+        .map_err(|e| e.to_string());
+    sections.push(section("WAL senders", senders, "No connected standbys."));
+
+    let subscriptions = database
+        .execute_query(SUBSCRIPTIONS_QUERY)
+        .await
+        .map(|results| subscription_table(&rows(&results), &mut worst))
+        .map_err(|e| e.to_string());
+    sections.push(section(
+        "Subscriptions",
+        subscriptions,
+        "No subscriptions in this database.",
+    ));
+
+    sections.push(format!(
+        "Status: {}",
+        worst.color().bold().paint(worst.label())
+    ));
+    Ok(sections.join("\n\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn row(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[rstest]
+    #[case(0, Health::Ok)]
+    #[case(LAG_BYTES_WARNING - 1, Health::Ok)]
+    #[case(LAG_BYTES_WARNING, Health::Warning)]
+    #[case(LAG_BYTES_CRITICAL, Health::Critical)]
+    fn test_health_from_bytes(#[case] bytes: i64, #[case] expected: Health) {
+        assert_eq!(Health::from_bytes(bytes), expected);
+    }
+
+    #[rstest]
+    #[case(0.5, Health::Ok)]
+    #[case(10.0, Health::Warning)]
+    #[case(299.9, Health::Warning)]
+    #[case(300.0, Health::Critical)]
+    fn test_health_from_seconds(#[case] seconds: f64, #[case] expected: Health) {
+        assert_eq!(Health::from_seconds(seconds), expected);
+    }
+
+    #[test]
+    fn test_inactive_slot_is_critical() {
+        let mut worst = Health::Ok;
+        let table = slot_table(
+            &[row(&[
+                ("slot_name", "orders_sub"),
+                ("slot_type", "logical"),
+                ("active", "false"),
+                ("wal_status", "reserved"),
+                ("retained_bytes", "1024"),
+            ])],
+            &mut worst,
+        );
+        assert_eq!(worst, Health::Critical);
+        assert_eq!(table.len(), 2);
+        assert_eq!(table[1][7], "-");
+    }
+
+    #[test]
+    fn test_stale_subscription_is_flagged() {
+        let mut worst = Health::Ok;
+        subscription_table(
+            &[row(&[
+                ("subname", "orders_sub"),
+                ("enabled", "true"),
+                ("worker_running", "true"),
+                ("last_message_age", "90"),
+                ("transit_lag", "0.01"),
+            ])],
+            &mut worst,
+        );
+        assert_eq!(worst, Health::Warning);
+    }
+}
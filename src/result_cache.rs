@@ -0,0 +1,338 @@
+//! Client-side cache of SELECT results (`[result_cache]`, `\cache`)
+//!
+//! Results are keyed by the connection and the statement with whitespace
+//! and comments outside literals collapsed, and served again until the TTL
+//! passes. Only single read-only `SELECT`/`WITH` statements without volatile
+//! functions are cached. Any statement that may write empties the cache,
+//! since the cached rows could be stale from then on.
+
+use crate::format::format_bytes;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// Functions whose result changes between runs; statements calling them are never cached
+const VOLATILE: &[&str] = &[
+    "now(",
+    "random(",
+    "rand(",
+    "uuid(",
+    "gen_random_uuid(",
+    "nextval(",
+    "clock_timestamp(",
+    "current_timestamp",
+    "current_time",
+    "current_date",
+    "localtimestamp",
+    "sysdate(",
+    "pg_sleep(",
+    "sleep(",
+];
+
+/// `[result_cache]` configuration section
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ResultCacheConfig {
+    /// Cache from the start of every session instead of after `\cache on`
+    pub enabled: bool,
+    /// Seconds a cached result is served
+    pub ttl_secs: u64,
+    /// Results kept; the oldest goes first
+    pub max_entries: usize,
+    /// Cell text kept across all results, in MB
+    pub max_size_mb: u64,
+}
+
+impl Default for ResultCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ttl_secs: 60,
+            max_entries: 100,
+            max_size_mb: 64,
+        }
+    }
+}
+
+struct CachedResult {
+    /// Every result set of the statement, header row first
+    sets: Vec<Vec<Vec<String>>>,
+    /// Database types of the first set's columns
+    column_types: Vec<String>,
+    stored: Instant,
+    bytes: u64,
+}
+
+/// Per-connection result cache kept on [`crate::db::Database`]
+pub struct ResultCache {
+    enabled: bool,
+    ttl: Duration,
+    max_entries: usize,
+    max_bytes: u64,
+    entries: HashMap<String, CachedResult>,
+    /// Keys from oldest to newest
+    order: VecDeque<String>,
+    bytes: u64,
+}
+
+impl Default for ResultCache {
+    fn default() -> Self {
+        Self::new(&ResultCacheConfig::default())
+    }
+}
+
+impl ResultCache {
+    pub fn new(config: &ResultCacheConfig) -> Self {
+        Self {
+            enabled: config.enabled,
+            ttl: Duration::from_secs(config.ttl_secs),
+            max_entries: config.max_entries,
+            max_bytes: config.max_size_mb.saturating_mul(1024 * 1024),
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            bytes: 0,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Turning the cache off also empties it
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.clear();
+        }
+    }
+
+    /// Drop every result; returns how many there were
+    pub fn clear(&mut self) -> usize {
+        let count = self.entries.len();
+        self.entries.clear();
+        self.order.clear();
+        self.bytes = 0;
+        count
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.bytes -= entry.bytes;
+            self.order.retain(|k| k != key);
+        }
+    }
+
+    /// The result sets stored under `key`, their column types and their age,
+    /// unless expired
+    pub fn get(&mut self, key: &str) -> Option<(Vec<Vec<Vec<String>>>, Vec<String>, Duration)> {
+        let age = self.entries.get(key)?.stored.elapsed();
+        if age > self.ttl {
+            self.remove(key);
+            return None;
+        }
+        let entry = &self.entries[key];
+        Some((entry.sets.clone(), entry.column_types.clone(), age))
+    }
+
+    /// Store a result, evicting the oldest ones past the size limits.
+    /// Results larger than the whole cache are not kept.
+    pub fn insert(&mut self, key: String, sets: Vec<Vec<Vec<String>>>, column_types: Vec<String>) {
+        let bytes = sets
+            .iter()
+            .flatten()
+            .flatten()
+            .map(|cell| cell.len() as u64)
+            .sum();
+        self.remove(&key);
+        if self.max_entries == 0 || bytes > self.max_bytes {
+            return;
+        }
+        while self.entries.len() >= self.max_entries || self.bytes + bytes > self.max_bytes {
+            let Some(oldest) = self.order.pop_front() else {
+                break;
+            };
+            if let Some(entry) = self.entries.remove(&oldest) {
+                self.bytes -= entry.bytes;
+            }
+        }
+        self.bytes += bytes;
+        self.order.push_back(key.clone());
+        self.entries.insert(
+            key,
+            CachedResult {
+                sets,
+                column_types,
+                stored: Instant::now(),
+                bytes,
+            },
+        );
+    }
+
+    /// One-line state for `\cache`
+    pub fn status(&self) -> String {
+        let count = self.entries.len();
+        format!(
+            "Result cache is {}: {count} result{} ({} of {}), max {} results, TTL {}s.",
+            if self.enabled { "on" } else { "off" },
+            if count == 1 { "" } else { "s" },
+            format_bytes(self.bytes as i64),
+            format_bytes(self.max_bytes as i64),
+            self.max_entries,
+            self.ttl.as_secs()
+        )
+    }
+}
+
+/// Statement text with comments removed and whitespace outside literals collapsed
+fn normalize(sql: &str) -> String {
+    let mut out = String::with_capacity(sql.len());
+    let mut chars = sql.chars().peekable();
+    let mut pending_space = false;
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => pending_space = true,
+            '-' if chars.peek() == Some(&'-') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+                pending_space = true;
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+                pending_space = true;
+            }
+            '\'' | '"' | '`' => {
+                if pending_space && !out.is_empty() {
+                    out.push(' ');
+                }
+                pending_space = false;
+                out.push(c);
+                for inner in chars.by_ref() {
+                    out.push(inner);
+                    if inner == c {
+                        break;
+                    }
+                }
+            }
+            c => {
+                if pending_space && !out.is_empty() {
+                    out.push(' ');
+                }
+                pending_space = false;
+                out.push(c);
+            }
+        }
+    }
+    out.trim_end_matches(|c: char| c == ';' || c.is_whitespace())
+        .to_string()
+}
+
+/// Whether `sql` is a read-only query whose result can be reused
+pub fn is_cacheable(sql: &str) -> bool {
+    let lower = normalize(sql).to_lowercase();
+    (lower.starts_with("select") || lower.starts_with("with"))
+        && crate::ai::streaming::is_select_query(&lower)
+        && !lower.contains(" for update")
+        && !lower.contains(" for share")
+        && !lower.contains(" into ")
+        && !VOLATILE.iter().any(|function| lower.contains(function))
+}
+
+/// Whether running `sql` may change data, so cached results must go
+pub fn invalidates(sql: &str) -> bool {
+    !crate::ai::streaming::is_select_query(sql)
+}
+
+/// Cache key of `sql` on the connection identified by `connection`, run as
+/// `role` (`\\setrole`) with `schema` first on the search path
+pub fn cache_key(connection: &str, role: Option<&str>, schema: Option<&str>, sql: &str) -> String {
+    format!(
+        "{connection}\nrole={}\nschema={}\n{}",
+        role.unwrap_or(""),
+        schema.unwrap_or(""),
+        normalize(sql)
+    )
+}
+
+/// Footer line for a result served from the cache
+pub fn age_note(age: Duration) -> String {
+    format!("(cached {}s ago)", age.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(value: &str) -> Vec<Vec<Vec<String>>> {
+        vec![vec![vec!["v".to_string()], vec![value.to_string()]]]
+    }
+
+    #[test]
+    fn test_cacheable_statements_and_keys() {
+        assert!(is_cacheable("SELECT * FROM users;"));
+        assert!(is_cacheable("with t as (select 1) select * from t"));
+        assert!(!is_cacheable("SELECT now()"));
+        assert!(!is_cacheable("SELECT * FROM jobs FOR UPDATE SKIP LOCKED"));
+        assert!(!is_cacheable(
+            "WITH d AS (DELETE FROM t RETURNING *) SELECT * FROM d"
+        ));
+        assert!(!is_cacheable("UPDATE users SET active = false"));
+
+        assert_eq!(
+            cache_key("db", None, None, "SELECT  *\n  FROM users -- all\n;"),
+            cache_key("db", None, None, "SELECT * FROM users")
+        );
+        assert_ne!(
+            cache_key("db", None, None, "SELECT 'a  b'"),
+            cache_key("db", None, None, "SELECT 'a b'")
+        );
+        assert_ne!(
+            cache_key("a", None, None, "SELECT 1"),
+            cache_key("b", None, None, "SELECT 1")
+        );
+        // Row-level security and search_path change what the same text returns
+        assert_ne!(
+            cache_key("db", None, None, "SELECT * FROM t"),
+            cache_key("db", Some("tenant_a"), None, "SELECT * FROM t")
+        );
+        assert_ne!(
+            cache_key("db", None, Some("public"), "SELECT * FROM t"),
+            cache_key("db", None, Some("audit"), "SELECT * FROM t")
+        );
+
+        assert!(invalidates("DELETE FROM users"));
+        assert!(!invalidates("SELECT 1"));
+    }
+
+    #[test]
+    fn test_cache_evicts_oldest_and_expires() {
+        let mut cache = ResultCache::new(&ResultCacheConfig {
+            enabled: true,
+            max_entries: 2,
+            ..Default::default()
+        });
+        cache.insert("a".to_string(), result("1"), Vec::new());
+        cache.insert("b".to_string(), result("2"), Vec::new());
+        cache.insert("c".to_string(), result("3"), Vec::new());
+        assert!(cache.get("a").is_none());
+        assert_eq!(cache.get("c").unwrap().0, result("3"));
+
+        cache.ttl = Duration::ZERO;
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(cache.get("b").is_none());
+        assert_eq!(cache.clear(), 1);
+        assert_eq!(cache.bytes, 0);
+
+        cache.set_enabled(false);
+        assert!(cache.status().starts_with("Result cache is off: 0 results"));
+    }
+}
@@ -0,0 +1,368 @@
+//! Cursor state, key handling and clipboard copies for the result viewer
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use std::time::Duration;
+use unicode_width::UnicodeWidthStr;
+
+/// Widest a column is drawn; copies always keep the full value
+const MAX_COLUMN_WIDTH: usize = 40;
+/// Space between two columns, as drawn by the table widget
+pub(super) const COLUMN_SPACING: u16 = 1;
+
+/// What a copy key puts on the clipboard
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CopyTarget {
+    Cell,
+    RowCsv,
+    RowJson,
+    AllCsv,
+    AllJson,
+}
+
+impl CopyTarget {
+    fn label(self) -> &'static str {
+        match self {
+            CopyTarget::Cell => "cell",
+            CopyTarget::RowCsv => "row as CSV",
+            CopyTarget::RowJson => "row as JSON",
+            CopyTarget::AllCsv => "result as CSV",
+            CopyTarget::AllJson => "result as JSON",
+        }
+    }
+}
+
+/// The main application state for the result viewer
+pub struct ResultViewerApp<'a> {
+    /// Header row first, as returned by the query paths
+    results: &'a [Vec<String>],
+    /// Selected data row (0 is the first row after the header)
+    pub row: usize,
+    pub column: usize,
+    /// First data row and first column drawn
+    pub row_offset: usize,
+    pub column_offset: usize,
+    pub column_widths: Vec<u16>,
+    pub should_quit: bool,
+    pub show_help: bool,
+    /// Outcome of the last copy, shown in the footer
+    pub status: Option<String>,
+    /// Data rows that fit on screen, updated on every draw (page up/down)
+    page_size: usize,
+    /// Kept open while the viewer runs: on X11 the copied text is served by
+    /// the clipboard owner, so dropping it right away could lose the copy
+    clipboard: Option<arboard::Clipboard>,
+}
+
+impl<'a> ResultViewerApp<'a> {
+    pub fn new(results: &'a [Vec<String>]) -> Self {
+        let column_count = results.first().map_or(0, Vec::len);
+        let column_widths = (0..column_count)
+            .map(|column| {
+                results
+                    .iter()
+                    .filter_map(|row| row.get(column))
+                    .map(|value| display_value(value).width())
+                    .max()
+                    .unwrap_or(0)
+                    .clamp(1, MAX_COLUMN_WIDTH) as u16
+            })
+            .collect();
+
+        Self {
+            results,
+            row: 0,
+            column: 0,
+            row_offset: 0,
+            column_offset: 0,
+            column_widths,
+            should_quit: false,
+            show_help: false,
+            status: None,
+            page_size: 1,
+            clipboard: None,
+        }
+    }
+
+    pub fn headers(&self) -> &[String] {
+        self.results.first().map_or(&[], Vec::as_slice)
+    }
+
+    /// Data rows, without the header
+    pub fn rows(&self) -> &[Vec<String>] {
+        self.results.get(1..).unwrap_or(&[])
+    }
+
+    pub fn column_count(&self) -> usize {
+        self.column_widths.len()
+    }
+
+    /// Value under the cursor
+    pub fn current_value(&self) -> Option<&str> {
+        self.rows()
+            .get(self.row)
+            .and_then(|row| row.get(self.column))
+            .map(String::as_str)
+    }
+
+    fn move_to(&mut self, row: usize, column: usize) {
+        self.row = row.min(self.rows().len().saturating_sub(1));
+        self.column = column.min(self.column_count().saturating_sub(1));
+        self.scroll_rows_into_view();
+    }
+
+    fn move_by(&mut self, rows: isize, columns: isize) {
+        self.move_to(
+            self.row.saturating_add_signed(rows),
+            self.column.saturating_add_signed(columns),
+        );
+    }
+
+    fn scroll_rows_into_view(&mut self) {
+        if self.row < self.row_offset {
+            self.row_offset = self.row;
+        } else if self.row >= self.row_offset + self.page_size {
+            self.row_offset = self.row + 1 - self.page_size;
+        }
+    }
+
+    /// Keep the cursor on screen for a grid of `page_size` rows and `width`
+    /// columns; called before every draw, so resizing keeps it visible too
+    pub fn scroll_into_view(&mut self, page_size: usize, width: u16) {
+        self.page_size = page_size.max(1);
+        self.scroll_rows_into_view();
+
+        if self.column < self.column_offset {
+            self.column_offset = self.column;
+        }
+        let span = |from: usize, to: usize| -> u16 {
+            self.column_widths[from..=to]
+                .iter()
+                .map(|w| w + COLUMN_SPACING)
+                .sum()
+        };
+        while self.column_offset < self.column && span(self.column_offset, self.column) > width {
+            self.column_offset += 1;
+        }
+    }
+
+    /// Text a copy key puts on the clipboard
+    pub fn copy_payload(&self, target: CopyTarget) -> String {
+        let row = self.rows().get(self.row).map_or(&[][..], Vec::as_slice);
+        match target {
+            CopyTarget::Cell => self.current_value().unwrap_or_default().to_string(),
+            CopyTarget::RowCsv => csv_line(row),
+            CopyTarget::RowJson => json_object(self.headers(), row, ""),
+            CopyTarget::AllCsv => self
+                .results
+                .iter()
+                .map(|row| csv_line(row) + "\n")
+                .collect(),
+            CopyTarget::AllJson if self.rows().is_empty() => "[]".to_string(),
+            CopyTarget::AllJson => {
+                let rows: Vec<_> = self
+                    .rows()
+                    .iter()
+                    .map(|row| format!("  {}", json_object(self.headers(), row, "  ")))
+                    .collect();
+                format!("[\n{}\n]", rows.join(",\n"))
+            }
+        }
+    }
+
+    fn copy(&mut self, target: CopyTarget) {
+        let text = self.copy_payload(target);
+        if self.clipboard.is_none() {
+            match arboard::Clipboard::new() {
+                Ok(clipboard) => self.clipboard = Some(clipboard),
+                Err(e) => {
+                    self.status = Some(format!("Error accessing clipboard: {e}"));
+                    return;
+                }
+            }
+        }
+        if let Some(clipboard) = self.clipboard.as_mut() {
+            let characters = text.chars().count();
+            self.status = Some(match clipboard.set_text(text) {
+                Ok(()) => format!("Copied {} ({characters} characters)", target.label()),
+                Err(e) => format!("Error copying to clipboard: {e}"),
+            });
+        }
+    }
+
+    pub fn handle_key_event(&mut self, key: event::KeyEvent) {
+        if key.kind != KeyEventKind::Press {
+            return;
+        }
+
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        if ctrl && key.code == KeyCode::Char('c') {
+            self.should_quit = true;
+            return;
+        }
+
+        // Help overlay: any key closes it
+        if self.show_help {
+            self.show_help = false;
+            return;
+        }
+
+        self.status = None;
+        let page = self.page_size as isize;
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.should_quit = true,
+            KeyCode::Char('?') => self.show_help = true,
+
+            KeyCode::Char('j') | KeyCode::Down => self.move_by(1, 0),
+            KeyCode::Char('k') | KeyCode::Up => self.move_by(-1, 0),
+            KeyCode::Char('l') | KeyCode::Right | KeyCode::Tab => self.move_by(0, 1),
+            KeyCode::Char('h') | KeyCode::Left | KeyCode::BackTab => self.move_by(0, -1),
+            KeyCode::Char('f') if ctrl => self.move_by(page, 0),
+            KeyCode::Char('b') if ctrl => self.move_by(-page, 0),
+            KeyCode::PageDown | KeyCode::Char(' ') => self.move_by(page, 0),
+            KeyCode::PageUp => self.move_by(-page, 0),
+            KeyCode::Char('g') | KeyCode::Home => self.move_to(0, self.column),
+            KeyCode::Char('G') | KeyCode::End => self.move_to(usize::MAX, self.column),
+            KeyCode::Char('0') | KeyCode::Char('^') => self.move_to(self.row, 0),
+            KeyCode::Char('$') => self.move_to(self.row, usize::MAX),
+
+            KeyCode::Char('y') | KeyCode::Char('c') | KeyCode::Enter => self.copy(CopyTarget::Cell),
+            KeyCode::Char('r') => self.copy(CopyTarget::RowCsv),
+            KeyCode::Char('R') => self.copy(CopyTarget::RowJson),
+            KeyCode::Char('a') => self.copy(CopyTarget::AllCsv),
+            KeyCode::Char('A') => self.copy(CopyTarget::AllJson),
+            _ => {}
+        }
+    }
+
+    pub fn poll_events(&mut self, timeout: Duration) -> std::io::Result<bool> {
+        if event::poll(timeout)?
+            && let Event::Key(key) = event::read()?
+        {
+            self.handle_key_event(key);
+            return Ok(true);
+        }
+        Ok(false)
+    }
+}
+
+/// Cell text on one line: newlines and tabs would break the grid
+pub(super) fn display_value(value: &str) -> String {
+    value.replace(['\n', '\r'], "↵").replace('\t', " ")
+}
+
+/// RFC 4180 line: fields with a comma, quote or line break are quoted
+fn csv_line(values: &[String]) -> String {
+    values
+        .iter()
+        .map(|value| {
+            if value.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", value.replace('"', "\"\""))
+            } else {
+                value.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Pretty JSON object with the keys in column order (`serde_json::Map`
+/// would sort them); values stay strings, as displayed
+fn json_object(headers: &[String], row: &[String], indent: &str) -> String {
+    let quote = |text: &str| serde_json::Value::from(text).to_string();
+    let fields: Vec<String> = headers
+        .iter()
+        .zip(row)
+        .map(|(name, value)| format!("{indent}  {}: {}", quote(name), quote(value)))
+        .collect();
+    format!("{{\n{}\n{indent}}}", fields.join(",\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyEvent;
+
+    fn results() -> Vec<Vec<String>> {
+        [
+            ["id", "name", "note"],
+            ["1", "alice", "likes \"tea\", a lot"],
+            ["2", "bob", "two\nlines"],
+            ["3", "carol", ""],
+        ]
+        .iter()
+        .map(|row| row.iter().map(|s| s.to_string()).collect())
+        .collect()
+    }
+
+    fn press(app: &mut ResultViewerApp, code: KeyCode) {
+        app.handle_key_event(KeyEvent::new(code, KeyModifiers::NONE));
+    }
+
+    #[test]
+    fn test_cursor_moves_and_stays_in_bounds() {
+        let results = results();
+        let mut app = ResultViewerApp::new(&results);
+        assert_eq!(app.column_widths, vec![2, 5, 18]);
+
+        press(&mut app, KeyCode::Char('G'));
+        press(&mut app, KeyCode::Char('$'));
+        assert_eq!((app.row, app.column), (2, 2));
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Right);
+        assert_eq!((app.row, app.column), (2, 2));
+
+        press(&mut app, KeyCode::Char('g'));
+        press(&mut app, KeyCode::Char('0'));
+        press(&mut app, KeyCode::Char('l'));
+        assert_eq!(app.current_value(), Some("alice"));
+
+        press(&mut app, KeyCode::Char('q'));
+        assert!(app.should_quit);
+    }
+
+    #[test]
+    fn test_scroll_into_view() {
+        let results = results();
+        let mut app = ResultViewerApp::new(&results);
+
+        app.scroll_into_view(2, 80);
+        press(&mut app, KeyCode::Char('G'));
+        assert_eq!(app.row_offset, 1);
+
+        // Only "note" fits in 20 columns once the cursor reaches it
+        press(&mut app, KeyCode::Char('$'));
+        app.scroll_into_view(2, 20);
+        assert_eq!(app.column_offset, 2);
+        press(&mut app, KeyCode::Char('0'));
+        app.scroll_into_view(2, 20);
+        assert_eq!(app.column_offset, 0);
+    }
+
+    #[test]
+    fn test_copy_payloads() {
+        let results = results();
+        let mut app = ResultViewerApp::new(&results);
+        press(&mut app, KeyCode::Char('$'));
+
+        assert_eq!(app.copy_payload(CopyTarget::Cell), "likes \"tea\", a lot");
+        assert_eq!(
+            app.copy_payload(CopyTarget::RowCsv),
+            "1,alice,\"likes \"\"tea\"\", a lot\""
+        );
+        let row: serde_json::Value =
+            serde_json::from_str(&app.copy_payload(CopyTarget::RowJson)).unwrap();
+        assert_eq!(row["name"], "alice");
+        assert!(
+            app.copy_payload(CopyTarget::RowJson)
+                .starts_with("{\n  \"id\": \"1\",\n  \"name\"")
+        );
+
+        let all = app.copy_payload(CopyTarget::AllCsv);
+        assert!(all.starts_with("id,name,note\n1,alice,"));
+        assert!(all.contains("2,bob,\"two\nlines\"\n"));
+        let all: serde_json::Value =
+            serde_json::from_str(&app.copy_payload(CopyTarget::AllJson)).unwrap();
+        assert_eq!(all.as_array().unwrap().len(), 3);
+        assert_eq!(all[2]["id"], "3");
+    }
+}
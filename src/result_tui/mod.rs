@@ -0,0 +1,98 @@
+//! Interactive result viewer
+//!
+//! Replaces the external pager for tabular results that do not fit on the
+//! screen: a scrollable grid with a cell cursor, where single keys copy the
+//! current cell, the current row (CSV or JSON) or the whole result to the
+//! clipboard.
+//!
+//! # Usage
+//!
+//! Used automatically when `pager_enabled` and `pager_interactive` are on and
+//! a result is taller than the pager threshold, or wider than the terminal
+//! with `pager_trigger_on_width`. Set `pager_interactive = false` to keep the
+//! external `pager_command` for every output.
+
+mod app;
+mod ui;
+
+pub use app::{CopyTarget, ResultViewerApp};
+
+use crossterm::{
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute,
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+};
+use ratatui::prelude::*;
+use std::io::stdout;
+use std::panic;
+use std::time::Duration;
+
+/// Browse `results` (header row first) until the user quits
+pub fn run_result_viewer(results: &[Vec<String>]) -> Result<(), String> {
+    let mut app = ResultViewerApp::new(results);
+
+    // Set up panic hook to restore terminal on panic
+    let original_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |panic_info| {
+        let _ = disable_raw_mode();
+        let _ = execute!(stdout(), LeaveAlternateScreen, DisableMouseCapture);
+        original_hook(panic_info);
+    }));
+
+    let result = run_tui_loop(&mut app);
+
+    // Restore panic hook
+    let _ = panic::take_hook();
+
+    result
+}
+
+/// Internal function to run the TUI event loop
+fn run_tui_loop(app: &mut ResultViewerApp) -> Result<(), String> {
+    enable_raw_mode().map_err(|e| format!("Failed to enable raw mode: {e}"))?;
+
+    let mut stdout = stdout();
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)
+        .map_err(|e| format!("Failed to enter alternate screen: {e}"))?;
+
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal =
+        Terminal::new(backend).map_err(|e| format!("Failed to create terminal: {e}"))?;
+
+    terminal
+        .clear()
+        .map_err(|e| format!("Failed to clear terminal: {e}"))?;
+
+    let result = loop {
+        terminal
+            .draw(|frame| ui::render(frame, &mut *app))
+            .map_err(|e| format!("Failed to draw: {e}"))?;
+
+        match app.poll_events(Duration::from_millis(100)) {
+            Ok(_) => {
+                if app.should_quit {
+                    break Ok(());
+                }
+            }
+            Err(e) => {
+                break Err(format!("Event error: {e}"));
+            }
+        }
+    };
+
+    // Restore terminal
+    disable_raw_mode().map_err(|e| format!("Failed to disable raw mode: {e}"))?;
+
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    )
+    .map_err(|e| format!("Failed to leave alternate screen: {e}"))?;
+
+    terminal
+        .show_cursor()
+        .map_err(|e| format!("Failed to show cursor: {e}"))?;
+
+    result
+}
@@ -0,0 +1,228 @@
+//! UI rendering for the result viewer
+
+use super::app::{COLUMN_SPACING, ResultViewerApp, display_value};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, TableState},
+};
+
+/// Render the entire result viewer
+pub fn render(frame: &mut Frame, app: &mut ResultViewerApp) {
+    let area = frame.area();
+
+    let main_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1), // Header
+            Constraint::Min(3),    // Grid
+            Constraint::Length(2), // Footer
+        ])
+        .split(area);
+
+    render_header(frame, app, main_chunks[0]);
+    render_grid(frame, app, main_chunks[1]);
+    render_footer(frame, app, main_chunks[2]);
+
+    if app.show_help {
+        render_help_overlay(frame, area);
+    }
+}
+
+/// Render the header bar
+fn render_header(frame: &mut Frame, app: &ResultViewerApp, area: Rect) {
+    let dim = Style::default().fg(Color::DarkGray);
+    let column_name = app
+        .headers()
+        .get(app.column)
+        .map(String::as_str)
+        .unwrap_or_default();
+    let title = Line::from(vec![
+        Span::styled(
+            " Results ",
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::styled("| ", dim),
+        Span::styled(
+            format!("{} rows × {} columns", app.rows().len(), app.column_count()),
+            Style::default().fg(Color::White),
+        ),
+        Span::styled(" | ", dim),
+        Span::styled(
+            format!("row {} ", (app.row + 1).min(app.rows().len())),
+            Style::default().fg(Color::White),
+        ),
+        Span::styled("· ", dim),
+        Span::styled(
+            column_name.to_string(),
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ),
+    ]);
+    frame.render_widget(Paragraph::new(title), area);
+}
+
+/// Render the visible window of rows and columns, cursor cell highlighted
+fn render_grid(frame: &mut Frame, app: &mut ResultViewerApp, area: Rect) {
+    // Borders take two lines and the header row one more
+    app.scroll_into_view(
+        area.height.saturating_sub(3) as usize,
+        area.width.saturating_sub(2),
+    );
+
+    let mut visible_width = 0;
+    let columns: Vec<usize> = (app.column_offset..app.column_count())
+        .take_while(|&column| {
+            let fits = visible_width == 0 || visible_width < area.width.saturating_sub(2);
+            visible_width += app.column_widths[column] + COLUMN_SPACING;
+            fits
+        })
+        .collect();
+    let widths: Vec<Constraint> = columns
+        .iter()
+        .map(|&column| Constraint::Length(app.column_widths[column]))
+        .collect();
+
+    let header = Row::new(columns.iter().map(|&column| {
+        Cell::from(display_value(&app.headers()[column])).style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )
+    }));
+    let page = area.height.saturating_sub(3) as usize;
+    let rows = app
+        .rows()
+        .iter()
+        .skip(app.row_offset)
+        .take(page)
+        .map(|row| {
+            Row::new(columns.iter().map(|&column| {
+                let value = row.get(column).map(String::as_str).unwrap_or_default();
+                Cell::from(display_value(value))
+            }))
+        });
+
+    let table = Table::new(rows, widths)
+        .header(header)
+        .column_spacing(COLUMN_SPACING)
+        .row_highlight_style(Style::default().bg(Color::Rgb(40, 40, 55)))
+        .cell_highlight_style(
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::DarkGray)),
+        );
+
+    let mut state = TableState::default();
+    if !app.rows().is_empty() {
+        state.select_cell(Some((
+            app.row - app.row_offset,
+            app.column - app.column_offset,
+        )));
+    }
+    frame.render_stateful_widget(table, area, &mut state);
+}
+
+/// Render the footer: the last copy's outcome, or the key hints
+fn render_footer(frame: &mut Frame, app: &ResultViewerApp, area: Rect) {
+    let line = if let Some(status) = &app.status {
+        let color = if status.starts_with("Error") {
+            Color::Red
+        } else {
+            Color::Green
+        };
+        Line::from(Span::styled(
+            format!(" {status}"),
+            Style::default().fg(color),
+        ))
+    } else {
+        let dim = Style::default().fg(Color::DarkGray);
+        let key = Style::default().fg(Color::Cyan);
+        Line::from(vec![
+            Span::styled(" [", dim),
+            Span::styled("hjkl", key),
+            Span::styled("] Move  [", dim),
+            Span::styled("y", key),
+            Span::styled("] Copy cell  [", dim),
+            Span::styled("r/R", key),
+            Span::styled("] Row CSV/JSON  [", dim),
+            Span::styled("a/A", key),
+            Span::styled("] All CSV/JSON  [", dim),
+            Span::styled("?", key),
+            Span::styled("] Help  [", dim),
+            Span::styled("q", key),
+            Span::styled("] Quit ", dim),
+        ])
+    };
+
+    let footer = Paragraph::new(line)
+        .style(Style::default().fg(Color::DarkGray))
+        .block(Block::default().borders(Borders::TOP));
+    frame.render_widget(footer, area);
+}
+
+/// Render help overlay
+fn render_help_overlay(frame: &mut Frame, area: Rect) {
+    let popup_width = 56.min(area.width.saturating_sub(4));
+    let popup_height = 24.min(area.height.saturating_sub(4));
+    let popup_x = (area.width.saturating_sub(popup_width)) / 2;
+    let popup_y = (area.height.saturating_sub(popup_height)) / 2;
+    let popup_area = Rect::new(popup_x, popup_y, popup_width, popup_height);
+
+    frame.render_widget(Clear, popup_area);
+
+    let section = |title: &'static str| {
+        Line::from(Span::styled(
+            title,
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ))
+    };
+    let help_lines = vec![
+        Line::from(Span::styled(
+            "Result Viewer - Help",
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        section("  Navigation:"),
+        Line::from("    j/k, Up/Down      - Next / previous row"),
+        Line::from("    h/l, Left/Right   - Previous / next column"),
+        Line::from("    Space, PgDn, C-f  - Page down"),
+        Line::from("    PgUp, C-b         - Page up"),
+        Line::from("    g / G             - First / last row"),
+        Line::from("    0 / $             - First / last column"),
+        Line::from(""),
+        section("  Copy to clipboard:"),
+        Line::from("    y, c, Enter       - Current cell"),
+        Line::from("    r                 - Current row as CSV"),
+        Line::from("    R                 - Current row as JSON"),
+        Line::from("    a                 - Whole result as CSV"),
+        Line::from("    A                 - Whole result as JSON"),
+        Line::from(""),
+        section("  General:"),
+        Line::from("    ?                 - Toggle this help"),
+        Line::from("    q, Esc, C-c       - Quit"),
+    ];
+
+    let help = Paragraph::new(help_lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan))
+            .title(" Help "),
+    );
+    frame.render_widget(help, popup_area);
+}
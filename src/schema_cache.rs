@@ -0,0 +1,493 @@
+//! Completion metadata kept on disk per connection (`\refreshschema`)
+//!
+//! Table and column names fetched for autocompletion are saved under
+//! `schema_cache/` in the config directory, one file per connection
+//! fingerprint (the key of the per-session history plus the active schema).
+//! The next session completes from that snapshot straight away instead of
+//! querying the server on the first Tab, which is slow over high-latency
+//! tunnels. The REPL re-reads everything the snapshot holds in the background
+//! at startup; `\refreshschema` does the same on demand.
+//!
+//! Column completions also show each column's type, nullability, primary and
+//! foreign key and, when the server's statistics have one, its estimated
+//! number of distinct values. That metadata is read with the column list and
+//! saved alongside it.
+
+use crate::database::{DatabaseType, escape_sql_string};
+use crate::db::Database;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tracing::debug;
+
+/// What a connection's completion knew about its tables
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct SchemaSnapshot {
+    /// Unix time of the last full refresh, 0 before the first one
+    #[serde(default)]
+    pub refreshed_at: i64,
+    /// Table names by the schema they were listed for ("" = the default one)
+    #[serde(default)]
+    pub tables: BTreeMap<String, Vec<String>>,
+    /// Column names by table, as the table was written when completing
+    #[serde(default)]
+    pub columns: BTreeMap<String, Vec<String>>,
+    /// Column metadata by table, then by column name
+    #[serde(default)]
+    pub column_meta: BTreeMap<String, BTreeMap<String, ColumnMeta>>,
+}
+
+/// What column completions show next to a column name
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ColumnMeta {
+    pub data_type: String,
+    pub nullable: bool,
+    #[serde(default)]
+    pub primary_key: bool,
+    /// Table the column refers to through a foreign key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub references: Option<String>,
+    /// Distinct values estimated by the server's statistics; never counted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub distinct: Option<i64>,
+}
+
+impl ColumnMeta {
+    /// One line for the completion menu, e.g.
+    /// `bigint · FK → users · not null · ~1,204 distinct`
+    pub fn summary(&self) -> String {
+        let mut parts = vec![self.data_type.clone()];
+        if self.primary_key {
+            parts.push("PK".to_string());
+        }
+        if let Some(table) = &self.references {
+            parts.push(format!("FK → {table}"));
+        }
+        parts.push(if self.nullable { "null" } else { "not null" }.to_string());
+        if let Some(distinct) = self.distinct {
+            parts.push(format!(
+                "~{} distinct",
+                crate::query_progress::group_digits(distinct)
+            ));
+        }
+        parts.retain(|part| !part.is_empty());
+        parts.join(" · ")
+    }
+}
+
+/// The snapshot of the connection in use, kept on [`Database`]
+#[derive(Debug, Default)]
+pub struct SchemaCache {
+    fingerprint: Option<String>,
+    snapshot: SchemaSnapshot,
+    /// `None` keeps the snapshot in memory only (tests, batch mode)
+    path: Option<PathBuf>,
+    /// Bumped when the snapshot is replaced, so completers drop what they hold
+    generation: u64,
+}
+
+/// File-safe fingerprint of a connection identifier
+pub fn fingerprint(identifier: &str) -> String {
+    let digest = Sha256::digest(identifier.as_bytes());
+    digest.iter().take(8).map(|b| format!("{b:02x}")).collect()
+}
+
+impl SchemaCache {
+    /// Load the snapshot saved for `fingerprint`; `persist = false` keeps it
+    /// in memory only
+    pub fn load(fingerprint: &str, persist: bool) -> Self {
+        let path = persist
+            .then(crate::config::Config::get_config_dir)
+            .and_then(|dir| match dir {
+                Ok(dir) => Some(dir.join("schema_cache").join(format!("{fingerprint}.json"))),
+                Err(e) => {
+                    debug!("No config directory for the schema cache: {}", e);
+                    None
+                }
+            });
+        let snapshot = path
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|content| match serde_json::from_str(&content) {
+                Ok(snapshot) => Some(snapshot),
+                Err(e) => {
+                    debug!("Ignoring unreadable schema cache: {}", e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+        Self {
+            fingerprint: Some(fingerprint.to_string()),
+            snapshot,
+            path,
+            generation: 0,
+        }
+    }
+
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let result = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .map_err(|e| e.to_string())
+            .and_then(|_| serde_json::to_string(&self.snapshot).map_err(|e| e.to_string()))
+            .and_then(|content| std::fs::write(path, content).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            debug!("Failed to save the schema cache: {}", e);
+        }
+    }
+
+    pub fn fingerprint(&self) -> Option<&str> {
+        self.fingerprint.as_deref()
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn snapshot(&self) -> &SchemaSnapshot {
+        &self.snapshot
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshot.tables.is_empty() && self.snapshot.columns.is_empty()
+    }
+
+    pub fn tables(&self, schema: &str) -> Option<&Vec<String>> {
+        self.snapshot.tables.get(schema)
+    }
+
+    pub fn columns(&self, table: &str) -> Option<&Vec<String>> {
+        self.snapshot.columns.get(table)
+    }
+
+    /// Remember the tables listed for `schema`; empty lists are not kept
+    /// since a failed lookup looks the same
+    pub fn store_tables(&mut self, schema: &str, tables: &[String]) {
+        if !tables.is_empty() {
+            self.snapshot
+                .tables
+                .insert(schema.to_string(), tables.to_vec());
+            self.save();
+        }
+    }
+
+    /// Remember the columns of `table`; empty lists are not kept
+    pub fn store_columns(&mut self, table: &str, columns: &[String]) {
+        if !columns.is_empty() {
+            self.snapshot
+                .columns
+                .insert(table.to_string(), columns.to_vec());
+            self.save();
+        }
+    }
+
+    pub fn column_meta(&self, table: &str) -> Option<&BTreeMap<String, ColumnMeta>> {
+        self.snapshot.column_meta.get(table)
+    }
+
+    /// Remember the column metadata of `table`; empty maps are not kept
+    pub fn store_column_meta(&mut self, table: &str, meta: &BTreeMap<String, ColumnMeta>) {
+        if !meta.is_empty() {
+            self.snapshot
+                .column_meta
+                .insert(table.to_string(), meta.clone());
+            self.save();
+        }
+    }
+
+    /// Swap in a freshly read snapshot
+    pub fn replace(&mut self, snapshot: SchemaSnapshot) {
+        self.snapshot = snapshot;
+        self.generation += 1;
+        self.save();
+    }
+}
+
+/// Whether the column metadata of `database_type` comes from a catalog cheap
+/// enough to read on Tab; MongoDB and Elasticsearch sample documents instead
+pub fn has_column_catalog(database_type: &DatabaseType) -> bool {
+    !matches!(
+        database_type,
+        DatabaseType::MongoDB | DatabaseType::Elasticsearch
+    )
+}
+
+/// A parsed `FOREIGN KEY (a, b) REFERENCES t(x, y)` definition
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ForeignKeyRef {
+    pub columns: Vec<String>,
+    /// Referenced table, `schema.table` when qualified
+    pub table: String,
+    /// Referenced columns; empty when the definition leaves them out
+    pub referenced: Vec<String>,
+}
+
+pub(crate) fn parse_foreign_key(definition: &str) -> Option<ForeignKeyRef> {
+    let re =
+        Regex::new(r#"(?i)FOREIGN KEY\s*\(([^)]*)\)\s*REFERENCES\s+([^\s(]+)\s*(?:\(([^)]*)\))?"#)
+            .unwrap();
+    let captures = re.captures(definition)?;
+    let unquote = |name: &str| {
+        name.trim()
+            .trim_matches(|c| c == '"' || c == '`')
+            .to_string()
+    };
+    let list = |names: &str| names.split(',').map(unquote).collect::<Vec<_>>();
+    Some(ForeignKeyRef {
+        columns: list(&captures[1]),
+        table: captures[2]
+            .split('.')
+            .map(unquote)
+            .collect::<Vec<_>>()
+            .join("."),
+        referenced: captures.get(3).map_or_else(Vec::new, |m| list(m.as_str())),
+    })
+}
+
+/// Distinct value estimates by column from the planner statistics
+/// (PostgreSQL `pg_stats`, MySQL index cardinality); empty elsewhere
+async fn distinct_estimates(db: &Database, table: &str) -> HashMap<String, i64> {
+    let parts: Vec<&str> = table
+        .split('.')
+        .map(|part| part.trim_matches(|c| c == '"' || c == '`'))
+        .collect();
+    let name = escape_sql_string(parts.last().copied().unwrap_or_default());
+    let sql = match db.get_database_type() {
+        DatabaseType::PostgreSQL => format!(
+            "SELECT s.attname, CASE WHEN s.n_distinct >= 0 THEN s.n_distinct \
+             ELSE -s.n_distinct * c.reltuples END::bigint \
+             FROM pg_class c JOIN pg_namespace n ON n.oid = c.relnamespace \
+             JOIN pg_stats s ON s.schemaname = n.nspname AND s.tablename = c.relname \
+             WHERE c.oid = '{}'::regclass",
+            escape_sql_string(table)
+        ),
+        DatabaseType::MySQL => {
+            let schema = match parts.as_slice() {
+                [schema, _] => format!("'{}'", escape_sql_string(schema)),
+                _ => "DATABASE()".to_string(),
+            };
+            format!(
+                "SELECT COLUMN_NAME, MAX(CARDINALITY) FROM information_schema.STATISTICS \
+                 WHERE TABLE_SCHEMA = {schema} AND TABLE_NAME = '{name}' \
+                 AND SEQ_IN_INDEX = 1 GROUP BY COLUMN_NAME"
+            )
+        }
+        _ => return HashMap::new(),
+    };
+    let Some(client) = db.get_database_client() else {
+        return HashMap::new();
+    };
+    let rows = match client.execute_query(&sql).await {
+        Ok(rows) => rows,
+        Err(e) => {
+            debug!("No distinct estimates for '{}': {}", table, e);
+            return HashMap::new();
+        }
+    };
+    rows.into_iter()
+        .skip(1)
+        .filter_map(|row| {
+            let count = row
+                .get(1)?
+                .parse::<f64>()
+                .ok()
+                .filter(|count| *count > 0.0)?;
+            Some((row.first()?.clone(), count as i64))
+        })
+        .collect()
+}
+
+/// Read the column metadata of `table`: types and nullability from the
+/// table details, keys, and distinct estimates when statistics exist
+pub async fn fetch_column_meta(db: &mut Database, table: &str) -> BTreeMap<String, ColumnMeta> {
+    let (schema, name) = match table.split_once('.') {
+        Some((schema, name)) => (Some(schema), name),
+        None => (None, table),
+    };
+    let details = match db.get_table_details_in_schema(name, schema).await {
+        Ok(details) => details,
+        Err(e) => {
+            debug!("No column metadata for '{}': {}", table, e);
+            return BTreeMap::new();
+        }
+    };
+    let primary_key = crate::export_chunks::primary_key(db, table)
+        .await
+        .unwrap_or_default();
+    let references: HashMap<String, String> = details
+        .foreign_keys
+        .iter()
+        .filter_map(|fk| parse_foreign_key(&fk.definition))
+        .flat_map(|fk| {
+            let table = fk.table;
+            fk.columns.into_iter().map(move |c| (c, table.clone()))
+        })
+        .collect();
+    let distinct = distinct_estimates(db, table).await;
+    details
+        .columns
+        .into_iter()
+        .map(|column| {
+            let meta = ColumnMeta {
+                data_type: column.data_type,
+                nullable: column.nullable,
+                primary_key: primary_key.contains(&column.name),
+                references: references.get(&column.name).cloned(),
+                distinct: distinct.get(&column.name).copied(),
+            };
+            (column.name, meta)
+        })
+        .collect()
+}
+
+/// Counts reported by [`refresh`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RefreshSummary {
+    pub tables: usize,
+    pub column_lists: usize,
+}
+
+/// Read again every table list and column list the snapshot holds (the
+/// default table list when it holds nothing) and replace it. The database is
+/// locked for one metadata query at a time, so statements typed meanwhile
+/// only wait for that one. Columns of tables that no longer exist are dropped.
+#[allow(clippy::await_holding_lock)]
+pub async fn refresh(database: &Arc<Mutex<Database>>) -> Result<RefreshSummary, String> {
+    let (fingerprint, schemas, tables, meta_tables) = {
+        let mut db = database.lock().unwrap();
+        let cache = db.schema_cache_mut();
+        let snapshot = cache.snapshot();
+        (
+            cache.fingerprint().map(str::to_string),
+            snapshot.tables.keys().cloned().collect::<Vec<_>>(),
+            snapshot.columns.keys().cloned().collect::<Vec<_>>(),
+            snapshot.column_meta.keys().cloned().collect::<Vec<_>>(),
+        )
+    };
+    let schemas = if schemas.is_empty() {
+        vec![String::new()]
+    } else {
+        schemas
+    };
+
+    let mut snapshot = SchemaSnapshot {
+        refreshed_at: chrono::Utc::now().timestamp(),
+        ..Default::default()
+    };
+    for schema in schemas {
+        let names = {
+            let mut db = database.lock().unwrap();
+            db.get_tables_and_views((!schema.is_empty()).then_some(schema.as_str()))
+                .await
+                .map_err(|e| e.to_string())?
+        };
+        snapshot.tables.insert(schema, names);
+    }
+    for table in tables {
+        let columns = {
+            let mut db = database.lock().unwrap();
+            db.get_columns(&table).await.unwrap_or_default()
+        };
+        if !columns.is_empty() {
+            snapshot.columns.insert(table, columns);
+        }
+    }
+    for table in meta_tables {
+        let meta = {
+            let mut db = database.lock().unwrap();
+            fetch_column_meta(&mut db, &table).await
+        };
+        if !meta.is_empty() {
+            snapshot.column_meta.insert(table, meta);
+        }
+    }
+
+    let summary = RefreshSummary {
+        tables: snapshot.tables.values().map(Vec::len).sum(),
+        column_lists: snapshot.columns.len(),
+    };
+    let mut db = database.lock().unwrap();
+    // Dropped if the connection or schema changed while reading
+    if db.schema_cache_mut().fingerprint() == fingerprint.as_deref() {
+        db.schema_cache_mut().replace(snapshot);
+    }
+    Ok(summary)
+}
+
+/// Start [`refresh`] on a blocking thread when the snapshot loaded for the
+/// connection has something to bring up to date
+pub fn refresh_in_background(database: Arc<Mutex<Database>>) {
+    if database.lock().unwrap().schema_cache_mut().is_empty() {
+        return;
+    }
+    let Ok(handle) = tokio::runtime::Handle::try_current() else {
+        return;
+    };
+    tokio::task::spawn_blocking(move || {
+        if let Err(e) = handle.block_on(refresh(&database)) {
+            debug!("Background schema cache refresh failed: {}", e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_and_replace() {
+        let mut cache = SchemaCache::load(&fingerprint("postgresql:db:5432:app:shop"), false);
+        assert!(cache.is_empty());
+
+        cache.store_tables("", &["orders".to_string(), "users".to_string()]);
+        cache.store_columns("orders", &[]);
+        cache.store_columns("users", &["id".to_string(), "email".to_string()]);
+        assert_eq!(cache.tables("").unwrap().len(), 2);
+        assert!(cache.columns("orders").is_none());
+        assert_eq!(cache.columns("users").unwrap()[1], "email");
+
+        cache.replace(SchemaSnapshot::default());
+        assert!(cache.is_empty());
+        assert_eq!(cache.generation(), 1);
+        assert_eq!(fingerprint("a").len(), 16);
+        assert_ne!(fingerprint("a"), fingerprint("b"));
+    }
+
+    #[test]
+    fn test_column_meta_summary() {
+        let id = ColumnMeta {
+            data_type: "integer".to_string(),
+            primary_key: true,
+            distinct: Some(1204),
+            ..Default::default()
+        };
+        assert_eq!(id.summary(), "integer · PK · not null · ~1,204 distinct");
+        let user_id = ColumnMeta {
+            data_type: "bigint".to_string(),
+            nullable: true,
+            references: Some("users".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(user_id.summary(), "bigint · FK → users · null");
+
+        let fk = parse_foreign_key("FOREIGN KEY (user_id) REFERENCES users(id)").unwrap();
+        assert_eq!(fk.columns, ["user_id"]);
+        assert_eq!(
+            (fk.table.as_str(), fk.referenced),
+            ("users", vec!["id".to_string()])
+        );
+        let fk = parse_foreign_key(
+            r#"FOREIGN KEY (a, "b") REFERENCES shop."order" (x, y) ON DELETE CASCADE"#,
+        )
+        .unwrap();
+        assert_eq!(fk.columns, ["a", "b"]);
+        assert_eq!(fk.table, "shop.order");
+        assert_eq!(fk.referenced, ["x", "y"]);
+    }
+}
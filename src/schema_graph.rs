@@ -0,0 +1,585 @@
+//! Foreign key graph rendering for the `\erd` command
+//!
+//! Builds an entity-relationship graph from the FK metadata that the schema viewer
+//! already loads (PostgreSQL, MySQL and SQLite) and renders it either as a terminal
+//! diagram — one box per table listing its key columns, with arrows to the tables
+//! they reference — or as Graphviz DOT / Mermaid source for external tooling.
+
+use crate::database::{DatabaseType, DatabaseTypeExt};
+use crate::db::Database;
+use crate::schema_tui::schema_data::{Relationship, load_relationships};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::str::FromStr;
+use textwrap::core::display_width;
+
+/// Output format for `\erd`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErdFormat {
+    /// Box-drawing characters
+    #[default]
+    Unicode,
+    /// Plain ASCII, for terminals or logs that mangle box-drawing characters
+    Ascii,
+    /// Graphviz DOT
+    Dot,
+    /// Mermaid `erDiagram`
+    Mermaid,
+}
+
+impl fmt::Display for ErdFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ErdFormat::Unicode => "unicode",
+            ErdFormat::Ascii => "ascii",
+            ErdFormat::Dot => "dot",
+            ErdFormat::Mermaid => "mermaid",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for ErdFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "unicode" => Ok(ErdFormat::Unicode),
+            "ascii" => Ok(ErdFormat::Ascii),
+            "dot" | "graphviz" => Ok(ErdFormat::Dot),
+            "mermaid" => Ok(ErdFormat::Mermaid),
+            _ => Err(format!(
+                "Unknown ERD format '{s}'. Valid formats: unicode, ascii, dot, mermaid"
+            )),
+        }
+    }
+}
+
+/// Which part of the database to draw
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErdScope {
+    /// Every table that takes part in a foreign key
+    All,
+    /// All tables of one schema, including those without foreign keys
+    Schema(String),
+    /// One table and the tables it references or is referenced by
+    Table {
+        schema: Option<String>,
+        name: String,
+    },
+}
+
+/// A table node in the graph
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphTable {
+    pub schema: String,
+    pub name: String,
+}
+
+/// Tables and the foreign keys between them
+#[derive(Debug, Clone, Default)]
+pub struct SchemaGraph {
+    pub tables: Vec<GraphTable>,
+    pub relationships: Vec<Relationship>,
+}
+
+/// One row of a table box: a column list with the edges attached to it
+#[derive(Debug, Default)]
+struct KeyRow {
+    /// `table.columns` this row references
+    references: Vec<String>,
+    /// `table.columns` referencing this row
+    referenced_by: Vec<String>,
+}
+
+impl SchemaGraph {
+    /// Build the graph for a scope from loaded relationships. `schema_tables` lists
+    /// the tables of the scoped schema so that tables without foreign keys are drawn too.
+    pub fn build(
+        relationships: Vec<Relationship>,
+        scope: &ErdScope,
+        schema_tables: &[String],
+    ) -> Self {
+        let relationships: Vec<Relationship> = relationships
+            .into_iter()
+            .filter(|rel| match scope {
+                ErdScope::All => true,
+                ErdScope::Schema(schema) => {
+                    rel.source_schema == *schema && rel.target_schema == *schema
+                }
+                ErdScope::Table { schema, name } => {
+                    let matches = |s: &str, t: &str| {
+                        t == name && schema.as_deref().is_none_or(|schema| schema == s)
+                    };
+                    matches(&rel.source_schema, &rel.source_table)
+                        || matches(&rel.target_schema, &rel.target_table)
+                }
+            })
+            .collect();
+
+        let mut nodes: BTreeSet<(String, String)> = BTreeSet::new();
+        for rel in &relationships {
+            nodes.insert((rel.source_schema.clone(), rel.source_table.clone()));
+            nodes.insert((rel.target_schema.clone(), rel.target_table.clone()));
+        }
+        match scope {
+            ErdScope::Schema(schema) => {
+                for table in schema_tables {
+                    nodes.insert((schema.clone(), table.clone()));
+                }
+            }
+            ErdScope::Table {
+                schema: Some(schema),
+                name,
+            } if nodes.is_empty() => {
+                nodes.insert((schema.clone(), name.clone()));
+            }
+            _ => {}
+        }
+
+        let mut tables: Vec<GraphTable> = nodes
+            .into_iter()
+            .map(|(schema, name)| GraphTable { schema, name })
+            .collect();
+
+        // Referenced tables first, so the diagram reads top-down along the arrows
+        let depths = dependency_depths(&tables, &relationships);
+        tables.sort_by(|a, b| {
+            let key = |t: &GraphTable| depths.get(&(t.schema.clone(), t.name.clone())).copied();
+            key(a)
+                .cmp(&key(b))
+                .then_with(|| a.schema.cmp(&b.schema))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+
+        SchemaGraph {
+            tables,
+            relationships,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+
+    /// Qualify names with their schema only when the graph spans several schemas
+    fn display_name(&self, schema: &str, table: &str) -> String {
+        let multi_schema = self
+            .tables
+            .iter()
+            .any(|t| t.schema != self.tables[0].schema);
+        if multi_schema {
+            format!("{schema}.{table}")
+        } else {
+            table.to_string()
+        }
+    }
+
+    /// Key rows of a table: referenced columns first, then foreign key columns
+    fn key_rows(&self, table: &GraphTable) -> Vec<(String, KeyRow)> {
+        let mut incoming: BTreeMap<String, KeyRow> = BTreeMap::new();
+        let mut outgoing: BTreeMap<String, KeyRow> = BTreeMap::new();
+
+        for rel in &self.relationships {
+            if rel.target_schema == table.schema && rel.target_table == table.name {
+                let source = format!(
+                    "{}.{}",
+                    self.display_name(&rel.source_schema, &rel.source_table),
+                    rel.source_columns.join(", ")
+                );
+                incoming
+                    .entry(rel.target_columns.join(", "))
+                    .or_default()
+                    .referenced_by
+                    .push(source);
+            }
+        }
+        for rel in &self.relationships {
+            if rel.source_schema == table.schema && rel.source_table == table.name {
+                let target = format!(
+                    "{}.{}",
+                    self.display_name(&rel.target_schema, &rel.target_table),
+                    rel.target_columns.join(", ")
+                );
+                let columns = rel.source_columns.join(", ");
+                // A column can be both referenced and a foreign key (1:1 tables)
+                match incoming.get_mut(&columns) {
+                    Some(row) => row.references.push(target),
+                    None => outgoing.entry(columns).or_default().references.push(target),
+                }
+            }
+        }
+
+        incoming.into_iter().chain(outgoing).collect()
+    }
+
+    /// Terminal diagram (Unicode or ASCII)
+    pub fn render_text(&self, format: ErdFormat) -> String {
+        let ascii = format == ErdFormat::Ascii;
+        let (tl, tr, bl, br, h, v, lt, rt) = if ascii {
+            ('+', '+', '+', '+', '-', '|', '+', '+')
+        } else {
+            ('┌', '┐', '└', '┘', '─', '│', '├', '┤')
+        };
+        let (out_arrow, in_arrow) = if ascii {
+            ("--> ", "<-- ")
+        } else {
+            ("──▶ ", "◀── ")
+        };
+
+        let mut blocks = Vec::new();
+        for table in &self.tables {
+            let title = self.display_name(&table.schema, &table.name);
+            let rows = self.key_rows(table);
+            let width = rows
+                .iter()
+                .map(|(columns, _)| display_width(columns))
+                .chain(std::iter::once(display_width(&title)))
+                .max()
+                .unwrap_or(0)
+                + 2;
+
+            let pad = |text: &str| {
+                format!(
+                    "{v} {text}{} {v}",
+                    " ".repeat(width - 2 - display_width(text))
+                )
+            };
+            let rule = h.to_string().repeat(width);
+
+            let mut lines = vec![format!("{tl}{rule}{tr}"), pad(&title)];
+            if !rows.is_empty() {
+                lines.push(format!("{lt}{rule}{rt}"));
+            }
+            for (columns, row) in &rows {
+                let mut line = pad(columns);
+                let edges: Vec<String> = row
+                    .references
+                    .iter()
+                    .map(|target| format!("{out_arrow}{target}"))
+                    .chain(
+                        row.referenced_by
+                            .iter()
+                            .map(|source| format!("{in_arrow}{source}")),
+                    )
+                    .collect();
+                if !edges.is_empty() {
+                    line.push(' ');
+                    line.push_str(&edges.join("  "));
+                }
+                lines.push(line);
+            }
+            lines.push(format!("{bl}{rule}{br}"));
+            blocks.push(lines.join("\n"));
+        }
+
+        let mut out = blocks.join("\n\n");
+        if !out.is_empty() {
+            out.push_str("\n\n");
+        }
+        out.push_str(&self.summary());
+        out
+    }
+
+    /// Graphviz DOT source
+    pub fn render_dot(&self) -> String {
+        let mut out = String::from(
+            "digraph erd {\n    rankdir=LR;\n    node [shape=record, fontname=\"Helvetica\"];\n",
+        );
+        for table in &self.tables {
+            let rows: Vec<String> = self
+                .key_rows(table)
+                .into_iter()
+                .map(|(columns, _)| dot_record_escape(&columns))
+                .collect();
+            let label = if rows.is_empty() {
+                dot_record_escape(&table.name)
+            } else {
+                format!(
+                    "{{{}|{}\\l}}",
+                    dot_record_escape(&table.name),
+                    rows.join("\\l")
+                )
+            };
+            out.push_str(&format!(
+                "    \"{}\" [label=\"{label}\"];\n",
+                dot_id_escape(&format!("{}.{}", table.schema, table.name))
+            ));
+        }
+        for rel in &self.relationships {
+            out.push_str(&format!(
+                "    \"{}\" -> \"{}\" [label=\"{} → {}\"];\n",
+                dot_id_escape(&format!("{}.{}", rel.source_schema, rel.source_table)),
+                dot_id_escape(&format!("{}.{}", rel.target_schema, rel.target_table)),
+                dot_id_escape(&rel.source_columns.join(", ")),
+                dot_id_escape(&rel.target_columns.join(", "))
+            ));
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Mermaid `erDiagram` source
+    pub fn render_mermaid(&self) -> String {
+        let mut out = String::from("erDiagram\n");
+        for table in &self.tables {
+            let entity = mermaid_ident(&self.display_name(&table.schema, &table.name));
+            let mut attributes = Vec::new();
+            for (columns, row) in self.key_rows(table) {
+                let is_fk = !row.references.is_empty();
+                for column in columns.split(", ") {
+                    let marker = if is_fk { " FK" } else { "" };
+                    attributes.push(format!("        key {}{marker}", mermaid_ident(column)));
+                }
+            }
+            if attributes.is_empty() {
+                out.push_str(&format!("    {entity} {{\n    }}\n"));
+            } else {
+                out.push_str(&format!(
+                    "    {entity} {{\n{}\n    }}\n",
+                    attributes.join("\n")
+                ));
+            }
+        }
+        for rel in &self.relationships {
+            out.push_str(&format!(
+                "    {} ||--o{{ {} : \"{}\"\n",
+                mermaid_ident(&self.display_name(&rel.target_schema, &rel.target_table)),
+                mermaid_ident(&self.display_name(&rel.source_schema, &rel.source_table)),
+                rel.source_columns.join(", ").replace('"', "'")
+            ));
+        }
+        out
+    }
+
+    pub fn render(&self, format: ErdFormat) -> String {
+        match format {
+            ErdFormat::Unicode | ErdFormat::Ascii => self.render_text(format),
+            ErdFormat::Dot => self.render_dot(),
+            ErdFormat::Mermaid => self.render_mermaid(),
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "({} table{}, {} foreign key{})",
+            self.tables.len(),
+            if self.tables.len() == 1 { "" } else { "s" },
+            self.relationships.len(),
+            if self.relationships.len() == 1 {
+                ""
+            } else {
+                "s"
+            }
+        )
+    }
+}
+
+/// Longest chain of foreign keys from each table to a table that references nothing.
+/// Cycles (self-references, mutual FKs) are cut off after `tables.len()` rounds.
+fn dependency_depths(
+    tables: &[GraphTable],
+    relationships: &[Relationship],
+) -> BTreeMap<(String, String), usize> {
+    let mut depths: BTreeMap<(String, String), usize> = tables
+        .iter()
+        .map(|t| ((t.schema.clone(), t.name.clone()), 0))
+        .collect();
+
+    for _ in 0..tables.len() {
+        let mut changed = false;
+        for rel in relationships {
+            let source = (rel.source_schema.clone(), rel.source_table.clone());
+            let target = (rel.target_schema.clone(), rel.target_table.clone());
+            if source == target {
+                continue;
+            }
+            let candidate = depths.get(&target).copied().unwrap_or(0) + 1;
+            if let Some(depth) = depths.get_mut(&source)
+                && candidate > *depth
+            {
+                *depth = candidate;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    depths
+}
+
+fn dot_id_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn dot_record_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in dot_id_escape(s).chars() {
+        if matches!(c, '{' | '}' | '|' | '<' | '>') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Mermaid entity and attribute names only allow word characters and hyphens
+fn mermaid_ident(s: &str) -> String {
+    s.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Load the FK graph for `target`: a schema name, a table, `schema.table`, or
+/// everything when `None`.
+pub async fn load_schema_graph(
+    database: &mut Database,
+    target: Option<&str>,
+) -> Result<SchemaGraph, String> {
+    let db_type = database.get_database_type();
+    if !matches!(
+        db_type,
+        DatabaseType::PostgreSQL | DatabaseType::MySQL | DatabaseType::SQLite
+    ) {
+        return Err(format!(
+            "\\erd is not supported for {} (PostgreSQL, MySQL and SQLite only)",
+            db_type.display_name()
+        ));
+    }
+
+    let relationships = load_relationships(database, &db_type).await;
+
+    let client = database
+        .get_database_client()
+        .ok_or_else(|| "No database client available".to_string())?;
+    let metadata = client.get_metadata_provider();
+
+    let (scope, schema_tables) = match target {
+        None => (ErdScope::All, Vec::new()),
+        Some(target) => {
+            let schemas = metadata.get_schemas().await.unwrap_or_default();
+            if schemas.iter().any(|s| s == target) {
+                let tables = metadata.get_tables(Some(target)).await.unwrap_or_default();
+                (ErdScope::Schema(target.to_string()), tables)
+            } else {
+                let scope = match target.split_once('.') {
+                    Some((schema, name)) => ErdScope::Table {
+                        schema: Some(schema.to_string()),
+                        name: name.to_string(),
+                    },
+                    None => ErdScope::Table {
+                        schema: None,
+                        name: target.to_string(),
+                    },
+                };
+                (scope, Vec::new())
+            }
+        }
+    };
+
+    let graph = SchemaGraph::build(relationships, &scope, &schema_tables);
+    if graph.is_empty() {
+        return Err(match scope {
+            ErdScope::All => "No foreign key relationships found".to_string(),
+            ErdScope::Schema(schema) => format!("No tables found in schema '{schema}'"),
+            ErdScope::Table { name, .. } => {
+                format!("No schema or table with foreign keys named '{name}'")
+            }
+        });
+    }
+    Ok(graph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn rel(source: &str, source_cols: &[&str], target: &str, target_cols: &[&str]) -> Relationship {
+        Relationship {
+            constraint_name: format!("{source}_fk"),
+            source_schema: "public".to_string(),
+            source_table: source.to_string(),
+            source_columns: source_cols.iter().map(|c| c.to_string()).collect(),
+            target_schema: "public".to_string(),
+            target_table: target.to_string(),
+            target_columns: target_cols.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+
+    fn shop() -> Vec<Relationship> {
+        vec![
+            rel("orders", &["user_id"], "users", &["id"]),
+            rel("order_items", &["order_id"], "orders", &["id"]),
+            rel("reviews", &["user_id"], "users", &["id"]),
+        ]
+    }
+
+    #[rstest]
+    #[case("dot", ErdFormat::Dot)]
+    #[case("Mermaid", ErdFormat::Mermaid)]
+    #[case("ascii", ErdFormat::Ascii)]
+    fn test_format_from_str(#[case] input: &str, #[case] expected: ErdFormat) {
+        assert_eq!(input.parse::<ErdFormat>().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_build_orders_referenced_tables_first() {
+        let graph = SchemaGraph::build(shop(), &ErdScope::All, &[]);
+        let names: Vec<&str> = graph.tables.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["users", "orders", "reviews", "order_items"]);
+    }
+
+    #[test]
+    fn test_build_table_scope_keeps_direct_neighbours() {
+        let scope = ErdScope::Table {
+            schema: None,
+            name: "orders".to_string(),
+        };
+        let graph = SchemaGraph::build(shop(), &scope, &[]);
+        assert_eq!(graph.relationships.len(), 2);
+        assert!(!graph.tables.iter().any(|t| t.name == "reviews"));
+    }
+
+    #[test]
+    fn test_build_schema_scope_includes_isolated_tables() {
+        let scope = ErdScope::Schema("public".to_string());
+        let graph = SchemaGraph::build(shop(), &scope, &["settings".to_string()]);
+        assert!(graph.tables.iter().any(|t| t.name == "settings"));
+        assert_eq!(graph.tables.len(), 5);
+    }
+
+    #[test]
+    fn test_render_text_draws_arrows() {
+        let graph = SchemaGraph::build(shop(), &ErdScope::All, &[]);
+        let unicode = graph.render(ErdFormat::Unicode);
+        assert!(unicode.contains("│ user_id │ ──▶ users.id"));
+        assert!(unicode.contains("◀── orders.user_id  ◀── reviews.user_id"));
+        assert!(unicode.ends_with("(4 tables, 3 foreign keys)"));
+
+        let ascii = graph.render(ErdFormat::Ascii);
+        assert!(ascii.is_ascii());
+        assert!(ascii.contains("| user_id | --> users.id"));
+    }
+
+    #[test]
+    fn test_render_exports() {
+        let graph = SchemaGraph::build(shop(), &ErdScope::All, &[]);
+        let dot = graph.render_dot();
+        assert!(dot.starts_with("digraph erd {"));
+        assert!(dot.contains("\"public.orders\" -> \"public.users\" [label=\"user_id → id\"];"));
+
+        let mermaid = graph.render_mermaid();
+        assert!(mermaid.starts_with("erDiagram\n"));
+        assert!(mermaid.contains("users ||--o{ orders : \"user_id\""));
+        assert!(mermaid.contains("key user_id FK"));
+    }
+}
@@ -1,9 +1,10 @@
 //! Application state and event handling for the schema TUI viewer
 
-use super::schema_data::{Relationship, SchemaData, TableListEntry};
+use super::schema_data::{Relationship, SchemaData, TableItemKind, TableListEntry};
+use crate::database::DatabaseType;
 use crate::db::{Database, TableDetails};
 use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
@@ -15,15 +16,43 @@ pub enum PanelFocus {
     Relationships,
 }
 
+/// `\sv` (table list, details, FK panel) or `\browse` (tree and preview)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ViewerMode {
+    Viewer,
+    Browser,
+}
+
+impl ViewerMode {
+    /// The viewer only selects tables; the browser tree selects every node
+    fn selects(self, entry: &TableListEntry) -> bool {
+        match self {
+            ViewerMode::Viewer => entry.is_table(),
+            ViewerMode::Browser => true,
+        }
+    }
+}
+
+/// Rows shown in the browser preview pane
+const PREVIEW_ROWS: usize = 10;
+
 /// The main application state for the schema TUI viewer
 pub struct SchemaTuiApp {
     pub schema_data: SchemaData,
+    pub mode: ViewerMode,
     pub should_quit: bool,
     pub show_help: bool,
+    /// Text to put in the REPL prompt after quitting (browser Enter)
+    pub insert_text: Option<String>,
 
     // Database handle for lazy-loading details
     database: Arc<Mutex<Database>>,
     pub(super) details_cache: HashMap<(String, String), TableDetails>,
+    pub(super) preview_cache: HashMap<(String, String), Result<Vec<Vec<String>>, String>>,
+
+    // Browser tree state
+    pub collapsed_schemas: HashSet<String>,
+    pub expanded_tables: HashSet<(String, String)>,
 
     // Left panel: table list
     pub table_list: Vec<TableListEntry>,
@@ -33,8 +62,9 @@ pub struct SchemaTuiApp {
     pub search_query: String,
     pub search_active: bool,
 
-    // Center panel: table details
+    // Center panel: table details (browser: sample rows, plus horizontal scroll)
     pub center_scroll: u16,
+    pub preview_hscroll: u16,
 
     // Right panel: relationships
     pub selected_relationship_idx: usize,
@@ -56,10 +86,15 @@ impl SchemaTuiApp {
 
         Self {
             schema_data,
+            mode: ViewerMode::Viewer,
             should_quit: false,
             show_help: false,
+            insert_text: None,
             database,
             details_cache: HashMap::new(),
+            preview_cache: HashMap::new(),
+            collapsed_schemas: HashSet::new(),
+            expanded_tables: HashSet::new(),
             table_list: table_list.clone(),
             all_table_list: table_list,
             selected_table_idx: 0,
@@ -67,6 +102,7 @@ impl SchemaTuiApp {
             search_query: String::new(),
             search_active: false,
             center_scroll: 0,
+            preview_hscroll: 0,
             selected_relationship_idx: 0,
             right_scroll: 0,
             focus: PanelFocus::TableList,
@@ -76,6 +112,14 @@ impl SchemaTuiApp {
         }
     }
 
+    /// `\browse`: schema tree on the left, sample rows and DDL on the right
+    pub fn browser(schema_data: SchemaData, database: Arc<Mutex<Database>>) -> Self {
+        let mut app = Self::new(schema_data, database);
+        app.mode = ViewerMode::Browser;
+        app.apply_search_filter();
+        app
+    }
+
     /// Get cached table details, loading on-demand if not yet cached.
     /// Returns None if loading fails or no table is selected.
     pub fn get_or_load_details(&mut self, schema: &str, table: &str) -> Option<&TableDetails> {
@@ -89,6 +133,42 @@ impl SchemaTuiApp {
         self.details_cache.get(&key)
     }
 
+    /// Get cached sample rows for the browser preview, loading on first use
+    pub fn get_or_load_preview(
+        &mut self,
+        schema: &str,
+        table: &str,
+    ) -> &Result<Vec<Vec<String>>, String> {
+        let key = (schema.to_string(), table.to_string());
+        let query = format!(
+            "SELECT * FROM {} LIMIT {PREVIEW_ROWS}",
+            self.qualified_name(schema, table)
+        );
+        let database = self.database.clone();
+        self.preview_cache
+            .entry(key)
+            .or_insert_with(|| load_preview_rows(&database, &query))
+    }
+
+    /// Table name as typed at the prompt: schema-qualified unless it is the
+    /// default one, quoted only where the backend needs it
+    pub fn qualified_name(&self, schema: &str, table: &str) -> String {
+        let db_type = &self.schema_data.database_type;
+        let default_schema = schema.is_empty()
+            || schema == "public"
+            || schema == "main"
+            || schema == self.schema_data.database_name;
+        if default_schema {
+            prompt_identifier(table, db_type)
+        } else {
+            format!(
+                "{}.{}",
+                prompt_identifier(schema, db_type),
+                prompt_identifier(table, db_type)
+            )
+        }
+    }
+
     /// Get the currently selected table's (schema, name) if any
     pub fn selected_table_key(&self) -> Option<(String, String)> {
         self.table_list
@@ -217,21 +297,190 @@ impl SchemaTuiApp {
                             filtered.push(entry.clone());
                         }
                     }
+                    // Child rows are added by expand_tree, never stored here
+                    TableListEntry::Item { .. } => {}
                 }
             }
 
             self.table_list = filtered;
         }
+        if self.mode == ViewerMode::Browser {
+            self.expand_tree();
+        }
 
         // Reset selection
+        let mode = self.mode;
         self.selected_table_idx = self
             .table_list
             .iter()
-            .position(|e| e.is_table())
+            .position(|e| mode.selects(e))
             .unwrap_or(0);
         self.table_list_scroll = 0;
     }
 
+    /// Hide the tables of collapsed schemas and add the child rows of
+    /// expanded tables to the (filtered) table list
+    fn expand_tree(&mut self) {
+        let base = std::mem::take(&mut self.table_list);
+        let mut tree = Vec::with_capacity(base.len());
+        for entry in base {
+            if let TableListEntry::Table { name, schema, .. } = &entry {
+                if self.collapsed_schemas.contains(schema) {
+                    continue;
+                }
+                let key = (schema.clone(), name.clone());
+                let expanded = self.expanded_tables.contains(&key);
+                tree.push(entry);
+                if expanded {
+                    let items = self.table_items(&key.0, &key.1);
+                    tree.extend(items);
+                }
+            } else {
+                tree.push(entry);
+            }
+        }
+        self.table_list = tree;
+    }
+
+    /// Child rows of an expanded table, honouring the index/constraint toggles
+    fn table_items(&mut self, schema: &str, table: &str) -> Vec<TableListEntry> {
+        let (show_indexes, show_constraints) = (self.show_indexes, self.show_constraints);
+        let Some(details) = self.get_or_load_details(schema, table) else {
+            return Vec::new();
+        };
+        let item = |kind, name: &str, detail: String| TableListEntry::Item {
+            schema: schema.to_string(),
+            table: table.to_string(),
+            kind,
+            name: name.to_string(),
+            detail,
+        };
+
+        let mut items: Vec<TableListEntry> = details
+            .columns
+            .iter()
+            .map(|col| {
+                let nullability = if col.nullable { "" } else { " NOT NULL" };
+                item(
+                    TableItemKind::Column,
+                    &col.name,
+                    format!("{}{nullability}", col.data_type),
+                )
+            })
+            .collect();
+        if show_indexes {
+            items.extend(details.indexes.iter().map(|idx| {
+                let kind = if idx.is_primary {
+                    "PRIMARY".to_string()
+                } else if idx.is_unique {
+                    "UNIQUE".to_string()
+                } else {
+                    idx.index_type.clone()
+                };
+                item(TableItemKind::Index, &idx.name, kind)
+            }));
+        }
+        if show_constraints {
+            items.extend(
+                details
+                    .check_constraints
+                    .iter()
+                    .map(|chk| item(TableItemKind::Constraint, &chk.name, "CHECK".to_string())),
+            );
+            items.extend(details.foreign_keys.iter().map(|fk| {
+                item(
+                    TableItemKind::Constraint,
+                    &fk.name,
+                    "FOREIGN KEY".to_string(),
+                )
+            }));
+        }
+        items
+    }
+
+    /// Rebuild the browser tree after an expand/collapse, keeping the cursor
+    /// on the same node (or `fallback` when that node disappeared)
+    fn rebuild_tree(&mut self, fallback: Option<TableListEntry>) {
+        let selected = self.table_list.get(self.selected_table_idx).cloned();
+        self.apply_search_filter();
+
+        let find =
+            |target: &TableListEntry| self.table_list.iter().position(|e| e.same_node(target));
+        if let Some(idx) = selected
+            .as_ref()
+            .and_then(find)
+            .or_else(|| fallback.as_ref().and_then(find))
+        {
+            self.selected_table_idx = idx;
+        }
+        self.ensure_selected_visible();
+    }
+
+    /// Browser: expand (`Some(true)`), collapse (`Some(false)`) or toggle the selected node
+    pub fn set_expanded(&mut self, expand: Option<bool>) {
+        let Some(entry) = self.table_list.get(self.selected_table_idx).cloned() else {
+            return;
+        };
+        match &entry {
+            TableListEntry::SchemaHeader { name } => {
+                let collapsed = self.collapsed_schemas.contains(name);
+                if expand.unwrap_or(collapsed) {
+                    self.collapsed_schemas.remove(name);
+                } else {
+                    self.collapsed_schemas.insert(name.clone());
+                }
+                self.rebuild_tree(None);
+            }
+            TableListEntry::Table { .. } => {
+                let Some(key) = entry.table_key() else { return };
+                let expanded = self.expanded_tables.contains(&key);
+                if expand.unwrap_or(!expanded) {
+                    self.expanded_tables.insert(key);
+                } else if expanded {
+                    self.expanded_tables.remove(&key);
+                } else if let Some(header) = self.schema_header_of(&key.0) {
+                    // Left on a collapsed table climbs to its schema
+                    self.selected_table_idx = header;
+                    return;
+                }
+                self.rebuild_tree(None);
+            }
+            TableListEntry::Item { .. } => {
+                // Collapsing from a child row folds its table and selects it
+                if expand != Some(true)
+                    && let Some(key) = entry.table_key()
+                {
+                    self.expanded_tables.remove(&key);
+                    let parent = self
+                        .table_list
+                        .iter()
+                        .find(|e| e.is_table() && e.table_key().as_ref() == Some(&key))
+                        .cloned();
+                    self.rebuild_tree(parent);
+                }
+            }
+        }
+    }
+
+    fn schema_header_of(&self, schema: &str) -> Option<usize> {
+        self.table_list
+            .iter()
+            .position(|e| matches!(e, TableListEntry::SchemaHeader { name } if name == schema))
+    }
+
+    /// Browser Enter: leave the TUI with the selected name for the prompt
+    pub fn insert_selected(&mut self) {
+        let db_type = self.schema_data.database_type.clone();
+        let text = match self.table_list.get(self.selected_table_idx) {
+            Some(TableListEntry::Table { name, schema, .. }) => self.qualified_name(schema, name),
+            Some(TableListEntry::Item { name, .. }) => prompt_identifier(name, &db_type),
+            Some(TableListEntry::SchemaHeader { name }) => prompt_identifier(name, &db_type),
+            None => return,
+        };
+        self.insert_text = Some(text);
+        self.should_quit = true;
+    }
+
     /// Move selection down in the focused panel
     pub fn move_down(&mut self) {
         match self.focus {
@@ -242,7 +491,7 @@ impl SchemaTuiApp {
                 }
                 let mut next = self.selected_table_idx + 1;
                 // Skip schema headers
-                while next < len && !self.table_list[next].is_table() {
+                while next < len && !self.mode.selects(&self.table_list[next]) {
                     next += 1;
                 }
                 if next < len {
@@ -280,10 +529,14 @@ impl SchemaTuiApp {
                     return;
                 }
                 let mut prev = self.selected_table_idx - 1;
-                while prev > 0 && !self.table_list[prev].is_table() {
+                while prev > 0 && !self.mode.selects(&self.table_list[prev]) {
                     prev -= 1;
                 }
-                if self.table_list.get(prev).is_some_and(|e| e.is_table()) {
+                if self
+                    .table_list
+                    .get(prev)
+                    .is_some_and(|e| self.mode.selects(e))
+                {
                     self.selected_table_idx = prev;
                     self.center_scroll = 0;
                     self.selected_relationship_idx = 0;
@@ -314,10 +567,11 @@ impl SchemaTuiApp {
     pub fn jump_first(&mut self) {
         match self.focus {
             PanelFocus::TableList => {
+                let mode = self.mode;
                 self.selected_table_idx = self
                     .table_list
                     .iter()
-                    .position(|e| e.is_table())
+                    .position(|e| mode.selects(e))
                     .unwrap_or(0);
                 self.center_scroll = 0;
                 self.selected_relationship_idx = 0;
@@ -341,7 +595,7 @@ impl SchemaTuiApp {
                 let len = self.table_list.len();
                 if len > 0 {
                     let mut idx = len - 1;
-                    while idx > 0 && !self.table_list[idx].is_table() {
+                    while idx > 0 && !self.mode.selects(&self.table_list[idx]) {
                         idx -= 1;
                     }
                     self.selected_table_idx = idx;
@@ -401,15 +655,20 @@ impl SchemaTuiApp {
 
     /// Cycle panel focus forward
     pub fn next_focus(&mut self) {
-        self.focus = match self.focus {
-            PanelFocus::TableList => PanelFocus::Details,
-            PanelFocus::Details => PanelFocus::Relationships,
-            PanelFocus::Relationships => PanelFocus::TableList,
+        self.focus = match (self.mode, self.focus) {
+            (ViewerMode::Browser, PanelFocus::TableList) => PanelFocus::Details,
+            (ViewerMode::Browser, _) => PanelFocus::TableList,
+            (_, PanelFocus::TableList) => PanelFocus::Details,
+            (_, PanelFocus::Details) => PanelFocus::Relationships,
+            (_, PanelFocus::Relationships) => PanelFocus::TableList,
         };
     }
 
     /// Cycle panel focus backward
     pub fn prev_focus(&mut self) {
+        if self.mode == ViewerMode::Browser {
+            return self.next_focus();
+        }
         self.focus = match self.focus {
             PanelFocus::TableList => PanelFocus::Relationships,
             PanelFocus::Details => PanelFocus::TableList,
@@ -508,19 +767,52 @@ impl SchemaTuiApp {
                 self.search_active = true;
             }
 
-            // Enter: follow FK in relationships panel, or select table in table list
+            // Enter: follow FK in relationships panel; in the browser, insert
+            // the selected name into the prompt
             KeyCode::Enter => {
-                if self.focus == PanelFocus::Relationships {
+                if self.mode == ViewerMode::Browser {
+                    self.insert_selected();
+                } else if self.focus == PanelFocus::Relationships {
                     self.follow_relationship();
                 }
             }
 
+            // Browser tree expand/collapse
+            KeyCode::Right | KeyCode::Char('l')
+                if self.mode == ViewerMode::Browser && self.focus == PanelFocus::TableList =>
+            {
+                self.set_expanded(Some(true));
+            }
+            KeyCode::Left | KeyCode::Char('h')
+                if self.mode == ViewerMode::Browser && self.focus == PanelFocus::TableList =>
+            {
+                self.set_expanded(Some(false));
+            }
+            KeyCode::Char(' ')
+                if self.mode == ViewerMode::Browser && self.focus == PanelFocus::TableList =>
+            {
+                self.set_expanded(None);
+            }
+            // Wide sample rows scroll sideways in the preview
+            KeyCode::Right | KeyCode::Char('l') if self.mode == ViewerMode::Browser => {
+                self.preview_hscroll = self.preview_hscroll.saturating_add(4);
+            }
+            KeyCode::Left | KeyCode::Char('h') if self.mode == ViewerMode::Browser => {
+                self.preview_hscroll = self.preview_hscroll.saturating_sub(4);
+            }
+
             // Toggle visibility
             KeyCode::Char('i') => {
                 self.show_indexes = !self.show_indexes;
+                if self.mode == ViewerMode::Browser {
+                    self.rebuild_tree(None);
+                }
             }
             KeyCode::Char('c') => {
                 self.show_constraints = !self.show_constraints;
+                if self.mode == ViewerMode::Browser {
+                    self.rebuild_tree(None);
+                }
             }
 
             // Scrolling with Ctrl
@@ -600,6 +892,46 @@ fn load_table_details(
     })
 }
 
+/// Sample rows for the browser preview (header row first), same sync
+/// wrapper as `load_table_details`
+#[allow(clippy::await_holding_lock)]
+fn load_preview_rows(
+    database: &Arc<Mutex<Database>>,
+    query: &str,
+) -> Result<Vec<Vec<String>>, String> {
+    let db = database.clone();
+    let interrupt = Arc::new(std::sync::atomic::AtomicBool::new(false));
+
+    tokio::task::block_in_place(|| {
+        let handle = tokio::runtime::Handle::current();
+        handle.block_on(async {
+            let mut db_guard = db.lock().unwrap();
+            db_guard
+                .execute_query_with_interrupt_no_column_selection(query, &interrupt)
+                .await
+                .map_err(|e| e.to_string())
+        })
+    })
+}
+
+/// Identifier as the user would type it: bare when it is a plain lowercase
+/// name, otherwise quoted for the backend
+fn prompt_identifier(ident: &str, db_type: &DatabaseType) -> String {
+    let plain = ident
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_lowercase() || c == '_')
+        && ident
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if plain && !crate::sql_parser_trait::parsing_utils::needs_quoting(ident, db_type.clone()) {
+        return ident.to_string();
+    }
+    let quote = crate::sql_parser_trait::parsing_utils::get_quote_char(db_type.clone());
+    let doubled = format!("{quote}{quote}");
+    format!("{quote}{}{quote}", ident.replace(quote, &doubled))
+}
+
 /// Build the flattened table list from schema data
 fn build_table_list(schema_data: &SchemaData) -> Vec<TableListEntry> {
     let mut list = Vec::new();
@@ -9,15 +9,20 @@
 //! - Toggle visibility for indexes and constraints
 //! - Keyboard-driven navigation
 //!
+//! `\browse` reuses the same state in browser mode: a schemas → tables →
+//! columns/indexes/constraints tree next to a preview of sample rows and DDL,
+//! where Enter puts the selected name into the REPL prompt.
+//!
 //! # Usage
 //!
-//! Run the `\sv` command in the REPL to launch the schema viewer.
+//! Run the `\sv` command in the REPL to launch the schema viewer, or
+//! `\browse` for the schema browser.
 
 mod app;
 pub mod schema_data;
 mod ui;
 
-pub use app::SchemaTuiApp;
+pub use app::{SchemaTuiApp, ViewerMode};
 pub use schema_data::{SchemaData, load_schema_data};
 
 use crate::db::Database;
@@ -33,9 +38,11 @@ use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 /// Result of running the schema TUI
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum TuiResult {
     Quit,
+    /// Browser Enter: text to insert into the REPL prompt
+    Insert(String),
 }
 
 /// Run the interactive schema TUI viewer
@@ -43,8 +50,18 @@ pub fn run_schema_tui(
     schema_data: SchemaData,
     database: Arc<Mutex<Database>>,
 ) -> Result<TuiResult, String> {
-    let mut app = SchemaTuiApp::new(schema_data, database);
+    run_app(SchemaTuiApp::new(schema_data, database))
+}
+
+/// Run the interactive schema browser (`\browse`)
+pub fn run_schema_browser(
+    schema_data: SchemaData,
+    database: Arc<Mutex<Database>>,
+) -> Result<TuiResult, String> {
+    run_app(SchemaTuiApp::browser(schema_data, database))
+}
 
+fn run_app(mut app: SchemaTuiApp) -> Result<TuiResult, String> {
     // Set up panic hook to restore terminal on panic
     let original_hook = panic::take_hook();
     panic::set_hook(Box::new(move |panic_info| {
@@ -85,7 +102,10 @@ fn run_tui_loop(app: &mut SchemaTuiApp) -> Result<TuiResult, String> {
         match app.poll_events(Duration::from_millis(100)) {
             Ok(_) => {
                 if app.should_quit {
-                    break Ok(TuiResult::Quit);
+                    break Ok(app
+                        .insert_text
+                        .take()
+                        .map_or(TuiResult::Quit, TuiResult::Insert));
                 }
             }
             Err(e) => {
@@ -176,4 +196,51 @@ mod tests {
         // First entry is schema header, so first table should be at index 1
         assert_eq!(app.table_list.len(), 3); // 1 header + 2 tables
     }
+
+    #[test]
+    fn test_schema_browser_tree_and_insert() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        use schema_data::{SchemaInfo, TableSummary};
+
+        let table = |schema: &str, name: &str| TableSummary {
+            name: name.to_string(),
+            schema: schema.to_string(),
+            outgoing_fk_count: 0,
+            incoming_fk_count: 0,
+        };
+        let schema_data = SchemaData {
+            database_name: "testdb".to_string(),
+            database_type: DatabaseType::PostgreSQL,
+            schemas: vec![
+                SchemaInfo {
+                    name: "billing".to_string(),
+                    tables: vec![table("billing", "Invoices")],
+                },
+                SchemaInfo {
+                    name: "public".to_string(),
+                    tables: vec![table("public", "users")],
+                },
+            ],
+            relationships: vec![],
+        };
+
+        let mut app = SchemaTuiApp::browser(schema_data, test_database());
+        assert_eq!(app.mode, ViewerMode::Browser);
+        // Schema headers are selectable tree nodes in the browser
+        assert_eq!(app.selected_table_idx, 0);
+
+        // Collapsing a schema hides its tables
+        app.set_expanded(Some(false));
+        assert_eq!(app.table_list.len(), 3);
+        app.set_expanded(Some(true));
+        assert_eq!(app.table_list.len(), 4);
+
+        let press = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        app.handle_key_event(press(KeyCode::Down));
+        app.handle_key_event(press(KeyCode::Enter));
+        assert!(app.should_quit);
+        assert_eq!(app.insert_text.as_deref(), Some("billing.\"Invoices\""));
+
+        assert_eq!(app.qualified_name("public", "users"), "users");
+    }
 }
@@ -40,6 +40,14 @@ pub struct Relationship {
     pub target_columns: Vec<String>,
 }
 
+/// Kind of a table child row in the `\browse` tree
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TableItemKind {
+    Column,
+    Index,
+    Constraint,
+}
+
 /// Flattened entry for the table list panel
 #[derive(Debug, Clone)]
 pub enum TableListEntry {
@@ -52,6 +60,14 @@ pub enum TableListEntry {
         outgoing_fk: usize,
         incoming_fk: usize,
     },
+    /// Column, index or constraint of an expanded table (browser tree only)
+    Item {
+        schema: String,
+        table: String,
+        kind: TableItemKind,
+        name: String,
+        detail: String,
+    },
 }
 
 impl TableListEntry {
@@ -59,10 +75,38 @@ impl TableListEntry {
         matches!(self, TableListEntry::Table { .. })
     }
 
+    /// The table this entry is, or belongs to
     pub fn table_key(&self) -> Option<(String, String)> {
         match self {
             TableListEntry::Table { name, schema, .. } => Some((schema.clone(), name.clone())),
-            _ => None,
+            TableListEntry::Item { schema, table, .. } => Some((schema.clone(), table.clone())),
+            TableListEntry::SchemaHeader { .. } => None,
+        }
+    }
+
+    /// Whether two entries denote the same tree node (selection survives rebuilds)
+    pub fn same_node(&self, other: &TableListEntry) -> bool {
+        match (self, other) {
+            (
+                TableListEntry::SchemaHeader { name: a },
+                TableListEntry::SchemaHeader { name: b },
+            ) => a == b,
+            (TableListEntry::Table { .. }, TableListEntry::Table { .. }) => {
+                self.table_key() == other.table_key()
+            }
+            (
+                TableListEntry::Item {
+                    kind: kind_a,
+                    name: name_a,
+                    ..
+                },
+                TableListEntry::Item {
+                    kind: kind_b,
+                    name: name_b,
+                    ..
+                },
+            ) => kind_a == kind_b && name_a == name_b && self.table_key() == other.table_key(),
+            _ => false,
         }
     }
 }
@@ -142,7 +186,10 @@ pub async fn load_schema_data(database: &mut Database) -> Result<SchemaData, Str
 }
 
 /// Load FK relationships using bulk queries where possible
-async fn load_relationships(database: &mut Database, db_type: &DatabaseType) -> Vec<Relationship> {
+pub(crate) async fn load_relationships(
+    database: &mut Database,
+    db_type: &DatabaseType,
+) -> Vec<Relationship> {
     match db_type {
         DatabaseType::PostgreSQL => load_postgresql_relationships(database).await,
         DatabaseType::MySQL => load_mysql_relationships(database).await,
@@ -325,6 +372,18 @@ mod tests {
             table.table_key(),
             Some(("public".to_string(), "users".to_string()))
         );
+
+        let column = TableListEntry::Item {
+            schema: "public".to_string(),
+            table: "users".to_string(),
+            kind: TableItemKind::Column,
+            name: "email".to_string(),
+            detail: "text".to_string(),
+        };
+        assert!(!column.is_table());
+        assert_eq!(column.table_key(), table.table_key());
+        assert!(column.same_node(&column.clone()));
+        assert!(!column.same_node(&table));
     }
 
     #[test]
@@ -1,7 +1,7 @@
 //! UI rendering for the schema TUI viewer
 
-use super::app::{PanelFocus, RelationshipEntry, SchemaTuiApp};
-use super::schema_data::TableListEntry;
+use super::app::{PanelFocus, RelationshipEntry, SchemaTuiApp, ViewerMode};
+use super::schema_data::{TableItemKind, TableListEntry};
 use crate::database::DatabaseTypeExt;
 use ratatui::{
     Frame,
@@ -32,7 +32,7 @@ pub fn render(frame: &mut Frame, app: &mut SchemaTuiApp) {
     render_footer(frame, app, main_chunks[2]);
 
     if app.show_help {
-        render_help_overlay(frame, area);
+        render_help_overlay(frame, app, area);
     }
 }
 
@@ -40,7 +40,10 @@ pub fn render(frame: &mut Frame, app: &mut SchemaTuiApp) {
 fn render_header(frame: &mut Frame, app: &SchemaTuiApp, area: Rect) {
     let title = Line::from(vec![
         Span::styled(
-            " Schema Viewer ",
+            match app.mode {
+                ViewerMode::Viewer => " Schema Viewer ",
+                ViewerMode::Browser => " Schema Browser ",
+            },
             Style::default()
                 .fg(Color::Cyan)
                 .add_modifier(Modifier::BOLD),
@@ -82,8 +85,22 @@ fn render_header(frame: &mut Frame, app: &SchemaTuiApp, area: Rect) {
     frame.render_widget(header, area);
 }
 
-/// Render the three-panel content area
+/// Render the three-panel content area (tree and preview for the browser)
 fn render_content(frame: &mut Frame, app: &mut SchemaTuiApp, area: Rect) {
+    if app.mode == ViewerMode::Browser {
+        let content_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(35), // Tree
+                Constraint::Percentage(65), // Preview
+            ])
+            .split(area);
+
+        render_table_list(frame, app, content_chunks[0]);
+        render_preview(frame, app, content_chunks[1]);
+        return;
+    }
+
     let content_chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([
@@ -107,9 +124,12 @@ fn render_table_list(frame: &mut Frame, app: &SchemaTuiApp, area: Rect) {
         format!(" / {} ", app.search_query)
     } else if !app.search_query.is_empty() {
         format!(" Tables [/: {}] ", app.search_query)
+    } else if app.mode == ViewerMode::Browser {
+        " Schemas ".to_string()
     } else {
         " Tables ".to_string()
     };
+    let browser = app.mode == ViewerMode::Browser;
 
     let block = Block::default()
         .title(title)
@@ -129,18 +149,43 @@ fn render_table_list(frame: &mut Frame, app: &SchemaTuiApp, area: Rect) {
 
         match entry {
             TableListEntry::SchemaHeader { name } => {
+                let marker = match (browser, app.collapsed_schemas.contains(name)) {
+                    (false, _) => "",
+                    (true, true) => "▸ ",
+                    (true, false) => "▾ ",
+                };
                 items.push(ListItem::new(Line::from(Span::styled(
-                    format!(" {name}"),
+                    format!(" {marker}{name}"),
                     Style::default()
                         .fg(Color::Cyan)
                         .add_modifier(Modifier::BOLD),
                 ))));
+                if is_selected {
+                    selected_index = items.len() - 1;
+                }
+            }
+            TableListEntry::Item {
+                kind, name, detail, ..
+            } => {
+                let (tag, color) = match kind {
+                    TableItemKind::Column => ("", Color::White),
+                    TableItemKind::Index => ("idx ", Color::Magenta),
+                    TableItemKind::Constraint => ("con ", Color::Green),
+                };
+                items.push(ListItem::new(Line::from(vec![
+                    Span::styled(format!("      {tag}"), Style::default().fg(Color::DarkGray)),
+                    Span::styled(name.as_str(), Style::default().fg(color)),
+                    Span::styled(format!("  {detail}"), Style::default().fg(Color::DarkGray)),
+                ])));
+                if is_selected {
+                    selected_index = items.len() - 1;
+                }
             }
             TableListEntry::Table {
                 name,
+                schema,
                 outgoing_fk,
                 incoming_fk,
-                ..
             } => {
                 let total_fk = outgoing_fk + incoming_fk;
                 let fk_indicator = if total_fk > 0 {
@@ -149,8 +194,18 @@ fn render_table_list(frame: &mut Frame, app: &SchemaTuiApp, area: Rect) {
                     String::new()
                 };
 
+                let marker = if !browser {
+                    ""
+                } else if app
+                    .expanded_tables
+                    .contains(&(schema.clone(), name.clone()))
+                {
+                    "▾ "
+                } else {
+                    "▸ "
+                };
                 let mut spans = vec![Span::styled(
-                    format!("  {name}"),
+                    format!("  {marker}{name}"),
                     Style::default()
                         .fg(Color::White)
                         .add_modifier(if is_selected {
@@ -502,6 +557,78 @@ fn render_details(frame: &mut Frame, app: &mut SchemaTuiApp, area: Rect) {
     }
 }
 
+/// Render the browser preview: sample rows above the table's DDL
+fn render_preview(frame: &mut Frame, app: &mut SchemaTuiApp, area: Rect) {
+    let is_focused = app.focus == PanelFocus::Details;
+    let border = Style::default().fg(if is_focused {
+        Color::Cyan
+    } else {
+        Color::DarkGray
+    });
+
+    let Some((schema, table_name)) = app.selected_table_key() else {
+        let empty = Paragraph::new("Select a table to preview its rows and DDL")
+            .style(Style::default().fg(Color::DarkGray))
+            .block(
+                Block::default()
+                    .title(" Preview ")
+                    .borders(Borders::ALL)
+                    .border_style(border),
+            );
+        frame.render_widget(empty, area);
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+        .split(area);
+
+    let rows_text = match app.get_or_load_preview(&schema, &table_name) {
+        Ok(rows) if rows.len() > 1 => crate::format::format_query_results_psql(rows),
+        Ok(_) => "(no rows)".to_string(),
+        Err(e) => format!("Cannot read sample rows: {e}"),
+    };
+    let rows_lines: Vec<Line> = rows_text.lines().map(Line::raw).collect();
+    let scroll = (app.center_scroll, app.preview_hscroll);
+    let rows = Paragraph::new(rows_lines)
+        .block(
+            Block::default()
+                .title(format!(" {} ", app.qualified_name(&schema, &table_name)))
+                .borders(Borders::ALL)
+                .border_style(border),
+        )
+        .scroll(scroll);
+    frame.render_widget(rows, chunks[0]);
+
+    let db_type = app.schema_data.database_type.clone();
+    let ddl = match app.get_or_load_details(&schema, &table_name) {
+        Some(details) => crate::ai::schema_context::format_table_ddl(details, &db_type),
+        None => "Table details unavailable".to_string(),
+    };
+    let ddl_lines: Vec<Line> = ddl
+        .lines()
+        .map(|line| {
+            let style = if line.starts_with("--") {
+                Style::default().fg(Color::DarkGray)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            Line::from(Span::styled(line.to_string(), style))
+        })
+        .collect();
+    let ddl = Paragraph::new(ddl_lines)
+        .block(
+            Block::default()
+                .title(" DDL ")
+                .borders(Borders::ALL)
+                .border_style(border)
+                .padding(Padding::horizontal(1)),
+        )
+        .wrap(Wrap { trim: false });
+    frame.render_widget(ddl, chunks[1]);
+}
+
 /// Render the right panel: FK relationships
 fn render_relationships(frame: &mut Frame, app: &SchemaTuiApp, area: Rect) {
     let is_focused = app.focus == PanelFocus::Relationships;
@@ -634,6 +761,24 @@ fn render_footer(frame: &mut Frame, app: &SchemaTuiApp, area: Rect) {
             Span::styled("Esc", Style::default().fg(Color::Cyan)),
             Span::styled("] Cancel ", Style::default().fg(Color::DarkGray)),
         ])
+    } else if app.mode == ViewerMode::Browser {
+        Line::from(vec![
+            Span::styled(" [", Style::default().fg(Color::DarkGray)),
+            Span::styled("j/k", Style::default().fg(Color::Cyan)),
+            Span::styled("] Nav  [", Style::default().fg(Color::DarkGray)),
+            Span::styled("h/l", Style::default().fg(Color::Cyan)),
+            Span::styled("] Fold  [", Style::default().fg(Color::DarkGray)),
+            Span::styled("Tab", Style::default().fg(Color::Cyan)),
+            Span::styled("] Preview  [", Style::default().fg(Color::DarkGray)),
+            Span::styled("/", Style::default().fg(Color::Cyan)),
+            Span::styled("] Search  [", Style::default().fg(Color::DarkGray)),
+            Span::styled("Enter", Style::default().fg(Color::Cyan)),
+            Span::styled("] Insert name  [", Style::default().fg(Color::DarkGray)),
+            Span::styled("?", Style::default().fg(Color::Cyan)),
+            Span::styled("] Help  [", Style::default().fg(Color::DarkGray)),
+            Span::styled("q", Style::default().fg(Color::Cyan)),
+            Span::styled("] Quit ", Style::default().fg(Color::DarkGray)),
+        ])
     } else {
         Line::from(vec![
             Span::styled(" [", Style::default().fg(Color::DarkGray)),
@@ -664,9 +809,9 @@ fn render_footer(frame: &mut Frame, app: &SchemaTuiApp, area: Rect) {
 }
 
 /// Render help overlay
-fn render_help_overlay(frame: &mut Frame, area: Rect) {
+fn render_help_overlay(frame: &mut Frame, app: &SchemaTuiApp, area: Rect) {
     let popup_width = 60.min(area.width.saturating_sub(4));
-    let popup_height = 28.min(area.height.saturating_sub(4));
+    let popup_height = 32.min(area.height.saturating_sub(4));
     let popup_x = (area.width.saturating_sub(popup_width)) / 2;
     let popup_y = (area.height.saturating_sub(popup_height)) / 2;
 
@@ -674,9 +819,12 @@ fn render_help_overlay(frame: &mut Frame, area: Rect) {
 
     frame.render_widget(Clear, popup_area);
 
-    let help_lines = vec![
+    let mut help_lines = vec![
         Line::from(Span::styled(
-            "Schema Viewer - Help",
+            match app.mode {
+                ViewerMode::Viewer => "Schema Viewer - Help",
+                ViewerMode::Browser => "Schema Browser - Help",
+            },
             Style::default()
                 .fg(Color::Cyan)
                 .add_modifier(Modifier::BOLD),
@@ -713,8 +861,22 @@ fn render_help_overlay(frame: &mut Frame, area: Rect) {
                 .add_modifier(Modifier::BOLD),
         )),
         Line::from("    /           - Search/filter tables"),
-        Line::from("    Enter       - Follow FK relationship"),
-        Line::from("    Esc         - Back (nav history) / quit"),
+    ];
+    if app.mode == ViewerMode::Browser {
+        help_lines.extend([
+            Line::from("    l / Right   - Expand schema or table"),
+            Line::from("    h / Left    - Collapse / go to parent"),
+            Line::from("    Space       - Toggle expand"),
+            Line::from("    Enter       - Insert name into the prompt"),
+            Line::from("    h/l (preview) - Scroll sample rows sideways"),
+        ]);
+    } else {
+        help_lines.extend([
+            Line::from("    Enter       - Follow FK relationship"),
+            Line::from("    Esc         - Back (nav history) / quit"),
+        ]);
+    }
+    help_lines.extend([
         Line::from("    i           - Toggle index visibility"),
         Line::from("    c           - Toggle constraint visibility"),
         Line::from(""),
@@ -731,7 +893,7 @@ fn render_help_overlay(frame: &mut Frame, area: Rect) {
             "  Press any key to close this help",
             Style::default().fg(Color::DarkGray),
         )),
-    ];
+    ]);
 
     let help = Paragraph::new(help_lines)
         .block(
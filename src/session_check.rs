@@ -0,0 +1,228 @@
+//! Bulk reachability check for saved sessions (`dbcrust check`)
+//!
+//! Connects to every session of a group (or every saved session) at once and
+//! reports whether it answered, how long connecting took and the server
+//! version. Nothing prompts: passwords come from the session's password
+//! command, the `.dbcrust` file or the driver's own files (`.pgpass`,
+//! `.my.cnf`), so a morning smoke test can run unattended. Vault sessions
+//! need fresh credentials and are reported as skipped.
+
+use crate::config::{Config, SavedSession};
+use crate::database::DatabaseTypeExt;
+use crate::db::{Database, FrontendMode};
+use futures_util::future::join_all;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CheckStatus {
+    Reachable {
+        connect_time: Duration,
+        version: String,
+    },
+    Failed(String),
+    Skipped(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckResult {
+    pub session: String,
+    pub target: String,
+    pub status: CheckStatus,
+}
+
+impl CheckResult {
+    pub fn is_failure(&self) -> bool {
+        matches!(self.status, CheckStatus::Failed(_))
+    }
+}
+
+/// `PostgreSQL 15.18` from the server's long version string
+pub(crate) fn short_version(server_type: &str, server_version: &str) -> String {
+    let number = server_version
+        .split_whitespace()
+        .find(|token| token.starts_with(|c: char| c.is_ascii_digit()))
+        .map(|token| token.trim_end_matches([',', ';']))
+        .unwrap_or(server_version);
+    format!("{server_type} {number}")
+}
+
+/// Session URL with a password from its password command or `.dbcrust`, if any
+pub(crate) fn connection_url(session: &SavedSession) -> Result<String, String> {
+    let url = session.reconstruct_connection_url()?;
+    if url.starts_with("docker://") || session.database_type.is_file_based() {
+        return Ok(url);
+    }
+    let info = crate::database::ConnectionInfo::parse_url(&url).map_err(|e| e.to_string())?;
+    if info.password.is_some() {
+        return crate::config::strip_password_command_options_from_url(&url);
+    }
+
+    let password = match crate::config::password_command_from_options(&info.options) {
+        Some(command) => Some(crate::config::resolve_password_command(command)?),
+        None => crate::dbcrust_pass::DatabaseType::from_str(info.database_type.url_scheme())
+            .and_then(|db_type| {
+                crate::dbcrust_pass::lookup_password(
+                    db_type,
+                    info.host.as_deref().unwrap_or("localhost"),
+                    info.port
+                        .or_else(|| info.default_port())
+                        .unwrap_or_default(),
+                    info.database.as_deref().unwrap_or(""),
+                    info.username.as_deref().unwrap_or(""),
+                )
+                .ok()
+                .flatten()
+            }),
+    };
+    let mut url = url::Url::parse(&url).map_err(|e| e.to_string())?;
+    if let Some(password) = password {
+        url.set_password(Some(&password))
+            .map_err(|_| "cannot set a password on this URL".to_string())?;
+    }
+    crate::config::strip_password_command_options_from_url(url.as_str())
+}
+
+async fn check_session(name: String, session: SavedSession, timeout: Duration) -> CheckResult {
+    let target = session.reconnect_target();
+    let status = if session.options.contains_key("vault_mount") {
+        CheckStatus::Skipped("Vault credentials are issued on connect".to_string())
+    } else {
+        match connection_url(&session) {
+            Err(e) => CheckStatus::Failed(e),
+            Ok(url) => {
+                let started = Instant::now();
+                let connect = Database::from_url_with_mode(&url, None, None, FrontendMode::Batch);
+                match tokio::time::timeout(timeout, connect).await {
+                    Err(_) => {
+                        CheckStatus::Failed(format!("timed out after {}s", timeout.as_secs()))
+                    }
+                    Ok(Err(e)) => CheckStatus::Failed(
+                        crate::password_sanitizer::sanitize_text_for_logging(&e.to_string()),
+                    ),
+                    Ok(Ok(database)) => {
+                        let connect_time = started.elapsed();
+                        let version = match database.get_database_client() {
+                            Some(client) => match client.get_server_info().await {
+                                Ok(info) => short_version(&info.server_type, &info.server_version),
+                                Err(_) => session.database_type.display_name().to_string(),
+                            },
+                            None => session.database_type.display_name().to_string(),
+                        };
+                        CheckStatus::Reachable {
+                            connect_time,
+                            version,
+                        }
+                    }
+                }
+            }
+        }
+    };
+    CheckResult {
+        session: name,
+        target,
+        status,
+    }
+}
+
+/// Check the sessions of `group` (all sessions when `None`) concurrently
+pub async fn check_sessions(
+    config: &Config,
+    group: Option<&str>,
+    timeout: Duration,
+) -> Result<Vec<CheckResult>, String> {
+    let sessions = match group {
+        Some(group) => config.sessions_in_group(group),
+        None => {
+            let mut sessions = config.list_sessions();
+            sessions.sort_by(|a, b| a.0.cmp(&b.0));
+            sessions
+        }
+    };
+    if sessions.is_empty() {
+        return Err(match group {
+            Some(group) => {
+                format!("No sessions in group '{group}'. Add one with \\ss <name> --group {group}.")
+            }
+            None => "No saved sessions. Save one with \\ss <name>.".to_string(),
+        });
+    }
+    Ok(join_all(
+        sessions
+            .into_iter()
+            .map(|(name, session)| check_session(name, session, timeout)),
+    )
+    .await)
+}
+
+/// Result rows, header first
+pub fn to_rows(results: &[CheckResult]) -> Vec<Vec<String>> {
+    let mut rows = vec![
+        ["Session", "Target", "Status", "Connect", "Version"]
+            .map(String::from)
+            .to_vec(),
+    ];
+    for result in results {
+        let (status, connect, version) = match &result.status {
+            CheckStatus::Reachable {
+                connect_time,
+                version,
+            } => (
+                "ok".to_string(),
+                format!("{} ms", connect_time.as_millis()),
+                version.clone(),
+            ),
+            CheckStatus::Failed(e) => (format!("FAILED: {e}"), "-".to_string(), String::new()),
+            CheckStatus::Skipped(why) => {
+                (format!("skipped: {why}"), "-".to_string(), String::new())
+            }
+        };
+        rows.push(vec![
+            result.session.clone(),
+            result.target.clone(),
+            status,
+            connect,
+            version,
+        ]);
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_version() {
+        assert_eq!(
+            short_version(
+                "PostgreSQL",
+                "PostgreSQL 15.18 (Debian 15.18-0+deb12u1) on x86_64-pc-linux-gnu"
+            ),
+            "PostgreSQL 15.18"
+        );
+        assert_eq!(short_version("MySQL", "8.0.36"), "MySQL 8.0.36");
+    }
+
+    #[test]
+    fn test_rows_report_each_status() {
+        let results = vec![
+            CheckResult {
+                session: "eu".to_string(),
+                target: "app@eu-db:5432/app".to_string(),
+                status: CheckStatus::Reachable {
+                    connect_time: Duration::from_millis(42),
+                    version: "PostgreSQL 16.2".to_string(),
+                },
+            },
+            CheckResult {
+                session: "us".to_string(),
+                target: "app@us-db:5432/app".to_string(),
+                status: CheckStatus::Failed("timed out after 10s".to_string()),
+            },
+        ];
+        let rows = to_rows(&results);
+        assert_eq!(rows[1][2..], ["ok", "42 ms", "PostgreSQL 16.2"]);
+        assert_eq!(rows[2][2], "FAILED: timed out after 10s");
+        assert!(results[1].is_failure() && !results[0].is_failure());
+    }
+}
@@ -0,0 +1,169 @@
+//! Per-session usage counters (`\stats`, `Connection.stats()` in Python)
+//!
+//! Kept on [`crate::db::Database`] and updated by its query paths. The drivers
+//! do not expose wire-level byte counts, so "bytes sent" is the SQL text sent
+//! and "bytes received" is the text of the buffered results.
+
+use crate::format::{format_bytes, format_lag};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone)]
+pub struct SessionStats {
+    /// When the session started, or when the counters were last reset
+    pub since: Instant,
+    pub queries: u64,
+    pub failed_queries: u64,
+    pub rows_fetched: u64,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    pub query_time: Duration,
+    /// Autocompletion metadata lookups answered from the completer cache
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+}
+
+impl Default for SessionStats {
+    fn default() -> Self {
+        Self {
+            since: Instant::now(),
+            queries: 0,
+            failed_queries: 0,
+            rows_fetched: 0,
+            bytes_sent: 0,
+            bytes_received: 0,
+            query_time: Duration::ZERO,
+            cache_hits: 0,
+            cache_misses: 0,
+        }
+    }
+}
+
+impl SessionStats {
+    /// Count one statement; `rows` is `None` when it failed
+    pub fn record_query(
+        &mut self,
+        sql: &str,
+        elapsed: Duration,
+        rows: Option<u64>,
+        bytes_received: u64,
+    ) {
+        crate::metrics::record_query(elapsed, rows.is_some());
+        self.queries += 1;
+        self.bytes_sent += sql.len() as u64;
+        self.query_time += elapsed;
+        match rows {
+            Some(rows) => {
+                self.rows_fetched += rows;
+                self.bytes_received += bytes_received;
+            }
+            None => self.failed_queries += 1,
+        }
+    }
+
+    /// Count a buffered result (header row first) of a successful statement
+    pub fn record_results(&mut self, sql: &str, elapsed: Duration, results: &[Vec<String>]) {
+        let bytes = results.iter().flatten().map(|cell| cell.len() as u64).sum();
+        let rows = results.len().saturating_sub(1) as u64;
+        self.record_query(sql, elapsed, Some(rows), bytes);
+    }
+
+    pub fn record_cache_lookup(&mut self, hit: bool) {
+        if hit {
+            self.cache_hits += 1;
+        } else {
+            self.cache_misses += 1;
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// `\stats` rows, header first
+    pub fn to_rows(&self) -> Vec<Vec<String>> {
+        let average = if self.queries == 0 {
+            "-".to_string()
+        } else {
+            format_duration(self.query_time.div_f64(self.queries as f64))
+        };
+        let lookups = self.cache_hits + self.cache_misses;
+        let cache = if lookups == 0 {
+            "0".to_string()
+        } else {
+            format!(
+                "{} of {} ({:.0}%)",
+                self.cache_hits,
+                lookups,
+                self.cache_hits as f64 * 100.0 / lookups as f64
+            )
+        };
+        [
+            (
+                "Tracked for",
+                format_lag(self.since.elapsed().as_secs_f64()),
+            ),
+            ("Queries executed", self.queries.to_string()),
+            ("Failed queries", self.failed_queries.to_string()),
+            ("Rows fetched", self.rows_fetched.to_string()),
+            ("Bytes sent", format_bytes(self.bytes_sent as i64)),
+            ("Bytes received", format_bytes(self.bytes_received as i64)),
+            ("Total query time", format_duration(self.query_time)),
+            ("Average query time", average),
+            ("Metadata cache hits", cache),
+        ]
+        .into_iter()
+        .fold(
+            vec![vec!["Statistic".to_string(), "Value".to_string()]],
+            |mut rows, (name, value)| {
+                rows.push(vec![name.to_string(), value]);
+                rows
+            },
+        )
+    }
+}
+
+/// `12.3 ms` below a second, `4.20s` / `5m12s` above
+fn format_duration(duration: Duration) -> String {
+    if duration < Duration::from_secs(1) {
+        format!("{:.1} ms", duration.as_secs_f64() * 1000.0)
+    } else {
+        format_lag(duration.as_secs_f64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_results_counts_rows_and_bytes() {
+        let mut stats = SessionStats::default();
+        let results = vec![
+            vec!["id".to_string(), "name".to_string()],
+            vec!["1".to_string(), "alice".to_string()],
+            vec!["2".to_string(), "bob".to_string()],
+        ];
+        stats.record_results("SELECT * FROM t", Duration::from_millis(30), &results);
+        stats.record_query("SELEC", Duration::from_millis(10), None, 0);
+
+        assert_eq!(stats.queries, 2);
+        assert_eq!(stats.failed_queries, 1);
+        assert_eq!(stats.rows_fetched, 2);
+        assert_eq!(stats.bytes_sent, 20);
+        assert_eq!(stats.bytes_received, 16);
+        assert_eq!(stats.query_time, Duration::from_millis(40));
+
+        let rows = stats.to_rows();
+        let value = |name: &str| rows.iter().find(|r| r[0] == name).unwrap()[1].clone();
+        assert_eq!(value("Average query time"), "20.0 ms");
+        assert_eq!(value("Metadata cache hits"), "0");
+
+        stats.record_cache_lookup(true);
+        stats.record_cache_lookup(true);
+        stats.record_cache_lookup(false);
+        assert_eq!(stats.to_rows()[9][1], "2 of 3 (67%)");
+
+        stats.reset();
+        assert_eq!((stats.queries, stats.cache_hits), (0, 0));
+    }
+}
@@ -5,6 +5,9 @@ use std::io::Write;
 use crate::url_scheme::UrlScheme;
 use strum::IntoEnumIterator;
 
+/// Prints the `[aliases]` names of the config file, one per line
+const ALIAS_NAMES_COMMAND: &str = r#"awk '/^\[/ { in_aliases = ($0 ~ /^\[aliases\]/); next } in_aliases && /^[^#[:space:]].*=/ { sub(/[[:space:]]*=.*/, ""); gsub(/"/, ""); print }' "$HOME/.config/dbcrust/config.toml" 2>/dev/null"#;
+
 /// Generate shell completion scripts with custom URL scheme support
 pub fn generate_completion_with_url_schemes<W: Write>(
     shell: Shell,
@@ -79,13 +82,19 @@ _{bin_name}_complete_sessions() {{
     fi
 }}
 
+# Function to complete connection aliases
+_{bin_name}_complete_aliases() {{
+    {ALIAS_NAMES_COMMAND} || true
+}}
+
 # Custom URL completion
 _{bin_name}_complete_url() {{
     local cur="$1"
 
-    # If the current word doesn't contain "://", complete schemes
+    # If the current word doesn't contain "://", complete schemes and aliases
     if [[ "$cur" != *"://"* ]]; then
         _{bin_name}_complete_url_schemes | grep "^$cur"
+        _{bin_name}_complete_aliases | grep "^$cur"
     else
         # Extract the scheme and the part after "://"
         local scheme="${{cur%%://*}}"
@@ -208,6 +217,10 @@ _{bin_name}_sessions() {{
     fi
 }}
 
+_{bin_name}_aliases() {{
+    {ALIAS_NAMES_COMMAND} || true
+}}
+
 _{bin_name}_complete_url() {{
     local curcontext="$curcontext" state line
     local current_word="${{words[CURRENT]}}"
@@ -226,6 +239,13 @@ _{bin_name}_complete_url() {{
             'vault://'
         )
         compadd -S "" -a scheme_completions
+
+        # Connection aliases from [aliases] in config.toml
+        local -a alias_completions
+        alias_completions=(${{(f)"$(_{bin_name}_aliases)"}})
+        if [[ -n "$alias_completions" ]]; then
+            compadd -a alias_completions
+        fi
     else
         # Complete based on scheme
         local scheme="${{current_word%%://*}}"
@@ -348,6 +368,18 @@ end
 complete -c {bin_name} -n 'string match -q "session://*" (commandline -ct)' -f -a '(printf "session://%s\n" (__dbcrust_sessions))'
 "#));
 
+    // Add connection alias completion
+    custom_completions.push_str(&format!(
+        r#"
+# Connection alias completion
+function __dbcrust_aliases
+    {ALIAS_NAMES_COMMAND}
+end
+
+complete -c {bin_name} -n '__fish_is_first_token' -f -a '(__dbcrust_aliases)' -d 'Connection alias'
+"#
+    ));
+
     completion_script.push_str(&custom_completions);
 
     buf.write_all(completion_script.as_bytes())?;
@@ -378,6 +410,8 @@ mod tests {
         assert!(script.contains("_dbcrust_complete_url_schemes"));
         assert!(script.contains("_dbcrust_complete_docker_containers"));
         assert!(script.contains("_dbcrust_complete_sessions"));
+        assert!(script.contains("_dbcrust_complete_aliases"));
+        assert!(script.contains("\\[aliases\\]"));
     }
 
     #[test]
@@ -394,6 +428,7 @@ mod tests {
         assert!(script.contains("_dbcrust_url_schemes"));
         assert!(script.contains("_dbcrust_docker_containers"));
         assert!(script.contains("_dbcrust_sessions"));
+        assert!(script.contains("_dbcrust_aliases"));
     }
 
     #[test]
@@ -410,5 +445,6 @@ mod tests {
         assert!(script.contains("postgres://"));
         assert!(script.contains("__dbcrust_docker_containers"));
         assert!(script.contains("__dbcrust_sessions"));
+        assert!(script.contains("__dbcrust_aliases"));
     }
 }
@@ -0,0 +1,445 @@
+//! Saved result snapshots and drift between them and the live data (`\snapshot`)
+//!
+//! `\snapshot save <name>` keeps the last result as displayed, with the
+//! query that produced it (default limit included) and when it ran, in
+//! `snapshots/<name>.json` next to the config. `\snapshot diff <name>` runs
+//! that query again and lists the rows added, removed and changed since.
+//!
+//! Rows are matched on key columns: the ones given to `\snapshot diff`, else
+//! the first column when it identifies rows on both sides. Without a key,
+//! whole rows are compared and an edited row shows as removed and added.
+//! Only the columns present on both sides are compared.
+
+use chrono::{DateTime, Utc};
+use nu_ansi_term::Color;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+/// A saved result
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Snapshot {
+    pub query: String,
+    /// RFC 3339, UTC
+    pub taken_at: String,
+    /// Connection URL without password, to tell snapshots of different servers apart
+    #[serde(default)]
+    pub connection: String,
+    /// Header first
+    pub data: Vec<Vec<String>>,
+}
+
+impl Snapshot {
+    pub fn new(query: &str, connection: &str, data: &[Vec<String>]) -> Self {
+        Self {
+            query: query.to_string(),
+            taken_at: Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            connection: connection.to_string(),
+            data: data.to_vec(),
+        }
+    }
+
+    /// `2026-03-01 09:12:03 UTC (2h03m ago)`
+    pub fn age(&self) -> String {
+        match DateTime::parse_from_rfc3339(&self.taken_at) {
+            Ok(taken) => {
+                let taken = taken.with_timezone(&Utc);
+                let seconds = (Utc::now() - taken).num_seconds() as f64;
+                format!(
+                    "{} ({} ago)",
+                    taken.format("%Y-%m-%d %H:%M:%S UTC"),
+                    crate::format::format_lag(seconds)
+                )
+            }
+            Err(_) => self.taken_at.clone(),
+        }
+    }
+
+    pub fn row_count(&self) -> usize {
+        self.data.len().saturating_sub(1)
+    }
+}
+
+fn directory() -> Result<PathBuf, String> {
+    crate::config::Config::get_config_dir()
+        .map(|dir| dir.join("snapshots"))
+        .map_err(|e| format!("No config directory for snapshots: {e}"))
+}
+
+fn path(name: &str) -> Result<PathBuf, String> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+        || name.starts_with('.')
+    {
+        return Err(format!(
+            "Invalid snapshot name '{name}': use letters, digits, '_', '-' and '.'"
+        ));
+    }
+    Ok(directory()?.join(format!("{name}.json")))
+}
+
+/// Write `snapshot` as `name`, replacing a snapshot of that name
+pub fn save(name: &str, snapshot: &Snapshot) -> Result<(), String> {
+    let path = path(name)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Cannot create {}: {e}", parent.display()))?;
+    }
+    let json = serde_json::to_string(snapshot).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| format!("Cannot write {}: {e}", path.display()))
+}
+
+pub fn load(name: &str) -> Result<Snapshot, String> {
+    let path = path(name)?;
+    let content = std::fs::read_to_string(&path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => {
+            format!("No snapshot named '{name}'. \\snapshot list shows the saved ones.")
+        }
+        _ => format!("Cannot read {}: {e}", path.display()),
+    })?;
+    serde_json::from_str(&content).map_err(|e| format!("Unreadable snapshot '{name}': {e}"))
+}
+
+pub fn delete(name: &str) -> Result<(), String> {
+    let path = path(name)?;
+    std::fs::remove_file(&path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => format!("No snapshot named '{name}'"),
+        _ => format!("Cannot delete {}: {e}", path.display()),
+    })
+}
+
+/// Saved snapshots by name
+pub fn list() -> Result<Vec<(String, Snapshot)>, String> {
+    let directory = directory()?;
+    let Ok(entries) = std::fs::read_dir(&directory) else {
+        return Ok(Vec::new());
+    };
+    let mut snapshots: Vec<(String, Snapshot)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry
+                .file_name()
+                .to_str()?
+                .strip_suffix(".json")?
+                .to_string();
+            let snapshot = load(&name).ok()?;
+            Some((name, snapshot))
+        })
+        .collect();
+    snapshots.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(snapshots)
+}
+
+/// Rows and columns that differ between a snapshot and the current result
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Drift {
+    /// `change` column, then the compared columns; only rows that differ
+    pub table: Vec<Vec<String>>,
+    pub added: usize,
+    pub removed: usize,
+    pub changed: usize,
+    pub unchanged: usize,
+    /// Key columns rows were matched on; empty when whole rows were compared
+    pub key: Vec<String>,
+    pub new_columns: Vec<String>,
+    pub dropped_columns: Vec<String>,
+}
+
+impl Drift {
+    /// `2 added, 1 removed, 3 changed, 94 unchanged (matched on id).`
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "{} added, {} removed, {} changed, {} unchanged",
+            self.added, self.removed, self.changed, self.unchanged
+        );
+        if self.key.is_empty() {
+            summary.push_str(" (whole rows compared).");
+        } else {
+            summary.push_str(&format!(" (matched on {}).", self.key.join(", ")));
+        }
+        if !self.new_columns.is_empty() {
+            summary.push_str(&format!("\nNew columns: {}.", self.new_columns.join(", ")));
+        }
+        if !self.dropped_columns.is_empty() {
+            summary.push_str(&format!(
+                "\nColumns no longer returned: {}.",
+                self.dropped_columns.join(", ")
+            ));
+        }
+        summary
+    }
+}
+
+/// The values of `columns` in `row`
+fn pick<'a>(row: &'a [String], columns: &[usize]) -> Vec<&'a str> {
+    columns
+        .iter()
+        .map(|index| row.get(*index).map(String::as_str).unwrap_or_default())
+        .collect()
+}
+
+fn is_unique(rows: &[Vec<String>], columns: &[usize]) -> bool {
+    let mut seen = HashSet::new();
+    rows.iter().all(|row| seen.insert(pick(row, columns)))
+}
+
+fn marker(color: Color, text: &str) -> String {
+    color.paint(text).to_string()
+}
+
+/// Compare `current` against the snapshot `saved` (both header first),
+/// matching rows on `key` (column names or 1-based positions; the first
+/// column when it is unique, when empty)
+pub fn diff(
+    saved: &[Vec<String>],
+    current: &[Vec<String>],
+    key: &[String],
+) -> Result<Drift, String> {
+    let (Some((old_header, old_rows)), Some((new_header, new_rows))) =
+        (saved.split_first(), current.split_first())
+    else {
+        return Err("Nothing to compare: a result without columns".to_string());
+    };
+    // Compared columns, as positions in the snapshot and in the current result
+    let shared: Vec<(usize, usize)> = old_header
+        .iter()
+        .enumerate()
+        .filter_map(|(old, name)| Some((old, new_header.iter().position(|n| n == name)?)))
+        .collect();
+    if shared.is_empty() {
+        return Err(format!(
+            "The query no longer returns any of the snapshot's columns ({})",
+            old_header.join(", ")
+        ));
+    }
+    let old_columns: Vec<usize> = shared.iter().map(|(old, _)| *old).collect();
+    let new_columns: Vec<usize> = shared.iter().map(|(_, new)| *new).collect();
+
+    let key_positions: Vec<usize> = if key.is_empty() {
+        let first = vec![0];
+        if is_unique(old_rows, &old_columns[..1]) && is_unique(new_rows, &new_columns[..1]) {
+            first
+        } else {
+            Vec::new()
+        }
+    } else {
+        let compared: Vec<String> = old_columns
+            .iter()
+            .map(|index| old_header[*index].clone())
+            .collect();
+        key.iter()
+            .map(|column| crate::pivot::resolve(&compared, column))
+            .collect::<Result<_, _>>()?
+    };
+    let old_key: Vec<usize> = key_positions.iter().map(|p| old_columns[*p]).collect();
+    let new_key: Vec<usize> = key_positions.iter().map(|p| new_columns[*p]).collect();
+    let unique = is_unique(old_rows, &old_key) && is_unique(new_rows, &new_key);
+    if !key.is_empty() && !unique {
+        return Err(format!(
+            "{} does not identify rows uniquely; pick key columns that do",
+            key.join(", ")
+        ));
+    }
+
+    let mut header = vec!["change".to_string()];
+    header.extend(old_columns.iter().map(|index| old_header[*index].clone()));
+    let mut drift = Drift {
+        table: vec![header],
+        key: key_positions
+            .iter()
+            .map(|p| old_header[old_columns[*p]].clone())
+            .collect(),
+        new_columns: new_header
+            .iter()
+            .filter(|name| !old_header.contains(name))
+            .cloned()
+            .collect(),
+        dropped_columns: old_header
+            .iter()
+            .filter(|name| !new_header.contains(name))
+            .cloned()
+            .collect(),
+        ..Default::default()
+    };
+    let row_of = |label: String, row: &[String], columns: &[usize]| {
+        let mut cells = vec![label];
+        cells.extend(pick(row, columns).into_iter().map(str::to_string));
+        cells
+    };
+
+    if old_key.is_empty() {
+        // Whole rows as a multiset
+        let mut remaining: HashMap<Vec<&str>, usize> = HashMap::new();
+        for row in old_rows {
+            *remaining.entry(pick(row, &old_columns)).or_default() += 1;
+        }
+        for row in new_rows {
+            match remaining.get_mut(&pick(row, &new_columns)) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    drift.unchanged += 1;
+                }
+                _ => {
+                    drift.added += 1;
+                    let label = marker(Color::Green, "added");
+                    drift.table.push(row_of(label, row, &new_columns));
+                }
+            }
+        }
+        for row in old_rows {
+            let count = remaining
+                .get_mut(&pick(row, &old_columns))
+                .expect("counted");
+            if *count > 0 {
+                *count -= 1;
+                drift.removed += 1;
+                let label = marker(Color::Red, "removed");
+                drift.table.push(row_of(label, row, &old_columns));
+            }
+        }
+        return Ok(drift);
+    }
+
+    let old_by_key: HashMap<Vec<&str>, &Vec<String>> = old_rows
+        .iter()
+        .map(|row| (pick(row, &old_key), row))
+        .collect();
+    let mut matched = HashSet::new();
+    for row in new_rows {
+        let row_key = pick(row, &new_key);
+        let Some(old) = old_by_key.get(&row_key) else {
+            drift.added += 1;
+            let label = marker(Color::Green, "added");
+            drift.table.push(row_of(label, row, &new_columns));
+            continue;
+        };
+        matched.insert(row_key);
+        let before = pick(old, &old_columns);
+        let after = pick(row, &new_columns);
+        if before == after {
+            drift.unchanged += 1;
+            continue;
+        }
+        drift.changed += 1;
+        let mut cells = vec![marker(Color::Yellow, "changed")];
+        cells.extend(before.iter().zip(&after).map(|(before, after)| {
+            if before == after {
+                after.to_string()
+            } else {
+                Color::Yellow
+                    .paint(format!("{before} → {after}"))
+                    .to_string()
+            }
+        }));
+        drift.table.push(cells);
+    }
+    for row in old_rows {
+        if !matched.contains(&pick(row, &old_key)) {
+            drift.removed += 1;
+            let label = marker(Color::Red, "removed");
+            drift.table.push(row_of(label, row, &old_columns));
+        }
+    }
+    Ok(drift)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(rows: &[&[&str]]) -> Vec<Vec<String>> {
+        rows.iter()
+            .map(|row| row.iter().map(|v| v.to_string()).collect())
+            .collect()
+    }
+
+    fn plain(text: &str) -> String {
+        crate::pager::strip_ansi(text).into_owned()
+    }
+
+    #[test]
+    fn test_diff_on_first_column() {
+        let saved = table(&[
+            &["id", "status", "amount"],
+            &["1", "paid", "10"],
+            &["2", "pending", "20"],
+            &["3", "pending", "30"],
+        ]);
+        let current = table(&[
+            &["id", "status", "amount", "note"],
+            &["1", "paid", "10", ""],
+            &["2", "paid", "20", ""],
+            &["4", "pending", "40", ""],
+        ]);
+        let drift = diff(&saved, &current, &[]).unwrap();
+        assert_eq!(
+            (drift.added, drift.removed, drift.changed, drift.unchanged),
+            (1, 1, 1, 1)
+        );
+        assert_eq!(drift.key, vec!["id"]);
+        assert_eq!(drift.new_columns, vec!["note"]);
+        let rows: Vec<Vec<String>> = drift
+            .table
+            .iter()
+            .map(|row| row.iter().map(|cell| plain(cell)).collect())
+            .collect();
+        assert_eq!(
+            rows,
+            table(&[
+                &["change", "id", "status", "amount"],
+                &["changed", "2", "pending → paid", "20"],
+                &["added", "4", "pending", "40"],
+                &["removed", "3", "pending", "30"],
+            ])
+        );
+        assert!(
+            drift
+                .summary()
+                .starts_with("1 added, 1 removed, 1 changed, 1 unchanged (matched on id).")
+        );
+    }
+
+    #[test]
+    fn test_diff_without_unique_key_compares_whole_rows() {
+        let saved = table(&[&["day", "n"], &["mon", "1"], &["mon", "2"], &["tue", "1"]]);
+        let current = table(&[&["day", "n"], &["mon", "1"], &["mon", "3"], &["tue", "1"]]);
+        let drift = diff(&saved, &current, &[]).unwrap();
+        assert!(drift.key.is_empty());
+        assert_eq!(
+            (drift.added, drift.removed, drift.changed, drift.unchanged),
+            (1, 1, 0, 2)
+        );
+
+        let keyed = diff(&saved, &current, &["day".to_string(), "n".to_string()]).unwrap();
+        assert_eq!((keyed.added, keyed.removed), (1, 1));
+        assert!(diff(&saved, &current, &["day".to_string()]).is_err());
+        assert!(diff(&saved, &table(&[&["other"]]), &[]).is_err());
+    }
+
+    #[test]
+    fn test_save_load_and_names() {
+        let snapshot = Snapshot::new(
+            "SELECT 1 AS x",
+            "postgres://app@db/app",
+            &table(&[&["x"], &["1"]]),
+        );
+        save("test-snapshot.v1", &snapshot).unwrap();
+        assert_eq!(load("test-snapshot.v1").unwrap(), snapshot);
+        assert!(
+            list()
+                .unwrap()
+                .iter()
+                .any(|(name, _)| name == "test-snapshot.v1")
+        );
+        delete("test-snapshot.v1").unwrap();
+        assert!(
+            load("test-snapshot.v1")
+                .unwrap_err()
+                .contains("No snapshot named")
+        );
+        assert!(save("../escape", &snapshot).is_err());
+        assert!(path(".hidden").is_err());
+    }
+}
@@ -0,0 +1,368 @@
+//! Snippet expansion for the REPL (`[snippets]`, `\snippets`)
+//!
+//! Typing a trigger and pressing Tab replaces it with its expansion.
+//! Expansions may contain `${name}` or `${name:default}` placeholders: while
+//! one is left in the buffer, Tab jumps to it and selects its default text so
+//! typing overwrites it. Tab falls back to regular completion otherwise.
+//! None ship by default, so Tab only expands triggers the user defined.
+//!
+//! The hook is an [`EditMode`] wrapper because completion menus can only
+//! replace text before the cursor, not move it onto a placeholder.
+
+use crate::config::Config;
+use reedline::{EditCommand, EditMode, PromptEditMode, ReedlineEvent, ReedlineRawEvent};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// A `${name}` or `${name:default}` marker; `start..end` are byte offsets
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Placeholder {
+    pub start: usize,
+    pub end: usize,
+    pub name: String,
+    pub default: String,
+}
+
+/// First placeholder at or after byte offset `from`
+pub fn find_placeholder(text: &str, from: usize) -> Option<Placeholder> {
+    let mut offset = from;
+    while let Some(found) = text.get(offset..)?.find("${") {
+        let start = offset + found;
+        let inner_start = start + 2;
+        let close = text[inner_start..].find(['}', '\n'])?;
+        let inner = &text[inner_start..inner_start + close];
+        let (name, default) = inner.split_once(':').unwrap_or((inner, ""));
+        if text[inner_start + close..].starts_with('}') && is_trigger_word(name) {
+            return Some(Placeholder {
+                start,
+                end: inner_start + close + 1,
+                name: name.to_string(),
+                default: default.to_string(),
+            });
+        }
+        offset = inner_start;
+    }
+    None
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn is_trigger_word(word: &str) -> bool {
+    !word.is_empty() && word.chars().all(is_word_char)
+}
+
+/// The snippet whose trigger is the word ending at `cursor`, with the byte
+/// offset where that word starts. `schema.sel` or `\sel` do not count.
+pub fn trigger_before<'a>(
+    buffer: &str,
+    cursor: usize,
+    snippets: &'a HashMap<String, String>,
+) -> Option<(usize, &'a str)> {
+    let before = buffer.get(..cursor)?;
+    let start = before
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| is_word_char(*c))
+        .last()
+        .map(|(i, _)| i)?;
+    if before[..start].ends_with(['.', '\\', '"', '\'', '$']) {
+        return None;
+    }
+    snippets
+        .get(&before[start..])
+        .map(|expansion| (start, expansion.as_str()))
+}
+
+/// Edits that replace `start..end` with the placeholder's default, selected
+fn fill_placeholder(placeholder: &Placeholder) -> Vec<EditCommand> {
+    let mut edits = vec![
+        EditCommand::MoveToPosition {
+            position: placeholder.start,
+            select: false,
+        },
+        EditCommand::MoveToPosition {
+            position: placeholder.end,
+            select: true,
+        },
+        EditCommand::InsertString(placeholder.default.clone()),
+    ];
+    if !placeholder.default.is_empty() {
+        edits.push(EditCommand::MoveToPosition {
+            position: placeholder.start,
+            select: true,
+        });
+    }
+    edits
+}
+
+/// What Tab does in `buffer` with the cursor at `cursor`: expand a trigger
+/// (then jump into its first placeholder), jump to the first placeholder
+/// left in the buffer, or `None` for regular completion.
+pub fn tab_edits(
+    buffer: &str,
+    cursor: usize,
+    snippets: &HashMap<String, String>,
+) -> Option<Vec<EditCommand>> {
+    if let Some((start, expansion)) = trigger_before(buffer, cursor, snippets) {
+        let mut edits = vec![
+            EditCommand::MoveToPosition {
+                position: start,
+                select: false,
+            },
+            EditCommand::MoveToPosition {
+                position: cursor,
+                select: true,
+            },
+            EditCommand::InsertString(expansion.to_string()),
+        ];
+        if let Some(placeholder) = find_placeholder(expansion, 0) {
+            edits.extend(fill_placeholder(&Placeholder {
+                start: start + placeholder.start,
+                end: start + placeholder.end,
+                ..placeholder
+            }));
+        }
+        return Some(edits);
+    }
+    find_placeholder(buffer, 0).map(|placeholder| fill_placeholder(&placeholder))
+}
+
+/// Wraps the REPL edit mode and turns the Tab completion event into snippet
+/// edits when [`tab_edits`] has something to do.
+///
+/// The buffer and cursor come from the highlighter, which sees them on every
+/// repaint — i.e. before each key is handled.
+pub struct SnippetEditMode {
+    inner: Box<dyn EditMode>,
+    config: Arc<Mutex<Config>>,
+    buffer: Arc<Mutex<Option<String>>>,
+    cursor: Arc<AtomicUsize>,
+}
+
+impl SnippetEditMode {
+    pub fn new(
+        inner: Box<dyn EditMode>,
+        config: Arc<Mutex<Config>>,
+        buffer: Arc<Mutex<Option<String>>>,
+        cursor: Arc<AtomicUsize>,
+    ) -> Self {
+        Self {
+            inner,
+            config,
+            buffer,
+            cursor,
+        }
+    }
+
+    fn is_tab_completion(event: &ReedlineEvent) -> bool {
+        matches!(event, ReedlineEvent::UntilFound(events)
+            if matches!(events.first(), Some(ReedlineEvent::Menu(name)) if name == "completion_menu"))
+    }
+}
+
+impl EditMode for SnippetEditMode {
+    fn parse_event(&mut self, event: ReedlineRawEvent) -> ReedlineEvent {
+        let event = self.inner.parse_event(event);
+        if !Self::is_tab_completion(&event) {
+            return event;
+        }
+        let Some(buffer) = self.buffer.lock().ok().and_then(|b| b.clone()) else {
+            return event;
+        };
+        let cursor = self.cursor.load(Ordering::Relaxed);
+        let edits = match self.config.lock() {
+            Ok(config) => tab_edits(&buffer, cursor, &config.snippets),
+            Err(_) => None,
+        };
+        edits.map(ReedlineEvent::Edit).unwrap_or(event)
+    }
+
+    fn edit_mode(&self) -> PromptEditMode {
+        self.inner.edit_mode()
+    }
+}
+
+/// `\snippets` — the configured snippets, sorted by trigger
+pub fn list_snippets(config: &Config) -> String {
+    if config.snippets.is_empty() {
+        return "No snippets configured. Add one with \\snippets add <trigger> <expansion>."
+            .to_string();
+    }
+    let sorted: BTreeMap<_, _> = config.snippets.iter().collect();
+    let width = sorted.keys().map(|t| t.chars().count()).max().unwrap_or(0);
+    sorted
+        .iter()
+        .map(|(trigger, expansion)| {
+            format!(
+                "{trigger:<width$}  →  {}",
+                expansion.replace('\n', "\\n").trim_end()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// `\snippets add` — insert or replace a snippet and save the config.
+/// A literal `\n` in the expansion becomes a newline.
+pub fn add_snippet(config: &mut Config, trigger: &str, expansion: &str) -> Result<String, String> {
+    if !is_trigger_word(trigger) {
+        return Err(format!(
+            "Invalid trigger '{trigger}': use letters, digits and underscores only"
+        ));
+    }
+    if expansion.trim().is_empty() {
+        return Err("Snippet expansion cannot be empty".to_string());
+    }
+    let expansion = expansion.replace("\\n", "\n");
+    let previous = config
+        .snippets
+        .insert(trigger.to_string(), expansion.clone());
+    save(config)?;
+    Ok(match previous {
+        Some(previous) if previous != expansion => format!(
+            "Snippet '{trigger}' updated (was: {})",
+            previous.replace('\n', "\\n")
+        ),
+        _ => format!("Snippet '{trigger}' saved. Type {trigger}<Tab> to expand it."),
+    })
+}
+
+/// `\snippets remove`
+pub fn remove_snippet(config: &mut Config, trigger: &str) -> Result<String, String> {
+    if config.snippets.remove(trigger).is_none() {
+        return Err(format!("No snippet '{trigger}'"));
+    }
+    save(config)?;
+    Ok(format!("Snippet '{trigger}' removed"))
+}
+
+fn save(config: &Config) -> Result<(), String> {
+    config
+        .save_with_documentation()
+        .map_err(|e| format!("Failed to save configuration: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snippets() -> HashMap<String, String> {
+        [
+            ("sel", "SELECT * FROM "),
+            (
+                "selw",
+                "SELECT ${columns:*} FROM ${table} WHERE ${condition}",
+            ),
+            ("cnt", "SELECT count(*) FROM ${table}"),
+        ]
+        .into_iter()
+        .map(|(trigger, expansion)| (trigger.to_string(), expansion.to_string()))
+        .collect()
+    }
+
+    /// Apply edits the way reedline's editor does, returning the buffer,
+    /// cursor and selected text.
+    fn apply(buffer: &str, cursor: usize, edits: &[EditCommand]) -> (String, usize, String) {
+        let mut text = buffer.to_string();
+        let mut pos = cursor;
+        let mut anchor: Option<usize> = None;
+        for edit in edits {
+            match edit {
+                EditCommand::MoveToPosition { position, select } => {
+                    if *select {
+                        anchor.get_or_insert(pos);
+                    } else {
+                        anchor = None;
+                    }
+                    pos = *position;
+                }
+                EditCommand::InsertString(s) => {
+                    if let Some(a) = anchor.take() {
+                        let (lo, hi) = (a.min(pos), a.max(pos));
+                        text.replace_range(lo..hi, "");
+                        pos = lo;
+                    }
+                    text.insert_str(pos, s);
+                    pos += s.len();
+                }
+                other => panic!("unexpected edit {other:?}"),
+            }
+        }
+        let selected = anchor
+            .map(|a| text[a.min(pos)..a.max(pos)].to_string())
+            .unwrap_or_default();
+        (text, pos, selected)
+    }
+
+    #[test]
+    fn test_find_placeholder() {
+        let text = "SELECT ${columns:*} FROM ${table}";
+        let first = find_placeholder(text, 0).unwrap();
+        assert_eq!(&text[first.start..first.end], "${columns:*}");
+        assert_eq!(
+            (first.name.as_str(), first.default.as_str()),
+            ("columns", "*")
+        );
+        let second = find_placeholder(text, first.end).unwrap();
+        assert_eq!(
+            (second.name.as_str(), second.default.as_str()),
+            ("table", "")
+        );
+
+        assert_eq!(find_placeholder("SELECT $1, ${} FROM t", 0), None);
+        assert_eq!(find_placeholder("SELECT '${not closed", 0), None);
+    }
+
+    #[test]
+    fn test_trigger_before() {
+        let snippets = snippets();
+        assert_eq!(
+            trigger_before("sel", 3, &snippets),
+            Some((0, "SELECT * FROM "))
+        );
+        assert_eq!(
+            trigger_before("WITH x AS (sel", 14, &snippets).unwrap().0,
+            11
+        );
+        assert_eq!(trigger_before("select", 6, &snippets), None);
+        assert_eq!(trigger_before("\\sel", 4, &snippets), None);
+        assert_eq!(trigger_before("public.sel", 10, &snippets), None);
+        // Only the word before the cursor counts
+        assert_eq!(trigger_before("sel x", 5, &snippets), None);
+    }
+
+    #[test]
+    fn test_tab_expands_and_selects_first_placeholder() {
+        let snippets = snippets();
+
+        let edits = tab_edits("sel", 3, &snippets).unwrap();
+        assert_eq!(
+            apply("sel", 3, &edits),
+            ("SELECT * FROM ".to_string(), 14, String::new())
+        );
+
+        let edits = tab_edits("selw", 4, &snippets).unwrap();
+        let (text, _, selected) = apply("selw", 4, &edits);
+        assert_eq!(text, "SELECT * FROM ${table} WHERE ${condition}");
+        assert_eq!(selected, "*");
+
+        // Next Tab: jump to the next placeholder, which has no default
+        let edits = tab_edits(&text, 8, &snippets).unwrap();
+        let (text, cursor, selected) = apply(&text, 8, &edits);
+        assert_eq!(text, "SELECT * FROM  WHERE ${condition}");
+        assert_eq!((cursor, selected.as_str()), (14, ""));
+
+        assert!(tab_edits("SELECT * FROM users", 19, &snippets).is_none());
+    }
+
+    #[test]
+    fn test_add_snippet_validates_trigger() {
+        let mut config = Config::default();
+        assert!(add_snippet(&mut config, "bad trigger", "x").is_err());
+        assert!(add_snippet(&mut config, "x", "  ").is_err());
+        assert!(remove_snippet(&mut config, "missing").is_err());
+    }
+}
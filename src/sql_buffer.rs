@@ -3,27 +3,91 @@
 //! Two consumers share the same lexer:
 //! - [`SqlValidator`] tells reedline whether Enter should submit the buffer
 //!   or insert a newline (unterminated strings / dollar-quotes / block
-//!   comments keep the buffer open);
+//!   comments / open parentheses keep the buffer open);
+//! - [`matching_bracket`] finds the parenthesis matching the one at the
+//!   cursor, for the prompt highlighter;
 //! - [`split_statements`] splits a buffer into individual statements on
 //!   top-level semicolons so multi-statement input (pasted scripts, `\i`
 //!   files) executes statement-by-statement instead of failing in the
-//!   driver's prepared-statement path.
+//!   driver's prepared-statement path;
+//! - [`split_script`] turns a script (`-f file`, piped stdin) into a
+//!   sequence of SQL chunks and backslash commands;
+//! - [`query_fingerprint`] normalizes a query so repeated runs with other
+//!   literals or formatting match (saved column views);
+//! - [`strip_comments`] blanks out comments so token-level checks (`\lint`)
+//!   don't match keywords inside them.
 //!
 //! The lexer understands single-quoted strings (with `''` escapes), quoted
 //! identifiers (with `""` escapes), `--` line comments, nested `/* */` block
-//! comments, and PostgreSQL dollar-quoted blocks (`$tag$ … $tag$`).
+//! comments, and PostgreSQL dollar-quoted blocks (`$tag$ … $tag$`). With a
+//! [`Dialect`] it also follows the connected database's rules: backslash
+//! escapes in MySQL strings, backtick identifiers, no dollar quotes where
+//! `$` is just an identifier character.
 
+use crate::database::DatabaseType;
+use crate::db::Database;
+use crate::sql_parser_trait::parsing_utils;
 use reedline::{ValidationResult, Validator};
+use std::sync::{Arc, Mutex};
+
+/// Quoting rules that decide where strings and identifiers end
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Dialect {
+    /// `\'` does not end a string
+    pub backslash_escapes: bool,
+    /// `$tag$ … $tag$` quotes a string
+    pub dollar_quotes: bool,
+    /// Identifiers may be quoted with backticks
+    pub backtick_identifiers: bool,
+}
+
+impl Default for Dialect {
+    /// PostgreSQL's rules, used when the database is unknown
+    fn default() -> Self {
+        Self::for_database(DatabaseType::PostgreSQL)
+    }
+}
+
+impl Dialect {
+    pub fn for_database(database_type: DatabaseType) -> Self {
+        Self {
+            backslash_escapes: parsing_utils::backslash_escapes_in_strings(database_type.clone()),
+            dollar_quotes: parsing_utils::supports_dollar_quotes(database_type.clone()),
+            backtick_identifiers: parsing_utils::get_quote_char(database_type) == '`',
+        }
+    }
+}
 
 /// Reedline validator: keep the buffer open while a statement is clearly
 /// unterminated. Deliberately conservative — it never demands a trailing
 /// `;` for plain statements, so existing "type a query, press Enter" muscle
 /// memory keeps working.
-pub struct SqlValidator;
+#[derive(Default)]
+pub struct SqlValidator {
+    /// Connection whose dialect applies; PostgreSQL's rules without one
+    database: Option<Arc<Mutex<Database>>>,
+}
+
+impl SqlValidator {
+    pub fn new(database: Arc<Mutex<Database>>) -> Self {
+        Self {
+            database: Some(database),
+        }
+    }
+
+    fn dialect(&self) -> Dialect {
+        // Never wait on a statement or keepalive ping holding the connection
+        self.database
+            .as_ref()
+            .and_then(|database| database.try_lock().ok())
+            .map(|db| Dialect::for_database(db.get_database_type()))
+            .unwrap_or_default()
+    }
+}
 
 impl Validator for SqlValidator {
     fn validate(&self, line: &str) -> ValidationResult {
-        if is_buffer_complete(line) {
+        if is_buffer_complete_in(line, self.dialect()) {
             ValidationResult::Complete
         } else {
             ValidationResult::Incomplete
@@ -36,6 +100,7 @@ enum LexState {
     Normal,
     SingleQuote,
     DoubleQuote,
+    Backtick,
     LineComment,
     BlockComment(u32),
     DollarQuote(String),
@@ -44,6 +109,17 @@ enum LexState {
 /// Scan `input`, invoking `on_statement_sep` with the byte index of every
 /// top-level `;`. Returns the lexer state at end of input.
 fn scan(input: &str, mut on_statement_sep: impl FnMut(usize)) -> LexState {
+    scan_in(input, Dialect::default(), |i, c| {
+        if c == ';' {
+            on_statement_sep(i)
+        }
+    })
+}
+
+/// Scan `input` with `dialect`'s quoting rules, invoking `on_symbol` with
+/// the byte index of every `;`, `(` and `)` outside strings and comments.
+/// Returns the lexer state at end of input.
+fn scan_in(input: &str, dialect: Dialect, mut on_symbol: impl FnMut(usize, char)) -> LexState {
     let mut state = LexState::Normal;
     let mut chars = input.char_indices().peekable();
 
@@ -52,6 +128,7 @@ fn scan(input: &str, mut on_statement_sep: impl FnMut(usize)) -> LexState {
             LexState::Normal => match c {
                 '\'' => state = LexState::SingleQuote,
                 '"' => state = LexState::DoubleQuote,
+                '`' if dialect.backtick_identifiers => state = LexState::Backtick,
                 '-' if matches!(chars.peek(), Some((_, '-'))) => {
                     chars.next();
                     state = LexState::LineComment;
@@ -60,7 +137,7 @@ fn scan(input: &str, mut on_statement_sep: impl FnMut(usize)) -> LexState {
                     chars.next();
                     state = LexState::BlockComment(1);
                 }
-                '$' => {
+                '$' if dialect.dollar_quotes => {
                     // Dollar quote opener: $tag$ where tag is empty or
                     // [A-Za-z_][A-Za-z0-9_]*. Anything else ($1 params,
                     // bare $) is left alone.
@@ -90,11 +167,13 @@ fn scan(input: &str, mut on_statement_sep: impl FnMut(usize)) -> LexState {
                         state = LexState::DollarQuote(tag);
                     }
                 }
-                ';' => on_statement_sep(i),
+                ';' | '(' | ')' => on_symbol(i, c),
                 _ => {}
             },
             LexState::SingleQuote => {
-                if c == '\'' {
+                if c == '\\' && dialect.backslash_escapes {
+                    chars.next(); // \' escape — stay in the string
+                } else if c == '\'' {
                     if matches!(chars.peek(), Some((_, '\''))) {
                         chars.next(); // '' escape — stay in the string
                     } else {
@@ -111,6 +190,11 @@ fn scan(input: &str, mut on_statement_sep: impl FnMut(usize)) -> LexState {
                     }
                 }
             }
+            LexState::Backtick => {
+                if c == '`' {
+                    state = LexState::Normal;
+                }
+            }
             LexState::LineComment => {
                 if c == '\n' {
                     state = LexState::Normal;
@@ -155,17 +239,75 @@ fn scan(input: &str, mut on_statement_sep: impl FnMut(usize)) -> LexState {
 
 /// Whether the buffer is safe to submit: backslash commands, AI input, and
 /// empty lines always are; SQL is complete unless a string, identifier,
-/// dollar-quote, or block comment is left open.
+/// dollar-quote, block comment or parenthesis is left open.
 pub fn is_buffer_complete(buffer: &str) -> bool {
+    is_buffer_complete_in(buffer, Dialect::default())
+}
+
+/// [`is_buffer_complete`] with `dialect`'s quoting rules
+pub fn is_buffer_complete_in(buffer: &str, dialect: Dialect) -> bool {
     let trimmed = buffer.trim();
     if trimmed.is_empty() || trimmed.starts_with('\\') || trimmed.starts_with("??") {
         return true;
     }
-    matches!(
-        scan(buffer, |_| {}),
-        // A line comment is terminated by end-of-input just as well as by \n
-        LexState::Normal | LexState::LineComment
-    )
+    // A stray `)` does not make up for a `(` opened after it
+    let mut depth = 0usize;
+    let state = scan_in(buffer, dialect, |_, c| match c {
+        '(' => depth += 1,
+        ')' => depth = depth.saturating_sub(1),
+        _ => {}
+    });
+    depth == 0
+        && matches!(
+            state,
+            // A line comment is terminated by end-of-input just as well as by \n
+            LexState::Normal | LexState::LineComment
+        )
+}
+
+/// Byte offsets of the parenthesis next to `cursor` (the one just before
+/// it, else the one under it) and of its match, outside strings and
+/// comments; the match is `None` when the parenthesis is unbalanced
+pub fn matching_bracket(line: &str, cursor: usize) -> Option<(usize, Option<usize>)> {
+    let mut brackets = Vec::new();
+    scan_in(line, Dialect::default(), |i, c| {
+        if c != ';' {
+            brackets.push((i, c));
+        }
+    });
+    let index = brackets
+        .iter()
+        .position(|&(i, _)| i + 1 == cursor)
+        .or_else(|| brackets.iter().position(|&(i, _)| i == cursor))?;
+
+    let (position, bracket) = brackets[index];
+    let mut depth = 0usize;
+    let found = if bracket == '(' {
+        brackets[index + 1..].iter().find(|&&(_, c)| {
+            if c == '(' {
+                depth += 1;
+                false
+            } else if depth == 0 {
+                true
+            } else {
+                depth -= 1;
+                false
+            }
+        })
+    } else {
+        brackets[..index].iter().rev().find(|&&(_, c)| {
+            if c == ')' {
+                depth += 1;
+                false
+            } else if depth == 0 {
+                true
+            } else {
+                depth -= 1;
+                false
+            }
+        })
+    };
+    Some((position, found.map(|&(i, _)| i)))
 }
 
 /// True if `segment` contains anything besides whitespace and comments.
@@ -239,6 +381,194 @@ pub fn split_statements(buffer: &str) -> Vec<String> {
     statements
 }
 
+/// Split a script into commands the way psql reads `-f` files: a line
+/// starting with `\` (outside any string, comment or dollar-quote) is a
+/// backslash command of its own, everything else is SQL. Consecutive SQL
+/// lines come back as one chunk for [`split_statements`] to split further.
+pub fn split_script(script: &str) -> Vec<String> {
+    let mut commands = Vec::new();
+    let mut sql = String::new();
+
+    for line in script.lines() {
+        if line.trim_start().starts_with('\\') && is_buffer_complete(&sql) {
+            if has_sql_content(&sql) {
+                commands.push(sql.trim().to_string());
+            }
+            sql.clear();
+            commands.push(line.trim().to_string());
+        } else {
+            sql.push_str(line);
+            sql.push('\n');
+        }
+    }
+    if has_sql_content(&sql) {
+        commands.push(sql.trim().to_string());
+    }
+
+    commands
+}
+
+/// Normalized form of a query for recognizing repeated runs: comments
+/// dropped, string and numeric literals replaced by `?`, whitespace
+/// collapsed, unquoted text lowercased, trailing semicolons removed.
+///
+/// `SELECT *  FROM t WHERE id = 42;` → `select * from t where id = ?`
+pub fn query_fingerprint(query: &str) -> String {
+    let mut out = String::with_capacity(query.len());
+    let mut chars = query.chars().peekable();
+    let mut pending_space = false;
+
+    let push = |out: &mut String, pending_space: &mut bool, text: &str| {
+        if *pending_space && !out.is_empty() {
+            out.push(' ');
+        }
+        *pending_space = false;
+        out.push_str(text);
+    };
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => pending_space = true,
+            '-' if chars.peek() == Some(&'-') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+                pending_space = true;
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut depth = 1u32;
+                while let Some(c) = chars.next() {
+                    match c {
+                        '*' if chars.peek() == Some(&'/') => {
+                            chars.next();
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        '/' if chars.peek() == Some(&'*') => {
+                            chars.next();
+                            depth += 1;
+                        }
+                        _ => {}
+                    }
+                }
+                pending_space = true;
+            }
+            '\'' => {
+                while let Some(c) = chars.next() {
+                    if c == '\'' {
+                        if chars.peek() == Some(&'\'') {
+                            chars.next(); // '' escape
+                        } else {
+                            break;
+                        }
+                    }
+                }
+                push(&mut out, &mut pending_space, "?");
+            }
+            '"' => {
+                let mut ident = String::from('"');
+                for c in chars.by_ref() {
+                    ident.push(c);
+                    if c == '"' {
+                        break;
+                    }
+                }
+                push(&mut out, &mut pending_space, &ident);
+            }
+            c if c.is_ascii_digit()
+                && !out
+                    .chars()
+                    .last()
+                    .is_some_and(|p| !pending_space && (p.is_alphanumeric() || p == '_')) =>
+            {
+                while chars
+                    .peek()
+                    .is_some_and(|c| c.is_ascii_alphanumeric() || *c == '.')
+                {
+                    chars.next();
+                }
+                push(&mut out, &mut pending_space, "?");
+            }
+            c => {
+                let lower: String = c.to_lowercase().collect();
+                push(&mut out, &mut pending_space, &lower);
+            }
+        }
+    }
+
+    out.trim_end_matches([';', ' ']).to_string()
+}
+
+/// Replace `--` and `/* */` comments with spaces, keeping byte offsets and
+/// line breaks intact. Quoted strings and identifiers are left untouched.
+pub fn strip_comments(sql: &str) -> String {
+    let mut out = String::with_capacity(sql.len());
+    let mut chars = sql.chars().peekable();
+    let blank = |out: &mut String, c: char| {
+        if c == '\n' {
+            out.push('\n');
+        } else {
+            out.extend(std::iter::repeat_n(' ', c.len_utf8()));
+        }
+    };
+
+    while let Some(c) = chars.next() {
+        match c {
+            '-' if chars.peek() == Some(&'-') => {
+                out.push_str("  ");
+                chars.next();
+                while let Some(&c) = chars.peek() {
+                    if c == '\n' {
+                        break;
+                    }
+                    blank(&mut out, c);
+                    chars.next();
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                out.push_str("  ");
+                chars.next();
+                let mut depth = 1u32;
+                while let Some(c) = chars.next() {
+                    match c {
+                        '*' if chars.peek() == Some(&'/') => {
+                            out.push_str("  ");
+                            chars.next();
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        '/' if chars.peek() == Some(&'*') => {
+                            out.push_str("  ");
+                            chars.next();
+                            depth += 1;
+                        }
+                        c => blank(&mut out, c),
+                    }
+                }
+            }
+            '\'' | '"' => {
+                out.push(c);
+                for inner in chars.by_ref() {
+                    out.push(inner);
+                    if inner == c {
+                        break;
+                    }
+                }
+            }
+            c => out.push(c),
+        }
+    }
+
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -275,6 +605,32 @@ mod tests {
         assert!(!is_buffer_complete("SELECT $tag$text"));
     }
 
+    #[test]
+    fn open_parentheses_and_dialect_quoting() {
+        assert!(!is_buffer_complete("SELECT count(*\nFROM t"));
+        assert!(!is_buffer_complete("SELECT ) (1"));
+        assert!(is_buffer_complete("SELECT '(' FROM t -- ("));
+        assert!(is_buffer_complete("SELECT (1))"));
+
+        let mysql = Dialect::for_database(DatabaseType::MySQL);
+        assert!(!is_buffer_complete_in("SELECT 'it\\'s", mysql));
+        assert!(is_buffer_complete_in("SELECT 'it\\'s'", mysql));
+        assert!(is_buffer_complete("SELECT 'C:\\'"));
+        assert!(!is_buffer_complete_in("SELECT `odd(name", mysql));
+        // `$` is an identifier character in MySQL, not a dollar quote
+        assert!(is_buffer_complete_in("SELECT a$b$c FROM t", mysql));
+    }
+
+    #[test]
+    fn matching_bracket_skips_strings() {
+        let line = "SELECT f(a, ')', (b))";
+        assert_eq!(matching_bracket(line, 9), Some((8, Some(20))));
+        assert_eq!(matching_bracket(line, 21), Some((20, Some(8))));
+        assert_eq!(matching_bracket(line, 17), Some((17, Some(19))));
+        assert_eq!(matching_bracket("SELECT (1", 8), Some((7, None)));
+        assert_eq!(matching_bracket(line, 3), None);
+    }
+
     #[test]
     fn split_basic() {
         assert_eq!(
@@ -326,7 +682,7 @@ mod tests {
 
     #[test]
     fn validator_matches_buffer_completeness() {
-        let validator = SqlValidator;
+        let validator = SqlValidator::default();
         assert!(matches!(
             validator.validate("SELECT 1"),
             ValidationResult::Complete
@@ -336,4 +692,51 @@ mod tests {
             ValidationResult::Incomplete
         ));
     }
+
+    #[test]
+    fn split_script_separates_backslash_commands() {
+        let script = "\\x on\nSELECT 1;\nSELECT 'a\n\\not a command';\n-- done\n\\dt\nSELECT 2";
+        assert_eq!(
+            split_script(script),
+            vec![
+                "\\x on",
+                "SELECT 1;\nSELECT 'a\n\\not a command';\n-- done",
+                "\\dt",
+                "SELECT 2"
+            ]
+        );
+        assert!(split_script("-- only comments\n").is_empty());
+    }
+
+    #[test]
+    fn query_fingerprint_ignores_literals_and_formatting() {
+        assert_eq!(
+            query_fingerprint("SELECT *  FROM t\nWHERE id = 42; "),
+            "select * from t where id = ?"
+        );
+        assert_eq!(
+            query_fingerprint("select * from t where id = 7 -- again"),
+            query_fingerprint("SELECT * FROM t WHERE id = 42;")
+        );
+        assert_eq!(
+            query_fingerprint("SELECT name FROM \"Users\" WHERE note = 'it''s' /* x */ LIMIT 10"),
+            "select name from \"Users\" where note = ? limit ?"
+        );
+        // Digits inside identifiers are kept
+        assert_eq!(
+            query_fingerprint("SELECT col1 FROM t2"),
+            "select col1 from t2"
+        );
+    }
+
+    #[test]
+    fn strip_comments_keeps_offsets_and_strings() {
+        let sql = "SELECT 1 -- where\nFROM t /* delete */ WHERE a = '--x'";
+        let stripped = strip_comments(sql);
+        assert_eq!(stripped.len(), sql.len());
+        assert_eq!(
+            stripped,
+            "SELECT 1         \nFROM t              WHERE a = '--x'"
+        );
+    }
 }
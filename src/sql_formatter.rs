@@ -0,0 +1,680 @@
+//! Dialect-aware SQL pretty-printer (`\paste`, `\fmt`, `[history] store_formatted`)
+//!
+//! A layout pass over tokens rather than a full parser: major clauses start
+//! their own line with their body indented below, select lists, `AND`/`OR`
+//! conditions and joins get one line each, and subqueries are indented one
+//! level deeper than the line that opens them. Keywords are uppercased;
+//! identifiers, literals and comments are kept as written. Statements that
+//! are not queries or DML (`SET`, `GRANT`, `ALTER`, ...) only get their
+//! whitespace and keywords normalized.
+//!
+//! The dialect decides the lexing details: backslash escapes and `#`
+//! comments for MySQL, dollar-quoted bodies for PostgreSQL.
+
+use crate::database::DatabaseType;
+
+const INDENT: &str = "  ";
+
+/// Where a keyword phrase goes in the layout
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Role {
+    /// Own line, body indented on the lines below (`SELECT`, `WHERE`)
+    Clause,
+    /// Own line, body kept on the same line (`LIMIT 10`)
+    InlineClause,
+    /// Own line between two queries (`UNION ALL`)
+    SetOperator,
+    /// Own line, indented like the `FROM` body
+    Join,
+    /// Kept inline; only listed so its words are not taken for a clause
+    Plain,
+}
+
+/// Keyword phrases, longest first for phrases sharing a first word
+const PHRASES: &[(&str, Role)] = &[
+    ("SELECT DISTINCT", Role::Clause),
+    ("SELECT", Role::Clause),
+    ("FROM", Role::Clause),
+    ("WHERE", Role::Clause),
+    ("GROUP BY", Role::Clause),
+    ("HAVING", Role::Clause),
+    ("ORDER BY", Role::Clause),
+    ("WINDOW", Role::Clause),
+    ("VALUES", Role::Clause),
+    ("INSERT INTO", Role::Clause),
+    ("INSERT", Role::Clause),
+    ("REPLACE INTO", Role::Clause),
+    ("UPDATE", Role::Clause),
+    ("SET", Role::Clause),
+    ("DELETE FROM", Role::Clause),
+    ("RETURNING", Role::Clause),
+    ("WITH RECURSIVE", Role::Clause),
+    ("WITH", Role::Clause),
+    ("DO UPDATE SET", Role::Clause),
+    ("DO NOTHING", Role::InlineClause),
+    ("ON DUPLICATE KEY UPDATE", Role::Clause),
+    ("ON CONFLICT", Role::InlineClause),
+    ("LIMIT", Role::InlineClause),
+    ("OFFSET", Role::InlineClause),
+    ("FETCH", Role::InlineClause),
+    ("FOR UPDATE", Role::InlineClause),
+    ("FOR SHARE", Role::InlineClause),
+    ("UNION ALL", Role::SetOperator),
+    ("UNION", Role::SetOperator),
+    ("INTERSECT", Role::SetOperator),
+    ("EXCEPT", Role::SetOperator),
+    ("LEFT OUTER JOIN", Role::Join),
+    ("RIGHT OUTER JOIN", Role::Join),
+    ("FULL OUTER JOIN", Role::Join),
+    ("INNER JOIN", Role::Join),
+    ("LEFT JOIN", Role::Join),
+    ("RIGHT JOIN", Role::Join),
+    ("FULL JOIN", Role::Join),
+    ("CROSS JOIN", Role::Join),
+    ("NATURAL JOIN", Role::Join),
+    ("JOIN", Role::Join),
+    ("IS NOT DISTINCT FROM", Role::Plain),
+    ("IS DISTINCT FROM", Role::Plain),
+];
+
+/// Phrases that only start a clause at the beginning of a (sub)query or
+/// right after a CTE, so `timestamp WITH TIME ZONE` stays inline
+const LEADING: &[&str] = &[
+    "WITH",
+    "WITH RECURSIVE",
+    "INSERT INTO",
+    "INSERT",
+    "REPLACE INTO",
+    "UPDATE",
+    "DELETE FROM",
+];
+
+/// First words of the statements that get the full layout
+const LAYOUT_STATEMENTS: &[&str] = &[
+    "SELECT", "WITH", "INSERT", "REPLACE", "UPDATE", "DELETE", "VALUES", "EXPLAIN", "CREATE",
+];
+
+/// Words uppercased wherever they appear unqualified
+const KEYWORDS: &[&str] = &[
+    "ALL",
+    "ALTER",
+    "ANALYZE",
+    "AND",
+    "ANY",
+    "AS",
+    "ASC",
+    "BEGIN",
+    "BETWEEN",
+    "BY",
+    "CASE",
+    "CAST",
+    "COMMIT",
+    "CONFLICT",
+    "CREATE",
+    "CROSS",
+    "DEFAULT",
+    "DELETE",
+    "DESC",
+    "DISTINCT",
+    "DO",
+    "DROP",
+    "DUPLICATE",
+    "ELSE",
+    "END",
+    "EXCEPT",
+    "EXISTS",
+    "EXPLAIN",
+    "FALSE",
+    "FETCH",
+    "FOR",
+    "FROM",
+    "FULL",
+    "GRANT",
+    "GROUP",
+    "HAVING",
+    "ILIKE",
+    "IN",
+    "INNER",
+    "INSERT",
+    "INTERSECT",
+    "INTO",
+    "IS",
+    "JOIN",
+    "LATERAL",
+    "LEFT",
+    "LIKE",
+    "LIMIT",
+    "NATURAL",
+    "NOT",
+    "NULL",
+    "OFFSET",
+    "ON",
+    "OR",
+    "ORDER",
+    "OUTER",
+    "OVER",
+    "PARTITION",
+    "RECURSIVE",
+    "REPLACE",
+    "RETURNING",
+    "REVOKE",
+    "RIGHT",
+    "ROLLBACK",
+    "SELECT",
+    "SET",
+    "SHARE",
+    "TABLE",
+    "THEN",
+    "TO",
+    "TRUE",
+    "TRUNCATE",
+    "UNION",
+    "UPDATE",
+    "USING",
+    "VALUES",
+    "VIEW",
+    "WHEN",
+    "WHERE",
+    "WINDOW",
+    "WITH",
+];
+
+/// Multi-character operators, longest first
+const OPERATORS: &[&str] = &[
+    "#>>", "->>", "!~*", "::", "<=", ">=", "<>", "!=", "||", "->", "#>", "@>", "<@", "&&", ":=",
+    "=>", "<<", ">>", "~~", "!~", "~*", "?|", "?&",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Word,
+    Quoted,
+    Number,
+    LineComment,
+    BlockComment,
+    Open,
+    Close,
+    Comma,
+    Semicolon,
+    Dot,
+    Operator,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token<'a> {
+    kind: Kind,
+    text: &'a str,
+    /// Whitespace separated it from the previous token in the input
+    space_before: bool,
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// Kind and byte length of the token at the start of `rest`
+fn lex(rest: &str, db_type: &DatabaseType) -> (Kind, usize) {
+    let mut chars = rest.chars();
+    let c = chars.next().unwrap_or(' ');
+    let next = chars.next();
+    let mysql = *db_type == DatabaseType::MySQL;
+    let word_len = |from: usize| {
+        from + rest[from..]
+            .find(|c: char| !is_word_char(c))
+            .unwrap_or(rest.len() - from)
+    };
+
+    if rest.starts_with("--") || (mysql && c == '#') {
+        return (Kind::LineComment, rest.find('\n').unwrap_or(rest.len()));
+    }
+    if rest.starts_with("/*") {
+        let mut depth = 0;
+        let mut i = 0;
+        while i < rest.len() {
+            if rest[i..].starts_with("/*") {
+                depth += 1;
+                i += 2;
+            } else if rest[i..].starts_with("*/") {
+                depth -= 1;
+                i += 2;
+                if depth == 0 {
+                    return (Kind::BlockComment, i);
+                }
+            } else {
+                i += rest[i..].chars().next().map_or(1, char::len_utf8);
+            }
+        }
+        return (Kind::BlockComment, rest.len());
+    }
+    if matches!(c, '\'' | '"' | '`') {
+        let backslash_escapes = mysql && c != '`';
+        let mut inner = rest.char_indices().skip(1).peekable();
+        while let Some((i, ch)) = inner.next() {
+            if backslash_escapes && ch == '\\' {
+                inner.next();
+            } else if ch == c {
+                if inner.peek().is_some_and(|(_, after)| *after == c) {
+                    inner.next(); // doubled quote
+                } else {
+                    return (Kind::Quoted, i + 1);
+                }
+            }
+        }
+        return (Kind::Quoted, rest.len());
+    }
+    if c == '$' {
+        if next.is_some_and(|n| n.is_ascii_digit()) {
+            return (Kind::Word, word_len(1));
+        }
+        if *db_type == DatabaseType::PostgreSQL {
+            let tag_end = 1 + rest[1..]
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len() - 1);
+            if rest[tag_end..].starts_with('$') {
+                let tag = &rest[..=tag_end];
+                let body = tag.len();
+                return match rest[body..].find(tag) {
+                    Some(close) => (Kind::Quoted, body + close + tag.len()),
+                    None => (Kind::Quoted, rest.len()),
+                };
+            }
+        }
+    }
+    if c.is_ascii_digit() {
+        let len = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '.'))
+            .unwrap_or(rest.len());
+        return (Kind::Number, len);
+    }
+    if is_word_char(c) {
+        return (Kind::Word, word_len(c.len_utf8()));
+    }
+    // `@var` / `@@session.x` (MySQL), `:name` bind parameters
+    if (c == '@' || c == ':') && next.is_some_and(|n| n == '@' || n.is_alphabetic()) {
+        let start = if next == Some('@') { 2 } else { 1 };
+        return (Kind::Word, word_len(start));
+    }
+    let kind = match c {
+        '(' => Kind::Open,
+        ')' => Kind::Close,
+        ',' => Kind::Comma,
+        ';' => Kind::Semicolon,
+        '.' => Kind::Dot,
+        _ => {
+            let len = OPERATORS
+                .iter()
+                .find(|op| rest.starts_with(**op))
+                .map_or(c.len_utf8(), |op| op.len());
+            return (Kind::Operator, len);
+        }
+    };
+    (kind, 1)
+}
+
+fn tokenize<'a>(sql: &'a str, db_type: &DatabaseType) -> Vec<Token<'a>> {
+    let mut tokens = Vec::new();
+    let mut space_before = false;
+    let mut i = 0;
+    while let Some(c) = sql[i..].chars().next() {
+        if c.is_whitespace() {
+            space_before = true;
+            i += c.len_utf8();
+            continue;
+        }
+        let (kind, len) = lex(&sql[i..], db_type);
+        tokens.push(Token {
+            kind,
+            text: &sql[i..i + len],
+            space_before,
+        });
+        space_before = false;
+        i += len;
+    }
+    tokens
+}
+
+/// `sql` with string literals, quoted identifiers, dollar-quoted bodies and
+/// comments blanked out, byte offsets and line breaks kept, for searches
+/// that must only look at the statement's own words
+pub(crate) fn blank_literals(sql: &str, db_type: &DatabaseType) -> String {
+    let mut out = String::with_capacity(sql.len());
+    let mut i = 0;
+    while let Some(c) = sql[i..].chars().next() {
+        if c.is_whitespace() {
+            out.push(c);
+            i += c.len_utf8();
+            continue;
+        }
+        let (kind, len) = lex(&sql[i..], db_type);
+        let text = &sql[i..i + len];
+        if matches!(kind, Kind::Quoted | Kind::LineComment | Kind::BlockComment) {
+            for c in text.chars() {
+                if c == '\n' {
+                    out.push('\n');
+                } else {
+                    out.extend(std::iter::repeat_n(' ', c.len_utf8()));
+                }
+            }
+        } else {
+            out.push_str(text);
+        }
+        i += len;
+    }
+    out
+}
+
+/// The phrase starting at `tokens[0]`, with its role and token count
+fn match_phrase(tokens: &[Token]) -> Option<(&'static str, Role, usize)> {
+    PHRASES.iter().find_map(|(phrase, role)| {
+        let words = phrase.split(' ').count();
+        let matches = tokens.len() >= words
+            && phrase.split(' ').zip(tokens).all(|(word, token)| {
+                token.kind == Kind::Word && token.text.eq_ignore_ascii_case(word)
+            });
+        matches.then_some((*phrase, *role, words))
+    })
+}
+
+fn is_keyword(word: &str) -> bool {
+    KEYWORDS.iter().any(|k| k.eq_ignore_ascii_case(word))
+}
+
+fn needs_space(prev: Option<&Token>, cur: &Token) -> bool {
+    let Some(prev) = prev else {
+        return false;
+    };
+    if prev.text == "::" || cur.text == "::" || prev.text == "[" || cur.text == "]" {
+        return false;
+    }
+    match (prev.kind, cur.kind) {
+        (_, Kind::Comma | Kind::Semicolon | Kind::Close | Kind::Dot)
+        | (Kind::Open | Kind::Dot, _) => false,
+        // Function calls, `E'...'` strings and subscripts stay glued as typed
+        (Kind::Word | Kind::Quoted | Kind::Close, Kind::Open) | (Kind::Word, Kind::Quoted) => {
+            cur.space_before
+        }
+        (_, Kind::Operator) if cur.text == "[" => cur.space_before,
+        _ => true,
+    }
+}
+
+/// One level of parentheses
+struct Frame {
+    /// Indent of the clause keywords in this frame
+    base: usize,
+    /// Indent of the line the closing parenthesis goes on
+    close_indent: usize,
+    /// Parenthesis around an expression or list rather than a query
+    inline: bool,
+    /// Nothing emitted in this frame yet
+    fresh: bool,
+    /// A clause keyword was emitted, so commas and conditions break lines
+    in_clause: bool,
+    case_depth: usize,
+    /// The next `AND` belongs to a `BETWEEN`
+    between: bool,
+}
+
+impl Frame {
+    fn query(base: usize, close_indent: usize) -> Self {
+        Frame {
+            base,
+            close_indent,
+            inline: false,
+            fresh: true,
+            in_clause: false,
+            case_depth: 0,
+            between: false,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Writer {
+    out: String,
+    indent: usize,
+    line_start: bool,
+}
+
+impl Writer {
+    fn newline(&mut self, indent: usize) {
+        self.indent = indent;
+        if self.line_start || self.out.is_empty() {
+            return;
+        }
+        let trimmed = self.out.trim_end_matches(' ').len();
+        self.out.truncate(trimmed);
+        self.out.push('\n');
+        self.line_start = true;
+    }
+
+    fn push(&mut self, text: &str, space: bool) {
+        if self.line_start {
+            self.out.push_str(&INDENT.repeat(self.indent));
+            self.line_start = false;
+        } else if space && !self.out.is_empty() {
+            self.out.push(' ');
+        }
+        self.out.push_str(text);
+    }
+}
+
+/// Pretty-print `sql` (one or more statements) for `db_type`
+pub fn format_sql(sql: &str, db_type: &DatabaseType) -> String {
+    let tokens = tokenize(sql, db_type);
+    let mut w = Writer::default();
+    let mut frames = vec![Frame::query(0, 0)];
+    let mut layout = true;
+    // A token other than a comment was seen in the current statement
+    let mut started = false;
+    let mut prev: Option<Token> = None;
+    let mut glue_next = false;
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let tok = tokens[i];
+        i += 1;
+        let space = !glue_next && needs_space(prev.as_ref(), &tok);
+        glue_next = false;
+        if !started && tok.kind == Kind::Word {
+            layout = LAYOUT_STATEMENTS
+                .iter()
+                .any(|s| s.eq_ignore_ascii_case(tok.text));
+        }
+        started |= !matches!(tok.kind, Kind::LineComment | Kind::BlockComment);
+        let frame = frames
+            .last_mut()
+            .expect("the statement frame is never popped");
+        let fresh = std::mem::replace(&mut frame.fresh, false);
+        let breaks = layout && !frame.inline;
+
+        match tok.kind {
+            Kind::Word => {
+                let qualified = prev.is_some_and(|p| p.kind == Kind::Dot)
+                    || tokens.get(i).is_some_and(|t| t.kind == Kind::Dot);
+                let phrase = match_phrase(&tokens[i - 1..]).filter(|(phrase, _, _)| {
+                    !qualified
+                        && (!LEADING.contains(phrase)
+                            || fresh
+                            || prev.is_some_and(|p| p.kind == Kind::Close))
+                });
+                if let Some((phrase, role, words)) = phrase {
+                    i += words - 1;
+                    let layout_role = if breaks { role } else { Role::Plain };
+                    match layout_role {
+                        Role::Clause | Role::InlineClause => {
+                            w.newline(frame.base);
+                            w.push(phrase, false);
+                            if layout_role == Role::Clause {
+                                w.newline(frame.base + 1);
+                            }
+                            frame.in_clause = true;
+                        }
+                        Role::SetOperator => {
+                            w.newline(frame.base);
+                            w.push(phrase, false);
+                            w.newline(frame.base);
+                            frame.in_clause = false;
+                        }
+                        Role::Join => {
+                            w.newline(frame.base + 1);
+                            w.push(phrase, false);
+                        }
+                        Role::Plain => w.push(phrase, space),
+                    }
+                    // Stand-in for the phrase: a keyword, never a function name
+                    prev = Some(Token {
+                        kind: Kind::Word,
+                        text: "AS",
+                        space_before: true,
+                    });
+                    continue;
+                }
+
+                let upper = tok.text.to_ascii_uppercase();
+                let keyword = !qualified && is_keyword(&upper);
+                let text = if keyword { upper.as_str() } else { tok.text };
+                match text {
+                    "AND" if frame.between => {
+                        frame.between = false;
+                        w.push(text, space);
+                    }
+                    "AND" | "OR" if breaks && frame.in_clause && frame.case_depth == 0 => {
+                        w.newline(frame.base + 1);
+                        w.push(text, false);
+                    }
+                    _ => w.push(text, space),
+                }
+                match text {
+                    "BETWEEN" => frame.between = true,
+                    "CASE" => frame.case_depth += 1,
+                    "END" => frame.case_depth = frame.case_depth.saturating_sub(1),
+                    _ => {}
+                }
+            }
+            Kind::Open => {
+                w.push("(", space);
+                let subquery = layout
+                    && tokens.get(i).is_some_and(|t| {
+                        t.kind == Kind::Word
+                            && ["SELECT", "WITH", "VALUES"]
+                                .iter()
+                                .any(|k| t.text.eq_ignore_ascii_case(k))
+                    });
+                let indent = w.indent;
+                frames.push(if subquery {
+                    Frame::query(indent + 1, indent)
+                } else {
+                    Frame {
+                        inline: true,
+                        ..Frame::query(indent, indent)
+                    }
+                });
+            }
+            Kind::Close => {
+                if frames.len() > 1 {
+                    let closed = frames.pop().expect("checked above");
+                    if !closed.inline {
+                        w.newline(closed.close_indent);
+                    }
+                }
+                w.push(")", false);
+            }
+            Kind::Comma => {
+                w.push(",", false);
+                if breaks && frame.in_clause && frame.case_depth == 0 {
+                    w.newline(frame.base + 1);
+                }
+            }
+            Kind::Semicolon => {
+                w.push(";", false);
+                frames.truncate(1);
+                frames[0] = Frame::query(0, 0);
+                layout = true;
+                started = false;
+                prev = None;
+                if i < tokens.len() {
+                    w.newline(0);
+                    w.out.push('\n');
+                }
+                continue;
+            }
+            Kind::LineComment => {
+                w.push(tok.text.trim_end(), space);
+                let indent = w.indent;
+                w.newline(indent);
+            }
+            Kind::Operator => {
+                w.push(tok.text, space);
+                let unary = matches!(tok.text, "-" | "+")
+                    && prev.is_none_or(|p| {
+                        matches!(p.kind, Kind::Operator | Kind::Open | Kind::Comma)
+                            || (p.kind == Kind::Word && is_keyword(p.text))
+                    });
+                glue_next = unary && tokens.get(i).is_some_and(|t| !t.space_before);
+            }
+            Kind::Quoted | Kind::Number | Kind::BlockComment | Kind::Dot => w.push(tok.text, space),
+        }
+        prev = Some(tok);
+    }
+    w.out.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pg(sql: &str) -> String {
+        format_sql(sql, &DatabaseType::PostgreSQL)
+    }
+
+    #[test]
+    fn test_clauses_lists_and_joins() {
+        assert_eq!(
+            pg(
+                "select u.id, count(*) as n from users u left join orders o on o.user_id = u.id \
+                where u.active and o.total between 10 and 20 group by u.id order by n desc limit 5"
+            ),
+            "SELECT\n  u.id,\n  count(*) AS n\nFROM\n  users u\n  LEFT JOIN orders o ON o.user_id = u.id\n\
+             WHERE\n  u.active\n  AND o.total BETWEEN 10 AND 20\nGROUP BY\n  u.id\nORDER BY\n  n DESC\nLIMIT 5"
+        );
+        assert_eq!(
+            pg("insert into t (a, b) values (1, -2) on conflict (a) do nothing"),
+            "INSERT INTO\n  t (a, b)\nVALUES\n  (1, -2)\nON CONFLICT (a)\nDO NOTHING"
+        );
+    }
+
+    #[test]
+    fn test_subqueries_and_statements() {
+        assert_eq!(
+            pg(
+                "with x as (select id from a where id in (select a_id from b)) select * from x; -- path\nset search_path to app"
+            ),
+            "WITH\n  x AS (\n    SELECT\n      id\n    FROM\n      a\n    WHERE\n      id IN (\n        SELECT\n          a_id\n        FROM\n          b\n      )\n  )\n\
+             SELECT\n  *\nFROM\n  x;\n\n-- path\nSET search_path TO app"
+        );
+        assert_eq!(
+            pg("select case when a and b then 'x' end, d::date, data->>'k', arr[1] from t"),
+            "SELECT\n  CASE WHEN a AND b THEN 'x' END,\n  d::date,\n  data ->> 'k',\n  arr[1]\nFROM\n  t"
+        );
+    }
+
+    #[test]
+    fn test_literals_and_comments_are_kept() {
+        assert_eq!(
+            pg("select 'a  from b', \"Select\" -- keep me\nfrom t /* where */"),
+            "SELECT\n  'a  from b',\n  \"Select\" -- keep me\nFROM\n  t /* where */"
+        );
+        assert_eq!(
+            pg("select $$ select 1 $$, $body$x$body$"),
+            "SELECT\n  $$ select 1 $$,\n  $body$x$body$"
+        );
+        assert_eq!(
+            format_sql(
+                "select `from`, 'it\\'s' # note\nfrom t",
+                &DatabaseType::MySQL
+            ),
+            "SELECT\n  `from`,\n  'it\\'s' # note\nFROM\n  t"
+        );
+    }
+}
@@ -0,0 +1,740 @@
+//! Syntax help for SQL statements (`\h <SQL keyword>`)
+//!
+//! Each topic carries a portable synopsis plus variants for the dialects that
+//! differ in ways worth knowing at the prompt (`ON CONFLICT` vs `ON DUPLICATE
+//! KEY UPDATE`, `EXPLAIN QUERY PLAN`, ClickHouse mutations, ...). The variant
+//! for the connected database is shown; other backends get the synopsis.
+
+use crate::database::DatabaseType;
+
+struct SqlTopic {
+    keyword: &'static str,
+    summary: &'static str,
+    syntax: &'static str,
+    dialects: &'static [(DatabaseType, &'static str)],
+}
+
+const TOPICS: &[SqlTopic] = &[
+    SqlTopic {
+        keyword: "SELECT",
+        summary: "retrieve rows from tables, views or subqueries",
+        syntax: "SELECT [DISTINCT] expression [AS alias], ...
+  FROM from_item [JOIN ...]
+  [WHERE condition]
+  [GROUP BY expression, ...] [HAVING condition]
+  [ORDER BY expression [ASC|DESC], ...]
+  [LIMIT count] [OFFSET start]",
+        dialects: &[
+            (
+                DatabaseType::PostgreSQL,
+                "SELECT [ALL | DISTINCT [ON (expression, ...)]] expression [AS alias], ...
+  FROM from_item [, ...]
+  [WHERE condition]
+  [GROUP BY [ALL | DISTINCT] grouping_element, ...] [HAVING condition]
+  [WINDOW name AS (window_definition)]
+  [ORDER BY expression [ASC|DESC] [NULLS {FIRST|LAST}], ...]
+  [LIMIT {count|ALL}] [OFFSET start]
+  [FETCH {FIRST|NEXT} count {ROW|ROWS} {ONLY|WITH TIES}]
+  [FOR {UPDATE|NO KEY UPDATE|SHARE} [OF table] [NOWAIT|SKIP LOCKED]]",
+            ),
+            (
+                DatabaseType::MySQL,
+                "SELECT [DISTINCT] [SQL_NO_CACHE] expression [AS alias], ...
+  FROM table_references [PARTITION (p, ...)]
+  [WHERE condition]
+  [GROUP BY expression, ... [WITH ROLLUP]] [HAVING condition]
+  [WINDOW name AS (window_spec)]
+  [ORDER BY expression [ASC|DESC], ...]
+  [LIMIT {count | offset, count | count OFFSET offset}]
+  [FOR {UPDATE|SHARE} [NOWAIT|SKIP LOCKED]]",
+            ),
+            (
+                DatabaseType::SQLite,
+                "SELECT [DISTINCT] expression [AS alias], ...
+  FROM table_or_subquery [JOIN ...]
+  [WHERE condition]
+  [GROUP BY expression, ...] [HAVING condition]
+  [WINDOW name AS (window_definition)]
+  [ORDER BY expression [ASC|DESC] [NULLS {FIRST|LAST}], ...]
+  [LIMIT count [OFFSET start]]",
+            ),
+            (
+                DatabaseType::ClickHouse,
+                "SELECT [DISTINCT [ON (column, ...)]] expression [AS alias], ...
+  FROM table [FINAL] [SAMPLE k [OFFSET m]]
+  [ARRAY JOIN array_column]
+  [PREWHERE condition] [WHERE condition]
+  [GROUP BY expression, ... [WITH ROLLUP|WITH CUBE] [WITH TOTALS]] [HAVING condition]
+  [ORDER BY expression [ASC|DESC] [WITH FILL], ...]
+  [LIMIT n BY column, ...]
+  [LIMIT [offset,] count]
+  [SETTINGS name = value, ...]
+  [FORMAT format]",
+            ),
+        ],
+    },
+    SqlTopic {
+        keyword: "INSERT",
+        summary: "add rows to a table",
+        syntax: "INSERT INTO table [(column, ...)]
+  {VALUES (value, ...), ... | SELECT ...}",
+        dialects: &[
+            (
+                DatabaseType::PostgreSQL,
+                "INSERT INTO table [AS alias] [(column, ...)]
+  {DEFAULT VALUES | VALUES (value, ...), ... | SELECT ...}
+  [ON CONFLICT [(column, ...) | ON CONSTRAINT name]
+     {DO NOTHING | DO UPDATE SET column = EXCLUDED.column, ... [WHERE condition]}]
+  [RETURNING expression, ...]",
+            ),
+            (
+                DatabaseType::MySQL,
+                "INSERT [IGNORE] INTO table [(column, ...)]
+  {VALUES (value, ...), ... | SELECT ... | SET column = value, ...}
+  [AS new_row]
+  [ON DUPLICATE KEY UPDATE column = new_row.column, ...]
+
+REPLACE INTO table ... deletes the conflicting row first.",
+            ),
+            (
+                DatabaseType::SQLite,
+                "INSERT [OR {REPLACE|IGNORE|ABORT|FAIL|ROLLBACK}] INTO table [(column, ...)]
+  {DEFAULT VALUES | VALUES (value, ...), ... | SELECT ...}
+  [ON CONFLICT [(column, ...)] {DO NOTHING | DO UPDATE SET column = excluded.column, ...}]
+  [RETURNING expression, ...]",
+            ),
+            (
+                DatabaseType::ClickHouse,
+                "INSERT INTO table [(column, ...)] [SETTINGS ...]
+  {VALUES (value, ...), ... | SELECT ... | FORMAT format_name data}
+
+Inserts are batched: prefer fewer, larger inserts over many single rows.",
+            ),
+        ],
+    },
+    SqlTopic {
+        keyword: "UPDATE",
+        summary: "change column values of existing rows",
+        syntax: "UPDATE table
+  SET column = expression, ...
+  [WHERE condition]",
+        dialects: &[
+            (
+                DatabaseType::PostgreSQL,
+                "UPDATE [ONLY] table [AS alias]
+  SET {column = expression | (column, ...) = (expression, ...)}, ...
+  [FROM from_item, ...]
+  [WHERE condition | WHERE CURRENT OF cursor]
+  [RETURNING expression, ...]",
+            ),
+            (
+                DatabaseType::MySQL,
+                "UPDATE [LOW_PRIORITY] [IGNORE] table_references
+  SET column = expression, ...
+  [WHERE condition]
+  [ORDER BY ...] [LIMIT count]
+
+Several tables can be joined in table_references (no ORDER BY/LIMIT then).",
+            ),
+            (
+                DatabaseType::SQLite,
+                "UPDATE [OR {REPLACE|IGNORE|ABORT|FAIL|ROLLBACK}] table
+  SET column = expression, ...
+  [FROM from_item, ...]
+  [WHERE condition]
+  [RETURNING expression, ...]",
+            ),
+            (
+                DatabaseType::ClickHouse,
+                "ALTER TABLE table [ON CLUSTER cluster]
+  UPDATE column = expression, ...
+  WHERE condition
+
+Runs as an asynchronous mutation; follow it in system.mutations.
+Newer servers also accept lightweight UPDATE table SET ... WHERE ...",
+            ),
+        ],
+    },
+    SqlTopic {
+        keyword: "DELETE",
+        summary: "remove rows from a table",
+        syntax: "DELETE FROM table
+  [WHERE condition]",
+        dialects: &[
+            (
+                DatabaseType::PostgreSQL,
+                "DELETE FROM [ONLY] table [AS alias]
+  [USING from_item, ...]
+  [WHERE condition | WHERE CURRENT OF cursor]
+  [RETURNING expression, ...]",
+            ),
+            (
+                DatabaseType::MySQL,
+                "DELETE [LOW_PRIORITY] [QUICK] [IGNORE] FROM table
+  [WHERE condition]
+  [ORDER BY ...] [LIMIT count]
+
+DELETE t1, t2 FROM t1 JOIN t2 ON ... WHERE ... deletes from several tables.",
+            ),
+            (
+                DatabaseType::SQLite,
+                "DELETE FROM table
+  [WHERE condition]
+  [RETURNING expression, ...]",
+            ),
+            (
+                DatabaseType::ClickHouse,
+                "DELETE FROM table [ON CLUSTER cluster] WHERE condition
+
+Lightweight delete: rows are masked now and removed on merge.
+ALTER TABLE table DELETE WHERE condition runs a full mutation instead.",
+            ),
+        ],
+    },
+    SqlTopic {
+        keyword: "MERGE",
+        summary: "insert, update or delete rows depending on a join",
+        syntax: "MERGE INTO target [AS alias]
+  USING source ON join_condition
+  WHEN MATCHED [AND condition] THEN {UPDATE SET column = value, ... | DELETE}
+  WHEN NOT MATCHED [AND condition] THEN INSERT (column, ...) VALUES (value, ...)",
+        dialects: &[
+            (
+                DatabaseType::PostgreSQL,
+                "MERGE INTO target [AS alias]
+  USING source ON join_condition
+  WHEN MATCHED [AND condition] THEN {UPDATE SET column = value, ... | DELETE | DO NOTHING}
+  WHEN NOT MATCHED [BY TARGET] [AND condition] THEN {INSERT (column, ...) VALUES (value, ...) | DO NOTHING}
+  [RETURNING expression, ...]
+
+PostgreSQL 15+ (RETURNING and BY SOURCE from 17).",
+            ),
+            (
+                DatabaseType::MySQL,
+                "Not supported. Use INSERT ... ON DUPLICATE KEY UPDATE (see \\h INSERT).",
+            ),
+            (
+                DatabaseType::SQLite,
+                "Not supported. Use INSERT ... ON CONFLICT DO UPDATE (see \\h INSERT).",
+            ),
+            (
+                DatabaseType::ClickHouse,
+                "Not supported. ReplacingMergeTree tables keep the latest version of a row.",
+            ),
+        ],
+    },
+    SqlTopic {
+        keyword: "WITH",
+        summary: "common table expressions: named subqueries for one statement",
+        syntax: "WITH [RECURSIVE] name [(column, ...)] AS (query), ...
+  statement",
+        dialects: &[(
+            DatabaseType::PostgreSQL,
+            "WITH [RECURSIVE] name [(column, ...)] AS [[NOT] MATERIALIZED] (query), ...
+  {SELECT | INSERT | UPDATE | DELETE | MERGE} ...
+
+The CTE query may itself be INSERT/UPDATE/DELETE ... RETURNING.",
+        )],
+    },
+    SqlTopic {
+        keyword: "JOIN",
+        summary: "combine rows from two tables",
+        syntax: "from_item [INNER | LEFT [OUTER] | RIGHT [OUTER] | FULL [OUTER] | CROSS] JOIN from_item
+  {ON condition | USING (column, ...)}",
+        dialects: &[
+            (
+                DatabaseType::PostgreSQL,
+                "from_item [NATURAL] {[INNER] | LEFT | RIGHT | FULL} [OUTER] JOIN from_item
+  {ON condition | USING (column, ...)}
+from_item CROSS JOIN [LATERAL] from_item
+from_item LEFT JOIN LATERAL (subquery) alias ON true",
+            ),
+            (
+                DatabaseType::MySQL,
+                "from_item [INNER | CROSS | STRAIGHT_JOIN | {LEFT|RIGHT} [OUTER]] JOIN from_item
+  {ON condition | USING (column, ...)}
+
+No FULL OUTER JOIN: UNION a LEFT and a RIGHT join instead.",
+            ),
+            (
+                DatabaseType::ClickHouse,
+                "from_item [GLOBAL] [ANY | ALL | ASOF | SEMI | ANTI]
+  [INNER | LEFT | RIGHT | FULL | CROSS] JOIN from_item
+  {ON condition | USING (column, ...)}
+
+The right-hand table is loaded into memory: keep the smaller table on the right.",
+            ),
+        ],
+    },
+    SqlTopic {
+        keyword: "CREATE TABLE",
+        summary: "define a new table",
+        syntax: "CREATE TABLE [IF NOT EXISTS] table (
+  column type [NOT NULL] [DEFAULT value] [PRIMARY KEY] [UNIQUE] [REFERENCES other (column)],
+  ...
+  [, PRIMARY KEY (column, ...)]
+)
+CREATE TABLE table AS SELECT ...",
+        dialects: &[
+            (
+                DatabaseType::PostgreSQL,
+                "CREATE [TEMP | UNLOGGED] TABLE [IF NOT EXISTS] table (
+  column type [COLLATE collation] [NOT NULL] [DEFAULT value]
+    [GENERATED ALWAYS AS IDENTITY | GENERATED ALWAYS AS (expression) STORED]
+    [PRIMARY KEY] [UNIQUE] [CHECK (condition)] [REFERENCES other (column) [ON DELETE CASCADE]],
+  ...
+  [, LIKE source [INCLUDING ALL]]
+) [PARTITION BY {RANGE|LIST|HASH} (column)] [INHERITS (parent)]
+  [WITH (storage_parameter = value)] [TABLESPACE name]",
+            ),
+            (
+                DatabaseType::MySQL,
+                "CREATE [TEMPORARY] TABLE [IF NOT EXISTS] table (
+  column type [NOT NULL] [DEFAULT value] [AUTO_INCREMENT] [PRIMARY KEY] [UNIQUE]
+    [COMMENT 'text'] [GENERATED ALWAYS AS (expression) {VIRTUAL|STORED}],
+  ...
+  [, INDEX name (column, ...)] [, FOREIGN KEY (column) REFERENCES other (column)]
+) [ENGINE = InnoDB] [DEFAULT CHARSET = utf8mb4] [COLLATE = ...]
+CREATE TABLE new_table LIKE existing_table",
+            ),
+            (
+                DatabaseType::SQLite,
+                "CREATE [TEMP] TABLE [IF NOT EXISTS] table (
+  column [type] [NOT NULL] [DEFAULT value] [PRIMARY KEY [AUTOINCREMENT]] [UNIQUE]
+    [CHECK (condition)] [REFERENCES other (column)] [GENERATED ALWAYS AS (expression)],
+  ...
+) [STRICT] [WITHOUT ROWID]
+
+INTEGER PRIMARY KEY is an alias for the rowid.",
+            ),
+            (
+                DatabaseType::ClickHouse,
+                "CREATE TABLE [IF NOT EXISTS] [db.]table [ON CLUSTER cluster] (
+  column type [DEFAULT expression | MATERIALIZED expression] [CODEC(...)] [TTL expression],
+  ...
+) ENGINE = MergeTree()
+  ORDER BY (column, ...)
+  [PARTITION BY expression]
+  [PRIMARY KEY (column, ...)]
+  [TTL expression]
+  [SETTINGS name = value, ...]",
+            ),
+        ],
+    },
+    SqlTopic {
+        keyword: "ALTER TABLE",
+        summary: "change the definition of a table",
+        syntax: "ALTER TABLE table
+  ADD COLUMN column type
+  | DROP COLUMN column
+  | RENAME COLUMN old TO new
+  | RENAME TO new_name",
+        dialects: &[
+            (
+                DatabaseType::PostgreSQL,
+                "ALTER TABLE [IF EXISTS] table action, ...
+  ADD COLUMN [IF NOT EXISTS] column type [constraint]
+  DROP COLUMN [IF EXISTS] column [CASCADE]
+  ALTER COLUMN column {TYPE type [USING expression] | SET DEFAULT value
+                       | DROP DEFAULT | {SET|DROP} NOT NULL}
+  ADD CONSTRAINT name {PRIMARY KEY | UNIQUE | CHECK | FOREIGN KEY} ... [NOT VALID]
+  VALIDATE CONSTRAINT name
+  RENAME [COLUMN] old TO new | RENAME TO new_name
+  ATTACH PARTITION partition FOR VALUES ...",
+            ),
+            (
+                DatabaseType::MySQL,
+                "ALTER TABLE table action, ... [, ALGORITHM = {INSTANT|INPLACE|COPY}] [, LOCK = NONE]
+  ADD [COLUMN] column type [FIRST | AFTER other]
+  DROP [COLUMN] column
+  MODIFY [COLUMN] column type ...
+  CHANGE [COLUMN] old new type ...
+  RENAME COLUMN old TO new | RENAME TO new_name
+  ADD INDEX name (column, ...) | DROP INDEX name",
+            ),
+            (
+                DatabaseType::SQLite,
+                "ALTER TABLE table RENAME TO new_name
+ALTER TABLE table RENAME [COLUMN] old TO new
+ALTER TABLE table ADD [COLUMN] column type [constraint]
+ALTER TABLE table DROP [COLUMN] column
+
+Other changes need a new table: create, copy rows, drop, rename.",
+            ),
+            (
+                DatabaseType::ClickHouse,
+                "ALTER TABLE table [ON CLUSTER cluster]
+  ADD COLUMN [IF NOT EXISTS] column type [AFTER other]
+  | DROP COLUMN column
+  | MODIFY COLUMN column type
+  | RENAME COLUMN old TO new
+  | MODIFY TTL expression
+  | DROP PARTITION partition | DETACH PARTITION partition",
+            ),
+        ],
+    },
+    SqlTopic {
+        keyword: "DROP TABLE",
+        summary: "remove a table and its data",
+        syntax: "DROP TABLE [IF EXISTS] table",
+        dialects: &[
+            (
+                DatabaseType::PostgreSQL,
+                "DROP TABLE [IF EXISTS] table, ... [CASCADE | RESTRICT]",
+            ),
+            (
+                DatabaseType::MySQL,
+                "DROP [TEMPORARY] TABLE [IF EXISTS] table, ...",
+            ),
+            (
+                DatabaseType::ClickHouse,
+                "DROP TABLE [IF EXISTS] [db.]table [ON CLUSTER cluster] [SYNC]",
+            ),
+        ],
+    },
+    SqlTopic {
+        keyword: "TRUNCATE",
+        summary: "remove every row of a table quickly",
+        syntax: "TRUNCATE TABLE table",
+        dialects: &[
+            (
+                DatabaseType::PostgreSQL,
+                "TRUNCATE [TABLE] [ONLY] table, ... [RESTART IDENTITY] [CASCADE]",
+            ),
+            (
+                DatabaseType::SQLite,
+                "Not supported. DELETE FROM table without WHERE is optimised the same way.",
+            ),
+            (
+                DatabaseType::ClickHouse,
+                "TRUNCATE TABLE [IF EXISTS] [db.]table [ON CLUSTER cluster]",
+            ),
+        ],
+    },
+    SqlTopic {
+        keyword: "CREATE INDEX",
+        summary: "add an index on table columns",
+        syntax: "CREATE [UNIQUE] INDEX name ON table (column [ASC|DESC], ...)",
+        dialects: &[
+            (
+                DatabaseType::PostgreSQL,
+                "CREATE [UNIQUE] INDEX [CONCURRENTLY] [IF NOT EXISTS] name
+  ON table [USING {btree|hash|gin|gist|brin}] (column_or_expression [opclass], ...)
+  [INCLUDE (column, ...)]
+  [WHERE predicate]
+
+CONCURRENTLY avoids blocking writes but cannot run inside a transaction.",
+            ),
+            (
+                DatabaseType::MySQL,
+                "CREATE [UNIQUE | FULLTEXT | SPATIAL] INDEX name
+  ON table (column[(length)] [ASC|DESC], ... | (expression))
+  [ALGORITHM = {INPLACE|COPY}] [LOCK = {NONE|SHARED}]",
+            ),
+            (
+                DatabaseType::SQLite,
+                "CREATE [UNIQUE] INDEX [IF NOT EXISTS] name
+  ON table (column_or_expression [COLLATE name] [ASC|DESC], ...)
+  [WHERE predicate]",
+            ),
+            (
+                DatabaseType::ClickHouse,
+                "ALTER TABLE table ADD INDEX name expression TYPE {minmax|set(n)|bloom_filter} GRANULARITY n
+ALTER TABLE table MATERIALIZE INDEX name
+
+Data-skipping indexes; the primary index is the table's ORDER BY.",
+            ),
+        ],
+    },
+    SqlTopic {
+        keyword: "CREATE VIEW",
+        summary: "define a named query",
+        syntax: "CREATE [OR REPLACE] VIEW name [(column, ...)] AS SELECT ...",
+        dialects: &[
+            (
+                DatabaseType::PostgreSQL,
+                "CREATE [OR REPLACE] [TEMP] VIEW name [(column, ...)] AS SELECT ...
+  [WITH [CASCADED | LOCAL] CHECK OPTION]
+CREATE MATERIALIZED VIEW [IF NOT EXISTS] name AS SELECT ... [WITH [NO] DATA]
+REFRESH MATERIALIZED VIEW [CONCURRENTLY] name",
+            ),
+            (
+                DatabaseType::SQLite,
+                "CREATE [TEMP] VIEW [IF NOT EXISTS] name [(column, ...)] AS SELECT ...",
+            ),
+            (
+                DatabaseType::ClickHouse,
+                "CREATE [OR REPLACE] VIEW name AS SELECT ...
+CREATE MATERIALIZED VIEW name [TO target_table] [ENGINE = ...] [POPULATE] AS SELECT ...
+
+A materialized view is an insert trigger: it sees only newly inserted blocks.",
+            ),
+        ],
+    },
+    SqlTopic {
+        keyword: "EXPLAIN",
+        summary: "show how the server will run a statement",
+        syntax: "EXPLAIN statement",
+        dialects: &[
+            (
+                DatabaseType::PostgreSQL,
+                "EXPLAIN [(ANALYZE, VERBOSE, BUFFERS, SETTINGS, WAL, FORMAT {TEXT|JSON|YAML})] statement
+
+ANALYZE runs the statement: wrap writes in BEGIN ... ROLLBACK.
+\\e toggles automatic EXPLAIN, \\ev the plan visualizer.",
+            ),
+            (
+                DatabaseType::MySQL,
+                "EXPLAIN [FORMAT = {TRADITIONAL|JSON|TREE}] statement
+EXPLAIN ANALYZE SELECT ...",
+            ),
+            (
+                DatabaseType::SQLite,
+                "EXPLAIN QUERY PLAN statement
+
+Plain EXPLAIN lists the bytecode program instead.",
+            ),
+            (
+                DatabaseType::ClickHouse,
+                "EXPLAIN [AST | SYNTAX | PLAN | PIPELINE | ESTIMATE] [indexes = 1, actions = 1] SELECT ...",
+            ),
+        ],
+    },
+    SqlTopic {
+        keyword: "BEGIN",
+        summary: "start a transaction",
+        syntax: "BEGIN;
+  ...
+COMMIT; | ROLLBACK;",
+        dialects: &[
+            (
+                DatabaseType::PostgreSQL,
+                "BEGIN [ISOLATION LEVEL {SERIALIZABLE | REPEATABLE READ | READ COMMITTED}] [READ ONLY];
+  SAVEPOINT name; ... ROLLBACK TO SAVEPOINT name;
+COMMIT; | ROLLBACK;
+
+DDL is transactional too.",
+            ),
+            (
+                DatabaseType::MySQL,
+                "START TRANSACTION [READ ONLY | WITH CONSISTENT SNAPSHOT];
+  SAVEPOINT name; ... ROLLBACK TO SAVEPOINT name;
+COMMIT; | ROLLBACK;
+
+DDL statements commit the open transaction implicitly.",
+            ),
+            (
+                DatabaseType::SQLite,
+                "BEGIN [DEFERRED | IMMEDIATE | EXCLUSIVE];
+  SAVEPOINT name; ... ROLLBACK TO name;
+COMMIT; | ROLLBACK;",
+            ),
+            (
+                DatabaseType::ClickHouse,
+                "No multi-statement transactions: each INSERT block is atomic on its own.",
+            ),
+        ],
+    },
+    SqlTopic {
+        keyword: "GRANT",
+        summary: "give privileges to a user or role",
+        syntax: "GRANT privilege, ... ON object TO grantee
+REVOKE privilege, ... ON object FROM grantee",
+        dialects: &[
+            (
+                DatabaseType::PostgreSQL,
+                "GRANT {SELECT | INSERT | UPDATE | DELETE | TRUNCATE | REFERENCES | ALL} [(column, ...)]
+  ON {TABLE table | ALL TABLES IN SCHEMA schema} TO role [WITH GRANT OPTION]
+GRANT USAGE ON SCHEMA schema TO role
+GRANT role TO other_role
+ALTER DEFAULT PRIVILEGES IN SCHEMA schema GRANT SELECT ON TABLES TO role",
+            ),
+            (
+                DatabaseType::MySQL,
+                "GRANT privilege, ... ON {*.* | db.* | db.table} TO 'user'@'host' [WITH GRANT OPTION]
+GRANT role TO 'user'@'host'
+SHOW GRANTS FOR 'user'@'host'",
+            ),
+            (
+                DatabaseType::SQLite,
+                "Not supported: access is controlled by file permissions.",
+            ),
+            (
+                DatabaseType::ClickHouse,
+                "GRANT [ON CLUSTER cluster] privilege[(column, ...)], ... ON {db.table | db.* | *.*}
+  TO {user | role} [WITH GRANT OPTION]",
+            ),
+        ],
+    },
+    SqlTopic {
+        keyword: "VACUUM",
+        summary: "reclaim space and refresh planner statistics",
+        syntax: "VACUUM [table]",
+        dialects: &[
+            (
+                DatabaseType::PostgreSQL,
+                "VACUUM [(FULL, FREEZE, VERBOSE, ANALYZE)] [table [(column, ...)], ...]
+ANALYZE [table]
+
+FULL rewrites the table under an exclusive lock.",
+            ),
+            (
+                DatabaseType::MySQL,
+                "OPTIMIZE TABLE table, ...
+ANALYZE TABLE table, ...",
+            ),
+            (
+                DatabaseType::SQLite,
+                "VACUUM [schema] [INTO 'file']
+ANALYZE [table]
+
+\\vacuum runs it with before/after sizes.",
+            ),
+            (
+                DatabaseType::ClickHouse,
+                "OPTIMIZE TABLE table [PARTITION partition] [FINAL] [DEDUPLICATE]",
+            ),
+        ],
+    },
+    SqlTopic {
+        keyword: "COPY",
+        summary: "bulk load or unload table data",
+        syntax: "No portable form; see your database's bulk load statement.",
+        dialects: &[
+            (
+                DatabaseType::PostgreSQL,
+                "COPY table [(column, ...)] FROM {'file' | STDIN} [WITH (FORMAT csv, HEADER, DELIMITER ',')]
+COPY {table | (query)} TO {'file' | STDOUT} [WITH (FORMAT csv, HEADER)]
+
+Server-side files need superuser or pg_read/write_server_files.",
+            ),
+            (
+                DatabaseType::MySQL,
+                "LOAD DATA [LOCAL] INFILE 'file' INTO TABLE table
+  [FIELDS TERMINATED BY ',' [ENCLOSED BY '\"']] [LINES TERMINATED BY '\\n']
+  [IGNORE 1 LINES] [(column, ...)]
+SELECT ... INTO OUTFILE 'file'",
+            ),
+            (
+                DatabaseType::ClickHouse,
+                "INSERT INTO table FROM INFILE 'file' [COMPRESSION 'gzip'] FORMAT CSVWithNames
+SELECT ... INTO OUTFILE 'file' FORMAT Parquet
+
+\\chinsert streams a local file into a table.",
+            ),
+        ],
+    },
+];
+
+fn normalize(topic: &str) -> String {
+    topic
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_uppercase()
+}
+
+/// SQL keywords with syntax help, for completion and suggestions
+pub fn keywords() -> impl Iterator<Item = &'static str> {
+    TOPICS.iter().map(|topic| topic.keyword)
+}
+
+/// Syntax help for `topic` in the dialect of `database_type`. `None` when no
+/// topic matches; a bare `CREATE` lists the matching `CREATE ...` topics.
+pub fn help_for(topic: &str, database_type: Option<&DatabaseType>) -> Option<String> {
+    let wanted = normalize(topic);
+    let wanted = match wanted.as_str() {
+        "START TRANSACTION" | "COMMIT" | "ROLLBACK" | "SAVEPOINT" => "BEGIN",
+        "REVOKE" => "GRANT",
+        "UPSERT" | "REPLACE" => "INSERT",
+        "ANALYZE" | "OPTIMIZE" => "VACUUM",
+        "CTE" => "WITH",
+        "LOAD DATA" => "COPY",
+        other => other,
+    };
+    if let Some(topic) = TOPICS.iter().find(|topic| topic.keyword == wanted) {
+        let dialect = database_type.and_then(|db_type| {
+            topic
+                .dialects
+                .iter()
+                .find(|(dialect, _)| dialect == db_type)
+        });
+        let (label, syntax) = match dialect {
+            Some((db_type, syntax)) => (db_type.to_string(), *syntax),
+            None => ("standard SQL".to_string(), topic.syntax),
+        };
+        return Some(format!(
+            "{} — {}\n\nSyntax ({label}):\n{}\n",
+            topic.keyword,
+            topic.summary,
+            indent(syntax)
+        ));
+    }
+
+    let related: Vec<&SqlTopic> = TOPICS
+        .iter()
+        .filter(|topic| {
+            topic
+                .keyword
+                .strip_prefix(wanted)
+                .is_some_and(|rest| rest.starts_with(' '))
+        })
+        .collect();
+    if related.is_empty() {
+        return None;
+    }
+    let mut out = String::new();
+    for topic in related {
+        out.push_str(&format!("  {:<14} {}\n", topic.keyword, topic.summary));
+    }
+    Some(out)
+}
+
+fn indent(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            if line.is_empty() {
+                String::new()
+            } else {
+                format!("  {line}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_help_uses_connected_dialect() {
+        let pg = help_for("insert", Some(&DatabaseType::PostgreSQL)).unwrap();
+        assert!(pg.starts_with("INSERT — add rows to a table\n\nSyntax (PostgreSQL):\n"));
+        assert!(pg.contains("ON CONFLICT"));
+
+        let mysql = help_for("INSERT", Some(&DatabaseType::MySQL)).unwrap();
+        assert!(mysql.contains("ON DUPLICATE KEY UPDATE"));
+
+        // Backends without a variant get the portable synopsis
+        let mongo = help_for("insert", Some(&DatabaseType::MongoDB)).unwrap();
+        assert!(mongo.contains("Syntax (standard SQL)"));
+    }
+
+    #[test]
+    fn test_help_aliases_and_prefixes() {
+        assert!(
+            help_for("commit", Some(&DatabaseType::SQLite))
+                .unwrap()
+                .contains("BEGIN [DEFERRED")
+        );
+        assert!(
+            help_for("create  table", None)
+                .unwrap()
+                .starts_with("CREATE TABLE")
+        );
+        let create = help_for("create", None).unwrap();
+        assert!(create.contains("CREATE INDEX") && create.contains("CREATE VIEW"));
+        assert_eq!(help_for("selct", None), None);
+    }
+}
@@ -0,0 +1,120 @@
+//! ClickHouse-specific SQL parser implementation
+//! Reuses the PostgreSQL parser for the SQL ClickHouse shares with it and adds
+//! the `ON CLUSTER <name>` clause of distributed DDL
+
+use crate::database::DatabaseType;
+use crate::sql_parser_postgresql::PostgreSQLParser;
+use crate::sql_parser_trait::{
+    CompletionHint, DatabaseSpecificContext, EnhancedSqlContext, KeywordCategory, SqlParserEngine,
+};
+use async_trait::async_trait;
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// `ON CLUSTER` followed by the (possibly partial) cluster name being typed
+static ON_CLUSTER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\bON\s+CLUSTER\s+[\w'`{}.-]*$").unwrap());
+
+/// ClickHouse-specific SQL parser
+pub struct ClickHouseParser {
+    base: PostgreSQLParser,
+}
+
+impl Default for ClickHouseParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClickHouseParser {
+    pub fn new() -> Self {
+        Self {
+            base: PostgreSQLParser::new(),
+        }
+    }
+
+    /// Detect ClickHouse-specific syntax before the cursor
+    fn detect_clickhouse_patterns(&self, sql: &str, cursor_pos: usize) -> DatabaseSpecificContext {
+        let before = sql.get(..cursor_pos).unwrap_or(sql);
+        DatabaseSpecificContext::ClickHouse {
+            expecting_cluster: ON_CLUSTER.is_match(before),
+        }
+    }
+}
+
+#[async_trait]
+impl SqlParserEngine for ClickHouseParser {
+    fn database_type(&self) -> DatabaseType {
+        DatabaseType::ClickHouse
+    }
+
+    fn parse_at_cursor(&self, sql: &str, cursor_pos: usize) -> EnhancedSqlContext {
+        EnhancedSqlContext {
+            base_context: crate::sql_parser::parse_sql_at_cursor(sql, cursor_pos),
+            database_context: self.detect_clickhouse_patterns(sql, cursor_pos),
+            database_type: DatabaseType::ClickHouse,
+        }
+    }
+
+    fn get_keywords_by_category(&self, category: KeywordCategory) -> Vec<&'static str> {
+        self.base.get_keywords_by_category(category)
+    }
+
+    fn get_functions(&self) -> Vec<&'static str> {
+        self.base.get_functions()
+    }
+
+    fn get_operators(&self) -> Vec<&'static str> {
+        self.base.get_operators()
+    }
+
+    fn get_data_types(&self) -> Vec<&'static str> {
+        self.base.get_data_types()
+    }
+
+    fn is_keyword_valid_in_context(&self, keyword: &str, context: &EnhancedSqlContext) -> bool {
+        self.base.is_keyword_valid_in_context(keyword, context)
+    }
+
+    fn get_context_suggestions(
+        &self,
+        context: &EnhancedSqlContext,
+        current_word: &str,
+    ) -> Vec<String> {
+        self.base.get_context_suggestions(context, current_word)
+    }
+
+    fn parse_operators_at_cursor(&self, sql: &str, cursor_pos: usize) -> Vec<String> {
+        self.base.parse_operators_at_cursor(sql, cursor_pos)
+    }
+
+    fn get_completion_hints(&self, context: &EnhancedSqlContext) -> Vec<CompletionHint> {
+        self.base.get_completion_hints(context)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expecting_cluster(sql: &str) -> bool {
+        let context = ClickHouseParser::new().parse_at_cursor(sql, sql.len());
+        matches!(
+            context.database_context,
+            DatabaseSpecificContext::ClickHouse {
+                expecting_cluster: true
+            }
+        )
+    }
+
+    #[test]
+    fn test_on_cluster_detection() {
+        assert!(expecting_cluster("CREATE TABLE events ON CLUSTER "));
+        assert!(expecting_cluster("alter table events on cluster ana"));
+        assert!(expecting_cluster("DROP TABLE t ON CLUSTER '{cluster}"));
+        assert!(!expecting_cluster(
+            "CREATE TABLE events ON CLUSTER analytics ("
+        ));
+        assert!(!expecting_cluster("SELECT * FROM events WHERE "));
+    }
+}
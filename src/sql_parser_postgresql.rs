@@ -2,6 +2,7 @@
 //! Handles PostgreSQL-specific syntax, operators, and functions
 
 use crate::database::DatabaseType;
+use crate::database_postgresql::PgExtension;
 use crate::sql_parser::SqlClause;
 use crate::sql_parser_trait::{
     CompletionHint, CompletionHintCategory, DatabaseSpecificContext, EnhancedSqlContext,
@@ -10,7 +11,10 @@ use crate::sql_parser_trait::{
 use async_trait::async_trait;
 
 /// PostgreSQL-specific SQL parser
-pub struct PostgreSQLParser {}
+pub struct PostgreSQLParser {
+    /// Installed extensions whose functions and operators are offered too
+    extensions: Vec<PgExtension>,
+}
 
 impl Default for PostgreSQLParser {
     fn default() -> Self {
@@ -20,7 +24,15 @@ impl Default for PostgreSQLParser {
 
 impl PostgreSQLParser {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            extensions: Vec::new(),
+        }
+    }
+
+    pub fn with_extensions(extensions: &[PgExtension]) -> Self {
+        Self {
+            extensions: extensions.to_vec(),
+        }
     }
 
     /// Parse PostgreSQL-specific operators at the cursor position
@@ -362,7 +374,7 @@ impl SqlParserEngine for PostgreSQLParser {
     }
 
     fn get_functions(&self) -> Vec<&'static str> {
-        vec![
+        let mut functions = vec![
             // Standard SQL functions
             "COUNT",
             "SUM",
@@ -423,11 +435,15 @@ impl SqlParserEngine for PostgreSQLParser {
             "ARRAY_POSITION",
             "ARRAY_REMOVE",
             "ARRAY_REPLACE",
-        ]
+        ];
+        for extension in &self.extensions {
+            functions.extend_from_slice(extension.functions());
+        }
+        functions
     }
 
     fn get_operators(&self) -> Vec<&'static str> {
-        vec![
+        let mut operators = vec![
             // Standard operators
             "=",
             "!=",
@@ -475,7 +491,13 @@ impl SqlParserEngine for PostgreSQLParser {
             "~=",
             "@",
             "##",
-        ]
+        ];
+        for operator in self.extensions.iter().flat_map(|ext| ext.operators()) {
+            if !operators.contains(operator) {
+                operators.push(operator);
+            }
+        }
+        operators
     }
 
     fn get_data_types(&self) -> Vec<&'static str> {
@@ -712,6 +734,17 @@ mod tests {
         assert!(functions.contains(&"JSON_BUILD_OBJECT"));
     }
 
+    #[test]
+    fn test_extension_functions_and_operators() {
+        let plain = PostgreSQLParser::new();
+        assert!(!plain.get_functions().contains(&"l2_distance"));
+
+        let parser = PostgreSQLParser::with_extensions(&[PgExtension::Vector]);
+        assert!(parser.get_functions().contains(&"l2_distance"));
+        assert!(parser.get_operators().contains(&"<=>"));
+        assert!(!parser.get_functions().contains(&"ST_Distance"));
+    }
+
     #[test]
     fn test_postgresql_json_operator_detection() {
         let parser = PostgreSQLParser::new();
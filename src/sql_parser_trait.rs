@@ -37,6 +37,11 @@ pub enum DatabaseSpecificContext {
         /// WITHOUT ROWID context
         without_rowid_context: bool,
     },
+    /// ClickHouse-specific context
+    ClickHouse {
+        /// The cursor is on the name after `ON CLUSTER`
+        expecting_cluster: bool,
+    },
     /// Generic context for unknown database types
     Generic,
 }
@@ -150,15 +155,23 @@ pub struct SqlParserFactory;
 impl SqlParserFactory {
     /// Create a parser for the specified database type
     pub fn create_parser(database_type: DatabaseType) -> Box<dyn SqlParserEngine> {
+        Self::create_parser_with_extensions(database_type, &[])
+    }
+
+    /// Like [`Self::create_parser`], with the functions and operators of the
+    /// connection's installed PostgreSQL extensions
+    pub fn create_parser_with_extensions(
+        database_type: DatabaseType,
+        extensions: &[crate::database_postgresql::PgExtension],
+    ) -> Box<dyn SqlParserEngine> {
         match database_type {
-            DatabaseType::PostgreSQL => {
-                Box::new(crate::sql_parser_postgresql::PostgreSQLParser::new())
-            }
+            DatabaseType::PostgreSQL => Box::new(
+                crate::sql_parser_postgresql::PostgreSQLParser::with_extensions(extensions),
+            ),
             DatabaseType::MySQL => Box::new(crate::sql_parser_mysql::MySQLParser::new()),
             DatabaseType::SQLite => Box::new(crate::sql_parser_sqlite::SQLiteParser::new()),
             DatabaseType::ClickHouse => {
-                // Use PostgreSQL parser for now as ClickHouse SQL is similar
-                Box::new(crate::sql_parser_postgresql::PostgreSQLParser::new())
+                Box::new(crate::sql_parser_clickhouse::ClickHouseParser::new())
             }
             DatabaseType::MongoDB => {
                 // MongoDB doesn't use SQL, use generic parser
@@ -168,6 +181,10 @@ impl SqlParserFactory {
                 // Elasticsearch uses SQL API, use PostgreSQL parser as base
                 Box::new(crate::sql_parser_postgresql::PostgreSQLParser::new())
             }
+            // Snowflake SQL is close to PostgreSQL's
+            DatabaseType::Snowflake => {
+                Box::new(crate::sql_parser_postgresql::PostgreSQLParser::new())
+            }
             // File formats via DataFusion - use PostgreSQL parser (DataFusion SQL is similar to PostgreSQL)
             DatabaseType::Parquet
             | DatabaseType::CSV
@@ -337,6 +354,7 @@ pub mod parsing_utils {
             DatabaseType::MySQL => ch.is_alphanumeric() || ch == '_' || ch == '$',
             DatabaseType::SQLite => ch.is_alphanumeric() || ch == '_',
             DatabaseType::ClickHouse => ch.is_alphanumeric() || ch == '_',
+            DatabaseType::Snowflake => ch.is_alphanumeric() || ch == '_' || ch == '$',
             DatabaseType::MongoDB => ch.is_alphanumeric() || ch == '_', // MongoDB collection/field names
             DatabaseType::Elasticsearch => ch.is_alphanumeric() || ch == '_' || ch == '.', // Elasticsearch field names can contain dots
             DatabaseType::Parquet
@@ -380,7 +398,7 @@ pub mod parsing_utils {
                     "SELECT" | "FROM" | "WHERE" | "ORDER" | "GROUP"
                 )
             }
-            DatabaseType::ClickHouse => {
+            DatabaseType::ClickHouse | DatabaseType::Snowflake => {
                 matches!(
                     upper_identifier.as_str(),
                     "SELECT" | "FROM" | "WHERE" | "ORDER" | "GROUP" | "LIMIT"
@@ -409,6 +427,23 @@ pub mod parsing_utils {
         }
     }
 
+    /// Whether `\'` inside a string literal is an escaped quote rather
+    /// than the end of the string
+    pub fn backslash_escapes_in_strings(database_type: DatabaseType) -> bool {
+        matches!(
+            database_type,
+            DatabaseType::MySQL | DatabaseType::ClickHouse | DatabaseType::Snowflake
+        )
+    }
+
+    /// Whether `$tag$ ... $tag$` quotes a string (function bodies)
+    pub fn supports_dollar_quotes(database_type: DatabaseType) -> bool {
+        matches!(
+            database_type,
+            DatabaseType::PostgreSQL | DatabaseType::Snowflake | DatabaseType::DuckDB
+        )
+    }
+
     /// Get the appropriate quote character for identifiers
     pub fn get_quote_char(database_type: DatabaseType) -> char {
         match database_type {
@@ -418,6 +453,7 @@ pub mod parsing_utils {
             DatabaseType::ClickHouse => '`', // ClickHouse uses backticks like MySQL
             DatabaseType::MongoDB => '"',    // MongoDB uses double quotes for field names
             DatabaseType::Elasticsearch => '"', // Elasticsearch uses double quotes for field names
+            DatabaseType::Snowflake => '"',
             DatabaseType::Parquet
             | DatabaseType::CSV
             | DatabaseType::JSON
@@ -1,17 +1,29 @@
+//! SSH tunnels for database connections
+//!
+//! The tunnel runs the system `ssh` binary with `-L` port forwarding. Password
+//! and keyboard-interactive prompts reach the user through dbcrust itself
+//! acting as `SSH_ASKPASS`, and ProxyCommand, agent auth and known_hosts
+//! checks are the ones OpenSSH does.
+//!
+//! This is not the in-process russh transport the feature was requested as:
+//! the russh crate could not be added to this build, so the OpenSSH design
+//! stays until that request is re-scoped or the port is done.
+
 // Locks are held across awaits for SSH tunnel process management
 #![allow(clippy::await_holding_lock)]
 
 use crate::config::SSHTunnelConfig;
 use rand::{Rng, rng};
+use serde::{Deserialize, Serialize};
 use std::io;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::path::PathBuf;
 use std::process::Stdio;
-use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
 use thiserror::Error;
 use tokio::io::AsyncReadExt;
-use tokio::net::TcpListener;
+use tokio::net::{TcpListener, TcpStream};
 use tokio::process::Command;
 use tokio::time::timeout;
 use tracing::{debug, info};
@@ -52,6 +64,40 @@ impl From<io::Error> for SSHTunnelError {
     }
 }
 
+/// Set in the environment of `ssh` when it runs this binary as its
+/// `SSH_ASKPASS` helper: `interactive` when prompts may reach the user,
+/// `batch` when only a configured password can be given
+const ASKPASS_ENV: &str = "DBCRUST_SSH_ASKPASS";
+/// Path of the owner-only file holding the tunnel target's password,
+/// answered to `ssh`'s password prompt. The password itself stays out of the
+/// environment (readable in `/proc/<pid>/environ`), and ssh closes inherited
+/// descriptors before running the helper, so a pipe would not reach it.
+const ASKPASS_PASSWORD_FILE_ENV: &str = "DBCRUST_SSH_PASSWORD_FILE";
+
+/// `[ssh]` configuration section, applied to every tunnel
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct SshConfig {
+    /// OpenSSH `ProxyCommand` used to reach the SSH host; `%h` and `%p` are
+    /// the SSH host and port (empty: connect directly)
+    pub proxy_command: String,
+    /// known_hosts file checked and updated instead of `~/.ssh/known_hosts`
+    pub known_hosts_file: String,
+    /// Ask before trusting a host key seen for the first time. Without a
+    /// terminal, or when off, new keys are accepted and remembered.
+    pub confirm_new_host_keys: bool,
+}
+
+impl Default for SshConfig {
+    fn default() -> Self {
+        Self {
+            proxy_command: String::new(),
+            known_hosts_file: String::new(),
+            confirm_new_host_keys: true,
+        }
+    }
+}
+
 /// A struct that represents an SSH tunnel
 #[derive(Clone)]
 pub struct SSHTunnel {
@@ -63,11 +109,182 @@ pub struct SSHTunnel {
     ssh_host: String,
     ssh_port: u16,
     tunnel_process: Arc<Mutex<Option<tokio::process::Child>>>,
+    /// `ssh -E` log holding the errors ssh reports
+    log_file: Option<Arc<tempfile::TempPath>>,
+    /// Settings the tunnel was established with, for [`SSHTunnel::reopen`]
+    config: Option<SSHTunnelConfig>,
 }
 
 /// Shared type for the SSH tunnel
 pub type SharedSSHTunnel = Arc<Mutex<Option<SSHTunnel>>>;
 
+/// Tunnel target given with `--ssh-tunnel`; wins over `ssh_tunnel_patterns`
+static EXPLICIT_TUNNEL: RwLock<Option<String>> = RwLock::new(None);
+
+/// Force every connection of this process through `target` (`[user[:password]@]host[:port]`)
+pub fn set_explicit_tunnel(target: Option<String>) {
+    if let Ok(mut guard) = EXPLICIT_TUNNEL.write() {
+        *guard = target.filter(|t| !t.trim().is_empty());
+    }
+}
+
+/// The `--ssh-tunnel` target, if one was given
+pub fn explicit_tunnel() -> Option<String> {
+    EXPLICIT_TUNNEL.read().ok().and_then(|guard| guard.clone())
+}
+
+/// Program `ssh` runs to ask for passwords, 2FA codes and host key
+/// confirmations. Only the dbcrust binaries set it; embedded uses (Python)
+/// keep ssh in batch mode.
+static ASKPASS_PROGRAM: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Let `ssh` run this executable as its `SSH_ASKPASS` helper
+pub fn enable_askpass() {
+    if let Ok(mut guard) = ASKPASS_PROGRAM.write() {
+        *guard = std::env::current_exe().ok();
+    }
+}
+
+fn askpass_program() -> Option<PathBuf> {
+    ASKPASS_PROGRAM.read().ok().and_then(|guard| guard.clone())
+}
+
+/// What `ssh` asks its askpass helper for
+#[derive(Debug, PartialEq)]
+enum AskpassPrompt {
+    /// Account password
+    Password,
+    /// Any other secret: OTP/2FA code, key passphrase
+    Secret,
+    /// yes/no question, such as trusting an unknown host key
+    Confirm,
+    /// Message only, such as touching a security key
+    Notice,
+}
+
+/// Classify `prompt`; `kind` is ssh's `SSH_ASKPASS_PROMPT` hint
+fn classify_askpass_prompt(prompt: &str, kind: Option<&str>) -> AskpassPrompt {
+    let lower = prompt.to_lowercase();
+    match kind {
+        Some("none") => AskpassPrompt::Notice,
+        Some("confirm") => AskpassPrompt::Confirm,
+        _ if lower.contains("(yes/no") => AskpassPrompt::Confirm,
+        _ if lower.trim_end().ends_with("password:") => AskpassPrompt::Password,
+        _ => AskpassPrompt::Secret,
+    }
+}
+
+/// When `ssh` runs this binary as its askpass helper, answer the prompt on
+/// stdout and return the exit code; `None` in a normal run. Prompts go
+/// through inquire on the terminal (crossterm opens `/dev/tty`, since ssh
+/// gives the helper no stdin).
+pub fn run_askpass() -> Option<i32> {
+    let mode = std::env::var(ASKPASS_ENV).ok()?;
+    let prompt = std::env::args().nth(1).unwrap_or_default();
+    let kind = std::env::var("SSH_ASKPASS_PROMPT").ok();
+    let interactive = mode == "interactive";
+
+    let password = std::env::var_os(ASKPASS_PASSWORD_FILE_ENV)
+        .and_then(|path| std::fs::read_to_string(path).ok());
+
+    let answer = match classify_askpass_prompt(&prompt, kind.as_deref()) {
+        AskpassPrompt::Password if password.is_some() => password,
+        _ if !interactive => None,
+        AskpassPrompt::Notice => {
+            eprintln!("{}", prompt.trim());
+            Some(String::new())
+        }
+        AskpassPrompt::Confirm => {
+            // ssh puts the host key fingerprint in the lines before the question
+            let (details, question) = prompt.trim().rsplit_once('\n').unwrap_or(("", &prompt));
+            if !details.is_empty() {
+                eprintln!("{details}");
+            }
+            let question = question.split(" (yes/no").next().unwrap_or(question).trim();
+            inquire::Confirm::new(question)
+                .with_default(false)
+                .prompt()
+                .ok()
+                .map(|yes| if yes { "yes" } else { "no" }.to_string())
+        }
+        AskpassPrompt::Password | AskpassPrompt::Secret => inquire::Password::new(prompt.trim())
+            .without_confirmation()
+            .with_display_mode(inquire::PasswordDisplayMode::Masked)
+            .prompt()
+            .ok(),
+    };
+    Some(match answer {
+        Some(answer) => {
+            println!("{answer}");
+            0
+        }
+        None => 1,
+    })
+}
+
+/// Owner-only (0600) temporary file holding `password`, for the askpass helper
+fn write_password_file(password: &str) -> Result<tempfile::TempPath, SSHTunnelError> {
+    use std::io::Write;
+    let mut file = tempfile::Builder::new()
+        .prefix("dbcrust-ssh-")
+        .suffix(".pass")
+        .tempfile()?;
+    file.write_all(password.as_bytes())?;
+    file.flush()?;
+    Ok(file.into_temp_path())
+}
+
+/// `path` with a leading `~/` expanded
+fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/").zip(dirs::home_dir()) {
+        Some((rest, home)) => home.join(rest),
+        None => PathBuf::from(path),
+    }
+}
+
+/// Open a tunnel to `host:port`, connect through it, and close it again.
+///
+/// `ssh -L` accepts the local connection before it dials the target, so a
+/// successful connect alone proves nothing: the forward is only considered
+/// working when the connection stays open (or the server greets us) instead
+/// of being closed right away by ssh.
+pub async fn check_connectivity(
+    conn_config: &SSHTunnelConfig,
+    host: &str,
+    port: u16,
+) -> Result<(String, Duration), SSHTunnelError> {
+    let started = Instant::now();
+    let mut tunnel = SSHTunnel::default();
+    let local_port = tunnel.establish(conn_config, host, port).await?;
+    let route = tunnel.describe();
+
+    let probe = async {
+        let mut stream = timeout(
+            Duration::from_secs(5),
+            TcpStream::connect(("127.0.0.1", local_port)),
+        )
+        .await
+        .map_err(|_| {
+            SSHTunnelError::TimeoutError(format!("connecting to local port {local_port}"))
+        })?
+        .map_err(|e| SSHTunnelError::ConnectionError(format!("local port {local_port}: {e}")))?;
+        let mut buf = [0u8; 1];
+        match timeout(Duration::from_millis(1500), stream.read(&mut buf)).await {
+            Ok(Ok(0)) => Err(SSHTunnelError::ConnectionError(format!(
+                "the SSH host could not reach {host}:{port}"
+            ))),
+            Ok(Err(e)) => Err(SSHTunnelError::ConnectionError(format!(
+                "the SSH host could not reach {host}:{port}: {e}"
+            ))),
+            // Greeting received, or a server waiting for the client to speak first
+            Ok(Ok(_)) | Err(_) => Ok(()),
+        }
+    };
+    let result = probe.await;
+    tunnel.stop().await?;
+    result.map(|()| (route, started.elapsed()))
+}
+
 impl SSHTunnel {
     /// Create a new empty SSH tunnel
     pub fn new() -> Option<Self> {
@@ -90,62 +307,106 @@ impl SSHTunnel {
         self.ssh_key = conn_config.ssh_key_path.clone().map(PathBuf::from);
         self.remote_host = target_service_host.to_string();
         self.remote_port = target_service_port;
+        self.config = Some(conn_config.clone());
 
         let local_port = self.find_available_port().await?;
         self.local_port = local_port;
 
-        let mut cmd = Command::new("ssh");
-        cmd.arg(format!(
-            "-L{}:{}:{}",
-            self.local_port, self.remote_host, self.remote_port
-        ));
-        cmd.arg("-N");
-        cmd.arg("-o");
-        cmd.arg("ExitOnForwardFailure=yes");
-        cmd.arg("-o");
-        cmd.arg("BatchMode=yes");
-        cmd.arg("-o");
-        cmd.arg("ConnectTimeout=3"); // Reduced from 5 to 3 seconds
-        cmd.arg("-o");
-        cmd.arg("ServerAliveInterval=10");
-        cmd.arg("-o");
-        cmd.arg("ServerAliveCountMax=2");
-        cmd.arg("-o");
-        cmd.arg("StrictHostKeyChecking=accept-new");
-        cmd.arg("-o");
-        cmd.arg("PasswordAuthentication=no");
-        cmd.arg("-o");
-        cmd.arg("LogLevel=ERROR");
+        let options = &conn_config.options;
+        let interactive = crate::config_editor::can_run_interactive();
+        // Password, 2FA and passphrase prompts go through this binary as
+        // SSH_ASKPASS; without it ssh stays in batch mode (keys and agent only)
+        let askpass =
+            askpass_program().filter(|_| interactive || conn_config.ssh_password.is_some());
+        let host_key_checking = if askpass.is_some() && interactive && options.confirm_new_host_keys
+        {
+            "ask"
+        } else {
+            "accept-new"
+        };
+
+        let log_file = tempfile::Builder::new()
+            .prefix("dbcrust-ssh-")
+            .suffix(".log")
+            .tempfile()?
+            .into_temp_path();
+
+        let mut args = vec![
+            format!(
+                "-L{}:{}:{}",
+                self.local_port, self.remote_host, self.remote_port
+            ),
+            "-N".to_string(),
+        ];
+        let mut ssh_options = vec![
+            "ExitOnForwardFailure=yes".to_string(),
+            format!("BatchMode={}", if askpass.is_some() { "no" } else { "yes" }),
+            "ConnectTimeout=3".to_string(),
+            "ServerAliveInterval=10".to_string(),
+            "ServerAliveCountMax=2".to_string(),
+            format!("StrictHostKeyChecking={host_key_checking}"),
+            format!(
+                "PasswordAuthentication={}",
+                if askpass.is_some() { "yes" } else { "no" }
+            ),
+            format!(
+                "KbdInteractiveAuthentication={}",
+                if askpass.is_some() { "yes" } else { "no" }
+            ),
+            "LogLevel=ERROR".to_string(),
+        ];
+        if !options.known_hosts_file.is_empty() {
+            ssh_options.push(format!(
+                "UserKnownHostsFile={}",
+                expand_home(&options.known_hosts_file).display()
+            ));
+        }
+        if !options.proxy_command.is_empty() {
+            ssh_options.push(format!("ProxyCommand={}", options.proxy_command));
+        }
+        for option in ssh_options {
+            args.push("-o".to_string());
+            args.push(option);
+        }
+        // Errors go to the log so ssh's stderr stays free for askpass prompts
+        args.push("-E".to_string());
+        args.push(log_file.display().to_string());
 
         if let Some(key_path) = &self.ssh_key {
-            cmd.arg("-i");
-            cmd.arg(key_path);
+            args.push("-i".to_string());
+            args.push(key_path.display().to_string());
         }
 
-        cmd.arg("-p");
-        cmd.arg(self.ssh_port.to_string());
+        args.push("-p".to_string());
+        args.push(self.ssh_port.to_string());
+
+        args.push(format!("{}@{}", self.ssh_user, self.ssh_host));
 
-        cmd.arg(format!("{}@{}", self.ssh_user, self.ssh_host));
+        // Written before spawning and removed when establishing ends: ssh
+        // only asks for the password while authenticating
+        let password_file = match (&askpass, &conn_config.ssh_password) {
+            (Some(_), Some(password)) => Some(write_password_file(password)?),
+            _ => None,
+        };
+
+        let mut cmd = Command::new("ssh");
+        cmd.args(&args);
+        if let Some(program) = &askpass {
+            cmd.env("SSH_ASKPASS", program);
+            cmd.env("SSH_ASKPASS_REQUIRE", "force");
+            cmd.env(
+                ASKPASS_ENV,
+                if interactive { "interactive" } else { "batch" },
+            );
+            if let Some(path) = &password_file {
+                cmd.env(ASKPASS_PASSWORD_FILE_ENV, path);
+            }
+        }
 
         cmd.stdout(Stdio::null());
-        cmd.stderr(Stdio::piped());
+        cmd.stderr(Stdio::inherit());
         cmd.stdin(Stdio::null());
-
-        // Create a user-friendly representation of the SSH command for debug output
-        let ssh_command_str = format!(
-            "ssh -L{}:{}:{} -N -o ExitOnForwardFailure=yes -o BatchMode=yes -o ConnectTimeout=3 -o ServerAliveInterval=10 -o ServerAliveCountMax=2 -o StrictHostKeyChecking=accept-new -o PasswordAuthentication=no -o LogLevel=ERROR {}{}@{} -p {}",
-            self.local_port,
-            self.remote_host,
-            self.remote_port,
-            if let Some(key) = &self.ssh_key {
-                format!("-i {} ", key.display())
-            } else {
-                String::new()
-            },
-            self.ssh_user,
-            self.ssh_host,
-            self.ssh_port
-        );
+        self.log_file = Some(Arc::new(log_file));
 
         // Log that we're initiating the SSH tunnel
         info!(
@@ -155,7 +416,7 @@ impl SSHTunnel {
 
         debug!(
             "Executing SSH command: {}",
-            crate::password_sanitizer::sanitize_ssh_command(&ssh_command_str)
+            crate::password_sanitizer::sanitize_ssh_command(&format!("ssh {}", args.join(" ")))
         );
 
         let child = cmd.spawn().map_err(|e| {
@@ -174,7 +435,12 @@ impl SSHTunnel {
         *process_guard = Some(child);
         drop(process_guard);
 
-        let total_establishment_timeout = Duration::from_secs(8); // Reduced from 12
+        // Leave time to type a password or 2FA code when ssh may ask for one
+        let total_establishment_timeout = if askpass.is_some() && interactive {
+            Duration::from_secs(120)
+        } else {
+            Duration::from_secs(8)
+        };
         let tcp_check_interval = Duration::from_millis(500); // Check more frequently
         let individual_tcp_connect_timeout = Duration::from_millis(500); // Faster timeout
         let start_time = tokio::time::Instant::now();
@@ -190,14 +456,10 @@ impl SSHTunnel {
                     match child.try_wait() {
                         Ok(Some(status)) => {
                             // SSH process has exited - this is an error
-                            let mut stderr_output = String::new();
-                            if let Some(mut stderr) = child.stderr.take() {
-                                let _ = stderr.read_to_string(&mut stderr_output).await;
-                            }
                             return Err(SSHTunnelError::SshCommandFailed(format!(
                                 "SSH process exited with status: {}. Error: {}",
                                 status,
-                                stderr_output.trim()
+                                self.ssh_errors()
                             )));
                         }
                         Ok(None) => {
@@ -214,27 +476,8 @@ impl SSHTunnel {
                 eprintln!(
                     "Total timeout of {total_establishment_timeout:?} reached for SSH tunnel establishment to {local_addr}."
                 );
-                // Attempt to grab stderr before failing
-                let mut stderr_output = String::new();
                 if let Ok(mut guard) = self.tunnel_process.lock() {
                     if let Some(child_check) = guard.as_mut() {
-                        if let Some(mut stderr) = child_check.stderr.take() {
-                            // Use a short timeout for this final stderr read attempt
-                            match tokio::time::timeout(
-                                Duration::from_secs(1),
-                                stderr.read_to_string(&mut stderr_output),
-                            )
-                            .await
-                            {
-                                Ok(Ok(_)) => { /* Successfully read stderr */ }
-                                Ok(Err(io_err)) => {
-                                    eprintln!("IO error reading SSH stderr after timeout: {io_err}")
-                                }
-                                Err(_) => eprintln!(
-                                    "Timeout reading SSH stderr after overall establishment timeout."
-                                ),
-                            }
-                        }
                         match child_check.try_wait() {
                             Ok(Some(status)) => eprintln!(
                                 "SSH process {child_id} exited with status {status} during final error handling."
@@ -249,10 +492,10 @@ impl SSHTunnel {
                     }
                 }
                 return Err(SSHTunnelError::TimeoutError(format!(
-                    "Failed to establish SSH tunnel to {} within {:?}. Last SSH stderr: {}",
+                    "Failed to establish SSH tunnel to {} within {:?}. Last SSH error: {}",
                     local_addr,
                     total_establishment_timeout,
-                    stderr_output.trim()
+                    self.ssh_errors()
                 )));
             }
 
@@ -350,6 +593,56 @@ impl SSHTunnel {
         ))
     }
 
+    /// Errors ssh logged so far
+    fn ssh_errors(&self) -> String {
+        self.log_file
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path.as_ref() as &std::path::Path).ok())
+            .map(|log| log.trim().to_string())
+            .unwrap_or_default()
+    }
+
+    /// Start the tunnel again with the settings it was established with,
+    /// after its ssh process exited; returns the new local port
+    pub async fn reopen(&mut self) -> Result<u16, SSHTunnelError> {
+        let config = self.config.clone().ok_or_else(|| {
+            SSHTunnelError::ConfigError("The SSH tunnel was never established".to_string())
+        })?;
+        // The process is gone already; this only forgets it
+        let _ = self.stop().await;
+        let remote_host = self.remote_host.clone();
+        self.establish(&config, &remote_host, self.remote_port)
+            .await
+    }
+
+    /// Local end of the tunnel
+    pub fn local_port(&self) -> u16 {
+        self.local_port
+    }
+
+    /// Host the tunnel forwards to, as seen from the SSH server
+    pub fn remote_host(&self) -> &str {
+        &self.remote_host
+    }
+
+    /// SSH host and port the tunnel goes through
+    pub fn ssh_address(&self) -> (&str, u16) {
+        (&self.ssh_host, self.ssh_port)
+    }
+
+    /// Human-readable route, e.g. `ops@bastion:22 → db.internal:5432 (local port 54012)`
+    pub fn describe(&self) -> String {
+        let jump = if self.ssh_user.is_empty() {
+            format!("{}:{}", self.ssh_host, self.ssh_port)
+        } else {
+            format!("{}@{}:{}", self.ssh_user, self.ssh_host, self.ssh_port)
+        };
+        format!(
+            "{jump} → {}:{} (local port {})",
+            self.remote_host, self.remote_port, self.local_port
+        )
+    }
+
     /// Check if the SSH tunnel process is currently active.
     pub fn is_active(&self) -> bool {
         if let Ok(mut guard) = self.tunnel_process.lock() {
@@ -419,6 +712,8 @@ impl Default for SSHTunnel {
             ssh_host: String::new(),
             ssh_port: 22,
             tunnel_process: Arc::new(Mutex::new(None)),
+            log_file: None,
+            config: None,
         }
     }
 }
@@ -456,6 +751,7 @@ mod tests {
             ssh_username: Some("testuser".to_string()),
             ssh_password: None,
             ssh_key_path: None,
+            options: SshConfig::default(),
         }
     }
 
@@ -473,6 +769,53 @@ mod tests {
         );
     }
 
+    #[rstest]
+    #[case("ops@bastion's password: ", None, AskpassPrompt::Password)]
+    #[case("(ops@bastion) Password: ", None, AskpassPrompt::Password)]
+    #[case("(ops@bastion) Verification code: ", None, AskpassPrompt::Secret)]
+    #[case(
+        "Enter passphrase for key '/home/ops/.ssh/id_ed25519': ",
+        None,
+        AskpassPrompt::Secret
+    )]
+    #[case(
+        "The authenticity of host 'bastion (10.0.0.5)' can't be established.\nED25519 key fingerprint is SHA256:abc.\nAre you sure you want to continue connecting (yes/no/[fingerprint])? ",
+        None,
+        AskpassPrompt::Confirm
+    )]
+    #[case(
+        "Allow use of key id_ed25519?",
+        Some("confirm"),
+        AskpassPrompt::Confirm
+    )]
+    #[case(
+        "Confirm user presence for key ED25519-SK",
+        Some("none"),
+        AskpassPrompt::Notice
+    )]
+    fn test_classify_askpass_prompt(
+        #[case] prompt: &str,
+        #[case] kind: Option<&str>,
+        #[case] expected: AskpassPrompt,
+    ) {
+        assert_eq!(classify_askpass_prompt(prompt, kind), expected);
+    }
+
+    #[rstest]
+    fn test_password_file_is_owner_only_and_removed() {
+        let path = write_password_file("s3cret").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "s3cret");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o077, 0);
+        }
+        let kept = path.to_path_buf();
+        drop(path);
+        assert!(!kept.exists());
+    }
+
     #[rstest]
     fn test_error_from_io() {
         let io_err = io::Error::new(io::ErrorKind::NotFound, "file not found");
@@ -0,0 +1,680 @@
+//! Encryption report for the `\ssl` command
+//!
+//! PostgreSQL and MySQL report the protocol and cipher of the session itself.
+//! The server certificate comes from a separate probe handshake against the
+//! same address (through the SSH tunnel when there is one): subject, issuer,
+//! validity, DNS names and fingerprint, plus whether the chain is trusted for
+//! the host under the connection's TLS settings. MongoDB, Elasticsearch and
+//! ClickHouse have no server-side view, so the probe supplies the protocol
+//! and cipher as well.
+
+use crate::database::{ConnectionInfo, DatabaseType, DatabaseTypeExt};
+use crate::db::Database;
+use crate::tls::{TlsMode, TlsSettings};
+use chrono::{NaiveDateTime, Utc};
+use rustls::client::WebPkiServerVerifier;
+use rustls::client::danger::ServerCertVerifier;
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Ordered `label: value` report
+#[derive(Debug, Default)]
+pub struct SslReport {
+    fields: Vec<(&'static str, String)>,
+}
+
+impl SslReport {
+    fn push(&mut self, label: &'static str, value: impl Into<String>) {
+        let value = value.into();
+        if !value.is_empty() {
+            self.fields.push((label, value));
+        }
+    }
+
+    pub fn render(&self) -> String {
+        let width = self.fields.iter().map(|(l, _)| l.len()).max().unwrap_or(0) + 1;
+        self.fields
+            .iter()
+            .map(|(label, value)| format!("{:<width$} {value}", format!("{label}:")))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// What the server speaks before the TLS handshake starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Preamble {
+    None,
+    /// SSLRequest message, answered with a single `S`
+    Postgres,
+    /// Server greeting, then an SSLRequest packet
+    MySql,
+}
+
+/// Result of the probe handshake
+struct Probe {
+    protocol: String,
+    cipher: String,
+    chain: Vec<CertificateDer<'static>>,
+    /// TCP connect time, before any preamble
+    connect: Duration,
+    /// TLS handshake time
+    handshake: Duration,
+}
+
+async fn postgres_preamble(stream: &mut TcpStream) -> std::io::Result<bool> {
+    stream
+        .write_all(&[0, 0, 0, 8, 0x04, 0xd2, 0x16, 0x2f])
+        .await?;
+    let mut answer = [0u8; 1];
+    stream.read_exact(&mut answer).await?;
+    Ok(answer[0] == b'S')
+}
+
+async fn mysql_preamble(stream: &mut TcpStream) -> std::io::Result<bool> {
+    const CLIENT_LONG_PASSWORD: u32 = 0x1;
+    const CLIENT_PROTOCOL_41: u32 = 0x200;
+    const CLIENT_SSL: u32 = 0x800;
+    const CLIENT_SECURE_CONNECTION: u32 = 0x8000;
+
+    let mut header = [0u8; 4];
+    stream.read_exact(&mut header).await?;
+    let length = u32::from_le_bytes([header[0], header[1], header[2], 0]) as usize;
+    let mut greeting = vec![0u8; length];
+    stream.read_exact(&mut greeting).await?;
+    // protocol version, NUL-terminated server version, connection id,
+    // 8 bytes of scramble, filler, then the low capability flags
+    let Some(version_end) = greeting.iter().skip(1).position(|b| *b == 0) else {
+        return Ok(false);
+    };
+    let flags_at = 1 + version_end + 1 + 4 + 8 + 1;
+    let Some(flags) = greeting.get(flags_at..flags_at + 2) else {
+        return Ok(false);
+    };
+    if u16::from_le_bytes([flags[0], flags[1]]) as u32 & CLIENT_SSL == 0 {
+        return Ok(false);
+    }
+
+    let capabilities =
+        CLIENT_LONG_PASSWORD | CLIENT_PROTOCOL_41 | CLIENT_SSL | CLIENT_SECURE_CONNECTION;
+    let mut packet = vec![32, 0, 0, header[3].wrapping_add(1)];
+    packet.extend(capabilities.to_le_bytes());
+    packet.extend((16u32 * 1024 * 1024).to_le_bytes());
+    packet.push(45); // utf8mb4_general_ci
+    packet.extend([0u8; 23]);
+    stream.write_all(&packet).await?;
+    Ok(true)
+}
+
+/// Handshake with the server and keep what it negotiated and presented.
+/// The probe trusts any certificate: it reports, the checks come after.
+async fn probe(
+    address: (&str, u16),
+    server_name: &str,
+    preamble: Preamble,
+    tls: &TlsSettings,
+) -> Result<Probe, String> {
+    let connect = async {
+        let started = Instant::now();
+        let mut stream = TcpStream::connect(address)
+            .await
+            .map_err(|e| format!("Cannot reach {}:{}: {e}", address.0, address.1))?;
+        let connect = started.elapsed();
+        let accepted = match preamble {
+            Preamble::None => Ok(true),
+            Preamble::Postgres => postgres_preamble(&mut stream).await,
+            Preamble::MySql => mysql_preamble(&mut stream).await,
+        }
+        .map_err(|e| format!("Handshake with the server failed: {e}"))?;
+        if !accepted {
+            return Err("The server does not offer TLS".to_string());
+        }
+
+        let config = tls.client_config(TlsMode::Require)?;
+        let name = ServerName::try_from(server_name.to_string())
+            .map_err(|e| format!("Invalid server name '{server_name}': {e}"))?;
+        let started = Instant::now();
+        let stream = tokio_rustls::TlsConnector::from(Arc::new(config))
+            .connect(name, stream)
+            .await
+            .map_err(|e| format!("TLS handshake failed: {e}"))?;
+        let handshake = started.elapsed();
+        let (_, connection) = stream.get_ref();
+        Ok(Probe {
+            protocol: connection
+                .protocol_version()
+                .and_then(|version| version.as_str())
+                .map(|version| version.replace('_', "."))
+                .unwrap_or_default(),
+            // rustls spells the TLS 1.3 suites TLS13_*, servers report the IANA names
+            cipher: connection
+                .negotiated_cipher_suite()
+                .and_then(|suite| suite.suite().as_str())
+                .map(|suite| suite.replacen("TLS13_", "TLS_", 1))
+                .unwrap_or_default(),
+            chain: connection
+                .peer_certificates()
+                .unwrap_or_default()
+                .iter()
+                .map(|cert| cert.clone().into_owned())
+                .collect(),
+            connect,
+            handshake,
+        })
+    };
+    tokio::time::timeout(PROBE_TIMEOUT, connect)
+        .await
+        .map_err(|_| format!("No TLS answer from {}:{} within 5s", address.0, address.1))?
+}
+
+/// Whether the chain is trusted for `server_name` under `mode`
+fn check_chain(
+    chain: &[CertificateDer<'static>],
+    server_name: &str,
+    mode: TlsMode,
+    tls: &TlsSettings,
+) -> String {
+    let Some((end_entity, intermediates)) = chain.split_first() else {
+        return "no certificate presented".to_string();
+    };
+    let verify = || -> Result<(), String> {
+        let verifier = WebPkiServerVerifier::builder_with_provider(
+            Arc::new(tls.root_store()?),
+            crate::tls::provider(),
+        )
+        .build()
+        .map_err(|e| e.to_string())?;
+        let name = ServerName::try_from(server_name.to_string()).map_err(|e| e.to_string())?;
+        verifier
+            .verify_server_cert(end_entity, intermediates, &name, &[], UnixTime::now())
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    };
+    let roots = if tls.root_cert.is_some() {
+        "sslrootcert"
+    } else {
+        "built-in roots"
+    };
+    match verify() {
+        Ok(()) => format!("trusted for {server_name} ({roots})"),
+        Err(e) if mode.requires_tls() && mode != TlsMode::VerifyFull => {
+            format!("not trusted for {server_name}: {e} (not checked in {mode} mode)")
+        }
+        Err(e) => format!("not trusted for {server_name}: {e}"),
+    }
+}
+
+/// Subject, issuer, validity and names of a DER certificate
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct CertificateSummary {
+    pub(crate) subject: String,
+    pub(crate) issuer: String,
+    pub(crate) not_before: Option<NaiveDateTime>,
+    pub(crate) not_after: Option<NaiveDateTime>,
+    pub(crate) dns_names: Vec<String>,
+}
+
+/// One DER element: tag, contents, remaining input
+fn der_element(input: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, rest) = input.split_first()?;
+    let (&first, rest) = rest.split_first()?;
+    let (length, rest) = if first < 0x80 {
+        (first as usize, rest)
+    } else {
+        let count = (first & 0x7f) as usize;
+        if count == 0 || count > 4 || rest.len() < count {
+            return None;
+        }
+        let length = rest[..count]
+            .iter()
+            .fold(0usize, |acc, byte| (acc << 8) | *byte as usize);
+        (length, &rest[count..])
+    };
+    (rest.len() >= length).then(|| (tag, &rest[..length], &rest[length..]))
+}
+
+/// The elements of a constructed value
+fn der_children(mut input: &[u8]) -> Vec<(u8, &[u8])> {
+    let mut children = Vec::new();
+    while let Some((tag, contents, rest)) = der_element(input) {
+        children.push((tag, contents));
+        input = rest;
+    }
+    children
+}
+
+/// `CN=db.example.com, O=Example` from an X.509 Name
+fn der_name(name: &[u8]) -> String {
+    let mut parts = Vec::new();
+    for (_, set) in der_children(name) {
+        for (_, attribute) in der_children(set) {
+            let [(0x06, oid), (_, value), ..] = der_children(attribute)[..] else {
+                continue;
+            };
+            let label = match oid {
+                [0x55, 0x04, 0x03] => "CN",
+                [0x55, 0x04, 0x06] => "C",
+                [0x55, 0x04, 0x07] => "L",
+                [0x55, 0x04, 0x08] => "ST",
+                [0x55, 0x04, 0x0a] => "O",
+                [0x55, 0x04, 0x0b] => "OU",
+                [0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x09, 0x01] => "emailAddress",
+                _ => continue,
+            };
+            parts.push(format!("{label}={}", String::from_utf8_lossy(value)));
+        }
+    }
+    parts.join(", ")
+}
+
+/// UTCTime (`YYMMDDHHMMSSZ`) or GeneralizedTime (`YYYYMMDDHHMMSSZ`)
+fn der_time(tag: u8, value: &[u8]) -> Option<NaiveDateTime> {
+    let text = std::str::from_utf8(value).ok()?.trim_end_matches('Z');
+    let full = match tag {
+        0x17 => {
+            let year: u32 = text.get(..2)?.parse().ok()?;
+            format!("{}{text}", if year < 50 { "20" } else { "19" })
+        }
+        0x18 => text.to_string(),
+        _ => return None,
+    };
+    NaiveDateTime::parse_from_str(&full, "%Y%m%d%H%M%S").ok()
+}
+
+pub(crate) fn summarize(der: &[u8]) -> Option<CertificateSummary> {
+    let (_, certificate, _) = der_element(der)?;
+    let (_, tbs, _) = der_element(certificate)?;
+    let mut fields = der_children(tbs);
+    if fields.first().is_some_and(|(tag, _)| *tag == 0xa0) {
+        fields.remove(0); // explicit version
+    }
+    // serial, signature algorithm, issuer, validity, subject, key, [extensions]
+    let issuer = fields.get(2)?.1;
+    let validity = der_children(fields.get(3)?.1);
+    let subject = fields.get(4)?.1;
+
+    let mut dns_names = Vec::new();
+    if let Some((_, extensions)) = fields.iter().find(|(tag, _)| *tag == 0xa3) {
+        for (_, list) in der_children(extensions) {
+            for (_, extension) in der_children(list) {
+                let parts = der_children(extension);
+                if parts.first() != Some(&(0x06, &[0x55, 0x1d, 0x11][..])) {
+                    continue;
+                }
+                let Some((_, value)) = parts.iter().find(|(tag, _)| *tag == 0x04) else {
+                    continue;
+                };
+                let Some((_, names, _)) = der_element(value) else {
+                    continue;
+                };
+                for (tag, name) in der_children(names) {
+                    match tag {
+                        0x82 => dns_names.push(String::from_utf8_lossy(name).into_owned()),
+                        0x87 => match name.len() {
+                            4 => dns_names.push(
+                                std::net::Ipv4Addr::new(name[0], name[1], name[2], name[3])
+                                    .to_string(),
+                            ),
+                            16 => {
+                                let octets: [u8; 16] = name.try_into().ok()?;
+                                dns_names.push(std::net::Ipv6Addr::from(octets).to_string());
+                            }
+                            _ => {}
+                        },
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+
+    Some(CertificateSummary {
+        subject: der_name(subject),
+        issuer: der_name(issuer),
+        not_before: validity.first().and_then(|(tag, v)| der_time(*tag, v)),
+        not_after: validity.get(1).and_then(|(tag, v)| der_time(*tag, v)),
+        dns_names,
+    })
+}
+
+/// `AB:CD:...` SHA-256 of the DER bytes
+fn fingerprint(der: &[u8]) -> String {
+    Sha256::digest(der)
+        .iter()
+        .map(|byte| format!("{byte:02X}"))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+fn validity(summary: &CertificateSummary) -> String {
+    let format = |time: &Option<NaiveDateTime>| {
+        time.map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+            .unwrap_or_else(|| "?".to_string())
+    };
+    let remaining = summary.not_after.map(|not_after| {
+        let days = (not_after - Utc::now().naive_utc()).num_days();
+        match days {
+            d if d < 0 => format!(" (expired {} days ago)", -d),
+            0 => " (expires today)".to_string(),
+            d => format!(" (expires in {d} days)"),
+        }
+    });
+    format!(
+        "{} → {}{}",
+        format(&summary.not_before),
+        format(&summary.not_after),
+        remaining.unwrap_or_default()
+    )
+}
+
+/// Where to probe and which name to check: the tunnel's local end with the
+/// real host name, or the host itself
+fn probe_target(database: &Database, info: &ConnectionInfo) -> Option<(String, u16, String)> {
+    if let Some(tunnel) = database.get_ssh_tunnel() {
+        return Some((
+            "127.0.0.1".to_string(),
+            tunnel.local_port(),
+            tunnel.remote_host().to_string(),
+        ));
+    }
+    let host = info.host.as_deref()?;
+    if info.srv {
+        return None;
+    }
+    // Seed lists: the first member
+    let (host, port) = match host.split(',').next()?.rsplit_once(':') {
+        Some((host, port)) => (host, port.parse().ok()?),
+        None => (host.split(',').next()?, info.port.or(info.default_port())?),
+    };
+    let address = if host == "localhost" || host.ends_with(".localhost") {
+        "127.0.0.1"
+    } else {
+        host
+    };
+    Some((address.to_string(), port, host.to_string()))
+}
+
+/// Session protocol and cipher as the server reports them; `None` when it
+/// cannot tell, `Some(None)` for an unencrypted session
+async fn session_encryption(
+    database: &mut Database,
+    db_type: &DatabaseType,
+) -> Option<Option<(String, String)>> {
+    match db_type {
+        DatabaseType::PostgreSQL => {
+            let query = "SELECT ssl, version, cipher, bits FROM pg_stat_ssl \
+                         WHERE pid = pg_backend_pid()";
+            let results = database.execute_query(query).await.ok()?;
+            let row = crate::whoami::first_row(&results);
+            if row.get("ssl").is_none_or(|ssl| ssl != "t" && ssl != "true") {
+                return Some(None);
+            }
+            let cipher = match row.get("bits") {
+                Some(bits) => format!("{} ({bits} bits)", row.get("cipher")?),
+                None => row.get("cipher")?.clone(),
+            };
+            Some(Some((row.get("version")?.clone(), cipher)))
+        }
+        DatabaseType::MySQL => {
+            let results = database
+                .execute_query("SHOW SESSION STATUS LIKE 'Ssl_%'")
+                .await
+                .ok()?;
+            let status = |name: &str| {
+                results
+                    .iter()
+                    .skip(1)
+                    .find(|row| row.first().is_some_and(|n| n == name))
+                    .and_then(|row| row.get(1))
+                    .filter(|value| !value.is_empty())
+                    .cloned()
+            };
+            match status("Ssl_version") {
+                None => Some(None),
+                Some(version) => Some(Some((version, status("Ssl_cipher").unwrap_or_default()))),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Preamble of the backends `\ssl` can probe; `None` for local databases
+fn preamble_for(db_type: &DatabaseType) -> Option<Preamble> {
+    match db_type {
+        DatabaseType::PostgreSQL => Some(Preamble::Postgres),
+        DatabaseType::MySQL => Some(Preamble::MySql),
+        DatabaseType::MongoDB | DatabaseType::Elasticsearch | DatabaseType::ClickHouse => {
+            Some(Preamble::None)
+        }
+        _ => None,
+    }
+}
+
+/// Whether the session is encrypted: as the server reports it, else exactly
+/// when the client asked for it
+fn is_encrypted(
+    session: &Option<Option<(String, String)>>,
+    tls: &TlsSettings,
+    info: &ConnectionInfo,
+) -> bool {
+    match session {
+        Some(session) => session.is_some(),
+        None => match tls.mode {
+            Some(TlsMode::Disable) => false,
+            Some(mode) if mode.requires_tls() => true,
+            _ => info.use_tls,
+        },
+    }
+}
+
+/// Time spent opening a connection to the server
+#[derive(Debug, Clone, Copy)]
+pub struct HandshakeTimings {
+    pub connect: Duration,
+    /// `None` for an unencrypted session
+    pub tls: Option<Duration>,
+}
+
+/// TCP connect and TLS handshake times of a fresh connection to the server,
+/// made like the `\ssl` probe; `None` when there is no single network
+/// address to time
+pub async fn handshake_timings(
+    database: &mut Database,
+) -> Option<Result<HandshakeTimings, String>> {
+    let db_type = database.get_database_type();
+    let preamble = preamble_for(&db_type)?;
+    let info = database.get_connection_info().cloned()?;
+    let (address, port, server_name) = probe_target(database, &info)?;
+    let tls = match TlsSettings::for_connection(&info) {
+        Ok(tls) => tls,
+        Err(e) => return Some(Err(e)),
+    };
+    let session = session_encryption(database, &db_type).await;
+    if is_encrypted(&session, &tls, &info) {
+        let probe = probe((&address, port), &server_name, preamble, &tls).await;
+        return Some(probe.map(|probe| HandshakeTimings {
+            connect: probe.connect,
+            tls: Some(probe.handshake),
+        }));
+    }
+    let started = Instant::now();
+    let connect = tokio::time::timeout(PROBE_TIMEOUT, TcpStream::connect((address.as_str(), port)))
+        .await
+        .map_err(|_| format!("No answer from {address}:{port} within 5s"))
+        .and_then(|connected| connected.map_err(|e| format!("Cannot reach {address}:{port}: {e}")));
+    Some(connect.map(|_| HandshakeTimings {
+        connect: started.elapsed(),
+        tls: None,
+    }))
+}
+
+/// Build the `\ssl` report for the current connection
+pub async fn collect_ssl(database: &mut Database) -> SslReport {
+    let mut report = SslReport::default();
+    let db_type = database.get_database_type();
+    let Some(info) = database.get_connection_info().cloned() else {
+        report.push("Encryption", "no connection");
+        return report;
+    };
+
+    let Some(preamble) = preamble_for(&db_type) else {
+        report.push(
+            "Encryption",
+            format!(
+                "not applicable ({} is not a network connection)",
+                db_type.display_name()
+            ),
+        );
+        return report;
+    };
+    let tls = match TlsSettings::for_connection(&info) {
+        Ok(tls) => tls,
+        Err(e) => {
+            report.push("Settings", format!("invalid: {e}"));
+            return report;
+        }
+    };
+
+    // What the session negotiated; the other backends are encrypted exactly
+    // when the client asked for it
+    let session = session_encryption(database, &db_type).await;
+    let encrypted = is_encrypted(&session, &tls, &info);
+    let mode = match (preamble, tls.mode) {
+        (Preamble::Postgres | Preamble::MySql, None) => "prefer (default)".to_string(),
+        (_, None) if encrypted => format!("{} (default)", tls.encrypted_mode()),
+        (_, None) => "disable (default)".to_string(),
+        (_, Some(_)) if encrypted && preamble == Preamble::None => tls.encrypted_mode().to_string(),
+        (_, Some(mode)) => tls.mode_or(mode).to_string(),
+    };
+    report.push("Mode", mode);
+    if !encrypted {
+        report.push("Encryption", "off");
+        return report;
+    }
+    if let Some(Some((version, cipher))) = &session {
+        report.push("Protocol", version.clone());
+        report.push("Cipher", cipher.clone());
+    }
+    if let Some(version) = tls.min_version {
+        report.push("Minimum version", version.to_string());
+    }
+    if let Some(client_cert) = &tls.client_cert {
+        report.push("Client certificate", client_cert.display().to_string());
+        match crate::client_cert::read(client_cert) {
+            Ok(summary) => {
+                report.push("Client subject", summary.subject.clone());
+                report.push("Client issuer", summary.issuer.clone());
+                report.push("Client valid", validity(&summary));
+            }
+            Err(e) => report.push("Client subject", format!("unreadable: {e}")),
+        }
+    }
+
+    let Some((address, port, server_name)) = probe_target(database, &info) else {
+        report.push(
+            "Certificate",
+            "not probed (SRV connection strings have no single host)",
+        );
+        return report;
+    };
+    let probe = match probe((&address, port), &server_name, preamble, &tls).await {
+        Ok(probe) => probe,
+        Err(e) => {
+            report.push("Certificate", format!("probe failed: {e}"));
+            return report;
+        }
+    };
+    if session.is_none() {
+        report.push("Protocol", format!("{} (probe)", probe.protocol));
+        report.push("Cipher", format!("{} (probe)", probe.cipher));
+    }
+    if let Some(leaf) = probe.chain.first() {
+        match summarize(leaf) {
+            Some(summary) => {
+                report.push("Subject", summary.subject.clone());
+                report.push("Issuer", summary.issuer.clone());
+                report.push("Valid", validity(&summary));
+                report.push("Names", summary.dns_names.join(", "));
+            }
+            None => report.push("Certificate", "unreadable"),
+        }
+        report.push("SHA-256", fingerprint(leaf));
+    }
+    report.push(
+        "Chain",
+        match probe.chain.len() {
+            1 => "1 certificate".to_string(),
+            n => format!("{n} certificates"),
+        },
+    );
+    report.push(
+        "Trust",
+        check_chain(&probe.chain, &server_name, tls.encrypted_mode(), &tls),
+    );
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Self-signed certificate for `db.example.com` (SAN db.example.com,
+    /// 127.0.0.1), valid 2024-01-01 to 2034-01-01
+    const CERT_PEM: &str = "\
+-----BEGIN CERTIFICATE-----
+MIIBzDCCAXKgAwIBAgIUCzdAMyDAwDEJ8rEjgywX/KrwedMwCgYIKoZIzj0EAwIw
+KzEXMBUGA1UEAwwOZGIuZXhhbXBsZS5jb20xEDAOBgNVBAoMB0V4YW1wbGUwHhcN
+MjQwMTAxMDAwMDAwWhcNMzQwMTAxMDAwMDAwWjArMRcwFQYDVQQDDA5kYi5leGFt
+cGxlLmNvbTEQMA4GA1UECgwHRXhhbXBsZTBZMBMGByqGSM49AgEGCCqGSM49AwEH
+A0IABBy0fdKJ1PtIEYM7wcIZXs3kneiNfmkD1San853UAX4ja2rnRFRO783sQrH7
+lANMqzevHsvmkNQTfDxSooUy05ijdDByMB0GA1UdDgQWBBROzXeWQC75dzj1SWnb
+DY1WlUN33jAfBgNVHSMEGDAWgBROzXeWQC75dzj1SWnbDY1WlUN33jAPBgNVHRMB
+Af8EBTADAQH/MB8GA1UdEQQYMBaCDmRiLmV4YW1wbGUuY29thwR/AAABMAoGCCqG
+SM49BAMCA0gAMEUCIQDeki8e09LTLt57Ufm4HZf8ctBrHwWVmkrkFoY97CWO7AIg
+ArJdo1gS/xYjr6zyHqAOh4erFnO63m8q+gHtvTgIDuM=
+-----END CERTIFICATE-----";
+
+    fn der() -> Vec<u8> {
+        use rustls::pki_types::pem::PemObject;
+        CertificateDer::from_pem_slice(CERT_PEM.as_bytes())
+            .unwrap()
+            .to_vec()
+    }
+
+    #[test]
+    fn test_summarize_reads_names_and_validity() {
+        let summary = summarize(&der()).unwrap();
+        assert_eq!(summary.subject, "CN=db.example.com, O=Example");
+        assert_eq!(summary.issuer, summary.subject);
+        assert_eq!(summary.dns_names, vec!["db.example.com", "127.0.0.1"]);
+        assert_eq!(
+            summary.not_before.unwrap().to_string(),
+            "2024-01-01 00:00:00"
+        );
+        assert!(validity(&summary).starts_with("2024-01-01 00:00:00 UTC → 2034-01-01"));
+        assert_eq!(fingerprint(&der()).len(), 32 * 3 - 1);
+        assert!(summarize(b"\x30\x03\x02\x01\x00").is_none());
+    }
+
+    #[test]
+    fn test_der_time_handles_both_encodings() {
+        assert_eq!(
+            der_time(0x17, b"491231235959Z").unwrap().to_string(),
+            "2049-12-31 23:59:59"
+        );
+        assert_eq!(
+            der_time(0x17, b"500101000000Z").unwrap().to_string(),
+            "1950-01-01 00:00:00"
+        );
+        assert_eq!(
+            der_time(0x18, b"20500101000000Z").unwrap().to_string(),
+            "2050-01-01 00:00:00"
+        );
+    }
+}
@@ -0,0 +1,265 @@
+//! Saved per-table display preferences (`\prefs`)
+//!
+//! Expanded display, picked columns and a sort order are remembered for the
+//! table a query reads from (or, for anything more involved than a one-table
+//! SELECT, for the query's fingerprint) and reapplied the next time it is
+//! queried. Recorded as you go: toggling `\x` right after a query or picking
+//! columns in the selector updates the entry, `\prefs sort` sets the order.
+//! Stored next to the config in `table_prefs.toml`.
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use tracing::debug;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SortPref {
+    pub column: String,
+    #[serde(default)]
+    pub descending: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct TablePrefs {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expanded: Option<bool>,
+    /// Columns to display, in order; empty shows them all
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub columns: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sort: Option<SortPref>,
+}
+
+impl TablePrefs {
+    fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct PrefsFile {
+    #[serde(default)]
+    tables: BTreeMap<String, TablePrefs>,
+}
+
+#[derive(Debug, Default)]
+pub struct TablePrefsStore {
+    file: PrefsFile,
+    /// `None` keeps the preferences in memory only (tests, batch mode)
+    path: Option<PathBuf>,
+}
+
+impl TablePrefsStore {
+    /// Load `table_prefs.toml` from the config directory
+    pub fn load() -> Self {
+        let path = match crate::config::Config::get_config_dir() {
+            Ok(dir) => dir.join("table_prefs.toml"),
+            Err(e) => {
+                debug!("No config directory for table preferences: {}", e);
+                return Self::default();
+            }
+        };
+        let file = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| match toml::from_str(&content) {
+                Ok(file) => Some(file),
+                Err(e) => {
+                    debug!("Ignoring unreadable {}: {}", path.display(), e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+        Self {
+            file,
+            path: Some(path),
+        }
+    }
+
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let result = toml::to_string_pretty(&self.file)
+            .map_err(|e| e.to_string())
+            .and_then(|content| std::fs::write(path, content).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            debug!("Failed to save table preferences: {}", e);
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&TablePrefs> {
+        self.file.tables.get(key)
+    }
+
+    /// All entries, sorted by key
+    pub fn entries(&self) -> impl Iterator<Item = (&String, &TablePrefs)> {
+        self.file.tables.iter()
+    }
+
+    /// Change the entry for `key` and persist it; entries left empty are dropped
+    pub fn update(&mut self, key: &str, change: impl FnOnce(&mut TablePrefs)) {
+        let mut prefs = self.file.tables.remove(key).unwrap_or_default();
+        change(&mut prefs);
+        if !prefs.is_empty() {
+            self.file.tables.insert(key.to_string(), prefs);
+        }
+        self.save();
+    }
+
+    /// Forget one entry; returns whether it existed
+    pub fn remove(&mut self, key: &str) -> bool {
+        let removed = self.file.tables.remove(key).is_some();
+        if removed {
+            self.save();
+        }
+        removed
+    }
+
+    /// Forget every entry; returns how many there were
+    pub fn clear(&mut self) -> usize {
+        let count = self.file.tables.len();
+        self.file.tables.clear();
+        self.save();
+        count
+    }
+}
+
+/// Key preferences are stored under: `table:<name>` for a one-table SELECT,
+/// `query:<fingerprint>` otherwise
+pub fn prefs_key(query: &str) -> String {
+    let fingerprint = crate::sql_buffer::query_fingerprint(query);
+    match single_table(&fingerprint) {
+        Some(table) => format!("table:{table}"),
+        None => format!("query:{fingerprint}"),
+    }
+}
+
+/// `\prefs clear users` → `table:users`; full keys are kept as given.
+/// Unquoted names are lowercased, as in query fingerprints.
+pub fn key_for_table(name: &str) -> String {
+    if name.starts_with("table:") || name.starts_with("query:") {
+        name.to_string()
+    } else if name.contains(['"', '`', '[']) {
+        format!("table:{}", unquote(name))
+    } else {
+        format!("table:{}", name.to_lowercase())
+    }
+}
+
+fn unquote(name: &str) -> String {
+    name.chars()
+        .filter(|c| !matches!(c, '"' | '`' | '[' | ']'))
+        .collect()
+}
+
+/// The table of `select ... from <table> [where/order by/limit ...]`, from a
+/// fingerprint; `None` for joins, subqueries, CTEs and set operations
+fn single_table(fingerprint: &str) -> Option<String> {
+    let words: Vec<&str> = fingerprint.split_whitespace().collect();
+    if words.first() != Some(&"select")
+        || fingerprint.contains('(')
+        || words.iter().any(|word| {
+            matches!(
+                *word,
+                "join" | "union" | "intersect" | "except" | "with" | "into"
+            )
+        })
+    {
+        return None;
+    }
+    let from = words.iter().position(|word| *word == "from")?;
+    if words[from + 1..].contains(&"from") {
+        return None;
+    }
+    let table = words.get(from + 1)?;
+    let next = words.get(from + 2).copied();
+    if table.ends_with(',') || next.is_some_and(|word| word.starts_with(',')) {
+        return None;
+    }
+    let table = unquote(table.trim_end_matches(';'));
+    (!table.is_empty()).then_some(table)
+}
+
+/// Sort data rows (header first) by `sort`; `false` when the column is not there
+pub fn sort_rows(data: &mut [Vec<String>], sort: &SortPref) -> bool {
+    let Some((header, rows)) = data.split_first_mut() else {
+        return false;
+    };
+    let Some(index) = header
+        .iter()
+        .position(|name| name.eq_ignore_ascii_case(&sort.column))
+    else {
+        return false;
+    };
+    rows.sort_by(|a, b| {
+        let ordering = compare_values(
+            a.get(index).map_or("", String::as_str),
+            b.get(index).map_or("", String::as_str),
+        );
+        if sort.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+    true
+}
+
+/// Numbers compare numerically, anything else as text
+fn compare_values(a: &str, b: &str) -> Ordering {
+    match (a.trim().parse::<f64>(), b.trim().parse::<f64>()) {
+        (Ok(a), Ok(b)) => a.total_cmp(&b),
+        _ => a.cmp(b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefs_key() {
+        assert_eq!(prefs_key("SELECT * FROM users"), "table:users");
+        assert_eq!(
+            prefs_key("select id, name from public.\"Users\" where id = 4 order by id;"),
+            "table:public.Users"
+        );
+        assert_eq!(
+            prefs_key("SELECT * FROM users u JOIN orders o ON o.user_id = u.id"),
+            "query:select * from users u join orders o on o.user_id = u.id"
+        );
+        assert!(prefs_key("SELECT * FROM a, b").starts_with("query:"));
+        assert!(prefs_key("SELECT count(*) FROM users").starts_with("query:"));
+        assert_eq!(key_for_table("Users"), "table:users");
+        assert_eq!(key_for_table("public.\"Users\""), "table:public.Users");
+    }
+
+    #[test]
+    fn test_update_drops_empty_entries_and_sorts() {
+        let mut store = TablePrefsStore::default();
+        store.update("table:users", |prefs| prefs.expanded = Some(true));
+        assert_eq!(store.get("table:users").unwrap().expanded, Some(true));
+        store.update("table:users", |prefs| prefs.expanded = None);
+        assert!(store.get("table:users").is_none());
+
+        let mut data = vec![
+            vec!["id".to_string(), "name".to_string()],
+            vec!["10".to_string(), "b".to_string()],
+            vec!["9".to_string(), "a".to_string()],
+        ];
+        let sort = SortPref {
+            column: "ID".to_string(),
+            descending: true,
+        };
+        assert!(sort_rows(&mut data, &sort));
+        assert_eq!(data[1][0], "10");
+        assert!(!sort_rows(
+            &mut data,
+            &SortPref {
+                column: "missing".to_string(),
+                descending: false,
+            }
+        ));
+    }
+}
@@ -0,0 +1,561 @@
+//! TLS settings per connection (`[tls]` and URL parameters)
+//!
+//! Every network backend understands the same parameters: `sslmode`,
+//! `sslrootcert`, `sslcert`, `sslkey`, `verify_hostname` and
+//! `ssl_min_protocol_version` (MySQL's `ssl-mode`, `ssl-ca`, `ssl-cert` and
+//! `ssl-key` spellings work too). Whatever the URL leaves out comes from the
+//! `[tls]` config section. Each client maps the settings onto its driver;
+//! a setting the driver cannot honour fails the connection instead of being
+//! dropped silently.
+
+use rustls::client::WebPkiServerVerifier;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use rustls::{CertificateError, DigitallySignedStruct, RootCertStore, SignatureScheme};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+
+const MODE_KEYS: &[&str] = &["sslmode", "ssl-mode", "ssl_mode"];
+const ROOT_CERT_KEYS: &[&str] = &["sslrootcert", "ssl-ca", "sslca"];
+const CLIENT_CERT_KEYS: &[&str] = &["sslcert", "ssl-cert"];
+const CLIENT_KEY_KEYS: &[&str] = &["sslkey", "ssl-key"];
+const VERIFY_HOSTNAME_KEYS: &[&str] = &["verify_hostname", "verify-hostname"];
+const MIN_VERSION_KEYS: &[&str] = &["ssl_min_protocol_version", "tls_min_version"];
+
+/// Whether a URL parameter is one of the TLS settings handled here
+pub fn is_tls_option(key: &str) -> bool {
+    [
+        MODE_KEYS,
+        ROOT_CERT_KEYS,
+        CLIENT_CERT_KEYS,
+        CLIENT_KEY_KEYS,
+        VERIFY_HOSTNAME_KEYS,
+        MIN_VERSION_KEYS,
+    ]
+    .iter()
+    .any(|keys| keys.iter().any(|k| k.eq_ignore_ascii_case(key)))
+}
+
+/// How hard to insist on TLS, with libpq's names
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TlsMode {
+    Disable,
+    Allow,
+    Prefer,
+    /// Encrypt, but accept any certificate
+    Require,
+    /// Verify the chain, not the host name
+    VerifyCa,
+    VerifyFull,
+}
+
+impl TlsMode {
+    /// Whether the connection must be encrypted
+    pub fn requires_tls(self) -> bool {
+        matches!(self, Self::Require | Self::VerifyCa | Self::VerifyFull)
+    }
+}
+
+impl fmt::Display for TlsMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Disable => "disable",
+            Self::Allow => "allow",
+            Self::Prefer => "prefer",
+            Self::Require => "require",
+            Self::VerifyCa => "verify-ca",
+            Self::VerifyFull => "verify-full",
+        })
+    }
+}
+
+impl FromStr for TlsMode {
+    type Err = String;
+
+    /// libpq names, plus MySQL's `DISABLED`/`PREFERRED`/`REQUIRED`/`VERIFY_IDENTITY`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().replace('_', "-").as_str() {
+            "disable" | "disabled" => Ok(Self::Disable),
+            "allow" => Ok(Self::Allow),
+            "prefer" | "preferred" => Ok(Self::Prefer),
+            "require" | "required" => Ok(Self::Require),
+            "verify-ca" => Ok(Self::VerifyCa),
+            "verify-full" | "verify-identity" => Ok(Self::VerifyFull),
+            _ => Err(format!(
+                "Unknown TLS mode '{s}' (expected disable, allow, prefer, require, verify-ca or verify-full)"
+            )),
+        }
+    }
+}
+
+/// Protocol version floor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TlsVersion {
+    V1_0,
+    V1_1,
+    V1_2,
+    V1_3,
+}
+
+impl fmt::Display for TlsVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::V1_0 => "TLSv1",
+            Self::V1_1 => "TLSv1.1",
+            Self::V1_2 => "TLSv1.2",
+            Self::V1_3 => "TLSv1.3",
+        })
+    }
+}
+
+impl FromStr for TlsVersion {
+    type Err = String;
+
+    /// `1.2`, `TLSv1.2`, `tls1_2`; `TLSv1` is 1.0
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_ascii_lowercase().replace('_', ".");
+        let number = lower
+            .strip_prefix("tlsv")
+            .or_else(|| lower.strip_prefix("tls"))
+            .unwrap_or(&lower);
+        match number {
+            "1" | "1.0" => Ok(Self::V1_0),
+            "1.1" => Ok(Self::V1_1),
+            "1.2" => Ok(Self::V1_2),
+            "1.3" => Ok(Self::V1_3),
+            _ => Err(format!(
+                "Unknown TLS version '{s}' (expected 1.0, 1.1, 1.2 or 1.3)"
+            )),
+        }
+    }
+}
+
+/// `[tls]` configuration section: defaults for URLs that do not say otherwise
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct TlsConfig {
+    /// Mode for URLs without `sslmode`; empty keeps each backend's own default
+    pub mode: String,
+    /// CA bundle (PEM) servers are verified against; empty uses the built-in roots
+    pub root_cert: String,
+    /// Client certificate (PEM) for mutual TLS
+    pub client_cert: String,
+    /// Private key (PEM) of `client_cert`; empty reads it from `client_cert`
+    pub client_key: String,
+    /// Check the server name in verify-full mode
+    pub verify_hostname: bool,
+    /// Lowest accepted protocol version, e.g. `1.2`; empty leaves it to the driver
+    pub min_version: String,
+    /// Warn when connecting with a client certificate that expires within
+    /// this many days; 0 never warns
+    pub cert_expiry_warn_days: u32,
+}
+
+impl Default for TlsConfig {
+    fn default() -> Self {
+        Self {
+            mode: String::new(),
+            root_cert: String::new(),
+            client_cert: String::new(),
+            client_key: String::new(),
+            verify_hostname: true,
+            min_version: String::new(),
+            cert_expiry_warn_days: 14,
+        }
+    }
+}
+
+/// TLS settings of one connection, URL parameters over `[tls]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TlsSettings {
+    pub mode: Option<TlsMode>,
+    pub root_cert: Option<PathBuf>,
+    pub client_cert: Option<PathBuf>,
+    pub client_key: Option<PathBuf>,
+    pub verify_hostname: bool,
+    pub min_version: Option<TlsVersion>,
+}
+
+pub(crate) fn expand_path(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+impl TlsSettings {
+    /// Settings from connection options, with `defaults` filling the gaps
+    pub fn resolve(
+        options: &HashMap<String, String>,
+        defaults: &TlsConfig,
+    ) -> Result<Self, String> {
+        let option = |keys: &[&str]| {
+            options
+                .iter()
+                .find(|(key, _)| keys.iter().any(|k| k.eq_ignore_ascii_case(key)))
+                .map(|(_, value)| value.trim().to_string())
+        };
+        let or_default = |keys: &[&str], default: &str| {
+            option(keys)
+                .or_else(|| (!default.trim().is_empty()).then(|| default.trim().to_string()))
+                .filter(|value| !value.is_empty())
+        };
+
+        let verify_hostname = match option(VERIFY_HOSTNAME_KEYS) {
+            Some(value) => match value.to_ascii_lowercase().as_str() {
+                "true" | "1" | "yes" | "on" => true,
+                "false" | "0" | "no" | "off" => false,
+                _ => {
+                    return Err(format!(
+                        "verify_hostname must be true or false, got '{value}'"
+                    ));
+                }
+            },
+            None => defaults.verify_hostname,
+        };
+
+        Ok(Self {
+            mode: or_default(MODE_KEYS, &defaults.mode)
+                .map(|mode| mode.parse())
+                .transpose()?,
+            root_cert: or_default(ROOT_CERT_KEYS, &defaults.root_cert).map(|p| expand_path(&p)),
+            client_cert: or_default(CLIENT_CERT_KEYS, &defaults.client_cert)
+                .map(|p| expand_path(&p)),
+            client_key: or_default(CLIENT_KEY_KEYS, &defaults.client_key).map(|p| expand_path(&p)),
+            verify_hostname,
+            min_version: or_default(MIN_VERSION_KEYS, &defaults.min_version)
+                .map(|version| version.parse())
+                .transpose()?,
+        })
+    }
+
+    /// Settings for a connection, with `[tls]` from the user's config
+    pub fn for_connection(
+        connection_info: &crate::database::ConnectionInfo,
+    ) -> Result<Self, String> {
+        // Both rustls backends end up compiled in, so drivers that build their
+        // config with `ClientConfig::builder()` (sqlx for verify-ca and client
+        // certificates) need a process default
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        let config = crate::config::Config::load();
+        Self::resolve(&connection_info.options, &config.tls)
+    }
+
+    /// The mode to apply, `default` when none is set; verify-full drops to
+    /// verify-ca when host name checks are off
+    pub fn mode_or(&self, default: TlsMode) -> TlsMode {
+        match self.mode.unwrap_or(default) {
+            TlsMode::VerifyFull if !self.verify_hostname => TlsMode::VerifyCa,
+            mode => mode,
+        }
+    }
+
+    /// The mode for a connection encrypted either way (`https`, `ssl=true`):
+    /// verify-full unless the settings ask for less
+    pub fn encrypted_mode(&self) -> TlsMode {
+        match self.mode {
+            Some(mode) if mode.requires_tls() => self.mode_or(mode),
+            _ if self.verify_hostname => TlsMode::VerifyFull,
+            _ => TlsMode::VerifyCa,
+        }
+    }
+
+    /// Whether anything beyond the mode was asked for
+    pub fn customized(&self) -> bool {
+        self.root_cert.is_some()
+            || self.client_cert.is_some()
+            || self.client_key.is_some()
+            || !self.verify_hostname
+            || self.min_version.is_some()
+    }
+
+    /// Fail when the negotiated protocol (`TLSv1.2`, or `None` for a plain
+    /// connection) is below `min_version`
+    pub fn check_negotiated(&self, negotiated: Option<&str>) -> Result<(), String> {
+        let Some(min) = self.min_version else {
+            return Ok(());
+        };
+        match negotiated.filter(|v| !v.is_empty()) {
+            None => Err(format!(
+                "{min} or newer is required but the connection is not encrypted"
+            )),
+            Some(version) => match version.parse::<TlsVersion>() {
+                Ok(version) if version < min => Err(format!(
+                    "Server negotiated {version}, below the required {min}"
+                )),
+                _ => Ok(()),
+            },
+        }
+    }
+
+    /// Error for a setting `backend` cannot apply
+    pub fn unsupported(backend: &str, setting: &str) -> String {
+        format!("{backend} connections do not support {setting}")
+    }
+
+    /// Client certificate and key as one PEM blob, for drivers that take an identity
+    pub fn pem_identity(&self) -> Result<Option<Vec<u8>>, String> {
+        let Some(cert) = &self.client_cert else {
+            return match &self.client_key {
+                Some(_) => Err("sslkey needs a client certificate (sslcert)".to_string()),
+                None => Ok(None),
+            };
+        };
+        let mut pem = read_file(cert)?;
+        if let Some(key) = self.client_key.as_ref().filter(|key| *key != cert) {
+            pem.push(b'\n');
+            pem.extend(read_file(key)?);
+        }
+        Ok(Some(pem))
+    }
+
+    /// Trust anchors: `root_cert` when set, the bundled web PKI roots otherwise
+    pub fn root_store(&self) -> Result<RootCertStore, String> {
+        let mut roots = RootCertStore::empty();
+        match &self.root_cert {
+            Some(path) => {
+                for cert in load_certs(path)? {
+                    roots.add(cert).map_err(|e| {
+                        format!("Invalid CA certificate in {}: {e}", path.display())
+                    })?;
+                }
+            }
+            None => roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned()),
+        }
+        Ok(roots)
+    }
+
+    /// rustls client configuration for `mode`
+    pub fn client_config(&self, mode: TlsMode) -> Result<rustls::ClientConfig, String> {
+        let provider = provider();
+        let versions: &[&rustls::SupportedProtocolVersion] =
+            if self.min_version == Some(TlsVersion::V1_3) {
+                &[&rustls::version::TLS13]
+            } else {
+                &[&rustls::version::TLS13, &rustls::version::TLS12]
+            };
+        let verifier: Arc<dyn ServerCertVerifier> = match mode {
+            TlsMode::VerifyCa | TlsMode::VerifyFull => {
+                let webpki = WebPkiServerVerifier::builder_with_provider(
+                    Arc::new(self.root_store()?),
+                    provider.clone(),
+                )
+                .build()
+                .map_err(|e| format!("Cannot build the certificate verifier: {e}"))?;
+                if mode == TlsMode::VerifyCa {
+                    Arc::new(SkipHostname(webpki))
+                } else {
+                    webpki
+                }
+            }
+            _ => Arc::new(AcceptAnyCertificate(provider.clone())),
+        };
+        let builder = rustls::ClientConfig::builder_with_provider(provider)
+            .with_protocol_versions(versions)
+            .map_err(|e| format!("Unsupported TLS versions: {e}"))?
+            .dangerous()
+            .with_custom_certificate_verifier(verifier);
+
+        match (&self.client_cert, &self.client_key) {
+            (Some(cert), key) => {
+                let chain = load_certs(cert)?;
+                let key_path = key.as_ref().unwrap_or(cert);
+                let key = PrivateKeyDer::from_pem_file(key_path).map_err(|e| {
+                    format!("Cannot read the private key {}: {e}", key_path.display())
+                })?;
+                builder
+                    .with_client_auth_cert(chain, key)
+                    .map_err(|e| format!("Invalid client certificate: {e}"))
+            }
+            (None, Some(_)) => Err("sslkey needs a client certificate (sslcert)".to_string()),
+            (None, None) => Ok(builder.with_no_client_auth()),
+        }
+    }
+}
+
+/// ring, as for every other rustls user in the build
+pub(crate) fn provider() -> Arc<CryptoProvider> {
+    Arc::new(rustls::crypto::ring::default_provider())
+}
+
+fn read_file(path: &PathBuf) -> Result<Vec<u8>, String> {
+    std::fs::read(path).map_err(|e| format!("Cannot read {}: {e}", path.display()))
+}
+
+pub(crate) fn load_certs(path: &PathBuf) -> Result<Vec<CertificateDer<'static>>, String> {
+    let certs = CertificateDer::pem_file_iter(path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| format!("Cannot read certificates from {}: {e}", path.display()))?;
+    if certs.is_empty() {
+        return Err(format!("No PEM certificate found in {}", path.display()));
+    }
+    Ok(certs)
+}
+
+/// Verifier for `require`: encryption without authentication
+#[derive(Debug)]
+pub(crate) struct AcceptAnyCertificate(pub(crate) Arc<CryptoProvider>);
+
+impl ServerCertVerifier for AcceptAnyCertificate {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+/// Verifier for `verify-ca`: the full chain check, minus the host name
+#[derive(Debug)]
+struct SkipHostname(Arc<WebPkiServerVerifier>);
+
+impl ServerCertVerifier for SkipHostname {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        match self
+            .0
+            .verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now)
+        {
+            Err(rustls::Error::InvalidCertificate(
+                CertificateError::NotValidForName | CertificateError::NotValidForNameContext { .. },
+            )) => Ok(ServerCertVerified::assertion()),
+            result => result,
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.0.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.0.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.supported_verify_schemes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_resolve_prefers_url_over_config() {
+        let defaults = TlsConfig {
+            mode: "verify-full".to_string(),
+            root_cert: "/etc/ssl/corp-ca.pem".to_string(),
+            min_version: "1.2".to_string(),
+            ..TlsConfig::default()
+        };
+        let settings = TlsSettings::resolve(
+            &options(&[("ssl-mode", "VERIFY_CA"), ("sslcert", "/tmp/client.pem")]),
+            &defaults,
+        )
+        .unwrap();
+        assert_eq!(settings.mode, Some(TlsMode::VerifyCa));
+        assert_eq!(
+            settings.root_cert,
+            Some(PathBuf::from("/etc/ssl/corp-ca.pem"))
+        );
+        assert_eq!(settings.client_cert, Some(PathBuf::from("/tmp/client.pem")));
+        assert_eq!(settings.min_version, Some(TlsVersion::V1_2));
+
+        let settings = TlsSettings::resolve(
+            &options(&[("verify_hostname", "false")]),
+            &TlsConfig::default(),
+        )
+        .unwrap();
+        assert_eq!(settings.mode_or(TlsMode::VerifyFull), TlsMode::VerifyCa);
+        assert_eq!(settings.mode_or(TlsMode::Require), TlsMode::Require);
+        assert!(settings.customized());
+        assert!(
+            !TlsSettings::resolve(&options(&[]), &TlsConfig::default())
+                .unwrap()
+                .customized()
+        );
+
+        assert!(TlsSettings::resolve(&options(&[("sslmode", "strict")]), &defaults).is_err());
+        assert!(is_tls_option("SSLROOTCERT"));
+        assert!(!is_tls_option("ssl"));
+    }
+
+    #[test]
+    fn test_check_negotiated_version() {
+        let settings = TlsSettings::resolve(
+            &options(&[("ssl_min_protocol_version", "TLSv1.3")]),
+            &TlsConfig::default(),
+        )
+        .unwrap();
+        assert!(settings.check_negotiated(Some("TLSv1.3")).is_ok());
+        assert!(settings.check_negotiated(Some("TLSv1.2")).is_err());
+        assert!(settings.check_negotiated(None).is_err());
+        assert_eq!("tls1_1".parse::<TlsVersion>(), Ok(TlsVersion::V1_1));
+        assert_eq!("TLSv1".parse::<TlsVersion>(), Ok(TlsVersion::V1_0));
+    }
+}
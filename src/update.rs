@@ -138,7 +138,7 @@ fn repo_slug(repo_url: &str) -> &str {
 
 async fn fetch_latest_version(slug: &str) -> Result<String, String> {
     let url = format!("https://api.github.com/repos/{slug}/releases/latest");
-    let client = reqwest::Client::builder()
+    let client = crate::network::client_builder()
         .timeout(std::time::Duration::from_secs(10))
         .build()
         .map_err(|e| e.to_string())?;
@@ -1,29 +1,38 @@
 use std::collections::{HashMap, HashSet};
 use std::env;
+use std::fmt;
 use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::{LazyLock, Mutex, RwLock};
+use std::time::{Duration, Instant};
 
 use futures_util::stream::{FuturesUnordered, StreamExt};
 use regex::Regex;
 use reqwest::header::HeaderMap;
-use serde::{Deserialize, Deserializer};
+use serde::{Deserialize, Deserializer, Serialize};
 use thiserror::Error;
 use url::Url;
 
 use tracing::debug;
 
 // Parse a vault:// URL and extract vault parameters
-// Format: vault://<role_name>@<mount_path:database>/<vault_db_name>
+// Format: vault://<role_name>@<mount_path:database>/<vault_db_name>[?auth=...]
 // All components are optional:
 // - If role_name is not specified, user will be prompted to select one
 // - If mount_path is not specified, defaults to "database"
 // - If vault_db_name is not specified, user will be prompted to select one
+// Query parameters select the auth method (see VaultAuthOptions::resolve)
 pub fn parse_vault_url(url_str: &str) -> Option<(Option<String>, String, Option<String>)> {
     if !url_str.starts_with("vault://") {
         return None;
     }
 
-    // Remove the protocol prefix
+    // Remove the protocol prefix and the auth query parameters
     let url_without_prefix = &url_str["vault://".len()..];
+    let url_without_prefix = url_without_prefix
+        .split_once('?')
+        .map_or(url_without_prefix, |(path, _)| path);
 
     // Extract role_name and mount_path from the user/host part
     let (user_host_part, db_part) = match url_without_prefix.find('/') {
@@ -74,6 +83,8 @@ pub fn parse_vault_url(url_str: &str) -> Option<(Option<String>, String, Option<
 pub enum VaultError {
     #[error("Vault address not set (explicit override or VAULT_ADDR environment variable)")]
     AddressError,
+    #[error("Vault login failed: {0}")]
+    AuthError(String),
     #[error(
         "Vault token not found. Set VAULT_TOKEN environment variable or place token in ~/.vault-token"
     )]
@@ -149,11 +160,6 @@ pub struct VaultDynamicCredentialsData {
     pub password: String,
 }
 
-#[derive(Deserialize, Debug)]
-struct VaultListResponse {
-    pub data: VaultListData,
-}
-
 #[derive(Deserialize, Debug)]
 struct VaultListData {
     pub keys: Vec<String>,
@@ -162,6 +168,13 @@ struct VaultListData {
 #[derive(Deserialize, Debug)]
 struct VaultReadResponse<T> {
     pub data: T,
+    // Set on responses that create a lease (dynamic credentials)
+    #[serde(default)]
+    pub lease_id: String,
+    #[serde(default)]
+    pub lease_duration: u64,
+    #[serde(default)]
+    pub renewable: bool,
 }
 
 #[derive(Deserialize, Debug)]
@@ -175,6 +188,329 @@ pub struct VaultPathCapabilities {
     pub capabilities: Vec<String>,
 }
 
+#[derive(Deserialize, Debug)]
+struct VaultLoginResponse {
+    pub auth: VaultLoginAuth,
+}
+
+#[derive(Deserialize, Debug)]
+struct VaultLoginAuth {
+    pub client_token: String,
+    #[serde(default)]
+    pub lease_duration: u64,
+}
+
+/// How dbcrust obtains the token used for Vault API calls
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VaultAuthMethod {
+    /// VAULT_TOKEN or ~/.vault-token
+    #[default]
+    Token,
+    /// Kubernetes service-account JWT exchanged at auth/<mount>/login
+    Kubernetes,
+    /// AppRole role_id + secret_id exchanged at auth/<mount>/login
+    AppRole,
+}
+
+impl VaultAuthMethod {
+    fn default_mount(self) -> &'static str {
+        match self {
+            VaultAuthMethod::Token => "token",
+            VaultAuthMethod::Kubernetes => "kubernetes",
+            VaultAuthMethod::AppRole => "approle",
+        }
+    }
+}
+
+impl fmt::Display for VaultAuthMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.default_mount())
+    }
+}
+
+impl FromStr for VaultAuthMethod {
+    type Err = VaultError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "" | "token" => Ok(VaultAuthMethod::Token),
+            "k8s" | "kubernetes" => Ok(VaultAuthMethod::Kubernetes),
+            "approle" => Ok(VaultAuthMethod::AppRole),
+            other => Err(VaultError::AuthError(format!(
+                "unknown auth method '{other}' (expected token, kubernetes or approle)"
+            ))),
+        }
+    }
+}
+
+/// `[vault]` configuration section: auth method and namespace for vault:// URLs
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct VaultConfig {
+    /// token, kubernetes or approle
+    pub auth_method: String,
+    /// Enterprise namespace; empty falls back to VAULT_NAMESPACE
+    pub namespace: String,
+    /// Mount path of the auth method; empty uses "kubernetes" / "approle"
+    pub auth_mount: String,
+    /// Vault role to log in as with Kubernetes auth
+    pub auth_role: String,
+    /// Service-account token read for Kubernetes auth
+    pub kubernetes_token_path: String,
+    /// AppRole role_id; empty falls back to VAULT_ROLE_ID
+    pub role_id: String,
+    /// File holding the AppRole secret_id; empty falls back to VAULT_SECRET_ID
+    pub secret_id_file: String,
+}
+
+impl Default for VaultConfig {
+    fn default() -> Self {
+        Self {
+            auth_method: VaultAuthMethod::Token.to_string(),
+            namespace: String::new(),
+            auth_mount: String::new(),
+            auth_role: String::new(),
+            kubernetes_token_path: "/var/run/secrets/kubernetes.io/serviceaccount/token"
+                .to_string(),
+            role_id: String::new(),
+            secret_id_file: String::new(),
+        }
+    }
+}
+
+/// Auth settings for the Vault calls of one connection, resolved from the
+/// vault:// URL, `[vault]` and the standard VAULT_* environment variables
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct VaultAuthOptions {
+    pub method: VaultAuthMethod,
+    pub namespace: Option<String>,
+    pub auth_mount: String,
+    pub auth_role: Option<String>,
+    pub kubernetes_token_path: PathBuf,
+    pub role_id: Option<String>,
+    pub secret_id_file: Option<PathBuf>,
+}
+
+impl VaultAuthOptions {
+    /// URL query parameters (`auth`, `namespace`, `auth_mount`, `auth_role`)
+    /// win over `[vault]`, which wins over the environment
+    pub fn resolve(url_str: &str, config: &VaultConfig) -> Result<Self, VaultError> {
+        let params: HashMap<String, String> = url_str
+            .split_once('?')
+            .map(|(_, query)| {
+                url::form_urlencoded::parse(query.as_bytes())
+                    .into_owned()
+                    .collect()
+            })
+            .unwrap_or_default();
+        let pick = |param: &str, configured: &str| -> Option<String> {
+            params
+                .get(param)
+                .map(String::as_str)
+                .or(Some(configured))
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+        let non_empty_env = |name: &str| {
+            env::var(name)
+                .ok()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+
+        let method: VaultAuthMethod = pick("auth", &config.auth_method)
+            .unwrap_or_default()
+            .parse()?;
+        Ok(VaultAuthOptions {
+            method,
+            namespace: pick("namespace", &config.namespace)
+                .or_else(|| non_empty_env("VAULT_NAMESPACE"))
+                .map(|namespace| namespace.trim_matches('/').to_string()),
+            auth_mount: pick("auth_mount", &config.auth_mount)
+                .map(|mount| mount.trim_matches('/').to_string())
+                .unwrap_or_else(|| method.default_mount().to_string()),
+            auth_role: pick("auth_role", &config.auth_role),
+            kubernetes_token_path: PathBuf::from(&config.kubernetes_token_path),
+            role_id: pick("role_id", &config.role_id).or_else(|| non_empty_env("VAULT_ROLE_ID")),
+            secret_id_file: pick("secret_id_file", &config.secret_id_file).map(PathBuf::from),
+        })
+    }
+
+    /// Short description for the connection banner, empty for plain token auth
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if self.method != VaultAuthMethod::Token {
+            parts.push(format!("{} auth", self.method));
+        }
+        if let Some(namespace) = &self.namespace {
+            parts.push(format!("namespace {namespace}"));
+        }
+        parts.join(", ")
+    }
+
+    /// Mount path as used in the credential cache key: the same mount in two
+    /// namespaces holds different credentials
+    fn cache_mount(&self, mount_path: &str) -> String {
+        match &self.namespace {
+            Some(namespace) => format!("{namespace}/{mount_path}"),
+            None => mount_path.to_string(),
+        }
+    }
+
+    /// Login token cache key: one token per address, namespace, method and identity
+    fn token_cache_key(&self, vault_addr: &str) -> String {
+        let identity = match self.method {
+            VaultAuthMethod::Token => "",
+            VaultAuthMethod::Kubernetes => self.auth_role.as_deref().unwrap_or_default(),
+            VaultAuthMethod::AppRole => self.role_id.as_deref().unwrap_or_default(),
+        };
+        format!(
+            "{vault_addr}|{}|{}|{}|{identity}",
+            self.namespace.as_deref().unwrap_or_default(),
+            self.method,
+            self.auth_mount
+        )
+    }
+
+    fn login_payload(&self) -> Result<serde_json::Value, VaultError> {
+        match self.method {
+            VaultAuthMethod::Token => Err(VaultError::AuthError(
+                "token auth has no login step".to_string(),
+            )),
+            VaultAuthMethod::Kubernetes => {
+                let role = self.auth_role.as_deref().ok_or_else(|| {
+                    VaultError::AuthError(
+                        "Kubernetes auth needs a Vault role: add ?auth_role=<role> to the URL or set auth_role in [vault]".to_string(),
+                    )
+                })?;
+                let jwt = fs::read_to_string(&self.kubernetes_token_path).map_err(|e| {
+                    VaultError::AuthError(format!(
+                        "cannot read service-account token {}: {e}",
+                        self.kubernetes_token_path.display()
+                    ))
+                })?;
+                Ok(serde_json::json!({ "role": role, "jwt": jwt.trim() }))
+            }
+            VaultAuthMethod::AppRole => {
+                let role_id = self.role_id.as_deref().ok_or_else(|| {
+                    VaultError::AuthError(
+                        "AppRole auth needs a role_id: set VAULT_ROLE_ID or role_id in [vault]"
+                            .to_string(),
+                    )
+                })?;
+                let secret_id = match &self.secret_id_file {
+                    Some(path) => fs::read_to_string(path)
+                        .map(|secret| secret.trim().to_string())
+                        .map_err(|e| {
+                            VaultError::AuthError(format!(
+                                "cannot read secret_id file {}: {e}",
+                                path.display()
+                            ))
+                        })?,
+                    None => env::var("VAULT_SECRET_ID").unwrap_or_default(),
+                };
+                let mut payload = serde_json::json!({ "role_id": role_id });
+                // Roles created with bind_secret_id=false log in with the role_id alone
+                if !secret_id.trim().is_empty() {
+                    payload["secret_id"] = secret_id.trim().into();
+                }
+                Ok(payload)
+            }
+        }
+    }
+}
+
+/// Auth settings of the current vault:// connection (same pattern as the
+/// global display settings: the Vault helpers below take no config)
+static VAULT_AUTH: RwLock<Option<VaultAuthOptions>> = RwLock::new(None);
+
+pub fn set_vault_auth_options(options: VaultAuthOptions) {
+    if let Ok(mut current) = VAULT_AUTH.write() {
+        *current = Some(options);
+    }
+}
+
+fn vault_auth_options() -> VaultAuthOptions {
+    VAULT_AUTH
+        .read()
+        .ok()
+        .and_then(|options| options.clone())
+        .unwrap_or_default()
+}
+
+/// Tokens from Kubernetes/AppRole logins, reused until close to expiry so the
+/// handful of API calls behind one connection log in once
+static LOGIN_TOKENS: LazyLock<Mutex<HashMap<String, (String, Instant)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// A cached login token must stay valid at least this long to be reused
+const LOGIN_TOKEN_MIN_TTL: Duration = Duration::from_secs(30);
+
+fn cached_login_token(key: &str) -> Option<String> {
+    let tokens = LOGIN_TOKENS.lock().ok()?;
+    let (token, expires_at) = tokens.get(key)?;
+    (expires_at.saturating_duration_since(Instant::now()) >= LOGIN_TOKEN_MIN_TTL)
+        .then(|| token.clone())
+}
+
+fn cache_login_token(key: String, token: String, lease_duration: u64) {
+    // Root-like tokens report no lease; treat them as valid for an hour
+    let ttl = Duration::from_secs(if lease_duration == 0 {
+        3600
+    } else {
+        lease_duration
+    });
+    if let Ok(mut tokens) = LOGIN_TOKENS.lock() {
+        tokens.insert(key, (token, Instant::now() + ttl));
+    }
+}
+
+/// Token for API calls under `options`: the local token, or a (cached) login
+async fn resolve_vault_token(
+    vault_addr: &str,
+    options: &VaultAuthOptions,
+) -> Result<String, VaultError> {
+    if options.method == VaultAuthMethod::Token {
+        return get_vault_token();
+    }
+
+    let key = options.token_cache_key(vault_addr);
+    if let Some(token) = cached_login_token(&key) {
+        debug!("Reusing cached Vault {} login token", options.method);
+        return Ok(token);
+    }
+
+    let mut request = crate::network::client_builder()
+        .build()?
+        .post(format!("{vault_addr}/v1/auth/{}/login", options.auth_mount))
+        .json(&options.login_payload()?);
+    if let Some(namespace) = &options.namespace {
+        request = request.header("X-Vault-Namespace", namespace);
+    }
+    let response = request.send().await?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await?;
+        return Err(VaultError::AuthError(format!(
+            "{} login at auth/{} ({status}): {error_text}",
+            options.method, options.auth_mount
+        )));
+    }
+
+    let login: VaultLoginResponse = response.json().await?;
+    debug!(
+        "Logged in to Vault with {} auth (token TTL {}s)",
+        options.method, login.auth.lease_duration
+    );
+    cache_login_token(
+        key,
+        login.auth.client_token.clone(),
+        login.auth.lease_duration,
+    );
+    Ok(login.auth.client_token)
+}
+
 #[derive(Debug, Clone)]
 pub struct DetectedVaultAddr {
     pub addr: String,
@@ -231,15 +567,22 @@ async fn create_vault_client_with_addr(
     vault_addr_override: Option<&str>,
 ) -> Result<(reqwest::Client, String), VaultError> {
     let vault_addr = get_vault_addr_with_override(vault_addr_override)?;
-    let vault_token = get_vault_token()?;
+    let options = vault_auth_options();
+    let vault_token = resolve_vault_token(&vault_addr, &options).await?;
 
     let mut headers = HeaderMap::new();
     let header_value = vault_token
         .parse()
         .map_err(|e| VaultError::ApiError(format!("Invalid token header value: {e}")))?;
     headers.insert("X-Vault-Token", header_value);
+    if let Some(namespace) = &options.namespace {
+        let header_value = namespace
+            .parse()
+            .map_err(|e| VaultError::ApiError(format!("Invalid namespace header value: {e}")))?;
+        headers.insert("X-Vault-Namespace", header_value);
+    }
 
-    let client = reqwest::Client::builder()
+    let client = crate::network::client_builder()
         .default_headers(headers)
         .build()?;
 
@@ -261,29 +604,68 @@ pub async fn list_vault_databases_with_addr(
     let (client, vault_addr) = create_vault_client_with_addr(vault_addr_override).await?;
     let list_path = format!("{vault_addr}/v1/{mount_path}/config?list=true");
 
-    let response = client.get(&list_path).send().await?;
+    let Some(data) = fetch_vault_data(&client, &list_path).await? else {
+        return Err(VaultError::ApiError(format!(
+            "Path {mount_path}/config not found (404). Ensure DB secrets engine is mounted at '{mount_path}' and that your token can list it."
+        )));
+    };
+    let list_data: VaultListData = serde_json::from_value(data)?;
+    Ok(list_data.keys)
+}
+
+/// Roles defined under `<mount>/roles`; empty when there are none
+async fn list_roles(
+    client: &reqwest::Client,
+    vault_addr: &str,
+    mount_path: &str,
+) -> Result<Vec<String>, VaultError> {
+    let list_path = format!("{vault_addr}/v1/{mount_path}/roles?list=true");
+    match fetch_vault_data(client, &list_path).await? {
+        Some(data) => Ok(serde_json::from_value::<VaultListData>(data)?.keys),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Listing and config responses, reused for a minute so connecting (which
+/// filters databases, lists roles and then reads the config again) asks once
+static VAULT_READS: LazyLock<Mutex<HashMap<String, (serde_json::Value, Instant)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+const VAULT_READ_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// `data` of the Vault response at `url`, or `None` on 404
+async fn fetch_vault_data(
+    client: &reqwest::Client,
+    url: &str,
+) -> Result<Option<serde_json::Value>, VaultError> {
+    if let Ok(reads) = VAULT_READS.lock()
+        && let Some((data, stored)) = reads.get(url)
+        && stored.elapsed() < VAULT_READ_CACHE_TTL
+    {
+        return Ok(Some(data.clone()));
+    }
+
+    let response = client.get(url).send().await?;
 
     if !response.status().is_success() {
         let status = response.status();
-        let error_text = response.text().await?;
-
         if status.as_u16() == 404 {
-            return Err(VaultError::ApiError(format!(
-                "Path {mount_path}/config not found (404). Ensure DB secrets engine is mounted at '{mount_path}' and that your token can list it."
-            )));
-        } else if status.as_u16() == 403 {
-            return Err(VaultError::ApiError(format!(
-                "Vault API error (403 Forbidden): {error_text}"
-            )));
+            return Ok(None);
         }
-
+        let error_text = response.text().await?;
         return Err(VaultError::ApiError(format!(
             "Vault API error ({status}): {error_text}"
         )));
     }
 
-    let list_response: VaultListResponse = response.json().await?;
-    Ok(list_response.data.keys)
+    let read_response: VaultReadResponse<serde_json::Value> = response.json().await?;
+    if let Ok(mut reads) = VAULT_READS.lock() {
+        reads.insert(
+            url.to_string(),
+            (read_response.data.clone(), Instant::now()),
+        );
+    }
+    Ok(Some(read_response.data))
 }
 
 async fn fetch_vault_database_config(
@@ -294,24 +676,81 @@ async fn fetch_vault_database_config(
 ) -> Result<VaultDbConfigData, VaultError> {
     let path = format!("{vault_addr}/v1/{mount_path}/config/{db_config_name}");
 
-    let response = client.get(&path).send().await?;
+    let Some(data) = fetch_vault_data(client, &path).await? else {
+        return Err(VaultError::DbConfigNotFound(
+            db_config_name.to_string(),
+            mount_path.to_string(),
+        ));
+    };
+    Ok(serde_json::from_value(data)?)
+}
 
-    if !response.status().is_success() {
-        let status = response.status();
-        if status.as_u16() == 404 {
-            return Err(VaultError::DbConfigNotFound(
-                db_config_name.to_string(),
-                mount_path.to_string(),
-            ));
+/// `allowed_roles` of a database config with glob entries (`*`, `app-*`)
+/// replaced by the matching roles of the mount, keeping the listed order
+fn expand_allowed_roles(allowed_roles: Vec<String>, mount_roles: &[String]) -> Vec<String> {
+    let mut roles: Vec<String> = Vec::new();
+    for allowed in allowed_roles {
+        let matches: Vec<String> = if allowed.contains('*') || allowed.contains('+') {
+            mount_roles
+                .iter()
+                .filter(|role| glob_matches(&allowed, role))
+                .cloned()
+                .collect()
+        } else {
+            vec![allowed]
+        };
+        for role in matches {
+            if !roles.contains(&role) {
+                roles.push(role);
+            }
         }
-        let error_text = response.text().await?;
-        return Err(VaultError::ApiError(format!(
-            "Vault API error ({status}): {error_text}"
-        )));
+    }
+    roles
+}
+
+/// Roles that can issue credentials for `db_config_name`: its allowed roles,
+/// with globs expanded against `<mount>/roles` and roles bound to another
+/// config dropped when the token can read them
+async fn config_roles(
+    client: &reqwest::Client,
+    vault_addr: &str,
+    mount_path: &str,
+    db_config_name: &str,
+    allowed_roles: Vec<String>,
+) -> Vec<String> {
+    if !allowed_roles
+        .iter()
+        .any(|role| role.contains('*') || role.contains('+'))
+    {
+        return allowed_roles;
     }
 
-    let config_response: VaultReadResponse<VaultDbConfigData> = response.json().await?;
-    Ok(config_response.data)
+    let mount_roles = list_roles(client, vault_addr, mount_path)
+        .await
+        .unwrap_or_else(|error| {
+            debug!(
+                "Could not list roles of Vault mount {}: {}",
+                mount_path, error
+            );
+            Vec::new()
+        });
+    let candidates = expand_allowed_roles(allowed_roles, &mount_roles);
+
+    let mut kept = Vec::new();
+    for role_name in candidates {
+        let path = format!("{vault_addr}/v1/{mount_path}/roles/{role_name}");
+        let bound_to = match fetch_vault_data(client, &path).await {
+            Ok(Some(data)) => data
+                .get("db_name")
+                .and_then(|name| name.as_str())
+                .map(str::to_string),
+            _ => None,
+        };
+        if bound_to.is_none_or(|name| name == db_config_name) {
+            kept.push(role_name);
+        }
+    }
+    kept
 }
 
 fn role_creds_path(mount_path: &str, role_name: &str) -> String {
@@ -463,8 +902,27 @@ pub async fn get_dynamic_credentials_with_caching_with_addr(
     config: &mut crate::config::Config,
     vault_addr_override: Option<&str>,
 ) -> Result<(VaultDynamicCredentialsData, VaultLeaseInfo), VaultError> {
+    let cache_mount = vault_auth_options().cache_mount(mount_path);
+
+    // Held until the new credentials are cached, so instances starting
+    // together share one set instead of each asking Vault for its own
+    let _guard = if config.vault_credential_cache_enabled {
+        match crate::vault_lease::CacheLock::exclusive().await {
+            Ok(guard) => {
+                config.reload_vault_credentials();
+                Some(guard)
+            }
+            Err(e) => {
+                debug!("Cannot lock the vault credential cache: {e}");
+                None
+            }
+        }
+    } else {
+        None
+    };
+
     if let Some(cached_creds) =
-        config.get_cached_vault_credentials(mount_path, db_config_name, role_name)
+        config.get_cached_vault_credentials(&cache_mount, db_config_name, role_name)
     {
         debug!(
             "Using cached vault credentials for {}/{}/{}",
@@ -482,6 +940,7 @@ pub async fn get_dynamic_credentials_with_caching_with_addr(
             renewable: cached_creds.renewable,
         };
 
+        hold_lease(config, &cache_mount, db_config_name, role_name);
         return Ok((credentials, lease_info));
     }
 
@@ -512,19 +971,29 @@ pub async fn get_dynamic_credentials_with_caching_with_addr(
 
     let full_response: VaultReadResponse<VaultDynamicCredentialsData> = response.json().await?;
 
-    let lease_duration = 3600;
-    let lease_id = format!(
-        "{}/creds/{}/{}",
-        mount_path,
-        role_name,
-        chrono::Utc::now().timestamp()
-    );
+    // Some mocks and proxies leave out the lease; fall back to a one-hour
+    // renewable one under a synthetic id that revocation will simply miss
+    let (lease_id, lease_duration, renewable) = if full_response.lease_id.is_empty() {
+        let lease_id = format!(
+            "{}/creds/{}/{}",
+            mount_path,
+            role_name,
+            chrono::Utc::now().timestamp()
+        );
+        (lease_id, 3600, true)
+    } else {
+        (
+            full_response.lease_id,
+            full_response.lease_duration,
+            full_response.renewable,
+        )
+    };
 
     let credentials = full_response.data;
     let lease_info = VaultLeaseInfo {
         lease_id: lease_id.clone(),
         lease_duration,
-        renewable: true,
+        renewable,
     };
 
     if config.vault_credential_cache_enabled {
@@ -539,13 +1008,14 @@ pub async fn get_dynamic_credentials_with_caching_with_addr(
             issue_time: now,
             expire_time,
             renewable: lease_info.renewable,
-            mount_path: mount_path.to_string(),
+            mount_path: cache_mount.clone(),
             database_name: db_config_name.to_string(),
             role_name: role_name.to_string(),
+            holders: Vec::new(),
         };
 
         if let Err(e) =
-            config.cache_vault_credentials(mount_path, db_config_name, role_name, cached_creds)
+            config.cache_vault_credentials(&cache_mount, db_config_name, role_name, cached_creds)
         {
             debug!("Failed to cache vault credentials: {e}");
         } else {
@@ -553,12 +1023,84 @@ pub async fn get_dynamic_credentials_with_caching_with_addr(
                 "Cached vault credentials for {}/{}/{}",
                 mount_path, db_config_name, role_name
             );
+            hold_lease(config, &cache_mount, db_config_name, role_name);
         }
     }
 
     Ok((credentials, lease_info))
 }
 
+/// Record this instance as using the cached lease; the caller holds the
+/// cache lock
+fn hold_lease(
+    config: &mut crate::config::Config,
+    cache_mount: &str,
+    db_config_name: &str,
+    role_name: &str,
+) {
+    let Some(pid) = crate::vault_lease::register() else {
+        debug!("Cannot register as a vault lease holder; the lease will not be revoked");
+        return;
+    };
+    if let Err(e) = config.add_vault_lease_holder(cache_mount, db_config_name, role_name, pid) {
+        debug!("Failed to record vault lease holder: {e}");
+    }
+}
+
+/// Let go of the cached leases this instance used, at exit. With
+/// `vault_revoke_on_exit`, leases no other running instance holds are
+/// revoked and dropped from the cache.
+pub async fn release_leases(config: &mut crate::config::Config) {
+    if !crate::vault_lease::is_registered() {
+        return;
+    }
+    let pid = std::process::id();
+    let released = match crate::vault_lease::CacheLock::exclusive().await {
+        Ok(_guard) => {
+            config.reload_vault_credentials();
+            config.release_vault_lease_holder(
+                pid,
+                config.vault_revoke_on_exit,
+                crate::vault_lease::is_alive,
+            )
+        }
+        Err(e) => {
+            debug!("Cannot lock the vault credential cache: {e}");
+            Vec::new()
+        }
+    };
+    crate::vault_lease::unregister();
+
+    for lease in released {
+        if let Err(e) = revoke_lease(&lease.lease_id).await {
+            eprintln!(
+                "Warning: could not revoke Vault lease for {}/{}/{}: {e}",
+                lease.mount_path, lease.database_name, lease.role_name
+            );
+        } else {
+            debug!("Revoked vault lease {}", lease.lease_id);
+        }
+    }
+}
+
+/// Revoke a lease through `sys/leases/revoke`
+pub async fn revoke_lease(lease_id: &str) -> Result<(), VaultError> {
+    let (client, vault_addr) = create_vault_client_with_addr(None).await?;
+    let response = client
+        .put(format!("{vault_addr}/v1/sys/leases/revoke"))
+        .json(&serde_json::json!({ "lease_id": lease_id }))
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await?;
+        return Err(VaultError::ApiError(format!(
+            "Vault API error ({status}): {error_text}"
+        )));
+    }
+    Ok(())
+}
+
 /// Lease information returned with dynamic credentials
 #[derive(Debug, Clone)]
 pub struct VaultLeaseInfo {
@@ -604,6 +1146,14 @@ pub async fn get_available_roles_for_user_with_addr(
         debug!("No roles defined for database {}", db_config_name);
         return Ok(Vec::new());
     };
+    let allowed_roles = config_roles(
+        &client,
+        &vault_addr,
+        mount_path,
+        db_config_name,
+        allowed_roles,
+    )
+    .await;
 
     let role_paths: Vec<String> = allowed_roles
         .iter()
@@ -673,7 +1223,14 @@ pub async fn filter_databases_with_available_roles_with_addr(
             }
         };
 
-        let allowed_roles = db_config.allowed_roles.unwrap_or_default();
+        let allowed_roles = config_roles(
+            &client,
+            &vault_addr,
+            mount_path,
+            &db_name,
+            db_config.allowed_roles.unwrap_or_default(),
+        )
+        .await;
         if allowed_roles.is_empty() {
             debug!("Database {} has no allowed roles configured", db_name);
             continue;
@@ -881,6 +1438,22 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_expand_allowed_roles() {
+        let mount_roles = ["app-reader", "app-writer", "billing-reader"].map(String::from);
+        let allowed = |roles: &[&str]| roles.iter().map(|r| r.to_string()).collect();
+
+        assert_eq!(
+            expand_allowed_roles(allowed(&["app-*", "admin"]), &mount_roles),
+            ["app-reader", "app-writer", "admin"]
+        );
+        assert_eq!(
+            expand_allowed_roles(allowed(&["*", "app-reader"]), &mount_roles),
+            ["app-reader", "app-writer", "billing-reader"]
+        );
+        assert!(expand_allowed_roles(allowed(&["ops-*"]), &mount_roles).is_empty());
+    }
+
     #[test]
     fn test_has_capabilities() {
         let user_caps = vec!["read".to_string(), "list".to_string()];
@@ -940,5 +1513,79 @@ mod tests {
         // Test minimal vault URL
         let result = parse_vault_url("vault://");
         assert_eq!(result, Some((None, "database".to_string(), None)));
+
+        // Auth parameters are not part of the database name
+        let result = parse_vault_url("vault://role@mount/database?auth=k8s");
+        assert_eq!(
+            result,
+            Some((
+                Some("role".to_string()),
+                "mount".to_string(),
+                Some("database".to_string())
+            ))
+        );
+    }
+
+    #[test]
+    fn test_vault_auth_options_resolve() {
+        let config = VaultConfig {
+            auth_method: "approle".to_string(),
+            namespace: "platform".to_string(),
+            role_id: "configured-role-id".to_string(),
+            ..Default::default()
+        };
+
+        let options = VaultAuthOptions::resolve("vault://ro@database/app", &config).unwrap();
+        assert_eq!(options.method, VaultAuthMethod::AppRole);
+        assert_eq!(options.auth_mount, "approle");
+        assert_eq!(options.namespace.as_deref(), Some("platform"));
+        assert_eq!(options.role_id.as_deref(), Some("configured-role-id"));
+
+        // URL parameters override [vault]
+        let options = VaultAuthOptions::resolve(
+            "vault://ro@database/app?auth=k8s&namespace=team-a/&auth_role=dbcrust&auth_mount=k8s-prod",
+            &config,
+        )
+        .unwrap();
+        assert_eq!(options.method, VaultAuthMethod::Kubernetes);
+        assert_eq!(options.auth_mount, "k8s-prod");
+        assert_eq!(options.auth_role.as_deref(), Some("dbcrust"));
+        assert_eq!(options.namespace.as_deref(), Some("team-a"));
+        assert_eq!(options.summary(), "kubernetes auth, namespace team-a");
+        assert_eq!(options.cache_mount("database"), "team-a/database");
+
+        assert!(VaultAuthOptions::resolve("vault://?auth=ldap", &config).is_err());
+    }
+
+    #[test]
+    fn test_login_token_cache_is_per_auth_method() {
+        let kubernetes = VaultAuthOptions {
+            method: VaultAuthMethod::Kubernetes,
+            auth_mount: "kubernetes".to_string(),
+            auth_role: Some("dbcrust".to_string()),
+            ..Default::default()
+        };
+        let approle = VaultAuthOptions {
+            method: VaultAuthMethod::AppRole,
+            auth_mount: "approle".to_string(),
+            role_id: Some("dbcrust".to_string()),
+            ..Default::default()
+        };
+        let addr = "https://vault.test:8200";
+        assert_ne!(
+            kubernetes.token_cache_key(addr),
+            approle.token_cache_key(addr)
+        );
+
+        cache_login_token(kubernetes.token_cache_key(addr), "s.k8s".to_string(), 600);
+        assert_eq!(
+            cached_login_token(&kubernetes.token_cache_key(addr)).as_deref(),
+            Some("s.k8s")
+        );
+        assert_eq!(cached_login_token(&approle.token_cache_key(addr)), None);
+
+        // Nearly expired tokens are not handed out
+        cache_login_token(approle.token_cache_key(addr), "s.old".to_string(), 5);
+        assert_eq!(cached_login_token(&approle.token_cache_key(addr)), None);
     }
 }
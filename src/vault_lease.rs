@@ -0,0 +1,115 @@
+//! Sharing cached Vault credentials between running dbcrust instances
+//!
+//! Every instance reads and writes `vault_credentials.enc` under an exclusive
+//! lock on `vault_credentials.lock`, held from the cache lookup to the write of
+//! freshly generated credentials, so a second instance started against the
+//! same role waits for the first and reuses its credentials instead of asking
+//! Vault for another set.
+//!
+//! Each cached lease lists the instances using it. An instance holds a lock on
+//! its own `vault_holders/<pid>.lock` file for as long as it runs, so a holder
+//! whose file can be locked by someone else has exited, even after a crash.
+//! With `vault_revoke_on_exit`, the last instance to let go of a lease
+//! revokes it.
+
+use crate::config::Config;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// Set once this process has its holder file locked
+static HOLDER: OnceLock<Option<u32>> = OnceLock::new();
+
+fn config_path(name: &str) -> io::Result<PathBuf> {
+    Config::get_config_directory()
+        .map(|dir| dir.join(name))
+        .map_err(|e| io::Error::other(e.to_string()))
+}
+
+fn holder_path(pid: u32) -> io::Result<PathBuf> {
+    Ok(config_path("vault_holders")?.join(format!("{pid}.lock")))
+}
+
+fn open_lock_file(path: &PathBuf) -> io::Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .read(true)
+        .write(true)
+        .open(path)
+}
+
+/// The lock on the credential cache file, held until dropped
+pub struct CacheLock {
+    /// Locked; closing it lets go of the lock
+    _file: File,
+}
+
+impl CacheLock {
+    /// Wait until no other instance holds the cache, then hold it. The other
+    /// instance may be waiting on Vault, so the wait runs on a blocking
+    /// thread rather than a runtime one.
+    pub async fn exclusive() -> io::Result<Self> {
+        let path = config_path("vault_credentials.lock")?;
+        tokio::task::spawn_blocking(move || {
+            let mut lock = fd_lock::RwLock::new(open_lock_file(&path)?);
+            // The file keeps the lock once the guard is gone, until it is closed
+            std::mem::forget(lock.write()?);
+            Ok(Self {
+                _file: lock.into_inner(),
+            })
+        })
+        .await
+        .map_err(io::Error::other)?
+    }
+}
+
+/// This instance's holder id (its pid), registering it on first use;
+/// `None` when its holder file cannot be locked
+pub fn register() -> Option<u32> {
+    *HOLDER.get_or_init(|| {
+        let pid = std::process::id();
+        let path = holder_path(pid).ok()?;
+        std::fs::create_dir_all(path.parent()?).ok()?;
+        let lock = Box::leak(Box::new(fd_lock::RwLock::new(open_lock_file(&path).ok()?)));
+        // Kept locked until the process exits and the file is closed
+        std::mem::forget(lock.try_write().ok()?);
+        Some(pid)
+    })
+}
+
+/// Whether this instance registered as a lease holder
+pub fn is_registered() -> bool {
+    HOLDER.get().is_some_and(Option::is_some)
+}
+
+/// Remove this instance's holder file, once it let go of its leases
+pub fn unregister() {
+    if let Some(Some(pid)) = HOLDER.get()
+        && let Ok(path) = holder_path(*pid)
+    {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// Whether the instance `pid` is still running; a stale holder file left by
+/// a crash is removed
+pub fn is_alive(pid: u32) -> bool {
+    if HOLDER.get().copied().flatten() == Some(pid) {
+        return true;
+    }
+    let Ok(path) = holder_path(pid) else {
+        return false;
+    };
+    let file = match OpenOptions::new().read(true).write(true).open(&path) {
+        Ok(file) => file,
+        Err(_) => return false,
+    };
+    let mut lock = fd_lock::RwLock::new(file);
+    let stale = lock.try_write().is_ok();
+    if stale {
+        let _ = std::fs::remove_file(&path);
+    }
+    !stale
+}
@@ -0,0 +1,232 @@
+//! Similarity search helpers for pgvector columns
+//!
+//! Builds the `ORDER BY <column> <op> <target> LIMIT k` queries behind the `\knn`
+//! command. The target can either be a vector literal (`[0.1, 0.2, ...]`) or a
+//! reference to an existing row (`id=42`), in which case that row's vector is used
+//! and the row itself is excluded from the results.
+
+use crate::database::{escape_sql_string, quote_sql_ident};
+use std::fmt;
+use std::str::FromStr;
+
+/// Default number of neighbours returned by `\knn`
+pub const DEFAULT_KNN_LIMIT: usize = 10;
+
+/// pgvector distance operators
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DistanceMetric {
+    /// Euclidean distance (`<->`)
+    #[default]
+    L2,
+    /// Cosine distance (`<=>`)
+    Cosine,
+    /// Negative inner product (`<#>`)
+    InnerProduct,
+    /// Taxicab distance (`<+>`, pgvector 0.7+)
+    L1,
+}
+
+impl DistanceMetric {
+    /// The pgvector operator implementing this metric
+    pub fn operator(&self) -> &'static str {
+        match self {
+            DistanceMetric::L2 => "<->",
+            DistanceMetric::Cosine => "<=>",
+            DistanceMetric::InnerProduct => "<#>",
+            DistanceMetric::L1 => "<+>",
+        }
+    }
+
+    pub fn all_metrics() -> Vec<&'static str> {
+        vec!["l2", "cosine", "ip", "l1"]
+    }
+}
+
+impl fmt::Display for DistanceMetric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DistanceMetric::L2 => write!(f, "l2"),
+            DistanceMetric::Cosine => write!(f, "cosine"),
+            DistanceMetric::InnerProduct => write!(f, "ip"),
+            DistanceMetric::L1 => write!(f, "l1"),
+        }
+    }
+}
+
+impl FromStr for DistanceMetric {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "l2" | "euclidean" | "<->" => Ok(DistanceMetric::L2),
+            "cosine" | "cos" | "<=>" => Ok(DistanceMetric::Cosine),
+            "ip" | "inner" | "inner_product" | "dot" | "<#>" => Ok(DistanceMetric::InnerProduct),
+            "l1" | "manhattan" | "taxicab" | "<+>" => Ok(DistanceMetric::L1),
+            _ => Err(format!(
+                "Invalid distance metric: '{}'. Valid metrics: {}",
+                s,
+                Self::all_metrics().join(", ")
+            )),
+        }
+    }
+}
+
+/// What the nearest neighbours are computed against
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KnnTarget {
+    /// A literal vector, normalized to pgvector text form (`[1,2,3]`)
+    Vector(String),
+    /// An existing row identified by `column=value`
+    Row { key: String, value: String },
+}
+
+impl KnnTarget {
+    /// Parse a target argument: either a `[...]` vector literal or `key=value`
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let input = input.trim();
+
+        if input.starts_with('[') {
+            let inner = input
+                .strip_prefix('[')
+                .and_then(|s| s.strip_suffix(']'))
+                .ok_or_else(|| format!("Unterminated vector literal: {input}"))?;
+
+            let mut components = Vec::new();
+            for part in inner.split(',') {
+                let part = part.trim();
+                part.parse::<f64>()
+                    .map_err(|_| format!("Invalid vector component: '{part}'"))?;
+                components.push(part);
+            }
+
+            if components.is_empty() {
+                return Err("Vector literal must not be empty".to_string());
+            }
+
+            return Ok(KnnTarget::Vector(format!("[{}]", components.join(","))));
+        }
+
+        match input.split_once('=') {
+            Some((key, value)) if !key.trim().is_empty() && !value.trim().is_empty() => {
+                Ok(KnnTarget::Row {
+                    key: key.trim().to_string(),
+                    value: value.trim().to_string(),
+                })
+            }
+            _ => Err(format!(
+                "Invalid target '{input}': expected a vector literal like [0.1,0.2] or a row reference like id=42"
+            )),
+        }
+    }
+}
+
+/// Quote a possibly schema-qualified name (`schema.table`)
+fn quote_qualified(name: &str) -> String {
+    name.split('.')
+        .map(quote_sql_ident)
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Build the nearest-neighbour query for a pgvector column.
+///
+/// The result includes every column of the table plus a trailing `distance`
+/// column. With [`DistanceMetric::InnerProduct`] pgvector returns the *negative*
+/// inner product, so smaller values still mean closer matches.
+pub fn build_knn_query(
+    table: &str,
+    column: &str,
+    target: &KnnTarget,
+    k: usize,
+    metric: DistanceMetric,
+) -> String {
+    let table_ident = quote_qualified(table);
+    let column_ident = quote_sql_ident(column);
+    let op = metric.operator();
+
+    match target {
+        KnnTarget::Vector(vector) => format!(
+            "SELECT t.*, t.{column_ident} {op} '{}'::vector AS distance FROM {table_ident} t ORDER BY distance LIMIT {k}",
+            escape_sql_string(vector)
+        ),
+        KnnTarget::Row { key, value } => {
+            let key_ident = quote_sql_ident(key);
+            let value = escape_sql_string(value);
+            format!(
+                "SELECT t.*, t.{column_ident} {op} (SELECT r.{column_ident} FROM {table_ident} r WHERE r.{key_ident} = '{value}' LIMIT 1) AS distance \
+                 FROM {table_ident} t WHERE t.{key_ident} IS DISTINCT FROM '{value}' ORDER BY distance LIMIT {k}"
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("l2", DistanceMetric::L2)]
+    #[case("cosine", DistanceMetric::Cosine)]
+    #[case("IP", DistanceMetric::InnerProduct)]
+    #[case("<+>", DistanceMetric::L1)]
+    fn test_distance_metric_parsing(#[case] input: &str, #[case] expected: DistanceMetric) {
+        assert_eq!(input.parse::<DistanceMetric>(), Ok(expected));
+    }
+
+    #[test]
+    fn test_invalid_distance_metric() {
+        assert!("hamming".parse::<DistanceMetric>().is_err());
+    }
+
+    #[test]
+    fn test_target_parsing() {
+        assert_eq!(
+            KnnTarget::parse("[0.1, 0.2,0.3]"),
+            Ok(KnnTarget::Vector("[0.1,0.2,0.3]".to_string()))
+        );
+        assert_eq!(
+            KnnTarget::parse("id=42"),
+            Ok(KnnTarget::Row {
+                key: "id".to_string(),
+                value: "42".to_string()
+            })
+        );
+        assert!(KnnTarget::parse("[0.1, abc]").is_err());
+        assert!(KnnTarget::parse("[0.1, 0.2").is_err());
+        assert!(KnnTarget::parse("[]").is_err());
+        assert!(KnnTarget::parse("42").is_err());
+    }
+
+    #[test]
+    fn test_build_knn_query_with_vector() {
+        let sql = build_knn_query(
+            "public.items",
+            "embedding",
+            &KnnTarget::Vector("[1,2,3]".to_string()),
+            5,
+            DistanceMetric::Cosine,
+        );
+        assert_eq!(
+            sql,
+            "SELECT t.*, t.\"embedding\" <=> '[1,2,3]'::vector AS distance FROM \"public\".\"items\" t ORDER BY distance LIMIT 5"
+        );
+    }
+
+    #[test]
+    fn test_build_knn_query_with_row_reference() {
+        let sql = build_knn_query(
+            "items",
+            "embedding",
+            &KnnTarget::Row {
+                key: "name".to_string(),
+                value: "o'brien".to_string(),
+            },
+            3,
+            DistanceMetric::L2,
+        );
+        assert!(sql.contains("WHERE r.\"name\" = 'o''brien' LIMIT 1) AS distance"));
+        assert!(sql.contains("WHERE t.\"name\" IS DISTINCT FROM 'o''brien'"));
+        assert!(sql.ends_with("ORDER BY distance LIMIT 3"));
+    }
+}
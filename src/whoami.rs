@@ -0,0 +1,353 @@
+//! Identity report for the `\whoami` command
+//!
+//! Answers "who am I connected as, and how?" in one place: the authenticated and
+//! effective user, role memberships, current database/schema, whether the session
+//! is encrypted, the real server address when the connection runs through an SSH
+//! tunnel, and the Vault lease backing the credentials when there is one. Server
+//! side details come from a per-backend query; anything the server refuses to
+//! report (missing privileges, old versions) is simply left out.
+
+use crate::config::Config;
+use crate::database::{DatabaseType, DatabaseTypeExt};
+use crate::db::Database;
+use std::collections::HashMap;
+
+/// Header → value map for the first row of a query result
+pub(crate) fn first_row(results: &[Vec<String>]) -> HashMap<String, String> {
+    match (results.first(), results.get(1)) {
+        (Some(header), Some(row)) => header
+            .iter()
+            .cloned()
+            .zip(row.iter().cloned())
+            .filter(|(_, value)| !value.is_empty() && value != "NULL")
+            .collect(),
+        _ => HashMap::new(),
+    }
+}
+
+/// `1h05m`, `42m`, `30s`
+fn format_remaining(seconds: i64) -> String {
+    let seconds = seconds.max(0);
+    match (seconds / 3600, (seconds % 3600) / 60) {
+        (0, 0) => format!("{seconds}s"),
+        (0, minutes) => format!("{minutes}m"),
+        (hours, minutes) => format!("{hours}h{minutes:02}m"),
+    }
+}
+
+/// Ordered `label: value` report
+#[derive(Debug, Default)]
+pub struct IdentityReport {
+    fields: Vec<(&'static str, String)>,
+}
+
+impl IdentityReport {
+    fn push(&mut self, label: &'static str, value: impl Into<String>) {
+        let value = value.into();
+        if !value.is_empty() {
+            self.fields.push((label, value));
+        }
+    }
+
+    fn get(&self, label: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(l, _)| *l == label)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn render(&self) -> String {
+        let width = self.fields.iter().map(|(l, _)| l.len()).max().unwrap_or(0) + 1;
+        self.fields
+            .iter()
+            .map(|(label, value)| format!("{:<width$} {value}", format!("{label}:")))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+async fn postgresql_identity(database: &mut Database, report: &mut IdentityReport) {
+    let query = r#"
+        SELECT
+            current_user AS effective_user,
+            session_user AS auth_user,
+            current_database() AS database,
+            current_schema() AS schema,
+            (SELECT string_agg(r.rolname, ', ' ORDER BY r.rolname)
+               FROM pg_roles r
+              WHERE pg_has_role(current_user, r.oid, 'member')
+                AND r.rolname <> current_user) AS member_of,
+            (SELECT rolsuper FROM pg_roles WHERE rolname = current_user) AS superuser,
+            host(inet_server_addr()) AS server_addr,
+            inet_server_port() AS server_port,
+            (SELECT CASE WHEN ssl THEN version || ' ' || cipher ELSE 'off' END
+               FROM pg_stat_ssl WHERE pid = pg_backend_pid()) AS ssl
+    "#;
+    let Ok(results) = database.execute_query(query).await else {
+        return;
+    };
+    let row = first_row(&results);
+    let value = |key: &str| row.get(key).cloned().unwrap_or_default();
+
+    let user = value("effective_user");
+    let auth_user = value("auth_user");
+    if !auth_user.is_empty() && auth_user != user {
+        report.push("User", format!("{user} (authenticated as {auth_user})"));
+    } else {
+        report.push("User", user);
+    }
+    report.push("Member of", value("member_of"));
+    report.push(
+        "Superuser",
+        if value("superuser") == "true" {
+            "yes"
+        } else {
+            "no"
+        },
+    );
+    report.push("Database", value("database"));
+    report.push("Schema", value("schema"));
+    match (row.get("server_addr"), row.get("server_port")) {
+        (Some(addr), Some(port)) => report.push("Server address", format!("{addr}:{port}")),
+        _ => report.push("Server address", "local socket"),
+    }
+    report.push("Encryption", value("ssl"));
+}
+
+async fn mysql_identity(database: &mut Database, report: &mut IdentityReport) {
+    let query = "SELECT CURRENT_USER() AS effective_user, USER() AS auth_user, \
+                 DATABASE() AS `database`, @@hostname AS server_host, @@port AS server_port";
+    if let Ok(results) = database.execute_query(query).await {
+        let row = first_row(&results);
+        let value = |key: &str| row.get(key).cloned().unwrap_or_default();
+        report.push(
+            "User",
+            format!(
+                "{} (authenticated as {})",
+                value("effective_user"),
+                value("auth_user")
+            ),
+        );
+        report.push("Database", value("database"));
+        report.push(
+            "Server address",
+            format!("{}:{}", value("server_host"), value("server_port")),
+        );
+    }
+
+    // CURRENT_ROLE() needs MySQL 8.0 / MariaDB 10.0.5
+    if let Ok(results) = database
+        .execute_query("SELECT CURRENT_ROLE() AS roles")
+        .await
+        && let Some(roles) = first_row(&results).remove("roles")
+        && roles != "NONE"
+    {
+        report.push("Active roles", roles);
+    }
+
+    if let Ok(results) = database
+        .execute_query("SHOW SESSION STATUS LIKE 'Ssl_%'")
+        .await
+    {
+        let status: HashMap<String, String> = results
+            .iter()
+            .skip(1)
+            .filter_map(|row| Some((row.first()?.clone(), row.get(1)?.clone())))
+            .collect();
+        let cipher = status.get("Ssl_cipher").filter(|c| !c.is_empty());
+        report.push(
+            "Encryption",
+            match cipher {
+                Some(cipher) => format!(
+                    "{} {cipher}",
+                    status.get("Ssl_version").cloned().unwrap_or_default()
+                )
+                .trim()
+                .to_string(),
+                None => "off".to_string(),
+            },
+        );
+    }
+}
+
+async fn clickhouse_identity(database: &mut Database, report: &mut IdentityReport) {
+    let query = "SELECT currentUser() AS user, currentDatabase() AS database, \
+                 arrayStringConcat(currentRoles(), ', ') AS roles, hostName() AS host";
+    if let Ok(results) = database.execute_query(query).await {
+        let row = first_row(&results);
+        let value = |key: &str| row.get(key).cloned().unwrap_or_default();
+        report.push("User", value("user"));
+        report.push("Active roles", value("roles"));
+        report.push("Database", value("database"));
+        report.push("Server host", value("host"));
+    }
+}
+
+/// Topology and read settings reported by the MongoDB client
+async fn mongodb_identity(database: &mut Database, report: &mut IdentityReport) {
+    let Some(client) = database.get_database_client() else {
+        return;
+    };
+    let username = client.get_connection_info().username.clone();
+    report.push("User", username.unwrap_or_default());
+    let current = database.get_current_db();
+    if current != "unknown" {
+        report.push("Database", current);
+    }
+    let Ok(info) = client.get_server_info().await else {
+        return;
+    };
+    let value = |key: &str| info.additional_info.get(key).cloned().unwrap_or_default();
+    report.push("Replica set", value("replica_set"));
+    let member = match (value("member"), value("member_state")) {
+        (member, state) if !member.is_empty() && !state.is_empty() => {
+            format!("{member} ({state})")
+        }
+        (member, _) => member,
+    };
+    report.push("Connected member", member);
+    report.push("Primary", value("primary"));
+    report.push("Seed hosts", value("hosts"));
+    report.push("Read preference", value("read_preference"));
+    report.push("Auth source", value("auth_source"));
+    report.push("Encryption", value("tls"));
+}
+
+/// Cached Vault credentials issued for the connected user
+fn vault_lease(config: &Config, username: &str) -> Option<String> {
+    let (_, creds) = config
+        .list_cached_vault_credentials()
+        .into_iter()
+        .find(|(_, creds)| creds.username == username)?;
+    let remaining = (creds.expire_time - chrono::Utc::now()).num_seconds();
+    let expiry = if remaining > 0 {
+        format!("expires in {}", format_remaining(remaining))
+    } else {
+        "expired".to_string()
+    };
+    Some(format!(
+        "role {} on {}/{} ({expiry}, lease {}, {})",
+        creds.role_name,
+        creds.mount_path,
+        creds.database_name,
+        format_remaining(creds.lease_duration as i64),
+        if creds.renewable {
+            "renewable"
+        } else {
+            "not renewable"
+        }
+    ))
+}
+
+/// Gather the identity report for the current connection
+pub async fn collect_identity(database: &mut Database, config: &Config) -> IdentityReport {
+    let mut report = IdentityReport::default();
+    let db_type = database.get_database_type();
+    let info = database.get_connection_info().cloned();
+
+    match db_type {
+        DatabaseType::PostgreSQL => postgresql_identity(database, &mut report).await,
+        DatabaseType::MySQL => mysql_identity(database, &mut report).await,
+        DatabaseType::ClickHouse => clickhouse_identity(database, &mut report).await,
+        DatabaseType::MongoDB => mongodb_identity(database, &mut report).await,
+        _ => {}
+    }
+
+    // Fall back to what the client connected with
+    let username = info
+        .as_ref()
+        .and_then(|info| info.username.clone())
+        .unwrap_or_default();
+    if report.get("User").is_none() {
+        report.push("User", username.clone());
+    }
+    if report.get("Database").is_none() {
+        let current = database.get_current_db();
+        if current != "unknown" {
+            report.push("Database", current);
+        }
+    }
+    if db_type.is_file_based()
+        && let Some(path) = info.as_ref().and_then(|info| info.file_path.clone())
+    {
+        report.push("File", path);
+    }
+
+    report.push("Database type", db_type.display_name());
+
+    if let Some(tunnel) = database.get_ssh_tunnel() {
+        report.push("SSH tunnel", tunnel.describe());
+    } else if let Some(info) = &info
+        && let Some(host) = &info.host
+        && report.get("Server address").is_none()
+    {
+        let address = match info.port {
+            Some(port) => format!("{host}:{port}"),
+            None => host.clone(),
+        };
+        report.push("Server address", address);
+    }
+    if let Some(container) = info.as_ref().and_then(|info| info.docker_container.clone()) {
+        report.push("Docker container", container);
+    }
+
+    if report.get("Encryption").is_none() && !db_type.is_file_based() {
+        let tls = info.as_ref().is_some_and(|info| info.use_tls);
+        report.push(
+            "Encryption",
+            if tls {
+                "TLS requested"
+            } else {
+                "not requested"
+            },
+        );
+    }
+
+    if !username.is_empty()
+        && let Some(lease) = vault_lease(config, &username)
+    {
+        report.push("Vault lease", lease);
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[test]
+    fn test_first_row_skips_null_and_empty() {
+        let results = vec![
+            vec!["user".to_string(), "ssl".to_string(), "roles".to_string()],
+            vec!["alice".to_string(), "NULL".to_string(), String::new()],
+        ];
+        let row = first_row(&results);
+        assert_eq!(row.get("user").map(String::as_str), Some("alice"));
+        assert!(!row.contains_key("ssl"));
+        assert!(!row.contains_key("roles"));
+        assert!(first_row(&results[..1]).is_empty());
+    }
+
+    #[rstest]
+    #[case(30, "30s")]
+    #[case(2520, "42m")]
+    #[case(3900, "1h05m")]
+    #[case(-5, "0s")]
+    fn test_format_remaining(#[case] seconds: i64, #[case] expected: &str) {
+        assert_eq!(format_remaining(seconds), expected);
+    }
+
+    #[test]
+    fn test_report_render_aligns_labels_and_skips_empty() {
+        let mut report = IdentityReport::default();
+        report.push("User", "alice");
+        report.push("Schema", "");
+        report.push("Encryption", "TLSv1.3 TLS_AES_256_GCM_SHA384");
+        assert_eq!(
+            report.render(),
+            "User:       alice\nEncryption: TLSv1.3 TLS_AES_256_GCM_SHA384"
+        );
+    }
+}